@@ -15,11 +15,16 @@
 #include "../src/core_audio/buffer.hpp"
 #include "../src/core_audio/audio_stream.hpp"  // Changed to include header file
 #include "../src/core_audio/sync_engine.hpp"   // Changed to include header file instead of cpp
+#include "../src/core_audio/rtp_egress.hpp"
+#include "../src/core_audio/silence.hpp"
+#include "../src/core_audio/crossover.hpp"
+#include "../src/include/spec.h"
 
 #include <string>
 #include <memory>
 #include <vector>
 #include <functional>
+#include <stdexcept>
 
 namespace py = pybind11; 
 
@@ -28,7 +33,7 @@ class AudioController {
 public:
     AudioController() 
         : is_initialized_(false)
-        , sample_rate_(48000)  // Default per musica come da specifiche PAPER.md
+        , sample_rate_(saber::spec::SAMPLE_RATE_MUSIC_HZ)
         , channels_(2)         // Default stereo
         , sync_engine_(nullptr)
     {}
@@ -53,7 +58,7 @@ public:
             );
 
             // Uso una lambda che cattura this per fornire il timestamp
-            sync_engine_->initialize([this]() { return this->get_time_ms(); });
+            sync_engine_->initialize([this]() { return this->get_time_ms(); }, output_config_);
 
             is_initialized_ = true;
             return true;
@@ -151,6 +156,109 @@ public:
         return written > 0;
     }
 
+    // Configura la gestione dei bassi ("full_range", "satellite" o "subwoofer")
+    bool set_bass_management(const std::string& role, float crossover_hz) {
+        if (!sync_engine_) {
+            return false;
+        }
+
+        saber::audio::BassRole bass_role;
+        if (role == "subwoofer") {
+            bass_role = saber::audio::BassRole::Subwoofer;
+        } else if (role == "satellite") {
+            bass_role = saber::audio::BassRole::Satellite;
+        } else if (role == "full_range") {
+            bass_role = saber::audio::BassRole::FullRange;
+        } else {
+            py::print("Ruolo di gestione dei bassi non valido:", role);
+            return false;
+        }
+
+        try {
+            sync_engine_->setBassManagement(bass_role, crossover_hz);
+            return true;
+        } catch (const std::exception& e) {
+            py::print("Errore configurazione bassi:", e.what());
+            return false;
+        }
+    }
+
+    // Configura i frame in ritardo ("drop", "late" o "resync")
+    bool set_late_frame_policy(const std::string& policy, uint32_t tolerance_ms = 0) {
+        if (!sync_engine_) {
+            return false;
+        }
+
+        saber::audio::LateFramePolicy late_policy;
+        if (policy == "drop") {
+            late_policy = saber::audio::LateFramePolicy::Drop;
+        } else if (policy == "late") {
+            late_policy = saber::audio::LateFramePolicy::PlayLate;
+        } else if (policy == "resync") {
+            late_policy = saber::audio::LateFramePolicy::Resync;
+        } else {
+            py::print("Politica per i frame in ritardo non valida:", policy);
+            return false;
+        }
+
+        sync_engine_->setLateFramePolicy(late_policy, tolerance_ms);
+        return true;
+    }
+
+    // Contatori degli esiti dei frame scritti
+    py::dict get_late_frame_stats() const {
+        py::dict result;
+        saber::audio::LateFrameStats stats;
+        if (sync_engine_) {
+            stats = sync_engine_->getLateFrameStats();
+        }
+        result["on_time"] = stats.on_time;
+        result["dropped"] = stats.dropped;
+        result["played_late"] = stats.played_late;
+        result["resyncs"] = stats.resyncs;
+        result["slid_ms"] = stats.slid_ms;
+        return result;
+    }
+
+    // Invia l'audio ad un amplificatore di rete (RTP/AES67); vale dal prossimo initialize
+    bool set_rtp_output(const std::string& address, uint16_t port, uint8_t payload_type,
+                        const std::string& encoding, uint32_t packet_time_us,
+                        uint32_t link_offset_ms, uint8_t multicast_ttl) {
+        saber::audio::RtpEgressConfig rtp;
+        if (encoding == "L24") {
+            rtp.encoding = saber::audio::RtpEncoding::L24;
+        } else if (encoding == "L16") {
+            rtp.encoding = saber::audio::RtpEncoding::L16;
+        } else {
+            py::print("Formato RTP non valido:", encoding);
+            return false;
+        }
+        rtp.destination = address;
+        rtp.port = port;
+        rtp.payload_type = payload_type;
+        rtp.packet_time_us = packet_time_us;
+        rtp.link_offset_ms = link_offset_ms;
+        rtp.multicast_ttl = multicast_ttl;
+
+        output_config_.backend = saber::audio::OutputBackend::Rtp;
+        output_config_.rtp = rtp;
+        return true;
+    }
+
+    // Torna alla scheda audio locale; vale dal prossimo initialize
+    void set_local_output() {
+        output_config_ = saber::audio::OutputConfig();
+    }
+
+    // Descrizione SDP del flusso RTP (vuota se l'uscita è locale)
+    std::string get_rtp_sdp(const std::string& session_name, const std::string& origin_address) const {
+        if (!sync_engine_) {
+            return "";
+        }
+        auto* egress = dynamic_cast<saber::audio::RtpEgress*>(sync_engine_->getOutput());
+        return egress ? egress->describeSdp(session_name, origin_address) : "";
+    }
+
     // Configura la dimensione del buffer
     void set_buffer_size(uint32_t buffer_ms) {
         if (sync_engine_) {
@@ -178,6 +286,7 @@ private:
     uint8_t channels_;
     std::unique_ptr<saber::audio::SyncEngine> sync_engine_;
     std::function<uint64_t()> time_provider_;
+    saber::audio::OutputConfig output_config_;
 };
 
 // Modulo Python
@@ -209,13 +318,87 @@ PYBIND11_MODULE(libpy_audio, m) {
         .def("play_audio_buffer", &AudioController::play_audio_buffer,
             py::arg("samples"), py::arg("timestamp"),
             "Riproduce un buffer audio con timestamp")
+        .def("set_bass_management", &AudioController::set_bass_management,
+            py::arg("role"), py::arg("crossover_hz") = 80.0f,
+            "Configura la gestione dei bassi del sink (full_range, satellite, subwoofer)")
+        .def("set_late_frame_policy", &AudioController::set_late_frame_policy,
+            py::arg("policy"), py::arg("tolerance_ms") = 0,
+            "Configura i frame in ritardo (drop, late, resync)")
+        .def("get_late_frame_stats", &AudioController::get_late_frame_stats,
+            "Contatori dei frame in tempo, scartati, riprodotti in ritardo e dei riallineamenti")
+        .def("set_rtp_output", &AudioController::set_rtp_output,
+            py::arg("address"), py::arg("port") = 5004, py::arg("payload_type") = 97,
+            py::arg("encoding") = "L24", py::arg("packet_time_us") = 1000,
+            py::arg("link_offset_ms") = 2, py::arg("multicast_ttl") = 16,
+            "Invia l'audio sincronizzato ad un amplificatore di rete RTP/AES67")
+        .def("set_local_output", &AudioController::set_local_output,
+            "Riproduce l'audio sulla scheda audio locale")
+        .def("get_rtp_sdp", &AudioController::get_rtp_sdp,
+            py::arg("session_name"), py::arg("origin_address"),
+            "Descrizione SDP del flusso RTP da caricare sul ricevitore")
         .def("set_buffer_size", &AudioController::set_buffer_size,
             py::arg("buffer_ms"),
             "Configura la dimensione del buffer in millisecondi");
 
+    py::enum_<saber::audio::CodecType>(m, "CodecType")
+        .value("Pcm", saber::audio::CodecType::Pcm)
+        .value("Lc3", saber::audio::CodecType::Lc3);
+
+    // Cache dei frame di concealment; l'encoder Python riceve i campioni e restituisce bytes
+    py::class_<saber::audio::SilenceFrameCache>(m, "SilenceFrameCache")
+        .def(py::init<float, uint32_t>(),
+            py::arg("comfort_noise_level") = 0.0005f, py::arg("seed") = 0x5AB3u)
+        .def("register_codec", [](saber::audio::SilenceFrameCache& self, saber::audio::CodecType codec,
+                                  uint32_t sample_rate, uint8_t channels, uint32_t frame_us,
+                                  std::function<py::bytes(std::vector<float>)> encoder) {
+                saber::audio::FrameEncoder frame_encoder;
+                if (encoder) {
+                    frame_encoder = [encoder, channels](const float* samples, size_t frames) {
+                        std::string encoded = encoder(std::vector<float>(samples, samples + frames * channels));
+                        return std::vector<uint8_t>(encoded.begin(), encoded.end());
+                    };
+                }
+                self.register_codec(codec, {sample_rate, channels, frame_us}, frame_encoder);
+            },
+            py::arg("codec"), py::arg("sample_rate"), py::arg("channels"), py::arg("frame_us"),
+            py::arg("encoder") = nullptr,
+            "Pre-calcola i frame di silenzio e comfort noise di un codec")
+        .def("has_codec", &saber::audio::SilenceFrameCache::has_codec)
+        .def("silence_frame", [](const saber::audio::SilenceFrameCache& self, saber::audio::CodecType codec) {
+                const auto& frame = self.silence_frame(codec);
+                return py::bytes(reinterpret_cast<const char*>(frame.data()), frame.size());
+            })
+        .def("comfort_noise_frame", [](const saber::audio::SilenceFrameCache& self, saber::audio::CodecType codec) {
+                const auto& frame = self.comfort_noise_frame(codec);
+                return py::bytes(reinterpret_cast<const char*>(frame.data()), frame.size());
+            })
+        .def("comfort_noise_samples", &saber::audio::SilenceFrameCache::comfort_noise_samples);
+
+    py::enum_<saber::audio::BassRole>(m, "BassRole")
+        .value("FullRange", saber::audio::BassRole::FullRange)
+        .value("Satellite", saber::audio::BassRole::Satellite)
+        .value("Subwoofer", saber::audio::BassRole::Subwoofer);
+
+    // Crossover del sink; process restituisce una copia filtrata dei campioni interleaved
+    py::class_<saber::audio::BassManager>(m, "BassManager")
+        .def(py::init<uint32_t, uint8_t>(), py::arg("sample_rate"), py::arg("channels"))
+        .def("configure", &saber::audio::BassManager::configure,
+            py::arg("role"), py::arg("crossover_hz"))
+        .def("process", [](saber::audio::BassManager& self, std::vector<float> samples) {
+                if (samples.size() % self.channels() != 0) {
+                    throw std::invalid_argument("Numero di campioni non multiplo dei canali");
+                }
+                self.process(samples.data(), samples.size() / self.channels());
+                return samples;
+            },
+            py::arg("samples"), "Filtra campioni interleaved e restituisce il risultato")
+        .def("role", &saber::audio::BassManager::role)
+        .def("crossover_hz", &saber::audio::BassManager::crossover_hz)
+        .def("channels", &saber::audio::BassManager::channels);
+
     // Aggiungo costanti e versione
-    m.attr("DEFAULT_SAMPLE_RATE_MUSIC") = 48000;
-    m.attr("DEFAULT_SAMPLE_RATE_VOICE") = 16000;
+    m.attr("DEFAULT_SAMPLE_RATE_MUSIC") = saber::spec::SAMPLE_RATE_MUSIC_HZ;
+    m.attr("DEFAULT_SAMPLE_RATE_VOICE") = saber::spec::SAMPLE_RATE_VOICE_HZ;
     m.attr("DEFAULT_CHANNELS") = 2;
     m.attr("__version__") = "0.1.0";
 }
@@ -0,0 +1,57 @@
+// Benchmark della granularità di cifratura dei frame audio
+// Confronta CPU e byte in aria tra cifratura per frame e per frame di trasporto
+
+#include "crypto.h"
+#include "frame_crypto.h"
+
+#include <chrono>
+#include <cstdio>
+#include <memory>
+#include <vector>
+
+using namespace saber;
+
+namespace {
+
+// Frame LC3 da 10 ms a 128 kbps
+const size_t FRAME_BYTES = 160;
+const int ITERATIONS = 2000;
+
+double microsecondsPerFrame(AudioFrameSealer& sealer, const std::vector<std::vector<uint8_t>>& frames) {
+    auto start = std::chrono::steady_clock::now();
+    for (int i = 0; i < ITERATIONS; ++i) {
+        auto transport = sealer.seal(frames);
+        auto opened = sealer.open(transport);
+        if (opened.size() != frames.size()) {
+            std::fprintf(stderr, "Numero di frame errato dopo la decifratura\n");
+        }
+    }
+    auto elapsed = std::chrono::duration_cast<std::chrono::microseconds>(
+        std::chrono::steady_clock::now() - start).count();
+    return static_cast<double>(elapsed) / (ITERATIONS * frames.size());
+}
+
+} // namespace
+
+int main() {
+    auto crypto = std::make_shared<MeshCrypto>();
+    const EncryptionGranularity modes[] = {
+        EncryptionGranularity::PerFrame,
+        EncryptionGranularity::PerTransportFrame
+    };
+
+    std::printf("%-10s %-8s %14s %16s %10s\n",
+                "mode", "frames", "us/frame", "overhead B/fr", "airtime");
+    for (size_t coalesced : {1, 2, 4, 8}) {
+        std::vector<std::vector<uint8_t>> frames(coalesced, std::vector<uint8_t>(FRAME_BYTES, 0x5A));
+        for (auto mode : modes) {
+            AudioFrameSealer sealer(crypto, mode);
+            double cpu = microsecondsPerFrame(sealer, frames);
+            double overhead = static_cast<double>(AudioFrameSealer::overheadBytes(mode, coalesced)) / coalesced;
+            double airtime = (FRAME_BYTES + overhead) / FRAME_BYTES;
+            std::printf("%-10s %-8zu %14.2f %16.1f %9.3fx\n",
+                        encryptionGranularityToString(mode).c_str(), coalesced, cpu, overhead, airtime);
+        }
+    }
+    return 0;
+}
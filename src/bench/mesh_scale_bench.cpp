@@ -0,0 +1,57 @@
+// Benchmark della scalabilità della rete mesh di un Master
+// Misura registrazione dei nodi e calcolo dell'albero di distribuzione fino al limite di ammissione
+
+#include "mesh.h"
+#include "spec.h"
+
+#include <chrono>
+#include <cinttypes>
+#include <cstdio>
+#include <string>
+
+using namespace saber;
+
+namespace {
+
+const StreamId STREAM = 1;
+const int TREE_ITERATIONS = 200;
+
+// Un Repeater ogni dieci nodi, il resto sono sink
+NodeRole roleFor(size_t index) {
+    return index % 10 == 0 ? NodeRole::Repeater : NodeRole::Sink;
+}
+
+} // namespace
+
+int main() {
+    std::printf("%-8s %10s %10s %14s %14s\n",
+                "offered", "admitted", "rejected", "us/register", "us/tree");
+    for (size_t offered : {10, 25, 50, 100, 150}) {
+        MeshNetwork network(Node("master-bench", NodeRole::Master));
+        network.setCapacityLimits(spec::MAX_NODES, 0);
+
+        auto start = std::chrono::steady_clock::now();
+        for (size_t i = 0; i < offered; ++i) {
+            std::string nodeId = "node-" + std::to_string(i);
+            if (network.registerNode(nodeId, roleFor(i)) && roleFor(i) == NodeRole::Sink) {
+                network.subscribe(nodeId, STREAM);
+            }
+        }
+        auto registered = std::chrono::steady_clock::now();
+        for (int i = 0; i < TREE_ITERATIONS; ++i) {
+            network.unsubscribe("node-1", STREAM);
+            network.subscribe("node-1", STREAM);
+            network.getDistributionTree(STREAM);
+        }
+        auto end = std::chrono::steady_clock::now();
+
+        auto stats = network.getAdmissionStats();
+        double registerUs = static_cast<double>(std::chrono::duration_cast<std::chrono::microseconds>(
+            registered - start).count()) / offered;
+        double treeUs = static_cast<double>(std::chrono::duration_cast<std::chrono::microseconds>(
+            end - registered).count()) / TREE_ITERATIONS;
+        std::printf("%-8zu %10" PRIu64 " %10" PRIu64 " %14.2f %14.2f\n",
+                    offered, stats.admitted, stats.rejected, registerUs, treeUs);
+    }
+    return 0;
+}
@@ -0,0 +1,56 @@
+#!/usr/bin/env python3
+# -*- coding: utf-8 -*-
+"""
+Client del socket di controllo SABER
+Invia comandi di amministrazione ad un nodo in esecuzione, ad esempio:
+
+    saber_cli.py log set mesh=debug,sync=trace
+    saber_cli.py log set mesh=debug --mesh
+    saber_cli.py log set trace --node sink-01
+    saber_cli.py log get
+"""
+
+import argparse
+import socket
+import sys
+
+DEFAULT_HOST = "127.0.0.1"
+DEFAULT_PORT = 7700
+
+
+def send_command(host: str, port: int, line: str, timeout: float = 5.0) -> str:
+    """Invia una riga di comando e restituisce la risposta del nodo"""
+    with socket.create_connection((host, port), timeout=timeout) as sock:
+        sock.sendall((line + "\n").encode("utf-8"))
+        response = b""
+        while not response.endswith(b"\n"):
+            chunk = sock.recv(1024)
+            if not chunk:
+                break
+            response += chunk
+    return response.decode("utf-8").strip()
+
+
+def main() -> int:
+    parser = argparse.ArgumentParser(description="Client del socket di controllo SABER")
+    parser.add_argument("--host", default=DEFAULT_HOST, help="Indirizzo del socket di controllo")
+    parser.add_argument("--port", type=int, default=DEFAULT_PORT, help="Porta del socket di controllo")
+    parser.add_argument("command", nargs=argparse.REMAINDER, help="Comando da inviare (es. log set mesh=debug)")
+    args = parser.parse_args()
+
+    if not args.command:
+        parser.print_usage()
+        return 2
+
+    try:
+        response = send_command(args.host, args.port, " ".join(args.command))
+    except OSError as e:
+        print(f"Impossibile contattare il nodo su {args.host}:{args.port}: {e}", file=sys.stderr)
+        return 1
+
+    print(response)
+    return 0 if response.startswith("ok") else 1
+
+
+if __name__ == "__main__":
+    sys.exit(main())
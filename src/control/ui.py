@@ -176,7 +176,8 @@ class SaberUI:
                 "latency_ms": latency,
                 "buffer_level": buffer_level,
                 "is_active": is_active,
-                "active_nodes": active_nodes
+                "active_nodes": active_nodes,
+                "now_playing": local_info.get("now_playing", {})
             }
             
             return status
@@ -222,9 +223,25 @@ async def main():
     parser.add_argument("--bt", type=str, help="Indirizzo Bluetooth (opzionale)")
     parser.add_argument("--voice", action="store_true", help="Modalità voce (16kHz) anziché musica (48kHz)")
     parser.add_argument("--dashboard", action="store_true", help="Avvia la dashboard grafica")
+    parser.add_argument("--profile", type=str, 
+                        help="Profilo d'uso (home-music, conference-voice, stage-monitor)")
     
     args = parser.parse_args()
     
+    # Il profilo stabilisce modalità audio e buffer; --voice resta prioritario
+    profile = None
+    if args.profile:
+        try:
+            from saber_protocol import find_profile, list_profiles
+        except ImportError:
+            logger.error("I profili richiedono il modulo saber_protocol compilato")
+            return 1
+        profile = find_profile(args.profile)
+        if profile is None:
+            logger.error(f"Profilo sconosciuto: {args.profile} (disponibili: {', '.join(list_profiles())})")
+            return 1
+    is_music = profile.is_music_mode if profile and not args.voice else not args.voice
+    
     # Creo e inizializzo l'interfaccia
     ui = SaberUI()
     
@@ -233,11 +250,14 @@ async def main():
         ui.enable_dashboard()
     
     # Inizializzo il nodo col ruolo specificato
-    success = await ui.initialize(args.role, args.id, args.bt, not args.voice)
+    success = await ui.initialize(args.role, args.id, args.bt, is_music)
     if not success:
         logger.error("Inizializzazione fallita")
         return 1
     
+    if profile and args.role == ROLE_SINK:
+        ui.adjust_buffer_size(profile.buffer_target_ms)
+    
     # Avvio il protocollo
     success = await ui.start()
     if not success:
@@ -0,0 +1,83 @@
+// Interfaccia comune delle uscite audio del protocollo SABER
+// Una scheda audio locale (PortAudio) o un'uscita di rete (RTP/AES67)
+
+#ifndef SABER_AUDIO_OUTPUT_HPP
+#define SABER_AUDIO_OUTPUT_HPP
+
+#include "crossover.hpp"
+#include <cstddef>
+#include <cstdint>
+
+namespace saber {
+namespace audio {
+
+/**
+ * Backend di uscita di un sink
+ */
+enum class OutputBackend : uint8_t {
+    SoundCard = 0,  // Dispositivo audio locale tramite PortAudio
+    Rtp = 1         // Flusso RTP/AES67 verso un amplificatore di rete
+};
+
+/**
+ * Uscita dello stream decodificato e sincronizzato
+ *
+ * Il SyncEngine scrive i campioni con il loro timestamp e l'uscita li
+ * riproduce (o li trasmette) quando l'orologio sincronizzato li raggiunge.
+ */
+class AudioOutput {
+public:
+    virtual ~AudioOutput() = default;
+
+    /**
+     * Avvia l'uscita
+     */
+    virtual void startStream() = 0;
+
+    /**
+     * Ferma l'uscita
+     */
+    virtual void stopStream() = 0;
+
+    /**
+     * Scrive campioni nel buffer di uscita
+     * @param data Campioni audio (float interleaved)
+     * @param frames Numero di frame
+     * @param timestamp Timestamp del primo campione in ms
+     * @return Numero di frame scritti
+     */
+    virtual size_t writeAudio(const float* data, size_t frames, uint64_t timestamp) = 0;
+
+    /**
+     * Latenza complessiva dell'uscita in millisecondi
+     */
+    virtual uint32_t getCurrentLatency() const = 0;
+
+    /**
+     * Cambia la dimensione del buffer
+     * @param buffer_ms Nuova dimensione in millisecondi
+     */
+    virtual void setBufferSize(uint32_t buffer_ms) = 0;
+
+    /**
+     * Livello di riempimento del buffer (0-100)
+     */
+    virtual uint8_t getBufferLevel() const = 0;
+
+    /**
+     * Scarta i campioni in attesa, riallineando l'uscita al prossimo frame scritto
+     */
+    virtual void flush() = 0;
+
+    /**
+     * Configura la gestione dei bassi applicata prima del buffer
+     * @param role Ruolo del sink nel gruppo
+     * @param crossover_hz Frequenza di crossover in Hz
+     */
+    virtual void setBassManagement(BassRole role, float crossover_hz) = 0;
+};
+
+} // namespace audio
+} // namespace saber
+
+#endif // SABER_AUDIO_OUTPUT_HPP
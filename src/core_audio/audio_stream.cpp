@@ -20,11 +20,17 @@ struct StreamCallbackData {
     std::function<uint64_t()> get_time_callback;
     std::atomic<bool> is_active{false};
     uint8_t channels; // Store channels directly in callback data
+    std::vector<float> concealment; // Comfort noise copied from the silence cache
+    size_t concealment_cursor = 0;
+    std::atomic<uint64_t> concealed_frames{0};
     
     StreamCallbackData(AudioBuffer* buf, std::function<uint64_t()> time_cb, uint8_t ch)
         : buffer(buf), get_time_callback(time_cb), channels(ch) {}
 };
 
+// Durata del frame di concealment PCM pre-calcolato
+static const uint32_t CONCEALMENT_FRAME_US = 10000;
+
 AudioStream::AudioStream(
     uint32_t sample_rate,
     uint8_t channels,
@@ -34,11 +40,16 @@ AudioStream::AudioStream(
     : sample_rate_(sample_rate)
     , channels_(channels)
     , buffer_(sample_rate, channels, buffer_ms)
+    , bass_manager_(sample_rate, channels)
     , time_provider_(time_provider)
     , callback_data_(std::make_unique<StreamCallbackData>(&buffer_, time_provider, channels))
     , stream_(nullptr)
     , is_initialized_(false)
 {
+    // Pre-calcolo il comfort noise una volta sola, fuori dal callback real-time
+    silence_cache_.register_codec(CodecType::Pcm, {sample_rate, channels, CONCEALMENT_FRAME_US});
+    callback_data_->concealment = silence_cache_.comfort_noise_samples(CodecType::Pcm);
+    
     initAudio();
 }
 
@@ -130,7 +141,21 @@ void AudioStream::stopStream() {
 }
 
 size_t AudioStream::writeAudio(const float* data, size_t frames, uint64_t timestamp) {
-    return buffer_.write_samples(data, frames, timestamp);
+    std::lock_guard<std::mutex> lock(bass_mutex_);
+    
+    if (bass_manager_.role() == BassRole::FullRange) {
+        return buffer_.write_samples(data, frames, timestamp);
+    }
+    
+    // Filtro una copia: il chiamante mantiene il mix originale
+    bass_scratch_.assign(data, data + frames * channels_);
+    bass_manager_.process(bass_scratch_.data(), frames);
+    return buffer_.write_samples(bass_scratch_.data(), frames, timestamp);
+}
+
+void AudioStream::setBassManagement(BassRole role, float crossover_hz) {
+    std::lock_guard<std::mutex> lock(bass_mutex_);
+    bass_manager_.configure(role, crossover_hz);
 }
 
 uint32_t AudioStream::getCurrentLatency() const {
@@ -153,6 +178,18 @@ uint8_t AudioStream::getBufferLevel() const {
     return buffer_.get_fill_level();
 }
 
+void AudioStream::flush() {
+    buffer_.clear();
+}
+
+uint64_t AudioStream::getConcealedFrames() const {
+    return callback_data_->concealed_frames.load();
+}
+
+SilenceFrameCache& AudioStream::getSilenceCache() {
+    return silence_cache_;
+}
+
 int AudioStream::paCallback(
     const void* inputBuffer,
     void* outputBuffer,
@@ -176,13 +213,16 @@ int AudioStream::paCallback(
     // Legge i dati dal buffer con timestamp per sincronizzazione
     size_t read = data->buffer->read_samples(out, framesPerBuffer, current_time);
     
-    // Se non ho letto abbastanza dati, riempio il resto con silenzio
+    // Se non ho letto abbastanza dati (perdita o rebuffering), riempio il resto
+    // con il comfort noise pre-calcolato invece di un silenzio digitale netto
     if (read < framesPerBuffer) {
-        std::memset(
+        fill_from_frame(
+            data->concealment,
             out + (read * data->channels),
-            0,
-            (framesPerBuffer - read) * data->channels * sizeof(float)
+            (framesPerBuffer - read) * data->channels,
+            data->concealment_cursor
         );
+        data->concealed_frames += framesPerBuffer - read;
     }
     
     return paContinue;
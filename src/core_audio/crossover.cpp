@@ -0,0 +1,130 @@
+// Implementazione del crossover per la gestione dei bassi
+
+#include "crossover.hpp"
+#include <cmath>
+#include <stdexcept>
+
+namespace saber {
+namespace audio {
+
+namespace {
+
+const float PI = 3.14159265358979f;
+
+// Fattore di qualità Butterworth del 2° ordine
+const float BUTTERWORTH_Q = 0.70710678f;
+
+} // namespace
+
+void Biquad::set_lowpass(uint32_t sample_rate, float cutoff_hz) {
+    float w0 = 2.0f * PI * cutoff_hz / static_cast<float>(sample_rate);
+    float alpha = std::sin(w0) / (2.0f * BUTTERWORTH_Q);
+    float cosw0 = std::cos(w0);
+    float a0 = 1.0f + alpha;
+
+    b0_ = ((1.0f - cosw0) / 2.0f) / a0;
+    b1_ = (1.0f - cosw0) / a0;
+    b2_ = b0_;
+    a1_ = (-2.0f * cosw0) / a0;
+    a2_ = (1.0f - alpha) / a0;
+}
+
+void Biquad::set_highpass(uint32_t sample_rate, float cutoff_hz) {
+    float w0 = 2.0f * PI * cutoff_hz / static_cast<float>(sample_rate);
+    float alpha = std::sin(w0) / (2.0f * BUTTERWORTH_Q);
+    float cosw0 = std::cos(w0);
+    float a0 = 1.0f + alpha;
+
+    b0_ = ((1.0f + cosw0) / 2.0f) / a0;
+    b1_ = -(1.0f + cosw0) / a0;
+    b2_ = b0_;
+    a1_ = (-2.0f * cosw0) / a0;
+    a2_ = (1.0f - alpha) / a0;
+}
+
+float Biquad::process(float input) {
+    float output = b0_ * input + b1_ * x1_ + b2_ * x2_ - a1_ * y1_ - a2_ * y2_;
+    x2_ = x1_;
+    x1_ = input;
+    y2_ = y1_;
+    y1_ = output;
+    return output;
+}
+
+void Biquad::reset() {
+    x1_ = x2_ = y1_ = y2_ = 0.0f;
+}
+
+BassManager::BassManager(uint32_t sample_rate, uint8_t channels)
+    : sample_rate_(sample_rate)
+    , channels_(channels)
+    , role_(BassRole::FullRange)
+    , crossover_hz_(0.0f)
+{
+    if (sample_rate == 0 || channels == 0) {
+        throw std::invalid_argument("Parametri audio non validi");
+    }
+}
+
+void BassManager::configure(BassRole role, float crossover_hz) {
+    if (role != BassRole::FullRange &&
+        (crossover_hz <= 0.0f || crossover_hz >= static_cast<float>(sample_rate_) / 2.0f)) {
+        throw std::invalid_argument("Frequenza di crossover non valida");
+    }
+
+    role_ = role;
+    crossover_hz_ = crossover_hz;
+
+    // Il subwoofer filtra il solo mix mono
+    size_t filtered_channels = role == BassRole::Subwoofer ? 1 : channels_;
+    stages_.assign(role == BassRole::FullRange ? 0 : filtered_channels * 2, Biquad());
+    for (auto& stage : stages_) {
+        if (role == BassRole::Subwoofer) {
+            stage.set_lowpass(sample_rate_, crossover_hz);
+        } else {
+            stage.set_highpass(sample_rate_, crossover_hz);
+        }
+    }
+}
+
+void BassManager::process(float* samples, size_t frames) {
+    if (role_ == BassRole::FullRange) {
+        return;
+    }
+
+    for (size_t frame = 0; frame < frames; ++frame) {
+        float* current = samples + frame * channels_;
+
+        if (role_ == BassRole::Subwoofer) {
+            // Somma mono dei canali, filtrata e replicata su tutte le uscite
+            float mono = 0.0f;
+            for (uint8_t ch = 0; ch < channels_; ++ch) {
+                mono += current[ch];
+            }
+            mono /= static_cast<float>(channels_);
+            float low = stages_[1].process(stages_[0].process(mono));
+            for (uint8_t ch = 0; ch < channels_; ++ch) {
+                current[ch] = low;
+            }
+        } else {
+            for (uint8_t ch = 0; ch < channels_; ++ch) {
+                current[ch] = stages_[ch * 2 + 1].process(stages_[ch * 2].process(current[ch]));
+            }
+        }
+    }
+}
+
+BassRole BassManager::role() const {
+    return role_;
+}
+
+float BassManager::crossover_hz() const {
+    return crossover_hz_;
+}
+
+uint8_t BassManager::channels() const {
+    return channels_;
+}
+
+} // namespace audio
+} // namespace saber
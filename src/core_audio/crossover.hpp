@@ -0,0 +1,114 @@
+// Crossover per la gestione dei bassi tra subwoofer e satelliti
+// Filtri Linkwitz-Riley del 4° ordine applicati sui sink
+
+#ifndef SABER_AUDIO_CROSSOVER_HPP
+#define SABER_AUDIO_CROSSOVER_HPP
+
+#include <cstddef>
+#include <cstdint>
+#include <vector>
+
+namespace saber {
+namespace audio {
+
+/**
+ * Ruolo di un sink nella gestione dei bassi del suo gruppo
+ */
+enum class BassRole : uint8_t {
+    FullRange = 0,  // Nessun filtro (gestione dei bassi disattivata)
+    Satellite = 1,  // Riceve solo le frequenze sopra il crossover
+    Subwoofer = 2   // Riceve il mix mono sotto il crossover
+};
+
+/**
+ * Filtro biquad (forma diretta I) con stato per canale
+ */
+class Biquad {
+public:
+    /**
+     * Configura un passa-basso Butterworth del 2° ordine
+     * @param sample_rate Frequenza di campionamento in Hz
+     * @param cutoff_hz Frequenza di taglio in Hz
+     */
+    void set_lowpass(uint32_t sample_rate, float cutoff_hz);
+
+    /**
+     * Configura un passa-alto Butterworth del 2° ordine
+     * @param sample_rate Frequenza di campionamento in Hz
+     * @param cutoff_hz Frequenza di taglio in Hz
+     */
+    void set_highpass(uint32_t sample_rate, float cutoff_hz);
+
+    /**
+     * Filtra un campione
+     */
+    float process(float input);
+
+    /**
+     * Azzera lo stato del filtro
+     */
+    void reset();
+
+private:
+    float b0_ = 1.0f, b1_ = 0.0f, b2_ = 0.0f, a1_ = 0.0f, a2_ = 0.0f;
+    float x1_ = 0.0f, x2_ = 0.0f, y1_ = 0.0f, y2_ = 0.0f;
+};
+
+/**
+ * Gestione dei bassi di un sink
+ *
+ * Il subwoofer e i satelliti di un gruppo ricevono lo stesso mix e lo
+ * filtrano localmente con filtri Linkwitz-Riley complementari: la somma
+ * acustica è piatta e in fase, e l'allineamento dei campioni resta quello
+ * stabilito dalla sincronizzazione perché nessun sink introduce ritardi.
+ */
+class BassManager {
+public:
+    /**
+     * Costruttore
+     * @param sample_rate Frequenza di campionamento in Hz
+     * @param channels Numero di canali del mix ricevuto
+     */
+    BassManager(uint32_t sample_rate, uint8_t channels);
+
+    /**
+     * Configura ruolo e frequenza di crossover
+     * @param role Ruolo del sink nel gruppo
+     * @param crossover_hz Frequenza di crossover in Hz (ignorata per FullRange)
+     */
+    void configure(BassRole role, float crossover_hz);
+
+    /**
+     * Applica il filtro in-place su campioni interleaved
+     * @param samples Campioni interleaved
+     * @param frames Numero di frame
+     */
+    void process(float* samples, size_t frames);
+
+    /**
+     * Ottiene il ruolo configurato
+     */
+    BassRole role() const;
+
+    /**
+     * Ottiene la frequenza di crossover configurata
+     */
+    float crossover_hz() const;
+
+    /**
+     * Ottiene il numero di canali del mix
+     */
+    uint8_t channels() const;
+
+private:
+    uint32_t sample_rate_;              // Frequenza di campionamento
+    uint8_t channels_;                  // Numero di canali
+    BassRole role_;                     // Ruolo del sink
+    float crossover_hz_;                // Frequenza di crossover
+    std::vector<Biquad> stages_;        // Due stadi per canale (Linkwitz-Riley = Butterworth²)
+};
+
+} // namespace audio
+} // namespace saber
+
+#endif // SABER_AUDIO_CROSSOVER_HPP
@@ -0,0 +1,314 @@
+// Implementazione dell'uscita audio RTP/AES67
+// Pacchetti RTP (RFC 3550) con payload PCM lineare big-endian
+
+#include "rtp_egress.hpp"
+#include "../include/log.h"
+#include "../include/socket_compat.h"
+#include <algorithm>
+#include <chrono>
+#include <cmath>
+#include <random>
+#include <sstream>
+#include <stdexcept>
+
+namespace saber {
+namespace audio {
+
+namespace {
+
+// Dimensione dell'header RTP senza CSRC né estensioni
+const size_t RTP_HEADER_SIZE = 12;
+
+// Payload massimo che evita la frammentazione IP su Ethernet
+const size_t MAX_RTP_PAYLOAD = 1440;
+
+// Pacchetti di ritardo oltre i quali il pacing riparte invece di recuperare a raffica
+const uint32_t MAX_PACING_BACKLOG = 10;
+
+size_t bytesPerSample(RtpEncoding encoding) {
+    return encoding == RtpEncoding::L24 ? 3 : 2;
+}
+
+void writeBigEndian(std::vector<uint8_t>& out, uint32_t value, size_t bytes) {
+    for (size_t i = bytes; i > 0; --i) {
+        out.push_back(static_cast<uint8_t>((value >> ((i - 1) * 8)) & 0xFF));
+    }
+}
+
+// Un indirizzo IPv4 multicast ha i primi quattro bit a 1110
+bool isMulticast(uint32_t network_addr) {
+    return (ntohl(network_addr) >> 28) == 0xE;
+}
+
+} // namespace
+
+RtpEgress::RtpEgress(
+    uint32_t sample_rate,
+    uint8_t channels,
+    uint32_t buffer_ms,
+    std::function<uint64_t()> time_provider,
+    const RtpEgressConfig& config
+)
+    : sample_rate_(sample_rate)
+    , channels_(channels)
+    , config_(config)
+    , frames_per_packet_(static_cast<size_t>(sample_rate) * config.packet_time_us / 1000000)
+    , buffer_(sample_rate, channels, buffer_ms)
+    , bass_manager_(sample_rate, channels)
+    , time_provider_(time_provider)
+    , socket_(-1)
+    , destination_addr_(0)
+    , sequence_(0)
+    , media_clock_(0)
+    , clock_anchored_(false)
+    , is_active_(false)
+    , packets_sent_(0)
+    , concealed_frames_(0)
+{
+    if (frames_per_packet_ == 0) {
+        throw std::invalid_argument("Durata del pacchetto RTP troppo breve");
+    }
+    if (frames_per_packet_ * channels_ * bytesPerSample(config_.encoding) > MAX_RTP_PAYLOAD) {
+        throw std::invalid_argument("Pacchetto RTP oltre la MTU: ridurre packet_time_us o i canali");
+    }
+    if (config_.payload_type > 127) {
+        throw std::invalid_argument("Payload type RTP non valido");
+    }
+
+    in_addr parsed{};
+    if (inet_pton(AF_INET, config_.destination.c_str(), &parsed) != 1) {
+        throw std::invalid_argument("Indirizzo RTP non valido: " + config_.destination);
+    }
+    destination_addr_ = parsed.s_addr;
+
+    // SSRC e numero di sequenza iniziale casuali (RFC 3550, 5.1)
+    std::random_device device;
+    if (config_.ssrc == 0) {
+        config_.ssrc = static_cast<uint32_t>(device()) | 1u;
+    }
+    sequence_ = static_cast<uint16_t>(device());
+
+    packet_samples_.resize(frames_per_packet_ * channels_);
+
+#ifdef _WIN32
+    WSADATA wsaData;
+    if (WSAStartup(MAKEWORD(2, 2), &wsaData) != 0) {
+        throw std::runtime_error("Impossibile inizializzare Winsock");
+    }
+#endif
+
+    auto sock = socket(AF_INET, SOCK_DGRAM, 0);
+    if (sock < 0) {
+        throw std::runtime_error("Impossibile creare il socket RTP");
+    }
+    socket_ = static_cast<intptr_t>(sock);
+
+    if (isMulticast(destination_addr_)) {
+#ifdef _WIN32
+        DWORD ttl = config_.multicast_ttl;
+#else
+        unsigned char ttl = config_.multicast_ttl;
+#endif
+        setsockopt(sock, IPPROTO_IP, IP_MULTICAST_TTL, reinterpret_cast<const char*>(&ttl), sizeof(ttl));
+    }
+
+    SABER_LOG(Info, "audio", "Uscita RTP inizializzata: " << config_.destination << ":" << config_.port
+              << ", " << sample_rate_ << "Hz, " << (int)channels_ << " canali");
+}
+
+RtpEgress::~RtpEgress() {
+    stopStream();
+
+    if (socket_ >= 0) {
+        SABER_CLOSE_SOCKET(static_cast<int>(socket_));
+        socket_ = -1;
+    }
+}
+
+void RtpEgress::startStream() {
+    if (is_active_.exchange(true)) {
+        return;
+    }
+
+    clock_anchored_ = false;
+    pacing_thread_ = std::thread(&RtpEgress::pacingLoop, this);
+    SABER_LOG(Info, "audio", "Uscita RTP avviata");
+}
+
+void RtpEgress::stopStream() {
+    if (!is_active_.exchange(false)) {
+        return;
+    }
+
+    if (pacing_thread_.joinable()) {
+        pacing_thread_.join();
+    }
+    SABER_LOG(Info, "audio", "Uscita RTP fermata");
+}
+
+size_t RtpEgress::writeAudio(const float* data, size_t frames, uint64_t timestamp) {
+    std::lock_guard<std::mutex> lock(bass_mutex_);
+
+    if (bass_manager_.role() == BassRole::FullRange) {
+        return buffer_.write_samples(data, frames, timestamp);
+    }
+
+    // Filtro una copia: il chiamante mantiene il mix originale
+    bass_scratch_.assign(data, data + frames * channels_);
+    bass_manager_.process(bass_scratch_.data(), frames);
+    return buffer_.write_samples(bass_scratch_.data(), frames, timestamp);
+}
+
+uint32_t RtpEgress::getCurrentLatency() const {
+    // Il ricevitore aggiunge il proprio link offset dopo il pacchetto
+    return buffer_.get_latency_ms() + config_.packet_time_us / 1000 + config_.link_offset_ms;
+}
+
+void RtpEgress::setBufferSize(uint32_t buffer_ms) {
+    buffer_.set_buffer_size_ms(buffer_ms);
+}
+
+uint8_t RtpEgress::getBufferLevel() const {
+    return buffer_.get_fill_level();
+}
+
+void RtpEgress::flush() {
+    buffer_.clear();
+}
+
+void RtpEgress::setBassManagement(BassRole role, float crossover_hz) {
+    std::lock_guard<std::mutex> lock(bass_mutex_);
+    bass_manager_.configure(role, crossover_hz);
+}
+
+uint64_t RtpEgress::getPacketsSent() const {
+    return packets_sent_.load();
+}
+
+uint64_t RtpEgress::getConcealedFrames() const {
+    return concealed_frames_.load();
+}
+
+uint32_t RtpEgress::getSsrc() const {
+    return config_.ssrc;
+}
+
+std::string RtpEgress::describeSdp(const std::string& session_name, const std::string& origin_address) const {
+    std::ostringstream sdp;
+    sdp << "v=0\r\n";
+    sdp << "o=- " << config_.ssrc << " 0 IN IP4 " << origin_address << "\r\n";
+    sdp << "s=" << session_name << "\r\n";
+    sdp << "c=IN IP4 " << config_.destination;
+    if (isMulticast(destination_addr_)) {
+        sdp << "/" << (int)config_.multicast_ttl;
+    }
+    sdp << "\r\n";
+    sdp << "t=0 0\r\n";
+    sdp << "m=audio " << config_.port << " RTP/AVP " << (int)config_.payload_type << "\r\n";
+    sdp << "a=rtpmap:" << (int)config_.payload_type << " "
+        << (config_.encoding == RtpEncoding::L24 ? "L24" : "L16") << "/"
+        << sample_rate_ << "/" << (int)channels_ << "\r\n";
+    sdp << "a=ptime:" << config_.packet_time_us / 1000.0 << "\r\n";
+    sdp << "a=sendonly\r\n";
+    // Il media clock coincide con l'orologio sincronizzato della mesh
+    sdp << "a=ts-refclk:ptp=traceable\r\n";
+    sdp << "a=mediaclk:direct=0\r\n";
+    return sdp.str();
+}
+
+std::vector<uint8_t> RtpEgress::buildPacket(
+    uint8_t payload_type,
+    uint16_t sequence,
+    uint32_t timestamp,
+    uint32_t ssrc,
+    RtpEncoding encoding,
+    const float* samples,
+    size_t count
+) {
+    std::vector<uint8_t> packet;
+    packet.reserve(RTP_HEADER_SIZE + count * bytesPerSample(encoding));
+
+    // Versione 2, nessun padding, estensione, CSRC o marker
+    packet.push_back(0x80);
+    packet.push_back(payload_type & 0x7F);
+    writeBigEndian(packet, sequence, 2);
+    writeBigEndian(packet, timestamp, 4);
+    writeBigEndian(packet, ssrc, 4);
+
+    float scale = encoding == RtpEncoding::L24 ? 8388607.0f : 32767.0f;
+    size_t width = bytesPerSample(encoding);
+    for (size_t i = 0; i < count; ++i) {
+        float clamped = std::max(-1.0f, std::min(1.0f, samples[i]));
+        int32_t value = static_cast<int32_t>(std::lrint(clamped * scale));
+        writeBigEndian(packet, static_cast<uint32_t>(value), width);
+    }
+    return packet;
+}
+
+uint64_t RtpEgress::mediaClock(uint64_t sync_time_ms, uint32_t sample_rate) {
+    return sync_time_ms * sample_rate / 1000;
+}
+
+void RtpEgress::pacingLoop() {
+    auto period = std::chrono::microseconds(config_.packet_time_us);
+    auto deadline = std::chrono::steady_clock::now();
+
+    while (is_active_) {
+        sendNextPacket();
+
+        deadline += period;
+        auto now = std::chrono::steady_clock::now();
+        if (now - deadline > period * MAX_PACING_BACKLOG) {
+            // Troppo indietro (sospensione, scheduler): riparto dal media clock corrente
+            deadline = now;
+            clock_anchored_ = false;
+        }
+        std::this_thread::sleep_until(deadline);
+    }
+}
+
+void RtpEgress::sendNextPacket() {
+    uint64_t now_ms = time_provider_();
+
+    // Il media clock avanza di un pacchetto alla volta; lo riaggancio
+    // all'orologio sincronizzato solo se deriva oltre la risoluzione in ms
+    uint64_t expected = mediaClock(now_ms, sample_rate_);
+    uint64_t drift = media_clock_ > expected ? media_clock_ - expected : expected - media_clock_;
+    if (!clock_anchored_ || drift > frames_per_packet_ * 2 + sample_rate_ / 1000) {
+        media_clock_ = expected;
+        clock_anchored_ = true;
+    }
+
+    // Leggo in anticipo i campioni che il ricevitore riprodurrà dopo il link offset
+    size_t read = buffer_.read_samples(packet_samples_.data(), frames_per_packet_,
+                                       now_ms + config_.link_offset_ms);
+    if (read < frames_per_packet_) {
+        // Un amplificatore di rete riceve silenzio digitale: il comfort noise è dei DAC locali
+        std::fill(packet_samples_.begin() + read * channels_, packet_samples_.end(), 0.0f);
+        concealed_frames_ += frames_per_packet_ - read;
+    }
+
+    auto packet = buildPacket(config_.payload_type, sequence_, static_cast<uint32_t>(media_clock_),
+                              config_.ssrc, config_.encoding, packet_samples_.data(),
+                              packet_samples_.size());
+
+    sockaddr_in destination{};
+    destination.sin_family = AF_INET;
+    destination.sin_port = htons(config_.port);
+    destination.sin_addr.s_addr = destination_addr_;
+
+    auto sent = sendto(static_cast<int>(socket_), reinterpret_cast<const char*>(packet.data()),
+                       static_cast<int>(packet.size()), 0,
+                       reinterpret_cast<const sockaddr*>(&destination), sizeof(destination));
+    if (sent < 0) {
+        SABER_LOG(Warn, "audio", "Errore invio pacchetto RTP");
+    } else {
+        packets_sent_++;
+    }
+
+    sequence_++;
+    media_clock_ += frames_per_packet_;
+}
+
+} // namespace audio
+} // namespace saber
@@ -0,0 +1,177 @@
+// Uscita audio di rete RTP/AES67 per il protocollo SABER
+// Trasmette lo stream sincronizzato ad amplificatori raggiungibili in rete
+
+#ifndef SABER_AUDIO_RTP_EGRESS_HPP
+#define SABER_AUDIO_RTP_EGRESS_HPP
+
+#include "audio_output.hpp"
+#include "buffer.hpp"
+#include "crossover.hpp"
+#include <atomic>
+#include <cstdint>
+#include <functional>
+#include <mutex>
+#include <string>
+#include <thread>
+#include <vector>
+
+namespace saber {
+namespace audio {
+
+/**
+ * Codifica dei campioni nel payload RTP
+ */
+enum class RtpEncoding : uint8_t {
+    L16 = 0,    // PCM 16 bit big-endian (RFC 3551)
+    L24 = 1     // PCM 24 bit big-endian (RFC 3190), formato base di AES67
+};
+
+/**
+ * Destinazione di un'uscita RTP
+ *
+ * I valori di default seguono il profilo base AES67: L24, 1 ms per
+ * pacchetto, payload type dinamico e indirizzo multicast amministrativo.
+ */
+struct RtpEgressConfig {
+    std::string destination = "239.69.0.1";  // Indirizzo IPv4 unicast o multicast
+    uint16_t port = 5004;                    // Porta UDP di destinazione
+    uint8_t payload_type = 97;               // Payload type dinamico (96-127)
+    RtpEncoding encoding = RtpEncoding::L24; // Formato dei campioni
+    uint32_t packet_time_us = 1000;          // Durata audio di ogni pacchetto
+    uint32_t ssrc = 0;                       // SSRC del flusso (casuale se 0)
+    uint8_t multicast_ttl = 16;              // TTL dei pacchetti multicast
+    uint32_t link_offset_ms = 2;             // Ritardo di riproduzione configurato sul ricevitore
+};
+
+/**
+ * Uscita audio verso un ricevitore RTP/AES67
+ *
+ * Un thread di pacing legge dal buffer sincronizzato un pacchetto ogni
+ * packet_time_us. I campioni vengono letti con link_offset_ms di anticipo
+ * e marcati con il media clock dell'istante di invio: il ricevitore, che
+ * riproduce con lo stesso ritardo, li emette quindi insieme ai sink locali.
+ * Il timestamp RTP è l'orologio sincronizzato espresso in campioni
+ * (mediaclk:direct=0), come il riferimento PTP di AES67.
+ */
+class RtpEgress : public AudioOutput {
+public:
+    /**
+     * Costruttore
+     * @param sample_rate Frequenza di campionamento in Hz
+     * @param channels Numero di canali
+     * @param buffer_ms Dimensione del buffer in millisecondi
+     * @param time_provider Orologio sincronizzato in millisecondi
+     * @param config Destinazione e formato del flusso
+     * @throws std::invalid_argument se la configurazione non è valida
+     * @throws std::runtime_error se il socket non può essere aperto
+     */
+    RtpEgress(
+        uint32_t sample_rate,
+        uint8_t channels,
+        uint32_t buffer_ms,
+        std::function<uint64_t()> time_provider,
+        const RtpEgressConfig& config
+    );
+
+    /**
+     * Distruttore: ferma il thread di pacing e chiude il socket
+     */
+    ~RtpEgress() override;
+
+    void startStream() override;
+    void stopStream() override;
+    size_t writeAudio(const float* data, size_t frames, uint64_t timestamp) override;
+    uint32_t getCurrentLatency() const override;
+    void setBufferSize(uint32_t buffer_ms) override;
+    uint8_t getBufferLevel() const override;
+    void flush() override;
+    void setBassManagement(BassRole role, float crossover_hz) override;
+
+    /**
+     * Numero di pacchetti RTP inviati
+     */
+    uint64_t getPacketsSent() const;
+
+    /**
+     * Frame trasmessi come silenzio perché il buffer era vuoto
+     */
+    uint64_t getConcealedFrames() const;
+
+    /**
+     * SSRC del flusso
+     */
+    uint32_t getSsrc() const;
+
+    /**
+     * Descrizione SDP del flusso, da caricare sul ricevitore
+     * @param session_name Nome della sessione mostrato dal ricevitore
+     * @param origin_address Indirizzo IPv4 del nodo che trasmette
+     * @return Descrizione SDP (RFC 4566) con gli attributi AES67
+     */
+    std::string describeSdp(const std::string& session_name, const std::string& origin_address) const;
+
+    /**
+     * Costruisce un pacchetto RTP
+     * @param payload_type Payload type
+     * @param sequence Numero di sequenza
+     * @param timestamp Timestamp RTP (media clock)
+     * @param ssrc SSRC del flusso
+     * @param encoding Formato dei campioni
+     * @param samples Campioni float interleaved
+     * @param count Numero totale di campioni (frame per canali)
+     * @return Header RTP di 12 byte seguito dal payload big-endian
+     */
+    static std::vector<uint8_t> buildPacket(
+        uint8_t payload_type,
+        uint16_t sequence,
+        uint32_t timestamp,
+        uint32_t ssrc,
+        RtpEncoding encoding,
+        const float* samples,
+        size_t count
+    );
+
+    /**
+     * Converte l'orologio sincronizzato nel media clock RTP
+     * @param sync_time_ms Tempo sincronizzato in millisecondi
+     * @param sample_rate Frequenza di campionamento in Hz
+     * @return Campioni trascorsi dall'epoca dell'orologio
+     */
+    static uint64_t mediaClock(uint64_t sync_time_ms, uint32_t sample_rate);
+
+private:
+    /**
+     * Ciclo del thread di pacing
+     */
+    void pacingLoop();
+
+    /**
+     * Invia un pacchetto con i campioni correnti del buffer
+     */
+    void sendNextPacket();
+
+    uint32_t sample_rate_;
+    uint8_t channels_;
+    RtpEgressConfig config_;
+    size_t frames_per_packet_;                 // Frame audio per pacchetto
+    AudioBuffer buffer_;                       // Campioni in attesa di trasmissione
+    BassManager bass_manager_;                 // Crossover per sink subwoofer/satellite
+    std::vector<float> bass_scratch_;          // Copia filtrata del blocco in ingresso
+    std::mutex bass_mutex_;                    // Protegge bass manager e scratch
+    std::function<uint64_t()> time_provider_;  // Orologio sincronizzato
+    std::vector<float> packet_samples_;        // Campioni del pacchetto corrente
+    intptr_t socket_;                          // Socket UDP di uscita
+    uint32_t destination_addr_;                // Indirizzo di destinazione (network byte order)
+    uint16_t sequence_;                        // Prossimo numero di sequenza
+    uint64_t media_clock_;                     // Media clock del prossimo pacchetto
+    bool clock_anchored_;                      // Media clock agganciato all'orologio
+    std::atomic<bool> is_active_;
+    std::atomic<uint64_t> packets_sent_;
+    std::atomic<uint64_t> concealed_frames_;
+    std::thread pacing_thread_;
+};
+
+} // namespace audio
+} // namespace saber
+
+#endif // SABER_AUDIO_RTP_EGRESS_HPP
@@ -0,0 +1,115 @@
+// Implementazione della cache dei frame di silenzio per SABER Protocol
+
+#include "silence.hpp"
+#include <algorithm>
+#include <cstring>
+#include <stdexcept>
+
+namespace saber {
+namespace audio {
+
+namespace {
+
+// Codifica PCM float grezza, usata quando il codec non fornisce un encoder
+std::vector<uint8_t> encode_raw_pcm(const std::vector<float>& samples) {
+    std::vector<uint8_t> bytes(samples.size() * sizeof(float));
+    if (!bytes.empty()) {
+        std::memcpy(bytes.data(), samples.data(), bytes.size());
+    }
+    return bytes;
+}
+
+} // namespace
+
+SilenceFrameCache::SilenceFrameCache(float comfort_noise_level, uint32_t seed)
+    : comfort_noise_level_(comfort_noise_level)
+    , seed_(seed == 0 ? 1 : seed)
+{
+    if (comfort_noise_level < 0.0f || comfort_noise_level > 1.0f) {
+        throw std::invalid_argument("Livello del comfort noise non valido");
+    }
+}
+
+void SilenceFrameCache::register_codec(CodecType codec, const CodecFrameFormat& format, FrameEncoder encoder) {
+    size_t frames = format.frames_per_codec_frame();
+    if (frames == 0 || format.channels == 0) {
+        throw std::invalid_argument("Formato del frame non valido");
+    }
+
+    CachedFrames cached;
+    cached.format = format;
+
+    std::vector<float> silence(frames * format.channels, 0.0f);
+
+    // Rumore bianco a basso livello da generatore xorshift: deterministico e senza allocazioni
+    cached.comfort_noise_samples.resize(frames * format.channels);
+    uint32_t state = seed_;
+    for (float& sample : cached.comfort_noise_samples) {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        float unit = static_cast<float>(state) / 4294967295.0f;
+        sample = (unit * 2.0f - 1.0f) * comfort_noise_level_;
+    }
+
+    if (encoder) {
+        cached.silence_frame = encoder(silence.data(), frames);
+        cached.comfort_noise_frame = encoder(cached.comfort_noise_samples.data(), frames);
+    } else {
+        cached.silence_frame = encode_raw_pcm(silence);
+        cached.comfort_noise_frame = encode_raw_pcm(cached.comfort_noise_samples);
+    }
+
+    std::lock_guard<std::mutex> lock(mutex_);
+    frames_[codec] = std::move(cached);
+}
+
+bool SilenceFrameCache::has_codec(CodecType codec) const {
+    std::lock_guard<std::mutex> lock(mutex_);
+    return frames_.count(codec) > 0;
+}
+
+const std::vector<uint8_t>& SilenceFrameCache::silence_frame(CodecType codec) const {
+    return entry(codec).silence_frame;
+}
+
+const std::vector<uint8_t>& SilenceFrameCache::comfort_noise_frame(CodecType codec) const {
+    return entry(codec).comfort_noise_frame;
+}
+
+const std::vector<float>& SilenceFrameCache::comfort_noise_samples(CodecType codec) const {
+    return entry(codec).comfort_noise_samples;
+}
+
+const CodecFrameFormat& SilenceFrameCache::format(CodecType codec) const {
+    return entry(codec).format;
+}
+
+const SilenceFrameCache::CachedFrames& SilenceFrameCache::entry(CodecType codec) const {
+    std::lock_guard<std::mutex> lock(mutex_);
+    auto it = frames_.find(codec);
+    if (it == frames_.end()) {
+        throw std::out_of_range("Codec non registrato nella cache dei frame di silenzio");
+    }
+    // I riferimenti restano validi: gli elementi di std::map non vengono spostati
+    return it->second;
+}
+
+void fill_from_frame(const std::vector<float>& frame, float* out, size_t count, size_t& cursor) {
+    if (frame.empty()) {
+        std::memset(out, 0, count * sizeof(float));
+        return;
+    }
+
+    size_t written = 0;
+    while (written < count) {
+        cursor %= frame.size();
+        size_t chunk = std::min(count - written, frame.size() - cursor);
+        std::memcpy(out + written, frame.data() + cursor, chunk * sizeof(float));
+        written += chunk;
+        cursor += chunk;
+    }
+}
+
+} // namespace audio
+} // namespace saber
@@ -0,0 +1,127 @@
+// Frame di silenzio e comfort noise pre-calcolati per il protocollo SABER
+// Evitano di invocare l'encoder durante PLC, sospensione e rebuffering
+
+#ifndef SABER_AUDIO_SILENCE_HPP
+#define SABER_AUDIO_SILENCE_HPP
+
+#include <cstdint>
+#include <functional>
+#include <map>
+#include <mutex>
+#include <vector>
+
+namespace saber {
+namespace audio {
+
+/**
+ * Codec audio supportati dalla pipeline
+ */
+enum class CodecType : uint8_t {
+    Pcm = 0,    // PCM float 32-bit interleaved, nessuna compressione
+    Lc3 = 1     // LC3 (Auracast)
+};
+
+/**
+ * Formato di un frame codificato
+ */
+struct CodecFrameFormat {
+    uint32_t sample_rate;   // Frequenza di campionamento in Hz
+    uint8_t channels;       // Numero di canali
+    uint32_t frame_us;      // Durata del frame in microsecondi
+
+    /**
+     * Restituisce il numero di frame audio (per canale) contenuti in un frame codec
+     */
+    size_t frames_per_codec_frame() const {
+        return static_cast<size_t>(sample_rate) * frame_us / 1000000;
+    }
+};
+
+/**
+ * Encoder di un singolo frame: riceve campioni float interleaved e
+ * restituisce il frame codificato
+ */
+using FrameEncoder = std::function<std::vector<uint8_t>(const float* samples, size_t frames)>;
+
+/**
+ * Cache dei frame di silenzio e comfort noise per ogni codec
+ *
+ * L'encoder viene eseguito una sola volta alla registrazione del codec;
+ * i percorsi di concealment leggono poi i frame dalla cache senza costo di CPU.
+ */
+class SilenceFrameCache {
+public:
+    /**
+     * Costruttore
+     * @param comfort_noise_level Ampiezza di picco del comfort noise (0.0-1.0)
+     * @param seed Seme del generatore di rumore, per frame riproducibili
+     */
+    explicit SilenceFrameCache(float comfort_noise_level = 0.0005f, uint32_t seed = 0x5AB3u);
+
+    /**
+     * Registra un codec pre-calcolando i suoi frame
+     * Va chiamato prima di avviare i percorsi audio che leggono la cache
+     * @param codec Codec da registrare
+     * @param format Formato dei frame
+     * @param encoder Encoder del codec; se assente i frame sono PCM float grezzi
+     */
+    void register_codec(CodecType codec, const CodecFrameFormat& format, FrameEncoder encoder = nullptr);
+
+    /**
+     * Verifica se un codec è stato registrato
+     */
+    bool has_codec(CodecType codec) const;
+
+    /**
+     * Restituisce il frame codificato di silenzio digitale
+     * @throws std::out_of_range se il codec non è registrato
+     */
+    const std::vector<uint8_t>& silence_frame(CodecType codec) const;
+
+    /**
+     * Restituisce il frame codificato di comfort noise
+     * @throws std::out_of_range se il codec non è registrato
+     */
+    const std::vector<uint8_t>& comfort_noise_frame(CodecType codec) const;
+
+    /**
+     * Restituisce i campioni decodificati del comfort noise (float interleaved)
+     * @throws std::out_of_range se il codec non è registrato
+     */
+    const std::vector<float>& comfort_noise_samples(CodecType codec) const;
+
+    /**
+     * Restituisce il formato registrato per un codec
+     * @throws std::out_of_range se il codec non è registrato
+     */
+    const CodecFrameFormat& format(CodecType codec) const;
+
+private:
+    struct CachedFrames {
+        CodecFrameFormat format;
+        std::vector<float> comfort_noise_samples;
+        std::vector<uint8_t> silence_frame;
+        std::vector<uint8_t> comfort_noise_frame;
+    };
+
+    const CachedFrames& entry(CodecType codec) const;
+
+    float comfort_noise_level_;             // Ampiezza del comfort noise
+    uint32_t seed_;                         // Seme del generatore
+    std::map<CodecType, CachedFrames> frames_;  // Frame pre-calcolati per codec
+    mutable std::mutex mutex_;              // Mutex per thread-safety
+};
+
+/**
+ * Riempie un buffer ripetendo ciclicamente un frame pre-calcolato
+ * @param frame Campioni interleaved del frame sorgente
+ * @param out Buffer di destinazione
+ * @param count Numero di campioni (non frame) da scrivere
+ * @param cursor Posizione di lettura nel frame, aggiornata per la chiamata successiva
+ */
+void fill_from_frame(const std::vector<float>& frame, float* out, size_t count, size_t& cursor);
+
+} // namespace audio
+} // namespace saber
+
+#endif // SABER_AUDIO_SILENCE_HPP
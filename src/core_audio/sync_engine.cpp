@@ -1,6 +1,7 @@
 #include "sync_engine.hpp"
 #include "buffer.hpp"
 #include "audio_stream.hpp"
+#include <algorithm>
 #include <thread>
 #include <mutex>
 #include <iostream>
@@ -17,6 +18,8 @@ SyncEngine::SyncEngine(uint32_t sample_rate, uint8_t channels, uint32_t initial_
     , is_active_(false)
     , is_synchronized_(false)
     , audio_stream_(nullptr)
+    , late_policy_(LateFramePolicy::Drop)
+    , late_tolerance_ms_(0)
 {
     // Inizializzo un timestamp di partenza
     start_time_ = std::chrono::steady_clock::now();
@@ -26,19 +29,30 @@ SyncEngine::~SyncEngine() {
     stop();
 }
 
-void SyncEngine::initialize(std::function<uint64_t()> time_provider) {
+void SyncEngine::initialize(std::function<uint64_t()> time_provider, const OutputConfig& output) {
     time_provider_ = time_provider;
 
-    // Creo lo stream audio passando la funzione che fornisce il timestamp locale sincronizzato
-    audio_stream_ = std::make_unique<AudioStream>(
-        sample_rate_,
-        channels_,
-        20, // Buffer iniziale di 20ms
-        [this]() { return getLocalSyncTime(); }
-    );
+    // Creo l'uscita passando la funzione che fornisce il timestamp locale sincronizzato
+    if (output.backend == OutputBackend::Rtp) {
+        audio_stream_ = std::make_unique<RtpEgress>(
+            sample_rate_,
+            channels_,
+            20, // Buffer iniziale di 20ms
+            [this]() { return getLocalSyncTime(); },
+            output.rtp
+        );
+    } else {
+        audio_stream_ = std::make_unique<AudioStream>(
+            sample_rate_,
+            channels_,
+            20, // Buffer iniziale di 20ms
+            [this]() { return getLocalSyncTime(); }
+        );
+    }
 
     std::cout << "SyncEngine inizializzato: " << sample_rate_ << "Hz, " 
-              << (int)channels_ << " canali" << std::endl;
+              << (int)channels_ << " canali, uscita "
+              << (output.backend == OutputBackend::Rtp ? "RTP" : "locale") << std::endl;
 }
 
 void SyncEngine::start(uint32_t optimal_buffer_ms) {
@@ -83,7 +97,59 @@ size_t SyncEngine::writeAudioData(const float* data, size_t frames, uint64_t sou
         return 0;
     }
 
-    return audio_stream_->writeAudio(data, frames, source_timestamp);
+    uint64_t now = getLocalSyncTime();
+    std::lock_guard<std::mutex> lock(late_mutex_);
+    if (source_timestamp + late_tolerance_ms_ >= now) {
+        late_stats_.on_time++;
+        return audio_stream_->writeAudio(data, frames, source_timestamp);
+    }
+
+    uint64_t late_ms = now - source_timestamp;
+    switch (late_policy_) {
+        case LateFramePolicy::PlayLate:
+            // Il frame parte adesso: tutto ciò che segue scorre dello stesso ritardo
+            late_stats_.played_late++;
+            late_stats_.slid_ms += late_ms;
+            return audio_stream_->writeAudio(data, frames, now);
+        case LateFramePolicy::Resync: {
+            // Scarto quanto già scaduto e riparto dall'orologio del master
+            late_stats_.resyncs++;
+            audio_stream_->flush();
+            size_t expired = std::min<size_t>(frames, static_cast<size_t>(late_ms * sample_rate_ / 1000));
+            if (expired == frames) {
+                return 0;
+            }
+            return audio_stream_->writeAudio(data + expired * channels_, frames - expired, now);
+        }
+        case LateFramePolicy::Drop:
+        default:
+            late_stats_.dropped++;
+            return 0;
+    }
+}
+
+void SyncEngine::setBassManagement(BassRole role, float crossover_hz) {
+    if (!audio_stream_) {
+        throw std::runtime_error("SyncEngine non inizializzato");
+    }
+
+    audio_stream_->setBassManagement(role, crossover_hz);
+}
+
+void SyncEngine::setLateFramePolicy(LateFramePolicy policy, uint32_t tolerance_ms) {
+    std::lock_guard<std::mutex> lock(late_mutex_);
+    late_policy_ = policy;
+    late_tolerance_ms_ = tolerance_ms;
+}
+
+LateFramePolicy SyncEngine::getLateFramePolicy() const {
+    std::lock_guard<std::mutex> lock(late_mutex_);
+    return late_policy_;
+}
+
+LateFrameStats SyncEngine::getLateFrameStats() const {
+    std::lock_guard<std::mutex> lock(late_mutex_);
+    return late_stats_;
 }
 
 uint32_t SyncEngine::getCurrentLatency() const {
@@ -102,6 +168,10 @@ uint8_t SyncEngine::getBufferLevel() const {
     return audio_stream_->getBufferLevel();
 }
 
+AudioOutput* SyncEngine::getOutput() const {
+    return audio_stream_.get();
+}
+
 bool SyncEngine::isActive() const {
     return is_active_;
 }
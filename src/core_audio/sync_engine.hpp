@@ -1,103 +1,167 @@
-#pragma once
-
-#include "buffer.hpp"
-#include "audio_stream.hpp"
-#include <chrono>
-#include <functional>
-#include <memory>
-#include <atomic>
-
-namespace saber {
-namespace audio {
-
-/**
- * Engine di sincronizzazione audio
- * Gestisce la comunicazione tra il livello di protocollo e l'audio engine
- */
-class SyncEngine {
-public:
-    /**
-     * Costruttore
-     * @param sample_rate Frequenza di campionamento in Hz
-     * @param channels Numero di canali (1=mono, 2=stereo)
-     * @param initial_buffer_ms Buffer iniziale in millisecondi
-     */
-    SyncEngine(uint32_t sample_rate, uint8_t channels, uint32_t initial_buffer_ms = 20);
-
-    /**
-     * Distruttore
-     */
-    ~SyncEngine();
-
-    /**
-     * Inizializza l'engine
-     * @param time_provider Funzione che fornisce il timestamp globale sincronizzato
-     */
-    void initialize(std::function<uint64_t()> time_provider);
-
-    /**
-     * Avvia la riproduzione sincronizzata
-     * @param optimal_buffer_ms Buffer ottimale in ms basato sulla latenza di rete
-     */
-    void start(uint32_t optimal_buffer_ms = 20);
-
-    /**
-     * Ferma la riproduzione
-     */
-    void stop();
-
-    /**
-     * Aggiorna lo stato di sincronizzazione
-     * @param is_synced Flag che indica se il nodo è sincronizzato
-     * @param time_offset Offset temporale da applicare in millisecondi
-     */
-    void updateSyncState(bool is_synced, int64_t time_offset);
-
-    /**
-     * Scrive dati audio nel buffer
-     * @param data Campioni audio (float interleaved)
-     * @param frames Numero di frame
-     * @param source_timestamp Timestamp dei campioni (dal master)
-     * @return Numero di frame scritti
-     */
-    size_t writeAudioData(const float* data, size_t frames, uint64_t source_timestamp);
-
-    /**
-     * Ottiene la latenza corrente in millisecondi
-     */
-    uint32_t getCurrentLatency() const;
-
-    /**
-     * Ottiene il livello di riempimento del buffer (0-100)
-     */
-    uint8_t getBufferLevel() const;
-
-    /**
-     * Verifica se il motore è attivo
-     */
-    bool isActive() const;
-
-    /**
-     * Verifica se il motore è sincronizzato
-     */
-    bool isSynchronized() const;
-
-private:
-    /**
-     * Ottiene il timestamp corrente sincronizzato per l'audio locale
-     * Questo timestamp tiene conto dell'offset di sincronizzazione
-     */
-    uint64_t getLocalSyncTime() const;
-
-    uint32_t sample_rate_;                      // Frequenza di campionamento
-    uint8_t channels_;                          // Numero di canali
-    std::chrono::steady_clock::time_point start_time_;  // Timestamp di partenza
-    int64_t time_offset_;                       // Offset di sincronizzazione in ms
-    std::atomic<bool> is_active_;               // Flag attività
-    std::atomic<bool> is_synchronized_;         // Flag sincronizzazione
-    std::function<uint64_t()> time_provider_;   // Provider timestamp sincronizzato
-    std::unique_ptr<AudioStream> audio_stream_; // Stream audio
-};
-
-} // namespace audio
+#pragma once
+
+#include "buffer.hpp"
+#include "audio_stream.hpp"
+#include "rtp_egress.hpp"
+#include <chrono>
+#include <functional>
+#include <memory>
+#include <atomic>
+#include <mutex>
+
+namespace saber {
+namespace audio {
+
+/**
+ * Uscita scelta per un sink
+ */
+struct OutputConfig {
+    OutputBackend backend = OutputBackend::SoundCard;  // Scheda audio locale o rete
+    RtpEgressConfig rtp;                               // Destinazione se backend == Rtp
+};
+
+/**
+ * Comportamento con un frame arrivato dopo il suo istante di riproduzione
+ */
+enum class LateFramePolicy : uint8_t {
+    Drop = 0,      // Il frame viene scartato, l'uscita resta allineata
+    PlayLate = 1,  // Il frame viene riprodotto subito, la timeline scorre in avanti
+    Resync = 2     // Il buffer viene svuotato e la parte ancora utile del frame riallinea l'uscita
+};
+
+/**
+ * Esiti dei frame scritti nel SyncEngine
+ */
+struct LateFrameStats {
+    uint64_t on_time = 0;      // Frame arrivati in tempo
+    uint64_t dropped = 0;      // Frame in ritardo scartati
+    uint64_t played_late = 0;  // Frame in ritardo riprodotti facendo scorrere la timeline
+    uint64_t resyncs = 0;      // Riallineamenti dell'uscita
+    uint64_t slid_ms = 0;      // Scorrimento complessivo della timeline in ms
+};
+
+/**
+ * Engine di sincronizzazione audio
+ * Gestisce la comunicazione tra il livello di protocollo e l'audio engine
+ */
+class SyncEngine {
+public:
+    /**
+     * Costruttore
+     * @param sample_rate Frequenza di campionamento in Hz
+     * @param channels Numero di canali (1=mono, 2=stereo)
+     * @param initial_buffer_ms Buffer iniziale in millisecondi
+     */
+    SyncEngine(uint32_t sample_rate, uint8_t channels, uint32_t initial_buffer_ms = 20);
+
+    /**
+     * Distruttore
+     */
+    ~SyncEngine();
+
+    /**
+     * Inizializza l'engine
+     * @param time_provider Funzione che fornisce il timestamp globale sincronizzato
+     * @param output Uscita su cui riprodurre lo stream sincronizzato
+     */
+    void initialize(std::function<uint64_t()> time_provider, const OutputConfig& output = OutputConfig());
+
+    /**
+     * Avvia la riproduzione sincronizzata
+     * @param optimal_buffer_ms Buffer ottimale in ms basato sulla latenza di rete
+     */
+    void start(uint32_t optimal_buffer_ms = 20);
+
+    /**
+     * Ferma la riproduzione
+     */
+    void stop();
+
+    /**
+     * Aggiorna lo stato di sincronizzazione
+     * @param is_synced Flag che indica se il nodo è sincronizzato
+     * @param time_offset Offset temporale da applicare in millisecondi
+     */
+    void updateSyncState(bool is_synced, int64_t time_offset);
+
+    /**
+     * Scrive dati audio nel buffer
+     * @param data Campioni audio (float interleaved)
+     * @param frames Numero di frame
+     * @param source_timestamp Timestamp dei campioni (dal master)
+     * @return Numero di frame scritti
+     */
+    size_t writeAudioData(const float* data, size_t frames, uint64_t source_timestamp);
+
+    /**
+     * Configura la gestione dei bassi del sink
+     * @param role Ruolo del sink nel gruppo
+     * @param crossover_hz Frequenza di crossover in Hz
+     */
+    void setBassManagement(BassRole role, float crossover_hz);
+
+    /**
+     * Configura il comportamento con i frame in ritardo
+     * @param policy Scarto, riproduzione in ritardo o riallineamento
+     * @param tolerance_ms Ritardo tollerato prima di applicare la politica
+     */
+    void setLateFramePolicy(LateFramePolicy policy, uint32_t tolerance_ms = 0);
+
+    /**
+     * Ottiene la politica corrente per i frame in ritardo
+     */
+    LateFramePolicy getLateFramePolicy() const;
+
+    /**
+     * Ottiene i contatori degli esiti dei frame scritti
+     */
+    LateFrameStats getLateFrameStats() const;
+
+    /**
+     * Ottiene la latenza corrente in millisecondi
+     */
+    uint32_t getCurrentLatency() const;
+
+    /**
+     * Ottiene il livello di riempimento del buffer (0-100)
+     */
+    uint8_t getBufferLevel() const;
+
+    /**
+     * Ottiene l'uscita audio corrente (nullptr se non inizializzato)
+     */
+    AudioOutput* getOutput() const;
+
+    /**
+     * Verifica se il motore è attivo
+     */
+    bool isActive() const;
+
+    /**
+     * Verifica se il motore è sincronizzato
+     */
+    bool isSynchronized() const;
+
+private:
+    /**
+     * Ottiene il timestamp corrente sincronizzato per l'audio locale
+     * Questo timestamp tiene conto dell'offset di sincronizzazione
+     */
+    uint64_t getLocalSyncTime() const;
+
+    uint32_t sample_rate_;                      // Frequenza di campionamento
+    uint8_t channels_;                          // Numero di canali
+    std::chrono::steady_clock::time_point start_time_;  // Timestamp di partenza
+    int64_t time_offset_;                       // Offset di sincronizzazione in ms
+    std::atomic<bool> is_active_;               // Flag attività
+    std::atomic<bool> is_synchronized_;         // Flag sincronizzazione
+    std::function<uint64_t()> time_provider_;   // Provider timestamp sincronizzato
+    std::unique_ptr<AudioOutput> audio_stream_; // Uscita audio (locale o RTP)
+    LateFramePolicy late_policy_;               // Politica per i frame in ritardo
+    uint32_t late_tolerance_ms_;                // Ritardo tollerato
+    LateFrameStats late_stats_;                 // Esiti dei frame scritti
+    mutable std::mutex late_mutex_;             // Protegge politica e contatori
+};
+
+} // namespace audio
 } // namespace saber
\ No newline at end of file
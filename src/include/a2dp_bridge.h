@@ -0,0 +1,97 @@
+#ifndef SABER_A2DP_BRIDGE_H
+#define SABER_A2DP_BRIDGE_H
+
+#include <cstdint>
+#include <mutex>
+#include <string>
+
+namespace saber {
+
+/**
+ * @brief Contatori del ponte verso un dispositivo A2DP
+ */
+struct A2dpBridgeStats {
+    /// Indirizzo del dispositivo A2DP
+    std::string device;
+
+    /// Latenza nota del collegamento A2DP (ms)
+    uint32_t latencyMs = 0;
+
+    /// Frame inoltrati al dispositivo
+    uint64_t framesForwarded = 0;
+
+    /// Frame arrivati dopo il loro istante di emissione
+    uint64_t lateFrames = 0;
+
+    /// Anticipo dell'ultimo frame sul suo istante di emissione (ms, negativo se in ritardo)
+    int64_t lastLeadMs = 0;
+};
+
+/**
+ * @brief Ritrasmissione dei flussi di un Repeater verso cuffie A2DP classiche
+ *
+ * Le cuffie A2DP non partecipano alla mesh e aggiungono una latenza
+ * propria, nota ma non misurabile, tra l'invio dei campioni e il suono.
+ * Il ponte anticipa l'emissione di ogni frame di quella latenza e la
+ * somma a quella riportata al Master, così che il pianificatore dimensioni
+ * il buffer della zona (o suggerisca un gruppo ritardato) includendo le
+ * cuffie. La sincronizzazione resta al meglio delle possibilità: la
+ * latenza reale del collegamento può variare tra dispositivi e connessioni.
+ */
+class A2dpBridge {
+public:
+    /**
+     * @brief Crea un ponte
+     * @param device Indirizzo del dispositivo A2DP associato
+     * @param latencyMs Latenza nota del collegamento A2DP (ms)
+     * @param targetBufferMs Buffer di riproduzione obiettivo (ms)
+     */
+    A2dpBridge(const std::string& device, uint32_t latencyMs, uint32_t targetBufferMs);
+
+    /**
+     * @brief Istante a cui emettere un frame perché le cuffie lo riproducano in tempo
+     * @param playoutTimeUs Istante di riproduzione del frame (µs)
+     * @return Istante di emissione verso il dispositivo (µs)
+     */
+    uint64_t emitTimeUs(uint64_t playoutTimeUs) const;
+
+    /**
+     * @brief Registra l'inoltro di un frame al dispositivo
+     * @param playoutTimeUs Istante di riproduzione del frame (µs)
+     * @param nowUs Istante corrente sull'orologio della rete (µs)
+     * @return Istante di emissione verso il dispositivo (µs)
+     */
+    uint64_t forward(uint64_t playoutTimeUs, uint64_t nowUs);
+
+    /**
+     * @brief Latenza da riportare al Master
+     * @param meshLatencyMs Latenza misurata sulla mesh (ms)
+     * @return Latenza della mesh più quella del collegamento A2DP (ms)
+     */
+    uint32_t reportedLatencyMs(uint32_t meshLatencyMs) const;
+
+    /**
+     * @brief Riempimento del buffer, come riportato nei pacchetti Status
+     * @return Percentuale (0-100) del buffer obiettivo coperta dall'ultimo frame
+     */
+    uint8_t bufferLevel() const;
+
+    /**
+     * @brief Contatori del ponte
+     */
+    A2dpBridgeStats getStats() const;
+
+private:
+    /// Contatori, con indirizzo e latenza del dispositivo
+    A2dpBridgeStats stats;
+
+    /// Buffer obiettivo
+    uint32_t targetBufferMs;
+
+    /// Mutex per i contatori
+    mutable std::mutex bridgeMutex;
+};
+
+} // namespace saber
+
+#endif // SABER_A2DP_BRIDGE_H
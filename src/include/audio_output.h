@@ -0,0 +1,261 @@
+#ifndef SABER_AUDIO_OUTPUT_H
+#define SABER_AUDIO_OUTPUT_H
+
+#include <atomic>
+#include <condition_variable>
+#include <cstdint>
+#include <fstream>
+#include <functional>
+#include <map>
+#include <memory>
+#include <mutex>
+#include <optional>
+#include <string>
+#include <thread>
+
+#include "codec.h"
+
+namespace saber {
+
+/// Frame in attesa di riproduzione oltre i quali i nuovi arrivi vengono scartati
+constexpr size_t MAX_RENDER_QUEUE_FRAMES = 200;
+
+/**
+ * @brief Uscita audio di un sink (scheda audio, file, ...)
+ *
+ * Riceve i frame decodificati in ordine di istante di riproduzione, dal
+ * thread di riproduzione, nel momento in cui vanno scritti perché il
+ * suono esca all'istante sincronizzato.
+ */
+class AudioOutput {
+public:
+    virtual ~AudioOutput() = default;
+
+    /**
+     * @brief Scrive un frame sul dispositivo
+     * @param frame Frame da riprodurre
+     */
+    virtual void write(const AudioFrame& frame) = 0;
+
+    /**
+     * @brief Ottiene il ritardo tra la scrittura di un frame e l'emissione del suono
+     * @return Ritardo in microsecondi (0 per le uscite senza buffer proprio)
+     */
+    virtual uint64_t getLatencyUs() const;
+
+    /**
+     * @brief Completa le scritture in sospeso, ad esempio l'intestazione di un file
+     */
+    virtual void flush();
+};
+
+/**
+ * @brief Uscita che scarta i frame contandoli, per i nodi senza dispositivo e i test
+ */
+class NullAudioOutput : public AudioOutput {
+public:
+    void write(const AudioFrame& frame) override;
+
+    /**
+     * @brief Ottiene i frame ricevuti
+     */
+    uint64_t getFramesWritten() const;
+
+    /**
+     * @brief Ottiene l'istante di riproduzione dell'ultimo frame ricevuto (µs)
+     */
+    std::optional<uint64_t> getLastPlayoutTimeUs() const;
+
+private:
+    /// Frame ricevuti
+    std::atomic<uint64_t> framesWritten{0};
+
+    /// Istante dell'ultimo frame
+    std::optional<uint64_t> lastPlayoutTimeUs;
+
+    /// Mutex per l'istante dell'ultimo frame
+    mutable std::mutex outputMutex;
+};
+
+/**
+ * @brief Uscita che registra i frame in un file WAV PCM a 16 bit
+ *
+ * Il formato del file è quello del primo frame; i frame in un formato
+ * diverso vengono scartati. I vuoti tra l'istante di un frame e la fine
+ * del precedente diventano silenzio, così il file segue la linea
+ * temporale della riproduzione. L'intestazione viene aggiornata da
+ * flush() e alla distruzione.
+ */
+class WavFileOutput : public AudioOutput {
+public:
+    /**
+     * @brief Crea il file
+     * @param path Percorso del file, sovrascritto se esiste
+     * @throws std::runtime_error se il file non può essere creato
+     */
+    explicit WavFileOutput(const std::string& path);
+
+    /**
+     * @brief Distruttore, completa l'intestazione
+     */
+    ~WavFileOutput() override;
+
+    void write(const AudioFrame& frame) override;
+    void flush() override;
+
+    /**
+     * @brief Ottiene i campioni per canale scritti, silenzio compreso
+     */
+    uint64_t getSamplesWritten() const;
+
+    /**
+     * @brief Ottiene i frame scartati perché in un formato diverso dal primo
+     */
+    uint64_t getFramesRejected() const;
+
+private:
+    /**
+     * @brief Scrive l'intestazione con la lunghezza corrente (con outputMutex acquisito)
+     */
+    void writeHeaderLocked();
+
+    /// File di destinazione
+    std::ofstream file;
+
+    /// Frequenza di campionamento del file (0 prima del primo frame)
+    uint32_t sampleRate = 0;
+
+    /// Numero di canali del file
+    uint8_t channels = 0;
+
+    /// Campioni per canale scritti
+    uint64_t samplesWritten = 0;
+
+    /// Istante in cui termina l'ultimo frame scritto (µs)
+    std::optional<uint64_t> nextPlayoutTimeUs;
+
+    /// Frame scartati
+    uint64_t framesRejected = 0;
+
+    /// Mutex per il file
+    mutable std::mutex outputMutex;
+};
+
+/**
+ * @brief Contatori della riproduzione su un'uscita
+ */
+struct AudioOutputStats {
+    /// Frame scritti sull'uscita
+    uint64_t framesPlayed = 0;
+
+    /// Frame arrivati o estratti dopo il proprio istante di riproduzione e scartati
+    uint64_t framesLate = 0;
+
+    /// Frame scartati perché la coda era piena
+    uint64_t framesDropped = 0;
+
+    /// Frame in attesa
+    size_t queued = 0;
+};
+
+/**
+ * @brief Riproduce i frame decodificati all'istante sincronizzato
+ *
+ * I frame consegnati dal sink attendono in coda, ordinati per istante di
+ * riproduzione, e un thread dedicato li scrive sull'uscita quando
+ * l'orologio della rete raggiunge quell'istante meno la latenza
+ * dell'uscita. Un frame che arriva dopo il proprio istante viene scartato
+ * invece di ritardare i successivi.
+ */
+class AudioRenderer {
+public:
+    /**
+     * @brief Tipo di funzione che fornisce l'orologio della rete (µs)
+     */
+    using Clock = std::function<uint64_t()>;
+
+    /**
+     * @brief Crea la riproduzione, senza avviarla
+     * @param output Uscita su cui scrivere
+     * @param clock Orologio della rete
+     * @param capacity Frame in attesa oltre i quali i nuovi arrivi vengono scartati
+     */
+    AudioRenderer(std::shared_ptr<AudioOutput> output, Clock clock, size_t capacity = MAX_RENDER_QUEUE_FRAMES);
+
+    /**
+     * @brief Distruttore, ferma la riproduzione se in esecuzione
+     */
+    ~AudioRenderer();
+
+    AudioRenderer(const AudioRenderer&) = delete;
+    AudioRenderer& operator=(const AudioRenderer&) = delete;
+
+    /**
+     * @brief Avvia il thread di riproduzione
+     */
+    void start();
+
+    /**
+     * @brief Ferma il thread di riproduzione e svuota l'uscita con flush()
+     */
+    void stop();
+
+    /**
+     * @brief Accoda un frame da riprodurre
+     * @param frame Frame con il proprio istante di riproduzione
+     * @return true se il frame è stato accodato, false se in ritardo o con la coda piena
+     */
+    bool submit(const AudioFrame& frame);
+
+    /**
+     * @brief Ottiene l'uscita
+     */
+    std::shared_ptr<AudioOutput> getOutput() const;
+
+    /**
+     * @brief Ottiene i contatori della riproduzione
+     */
+    AudioOutputStats getStats() const;
+
+private:
+    /**
+     * @brief Ciclo del thread di riproduzione
+     */
+    void run();
+
+    /**
+     * @brief Verifica se un frame è ormai in ritardo (con queueMutex acquisito)
+     */
+    bool isLateLocked(const AudioFrame& frame, uint64_t nowUs) const;
+
+    /// Uscita
+    std::shared_ptr<AudioOutput> output;
+
+    /// Orologio della rete
+    Clock clock;
+
+    /// Capacità della coda
+    size_t capacity;
+
+    /// Frame in attesa per istante di riproduzione
+    std::multimap<uint64_t, AudioFrame> queue;
+
+    /// Contatori
+    AudioOutputStats stats;
+
+    /// Flag per il thread di riproduzione
+    bool running = false;
+
+    /// Thread di riproduzione
+    std::thread worker;
+
+    /// Mutex per la coda, i contatori e il flag
+    mutable std::mutex queueMutex;
+
+    /// Risveglia il thread ad ogni nuovo frame e all'arresto
+    std::condition_variable queueChanged;
+};
+
+} // namespace saber
+
+#endif // SABER_AUDIO_OUTPUT_H
@@ -0,0 +1,218 @@
+#ifndef SABER_AUDIO_PACKET_H
+#define SABER_AUDIO_PACKET_H
+
+#include <cstdint>
+#include <functional>
+#include <map>
+#include <mutex>
+#include <optional>
+#include <string>
+#include <vector>
+
+#include "spec.h"
+
+namespace saber {
+
+class MeshCrypto;
+
+/// Versione del formato AudioPacket
+constexpr uint8_t AUDIO_PACKET_VERSION = 1;
+
+/// Lunghezza dell'intestazione in chiaro di un AudioPacket (byte)
+constexpr size_t AUDIO_PACKET_HEADER_BYTES = 1 + 2 + 4 + 8;
+
+/// Frame trattenuti per stream in attesa di quelli mancanti prima di dichiararli persi
+constexpr size_t DEFAULT_REORDER_DEPTH = 4;
+
+/**
+ * @brief Frame audio cifrato da un capo all'altro della rete
+ *
+ * Formato (big-endian): versione (u8), stream (u16), numero di sequenza
+ * (u32), istante di presentazione (u64, µs), poi il frame LC3 cifrato con
+ * MeshCrypto fino alla fine del pacchetto. L'intestazione resta in chiaro
+ * perché i nodi intermedi possano inoltrare e riordinare senza la chiave,
+ * ma è autenticata come AAD: un numero di sequenza o un istante alterati
+ * fanno fallire la decifratura.
+ */
+struct AudioPacket {
+    /// Stream a cui appartiene il frame
+    StreamId streamId = 0;
+
+    /// Numero di sequenza del frame nello stream
+    uint32_t sequence = 0;
+
+    /// Istante di presentazione sull'orologio della rete (µs)
+    uint64_t presentationTimeUs = 0;
+
+    /// Frame LC3 in chiaro
+    std::vector<uint8_t> payload;
+
+    /**
+     * @brief Cifra il frame e serializza il pacchetto
+     * @param crypto Gestore crittografico con la chiave di rete
+     * @return Pacchetto da trasmettere
+     * @throws CryptoError in caso di errore di cifratura
+     */
+    std::vector<uint8_t> seal(MeshCrypto& crypto) const;
+
+    /**
+     * @brief Interpreta e decifra un pacchetto ricevuto
+     * @param data Pacchetto ricevuto
+     * @param crypto Gestore crittografico con la chiave di rete
+     * @param senderId Mittente del pacchetto; se indicato le ripetizioni vengono rifiutate
+     * @return Frame in chiaro
+     * @throws CryptoError se la decifratura fallisce o il pacchetto è ripetuto
+     * @throws std::out_of_range se il pacchetto è troncato
+     * @throws std::invalid_argument se la versione non è supportata
+     */
+    static AudioPacket open(const std::vector<uint8_t>& data, MeshCrypto& crypto, const std::string& senderId = "");
+
+    /**
+     * @brief Legge l'intestazione senza decifrare, ad esempio su un nodo che inoltra
+     * @param data Pacchetto ricevuto
+     * @return Pacchetto con stream, sequenza e istante, senza payload
+     * @throws std::out_of_range se il pacchetto è troncato
+     * @throws std::invalid_argument se la versione non è supportata
+     */
+    static AudioPacket peekHeader(const std::vector<uint8_t>& data);
+};
+
+/**
+ * @brief Contatori del riordino dei frame di un sink
+ */
+struct AudioReorderStats {
+    /// Frame consegnati in ordine
+    uint64_t delivered = 0;
+
+    /// Frame arrivati fuori ordine e rimessi in sequenza
+    uint64_t reordered = 0;
+
+    /// Frame mai arrivati in tempo
+    uint64_t lost = 0;
+
+    /// Frame persi segnalati al gestore della perdita
+    uint64_t concealed = 0;
+
+    /// Frame arrivati dopo che il loro posto era già stato superato
+    uint64_t late = 0;
+
+    /// Frame ricevuti più volte
+    uint64_t duplicates = 0;
+};
+
+/**
+ * @brief Rimette in ordine i frame ricevuti da un sink
+ *
+ * I frame di ogni stream vengono consegnati in ordine di sequenza. Un
+ * frame che arriva dopo un vuoto resta in attesa finché il vuoto non si
+ * colma o i frame trattenuti superano la profondità: a quel punto i frame
+ * mancanti sono dichiarati persi e segnalati, uno per uno e al loro posto
+ * nella sequenza, al gestore della perdita, che può ricostruirli ad
+ * esempio con Lc3Decoder::conceal(). Oltre un limite di frame consecutivi
+ * la sequenza riparte senza ricostruzione, come dopo un riavvio del Master.
+ * I gestori sono invocati fuori dai lock, dal thread che consegna i frame.
+ */
+class AudioPacketReorderer {
+public:
+    /**
+     * @brief Tipo di callback per i frame consegnati in ordine
+     */
+    using PacketHandler = std::function<void(const AudioPacket&)>;
+
+    /**
+     * @brief Tipo di callback per i frame persi, con il posto che avrebbero occupato
+     */
+    using LossHandler = std::function<void(StreamId streamId, uint32_t sequence, uint64_t presentationTimeUs)>;
+
+    /**
+     * @brief Crea il riordino
+     * @param onPacket Gestore dei frame consegnati
+     * @param onLoss Gestore dei frame persi (opzionale)
+     * @param depth Frame trattenuti per stream prima di dichiarare persi quelli mancanti
+     * @throws std::invalid_argument se la profondità è zero
+     */
+    AudioPacketReorderer(PacketHandler onPacket, LossHandler onLoss = nullptr,
+                         size_t depth = DEFAULT_REORDER_DEPTH);
+
+    /**
+     * @brief Consegna un frame ricevuto
+     * @param packet Frame decifrato
+     * @return true se il frame è stato accettato, false se duplicato o in ritardo
+     */
+    bool push(const AudioPacket& packet);
+
+    /**
+     * @brief Consegna tutti i frame trattenuti, segnalando i vuoti tra di essi
+     */
+    void flush();
+
+    /**
+     * @brief Dimentica uno stream, ad esempio quando viene fermato
+     * @param streamId Stream da dimenticare
+     */
+    void reset(StreamId streamId);
+
+    /**
+     * @brief Ottiene i contatori del riordino
+     */
+    AudioReorderStats getStats() const;
+
+private:
+    /**
+     * @brief Frame o vuoto da segnalare ai gestori, in ordine di sequenza
+     */
+    struct Release {
+        /// Frame consegnato, assente per un frame perso
+        std::optional<AudioPacket> packet;
+        StreamId streamId;
+        uint32_t sequence;
+        uint64_t presentationTimeUs;
+    };
+
+    /**
+     * @brief Stato di uno stream
+     */
+    struct StreamState {
+        /// Prossimo numero di sequenza atteso
+        uint32_t next = 0;
+
+        /// Frame trattenuti per numero di sequenza
+        std::map<uint32_t, AudioPacket> pending;
+    };
+
+    /**
+     * @brief Consegna i frame pronti di uno stream (con reorderMutex acquisito)
+     * @param streamId Stream da esaminare
+     * @param state Stato dello stream
+     * @param drain Consegna anche i frame dopo un vuoto, senza attendere la profondità
+     * @param out Frame e vuoti da segnalare
+     */
+    void releaseLocked(StreamId streamId, StreamState& state, bool drain, std::vector<Release>& out);
+
+    /**
+     * @brief Segnala i gestori fuori dal lock
+     */
+    void dispatch(const std::vector<Release>& releases) const;
+
+    /// Gestore dei frame consegnati
+    PacketHandler onPacket;
+
+    /// Gestore dei frame persi
+    LossHandler onLoss;
+
+    /// Profondità del riordino
+    size_t depth;
+
+    /// Stato per stream
+    std::map<StreamId, StreamState> streams;
+
+    /// Contatori
+    AudioReorderStats stats;
+
+    /// Mutex per gli stream e i contatori
+    mutable std::mutex reorderMutex;
+};
+
+} // namespace saber
+
+#endif // SABER_AUDIO_PACKET_H
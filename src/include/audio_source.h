@@ -0,0 +1,312 @@
+#ifndef SABER_AUDIO_SOURCE_H
+#define SABER_AUDIO_SOURCE_H
+
+#include <atomic>
+#include <cstdint>
+#include <fstream>
+#include <functional>
+#include <memory>
+#include <mutex>
+#include <optional>
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "codec.h"
+
+namespace saber {
+
+/// Durata predefinita del buffer di una sorgente alimentata dall'applicazione (ms)
+constexpr uint32_t DEFAULT_SOURCE_BUFFER_MS = 500;
+
+/**
+ * @brief Sorgente dei frame PCM trasmessi dal Master
+ *
+ * Il Master legge un frame di 10ms alla volta, al ritmo della
+ * riproduzione, e lo codifica in LC3 sullo stream a cui la sorgente è
+ * collegata. Le letture arrivano da un solo thread.
+ */
+class AudioSource {
+public:
+    virtual ~AudioSource() = default;
+
+    /**
+     * @brief Ottiene la frequenza di campionamento dei frame (Hz)
+     */
+    virtual uint32_t getSampleRate() const = 0;
+
+    /**
+     * @brief Ottiene il numero di canali dei frame
+     */
+    virtual uint8_t getChannels() const = 0;
+
+    /**
+     * @brief Legge il prossimo frame di 10ms
+     * @return Frame senza istante di riproduzione, o std::nullopt se i campioni non sono ancora disponibili
+     */
+    virtual std::optional<AudioFrame> nextFrame() = 0;
+
+    /**
+     * @brief Verifica se la sorgente non produrrà altri frame
+     */
+    virtual bool isFinished() const;
+};
+
+/**
+ * @brief Sorgente che legge un file PCM a 16 bit, grezzo o WAV
+ *
+ * Il file viene letto un frame alla volta; l'ultimo frame incompleto
+ * viene completato con il silenzio. In ripetizione la lettura riparte
+ * dall'inizio dei campioni senza mai finire.
+ */
+class PcmFileSource : public AudioSource {
+public:
+    /**
+     * @brief Apre un file PCM grezzo (16 bit little-endian, canali interleaved)
+     * @param path Percorso del file
+     * @param sampleRate Frequenza di campionamento (Hz, multipla di 100)
+     * @param channels Numero di canali
+     * @param loop Ripete il file indefinitamente
+     * @throws std::invalid_argument se il formato non è valido
+     * @throws std::runtime_error se il file non può essere aperto
+     */
+    PcmFileSource(const std::string& path, uint32_t sampleRate, uint8_t channels, bool loop = false);
+
+    /**
+     * @brief Apre un file WAV PCM a 16 bit
+     * @param path Percorso del file
+     * @param loop Ripete il file indefinitamente
+     * @return Sorgente con il formato dichiarato dal file
+     * @throws std::runtime_error se il file non può essere aperto o non è un WAV PCM a 16 bit
+     */
+    static std::shared_ptr<PcmFileSource> fromWav(const std::string& path, bool loop = false);
+
+    uint32_t getSampleRate() const override;
+    uint8_t getChannels() const override;
+    std::optional<AudioFrame> nextFrame() override;
+    bool isFinished() const override;
+
+private:
+    /**
+     * @brief Apre un file di cui è già noto l'intervallo dei campioni
+     */
+    PcmFileSource(const std::string& path, uint32_t sampleRate, uint8_t channels, bool loop,
+                  uint64_t dataOffset, std::optional<uint64_t> dataSize);
+
+    /// File aperto
+    std::ifstream file;
+
+    /// Frequenza di campionamento (Hz)
+    uint32_t sampleRate;
+
+    /// Numero di canali
+    uint8_t channels;
+
+    /// Ripetizione del file
+    bool loop;
+
+    /// Inizio dei campioni nel file (byte)
+    uint64_t dataOffset;
+
+    /// Lunghezza dei campioni (byte)
+    uint64_t dataSize;
+
+    /// Posizione di lettura dall'inizio dei campioni (byte)
+    uint64_t position = 0;
+};
+
+/**
+ * @brief Sorgente alimentata dall'applicazione, ad esempio da Python
+ *
+ * I campioni interleaved spinti con push() restano in un buffer circolare
+ * di capacità fissa fino a formare un frame intero; quelli che non
+ * trovano posto vengono scartati. Dopo close() la sorgente finisce quando
+ * il buffer si svuota.
+ */
+class RingBufferSource : public AudioSource {
+public:
+    /**
+     * @brief Crea una sorgente vuota
+     * @param sampleRate Frequenza di campionamento (Hz, multipla di 100)
+     * @param channels Numero di canali
+     * @param capacityMs Durata dei campioni che il buffer può contenere (ms)
+     * @throws std::invalid_argument se il formato o la capacità non sono validi
+     */
+    RingBufferSource(uint32_t sampleRate, uint8_t channels, uint32_t capacityMs = DEFAULT_SOURCE_BUFFER_MS);
+
+    /**
+     * @brief Aggiunge campioni in coda
+     * @param samples Campioni interleaved (un multiplo del numero di canali)
+     * @return Campioni accettati; gli altri sono stati scartati per mancanza di spazio
+     * @throws std::invalid_argument se i campioni non coprono un numero intero di istanti
+     * @throws std::logic_error se la sorgente è stata chiusa
+     */
+    size_t push(const std::vector<int16_t>& samples);
+
+    /**
+     * @brief Ottiene i campioni nel buffer
+     */
+    size_t available() const;
+
+    /**
+     * @brief Ottiene i campioni scartati perché il buffer era pieno
+     */
+    uint64_t getDropped() const;
+
+    /**
+     * @brief Svuota il buffer
+     */
+    void clear();
+
+    /**
+     * @brief Segnala che non arriveranno altri campioni
+     */
+    void close();
+
+    uint32_t getSampleRate() const override;
+    uint8_t getChannels() const override;
+    std::optional<AudioFrame> nextFrame() override;
+    bool isFinished() const override;
+
+private:
+    /// Frequenza di campionamento (Hz)
+    uint32_t sampleRate;
+
+    /// Numero di canali
+    uint8_t channels;
+
+    /// Buffer circolare
+    std::vector<int16_t> buffer;
+
+    /// Indice del campione più vecchio
+    size_t head = 0;
+
+    /// Campioni nel buffer
+    size_t count = 0;
+
+    /// Campioni scartati
+    uint64_t dropped = 0;
+
+    /// Sorgente chiusa dall'applicazione
+    bool closed = false;
+
+    /// Mutex per il buffer
+    mutable std::mutex bufferMutex;
+};
+
+/**
+ * @brief Contatori della trasmissione di una sorgente
+ */
+struct AudioSourceStats {
+    /// Frame letti e consegnati al codificatore
+    uint64_t framesSent = 0;
+
+    /// Intervalli di 10ms in cui la sorgente non aveva un frame pronto
+    uint64_t underruns = 0;
+
+    /// La sorgente è finita e la trasmissione si è fermata
+    bool finished = false;
+};
+
+/**
+ * @brief Legge una sorgente al ritmo della riproduzione
+ *
+ * Un thread dedicato legge un frame ogni 10ms e gli assegna l'istante di
+ * riproduzione: il primo cade lead dopo l'avvio, i successivi a passi di
+ * 10ms. Un frame mancante lascia un vuoto nella riproduzione anziché
+ * spostare i successivi, così una sorgente in ritardo non accumula
+ * latenza.
+ */
+class AudioSourcePump {
+public:
+    /**
+     * @brief Tipo di callback per i frame letti
+     */
+    using FrameHandler = std::function<void(const AudioFrame&)>;
+
+    /**
+     * @brief Tipo di funzione che fornisce l'orologio della rete (µs)
+     */
+    using Clock = std::function<uint64_t()>;
+
+    /**
+     * @brief Crea la trasmissione, senza avviarla
+     * @param source Sorgente da leggere
+     * @param clock Orologio della rete
+     * @param leadUs Anticipo dell'istante di riproduzione rispetto alla lettura (µs)
+     * @param handler Funzione invocata dal thread di lettura per ogni frame
+     */
+    AudioSourcePump(std::shared_ptr<AudioSource> source, Clock clock, uint64_t leadUs, FrameHandler handler);
+
+    /**
+     * @brief Distruttore, ferma la trasmissione se in esecuzione
+     */
+    ~AudioSourcePump();
+
+    AudioSourcePump(const AudioSourcePump&) = delete;
+    AudioSourcePump& operator=(const AudioSourcePump&) = delete;
+
+    /**
+     * @brief Avvia il thread di lettura
+     */
+    void start();
+
+    /**
+     * @brief Ferma il thread di lettura; non va chiamato dal gestore dei frame
+     */
+    void stop();
+
+    /**
+     * @brief Ottiene la sorgente letta
+     */
+    std::shared_ptr<AudioSource> getSource() const;
+
+    /**
+     * @brief Cambia l'anticipo dell'istante di riproduzione
+     *
+     * Il frame successivo si sposta della differenza: un anticipo maggiore
+     * lascia un vuoto nella riproduzione, uno minore la sovrappone.
+     *
+     * @param leadUs Anticipo rispetto alla lettura (µs)
+     */
+    void setLeadUs(uint64_t leadUs);
+
+    /**
+     * @brief Ottiene i contatori della trasmissione
+     */
+    AudioSourceStats getStats() const;
+
+private:
+    /**
+     * @brief Ciclo del thread di lettura
+     */
+    void run();
+
+    /// Sorgente letta
+    std::shared_ptr<AudioSource> source;
+
+    /// Orologio della rete
+    Clock clock;
+
+    /// Anticipo dell'istante di riproduzione (µs)
+    std::atomic<uint64_t> leadUs;
+
+    /// Gestore dei frame
+    FrameHandler handler;
+
+    /// Flag per il thread di lettura
+    std::atomic<bool> running{false};
+
+    /// Thread di lettura
+    std::thread worker;
+
+    /// Contatori
+    AudioSourceStats stats;
+
+    /// Mutex per i contatori
+    mutable std::mutex statsMutex;
+};
+
+} // namespace saber
+
+#endif // SABER_AUDIO_SOURCE_H
@@ -0,0 +1,302 @@
+#ifndef SABER_AUTHORIZATION_H
+#define SABER_AUTHORIZATION_H
+
+#include "mesh.h"
+
+#include <condition_variable>
+#include <cstdint>
+#include <deque>
+#include <functional>
+#include <map>
+#include <memory>
+#include <mutex>
+#include <optional>
+#include <string>
+#include <thread>
+
+namespace saber {
+
+/// Attesa di default della decisione di una politica esterna (ms)
+constexpr uint32_t DEFAULT_AUTHORIZATION_TIMEOUT_MS = 5000;
+
+/**
+ * @brief Operazione sottoposta all'autorizzazione esterna
+ */
+enum class AuthorizationAction : uint8_t {
+    /// Richiesta di ingresso nella rete di un nuovo nodo
+    Join,
+    /// Comando privilegiato (ambito Admin) sul socket di controllo
+    Command
+};
+
+/**
+ * @brief Converte un'operazione nella sua rappresentazione testuale
+ * @param action Operazione
+ * @return Nome dell'operazione ("join", "command")
+ */
+std::string authorizationActionToString(AuthorizationAction action);
+
+/**
+ * @brief Interpreta il nome di un'operazione
+ * @param name Nome dell'operazione
+ * @return Operazione, o std::nullopt se il nome non è valido
+ */
+std::optional<AuthorizationAction> authorizationActionFromString(const std::string& name);
+
+/**
+ * @brief Richiesta sottoposta alla politica esterna
+ */
+struct AuthorizationRequest {
+    /// Identificatore assegnato dal gate
+    uint64_t id = 0;
+
+    /// Operazione da autorizzare
+    AuthorizationAction action = AuthorizationAction::Join;
+
+    /// Nodo che chiede l'ingresso (Join) o soggetto del token presentato (Command)
+    std::string nodeId;
+
+    /// Ruolo dichiarato dal nodo (solo Join)
+    NodeRole role = NodeRole::Sink;
+
+    /// Riga di comando (solo Command)
+    std::string command;
+
+    /// Identificatore del token presentato (solo Command, vuoto senza autenticazione)
+    std::string tokenId;
+};
+
+/**
+ * @brief Decisione della politica esterna
+ */
+struct AuthorizationDecision {
+    /// Operazione consentita
+    bool allowed = false;
+
+    /// Motivo della decisione, riportato nei rifiuti e nel registro
+    std::string reason;
+};
+
+/**
+ * @brief Contatori delle richieste di autorizzazione
+ */
+struct AuthorizationStats {
+    /// Richieste sottoposte alla politica
+    uint64_t requests = 0;
+
+    /// Richieste consentite
+    uint64_t allowed = 0;
+
+    /// Richieste negate
+    uint64_t denied = 0;
+
+    /// Richieste decise per scadenza dell'attesa
+    uint64_t timedOut = 0;
+
+    /// Richieste in attesa di decisione
+    uint64_t pending = 0;
+};
+
+/**
+ * @brief Politica di autorizzazione fornita dall'integratore
+ *
+ * Consultata sugli ingressi nella rete e sui comandi privilegiati, così
+ * da poter delegare la decisione ad una sorgente esterna (directory LDAP,
+ * API cloud, interfaccia di approvazione locale) senza toccare il
+ * protocollo. La decisione è asincrona: authorize() deve tornare subito
+ * e rispondere, anche più tardi e da un altro thread, con la funzione
+ * ricevuta. Una risposta oltre il tempo massimo viene ignorata.
+ */
+class AuthorizationHook {
+public:
+    /**
+     * @brief Tipo di funzione con cui rispondere ad una richiesta
+     *
+     * Solo la prima risposta conta; può essere chiamata da qualsiasi thread.
+     */
+    using Responder = std::function<void(const AuthorizationDecision&)>;
+
+    virtual ~AuthorizationHook() = default;
+
+    /**
+     * @brief Sottopone una richiesta alla politica
+     * @param request Richiesta da valutare
+     * @param respond Funzione con cui comunicare la decisione
+     */
+    virtual void authorize(const AuthorizationRequest& request, Responder respond) = 0;
+};
+
+/**
+ * @brief Politica definita da una funzione (usata anche dai binding Python)
+ */
+class FunctionAuthorizationHook : public AuthorizationHook {
+public:
+    /**
+     * @brief Tipo di funzione che valuta le richieste
+     */
+    using Function = std::function<void(const AuthorizationRequest&, Responder)>;
+
+    /**
+     * @brief Crea la politica
+     * @param function Funzione chiamata per ogni richiesta
+     */
+    explicit FunctionAuthorizationHook(Function function);
+
+    void authorize(const AuthorizationRequest& request, Responder respond) override;
+
+private:
+    /// Funzione che valuta le richieste
+    Function function;
+};
+
+/**
+ * @brief Punto di consultazione della politica di autorizzazione
+ *
+ * Le richieste vengono passate alla politica da un thread dedicato, mai
+ * dai thread della rete: una politica lenta o che acquisisce il GIL non
+ * blocca la mesh. Ogni richiesta riceve esattamente una decisione: quella
+ * della politica, quella di default allo scadere dell'attesa, oppure un
+ * rifiuto se il gate viene annullato. Senza politica non c'è nulla da
+ * consultare e le operazioni seguono le regole del protocollo.
+ */
+class AuthorizationGate {
+public:
+    /**
+     * @brief Tipo di funzione che riceve la decisione di una richiesta
+     */
+    using Completion = std::function<void(const AuthorizationDecision&)>;
+
+    /**
+     * @brief Crea il gate e avvia il thread di consultazione
+     * @param timeoutMs Attesa massima della decisione
+     * @param allowOnTimeout Decisione allo scadere dell'attesa (false = nega)
+     */
+    explicit AuthorizationGate(uint32_t timeoutMs = DEFAULT_AUTHORIZATION_TIMEOUT_MS, bool allowOnTimeout = false);
+
+    /**
+     * @brief Nega le richieste in attesa e ferma il thread di consultazione
+     */
+    ~AuthorizationGate();
+
+    AuthorizationGate(const AuthorizationGate&) = delete;
+    AuthorizationGate& operator=(const AuthorizationGate&) = delete;
+
+    /**
+     * @brief Imposta la politica da consultare
+     * @param hook Politica (nullptr per non consultarne alcuna)
+     */
+    void setHook(std::shared_ptr<AuthorizationHook> hook);
+
+    /**
+     * @brief Verifica se è impostata una politica
+     */
+    bool hasHook() const;
+
+    /**
+     * @brief Imposta l'attesa massima e la decisione allo scadere
+     * @param timeoutMs Attesa massima della decisione
+     * @param allowOnTimeout Decisione allo scadere dell'attesa
+     */
+    void setTimeout(uint32_t timeoutMs, bool allowOnTimeout);
+
+    /**
+     * @brief Sottopone una richiesta alla politica senza attenderne la decisione
+     *
+     * Non blocca e non chiama mai la funzione di completamento prima di
+     * tornare: può essere usato con i mutex della rete occupati.
+     *
+     * @param request Richiesta (l'identificatore viene assegnato dal gate)
+     * @param completion Funzione che riceve la decisione
+     * @return false se non c'è una politica da consultare (completion non viene chiamata)
+     */
+    bool submit(AuthorizationRequest request, Completion completion);
+
+    /**
+     * @brief Sottopone una richiesta alla politica e ne attende la decisione
+     * @param request Richiesta da valutare
+     * @return Decisione, o std::nullopt se non c'è una politica da consultare
+     */
+    std::optional<AuthorizationDecision> decide(AuthorizationRequest request);
+
+    /**
+     * @brief Nega tutte le richieste in attesa
+     * @param reason Motivo del rifiuto
+     */
+    void cancelPending(const std::string& reason);
+
+    /**
+     * @brief Ottiene i contatori delle richieste
+     */
+    AuthorizationStats getStats() const;
+
+private:
+    /**
+     * @brief Collegamento tra le risposte della politica e il gate
+     *
+     * Le risposte possono arrivare dopo la distruzione del gate: il
+     * collegamento viene interrotto dal distruttore.
+     */
+    struct Link {
+        std::mutex mutex;
+        AuthorizationGate* gate = nullptr;
+    };
+
+    /**
+     * @brief Richiesta in attesa di decisione
+     */
+    struct Pending {
+        AuthorizationRequest request;
+        Completion completion;
+        int64_t deadlineMs = 0;
+    };
+
+    /**
+     * @brief Ciclo del thread di consultazione
+     */
+    void run();
+
+    /**
+     * @brief Consegna la decisione di una richiesta, se ancora in attesa
+     * @param id Identificatore della richiesta
+     * @param decision Decisione
+     * @param timedOut La decisione è quella di default per scadenza
+     */
+    void resolve(uint64_t id, const AuthorizationDecision& decision, bool timedOut);
+
+    /// Politica consultata
+    std::shared_ptr<AuthorizationHook> hook;
+
+    /// Attesa massima della decisione (ms)
+    uint32_t timeoutMs;
+
+    /// Decisione allo scadere dell'attesa
+    bool allowOnTimeout;
+
+    /// Richieste in attesa di decisione, per identificatore
+    std::map<uint64_t, Pending> pending;
+
+    /// Richieste ancora da passare alla politica
+    std::deque<uint64_t> queue;
+
+    /// Prossimo identificatore di richiesta
+    uint64_t nextId = 1;
+
+    /// Contatori
+    AuthorizationStats stats;
+
+    /// Il thread di consultazione deve terminare
+    bool stopping = false;
+
+    /// Collegamento usato dalle risposte della politica
+    std::shared_ptr<Link> link;
+
+    /// Thread di consultazione
+    std::thread worker;
+
+    mutable std::mutex mutex;
+    std::condition_variable wakeup;
+};
+
+} // namespace saber
+
+#endif // SABER_AUTHORIZATION_H
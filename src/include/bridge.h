@@ -0,0 +1,178 @@
+#ifndef SABER_BRIDGE_H
+#define SABER_BRIDGE_H
+
+#include <condition_variable>
+#include <cstdint>
+#include <deque>
+#include <map>
+#include <memory>
+#include <mutex>
+#include <string>
+#include <thread>
+
+#include "mesh.h"
+
+namespace saber {
+
+class SaberProtocol;
+
+/// Pacchetti in attesa di essere riproposti oltre i quali i nuovi arrivi vengono scartati
+constexpr size_t BRIDGE_QUEUE_CAPACITY = 256;
+
+/**
+ * @brief Configurazione di un ponte tra due reti SABER
+ */
+struct BridgeConfig {
+    /// ID del ponte, scritto nei pacchetti che lo attraversano
+    std::string bridgeId;
+
+    /// Stream riproposti dalla prima alla seconda rete, con l'ID che assumono a destinazione
+    std::map<StreamId, StreamId> forward;
+
+    /// Stream riproposti dalla seconda alla prima rete, con l'ID che assumono a destinazione
+    std::map<StreamId, StreamId> reverse;
+};
+
+/**
+ * @brief Contatori di un ponte
+ */
+struct BridgeStats {
+    /// Frame audio riproposti dalla prima alla seconda rete
+    uint64_t framesForwarded = 0;
+
+    /// Frame audio riproposti dalla seconda alla prima rete
+    uint64_t framesReversed = 0;
+
+    /// Metadati riproposti, in entrambe le direzioni
+    uint64_t metadataBridged = 0;
+
+    /// Pacchetti già passati da questo ponte, o da troppi ponti, e fermati
+    uint64_t loopsPrevented = 0;
+
+    /// Pacchetti scartati perché la coda era piena o la rete di destinazione ferma
+    uint64_t dropped = 0;
+};
+
+/**
+ * @brief Ponte tra due reti SABER, ad esempio due piani di un edificio
+ *
+ * Il nodo ponte partecipa ad entrambe le reti con due istanze del
+ * protocollo, ciascuna con la propria chiave di rete. I frame audio e i
+ * metadati degli stream selezionati, ricevuti e decifrati su una rete,
+ * vengono riproposti sull'altra dal nodo ponte, che li firma e li cifra
+ * con la chiave di destinazione. L'istante di riproduzione dei frame
+ * viene portato sull'orologio della rete di destinazione.
+ *
+ * Ogni pacchetto riproposto porta l'ID del ponte nell'intestazione: un
+ * pacchetto che torna ad un ponte già attraversato, ad esempio attraverso
+ * un secondo ponte tra le stesse reti, viene fermato.
+ *
+ * I pacchetti arrivano dal thread di rete di ciascuna istanza e vengono
+ * riproposti da un thread dedicato, così le due reti non si attendono a
+ * vicenda.
+ */
+class NetworkBridge {
+public:
+    /**
+     * @brief Crea il ponte, senza avviarlo
+     * @param first Protocollo della prima rete
+     * @param second Protocollo della seconda rete
+     * @param config Configurazione del ponte
+     * @throws std::invalid_argument se manca un protocollo, sono la stessa istanza o l'ID è vuoto
+     */
+    NetworkBridge(std::shared_ptr<SaberProtocol> first, std::shared_ptr<SaberProtocol> second,
+                  const BridgeConfig& config);
+
+    /**
+     * @brief Distruttore, ferma il ponte se in esecuzione
+     */
+    ~NetworkBridge();
+
+    NetworkBridge(const NetworkBridge&) = delete;
+    NetworkBridge& operator=(const NetworkBridge&) = delete;
+
+    /**
+     * @brief Collega il ponte alle due reti e avvia il thread che ripropone i pacchetti
+     */
+    void start();
+
+    /**
+     * @brief Scollega il ponte; i pacchetti ancora in coda vengono scartati
+     */
+    void stop();
+
+    /**
+     * @brief Verifica se il ponte è in esecuzione
+     */
+    bool isRunning() const;
+
+    /**
+     * @brief Ottiene la configurazione del ponte
+     */
+    const BridgeConfig& getConfig() const;
+
+    /**
+     * @brief Ottiene i contatori del ponte
+     */
+    BridgeStats getStats() const;
+
+private:
+    /**
+     * @brief Pacchetto in attesa di essere riproposto
+     */
+    struct PendingPacket {
+        /// true dalla prima alla seconda rete
+        bool forward;
+
+        /// Pacchetto ricevuto
+        MeshPacket packet;
+    };
+
+    /**
+     * @brief Accoda un pacchetto ricevuto se appartiene ad uno stream selezionato
+     * @param forward true se ricevuto sulla prima rete
+     * @param packet Pacchetto ricevuto
+     */
+    void enqueue(bool forward, const MeshPacket& packet);
+
+    /**
+     * @brief Ripropone un pacchetto sulla rete di destinazione
+     */
+    void bridge(const PendingPacket& pending);
+
+    /**
+     * @brief Ciclo del thread che ripropone i pacchetti
+     */
+    void run();
+
+    /// Protocollo della prima rete
+    std::shared_ptr<SaberProtocol> first;
+
+    /// Protocollo della seconda rete
+    std::shared_ptr<SaberProtocol> second;
+
+    /// Configurazione
+    BridgeConfig config;
+
+    /// Pacchetti in attesa
+    std::deque<PendingPacket> queue;
+
+    /// Contatori
+    BridgeStats stats;
+
+    /// Flag per il thread
+    bool running = false;
+
+    /// Thread che ripropone i pacchetti
+    std::thread worker;
+
+    /// Mutex per la coda, i contatori e il flag
+    mutable std::mutex bridgeMutex;
+
+    /// Risveglia il thread ad ogni nuovo pacchetto e all'arresto
+    std::condition_variable queueChanged;
+};
+
+} // namespace saber
+
+#endif // SABER_BRIDGE_H
@@ -0,0 +1,127 @@
+#ifndef SABER_CLOCK_DISCIPLINE_H
+#define SABER_CLOCK_DISCIPLINE_H
+
+#include <cstdint>
+
+namespace saber {
+
+/**
+ * @brief Stato dell'orologio sincronizzato di un nodo
+ */
+struct ClockStats {
+    /// Offset applicato all'orologio locale (µs)
+    int64_t offsetUs = 0;
+
+    /// Offset stimato dal filtro verso cui l'orologio converge (µs)
+    int64_t estimatedOffsetUs = 0;
+
+    /// Deriva stimata dell'orologio locale rispetto al Master (ppm)
+    double skewPpm = 0.0;
+
+    /// Beacon elaborati
+    uint64_t updates = 0;
+
+    /// Riallineamenti di colpo (primo beacon e scarti oltre la soglia)
+    uint64_t steps = 0;
+};
+
+/**
+ * @brief Disciplina dell'orologio locale sui beacon del Master
+ *
+ * Un filtro di Kalman a due stati (offset e deriva in ppm) attenua il
+ * rumore dei beacon e stima la deriva del quarzo locale. L'orologio
+ * esposto non salta sulla stima: la segue alla propria deriva e recupera
+ * lo scarto residuo al massimo a maxSlewPpm, così il tempo sincronizzato
+ * resta continuo e monotono. Solo il primo beacon e gli scarti oltre la
+ * soglia di riallineamento spostano l'orologio di colpo.
+ *
+ * I tempi sono passati dal chiamante, perché la disciplina non legge
+ * alcun orologio: SyncManager usa il tempo di sistema in microsecondi.
+ */
+class ClockDiscipline {
+public:
+    /**
+     * @brief Crea una disciplina non ancora agganciata
+     * @param stepThresholdUs Scarto oltre cui l'orologio viene riallineato di colpo (0 = sempre)
+     * @param maxSlewPpm Velocità massima di recupero dello scarto (ppm)
+     */
+    explicit ClockDiscipline(int64_t stepThresholdUs = 100000, uint32_t maxSlewPpm = 500);
+
+    /**
+     * @brief Elabora la misura di un beacon
+     * @param localUs Tempo locale alla ricezione (µs)
+     * @param measuredOffsetUs Tempo del Master meno tempo locale (µs)
+     * @return true se l'orologio è stato riallineato di colpo
+     */
+    bool update(int64_t localUs, int64_t measuredOffsetUs);
+
+    /**
+     * @brief Riallinea l'orologio di colpo, conservando la deriva stimata
+     * @param localUs Tempo locale (µs)
+     * @param offsetUs Nuovo offset (µs)
+     */
+    void step(int64_t localUs, int64_t offsetUs);
+
+    /**
+     * @brief Offset da sommare al tempo locale
+     * @param localUs Tempo locale (µs)
+     * @return Offset applicato in quell'istante (µs)
+     */
+    int64_t offsetAt(int64_t localUs) const;
+
+    /**
+     * @brief Ottiene lo stato dell'orologio in un istante
+     * @param localUs Tempo locale (µs)
+     */
+    ClockStats stats(int64_t localUs) const;
+
+    /**
+     * @brief Verifica se almeno un beacon è stato elaborato
+     */
+    bool isLocked() const;
+
+    /**
+     * @brief Torna allo stato iniziale, senza stima della deriva
+     */
+    void reset();
+
+private:
+    /// Scarto oltre cui si riallinea di colpo (µs)
+    int64_t stepThresholdUs;
+
+    /// Velocità massima di recupero (ppm)
+    uint32_t maxSlewPpm;
+
+    /// Almeno un beacon elaborato
+    bool locked = false;
+
+    /// Istante dell'ultimo aggiornamento (µs locali)
+    int64_t referenceUs = 0;
+
+    /// Offset applicato all'istante di riferimento (µs)
+    double appliedUs = 0.0;
+
+    /// Offset stimato all'istante di riferimento (µs)
+    double estimateUs = 0.0;
+
+    /// Deriva stimata (ppm, cioè µs al secondo)
+    double skewPpm = 0.0;
+
+    /// Covarianza della stima [offset, deriva]
+    double p00 = 0.0;
+    double p01 = 0.0;
+    double p11 = 0.0;
+
+    /// Contatori per le statistiche
+    uint64_t updates = 0;
+    uint64_t steps = 0;
+
+    /**
+     * @brief Offset applicato dopo elapsedS secondi dall'istante di riferimento
+     */
+    double appliedAfter(double elapsedS) const;
+};
+
+} // namespace saber
+
+#endif // SABER_CLOCK_DISCIPLINE_H
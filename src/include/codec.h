@@ -0,0 +1,154 @@
+#ifndef SABER_CODEC_H
+#define SABER_CODEC_H
+
+#include <cstdint>
+#include <memory>
+#include <vector>
+
+#include "spec.h"
+
+namespace saber {
+
+/**
+ * @brief Frame audio PCM di 10ms, interleaved a 16 bit
+ */
+struct AudioFrame {
+    /// Istante di riproduzione sull'orologio della rete (µs)
+    uint64_t playoutTimeUs = 0;
+
+    /// Frequenza di campionamento (Hz)
+    uint32_t sampleRate = spec::SAMPLE_RATE_MUSIC_HZ;
+
+    /// Numero di canali
+    uint8_t channels = 2;
+
+    /// Campioni interleaved (sampleRate / 100 per canale)
+    std::vector<int16_t> samples;
+
+    /**
+     * @brief Campioni per canale attesi in un frame di 10ms
+     */
+    size_t samplesPerChannel() const;
+};
+
+/**
+ * @brief Scala l'ampiezza di un frame PCM secondo un volume
+ * @param frame Frame da scalare
+ * @param volumePercent Volume lineare in percentuale (100 lascia il frame invariato, 0 lo silenzia)
+ */
+void applyVolume(AudioFrame& frame, uint8_t volumePercent);
+
+/**
+ * @brief Verifica se la libreria LC3 è stata compilata
+ * @return true se codifica e decodifica sono disponibili
+ */
+bool lc3Available();
+
+/**
+ * @brief Codificatore LC3 a frame di 10ms
+ *
+ * Ogni canale è codificato come un flusso LC3 indipendente; il frame
+ * codificato concatena i blocchi dei canali nello stesso ordine dei
+ * campioni interleaved. Il bitrate indicato è quello complessivo.
+ */
+class Lc3Encoder {
+public:
+    /**
+     * @brief Crea un codificatore
+     * @param sampleRate Frequenza di campionamento (Hz)
+     * @param channels Numero di canali
+     * @param bitrateKbps Bitrate complessivo (kbps)
+     * @throws std::invalid_argument se il formato non è supportato da LC3
+     * @throws std::runtime_error se la libreria LC3 non è stata compilata
+     */
+    Lc3Encoder(uint32_t sampleRate, uint8_t channels, uint32_t bitrateKbps);
+
+    ~Lc3Encoder();
+
+    Lc3Encoder(const Lc3Encoder&) = delete;
+    Lc3Encoder& operator=(const Lc3Encoder&) = delete;
+
+    /**
+     * @brief Codifica un frame PCM
+     * @param frame Frame di 10ms nel formato del codificatore
+     * @return Frame codificato
+     * @throws std::invalid_argument se il frame non ha il formato atteso
+     */
+    std::vector<uint8_t> encode(const AudioFrame& frame);
+
+    /**
+     * @brief Cambia il bitrate dai frame successivi
+     * @param bitrateKbps Bitrate complessivo (kbps)
+     */
+    void setBitrate(uint32_t bitrateKbps);
+
+    /**
+     * @brief Dimensione di un frame codificato (byte)
+     */
+    size_t frameBytes() const;
+
+private:
+    /// Stato della libreria LC3
+    struct State;
+    std::unique_ptr<State> state;
+
+    /// Frequenza di campionamento (Hz)
+    uint32_t sampleRate;
+
+    /// Numero di canali
+    uint8_t channels;
+
+    /// Byte di ogni canale in un frame codificato
+    size_t channelBytes;
+};
+
+/**
+ * @brief Decodificatore LC3 a frame di 10ms
+ */
+class Lc3Decoder {
+public:
+    /**
+     * @brief Crea un decodificatore
+     * @param sampleRate Frequenza di campionamento (Hz)
+     * @param channels Numero di canali
+     * @throws std::invalid_argument se il formato non è supportato da LC3
+     * @throws std::runtime_error se la libreria LC3 non è stata compilata
+     */
+    Lc3Decoder(uint32_t sampleRate, uint8_t channels);
+
+    ~Lc3Decoder();
+
+    Lc3Decoder(const Lc3Decoder&) = delete;
+    Lc3Decoder& operator=(const Lc3Decoder&) = delete;
+
+    /**
+     * @brief Decodifica un frame
+     * @param payload Frame codificato
+     * @param playoutTimeUs Istante di riproduzione del frame (µs)
+     * @return Frame PCM
+     * @throws std::invalid_argument se il frame codificato non è valido
+     */
+    AudioFrame decode(const std::vector<uint8_t>& payload, uint64_t playoutTimeUs);
+
+    /**
+     * @brief Ricostruisce un frame perso a partire da quelli precedenti
+     * @param playoutTimeUs Istante di riproduzione del frame perso (µs)
+     * @return Frame PCM ricostruito
+     */
+    AudioFrame conceal(uint64_t playoutTimeUs);
+
+private:
+    /// Stato della libreria LC3
+    struct State;
+    std::unique_ptr<State> state;
+
+    /// Frequenza di campionamento (Hz)
+    uint32_t sampleRate;
+
+    /// Numero di canali
+    uint8_t channels;
+};
+
+} // namespace saber
+
+#endif // SABER_CODEC_H
@@ -0,0 +1,223 @@
+#ifndef SABER_CONFIG_H
+#define SABER_CONFIG_H
+
+#include <array>
+#include <map>
+#include <optional>
+#include <ostream>
+#include <stdexcept>
+#include <string>
+#include <vector>
+
+namespace saber {
+
+/**
+ * @brief Errore durante il caricamento della configurazione
+ */
+class ConfigError : public std::runtime_error {
+public:
+    explicit ConfigError(const std::string& message);
+};
+
+/**
+ * @brief Valore segreto (passphrase, codici, credenziali)
+ *
+ * Il valore non viene mai stampato: l'operatore di output produce
+ * sempre un segnaposto, così i segreti non finiscono nei log.
+ */
+class SecretString {
+public:
+    SecretString() = default;
+    
+    /**
+     * @brief Crea un segreto a partire dal valore in chiaro
+     * @param value Valore del segreto
+     */
+    explicit SecretString(std::string value);
+    
+    /**
+     * @brief Ottiene il valore in chiaro del segreto
+     * @return Valore in chiaro
+     */
+    const std::string& reveal() const;
+    
+    /**
+     * @brief Verifica se il segreto è vuoto
+     * @return true se non è stato impostato alcun valore
+     */
+    bool empty() const;
+    
+private:
+    std::string value;
+};
+
+std::ostream& operator<<(std::ostream& os, const SecretString& secret);
+
+/**
+ * @brief File di configurazione in formato TOML (sottoinsieme)
+ *
+ * Supporta sezioni, stringhe, interi, booleani e commenti. Le chiavi
+ * vengono esposte in forma piatta "sezione.chiave".
+ */
+class ConfigFile {
+public:
+    /**
+     * @brief Carica un file di configurazione
+     * @param path Percorso del file
+     * @return File di configurazione
+     * @throws ConfigError se il file non è leggibile o non è valido
+     */
+    static ConfigFile load(const std::string& path);
+    
+    /**
+     * @brief Interpreta una configurazione da testo
+     * @param text Contenuto TOML
+     * @return File di configurazione
+     * @throws ConfigError se il testo non è valido
+     */
+    static ConfigFile parse(const std::string& text);
+    
+    /**
+     * @brief Verifica se una chiave è presente
+     * @param key Chiave nella forma "sezione.chiave"
+     * @return true se la chiave è presente
+     */
+    bool has(const std::string& key) const;
+    
+    /**
+     * @brief Ottiene una stringa
+     * @param key Chiave nella forma "sezione.chiave"
+     * @return Valore, o nullopt se la chiave non è presente
+     */
+    std::optional<std::string> getString(const std::string& key) const;
+    
+    /**
+     * @brief Ottiene un intero
+     * @param key Chiave nella forma "sezione.chiave"
+     * @return Valore, o nullopt se la chiave non è presente
+     * @throws ConfigError se il valore non è un intero
+     */
+    std::optional<int64_t> getInt(const std::string& key) const;
+    
+    /**
+     * @brief Ottiene un booleano
+     * @param key Chiave nella forma "sezione.chiave"
+     * @return Valore, o nullopt se la chiave non è presente
+     * @throws ConfigError se il valore non è un booleano
+     */
+    std::optional<bool> getBool(const std::string& key) const;
+    
+    /**
+     * @brief Ottiene tutte le chiavi presenti
+     * @return Vettore di chiavi
+     */
+    std::vector<std::string> keys() const;
+    
+private:
+    /// Valori grezzi (stringhe senza virgolette)
+    std::map<std::string, std::string> values;
+};
+
+/**
+ * @brief Archivio di segreti cifrati su disco
+ *
+ * Ogni segreto è salvato come "nome = <hex>" dove il valore esadecimale
+ * contiene nonce, testo cifrato e tag AES-256-GCM. La chiave dell'archivio
+ * proviene dall'ambiente (SABER_KEYSTORE_KEY in esadecimale, oppure
+ * derivata da SABER_KEYSTORE_PASSPHRASE con PBKDF2-HMAC-SHA256) e non è
+ * mai salvata su disco. Il sale casuale e il numero di iterazioni della
+ * derivazione sono nell'intestazione (kdf.salt, kdf.iterations).
+ */
+class Keystore {
+public:
+    /// Iterazioni PBKDF2 per i nuovi archivi
+    static constexpr uint32_t KDF_ITERATIONS = 200000;
+    
+    /// Lunghezza del sale PBKDF2 in byte
+    static constexpr size_t KDF_SALT_BYTES = 16;
+    
+
+    /**
+     * @brief Apre un archivio con la chiave fornita dall'ambiente
+     * @param path Percorso del file
+     * @return Archivio
+     * @throws ConfigError se la chiave non è disponibile, il file non è valido
+     *         o un archivio non vuoto non ha l'intestazione kdf.salt
+     */
+    static Keystore open(const std::string& path);
+    
+    /**
+     * @brief Apre un archivio con una chiave esplicita
+     * @param path Percorso del file (può non esistere)
+     * @param key Chiave dell'archivio
+     * @return Archivio
+     * @throws ConfigError se il file non è valido
+     */
+    static Keystore open(const std::string& path, const std::array<uint8_t, 32>& key);
+    
+    /**
+     * @brief Ottiene un segreto decifrato
+     * @param name Nome del segreto
+     * @return Segreto
+     * @throws ConfigError se il segreto non esiste o non è decifrabile
+     */
+    SecretString get(const std::string& name) const;
+    
+    /**
+     * @brief Cifra e memorizza un segreto
+     * @param name Nome del segreto
+     * @param value Valore del segreto
+     * @throws ConfigError se il nome è riservato all'intestazione (kdf.*)
+     */
+    void put(const std::string& name, const SecretString& value);
+    
+    /**
+     * @brief Salva l'archivio su disco
+     * @throws ConfigError se il file non è scrivibile
+     */
+    void save() const;
+    
+private:
+    Keystore(const std::string& path, const std::array<uint8_t, 32>& key);
+    
+    /// Deriva la chiave dell'archivio dalla passphrase
+    static std::array<uint8_t, 32> deriveKey(const std::string& passphrase, const std::vector<uint8_t>& salt,
+                                             uint32_t iterations);
+    
+    /// Verifica se un nome è riservato all'intestazione
+    static bool isReservedName(const std::string& name);
+    
+    /// Percorso del file
+    std::string path;
+    
+    /// Chiave dell'archivio
+    std::array<uint8_t, 32> key;
+    
+    /// Sale della derivazione (vuoto se la chiave è esplicita)
+    std::vector<uint8_t> salt;
+    
+    /// Iterazioni della derivazione
+    uint32_t iterations = KDF_ITERATIONS;
+    
+    /// Segreti cifrati (nome -> nonce + testo cifrato + tag)
+    std::map<std::string, std::vector<uint8_t>> entries;
+};
+
+/**
+ * @brief Risolve il riferimento ad un segreto presente nella configurazione
+ *
+ * Formati supportati:
+ * - "env:NOME" legge la variabile d'ambiente NOME
+ * - "keystore:NOME" legge il segreto NOME dall'archivio cifrato
+ * - qualsiasi altro valore viene usato letteralmente
+ *
+ * @param reference Valore presente nella configurazione
+ * @param keystore Archivio dei segreti (opzionale)
+ * @return Segreto risolto
+ * @throws ConfigError se il riferimento non è risolvibile
+ */
+SecretString resolveSecret(const std::string& reference, const Keystore* keystore);
+
+} // namespace saber
+
+#endif // SABER_CONFIG_H
@@ -0,0 +1,86 @@
+#ifndef SABER_CONGESTION_H
+#define SABER_CONGESTION_H
+
+#include <cstdint>
+#include <string>
+
+#include "spec.h"
+
+namespace saber {
+
+/**
+ * @brief Stato di congestione del canale radio
+ */
+enum class CongestionState {
+    /// Canale con banda disponibile
+    Clear,
+    /// Canale vicino alla saturazione: il bitrate va ridotto
+    Congested
+};
+
+/**
+ * @brief Converte uno stato di congestione nella sua rappresentazione testuale
+ * @param state Stato di congestione
+ * @return Nome dello stato ("clear" o "congested")
+ */
+std::string congestionStateToString(CongestionState state);
+
+/**
+ * @brief Controllore di congestione basato sull'occupazione del canale
+ *
+ * Riceve periodicamente il tempo d'aria misurato da MeshStats e indica il
+ * bitrate LC3 da usare. Le due soglie introducono isteresi, così che
+ * un'occupazione vicina al limite non faccia oscillare il bitrate.
+ */
+class CongestionController {
+public:
+    /**
+     * @brief Crea un controllore
+     * @param enterThreshold Occupazione del canale oltre cui il canale è congestionato
+     * @param exitThreshold Occupazione sotto cui il canale torna libero
+     */
+    explicit CongestionController(double enterThreshold = 0.8, double exitThreshold = 0.6);
+    
+    /**
+     * @brief Aggiorna lo stato con una nuova misura
+     * @param airtimeUtilization Frazione del canale occupata (0-1)
+     * @return true se lo stato è cambiato
+     */
+    bool update(double airtimeUtilization);
+    
+    /**
+     * @brief Ottiene lo stato corrente
+     * @return Stato di congestione
+     */
+    CongestionState getState() const;
+    
+    /**
+     * @brief Ottiene l'ultima occupazione misurata
+     * @return Frazione del canale occupata
+     */
+    double getUtilization() const;
+    
+    /**
+     * @brief Bitrate LC3 consigliato nello stato corrente
+     * @param isMusicMode true per la musica, false per la voce
+     * @return Bitrate in kbps
+     */
+    uint32_t recommendedBitrateKbps(bool isMusicMode) const;
+    
+private:
+    /// Soglia di ingresso nello stato congestionato
+    double enterThreshold;
+    
+    /// Soglia di uscita dallo stato congestionato
+    double exitThreshold;
+    
+    /// Stato corrente
+    CongestionState state;
+    
+    /// Ultima occupazione misurata
+    double utilization;
+};
+
+} // namespace saber
+
+#endif // SABER_CONGESTION_H
@@ -0,0 +1,238 @@
+#ifndef SABER_CONTROL_SERVER_H
+#define SABER_CONTROL_SERVER_H
+
+#include "crypto.h"
+
+#include <atomic>
+#include <functional>
+#include <map>
+#include <memory>
+#include <mutex>
+#include <optional>
+#include <string>
+#include <thread>
+#include <vector>
+
+namespace saber {
+
+/// Tempo concesso ad una connessione per autenticarsi (ms)
+constexpr uint32_t CONTROL_AUTH_TIMEOUT_MS = 5000;
+
+/// Inattività dopo cui una connessione viene chiusa (ms)
+constexpr uint32_t CONTROL_IDLE_TIMEOUT_MS = 300000;
+
+/// Attesa massima per l'invio di una risposta (ms)
+constexpr uint32_t CONTROL_SEND_TIMEOUT_MS = 1000;
+
+/// Connessioni servite contemporaneamente
+constexpr size_t CONTROL_MAX_CONNECTIONS = 8;
+
+/**
+ * @brief Socket di controllo per l'amministrazione del nodo a runtime
+ *
+ * Protocollo testuale a righe: ogni riga è un comando seguito dai suoi
+ * argomenti separati da spazi (es. "log set mesh=debug"); il server
+ * risponde con una riga che inizia con "ok" o "error". Pensato per
+ * l'operatore locale: va esposto solo su loopback.
+ *
+ * Con un autenticatore impostato ogni connessione deve presentare un token
+ * con "auth <token>" prima di qualsiasi altro comando; il token viene
+ * riverificato ad ogni comando, così revoca e scadenza hanno effetto anche
+ * sulle connessioni già aperte.
+ *
+ * Ogni connessione è servita da un proprio thread, fino ad un massimo di
+ * connessioni contemporanee. Una connessione che non si autentica entro
+ * pochi secondi, resta inattiva troppo a lungo o non legge le risposte
+ * viene chiusa, così un client non blocca il socket per gli altri.
+ */
+class ControlServer {
+public:
+    /**
+     * @brief Tipo di callback per un comando
+     *
+     * Riceve gli argomenti successivi al nome del comando e restituisce il
+     * testo della risposta; un'eccezione produce una risposta "error".
+     */
+    using CommandHandler = std::function<std::string(const std::vector<std::string>&)>;
+
+    /**
+     * @brief Tipo di callback per la verifica di un token
+     *
+     * Restituisce l'ambito concesso dal token, o std::nullopt se il token
+     * non è valido, è scaduto o è stato revocato.
+     */
+    using Authenticator = std::function<std::optional<TokenScope>(const std::string&)>;
+
+    /**
+     * @brief Tipo di callback per l'autorizzazione dei comandi privilegiati
+     *
+     * Riceve la riga di comando e il token presentato (vuoto senza
+     * autenticazione) e restituisce il motivo del rifiuto, o std::nullopt
+     * se il comando può essere eseguito. Può bloccare: viene chiamata dal
+     * thread della connessione.
+     */
+    using CommandAuthorizer = std::function<std::optional<std::string>(const std::string&, const std::string&)>;
+
+    /**
+     * @brief Crea un nuovo socket di controllo
+     * @param bindAddress Indirizzo IPv4 su cui mettersi in ascolto
+     * @param port Porta TCP
+     */
+    ControlServer(const std::string& bindAddress, uint16_t port);
+
+    /**
+     * @brief Distruttore, ferma il server se in esecuzione
+     */
+    ~ControlServer();
+
+    /**
+     * @brief Registra un comando
+     * @param name Nome del comando (es. "log")
+     * @param handler Funzione che esegue il comando
+     */
+    void addCommand(const std::string& name, CommandHandler handler);
+
+    /**
+     * @brief Imposta l'ambito richiesto da un comando o sottocomando
+     *
+     * Il nome può indicare un sottocomando (es. "log get"), che prevale
+     * sul comando principale. I comandi senza ambito richiedono Admin.
+     *
+     * @param command Comando o sottocomando
+     * @param scope Ambito minimo richiesto
+     */
+    void setRequiredScope(const std::string& command, TokenScope scope);
+
+    /**
+     * @brief Imposta l'autenticatore delle connessioni
+     * @param authenticator Funzione di verifica dei token
+     */
+    void setAuthenticator(Authenticator authenticator);
+
+    /**
+     * @brief Imposta l'autorizzazione dei comandi privilegiati ricevuti sul socket
+     *
+     * Consultata solo per i comandi che richiedono l'ambito Admin, dopo la
+     * verifica del token; i comandi eseguiti con execute() non passano da
+     * qui.
+     *
+     * @param authorizer Funzione di autorizzazione (vuota per disattivare)
+     */
+    void setCommandAuthorizer(CommandAuthorizer authorizer);
+
+    /**
+     * @brief Esegue una riga di comando senza passare dal socket
+     * @param line Riga di comando
+     * @param scope Ambito con cui eseguire il comando
+     * @return Risposta ("ok ..." o "error ...")
+     */
+    std::string execute(const std::string& line, TokenScope scope = TokenScope::Admin);
+
+    /**
+     * @brief Avvia il server
+     * @return true se il socket è stato aperto correttamente, false altrimenti
+     */
+    bool start();
+
+    /**
+     * @brief Ferma il server
+     */
+    void stop();
+
+    /**
+     * @brief Verifica se il server è in esecuzione
+     * @return true se il server è in esecuzione, false altrimenti
+     */
+    bool isRunning() const;
+
+private:
+    /// Indirizzo di ascolto
+    std::string bindAddress;
+
+    /// Porta di ascolto
+    uint16_t port;
+
+    /// Socket di ascolto
+    intptr_t listenSocket;
+
+    /// Flag per il thread del server
+    std::atomic<bool> running;
+
+    /// Thread di accettazione delle connessioni
+    std::unique_ptr<std::thread> serverThread;
+
+    /**
+     * @brief Thread che serve una connessione
+     */
+    struct Worker {
+        std::thread thread;
+        std::shared_ptr<std::atomic<bool>> finished;
+    };
+
+    /// Connessioni aperte (usate solo dal thread di accettazione)
+    std::vector<Worker> workers;
+
+    /// Comandi registrati
+    std::map<std::string, CommandHandler> commands;
+
+    /// Ambiti richiesti per comando o sottocomando
+    std::map<std::string, TokenScope> requiredScopes;
+
+    /// Autenticatore delle connessioni (nessuna autenticazione se assente)
+    Authenticator authenticator;
+
+    /// Autorizzazione dei comandi privilegiati (nessuna se assente)
+    CommandAuthorizer commandAuthorizer;
+
+    /// Mutex per comandi, ambiti, autenticatore e autorizzazione
+    mutable std::mutex commandsMutex;
+
+    /**
+     * @brief Ambito richiesto da una riga di comando
+     * @param name Nome del comando
+     * @param args Argomenti del comando
+     * @return Ambito minimo richiesto
+     */
+    TokenScope requiredScope(const std::string& name, const std::vector<std::string>& args) const;
+
+    /**
+     * @brief Gestisce una riga ricevuta sul socket
+     * @param line Riga ricevuta
+     * @param token Token presentato sulla connessione (vuoto se nessuno)
+     * @return Risposta da inviare al client
+     */
+    std::string handleLine(const std::string& line, std::string& token);
+
+    /**
+     * @brief Consulta l'autorizzazione esterna per un comando privilegiato
+     * @param line Riga di comando
+     * @param token Token presentato sulla connessione (vuoto se nessuno)
+     * @return Motivo del rifiuto, o std::nullopt se il comando può essere eseguito
+     */
+    std::optional<std::string> checkCommandAuthorization(const std::string& line, const std::string& token) const;
+
+    /**
+     * @brief Loop di accettazione delle connessioni
+     */
+    void runServerLoop();
+
+    /**
+     * @brief Attende i thread delle connessioni chiuse
+     * @param all Attende anche le connessioni ancora aperte (all'arresto)
+     */
+    void reapWorkers(bool all);
+
+    /**
+     * @brief Gestisce una connessione, una riga di comando alla volta
+     *
+     * Chiude la connessione se il client non si autentica entro
+     * CONTROL_AUTH_TIMEOUT_MS o resta inattivo per CONTROL_IDLE_TIMEOUT_MS.
+     *
+     * @param clientSocket Socket del client
+     */
+    void handleConnection(intptr_t clientSocket);
+};
+
+} // namespace saber
+
+#endif // SABER_CONTROL_SERVER_H
@@ -3,12 +3,18 @@
 
 #include <array>
 #include <cstdint>
+#include <functional>
 #include <map>
 #include <memory>
+#include <optional>
+#include <set>
 #include <stdexcept>
 #include <string>
 #include <vector>
 
+#include "revocation.h"
+#include "rng.h"
+
 // Definizione delle costanti per le dimensioni delle chiavi
 #define CRYPTO_SIGN_PUBLICKEYBYTES 32
 #define CRYPTO_SIGN_SECRETKEYBYTES 64
@@ -28,7 +34,8 @@ public:
         Signature,
         Verification,
         KeyExchange,
-        Hash
+        Hash,
+        Replay
     };
     
     CryptoError(Type type, const std::string& message);
@@ -38,6 +45,277 @@ private:
     Type type;
 };
 
+/**
+ * @brief Evento di sicurezza rilevato dal livello crittografico
+ */
+struct SecurityEvent {
+    enum class Type {
+        /// Lo stesso ID nodo è stato rivendicato con chiavi pubbliche diverse
+        KeyConflict,
+        /// Conflitto risolto dall'operatore
+        KeyConflictResolved,
+        /// Pacchetto autentico scartato perché già ricevuto o troppo vecchio
+        ReplayRejected,
+        /// Anomalia del traffico rilevata da un'euristica
+        IntrusionDetected,
+        /// Nodo messo in quarantena per comportamento anomalo
+        NodeQuarantined,
+        /// Quarantena rimossa dall'operatore
+        QuarantineReleased
+    };
+    
+    /// Tipo di evento
+    Type type;
+    
+    /// Nodo coinvolto
+    std::string nodeId;
+    
+    /// Key-ID delle chiavi coinvolte
+    std::vector<std::string> keyIds;
+    
+    /// Descrizione leggibile
+    std::string detail;
+    
+    /// Timestamp dell'evento in millisecondi
+    uint64_t timestamp;
+};
+
+/// Nonce per mittente tenuti nella finestra anti-replay
+const uint32_t DEFAULT_REPLAY_WINDOW = 1024;
+
+/// Prefissi di nonce passati ricordati per mittente, per scartare i pacchetti di istanze precedenti
+const size_t REPLAY_RETIRED_SALTS = 16;
+
+/// Contatori dei nonce riservati ad ogni scrittura dell'archivio persistente
+const uint64_t NONCE_RESERVATION_BLOCK = 65536;
+
+/**
+ * @brief Ambito di autorizzazione di un token di sicurezza
+ */
+enum class TokenScope : uint8_t {
+    /// Sola lettura dello stato del nodo
+    ReadOnly = 1,
+    /// Lettura e modifica della configurazione a runtime
+    Admin = 2
+};
+
+/**
+ * @brief Converte un ambito in stringa
+ * @param scope Ambito da convertire
+ * @return "read-only" o "admin"
+ */
+std::string tokenScopeToString(TokenScope scope);
+
+/**
+ * @brief Converte una stringa in ambito
+ * @param value Stringa da convertire ("read-only" o "admin")
+ * @return Ambito corrispondente, o std::nullopt se non riconosciuto
+ */
+std::optional<TokenScope> tokenScopeFromString(const std::string& value);
+
+/**
+ * @brief Contenuto verificato di un token di sicurezza
+ */
+struct SecurityToken {
+    /// Identificatore univoco del token, usato per la revoca (esadecimale)
+    std::string tokenId;
+    
+    /// Soggetto a cui è stato rilasciato il token
+    std::string nodeId;
+    
+    /// Ambito di autorizzazione
+    TokenScope scope;
+    
+    /// Istante di emissione in millisecondi
+    uint64_t issuedAt;
+    
+    /// Scadenza in millisecondi
+    uint64_t expiry;
+};
+
+/**
+ * @brief Contatori delle chiavi di sessione
+ */
+struct SessionStats {
+    /// Chiavi derivate con lo scambio X25519
+    uint64_t derivations = 0;
+    
+    /// Richieste servite dalla cache
+    uint64_t cacheHits = 0;
+    
+    /// Peer con una chiave in cache
+    size_t sessions = 0;
+};
+
+/**
+ * @brief Cache delle chiavi di sessione tra il nodo e i suoi peer
+ *
+ * Una chiave resta valida finché non cambiano l'epoca delle chiavi di
+ * MeshCrypto e la chiave d'identità fissata per il peer; al primo uso
+ * dopo un cambio va derivata di nuovo. Non è thread-safe, come il
+ * MeshCrypto che la contiene.
+ */
+class SessionManager {
+public:
+    SessionManager() = default;
+    SessionManager(const SessionManager&) = default;
+    SessionManager& operator=(const SessionManager&) = default;
+    
+    /**
+     * @brief Distruttore, azzera le chiavi in memoria
+     */
+    ~SessionManager();
+    
+    /**
+     * @brief Cerca la chiave valida per un peer
+     * @param nodeId ID del peer
+     * @param epoch Epoca corrente delle chiavi
+     * @param peerKey Chiave d'identità fissata per il peer
+     * @return Chiave in cache, o std::nullopt se assente o derivata per un'altra epoca o identità
+     */
+    std::optional<std::array<uint8_t, 32>> find(const std::string& nodeId, uint32_t epoch,
+                                                const std::vector<uint8_t>& peerKey);
+    
+    /**
+     * @brief Conserva una chiave appena derivata
+     * @param nodeId ID del peer
+     * @param epoch Epoca delle chiavi usata nella derivazione
+     * @param peerKey Chiave d'identità del peer usata nella derivazione
+     * @param key Chiave di sessione
+     */
+    void store(const std::string& nodeId, uint32_t epoch, const std::vector<uint8_t>& peerKey,
+               const std::array<uint8_t, 32>& key);
+    
+    /**
+     * @brief Dimentica la chiave di un peer
+     * @param nodeId ID del peer
+     * @return true se il peer aveva una chiave in cache
+     */
+    bool forget(const std::string& nodeId);
+    
+    /**
+     * @brief Dimentica tutte le chiavi
+     */
+    void clear();
+    
+    /**
+     * @brief Ottiene i peer con una chiave in cache
+     */
+    std::vector<std::string> getPeers() const;
+    
+    /**
+     * @brief Ottiene i contatori di derivazioni e riusi
+     */
+    SessionStats getStats() const;
+    
+private:
+    /**
+     * @brief Chiave derivata per un peer
+     */
+    struct Session {
+        /// Chiave AES-256-GCM
+        std::array<uint8_t, 32> key;
+        
+        /// Epoca delle chiavi al momento della derivazione
+        uint32_t epoch;
+        
+        /// Chiave d'identità del peer usata nella derivazione
+        std::vector<uint8_t> peerKey;
+    };
+    
+    /// Chiavi per ID del peer
+    std::map<std::string, Session> sessions;
+    
+    /// Contatori
+    SessionStats stats;
+};
+
+/**
+ * @brief Contatori dei nonce persistiti per chiave di rete
+ *
+ * Prima di usare un blocco di contatori ne viene salvato su disco il
+ * limite: dopo un riavvio, anche improvviso o con l'orologio riportato
+ * indietro, il nodo riprende oltre l'ultimo nonce che può aver usato
+ * con la stessa chiave. Le chiavi compaiono solo come key-ID derivato.
+ *
+ * Formato del file, una riga per chiave: key-ID, prefisso dei nonce in
+ * esadecimale e limite riservato in decimale, separati da spazi.
+ */
+class NonceCounterStore {
+public:
+    /**
+     * @brief Blocco di contatori riservato per una chiave
+     */
+    struct Reservation {
+        /// Prefisso dei nonce della chiave, fisso tra un avvio e l'altro
+        std::array<uint8_t, 4> salt;
+        
+        /// Primo contatore utilizzabile
+        uint64_t first;
+        
+        /// Primo contatore non coperto dalla riserva
+        uint64_t limit;
+    };
+    
+    /**
+     * @brief Apre l'archivio leggendo i limiti già salvati
+     * @param path File dei contatori, creato alla prima riserva
+     * @param block Contatori riservati ad ogni scrittura
+     * @throws std::invalid_argument se il blocco è nullo
+     * @throws CryptoError di tipo Encryption se il file esiste ma è illeggibile o malformato
+     */
+    explicit NonceCounterStore(const std::string& path, uint64_t block = NONCE_RESERVATION_BLOCK);
+    
+    /**
+     * @brief Riserva il blocco successivo di contatori per una chiave
+     * @param keyId Key-ID della chiave
+     * @param rng Sorgente del prefisso alla prima riserva della chiave
+     * @return Blocco riservato, già salvato su disco
+     * @throws CryptoError di tipo Encryption se il limite non può essere salvato
+     *         o i contatori della chiave sono esauriti
+     */
+    Reservation reserve(const std::string& keyId, RandomSource& rng);
+    
+    /**
+     * @brief Ottiene il limite salvato per una chiave
+     * @param keyId Key-ID della chiave
+     * @return Limite riservato, o std::nullopt se la chiave non ha mai avuto riserve
+     */
+    std::optional<uint64_t> getLimit(const std::string& keyId) const;
+    
+    /**
+     * @brief Ottiene il percorso del file dei contatori
+     */
+    const std::string& getPath() const;
+    
+private:
+    /**
+     * @brief Stato salvato per una chiave
+     */
+    struct Entry {
+        /// Prefisso dei nonce
+        std::array<uint8_t, 4> salt;
+        
+        /// Limite riservato
+        uint64_t limit;
+    };
+    
+    /// File dei contatori
+    std::string path;
+    
+    /// Contatori per riserva
+    uint64_t block;
+    
+    /// Stato per key-ID
+    std::map<std::string, Entry> entries;
+    
+    /**
+     * @brief Riscrive il file in modo atomico
+     * @return true se il file è stato sincronizzato su disco
+     */
+    bool save() const;
+};
+
 /**
  * @brief Gestore della crittografia per la rete mesh
  */
@@ -55,33 +333,149 @@ public:
         unsigned char secretKey[CRYPTO_SCALARMULT_SCALARBYTES];
     };
 
+    /// Orologio in ms dall'epoca Unix
+    using Clock = std::function<uint64_t()>;
+    
     /**
      * @brief Crea una nuova istanza di MeshCrypto con chiave casuale
+     * @param rng Sorgente casuale per chiavi, nonce e token (quella del sistema se nullptr)
      */
-    MeshCrypto();
+    explicit MeshCrypto(std::shared_ptr<RandomSource> rng = nullptr);
     
     /**
      * @brief Crea un'istanza con una chiave di rete specifica
      * @param networkKey Chiave di rete da utilizzare
+     * @param rng Sorgente casuale per chiavi, nonce e token (quella del sistema se nullptr)
      * @return Nuova istanza di MeshCrypto
      */
-    static MeshCrypto withNetworkKey(const std::array<uint8_t, 32>& networkKey);
+    static MeshCrypto withNetworkKey(const std::array<uint8_t, 32>& networkKey,
+                                     std::shared_ptr<RandomSource> rng = nullptr);
+    
+    /**
+     * @brief Sostituisce la chiave di rete
+     *
+     * Usata da un nodo che riceve la chiave della rete con l'abbinamento.
+     *
+     * @param networkKey Chiave di rete
+     */
+    void setNetworkKey(const std::array<uint8_t, 32>& networkKey);
+    
+    /**
+     * @brief Ottiene la chiave di rete, da consegnare ai nodi abbinati
+     * @return Chiave di rete
+     */
+    std::array<uint8_t, 32> getNetworkKey() const;
+    
+    /**
+     * @brief Ottiene l'epoca delle chiavi, che avanza ad ogni cambio della chiave di rete
+     * @return Numero di cambi della chiave di rete dalla creazione
+     */
+    uint32_t getKeyEpoch() const;
     
     /**
      * @brief Cifra un payload utilizzando AES-256-GCM
      * @param payload Dati da cifrare
+     * @param aad Dati autenticati ma non cifrati, da ripresentare in decifratura
+     * @return Dati cifrati con nonce preposto
+     * @throws CryptoError in caso di errore di cifratura
+     */
+    std::vector<uint8_t> encrypt(const std::vector<uint8_t>& payload, const std::vector<uint8_t>& aad = {});
+    
+    /**
+     * @brief Cifra un payload con AES-256-GCM e un nonce interamente casuale di 96 bit
+     *
+     * Per i dati a riposo sotto una chiave di lunga durata, cifrati da istanze
+     * diverse che non condividono un contatore: prefisso casuale e contatore
+     * ripartirebbero da 1 a ogni istanza. Il risultato si decifra con decrypt().
+     * @param payload Dati da cifrare
+     * @param aad Dati autenticati ma non cifrati, da ripresentare in decifratura
      * @return Dati cifrati con nonce preposto
      * @throws CryptoError in caso di errore di cifratura
      */
-    std::vector<uint8_t> encrypt(const std::vector<uint8_t>& payload);
+    std::vector<uint8_t> encryptWithRandomNonce(const std::vector<uint8_t>& payload,
+                                                const std::vector<uint8_t>& aad = {});
     
     /**
      * @brief Decifra un payload cifrato con AES-256-GCM
      * @param encryptedData Dati cifrati con nonce preposto
+     * @param aad Dati autenticati usati in cifratura
      * @return Dati decifrati
      * @throws CryptoError in caso di errore di decifratura
      */
-    std::vector<uint8_t> decrypt(const std::vector<uint8_t>& encryptedData);
+    std::vector<uint8_t> decrypt(const std::vector<uint8_t>& encryptedData, const std::vector<uint8_t>& aad = {});
+    
+    /**
+     * @brief Decifra un payload di un mittente noto scartando le ripetizioni
+     *
+     * Dopo la verifica del tag il nonce viene confrontato con la finestra
+     * anti-replay del mittente: un nonce già visto, o più vecchio della
+     * finestra rispetto al più recente, viene rifiutato. Un nuovo prefisso
+     * indica che il mittente è ripartito; i prefissi precedenti restano
+     * rifiutati.
+     *
+     * @param senderId ID del nodo mittente
+     * @param encryptedData Dati cifrati con nonce preposto
+     * @param aad Dati autenticati usati in cifratura
+     * @return Dati decifrati
+     * @throws CryptoError di tipo Replay se il pacchetto è una ripetizione,
+     *         di tipo Decryption in caso di errore di decifratura
+     */
+    std::vector<uint8_t> decryptFrom(const std::string& senderId, const std::vector<uint8_t>& encryptedData,
+                                     const std::vector<uint8_t>& aad = {});
+    
+    /**
+     * @brief Imposta l'ampiezza della finestra anti-replay
+     * @param nonces Nonce accettati fuori ordine dietro al più recente di ogni mittente
+     * @throws std::invalid_argument se l'ampiezza è zero
+     */
+    void setReplayWindow(uint32_t nonces);
+    
+    /**
+     * @brief Ottiene l'ampiezza della finestra anti-replay
+     * @return Numero di nonce della finestra
+     */
+    uint32_t getReplayWindow() const;
+    
+    /**
+     * @brief Ottiene i pacchetti scartati come ripetizioni
+     * @return Numero di pacchetti rifiutati dall'avvio
+     */
+    uint64_t getReplayRejections() const;
+    
+    /**
+     * @brief Persiste i contatori dei nonce della chiave di rete
+     *
+     * Senza archivio il prefisso dei nonce è estratto ad ogni avvio e il
+     * contatore riparte da zero: una sorgente casuale deterministica, o
+     * due prefissi uguali, riusano un nonce con la stessa chiave. Con
+     * l'archivio prefisso e contatore riprendono da dove erano rimasti,
+     * anche dopo un cambio di chiave e un ritorno alla precedente.
+     *
+     * @param store Archivio dei contatori (nullptr per tornare ai prefissi casuali)
+     * @throws CryptoError di tipo Encryption se la prima riserva non può essere salvata
+     */
+    void setNonceStore(std::shared_ptr<NonceCounterStore> store);
+    
+    /**
+     * @brief Sostituisce l'orologio di token, quarantene e revoche
+     *
+     * I nonce non dipendono dall'orologio: un orologio che torna indietro
+     * non ne causa il riuso.
+     *
+     * @param clock Orologio (quello di sistema se vuoto)
+     */
+    void setClock(Clock clock);
+    
+    /**
+     * @brief Ottiene l'archivio dei contatori dei nonce
+     * @return Archivio, o nullptr se i contatori non sono persistiti
+     */
+    std::shared_ptr<NonceCounterStore> getNonceStore() const;
+    
+    /**
+     * @brief Ottiene il contatore dell'ultimo nonce generato
+     */
+    uint64_t getNonceCounter() const;
     
     /**
      * @brief Firma un messaggio con la chiave privata del nodo
@@ -97,18 +491,109 @@ public:
      * @param message Messaggio originale
      * @param signature Firma da verificare
      * @return true se la firma è valida, false altrimenti
-     * @throws CryptoError se il nodo non è conosciuto
+     * @throws CryptoError se il nodo non è conosciuto o è in quarantena
      */
     bool verify(const std::string& nodeId, const std::vector<uint8_t>& message, 
                 const std::vector<uint8_t>& signature);
     
+    /**
+     * @brief Verifica una firma con una chiave pubblica esplicita
+     *
+     * Usata per le richieste di ingresso, firmate con una chiave non
+     * ancora registrata.
+     *
+     * @param publicKey Chiave pubblica Ed25519
+     * @param message Messaggio originale
+     * @param signature Firma da verificare
+     * @return true se chiave e firma sono ben formate e la firma è valida
+     */
+    static bool verifyWithKey(const std::vector<uint8_t>& publicKey, const std::vector<uint8_t>& message,
+                              const std::vector<uint8_t>& signature);
+    
     /**
      * @brief Registra la chiave pubblica di un nodo
+     *
+     * La prima chiave registrata per un nodo viene fissata (key pinning).
+     * Se lo stesso ID viene rivendicato con una chiave diversa il nodo va
+     * in quarantena, viene emesso un evento KeyConflict e nessuna delle
+     * due chiavi viene accettata finché l'operatore non risolve il conflitto.
+     *
      * @param nodeId ID del nodo
      * @param publicKey Chiave pubblica del nodo
      */
     void registerNodeKey(const std::string& nodeId, const std::vector<uint8_t>& publicKey);
     
+    /**
+     * @brief Tipo di callback per gli eventi di sicurezza
+     */
+    using SecurityEventHandler = std::function<void(const SecurityEvent&)>;
+    
+    /**
+     * @brief Imposta il gestore degli eventi di sicurezza
+     * @param handler Funzione invocata ad ogni evento
+     */
+    void setSecurityEventHandler(SecurityEventHandler handler);
+    
+    /**
+     * @brief Calcola il key-ID di una chiave pubblica
+     * @param publicKey Chiave pubblica
+     * @return Primi 8 byte dello SHA-256 della chiave, in esadecimale
+     */
+    static std::string keyId(const std::vector<uint8_t>& publicKey);
+    
+    /**
+     * @brief Verifica se un nodo è in quarantena per conflitto di chiavi o comportamento anomalo
+     * @param nodeId ID del nodo
+     * @return true se il nodo è in quarantena
+     */
+    bool isQuarantined(const std::string& nodeId) const;
+    
+    /**
+     * @brief Mette in quarantena un nodo per comportamento anomalo
+     *
+     * La chiave del nodo resta fissata; i suoi pacchetti vengono scartati
+     * finché l'operatore non chiama releaseQuarantine().
+     *
+     * @param nodeId ID del nodo
+     * @param reason Motivo della quarantena
+     */
+    void quarantineNode(const std::string& nodeId, const std::string& reason);
+    
+    /**
+     * @brief Rimuove la quarantena imposta con quarantineNode()
+     *
+     * Non risolve i conflitti di chiavi, che richiedono resolveKeyConflict().
+     *
+     * @param nodeId ID del nodo
+     * @return true se il nodo era in quarantena per comportamento anomalo
+     */
+    bool releaseQuarantine(const std::string& nodeId);
+    
+    /**
+     * @brief Ottiene i nodi in quarantena
+     * @return Vettore di ID dei nodi in quarantena
+     */
+    std::vector<std::string> getQuarantinedNodes() const;
+    
+    /**
+     * @brief Ottiene le chiavi contese per un nodo in quarantena
+     * @param nodeId ID del nodo
+     * @return Chiavi pubbliche rivendicate, la prima è quella fissata in origine
+     */
+    std::vector<std::vector<uint8_t>> getConflictingKeys(const std::string& nodeId) const;
+    
+    /**
+     * @brief Risolve un conflitto di chiavi scegliendo la chiave attendibile
+     *
+     * Fissa la chiave indicata per il nodo e rimuove la quarantena.
+     * È un'operazione riservata all'operatore.
+     *
+     * @param nodeId ID del nodo
+     * @param trustedKey Chiave pubblica da considerare attendibile
+     * @throws CryptoError se la chiave non ha un formato valido
+     */
+    void resolveKeyConflict(const std::string& nodeId, const std::vector<uint8_t>& trustedKey);
+    
     /**
      * @brief Calcola l'hash SHA-256 di un messaggio
      * @param data Dati da hashare
@@ -125,34 +610,162 @@ public:
      */
     std::array<uint8_t, 32> keyExchange(const std::vector<uint8_t>& peerPublic);
     
+    /**
+     * @brief Verifica se con un peer è possibile una chiave di sessione
+     * @param nodeId ID del peer
+     * @return true se la chiave d'identità del peer è fissata e il peer non è in quarantena
+     */
+    bool hasSessionPeer(const std::string& nodeId) const;
+    
+    /**
+     * @brief Ottiene la chiave di sessione condivisa con un peer
+     *
+     * La chiave deriva da keyExchange() con la chiave di scambio del peer,
+     * ricavata dalla sua chiave d'identità fissata, ed è legata alla chiave
+     * di rete e alle identità dei due nodi: i due lati ottengono la stessa
+     * chiave senza scambiare messaggi. Resta in cache finché non cambiano
+     * l'epoca delle chiavi o l'identità del peer.
+     *
+     * @param nodeId ID del peer
+     * @return Chiave di sessione
+     * @throws CryptoError di tipo KeyExchange se il peer non ha una chiave attendibile
+     */
+    std::array<uint8_t, 32> sessionKey(const std::string& nodeId);
+    
+    /**
+     * @brief Cifra un payload con la chiave di sessione di un peer
+     * @param nodeId ID del peer destinatario
+     * @param payload Dati da cifrare
+     * @param aad Dati autenticati ma non cifrati, da ripresentare in decifratura
+     * @return Dati cifrati con nonce preposto
+     * @throws CryptoError di tipo KeyExchange se il peer non ha una chiave attendibile,
+     *         di tipo Encryption in caso di errore di cifratura
+     */
+    std::vector<uint8_t> encryptFor(const std::string& nodeId, const std::vector<uint8_t>& payload,
+                                    const std::vector<uint8_t>& aad = {});
+    
+    /**
+     * @brief Decifra un payload di un peer con la chiave di sessione scartando le ripetizioni
+     *
+     * La finestra anti-replay del mittente è la stessa di decryptFrom().
+     *
+     * @param nodeId ID del peer mittente
+     * @param encryptedData Dati cifrati con nonce preposto
+     * @param aad Dati autenticati usati in cifratura
+     * @return Dati decifrati
+     * @throws CryptoError di tipo KeyExchange se il peer non ha una chiave attendibile,
+     *         di tipo Replay se il pacchetto è una ripetizione, di tipo Decryption
+     *         in caso di errore di decifratura
+     */
+    std::vector<uint8_t> decryptFromPeer(const std::string& nodeId, const std::vector<uint8_t>& encryptedData,
+                                         const std::vector<uint8_t>& aad = {});
+    
+    /**
+     * @brief Ottiene la cache delle chiavi di sessione
+     */
+    SessionManager& getSessions();
+    
+    /**
+     * @brief Ottiene la cache delle chiavi di sessione (sola lettura)
+     */
+    const SessionManager& getSessions() const;
+    
     /**
      * @brief Ottiene la chiave pubblica per la firma
      * @return Chiave pubblica
      */
     std::vector<uint8_t> getPublicKey() const;
     
+    /**
+     * @brief Rigenera le chiavi di firma da una seed nota
+     *
+     * Permette a un nodo migrato su nuovo hardware di conservare l'identità
+     * con cui gli altri nodi lo hanno registrato.
+     *
+     * @param seed Seed Ed25519 dell'identità
+     */
+    void setIdentitySeed(const std::array<uint8_t, 32>& seed);
+    
+    /**
+     * @brief Ottiene le chiavi pubbliche fissate per i nodi noti
+     * @return Mappa ID nodo -> chiave pubblica (esclusi i nodi in conflitto)
+     */
+    std::map<std::string, std::vector<uint8_t>> getKnownPublicKeys() const;
+    
     /**
      * @brief Ottiene la chiave pubblica per lo scambio
+     *
+     * Le chiavi di scambio derivano da quelle d'identità: un peer ricava
+     * questa chiave da quella di firma che ha fissato per il nodo.
+     *
      * @return Chiave pubblica X25519
      */
     std::array<uint8_t, 32> getExchangePublicKey() const;
     
     /**
      * @brief Genera un token di sicurezza con data di scadenza
+     *
+     * Il token (formato versione 2) contiene un identificatore casuale,
+     * l'ambito, l'istante di emissione, la scadenza e il soggetto, firmati
+     * con la chiave d'identità del nodo che lo emette e poi cifrati.
+     *
      * @param nodeId ID del nodo
      * @param ttlSeconds Durata di validità in secondi
+     * @param scope Ambito di autorizzazione
      * @return Token cifrato
      * @throws CryptoError in caso di errore
      */
-    std::vector<uint8_t> generateSecurityToken(const std::string& nodeId, uint64_t ttlSeconds);
+    std::vector<uint8_t> generateSecurityToken(const std::string& nodeId, uint64_t ttlSeconds,
+                                               TokenScope scope = TokenScope::Admin);
     
     /**
-     * @brief Verifica un token di sicurezza
+     * @brief Verifica un token di sicurezza emesso da questo nodo
      * @param token Token cifrato
-     * @return Coppia con ID nodo e timestamp di scadenza
-     * @throws CryptoError se il token è invalido o scaduto
+     * @return Contenuto del token
+     * @throws CryptoError se il token è invalido, scaduto o revocato (anche tramite il suo soggetto)
+     */
+    SecurityToken verifySecurityToken(const std::vector<uint8_t>& token);
+
+    /**
+     * @brief Interpreta il contenuto in chiaro di un token, firma esclusa
+     *
+     * Non verifica né firma né scadenza: serve a verifySecurityToken dopo
+     * il controllo della firma e a chi esamina i token senza la chiave
+     * d'identità. Ogni campo è letto con controllo dei limiti.
+     *
+     * @param payload Versione, identificatore, ambito, emissione, scadenza e soggetto
+     * @return Contenuto del token
+     * @throws CryptoError (Verification) se il contenuto è troncato o la versione o l'ambito non sono validi
+     */
+    static SecurityToken decodeSecurityToken(const std::vector<uint8_t>& payload);
+
+    /**
+     * @brief Revoca un token prima della sua scadenza
+     * @param tokenId Identificatore del token (SecurityToken::tokenId)
+     */
+    void revokeSecurityToken(const std::string& tokenId);
+    
+    /**
+     * @brief Verifica se un token è stato revocato
+     * @param tokenId Identificatore del token
+     * @return true se il token è stato revocato
+     */
+    bool isTokenRevoked(const std::string& tokenId) const;
+    
+    /**
+     * @brief Sostituisce la lista di revoca consultata dalla verifica dei token
+     *
+     * Permette di condividere con il protocollo la lista diffusa dal
+     * Master; revokeSecurityToken() scrive nella stessa lista.
+     *
+     * @param list Lista di revoca (ignorata se nulla)
+     */
+    void setRevocationList(std::shared_ptr<RevocationList> list);
+    
+    /**
+     * @brief Ottiene la lista di revoca in uso
      */
-    std::pair<std::string, uint64_t> verifySecurityToken(const std::vector<uint8_t>& token);
+    std::shared_ptr<RevocationList> getRevocationList() const;
     
 private:
     // Chiave principale della rete
@@ -162,21 +775,149 @@ private:
     std::unique_ptr<SigningKeys> signingKeys;
     std::unique_ptr<ExchangeKeys> exchangeKeys;
     
+    // Cambi della chiave di rete, che invalidano le chiavi di sessione
+    uint32_t keyEpoch = 0;
+    
+    // Chiavi di sessione con i peer
+    SessionManager sessions;
+    
     // Chiavi note di altri nodi (ID nodo -> chiave pubblica)
     std::map<std::string, std::vector<uint8_t>> knownPublicKeys;
     
+    // Nodi in quarantena (ID nodo -> chiavi contese)
+    std::map<std::string, std::vector<std::vector<uint8_t>>> keyConflicts;
+    
+    // Nodi in quarantena per comportamento anomalo (ID nodo -> motivo)
+    std::map<std::string, std::string> imposedQuarantine;
+    
+    // Gestore degli eventi di sicurezza
+    SecurityEventHandler securityEventHandler;
+    
+    // Token e nodi revocati prima della scadenza
+    std::shared_ptr<RevocationList> revocations = std::make_shared<RevocationList>();
+    
+    // Stato anti-replay di un mittente
+    struct ReplayState {
+        // Prefisso dei nonce dell'istanza corrente del mittente
+        std::array<uint8_t, 4> salt;
+        
+        // Contatore più alto accettato
+        uint64_t highest = 0;
+        
+        // Contatori accettati dentro la finestra
+        std::set<uint64_t> seen;
+        
+        // Prefissi delle istanze precedenti, dal più vecchio
+        std::vector<std::array<uint8_t, 4>> retiredSalts;
+    };
+    
+    // Finestre anti-replay (ID nodo -> stato)
+    std::map<std::string, ReplayState> replayStates;
+    
+    // Ampiezza della finestra anti-replay
+    uint32_t replayWindow = DEFAULT_REPLAY_WINDOW;
+    
+    // Pacchetti rifiutati come ripetizioni
+    uint64_t replayRejections = 0;
+    
+    /**
+     * @brief Verifica e registra un nonce nella finestra del mittente
+     * @param senderId ID del nodo mittente
+     * @param nonce Nonce del pacchetto autenticato
+     * @return Motivo del rifiuto, o nullopt se il nonce è nuovo
+     */
+    std::optional<std::string> checkReplay(const std::string& senderId, const std::array<uint8_t, 12>& nonce);
+    
+    /**
+     * @brief Emette un evento di sicurezza
+     * @param event Evento da emettere
+     */
+    void emitSecurityEvent(const SecurityEvent& event);
+    
+    /**
+     * @brief Ricava le chiavi di scambio X25519 da quelle di firma
+     */
+    void deriveExchangeKeys();
+    
+    /**
+     * @brief Cifra un payload con AES-256-GCM e una chiave data
+     * @param key Chiave di cifratura
+     * @param payload Dati da cifrare
+     * @param aad Dati autenticati ma non cifrati
+     * @return Dati cifrati con nonce preposto
+     * @throws CryptoError in caso di errore di cifratura
+     */
+    std::vector<uint8_t> encryptWithKey(const std::array<uint8_t, 32>& key, const std::vector<uint8_t>& payload,
+                                        const std::vector<uint8_t>& aad);
+    
+    /**
+     * @brief Cifra un payload con AES-256-GCM, una chiave e un nonce dati
+     * @param key Chiave di cifratura
+     * @param nonce Nonce da non riusare con la stessa chiave
+     * @param payload Dati da cifrare
+     * @param aad Dati autenticati ma non cifrati
+     * @return Dati cifrati con nonce preposto
+     * @throws CryptoError in caso di errore di cifratura
+     */
+    static std::vector<uint8_t> sealWithNonce(const std::array<uint8_t, 32>& key,
+                                              const std::array<uint8_t, 12>& nonce,
+                                              const std::vector<uint8_t>& payload,
+                                              const std::vector<uint8_t>& aad);
+    
+    /**
+     * @brief Decifra un payload AES-256-GCM con una chiave data
+     * @param key Chiave di decifratura
+     * @param encryptedData Dati cifrati con nonce preposto
+     * @param aad Dati autenticati usati in cifratura
+     * @return Dati decifrati
+     * @throws CryptoError in caso di errore di decifratura
+     */
+    static std::vector<uint8_t> decryptWithKey(const std::array<uint8_t, 32>& key,
+                                               const std::vector<uint8_t>& encryptedData,
+                                               const std::vector<uint8_t>& aad);
+    
+    /**
+     * @brief Scarta un blob autentico già ricevuto dal mittente
+     * @param senderId ID del nodo mittente
+     * @param encryptedData Dati cifrati con nonce preposto, già verificati
+     * @throws CryptoError di tipo Replay se il nonce è già stato visto
+     */
+    void rejectReplay(const std::string& senderId, const std::vector<uint8_t>& encryptedData);
+    
+    // Sorgente casuale iniettata
+    std::shared_ptr<RandomSource> rng;
+    
+    // Orologio iniettato (quello di sistema se vuoto)
+    Clock clock;
+    
+    // Prefisso dei nonce, casuale per istanza o salvato nell'archivio dei contatori
+    std::array<uint8_t, 4> nonceSalt;
+    
     // Contatore per i nonce incrementali
     uint64_t nonceCounter;
     
+    // Primo contatore non riservato sull'archivio
+    uint64_t nonceLimit = UINT64_MAX;
+    
+    // Archivio dei contatori (nessuno se i prefissi sono casuali)
+    std::shared_ptr<NonceCounterStore> nonceStore;
+    
+    /**
+     * @brief Riserva dall'archivio il blocco successivo per la chiave di rete corrente
+     * @throws CryptoError di tipo Encryption se la riserva non riesce
+     */
+    void reserveNonces();
+    
     /**
      * @brief Ottiene il timestamp corrente in millisecondi
      * @return Timestamp in millisecondi
      */
-    static uint64_t currentTimestamp();
+    uint64_t currentTimestamp() const;
     
     /**
-     * @brief Genera un nonce unico basato sul timestamp e un contatore
+     * @brief Genera un nonce unico dal prefisso e da un contatore che non torna indietro
      * @return Nonce di 12 byte
+     * @throws CryptoError di tipo Encryption se i contatori sono esauriti o non possono essere riservati
      */
     std::array<uint8_t, 12> generateNonce();
 };
@@ -0,0 +1,158 @@
+#ifndef SABER_DEGRADATION_H
+#define SABER_DEGRADATION_H
+
+#include <cstdint>
+#include <optional>
+#include <string>
+
+#include "spec.h"
+
+namespace saber {
+
+/**
+ * @brief Gradini della scala di degrado, in ordine di intervento
+ *
+ * Ogni gradino mantiene le misure dei gradini precedenti.
+ */
+enum class DegradationLevel : uint8_t {
+    /// Parametri nominali
+    Nominal = 0,
+    /// Bitrate ridotto
+    ReducedBitrate,
+    /// Correzione d'errore in avanti attivata
+    StrongerFec,
+    /// Buffer aumentato (più latenza)
+    LargerBuffer,
+    /// Frequenza di campionamento della voce
+    VoiceSampleRate,
+    /// Sink peggiori sospesi
+    SuspendedSinks,
+};
+
+/// Gradino più alto della scala
+const DegradationLevel MAX_DEGRADATION_LEVEL = DegradationLevel::SuspendedSinks;
+
+/**
+ * @brief Converte un gradino nella sua rappresentazione testuale
+ * @param level Gradino
+ * @return Nome del gradino (es. "reduced_bitrate")
+ */
+std::string degradationLevelToString(DegradationLevel level);
+
+/**
+ * @brief Configurazione della scala di degrado
+ */
+struct DegradationConfig {
+    /// Abilita la scala di degrado sul Master
+    bool enabled = true;
+
+    /// Qualità della rete (0-100) sotto cui si sale di un gradino
+    uint32_t badQualityPercent = 30;
+
+    /// Qualità della rete (0-100) sopra cui si scende di un gradino
+    uint32_t goodQualityPercent = 50;
+
+    /// Durata della qualità scarsa prima di salire di un gradino (ms)
+    uint32_t escalateAfterMs = 5000;
+
+    /// Durata della qualità buona prima di scendere di un gradino (ms)
+    uint32_t recoverAfterMs = 15000;
+
+    /// Buffer aggiunto al gradino LargerBuffer (ms)
+    uint32_t bufferStepMs = 20;
+
+    /// Sink sospesi al gradino SuspendedSinks
+    uint32_t maxSuspendedSinks = 1;
+};
+
+/**
+ * @brief Parametri audio in vigore ad un gradino
+ */
+struct DegradationSettings {
+    /// Gradino corrente
+    DegradationLevel level = DegradationLevel::Nominal;
+
+    /// Bitrate del codec (kbps)
+    uint32_t bitrateKbps = 0;
+
+    /// Correzione d'errore in avanti
+    bool fecEnabled = false;
+
+    /// Buffer di riproduzione dei sink (ms)
+    uint32_t bufferMs = 0;
+
+    /// Frequenza di campionamento (Hz)
+    uint32_t sampleRateHz = 0;
+};
+
+/**
+ * @brief Scala di degrado sotto una rete persistentemente scarsa
+ *
+ * Il Master misura la qualità della rete e, se resta scarsa, sale un
+ * gradino alla volta: prima riduce il bitrate, poi attiva la FEC, poi
+ * aumenta il buffer, poi passa alla frequenza della voce e infine
+ * sospende i sink peggiori. Quando la qualità resta buona scende un
+ * gradino alla volta. Le due soglie e i due tempi di permanenza evitano
+ * oscillazioni tra gradini vicini.
+ */
+class DegradationLadder {
+public:
+    /**
+     * @brief Crea una scala di degrado
+     * @param config Soglie, tempi e passi della scala
+     */
+    explicit DegradationLadder(const DegradationConfig& config = DegradationConfig());
+
+    /**
+     * @brief Aggiorna la scala con una nuova misura di qualità
+     * @param quality Qualità della rete (0-1)
+     * @param nowMs Istante della misura (ms, orologio monotono)
+     * @return Nuovo gradino se è cambiato
+     */
+    std::optional<DegradationLevel> update(double quality, int64_t nowMs);
+
+    /**
+     * @brief Ottiene il gradino corrente
+     */
+    DegradationLevel getLevel() const;
+
+    /**
+     * @brief Parametri in vigore ad un gradino
+     * @param level Gradino
+     * @param isMusicMode true per la musica, false per la voce
+     * @param baseBufferMs Buffer nominale dei sink (ms)
+     * @param maxBufferMs Buffer massimo ammesso dal budget di latenza (ms)
+     * @param baseFec Correzione d'errore in avanti nominale
+     * @return Parametri audio del gradino
+     */
+    DegradationSettings settingsFor(DegradationLevel level, bool isMusicMode, uint32_t baseBufferMs,
+                                    uint32_t maxBufferMs, bool baseFec) const;
+
+    /**
+     * @brief Ottiene la configurazione della scala
+     */
+    const DegradationConfig& getConfig() const;
+
+    /**
+     * @brief Sostituisce soglie, tempi e passi mantenendo il gradino corrente
+     * @param config Nuova configurazione
+     */
+    void setConfig(const DegradationConfig& config);
+
+private:
+    /// Configurazione
+    DegradationConfig config;
+
+    /// Gradino corrente
+    DegradationLevel level;
+
+    /// Inizio della qualità scarsa in corso (ms)
+    std::optional<int64_t> badSinceMs;
+
+    /// Inizio della qualità buona in corso (ms)
+    std::optional<int64_t> goodSinceMs;
+};
+
+} // namespace saber
+
+#endif // SABER_DEGRADATION_H
@@ -0,0 +1,147 @@
+#ifndef SABER_DISCOVERY_H
+#define SABER_DISCOVERY_H
+
+#include <cstdint>
+#include <map>
+#include <mutex>
+#include <optional>
+#include <string>
+#include <vector>
+
+#include "mesh.h"
+
+namespace saber {
+
+/// UUID a 128 bit del servizio SABER annunciato via BLE
+constexpr const char* SABER_SERVICE_UUID = "5abe7001-8f4e-4c1d-9b2a-6d5e3c0a1f42";
+
+/// Versione del formato dei dati di servizio nell'annuncio
+const uint8_t DISCOVERY_VERSION = 1;
+
+/// Dimensione massima di un annuncio esteso BLE 5 in un solo PDU (byte)
+const size_t MAX_ADVERTISEMENT_BYTES = 254;
+
+/// Intervallo senza annunci oltre cui un nodo scoperto viene dimenticato (ms)
+const uint32_t DEFAULT_DISCOVERY_STALE_MS = 10000;
+
+/**
+ * @brief Contenuto dell'annuncio BLE di un nodo SABER
+ */
+struct DiscoveryAdvertisement {
+    /// ID del nodo che annuncia
+    std::string nodeId;
+
+    /// Ruolo del nodo
+    NodeRole role = NodeRole::Sink;
+};
+
+/**
+ * @brief Nodo SABER scoperto tramite i suoi annunci BLE
+ */
+struct DiscoveredNode {
+    /// ID del nodo
+    std::string nodeId;
+
+    /// Ruolo annunciato
+    NodeRole role = NodeRole::Sink;
+
+    /// Indirizzo BLE dell'ultimo annuncio (può cambiare con gli indirizzi privati)
+    std::string address;
+
+    /// RSSI dell'ultimo annuncio (dBm)
+    int32_t rssiDbm = 0;
+
+    /// Istante dell'ultimo annuncio in millisecondi
+    uint64_t lastSeenMs = 0;
+
+    /// Annunci ricevuti dal nodo
+    uint32_t advertisements = 0;
+};
+
+/**
+ * @brief Codifica l'annuncio di un nodo come dati di advertising BLE
+ *
+ * L'annuncio contiene i flag di visibilità e i dati di servizio associati
+ * al UUID SABER: versione del formato, ruolo e ID del nodo.
+ *
+ * @param advertisement Nodo da annunciare
+ * @return Strutture AD pronte per la radio
+ * @throws std::invalid_argument se l'ID è vuoto o non entra nell'annuncio
+ */
+std::vector<uint8_t> encodeAdvertisement(const DiscoveryAdvertisement& advertisement);
+
+/**
+ * @brief Interpreta i dati di advertising BLE ricevuti da una scansione
+ * @param data Strutture AD dell'annuncio
+ * @return Nodo annunciato, o nullopt se l'annuncio non è di un nodo SABER
+ */
+std::optional<DiscoveryAdvertisement> parseAdvertisement(const std::vector<uint8_t>& data);
+
+/**
+ * @brief Scoperta dei nodi vicini tramite gli annunci BLE
+ *
+ * La radio è gestita dall'host: trasmette periodicamente l'annuncio del
+ * nodo locale e riporta gli annunci ricevuti durante la scansione. La
+ * scoperta rende noti i nodi alla rete mesh ma non sostituisce la
+ * verifica crittografica dei loro pacchetti.
+ */
+class NodeDiscovery {
+public:
+    /**
+     * @brief Crea la scoperta per il nodo locale
+     * @param localNodeId ID del nodo locale, ignorato nelle scansioni
+     * @param localRole Ruolo annunciato dal nodo locale
+     * @param staleMs Intervallo senza annunci oltre cui un nodo viene dimenticato
+     * @throws std::invalid_argument se l'ID non entra nell'annuncio
+     */
+    NodeDiscovery(const std::string& localNodeId, NodeRole localRole,
+                  uint32_t staleMs = DEFAULT_DISCOVERY_STALE_MS);
+
+    /**
+     * @brief Ottiene l'annuncio da trasmettere per il nodo locale
+     */
+    const std::vector<uint8_t>& advertisement() const;
+
+    /**
+     * @brief Registra un annuncio ricevuto dalla scansione
+     * @param address Indirizzo BLE del mittente
+     * @param data Strutture AD dell'annuncio
+     * @param rssiDbm RSSI di ricezione (dBm)
+     * @param nowMs Istante di ricezione in millisecondi
+     * @return Nodo aggiornato, o nullopt se l'annuncio non è di un altro nodo SABER
+     */
+    std::optional<DiscoveredNode> observe(const std::string& address, const std::vector<uint8_t>& data,
+                                          int32_t rssiDbm, uint64_t nowMs);
+
+    /**
+     * @brief Dimentica i nodi senza annunci recenti
+     * @param nowMs Istante corrente in millisecondi
+     * @return ID dei nodi dimenticati
+     */
+    std::vector<std::string> expire(uint64_t nowMs);
+
+    /**
+     * @brief Ottiene i nodi scoperti, in ordine di ID
+     */
+    std::vector<DiscoveredNode> getNodes() const;
+
+private:
+    /// ID del nodo locale
+    std::string localNodeId;
+
+    /// Annuncio del nodo locale
+    std::vector<uint8_t> localAdvertisement;
+
+    /// Intervallo senza annunci oltre cui un nodo viene dimenticato
+    uint32_t staleMs;
+
+    /// Nodi scoperti per ID
+    std::map<std::string, DiscoveredNode> nodes;
+
+    /// Mutex per proteggere l'accesso concorrente
+    mutable std::mutex discoveryMutex;
+};
+
+} // namespace saber
+
+#endif // SABER_DISCOVERY_H
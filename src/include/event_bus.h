@@ -0,0 +1,203 @@
+#ifndef SABER_EVENT_BUS_H
+#define SABER_EVENT_BUS_H
+
+#include <condition_variable>
+#include <cstdint>
+#include <deque>
+#include <functional>
+#include <map>
+#include <memory>
+#include <mutex>
+#include <string>
+#include <thread>
+#include <vector>
+
+namespace saber {
+
+/**
+ * @brief Evento del protocollo notificato alle applicazioni
+ */
+struct ProtocolEvent {
+    enum class Type {
+        /// Un nodo è entrato nella rete o ha ripreso a rispondere
+        NodeJoined,
+        /// Un nodo ha smesso di rispondere
+        NodeLeft,
+        /// Il nodo locale ha perso la sincronizzazione con il Master
+        SyncLost,
+        /// La latenza di un nodo ha superato il budget
+        LatencyAlert
+    };
+
+    /// Tipo di evento
+    Type type = Type::NodeJoined;
+
+    /// Nodo coinvolto
+    std::string nodeId;
+
+    /// Latenza riportata dal nodo (solo LatencyAlert, ms)
+    uint32_t latencyMs = 0;
+
+    /// Budget di latenza superato (solo LatencyAlert, ms)
+    uint32_t budgetMs = 0;
+
+    /// Timestamp dell'evento in millisecondi
+    uint64_t timestamp = 0;
+};
+
+/**
+ * @brief Converte un tipo di evento in un nome stabile
+ */
+std::string protocolEventTypeToString(ProtocolEvent::Type type);
+
+/**
+ * @brief Interfaccia per ricevere gli eventi del protocollo da C++
+ *
+ * I metodi vengono chiamati dal thread del bus, mai dai thread della
+ * rete: possono bloccare senza rallentare la mesh, ma non devono
+ * attendere altri eventi.
+ */
+class EventHandler {
+public:
+    virtual ~EventHandler() = default;
+
+    /// Un nodo è entrato nella rete o ha ripreso a rispondere
+    virtual void onNodeJoined(const std::string& nodeId) { (void)nodeId; }
+
+    /// Un nodo ha smesso di rispondere
+    virtual void onNodeLeft(const std::string& nodeId) { (void)nodeId; }
+
+    /// Il nodo locale ha perso la sincronizzazione
+    virtual void onSyncLost() {}
+
+    /// La latenza di un nodo ha superato il budget
+    virtual void onLatencyAlert(const std::string& nodeId, uint32_t latencyMs, uint32_t budgetMs) {
+        (void)nodeId; (void)latencyMs; (void)budgetMs;
+    }
+};
+
+/**
+ * @brief Bus degli eventi con un thread di consegna dedicato
+ *
+ * Gli eventi pubblicati vengono accodati e consegnati in ordine dal
+ * thread del bus, così un gestore lento (ad esempio una funzione Python
+ * che deve acquisire il GIL) non blocca i thread della rete. La coda è
+ * limitata: oltre il limite gli eventi più vecchi vengono scartati.
+ */
+class EventBus {
+public:
+    /// Funzione invocata per ogni evento di un tipo
+    using Callback = std::function<void(const ProtocolEvent&)>;
+
+    /**
+     * @brief Crea il bus e avvia il thread di consegna
+     * @param maxPending Eventi in coda oltre i quali si scartano i più vecchi
+     */
+    explicit EventBus(size_t maxPending = 1024);
+
+    /**
+     * @brief Ferma il thread di consegna dopo aver consegnato gli eventi in coda
+     */
+    ~EventBus();
+
+    EventBus(const EventBus&) = delete;
+    EventBus& operator=(const EventBus&) = delete;
+
+    /**
+     * @brief Registra una funzione per un tipo di evento
+     * @param type Tipo di evento
+     * @param callback Funzione da invocare
+     * @return Identificatore della registrazione
+     */
+    uint32_t subscribe(ProtocolEvent::Type type, Callback callback);
+
+    /**
+     * @brief Registra un gestore per tutti i tipi di evento
+     * @param handler Gestore (il bus ne condivide la proprietà)
+     * @return Identificatore della registrazione
+     */
+    uint32_t addHandler(std::shared_ptr<EventHandler> handler);
+
+    /**
+     * @brief Annulla una registrazione
+     * @param id Identificatore restituito da subscribe() o addHandler()
+     * @return true se la registrazione esisteva
+     */
+    bool unsubscribe(uint32_t id);
+
+    /**
+     * @brief Accoda un evento per la consegna
+     *
+     * Non blocca e non chiama i gestori: può essere usato con i mutex
+     * della rete occupati.
+     *
+     * @param event Evento da consegnare
+     */
+    void publish(const ProtocolEvent& event);
+
+    /**
+     * @brief Attende che gli eventi accodati finora siano stati consegnati
+     */
+    void flush();
+
+    /**
+     * @brief Ottiene il numero di eventi scartati per coda piena
+     */
+    uint64_t getDroppedEvents() const;
+
+private:
+    /**
+     * @brief Registrazione di un gestore
+     */
+    struct Subscription {
+        /// Tipo di evento (ignorato per i gestori)
+        ProtocolEvent::Type type;
+        /// Funzione per un solo tipo di evento
+        Callback callback;
+        /// Gestore per tutti i tipi di evento
+        std::shared_ptr<EventHandler> handler;
+    };
+
+    /**
+     * @brief Ciclo del thread di consegna
+     */
+    void run();
+
+    /**
+     * @brief Consegna un evento ai gestori registrati
+     */
+    void deliver(const ProtocolEvent& event);
+
+    /// Limite della coda
+    size_t maxPending;
+
+    /// Eventi in attesa di consegna
+    std::deque<ProtocolEvent> pending;
+
+    /// Registrazioni attive
+    std::map<uint32_t, Subscription> subscriptions;
+
+    /// Prossimo identificatore di registrazione
+    uint32_t nextId = 1;
+
+    /// Eventi pubblicati e completati (consegnati o scartati), per flush()
+    uint64_t published = 0;
+    uint64_t delivered = 0;
+
+    /// Eventi scartati per coda piena
+    uint64_t dropped = 0;
+
+    /// Il thread di consegna deve terminare
+    bool stopping = false;
+
+    /// Thread di consegna
+    std::thread worker;
+
+    mutable std::mutex mutex;
+    std::condition_variable wakeup;
+    std::condition_variable idle;
+};
+
+} // namespace saber
+
+#endif // SABER_EVENT_BUS_H
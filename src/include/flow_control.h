@@ -0,0 +1,198 @@
+#ifndef SABER_FLOW_CONTROL_H
+#define SABER_FLOW_CONTROL_H
+
+#include <cstdint>
+#include <map>
+#include <mutex>
+#include <optional>
+#include <string>
+
+namespace saber {
+
+/// Intervallo tra i resoconti del buffer inviati dai sink (ms)
+constexpr uint32_t FLOW_STATUS_INTERVAL_MS = 1000;
+
+/**
+ * @brief Stato del controllo di flusso del Master
+ */
+enum class FlowState {
+    /// Buffer dei sink nella norma
+    Normal,
+    /// Almeno un sink ha poco margine: il bitrate viene ridotto
+    LowHeadroom,
+    /// Almeno un sink riceve i frame troppo in anticipo: i frame a bassa priorità vengono scartati
+    Overflow
+};
+
+/**
+ * @brief Converte uno stato del controllo di flusso nella sua rappresentazione testuale
+ * @param state Stato
+ * @return Nome dello stato ("normal", "low_headroom" o "overflow")
+ */
+std::string flowStateToString(FlowState state);
+
+/**
+ * @brief Priorità dei frame di uno stream quando i buffer dei sink traboccano
+ */
+enum class FramePriority {
+    /// Primi frame scartati (es. musica di sottofondo)
+    Low,
+    /// Priorità di default
+    Normal,
+    /// Mai scartati (es. annunci)
+    High
+};
+
+/**
+ * @brief Converte una priorità nella sua rappresentazione testuale
+ * @param priority Priorità
+ * @return Nome della priorità ("low", "normal" o "high")
+ */
+std::string framePriorityToString(FramePriority priority);
+
+/**
+ * @brief Converte una stringa in priorità
+ * @param name Nome della priorità
+ * @return Priorità, o std::nullopt se il nome non è valido
+ */
+std::optional<FramePriority> framePriorityFromString(const std::string& name);
+
+/**
+ * @brief Soglie del controllo di flusso
+ *
+ * Il riempimento riportato da un sink è la percentuale del buffer di
+ * jitter coperta dall'anticipo dell'ultimo frame ricevuto: sotto 100 il
+ * frame arriva con meno margine del buffer, sopra 100 arriva prima che il
+ * buffer possa contenerlo.
+ */
+struct FlowConfig {
+    /// Controllo di flusso attivo sul Master
+    bool enabled = true;
+
+    /// Riempimento sotto cui un sink ha poco margine (percentuale)
+    uint32_t lowWatermarkPercent = 30;
+
+    /// Riempimento oltre cui il buffer di un sink trabocca (percentuale)
+    uint32_t highWatermarkPercent = 150;
+
+    /// Tempo con i buffer nella norma prima di tornare allo stato normale (ms)
+    uint32_t recoverAfterMs = 3000;
+
+    /// Età oltre cui il resoconto di un sink non conta più (ms)
+    uint32_t reportTimeoutMs = 3000;
+};
+
+/**
+ * @brief Stato del controllo di flusso
+ */
+struct FlowStatus {
+    /// Stato corrente
+    FlowState state = FlowState::Normal;
+
+    /// Ultimo riempimento riportato dai sink con un resoconto recente (percentuale)
+    std::map<std::string, uint8_t> bufferLevels;
+
+    /// Frame scartati per priorità dall'avvio
+    std::map<FramePriority, uint64_t> droppedFrames;
+
+    /// Cambi di stato dall'avvio
+    uint64_t transitions = 0;
+};
+
+/**
+ * @brief Controllo di flusso del Master guidato dal riempimento dei buffer dei sink
+ *
+ * I sink riportano il riempimento del proprio buffer nei pacchetti Status.
+ * Se un sink ha poco margine il Master riduce il bitrate, così i frame
+ * occupano meno tempo d'aria e arrivano prima; se un sink riceve i frame
+ * oltre la capacità del buffer il Master scarta i frame degli stream a
+ * bassa priorità. Gli stati peggiori si applicano subito, il ritorno allo
+ * stato normale solo dopo recoverAfterMs con tutti i buffer nella norma.
+ * Un margine scarso prevale su un buffer che trabocca, perché produce
+ * buchi udibili.
+ */
+class FlowController {
+public:
+    /**
+     * @brief Crea il controllore
+     * @param config Soglie del controllo di flusso
+     */
+    explicit FlowController(const FlowConfig& config = {});
+
+    /**
+     * @brief Aggiorna le soglie
+     */
+    void setConfig(const FlowConfig& config);
+
+    /**
+     * @brief Ottiene le soglie in uso
+     */
+    FlowConfig getConfig() const;
+
+    /**
+     * @brief Registra il riempimento riportato da un sink
+     * @param nodeId Sink che ha inviato il resoconto
+     * @param levelPercent Riempimento del buffer (0 = sink senza audio, resoconto ignorato)
+     * @param nowMs Istante corrente in millisecondi
+     */
+    void reportBuffer(const std::string& nodeId, uint8_t levelPercent, uint64_t nowMs);
+
+    /**
+     * @brief Rivaluta lo stato sui resoconti recenti
+     * @param nowMs Istante corrente in millisecondi
+     * @return Nuovo stato, se è cambiato
+     */
+    std::optional<FlowState> update(uint64_t nowMs);
+
+    /**
+     * @brief Ottiene lo stato corrente
+     */
+    FlowState getState() const;
+
+    /**
+     * @brief Decide se trasmettere un frame nello stato corrente
+     * @param priority Priorità dello stream del frame
+     * @return false se il frame va scartato (contato tra gli scarti)
+     */
+    bool admit(FramePriority priority);
+
+    /**
+     * @brief Ottiene stato, riempimenti e scarti
+     * @param nowMs Istante corrente in millisecondi
+     */
+    FlowStatus getStatus(uint64_t nowMs) const;
+
+private:
+    /**
+     * @brief Ultimo resoconto di un sink
+     */
+    struct Report {
+        uint8_t levelPercent = 0;
+        uint64_t receivedAtMs = 0;
+    };
+
+    /// Soglie in uso
+    FlowConfig config;
+
+    /// Ultimo resoconto per sink
+    std::map<std::string, Report> reports;
+
+    /// Stato corrente
+    FlowState state = FlowState::Normal;
+
+    /// Inizio del periodo con i buffer nella norma (ms)
+    std::optional<uint64_t> normalSinceMs;
+
+    /// Frame scartati per priorità
+    std::map<FramePriority, uint64_t> dropped;
+
+    /// Cambi di stato
+    uint64_t transitions = 0;
+
+    /// Mutex per proteggere l'accesso concorrente
+    mutable std::mutex flowMutex;
+};
+
+} // namespace saber
+
+#endif // SABER_FLOW_CONTROL_H
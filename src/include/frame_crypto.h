@@ -0,0 +1,125 @@
+#ifndef SABER_FRAME_CRYPTO_H
+#define SABER_FRAME_CRYPTO_H
+
+#include <cstdint>
+#include <memory>
+#include <optional>
+#include <string>
+#include <vector>
+
+namespace saber {
+
+class MeshCrypto;
+
+/**
+ * @brief Granularità della cifratura dei frame audio
+ */
+enum class EncryptionGranularity : uint8_t {
+    /// Ogni frame audio (10 ms) è cifrato separatamente: perdita isolata per frame
+    PerFrame = 0,
+    /// I frame vengono aggregati e cifrati insieme: un solo nonce e tag per trasporto
+    PerTransportFrame = 1
+};
+
+/// Capacità: il nodo sa aprire frame cifrati singolarmente
+const uint8_t CAP_ENCRYPT_PER_FRAME = 0x01;
+
+/// Capacità: il nodo sa aprire frame di trasporto cifrati in blocco
+const uint8_t CAP_ENCRYPT_TRANSPORT = 0x02;
+
+/// Byte aggiunti da ogni cifratura AES-256-GCM (nonce + tag)
+const size_t AEAD_OVERHEAD_BYTES = 12 + 16;
+
+/**
+ * @brief Converte una granularità nel nome usato in configurazione
+ * @param granularity Granularità
+ * @return "per_frame" o "transport"
+ */
+std::string encryptionGranularityToString(EncryptionGranularity granularity);
+
+/**
+ * @brief Interpreta il nome di una granularità
+ * @param value "per_frame" o "transport"
+ * @return Granularità, o nullopt se il nome non è valido
+ */
+std::optional<EncryptionGranularity> encryptionGranularityFromString(const std::string& value);
+
+/**
+ * @brief Restituisce il flag di capacità corrispondente a una granularità
+ */
+uint8_t capabilityFor(EncryptionGranularity granularity);
+
+/**
+ * @brief Sceglie la granularità supportata da entrambi i nodi
+ *
+ * Viene usata la granularità preferita se entrambi la supportano,
+ * altrimenti si ripiega sull'altra.
+ *
+ * @param localCaps Capacità del nodo locale
+ * @param peerCaps Capacità del nodo remoto
+ * @param preferred Granularità preferita
+ * @return Granularità concordata, o nullopt se non ce n'è una comune
+ */
+std::optional<EncryptionGranularity> negotiateGranularity(uint8_t localCaps, uint8_t peerCaps,
+                                                          EncryptionGranularity preferred);
+
+/**
+ * @brief Cifra e decifra i frame audio aggregati in un frame di trasporto
+ *
+ * Formato del frame di trasporto (big-endian):
+ * granularità (u8), numero di frame (u16), poi
+ * - PerFrame: un blob cifrato per frame (u32 lunghezza + dati)
+ * - PerTransportFrame: un solo blob cifrato contenente i frame con prefisso di lunghezza
+ */
+class AudioFrameSealer {
+public:
+    /**
+     * @brief Crea un sigillatore
+     * @param crypto Gestore crittografico con la chiave di rete
+     * @param granularity Granularità di cifratura in trasmissione
+     */
+    AudioFrameSealer(std::shared_ptr<MeshCrypto> crypto, EncryptionGranularity granularity);
+
+    /**
+     * @brief Aggrega e cifra un gruppo di frame audio
+     * @param frames Frame codificati da trasmettere
+     * @return Frame di trasporto
+     * @throws CryptoError in caso di errore di cifratura
+     */
+    std::vector<uint8_t> seal(const std::vector<std::vector<uint8_t>>& frames) const;
+
+    /**
+     * @brief Decifra un frame di trasporto, qualunque granularità abbia usato il mittente
+     * @param transportFrame Frame di trasporto ricevuto
+     * @param senderId Mittente del frame; se indicato le ripetizioni vengono rifiutate
+     * @return Frame audio contenuti
+     * @throws CryptoError se la decifratura fallisce o il frame è ripetuto
+     * @throws std::out_of_range se il frame di trasporto è troncato
+     */
+    std::vector<std::vector<uint8_t>> open(const std::vector<uint8_t>& transportFrame,
+                                           const std::string& senderId = "") const;
+
+    /**
+     * @brief Ottiene la granularità usata in trasmissione
+     */
+    EncryptionGranularity getGranularity() const;
+
+    /**
+     * @brief Calcola i byte di overhead di un frame di trasporto rispetto ai frame in chiaro
+     * @param granularity Granularità di cifratura
+     * @param frameCount Frame aggregati nel trasporto
+     * @return Byte aggiuntivi (intestazione, prefissi di lunghezza, nonce e tag)
+     */
+    static size_t overheadBytes(EncryptionGranularity granularity, size_t frameCount);
+
+private:
+    /// Gestore crittografico condiviso
+    std::shared_ptr<MeshCrypto> crypto;
+
+    /// Granularità in trasmissione
+    EncryptionGranularity granularity;
+};
+
+} // namespace saber
+
+#endif // SABER_FRAME_CRYPTO_H
@@ -0,0 +1,119 @@
+#ifndef SABER_HTTP_SERVER_H
+#define SABER_HTTP_SERVER_H
+
+#include <atomic>
+#include <functional>
+#include <map>
+#include <memory>
+#include <mutex>
+#include <string>
+#include <thread>
+
+namespace saber {
+
+/**
+ * @brief Risposta HTTP prodotta da un handler
+ */
+struct HttpResponse {
+    /// Codice di stato HTTP
+    int status;
+    
+    /// Content-Type della risposta
+    std::string contentType;
+    
+    /// Corpo della risposta
+    std::string body;
+};
+
+/**
+ * @brief Server HTTP minimale per endpoint di servizio (solo GET)
+ *
+ * Pensato per endpoint di monitoraggio a basso traffico: gestisce una
+ * connessione alla volta su un thread dedicato e chiude la connessione
+ * dopo ogni risposta. Un client che non invia la richiesta entro un
+ * secondo viene chiuso, così non blocca gli altri controlli né l'arresto.
+ *
+ * Serve solo HTTP in chiaro: le risposte non contengono segreti e, dove
+ * serve HTTPS, la terminazione TLS è lasciata al proxy o al sidecar
+ * dell'orchestratore davanti al nodo.
+ */
+class HttpServer {
+public:
+    /**
+     * @brief Tipo di callback per la gestione di una rotta
+     */
+    using RouteHandler = std::function<HttpResponse()>;
+    
+    /**
+     * @brief Crea un nuovo server HTTP
+     * @param bindAddress Indirizzo IPv4 su cui mettersi in ascolto
+     * @param port Porta TCP
+     */
+    HttpServer(const std::string& bindAddress, uint16_t port);
+    
+    /**
+     * @brief Distruttore, ferma il server se in esecuzione
+     */
+    ~HttpServer();
+    
+    /**
+     * @brief Registra un handler per un percorso
+     * @param path Percorso (es. "/healthz")
+     * @param handler Funzione che produce la risposta
+     */
+    void addRoute(const std::string& path, RouteHandler handler);
+    
+    /**
+     * @brief Avvia il server
+     * @return true se il socket è stato aperto correttamente, false altrimenti
+     */
+    bool start();
+    
+    /**
+     * @brief Ferma il server
+     */
+    void stop();
+    
+    /**
+     * @brief Verifica se il server è in esecuzione
+     * @return true se il server è in esecuzione, false altrimenti
+     */
+    bool isRunning() const;
+    
+private:
+    /// Indirizzo di ascolto
+    std::string bindAddress;
+    
+    /// Porta di ascolto
+    uint16_t port;
+    
+    /// Socket di ascolto
+    intptr_t listenSocket;
+    
+    /// Flag per il thread del server
+    std::atomic<bool> running;
+    
+    /// Thread di accettazione delle connessioni
+    std::unique_ptr<std::thread> serverThread;
+    
+    /// Rotte registrate
+    std::map<std::string, RouteHandler> routes;
+    
+    /// Mutex per le rotte
+    mutable std::mutex routesMutex;
+    
+    /**
+     * @brief Loop di accettazione delle connessioni
+     */
+    void runServerLoop();
+    
+    /**
+     * @brief Gestisce una singola connessione
+     * @param clientSocket Socket del client
+     */
+    void handleConnection(intptr_t clientSocket);
+};
+
+} // namespace saber
+
+#endif // SABER_HTTP_SERVER_H
@@ -0,0 +1,100 @@
+#ifndef SABER_INTERCOM_H
+#define SABER_INTERCOM_H
+
+#include <cstdint>
+#include <optional>
+#include <set>
+#include <string>
+
+#include "codec.h"
+#include "spec.h"
+
+namespace saber {
+
+using StreamId = uint16_t;
+
+/// Primo dei due stream riservati all'intercom, uno per verso
+const StreamId INTERCOM_STREAM_BASE = 0xFFF0;
+
+/// Frequenza di campionamento della voce nell'intercom (Hz, mono)
+const uint32_t INTERCOM_SAMPLE_RATE_HZ = spec::SAMPLE_RATE_VOICE_HZ;
+
+/**
+ * @brief Parametri della modalità intercom
+ *
+ * L'intercom privilegia la latenza rispetto alla qualità: voce a 16kHz
+ * mono in frame LC3 da 10ms a bitrate ridotto (40 byte a 32 kbps) e un
+ * buffer minimo. Durante una conversazione il Master infittisce i beacon.
+ */
+struct IntercomConfig {
+    /// Bitrate della voce (kbps)
+    uint32_t bitrateKbps = spec::BITRATE_VOICE_REDUCED_KBPS;
+
+    /// Anticipo con cui la voce viene riprodotta dall'altro nodo (ms)
+    uint32_t bufferMs = 10;
+
+    /// Intervallo tra beacon del Master durante una conversazione (ms)
+    uint32_t beaconIntervalMs = spec::BEACON_INTERVAL_MS / 2;
+
+    /// Attenuazione della musica sui nodi coinvolti mentre qualcuno parla (dB, 0 = nessuna)
+    uint32_t duckingDb = 18;
+};
+
+/**
+ * @brief Conversazione intercom in duplex tra due nodi
+ *
+ * Ogni nodo parla sul proprio stream e ascolta quello dell'altro; la
+ * voce viene trasmessa solo mentre il push-to-talk è premuto.
+ */
+struct IntercomSession {
+    /// Primo nodo, che parla sullo stream INTERCOM_STREAM_BASE
+    std::string nodeA;
+
+    /// Secondo nodo, che parla sullo stream INTERCOM_STREAM_BASE + 1
+    std::string nodeB;
+
+    /// Nodi con il push-to-talk premuto
+    std::set<std::string> talking;
+
+    /// Istante di inizio sull'orologio sincronizzato (ms)
+    uint64_t startedAtMs = 0;
+
+    /**
+     * @brief Verifica se un nodo partecipa alla conversazione
+     */
+    bool involves(const std::string& nodeId) const;
+
+    /**
+     * @brief Stream su cui parla un nodo
+     * @return Stream, o nullopt se il nodo non partecipa
+     */
+    std::optional<StreamId> talkStream(const std::string& nodeId) const;
+
+    /**
+     * @brief Stream che un nodo ascolta
+     * @return Stream dell'altro nodo, o nullopt se il nodo non partecipa
+     */
+    std::optional<StreamId> listenStream(const std::string& nodeId) const;
+
+    /**
+     * @brief Nodo che parla su uno stream dell'intercom
+     * @return ID del nodo, o nullopt se lo stream non è dell'intercom
+     */
+    std::optional<std::string> speakerOf(StreamId streamId) const;
+};
+
+/**
+ * @brief Verifica se uno stream è riservato all'intercom
+ */
+bool isIntercomStream(StreamId streamId);
+
+/**
+ * @brief Attenua un frame PCM (ducking della musica)
+ * @param frame Frame da attenuare
+ * @param attenuationDb Attenuazione in dB (0 lascia il frame invariato)
+ */
+void duckFrame(AudioFrame& frame, uint32_t attenuationDb);
+
+} // namespace saber
+
+#endif // SABER_INTERCOM_H
@@ -0,0 +1,156 @@
+#ifndef SABER_INTRUSION_H
+#define SABER_INTRUSION_H
+
+#include <cstdint>
+#include <deque>
+#include <functional>
+#include <map>
+#include <mutex>
+#include <optional>
+#include <string>
+#include <utility>
+#include <vector>
+
+namespace saber {
+
+/**
+ * @brief Soglie delle euristiche di rilevamento delle intrusioni
+ *
+ * Ogni euristica scatta quando lo stesso nodo raggiunge la soglia di
+ * anomalie dentro la finestra; una soglia a 0 disattiva l'euristica.
+ */
+struct IntrusionConfig {
+    /// Abilita il rilevamento
+    bool enabled = true;
+
+    /// Finestra in cui si contano le anomalie di un nodo (ms)
+    uint32_t windowMs = 10000;
+
+    /// Beacon temporali inviati da un nodo che non è il Master
+    uint32_t beaconThreshold = 3;
+
+    /// Pacchetti con firma non valida
+    uint32_t signatureFailureThreshold = 5;
+
+    /// Nonce già usati (pacchetti ripetuti)
+    uint32_t replayThreshold = 10;
+
+    /// Cambi di collegamento verso lo stesso peer
+    uint32_t routeFlapThreshold = 6;
+
+    /// Mette in quarantena il nodo che fa scattare un'euristica
+    bool autoQuarantine = false;
+};
+
+/**
+ * @brief Anomalia del traffico mesh attribuita a un nodo
+ */
+struct IntrusionAlert {
+    enum class Type {
+        /// Beacon temporali da un nodo che non è il Master
+        RogueBeacon,
+        /// Raffica di firme non valide
+        SignatureBurst,
+        /// Tentativi ripetuti di riuso dei nonce
+        NonceReuse,
+        /// Collegamento che cambia continuamente
+        RouteFlapping
+    };
+
+    /// Euristica che è scattata
+    Type type;
+
+    /// Nodo responsabile
+    std::string nodeId;
+
+    /// Anomalie contate nella finestra
+    uint32_t count = 0;
+
+    /// Istante dell'allarme in millisecondi
+    uint64_t timestamp = 0;
+
+    /// Il nodo deve essere messo in quarantena
+    bool quarantine = false;
+};
+
+/**
+ * @brief Converte il tipo di un allarme in un nome stabile
+ */
+std::string intrusionAlertTypeToString(IntrusionAlert::Type type);
+
+/// Allarmi conservati dal rilevatore
+const size_t MAX_INTRUSION_ALERTS = 256;
+
+/**
+ * @brief Rilevatore leggero di intrusioni sul traffico mesh
+ *
+ * La rete segnala firme non valide e beacon dei nodi non Master, il
+ * protocollo segnala i nonce ripetuti e i cambi di collegamento. Dopo
+ * un allarme il conteggio del nodo riparte da zero, così un attacco
+ * continuo produce un allarme per finestra invece che per pacchetto.
+ * Il rilevatore è condiviso tra i thread ed è protetto da un mutex.
+ */
+class IntrusionDetector {
+public:
+    /// Gestore degli allarmi, chiamato senza lock interni
+    using AlertHandler = std::function<void(const IntrusionAlert&)>;
+
+    /**
+     * @brief Crea un rilevatore
+     * @param config Soglie delle euristiche
+     */
+    explicit IntrusionDetector(const IntrusionConfig& config = IntrusionConfig());
+
+    /**
+     * @brief Registra un'anomalia di un nodo
+     * @param type Euristica interessata
+     * @param nodeId Nodo responsabile
+     * @param nowMs Istante dell'anomalia in millisecondi
+     * @return Allarme se il nodo ha raggiunto la soglia
+     */
+    std::optional<IntrusionAlert> observe(IntrusionAlert::Type type, const std::string& nodeId, uint64_t nowMs);
+
+    /**
+     * @brief Imposta il gestore degli allarmi
+     * @param handler Funzione chiamata ad ogni allarme (vuota per disattivare)
+     */
+    void setAlertHandler(AlertHandler handler);
+
+    /**
+     * @brief Sostituisce le soglie, azzerando i conteggi in corso
+     */
+    void setConfig(const IntrusionConfig& config);
+
+    /**
+     * @brief Ottiene le soglie in uso
+     */
+    IntrusionConfig getConfig() const;
+
+    /**
+     * @brief Ottiene gli allarmi più recenti, dal più vecchio
+     */
+    std::vector<IntrusionAlert> getAlerts() const;
+
+private:
+    /// Soglia dell'euristica (0 = disattivata)
+    uint32_t thresholdFor(IntrusionAlert::Type type) const;
+
+    /// Soglie in uso
+    IntrusionConfig config;
+
+    /// Istanti delle anomalie recenti per euristica e nodo
+    std::map<std::pair<IntrusionAlert::Type, std::string>, std::deque<uint64_t>> observations;
+
+    /// Allarmi recenti
+    std::deque<IntrusionAlert> alerts;
+
+    /// Gestore degli allarmi
+    AlertHandler alertHandler;
+
+    /// Mutex per proteggere l'accesso concorrente
+    mutable std::mutex detectorMutex;
+};
+
+} // namespace saber
+
+#endif // SABER_INTRUSION_H
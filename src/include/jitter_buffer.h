@@ -0,0 +1,180 @@
+#ifndef SABER_JITTER_BUFFER_H
+#define SABER_JITTER_BUFFER_H
+
+#include <cstdint>
+#include <map>
+#include <mutex>
+#include <optional>
+
+#include "spec.h"
+
+namespace saber {
+
+/**
+ * @brief Parametri del buffer di jitter adattivo
+ */
+struct JitterBufferConfig {
+    /// Ridimensiona il buffer durante la riproduzione (false = fisso all'avvio)
+    bool adaptive = true;
+
+    /// Buffer minimo (ms)
+    uint32_t minMs = spec::LC3_FRAME_DURATION_US / 1000;
+
+    /// Buffer massimo (ms); in modalità di latenza rigorosa è limitato al budget
+    uint32_t maxMs = spec::LATENCY_BUDGET_MS;
+
+    /// Passo di ogni ridimensionamento (ms)
+    uint32_t stepMs = spec::LC3_FRAME_DURATION_US / 1000;
+
+    /// Tempo senza buchi né crescita dopo cui il buffer si riduce di un passo (ms)
+    uint32_t shrinkAfterMs = 10000;
+
+    /// Perdita oltre cui il buffer cresce per lasciare tempo alle ritrasmissioni (%)
+    uint32_t maxLossPercent = 2;
+};
+
+/**
+ * @brief Stato del buffer di jitter
+ */
+struct JitterBufferStats {
+    /// Dimensione corrente (ms)
+    uint32_t bufferMs = 0;
+
+    /// Jitter stimato dei tempi di arrivo (ms)
+    double jitterMs = 0.0;
+
+    /// Anticipo dell'ultimo frame sul proprio istante di riproduzione (ms, negativo se in ritardo)
+    int64_t leadMs = 0;
+
+    /// Perdita nell'ultima finestra di frame (%)
+    double lossPercent = 0.0;
+
+    /// Frame ricevuti
+    uint64_t frames = 0;
+
+    /// Frame mai arrivati, dai buchi nei numeri di sequenza
+    uint64_t lost = 0;
+
+    /// Frame arrivati dopo il proprio istante di riproduzione (buffer vuoto)
+    uint64_t underruns = 0;
+
+    /// Frame arrivati in anticipo oltre la dimensione del buffer (buffer pieno)
+    uint64_t overruns = 0;
+
+    /// Ridimensionamenti dall'avvio della riproduzione
+    uint64_t resizes = 0;
+};
+
+/**
+ * @brief Buffer di jitter che si adatta alle condizioni della rete
+ *
+ * Il buffer contiene i frame dall'arrivo fino al loro istante di
+ * riproduzione. Ad ogni frame ricevuto il jitter dei tempi di arrivo
+ * viene stimato come in RTP (RFC 3550), confrontando l'intervallo tra gli
+ * arrivi con quello tra gli istanti di riproduzione. Il buffer cresce di
+ * un passo ad ogni frame arrivato in ritardo (buffer vuoto) o in anticipo
+ * oltre la sua dimensione (buffer pieno), quando il jitter stimato non ci
+ * sta più, o quando la perdita supera maxLossPercent; si riduce di un
+ * passo dopo shrinkAfterMs senza motivi per crescere. La dimensione resta
+ * sempre tra minMs e maxMs.
+ */
+class AdaptiveJitterBuffer {
+public:
+    /**
+     * @brief Crea il buffer
+     * @param config Parametri di adattamento
+     * @param bufferMs Dimensione iniziale (ms), limitata tra minMs e maxMs
+     */
+    explicit AdaptiveJitterBuffer(const JitterBufferConfig& config = {},
+                                  uint32_t bufferMs = spec::DEFAULT_BUFFER_MS);
+
+    /**
+     * @brief Aggiorna i parametri, limitando la dimensione corrente ai nuovi estremi
+     */
+    void setConfig(const JitterBufferConfig& config);
+
+    /**
+     * @brief Ottiene i parametri in uso
+     */
+    JitterBufferConfig getConfig() const;
+
+    /**
+     * @brief Riparte da una dimensione iniziale, azzerando stime e contatori
+     * @param bufferMs Dimensione iniziale (ms)
+     * @param pinned Dimensione imposta: i frame vengono contati ma il buffer non si adatta
+     */
+    void reset(uint32_t bufferMs, bool pinned = false);
+
+    /**
+     * @brief Registra l'arrivo di un frame
+     * @param streamId Stream del frame (i numeri di sequenza sono per stream)
+     * @param sequence Numero di sequenza del frame
+     * @param playoutTimeUs Istante di riproduzione del frame (µs)
+     * @param arrivalUs Istante di arrivo, sull'orologio sincronizzato (µs)
+     * @return Nuova dimensione (ms) se il buffer è stato ridimensionato
+     */
+    std::optional<uint32_t> recordFrame(StreamId streamId, uint32_t sequence, uint64_t playoutTimeUs,
+                                        uint64_t arrivalUs);
+
+    /**
+     * @brief Ottiene la dimensione corrente
+     * @return Dimensione in millisecondi
+     */
+    uint32_t getBufferMs() const;
+
+    /**
+     * @brief Ottiene dimensione, stime e contatori
+     */
+    JitterBufferStats getStats() const;
+
+private:
+    /**
+     * @brief Ultimo frame ricevuto di uno stream
+     */
+    struct StreamState {
+        uint32_t sequence = 0;
+        uint64_t playoutTimeUs = 0;
+        uint64_t arrivalUs = 0;
+    };
+
+    /**
+     * @brief Porta il buffer ad una nuova dimensione, entro gli estremi
+     * @return Nuova dimensione se è cambiata
+     */
+    std::optional<uint32_t> resizeTo(int64_t bufferMs, uint64_t nowUs);
+
+    /// Parametri in uso
+    JitterBufferConfig config;
+
+    /// Dimensione corrente (ms)
+    uint32_t bufferMs;
+
+    /// Dimensione imposta dall'esterno (es. modalità festa)
+    bool pinned = false;
+
+    /// Jitter stimato (µs)
+    double jitterUs = 0.0;
+
+    /// Ultimo frame per stream
+    std::map<StreamId, StreamState> streams;
+
+    /// Frame ricevuti e persi nella finestra di perdita corrente
+    uint32_t windowFrames = 0;
+    uint32_t windowLost = 0;
+
+    /// Ultima crescita o riduzione, o avvio (µs)
+    std::optional<uint64_t> lastChangeUs;
+
+    /// Anticipo massimo di un frame dall'ultima crescita o riduzione (µs)
+    int64_t maxLeadUs = 0;
+
+    /// Contatori dall'avvio
+    JitterBufferStats stats;
+
+    /// Mutex per proteggere l'accesso concorrente
+    mutable std::mutex bufferMutex;
+};
+
+} // namespace saber
+
+#endif // SABER_JITTER_BUFFER_H
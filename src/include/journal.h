@@ -0,0 +1,120 @@
+#ifndef SABER_JOURNAL_H
+#define SABER_JOURNAL_H
+
+#include <cstdint>
+#include <deque>
+#include <fstream>
+#include <mutex>
+#include <string>
+#include <vector>
+
+namespace saber {
+
+/**
+ * @brief Voce del diario degli eventi
+ */
+struct JournalEvent {
+    /// Cursore assegnato dal diario, strettamente crescente anche tra un riavvio e l'altro
+    uint64_t cursor = 0;
+
+    /// Timestamp dell'evento in millisecondi
+    uint64_t timestamp = 0;
+
+    /// Categoria ("mesh" o "security")
+    std::string category;
+
+    /// Tipo di evento (es. "node_lost", "key_conflict")
+    std::string type;
+
+    /// Nodo coinvolto
+    std::string nodeId;
+
+    /// Descrizione leggibile
+    std::string detail;
+};
+
+/**
+ * @brief Pagina di eventi restituita ad un client
+ */
+struct JournalPage {
+    /// Eventi successivi al cursore richiesto, in ordine
+    std::vector<JournalEvent> events;
+
+    /// Cursore da usare per la richiesta successiva
+    uint64_t nextCursor = 0;
+
+    /// Ci sono altri eventi oltre il limite della pagina
+    bool hasMore = false;
+
+    /// Alcuni eventi successivi al cursore sono già stati scartati dal diario
+    bool truncated = false;
+};
+
+/**
+ * @brief Diario persistente degli eventi della mesh e di sicurezza
+ *
+ * Ogni evento riceve un cursore crescente e viene aggiunto in coda al file;
+ * un client che si riconnette chiede gli eventi successivi all'ultimo
+ * cursore visto e non perde transizioni. Il diario conserva gli ultimi
+ * maxEntries eventi e compatta il file quando raddoppia.
+ */
+class EventJournal {
+public:
+    /**
+     * @brief Apre il diario, ricaricando gli eventi già su disco
+     * @param path File del diario (solo in memoria se vuoto)
+     * @param maxEntries Eventi conservati
+     */
+    explicit EventJournal(const std::string& path = "", size_t maxEntries = 10000);
+
+    /**
+     * @brief Aggiunge un evento
+     * @return Cursore assegnato all'evento
+     */
+    uint64_t append(const std::string& category, const std::string& type, const std::string& nodeId,
+                    const std::string& detail, uint64_t timestamp);
+
+    /**
+     * @brief Eventi successivi ad un cursore
+     * @param cursor Ultimo cursore visto dal client (0 per partire dall'inizio)
+     * @param limit Numero massimo di eventi restituiti
+     * @return Pagina di eventi
+     */
+    JournalPage since(uint64_t cursor, size_t limit = 256) const;
+
+    /**
+     * @brief Cursore dell'ultimo evento registrato (0 se il diario è vuoto)
+     */
+    uint64_t latestCursor() const;
+
+private:
+    /**
+     * @brief Riscrive il file con i soli eventi conservati (richiede journalMutex)
+     */
+    void compactLocked();
+
+    /// File del diario
+    std::string path;
+
+    /// Eventi conservati
+    size_t maxEntries;
+
+    /// Eventi in memoria, dal più vecchio
+    std::deque<JournalEvent> events;
+
+    /// Cursore del prossimo evento
+    uint64_t nextCursor;
+
+    /// Righe presenti nel file
+    size_t fileLines;
+
+    /// File aperto in aggiunta
+    std::ofstream file;
+
+    /// Mutex per il diario
+    mutable std::mutex journalMutex;
+};
+
+} // namespace saber
+
+#endif // SABER_JOURNAL_H
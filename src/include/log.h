@@ -0,0 +1,232 @@
+#ifndef SABER_LOG_H
+#define SABER_LOG_H
+
+#include <cstdint>
+#include <functional>
+#include <map>
+#include <mutex>
+#include <optional>
+#include <sstream>
+#include <string>
+
+namespace saber {
+
+/**
+ * @brief Livelli di log, dal meno al più verboso
+ */
+enum class LogLevel : uint8_t {
+    Off = 0,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace
+};
+
+/**
+ * @brief Converte un livello di log nel nome testuale
+ * @param level Livello
+ * @return Nome in minuscolo (es. "debug")
+ */
+std::string logLevelToString(LogLevel level);
+
+/**
+ * @brief Interpreta il nome di un livello di log
+ * @param value Nome del livello (es. "trace")
+ * @return Livello, o nullopt se il nome non è valido
+ */
+std::optional<LogLevel> logLevelFromString(const std::string& value);
+
+/**
+ * @brief Filtro dei log per target
+ *
+ * Sintassi: elenco separato da virgole di direttive "target=livello";
+ * una direttiva senza target imposta il livello predefinito.
+ * Esempio: "info,mesh=debug,sync=trace".
+ */
+class LogFilter {
+public:
+    /**
+     * @brief Crea un filtro con solo il livello predefinito
+     * @param defaultLevel Livello per i target non elencati
+     */
+    explicit LogFilter(LogLevel defaultLevel = LogLevel::Info);
+
+    /**
+     * @brief Interpreta una stringa di filtro
+     * @param spec Stringa di filtro (es. "mesh=debug,sync=trace")
+     * @return Filtro interpretato
+     * @throws std::invalid_argument se la stringa non è valida
+     */
+    static LogFilter parse(const std::string& spec);
+
+    /**
+     * @brief Imposta il livello di un target
+     * @param target Target del log (es. "mesh"); vuoto per il livello predefinito
+     * @param level Livello da abilitare
+     */
+    void setLevel(const std::string& target, LogLevel level);
+
+    /**
+     * @brief Ottiene il livello massimo abilitato per un target
+     * @param target Target del log (es. "mesh")
+     * @return Livello abilitato
+     */
+    LogLevel levelFor(const std::string& target) const;
+
+    /**
+     * @brief Verifica se un messaggio va emesso
+     * @param target Target del messaggio
+     * @param level Livello del messaggio
+     * @return true se il messaggio passa il filtro
+     */
+    bool enabled(const std::string& target, LogLevel level) const;
+
+    /**
+     * @brief Restituisce il filtro nella sintassi accettata da parse
+     */
+    std::string toString() const;
+
+private:
+    /// Livello per i target non elencati
+    LogLevel defaultLevel;
+
+    /// Livelli specifici per target
+    std::map<std::string, LogLevel> targets;
+};
+
+/**
+ * @brief Destinazione alternativa dei messaggi di log: (target, livello, messaggio)
+ */
+using LogSink = std::function<void(const std::string&, LogLevel, const std::string&)>;
+
+/**
+ * @brief Logger di processo con filtro modificabile a runtime
+ */
+class Logger {
+public:
+    /**
+     * @brief Ottiene l'istanza di processo
+     */
+    static Logger& instance();
+
+    /**
+     * @brief Sostituisce il filtro attivo
+     * @param filter Nuovo filtro
+     */
+    void setFilter(const LogFilter& filter);
+
+    /**
+     * @brief Ottiene il filtro attivo
+     */
+    LogFilter getFilter() const;
+
+    /**
+     * @brief Cambia il livello di un solo target lasciando invariati gli altri
+     * @param target Target del log; vuoto per il livello predefinito
+     * @param level Nuovo livello
+     */
+    void setLevel(const std::string& target, LogLevel level);
+
+    /**
+     * @brief Inoltra i messaggi ad una destinazione al posto di stdout/stderr
+     * @param sink Destinazione, o nullptr per tornare all'uscita standard
+     *
+     * La destinazione viene chiamata fuori dal lock del logger, dal thread
+     * che emette il messaggio.
+     */
+    void setSink(LogSink sink);
+
+    /**
+     * @brief Verifica se un messaggio va emesso con il filtro attivo
+     */
+    bool enabled(const std::string& target, LogLevel level) const;
+
+    /**
+     * @brief Emette un messaggio (errori e avvisi su stderr, il resto su stdout)
+     *
+     * Il messaggio viene preceduto dagli span aperti nel thread corrente.
+     * @param target Target del messaggio
+     * @param level Livello del messaggio
+     * @param message Testo del messaggio
+     */
+    void write(const std::string& target, LogLevel level, const std::string& message);
+
+private:
+    Logger() = default;
+
+    /// Filtro attivo
+    LogFilter filter;
+
+    /// Destinazione alternativa dei messaggi
+    LogSink sink;
+
+    /// Mutex per filtro e output
+    mutable std::mutex logMutex;
+};
+
+/**
+ * @brief Span di log: contesto che precede i messaggi emessi nel thread finché resta aperto
+ *
+ * Gli span si annidano; ognuno compare come "nome{campi}: " davanti ai messaggi.
+ * Da usare tramite SABER_LOG_SPAN, che evita di formattare i campi se il target
+ * non è abilitato a livello Debug.
+ */
+class LogSpan {
+public:
+    /**
+     * @brief Apre uno span nel thread corrente
+     * @param name Nome dello span (es. "packet")
+     * @param fields Campi già formattati (es. "type=ping source=sink-1")
+     */
+    LogSpan(const std::string& name, const std::string& fields);
+
+    /**
+     * @brief Chiude lo span
+     */
+    ~LogSpan();
+
+    LogSpan(const LogSpan&) = delete;
+    LogSpan& operator=(const LogSpan&) = delete;
+
+    /**
+     * @brief Contesto degli span aperti nel thread corrente
+     * @return Prefisso da anteporre ai messaggi, vuoto se non ci sono span
+     */
+    static std::string current();
+};
+
+} // namespace saber
+
+/**
+ * @brief Apre uno span di log fino alla fine del blocco corrente
+ *
+ * Esempio: SABER_LOG_SPAN("mesh", "packet", "source=" << source << " seq=" << seq);
+ */
+#define SABER_LOG_SPAN_NAME2(line) saberLogSpan##line
+#define SABER_LOG_SPAN_NAME(line) SABER_LOG_SPAN_NAME2(line)
+#define SABER_LOG_SPAN(target, name, fields)                                           \
+    std::optional<saber::LogSpan> SABER_LOG_SPAN_NAME(__LINE__);                       \
+    if (saber::Logger::instance().enabled(target, saber::LogLevel::Debug)) {           \
+        std::ostringstream saberSpanStream;                                            \
+        saberSpanStream << fields;                                                     \
+        SABER_LOG_SPAN_NAME(__LINE__).emplace(name, saberSpanStream.str());            \
+    }
+
+/**
+ * @brief Emette un messaggio di log se abilitato dal filtro attivo
+ *
+ * Il messaggio viene formattato solo se il filtro lo lascia passare.
+ * Esempio: SABER_LOG(Debug, "mesh", "pacchetto da " << source);
+ */
+#define SABER_LOG(level, target, message)                                              \
+    do {                                                                               \
+        if (saber::Logger::instance().enabled(target, saber::LogLevel::level)) {      \
+            std::ostringstream saberLogStream;                                         \
+            saberLogStream << message;                                                 \
+            saber::Logger::instance().write(target, saber::LogLevel::level,            \
+                                            saberLogStream.str());                     \
+        }                                                                              \
+    } while (0)
+
+#endif // SABER_LOG_H
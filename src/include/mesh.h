@@ -1,19 +1,38 @@
 #ifndef SABER_MESH_H
 #define SABER_MESH_H
 
+#include <array>
+#include <atomic>
 #include <chrono>
 #include <condition_variable>
+#include <deque>
 #include <functional>
+#include <future>
 #include <map>
 #include <memory>
 #include <mutex>
 #include <optional>
+#include <set>
 #include <string>
 #include <thread>
+#include <tuple>
 #include <vector>
 
+#include "intrusion.h"
+#include "provisioning.h"
+#include "repair.h"
+#include "spec.h"
+#include "stats.h"
+#include "timestamp.h"
+#include "timing.h"
+#include "tracing.h"
+#include "transport.h"
+
 namespace saber {
 
+class MeshCrypto;
+struct SessionStats;
+
 /**
  * @brief Definizione dei ruoli dei nodi nella rete mesh
  */
@@ -26,6 +45,32 @@ enum class NodeRole {
     Sink
 };
 
+/**
+ * @brief Converte un ruolo nella sua rappresentazione testuale
+ * @param role Ruolo del nodo
+ * @return Nome del ruolo ("master", "repeater", "sink")
+ */
+std::string nodeRoleToString(NodeRole role);
+
+/**
+ * @brief Interpreta il nome di un ruolo (senza distinzione maiuscole/minuscole)
+ * @param name Nome del ruolo
+ * @return Ruolo, o nullopt se il nome non è valido
+ */
+std::optional<NodeRole> nodeRoleFromString(const std::string& name);
+
+/// RSSI a cui un collegamento è al limite della portata e la sua qualità dimezza (dBm)
+constexpr int32_t LINK_RSSI_WEAK_DBM = -90;
+
+/// RSSI da cui il segnale non penalizza più la qualità di un collegamento (dBm)
+constexpr int32_t LINK_RSSI_STRONG_DBM = -60;
+
+/// Peso di una nuova misura nella media del tasso di consegna di un collegamento
+constexpr double LINK_SUCCESS_SMOOTHING = 0.3;
+
+/// Qualità minima nel costo di un collegamento: anche il peggiore resta un'ultima risorsa
+constexpr double MIN_LINK_QUALITY = 0.05;
+
 /**
  * @brief Struttura dati che rappresenta un nodo nella rete mesh
  */
@@ -61,12 +106,75 @@ public:
      */
     uint32_t getLatency() const;
     
+    /**
+     * @brief Ottiene lo stato del buffer riportato dal nodo
+     * @return Percentuale di buffer disponibile (0-100)
+     */
+    uint8_t getBufferState() const;
+    
+    /**
+     * @brief Imposta la potenza del segnale ricevuto dal nodo
+     * @param rssiDbm RSSI in dBm
+     */
+    void setSignalStrength(int32_t rssiDbm);
+    
+    /**
+     * @brief Ottiene la potenza del segnale ricevuto dal nodo
+     * @return RSSI in dBm, o nullopt se mai misurato
+     */
+    std::optional<int32_t> getSignalStrength() const;
+    
+    /**
+     * @brief Registra il tasso di consegna misurato verso il nodo
+     *
+     * Le misure vengono mediate con peso LINK_SUCCESS_SMOOTHING, così un
+     * singolo resoconto negativo non sposta subito i percorsi.
+     *
+     * @param successRate Frazione di pacchetti consegnati (0-1)
+     */
+    void recordPacketSuccess(double successRate);
+    
+    /**
+     * @brief Ottiene il tasso di consegna medio verso il nodo
+     * @return Frazione di pacchetti consegnati (0-1), o nullopt se mai misurato
+     */
+    std::optional<double> getPacketSuccessRate() const;
+    
+    /**
+     * @brief Ottiene il punteggio del collegamento verso il nodo
+     *
+     * Il tasso di consegna, scalato da un fattore tra 0.5 e 1 che dipende
+     * dall'RSSI tra LINK_RSSI_WEAK_DBM e LINK_RSSI_STRONG_DBM. Le misure
+     * mancanti non penalizzano: un nodo mai misurato vale 1.
+     *
+     * @return Qualità del collegamento (0-1)
+     */
+    double getLinkQuality() const;
+    
     /**
      * @brief Controlla se il nodo è attivo (ha inviato un ping recentemente)
      * @return true se il nodo è attivo, false altrimenti
      */
     bool isActive() const;
     
+    /**
+     * @brief Imposta il tempo senza ping dopo cui il nodo non è più attivo
+     * @param timeoutMs Timeout in millisecondi (predefinito: spec::NODE_TIMEOUT_S)
+     */
+    void setHeartbeatTimeout(uint32_t timeoutMs);
+    
+    /**
+     * @brief Controlla se il nodo ha mai inviato un ping
+     * @return true se almeno un ping è stato ricevuto
+     */
+    bool hasPinged() const;
+    
+    /**
+     * @brief Ottiene il tempo trascorso dall'ultimo ping ricevuto
+     * @return Millisecondi dall'ultimo ping, o nullopt se il nodo non ne ha mai inviati
+     */
+    std::optional<int64_t> getLastSeenMs() const;
+    
     /// Identificatore univoco del nodo
     std::string id;
     
@@ -77,11 +185,83 @@ private:
     /// Timestamp dell'ultimo ping ricevuto, usato per sincronizzazione
     std::optional<std::chrono::steady_clock::time_point> lastPing;
     
+    /// Tempo senza ping dopo cui il nodo non è più attivo (ms)
+    uint32_t heartbeatTimeoutMs = spec::NODE_TIMEOUT_S * 1000;
+    
     /// Latenza misurata in millisecondi
     uint32_t latency;
     
     /// Stato del buffer (percentuale disponibile)
     uint8_t bufferState;
+    
+    /// RSSI dell'ultimo annuncio BLE o resoconto del collegamento (dBm)
+    std::optional<int32_t> signalStrength;
+    
+    /// Tasso di consegna medio riportato dai collegamenti
+    std::optional<double> packetSuccessRate;
+};
+
+/**
+ * @brief Qualità del collegamento del nodo locale verso un altro nodo
+ */
+struct LinkQuality {
+    /// Potenza del segnale ricevuto (dBm), se misurata
+    std::optional<int32_t> rssiDbm;
+    
+    /// Tasso di consegna medio (0-1), se misurato
+    std::optional<double> packetSuccessRate;
+    
+    /// Punteggio usato nella scelta dei percorsi (0-1)
+    double score = 1.0;
+};
+
+/**
+ * @brief Istantanea di un nodo noto alla rete mesh, per le interfacce e gli strumenti
+ */
+struct MeshNodeInfo {
+    /// ID del nodo
+    std::string nodeId;
+    
+    /// Ruolo del nodo
+    NodeRole role = NodeRole::Sink;
+    
+    /// Latenza riportata dal nodo (ms)
+    uint32_t latencyMs = 0;
+    
+    /// Buffer disponibile riportato dal nodo (percentuale 0-100)
+    uint8_t bufferState = 0;
+    
+    /// Tempo trascorso dall'ultimo ping del nodo (ms), se mai ricevuto
+    std::optional<int64_t> lastSeenMs;
+    
+    /// Punteggio del collegamento verso il nodo (0-1)
+    double linkQuality = 1.0;
+    
+    /// Il nodo ha inviato un ping di recente
+    bool active = false;
+};
+
+/**
+ * @brief Metadati del contenuto di uno stream (brano in riproduzione)
+ */
+struct StreamMetadata {
+    /// Stream a cui si riferiscono i metadati
+    StreamId streamId = 0;
+    
+    /// Revisione, incrementata dal Master ad ogni cambio brano
+    uint32_t revision = 0;
+    
+    /// Titolo del brano
+    std::string title;
+    
+    /// Artista
+    std::string artist;
+    
+    /// Album
+    std::string album;
+    
+    /// Hash SHA-256 (esadecimale) della copertina, vuoto se assente
+    std::string artworkHash;
 };
 
 /**
@@ -92,11 +272,141 @@ enum class MeshPacketType {
     Command,
     Status,
     TimeBeacon,
-    EmergencySync
+    EmergencySync,
+    /// Richiesta di un sink di ricevere uno stream
+    Subscribe,
+    /// Revoca della sottoscrizione ad uno stream
+    Unsubscribe,
+    /// Notifica al mittente che un suo pacchetto è stato scartato
+    Reject,
+    /// Metadati del brano in riproduzione su uno stream
+    Metadata,
+    /// Copertina identificata dal suo hash, inviata su richiesta
+    Artwork,
+    /// Frame audio codificato di uno stream
+    Audio,
+    /// Richiesta di ritrasmissione di frame audio mancanti
+    Nack,
+    /// Richiesta di ingresso nella rete di un nuovo nodo
+    Join,
+    /// Fase dell'abbinamento con PIN di un nuovo nodo, prima che abbia la chiave di rete
+    Pairing,
+    /// Conferma di ricezione di un comando o di una richiesta di ingresso
+    Ack
+};
+
+/**
+ * @brief Converte un tipo di pacchetto nella sua rappresentazione testuale
+ * @param type Tipo di pacchetto
+ * @return Nome del tipo (es. "time_beacon")
+ */
+std::string meshPacketTypeToString(MeshPacketType type);
+
+/**
+ * @brief Interpreta il nome di un tipo di pacchetto
+ * @param name Nome del tipo (es. "status")
+ * @return Tipo, o std::nullopt se il nome non è valido
+ */
+std::optional<MeshPacketType> meshPacketTypeFromString(const std::string& name);
+
+/**
+ * @brief Fase dell'abbinamento con PIN trasportata da un pacchetto Pairing
+ */
+enum class PairingStep : uint8_t {
+    /// Nuovo nodo -> Master: chiave effimera mascherata e identità
+    Hello = 0,
+    /// Master -> nuovo nodo: chiave effimera mascherata e identità del Master
+    Challenge = 1,
+    /// Nuovo nodo -> Master: prova di conoscenza del PIN
+    Proof = 2,
+    /// Master -> nuovo nodo: chiave di rete e token cifrati
+    Welcome = 3,
+    /// Master -> nuovo nodo: abbinamento rifiutato, con il motivo
+    Failed = 4
+};
+
+/**
+ * @brief Converte una fase dell'abbinamento nella sua rappresentazione testuale
+ * @param step Fase dell'abbinamento
+ * @return Nome della fase (es. "challenge")
+ */
+std::string pairingStepToString(PairingStep step);
+
+/**
+ * @brief Motivo per cui un pacchetto è stato scartato
+ *
+ * I valori sono stabili: vengono trasmessi nei pacchetti Reject e
+ * devono essere interpretabili da nodi con versioni diverse.
+ */
+enum class RejectReason : uint8_t {
+    /// Firma assente o non valida
+    BadSignature = 1,
+    /// Pacchetto già ricevuto (replay)
+    Replay = 2,
+    /// Limite di traffico superato
+    RateLimited = 3,
+    /// Stream non pubblicato dal Master
+    UnknownStream = 4,
+    /// Mittente senza chiave pubblica registrata
+    UnknownSender = 5,
+    /// Payload non decifrabile (chiave di rete errata)
+    WrongNetworkKey = 6,
+    /// Epoca delle chiavi non più valida
+    StaleEpoch = 7,
+    /// Mittente in quarantena per conflitto di chiavi
+    Quarantined = 8,
+    /// Richiesta di ingresso a finestra di provisioning chiusa
+    ProvisioningClosed = 9,
+    /// Rete al numero massimo di nodi o di sink
+    CapacityExceeded = 10,
+    /// TTL esaurito prima di raggiungere la destinazione
+    TtlExpired = 11,
+    /// Il pacchetto è già passato da questo nodo
+    RoutingLoop = 12,
+    /// Ingresso negato dalla politica di autorizzazione esterna
+    Unauthorized = 13
 };
 
+/**
+ * @brief Converte un motivo di scarto nella sua rappresentazione testuale
+ * @param reason Motivo dello scarto
+ * @return Nome del motivo (es. "bad_signature")
+ */
+std::string rejectReasonToString(RejectReason reason);
+
+/// TTL assegnato di default ai pacchetti generati localmente
+const uint8_t DEFAULT_PACKET_TTL = 8;
+
+/// Versione del formato dei pacchetti sul collegamento
+const uint8_t WIRE_FORMAT_VERSION = 2;
+
+/// Versione più vecchia ancora decodificabile (senza registrazione del percorso)
+const uint8_t MIN_WIRE_FORMAT_VERSION = 1;
+
+/// Hop registrabili al massimo nel percorso di un pacchetto
+const size_t MAX_RECORDED_HOPS = 32;
+
+/// Ponti tra reti attraversabili al massimo da un pacchetto
+const size_t MAX_BRIDGE_HOPS = 4;
+
+/**
+ * @brief Identificativo breve di un nodo registrato nel percorso dei pacchetti
+ * @param nodeId ID del nodo
+ * @return Hash FNV-1a dell'ID ridotto a 16 bit
+ */
+uint16_t shortNodeId(const std::string& nodeId);
+
+class ByteWriter;
+class ByteReader;
+
 /**
  * @brief Pacchetto mesh
+ *
+ * Oltre ai dati specifici del tipo, ogni pacchetto porta un'intestazione
+ * (sorgente, numero di sequenza, TTL) e una firma Ed25519 opzionale. La
+ * firma copre l'intestazione e il contenuto: un Repeater può decrementare
+ * il TTL corrente ma non alterare i metadati di instradamento senza
+ * invalidare la firma.
  */
 class MeshPacket {
 public:
@@ -117,6 +427,20 @@ public:
     static MeshPacket createCommand(const std::string& cmdType, 
                                    const std::map<std::string, std::string>& params);
     
+    /**
+     * @brief Crea un pacchetto Command destinato ai nodi di una zona
+     *
+     * La zona viaggia nel parametro "zone"; ogni nodo decide da sé se il
+     * comando lo riguarda confrontandola con la propria.
+     *
+     * @param zone Zona destinataria ("all" per l'intera rete)
+     * @param cmdType Tipo di comando
+     * @param params Parametri del comando
+     * @return Pacchetto Command
+     */
+    static MeshPacket createZoneCommand(const std::string& zone, const std::string& cmdType,
+                                        const std::map<std::string, std::string>& params = {});
+    
     /**
      * @brief Crea un pacchetto di tipo Status
      * @param nodeId ID del nodo
@@ -135,13 +459,39 @@ public:
     
     /**
      * @brief Crea un pacchetto di tipo EmergencySync
-     * @param masterTime Tempo del master
-     * @param targetNodes Nodi target per la sincronizzazione
+     * @param masterTime Tempo del master (ms)
+     * @param targetNodes Nodi da riallineare (vuoto per tutti)
      * @return Pacchetto EmergencySync
      */
     static MeshPacket createEmergencySync(uint64_t masterTime, 
                                          const std::vector<std::string>& targetNodes);
     
+    /**
+     * @brief Crea un pacchetto di tipo Subscribe
+     * @param nodeId ID del nodo che si sottoscrive
+     * @param streamId Stream richiesto
+     * @return Pacchetto Subscribe
+     */
+    static MeshPacket createSubscribe(const std::string& nodeId, StreamId streamId);
+    
+    /**
+     * @brief Crea un pacchetto di tipo Unsubscribe
+     * @param nodeId ID del nodo che revoca la sottoscrizione
+     * @param streamId Stream da abbandonare
+     * @return Pacchetto Unsubscribe
+     */
+    static MeshPacket createUnsubscribe(const std::string& nodeId, StreamId streamId);
+    
+    /**
+     * @brief Crea un pacchetto di tipo Reject in risposta ad un pacchetto scartato
+     * @param rejected Pacchetto scartato
+     * @param reason Motivo dello scarto
+     * @param detail Descrizione leggibile (opzionale)
+     * @return Pacchetto Reject indirizzato alla sorgente del pacchetto scartato
+     */
+    static MeshPacket createReject(const MeshPacket& rejected, RejectReason reason,
+                                   const std::string& detail = "");
+    
     /**
      * @brief Costruttore di copia
      * @param other Pacchetto da copiare
@@ -193,6 +543,13 @@ public:
      */
     std::pair<std::string, std::map<std::string, std::string>> getCommandData() const;
     
+    /**
+     * @brief Ottiene la zona a cui è destinato un pacchetto Command
+     * @return Nome della zona, o nullopt se il comando non indica una zona
+     * @throws std::runtime_error se il pacchetto non è di tipo Command
+     */
+    std::optional<std::string> getCommandZone() const;
+    
     /**
      * @brief Ottiene i dati del pacchetto Status
      * @return Tupla con ID nodo, stato buffer e latenza
@@ -214,160 +571,2374 @@ public:
      */
     std::pair<uint64_t, std::vector<std::string>> getEmergencySyncData() const;
     
-private:
-    MeshPacket(MeshPacketType type);
-    
-    MeshPacketType type;
+    /**
+     * @brief Ottiene i dati di un pacchetto Subscribe o Unsubscribe
+     * @return Coppia con ID nodo e stream
+     * @throws std::runtime_error se il pacchetto non è di tipo Subscribe/Unsubscribe
+     */
+    std::pair<std::string, StreamId> getSubscriptionData() const;
     
-    // Dati specifici per ogni tipo di pacchetto
-    struct PingData {
-        std::string source;
-        uint64_t timestamp;
+    /**
+     * @brief Dati di un pacchetto Reject
+     */
+    struct RejectInfo {
+        /// Nodo che ha originato il pacchetto scartato
+        std::string origin;
+        /// Tipo del pacchetto scartato
+        MeshPacketType rejectedType;
+        /// Numero di sequenza del pacchetto scartato
+        uint32_t rejectedSequence;
+        /// Motivo dello scarto
+        RejectReason reason;
+        /// Descrizione leggibile
+        std::string detail;
     };
     
-    struct CommandData {
-        std::string cmdType;
-        std::map<std::string, std::string> params;
-    };
+    /**
+     * @brief Ottiene i dati del pacchetto Reject
+     * @return Dati dello scarto
+     * @throws std::runtime_error se il pacchetto non è di tipo Reject
+     */
+    RejectInfo getRejectData() const;
     
-    struct StatusData {
-        std::string nodeId;
-        uint8_t buffer;
-        uint32_t latency;
-    };
+    /**
+     * @brief Crea un pacchetto di tipo Metadata
+     * @param metadata Metadati dello stream
+     * @return Pacchetto Metadata
+     */
+    static MeshPacket createMetadata(const StreamMetadata& metadata);
     
-    struct TimeBeaconData {
-        uint64_t masterTime;
-    };
+    /**
+     * @brief Ottiene i dati del pacchetto Metadata
+     * @return Metadati dello stream
+     * @throws std::runtime_error se il pacchetto non è di tipo Metadata
+     */
+    StreamMetadata getMetadata() const;
     
-    struct EmergencySyncData {
-        uint64_t masterTime;
-        std::vector<std::string> targetNodes;
-    };
+    /**
+     * @brief Crea un pacchetto di tipo Artwork
+     * @param artworkHash Hash SHA-256 (esadecimale) della copertina
+     * @param image Byte dell'immagine
+     * @return Pacchetto Artwork
+     */
+    static MeshPacket createArtwork(const std::string& artworkHash, const std::vector<uint8_t>& image);
     
-    // Utilizziamo std::variant in C++17, ma per semplicità qui usiamo union
-    union PacketData {
-        PingData ping;
-        CommandData command;
-        StatusData status;
-        TimeBeaconData timeBeacon;
-        EmergencySyncData emergencySync;
-        
-        PacketData() {} // Default constructor
-        ~PacketData() {} // Default destructor
+    /**
+     * @brief Ottiene i dati del pacchetto Artwork
+     * @return Coppia con hash e byte dell'immagine
+     * @throws std::runtime_error se il pacchetto non è di tipo Artwork
+     */
+    std::pair<std::string, std::vector<uint8_t>> getArtworkData() const;
+    
+    /**
+     * @brief Dati di un pacchetto Audio
+     */
+    struct AudioFrameInfo {
+        /// Stream a cui appartiene il frame
+        StreamId streamId;
+        /// Numero di sequenza del frame nello stream
+        uint32_t frameSequence;
+        /// Istante di riproduzione sull'orologio della rete (µs)
+        uint64_t playoutTimeUs;
+        /// Ampiezza del timestamp trasmesso
+        TimestampWidth timestampWidth;
+        /// Bit meno significativi del timestamp, se compresso
+        uint32_t compactTimestamp;
+        /// Frame codificato
+        std::vector<uint8_t> payload;
     };
     
-    // Funzione helper per copiare i dati in base al tipo
-    void copyDataFromOther(const MeshPacket& other);
+    /**
+     * @brief Crea un pacchetto di tipo Audio
+     * @param streamId Stream a cui appartiene il frame
+     * @param frameSequence Numero di sequenza del frame nello stream
+     * @param playoutTimeUs Istante di riproduzione sull'orologio della rete (µs)
+     * @param payload Frame codificato
+     * @param width Ampiezza del timestamp trasmesso (Full per le ancore)
+     * @return Pacchetto Audio
+     */
+    static MeshPacket createAudio(StreamId streamId, uint32_t frameSequence, uint64_t playoutTimeUs,
+                                  const std::vector<uint8_t>& payload,
+                                  TimestampWidth width = TimestampWidth::Full);
     
-    // Funzione helper per distruggere i dati in base al tipo
-    void destroyData();
+    /**
+     * @brief Imposta il timestamp ricostruito dal ricevitore di un frame compresso
+     *
+     * Il timestamp completo non è coperto dalla firma, che riguarda solo i
+     * bit trasmessi.
+     *
+     * @param playoutTimeUs Timestamp completo decodificato
+     * @throws std::runtime_error se il pacchetto non è di tipo Audio
+     */
+    void resolvePlayoutTime(uint64_t playoutTimeUs);
     
-    PacketData data;
-};
-
-/**
- * @brief Gestore della rete mesh
- */
-class MeshNetwork {
-public:
     /**
-     * @brief Tipo di callback per gestione pacchetti
+     * @brief Ottiene i dati del pacchetto Audio
+     * @return Dati del frame
+     * @throws std::runtime_error se il pacchetto non è di tipo Audio
      */
-    using PacketHandler = std::function<void(const MeshPacket&)>;
-
+    const AudioFrameInfo& getAudioData() const;
+    
     /**
-     * @brief Crea una nuova istanza della rete mesh
-     * @param localNode Nodo locale
+     * @brief Dati di un pacchetto Nack
      */
-    MeshNetwork(const Node& localNode);
+    struct NackInfo {
+        /// Stream dei frame mancanti
+        StreamId streamId;
+        /// Nodo a monte a cui è chiesta la ritrasmissione
+        std::string responder;
+        /// Numeri di sequenza dei frame mancanti
+        std::vector<uint32_t> frames;
+    };
     
     /**
-     * @brief Distruttore
+     * @brief Crea un pacchetto di tipo Nack
+     * @param streamId Stream dei frame mancanti
+     * @param responder Nodo a monte a cui è chiesta la ritrasmissione
+     * @param frames Numeri di sequenza dei frame mancanti
+     * @return Pacchetto Nack
      */
-    ~MeshNetwork();
+    static MeshPacket createNack(StreamId streamId, const std::string& responder,
+                                 const std::vector<uint32_t>& frames);
     
     /**
-     * @brief Avvia la rete mesh
+     * @brief Ottiene i dati del pacchetto Nack
+     * @return Dati della richiesta
+     * @throws std::runtime_error se il pacchetto non è di tipo Nack
      */
-    void start();
+    NackInfo getNackData() const;
     
     /**
-     * @brief Ferma la rete mesh
+     * @brief Dati di un pacchetto Join
+     *
+     * Il nodo che chiede di entrare è la sorgente del pacchetto, firmato
+     * con la chiave privata corrispondente a publicKey.
      */
-    void stop();
+    struct JoinInfo {
+        /// Ruolo richiesto nella rete
+        NodeRole role;
+        /// Chiave pubblica Ed25519 del nuovo nodo
+        std::vector<uint8_t> publicKey;
+    };
     
     /**
-     * @brief Invia un pacchetto nella rete mesh
-     * @param packet Pacchetto da inviare
+     * @brief Crea un pacchetto di tipo Join
+     * @param role Ruolo richiesto nella rete
+     * @param publicKey Chiave pubblica del nodo
+     * @return Pacchetto Join
      */
-    void sendPacket(const MeshPacket& packet);
+    static MeshPacket createJoin(NodeRole role, const std::vector<uint8_t>& publicKey);
     
     /**
-     * @brief Registra un nuovo nodo nella rete
-     * @param nodeId ID del nodo da registrare
-     * @param role Ruolo del nodo nella rete
+     * @brief Ottiene i dati del pacchetto Join
+     * @return Dati della richiesta
+     * @throws std::runtime_error se il pacchetto non è di tipo Join
      */
-    void registerNode(const std::string& nodeId, NodeRole role);
+    JoinInfo getJoinData() const;
     
     /**
-     * @brief Aggiorna lo stato di un nodo
-     * @param nodeId ID del nodo da aggiornare
-     * @param bufferState Stato del buffer del nodo
-     * @param latency Latenza del nodo
+     * @brief Dati di un pacchetto Ack
      */
-    void updateNodeStatus(const std::string& nodeId, uint8_t bufferState, uint32_t latency);
+    struct AckInfo {
+        /// Nodo che ha originato il pacchetto confermato
+        std::string origin;
+        /// Numero di sequenza del pacchetto confermato
+        uint32_t sequence;
+        /// Tipo del pacchetto confermato
+        MeshPacketType ackedType;
+    };
     
     /**
-     * @brief Ottiene la lista dei nodi attivi
-     * @return Vettore di ID dei nodi attivi
+     * @brief Crea un pacchetto di tipo Ack
+     * @param acked Pacchetto di cui si conferma la ricezione
+     * @return Pacchetto Ack
      */
-    std::vector<std::string> getActiveNodes() const;
+    static MeshPacket createAck(const MeshPacket& acked);
     
     /**
-     * @brief Imposta il gestore di pacchetti
-     * @param handler Funzione di callback per gestire i pacchetti
+     * @brief Ottiene i dati del pacchetto Ack
+     * @return Dati della conferma
+     * @throws std::runtime_error se il pacchetto non è di tipo Ack
      */
-    void setPacketHandler(PacketHandler handler);
+    AckInfo getAckData() const;
     
-private:
-    /// Nodo locale
-    Node localNode;
+    /**
+     * @brief Dati di un pacchetto Pairing
+     *
+     * Viaggia in chiaro e senza firma: il nuovo nodo non ha ancora chiave
+     * di rete né identità registrata. L'autenticità dei messaggi è data
+     * dal PIN (vedi PairingJoiner).
+     */
+    struct PairingInfo {
+        /// Fase dell'abbinamento
+        PairingStep step;
+        /// Destinatario (vuoto per un Hello rivolto a qualunque Master)
+        std::string peer;
+        /// Messaggio della fase
+        std::vector<uint8_t> payload;
+    };
     
-    /// Mappa dei nodi connessi
-    std::map<std::string, Node> nodes;
+    /**
+     * @brief Crea un pacchetto di tipo Pairing
+     * @param step Fase dell'abbinamento
+     * @param peer Destinatario (vuoto per qualunque Master)
+     * @param payload Messaggio della fase
+     * @return Pacchetto Pairing
+     */
+    static MeshPacket createPairing(PairingStep step, const std::string& peer, const std::vector<uint8_t>& payload);
     
-    /// Flag per il thread di gestione pacchetti
-    bool running;
+    /**
+     * @brief Ottiene i dati del pacchetto Pairing
+     * @return Dati della fase
+     * @throws std::runtime_error se il pacchetto non è di tipo Pairing
+     */
+    PairingInfo getPairingData() const;
     
-    /// Thread di gestione pacchetti
-    std::unique_ptr<std::thread> networkThread;
+    /**
+     * @brief Imposta l'intestazione del pacchetto
+     * @param source ID del nodo che origina il pacchetto
+     * @param sequence Numero di sequenza assegnato dalla sorgente
+     * @param ttl TTL all'origine (numero massimo di hop)
+     */
+    void setHeader(const std::string& source, uint32_t sequence, uint8_t ttl);
     
-    /// Coda di pacchetti da processare
-    std::vector<MeshPacket> packetQueue;
+    /**
+     * @brief Ottiene il nodo sorgente indicato nell'intestazione
+     * @return ID del nodo sorgente, vuoto se l'intestazione non è impostata
+     */
+    const std::string& getSource() const;
     
-    /// Mutex per la rete
-    mutable std::mutex networkMutex;
+    /**
+     * @brief Ottiene il numero di sequenza
+     * @return Numero di sequenza
+     */
+    uint32_t getSequence() const;
     
-    /// Mutex per la coda di pacchetti
-    mutable std::mutex queueMutex;
+    /**
+     * @brief Ottiene il TTL residuo
+     * @return TTL residuo
+     */
+    uint8_t getTtl() const;
     
-    /// Condition variable per la coda di pacchetti
-    std::condition_variable queueCondition;
+    /**
+     * @brief Ottiene il TTL impostato all'origine
+     * @return TTL all'origine
+     */
+    uint8_t getOriginTtl() const;
     
-    /// Handler per i pacchetti
-    PacketHandler packetHandler;
+    /**
+     * @brief Decrementa il TTL residuo (eseguito da ogni nodo che inoltra)
+     * @return true se il pacchetto può essere ancora inoltrato, false se il TTL è esaurito
+     */
+    bool decrementTtl();
     
     /**
-     * @brief Loop principale per la gestione della rete
+     * @brief Numero di inoltri già subiti dal pacchetto
+     * @return Differenza tra TTL all'origine e TTL residuo
      */
-    void runNetworkLoop();
+    uint8_t getHopCount() const;
     
     /**
-     * @brief Processa un pacchetto
-     * @param packet Pacchetto da processare
+     * @brief Attiva o disattiva la registrazione del percorso (diagnostica)
+     * @param enabled Se true, ogni nodo che inoltra aggiunge il proprio ID breve
      */
-    void processPacket(const MeshPacket& packet);
+    void setPathRecording(bool enabled);
+    
+    /**
+     * @brief Verifica se il pacchetto registra il proprio percorso
+     */
+    bool isPathRecording() const;
+    
+    /**
+     * @brief Aggiunge un nodo che inoltra al percorso registrato
+     *
+     * Come il TTL residuo, il percorso non è coperto dalla firma.
+     *
+     * @param shortId ID breve del nodo (shortNodeId())
+     * @return false se il nodo compare già nel percorso (ciclo) o il percorso è pieno
+     */
+    bool appendHop(uint16_t shortId);
+    
+    /**
+     * @brief Ottiene gli ID brevi dei nodi che hanno inoltrato il pacchetto, in ordine
+     */
+    const std::vector<uint16_t>& getPath() const;
+    
+    /**
+     * @brief Imposta il contesto di tracciamento propagato con il pacchetto
+     * @param context Contesto dello span mittente (nullopt per non propagarlo)
+     */
+    void setTraceContext(const std::optional<TraceContext>& context);
+    
+    /**
+     * @brief Ottiene il contesto di tracciamento del pacchetto
+     * @return Contesto dello span mittente, se propagato
+     */
+    const std::optional<TraceContext>& getTraceContext() const;
+    
+    /**
+     * @brief Aggiunge un ponte attraversato dal pacchetto
+     *
+     * Va chiamato dal ponte che ripropone il pacchetto sull'altra rete,
+     * prima della firma.
+     *
+     * @param bridgeId ID del ponte
+     * @return false se il pacchetto ha già attraversato il ponte (ciclo) o troppi ponti
+     */
+    bool addBridge(const std::string& bridgeId);
+    
+    /**
+     * @brief Verifica se il pacchetto ha già attraversato un ponte
+     * @param bridgeId ID del ponte
+     */
+    bool hasCrossedBridge(const std::string& bridgeId) const;
+    
+    /**
+     * @brief Ottiene i ponti attraversati dal pacchetto, in ordine
+     */
+    const std::vector<std::string>& getBridges() const;
+    
+    /**
+     * @brief Aggiunge il tempo trascorso in un nodo che inoltra il pacchetto
+     *
+     * Come il campo di correzione di un transparent clock PTP, permette a
+     * chi riceve un TimeBeacon inoltrato di compensare il ritardo
+     * accumulato nei Repeater. Come il TTL residuo, non è coperto dalla
+     * firma.
+     *
+     * @param delayUs Ritardo di inoltro (µs)
+     */
+    void addRelayDelay(uint64_t delayUs);
+    
+    /**
+     * @brief Ottiene il ritardo accumulato negli inoltri
+     * @return Ritardo (µs, 0 per un pacchetto ricevuto direttamente)
+     */
+    uint32_t getRelayDelayUs() const;
+    
+    /**
+     * @brief Registra l'istante di arrivo del pacchetto dal collegamento (non trasmesso)
+     * @param steadyUs Istante di arrivo (µs, orologio monotono locale)
+     */
+    void setArrivalTime(int64_t steadyUs);
+    
+    /**
+     * @brief Ottiene l'istante di arrivo dal collegamento
+     * @return Istante (µs, orologio monotono locale), o nullopt per un pacchetto generato nel processo
+     */
+    std::optional<int64_t> getArrivalTime() const;
+    
+    /**
+     * @brief Registra il ruolo del mittente noto al nodo ricevente (non trasmesso)
+     * @param role Ruolo con cui il mittente è entrato nella rete
+     */
+    void setSenderRole(NodeRole role);
+    
+    /**
+     * @brief Ottiene il ruolo del mittente noto al nodo ricevente
+     * @return Ruolo, o nullopt se il mittente non è tra i nodi noti
+     */
+    std::optional<NodeRole> getSenderRole() const;
+    
+    /**
+     * @brief Codifica canonica dei campi coperti dalla firma
+     *
+     * Include tipo, sorgente, sequenza, TTL all'origine, i ponti
+     * attraversati e il contenuto specifico del tipo. Il TTL residuo è
+     * escluso perché viene modificato legittimamente durante l'inoltro.
+     *
+     * @return Byte da firmare
+     */
+    std::vector<uint8_t> signingBytes() const;
+    
+    /**
+     * @brief Dimensione del pacchetto sul collegamento
+     * @return Byte prodotti da encode()
+     */
+    size_t encodedSize() const;
+    
+    /**
+     * @brief Codifica il pacchetto nel formato trasmesso tra i nodi
+     *
+     * Il formato è: versione, tipo, sorgente, sequenza, TTL all'origine,
+     * TTL residuo, flag (bit 0: percorso registrato, bit 1: cifrato,
+     * bit 2: contesto di tracciamento, bit 3: ponti attraversati, bit 4:
+     * ritardo degli inoltri) seguiti
+     * dai campi indicati dai flag, contenuto specifico del tipo
+     * (come in signingBytes()) e firma. In un pacchetto cifrato contenuto
+     * e firma sono sostituiti dal blob prodotto da seal(). Gli interi sono
+     * big-endian, stringhe e byte hanno un prefisso di lunghezza.
+     *
+     * @return Byte del pacchetto
+     */
+    std::vector<uint8_t> encode() const;
+    
+    /**
+     * @brief Ricostruisce un pacchetto ricevuto dal collegamento
+     *
+     * I frame audio con timestamp compresso restano da risolvere con
+     * resolvePlayoutTime().
+     *
+     * @param bytes Byte prodotti da encode()
+     * @return Pacchetto decodificato
+     * @throws std::invalid_argument se i byte sono troncati, in eccesso, di
+     *         una versione non supportata o con valori fuori dominio
+     */
+    static MeshPacket decode(const std::vector<uint8_t>& bytes);
+    
+    /**
+     * @brief Firma il pacchetto con la chiave del nodo locale
+     * @param crypto Gestore crittografico del nodo sorgente
+     * @throws CryptoError in caso di errore di firma
+     */
+    void sign(MeshCrypto& crypto);
+    
+    /**
+     * @brief Verifica la firma del pacchetto con la chiave pubblica della sorgente
+     * @param crypto Gestore crittografico con la chiave della sorgente registrata
+     * @return true se il pacchetto è firmato e la firma è valida, false altrimenti
+     * @throws CryptoError se la sorgente non è conosciuta
+     */
+    bool verifySignature(MeshCrypto& crypto) const;
+    
+    /**
+     * @brief Verifica se il pacchetto porta una firma
+     * @return true se il pacchetto è firmato
+     */
+    bool isSigned() const;
+    
+    /**
+     * @brief Ottiene la firma del pacchetto
+     * @return Firma Ed25519, vuota se il pacchetto non è firmato
+     */
+    const std::vector<uint8_t>& getSignature() const;
+    
+    /**
+     * @brief Cifra contenuto e firma con la chiave di rete
+     *
+     * Va chiamato dopo sign() (sign-then-encrypt). La sorgente è
+     * autenticata come dato associato, così il blob non può essere
+     * attribuito ad un altro nodo; intestazione, TTL e percorso restano
+     * in chiaro perché servono all'inoltro. Il contenuto resta leggibile
+     * sul nodo che cifra.
+     *
+     * @param crypto Gestore crittografico con la chiave di rete
+     * @throws CryptoError in caso di errore di cifratura
+     */
+    void seal(MeshCrypto& crypto);
+    
+    /**
+     * @brief Cifra contenuto e firma con la chiave di sessione di un peer
+     *
+     * Come seal(), ma solo il peer indicato può aprire il pacchetto. Il
+     * destinatario viaggia in chiaro dopo l'intestazione, così gli altri
+     * nodi scartano il pacchetto senza tentare di decifrarlo.
+     *
+     * @param crypto Gestore crittografico con la chiave d'identità del peer
+     * @param peer ID del nodo destinatario
+     * @throws CryptoError se il peer non ha una chiave attendibile o la cifratura non riesce
+     */
+    void sealFor(MeshCrypto& crypto, const std::string& peer);
+    
+    /**
+     * @brief Ottiene il destinatario di un pacchetto cifrato con una chiave di sessione
+     * @return ID del peer, vuoto se il pacchetto è in chiaro o cifrato con la chiave di rete
+     */
+    const std::string& getSessionPeer() const;
+    
+    /**
+     * @brief Decifra un pacchetto ricevuto cifrato
+     *
+     * Il pacchetto restituito conserva il blob cifrato, così può essere
+     * inoltrato senza essere cifrato di nuovo. La firma contenuta va
+     * ancora verificata con verifySignature().
+     *
+     * @param crypto Gestore crittografico con la chiave di rete
+     * @return Pacchetto con il contenuto e la firma decifrati
+     * @throws CryptoError di tipo Replay se il blob è già stato ricevuto,
+     *         di tipo Decryption se non è autentico, di tipo KeyExchange
+     *         se è cifrato con la chiave di sessione di un mittente ignoto
+     * @throws std::invalid_argument se il contenuto decifrato non è valido
+     * @throws std::runtime_error se il pacchetto non è cifrato
+     */
+    MeshPacket open(MeshCrypto& crypto) const;
+    
+    /**
+     * @brief Verifica se il pacchetto viaggia cifrato
+     *
+     * Il contenuto di un pacchetto cifrato decodificato dal collegamento
+     * non è disponibile finché non viene aperto con open().
+     */
+    bool isEncrypted() const;
+    
+private:
+    MeshPacket(MeshPacketType type);
+    
+    MeshPacketType type;
+    
+    /// Nodo che origina il pacchetto
+    std::string source;
+    
+    /// Numero di sequenza assegnato dalla sorgente
+    uint32_t sequence = 0;
+    
+    /// TTL all'origine (coperto dalla firma)
+    uint8_t originTtl = DEFAULT_PACKET_TTL;
+    
+    /// TTL residuo (decrementato ad ogni hop)
+    uint8_t ttl = DEFAULT_PACKET_TTL;
+    
+    /// Registrazione del percorso attiva
+    bool recordPath = false;
+    
+    /// ID brevi dei nodi che hanno inoltrato il pacchetto
+    std::vector<uint16_t> path;
+    
+    /// Contesto di tracciamento dello span mittente (non coperto dalla firma)
+    std::optional<TraceContext> traceContext;
+    
+    /// Ponti tra reti attraversati, in ordine (coperti dalla firma)
+    std::vector<std::string> bridges;
+    
+    /// Ritardo accumulato negli inoltri (µs, non coperto dalla firma)
+    uint32_t relayDelayUs = 0;
+    
+    /// Istante di arrivo dal collegamento (µs, orologio monotono locale; non trasmesso)
+    std::optional<int64_t> arrivalUs;
+    
+    /// Ruolo del mittente tra i nodi noti al ricevente (non trasmesso)
+    std::optional<NodeRole> senderRole;
+    
+    /// Firma Ed25519 di signingBytes()
+    std::vector<uint8_t> signature;
+    
+    /// Contenuto e firma cifrati (vuoto se il pacchetto viaggia in chiaro)
+    std::vector<uint8_t> sealed;
+    
+    /// Destinatario della chiave di sessione (vuoto con la chiave di rete)
+    std::string sessionPeer;
+    
+    /**
+     * @brief Copia intestazione e firma da un altro pacchetto
+     * @param other Pacchetto da cui copiare
+     */
+    void copyHeaderFromOther(const MeshPacket& other);
+    
+    /**
+     * @brief Scrive il contenuto specifico del tipo
+     * @param writer Destinazione dei campi
+     */
+    void writeContent(ByteWriter& writer) const;
+    
+    /**
+     * @brief Legge il contenuto specifico di un tipo
+     * @param type Tipo del pacchetto
+     * @param reader Sorgente dei campi
+     * @return Pacchetto senza intestazione
+     */
+    static MeshPacket readContent(MeshPacketType type, ByteReader& reader);
+    
+    // Dati specifici per ogni tipo di pacchetto
+    struct PingData {
+        std::string source;
+        uint64_t timestamp;
+    };
+    
+    struct CommandData {
+        std::string cmdType;
+        std::map<std::string, std::string> params;
+    };
+    
+    struct StatusData {
+        std::string nodeId;
+        uint8_t buffer;
+        uint32_t latency;
+    };
+    
+    struct TimeBeaconData {
+        uint64_t masterTime;
+    };
+    
+    struct EmergencySyncData {
+        uint64_t masterTime;
+        std::vector<std::string> targetNodes;
+    };
+    
+    struct SubscriptionData {
+        std::string nodeId;
+        StreamId streamId;
+    };
+    
+    struct ArtworkData {
+        std::string hash;
+        std::vector<uint8_t> image;
+    };
+    
+    // Utilizziamo std::variant in C++17, ma per semplicità qui usiamo union
+    union PacketData {
+        PingData ping;
+        CommandData command;
+        StatusData status;
+        TimeBeaconData timeBeacon;
+        EmergencySyncData emergencySync;
+        SubscriptionData subscription;
+        RejectInfo reject;
+        StreamMetadata metadata;
+        ArtworkData artwork;
+        AudioFrameInfo audio;
+        NackInfo nack;
+        JoinInfo join;
+        PairingInfo pairing;
+        AckInfo ack;
+        
+        PacketData() {} // Default constructor
+        ~PacketData() {} // Default destructor
+    };
+    
+    // Funzione helper per copiare i dati in base al tipo
+    void copyDataFromOther(const MeshPacket& other);
+    
+    // Funzione helper per distruggere i dati in base al tipo
+    void destroyData();
+    
+    PacketData data;
+};
+
+/**
+ * @brief Albero di distribuzione di uno stream
+ *
+ * Descrive quale nodo inoltra lo stream a quali figli, partendo dal Master.
+ * Contiene solo i rami che portano ad almeno un sink sottoscritto; i
+ * collegamenti del nodo locale con perdite o segnale debole vengono
+ * aggirati quando un percorso attraverso un Repeater è più affidabile.
+ */
+struct DistributionTree {
+    /// Stream a cui si riferisce l'albero
+    StreamId streamId;
+    
+    /// Radice dell'albero (il Master)
+    std::string root;
+    
+    /// Nodo -> figli a cui inoltrare lo stream
+    std::map<std::string, std::vector<std::string>> children;
+};
+
+/**
+ * @brief Cambio di stato della rete mesh
+ */
+struct MeshEvent {
+    enum class Type {
+        /// Un nodo è entrato nella rete
+        NodeJoined,
+        /// Un nodo attivo ha smesso di rispondere
+        NodeLost,
+        /// Un nodo perso ha ripreso a rispondere
+        NodeReturned,
+        /// Un nodo perso da troppo tempo è stato tolto dalla rete
+        NodeEvicted,
+        /// Un nodo è stato assegnato ad una zona diversa
+        ZoneChanged,
+        /// Un nodo è stato respinto perché la rete è al completo
+        AdmissionRejected,
+        /// Un nodo non ha confermato un comando entro l'ultimo tentativo
+        DeliveryFailed
+    };
+    
+    /// Tipo di evento
+    Type type;
+    
+    /// Nodo coinvolto
+    std::string nodeId;
+    
+    /// Descrizione leggibile
+    std::string detail;
+    
+    /// Timestamp dell'evento in millisecondi
+    uint64_t timestamp;
+};
+
+/**
+ * @brief Percorso seguito dall'ultimo frame ricevuto di uno stream
+ */
+struct RouteTrace {
+    /// Stream del frame
+    StreamId streamId;
+    
+    /// Nodo che ha originato il frame
+    std::string source;
+    
+    /// Inoltri subiti prima di arrivare al nodo locale
+    uint8_t hops = 0;
+    
+    /// TTL residuo all'arrivo
+    uint8_t ttl = 0;
+    
+    /// ID brevi dei nodi che hanno inoltrato il frame (vuoto senza registrazione del percorso)
+    std::vector<uint16_t> path;
+    
+    /// Istante di ricezione in millisecondi
+    uint64_t observedAtMs = 0;
+};
+
+/// Intervallo tra i resoconti dei vicini inviati da ogni nodo (ms)
+constexpr uint32_t TOPOLOGY_REPORT_INTERVAL_MS = 5000;
+
+/// Tempo dopo cui un collegamento non più ascoltato esce dalla topologia (ms)
+constexpr uint32_t TOPOLOGY_LINK_TIMEOUT_MS = 15000;
+
+/// Tempo dopo la perdita di un nodo oltre cui viene tolto dalla rete (ms)
+constexpr uint32_t DEFAULT_NODE_EVICT_AFTER_MS = 300000;
+
+/**
+ * @brief Nodo del grafo della topologia
+ */
+struct TopologyNode {
+    /// ID del nodo
+    std::string id;
+
+    /// Ruolo del nodo
+    NodeRole role = NodeRole::Sink;
+
+    /// Il nodo ha inviato un ping di recente
+    bool active = false;
+
+    /// Il nodo è quello locale
+    bool local = false;
+
+    /// Inoltri subiti dall'ultimo pacchetto del nodo arrivato al nodo locale
+    std::optional<uint8_t> hops;
+};
+
+/**
+ * @brief Collegamento radio osservato: il nodo "to" ascolta direttamente il nodo "from"
+ */
+struct TopologyLink {
+    /// Nodo che trasmette
+    std::string from;
+
+    /// Nodo che riceve
+    std::string to;
+
+    /// Potenza del segnale misurata dal ricevente (dBm), se nota
+    std::optional<int32_t> rssiDbm;
+
+    /// Tempo trascorso dall'ultimo pacchetto ascoltato (ms)
+    uint64_t ageMs = 0;
+};
+
+/**
+ * @brief Grafo della rete: nodi noti e collegamenti osservati
+ *
+ * I collegamenti sono orientati, perché un nodo può ascoltarne un altro
+ * senza esserne ascoltato.
+ */
+struct TopologyGraph {
+    /// Nodo che ha costruito il grafo
+    std::string localNode;
+
+    /// Nodi noti, in ordine di ID
+    std::vector<TopologyNode> nodes;
+
+    /// Collegamenti osservati
+    std::vector<TopologyLink> links;
+};
+
+/**
+ * @brief Esporta un grafo della topologia in formato DOT (Graphviz)
+ * @param graph Grafo da esportare
+ * @return Documento DOT
+ */
+std::string topologyToDot(const TopologyGraph& graph);
+
+/**
+ * @brief Esporta un grafo della topologia in formato JSON
+ * @param graph Grafo da esportare
+ * @return Documento JSON
+ */
+std::string topologyToJson(const TopologyGraph& graph);
+
+/**
+ * @brief Codifica i collegamenti ascoltati da un nodo come "nodo:rssi:età,..."
+ * @param links Collegamenti verso il nodo che li riporta
+ * @return Testo usato nei resoconti dei vicini (l'RSSI è vuoto se non misurato)
+ */
+std::string encodeNeighborReport(const std::vector<TopologyLink>& links);
+
+/**
+ * @brief Interpreta un resoconto dei vicini codificato da encodeNeighborReport()
+ * @param reporter Nodo che ha inviato il resoconto, destinazione dei collegamenti
+ * @param text Testo da interpretare (vuoto se il nodo non ascolta nessuno)
+ * @return Collegamenti, o std::nullopt se il testo non è valido
+ */
+std::optional<std::vector<TopologyLink>> parseNeighborReport(const std::string& reporter, const std::string& text);
+
+/**
+ * @brief Occupazione della rete rispetto ai limiti di ammissione
+ */
+struct AdmissionStats {
+    /// Nodi ammessi al massimo, nodo locale incluso (0 = nessun limite)
+    uint32_t maxNodes = 0;
+    
+    /// Sink ammessi al massimo (0 = solo il limite sui nodi)
+    uint32_t maxSinks = 0;
+    
+    /// Nodi membri della rete, nodo locale incluso
+    uint32_t nodes = 0;
+    
+    /// Sink membri della rete
+    uint32_t sinks = 0;
+    
+    /// Nodi ammessi dall'avvio
+    uint64_t admitted = 0;
+    
+    /// Nodi respinti perché la rete era al completo
+    uint64_t rejected = 0;
+    
+    /// Nodi tolti dalla rete per inattività dall'avvio
+    uint64_t evicted = 0;
+};
+
+/**
+ * @brief Converte un tipo di evento della mesh nella sua rappresentazione testuale
+ * @param type Tipo di evento
+ * @return Nome dell'evento (es. "node_joined")
+ */
+std::string meshEventTypeToString(MeshEvent::Type type);
+
+/// Pacchetti in coda per classe di priorità, oltre cui si scartano i più vecchi
+constexpr size_t PACKET_QUEUE_CAPACITY = 1024;
+
+/**
+ * @brief Classe di priorità con cui un pacchetto attende l'elaborazione
+ */
+enum class PacketClass : uint8_t {
+    /// TimeBeacon ed EmergencySync: non attendono mai dietro ad altro traffico
+    Sync = 0,
+    /// Frame audio e richieste di ritrasmissione
+    Audio = 1,
+    /// Stato dei nodi e ping
+    Status = 2,
+    /// Comandi, metadati, copertine e gestione dei membri
+    Command = 3
+};
+
+/**
+ * @brief Converte una classe di priorità nella sua rappresentazione testuale
+ * @param packetClass Classe di priorità
+ * @return Nome della classe ("sync", "audio", "status" o "command")
+ */
+std::string packetClassToString(PacketClass packetClass);
+
+/**
+ * @brief Converte una stringa in una classe di priorità
+ * @param name Nome della classe
+ * @return Classe, o std::nullopt se il nome non è valido
+ */
+std::optional<PacketClass> packetClassFromString(const std::string& name);
+
+/**
+ * @brief Classe di priorità di un tipo di pacchetto
+ * @param type Tipo di pacchetto
+ * @return Classe con cui il pacchetto viene accodato
+ */
+PacketClass packetClassOf(MeshPacketType type);
+
+/**
+ * @brief Statistiche della coda di una classe di priorità
+ */
+struct PacketQueueStats {
+    /// Pacchetti in coda
+    size_t queued = 0;
+    
+    /// Pacchetti in coda al massimo dall'avvio
+    size_t peak = 0;
+    
+    /// Pacchetti estratti per l'elaborazione
+    uint64_t dequeued = 0;
+    
+    /// Pacchetti scartati a coda piena
+    uint64_t dropped = 0;
+};
+
+/**
+ * @brief Code di pacchetti per classe di priorità
+ *
+ * I pacchetti di sincronizzazione escono sempre per primi; le altre
+ * classi si alternano con pesi 8:2:1 (audio, stato, comandi), così che
+ * il traffico di massa non ritardi l'audio e i comandi non restino
+ * bloccati per sempre dietro ad uno stream continuo. A coda piena si
+ * scarta il pacchetto più vecchio della stessa classe.
+ *
+ * Non è thread-safe: MeshNetwork la protegge con il mutex della coda.
+ */
+class PacketScheduler {
+public:
+    /**
+     * @brief Costruttore
+     * @param capacity Pacchetti in coda al massimo per classe
+     */
+    explicit PacketScheduler(size_t capacity = PACKET_QUEUE_CAPACITY);
+    
+    /**
+     * @brief Accoda un pacchetto nella coda della sua classe
+     * @param packet Pacchetto da accodare
+     * @return false se per fargli posto è stato scartato il pacchetto più vecchio della classe
+     */
+    bool push(MeshPacket packet);
+    
+    /**
+     * @brief Estrae il prossimo pacchetto da elaborare
+     * @return Pacchetto, o std::nullopt se le code sono vuote
+     */
+    std::optional<MeshPacket> pop();
+    
+    /**
+     * @brief Verifica se tutte le code sono vuote
+     */
+    bool empty() const;
+    
+    /**
+     * @brief Pacchetti in coda in tutte le classi
+     */
+    size_t size() const;
+    
+    /**
+     * @brief Ottiene le statistiche delle code per classe
+     */
+    std::map<PacketClass, PacketQueueStats> getStats() const;
+    
+private:
+    /// Pacchetti in coda al massimo per classe
+    size_t capacity;
+    
+    /// Code per classe, nell'ordine di PacketClass
+    std::array<std::deque<MeshPacket>, 4> queues;
+    
+    /// Statistiche per classe
+    std::array<PacketQueueStats, 4> stats;
+    
+    /// Estrazioni rimaste nel giro corrente per classe (la sincronizzazione non ne ha bisogno)
+    std::array<uint32_t, 4> credits{};
+    
+    /**
+     * @brief Estrae il primo pacchetto di una classe
+     */
+    MeshPacket take(PacketClass packetClass);
+};
+
+/**
+ * @brief Limite di traffico per un tipo di pacchetto, applicato ad ogni mittente
+ */
+struct RateLimit {
+    /// Pacchetti al secondo concessi a regime
+    double packetsPerSecond = 0.0;
+    
+    /// Pacchetti concessi di seguito a secchio pieno
+    uint32_t burst = 0;
+};
+
+/**
+ * @brief Limiti di traffico predefiniti: Ping e Status, che i nodi inviano circa una volta al secondo
+ */
+std::map<MeshPacketType, RateLimit> defaultRateLimits();
+
+/**
+ * @brief Contatori dei pacchetti scartati per superamento dei limiti di traffico
+ */
+struct RateLimitStats {
+    /// Pacchetti scartati in totale
+    uint64_t dropped = 0;
+    
+    /// Pacchetti scartati per tipo
+    std::map<MeshPacketType, uint64_t> droppedByType;
+    
+    /// Pacchetti scartati per mittente
+    std::map<std::string, uint64_t> droppedBySource;
+};
+
+/**
+ * @brief Limitatore del traffico in ingresso per mittente e tipo di pacchetto
+ *
+ * Ogni coppia mittente-tipo ha un secchio di gettoni che si ricarica di
+ * packetsPerSecond al secondo fino a burst: un nodo che inonda la rete di
+ * Ping o Status viene fermato senza toccare il traffico degli altri nodi.
+ * I tipi senza limite passano sempre. I secchi tornati pieni vengono
+ * dimenticati, così la memoria segue i soli mittenti attivi.
+ *
+ * Non è thread-safe: MeshNetwork lo protegge con il mutex della rete.
+ */
+class PacketRateLimiter {
+public:
+    /**
+     * @brief Costruttore
+     * @param limits Limite per tipo di pacchetto
+     */
+    explicit PacketRateLimiter(std::map<MeshPacketType, RateLimit> limits = defaultRateLimits());
+    
+    /**
+     * @brief Sostituisce i limiti; i secchi ripartono pieni
+     * @param limits Limite per tipo di pacchetto (i tipi assenti non sono limitati)
+     */
+    void setLimits(std::map<MeshPacketType, RateLimit> limits);
+    
+    /**
+     * @brief Ottiene i limiti in vigore
+     */
+    const std::map<MeshPacketType, RateLimit>& getLimits() const;
+    
+    /**
+     * @brief Decide se un pacchetto rientra nel limite del suo mittente
+     * @param source Mittente del pacchetto
+     * @param type Tipo di pacchetto
+     * @param nowMs Istante di arrivo (ms, orologio monotono)
+     * @return false se il pacchetto va scartato
+     */
+    bool allow(const std::string& source, MeshPacketType type, int64_t nowMs);
+    
+    /**
+     * @brief Ottiene i contatori dei pacchetti scartati
+     */
+    const RateLimitStats& getStats() const;
+    
+private:
+    /**
+     * @brief Secchio di gettoni di una coppia mittente-tipo
+     */
+    struct Bucket {
+        /// Gettoni disponibili
+        double tokens;
+        
+        /// Ultima ricarica (ms)
+        int64_t lastRefillMs;
+    };
+    
+    /// Limite per tipo di pacchetto
+    std::map<MeshPacketType, RateLimit> limits;
+    
+    /// Secchi per mittente e tipo
+    std::map<std::pair<std::string, MeshPacketType>, Bucket> buckets;
+    
+    /// Contatori
+    RateLimitStats stats;
+    
+    /// Ultima pulizia dei secchi pieni (ms)
+    int64_t lastSweepMs = 0;
+};
+
+/**
+ * @brief Configurazione della consegna affidabile di comandi e richieste di ingresso
+ *
+ * L'audio resta senza conferme: per i frame esiste la riparazione tramite
+ * NACK (vedi RepairConfig).
+ */
+struct ReliableConfig {
+    /// Abilita conferme e ritrasmissioni
+    bool enabled = true;
+    
+    /// Attesa della conferma prima della prima ritrasmissione (ms)
+    uint32_t initialTimeoutMs = 200;
+    
+    /// Attesa massima tra due ritrasmissioni, raggiunta raddoppiando la precedente (ms)
+    uint32_t maxTimeoutMs = 3200;
+    
+    /// Invii complessivi, compreso il primo, prima di dichiarare fallita la consegna
+    uint32_t maxAttempts = 5;
+};
+
+/**
+ * @brief Contatori della consegna affidabile
+ */
+struct ReliableStats {
+    /// Pacchetti inviati con richiesta di conferma
+    uint64_t sent = 0;
+    
+    /// Ritrasmissioni per conferma mancante
+    uint64_t retransmitted = 0;
+    
+    /// Pacchetti confermati da tutti i destinatari
+    uint64_t acknowledged = 0;
+    
+    /// Pacchetti rimasti senza conferma dopo l'ultimo tentativo
+    uint64_t failed = 0;
+    
+    /// Conferme inviate per i pacchetti ricevuti
+    uint64_t acksSent = 0;
+    
+    /// Copie già ricevute, confermate ma non elaborate di nuovo
+    uint64_t duplicates = 0;
+};
+
+/**
+ * @brief Consegna senza conferma di un pacchetto inviato dal nodo locale
+ */
+struct ReliableFailure {
+    /// Pacchetto non confermato
+    MeshPacket packet;
+    
+    /// Destinatari che non hanno confermato (vuoto se nessun nodo ha risposto ad un invio senza destinatari)
+    std::set<std::string> missing;
+};
+
+/**
+ * @brief Ritrasmissione dei pacchetti locali fino alla conferma dei destinatari
+ *
+ * Conserva il pacchetto in chiaro, prima della cifratura: ogni
+ * ritrasmissione viene cifrata di nuovo con un nonce fresco, altrimenti
+ * la finestra anti-replay dei destinatari la scarterebbe. L'attesa tra
+ * due invii raddoppia fino a maxTimeoutMs; dopo maxAttempts invii il
+ * pacchetto viene restituito come consegna fallita.
+ *
+ * Non è thread-safe: MeshNetwork la protegge con il mutex della rete.
+ */
+class ReliableSender {
+public:
+    /**
+     * @brief Costruttore
+     * @param config Tempi e tentativi della ritrasmissione
+     */
+    explicit ReliableSender(const ReliableConfig& config = ReliableConfig());
+    
+    /**
+     * @brief Modifica tempi e tentativi; vale anche per i pacchetti in attesa
+     * @param config Nuova configurazione (disabilitata scarta i pacchetti in attesa)
+     */
+    void setConfig(const ReliableConfig& config);
+    
+    /**
+     * @brief Ottiene la configurazione in vigore
+     */
+    const ReliableConfig& getConfig() const;
+    
+    /**
+     * @brief Inizia ad attendere le conferme di un pacchetto appena inviato
+     * @param packet Pacchetto con intestazione, prima della cifratura
+     * @param recipients Nodi che devono confermare (vuoto: basta la prima conferma di qualunque nodo)
+     * @param nowMs Istante dell'invio (ms, orologio monotono)
+     * @return true se il pacchetto attende conferme, false se non è prevista alcuna ritrasmissione
+     */
+    bool track(const MeshPacket& packet, const std::set<std::string>& recipients, int64_t nowMs);
+    
+    /**
+     * @brief Registra la conferma di un destinatario
+     * @param sequence Numero di sequenza del pacchetto confermato
+     * @param from Nodo che conferma
+     * @return true se il pacchetto è ora confermato da tutti i destinatari
+     */
+    bool acknowledge(uint32_t sequence, const std::string& from);
+    
+    /**
+     * @brief Estrae i pacchetti la cui attesa è scaduta
+     * @param nowMs Istante di riferimento (ms, orologio monotono)
+     * @return Pacchetti da ritrasmettere, nell'ordine di invio
+     */
+    std::vector<MeshPacket> due(int64_t nowMs);
+    
+    /**
+     * @brief Estrae le consegne fallite dall'ultima chiamata
+     * @return Pacchetti esauriti i tentativi, con i destinatari mancanti
+     */
+    std::vector<ReliableFailure> takeFailures();
+    
+    /**
+     * @brief Pacchetti in attesa di conferma
+     */
+    size_t pending() const;
+    
+    /**
+     * @brief Ottiene i contatori lato mittente (sent, retransmitted, acknowledged, failed)
+     */
+    const ReliableStats& getStats() const;
+    
+private:
+    /**
+     * @brief Pacchetto in attesa di conferma
+     */
+    struct Pending {
+        /// Copia in chiaro del pacchetto
+        MeshPacket packet;
+        
+        /// Destinatari che non hanno ancora confermato
+        std::set<std::string> missing;
+        
+        /// Invii effettuati
+        uint32_t attempts;
+        
+        /// Attesa corrente (ms)
+        uint32_t timeoutMs;
+        
+        /// Istante della prossima ritrasmissione (ms)
+        int64_t nextMs;
+    };
+    
+    /// Tempi e tentativi
+    ReliableConfig config;
+    
+    /// Pacchetti in attesa per numero di sequenza
+    std::map<uint32_t, Pending> pendingPackets;
+    
+    /// Consegne fallite non ancora estratte
+    std::vector<ReliableFailure> failures;
+    
+    /// Contatori
+    ReliableStats stats;
+};
+
+/**
+ * @brief Riconoscimento delle copie di un pacchetto già elaborato
+ *
+ * Un pacchetto è identificato da sorgente, numero di sequenza e da un
+ * hash dei byte firmati: così un nodo riavviato, che ricomincia la
+ * sequenza, non vede scartati i propri comandi nuovi. Le voci scadono
+ * dopo windowMs, ben oltre l'ultima ritrasmissione possibile.
+ *
+ * Non è thread-safe: MeshNetwork lo protegge con il mutex della rete.
+ */
+class DuplicateFilter {
+public:
+    /**
+     * @brief Costruttore
+     * @param windowMs Durata del ricordo di un pacchetto (ms)
+     */
+    explicit DuplicateFilter(uint32_t windowMs = 30000);
+    
+    /**
+     * @brief Registra un pacchetto
+     * @param packet Pacchetto in chiaro
+     * @param nowMs Istante di arrivo (ms, orologio monotono)
+     * @return false se il pacchetto era già stato registrato nella finestra
+     */
+    bool insert(const MeshPacket& packet, int64_t nowMs);
+    
+    /**
+     * @brief Pacchetti ricordati
+     */
+    size_t size() const;
+    
+private:
+    /// Durata del ricordo
+    uint32_t windowMs;
+    
+    /// Sorgente, sequenza e hash dei byte firmati -> istante della prima ricezione
+    std::map<std::tuple<std::string, uint32_t, uint64_t>, int64_t> seen;
+    
+    /// Ultima pulizia delle voci scadute (ms)
+    int64_t lastSweepMs = 0;
+};
+
+/**
+ * @brief Gestore della rete mesh
+ */
+class MeshNetwork {
+public:
+    /**
+     * @brief Tipo di callback per gestione pacchetti
+     */
+    using PacketHandler = std::function<void(const MeshPacket&)>;
+    
+    /**
+     * @brief Tipo di callback per i cambi di stato della rete
+     */
+    using EventHandler = std::function<void(const MeshEvent&)>;
+    
+    /**
+     * @brief Tipo di callback per la trasmissione dei pacchetti codificati sul collegamento
+     */
+    using LinkSender = std::function<void(const std::vector<uint8_t>&)>;
+    
+    /**
+     * @brief Tipo di callback che sottopone un ingresso ad una politica esterna
+     *
+     * Riceve nodo, ruolo dichiarato e la funzione con cui comunicare la
+     * decisione (consentito, motivo). Restituisce false se non c'è una
+     * politica da consultare: l'ingresso viene deciso subito. Viene
+     * chiamata con i mutex della rete occupati: non deve bloccare né
+     * rispondere prima di tornare.
+     */
+    using JoinAuthorizer = std::function<bool(const std::string&, NodeRole,
+                                              std::function<void(bool, const std::string&)>)>;
+
+    /**
+     * @brief Crea una nuova istanza della rete mesh
+     * @param localNode Nodo locale
+     */
+    MeshNetwork(const Node& localNode);
+    
+    /**
+     * @brief Distruttore
+     */
+    ~MeshNetwork();
+    
+    /**
+     * @brief Avvia la rete mesh
+     */
+    void start();
+    
+    /**
+     * @brief Ferma la rete mesh
+     */
+    void stop();
+    
+    /**
+     * @brief Verifica se la rete mesh è in esecuzione
+     * @return true se il thread di rete è attivo, false altrimenti
+     */
+    bool isRunning() const;
+    
+    /**
+     * @brief Invia un pacchetto nella rete mesh
+     *
+     * Se l'intestazione non è impostata il pacchetto viene attribuito al
+     * nodo locale con il successivo numero di sequenza.
+     *
+     * @param packet Pacchetto da inviare
+     */
+    void sendPacket(const MeshPacket& packet);
+    
+    /**
+     * @brief Invia un pacchetto nella rete mesh senza bloccare in attesa dell'esito
+     *
+     * Il future di un comando tracciato dalla consegna affidabile si risolve
+     * quando tutti i nodi attivi lo hanno confermato, o con false esauriti i
+     * tentativi; per gli altri pacchetti si risolve appena trasmesso. Le
+     * conferme sono elaborate dal thread di rete, che non deve attendere il
+     * future.
+     *
+     * @param packet Pacchetto da inviare
+     * @return Future con true a consegna avvenuta, false se non confermata o se la rete si arresta prima
+     */
+    std::future<bool> sendPacketAsync(const MeshPacket& packet);
+    
+    /**
+     * @brief Verifica se la chiamata arriva dal thread di rete
+     * @return true dal thread che elabora i pacchetti e i gestori registrati sulla rete
+     */
+    bool isNetworkThread() const;
+    
+    /**
+     * @brief Registra un nuovo nodo nella rete
+     * @param nodeId ID del nodo da registrare
+     * @param role Ruolo del nodo nella rete
+     * @return true se il nodo è membro della rete, false se è stato respinto per capienza
+     */
+    bool registerNode(const std::string& nodeId, NodeRole role);
+    
+    /**
+     * @brief Aggiorna lo stato di un nodo
+     * @param nodeId ID del nodo da aggiornare
+     * @param bufferState Stato del buffer del nodo
+     * @param latency Latenza del nodo
+     */
+    void updateNodeStatus(const std::string& nodeId, uint8_t bufferState, uint32_t latency);
+    
+    /**
+     * @brief Ottiene la lista dei nodi attivi
+     * @return Vettore di ID dei nodi attivi
+     */
+    std::vector<std::string> getActiveNodes() const;
+    
+    /**
+     * @brief Ottiene lo stato dei nodi attivi
+     * @return Istantanee dei nodi attivi, in ordine di ID
+     */
+    std::vector<MeshNodeInfo> getActiveNodeInfo() const;
+    
+    /**
+     * @brief Ottiene il ruolo di tutti i nodi registrati, attivi o meno
+     * @return Mappa ID nodo -> ruolo
+     */
+    std::map<std::string, NodeRole> getNodeRoles() const;
+    
+    /**
+     * @brief Imposta il gestore di pacchetti
+     * @param handler Funzione di callback per gestire i pacchetti
+     */
+    void setPacketHandler(PacketHandler handler);
+    
+    /**
+     * @brief Imposta il collegamento su cui trasmettere i pacchetti del nodo
+     *
+     * Ogni pacchetto accodato dal nodo locale viene anche codificato e
+     * consegnato al collegamento (es. UdpTransport); senza collegamento i
+     * pacchetti restano nel processo, come con il BLE gestito
+     * dall'applicazione.
+     *
+     * @param sender Funzione di trasmissione (vuota per scollegare)
+     */
+    void setLinkSender(LinkSender sender);
+    
+    /**
+     * @brief Consegna alla rete un pacchetto codificato ricevuto dal collegamento
+     * @param datagram Pacchetto codificato con MeshPacket::encode()
+     * @return true se il pacchetto è stato accodato, false se non valido o generato dal nodo stesso
+     */
+    bool receiveFromLink(const std::vector<uint8_t>& datagram);
+    
+    /**
+     * @brief Imposta il gestore crittografico del nodo locale
+     *
+     * Con un gestore impostato i pacchetti firmati in ingresso vengono
+     * verificati e i pacchetti Reject inviati vengono firmati.
+     *
+     * @param crypto Gestore crittografico
+     */
+    void setCrypto(std::shared_ptr<MeshCrypto> crypto);
+    
+    /**
+     * @brief Imposta il profilatore che misura la ricezione dei frame audio
+     * @param profiler Profilatore condiviso (nessuna misura se nullptr)
+     */
+    void setProfiler(std::shared_ptr<PipelineProfiler> profiler);
+    
+    /**
+     * @brief Imposta il rilevatore a cui segnalare firme non valide e beacon sospetti
+     * @param detector Rilevatore condiviso (nessuna segnalazione se nullptr)
+     */
+    void setIntrusionDetector(std::shared_ptr<IntrusionDetector> detector);
+    
+    /**
+     * @brief Imposta il tracciatore degli handshake di ingresso
+     *
+     * I pacchetti generati localmente portano il contesto dello span
+     * attivo sul thread che li invia (vedi SpanScope).
+     *
+     * @param tracer Tracciatore condiviso (nessuno span se nullptr)
+     */
+    void setTracer(std::shared_ptr<Tracer> tracer);
+    
+    /**
+     * @brief Abilita l'invio di pacchetti Reject ai mittenti dei pacchetti scartati
+     *
+     * Solo i pacchetti autenticati ricevono un Reject: a firme non valide,
+     * mittenti sconosciuti o chiavi di rete errate non si risponde mai.
+     *
+     * @param enabled true per notificare i mittenti
+     */
+    void setSendRejects(bool enabled);
+    
+    /**
+     * @brief Richiede che tutti i pacchetti in ingresso siano firmati
+     * @param required true per scartare i pacchetti non firmati
+     */
+    void setRequireSignatures(bool required);
+    
+    /**
+     * @brief Attiva la cifratura autenticata dei pacchetti
+     *
+     * I pacchetti generati dal nodo vengono firmati e poi cifrati con la
+     * chiave di rete; in ingresso i pacchetti in chiaro e quelli non
+     * decifrabili vengono scartati come wrong_network_key. Le richieste
+     * di ingresso restano in chiaro. Senza gestore crittografico non ha
+     * effetto in trasmissione.
+     *
+     * @param enabled true per cifrare i pacchetti
+     */
+    void setPacketEncryption(bool enabled);
+    
+    /**
+     * @brief Verifica se la cifratura dei pacchetti è attiva
+     */
+    bool isPacketEncryption() const;
+    
+    /**
+     * @brief Attiva le chiavi di sessione per il traffico diretto ad un solo nodo
+     *
+     * Con la cifratura attiva, i comandi con un destinatario ("target") e
+     * le conferme vengono cifrati con la chiave della coppia di nodi
+     * invece che con la chiave di rete, se l'identità del destinatario è
+     * fissata; gli altri nodi li ignorano senza aprirli.
+     *
+     * @param enabled true per usare le chiavi di sessione
+     */
+    void setSessionKeys(bool enabled);
+    
+    /**
+     * @brief Verifica se le chiavi di sessione sono attive
+     */
+    bool isSessionKeys() const;
+    
+    /**
+     * @brief Ottiene i contatori delle chiavi di sessione del gestore crittografico
+     */
+    SessionStats getSessionStats() const;
+    
+    /**
+     * @brief Dichiara uno stream pubblicato dal nodo locale
+     *
+     * Se il Master ha dichiarato almeno uno stream, le sottoscrizioni a
+     * stream non pubblicati vengono scartate con UnknownStream.
+     *
+     * @param streamId Stream pubblicato
+     */
+    void publishStream(StreamId streamId);
+    
+    /**
+     * @brief Ottiene il numero di pacchetti scartati per motivo
+     * @return Mappa motivo -> contatore
+     */
+    std::map<RejectReason, uint64_t> getDropCounters() const;
+    
+    /**
+     * @brief Assegna un nodo ad una zona
+     *
+     * Le assegnazioni fatte dal Master raggiungono gli altri nodi con il
+     * comando "zone.assign", così ogni nodo conosce la propria zona.
+     *
+     * @param nodeId ID del nodo
+     * @param zone Nome della zona (es. "cucina")
+     */
+    void assignZone(const std::string& nodeId, const std::string& zone);
+    
+    /**
+     * @brief Ottiene la zona di un nodo
+     * @param nodeId ID del nodo
+     * @return Nome della zona, o nullopt se il nodo non è assegnato
+     */
+    std::optional<std::string> getZone(const std::string& nodeId) const;
+    
+    /**
+     * @brief Ottiene l'appartenenza di tutti i nodi alle zone
+     * @return Mappa nodo -> zona
+     */
+    std::map<std::string, std::string> getZones() const;
+    
+    /**
+     * @brief Ottiene le latenze riportate dai nodi attivi
+     * @return Mappa nodo -> latenza in millisecondi
+     */
+    std::map<std::string, uint32_t> getNodeLatencies() const;
+    
+    /**
+     * @brief Aggiorna la potenza del segnale ricevuto da un nodo registrato
+     * @param nodeId ID del nodo
+     * @param rssiDbm RSSI in dBm
+     */
+    void updateSignalStrength(const std::string& nodeId, int32_t rssiDbm);
+    
+    /**
+     * @brief Ottiene la potenza del segnale dei nodi di cui è stata misurata
+     * @return Mappa nodo -> RSSI in dBm
+     */
+    std::map<std::string, int32_t> getSignalStrengths() const;
+    
+    /**
+     * @brief Registra traffico audio attribuibile ad uno stream
+     * @param streamId Stream a cui appartiene il traffico
+     * @param bytes Dimensione del payload
+     */
+    void recordStreamTraffic(StreamId streamId, size_t bytes);
+    
+    /**
+     * @brief Ottiene l'utilizzo della banda nella finestra corrente
+     * @return Resoconto per stream, nodo vicino e zona
+     */
+    BandwidthReport getBandwidthReport();
+    
+    /**
+     * @brief Registra una latenza misurata fuori dai resoconti di stato
+     * @param nodeId Nodo misurato
+     * @param latencyMs Latenza in millisecondi
+     */
+    void recordNodeLatency(const std::string& nodeId, uint32_t latencyMs);
+    
+    /**
+     * @brief Ottiene gli istogrammi di latenza e la stima della perdita per nodo
+     * @return Resoconto sulle finestre correnti
+     */
+    NetworkStats getNetworkStats() const;
+    
+    /**
+     * @brief Ottiene la frazione del canale radio occupata
+     * @return Utilizzo del canale (0-1)
+     */
+    double getAirtimeUtilization();
+    
+    /**
+     * @brief Ottiene la qualità dei collegamenti verso i nodi registrati
+     * @return Mappa nodo -> qualità
+     */
+    std::map<std::string, LinkQuality> getLinkQualities() const;
+    
+    /**
+     * @brief Aggiorna qualità e latenza di un percorso verso un nodo
+     *
+     * La misura aggiorna anche il tasso di consegna e l'RSSI del nodo, da
+     * cui dipendono gli alberi di distribuzione.
+     *
+     * @param peer Nodo remoto
+     * @param kind Tipo di collegamento
+     * @param linkQuality Qualità del collegamento (0-1)
+     * @param latencyMs Latenza misurata sul percorso
+     * @param rssiDbm RSSI misurato dal collegamento, se disponibile
+     */
+    void reportLinkQuality(const std::string& peer, TransportKind kind, double linkQuality, uint32_t latencyMs,
+                           std::optional<int32_t> rssiDbm = std::nullopt);
+    
+    /**
+     * @brief Segnala la caduta di un percorso verso un nodo
+     * @param peer Nodo remoto
+     * @param kind Tipo di collegamento
+     */
+    void reportLinkDown(const std::string& peer, TransportKind kind);
+    
+    /**
+     * @brief Sceglie il collegamento per il prossimo pacchetto verso un nodo
+     * @param peer Nodo remoto
+     * @return Collegamento da usare, o nullopt se nessun percorso è disponibile
+     */
+    std::optional<TransportKind> routeTo(const std::string& peer);
+    
+    /**
+     * @brief Ottiene i contatori della selezione dei collegamenti
+     * @return Cambi di collegamento, pacchetti per collegamento e percorsi attivi
+     */
+    TransportStats getTransportStats() const;
+    
+    /**
+     * @brief Imposta il gestore degli eventi di cambio collegamento
+     * @param handler Funzione invocata ad ogni cambio
+     */
+    void setFailoverHandler(TransportSelector::FailoverHandler handler);
+    
+    /**
+     * @brief Imposta il gestore dei cambi di stato della rete
+     *
+     * Il gestore viene invocato con il mutex della rete acquisito e non
+     * deve chiamare metodi di MeshNetwork.
+     *
+     * @param handler Funzione invocata ad ogni evento
+     */
+    void setEventHandler(EventHandler handler);
+    
+    /**
+     * @brief Imposta il rilevamento dei nodi che non rispondono
+     *
+     * Un nodo senza heartbeat (ping o stato) per heartbeatTimeoutMs è perso
+     * (NodeLost); se resta perso per evictAfterMs viene tolto dalla rete
+     * (NodeEvicted) e libera il proprio posto. La sua chiave pubblica resta
+     * registrata: al primo pacchetto autentico rientra con lo stesso ruolo
+     * (NodeReturned), senza passare dalla finestra di provisioning, e un Join
+     * non può sostituirne la chiave.
+     *
+     * @param heartbeatTimeoutMs Tempo senza heartbeat dopo cui il nodo è perso (ms)
+     * @param evictAfterMs Tempo da perso dopo cui il nodo viene tolto (ms, 0 = mai)
+     */
+    void setLivenessConfig(uint32_t heartbeatTimeoutMs, uint32_t evictAfterMs);
+    
+    /**
+     * @brief Ottiene i nodi tolti dalla rete per inattività e non ancora rientrati
+     * @return ID dei nodi, in ordine
+     */
+    std::vector<std::string> getEvictedNodes() const;
+    
+    /**
+     * @brief Rileva i nodi persi, tornati attivi o da togliere dall'ultima verifica
+     */
+    void checkNodeLiveness();
+    
+    /**
+     * @brief Imposta la finestra di riparazione dei frame audio
+     * @param config Configurazione della riparazione
+     */
+    void setRepairConfig(const RepairConfig& config);
+    
+    /**
+     * @brief Ottiene i contatori della riparazione dei frame audio
+     * @return Contatori lato sink e lato nodo a monte
+     */
+    RepairStats getRepairStats() const;
+    
+    /**
+     * @brief Imposta tempi e tentativi della consegna affidabile di comandi e richieste di ingresso
+     * @param config Configurazione della consegna affidabile
+     */
+    void setReliableConfig(const ReliableConfig& config);
+    
+    /**
+     * @brief Ottiene i contatori della consegna affidabile
+     * @return Invii, ritrasmissioni, conferme e duplicati
+     */
+    ReliableStats getReliableStats() const;
+    
+    /**
+     * @brief Ritrasmette i pacchetti non confermati in tempo e segnala le consegne fallite
+     */
+    void retransmitPending();
+    
+    /**
+     * @brief Chiede l'ingresso nella rete con la chiave del nodo locale
+     * @return true se la richiesta è stata inviata
+     */
+    bool requestJoin();
+    
+    /**
+     * @brief Apre la finestra di provisioning per i nuovi nodi
+     * @param durationMs Durata dell'apertura
+     */
+    void openProvisioning(uint32_t durationMs);
+    
+    /**
+     * @brief Chiude la finestra di provisioning
+     */
+    void closeProvisioning();
+    
+    /**
+     * @brief Ottiene lo stato della finestra di provisioning
+     * @return Apertura, tempo rimanente e contatori
+     */
+    ProvisioningStatus getProvisioningStatus() const;
+    
+    /**
+     * @brief Imposta le richieste di ingresso al minuto a cui rispondere a finestra chiusa
+     * @param attemptsPerMinute Limite al minuto
+     */
+    void setJoinRateLimit(uint32_t attemptsPerMinute);
+    
+    /**
+     * @brief Imposta la politica esterna consultata sulle richieste di ingresso
+     *
+     * La politica viene consultata dopo la verifica della firma e della
+     * capienza; il nodo resta in attesa finché non arriva la decisione.
+     *
+     * @param authorizer Funzione che sottopone la richiesta (vuota per disattivare)
+     */
+    void setJoinAuthorizer(JoinAuthorizer authorizer);
+    
+    /**
+     * @brief Ottiene i nodi in attesa della decisione della politica esterna
+     */
+    std::vector<std::string> getPendingJoins() const;
+    
+    /**
+     * @brief Ammette un nodo autenticato dall'abbinamento con PIN
+     *
+     * Non dipende dalla finestra di provisioning né dalla politica
+     * esterna: il PIN mostrato dal Master vale già come consenso
+     * dell'operatore. La capienza viene comunque rispettata.
+     *
+     * @param nodeId ID del nuovo nodo
+     * @param role Ruolo richiesto nella rete
+     * @param publicKey Chiave pubblica Ed25519 del nuovo nodo
+     * @return Esito ("admitted", "already_member" o "capacity_exceeded")
+     */
+    std::string admitPairedNode(const std::string& nodeId, NodeRole role, const std::vector<uint8_t>& publicKey);
+    
+    /**
+     * @brief Applica le credenziali ricevute dal Master con l'abbinamento
+     *
+     * Adotta la chiave di rete del Master e ne registra l'identità, così
+     * da scambiare pacchetti cifrati con il resto della rete.
+     *
+     * @param masterId ID del Master
+     * @param masterPublicKey Chiave pubblica Ed25519 del Master
+     * @param networkKey Chiave di rete
+     */
+    void joinPairedNetwork(const std::string& masterId, const std::vector<uint8_t>& masterPublicKey,
+                           const std::array<uint8_t, 32>& networkKey);
+    
+    /**
+     * @brief Imposta la capienza della rete
+     *
+     * I nodi oltre i limiti vengono respinti all'ingresso, con un evento
+     * AdmissionRejected, invece di degradare la rete già in funzione. I
+     * nodi persi occupano il proprio posto finché non vengono tolti dalla
+     * rete per inattività. Il nodo locale conta sempre come membro: con
+     * maxNodes = 1 non viene ammesso nessun altro nodo. Abbassare i limiti
+     * non toglie i nodi già ammessi.
+     *
+     * @param maxNodes Nodi al massimo, nodo locale incluso (0 = nessun limite)
+     * @param maxSinks Sink al massimo (0 = solo il limite sui nodi)
+     */
+    void setCapacityLimits(uint32_t maxNodes, uint32_t maxSinks);
+    
+    /**
+     * @brief Ottiene l'occupazione della rete e i conteggi di ammissione
+     */
+    AdmissionStats getAdmissionStats() const;
+    
+    /**
+     * @brief Ottiene le statistiche delle code di elaborazione per classe di priorità
+     */
+    std::map<PacketClass, PacketQueueStats> getQueueStats() const;
+    
+    /**
+     * @brief Imposta i limiti di traffico in ingresso per tipo di pacchetto
+     *
+     * I limiti valgono per ogni mittente e si applicano ai pacchetti altrui
+     * già autenticati, così un nodo non può esaurire il limite di un altro
+     * spacciandosi per lui. I pacchetti oltre il limite vengono scartati
+     * senza inoltro né Reject e contati come rate_limited.
+     *
+     * @param limits Limite per tipo di pacchetto (i tipi assenti non sono limitati)
+     */
+    void setRateLimits(const std::map<MeshPacketType, RateLimit>& limits);
+    
+    /**
+     * @brief Ottiene i limiti di traffico in vigore
+     */
+    std::map<MeshPacketType, RateLimit> getRateLimits() const;
+    
+    /**
+     * @brief Ottiene i contatori dei pacchetti scartati per limite di traffico
+     * @return Scarti in totale, per tipo e per mittente
+     */
+    RateLimitStats getRateLimitStats() const;
+    
+    /**
+     * @brief Attiva la registrazione del percorso nei pacchetti generati localmente
+     *
+     * È un'opzione di diagnostica: ogni nodo che inoltra aggiunge al
+     * pacchetto il proprio ID breve, al costo di due byte per hop.
+     *
+     * @param enabled true per registrare il percorso
+     */
+    void setPathRecording(bool enabled);
+    
+    /**
+     * @brief Verifica se i pacchetti generati localmente registrano il percorso
+     */
+    bool isPathRecording() const;
+    
+    /**
+     * @brief Ottiene il percorso dell'ultimo frame ricevuto per ogni stream
+     * @return Percorsi in ordine di stream
+     */
+    std::vector<RouteTrace> getRouteTraces() const;
+    
+    /**
+     * @brief Ottiene gli ultimi scarti per TTL esaurito o ciclo dei pacchetti locali
+     * @return Descrizione dello scarto per nodo che lo ha segnalato
+     */
+    std::map<std::string, std::string> getRouteFailures() const;
+    
+    /**
+     * @brief Risolve un ID breve tra i nodi noti
+     * @param shortId ID breve registrato nel percorso
+     * @return ID del nodo, o "#" seguito dall'ID breve esadecimale se sconosciuto
+     */
+    std::string resolveShortId(uint16_t shortId) const;
+    
+    /**
+     * @brief Registra i metadati di uno stream se più recenti di quelli noti
+     * @param metadata Metadati ricevuti o pubblicati
+     * @return true se i metadati sono stati aggiornati
+     */
+    bool updateStreamMetadata(const StreamMetadata& metadata);
+    
+    /**
+     * @brief Ottiene i metadati correnti di uno stream
+     * @param streamId Stream richiesto
+     * @return Metadati, o nullopt se lo stream non ne ha
+     */
+    std::optional<StreamMetadata> getStreamMetadata(StreamId streamId) const;
+    
+    /**
+     * @brief Ottiene i metadati di tutti gli stream noti
+     * @return Mappa stream -> metadati
+     */
+    std::map<StreamId, StreamMetadata> getAllStreamMetadata() const;
+    
+    /**
+     * @brief Sottoscrive un nodo ad uno stream audio
+     * @param nodeId ID del nodo sink
+     * @param streamId Stream richiesto
+     */
+    void subscribe(const std::string& nodeId, StreamId streamId);
+    
+    /**
+     * @brief Revoca la sottoscrizione di un nodo ad uno stream audio
+     * @param nodeId ID del nodo sink
+     * @param streamId Stream da abbandonare
+     */
+    void unsubscribe(const std::string& nodeId, StreamId streamId);
+    
+    /**
+     * @brief Ottiene i nodi sottoscritti ad uno stream
+     * @param streamId Stream di interesse
+     * @return Vettore di ID dei nodi sottoscritti
+     */
+    std::vector<std::string> getSubscribers(StreamId streamId) const;
+    
+    /**
+     * @brief Calcola i nodi verso cui inoltrare l'audio di uno stream
+     *
+     * Il Master e i Repeater inoltrano l'audio solo verso i sink attivi
+     * sottoscritti allo stream (multicast potato), evitando di occupare
+     * il canale radio verso zone che non lo ascoltano.
+     *
+     * @param streamId Stream da inoltrare
+     * @return Vettore di ID dei nodi destinatari
+     */
+    std::vector<std::string> getForwardTargets(StreamId streamId) const;
+    
+    /**
+     * @brief Registra un collegamento radio tra due nodi
+     * @param nodeA Primo nodo
+     * @param nodeB Secondo nodo
+     */
+    void addLink(const std::string& nodeA, const std::string& nodeB);
+    
+    /**
+     * @brief Rimuove un collegamento radio tra due nodi
+     * @param nodeA Primo nodo
+     * @param nodeB Secondo nodo
+     */
+    void removeLink(const std::string& nodeA, const std::string& nodeB);
+    
+    /**
+     * @brief Ottiene l'albero di distribuzione di uno stream
+     *
+     * L'albero viene ricalcolato solo quando topologia o sottoscrizioni cambiano.
+     *
+     * @param streamId Stream di interesse
+     * @return Albero di distribuzione
+     */
+    DistributionTree getDistributionTree(StreamId streamId) const;
+    
+    /**
+     * @brief Esporta la topologia della rete in formato JSON
+     *
+     * Include nodi, collegamenti e alberi di distribuzione degli stream,
+     * utile per il debug del multicast.
+     *
+     * @return Documento JSON
+     */
+    std::string exportTopologyJson() const;
+    
+    /**
+     * @brief Ottiene il grafo visto dal nodo locale
+     *
+     * Include i nodi registrati e i vicini ascoltati direttamente: il
+     * mittente dei pacchetti arrivati senza inoltri, o l'ultimo nodo del
+     * percorso registrato nei pacchetti inoltrati.
+     *
+     * @return Grafo con i soli collegamenti verso il nodo locale
+     */
+    TopologyGraph getTopology() const;
+    
+private:
+    /// Nodo locale
+    Node localNode;
+    
+    /// Mappa dei nodi connessi
+    std::map<std::string, Node> nodes;
+    
+    /// Flag per il thread di gestione pacchetti, letto anche senza networkMutex
+    std::atomic<bool> running;
+    
+    /// Thread di gestione pacchetti
+    std::unique_ptr<std::thread> networkThread;
+    
+    /// Code di pacchetti da processare per classe di priorità (protette da queueMutex)
+    PacketScheduler packetQueue;
+    
+    /// Numero di sequenza per i pacchetti generati localmente
+    uint32_t nextSequence = 0;
+    
+    /// Gestore crittografico del nodo locale (opzionale)
+    std::shared_ptr<MeshCrypto> crypto;
+    
+    /// Profilatore della pipeline audio
+    std::shared_ptr<PipelineProfiler> profiler;
+    
+    /// Rilevatore delle intrusioni
+    std::shared_ptr<IntrusionDetector> intrusionDetector;
+    
+    /// Tracciatore degli handshake di ingresso
+    std::shared_ptr<Tracer> tracer;
+    
+    /// Flag per l'invio di pacchetti Reject
+    bool sendRejects = false;
+    
+    /// Flag che impone la firma dei pacchetti in ingresso
+    bool requireSignatures = false;
+    
+    /// Flag che impone la cifratura dei pacchetti
+    bool encryptPackets = false;
+    
+    /// Cifratura con le chiavi di sessione del traffico diretto ad un solo nodo
+    bool sessionKeys = false;
+    
+    /// Stream pubblicati dal nodo locale
+    std::set<StreamId> publishedStreams;
+    
+    /// Contatori dei pacchetti scartati per motivo
+    std::map<RejectReason, uint64_t> dropCounters;
+    
+    /// Limiti di traffico in ingresso per mittente e tipo
+    PacketRateLimiter rateLimiter;
+    
+    /// Appartenenza alle zone (nodo -> zona)
+    std::map<std::string, std::string> nodeZones;
+    
+    /// Contabilità della banda per stream e nodo vicino
+    MeshStats stats;
+    
+    /// Latenza e perdita per nodo remoto
+    LatencyStats latencyStats;
+    
+    /// Selezione del collegamento per nodo remoto
+    TransportSelector transports;
+    
+    /// Configurazione della finestra di riparazione
+    RepairConfig repairConfig;
+    
+    /// Rilevazione dei frame mancanti (sink)
+    NackTracker nackTracker;
+    
+    /// Contatori delle ritrasmissioni (Master e Repeater)
+    RepairStats repairStats;
+    
+    /// Pacchetti locali in attesa di conferma
+    ReliableSender reliableSender;
+    
+    /// Esiti attesi dagli invii asincroni dei comandi, per numero di sequenza
+    std::map<uint32_t, std::promise<bool>> pendingDeliveries;
+    
+    /// Comandi già elaborati, per non ripetere l'effetto delle ritrasmissioni
+    DuplicateFilter duplicateFilter;
+    
+    /// Conferme inviate e duplicati ricevuti
+    ReliableStats reliableStats;
+    
+    /// Frame recenti per stream, conservati per le ritrasmissioni
+    std::map<StreamId, std::map<uint32_t, MeshPacket>> repairCache;
+    
+    /// Ricostruzione dei timestamp compressi per stream
+    std::map<StreamId, TimestampDecoder> timestampDecoders;
+    
+    /// Finestra di ammissione dei nuovi nodi
+    ProvisioningWindow provisioning;
+    
+    /// Nodi al massimo, nodo locale incluso (0 = nessun limite)
+    uint32_t maxNodes = 0;
+    
+    /// Sink al massimo (0 = solo il limite sui nodi)
+    uint32_t maxSinks = 0;
+    
+    /// Nodi ammessi dall'avvio
+    uint64_t admittedNodes = 0;
+    
+    /// Nodi respinti per capienza o dalla politica esterna
+    uint64_t rejectedNodes = 0;
+    
+    /// Politica esterna consultata sulle richieste di ingresso
+    JoinAuthorizer joinAuthorizer;
+    
+    /// Richieste di ingresso in attesa della decisione della politica, per nodo
+    std::map<std::string, MeshPacket> pendingJoins;
+    
+    /// Registrazione del percorso nei pacchetti generati localmente
+    bool pathRecording = false;
+    
+    /// Percorso dell'ultimo frame ricevuto per stream
+    std::map<StreamId, RouteTrace> routeTraces;
+    
+    /// Sequenza dell'ultimo TimeBeacon ritrasmesso per Master (Repeater)
+    std::map<std::string, uint32_t> relayedBeacons;
+    
+    /// Ultimo scarto per TTL o ciclo segnalato da ogni nodo
+    std::map<std::string, std::string> routeFailures;
+
+    /// Vicini ascoltati direttamente: nodo -> istante dell'ultimo pacchetto (ms)
+    std::map<std::string, int64_t> heardNeighbors;
+
+    /// Inoltri subiti dall'ultimo pacchetto ricevuto da ogni nodo
+    std::map<std::string, uint8_t> observedHops;
+    
+    /// Gestore dei cambi di stato della rete
+    EventHandler eventHandler;
+    
+    /// Nodi registrati che hanno smesso di rispondere -> istante della perdita (ms)
+    std::map<std::string, int64_t> lostNodes;
+    
+    /// Nodi tolti dalla rete per inattività -> ruolo con cui rientrano
+    std::map<std::string, NodeRole> evictedNodes;
+    
+    /// Tempo senza heartbeat dopo cui un nodo è perso (ms)
+    uint32_t heartbeatTimeoutMs = spec::NODE_TIMEOUT_S * 1000;
+    
+    /// Tempo da perso dopo cui un nodo viene tolto dalla rete (ms, 0 = mai)
+    uint32_t evictAfterMs = DEFAULT_NODE_EVICT_AFTER_MS;
+    
+    /// Nodi tolti dalla rete per inattività dall'avvio
+    uint64_t evictedCount = 0;
+    
+    /**
+     * @brief Notifica un cambio di stato della rete (richiede networkMutex)
+     */
+    void emitEventLocked(MeshEvent::Type type, const std::string& nodeId, const std::string& detail);
+    
+    /**
+     * @brief Aggiorna lo stato riportato da un nodo (richiede networkMutex)
+     */
+    void updateNodeStatusLocked(const std::string& nodeId, uint8_t bufferState, uint32_t latency);
+    
+    /**
+     * @brief Registra un segno di vita di un nodo (richiede networkMutex)
+     *
+     * Un nodo che torna attivo invalida gli alberi di distribuzione, che
+     * escludono i sottoscrittori inattivi.
+     */
+    void touchNodeLocked(Node& node);
+    
+    /**
+     * @brief Registra la zona di un nodo (richiede networkMutex)
+     */
+    void assignZoneLocked(const std::string& nodeId, const std::string& zone);
+    
+    /**
+     * @brief Gestisce una richiesta di ingresso (richiede networkMutex)
+     */
+    void handleJoinLocked(const MeshPacket& packet);
+    
+    /**
+     * @brief Decide l'ammissione di una richiesta di ingresso (richiede networkMutex)
+     * @return Esito per la traccia ("admitted", "throttled", "refused", ...)
+     */
+    std::string admitJoinLocked(const MeshPacket& packet);
+    
+    /**
+     * @brief Registra un nodo la cui richiesta di ingresso è stata verificata (richiede networkMutex)
+     * @param detail Descrizione dell'ammissione per l'evento NodeJoined
+     */
+    void completeJoinLocked(const MeshPacket& packet, const std::string& detail);
+    
+    /**
+     * @brief Applica la decisione della politica esterna su un ingresso in attesa
+     * @param nodeId Nodo in attesa
+     * @param allowed Ingresso consentito
+     * @param reason Motivo della decisione
+     */
+    void resolveJoin(const std::string& nodeId, bool allowed, const std::string& reason);
+    
+    /**
+     * @brief Verifica che la rete abbia posto per un nuovo nodo
+     * @param role Ruolo del nodo
+     * @return Limite superato (std::nullopt se il nodo può essere ammesso)
+     */
+    std::optional<std::string> capacityExceededLocked(NodeRole role) const;
+    
+    /**
+     * @brief Aggiunge un nodo con il timeout di heartbeat in uso (richiede networkMutex)
+     * @return Nodo aggiunto, o quello già presente con lo stesso ID
+     */
+    Node& addNodeLocked(const std::string& nodeId, NodeRole role);
+    
+    /**
+     * @brief Verifica se un nodo è membro, anche se tolto per inattività (richiede networkMutex)
+     */
+    bool isMemberLocked(const std::string& nodeId) const;
+    
+    /**
+     * @brief Riammette un nodo tolto per inattività che ha ripreso a trasmettere (richiede networkMutex)
+     * @param packet Primo pacchetto ricevuto dal nodo
+     * @param authenticated Il pacchetto ha superato la verifica della firma o della cifratura
+     * @return false se la rete non ha più posto per il nodo
+     */
+    bool rejoinLocked(const MeshPacket& packet, bool authenticated);
+    
+    /**
+     * @brief Prepara un pacchetto ricevuto all'inoltro (richiede networkMutex)
+     *
+     * Decrementa il TTL e aggiunge il nodo locale al percorso registrato;
+     * un pacchetto con TTL esaurito o già passato di qui viene scartato.
+     *
+     * @param packet Pacchetto da inoltrare, modificato sul posto
+     * @param authenticated Il pacchetto ha superato la verifica della firma o della cifratura
+     * @return true se il pacchetto può essere inoltrato
+     */
+    bool prepareForwardLocked(MeshPacket& packet, bool authenticated);
+    
+    /**
+     * @brief Descrive il percorso registrato di un pacchetto (richiede networkMutex)
+     * @return Nodi separati da " > ", a partire dalla sorgente
+     */
+    std::string describePathLocked(const MeshPacket& packet) const;
+    
+    /**
+     * @brief Risolve un ID breve tra i nodi noti (richiede networkMutex)
+     */
+    std::string resolveShortIdLocked(uint16_t shortId) const;
+    
+    /**
+     * @brief Ritrasmette sul collegamento un TimeBeacon del Master (richiede networkMutex)
+     *
+     * Il beacon riparte con il TTL decrementato e il ritardo trascorso nel
+     * nodo dall'arrivo, così i nodi a valle compensano gli inoltri.
+     *
+     * @param received Beacon come ricevuto, ancora cifrato
+     * @param authenticated Il beacon ha superato la verifica della firma o della cifratura
+     */
+    void relayBeaconLocked(const MeshPacket& received, bool authenticated);
+    
+    /**
+     * @brief Instrada un frame audio verso i figli nell'albero di distribuzione (richiede networkMutex)
+     */
+    void routeAudioLocked(const MeshPacket& packet);
+    
+    /**
+     * @brief Conserva un frame audio per eventuali ritrasmissioni (richiede networkMutex)
+     */
+    void cacheAudioLocked(const MeshPacket& packet);
+    
+    /**
+     * @brief Gestisce una richiesta di ritrasmissione (richiede networkMutex)
+     */
+    void handleNackLocked(const MeshPacket& packet);
+    
+    /**
+     * @brief Conferma la ricezione di un pacchetto altrui (richiede networkMutex)
+     */
+    void sendAckLocked(const MeshPacket& packet);
+    
+    /**
+     * @brief Richiede i frame mancanti al nodo a monte (richiede networkMutex)
+     */
+    void requestRepairLocked(const MeshPacket& packet);
+    
+    /// Metadati del contenuto in riproduzione per stream
+    std::map<StreamId, StreamMetadata> streamMetadata;
+    
+    /**
+     * @brief Registra i metadati di uno stream (richiede networkMutex)
+     */
+    bool updateStreamMetadataLocked(const StreamMetadata& metadata);
+    
+    /// Mutex per la rete
+    mutable std::mutex networkMutex;
+    
+    /// Mutex per la coda di pacchetti
+    mutable std::mutex queueMutex;
+    
+    /// Condition variable per la coda di pacchetti
+    std::condition_variable queueCondition;
+    
+    /// Collegamento su cui trasmettere i pacchetti del nodo (protetto da queueMutex)
+    LinkSender linkSender;
+    
+    /// Handler per i pacchetti
+    PacketHandler packetHandler;
+    
+    /// Sottoscrizioni: stream -> nodi sottoscritti
+    std::map<StreamId, std::set<std::string>> streamSubscriptions;
+    
+    /// Collegamenti radio: nodo -> vicini
+    std::map<std::string, std::set<std::string>> links;
+    
+    /// Alberi di distribuzione calcolati per ogni stream
+    mutable std::map<StreamId, DistributionTree> distributionTrees;
+    
+    /// Flag che indica che gli alberi vanno ricalcolati
+    mutable bool treesDirty = true;
+    
+    /**
+     * @brief Calcola l'albero di distribuzione di uno stream (networkMutex già acquisito)
+     * @param streamId Stream di interesse
+     * @return Albero di distribuzione
+     */
+    const DistributionTree& distributionTreeLocked(StreamId streamId) const;
+    
+    /**
+     * @brief Ottiene i vicini di un nodo (networkMutex già acquisito)
+     *
+     * In assenza di collegamenti registrati si assume una topologia a stella
+     * attorno al Master, coerente con una rete a singolo hop.
+     *
+     * @param nodeId Nodo di interesse
+     * @param root Radice della rete
+     * @return Insieme dei vicini
+     */
+    std::set<std::string> neighborsLocked(const std::string& nodeId, const std::string& root) const;
+    
+    /**
+     * @brief Costo di un collegamento nel calcolo dei percorsi (networkMutex già acquisito)
+     *
+     * Un collegamento perfetto costa un hop, uno con perdite l'inverso della
+     * sua qualità. Il nodo locale misura solo i propri collegamenti: gli
+     * altri costano un hop.
+     */
+    double linkCostLocked(const std::string& from, const std::string& to) const;
+    
+    /**
+     * @brief Aggiorna le misure di un nodo e invalida gli alberi se la qualità cambia fascia
+     * @param nodeId Nodo misurato
+     * @param update Modifica da applicare al nodo
+     */
+    void updateLinkLocked(const std::string& nodeId, const std::function<void(Node&)>& update);
+    
+    /**
+     * @brief Loop principale per la gestione della rete
+     */
+    void runNetworkLoop();
+    
+    /**
+     * @brief Processa un pacchetto
+     * @param packet Pacchetto da processare
+     */
+    void processPacket(const MeshPacket& packet);
+    
+    /**
+     * @brief Verifica l'autenticità di un pacchetto (networkMutex già acquisito)
+     * @param packet Pacchetto da verificare
+     * @param verified Impostato a true se la firma del pacchetto è stata verificata; un pacchetto
+     *        senza firma accettato perché le firme non sono obbligatorie resta non verificato
+     * @return Motivo dello scarto, o nullopt se il pacchetto è accettato
+     */
+    std::optional<RejectReason> checkAuthenticityLocked(const MeshPacket& packet, bool& verified);
+    
+    /**
+     * @brief Firma e cifra un pacchetto in uscita se la cifratura è attiva
+     *        (networkMutex già acquisito)
+     *
+     * Solo i pacchetti del nodo locale vengono firmati; quelli già cifrati
+     * e le richieste di ingresso restano invariati.
+     *
+     * @param packet Pacchetto da proteggere
+     * @return true se il pacchetto è stato cifrato
+     */
+    bool sealLocked(MeshPacket& packet);
+    
+    /**
+     * @brief Scarta un pacchetto, aggiornando i contatori e notificando il mittente
+     *        (networkMutex già acquisito)
+     *
+     * Il Reject parte solo per i pacchetti autenticati: la sorgente di un
+     * pacchetto non verificato può essere falsa, e rispondere farebbe del
+     * nodo un riflettore verso la vittima indicata come mittente.
+     *
+     * @param packet Pacchetto scartato
+     * @param reason Motivo dello scarto
+     * @param authenticated Il pacchetto ha superato la verifica della firma o della cifratura
+     * @param detail Descrizione leggibile
+     */
+    void dropPacketLocked(const MeshPacket& packet, RejectReason reason, bool authenticated,
+                          const std::string& detail = "");
+    
+    /**
+     * @brief Completa l'intestazione di un pacchetto locale e lo trasmette
+     * @param packet Pacchetto da inviare
+     * @param delivery Esito da risolvere alla consegna, nullptr se non atteso
+     */
+    void dispatchPacket(const MeshPacket& packet, std::promise<bool>* delivery);
+    
+    /**
+     * @brief Risolve l'esito atteso di un comando inviato in modo asincrono (richiede networkMutex)
+     * @param sequence Numero di sequenza del comando
+     * @param delivered true se confermato da tutti i destinatari
+     */
+    void resolveDeliveryLocked(uint32_t sequence, bool delivered);
+    
+    /**
+     * @brief Accoda un pacchetto già completo di intestazione
+     * @param packet Pacchetto da accodare
+     */
+    void enqueuePacket(MeshPacket packet);
+    
+    /**
+     * @brief Inserisce un pacchetto nella coda della sua classe e sveglia il thread di rete (richiede queueMutex)
+     * @param packet Pacchetto da accodare
+     */
+    void pushPacketLocked(MeshPacket packet);
 };
 
 /**
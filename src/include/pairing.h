@@ -0,0 +1,316 @@
+#ifndef SABER_PAIRING_H
+#define SABER_PAIRING_H
+
+#include <array>
+#include <cstdint>
+#include <map>
+#include <memory>
+#include <optional>
+#include <string>
+#include <vector>
+
+#include "mesh.h"
+#include "rng.h"
+
+namespace saber {
+
+/// Cifre del PIN di abbinamento
+constexpr size_t PAIRING_PIN_DIGITS = 6;
+
+/// Prove errate dopo cui il PIN viene invalidato
+constexpr uint32_t PAIRING_MAX_FAILURES = 3;
+
+/// Abbinamenti in corso contemporaneamente sul Master
+constexpr size_t PAIRING_MAX_SESSIONS = 4;
+
+/// Attesa massima di una risposta del Master durante l'abbinamento (ms)
+constexpr uint32_t PAIRING_STEP_TIMEOUT_MS = 5000;
+
+/**
+ * @brief Genera un PIN di abbinamento
+ * @param rng Sorgente casuale
+ * @return PIN di PAIRING_PIN_DIGITS cifre decimali
+ */
+std::string generatePairingPin(RandomSource& rng);
+
+/**
+ * @brief Compone il codice da mostrare come QR per l'abbinamento
+ * @param masterId Master che accetta l'abbinamento
+ * @param pin PIN di abbinamento
+ * @return Codice "saber://pair?master=<id>&pin=<pin>"
+ */
+std::string pairingUri(const std::string& masterId, const std::string& pin);
+
+/**
+ * @brief Codice di abbinamento letto dall'operatore o da un QR
+ */
+struct PairingCode {
+    /// PIN di abbinamento
+    std::string pin;
+
+    /// Master a cui rivolgersi (vuoto se è stato inserito il solo PIN)
+    std::string masterId;
+};
+
+/**
+ * @brief Interpreta un codice di abbinamento
+ * @param code PIN di PAIRING_PIN_DIGITS cifre o codice prodotto da pairingUri()
+ * @return Codice, o std::nullopt se non valido
+ */
+std::optional<PairingCode> parsePairingCode(const std::string& code);
+
+/**
+ * @brief Stato dell'abbinamento visto dal nodo locale
+ */
+enum class PairingState {
+    /// Nessun abbinamento in corso
+    Idle,
+    /// Master: il PIN è valido e i nuovi nodi possono abbinarsi
+    Open,
+    /// Nuovo nodo: in attesa delle risposte del Master
+    Waiting,
+    /// Nuovo nodo: credenziali ricevute
+    Paired,
+    /// Nuovo nodo: abbinamento fallito
+    Failed
+};
+
+/**
+ * @brief Converte uno stato dell'abbinamento nella sua rappresentazione testuale
+ * @param state Stato
+ * @return Nome dello stato ("idle", "open", "waiting", "paired" o "failed")
+ */
+std::string pairingStateToString(PairingState state);
+
+/**
+ * @brief Stato dell'abbinamento
+ */
+struct PairingStatus {
+    /// Stato corrente
+    PairingState state = PairingState::Idle;
+
+    /// Codice da mostrare come QR (solo Master con abbinamento aperto)
+    std::string uri;
+
+    /// Tempo rimanente prima che il PIN scada (ms)
+    int64_t remainingMs = 0;
+
+    /// Prove errate ricevute con il PIN corrente
+    uint32_t failures = 0;
+
+    /// Nodi abbinati dall'avvio (Master)
+    uint64_t paired = 0;
+
+    /// Master che ha accettato il nodo locale
+    std::string masterId;
+
+    /// Token rilasciato dal Master al nodo locale (esadecimale)
+    std::string token;
+
+    /// Motivo dell'ultimo fallimento
+    std::string detail;
+};
+
+/**
+ * @brief Credenziali consegnate dal Master al termine dell'abbinamento
+ */
+struct PairingCredentials {
+    /// Master che ha accettato il nodo
+    std::string masterId;
+
+    /// Chiave pubblica Ed25519 del Master
+    std::vector<uint8_t> masterPublicKey;
+
+    /// Chiave di rete con cui cifrare i pacchetti mesh
+    std::array<uint8_t, 32> networkKey{};
+
+    /// Token di sicurezza rilasciato al nodo dal Master
+    std::vector<uint8_t> token;
+};
+
+/**
+ * @brief Nuovo nodo autenticato dal PIN, in attesa delle credenziali
+ */
+struct PairingRequest {
+    /// ID del nuovo nodo
+    std::string nodeId;
+
+    /// Ruolo richiesto nella rete
+    NodeRole role = NodeRole::Sink;
+
+    /// Chiave pubblica Ed25519 del nuovo nodo
+    std::vector<uint8_t> publicKey;
+};
+
+/**
+ * @brief Lato del nuovo nodo dell'abbinamento con PIN
+ *
+ * Lo scambio è un PAKE bilanciato CPace su ristretto255: il generatore
+ * del Diffie-Hellman deriva dal PIN e dalla sessione, così ogni
+ * messaggio è un punto valido per qualunque PIN. Chi osserva uno scambio
+ * non ha nulla con cui verificare un PIN offline, e un attaccante attivo
+ * può provare un solo PIN per tentativo. Dal segreto condiviso e
+ * dall'intera trascrizione deriva la chiave della sessione: il nuovo nodo prova di conoscerla per
+ * primo, poi il Master gli consegna chiave di rete e token cifrati con
+ * la stessa chiave.
+ */
+class PairingJoiner {
+public:
+    /**
+     * @brief Prepara l'abbinamento
+     * @param nodeId ID del nuovo nodo
+     * @param role Ruolo richiesto nella rete
+     * @param publicKey Chiave pubblica Ed25519 del nuovo nodo
+     * @param pin PIN mostrato dal Master
+     * @param rng Sorgente casuale (quella del sistema se nullptr)
+     */
+    PairingJoiner(const std::string& nodeId, NodeRole role, const std::vector<uint8_t>& publicKey,
+                  const std::string& pin, std::shared_ptr<RandomSource> rng = nullptr);
+
+    ~PairingJoiner();
+
+    PairingJoiner(const PairingJoiner&) = delete;
+    PairingJoiner& operator=(const PairingJoiner&) = delete;
+
+    /**
+     * @brief Primo messaggio, da inviare al Master
+     */
+    std::vector<uint8_t> hello() const;
+
+    /**
+     * @brief Risponde alla sfida del Master con la prova di conoscenza del PIN
+     * @param masterId Master che ha inviato la sfida
+     * @param challenge Sfida ricevuta
+     * @return Prova da inviare, o std::nullopt se la sfida è malformata
+     */
+    std::optional<std::vector<uint8_t>> respond(const std::string& masterId, const std::vector<uint8_t>& challenge);
+
+    /**
+     * @brief Apre il messaggio di benvenuto del Master
+     * @param welcome Benvenuto ricevuto
+     * @return Credenziali, o std::nullopt se il Master non conosce il PIN o il messaggio è alterato
+     */
+    std::optional<PairingCredentials> complete(const std::vector<uint8_t>& welcome) const;
+
+private:
+    std::string nodeId;
+    NodeRole role;
+    std::vector<uint8_t> publicKey;
+    std::string pin;
+
+    /// Identificatore casuale della sessione
+    std::array<uint8_t, 16> sessionId{};
+
+    /// Scalare effimero e chiave pubblica sul generatore del PIN
+    std::array<uint8_t, 32> secretKey{};
+    std::array<uint8_t, 32> keyShare{};
+
+    /// Master che ha risposto e sua identità
+    std::string masterId;
+    std::vector<uint8_t> masterPublicKey;
+
+    /// Chiave della sessione (valida dopo respond())
+    std::optional<std::array<uint8_t, 32>> sessionKey;
+};
+
+/**
+ * @brief Lato del Master dell'abbinamento con PIN
+ *
+ * Tiene al più PAIRING_MAX_SESSIONS abbinamenti in corso, scartando il
+ * più vecchio. Ogni sfida conta come un tentativo fallito finché il nodo
+ * non invia la prova giusta: dopo PAIRING_MAX_FAILURES tentativi falliti
+ * o senza risposta il PIN non è più accettato.
+ */
+class PairingResponder {
+public:
+    /**
+     * @brief Prepara il Master ad accettare abbinamenti
+     * @param masterId ID del Master
+     * @param publicKey Chiave pubblica Ed25519 del Master
+     * @param pin PIN mostrato all'operatore
+     * @param rng Sorgente casuale (quella del sistema se nullptr)
+     */
+    PairingResponder(const std::string& masterId, const std::vector<uint8_t>& publicKey, const std::string& pin,
+                     std::shared_ptr<RandomSource> rng = nullptr);
+
+    ~PairingResponder();
+
+    PairingResponder(const PairingResponder&) = delete;
+    PairingResponder& operator=(const PairingResponder&) = delete;
+
+    /**
+     * @brief Risponde al primo messaggio di un nuovo nodo
+     * @param nodeId Nodo che chiede l'abbinamento
+     * @param hello Messaggio ricevuto
+     * Ogni sfida inviata conta tra i fallimenti finché verify() non accetta la prova.
+     *
+     * @return Sfida da inviare, o std::nullopt se il messaggio è malformato o il PIN è esaurito
+     */
+    std::optional<std::vector<uint8_t>> challenge(const std::string& nodeId, const std::vector<uint8_t>& hello);
+
+    /**
+     * @brief Verifica la prova di un nuovo nodo
+     *
+     * Una prova errata chiude la sessione, lasciando il tentativo tra i fallimenti.
+     *
+     * @param nodeId Nodo che ha inviato la prova
+     * @param proof Prova ricevuta
+     * @return Nodo autenticato, o std::nullopt se la prova è errata o la sessione non esiste
+     */
+    std::optional<PairingRequest> verify(const std::string& nodeId, const std::vector<uint8_t>& proof);
+
+    /**
+     * @brief Cifra le credenziali per un nodo autenticato e chiude la sessione
+     * @param nodeId Nodo autenticato da verify()
+     * @param networkKey Chiave di rete
+     * @param token Token di sicurezza rilasciato al nodo
+     * @return Benvenuto da inviare, o std::nullopt se il nodo non è stato autenticato
+     */
+    std::optional<std::vector<uint8_t>> welcome(const std::string& nodeId, const std::array<uint8_t, 32>& networkKey,
+                                                const std::vector<uint8_t>& token);
+
+    /**
+     * @brief Verifica se un nodo ha ricevuto una sfida e non ha ancora inviato la prova
+     * @param nodeId Nodo da verificare
+     */
+    bool isPending(const std::string& nodeId) const;
+
+    /**
+     * @brief Tentativi sul PIN falliti o ancora senza risposta
+     */
+    uint32_t getFailures() const;
+
+    /**
+     * @brief Verifica se il PIN è stato invalidato dai tentativi falliti
+     */
+    bool isExhausted() const;
+
+private:
+    /**
+     * @brief Abbinamento in corso con un nuovo nodo
+     */
+    struct Session {
+        PairingRequest request;
+        std::array<uint8_t, 32> sessionKey{};
+        bool verified = false;
+        uint64_t order = 0;
+    };
+
+    std::string masterId;
+    std::vector<uint8_t> publicKey;
+    std::string pin;
+    std::shared_ptr<RandomSource> rng;
+
+    /// Abbinamenti in corso per nodo
+    std::map<std::string, Session> sessions;
+
+    /// Ordine di arrivo dell'ultimo abbinamento
+    uint64_t nextOrder = 0;
+
+    /// Tentativi falliti o senza risposta
+    uint32_t failures = 0;
+};
+
+} // namespace saber
+
+#endif // SABER_PAIRING_H
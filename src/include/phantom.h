@@ -0,0 +1,107 @@
+#ifndef SABER_PHANTOM_H
+#define SABER_PHANTOM_H
+
+#include <cstdint>
+#include <map>
+#include <mutex>
+#include <vector>
+
+#include "stats.h"
+
+namespace saber {
+
+/**
+ * @brief Contatori di uno stream consumato da un sink fantasma
+ */
+struct PhantomStreamStats {
+    /// Stream consumato
+    StreamId streamId = 0;
+
+    /// Frame ricevuti
+    uint64_t framesConsumed = 0;
+
+    /// Byte di payload ricevuti
+    uint64_t bytesConsumed = 0;
+
+    /// Frame arrivati dopo il loro istante di riproduzione
+    uint64_t lateFrames = 0;
+
+    /// Anticipo dell'ultimo frame sul suo istante di riproduzione (ms, negativo se in ritardo)
+    int64_t lastLeadMs = 0;
+
+    /// Anticipo minimo osservato (ms)
+    int64_t minLeadMs = 0;
+};
+
+/**
+ * @brief Consumo simulato dei flussi audio
+ *
+ * Un sink fantasma partecipa alla mesh come un sink reale (iscrizioni,
+ * sincronizzazione, riparazione dei frame e stato riportato al Master) ma
+ * non decodifica né riproduce l'audio: si limita a contare i frame e a
+ * misurarne l'anticipo sulla riproduzione. Permette di simulare molti
+ * diffusori con pochi computer e misurare il margine della mesh prima di
+ * acquistare l'hardware.
+ */
+class PhantomSink {
+public:
+    /**
+     * @brief Crea un sink fantasma
+     * @param targetBufferMs Buffer di riproduzione che un sink reale manterrebbe
+     */
+    explicit PhantomSink(uint32_t targetBufferMs);
+
+    /**
+     * @brief Consuma un frame senza decodificarlo
+     * @param streamId Stream del frame
+     * @param playoutTimeUs Istante di riproduzione del frame (µs)
+     * @param payloadSize Dimensione del frame codificato
+     * @param nowUs Istante corrente sull'orologio della rete (µs)
+     */
+    void consume(StreamId streamId, uint64_t playoutTimeUs, size_t payloadSize, uint64_t nowUs);
+
+    /**
+     * @brief Riempimento del buffer simulato, come riportato nei pacchetti Status
+     * @return Percentuale (0-100) del buffer obiettivo coperta dall'ultimo frame
+     */
+    uint8_t bufferLevel() const;
+
+    /**
+     * @brief Registra l'invio di un pacchetto Status
+     */
+    void recordStatusSent();
+
+    /**
+     * @brief Pacchetti Status inviati
+     */
+    uint64_t getStatusReportsSent() const;
+
+    /**
+     * @brief Contatori per stream
+     * @return Contatori ordinati per stream
+     */
+    std::vector<PhantomStreamStats> getStats() const;
+
+private:
+    /// Buffer obiettivo
+    uint32_t targetBufferMs;
+
+    /// Contatori per stream
+    std::map<StreamId, PhantomStreamStats> streams;
+
+    /// Anticipo dell'ultimo frame ricevuto su qualsiasi stream (ms)
+    int64_t lastLeadMs;
+
+    /// Almeno un frame ricevuto
+    bool started;
+
+    /// Pacchetti Status inviati
+    uint64_t statusReportsSent;
+
+    /// Mutex per i contatori
+    mutable std::mutex phantomMutex;
+};
+
+} // namespace saber
+
+#endif // SABER_PHANTOM_H
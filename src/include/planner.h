@@ -0,0 +1,195 @@
+#ifndef SABER_PLANNER_H
+#define SABER_PLANNER_H
+
+#include <cstdint>
+#include <map>
+#include <optional>
+#include <string>
+#include <vector>
+
+#include "spec.h"
+
+namespace saber {
+
+/**
+ * @brief Suggerimento di separazione di un sink lento in un gruppo ritardato
+ */
+struct GroupSplitSuggestion {
+    /// Zona attuale del sink
+    std::string zone;
+    
+    /// Sink che costringe la zona oltre il budget
+    std::string nodeId;
+    
+    /// Zona in cui spostare il sink
+    std::string suggestedZone;
+    
+    /// Buffer richiesto dalla zona con il sink (ms)
+    uint32_t zoneBufferMs;
+    
+    /// Buffer richiesto dalla zona senza il sink (ms)
+    uint32_t bufferWithoutNodeMs;
+    
+    /// Buffer richiesto dal solo sink nel gruppo ritardato (ms)
+    uint32_t nodeBufferMs;
+};
+
+/**
+ * @brief Pianificatore dei buffer di riproduzione per zona
+ *
+ * Tutti i sink di una zona condividono lo stesso buffer, dimensionato sul
+ * sink più lento. Quando un solo sink porta la zona oltre il budget di
+ * latenza, conviene isolarlo in un gruppo ritardato invece di degradare
+ * tutta la zona. In modalità rigorosa i sink oltre il budget vengono
+ * invece rifiutati e non contribuiscono al buffer della zona, che resta
+ * così entro il budget.
+ */
+class LatencyPlanner {
+public:
+    /**
+     * @brief Crea un pianificatore
+     * @param budgetMs Budget di latenza end-to-end in millisecondi
+     * @param marginMs Margine aggiunto alla latenza di ogni sink
+     * @param mode Rispetto del budget di latenza
+     */
+    explicit LatencyPlanner(uint32_t budgetMs = spec::LATENCY_BUDGET_MS, 
+                            uint32_t marginMs = spec::BUFFER_MARGIN_MS,
+                            LatencyMode mode = LatencyMode::BestEffort);
+    
+    /**
+     * @brief Calcola il buffer necessario per una latenza, senza limite superiore
+     * @param latencyMs Latenza del sink in millisecondi
+     * @return Buffer richiesto in millisecondi
+     */
+    uint32_t requiredBufferMs(uint32_t latencyMs) const;
+    
+    /**
+     * @brief Individua i sink rifiutati perché richiedono un buffer oltre il budget
+     * @param members Sink della zona
+     * @param latencies Latenze note dei nodi
+     * @return Sink rifiutati (sempre vuoto fuori dalla modalità rigorosa)
+     */
+    std::vector<std::string> rejectedSinks(const std::vector<std::string>& members,
+                                           const std::map<std::string, uint32_t>& latencies) const;
+    
+    /**
+     * @brief Calcola il buffer richiesto da un insieme di sink
+     *
+     * In modalità rigorosa i sink rifiutati non vengono considerati.
+     *
+     * @param members Sink della zona
+     * @param latencies Latenze note dei nodi
+     * @return Buffer richiesto in millisecondi
+     */
+    uint32_t zoneBufferMs(const std::vector<std::string>& members,
+                          const std::map<std::string, uint32_t>& latencies) const;
+    
+    /**
+     * @brief Individua le zone in cui un singolo sink forza il buffer oltre il budget
+     * @param zones Appartenenza alle zone (nodo -> zona)
+     * @param latencies Latenze note dei nodi
+     * @return Suggerimenti di separazione, uno per zona al massimo
+     */
+    std::vector<GroupSplitSuggestion> suggestSplits(
+        const std::map<std::string, std::string>& zones,
+        const std::map<std::string, uint32_t>& latencies) const;
+    
+    /**
+     * @brief Ottiene il budget di latenza
+     * @return Budget in millisecondi
+     */
+    uint32_t getBudgetMs() const;
+    
+    /**
+     * @brief Ottiene il modo di rispetto del budget
+     * @return Modo di latenza
+     */
+    LatencyMode getMode() const;
+    
+private:
+    /// Budget di latenza end-to-end
+    uint32_t budgetMs;
+    
+    /// Margine aggiunto alla latenza misurata
+    uint32_t marginMs;
+    
+    /// Rispetto del budget di latenza
+    LatencyMode mode;
+};
+
+/**
+ * @brief Parametri dell'equalizzazione dei ritardi tra i sink
+ */
+struct DelayEqualizationConfig {
+    /// Il Master adatta la scadenza di riproduzione al sink più lento
+    bool enabled = true;
+    
+    /// Tempo in cui la richiesta dei sink deve restare più bassa prima che la scadenza si riduca (ms)
+    uint32_t holdMs = 10000;
+};
+
+/**
+ * @brief Scadenza di riproduzione comune a tutti i sink
+ *
+ * Il Master marca ogni frame con l'istante di riproduzione, che cade una
+ * scadenza dopo la lettura della sorgente. Ogni sink trattiene il frame
+ * fino a quell'istante: i sink più veloci attendono così quello più lento
+ * e la riproduzione resta allineata al campione. La scadenza cresce
+ * subito quando un sink ne richiede di più, mentre si riduce solo dopo
+ * holdMs in cui la richiesta è rimasta più bassa, per non spostare di
+ * continuo la riproduzione.
+ */
+class DelayEqualizer {
+public:
+    /**
+     * @brief Crea un equalizzatore senza scadenza
+     * @param config Parametri dell'equalizzazione
+     */
+    explicit DelayEqualizer(const DelayEqualizationConfig& config = DelayEqualizationConfig());
+    
+    /**
+     * @brief Imposta i parametri; disattivare l'equalizzazione dimentica la scadenza
+     * @param config Parametri dell'equalizzazione
+     */
+    void setConfig(const DelayEqualizationConfig& config);
+    
+    /**
+     * @brief Ottiene i parametri
+     */
+    const DelayEqualizationConfig& getConfig() const;
+    
+    /**
+     * @brief Aggiorna la scadenza con quella richiesta dal sink più lento
+     *
+     * La richiesta viene arrotondata per eccesso alla durata di un frame,
+     * così ogni cambio sposta la riproduzione di frame interi.
+     *
+     * @param requiredMs Scadenza richiesta in millisecondi
+     * @param nowMs Istante corrente in millisecondi (clock monotono)
+     * @return Nuova scadenza, se è cambiata
+     */
+    std::optional<uint32_t> update(uint32_t requiredMs, int64_t nowMs);
+    
+    /**
+     * @brief Ottiene la scadenza corrente
+     * @return Scadenza in millisecondi (0 prima del primo aggiornamento)
+     */
+    uint32_t getDeadlineMs() const;
+    
+private:
+    /// Parametri dell'equalizzazione
+    DelayEqualizationConfig config;
+    
+    /// Scadenza corrente (ms)
+    uint32_t deadlineMs = 0;
+    
+    /// Inizio del periodo con una richiesta più bassa della scadenza
+    std::optional<int64_t> lowerSinceMs;
+    
+    /// Richiesta più alta vista nel periodo, che diventa la nuova scadenza (ms)
+    uint32_t lowerTargetMs = 0;
+};
+
+} // namespace saber
+
+#endif // SABER_PLANNER_H
@@ -0,0 +1,227 @@
+#ifndef SABER_PLAYBACK_COMMAND_H
+#define SABER_PLAYBACK_COMMAND_H
+
+#include <cstdint>
+#include <deque>
+#include <map>
+#include <optional>
+#include <set>
+#include <string>
+#include <vector>
+
+namespace saber {
+
+/// Comandi di riproduzione di cui il Master conserva lo stato
+constexpr size_t PLAYBACK_COMMAND_HISTORY = 32;
+
+/// Attesa delle conferme dopo la barriera, oltre la quale un nodo è dato per non raggiunto (ms)
+constexpr uint32_t PLAYBACK_COMMAND_ACK_TIMEOUT_MS = 3000;
+
+/**
+ * @brief Azione di un comando di riproduzione
+ */
+enum class PlaybackAction {
+    Start,
+    Stop,
+    Pause,
+    Resume,
+    Seek,
+    Volume
+};
+
+/**
+ * @brief Converte un'azione di riproduzione nella sua rappresentazione testuale
+ * @param action Azione da convertire
+ * @return Nome dell'azione ("start", "stop", "pause", "resume", "seek" o "volume")
+ */
+std::string playbackActionToString(PlaybackAction action);
+
+/**
+ * @brief Converte una stringa in un'azione di riproduzione
+ * @param name Nome dell'azione
+ * @return Azione, o std::nullopt se il nome non è valido
+ */
+std::optional<PlaybackAction> playbackActionFromString(const std::string& name);
+
+/**
+ * @brief Comando di riproduzione inviato dal Master ai nodi di una zona
+ *
+ * Sulla mesh viaggia come comando "playback.<azione>"; l'identificatore
+ * torna indietro nelle conferme dei nodi.
+ */
+struct PlaybackCommand {
+    /// Identificatore assegnato dal Master (0 se il mittente non chiede conferme)
+    uint32_t id = 0;
+
+    /// Azione da eseguire
+    PlaybackAction action = PlaybackAction::Start;
+
+    /// Zona destinataria ("all" per l'intera rete)
+    std::string zone = "all";
+
+    /// Istante comune di esecuzione sull'orologio sincronizzato (ms)
+    uint64_t atMs = 0;
+
+    /// Playlist da avviare (solo Start)
+    std::string playlist;
+
+    /// Volume in percentuale (solo Volume)
+    uint8_t volumePercent = 100;
+
+    /// Posizione nel brano in millisecondi (solo Seek)
+    uint64_t positionMs = 0;
+};
+
+/**
+ * @brief Codifica i parametri di un comando di riproduzione per la mesh
+ * @param command Comando da codificare
+ * @return Parametri del comando, zona esclusa
+ */
+std::map<std::string, std::string> encodePlaybackCommand(const PlaybackCommand& command);
+
+/**
+ * @brief Decodifica un comando di riproduzione ricevuto dalla mesh
+ * @param cmdType Tipo del comando ("playback.<azione>")
+ * @param zone Zona destinataria
+ * @param params Parametri del comando
+ * @return Comando, o std::nullopt se il tipo o i parametri non sono validi
+ */
+std::optional<PlaybackCommand> parsePlaybackCommand(const std::string& cmdType, const std::string& zone,
+                                                    const std::map<std::string, std::string>& params);
+
+/**
+ * @brief Esito di un comando di riproduzione su un nodo
+ */
+enum class CommandOutcome {
+    /// Conferma non ancora ricevuta
+    Pending,
+    /// Comando eseguito alla barriera
+    Applied,
+    /// Il nodo ha ricevuto il comando ma non è riuscito ad eseguirlo
+    Failed,
+    /// Nessuna conferma entro PLAYBACK_COMMAND_ACK_TIMEOUT_MS dalla barriera
+    TimedOut
+};
+
+/**
+ * @brief Converte l'esito di un comando nella sua rappresentazione testuale
+ * @param outcome Esito da convertire
+ * @return Nome dell'esito ("pending", "applied", "failed" o "timed_out")
+ */
+std::string commandOutcomeToString(CommandOutcome outcome);
+
+/**
+ * @brief Converte una stringa nell'esito di un comando
+ * @param name Nome dell'esito
+ * @return Esito, o std::nullopt se il nome non è valido
+ */
+std::optional<CommandOutcome> commandOutcomeFromString(const std::string& name);
+
+/**
+ * @brief Stato di un comando su un singolo nodo
+ */
+struct CommandNodeStatus {
+    /// Esito sul nodo
+    CommandOutcome outcome = CommandOutcome::Pending;
+
+    /// Istante dell'ultimo aggiornamento sull'orologio sincronizzato (ms)
+    uint64_t updatedAtMs = 0;
+
+    /// Motivo del fallimento (vuoto se il comando è stato eseguito)
+    std::string detail;
+};
+
+/**
+ * @brief Stato di un comando di riproduzione su tutti i nodi destinatari
+ */
+struct CommandStatus {
+    /// Comando inviato
+    PlaybackCommand command;
+
+    /// Istante di invio sull'orologio sincronizzato (ms)
+    uint64_t issuedAtMs = 0;
+
+    /// Stato per nodo
+    std::map<std::string, CommandNodeStatus> nodes;
+
+    /**
+     * @brief Verifica se tutti i nodi hanno un esito definitivo
+     */
+    bool isComplete() const;
+};
+
+/**
+ * @brief Registro dei comandi di riproduzione inviati dal Master
+ *
+ * Ogni comando attende la conferma dei nodi della zona attivi al momento
+ * dell'invio; le conferme di nodi inattesi (ad esempio assegnati alla
+ * zona nel frattempo) vengono comunque registrate. Non è thread-safe:
+ * il chiamante lo protegge con il proprio mutex.
+ */
+class CommandTracker {
+public:
+    /**
+     * @brief Crea un registro vuoto
+     * @param capacity Comandi conservati, i più vecchi vengono dimenticati
+     */
+    explicit CommandTracker(size_t capacity = PLAYBACK_COMMAND_HISTORY);
+
+    /**
+     * @brief Assegna l'identificatore ad un comando e ne inizia il tracciamento
+     * @param command Comando da inviare; l'identificatore viene sovrascritto
+     * @param nodes Nodi da cui si attende una conferma
+     * @param nowMs Istante corrente sull'orologio sincronizzato (ms)
+     * @return Comando con l'identificatore assegnato
+     */
+    PlaybackCommand issue(PlaybackCommand command, const std::set<std::string>& nodes, uint64_t nowMs);
+
+    /**
+     * @brief Registra la conferma di un nodo
+     * @param nodeId Nodo che conferma
+     * @param id Identificatore del comando
+     * @param outcome Esito riportato (Applied o Failed)
+     * @param detail Motivo del fallimento
+     * @param nowMs Istante corrente sull'orologio sincronizzato (ms)
+     * @return true se il comando è ancora nel registro
+     */
+    bool acknowledge(const std::string& nodeId, uint32_t id, CommandOutcome outcome, const std::string& detail,
+                     uint64_t nowMs);
+
+    /**
+     * @brief Segna come non raggiunti i nodi che non hanno confermato in tempo
+     * @param nowMs Istante corrente sull'orologio sincronizzato (ms)
+     * @return Numero di nodi passati a TimedOut
+     */
+    size_t expire(uint64_t nowMs);
+
+    /**
+     * @brief Ottiene lo stato di un comando
+     * @param id Identificatore del comando
+     * @return Stato, o std::nullopt se il comando non è nel registro
+     */
+    std::optional<CommandStatus> get(uint32_t id) const;
+
+    /**
+     * @brief Ottiene lo stato dei comandi conservati, dal più vecchio
+     */
+    std::vector<CommandStatus> recent() const;
+
+private:
+    /// Comandi conservati
+    size_t capacity;
+
+    /// Prossimo identificatore (0 è riservato ai comandi senza conferma)
+    uint32_t nextId = 1;
+
+    /// Stato dei comandi, dal più vecchio
+    std::deque<CommandStatus> commands;
+
+    /**
+     * @brief Cerca un comando nel registro
+     */
+    CommandStatus* find(uint32_t id);
+};
+
+} // namespace saber
+
+#endif // SABER_PLAYBACK_COMMAND_H
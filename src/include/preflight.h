@@ -0,0 +1,144 @@
+#ifndef SABER_PREFLIGHT_H
+#define SABER_PREFLIGHT_H
+
+#include <cstdint>
+#include <string>
+#include <vector>
+
+namespace saber {
+
+/**
+ * @brief Prerequisito dell'ambiente verificato all'avvio
+ */
+enum class PreflightCheck {
+    /// Adattatore Bluetooth presente e acceso
+    BluetoothAdapter,
+    /// Dispositivo di uscita audio disponibile
+    AudioDevice,
+    /// Porta di un servizio locale libera
+    PortBindable,
+    /// Risoluzione dell'orologio monotono
+    ClockResolution
+};
+
+/**
+ * @brief Converte un prerequisito in stringa
+ * @param check Prerequisito da convertire
+ * @return Nome del prerequisito (es. "bluetooth")
+ */
+std::string preflightCheckToString(PreflightCheck check);
+
+/**
+ * @brief Problema rilevato durante la verifica dell'ambiente
+ */
+struct PreflightIssue {
+    /// Prerequisito non soddisfatto
+    PreflightCheck check;
+
+    /// Se true il nodo non può avviarsi; altrimenti è solo un avviso
+    bool fatal;
+
+    /// Descrizione del problema
+    std::string message;
+
+    /// Azione suggerita per risolverlo
+    std::string remedy;
+};
+
+/**
+ * @brief Porta che un servizio locale dovrà aprire
+ */
+struct PortRequirement {
+    /// Servizio che usa la porta (es. "control")
+    std::string service;
+
+    /// Indirizzo IPv4 di ascolto
+    std::string bindAddress;
+
+    /// Porta TCP
+    uint16_t port;
+
+    /// Chiave di configurazione da modificare in caso di conflitto
+    std::string configKey;
+};
+
+/**
+ * @brief Prerequisiti richiesti da una configurazione
+ */
+struct PreflightRequirements {
+    /// Richiede un adattatore Bluetooth acceso
+    bool bluetooth = false;
+
+    /// Richiede un dispositivo di uscita audio
+    bool audioOutput = false;
+
+    /// Porte dei servizi locali da aprire
+    std::vector<PortRequirement> ports;
+
+    /// Risoluzione massima accettabile dell'orologio monotono (µs)
+    uint32_t maxClockResolutionUs = 1000;
+};
+
+/**
+ * @brief Esito della verifica dell'ambiente
+ */
+struct PreflightReport {
+    /// Problemi rilevati, fatali e non
+    std::vector<PreflightIssue> issues;
+
+    /// Risoluzione misurata dell'orologio monotono (µs)
+    double clockResolutionUs = 0.0;
+
+    /**
+     * @brief Verifica se il nodo può avviarsi
+     * @return true se nessun problema è fatale
+     */
+    bool passed() const;
+
+    /**
+     * @brief Riassume i problemi in forma leggibile
+     * @return Una riga per problema con l'azione suggerita
+     */
+    std::string summary() const;
+};
+
+/**
+ * @brief Verifica i prerequisiti dell'ambiente prima dell'avvio
+ *
+ * Le verifiche non richieste che falliscono non vengono riportate; quelle
+ * che non possono essere eseguite sulla piattaforma corrente producono un
+ * avviso non fatale.
+ *
+ * @param requirements Prerequisiti da verificare
+ * @return Esito con i problemi rilevati
+ */
+PreflightReport runPreflight(const PreflightRequirements& requirements);
+
+/**
+ * @brief Verifica la presenza di un adattatore Bluetooth acceso
+ * @param issues Destinazione dei problemi rilevati
+ */
+void probeBluetoothAdapter(std::vector<PreflightIssue>& issues);
+
+/**
+ * @brief Verifica la presenza di un dispositivo di uscita audio
+ * @param issues Destinazione dei problemi rilevati
+ */
+void probeAudioDevice(std::vector<PreflightIssue>& issues);
+
+/**
+ * @brief Verifica che una porta possa essere aperta in ascolto
+ * @param requirement Porta da verificare
+ * @param issues Destinazione dei problemi rilevati
+ */
+void probePort(const PortRequirement& requirement, std::vector<PreflightIssue>& issues);
+
+/**
+ * @brief Misura la risoluzione dell'orologio monotono
+ * @return Intervallo minimo osservabile in microsecondi
+ */
+double measureClockResolutionUs();
+
+} // namespace saber
+
+#endif // SABER_PREFLIGHT_H
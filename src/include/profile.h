@@ -0,0 +1,76 @@
+#ifndef SABER_PROFILE_H
+#define SABER_PROFILE_H
+
+#include <cstdint>
+#include <optional>
+#include <string>
+#include <vector>
+
+#include "spec.h"
+
+namespace saber {
+
+/**
+ * @brief Profilo d'uso con parametri audio e di rete coerenti con la specifica
+ *
+ * Un profilo raccoglie le scelte che dipendono dal caso d'uso (musica in
+ * casa, voce in conferenza, monitor da palco) così che l'utente non debba
+ * combinare a mano durata dei frame, bitrate e buffer.
+ */
+struct Profile {
+    /// Nome del profilo (es. "home-music")
+    std::string name;
+    
+    /// Descrizione per l'utente
+    std::string description;
+    
+    /// Audio musicale (48kHz) o vocale (16kHz)
+    bool isMusicMode;
+    
+    /// Codec dei frame audio ("lc3" o "pcm")
+    std::string codec;
+    
+    /// Durata di un frame in microsecondi (LC3: 7500 o 10000)
+    uint32_t frameDurationUs;
+    
+    /// Bitrate del codec in kbps
+    uint32_t bitrateKbps;
+    
+    /// Buffer di riproduzione dei sink (ms)
+    uint32_t bufferTargetMs;
+    
+    /// Budget di latenza end-to-end (ms)
+    uint32_t latencyBudgetMs;
+    
+    /// Rispetto del budget: rigoroso o al meglio
+    LatencyMode latencyMode;
+    
+    /// Correzione d'errore in avanti sui frame audio
+    bool fecEnabled;
+    
+    /// Intervallo tra beacon temporali del Master (ms)
+    uint32_t beaconIntervalMs;
+};
+
+/**
+ * @brief Ottiene i profili predefiniti
+ * @return Profili "home-music", "conference-voice" e "stage-monitor"
+ */
+const std::vector<Profile>& builtinProfiles();
+
+/**
+ * @brief Cerca un profilo predefinito per nome
+ * @param name Nome del profilo
+ * @return Profilo, o nullopt se il nome non è noto
+ */
+std::optional<Profile> findProfile(const std::string& name);
+
+/**
+ * @brief Elenca i nomi dei profili predefiniti
+ * @return Nomi dei profili
+ */
+std::vector<std::string> profileNames();
+
+} // namespace saber
+
+#endif // SABER_PROFILE_H
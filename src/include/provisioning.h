@@ -0,0 +1,125 @@
+#ifndef SABER_PROVISIONING_H
+#define SABER_PROVISIONING_H
+
+#include <cstdint>
+#include <mutex>
+
+namespace saber {
+
+/**
+ * @brief Esito dell'ammissione di una richiesta di ingresso nella rete
+ */
+enum class JoinDecision {
+    /// Finestra aperta: la richiesta può essere verificata
+    Accept,
+    /// Finestra chiusa: la richiesta viene rifiutata con un Reject
+    Refuse,
+    /// Troppe richieste a finestra chiusa: scartata senza risposta
+    Throttle
+};
+
+/**
+ * @brief Stato della finestra di provisioning
+ */
+struct ProvisioningStatus {
+    /// Finestra aperta
+    bool open = false;
+
+    /// Tempo rimanente prima della chiusura (ms)
+    int64_t remainingMs = 0;
+
+    /// Nodi ammessi nella rete
+    uint64_t accepted = 0;
+
+    /// Richieste rifiutate a finestra chiusa
+    uint64_t refused = 0;
+
+    /// Richieste scartate per superamento del limite
+    uint64_t throttled = 0;
+};
+
+/**
+ * @brief Finestra di provisioning per l'ingresso di nuovi nodi
+ *
+ * I nuovi nodi vengono accettati solo mentre l'operatore tiene aperta la
+ * finestra. A finestra chiusa le richieste vengono rifiutate senza alcuna
+ * verifica crittografica, e oltre un limite al minuto vengono scartate in
+ * silenzio: un dispositivo di passaggio non può far lavorare il nodo.
+ */
+class ProvisioningWindow {
+public:
+    /**
+     * @brief Crea una finestra chiusa
+     * @param closedAttemptsPerMinute Richieste a cui rispondere a finestra chiusa
+     */
+    explicit ProvisioningWindow(uint32_t closedAttemptsPerMinute = 6);
+
+    /**
+     * @brief Apre la finestra
+     * @param durationMs Durata dell'apertura
+     * @param nowMs Istante corrente (ms, orologio monotono)
+     */
+    void open(uint32_t durationMs, int64_t nowMs);
+
+    /**
+     * @brief Chiude la finestra prima della scadenza
+     */
+    void close();
+
+    /**
+     * @brief Verifica se la finestra è aperta
+     * @param nowMs Istante corrente (ms, orologio monotono)
+     * @return true se i nuovi nodi sono accettati
+     */
+    bool isOpen(int64_t nowMs) const;
+
+    /**
+     * @brief Decide come trattare una richiesta di ingresso
+     * @param nowMs Istante della richiesta (ms, orologio monotono)
+     * @return Esito dell'ammissione
+     */
+    JoinDecision admit(int64_t nowMs);
+
+    /**
+     * @brief Registra l'ingresso di un nodo verificato
+     */
+    void recordAccepted();
+
+    /**
+     * @brief Imposta il limite di richieste a finestra chiusa
+     * @param attemptsPerMinute Richieste al minuto a cui rispondere
+     */
+    void setClosedRateLimit(uint32_t attemptsPerMinute);
+
+    /**
+     * @brief Ottiene lo stato della finestra
+     * @param nowMs Istante corrente (ms, orologio monotono)
+     * @return Apertura, tempo rimanente e contatori
+     */
+    ProvisioningStatus status(int64_t nowMs) const;
+
+private:
+    /// Istante di chiusura della finestra (0 se chiusa)
+    int64_t openUntilMs = 0;
+
+    /// Richieste al minuto a finestra chiusa
+    uint32_t closedAttemptsPerMinute;
+
+    /// Gettoni disponibili nel secchio delle richieste a finestra chiusa
+    double tokens;
+
+    /// Ultimo aggiornamento del secchio (ms)
+    int64_t lastRefillMs = 0;
+
+    /// Contatori
+    uint64_t accepted = 0;
+    uint64_t refused = 0;
+    uint64_t throttled = 0;
+
+    /// Mutex per lo stato
+    mutable std::mutex windowMutex;
+};
+
+} // namespace saber
+
+#endif // SABER_PROVISIONING_H
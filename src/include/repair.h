@@ -0,0 +1,140 @@
+#ifndef SABER_REPAIR_H
+#define SABER_REPAIR_H
+
+#include <cstdint>
+#include <map>
+#include <vector>
+
+#include "stats.h"
+
+namespace saber {
+
+/**
+ * @brief Configurazione della finestra di riparazione dei frame audio
+ *
+ * Per l'audio la consegna affidabile non ha senso: un frame ritrasmesso
+ * dopo la sua scadenza di riproduzione è inutile. Un sink può però chiedere
+ * (NACK) un frame mancante entro una breve finestra; il nodo a monte lo
+ * ritrasmette una sola volta se può ancora arrivare in tempo, altrimenti
+ * interviene il mascheramento delle perdite.
+ */
+struct RepairConfig {
+    /// Abilita NACK e ritrasmissioni
+    bool enabled = true;
+
+    /// Tempo massimo, dalla rilevazione della perdita, per ottenere la riparazione (ms)
+    uint32_t windowMs = 20;
+
+    /// Frame recenti conservati per stream dal Master e dai Repeater
+    uint32_t cacheFrames = 64;
+};
+
+/**
+ * @brief Contatori della riparazione dei frame audio
+ */
+struct RepairStats {
+    /// Frame richiesti tramite NACK dal nodo locale
+    uint64_t nacksSent = 0;
+
+    /// Frame richiesti al nodo locale da altri nodi
+    uint64_t nacksReceived = 0;
+
+    /// Frame ritrasmessi dal nodo locale
+    uint64_t retransmitted = 0;
+
+    /// Richieste rifiutate perché il frame non arriverebbe in tempo
+    uint64_t refusedLate = 0;
+
+    /// Richieste di frame non più presenti nella cache
+    uint64_t cacheMisses = 0;
+
+    /// Frame mancanti arrivati entro la finestra
+    uint64_t repaired = 0;
+
+    /// Frame mancanti lasciati al mascheramento delle perdite
+    uint64_t expired = 0;
+};
+
+/**
+ * @brief Rilevazione dei frame mancanti lato sink
+ *
+ * Segue i numeri di sequenza dei frame di ogni stream: un salto produce
+ * una richiesta di riparazione per ogni frame mancante, che resta in attesa
+ * al massimo per la durata della finestra.
+ */
+class NackTracker {
+public:
+    /**
+     * @brief Crea un rilevatore
+     * @param windowMs Durata della finestra di riparazione
+     * @param maxGap Salto oltre il quale lo stream viene considerato riavviato
+     */
+    explicit NackTracker(uint32_t windowMs = 20, uint32_t maxGap = 32);
+
+    /**
+     * @brief Registra l'arrivo di un frame
+     * @param streamId Stream del frame
+     * @param frameSequence Numero di sequenza del frame
+     * @param nowMs Istante di arrivo (ms, orologio monotono)
+     * @return Frame mancanti da richiedere subito con un NACK
+     */
+    std::vector<uint32_t> onFrame(StreamId streamId, uint32_t frameSequence, int64_t nowMs);
+
+    /**
+     * @brief Scarta le richieste scadute
+     * @param nowMs Istante di riferimento (ms, orologio monotono)
+     */
+    void expire(int64_t nowMs);
+
+    /**
+     * @brief Ottiene i contatori lato sink (repaired, expired, nacksSent)
+     * @return Contatori
+     */
+    const RepairStats& getStats() const;
+
+    /**
+     * @brief Modifica la durata della finestra
+     * @param windowMs Nuova durata in millisecondi
+     */
+    void setWindowMs(uint32_t windowMs);
+
+private:
+    /**
+     * @brief Stato di uno stream
+     */
+    struct StreamState {
+        /// Prossimo numero di sequenza atteso
+        uint32_t expected = 0;
+
+        /// Almeno un frame ricevuto
+        bool started = false;
+
+        /// Frame mancanti -> istante della rilevazione
+        std::map<uint32_t, int64_t> missing;
+    };
+
+    /// Durata della finestra
+    uint32_t windowMs;
+
+    /// Salto massimo riparabile
+    uint32_t maxGap;
+
+    /// Stato per stream
+    std::map<StreamId, StreamState> streams;
+
+    /// Contatori
+    RepairStats stats;
+};
+
+/**
+ * @brief Verifica se un frame ritrasmesso può arrivare prima della sua riproduzione
+ * @param nowUs Istante corrente sull'orologio della rete (µs)
+ * @param playoutTimeUs Istante di riproduzione del frame (µs)
+ * @param latencyUs Latenza stimata verso il sink (µs)
+ * @return true se la ritrasmissione è utile
+ */
+bool arrivesBeforePlayout(uint64_t nowUs, uint64_t playoutTimeUs, uint64_t latencyUs);
+
+} // namespace saber
+
+#endif // SABER_REPAIR_H
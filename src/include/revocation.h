@@ -0,0 +1,134 @@
+#ifndef SABER_REVOCATION_H
+#define SABER_REVOCATION_H
+
+#include <cstdint>
+#include <map>
+#include <mutex>
+#include <optional>
+#include <string>
+#include <vector>
+
+namespace saber {
+
+/// Intervallo tra le ritrasmissioni della lista di revoca da parte del Master (ms)
+constexpr uint32_t REVOCATION_ANNOUNCE_INTERVAL_MS = 30000;
+
+/// Voci della lista di revoca per pacchetto
+constexpr size_t REVOCATION_ENTRIES_PER_PACKET = 16;
+
+/**
+ * @brief Oggetto di una revoca
+ */
+enum class RevocationKind {
+    /// Un singolo token, per identificatore
+    Token,
+    /// Tutti i token rilasciati ad un nodo fino all'istante della revoca
+    Node
+};
+
+/**
+ * @brief Converte il tipo di una revoca nella sua rappresentazione testuale
+ * @param kind Tipo della revoca
+ * @return Nome del tipo ("token" o "node")
+ */
+std::string revocationKindToString(RevocationKind kind);
+
+/**
+ * @brief Converte una stringa nel tipo di una revoca
+ * @param name Nome del tipo
+ * @return Tipo, o std::nullopt se il nome non è valido
+ */
+std::optional<RevocationKind> revocationKindFromString(const std::string& name);
+
+/**
+ * @brief Voce della lista di revoca
+ */
+struct Revocation {
+    /// Oggetto della revoca
+    RevocationKind kind = RevocationKind::Token;
+
+    /// Identificatore del token o ID del nodo revocato
+    std::string subject;
+
+    /// Istante della revoca in millisecondi (orologio di sistema, come l'emissione dei token)
+    uint64_t revokedAtMs = 0;
+};
+
+/**
+ * @brief Codifica le voci di revoca per un comando della mesh
+ * @param entries Voci da codificare
+ * @return Testo "<tipo>:<oggetto>:<istante>,..."
+ */
+std::string encodeRevocations(const std::vector<Revocation>& entries);
+
+/**
+ * @brief Decodifica le voci di revoca ricevute da un comando della mesh
+ * @param text Testo prodotto da encodeRevocations()
+ * @return Voci, o std::nullopt se il testo è malformato
+ */
+std::optional<std::vector<Revocation>> parseRevocations(const std::string& text);
+
+/**
+ * @brief Lista di revoca dei token di sicurezza
+ *
+ * Il Master diffonde le revoche a tutta la rete e ogni nodo ne conserva
+ * una copia: un token revocato viene rifiutato anche prima della
+ * scadenza. Le revoche non si annullano, per cui unire due liste dà lo
+ * stesso risultato in qualunque ordine: per un nodo revocato più volte
+ * vale l'istante più recente, che invalida anche i token emessi nel
+ * frattempo.
+ */
+class RevocationList {
+public:
+    /**
+     * @brief Registra una revoca
+     * @param entry Voce da registrare
+     * @return true se la voce ha modificato la lista
+     */
+    bool add(const Revocation& entry);
+
+    /**
+     * @brief Unisce le voci di un'altra lista
+     * @param entries Voci ricevute
+     * @return Voci che hanno modificato la lista
+     */
+    std::vector<Revocation> merge(const std::vector<Revocation>& entries);
+
+    /**
+     * @brief Verifica se un token è stato revocato
+     * @param tokenId Identificatore del token
+     * @param nodeId Soggetto del token
+     * @param issuedAtMs Istante di emissione del token in millisecondi
+     * @return true se il token o il suo soggetto sono stati revocati dopo l'emissione
+     */
+    bool isRevoked(const std::string& tokenId, const std::string& nodeId, uint64_t issuedAtMs) const;
+
+    /**
+     * @brief Verifica se un identificatore di token è stato revocato
+     */
+    bool isTokenRevoked(const std::string& tokenId) const;
+
+    /**
+     * @brief Ottiene tutte le voci, prima i token e poi i nodi
+     */
+    std::vector<Revocation> entries() const;
+
+    /**
+     * @brief Numero di voci della lista
+     */
+    size_t size() const;
+
+private:
+    /// Token revocati e istante della revoca
+    std::map<std::string, uint64_t> tokens;
+
+    /// Nodi revocati e istante della revoca più recente
+    std::map<std::string, uint64_t> nodes;
+
+    /// Mutex per proteggere l'accesso concorrente
+    mutable std::mutex revocationMutex;
+};
+
+} // namespace saber
+
+#endif // SABER_REVOCATION_H
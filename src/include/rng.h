@@ -0,0 +1,88 @@
+#ifndef SABER_RNG_H
+#define SABER_RNG_H
+
+#include <cstddef>
+#include <cstdint>
+#include <memory>
+#include <mutex>
+#include <random>
+
+namespace saber {
+
+/**
+ * @brief Sorgente di byte casuali per le operazioni crittografiche
+ *
+ * Permette di sostituire il generatore del sistema operativo con uno
+ * deterministico nei test e nelle simulazioni.
+ */
+class RandomSource {
+public:
+    virtual ~RandomSource() = default;
+
+    /**
+     * @brief Riempie un buffer con byte casuali
+     * @param data Buffer di destinazione
+     * @param size Numero di byte da generare
+     * @throws std::runtime_error se la sorgente non è disponibile
+     */
+    virtual void fill(uint8_t* data, size_t size) = 0;
+};
+
+/**
+ * @brief Generatore crittografico del sistema operativo (predefinito)
+ */
+class OsRandomSource : public RandomSource {
+public:
+    void fill(uint8_t* data, size_t size) override;
+};
+
+/**
+ * @brief Sorgente casuale predefinita condivisa
+ * @return Generatore del sistema operativo
+ */
+std::shared_ptr<RandomSource> defaultRandomSource();
+
+#ifdef SABER_WITH_SEEDED_RNG
+/**
+ * @brief Generatore deterministico per test e simulazioni
+ *
+ * La stessa seed produce sempre la stessa sequenza su ogni piattaforma,
+ * così chiavi e nonce di una sessione possono essere riprodotti. Non è
+ * crittograficamente sicuro: disponibile solo con SABER_WITH_SEEDED_RNG.
+ */
+class SeededRandomSource : public RandomSource {
+public:
+    /**
+     * @brief Crea un generatore con una seed
+     * @param seed Seed della sequenza
+     */
+    explicit SeededRandomSource(uint64_t seed);
+
+    void fill(uint8_t* data, size_t size) override;
+
+    /**
+     * @brief Riporta la sequenza all'inizio
+     */
+    void reset();
+
+    /**
+     * @brief Ottiene la seed della sequenza
+     * @return Seed usata alla creazione
+     */
+    uint64_t getSeed() const;
+
+private:
+    /// Seed della sequenza
+    uint64_t seed;
+
+    /// Generatore (sequenza definita dallo standard)
+    std::mt19937_64 engine;
+
+    /// Mutex per il generatore
+    std::mutex engineMutex;
+};
+#endif
+
+} // namespace saber
+
+#endif // SABER_RNG_H
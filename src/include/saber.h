@@ -0,0 +1,57 @@
+#ifndef SABER_H
+#define SABER_H
+
+#include "saber_protocol.h"
+
+/**
+ * @file saber.h
+ * @brief Interfaccia pubblica della libreria SABER
+ *
+ * Le applicazioni includono solo questo header e usano i nomi riesportati
+ * in saber::prelude. Questi nomi seguono il versionamento semantico della
+ * libreria: rimuoverli o cambiarne la firma richiede una nuova versione
+ * maggiore. Gli altri tipi dichiarati negli header di include/ sono
+ * visibili perché compaiono nei membri privati di SaberProtocol, ma sono
+ * dettagli di implementazione e possono cambiare in qualsiasi versione.
+ */
+
+namespace saber {
+namespace prelude {
+
+// Nodo e ciclo di vita
+using saber::SaberProtocol;
+using saber::SaberConfig;
+using saber::ProtocolState;
+using saber::NodeRole;
+using saber::NodeInfo;
+using saber::StreamId;
+using saber::startMaster;
+using saber::startRepeater;
+using saber::startSink;
+using saber::startNode;
+
+// Audio
+using saber::AudioFrame;
+using saber::StreamMetadata;
+using saber::PartyMode;
+using saber::ScheduleEntry;
+
+// Eventi e resoconti
+using saber::JournalEvent;
+using saber::JournalPage;
+using saber::SecurityEvent;
+using saber::FailoverEvent;
+using saber::SessionReport;
+using saber::SyncProbeResult;
+using saber::CoverageReport;
+using saber::PreflightReport;
+
+// Errori
+using saber::LifecycleError;
+using saber::ConfigError;
+using saber::CryptoError;
+
+} // namespace prelude
+} // namespace saber
+
+#endif // SABER_H
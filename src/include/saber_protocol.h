@@ -1,18 +1,94 @@
 #ifndef SABER_PROTOCOL_H
 #define SABER_PROTOCOL_H
 
+#include "a2dp_bridge.h"
+#include "audio_output.h"
+#include "audio_source.h"
+#include "config.h"
+#include "congestion.h"
+#include "control_server.h"
 #include "crypto.h"
+#include "degradation.h"
+#include "discovery.h"
+#include "event_bus.h"
+#include "flow_control.h"
+#include "frame_crypto.h"
+#include "intercom.h"
+#include "journal.h"
+#include "log.h"
 #include "mesh.h"
+#include "pairing.h"
+#include "phantom.h"
+#include "playback_command.h"
+#include "planner.h"
+#include "preflight.h"
+#include "profile.h"
+#include "revocation.h"
+#include "scheduler.h"
+#include "session.h"
+#include "sim.h"
+#include "simulcast.h"
+#include "source_selector.h"
+#include "spec.h"
+#include "standby.h"
+#include "state.h"
+#include "state_store.h"
+#include "survey.h"
 #include "sync.h"
+#include "sync_probe.h"
+#include "timing.h"
+#include "udp_transport.h"
+#include "authorization.h"
 
+#ifdef SABER_WITH_HTTP
+#include "http_server.h"
+#endif
+
+#include <atomic>
+#include <condition_variable>
+#include <deque>
+#include <filesystem>
+#include <functional>
+#include <map>
 #include <memory>
 #include <optional>
+#include <set>
+#include <stdexcept>
 #include <string>
 #include <thread>
 #include <vector>
 
 namespace saber {
 
+/// Ritardo acustico massimo di una zona (ms): il suono percorre circa 690 m
+const uint32_t MAX_ZONE_DELAY_MS = 2000;
+
+/**
+ * @brief Destinazione RTP/AES67 di un sink che riproduce su un amplificatore di rete
+ */
+struct RtpTarget {
+    /// Indirizzo IPv4 unicast o multicast del ricevitore
+    std::string address = "239.69.0.1";
+    
+    /// Porta UDP di destinazione
+    uint16_t port = 5004;
+    
+    /// Payload type RTP dinamico (96-127)
+    uint8_t payloadType = 97;
+    
+    /// Formato dei campioni ("L24" o "L16")
+    std::string encoding = "L24";
+    
+    /// Durata audio di ogni pacchetto in microsecondi (AES67: 1000)
+    uint32_t packetTimeUs = 1000;
+    
+    /// Ritardo di riproduzione configurato sul ricevitore (ms)
+    uint32_t linkOffsetMs = 2;
+    
+    /// TTL dei pacchetti multicast
+    uint8_t multicastTtl = 16;
+};
+
 /**
  * @brief Configurazione per il nodo SABER
  */
@@ -29,11 +105,485 @@ struct SaberConfig {
     /// Flag che indica se il nodo riproduce audio musicale (48kHz) o vocale (16kHz)
     bool isMusicMode;
     
+    /// Porta degli endpoint /healthz e /readyz (richiede SABER_WITH_HTTP)
+    std::optional<uint16_t> healthPort = std::nullopt;
+    
+    /// Indirizzo su cui esporre gli endpoint di health-check
+    std::string healthBindAddress = "0.0.0.0";
+    
+    /// Passphrase della rete mesh
+    SecretString networkPassphrase;
+    
+    /// Broadcast code del flusso BIS (Auracast)
+    SecretString broadcastCode;
+    
+    /// Utente per l'integrazione MQTT
+    std::string mqttUsername;
+    
+    /// Password per l'integrazione MQTT
+    SecretString mqttPassword;
+    
+    /// Seed dell'identità Ed25519 in esadecimale (generata a caso se vuota)
+    SecretString identityKey;
+    
+    /// Chiave di rete in esadecimale (generata a caso se vuota, ricevuta dal Master con l'abbinamento)
+    SecretString networkKey;
+    
+    /// Keystore da cui sono stati risolti i segreti
+    std::optional<std::string> keystoreFile = std::nullopt;
+    
+    /// Riferimenti "env:" o "keystore:" con cui sono stati configurati i segreti
+    std::map<std::string, std::string> secretReferences;
+    
+    /// Notifica ai mittenti i pacchetti scartati con un pacchetto Reject firmato
+    bool sendRejects = false;
+    
+    /// Firma e cifra i pacchetti mesh con la chiave di rete, scartando quelli in chiaro
+    bool encryptPackets = true;
+    
+    /// Cifra comandi con destinatario e conferme con la chiave di sessione della coppia di nodi
+    bool sessionKeys = true;
+    
+    /// Porta del socket di controllo (disattivato se assente)
+    std::optional<uint16_t> controlPort = std::nullopt;
+    
+    /// Indirizzo del socket di controllo; va lasciato su loopback
+    std::string controlBindAddress = "127.0.0.1";
+    
+    /// File in cui scrivere un token admin all'avvio (nessuno se assente)
+    std::optional<std::string> controlTokenFile = std::nullopt;
+    
+    /// Validità dei token di controllo emessi all'avvio (secondi)
+    uint64_t controlTokenTtlSeconds = 86400;
+    
+    /// Validità dei token rilasciati ai nodi abbinati (secondi)
+    uint64_t pairingTokenTtlSeconds = 2592000;
+    
+    /// Filtro iniziale dei log (es. "info,mesh=debug")
+    std::string logFilter = "info";
+    
+    /// Granularità preferita per la cifratura dei frame audio
+    EncryptionGranularity frameEncryption = EncryptionGranularity::PerFrame;
+    
+    /// Nonce per mittente nella finestra anti-replay
+    uint32_t replayWindow = DEFAULT_REPLAY_WINDOW;
+    
+    /// File in cui persistere i contatori dei nonce (prefisso casuale ad ogni avvio se assente)
+    std::optional<std::string> nonceFile = std::nullopt;
+    
+    /// Euristiche di rilevamento delle intrusioni sul traffico mesh
+    IntrusionConfig intrusion;
+    
+    /// Parametri di specifica; sovrascriverli solo per sperimentazione
+    spec::Parameters spec;
+    
+    /// Finestra di riparazione dei frame audio tramite NACK
+    RepairConfig repair;
+    
+    /// Adattamento del buffer di jitter dei sink durante la riproduzione
+    JitterBufferConfig jitterBuffer;
+    
+    /// Scadenza di riproduzione comune adattata dal Master al sink più lento
+    DelayEqualizationConfig delayEqualization;
+    
+    /// Ampiezza del timestamp nei frame audio tra due ancore
+    TimestampWidth audioTimestampWidth = TimestampWidth::Bits24;
+    
+    /// Frame audio tra due timestamp completi (ancore)
+    uint32_t timestampAnchorFrames = 50;
+    
+    /// Stream dichiarati dal Master (se vuoto ogni sottoscrizione è accettata)
+    std::set<StreamId> publishedStreams;
+    
+    /// Stream a cui un sink si sottoscrive all'avvio
+    std::set<StreamId> listenStreams;
+    
+    /// Stream vocali (16kHz mono), ad esempio per gli annunci; gli altri usano il formato del nodo
+    std::set<StreamId> voiceStreams;
+    
+    /// Profilo d'uso applicato (vuoto se nessuno)
+    std::string profile;
+    
+    /// Codec dei frame audio ("lc3" o "pcm")
+    std::string codec = "lc3";
+    
+    /// Durata di un frame audio in microsecondi
+    uint32_t frameDurationUs = 10000;
+    
+    /// Bitrate del codec in kbps
+    uint32_t bitrateKbps = spec::BITRATE_MUSIC_KBPS;
+    
+    /// Correzione d'errore in avanti sui frame audio
+    bool fecEnabled = false;
+    
+    /// Scala di degrado applicata dal Master sotto una rete persistentemente scarsa
+    DegradationConfig degradation;
+    
+    /// Intervallo tra beacon temporali del Master (ms)
+    uint32_t beaconIntervalMs = spec::BEACON_INTERVAL_MS;
+    
+    /// Modalità intercom tra due nodi
+    IntercomConfig intercom;
+    
+    /// Standby automatico dei sink senza stream
+    StandbyConfig standby;
+    
+    /// Scelta del livello degli stream simulcast ricevuti dai sink
+    SimulcastConfig simulcast;
+    
+    /// Controllo di flusso del Master sul riempimento dei buffer dei sink
+    FlowConfig flow;
+    
+    /// Sorgenti audio del Master, con passaggio automatico a quella di riserva
+    SourceSelectionConfig sources;
+    
+    /// Ritardo acustico per zona, propagato dal Master ai sink della zona (ms)
+    std::map<std::string, uint32_t> zoneDelays;
+    
+    /// Richiede un adattatore Bluetooth acceso (implicito se btAddress è impostato)
+    bool requireBluetooth = false;
+    
+    /// Richiede un dispositivo di uscita audio (ignorato con uscita RTP)
+    bool requireAudioDevice = false;
+    
+    /// Uscita del sink: "local" (scheda audio) o "rtp" (amplificatore di rete)
+    std::string audioOutput = "local";
+    
+    /// Destinazione del flusso con audioOutput = "rtp"
+    RtpTarget rtpTarget;
+    
+    /// Collegamento della rete mesh: BLE gestito dall'applicazione o UDP multicast
+    TransportKind transport = TransportKind::Ble;
+    
+    /// Gruppo multicast con transport = Udp
+    UdpTransportConfig udp;
+    
+    /// Sink fantasma: consuma e conferma i flussi senza decodificare né riprodurre l'audio
+    bool phantomSink = false;
+    
+    /// Cuffie A2DP a cui un Repeater ritrasmette i flussi ricevuti (nessuna se assente)
+    std::optional<std::string> a2dpDevice = std::nullopt;
+    
+    /// Latenza nota del collegamento A2DP, compensata anticipando l'emissione (ms)
+    uint32_t a2dpLatencyMs = 200;
+    
+    /// Frame arrivati dopo l'istante di riproduzione: "drop", "late" (timeline che scorre) o "resync"
+    std::string lateFramePolicy = "drop";
+    
+    /// Ritardo tollerato prima di applicare lateFramePolicy (ms)
+    uint32_t lateFrameToleranceMs = 0;
+    
+    /// Risoluzione massima accettabile dell'orologio monotono (µs)
+    uint32_t maxClockResolutionUs = 1000;
+    
+    /// Richieste di ingresso al minuto a cui rispondere a finestra di provisioning chiusa
+    uint32_t joinAttemptsPerMinute = 6;
+    
+    /// Attesa massima della decisione della politica di autorizzazione esterna (ms)
+    uint32_t authorizationTimeoutMs = DEFAULT_AUTHORIZATION_TIMEOUT_MS;
+    
+    /// Decisione se la politica esterna non risponde in tempo (false = nega)
+    bool authorizationAllowOnTimeout = false;
+    
+    /// Nodi ammessi al massimo dal Master, Master incluso come nodo locale (0 = nessun limite)
+    uint32_t maxNodes = spec::MAX_NODES;
+    
+    /// Sink ammessi al massimo dal Master (0 = solo il limite sui nodi)
+    uint32_t maxSinks = 0;
+    
+    /// Tempo senza heartbeat dopo cui un nodo è considerato perso (ms)
+    uint32_t heartbeatTimeoutMs = spec::NODE_TIMEOUT_S * 1000;
+    
+    /// Tempo da perso dopo cui un nodo viene tolto dalla rete (ms, 0 = mai)
+    uint32_t nodeEvictAfterMs = DEFAULT_NODE_EVICT_AFTER_MS;
+    
+    /// Conferme e ritrasmissioni di comandi e richieste di ingresso
+    ReliableConfig reliable;
+    
+    /// Limiti di traffico in ingresso per mittente e tipo di pacchetto (i tipi assenti non sono limitati)
+    std::map<MeshPacketType, RateLimit> rateLimits = defaultRateLimits();
+    
+    /// Stima dello sfasamento nelle misure di sincronizzazione tra sink
+    AsymmetryMode syncProbeAsymmetry = AsymmetryMode::Symmetric;
+    
+    /// Intervallo tra gli scambi temporali con il Master (ms, 0 = solo beacon)
+    uint32_t timeExchangeIntervalMs = 1000;
+    
+    /// Intervallo tra i TimeBeacon inviati dal Master sulla rete mesh e inoltrati dai Repeater (ms, 0 = nessuno)
+    uint32_t meshBeaconIntervalMs = 0;
+    
+    /// Intervallo tra i sondaggi di un sopralluogo radio (ms)
+    uint32_t surveyIntervalMs = 1000;
+    
+    /// RSSI medio minimo perché una posizione del sopralluogo sia coperta (dBm)
+    int32_t surveyMinRssiDbm = -80;
+    
+    /// Perdita massima perché una posizione del sopralluogo sia coperta (%)
+    uint32_t surveyMaxLossPercent = 10;
+    
+    /// Annuncia il nodo via BLE e registra i nodi SABER scoperti dalla scansione
+    bool discoveryEnabled = true;
+    
+    /// Intervallo tra le trasmissioni dell'annuncio BLE (ms)
+    uint32_t discoveryIntervalMs = 1000;
+    
+    /// Intervallo senza annunci oltre cui un nodo scoperto viene dimenticato (ms)
+    uint32_t discoveryStaleMs = DEFAULT_DISCOVERY_STALE_MS;
+    
+    /// Registra gli span dei comandi, degli ingressi e delle barriere di avvio
+    bool tracingEnabled = false;
+    
+    /// Collettore OTLP/HTTP degli span (es. "http://collector:4318/v1/traces", nessuno se assente)
+    std::optional<std::string> otlpEndpoint = std::nullopt;
+    
+    /// Intervallo tra le esportazioni degli span (ms)
+    uint32_t otlpExportIntervalMs = 5000;
+    
+    /// File in cui il Master persiste la programmazione oraria (nessuno se assente)
+    std::optional<std::string> scheduleFile = std::nullopt;
+    
+    /// File in cui il nodo persiste nodi noti e zone, ripristinati all'avvio (nessuno se assente)
+    std::optional<std::string> stateFile = std::nullopt;
+    
+    /// Scostamento da UTC dell'ora dell'installazione usata dalla programmazione (minuti)
+    int32_t scheduleUtcOffsetMinutes = 0;
+    
+    /// Anticipo con cui viene fissato l'istante comune di avvio o arresto di un gruppo (ms)
+    uint32_t startBarrierLeadMs = 500;
+    
+    /// File del diario degli eventi della mesh e di sicurezza (solo in memoria se assente)
+    std::optional<std::string> eventJournalFile = std::nullopt;
+    
+    /// Eventi conservati nel diario
+    uint32_t eventJournalMaxEntries = 10000;
+    
+    /// Campioni conservati per fase dal profilatore della pipeline audio
+    uint32_t profileWindowSamples = 1024;
+    
+    /// File a cui aggiungere i tempi della pipeline per flamegraph (nessuno se assente)
+    std::optional<std::string> pipelineTraceFile = std::nullopt;
+    
+    /// Registra nei pacchetti generati localmente i nodi che li inoltrano (diagnostica)
+    bool recordPaths = false;
+    
+    /// Sorgente casuale per chiavi, nonce e token (quella del sistema se nullptr)
+    std::shared_ptr<RandomSource> randomSource = nullptr;
+    
+    /// File da cui è stata caricata la configurazione, ricaricato quando cambia
+    std::optional<std::string> configFile = std::nullopt;
+    
+    /// Intervallo tra i controlli delle modifiche al file di configurazione (ms, 0 = nessun controllo)
+    uint32_t configWatchIntervalMs = 1000;
+    
     /**
      * @brief Crea una configurazione di default
      * @return Configurazione di default
      */
     static SaberConfig defaultConfig();
+    
+    /**
+     * @brief Crea una configurazione di default per un profilo d'uso e un ruolo
+     * @param profileName Nome del profilo (es. "home-music")
+     * @param role Ruolo del nodo
+     * @return Configurazione con i parametri del profilo applicati
+     * @throws std::invalid_argument se il profilo non esiste
+     */
+    static SaberConfig forProfile(const std::string& profileName, NodeRole role);
+    
+    /**
+     * @brief Applica un profilo d'uso tenendo conto del ruolo del nodo
+     *
+     * Buffer di riproduzione e finestra di riparazione riguardano i sink;
+     * la cache delle ritrasmissioni riguarda Master e Repeater.
+     *
+     * @param profile Profilo da applicare
+     */
+    void applyProfile(const Profile& profile);
+    
+    /**
+     * @brief Prerequisiti dell'ambiente richiesti da questa configurazione
+     * @return Hardware, porte e risoluzione dell'orologio da verificare all'avvio
+     */
+    PreflightRequirements preflightRequirements() const;
+    
+    /**
+     * @brief Carica la configurazione da un file TOML
+     *
+     * I segreti possono essere indicati come "env:VARIABILE" o
+     * "keystore:NOME" (con security.keystore impostato) e vengono
+     * risolti in modo trasparente senza mai comparire nei log.
+     *
+     * @param path Percorso del file
+     * @return Configurazione caricata
+     * @throws ConfigError se il file o un segreto non sono validi
+     */
+    static SaberConfig fromFile(const std::string& path);
+    
+    /**
+     * @brief Verifica se una chiave del file può cambiare senza riavviare il nodo
+     *
+     * Sono applicabili a caldo filtro dei log, soglie di degrado e di
+     * rilevamento delle intrusioni, limiti di ammissione, riparazione dei
+     * frame e parametri del sopralluogo; identità, porte, audio e
+     * parametri di specifica richiedono un riavvio.
+     *
+     * @param key Chiave nella forma "sezione.chiave"
+     * @return true se la chiave è applicabile a caldo
+     */
+    static bool isLiveReloadable(const std::string& key);
+};
+
+/**
+ * @brief Esito del ricaricamento del file di configurazione
+ */
+struct ConfigReloadReport {
+    /// Chiavi cambiate e applicate a caldo
+    std::vector<std::string> applied;
+    
+    /// Chiavi cambiate che avranno effetto solo dopo un riavvio
+    std::vector<std::string> restartRequired;
+    
+    /// Errore di validazione: in tal caso nessuna modifica è stata applicata
+    std::optional<std::string> error;
+};
+
+/**
+ * @brief Gestione dei bassi assegnata al nodo dal Master
+ */
+struct BassSettings {
+    /// Ruolo nel gruppo: "full_range", "satellite" o "subwoofer"
+    std::string role = "full_range";
+    
+    /// Frequenza di crossover in Hz
+    float crossoverHz = 0.0f;
+};
+
+/**
+ * @brief Contatori dei riallineamenti d'emergenza degli orologi
+ */
+struct EmergencySyncStats {
+    /// Pacchetti EmergencySync inviati dal Master
+    uint64_t sent = 0;
+    
+    /// Sink indicati nei pacchetti inviati
+    uint64_t nodesTargeted = 0;
+    
+    /// Riallineamenti applicati dal nodo locale
+    uint64_t applied = 0;
+    
+    /// Istante dell'ultimo riallineamento applicato (ms, orologio sincronizzato; 0 se nessuno)
+    uint64_t lastAppliedAtMs = 0;
+};
+
+/**
+ * @brief Informazioni sul nodo locale mostrate dalle interfacce utente
+ */
+struct NodeInfo {
+    /// ID del nodo
+    std::string nodeId;
+    
+    /// Ruolo del nodo
+    NodeRole role;
+    
+    /// Stato di sincronizzazione
+    bool isSynchronized = false;
+    
+    /// Latenza corrente in millisecondi
+    uint32_t latency = 0;
+    
+    /// Brano in riproduzione per ciascuno stream noto
+    std::map<StreamId, StreamMetadata> nowPlaying;
+    
+    /// Playlist avviata per il gruppo del nodo (vuota se nessuna)
+    std::string activePlaylist;
+};
+
+/**
+ * @brief Modalità festa: più zone unite temporaneamente su un solo stream
+ */
+struct PartyMode {
+    /// Zone unite ("all" per l'intera rete)
+    std::vector<std::string> zones;
+    
+    /// Stream comune a tutte le zone
+    StreamId streamId = 0;
+    
+    /// Buffer unico applicato a tutti i sink delle zone (ms)
+    uint32_t bufferMs = 0;
+    
+    /// Sink esclusi perché oltre il budget di latenza (modalità rigorosa)
+    std::vector<std::string> rejected;
+    
+    /// Playlist avviata (vuota per la sorgente corrente)
+    std::string playlist;
+    
+    /// Istante comune di avvio sull'orologio sincronizzato (ms)
+    uint64_t startAtMs = 0;
+};
+
+/**
+ * @brief Stato dell'equalizzazione dei ritardi tra i sink
+ */
+struct DelayEqualizationStatus {
+    /// Scadenza comune: anticipo dell'istante di riproduzione sulla lettura della sorgente (ms, 0 se ignota)
+    uint32_t deadlineMs = 0;
+    
+    /// Sink con la latenza più alta, che determina la scadenza (solo sul Master)
+    std::string slowestSink;
+    
+    /// Attesa di ciascun sink per raggiungere la scadenza (ms, solo sul Master)
+    std::map<std::string, uint32_t> sinkDelaysMs;
+    
+    /// Latenza del nodo locale, compresa quella delle uscite audio (ms)
+    uint32_t latencyMs = 0;
+    
+    /// Attesa del nodo locale per raggiungere la scadenza (ms)
+    uint32_t localDelayMs = 0;
+};
+
+/**
+ * @brief Verifica se la libreria è stata compilata con SABER_MINIMAL_SINK
+ *
+ * La build minimale contiene solo la pipeline dei sink: il ruolo Master,
+ * la programmazione oraria e i server di controllo non sono disponibili.
+ *
+ * @return true nella build minimal-sink
+ */
+bool minimalSinkBuild();
+
+/**
+ * @brief Stato del ciclo di vita del protocollo
+ */
+enum class ProtocolState {
+    /// Protocollo non ancora inizializzato o arrestato
+    Stopped,
+    /// Inizializzazione in corso
+    Initializing,
+    /// Protocollo operativo
+    Running,
+    /// Arresto in corso
+    ShuttingDown
+};
+
+/**
+ * @brief Errore durante una transizione del ciclo di vita del protocollo
+ */
+class LifecycleError : public std::runtime_error {
+public:
+    enum class Type {
+        /// Il protocollo non è in esecuzione
+        NotRunning,
+        /// La nuova inizializzazione non è riuscita
+        InitializationFailed,
+        /// Transizione richiesta da un thread interno del protocollo
+        InvalidThread
+    };
+    
+    LifecycleError(Type type, const std::string& message);
+    Type getType() const;
+    
+private:
+    Type type;
 };
 
 /**
@@ -58,12 +608,66 @@ public:
      */
     bool initialize();
     
+    /**
+     * @brief Arresta il protocollo e rilascia la rete mesh
+     *
+     * Chiude le sessioni aperte, ferma la riproduzione, i server di
+     * controllo e di health-check, il thread di runtime e la rete mesh.
+     * Diario, programmazione e statistiche accumulate restano disponibili
+     * e il protocollo può essere riavviato con initialize() o restart().
+     * Non va chiamato dai gestori registrati sul protocollo.
+     *
+     * @throws LifecycleError se il protocollo non è in esecuzione o la
+     *         chiamata arriva da un thread interno
+     */
+    void shutdown();
+    
+    /**
+     * @brief Arresta, se in esecuzione, e reinizializza il protocollo
+     * @throws LifecycleError se la nuova inizializzazione non riesce
+     */
+    void restart();
+    
+    /**
+     * @brief Inizializza il protocollo in un thread separato
+     *
+     * Per i chiamanti che non devono bloccarsi durante la verifica
+     * dell'ambiente e l'avvio dei servizi. Il future va conservato: la sua
+     * distruzione attende la fine dell'inizializzazione.
+     *
+     * @return Future con l'esito di initialize()
+     */
+    std::future<bool> initializeAsync();
+    
+    /**
+     * @brief Cambia il ruolo del nodo
+     *
+     * Se il protocollo è in esecuzione viene riavviato con il nuovo ruolo,
+     * altrimenti il ruolo viene usato dalla prossima initialize().
+     *
+     * @param role Nuovo ruolo
+     * @throws LifecycleError se il riavvio non riesce
+     */
+    void setRole(NodeRole role);
+    
+    /**
+     * @brief Ottiene il ruolo del nodo
+     * @return Ruolo configurato
+     */
+    NodeRole getRole() const;
+    
     /**
      * @brief Ottiene il manager di sincronizzazione
      * @return Puntatore condiviso al manager di sincronizzazione
      */
     std::shared_ptr<SyncManager> getSyncManager() const;
     
+    /**
+     * @brief Ottiene il gestore crittografico del nodo
+     * @return Puntatore condiviso al gestore crittografico, nullo prima di initialize()
+     */
+    std::shared_ptr<MeshCrypto> getCrypto() const;
+    
     /**
      * @brief Avvia la riproduzione audio sincronizzata
      * @return true se l'avvio è avvenuto con successo, false altrimenti
@@ -83,6 +687,24 @@ public:
      */
     bool updateTimeSync(uint64_t masterTime);
     
+    /**
+     * @brief Riallinea di colpo gli orologi di alcuni sink a quello del Master
+     *
+     * Il Master lo fa da sé quando lo scambio temporale di un sink ne
+     * rivela l'orologio oltre la tolleranza di jitter. I sink indicati
+     * saltano all'orologio del Master e tacciono per un istante, finché il
+     * buffer di jitter non si riempie di frame allineati.
+     *
+     * @param nodeIds Sink da riallineare (vuoto per tutti)
+     * @return true se il pacchetto è stato inviato, false se il nodo non è il Master
+     */
+    bool requestEmergencySync(const std::vector<std::string>& nodeIds);
+    
+    /**
+     * @brief Ottiene i contatori dei riallineamenti d'emergenza
+     */
+    EmergencySyncStats getEmergencySyncStats() const;
+    
     /**
      * @brief Ottiene la latenza corrente
      * @return Latenza in millisecondi
@@ -106,56 +728,2385 @@ public:
     std::vector<std::string> getActiveNodes() const;
     
     /**
-     * @brief Verifica se il nodo è sincronizzato
-     * @return true se il nodo è sincronizzato, false altrimenti
+     * @brief Ottiene lo stato dei nodi attivi: ruolo, latenza, buffer e ultimo ping
+     * @return Istantanee dei nodi attivi, in ordine di ID
      */
-    bool isSynchronized() const;
+    std::vector<MeshNodeInfo> getActiveNodeInfo() const;
     
-private:
-    /// Configurazione del nodo
-    SaberConfig config;
+    /**
+     * @brief Ottiene i nodi tolti dalla rete per inattività
+     * @return ID dei nodi non ancora rientrati, in ordine
+     */
+    std::vector<std::string> getEvictedNodes() const;
     
-    /// Rete mesh per gestione dei nodi
-    std::unique_ptr<MeshNetwork> meshNetwork;
+    /**
+     * @brief Sottoscrive il nodo locale ad uno stream audio
+     * @param streamId Stream richiesto
+     * @return true se la richiesta è stata inviata, false altrimenti
+     */
+    bool subscribeStream(StreamId streamId);
     
-    /// Manager per la sincronizzazione
-    std::shared_ptr<SyncManager> syncManager;
+    /**
+     * @brief Revoca la sottoscrizione del nodo locale ad uno stream audio
+     * @param streamId Stream da abbandonare
+     * @return true se la richiesta è stata inviata, false altrimenti
+     */
+    bool unsubscribeStream(StreamId streamId);
     
-    /// Sincronizzatore audio
-    std::unique_ptr<AudioSync> audioSync;
+    /**
+     * @brief Dichiara uno stream trasmesso dal Master
+     *
+     * Dopo la prima dichiarazione la rete accetta solo le sottoscrizioni
+     * agli stream dichiarati.
+     *
+     * @param streamId Stream pubblicato
+     * @param isMusic Flag che indica se lo stream è musicale o vocale (16kHz mono)
+     * @return true se lo stream è stato dichiarato, false se il nodo non è il Master
+     */
+    bool publishStream(StreamId streamId, bool isMusic = true);
     
-    /// Thread per il runtime asincrono
-    std::unique_ptr<std::thread> runtimeThread;
+    /**
+     * @brief Imposta il formato dei frame di uno stream sul nodo locale
+     *
+     * Master e sink devono usare lo stesso formato per uno stream: i
+     * formati non viaggiano nella rete.
+     *
+     * @param streamId Stream da configurare
+     * @param isMusic Flag che indica se lo stream è musicale o vocale (16kHz mono)
+     */
+    void setStreamFormat(StreamId streamId, bool isMusic);
     
-    /// Flag per il thread di runtime
-    bool running;
+    /**
+     * @brief Cambia il profilo audio della rete durante la trasmissione (solo Master)
+     *
+     * Il profilo vale per il nodo e per gli stream pubblicati, esclusi quelli
+     * dell'interfono, e viene inviato ai sink. Ogni nodo svuota il buffer di
+     * jitter e le uscite audio degli stream e riprende la riproduzione senza
+     * perdere la sincronizzazione. Le sorgenti audio che non hanno il nuovo
+     * formato vengono staccate.
+     *
+     * @param profile Profilo musicale (48kHz, 128kbps) o vocale (16kHz, 64kbps)
+     * @return true se il cambio è stato inviato alla rete
+     */
+    bool setAudioProfile(AudioProfile profile);
     
-    /// Mutex per proteggere l'accesso concorrente
-    mutable std::mutex protocolMutex;
-};
-
-/**
- * @brief Funzione principale per l'inizializzazione di SABER in modalità Master (UCB)
- * @param nodeId ID del nodo (opzionale)
- * @param btAddress Indirizzo Bluetooth (opzionale)
- * @return Puntatore unico all'istanza del protocollo SABER
- */
-std::unique_ptr<SaberProtocol> startMaster(const std::optional<std::string>& nodeId = std::nullopt, 
-                         const std::optional<std::string>& btAddress = std::nullopt);
-
-/**
- * @brief Funzione principale per l'inizializzazione di SABER in modalità Repeater
- * @param nodeId ID del nodo (opzionale)
- * @param btAddress Indirizzo Bluetooth (opzionale)
- * @return Puntatore unico all'istanza del protocollo SABER
- */
-std::unique_ptr<SaberProtocol> startRepeater(const std::optional<std::string>& nodeId = std::nullopt,
-                           const std::optional<std::string>& btAddress = std::nullopt);
-
-/**
- * @brief Funzione principale per l'inizializzazione di SABER in modalità Sink (ricevitore)
- * @param nodeId ID del nodo (opzionale)
- * @param btAddress Indirizzo Bluetooth (opzionale)
+    /**
+     * @brief Ottiene il profilo audio del nodo
+     * @return Profilo musicale o vocale
+     */
+    AudioProfile getAudioProfile() const;
+    
+    /**
+     * @brief Esporta la topologia della rete, inclusi gli alberi di distribuzione
+     * @return Documento JSON, vuoto se la rete non è inizializzata
+     */
+    std::string exportTopology() const;
+    
+    /**
+     * @brief Ottiene il grafo della rete: nodi noti e collegamenti osservati
+     *
+     * Ai vicini ascoltati dal nodo locale si aggiungono quelli riportati
+     * periodicamente dagli altri nodi, così ogni nodo vede chi ascolta chi
+     * nell'intera rete.
+     *
+     * @return Grafo, vuoto se la rete non è inizializzata
+     */
+    TopologyGraph getTopology() const;
+    
+    /**
+     * @brief Esporta il grafo restituito da getTopology()
+     * @param format "dot" (Graphviz) o "json"
+     * @return Documento nel formato richiesto
+     * @throws std::invalid_argument se il formato non è supportato
+     */
+    std::string exportTopologyGraph(const std::string& format) const;
+    
+    /**
+     * @brief Ottiene il numero di pacchetti scartati per motivo
+     * @return Mappa nome del motivo -> contatore
+     */
+    std::map<std::string, uint64_t> getDropCounters() const;
+    
+    /**
+     * @brief Ottiene i pacchetti scartati per superamento dei limiti di traffico
+     * @return Scarti in totale, per tipo e per mittente
+     */
+    RateLimitStats getRateLimitStats() const;
+    
+    /**
+     * @brief Ottiene l'occupazione della rete rispetto ai limiti di ammissione
+     * @return Nodi e sink membri, limiti configurati e nodi ammessi o respinti
+     */
+    AdmissionStats getAdmissionStats() const;
+    
+    /**
+     * @brief Cambia la capienza della rete senza riavvio
+     *
+     * Il nodo locale occupa sempre un posto. Un nodo respinto può entrare
+     * al tentativo successivo se il nuovo limite gli lascia posto.
+     *
+     * @param maxNodes Nodi al massimo, nodo locale incluso (0 = nessun limite)
+     * @param maxSinks Sink al massimo (0 = solo il limite sui nodi)
+     * @return true se i limiti sono stati applicati
+     */
+    bool setCapacityLimits(uint32_t maxNodes, uint32_t maxSinks);
+    
+    /**
+     * @brief Ottiene le statistiche delle code di elaborazione dei pacchetti
+     * @return Pacchetti in coda, estratti e scartati per classe di priorità
+     */
+    std::map<PacketClass, PacketQueueStats> getPacketQueueStats() const;
+    
+    /**
+     * @brief Ottiene gli eventi di sicurezza rilevati
+     * @return Vettore di eventi, dal più vecchio al più recente
+     */
+    std::vector<SecurityEvent> getSecurityEvents() const;
+    
+    /**
+     * @brief Ottiene i nodi in quarantena per conflitto di chiavi
+     * @return Vettore di ID dei nodi in quarantena
+     */
+    std::vector<std::string> getQuarantinedNodes() const;
+    
+    /**
+     * @brief Aggiorna qualità e latenza di un percorso verso un nodo
+     *
+     * Chiamato dai trasporti ad ogni misura; il collegamento usato per i
+     * frame audio viene scelto tra i percorsi riportati, e gli alberi di
+     * distribuzione preferiscono i nodi con la qualità migliore.
+     *
+     * @param peer Nodo remoto
+     * @param kind Tipo di collegamento
+     * @param linkQuality Qualità del collegamento (0-1, frazione di pacchetti consegnati)
+     * @param latencyMs Latenza misurata sul percorso
+     * @param rssiDbm RSSI misurato dal collegamento, se disponibile
+     * @return true se la misura è stata registrata
+     */
+    bool reportLinkQuality(const std::string& peer, TransportKind kind, double linkQuality, uint32_t latencyMs,
+                           std::optional<int32_t> rssiDbm = std::nullopt);
+    
+    /**
+     * @brief Ottiene la qualità dei collegamenti verso i nodi noti
+     * @return Mappa nodo -> RSSI, tasso di consegna e punteggio
+     */
+    std::map<std::string, LinkQuality> getLinkQualities() const;
+    
+    /**
+     * @brief Segnala la caduta di un percorso verso un nodo
+     * @param peer Nodo remoto
+     * @param kind Tipo di collegamento
+     * @return true se la segnalazione è stata registrata
+     */
+    bool reportLinkDown(const std::string& peer, TransportKind kind);
+    
+    /**
+     * @brief Ottiene i contatori della selezione dei collegamenti
+     * @return Cambi di collegamento, pacchetti per collegamento e percorsi attivi
+     */
+    TransportStats getTransportStats() const;
+    
+    /**
+     * @brief Ottiene gli eventi di cambio collegamento
+     * @return Eventi in ordine cronologico
+     */
+    std::vector<FailoverEvent> getFailoverEvents() const;
+    
+    /**
+     * @brief Ottiene gli eventi della mesh e di sicurezza successivi ad un cursore
+     *
+     * Un client che si riconnette passa l'ultimo cursore visto e riceve
+     * tutte le transizioni avvenute nel frattempo; truncated segnala che
+     * alcune sono già state scartate dal diario.
+     *
+     * @param cursor Ultimo cursore visto (0 per partire dall'inizio)
+     * @param limit Numero massimo di eventi restituiti
+     * @return Pagina di eventi
+     */
+    JournalPage getEventsSince(uint64_t cursor, size_t limit = 256) const;
+    
+    /**
+     * @brief Risolve un conflitto di chiavi scegliendo la chiave attendibile
+     * @param nodeId ID del nodo in quarantena
+     * @param trustedKey Chiave pubblica da considerare attendibile
+     * @return true se il conflitto è stato risolto, false altrimenti
+     */
+    bool resolveKeyConflict(const std::string& nodeId, const std::vector<uint8_t>& trustedKey);
+    
+    /**
+     * @brief Ottiene gli allarmi più recenti del rilevatore di intrusioni
+     * @return Allarmi dal più vecchio al più recente
+     */
+    std::vector<IntrusionAlert> getIntrusionAlerts() const;
+    
+    /**
+     * @brief Ricarica il file di configurazione e applica le modifiche sicure
+     *
+     * Il file viene validato per intero prima di applicare qualsiasi
+     * modifica; le chiavi che richiedono un riavvio vengono solo
+     * riportate e restano al valore in uso. Ogni ricaricamento finisce
+     * nel diario degli eventi. Viene chiamato anche dal thread di runtime
+     * quando il file cambia.
+     *
+     * @return Chiavi applicate, chiavi che richiedono un riavvio o errore
+     */
+    ConfigReloadReport reloadConfig();
+    
+    /**
+     * @brief Rimuove la quarantena imposta dal rilevatore di intrusioni
+     * @param nodeId ID del nodo
+     * @return true se il nodo era in quarantena per comportamento anomalo
+     */
+    bool releaseQuarantine(const std::string& nodeId);
+    
+    /**
+     * @brief Modifica il filtro dei log a runtime
+     *
+     * Senza nodo di destinazione il filtro viene applicato localmente e,
+     * se meshWide è true, propagato a tutti i nodi con un comando di
+     * amministrazione "log.set". Con un nodo di destinazione il comando
+     * viene inviato solo a quel nodo.
+     *
+     * @param filter Filtro nella sintassi "target=livello,..." (es. "mesh=debug,sync=trace")
+     * @param meshWide Propaga il filtro a tutta la rete mesh
+     * @param targetNode Nodo a cui applicare il filtro (opzionale)
+     * @return true se il filtro è valido ed è stato applicato o inviato
+     */
+    bool setLogFilter(const std::string& filter, bool meshWide = false,
+                      const std::optional<std::string>& targetNode = std::nullopt);
+    
+    /**
+     * @brief Ottiene il filtro dei log attivo sul nodo
+     * @return Filtro nella sintassi accettata da setLogFilter
+     */
+    std::string getLogFilter() const;
+    
+    /**
+     * @brief Apre la finestra di provisioning per i nuovi nodi
+     *
+     * Solo mentre la finestra è aperta i nodi sconosciuti possono entrare
+     * nella rete; la finestra si chiude da sola alla scadenza.
+     *
+     * @param durationSeconds Durata dell'apertura
+     * @return true se la finestra è stata aperta
+     */
+    bool openProvisioning(uint32_t durationSeconds);
+    
+    /**
+     * @brief Chiude la finestra di provisioning prima della scadenza
+     * @return true se la finestra è stata chiusa
+     */
+    bool closeProvisioning();
+    
+    /**
+     * @brief Ottiene lo stato della finestra di provisioning
+     * @return Apertura, tempo rimanente e contatori delle richieste
+     */
+    ProvisioningStatus getProvisioningStatus() const;
+    
+    /**
+     * @brief Chiede l'ingresso nella rete (da chiamare su un nodo nuovo)
+     * @return true se la richiesta è stata inviata
+     */
+    bool requestJoin();
+    
+    /**
+     * @brief Emette un token per le interfacce di controllo locali
+     *
+     * Il token è firmato con la chiave d'identità del nodo e vale solo
+     * per questo nodo; va presentato con "auth <token>" sul socket di
+     * controllo.
+     *
+     * @param scope Ambito concesso (sola lettura o admin)
+     * @param ttlSeconds Durata di validità in secondi
+     * @return Token in forma esadecimale, vuoto se il nodo non è inizializzato
+     */
+    std::string issueControlToken(TokenScope scope, uint64_t ttlSeconds);
+    
+    /**
+     * @brief Verifica un token delle interfacce di controllo
+     * @param token Token in forma esadecimale
+     * @return Contenuto del token, o std::nullopt se non valido, scaduto o revocato
+     */
+    std::optional<SecurityToken> verifyControlToken(const std::string& token) const;
+    
+    /**
+     * @brief Revoca un token delle interfacce di controllo
+     *
+     * Sul Master la revoca viene diffusa a tutta la rete; sugli altri
+     * nodi vale solo localmente.
+     *
+     * @param tokenId Identificatore del token (SecurityToken::tokenId)
+     * @return true se la revoca è stata registrata
+     */
+    bool revokeControlToken(const std::string& tokenId);
+    
+    /**
+     * @brief Revoca tutti i token rilasciati ad un nodo fino ad ora
+     *
+     * I token emessi dopo la revoca restano validi. Sul Master la revoca
+     * viene diffusa a tutta la rete; sugli altri nodi vale solo localmente.
+     *
+     * @param nodeId Soggetto dei token da revocare
+     * @return true se la revoca è stata registrata
+     */
+    bool revokeNodeTokens(const std::string& nodeId);
+    
+    /**
+     * @brief Ottiene la lista di revoca del nodo
+     * @return Token e nodi revocati, locali o ricevuti dal Master
+     */
+    std::vector<Revocation> getRevocations() const;
+    
+    /**
+     * @brief Apre l'abbinamento di nuovi nodi con un PIN (solo Master)
+     *
+     * Il PIN vale per un solo nodo e decade allo scadere della durata o
+     * dopo PAIRING_MAX_FAILURES prove errate.
+     *
+     * @param durationSeconds Validità del PIN in secondi
+     * @return PIN e ID del Master da mostrare all'operatore, o std::nullopt se il nodo non è il Master
+     */
+    std::optional<PairingCode> openPairing(uint32_t durationSeconds);
+    
+    /**
+     * @brief Chiude l'abbinamento aperto invalidando il PIN
+     * @return true se un abbinamento era aperto
+     */
+    bool closePairing();
+    
+    /**
+     * @brief Avvia l'abbinamento del nodo locale con il PIN mostrato dal Master
+     *
+     * Al termine il nodo adotta la chiave di rete del Master e riceve un
+     * token di sicurezza; l'esito si legge da getPairingStatus().
+     *
+     * @param code PIN o codice letto dal QR del Master
+     * @return true se l'abbinamento è stato avviato
+     */
+    bool startPairing(const std::string& code);
+    
+    /**
+     * @brief Ottiene lo stato dell'abbinamento
+     */
+    PairingStatus getPairingStatus() const;
+    
+    /**
+     * @brief Configura la gestione dei bassi di una zona
+     *
+     * Il subwoofer designato riceve il mix mono passa-basso, gli altri sink
+     * della zona il mix passa-alto. Il filtraggio avviene sui sink.
+     *
+     * @param zone Zona da configurare
+     * @param subwooferNodeId Sink della zona che fa da subwoofer
+     * @param crossoverHz Frequenza di crossover in Hz
+     * @return true se la configurazione è stata inviata ai sink della zona
+     */
+    bool configureBassManagement(const std::string& zone, const std::string& subwooferNodeId,
+                                 float crossoverHz);
+    
+    /**
+     * @brief Ottiene la gestione dei bassi ricevuta dal Master
+     * @return Ruolo e crossover da applicare alla pipeline audio locale
+     */
+    BassSettings getBassSettings() const;
+    
+    /**
+     * @brief Pubblica i metadati del brano in riproduzione su uno stream
+     *
+     * Da chiamare ad ogni cambio brano. La revisione viene incrementata
+     * automaticamente e la copertina, se presente, viene indicizzata per
+     * hash e servita ai nodi che la richiedono.
+     *
+     * @param metadata Metadati del brano (revisione e hash vengono calcolati)
+     * @param artwork Byte della copertina, vuoto se assente
+     * @return true se i metadati sono stati inviati alla rete
+     */
+    bool publishStreamMetadata(const StreamMetadata& metadata, const std::vector<uint8_t>& artwork = {});
+    
+    /**
+     * @brief Ottiene i metadati correnti di uno stream
+     * @param streamId Stream richiesto
+     * @return Metadati, o nullopt se lo stream non ne ha
+     */
+    std::optional<StreamMetadata> getStreamMetadata(StreamId streamId) const;
+    
+    /**
+     * @brief Richiede alla rete la copertina con l'hash indicato
+     * @param artworkHash Hash SHA-256 (esadecimale) della copertina
+     * @return true se la richiesta è stata inviata o la copertina è già disponibile
+     */
+    bool requestArtwork(const std::string& artworkHash);
+    
+    /**
+     * @brief Ottiene una copertina già ricevuta o pubblicata
+     * @param artworkHash Hash SHA-256 (esadecimale) della copertina
+     * @return Byte dell'immagine, o nullopt se non disponibile
+     */
+    std::optional<std::vector<uint8_t>> getArtwork(const std::string& artworkHash) const;
+    
+    /**
+     * @brief Ottiene le informazioni sul nodo locale
+     * @return ID, ruolo, sincronizzazione, latenza e brani in riproduzione
+     */
+    NodeInfo getNodeInfo() const;
+    
+    /**
+     * @brief Ottiene l'utilizzo della banda per stream, nodo vicino e zona
+     * @return Resoconto sulla finestra di misura corrente
+     */
+    BandwidthReport getBandwidthReport() const;
+    
+    /**
+     * @brief Ottiene gli istogrammi di latenza, il jitter e la perdita stimata per nodo
+     *
+     * Le latenze arrivano dai resoconti di stato dei nodi e dalle misure di
+     * sincronizzazione (metà del tempo di andata e ritorno); la perdita è
+     * stimata dai numeri di sequenza dei frame audio ricevuti.
+     *
+     * @return Resoconto per nodo e aggregato
+     */
+    NetworkStats getNetworkStats() const;
+    
+    /**
+     * @brief Esporta le metriche del nodo nel formato testuale di Prometheus
+     *
+     * È il documento servito dall'endpoint /metrics quando la libreria è
+     * compilata con SABER_ENABLE_HTTP e la porta di health-check è configurata.
+     *
+     * @return Metriche in formato di esposizione Prometheus
+     */
+    std::string getPrometheusMetrics() const;
+    
+    /**
+     * @brief Esporta lo stato del nodo come documento JSON versionato
+     *
+     * Il documento contiene identità, configurazione, zone, nodi noti con
+     * le loro chiavi pubbliche (e i relativi key-ID) e i riferimenti ai
+     * segreti: un segreto configurato in chiaro compare come null e va
+     * configurato di nuovo sul dispositivo di destinazione.
+     *
+     * @return Documento di stato
+     */
+    std::string exportState() const;
+    
+    /**
+     * @brief Importa lo stato esportato da un altro nodo
+     *
+     * Va chiamato prima di initialize(): identità, configurazione e
+     * segreti vengono applicati subito, nodi noti e zone all'avvio della
+     * rete mesh. I riferimenti ai segreti vengono risolti tramite il
+     * keystore indicato nel documento.
+     *
+     * @param json Documento prodotto da exportState()
+     * @throws std::invalid_argument se il documento non è valido o il protocollo è già stato avviato
+     * @throws ConfigError se un segreto non può essere risolto
+     */
+    void importState(const std::string& json);
+    
+    /**
+     * @brief Ottiene lo stato di congestione del canale radio
+     * @return Stato calcolato dall'ultima misura del tempo d'aria
+     */
+    CongestionState getCongestionState() const;
+    
+    /**
+     * @brief Ottiene il bitrate LC3 consigliato dal controllore di congestione
+     * @return Bitrate in kbps per la modalità audio del nodo
+     */
+    uint32_t getRecommendedBitrateKbps() const;
+    
+    /**
+     * @brief Imposta la priorità dei frame di uno stream pubblicato
+     *
+     * Quando il buffer di un sink trabocca il Master scarta i frame degli
+     * stream a bassa priorità; per uno stream simulcast vale per tutti i livelli.
+     *
+     * @param streamId Stream di interesse
+     * @param priority Priorità dei frame (default Normal)
+     */
+    void setStreamPriority(StreamId streamId, FramePriority priority);
+    
+    /**
+     * @brief Ottiene la priorità dei frame di uno stream
+     */
+    FramePriority getStreamPriority(StreamId streamId) const;
+    
+    /**
+     * @brief Ottiene lo stato del controllo di flusso del Master
+     * @return Stato, riempimenti riportati dai sink e frame scartati
+     */
+    FlowStatus getFlowStatus() const;
+    
+    /**
+     * @brief Ottiene i parametri audio imposti dalla scala di degrado
+     * @return Gradino corrente con bitrate, FEC, buffer e frequenza di campionamento
+     */
+    DegradationSettings getDegradationSettings() const;
+    
+    /**
+     * @brief Ottiene i sink sospesi dall'ultimo gradino della scala di degrado
+     * @return ID dei sink sospesi
+     */
+    std::vector<std::string> getSuspendedSinks() const;
+    
+    /**
+     * @brief Ottiene i contatori della riparazione dei frame audio
+     * @return NACK inviati e ricevuti, ritrasmissioni, riparazioni e perdite mascherate
+     */
+    RepairStats getRepairStats() const;
+    
+    /**
+     * @brief Ottiene i contatori della consegna affidabile dei comandi
+     * @return Invii, ritrasmissioni, conferme, consegne fallite e duplicati
+     */
+    ReliableStats getReliableStats() const;
+    
+    /**
+     * @brief Ottiene i contatori delle chiavi di sessione con i peer
+     * @return Derivazioni, riusi dalla cache e peer con una chiave
+     */
+    SessionStats getSessionStats() const;
+    
+    /**
+     * @brief Ottiene lo stato del buffer di jitter
+     * @return Dimensione corrente, jitter e perdita stimati, buchi e anticipi del buffer
+     */
+    JitterBufferStats getJitterBufferStats() const;
+    
+    /**
+     * @brief Ottiene i contatori del sink fantasma
+     * @return Frame consumati, byte, frame in ritardo e anticipo per stream (vuoto se non è un sink fantasma)
+     */
+    std::vector<PhantomStreamStats> getPhantomStats() const;
+    
+    /**
+     * @brief Ottiene i contatori del ponte verso le cuffie A2DP
+     * @return Frame inoltrati e in ritardo sull'emissione (std::nullopt se il ponte non è attivo)
+     */
+    std::optional<A2dpBridgeStats> getA2dpBridgeStats() const;
+    
+    /**
+     * @brief Ottiene i resoconti delle sessioni di riproduzione concluse
+     *
+     * Ogni sink conserva i propri resoconti e li invia al Master, che
+     * raccoglie quelli di tutta la rete.
+     *
+     * @param nodeId Sink di cui leggere i resoconti (vuoto per tutti)
+     * @return Resoconti dal più vecchio al più recente
+     */
+    std::vector<SessionReport> getSessionReports(const std::string& nodeId = "") const;
+    
+    /**
+     * @brief Avvia la misura dello sfasamento tra l'orologio locale e quello di un altro sink
+     *
+     * Gli inneschi partono dal thread di runtime, uno per ciclo; l'esito è
+     * disponibile con getSyncProbeResults() e nel giornale degli eventi.
+     *
+     * @param peer Sink da misurare
+     * @param samples Scambi da completare
+     * @param click Se true, entrambi i sink emettono un clic allo stesso istante di rete
+     * @return true se la misura è stata avviata
+     */
+    bool startSyncProbe(const std::string& peer, uint32_t samples = 20, bool click = false);
+    
+    /**
+     * @brief Ottiene l'ultimo esito della misura di sincronizzazione per ogni sink misurato
+     */
+    std::vector<SyncProbeResult> getSyncProbeResults() const;
+    
+    /**
+     * @brief Associa all'ultima misura verso un sink lo sfasamento registrato tra i due clic
+     * @param peer Sink misurato
+     * @param skewUs Ritardo del clic del peer rispetto a quello locale (µs)
+     * @return true se esiste una misura verso il sink
+     */
+    bool reportAcousticSkew(const std::string& peer, int64_t skewUs);
+    
+    /**
+     * @brief Registra chi emette il clic di una misura acustica
+     *
+     * Il destinatario riceve l'istante di rete (µs) a cui emettere il clic
+     * dal proprio dispositivo audio; viene chiamato dal thread di rete e
+     * non deve bloccarlo.
+     *
+     * @param handler Funzione chiamata con il peer e l'istante del clic (vuota per disattivare)
+     */
+    void setSyncClickHandler(std::function<void(const std::string&, uint64_t)> handler);
+    
+    /**
+     * @brief Registra chi viene avvisato alla perdita o al recupero della sincronizzazione
+     *
+     * La riproduzione locale viene già sospesa e ripresa dal protocollo;
+     * il gestore serve all'applicazione (es. per silenziare l'uscita).
+     * Con spec.beacon_timeout_ms a 0 la sincronizzazione non si perde mai.
+     *
+     * @param handler Funzione chiamata con il nuovo stato (vuota per disattivare)
+     */
+    void setSyncStateHandler(std::function<void(bool)> handler);
+    
+    /**
+     * @brief Registra una funzione chiamata quando un nodo entra nella rete o torna a rispondere
+     *
+     * Come le altre funzioni on*(), viene chiamata dal thread del bus degli
+     * eventi e non dai thread della rete.
+     *
+     * @param callback Funzione chiamata con l'ID del nodo
+     * @return Identificatore per removeEventCallback()
+     */
+    uint32_t onNodeJoined(std::function<void(const std::string&)> callback);
+    
+    /**
+     * @brief Registra una funzione chiamata quando un nodo smette di rispondere
+     * @param callback Funzione chiamata con l'ID del nodo
+     * @return Identificatore per removeEventCallback()
+     */
+    uint32_t onNodeLeft(std::function<void(const std::string&)> callback);
+    
+    /**
+     * @brief Registra una funzione chiamata quando il nodo perde la sincronizzazione
+     * @param callback Funzione da chiamare
+     * @return Identificatore per removeEventCallback()
+     */
+    uint32_t onSyncLost(std::function<void()> callback);
+    
+    /**
+     * @brief Registra una funzione chiamata quando la latenza di un nodo supera il budget
+     *
+     * L'avviso viene ripetuto solo dopo che la latenza del nodo è rientrata
+     * nel budget.
+     *
+     * @param callback Funzione chiamata con nodo, latenza e budget in ms
+     * @return Identificatore per removeEventCallback()
+     */
+    uint32_t onLatencyAlert(std::function<void(const std::string&, uint32_t, uint32_t)> callback);
+    
+    /**
+     * @brief Registra un gestore per tutti gli eventi del protocollo
+     * @param handler Gestore da avvisare
+     * @return Identificatore per removeEventCallback()
+     */
+    uint32_t addEventHandler(std::shared_ptr<EventHandler> handler);
+    
+    /**
+     * @brief Annulla la registrazione di una funzione o di un gestore
+     * @param id Identificatore restituito alla registrazione
+     * @return true se la registrazione esisteva
+     */
+    bool removeEventCallback(uint32_t id);
+    
+    /**
+     * @brief Attende la consegna degli eventi già accodati
+     */
+    void flushEvents();
+    
+    /**
+     * @brief Avvia un sopralluogo radio da questo nodo
+     *
+     * Il nodo, portato in giro per il locale, sonda tutti i nodi attivi ad
+     * ogni intervallo; le misure sono attribuite alla posizione impostata
+     * con setSurveyPosition(). Un nuovo sopralluogo scarta le misure del
+     * precedente.
+     *
+     * @return true se il sopralluogo è stato avviato
+     */
+    bool startSurvey();
+    
+    /**
+     * @brief Imposta l'etichetta della posizione corrente del sopralluogo
+     * @param label Etichetta inserita dall'operatore (es. "palco sinistra")
+     * @return true se esiste un sopralluogo in corso e l'etichetta è valida
+     */
+    bool setSurveyPosition(const std::string& label);
+    
+    /**
+     * @brief Ferma i sondaggi conservando le misure raccolte
+     * @return true se era in corso un sopralluogo
+     */
+    bool stopSurvey();
+    
+    /**
+     * @brief Ottiene il resoconto di copertura dell'ultimo sopralluogo
+     * @return Resoconto, o nullopt se non è mai stato avviato un sopralluogo
+     */
+    std::optional<CoverageReport> getCoverageReport() const;
+    
+    /**
+     * @brief Registra chi fornisce l'RSSI delle risposte ai sondaggi
+     *
+     * Viene chiamato dal thread di rete con il nodo che ha risposto e
+     * restituisce l'RSSI dell'ultimo pacchetto ricevuto da quel nodo; senza
+     * fornitore il sopralluogo misura solo la perdita.
+     *
+     * @param provider Funzione che restituisce l'RSSI in dBm (vuota per disattivare)
+     */
+    void setRssiProvider(std::function<std::optional<int32_t>(const std::string&)> provider);
+    
+    /**
+     * @brief Registra chi trasmette l'annuncio BLE del nodo
+     *
+     * Il thread di runtime lo chiama periodicamente con le strutture AD da
+     * trasmettere, che contengono il UUID del servizio SABER, il ruolo e
+     * l'ID del nodo.
+     *
+     * @param advertiser Funzione che trasmette l'annuncio e restituisce l'esito (vuota per disattivare)
+     */
+    void setAdvertiser(std::function<bool(const std::vector<uint8_t>&)> advertiser);
+    
+    /**
+     * @brief Riporta un annuncio BLE ricevuto dalla scansione dell'host
+     *
+     * Gli annunci di altri nodi SABER registrano il nodo nella rete mesh
+     * con il ruolo annunciato e ne aggiornano la potenza del segnale.
+     *
+     * @param address Indirizzo BLE del mittente
+     * @param data Strutture AD dell'annuncio
+     * @param rssiDbm RSSI di ricezione (dBm)
+     * @return true se l'annuncio era di un nodo SABER registrato
+     */
+    bool reportAdvertisement(const std::string& address, const std::vector<uint8_t>& data, int32_t rssiDbm);
+    
+    /**
+     * @brief Ottiene i nodi scoperti tramite gli annunci BLE
+     * @return Nodi con ruolo, indirizzo e RSSI, in ordine di ID
+     */
+    std::vector<DiscoveredNode> getDiscoveredNodes() const;
+    
+    /**
+     * @brief Preleva gli span conclusi non ancora esportati
+     *
+     * Senza un collettore OTLP configurato gli span restano in coda finché
+     * non vengono prelevati (fino a MAX_PENDING_SPANS).
+     *
+     * @return Span in ordine di chiusura
+     */
+    std::vector<TraceSpan> takeTraceSpans();
+    
+    /**
+     * @brief Attiva o disattiva la registrazione del percorso nei pacchetti locali
+     *
+     * Sul Master permette ai sink di ricostruire il percorso dei frame
+     * audio; ogni hop aggiunge due byte ai pacchetti.
+     *
+     * @param enabled true per registrare il percorso
+     * @return true se l'impostazione è stata applicata
+     */
+    bool setPathRecording(bool enabled);
+    
+    /**
+     * @brief Ottiene il percorso dell'ultimo frame ricevuto localmente per ogni stream
+     */
+    std::vector<RouteTrace> getRouteTraces() const;
+    
+    /**
+     * @brief Chiede a un sink il percorso degli ultimi frame ricevuti
+     *
+     * La risposta arriva in modo asincrono ed è letta con getRemoteRouteTraces().
+     *
+     * @param sink ID del sink
+     * @return true se la richiesta è stata inviata
+     */
+    bool requestRouteTrace(const std::string& sink);
+    
+    /**
+     * @brief Ottiene i percorsi riportati da un sink
+     * @param sink ID del sink
+     * @return Percorsi in ordine di stream (vuoto se il sink non ha ancora risposto)
+     */
+    std::vector<RouteTrace> getRemoteRouteTraces(const std::string& sink) const;
+    
+    /**
+     * @brief Registra la durata di una fase della pipeline misurata fuori dal protocollo
+     *
+     * Invio e ricezione sono misurati dal protocollo; codifica, cifratura,
+     * decodifica, ricampionamento e riproduzione vengono riportati dal
+     * motore audio.
+     *
+     * @param stage Fase della pipeline
+     * @param durationUs Durata in microsecondi
+     */
+    void recordPipelineStage(PipelineStage stage, uint64_t durationUs);
+    
+    /**
+     * @brief Ottiene i percentili dei tempi della pipeline audio
+     * @return Tempi delle fasi con almeno un campione, in ordine di pipeline
+     */
+    std::vector<StageTiming> getPipelineTimings() const;
+    
+    /**
+     * @brief Attiva o disattiva il tracciamento dei tempi su file
+     * @param path File in formato a pile compresse (vuoto per disattivare)
+     * @return true se il file è stato aperto o il tracciamento disattivato
+     */
+    bool setPipelineTraceFile(const std::string& path);
+    
+    /**
+     * @brief Invia un frame audio codificato di uno stream pubblicato
+     *
+     * Il numero di sequenza del frame è assegnato automaticamente; il
+     * timestamp viene compresso secondo la configurazione, con un'ancora
+     * periodica a 64 bit.
+     *
+     * @param streamId Stream a cui appartiene il frame
+     * @param playoutTimeUs Istante di riproduzione sull'orologio della rete (µs)
+     * @param payload Frame codificato
+     * @return true se il frame è stato inviato, false anche se scartato dal controllo di flusso
+     */
+    bool sendAudioFrame(StreamId streamId, uint64_t playoutTimeUs, const std::vector<uint8_t>& payload);
+    
+    /**
+     * @brief Codifica in LC3 e invia un frame PCM di uno stream pubblicato
+     * @param streamId Stream a cui appartiene il frame
+     * @param frame Frame di 10ms; il suo istante di riproduzione è quello del pacchetto
+     * @return true se il frame è stato codificato e inviato
+     */
+    bool sendPcmFrame(StreamId streamId, const AudioFrame& frame);
+    
+    /**
+     * @brief Collega una sorgente audio ad uno stream pubblicato dal Master
+     *
+     * Un thread dedicato legge la sorgente un frame ogni 10ms e lo invia
+     * come sendPcmFrame(), con l'istante di riproduzione spostato avanti
+     * del buffer predefinito (spec.defaultBufferMs). La sorgente che già
+     * alimentava lo stream viene fermata; una sorgente finita smette di
+     * trasmettere ma resta collegata fino alla successiva.
+     *
+     * @param streamId Stream da alimentare
+     * @param source Sorgente nel formato dello stream (nullptr per scollegare quella attuale)
+     * @return true se la sorgente è stata collegata o scollegata, false se il nodo non è il Master
+     * @throws std::invalid_argument se la sorgente non ha il formato dello stream
+     */
+    bool setAudioSource(StreamId streamId, std::shared_ptr<AudioSource> source);
+    
+    /**
+     * @brief Ottiene i contatori delle sorgenti audio collegate
+     * @return Contatori per stream
+     */
+    std::map<StreamId, AudioSourceStats> getAudioSourceStats() const;
+    
+    /**
+     * @brief Registra il destinatario dei frame decodificati dal sink
+     *
+     * Il sink decodifica in LC3 i frame Audio ricevuti e ricostruisce
+     * quelli persi tra due frame consecutivi dello stesso stream. Il
+     * destinatario è chiamato dal thread di rete e non deve bloccarlo.
+     *
+     * @param handler Funzione chiamata per ogni frame (vuota per disattivare)
+     */
+    void setAudioFrameHandler(std::function<void(StreamId, const AudioFrame&)> handler);
+    
+    /**
+     * @brief Collega un'uscita audio ad uno stream riprodotto dal sink
+     *
+     * I frame decodificati dello stream, con il ritardo acustico della
+     * zona già applicato, attendono in coda e un thread dedicato li scrive
+     * sull'uscita quando l'orologio della rete raggiunge il loro istante di
+     * riproduzione, anticipato della latenza dell'uscita. L'uscita che già
+     * riproduceva lo stream viene fermata; le uscite restano collegate
+     * fino allo spegnimento del nodo.
+     *
+     * @param streamId Stream da riprodurre
+     * @param output Uscita (nullptr per scollegare quella attuale)
+     */
+    void setAudioOutput(StreamId streamId, std::shared_ptr<AudioOutput> output);
+    
+    /**
+     * @brief Ottiene i contatori delle uscite audio collegate
+     * @return Contatori per stream
+     */
+    std::map<StreamId, AudioOutputStats> getAudioOutputStats() const;
+    
+    /**
+     * @brief Tipo di callback per i pacchetti destinati ad un ponte verso un'altra rete
+     */
+    using BridgeHandler = std::function<void(const MeshPacket&)>;
+    
+    /**
+     * @brief Registra il ponte che riceve i pacchetti Audio e Metadata degli altri nodi
+     *
+     * Il gestore riceve i pacchetti già decifrati, dal thread di rete:
+     * non deve bloccarlo né trasmettere sulla stessa rete.
+     *
+     * @param handler Funzione chiamata per ogni pacchetto (vuota per disattivare)
+     */
+    void setBridgeHandler(BridgeHandler handler);
+    
+    /**
+     * @brief Ripropone sulla rete un pacchetto arrivato da un'altra rete attraverso un ponte
+     *
+     * Il pacchetto parte dal nodo locale, firmato e cifrato con la chiave
+     * di questa rete; l'istante dei frame audio va già portato
+     * sull'orologio di questa rete.
+     *
+     * @param packet Pacchetto Audio o Metadata senza intestazione
+     * @return true se il pacchetto è stato inviato, false se la rete non è avviata
+     */
+    bool sendBridgedPacket(const MeshPacket& packet);
+    
+    /**
+     * @brief Invia un pacchetto nella rete mesh senza attenderne la consegna
+     *
+     * I comandi si considerano consegnati quando tutti i nodi attivi li
+     * hanno confermati, gli altri pacchetti appena trasmessi. I gestori
+     * registrati sul protocollo non devono attendere il future.
+     *
+     * @param packet Pacchetto da inviare
+     * @return Future con l'esito della consegna, già risolto con false se la rete non è avviata
+     */
+    std::future<bool> sendPacketAsync(const MeshPacket& packet);
+    
+    /**
+     * @brief Concorda la granularità di cifratura dei frame audio con un nodo
+     * @param peerCapabilities Capacità di cifratura annunciate dal nodo remoto
+     * @return Granularità concordata, o nullopt se non ce n'è una comune
+     */
+    std::optional<EncryptionGranularity> negotiateFrameEncryption(uint8_t peerCapabilities) const;
+    
+    /**
+     * @brief Assegna un nodo ad una zona
+     * @param nodeId ID del nodo
+     * @param zone Nome della zona
+     * @return true se l'assegnazione è avvenuta, false se la rete non è inizializzata
+     */
+    bool assignZone(const std::string& nodeId, const std::string& zone);
+    
+    /**
+     * @brief Imposta il ritardo acustico di una zona
+     *
+     * Il ritardo si somma all'istante di riproduzione sincronizzato di
+     * ogni frame dei sink della zona, ad esempio perché le casse in fondo
+     * al giardino suonino insieme al suono che arriva dal palco. Il Master
+     * lo propaga da solo ai sink della zona, anche a quelli assegnati o
+     * tornati attivi in seguito.
+     *
+     * @param zone Nome della zona
+     * @param delayMs Ritardo in millisecondi (0 per rimuoverlo)
+     * @return true se il ritardo è stato registrato, false se il nodo non è il Master
+     * @throws std::invalid_argument se il ritardo supera MAX_ZONE_DELAY_MS
+     */
+    bool setZoneDelay(const std::string& zone, uint32_t delayMs);
+    
+    /**
+     * @brief Ottiene i ritardi acustici delle zone impostati sul Master
+     * @return Ritardo in millisecondi per zona
+     */
+    std::map<std::string, uint32_t> getZoneDelays() const;
+    
+    /**
+     * @brief Ottiene il ritardo acustico ricevuto dal Master per la zona del nodo
+     * @return Ritardo in millisecondi applicato ai frame riprodotti
+     */
+    uint32_t getAcousticDelayMs() const;
+    
+    /**
+     * @brief Ottiene lo stato dell'equalizzazione dei ritardi tra i sink
+     *
+     * Il Master fissa una scadenza di riproduzione comune sulla latenza del
+     * sink più lento e marca con essa i frame delle sorgenti audio; ogni
+     * sink attende fino all'istante marcato, così la riproduzione resta
+     * allineata al campione in tutta la stanza.
+     *
+     * @return Scadenza, attese dei sink e del nodo locale
+     */
+    DelayEqualizationStatus getDelayEqualization() const;
+    
+    /**
+     * @brief Pubblica uno stream codificato a più livelli di qualità
+     *
+     * Ogni frame inviato con sendPcmFrame() sullo stream del gruppo viene
+     * codificato una volta per livello, al bitrate del livello. Il Master
+     * annuncia periodicamente il gruppo: i sink sottoscritti allo stream
+     * scelgono da soli il livello adatto al proprio collegamento, così un
+     * collegamento debole non abbassa la qualità dell'intera zona.
+     *
+     * @param group Gruppo da pubblicare (il primo livello è lo stream stesso)
+     * @param isMusic Flag che indica se lo stream è musicale o vocale (16kHz mono)
+     * @return true se il gruppo è stato pubblicato, false se il nodo non è il Master
+     * @throws std::invalid_argument se il gruppo non è ben formato
+     */
+    bool publishSimulcast(const SimulcastGroup& group, bool isMusic = true);
+    
+    /**
+     * @brief Smette di codificare i livelli inferiori di uno stream simulcast
+     *
+     * I sink tornano a ricevere lo stream stesso.
+     *
+     * @param streamId Stream del gruppo
+     * @return true se il gruppo era pubblicato
+     */
+    bool stopSimulcast(StreamId streamId);
+    
+    /**
+     * @brief Ottiene i gruppi simulcast pubblicati (Master) o annunciati dal Master (sink)
+     */
+    std::vector<SimulcastGroup> getSimulcastGroups() const;
+    
+    /**
+     * @brief Impone la banda disponibile per la scelta dei livelli simulcast
+     *
+     * Senza una banda imposta il sink la stima dalla perdita misurata
+     * sul livello che riceve.
+     *
+     * @param budgetKbps Banda in kbps (std::nullopt per tornare alla stima)
+     */
+    void setSimulcastBudget(std::optional<uint32_t> budgetKbps);
+    
+    /**
+     * @brief Ottiene lo stato della ricezione degli stream simulcast sottoscritti
+     */
+    std::vector<SimulcastStatus> getSimulcastStatus() const;
+    
+    /**
+     * @brief Calcola i suggerimenti di separazione dei sink troppo lenti
+     *
+     * Un sink la cui latenza porta il buffer dell'intera zona oltre il
+     * budget viene proposto per un gruppo ritardato separato.
+     *
+     * @return Suggerimenti di separazione
+     */
+    std::vector<GroupSplitSuggestion> suggestGroupSplits() const;
+    
+    /**
+     * @brief Applica un suggerimento di separazione spostando il sink nel gruppo ritardato
+     * @param suggestion Suggerimento da applicare
+     * @return true se il suggerimento è stato applicato
+     */
+    bool applyGroupSplit(const GroupSplitSuggestion& suggestion);
+    
+    /**
+     * @brief Avvia la riproduzione di un gruppo ad un istante comune
+     *
+     * L'istante di avvio (barriera) viene fissato sull'orologio
+     * sincronizzato con startBarrierLeadMs di anticipo, così che tutti i
+     * nodi della zona partano insieme.
+     *
+     * @param zone Zona da avviare ("all" per l'intera rete)
+     * @param playlist Playlist da riprodurre (vuota per la sorgente corrente)
+     * @return true se il comando è stato inviato
+     */
+    bool startGroupPlayback(const std::string& zone, const std::string& playlist = "");
+    
+    /**
+     * @brief Ferma la riproduzione di un gruppo ad un istante comune
+     * @param zone Zona da fermare ("all" per l'intera rete)
+     * @return true se il comando è stato inviato
+     */
+    bool stopGroupPlayback(const std::string& zone);
+    
+    /**
+     * @brief Mette in pausa la riproduzione di una zona ad un istante comune
+     *
+     * I nodi della zona continuano a ricevere e decodificare lo stream ma
+     * smettono di consegnare i frame (AudioSync::pausePlayback()), così la
+     * ripresa non deve attendere la ricostruzione dello stato del decoder.
+     *
+     * @param zone Zona da mettere in pausa ("all" per l'intera rete)
+     * @return true se il comando è stato inviato
+     */
+    bool pauseGroupPlayback(const std::string& zone);
+    
+    /**
+     * @brief Riprende la riproduzione di una zona messa in pausa
+     * @param zone Zona da riprendere ("all" per l'intera rete)
+     * @return true se il comando è stato inviato
+     */
+    bool resumeGroupPlayback(const std::string& zone);
+    
+    /**
+     * @brief Imposta il volume dei nodi di una zona ad un istante comune
+     * @param zone Zona da regolare ("all" per l'intera rete)
+     * @param volumePercent Volume in percentuale (0 silenzia la zona)
+     * @return true se il comando è stato inviato
+     * @throws std::invalid_argument se il volume supera 100
+     */
+    bool setZoneVolume(const std::string& zone, uint8_t volumePercent);
+    
+    /**
+     * @brief Sposta la riproduzione di una zona ad una posizione del brano
+     *
+     * Lo stream resta continuo: è l'applicazione a cambiare posizione, il
+     * Master nella sorgente ed i sink svuotando l'uscita, tramite il
+     * gestore di setPlaybackCommandHandler().
+     *
+     * @param zone Zona da spostare ("all" per l'intera rete)
+     * @param positionMs Posizione nel brano in millisecondi
+     * @return true se il comando è stato inviato
+     */
+    bool seekGroupPlayback(const std::string& zone, uint64_t positionMs);
+    
+    /**
+     * @brief Imposta la funzione chiamata per ogni comando di riproduzione eseguito dal nodo
+     *
+     * È chiamata alla barriera, dopo che il protocollo ha applicato la
+     * propria parte, dal thread di runtime; un'eccezione fa confermare il
+     * comando come fallito.
+     *
+     * @param handler Funzione chiamata con il comando eseguito
+     */
+    void setPlaybackCommandHandler(std::function<void(const PlaybackCommand&)> handler);
+    
+    /**
+     * @brief Ottiene l'esito dei comandi di riproduzione inviati da questo nodo
+     * @return Stato per nodo dei comandi più recenti, dal più vecchio
+     */
+    std::vector<CommandStatus> getCommandStatuses() const;
+    
+    /**
+     * @brief Ottiene l'esito di un comando di riproduzione inviato da questo nodo
+     * @param id Identificatore del comando
+     * @return Stato per nodo, o nullopt se il comando non è più nel registro
+     */
+    std::optional<CommandStatus> getCommandStatus(uint32_t id) const;
+    
+    /**
+     * @brief Ottiene il volume applicato dal nodo ai frame riprodotti
+     * @return Volume in percentuale
+     */
+    uint8_t getVolume() const;
+    
+    /**
+     * @brief Verifica se la riproduzione della zona del nodo è in pausa
+     */
+    bool isPlaybackPaused() const;
+    
+    /**
+     * @brief Unisce più zone su un solo stream con un avvio comune (modalità festa)
+     *
+     * Il Master dimensiona un buffer unico sul sink più lento di tutte le
+     * zone e fissa la barriera di avvio; ogni sink coinvolto ricorda i
+     * propri stream, passa allo stream comune e li ripristina all'uscita
+     * dalla modalità festa.
+     *
+     * @param zones Zone da unire ("all" per l'intera rete)
+     * @param streamId Stream comune
+     * @param playlist Playlist da riprodurre (vuota per la sorgente corrente)
+     * @return true se il comando è stato inviato
+     * @throws std::invalid_argument se nessuna zona è indicata o la modalità festa è già attiva
+     */
+    bool startPartyMode(const std::vector<std::string>& zones, StreamId streamId, 
+                        const std::string& playlist = "");
+    
+    /**
+     * @brief Termina la modalità festa e ripristina gli stream di ogni zona
+     * @return true se il comando è stato inviato, false se la modalità festa non è attiva
+     */
+    bool stopPartyMode();
+    
+    /**
+     * @brief Ottiene la modalità festa in corso
+     * @return Zone unite, stream e buffer comuni (std::nullopt se non attiva)
+     */
+    std::optional<PartyMode> getPartyMode() const;
+    
+    /**
+     * @brief Avvia una conversazione intercom in duplex tra due nodi
+     *
+     * Ogni nodo coinvolto sottoscrive lo stream vocale dell'altro; la
+     * musica sui suoi sink viene attenuata mentre qualcuno parla.
+     *
+     * @param nodeA Primo nodo
+     * @param nodeB Secondo nodo
+     * @return true se il comando è stato inviato
+     * @throws std::invalid_argument se i nodi mancano, coincidono o un intercom è già attivo
+     */
+    bool startIntercom(const std::string& nodeA, const std::string& nodeB);
+    
+    /**
+     * @brief Termina la conversazione intercom in corso
+     * @return true se il comando è stato inviato, false se l'intercom non è attivo
+     */
+    bool stopIntercom();
+    
+    /**
+     * @brief Preme o rilascia il push-to-talk del nodo locale
+     * @param talking true mentre il nodo parla
+     * @return true se il comando è stato inviato, false se il nodo non partecipa all'intercom
+     */
+    bool setIntercomTalking(bool talking);
+    
+    /**
+     * @brief Codifica e invia un frame vocale all'altro nodo dell'intercom
+     *
+     * Il frame deve essere a 16kHz mono; viene inviato solo mentre il
+     * push-to-talk del nodo locale è premuto.
+     *
+     * @param frame Frame PCM catturato dal microfono
+     * @return true se il frame è stato inviato
+     */
+    bool sendIntercomFrame(const AudioFrame& frame);
+    
+    /**
+     * @brief Imposta il destinatario dei frame vocali decodificati
+     * @param handler Funzione chiamata con il nodo che parla e il frame
+     */
+    void setIntercomFrameHandler(std::function<void(const std::string&, const AudioFrame&)> handler);
+    
+    /**
+     * @brief Ottiene la conversazione intercom a cui partecipa la rete
+     * @return Nodi coinvolti e chi sta parlando (std::nullopt se non attiva)
+     */
+    std::optional<IntercomSession> getIntercomSession() const;
+    
+    /**
+     * @brief Intervallo tra beacon del Master, più breve durante una conversazione intercom
+     * @return Intervallo in millisecondi
+     */
+    uint32_t getBeaconIntervalMs() const;
+    
+    /**
+     * @brief Imposta la funzione che spegne e riaccende il dispositivo audio
+     *
+     * Chiamata con true all'ingresso in standby e con false all'inizio del
+     * risveglio, un pre-roll prima che il sink torni attivo.
+     *
+     * @param handler Funzione chiamata con il nuovo stato del dispositivo
+     */
+    void setStandbyHandler(std::function<void(bool)> handler);
+    
+    /**
+     * @brief Ottiene lo stato di alimentazione del nodo
+     * @return Stato corrente (sempre attivo per Master e Repeater)
+     */
+    PowerState getPowerState() const;
+    
+    /**
+     * @brief Risveglia il nodo locale dallo standby (es. da un pulsante)
+     */
+    void wakeFromStandby();
+    
+    /**
+     * @brief Invia il comando di risveglio ai sink in standby
+     * @param nodeId Sink da risvegliare (tutti se vuoto)
+     * @return true se il comando è stato inviato, false altrimenti
+     */
+    bool wakeSinks(const std::string& nodeId = "");
+    
+    /**
+     * @brief Offre un frame PCM letto da una sorgente audio del Master
+     *
+     * L'applicazione offre i frame di tutte le sorgenti configurate; solo
+     * quelli della sorgente attiva vengono codificati e inviati. I frame
+     * offerti servono anche a riconoscere una sorgente ferma o muta.
+     *
+     * @param source Sorgente che ha prodotto il frame
+     * @param streamId Stream su cui inviare il frame
+     * @param frame Frame di 10ms
+     * @return true se il frame è stato inviato
+     */
+    bool sendSourceFrame(const std::string& source, StreamId streamId, const AudioFrame& frame);
+    
+    /**
+     * @brief Passa manualmente ad una sorgente audio
+     *
+     * Il ritorno automatico alla sorgente preferita resta sospeso finché
+     * la sorgente scelta è in salute o fino a resumeAutomaticSource().
+     *
+     * @param source Sorgente da attivare
+     * @throws std::invalid_argument se la sorgente non è configurata
+     */
+    void switchSource(const std::string& source);
+    
+    /**
+     * @brief Torna alla scelta automatica della sorgente dopo un cambio manuale
+     */
+    void resumeAutomaticSource();
+    
+    /**
+     * @brief Ottiene la sorgente audio trasmessa
+     * @return Nome della sorgente, o nullopt se nessuna è ancora in salute
+     */
+    std::optional<std::string> getActiveSource() const;
+    
+    /**
+     * @brief Ottiene lo stato delle sorgenti audio configurate
+     * @return Sorgenti nell'ordine dei parametri
+     */
+    std::vector<SourceStatus> getSourceStatus() const;
+    
+#ifndef SABER_MINIMAL_SINK
+    /**
+     * @brief Aggiunge un'azione alla programmazione oraria del Master
+     * @param minuteOfDay Minuto del giorno nell'ora dell'installazione
+     * @param days Giorni attivi (bit 0 = lunedì)
+     * @param command Riga da eseguire tramite il dispatcher dei comandi
+     * @return Identificatore dell'azione
+     * @throws std::invalid_argument se l'azione non è valida
+     */
+    uint32_t addScheduledAction(uint16_t minuteOfDay, uint8_t days, const std::string& command);
+    
+    /**
+     * @brief Modifica un'azione della programmazione oraria
+     * @return true se l'azione esisteva
+     * @throws std::invalid_argument se l'azione non è valida
+     */
+    bool updateScheduledAction(uint32_t id, uint16_t minuteOfDay, uint8_t days, const std::string& command);
+    
+    /**
+     * @brief Rimuove un'azione dalla programmazione oraria
+     * @return true se l'azione esisteva
+     */
+    bool removeScheduledAction(uint32_t id);
+    
+    /**
+     * @brief Ottiene la programmazione oraria
+     * @return Azioni programmate, ordinate per identificatore
+     */
+    std::vector<ScheduleEntry> getSchedule() const;
+#endif
+    
+    /**
+     * @brief Verifica se il nodo è sincronizzato
+     * @return true se il nodo è sincronizzato, false altrimenti
+     */
+    bool isSynchronized() const;
+    
+    /**
+     * @brief Ottiene l'orologio della rete, sincronizzato con il Master
+     * @return Tempo in microsecondi
+     */
+    uint64_t getNetworkTimeUs() const;
+    
+    /**
+     * @brief Ottiene lo stato del ciclo di vita del protocollo
+     * @return Stato corrente
+     */
+    ProtocolState getState() const;
+    
+    /**
+     * @brief Ottiene l'esito dell'ultima verifica dell'ambiente
+     *
+     * La verifica viene eseguita da initialize(); se fallisce, i problemi
+     * riportati indicano l'azione necessaria per avviare il nodo.
+     *
+     * @return Esito della verifica (vuoto se initialize() non è stato chiamato)
+     */
+    PreflightReport getPreflightReport() const;
+    
+    /**
+     * @brief Ottiene l'esito del ripristino dello stato persistito
+     *
+     * Con stateFile configurato, initialize() ripristina nodi noti e zone
+     * dall'ultimo stato consistente su disco; da quel momento ogni loro
+     * modifica viene registrata prima di proseguire.
+     *
+     * @return Esito del ripristino (vuoto senza stateFile o prima di initialize())
+     */
+    std::optional<StateRecoveryReport> getStateRecovery() const;
+    
+    /**
+     * @brief Ottiene i contatori del trasporto UDP multicast
+     * @return Contatori (vuoto se la rete mesh non usa il trasporto UDP)
+     */
+    std::optional<UdpTransportStats> getUdpTransportStats() const;
+    
+    /**
+     * @brief Collega il nodo ad una rete simulata al posto del BLE o dell'UDP
+     *
+     * Va chiamato prima di initialize(): all'avvio il nodo si collega alla
+     * rete con il proprio ID e se ne scollega all'arresto. Più protocolli
+     * nello stesso processo formano così una rete mesh completa senza
+     * hardware, con i pacchetti consegnati ad ogni SimNetwork::advance().
+     *
+     * @param network Rete simulata condivisa (nullptr per tornare al collegamento configurato)
+     */
+    void setSimNetwork(std::shared_ptr<SimNetwork> network);
+    
+    /**
+     * @brief Imposta la politica esterna consultata su ingressi e comandi privilegiati
+     *
+     * Sul Master la politica decide, dopo le verifiche del protocollo, le
+     * richieste di ingresso nella rete; su ogni nodo decide i comandi con
+     * ambito Admin ricevuti sul socket di controllo. Senza risposta entro
+     * authorizationTimeoutMs vale authorizationAllowOnTimeout.
+     *
+     * @param hook Politica (nullptr per seguire solo le regole del protocollo)
+     */
+    void setAuthorizationHook(std::shared_ptr<AuthorizationHook> hook);
+    
+    /**
+     * @brief Imposta la politica esterna come funzione
+     *
+     * Equivalente a setAuthorizationHook() per le applicazioni (e i binding
+     * Python) che non definiscono una classe: la funzione riceve la
+     * richiesta e la funzione con cui rispondere, anche più tardi.
+     *
+     * @param handler Funzione che valuta le richieste (vuota per disattivare)
+     */
+    void setAuthorizationHandler(FunctionAuthorizationHook::Function handler);
+    
+    /**
+     * @brief Ottiene i contatori delle richieste sottoposte alla politica esterna
+     */
+    AuthorizationStats getAuthorizationStats() const;
+    
+    /**
+     * @brief Verifica la liveness del nodo
+     *
+     * Il nodo è vivo se il protocollo è in esecuzione e il thread di runtime
+     * non è bloccato. Un nodo non vivo va riavviato.
+     *
+     * @return true se il nodo è vivo, false altrimenti
+     */
+    bool isLive() const;
+    
+    /**
+     * @brief Verifica la readiness del nodo
+     *
+     * Il nodo è pronto a servire audio se è vivo, sincronizzato e con la
+     * rete mesh attiva.
+     *
+     * @return true se il nodo è pronto, false altrimenti
+     */
+    bool isReady() const;
+    
+private:
+    /// Configurazione del nodo
+    SaberConfig config;
+    
+    /// Stato importato da applicare all'avvio della rete mesh
+    std::optional<NodeState> importedState;
+    
+    /// Archivio dei nodi noti e delle zone (nessuno senza stateFile)
+    std::unique_ptr<StateStore> stateStore;
+    
+    /// Collegamento UDP multicast della rete mesh (nessuno con il BLE)
+    std::unique_ptr<UdpTransport> udpTransport;
+    
+    /// Sorgenti audio collegate agli stream del Master
+    std::map<StreamId, std::unique_ptr<AudioSourcePump>> audioSources;
+    
+    /// Rete simulata dei test di integrazione (nessuna in esercizio)
+    std::shared_ptr<SimNetwork> simNetwork;
+    
+    /// Esito del ripristino dello stato all'avvio (protetto da eventsMutex)
+    std::optional<StateRecoveryReport> stateRecovery;
+    
+    /// Bus degli eventi per le applicazioni (sopravvive alla rete mesh)
+    EventBus eventBus;
+    
+    /// Consultazione della politica di autorizzazione esterna (sopravvive alla rete mesh)
+    AuthorizationGate authorizationGate;
+    
+    /// Rete mesh per gestione dei nodi
+    std::unique_ptr<MeshNetwork> meshNetwork;
+    
+    /// Manager per la sincronizzazione
+    std::shared_ptr<SyncManager> syncManager;
+    
+    /// Gestore crittografico del nodo
+    std::shared_ptr<MeshCrypto> crypto;
+    
+    /// Pianificatore dei buffer per zona
+    LatencyPlanner planner;
+    
+    /// Socket di controllo per l'amministrazione locale
+    std::unique_ptr<ControlServer> controlServer;
+    
+    /**
+     * @brief Gestisce i comandi di amministrazione ricevuti dalla rete mesh
+     * @param packet Pacchetto ricevuto
+     */
+    void handleAdminCommand(const MeshPacket& packet);
+    
+    /**
+     * @brief Sottopone un comando privilegiato del socket di controllo alla politica esterna
+     * @param line Riga di comando
+     * @param token Token presentato sulla connessione (vuoto se nessuno)
+     * @return Motivo del rifiuto, o std::nullopt se il comando può essere eseguito
+     */
+    std::optional<std::string> authorizeCommand(const std::string& line, const std::string& token);
+    
+    /**
+     * @brief Esegue il comando "log" del socket di controllo
+     * @param args Argomenti del comando
+     * @return Risposta per l'operatore
+     */
+    std::string runLogCommand(const std::vector<std::string>& args);
+    
+    /**
+     * @brief Esegue il comando "config" del socket di controllo
+     * @param args config reload
+     * @return Chiavi applicate e chiavi che richiedono un riavvio
+     */
+    std::string runConfigCommand(const std::vector<std::string>& args);
+    
+    /**
+     * @brief Esegue il comando "token" del socket di controllo
+     * @param args Argomenti del comando
+     * @return Risposta per l'operatore
+     */
+    std::string runTokenCommand(const std::vector<std::string>& args);
+    
+    /**
+     * @brief Esegue il comando "pair" del socket di controllo
+     * @param args Argomenti del comando
+     * @return Risposta per l'operatore
+     */
+    std::string runPairCommand(const std::vector<std::string>& args);
+    
+    /**
+     * @brief Esegue il comando "provision" del socket di controllo
+     * @param args Argomenti del comando
+     * @return Risposta per l'operatore
+     */
+    std::string runProvisionCommand(const std::vector<std::string>& args);
+    
+    /**
+     * @brief Esegue il comando "events" del socket di controllo
+     * @param args Argomenti del comando
+     * @return Risposta per l'operatore
+     */
+    std::string runEventsCommand(const std::vector<std::string>& args);
+    
+    /**
+     * @brief Esegue il comando "playback" del socket di controllo
+     * @param args Argomenti del comando
+     * @return Risposta per l'operatore
+     */
+    std::string runPlaybackCommand(const std::vector<std::string>& args);
+    
+    /**
+     * @brief Esegue il comando "party" del socket di controllo
+     * @param args Argomenti del comando
+     * @return Risposta per l'operatore
+     */
+    std::string runPartyCommand(const std::vector<std::string>& args);
+    
+    /**
+     * @brief Esegue il comando "intercom" del socket di controllo
+     * @param args Argomenti del comando
+     * @return Risposta per l'operatore
+     */
+    std::string runIntercomCommand(const std::vector<std::string>& args);
+    
+    /**
+     * @brief Esegue il comando "standby" del socket di controllo
+     * @param args Argomenti del comando
+     * @return Risposta per l'operatore
+     */
+    std::string runStandbyCommand(const std::vector<std::string>& args);
+    
+    /**
+     * @brief Esegue il comando "source" del socket di controllo
+     * @param args Argomenti del comando
+     * @return Risposta per l'operatore
+     */
+    std::string runSourceCommand(const std::vector<std::string>& args);
+    
+    /**
+     * @brief Esegue il comando "zone" del socket di controllo
+     * @param args Argomenti del comando
+     * @return Risposta per l'operatore
+     */
+    std::string runZoneCommand(const std::vector<std::string>& args);
+    
+    /**
+     * @brief Esegue il comando "simulcast" del socket di controllo
+     * @param args Argomenti del comando
+     * @return Risposta per l'operatore
+     */
+    std::string runSimulcastCommand(const std::vector<std::string>& args);
+    
+    /**
+     * @brief Esegue il comando "flow" del socket di controllo
+     * @param args Argomenti del comando
+     * @return Risposta per l'operatore
+     */
+    std::string runFlowCommand(const std::vector<std::string>& args);
+    
+    /**
+     * @brief Esegue il comando "topology" del socket di controllo
+     * @param args Argomenti del comando
+     * @return Risposta per l'operatore
+     */
+    std::string runTopologyCommand(const std::vector<std::string>& args);
+    
+#ifndef SABER_MINIMAL_SINK
+    /**
+     * @brief Esegue il comando "schedule" del socket di controllo
+     * @param args Argomenti del comando
+     * @return Risposta per l'operatore
+     */
+    std::string runScheduleCommand(const std::vector<std::string>& args);
+#endif
+    
+    /**
+     * @brief Esegue il comando "timings" del socket di controllo
+     * @param args Argomenti del comando
+     * @return Risposta per l'operatore
+     */
+    std::string runTimingsCommand(const std::vector<std::string>& args);
+    
+    /**
+     * @brief Esegue il comando di controllo "sessions"
+     */
+    std::string runSessionsCommand(const std::vector<std::string>& args);
+    
+    /**
+     * @brief Esegue il comando di controllo "syncprobe"
+     */
+    std::string runSyncProbeCommand(const std::vector<std::string>& args);
+    
+    /**
+     * @brief Esegue il comando survey del socket di controllo
+     * @param args start | position <etichetta> | stop | report
+     * @return Esito o resoconto di copertura
+     */
+    std::string runSurveyCommand(const std::vector<std::string>& args);
+    
+    /**
+     * @brief Esegue il comando "discovery" del socket di controllo
+     * @param args Argomenti del comando
+     * @return Risposta per l'operatore
+     */
+    std::string runDiscoveryCommand(const std::vector<std::string>& args);
+    
+    /**
+     * @brief Esegue il comando trace del socket di controllo
+     * @param args trace <sink> | trace local | trace record on|off
+     * @return Percorsi noti e scarti per TTL o ciclo
+     */
+    std::string runTraceCommand(const std::vector<std::string>& args);
+    
+    /**
+     * @brief Scrive un token admin nel file indicato dalla configurazione
+     */
+    void writeControlTokenFile();
+    
+#ifndef SABER_MINIMAL_SINK
+    /**
+     * @brief Esegue le azioni programmate scadute (solo sul Master)
+     */
+    void runDueSchedule();
+#endif
+    
+    /**
+     * @brief Ricarica la configurazione se il file è cambiato dall'ultimo controllo
+     */
+    void watchConfigFile();
+    
+    /**
+     * @brief Invia un comando di riproduzione alla zona e lo accoda per la propria barriera
+     *
+     * La barriera e l'identificatore vengono assegnati qui; i nodi della
+     * zona attivi al momento dell'invio sono quelli da cui si attende una
+     * conferma.
+     *
+     * @param command Comando da inviare
+     * @param span Span del mittente da chiudere alla barriera
+     * @return true se il comando è stato inviato
+     */
+    bool sendPlaybackCommand(PlaybackCommand command, std::optional<TraceSpan> span = std::nullopt);
+    
+    /**
+     * @brief Esegue un comando di riproduzione raggiunta la barriera
+     * @param command Comando da eseguire
+     * @return Motivo del fallimento, o nullopt se il comando è stato eseguito
+     */
+    std::optional<std::string> applyPlaybackCommand(const PlaybackCommand& command);
+    
+    /**
+     * @brief Applica i comandi di gruppo quando la barriera è raggiunta
+     */
+    void runPendingPlayback();
+    
+    /**
+     * @brief Entra o esce dalla modalità festa quando la barriera è raggiunta
+     */
+    void runPendingParty();
+    
+    /**
+     * @brief Avvia o termina la conversazione intercom richiesta dalla rete
+     */
+    void runPendingIntercom();
+    
+    /**
+     * @brief Porta il sink in standby o lo risveglia in base agli stream e ai comandi
+     */
+    void updateStandby();
+    
+    /**
+     * @brief Sostituisce la sorgente audio ferma o torna a quella preferita
+     */
+    void updateSources();
+    
+    /**
+     * @brief Registra nel diario un cambio della sorgente audio
+     */
+    void recordSourceSwitch(const SourceSwitch& change);
+    
+    /**
+     * @brief Invia il ritardo acustico della propria zona ai sink che non l'hanno ancora ricevuto
+     */
+    void propagateZoneDelays();
+    
+    /**
+     * @brief Adatta la scadenza di riproduzione comune alla latenza dei sink (solo sul Master)
+     */
+    void updateDelayEqualization();
+    
+    /**
+     * @brief Calcola la latenza del nodo locale, comprese le uscite audio
+     * @return Latenza in millisecondi
+     */
+    uint32_t localLatencyMs() const;
+    
+    /**
+     * @brief Comunica alla rete le assegnazioni di zona che i nodi attivi non hanno ancora ricevuto
+     */
+    void propagateZones();
+    
+    /**
+     * @brief Annuncia periodicamente ai sink i gruppi simulcast pubblicati dal Master
+     */
+    void announceSimulcast();
+    
+    /**
+     * @brief Invia periodicamente agli altri nodi i vicini ascoltati dal nodo locale
+     */
+    void reportNeighbors();
+    
+    /**
+     * @brief Sceglie il livello di ogni stream simulcast sottoscritto e aggiorna le sottoscrizioni
+     */
+    void updateSimulcast();
+    
+    /**
+     * @brief Valuta un frame ricevuto, se appartiene ad un livello simulcast
+     * @param info Dati del pacchetto Audio
+     * @param streamId Stream con cui consegnare il frame (quello del gruppo per un livello)
+     * @return Trattamento del frame (Play per gli stream non simulcast)
+     */
+    SimulcastVerdict acceptSimulcastFrame(const MeshPacket::AudioFrameInfo& info, StreamId& streamId);
+    
+    /**
+     * @brief Avvisa il bus degli eventi quando la latenza di un nodo supera il budget
+     * @param nodeId Nodo che ha riportato la latenza
+     * @param latencyMs Latenza riportata (ms)
+     */
+    void checkLatencyAlert(const std::string& nodeId, uint32_t latencyMs);
+    
+#ifndef SABER_MINIMAL_SINK
+    /**
+     * @brief Salva la programmazione nel file indicato dalla configurazione
+     */
+    void saveSchedule();
+#endif
+    
+    /**
+     * @brief Comando di riproduzione di un gruppo in attesa della barriera
+     */
+    struct PendingPlayback {
+        PlaybackCommand command;
+        /// Nodo a cui confermare l'esecuzione (vuoto per i comandi inviati da questo nodo)
+        std::string origin;
+        /// Span dall'arrivo del comando all'avvio effettivo
+        std::optional<TraceSpan> span = std::nullopt;
+    };
+    
+#ifndef SABER_MINIMAL_SINK
+    /// Programmazione oraria del Master
+    Scheduler scheduler;
+#endif
+    
+    /// Eventi di sicurezza rilevati
+    std::vector<SecurityEvent> securityEvents;
+    
+    /// Gestione dei bassi ricevuta dal Master
+    BassSettings bassSettings;
+    
+    /// Esito della verifica dell'ambiente all'avvio
+    PreflightReport preflightReport;
+    
+    /// Cambi di collegamento verso gli altri nodi
+    std::vector<FailoverEvent> failoverEvents;
+    
+    /// Diario persistente degli eventi della mesh e di sicurezza
+    std::unique_ptr<EventJournal> journal;
+    
+    /// Profilatore della pipeline audio, condiviso con la rete mesh
+    std::shared_ptr<PipelineProfiler> profiler;
+    
+    /// Rilevatore delle intrusioni, condiviso con la rete mesh
+    std::shared_ptr<IntrusionDetector> intrusionDetector;
+    
+    /// Tracciatore degli span, condiviso con la rete mesh
+    std::shared_ptr<Tracer> tracer;
+    
+    /// Esportazione degli span verso il collettore OTLP (nullptr se non configurato)
+    std::unique_ptr<OtlpExporter> otlpExporter;
+    
+    /// Serializza i ricaricamenti della configurazione
+    std::mutex reloadMutex;
+    
+    /// Valori grezzi dell'ultimo file di configurazione applicato (protetti da reloadMutex)
+    std::map<std::string, std::string> loadedConfigValues;
+    
+    /// Data di modifica del file di configurazione all'ultimo controllo (solo thread di runtime)
+    std::optional<std::filesystem::file_time_type> configFileTime;
+    
+    /// Ultimo controllo del file di configurazione (solo thread di runtime)
+    int64_t lastConfigCheckMs = 0;
+    
+    /// Configurazione del degrado ricaricata, applicata dal thread di runtime (protetta da eventsMutex)
+    std::optional<DegradationConfig> pendingDegradationConfig;
+    
+    /// Comandi di riproduzione di gruppo in attesa della barriera
+    std::vector<PendingPlayback> pendingPlayback;
+    
+    /**
+     * @brief Ingresso o uscita dalla modalità festa in attesa della barriera
+     */
+    struct PendingParty {
+        bool start;
+        PartyMode party;
+        uint64_t atMs;
+    };
+    
+    /// Modalità festa in attesa della barriera
+    std::vector<PendingParty> pendingParty;
+    
+    /// Modalità festa a cui partecipa il nodo
+    std::optional<PartyMode> partyMode;
+    
+    /// Stream sottoscritti prima della modalità festa, ripristinati all'uscita
+    std::set<StreamId> partyRestoreStreams;
+    
+    /// Riproduzione da riprendere all'uscita dalla modalità festa
+    bool partyResumePlayback = false;
+    
+    /// Avvii (sessione) e arresti (nullopt) dell'intercom da applicare (protetti da eventsMutex)
+    std::vector<std::optional<IntercomSession>> pendingIntercom;
+    
+    /// Conversazione intercom in corso (protetta da eventsMutex)
+    std::optional<IntercomSession> intercomSession;
+    
+    /// Destinatario dei frame vocali decodificati (protetto da eventsMutex)
+    std::function<void(const std::string&, const AudioFrame&)> intercomFrameHandler;
+    
+    /// Codificatore della voce del nodo locale (protetto da intercomMutex)
+    std::unique_ptr<Lc3Encoder> intercomEncoder;
+    
+    /// Decodificatore della voce dell'altro nodo (protetto da intercomMutex)
+    std::unique_ptr<Lc3Decoder> intercomDecoder;
+    
+    /// Mutex dei codec dell'intercom, separato perché usato dal thread di rete
+    std::mutex intercomMutex;
+    
+    /// Standby del sink
+    StandbyController standbyController;
+    
+    /// Risveglio richiesto da un comando o dall'host (protetto da eventsMutex)
+    bool pendingWake = false;
+    
+    /// Spegnimento e riaccensione del dispositivo audio (protetto da eventsMutex)
+    std::function<void(bool)> standbyHandler;
+    
+    /// Scelta della sorgente audio trasmessa dal Master
+    SourceSelector sourceSelector;
+    
+    /// Ritardo acustico inviato a ciascun sink attivo (solo thread di runtime)
+    std::map<std::string, uint32_t> propagatedZoneDelays;
+    
+    /// Ritardo acustico ricevuto dal Master per la zona del nodo (protetto da eventsMutex)
+    uint32_t acousticDelayMs = 0;
+    
+    /// Scadenza di riproduzione comune del Master (solo thread di runtime)
+    DelayEqualizer delayEqualizer;
+    
+    /// Nodi attivi a cui è stata comunicata la scadenza corrente (solo thread di runtime)
+    std::set<std::string> deadlineNodes;
+    
+    /// Scadenza di riproduzione comune, calcolata o ricevuta dal Master (ms, protetta da eventsMutex)
+    uint32_t playoutDeadlineMs = 0;
+    
+    /// Zona comunicata a ciascun nodo attivo (solo thread di runtime)
+    std::map<std::string, std::string> propagatedZones;
+    
+    /// Esito dei comandi di riproduzione inviati da questo nodo (protetto da eventsMutex)
+    CommandTracker commandTracker;
+    
+    /// Notifica all'applicazione dei comandi di riproduzione eseguiti (protetto da eventsMutex)
+    std::function<void(const PlaybackCommand&)> playbackCommandHandler;
+    
+    /// Gruppi simulcast pubblicati dal Master, per stream
+    std::map<StreamId, SimulcastGroup> simulcastGroups;
+    
+    /// Ultimo annuncio dei gruppi simulcast (ms, solo thread di runtime)
+    int64_t lastSimulcastAnnounceMs = 0;
+    
+    /// Gruppi simulcast annunciati dal Master, per stream (protetti da eventsMutex)
+    std::map<StreamId, SimulcastGroup> announcedSimulcast;
+    
+    /// Ultimo resoconto dei vicini inviato (ms, solo thread di runtime)
+    int64_t lastNeighborReportMs = 0;
+    
+    /// Vicini riportati dagli altri nodi e istante di ricezione (protetti da eventsMutex)
+    std::map<std::string, std::pair<std::vector<TopologyLink>, int64_t>> neighborReports;
+    
+    /// Scelta del livello degli stream simulcast sottoscritti (protetta da eventsMutex)
+    std::map<StreamId, std::unique_ptr<SimulcastReceiver>> simulcastReceivers;
+    
+    /// Livelli sottoscritti nella rete per stream simulcast (protetti da eventsMutex)
+    std::map<StreamId, std::vector<StreamId>> simulcastLayers;
+    
+    /// Banda imposta per la scelta dei livelli (protetta da eventsMutex)
+    std::optional<uint32_t> simulcastBudgetKbps;
+    
+    /// Stream a cui il nodo locale è sottoscritto
+    std::set<StreamId> subscribedStreams;
+    
+    /// Playlist avviata per il gruppo del nodo
+    std::string activePlaylist;
+    
+    /// Codificatori dei timestamp per stream inviato
+    std::map<StreamId, TimestampEncoder> timestampEncoders;
+    
+    /// Prossimo numero di sequenza dei frame per stream inviato
+    std::map<StreamId, uint32_t> audioSequences;
+    
+    /// Controllore di congestione alimentato dalla contabilità della banda
+    CongestionController congestion;
+    
+    /// Controllo di flusso sui resoconti del buffer dei sink
+    FlowController flowController;
+    
+    /// Priorità dei frame per stream pubblicato
+    std::map<StreamId, FramePriority> streamPriorities;
+    
+    /// Ultimo resoconto del buffer inviato dal sink (ms, solo thread di runtime)
+    int64_t lastBufferStatusMs = 0;
+    
+    /// Lista di revoca dei token, condivisa con la verifica di MeshCrypto
+    std::shared_ptr<RevocationList> revocationList = std::make_shared<RevocationList>();
+    
+    /// Ultima ritrasmissione della lista di revoca (ms, solo thread di runtime)
+    int64_t lastRevocationAnnounceMs = 0;
+    
+    /// Revoche ricevute per mittente, in attesa della verifica del ruolo (protette da eventsMutex)
+    std::vector<std::pair<std::string, std::vector<Revocation>>> pendingRevocations;
+    
+    /// Master: abbinamento aperto con il PIN corrente
+    std::unique_ptr<PairingResponder> pairingResponder;
+    
+    /// Master: PIN corrente e sua scadenza (ms)
+    std::string pairingPin;
+    int64_t pairingUntilMs = 0;
+    
+    /// Nuovo nodo: abbinamento in corso, Master a cui è rivolto e scadenza della risposta attesa (ms)
+    std::unique_ptr<PairingJoiner> pairingJoiner;
+    std::string pairingMasterId;
+    int64_t pairingDeadlineMs = 0;
+    
+    /// Esito dell'ultimo abbinamento e nodi abbinati
+    PairingStatus pairingStatus;
+    
+    /// Pacchetti Pairing ricevuti, elaborati dal thread di runtime (protetti da eventsMutex)
+    std::vector<MeshPacket> pendingPairing;
+    
+    /// Copertine note per hash (pubblicate o ricevute)
+    std::map<std::string, std::vector<uint8_t>> artworkStore;
+    
+    /// Copertine richieste da altri nodi, inviate dal thread di runtime
+    std::vector<std::string> pendingArtworkReplies;
+    
+    /**
+     * @brief Invia le copertine richieste dagli altri nodi
+     */
+    void flushArtworkReplies();
+    
+    /**
+     * @brief Aggiorna il controllore di congestione con l'occupazione del canale
+     */
+    void updateCongestion();
+    
+    /**
+     * @brief Riporta al Master lo stato del sink fantasma
+     */
+    void reportPhantomStatus();
+    
+    /**
+     * @brief Riporta al Master lo stato del ponte A2DP, latenza delle cuffie inclusa
+     */
+    void reportBridgeStatus();
+    
+    /**
+     * @brief Riporta al Master il riempimento del buffer di jitter del sink
+     */
+    void reportBufferStatus();
+    
+    /**
+     * @brief Rivaluta il controllo di flusso del Master e adegua il bitrate
+     */
+    void updateFlowControl();
+    
+    /**
+     * @brief Registra una revoca e, sul Master, la diffonde (protocolMutex già acquisito)
+     * @param entry Voce della lista di revoca
+     * @return true se la voce ha modificato la lista
+     */
+    bool recordRevocationLocked(const Revocation& entry);
+    
+    /**
+     * @brief Invia voci della lista di revoca agli altri nodi (protocolMutex già acquisito)
+     * @param entries Voci da inviare, suddivise in più pacchetti se necessario
+     */
+    void sendRevocationsLocked(const std::vector<Revocation>& entries);
+    
+    /**
+     * @brief Ritrasmette periodicamente la lista di revoca del Master, per i nodi entrati dopo
+     */
+    void announceRevocations();
+    
+    /**
+     * @brief Unisce alla lista locale le revoche ricevute dal Master
+     */
+    void applyRevocations();
+    
+    /**
+     * @brief Elabora i pacchetti Pairing ricevuti e le scadenze dell'abbinamento
+     */
+    void runPairing();
+    
+    /**
+     * @brief Risponde ad un nuovo nodo durante l'abbinamento aperto (protocolMutex già acquisito)
+     */
+    void handlePairingRequestLocked(const std::string& nodeId, const MeshPacket::PairingInfo& info);
+    
+    /**
+     * @brief Elabora una risposta del Master all'abbinamento del nodo locale (protocolMutex già acquisito)
+     */
+    void handlePairingReplyLocked(const std::string& masterId, const MeshPacket::PairingInfo& info);
+    
+    /**
+     * @brief Invia un passo dell'abbinamento ad un solo nodo (protocolMutex già acquisito)
+     */
+    void sendPairingLocked(PairingStep step, const std::string& peer, const std::vector<uint8_t>& payload);
+    
+    /**
+     * @brief Chiude l'abbinamento aperto sul Master (protocolMutex già acquisito)
+     */
+    void closePairingLocked(const std::string& reason);
+    
+    /**
+     * @brief Registra il fallimento dell'abbinamento del nodo locale (protocolMutex già acquisito)
+     */
+    void failPairingLocked(const std::string& reason);
+    
+    /**
+     * @brief Priorità dei frame di uno stream o del gruppo simulcast di cui è un livello (protocolMutex già acquisito)
+     */
+    FramePriority streamPriorityLocked(StreamId streamId) const;
+    
+    /**
+     * @brief Decodifica un frame Audio ricevuto dal sink e lo consegna al destinatario
+     * @param info Dati del pacchetto Audio
+     * @param streamId Stream con cui consegnare il frame (diverso da info.streamId per i livelli simulcast)
+     */
+    void decodeAudioFrame(const MeshPacket::AudioFrameInfo& info, StreamId streamId);
+    
+    /**
+     * @brief Decodifica senza consegnarlo un frame già riprodotto da un altro livello simulcast
+     *
+     * Prepara il decodificatore del nuovo livello, così il primo frame
+     * riprodotto non parte da uno stato vuoto.
+     *
+     * @param info Dati del pacchetto Audio
+     */
+    void primeAudioDecoder(const MeshPacket::AudioFrameInfo& info);
+    
+    /**
+     * @brief Decodifica un frame vocale dell'intercom e lo consegna al destinatario
+     * @param info Dati del pacchetto Audio
+     */
+    void decodeIntercomFrame(const MeshPacket::AudioFrameInfo& info);
+    
+    /**
+     * @brief Ferma i server di controllo e di health-check e il thread di runtime
+     */
+    void stopServices();
+    
+    /**
+     * @brief Chiude le sessioni degli stream senza frame recenti
+     */
+    void finishIdleSessions();
+    
+    /**
+     * @brief Ripristina nodi noti e zone dallo stato persistito
+     */
+    void restoreState();
+    
+    /**
+     * @brief Registra nello stato persistito le modifiche a nodi noti e zone
+     */
+    void persistState();
+    
+    /**
+     * @brief Conserva i resoconti del sink locale e li invia al Master
+     * @param reports Resoconti delle sessioni chiuse
+     */
+    void publishSessionReports(const std::vector<SessionReport>& reports);
+    
+    /**
+     * @brief Aggiunge un resoconto allo storico, scartando i più vecchi
+     * @param report Resoconto da conservare
+     */
+    void storeSessionReport(const SessionReport& report);
+    
+    /**
+     * @brief Invia inneschi e risposte delle misure di sincronizzazione e chiude quelle concluse
+     */
+    void runSyncProbes();
+    
+    /**
+     * @brief Invia le richieste di scambio temporale al Master e le risposte a quelle ricevute
+     *
+     * Sul Master invia anche i TimeBeacon sulla rete mesh ogni meshBeaconIntervalMs.
+     */
+    void runTimeExchange();
+    
+    /**
+     * @brief Riallinea i sink trovati fuori sincronia dagli scambi temporali (solo Master)
+     */
+    void recoverDesyncedNodes();
+    
+    /**
+     * @brief Applica un riallineamento d'emergenza ricevuto dal Master (thread di rete)
+     * @param packet Pacchetto EmergencySync
+     */
+    void handleEmergencySync(const MeshPacket& packet);
+    
+    /**
+     * @brief Invia i sondaggi del sopralluogo e le risposte a quelli ricevuti
+     */
+    void runSurvey();
+    
+    /**
+     * @brief Trasmette l'annuncio BLE e dimentica i nodi scoperti non più visibili
+     */
+    void runDiscovery();
+    
+    /**
+     * @brief Risponde alle richieste di percorso ricevute
+     */
+    void flushTraceReplies();
+    
+    /**
+     * @brief Aggiorna la scala di degrado con la qualità della rete (solo sul Master)
+     */
+    void updateDegradation();
+    
+    /**
+     * @brief Applica al nodo locale l'ultimo gradino ricevuto dal Master
+     */
+    void applyDegradation();
+    
+    /// Scala di degrado del Master
+    DegradationLadder degradationLadder;
+    
+    /// Parametri audio dell'ultimo gradino
+    DegradationSettings degradationSettings;
+    
+    /// Sink sospesi dall'ultimo gradino
+    std::vector<std::string> suspendedSinks;
+    
+    /// Gradino ricevuto dal Master non ancora applicato
+    bool degradationPending = false;
+    
+    /**
+     * @brief Applica al nodo locale l'ultimo profilo audio imposto dal Master
+     */
+    void applyAudioProfile();
+    
+    /**
+     * @brief Cambio di profilo audio in attesa del thread di runtime
+     */
+    struct PendingAudioProfile {
+        AudioProfile profile;
+        std::vector<StreamId> streams;
+    };
+    
+    /// Profilo audio non ancora applicato (protetto da eventsMutex)
+    std::optional<PendingAudioProfile> pendingAudioProfile;
+    
+    /// Il nodo locale è sospeso dalla scala di degrado
+    bool locallySuspended = false;
+    
+    /// Riproduzione da riprendere alla fine della sospensione
+    bool suspendedWhilePlaying = false;
+    
+    /// Consumo simulato dei flussi (solo con phantomSink)
+    std::unique_ptr<PhantomSink> phantom;
+    
+    /// Ponte verso le cuffie A2DP (solo Repeater con a2dpDevice)
+    std::unique_ptr<A2dpBridge> a2dpBridge;
+    
+    /// Ultimo pacchetto Status del sink fantasma (ms dal clock monotono)
+    int64_t lastPhantomStatusMs = 0;
+    
+    /// Ultimo pacchetto Status del ponte A2DP (ms dal clock monotono)
+    int64_t lastBridgeStatusMs = 0;
+    
+    /// Statistiche delle sessioni di riproduzione in corso (solo sui sink)
+    std::unique_ptr<SessionTracker> sessionTracker;
+    
+    /// Storico dei resoconti di sessione (protetto da eventsMutex)
+    std::deque<SessionReport> sessionReports;
+    
+    /**
+     * @brief Misura di sincronizzazione in corso
+     */
+    struct ActiveSyncProbe {
+        SyncProbe probe;
+        int64_t deadlineMs;
+    };
+    
+    /**
+     * @brief Risposta ad un innesco ricevuto, inviata dal thread di runtime
+     */
+    struct PendingProbeReply {
+        std::string origin;
+        std::string probeId;
+        std::string sentUs;
+        uint64_t receivedUs;
+    };
+    
+    /// Misure di sincronizzazione in corso per sink (protette da eventsMutex)
+    std::map<std::string, ActiveSyncProbe> activeSyncProbes;
+    
+    /// Risposte agli inneschi in attesa di invio (protette da eventsMutex)
+    std::vector<PendingProbeReply> pendingProbeReplies;
+    
+    /// Risposte alle richieste di scambio temporale in attesa di invio, sul Master (protette da eventsMutex)
+    std::vector<PendingProbeReply> pendingTimeReplies;
+    
+    /// Ultima richiesta di scambio temporale inviata al Master (ms, orologio monotono)
+    int64_t lastTimeExchangeMs = 0;
+    
+    /// Ultimo TimeBeacon inviato dal Master (ms, orologio monotono)
+    int64_t lastBeaconMs = 0;
+    
+    /// Sink trovati fuori sincronia in attesa di riallineamento, sul Master (protetti da eventsMutex)
+    std::set<std::string> desyncedNodes;
+    
+    /// Ultimo riallineamento d'emergenza per sink (ms, orologio monotono; protetto da eventsMutex)
+    std::map<std::string, int64_t> lastEmergencySyncMs;
+    
+    /// Fine del silenzio dopo un riallineamento d'emergenza (µs, orologio sincronizzato; protetto da eventsMutex)
+    uint64_t emergencyMuteUntilUs = 0;
+    
+    /// Contatori dei riallineamenti d'emergenza (protetti da eventsMutex)
+    EmergencySyncStats emergencySyncStats;
+    
+    /// Ultimo esito per sink misurato (protetto da eventsMutex)
+    std::map<std::string, SyncProbeResult> syncProbeResults;
+    
+    /// Emettitore dei clic delle misure acustiche (protetto da eventsMutex)
+    std::function<void(const std::string&, uint64_t)> syncClickHandler;
+    
+    /// Gestore dei cambi di stato della sincronizzazione (protetto da eventsMutex)
+    std::function<void(bool)> syncStateHandler;
+    
+    /// Nodi con un avviso di latenza in corso (protetto da eventsMutex)
+    std::set<std::string> latencyAlertNodes;
+    
+    /// Sopralluogo radio corrente o concluso (protetto da eventsMutex)
+    std::unique_ptr<CoverageSurvey> coverageSurvey;
+    
+    /// I sondaggi del sopralluogo sono in corso (protetto da eventsMutex)
+    bool surveyActive = false;
+    
+    /// Ultimo giro di sondaggi (ms dal clock monotono)
+    int64_t lastSurveyProbeMs = 0;
+    
+    /// Risposte ai sondaggi ricevuti: mittente e numero (protette da eventsMutex)
+    std::vector<std::pair<std::string, std::string>> pendingSurveyReplies;
+    
+    /// Fornitore dell'RSSI delle risposte (protetto da eventsMutex)
+    std::function<std::optional<int32_t>(const std::string&)> rssiProvider;
+    
+    /// Scoperta dei nodi vicini via BLE (nullptr se disattivata o a rete ferma)
+    std::unique_ptr<NodeDiscovery> discovery;
+    
+    /// Trasmettitore dell'annuncio BLE (protetto da eventsMutex)
+    std::function<bool(const std::vector<uint8_t>&)> advertiser;
+    
+    /// Ultima trasmissione dell'annuncio (solo thread di runtime)
+    int64_t lastAdvertisementMs = 0;
+    
+    /// Nodi che hanno chiesto il percorso dei frame (protetti da eventsMutex)
+    std::vector<std::string> pendingTraceReplies;
+    
+    /// Percorsi riportati dai sink, per sink e stream (protetti da eventsMutex)
+    std::map<std::string, std::map<StreamId, RouteTrace>> remoteRouteTraces;
+    
+    /// Destinatario dei frame decodificati (protetto da eventsMutex)
+    std::function<void(StreamId, const AudioFrame&)> audioFrameHandler;
+    
+    /// Uscite audio che riproducono gli stream (protette da eventsMutex)
+    std::map<StreamId, std::shared_ptr<AudioRenderer>> audioOutputs;
+    
+    /// Ponte verso un'altra rete (protetto da eventsMutex)
+    BridgeHandler bridgeHandler;
+    
+    /// Ultimo frame decodificato per stream (solo thread di rete)
+    std::map<StreamId, uint32_t> lastDecodedSequences;
+    
+    /// Mutex per eventi di sicurezza e impostazioni ricevute dalla rete
+    mutable std::mutex eventsMutex;
+    
+    /// Sincronizzatore audio
+    std::unique_ptr<AudioSync> audioSync;
+    
+    /// Thread per il runtime asincrono
+    std::unique_ptr<std::thread> runtimeThread;
+    
+    /// Flag per il thread di runtime
+    std::atomic<bool> running;
+    
+    /// Mutex dell'attesa tra due giri del thread di runtime
+    std::mutex runtimeMutex;
+    
+    /// Risveglia il thread di runtime all'arresto, senza attendere la fine del giro
+    std::condition_variable runtimeWakeup;
+    
+    /// Stato del ciclo di vita
+    std::atomic<ProtocolState> state;
+    
+    /// Ultimo giro del thread di runtime (ms dal clock monotono)
+    std::atomic<int64_t> lastRuntimeTick;
+    
+#ifdef SABER_WITH_HTTP
+    /// Server degli endpoint di health-check
+    std::unique_ptr<HttpServer> healthServer;
+#endif
+    
+    /// Mutex per proteggere l'accesso concorrente
+    mutable std::mutex protocolMutex;
+};
+
+/**
+ * @brief Funzione principale per l'inizializzazione di SABER in modalità Master (UCB)
+ * @param nodeId ID del nodo (opzionale)
+ * @param btAddress Indirizzo Bluetooth (opzionale)
+ * @return Puntatore unico all'istanza del protocollo SABER
+ */
+std::unique_ptr<SaberProtocol> startMaster(const std::optional<std::string>& nodeId = std::nullopt, 
+                         const std::optional<std::string>& btAddress = std::nullopt);
+
+/**
+ * @brief Funzione principale per l'inizializzazione di SABER in modalità Repeater
+ * @param nodeId ID del nodo (opzionale)
+ * @param btAddress Indirizzo Bluetooth (opzionale)
+ * @return Puntatore unico all'istanza del protocollo SABER
+ */
+std::unique_ptr<SaberProtocol> startRepeater(const std::optional<std::string>& nodeId = std::nullopt,
+                           const std::optional<std::string>& btAddress = std::nullopt);
+
+/**
+ * @brief Funzione principale per l'inizializzazione di SABER in modalità Sink (ricevitore)
+ * @param nodeId ID del nodo (opzionale)
+ * @param btAddress Indirizzo Bluetooth (opzionale)
  * @param isMusic Flag che indica se l'audio è musicale (true) o vocale (false)
  * @return Puntatore unico all'istanza del protocollo SABER
  */
@@ -163,6 +3114,56 @@ std::unique_ptr<SaberProtocol> startSink(const std::optional<std::string>& nodeI
                        const std::optional<std::string>& btAddress = std::nullopt,
                        bool isMusic = true);
 
+/**
+ * @brief Avvia un nodo SABER a partire da una configurazione completa
+ *
+ * Le funzioni precedenti ne sono scorciatoie; la riga di comando "saber"
+ * la usa per applicare il collegamento e le altre opzioni prima
+ * dell'inizializzazione.
+ *
+ * @param config Configurazione del nodo (ID generato dal ruolo se vuoto, es. "sink-123456")
+ * @return Puntatore unico all'istanza del protocollo SABER
+ */
+std::unique_ptr<SaberProtocol> startNode(SaberConfig config);
+
+/**
+ * @brief Opzioni della riga di comando "saber" risolte per l'avvio di un nodo
+ */
+struct NodeCommandLine {
+    /// Configurazione da passare a startNode (ID vuoto: generato dal ruolo)
+    SaberConfig config;
+    
+    /// File WAV che il Master trasmette sullo stream 0 (vuoto se assente)
+    std::string playPath;
+    
+    /// Ripete il file trasmesso
+    bool loop = false;
+    
+    /// File WAV in cui il sink registra lo stream 0 (vuoto se assente)
+    std::string recordPath;
+    
+    /// Secondi tra due aggiornamenti del cruscotto
+    int intervalSeconds = 1;
+    
+    /// Resoconti accodati senza ripulire il terminale
+    bool plain = false;
+};
+
+/**
+ * @brief Risolve le opzioni della riga di comando "saber" per un ruolo
+ *
+ * Il file di --config fa da base; --id, --bt, --transport e --music/--voice
+ * ne sostituiscono i valori. Senza --config e senza --id l'ID viene
+ * generato da startNode.
+ *
+ * @param role Ruolo del nodo
+ * @param args Opzioni che seguono il ruolo
+ * @return Opzioni risolte
+ * @throws std::invalid_argument se le opzioni sono in conflitto o non valide (uscita 2)
+ * @throws ConfigError se il file di configurazione non è valido
+ */
+NodeCommandLine parseNodeCommandLine(NodeRole role, const std::vector<std::string>& args);
+
 } // namespace saber
 
 #endif // SABER_PROTOCOL_H
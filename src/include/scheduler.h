@@ -0,0 +1,171 @@
+#ifndef SABER_SCHEDULER_H
+#define SABER_SCHEDULER_H
+
+#include <cstdint>
+#include <map>
+#include <mutex>
+#include <optional>
+#include <string>
+#include <vector>
+
+namespace saber {
+
+/// Tutti i giorni della settimana (bit 0 = lunedì, bit 6 = domenica)
+constexpr uint8_t SCHEDULE_EVERY_DAY = 0x7F;
+
+/**
+ * @brief Azione programmata del Master
+ */
+struct ScheduleEntry {
+    /// Identificatore assegnato dal programmatore
+    uint32_t id = 0;
+
+    /// Minuto del giorno (0-1439) nell'ora dell'installazione
+    uint16_t minuteOfDay = 0;
+
+    /// Giorni in cui l'azione è attiva (bit 0 = lunedì)
+    uint8_t days = SCHEDULE_EVERY_DAY;
+
+    /// Riga eseguita dal dispatcher dei comandi (es. "playback start patio aperitivo")
+    std::string command;
+
+    /**
+     * @brief Rappresentazione testuale, nello stesso formato del file
+     * @return Riga "<id> <HH:MM> <giorni> <comando>"
+     */
+    std::string toString() const;
+};
+
+/**
+ * @brief Programmazione oraria delle azioni del Master
+ *
+ * Gli orari sono espressi nell'ora dell'installazione come scostamento
+ * fisso da UTC e valutati sull'orologio sincronizzato della mesh: il fuso
+ * orario configurato sul sistema operativo dei nodi non ha alcun effetto.
+ * Le azioni mancate durante una sospensione più lunga di un minuto non
+ * vengono recuperate.
+ */
+class Scheduler {
+public:
+    /**
+     * @brief Crea una programmazione vuota
+     * @param utcOffsetMinutes Scostamento dell'ora dell'installazione da UTC
+     */
+    explicit Scheduler(int32_t utcOffsetMinutes = 0);
+
+    /**
+     * @brief Aggiunge un'azione
+     * @param minuteOfDay Minuto del giorno (0-1439)
+     * @param days Giorni attivi (bit 0 = lunedì)
+     * @param command Riga di comando da eseguire
+     * @return Identificatore dell'azione
+     * @throws std::invalid_argument se orario, giorni o comando non sono validi
+     */
+    uint32_t add(uint16_t minuteOfDay, uint8_t days, const std::string& command);
+
+    /**
+     * @brief Sostituisce orario, giorni e comando di un'azione esistente
+     * @return true se l'azione esisteva
+     * @throws std::invalid_argument se orario, giorni o comando non sono validi
+     */
+    bool update(uint32_t id, uint16_t minuteOfDay, uint8_t days, const std::string& command);
+
+    /**
+     * @brief Rimuove un'azione
+     * @return true se l'azione esisteva
+     */
+    bool remove(uint32_t id);
+
+    /**
+     * @brief Azioni programmate, ordinate per identificatore
+     */
+    std::vector<ScheduleEntry> entries() const;
+
+    /**
+     * @brief Imposta lo scostamento da UTC dell'ora dell'installazione
+     */
+    void setUtcOffset(int32_t minutes);
+
+    /**
+     * @brief Scostamento da UTC in minuti
+     */
+    int32_t getUtcOffset() const;
+
+    /**
+     * @brief Azioni il cui orario è scaduto dall'ultima chiamata
+     *
+     * La prima chiamata stabilisce solo il punto di partenza: le azioni
+     * già passate all'avvio non vengono eseguite.
+     *
+     * @param nowUtcMs Tempo sincronizzato corrente (ms dall'epoca Unix)
+     * @return Azioni da eseguire ora
+     */
+    std::vector<ScheduleEntry> due(uint64_t nowUtcMs);
+
+    /**
+     * @brief Carica le azioni da file, sostituendo quelle correnti
+     * @param path Percorso del file
+     * @return true se il file esiste ed è stato letto
+     * @throws std::invalid_argument se una riga non è valida
+     */
+    bool load(const std::string& path);
+
+    /**
+     * @brief Salva le azioni su file (scrittura atomica)
+     * @param path Percorso del file
+     * @return true se il file è stato scritto
+     */
+    bool save(const std::string& path) const;
+
+    /**
+     * @brief Interpreta un orario "HH:MM"
+     * @return Minuto del giorno, o std::nullopt se non valido
+     */
+    static std::optional<uint16_t> parseTimeOfDay(const std::string& text);
+
+    /**
+     * @brief Interpreta un insieme di giorni
+     *
+     * Accetta "daily", "weekdays", "weekend", intervalli ("mon-fri") ed
+     * elenchi separati da virgola ("mon,wed,sat").
+     *
+     * @return Maschera dei giorni, o std::nullopt se non valida
+     */
+    static std::optional<uint8_t> parseDays(const std::string& text);
+
+    /**
+     * @brief Interpreta uno scostamento da UTC "+HH:MM" o "-HH:MM"
+     * @return Scostamento in minuti, o std::nullopt se non valido
+     */
+    static std::optional<int32_t> parseUtcOffset(const std::string& text);
+
+    /**
+     * @brief Formatta una maschera dei giorni ("daily" o "mon,tue,...")
+     */
+    static std::string daysToString(uint8_t days);
+
+private:
+    /**
+     * @brief Verifica i campi di un'azione
+     */
+    static void validate(uint16_t minuteOfDay, uint8_t days, const std::string& command);
+
+    /// Azioni per identificatore
+    std::map<uint32_t, ScheduleEntry> schedule;
+
+    /// Prossimo identificatore libero
+    uint32_t nextId;
+
+    /// Scostamento da UTC in minuti
+    int32_t utcOffsetMinutes;
+
+    /// Istante dell'ultima valutazione (0 prima della prima)
+    uint64_t lastCheckMs;
+
+    /// Mutex per le azioni
+    mutable std::mutex scheduleMutex;
+};
+
+} // namespace saber
+
+#endif // SABER_SCHEDULER_H
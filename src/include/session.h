@@ -0,0 +1,134 @@
+#ifndef SABER_SESSION_H
+#define SABER_SESSION_H
+
+#include <cstdint>
+#include <map>
+#include <mutex>
+#include <optional>
+#include <string>
+#include <vector>
+
+#include "stats.h"
+
+namespace saber {
+
+/**
+ * @brief Resoconto della riproduzione di uno stream su un sink
+ */
+struct SessionReport {
+    /// Sink che ha riprodotto lo stream
+    std::string nodeId;
+
+    /// Stream riprodotto
+    StreamId streamId = 0;
+
+    /// Primo frame ricevuto (ms, orologio della rete)
+    uint64_t startedAtMs = 0;
+
+    /// Ultimo frame ricevuto (ms, orologio della rete)
+    uint64_t endedAtMs = 0;
+
+    /// Frame arrivati in tempo per la riproduzione
+    uint64_t framesPlayed = 0;
+
+    /// Frame mancanti nella sequenza, da ricostruire
+    uint64_t framesConcealed = 0;
+
+    /// Frame arrivati dopo il loro istante di riproduzione
+    uint64_t framesDropped = 0;
+
+    /// Anticipo medio dei frame sul loro istante di riproduzione (ms)
+    int64_t averageOffsetMs = 0;
+
+    /// Volte in cui il buffer si è svuotato dopo essere stato in tempo
+    uint64_t rebufferCount = 0;
+
+    /// Latenza massima stimata (buffer di riproduzione meno anticipo, ms)
+    uint32_t maxLatencyMs = 0;
+};
+
+/**
+ * @brief Raccoglie le statistiche di riproduzione di un sink per stream
+ *
+ * Una sessione inizia al primo frame di uno stream e termina quando lo
+ * stream viene fermato o quando non arrivano frame per un intervallo;
+ * alla chiusura restituisce il resoconto della sessione.
+ */
+class SessionTracker {
+public:
+    /**
+     * @brief Crea un raccoglitore
+     * @param nodeId Sink a cui appartengono i resoconti
+     * @param targetBufferMs Buffer di riproduzione del sink (ms)
+     */
+    SessionTracker(const std::string& nodeId, uint32_t targetBufferMs);
+
+    /**
+     * @brief Registra un frame ricevuto
+     * @param streamId Stream del frame
+     * @param sequence Numero di sequenza del frame
+     * @param playoutTimeUs Istante di riproduzione del frame (µs)
+     * @param nowUs Istante corrente sull'orologio della rete (µs)
+     */
+    void recordFrame(StreamId streamId, uint32_t sequence, uint64_t playoutTimeUs, uint64_t nowUs);
+
+    /**
+     * @brief Chiude la sessione di uno stream
+     * @param streamId Stream fermato
+     * @return Resoconto, oppure std::nullopt se lo stream non ha una sessione aperta
+     */
+    std::optional<SessionReport> finish(StreamId streamId);
+
+    /**
+     * @brief Chiude tutte le sessioni aperte
+     * @return Resoconti in ordine di stream
+     */
+    std::vector<SessionReport> finishAll();
+
+    /**
+     * @brief Chiude le sessioni senza frame da almeno idleMs
+     * @param nowUs Istante corrente sull'orologio della rete (µs)
+     * @param idleMs Intervallo senza frame dopo cui lo stream è considerato fermo
+     * @return Resoconti delle sessioni chiuse
+     */
+    std::vector<SessionReport> finishIdle(uint64_t nowUs, uint32_t idleMs);
+
+private:
+    /**
+     * @brief Stato di una sessione aperta
+     */
+    struct Session {
+        /// Resoconto parziale
+        SessionReport report;
+
+        /// Somma degli anticipi (ms) per la media
+        int64_t offsetSumMs = 0;
+
+        /// Ultimo numero di sequenza ricevuto
+        uint32_t lastSequence = 0;
+
+        /// L'ultimo frame era in ritardo
+        bool late = false;
+    };
+
+    /**
+     * @brief Completa il resoconto di una sessione
+     */
+    SessionReport close(const Session& session) const;
+
+    /// Sink locale
+    std::string nodeId;
+
+    /// Buffer di riproduzione (ms)
+    uint32_t targetBufferMs;
+
+    /// Sessioni aperte per stream
+    std::map<StreamId, Session> sessions;
+
+    /// Mutex per le sessioni
+    mutable std::mutex sessionMutex;
+};
+
+} // namespace saber
+
+#endif // SABER_SESSION_H
@@ -0,0 +1,244 @@
+#ifndef SABER_SIM_H
+#define SABER_SIM_H
+
+#include <cstdint>
+#include <functional>
+#include <map>
+#include <mutex>
+#include <random>
+#include <set>
+#include <string>
+#include <utility>
+#include <vector>
+
+namespace saber {
+
+/**
+ * @brief Caratteristiche di un collegamento simulato
+ */
+struct SimLinkConfig {
+    /// Latenza di base (ms)
+    uint32_t latencyMs = 5;
+
+    /// Ritardo massimo aggiunto alla latenza, estratto uniformemente per ogni pacchetto (ms)
+    uint32_t jitterMs = 0;
+
+    /// Probabilità di perdere un pacchetto (0-1)
+    double lossRate = 0.0;
+};
+
+/**
+ * @brief Contatori della rete simulata
+ *
+ * Ogni pacchetto trasmesso produce una copia per ciascun nodo collegato;
+ * consegne, perdite e scarti si contano sulle copie.
+ */
+struct SimStats {
+    /// Pacchetti trasmessi dai nodi
+    uint64_t packetsSent = 0;
+
+    /// Copie consegnate
+    uint64_t delivered = 0;
+
+    /// Copie perse per la perdita simulata
+    uint64_t lost = 0;
+
+    /// Copie scartate perché mittente e destinatario sono separati da una partizione
+    uint64_t partitioned = 0;
+
+    /// Copie in viaggio
+    uint64_t inFlight = 0;
+};
+
+/**
+ * @brief Rete virtuale per eseguire più nodi nello stesso processo
+ *
+ * Sostituisce il collegamento radio o UDP nei test di integrazione: ogni
+ * pacchetto trasmesso da un nodo raggiunge, come un multicast, tutti gli
+ * altri nodi collegati dopo la latenza del collegamento, salvo perdite e
+ * partizioni. Il tempo è virtuale e avanza solo con advance(), e le
+ * estrazioni casuali seguono la seed: la stessa sequenza di chiamate
+ * produce sempre le stesse consegne, nello stesso ordine.
+ */
+class SimNetwork {
+public:
+    /**
+     * @brief Tipo di callback per i pacchetti consegnati ad un nodo
+     */
+    using ReceiveHandler = std::function<void(const std::vector<uint8_t>&)>;
+
+    /**
+     * @brief Crea una rete senza nodi
+     * @param seed Seed delle estrazioni di perdita e jitter
+     * @param defaults Caratteristiche dei collegamenti senza impostazioni proprie
+     */
+    explicit SimNetwork(uint64_t seed = 0, const SimLinkConfig& defaults = SimLinkConfig());
+
+    /**
+     * @brief Collega un nodo alla rete
+     * @param nodeId Identificatore del nodo
+     * @param handler Funzione invocata da advance() per ogni pacchetto consegnato al nodo
+     *
+     * Se il nodo è già collegato il gestore viene sostituito.
+     */
+    void attach(const std::string& nodeId, ReceiveHandler handler);
+
+    /**
+     * @brief Scollega un nodo; le copie ancora in viaggio verso di lui vanno perse
+     *
+     * Attende la fine di un'eventuale consegna in corso: non va chiamato
+     * dai gestori dei pacchetti.
+     *
+     * @param nodeId Identificatore del nodo
+     */
+    void detach(const std::string& nodeId);
+
+    /**
+     * @brief Ottiene i nodi collegati
+     */
+    std::vector<std::string> getNodes() const;
+
+    /**
+     * @brief Trasmette un pacchetto a tutti gli altri nodi collegati
+     * @param from Nodo mittente
+     * @param datagram Pacchetto codificato
+     * @return true se il mittente è collegato
+     */
+    bool send(const std::string& from, const std::vector<uint8_t>& datagram);
+
+    /**
+     * @brief Imposta le caratteristiche dei collegamenti senza impostazioni proprie
+     * @param config Latenza, jitter e perdita
+     */
+    void setDefaultLink(const SimLinkConfig& config);
+
+    /**
+     * @brief Imposta le caratteristiche del collegamento da un nodo ad un altro
+     * @param from Nodo mittente
+     * @param to Nodo destinatario
+     * @param config Latenza, jitter e perdita (il verso opposto non cambia)
+     */
+    void setLink(const std::string& from, const std::string& to, const SimLinkConfig& config);
+
+    /**
+     * @brief Riporta un collegamento alle caratteristiche predefinite
+     * @param from Nodo mittente
+     * @param to Nodo destinatario
+     */
+    void clearLink(const std::string& from, const std::string& to);
+
+    /**
+     * @brief Ottiene le caratteristiche del collegamento da un nodo ad un altro
+     * @param from Nodo mittente
+     * @param to Nodo destinatario
+     * @return Impostazioni proprie del collegamento o, in loro assenza, quelle predefinite
+     */
+    SimLinkConfig getLink(const std::string& from, const std::string& to) const;
+
+    /**
+     * @brief Separa un gruppo di nodi dal resto della rete
+     *
+     * I nodi del gruppo comunicano solo tra loro finché heal() non
+     * ricompone la rete; le copie in viaggio tra le due parti vanno perse.
+     * Partizioni successive dividono ulteriormente la rete.
+     *
+     * @param side Nodi da separare (anche non ancora collegati)
+     */
+    void partition(const std::set<std::string>& side);
+
+    /**
+     * @brief Ricompone la rete annullando tutte le partizioni
+     */
+    void heal();
+
+    /**
+     * @brief Verifica se una partizione separa due nodi
+     * @param from Nodo mittente
+     * @param to Nodo destinatario
+     * @return true se i due nodi sono nella stessa parte della rete
+     */
+    bool canReach(const std::string& from, const std::string& to) const;
+
+    /**
+     * @brief Avanza il tempo virtuale consegnando le copie in scadenza
+     *
+     * Le copie vengono consegnate in ordine di arrivo, portando il tempo
+     * virtuale all'istante di ciascuna; i pacchetti trasmessi dai gestori
+     * durante la consegna partono da quell'istante e, se arrivano entro
+     * la finestra, vengono consegnati nella stessa chiamata.
+     *
+     * @param ms Millisecondi di cui avanzare
+     * @return Numero di copie consegnate
+     */
+    size_t advance(uint32_t ms);
+
+    /**
+     * @brief Ottiene il tempo virtuale (ms dalla creazione della rete)
+     */
+    uint64_t now() const;
+
+    /**
+     * @brief Ottiene i contatori della rete
+     */
+    SimStats getStats() const;
+
+private:
+    /**
+     * @brief Copia di un pacchetto in viaggio verso un nodo
+     */
+    struct Delivery {
+        std::string from;
+        std::string to;
+        std::vector<uint8_t> datagram;
+    };
+
+    /// Caratteristiche predefinite dei collegamenti
+    SimLinkConfig defaults;
+
+    /// Impostazioni proprie dei collegamenti (mittente, destinatario)
+    std::map<std::pair<std::string, std::string>, SimLinkConfig> links;
+
+    /// Gestori dei nodi collegati
+    std::map<std::string, ReceiveHandler> nodes;
+
+    /// Parte della rete di ogni nodo separato (assente = parte principale)
+    std::map<std::string, uint32_t> partitions;
+
+    /// Ultima parte creata
+    uint32_t lastPartition = 0;
+
+    /// Copie in viaggio per (istante di arrivo, ordine di trasmissione)
+    std::map<std::pair<uint64_t, uint64_t>, Delivery> inFlight;
+
+    /// Ordine di trasmissione della prossima copia
+    uint64_t nextSequence = 0;
+
+    /// Tempo virtuale (ms)
+    uint64_t clockMs = 0;
+
+    /// Generatore delle estrazioni (sequenza definita dallo standard)
+    std::mt19937_64 engine;
+
+    /// Contatori
+    SimStats stats;
+
+    /// Mutex per lo stato della rete
+    mutable std::mutex simMutex;
+
+    /// Serializza le consegne con detach(), così un gestore non viene invocato dopo lo scollegamento
+    std::mutex deliveryMutex;
+
+    /**
+     * @brief Caratteristiche di un collegamento (con simMutex acquisito)
+     */
+    const SimLinkConfig& linkLocked(const std::string& from, const std::string& to) const;
+
+    /**
+     * @brief Verifica la raggiungibilità (con simMutex acquisito)
+     */
+    bool canReachLocked(const std::string& from, const std::string& to) const;
+};
+
+} // namespace saber
+
+#endif // SABER_SIM_H
@@ -0,0 +1,260 @@
+#ifndef SABER_SIMULCAST_H
+#define SABER_SIMULCAST_H
+
+#include "spec.h"
+
+#include <cstdint>
+#include <mutex>
+#include <optional>
+#include <string>
+#include <vector>
+
+namespace saber {
+
+/// Numero massimo di livelli di qualità di uno stream simulcast
+constexpr size_t MAX_SIMULCAST_LAYERS = 3;
+
+/// Intervallo tra gli annunci dei gruppi simulcast del Master (ms)
+constexpr uint32_t SIMULCAST_ANNOUNCE_INTERVAL_MS = 2000;
+
+/// Frame attesi prima di valutare la perdita su un livello
+constexpr uint32_t SIMULCAST_MIN_WINDOW_FRAMES = 50;
+
+/// Attesa massima del primo frame di un livello richiesto (ms)
+constexpr uint32_t SIMULCAST_SWITCH_TIMEOUT_MS = 2000;
+
+/**
+ * @brief Livello di qualità di uno stream simulcast
+ */
+struct SimulcastLayer {
+    /// Stream su cui viene trasmesso il livello
+    StreamId streamId = 0;
+
+    /// Bitrate di codifica del livello (kbps)
+    uint32_t bitrateKbps = 0;
+};
+
+/**
+ * @brief Stream codificato dal Master a più livelli di qualità
+ *
+ * Il primo livello viaggia sullo stream stesso, gli altri su stream
+ * dedicati; i livelli sono ordinati per bitrate decrescente. I sink si
+ * sottoscrivono allo stream e ricevono il livello adatto al proprio
+ * collegamento.
+ */
+struct SimulcastGroup {
+    /// Stream a cui i sink si sottoscrivono
+    StreamId streamId = 0;
+
+    /// Livelli, dal bitrate più alto al più basso
+    std::vector<SimulcastLayer> layers;
+};
+
+/**
+ * @brief Verifica che un gruppo simulcast sia ben formato
+ * @param group Gruppo da verificare
+ * @throws std::invalid_argument se i livelli sono meno di due o più di MAX_SIMULCAST_LAYERS,
+ *         se il primo non è lo stream del gruppo, se uno stream si ripete o se i bitrate
+ *         non sono strettamente decrescenti
+ */
+void validateSimulcastGroup(const SimulcastGroup& group);
+
+/**
+ * @brief Codifica i livelli di un gruppo come "stream:kbps,stream:kbps"
+ * @param layers Livelli da codificare
+ * @return Testo usato negli annunci del Master
+ */
+std::string encodeSimulcastLayers(const std::vector<SimulcastLayer>& layers);
+
+/**
+ * @brief Interpreta i livelli codificati da encodeSimulcastLayers()
+ * @param text Testo da interpretare
+ * @return Livelli, o std::nullopt se il testo non è valido
+ */
+std::optional<std::vector<SimulcastLayer>> parseSimulcastLayers(const std::string& text);
+
+/**
+ * @brief Parametri della scelta del livello da parte dei sink
+ */
+struct SimulcastConfig {
+    /// Margine richiesto sopra il bitrate di un livello (percentuale)
+    uint32_t headroomPercent = 20;
+
+    /// Tempo senza perdite prima di salire di un livello (ms)
+    uint32_t upgradeAfterMs = 5000;
+
+    /// Perdita oltre cui il livello corrente non è sostenibile (percentuale)
+    uint32_t lossThresholdPercent = 5;
+};
+
+/**
+ * @brief Trattamento di un frame ricevuto su un livello simulcast
+ */
+enum class SimulcastVerdict {
+    /// Frame da decodificare e riprodurre
+    Play,
+    /// Frame già riprodotto dal livello precedente: va solo decodificato,
+    /// per preparare il decodificatore del nuovo livello
+    Prime,
+    /// Frame di un livello non riprodotto
+    Drop
+};
+
+/**
+ * @brief Stato della ricezione di uno stream simulcast
+ */
+struct SimulcastStatus {
+    /// Stream del gruppo
+    StreamId streamId = 0;
+
+    /// Livello riprodotto
+    StreamId activeLayer = 0;
+
+    /// Livello richiesto, in attesa del primo frame utile
+    std::optional<StreamId> pendingLayer;
+
+    /// Bitrate del livello riprodotto (kbps)
+    uint32_t bitrateKbps = 0;
+
+    /// Banda disponibile stimata o imposta (kbps)
+    std::optional<uint32_t> budgetKbps;
+
+    /// Perdita misurata sull'ultima finestra (percentuale)
+    double lossPercent = 0.0;
+
+    /// Cambi di livello completati
+    uint64_t switches = 0;
+};
+
+/**
+ * @brief Scelta del livello simulcast ricevuto da un sink
+ *
+ * Il livello scende appena la banda disponibile non basta più e sale di
+ * un gradino alla volta dopo un periodo senza perdite. Senza una banda
+ * imposta dall'esterno la stima viene dalla perdita misurata: una
+ * finestra con perdite limita la banda a quanto effettivamente ricevuto,
+ * mentre un periodo pulito rimuove il limite e prova il livello superiore.
+ *
+ * Durante un cambio il sink riceve entrambi i livelli e passa al nuovo
+ * al primo frame che ne arriva, al confine tra due frame: se quel frame
+ * è già stato riprodotto dal livello precedente serve solo a preparare
+ * il decodificatore, e da lì in poi passa soltanto il nuovo livello.
+ * Il Master numera i frame dei livelli in parallelo, così il cambio non
+ * salta né ripete alcun frame.
+ */
+class SimulcastReceiver {
+public:
+    /**
+     * @brief Crea il ricevitore, che parte dal livello più alto
+     * @param group Gruppo ricevuto
+     * @param config Parametri della scelta del livello
+     * @throws std::invalid_argument se il gruppo non è ben formato
+     */
+    explicit SimulcastReceiver(const SimulcastGroup& group, const SimulcastConfig& config = {});
+
+    /**
+     * @brief Aggiorna i parametri della scelta del livello
+     */
+    void setConfig(const SimulcastConfig& config);
+
+    /**
+     * @brief Ottiene il gruppo ricevuto
+     */
+    SimulcastGroup getGroup() const;
+
+    /**
+     * @brief Verifica se uno stream è un livello del gruppo
+     */
+    bool hasLayer(StreamId streamId) const;
+
+    /**
+     * @brief Valuta un frame ricevuto su un livello del gruppo
+     * @param layer Stream su cui è arrivato il frame
+     * @param sequence Numero di sequenza del frame
+     * @param playoutTimeUs Istante di riproduzione del frame (µs)
+     * @param nowMs Istante corrente in millisecondi
+     * @return Trattamento del frame
+     */
+    SimulcastVerdict accept(StreamId layer, uint32_t sequence, uint64_t playoutTimeUs, uint64_t nowMs);
+
+    /**
+     * @brief Rivaluta il livello da ricevere
+     * @param budgetKbps Banda disponibile imposta (std::nullopt per stimarla dalla perdita)
+     * @param nowMs Istante corrente in millisecondi
+     * @return true se i livelli da ricevere sono cambiati
+     */
+    bool update(std::optional<uint32_t> budgetKbps, uint64_t nowMs);
+
+    /**
+     * @brief Livelli da ricevere: quello riprodotto e quello richiesto, se c'è
+     */
+    std::vector<StreamId> wantedLayers() const;
+
+    /**
+     * @brief Ottiene lo stato della ricezione
+     */
+    SimulcastStatus getStatus() const;
+
+private:
+    /**
+     * @brief Indice di un livello nel gruppo
+     */
+    std::optional<size_t> indexOf(StreamId layer) const;
+
+    /**
+     * @brief Verifica se un livello rientra nella banda, con il margine configurato
+     */
+    bool fits(size_t index, std::optional<uint32_t> budgetKbps) const;
+
+    /**
+     * @brief Azzera la finestra di misura della perdita
+     */
+    void resetWindow();
+
+    /// Gruppo ricevuto
+    SimulcastGroup group;
+
+    /// Parametri della scelta del livello
+    SimulcastConfig config;
+
+    /// Indice del livello riprodotto
+    size_t active = 0;
+
+    /// Indice del livello richiesto
+    std::optional<size_t> pending;
+
+    /// Inizio dell'attesa del livello richiesto (ms)
+    uint64_t pendingSinceMs = 0;
+
+    /// Istante di riproduzione dell'ultimo frame consegnato (µs)
+    std::optional<uint64_t> lastPlayoutUs;
+
+    /// Primo e ultimo numero di sequenza della finestra corrente
+    std::optional<uint32_t> windowFirst;
+    uint32_t windowLast = 0;
+
+    /// Frame ricevuti nella finestra corrente
+    uint32_t windowReceived = 0;
+
+    /// Perdita misurata sull'ultima finestra completa (0-1)
+    double loss = 0.0;
+
+    /// Banda stimata dalla perdita (kbps)
+    std::optional<uint32_t> estimatedKbps;
+
+    /// Inizio del periodo senza perdite (ms)
+    std::optional<uint64_t> cleanSinceMs;
+
+    /// Ultima banda considerata (kbps)
+    std::optional<uint32_t> lastBudgetKbps;
+
+    /// Cambi di livello completati
+    uint64_t switches = 0;
+
+    /// Mutex per proteggere l'accesso concorrente
+    mutable std::mutex receiverMutex;
+};
+
+} // namespace saber
+
+#endif // SABER_SIMULCAST_H
@@ -0,0 +1,22 @@
+#ifndef SABER_SOCKET_COMPAT_H
+#define SABER_SOCKET_COMPAT_H
+
+// Compatibilità minima tra socket BSD e Winsock per i server di servizio
+
+#ifdef _WIN32
+#include <winsock2.h>
+#include <ws2tcpip.h>
+#pragma comment(lib, "ws2_32.lib")
+using socklen_t = int;
+#define SABER_CLOSE_SOCKET closesocket
+#else
+#include <arpa/inet.h>
+#include <netdb.h>
+#include <netinet/in.h>
+#include <sys/select.h>
+#include <sys/socket.h>
+#include <unistd.h>
+#define SABER_CLOSE_SOCKET close
+#endif
+
+#endif // SABER_SOCKET_COMPAT_H
@@ -0,0 +1,287 @@
+#ifndef SABER_SOURCE_SELECTOR_H
+#define SABER_SOURCE_SELECTOR_H
+
+#include <cstdint>
+#include <map>
+#include <mutex>
+#include <optional>
+#include <string>
+#include <vector>
+
+#include "codec.h"
+
+namespace saber {
+
+/**
+ * @brief Tipo di una sorgente audio del Master
+ */
+enum class SourceKind {
+    /// Scheda di acquisizione (es. mixer della sala)
+    Capture,
+    /// Coda di file riprodotti in sequenza
+    FileQueue,
+    /// Flusso RTP ricevuto dalla rete
+    Rtp,
+    /// Tono di prova generato localmente
+    TestTone
+};
+
+/**
+ * @brief Converte un tipo di sorgente in un nome stabile
+ */
+std::string sourceKindToString(SourceKind kind);
+
+/**
+ * @brief Interpreta il nome di un tipo di sorgente
+ * @param value "capture", "file", "rtp" o "tone"
+ * @return Tipo corrispondente, o nullopt se il nome è sconosciuto
+ */
+std::optional<SourceKind> sourceKindFromString(const std::string& value);
+
+/**
+ * @brief Sorgente audio configurata sul Master
+ */
+struct AudioSourceConfig {
+    /// Nome della sorgente, usato nei comandi e nei frame offerti
+    std::string name;
+
+    /// Tipo di sorgente
+    SourceKind kind = SourceKind::Capture;
+
+    /// Dispositivo, file o indirizzo della sorgente (interpretato dall'applicazione)
+    std::string location;
+
+    /// Priorità (0 = preferita); a parità di priorità le sorgenti si alternano
+    uint32_t priority = 0;
+};
+
+/**
+ * @brief Parametri della scelta automatica della sorgente
+ */
+struct SourceSelectionConfig {
+    /// Sorgenti; a parità di priorità la rotazione segue quest'ordine
+    std::vector<AudioSourceConfig> sources;
+
+    /// Tempo senza frame dopo cui una sorgente è ferma (ms)
+    uint32_t stallMs = 2000;
+
+    /// Tempo di solo silenzio dopo cui una sorgente è ferma (ms, 0 = il silenzio è ammesso)
+    uint32_t silenceMs = 10000;
+
+    /// Ampiezza di picco sotto cui un frame è silenzio
+    uint16_t silenceThreshold = 64;
+
+    /// Tempo di buona salute prima di tornare a una sorgente preferita (ms)
+    uint32_t failbackMs = 5000;
+
+    /// Ritorno automatico alla sorgente preferita quando riprende
+    bool autoFailback = true;
+};
+
+/**
+ * @brief Stato di una sorgente audio
+ */
+struct SourceStatus {
+    /// Nome della sorgente
+    std::string name;
+
+    /// Tipo di sorgente
+    SourceKind kind = SourceKind::Capture;
+
+    /// Priorità configurata
+    uint32_t priority = 0;
+
+    /// Sorgente trasmessa ai sink
+    bool active = false;
+
+    /// Frame recenti e non solo silenzio
+    bool healthy = false;
+
+    /// Frame offerti dalla sorgente
+    uint64_t frames = 0;
+
+    /// Millisecondi dall'ultimo frame (nullopt se non ne ha mai offerti)
+    std::optional<uint64_t> idleMs;
+};
+
+/**
+ * @brief Cambio della sorgente attiva
+ */
+struct SourceSwitch {
+    /// Sorgente precedente (vuota se non ce n'era una)
+    std::string from;
+
+    /// Nuova sorgente attiva (vuota se nessuna è in salute)
+    std::string to;
+
+    /// Motivo: "initial", "stalled", "silent", "failback", "manual" o "removed"
+    std::string reason;
+};
+
+/**
+ * @brief Scelta della sorgente audio trasmessa dal Master
+ *
+ * L'applicazione offre i frame di tutte le sorgenti che riesce a
+ * leggere; il selettore inoltra solo quelli della sorgente attiva. Una
+ * sorgente senza frame per stallMs, o con solo silenzio per silenceMs,
+ * viene sostituita dalla sorgente in salute con la priorità migliore;
+ * tra sorgenti con la stessa priorità si procede a rotazione, così una
+ * sorgente che si ferma ripetutamente non viene ripresa per prima. Un
+ * cambio manuale resta in vigore finché la sorgente scelta è in salute.
+ */
+class SourceSelector {
+public:
+    /**
+     * @brief Crea il selettore senza sorgente attiva
+     */
+    explicit SourceSelector(const SourceSelectionConfig& config = {});
+
+    /**
+     * @brief Aggiorna i parametri e le sorgenti
+     *
+     * Lo stato delle sorgenti che restano configurate viene conservato;
+     * se la sorgente attiva viene rimossa, la scelta riparte al prossimo
+     * update().
+     */
+    void setConfig(const SourceSelectionConfig& config);
+
+    /**
+     * @brief Ottiene i parametri in uso
+     */
+    SourceSelectionConfig getConfig() const;
+
+    /**
+     * @brief Registra un frame letto da una sorgente
+     * @param name Sorgente che ha prodotto il frame
+     * @param frame Frame PCM
+     * @param nowMs Istante corrente in millisecondi
+     * @return true se la sorgente è quella attiva e il frame va trasmesso
+     */
+    bool offerFrame(const std::string& name, const AudioFrame& frame, uint64_t nowMs);
+
+    /**
+     * @brief Valuta la salute delle sorgenti e cambia quella attiva se serve
+     * @param nowMs Istante corrente in millisecondi
+     * @return Cambio avvenuto, se la sorgente attiva è cambiata
+     */
+    std::optional<SourceSwitch> update(uint64_t nowMs);
+
+    /**
+     * @brief Passa manualmente ad una sorgente
+     *
+     * Il ritorno automatico alla sorgente preferita resta sospeso finché
+     * la sorgente scelta è in salute o fino a resumeAutomatic().
+     *
+     * @param name Sorgente da attivare
+     * @return Cambio avvenuto, o nullopt se la sorgente era già attiva
+     * @throws std::invalid_argument se la sorgente non è configurata
+     */
+    std::optional<SourceSwitch> switchTo(const std::string& name);
+
+    /**
+     * @brief Torna alla scelta automatica dopo un cambio manuale
+     */
+    void resumeAutomatic();
+
+    /**
+     * @brief Verifica se la sorgente attiva è stata scelta manualmente
+     */
+    bool isManual() const;
+
+    /**
+     * @brief Ottiene la sorgente attiva
+     * @return Nome della sorgente, o nullopt se nessuna è in salute
+     */
+    std::optional<std::string> getActive() const;
+
+    /**
+     * @brief Ottiene lo stato delle sorgenti nell'ordine dei parametri
+     * @param nowMs Istante corrente in millisecondi
+     */
+    std::vector<SourceStatus> getStatus(uint64_t nowMs) const;
+
+private:
+    /**
+     * @brief Salute di una sorgente
+     */
+    struct SourceHealth {
+        /// Frame offerti
+        uint64_t frames = 0;
+        /// Ultimo frame (ms)
+        std::optional<uint64_t> lastFrameMs;
+        /// Ultimo frame non silenzioso (ms)
+        std::optional<uint64_t> lastAudibleMs;
+        /// Inizio del periodo di buona salute corrente (ms)
+        std::optional<uint64_t> healthySinceMs;
+    };
+
+    /**
+     * @brief Motivo per cui una sorgente non è in salute
+     * @return "stalled", "silent", o stringa vuota se è in salute
+     */
+    std::string faultOf(const std::string& name, uint64_t nowMs) const;
+
+    /**
+     * @brief Sceglie la sorgente in salute con la priorità migliore
+     * @param after Sorgente da cui proseguire la rotazione a parità di priorità
+     */
+    std::optional<std::string> pickHealthy(uint64_t nowMs, const std::string& after) const;
+
+    /**
+     * @brief Cerca la configurazione di una sorgente
+     */
+    const AudioSourceConfig* findSource(const std::string& name) const;
+
+    /// Parametri in uso
+    SourceSelectionConfig config;
+
+    /// Salute delle sorgenti configurate
+    std::map<std::string, SourceHealth> health;
+
+    /// Sorgente attiva
+    std::optional<std::string> active;
+
+    /// La sorgente attiva è stata scelta manualmente
+    bool manual = false;
+
+    /// Mutex per proteggere l'accesso concorrente
+    mutable std::mutex selectorMutex;
+};
+
+/**
+ * @brief Generatore del tono di prova
+ *
+ * Produce frame di 10ms di una sinusoide continua da offrire come
+ * sorgente TestTone, ultima risorsa perché la sala non resti muta.
+ */
+class ToneGenerator {
+public:
+    /**
+     * @brief Crea il generatore
+     * @param frequencyHz Frequenza del tono
+     * @param sampleRate Frequenza di campionamento dei frame
+     * @param channels Numero di canali dei frame
+     * @param amplitude Ampiezza di picco
+     */
+    explicit ToneGenerator(float frequencyHz = 1000.0f, uint32_t sampleRate = spec::SAMPLE_RATE_MUSIC_HZ,
+                           uint8_t channels = 2, int16_t amplitude = 8192);
+
+    /**
+     * @brief Genera il frame successivo
+     * @param playoutTimeUs Istante di riproduzione del frame (µs)
+     */
+    AudioFrame next(uint64_t playoutTimeUs);
+
+private:
+    float frequencyHz;
+    uint32_t sampleRate;
+    uint8_t channels;
+    int16_t amplitude;
+
+    /// Fase della sinusoide (radianti)
+    double phase = 0.0;
+};
+
+} // namespace saber
+
+#endif // SABER_SOURCE_SELECTOR_H
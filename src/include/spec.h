@@ -0,0 +1,135 @@
+#ifndef SABER_SPEC_H
+#define SABER_SPEC_H
+
+#include <cstdint>
+#include <optional>
+#include <string>
+#include <vector>
+
+namespace saber {
+
+/// Identificatore di uno stream audio (BIS) pubblicato dal Master
+using StreamId = uint16_t;
+
+/**
+ * @brief Modo in cui pianificatore e controllori trattano il budget di latenza
+ */
+enum class LatencyMode {
+    /// Il budget orienta le scelte ma può essere superato per non perdere sink
+    BestEffort,
+    /// Configurazioni e adattamenti oltre il budget vengono rifiutati
+    Strict
+};
+
+/**
+ * @brief Converte un modo di latenza in un nome stabile
+ * @param mode Modo di latenza
+ * @return "best_effort" o "strict"
+ */
+std::string latencyModeToString(LatencyMode mode);
+
+/**
+ * @brief Interpreta il nome di un modo di latenza
+ * @param value Nome del modo
+ * @return Modo, oppure std::nullopt se il nome non è valido
+ */
+std::optional<LatencyMode> latencyModeFromString(const std::string& value);
+
+/**
+ * @brief Costanti imposte dalla specifica del protocollo (docs/PAPER.md)
+ *
+ * Ogni valore riporta la sezione del paper da cui deriva. I valori
+ * regolabili a runtime sono raccolti in spec::Parameters.
+ */
+namespace spec {
+
+/// Intervallo tra beacon temporali dell'UCB (sezione 3.3)
+constexpr uint32_t BEACON_INTERVAL_MS = 10;
+
+/// Jitter massimo tollerato tra dispositivi (sezione 4.2)
+constexpr uint32_t JITTER_TOLERANCE_MS = 5;
+
+/// Budget di latenza end-to-end (sezione 4.1)
+constexpr uint32_t LATENCY_BUDGET_MS = 40;
+
+/// Margine aggiunto alla latenza misurata nel dimensionare il buffer
+constexpr uint32_t BUFFER_MARGIN_MS = 10;
+
+/// Buffer di riproduzione in assenza di misurazioni di latenza
+constexpr uint32_t DEFAULT_BUFFER_MS = 20;
+
+/// Frequenza di campionamento per la musica (sezione 4.1)
+constexpr uint32_t SAMPLE_RATE_MUSIC_HZ = 48000;
+
+/// Frequenza di campionamento per la voce (sezione 4.1)
+constexpr uint32_t SAMPLE_RATE_VOICE_HZ = 16000;
+
+/// Bitrate LC3 massimo, usato per la musica (sezione 4.1)
+constexpr uint32_t BITRATE_MUSIC_KBPS = 128;
+
+/// Bitrate LC3 minimo, usato per la voce e per la musica su rete debole (sezione 4.1)
+constexpr uint32_t BITRATE_VOICE_KBPS = 64;
+
+/// Bitrate per la voce su rete debole
+constexpr uint32_t BITRATE_VOICE_REDUCED_KBPS = 32;
+
+/// Durata di un frame LC3 (sezione 4.1)
+constexpr uint32_t LC3_FRAME_DURATION_US = 10000;
+
+/// Intervallo senza ping dopo cui un nodo è considerato inattivo
+constexpr uint32_t NODE_TIMEOUT_S = 30;
+
+/// Dispositivi simultanei verificati per un Master, Master incluso (sezione 4.2)
+constexpr uint32_t MAX_NODES = 100;
+
+/**
+ * @brief Parametri di specifica sovrascrivibili tramite SaberConfig
+ *
+ * I valori predefiniti coincidono con le costanti della specifica; le
+ * sovrascritture servono solo per sperimentazione.
+ */
+struct Parameters {
+    /// Jitter massimo tollerato (ms)
+    uint32_t jitterToleranceMs = JITTER_TOLERANCE_MS;
+
+    /// Budget di latenza end-to-end (ms)
+    uint32_t latencyBudgetMs = LATENCY_BUDGET_MS;
+
+    /// Margine del buffer rispetto alla latenza misurata (ms)
+    uint32_t bufferMarginMs = BUFFER_MARGIN_MS;
+
+    /// Buffer in assenza di misurazioni (ms)
+    uint32_t defaultBufferMs = DEFAULT_BUFFER_MS;
+
+    /// Buffer minimo anche con latenze molto basse (ms, non fissato dalla specifica)
+    uint32_t minBufferMs = 0;
+
+    /// Tempo senza beacon dopo cui il nodo non è più sincronizzato (ms, 0 = mai)
+    uint32_t beaconTimeoutMs = 0;
+
+    /// Scarto dal Master oltre cui l'orologio viene riallineato di colpo (ms, 0 = sempre)
+    uint32_t clockStepThresholdMs = 100;
+
+    /// Velocità massima con cui l'orologio recupera uno scarto minore (ppm)
+    uint32_t clockMaxSlewPpm = 500;
+
+    /// Rispetto del budget di latenza da parte di pianificatore e controllori
+    LatencyMode latencyMode = LatencyMode::BestEffort;
+
+    /**
+     * @brief Elenca i parametri che si discostano dalla specifica
+     * @return Descrizioni nel formato "nome=valore (spec: valore)"
+     */
+    std::vector<std::string> deviations() const;
+
+    /**
+     * @brief Verifica la coerenza dei parametri
+     * @throws std::invalid_argument se i parametri non sono coerenti
+     */
+    void validate() const;
+};
+
+} // namespace spec
+} // namespace saber
+
+#endif // SABER_SPEC_H
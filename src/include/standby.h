@@ -0,0 +1,111 @@
+#ifndef SABER_STANDBY_H
+#define SABER_STANDBY_H
+
+#include <cstdint>
+#include <mutex>
+#include <optional>
+#include <string>
+
+namespace saber {
+
+/**
+ * @brief Parametri dello standby automatico dei sink
+ */
+struct StandbyConfig {
+    /// Abilita lo standby automatico
+    bool enabled = false;
+
+    /// Minuti senza stream sottoscritti prima dello standby
+    uint32_t idleMinutes = 10;
+
+    /// Anticipo con cui il dispositivo audio viene riacceso prima di riprodurre (ms)
+    uint32_t prerollMs = 500;
+
+    /// Intervallo minimo tra annunci e resoconti di stato in standby (ms)
+    uint32_t reportIntervalMs = 30000;
+};
+
+/**
+ * @brief Stato di alimentazione di un sink
+ */
+enum class PowerState {
+    /// Dispositivo audio acceso
+    Active,
+    /// Dispositivo audio spento e resoconti diradati
+    Standby,
+    /// Dispositivo audio in riaccensione durante il pre-roll
+    Waking
+};
+
+/**
+ * @brief Converte uno stato di alimentazione in un nome stabile
+ */
+std::string powerStateToString(PowerState state);
+
+/**
+ * @brief Macchina a stati dello standby di un sink
+ *
+ * Il sink va in standby quando resta senza stream sottoscritti per
+ * l'intervallo configurato. Un comando di risveglio o una nuova
+ * sottoscrizione lo riportano attivo dopo il pre-roll, il tempo che il
+ * dispositivo audio impiega a riaccendersi.
+ */
+class StandbyController {
+public:
+    /**
+     * @brief Crea il controllore, inizialmente attivo
+     */
+    explicit StandbyController(const StandbyConfig& config = {});
+
+    /**
+     * @brief Aggiorna i parametri (la finestra di inattività in corso resta valida)
+     */
+    void setConfig(const StandbyConfig& config);
+
+    /**
+     * @brief Avanza la macchina a stati
+     * @param streaming Il nodo ha almeno uno stream sottoscritto
+     * @param nowMs Istante corrente in millisecondi
+     * @return Nuovo stato, se è cambiato
+     */
+    std::optional<PowerState> update(bool streaming, uint64_t nowMs);
+
+    /**
+     * @brief Risveglia il nodo in standby
+     * @param nowMs Istante corrente in millisecondi
+     * @return PowerState::Waking se il nodo era in standby
+     */
+    std::optional<PowerState> wake(uint64_t nowMs);
+
+    /**
+     * @brief Ottiene lo stato di alimentazione corrente
+     */
+    PowerState getState() const;
+
+    /**
+     * @brief Intervallo dei resoconti periodici nello stato corrente
+     * @param activeIntervalMs Intervallo usato da attivo (ms)
+     * @return Intervallo da rispettare (ms)
+     */
+    uint32_t reportIntervalMs(uint32_t activeIntervalMs) const;
+
+private:
+    /// Parametri dello standby
+    StandbyConfig config;
+
+    /// Stato corrente
+    PowerState state = PowerState::Active;
+
+    /// Inizio dell'inattività corrente (ms)
+    std::optional<uint64_t> idleSinceMs;
+
+    /// Inizio del risveglio corrente (ms)
+    uint64_t wakingSinceMs = 0;
+
+    /// Mutex per proteggere l'accesso concorrente
+    mutable std::mutex standbyMutex;
+};
+
+} // namespace saber
+
+#endif // SABER_STANDBY_H
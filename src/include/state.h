@@ -0,0 +1,103 @@
+#ifndef SABER_STATE_H
+#define SABER_STATE_H
+
+#include <cstdint>
+#include <map>
+#include <optional>
+#include <set>
+#include <string>
+#include <vector>
+
+#include "mesh.h"
+#include "spec.h"
+
+namespace saber {
+
+/// Versione del documento di stato prodotta da questo nodo
+constexpr uint32_t NODE_STATE_VERSION = 1;
+
+/**
+ * @brief Nodo noto della rete con la sua chiave pubblica
+ */
+struct PeerState {
+    /// ID del nodo
+    std::string nodeId;
+
+    /// Ruolo del nodo
+    NodeRole role = NodeRole::Sink;
+
+    /// Chiave pubblica Ed25519 registrata all'ingresso
+    std::vector<uint8_t> publicKey;
+};
+
+/**
+ * @brief Stato completo di un nodo, trasferibile su un altro dispositivo
+ *
+ * Serve a migrare un Master su nuovo hardware o a preparare un Master di
+ * riserva a freddo. I segreti non vengono mai copiati nel documento: al
+ * loro posto compaiono i riferimenti "keystore:NOME" o "env:VARIABILE"
+ * con cui erano stati configurati, da risolvere sul dispositivo di
+ * destinazione.
+ */
+struct NodeState {
+    /// Versione del formato del documento
+    uint32_t version = NODE_STATE_VERSION;
+
+    /// Istante dell'esportazione (ms, orologio sincronizzato)
+    uint64_t exportedAtMs = 0;
+
+    /// ID del nodo
+    std::string nodeId;
+
+    /// Ruolo del nodo
+    NodeRole role = NodeRole::Sink;
+
+    /// Chiave pubblica di firma del nodo (vuota se l'identità non è ancora stata creata)
+    std::vector<uint8_t> publicKey;
+
+    /// Profilo d'uso applicato (vuoto se nessuno)
+    std::string profile;
+
+    /// Rispetto del budget di latenza
+    LatencyMode latencyMode = LatencyMode::BestEffort;
+
+    /// Stream pubblicati dal Master
+    std::set<StreamId> publishedStreams;
+
+    /// Stream ascoltati dai sink
+    std::set<StreamId> listenStreams;
+
+    /// Stream codificati come voce
+    std::set<StreamId> voiceStreams;
+
+    /// Keystore da cui risolvere i riferimenti ai segreti
+    std::optional<std::string> keystoreFile;
+
+    /// Riferimenti ai segreti (chiave del file -> riferimento, nullopt se il segreto era in chiaro)
+    std::map<std::string, std::optional<std::string>> secrets;
+
+    /// Appartenenza dei nodi alle zone (nodo -> zona)
+    std::map<std::string, std::string> zones;
+
+    /// Nodi noti con le loro chiavi pubbliche
+    std::vector<PeerState> peers;
+};
+
+/**
+ * @brief Codifica lo stato di un nodo come documento JSON versionato
+ * @param state Stato da codificare
+ * @return Documento JSON
+ */
+std::string encodeNodeState(const NodeState& state);
+
+/**
+ * @brief Interpreta un documento JSON di stato
+ * @param json Documento prodotto da encodeNodeState()
+ * @return Stato del nodo
+ * @throws std::invalid_argument se il documento non è valido o ha una versione più recente
+ */
+NodeState decodeNodeState(const std::string& json);
+
+} // namespace saber
+
+#endif // SABER_STATE_H
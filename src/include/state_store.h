@@ -0,0 +1,142 @@
+#ifndef SABER_STATE_STORE_H
+#define SABER_STATE_STORE_H
+
+#include <cstdint>
+#include <cstdio>
+#include <map>
+#include <mutex>
+#include <optional>
+#include <string>
+
+namespace saber {
+
+/// Record del registro delle modifiche dopo cui lo snapshot viene riscritto
+constexpr uint32_t DEFAULT_STATE_COMPACT_RECORDS = 256;
+
+/**
+ * @brief Esito del ripristino dello stato all'avvio
+ */
+struct StateRecoveryReport {
+    /// Snapshot valido trovato (principale o precedente)
+    bool snapshotLoaded = false;
+
+    /// Snapshot principale mancante o corrotto: usato quello precedente
+    bool fromBackup = false;
+
+    /// Record del registro riapplicati sopra lo snapshot
+    uint64_t replayedRecords = 0;
+
+    /// Byte in coda al registro scartati (scrittura interrotta o corrotta)
+    uint64_t discardedBytes = 0;
+
+    /// Voci dello stato ripristinato
+    uint64_t entries = 0;
+};
+
+/**
+ * @brief Archivio chiave-valore dello stato persistito, resistente agli spegnimenti
+ *
+ * Ogni modifica viene aggiunta al registro "<path>.wal" con il proprio
+ * CRC-32 e forzata su disco prima di essere considerata scritta. Dopo
+ * compactAfter record lo stato intero viene scritto in uno snapshot
+ * temporaneo, forzato su disco e sostituito con una rinomina atomica; lo
+ * snapshot sostituito resta in "<path>.bak" e solo allora il registro
+ * viene svuotato. All'avvio recover() carica l'ultimo snapshot integro e
+ * riapplica i record validi del registro, scartando una coda troncata o
+ * corrotta: lo stato torna sempre all'ultima modifica completata.
+ */
+class StateStore {
+public:
+    /**
+     * @brief Crea l'archivio, senza leggere il disco
+     * @param path File dello snapshot; il registro è "<path>.wal"
+     * @param compactAfter Record del registro dopo cui lo snapshot viene riscritto
+     */
+    explicit StateStore(const std::string& path, uint32_t compactAfter = DEFAULT_STATE_COMPACT_RECORDS);
+
+    ~StateStore();
+
+    StateStore(const StateStore&) = delete;
+    StateStore& operator=(const StateStore&) = delete;
+
+    /**
+     * @brief Ripristina l'ultimo stato consistente dal disco
+     *
+     * Va chiamato prima di put() ed erase(); la coda invalida del registro
+     * viene troncata così che le modifiche successive seguano l'ultimo
+     * record integro.
+     *
+     * @return Esito del ripristino
+     */
+    StateRecoveryReport recover();
+
+    /**
+     * @brief Ottiene il valore di una voce
+     */
+    std::optional<std::string> get(const std::string& key) const;
+
+    /**
+     * @brief Ottiene tutte le voci
+     */
+    std::map<std::string, std::string> entries() const;
+
+    /**
+     * @brief Imposta una voce e la rende persistente
+     * @return true se la modifica è su disco
+     * @throws std::invalid_argument se la chiave è vuota
+     */
+    bool put(const std::string& key, const std::string& value);
+
+    /**
+     * @brief Rimuove una voce e rende persistente la rimozione
+     * @return true se la modifica è su disco (anche se la voce non c'era)
+     */
+    bool erase(const std::string& key);
+
+    /**
+     * @brief Scrive lo snapshot e svuota il registro
+     * @return true se lo snapshot è stato sostituito
+     */
+    bool compact();
+
+    /**
+     * @brief Ottiene il file dello snapshot
+     */
+    const std::string& getPath() const;
+
+private:
+    /**
+     * @brief Aggiunge un record al registro e lo forza su disco (richiede storeMutex)
+     */
+    bool appendLocked(char operation, const std::string& key, const std::string& value);
+
+    /**
+     * @brief Scrive lo snapshot e svuota il registro (richiede storeMutex)
+     */
+    bool compactLocked();
+
+    /// File dello snapshot
+    std::string path;
+
+    /// Record dopo cui lo snapshot viene riscritto
+    uint32_t compactAfter;
+
+    /// Stato corrente
+    std::map<std::string, std::string> values;
+
+    /// Numero di sequenza dell'ultimo record scritto
+    uint64_t sequence = 0;
+
+    /// Record nel registro dall'ultimo snapshot
+    uint32_t walRecords = 0;
+
+    /// Registro aperto in aggiunta
+    std::FILE* wal = nullptr;
+
+    /// Mutex per l'archivio
+    mutable std::mutex storeMutex;
+};
+
+} // namespace saber
+
+#endif // SABER_STATE_STORE_H
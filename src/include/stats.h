@@ -0,0 +1,320 @@
+#ifndef SABER_STATS_H
+#define SABER_STATS_H
+
+#include <cstdint>
+#include <deque>
+#include <map>
+#include <mutex>
+#include <set>
+#include <string>
+#include <vector>
+
+namespace saber {
+
+/// Identificatore di uno stream audio (come in mesh.h)
+using StreamId = uint16_t;
+
+/**
+ * @brief Utilizzo della banda in una finestra temporale
+ */
+struct BandwidthUsage {
+    /// Byte trasmessi nella finestra
+    uint64_t bytes = 0;
+
+    /// Pacchetti trasmessi nella finestra
+    uint64_t packets = 0;
+
+    /// Tempo d'aria stimato in microsecondi
+    uint64_t airtimeUs = 0;
+
+    /// Throughput medio in byte al secondo
+    double bytesPerSecond = 0.0;
+
+    /// Frazione della finestra occupata dal canale radio (0-1)
+    double airtimeFraction = 0.0;
+};
+
+/**
+ * @brief Resoconto dell'utilizzo della banda della rete mesh
+ */
+struct BandwidthReport {
+    /// Durata della finestra in millisecondi
+    uint32_t windowMs = 0;
+
+    /// Utilizzo per stream
+    std::map<StreamId, BandwidthUsage> streams;
+
+    /// Utilizzo per nodo vicino (traffico ricevuto)
+    std::map<std::string, BandwidthUsage> neighbors;
+
+    /// Utilizzo per zona, aggregando i nodi vicini assegnati
+    std::map<std::string, BandwidthUsage> zones;
+
+    /// Utilizzo complessivo del canale (ricevuto e trasmesso)
+    BandwidthUsage total;
+};
+
+/**
+ * @brief Contabilità della banda per stream e per nodo vicino
+ *
+ * Ogni pacchetto viene registrato con la sua dimensione e un tempo d'aria
+ * stimato dalla velocità del collegamento; i campioni più vecchi della
+ * finestra vengono scartati.
+ */
+class MeshStats {
+public:
+    /**
+     * @brief Crea un contatore di banda
+     * @param windowMs Durata della finestra mobile in millisecondi
+     * @param linkRateKbps Velocità nominale del collegamento radio
+     * @param packetOverheadBytes Byte aggiunti dal livello di trasporto ad ogni pacchetto
+     */
+    explicit MeshStats(uint32_t windowMs = 5000, uint32_t linkRateKbps = 2000,
+                       uint32_t packetOverheadBytes = 16);
+
+    /**
+     * @brief Registra un pacchetto ricevuto da un nodo vicino
+     * @param neighborId Nodo da cui è arrivato il pacchetto
+     * @param bytes Dimensione del pacchetto
+     * @param nowMs Istante della ricezione (ms, orologio monotono)
+     */
+    void recordReceived(const std::string& neighborId, size_t bytes, int64_t nowMs);
+
+    /**
+     * @brief Registra un pacchetto trasmesso dal nodo locale
+     * @param bytes Dimensione del pacchetto
+     * @param nowMs Istante della trasmissione (ms, orologio monotono)
+     */
+    void recordSent(size_t bytes, int64_t nowMs);
+
+    /**
+     * @brief Registra il traffico attribuibile ad uno stream
+     * @param streamId Stream a cui appartiene il traffico
+     * @param bytes Dimensione del payload
+     * @param nowMs Istante del transito (ms, orologio monotono)
+     */
+    void recordStream(StreamId streamId, size_t bytes, int64_t nowMs);
+
+    /**
+     * @brief Calcola l'utilizzo della banda nella finestra corrente
+     * @param zones Appartenenza dei nodi alle zone, per l'aggregazione
+     * @param nowMs Istante di riferimento (ms, orologio monotono)
+     * @return Resoconto per stream, nodo vicino e zona
+     */
+    BandwidthReport report(const std::map<std::string, std::string>& zones, int64_t nowMs);
+
+    /**
+     * @brief Frazione del canale occupata nella finestra corrente
+     * @param nowMs Istante di riferimento (ms, orologio monotono)
+     * @return Utilizzo del canale (0-1, può superare 1 se il collegamento è saturo)
+     */
+    double airtimeUtilization(int64_t nowMs);
+
+    /**
+     * @brief Esporta un resoconto nel formato testuale di Prometheus
+     * @param report Resoconto da esportare
+     * @param nodeId ID del nodo, usato come etichetta
+     * @return Metriche in formato di esposizione Prometheus
+     */
+    static std::string toPrometheus(const BandwidthReport& report, const std::string& nodeId);
+
+private:
+    /**
+     * @brief Campione di traffico
+     */
+    struct Sample {
+        int64_t timeMs;
+        uint32_t bytes;
+    };
+
+    /// Finestra dei campioni di una singola chiave
+    using Window = std::deque<Sample>;
+
+    /// Durata della finestra
+    uint32_t windowMs;
+
+    /// Velocità del collegamento
+    uint32_t linkRateKbps;
+
+    /// Overhead di trasporto per pacchetto
+    uint32_t packetOverheadBytes;
+
+    /// Campioni per stream
+    std::map<StreamId, Window> streamSamples;
+
+    /// Campioni ricevuti per nodo vicino
+    std::map<std::string, Window> neighborSamples;
+
+    /// Campioni di tutto il traffico sul canale
+    Window channelSamples;
+
+    /// Mutex per i campioni
+    mutable std::mutex statsMutex;
+
+    /**
+     * @brief Scarta i campioni fuori dalla finestra
+     */
+    void expire(Window& window, int64_t nowMs) const;
+
+    /**
+     * @brief Riassume i campioni di una finestra
+     */
+    BandwidthUsage summarize(const Window& window) const;
+
+    /**
+     * @brief Tempo d'aria stimato per un pacchetto
+     */
+    uint64_t airtimeUs(uint32_t bytes) const;
+};
+
+/**
+ * @brief Statistiche di latenza e perdita di un nodo
+ */
+struct NodeNetworkStats {
+    /// Campioni di latenza nella finestra
+    size_t samples = 0;
+
+    /// Latenza media (ms)
+    double meanLatencyMs = 0.0;
+
+    /// Mediana della latenza (ms)
+    uint32_t p50LatencyMs = 0;
+
+    /// 95° percentile della latenza (ms)
+    uint32_t p95LatencyMs = 0;
+
+    /// 99° percentile della latenza (ms)
+    uint32_t p99LatencyMs = 0;
+
+    /// Latenza massima nella finestra (ms)
+    uint32_t maxLatencyMs = 0;
+
+    /// Varianza della latenza, misura del jitter (ms²)
+    double jitterVarianceMs2 = 0.0;
+
+    /// Istogramma della latenza (inizio dell'intervallo in ms -> campioni)
+    std::map<uint32_t, uint32_t> histogram;
+
+    /// Frame audio ricevuti nella finestra di sequenza
+    uint64_t framesReceived = 0;
+
+    /// Frame audio mancanti nella finestra di sequenza
+    uint64_t framesLost = 0;
+
+    /// Stima della perdita dei frame (0-1)
+    double lossRate = 0.0;
+};
+
+/**
+ * @brief Resoconto della latenza e della perdita della rete mesh
+ */
+struct NetworkStats {
+    /// Campioni conservati per nodo
+    size_t windowSamples = 0;
+
+    /// Ampiezza degli intervalli degli istogrammi (ms)
+    uint32_t bucketMs = 0;
+
+    /// Statistiche per nodo
+    std::map<std::string, NodeNetworkStats> nodes;
+
+    /// Statistiche aggregate su tutti i nodi
+    NodeNetworkStats overall;
+};
+
+/**
+ * @brief Istogrammi mobili della latenza e stima della perdita per nodo
+ *
+ * Conserva gli ultimi campioni di latenza di ogni nodo per calcolarne
+ * percentili, varianza e istogramma. La perdita viene stimata dai numeri
+ * di sequenza dei frame audio: nella finestra che termina con l'ultimo
+ * frame ricevuto ogni numero assente conta come perso, così i frame
+ * riparati o arrivati fuori ordine vengono recuperati.
+ */
+class LatencyStats {
+public:
+    /**
+     * @brief Crea le statistiche
+     * @param windowSamples Campioni di latenza e numeri di sequenza conservati per nodo
+     * @param bucketMs Ampiezza degli intervalli degli istogrammi (ms)
+     */
+    explicit LatencyStats(size_t windowSamples = 256, uint32_t bucketMs = 5);
+
+    /**
+     * @brief Registra un campione di latenza
+     * @param nodeId Nodo misurato
+     * @param latencyMs Latenza in millisecondi
+     */
+    void recordLatency(const std::string& nodeId, uint32_t latencyMs);
+
+    /**
+     * @brief Registra l'arrivo di un frame audio
+     * @param nodeId Nodo sorgente del frame
+     * @param streamId Stream del frame
+     * @param frameSequence Numero di sequenza del frame nello stream
+     */
+    void recordFrame(const std::string& nodeId, StreamId streamId, uint32_t frameSequence);
+
+    /**
+     * @brief Svuota le finestre di tutti i nodi
+     */
+    void reset();
+
+    /**
+     * @brief Calcola le statistiche correnti
+     * @return Resoconto per nodo e aggregato
+     */
+    NetworkStats report() const;
+
+    /**
+     * @brief Esporta un resoconto nel formato testuale di Prometheus
+     * @param stats Resoconto da esportare
+     * @param nodeId ID del nodo, usato come etichetta
+     * @return Metriche in formato di esposizione Prometheus
+     */
+    static std::string toPrometheus(const NetworkStats& stats, const std::string& nodeId);
+
+private:
+    /**
+     * @brief Numeri di sequenza ricevuti di uno stream
+     */
+    struct SequenceWindow {
+        /// Primo numero di sequenza osservato dall'ultimo azzeramento
+        uint32_t first = 0;
+
+        /// Numeri di sequenza ricevuti nella finestra
+        std::set<uint32_t> received;
+    };
+
+    /**
+     * @brief Finestre di un singolo nodo
+     */
+    struct NodeWindow {
+        /// Campioni di latenza (ms)
+        std::deque<uint32_t> latencies;
+
+        /// Sequenze ricevute per stream
+        std::map<StreamId, SequenceWindow> sequences;
+    };
+
+    /// Campioni conservati per nodo
+    size_t windowSamples;
+
+    /// Ampiezza degli intervalli degli istogrammi
+    uint32_t bucketMs;
+
+    /// Finestre per nodo
+    std::map<std::string, NodeWindow> nodes;
+
+    /// Mutex per le finestre
+    mutable std::mutex statsMutex;
+
+    /**
+     * @brief Riassume una serie di campioni di latenza
+     */
+    void summarizeLatency(std::vector<uint32_t> samples, NodeNetworkStats& stats) const;
+};
+
+} // namespace saber
+
+#endif // SABER_STATS_H
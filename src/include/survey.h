@@ -0,0 +1,156 @@
+#ifndef SABER_SURVEY_H
+#define SABER_SURVEY_H
+
+#include <cstdint>
+#include <map>
+#include <optional>
+#include <string>
+#include <vector>
+
+namespace saber {
+
+/**
+ * @brief Qualità misurata verso un nodo fisso da una posizione
+ */
+struct SurveyLink {
+    /// Nodo fisso misurato
+    std::string peer;
+
+    /// Sondaggi inviati
+    uint32_t probesSent = 0;
+
+    /// Risposte ricevute
+    uint32_t replies = 0;
+
+    /// Frazione di sondaggi senza risposta (0-1)
+    double lossRate = 0.0;
+
+    /// RSSI medio delle risposte (dBm), se la radio lo fornisce
+    std::optional<int32_t> meanRssiDbm;
+
+    /// RSSI peggiore delle risposte (dBm)
+    std::optional<int32_t> minRssiDbm;
+};
+
+/**
+ * @brief Collegamenti misurati da una posizione etichettata
+ */
+struct SurveyPosition {
+    /// Etichetta inserita dall'operatore
+    std::string label;
+
+    /// Collegamenti verso i nodi fissi, in ordine di ID
+    std::vector<SurveyLink> links;
+
+    /// Nodo fisso con il collegamento migliore (vuoto se nessuno ha risposto)
+    std::string bestPeer;
+
+    /// Almeno un collegamento rispetta le soglie di RSSI e perdita
+    bool covered = false;
+};
+
+/**
+ * @brief Resoconto di copertura di un sopralluogo
+ */
+struct CoverageReport {
+    /// Posizioni nell'ordine in cui sono state visitate
+    std::vector<SurveyPosition> positions;
+
+    /// Posizioni scoperte, dove serve un Repeater in più
+    std::vector<std::string> uncoveredPositions;
+};
+
+/**
+ * @brief Sopralluogo radio per decidere dove collocare i Repeater
+ *
+ * Un nodo portato in giro per il locale sonda periodicamente tutti i
+ * nodi fissi; l'operatore etichetta la posizione corrente e ogni
+ * sondaggio viene attribuito alla posizione in cui è partito. Una
+ * posizione è coperta se almeno un nodo fisso risponde con perdita e
+ * RSSI entro le soglie. I sondaggi ancora in volo contano come persi.
+ */
+class CoverageSurvey {
+public:
+    /**
+     * @brief Crea un sopralluogo
+     * @param minRssiDbm RSSI medio minimo di un collegamento utile (dBm)
+     * @param maxLossPercent Perdita massima di un collegamento utile (%)
+     */
+    explicit CoverageSurvey(int32_t minRssiDbm = -80, uint32_t maxLossPercent = 10);
+
+    /**
+     * @brief Imposta la posizione a cui attribuire i prossimi sondaggi
+     * @param label Etichetta della posizione (una già visitata riprende le sue misure)
+     * @throws std::invalid_argument se l'etichetta è vuota
+     */
+    void setPosition(const std::string& label);
+
+    /**
+     * @brief Posizione corrente (vuota prima della prima etichetta)
+     */
+    const std::string& getPosition() const;
+
+    /**
+     * @brief Registra un sondaggio inviato dalla posizione corrente
+     * @param peer Nodo fisso sondato
+     * @return Numero del sondaggio, o nullopt se non è stata impostata una posizione
+     */
+    std::optional<uint32_t> recordProbe(const std::string& peer);
+
+    /**
+     * @brief Registra la risposta a un sondaggio
+     * @param peer Nodo fisso che ha risposto
+     * @param probeId Numero del sondaggio
+     * @param rssiDbm RSSI della risposta (dBm), se disponibile
+     * @return true se il sondaggio era in attesa di risposta da quel nodo
+     */
+    bool recordReply(const std::string& peer, uint32_t probeId, std::optional<int32_t> rssiDbm);
+
+    /**
+     * @brief Riassume le misure raccolte
+     */
+    CoverageReport report() const;
+
+private:
+    /**
+     * @brief Misure grezze verso un nodo fisso
+     */
+    struct LinkSamples {
+        uint32_t probesSent = 0;
+        uint32_t replies = 0;
+        std::vector<int32_t> rssiDbm;
+    };
+
+    /**
+     * @brief Sondaggio in attesa di risposta
+     */
+    struct PendingProbe {
+        std::string position;
+        std::string peer;
+    };
+
+    /// RSSI medio minimo di un collegamento utile (dBm)
+    int32_t minRssiDbm;
+
+    /// Perdita massima di un collegamento utile (%)
+    uint32_t maxLossPercent;
+
+    /// Posizione corrente
+    std::string position;
+
+    /// Posizioni nell'ordine di visita
+    std::vector<std::string> order;
+
+    /// Misure per posizione e nodo fisso
+    std::map<std::string, std::map<std::string, LinkSamples>> samples;
+
+    /// Sondaggi senza risposta per numero
+    std::map<uint32_t, PendingProbe> pending;
+
+    /// Ultimo numero di sondaggio assegnato
+    uint32_t lastProbeId;
+};
+
+} // namespace saber
+
+#endif // SABER_SURVEY_H
@@ -1,24 +1,57 @@
 #ifndef SABER_SYNC_H
 #define SABER_SYNC_H
 
+#include <atomic>
 #include <chrono>
+#include <functional>
 #include <map>
 #include <memory>
 #include <mutex>
 #include <optional>
 #include <string>
 
+#include "clock_discipline.h"
+#include "codec.h"
+#include "jitter_buffer.h"
+#include "spec.h"
+
 namespace saber {
 
+/**
+ * @brief Esito di uno scambio temporale con il master
+ */
+struct TimeExchange {
+    /// Offset del master rispetto all'orologio locale (µs)
+    int64_t offsetUs = 0;
+    
+    /// Tempo di andata e ritorno, escluso il tempo di risposta del master (µs)
+    uint64_t roundTripUs = 0;
+};
+
 /**
  * @brief Struttura per gestire la sincronizzazione temporale tra i dispositivi
  */
 class SyncManager {
 public:
+    /// Gestore dei cambi di stato della sincronizzazione (true = sincronizzato)
+    using SyncStateHandler = std::function<void(bool)>;
+    
     /**
      * @brief Crea una nuova istanza del gestore di sincronizzazione
+     *
+     * Soglia di jitter, limiti del buffer e timeout dei beacon vengono
+     * dai parametri, che SaberProtocol riceve da SaberConfig::spec.
+     *
+     * @param params Parametri di specifica (jitter, budget di latenza, buffer, timeout dei beacon)
+     * @throws std::invalid_argument se i parametri non sono coerenti
+     */
+    explicit SyncManager(const spec::Parameters& params = spec::Parameters());
+    
+    /**
+     * @brief Ottiene i parametri in uso
+     * @return Parametri passati al costruttore
      */
-    SyncManager();
+    const spec::Parameters& getParameters() const;
     
     /**
      * @brief Ottiene il timestamp corrente sincronizzato
@@ -26,19 +59,99 @@ public:
      */
     uint64_t now() const;
     
+    /**
+     * @brief Ottiene il timestamp corrente sincronizzato con risoluzione al microsecondo
+     * @return Timestamp in microsecondi
+     */
+    uint64_t nowUs() const;
+    
+    /**
+     * @brief Ottiene il timestamp dell'orologio locale, senza la correzione verso il master
+     * @return Timestamp in microsecondi
+     */
+    uint64_t localUs() const;
+    
     /**
      * @brief Gestisce un beacon temporale ricevuto dal master
+     *
+     * Il primo beacon allinea l'orologio di colpo; i successivi aggiornano
+     * la stima di offset e deriva, e l'orologio la raggiunge gradualmente
+     * senza salti. Uno scarto oltre spec.clock_step_threshold_ms viene
+     * invece chiuso di colpo.
+     *
+     * Il beacon arriva in ritardo del transito dal master: dopo il primo
+     * scambio temporale il ritardo misurato viene aggiunto al tempo del
+     * master. Un beacon inoltrato dai Repeater porta anche il tempo
+     * trascorso negli inoltri, aggiunto allo stesso modo.
+     *
      * @param masterTime Tempo del master
+     * @param relayDelayUs Ritardo accumulato nei Repeater (µs, 0 se ricevuto direttamente)
      * @return true se la sincronizzazione è avvenuta con successo, false altrimenti
      */
-    bool handleTimeBeacon(uint64_t masterTime);
+    bool handleTimeBeacon(uint64_t masterTime, uint32_t relayDelayUs = 0);
+    
+    /**
+     * @brief Gestisce uno scambio temporale richiesta/risposta con il master
+     *
+     * Come in NTP l'offset è ((t2 - t1) + (t3 - t4)) / 2 e il tempo di
+     * andata e ritorno (t4 - t1) - (t3 - t2): a differenza del beacon
+     * l'offset non è falsato dal transito, purché simmetrico. L'offset
+     * aggiorna l'orologio come un beacon e metà del tempo di andata e
+     * ritorno diventa la latenza del master.
+     *
+     * @param masterId ID del master
+     * @param sentUs Invio della richiesta, orologio locale (t1, µs)
+     * @param masterReceivedUs Ricezione della richiesta, orologio del master (t2, µs)
+     * @param masterRepliedUs Invio della risposta, orologio del master (t3, µs)
+     * @param receivedUs Ricezione della risposta, orologio locale (t4, µs)
+     * @return false se gli istanti non sono coerenti e lo scambio è stato scartato
+     */
+    bool handleTimeExchange(const std::string& masterId, uint64_t sentUs, uint64_t masterReceivedUs,
+                            uint64_t masterRepliedUs, uint64_t receivedUs);
+    
+    /**
+     * @brief Ottiene l'ultimo scambio temporale accettato
+     * @return Esito dello scambio, o nullopt se non ce ne sono stati
+     */
+    std::optional<TimeExchange> getLastExchange() const;
+    
+    /**
+     * @brief Ottiene lo stato dell'orologio sincronizzato
+     * @return Offset applicato e stimato, deriva in ppm e contatori
+     */
+    ClockStats getClockStats() const;
     
     /**
      * @brief Verifica se il nodo è sincronizzato
+     *
+     * Con un timeout dei beacon impostato il nodo smette di essere
+     * sincronizzato se il Master tace più a lungo del timeout.
+     *
      * @return true se il nodo è sincronizzato, false altrimenti
      */
     bool isSynchronized() const;
     
+    /**
+     * @brief Rileva un Master muto da oltre il timeout dei beacon
+     *
+     * Va chiamato periodicamente: al primo controllo dopo il timeout il
+     * nodo viene marcato come non sincronizzato e il gestore notificato.
+     * Il beacon successivo lo sincronizza di nuovo.
+     *
+     * @return true se il nodo ha appena perso la sincronizzazione
+     */
+    bool checkBeaconTimeout();
+    
+    /**
+     * @brief Registra il gestore dei cambi di stato della sincronizzazione
+     *
+     * Viene chiamato senza lock interni, dal thread che riceve il beacon
+     * o che esegue checkBeaconTimeout().
+     *
+     * @param handler Funzione chiamata ad ogni perdita o recupero della sincronizzazione
+     */
+    void setSyncStateHandler(SyncStateHandler handler);
+    
     /**
      * @brief Calcola e registra la latenza di un nodo
      * @param nodeId ID del nodo
@@ -63,7 +176,7 @@ public:
     /**
      * @brief Calcola il buffer necessario per compensare la latenza
      * @param nodeLatency Latenza del nodo in millisecondi
-     * @return Dimensione del buffer in millisecondi
+     * @return Dimensione del buffer in millisecondi, tra il buffer minimo e il budget di latenza
      */
     uint32_t calculateBufferAdjustment(uint32_t nodeLatency) const;
     
@@ -75,14 +188,30 @@ public:
     
     /**
      * @brief Effettua una sincronizzazione di emergenza (quando la connessione BIS è persa)
-     * @param masterTime Tempo del master
+     *
+     * L'orologio viene riallineato di colpo, conservando la deriva stimata.
+     * Come per i beacon, il transito misurato dall'ultimo scambio temporale
+     * viene compensato.
+     *
+     * @param masterTime Tempo del master (ms)
      * @return true se la sincronizzazione è avvenuta con successo, false altrimenti
      */
     bool emergencySync(uint64_t masterTime);
     
 private:
-    /// Offset per sincronizzare l'orologio locale con il master
-    std::shared_ptr<int64_t> timeOffset;
+    /**
+     * @brief Applica un offset misurato all'orologio e aggiorna lo stato di sincronizzazione
+     * @param currentTime Istante della misura, orologio locale (µs)
+     * @param measuredOffset Offset del master rispetto all'orologio locale (µs)
+     * @param forceStep Riallinea di colpo invece di correggere gradualmente
+     */
+    bool synchronize(int64_t currentTime, int64_t measuredOffset, bool forceStep);
+    
+    /// Disciplina dell'orologio locale sui beacon del master
+    ClockDiscipline clock;
+    
+    /// Ultimo scambio temporale accettato
+    std::optional<TimeExchange> lastExchange;
     
     /// Timestamp dell'ultimo beacon ricevuto
     std::shared_ptr<std::optional<std::chrono::steady_clock::time_point>> lastBeacon;
@@ -93,15 +222,60 @@ private:
     /// Flag che indica se il dispositivo è sincronizzato
     std::shared_ptr<bool> isSynced;
     
-    /// Jitter massimo tollerato (in ms)
-    uint32_t maxJitterMs;
+    /// Parametri di specifica in uso
+    spec::Parameters params;
+    
+    /// Gestore dei cambi di stato della sincronizzazione
+    SyncStateHandler syncStateHandler;
     
     /// Mutex per proteggere l'accesso concorrente
     mutable std::mutex syncMutex;
 };
 
+/**
+ * @brief Profilo audio della rete
+ */
+enum class AudioProfile {
+    /// Musica: 48kHz stereo a 128kbps
+    Music,
+    /// Voce: 16kHz mono a 64kbps
+    Voice
+};
+
+/**
+ * @brief Converte un profilo audio nella sua rappresentazione testuale
+ * @param profile Profilo audio
+ * @return "music" o "voice"
+ */
+std::string audioProfileToString(AudioProfile profile);
+
+/**
+ * @brief Interpreta il nome di un profilo audio
+ * @param name Nome del profilo ("music" o "voice")
+ * @return Profilo, o nullopt se il nome non è noto
+ */
+std::optional<AudioProfile> audioProfileFromString(const std::string& name);
+
+/**
+ * @brief Formato dei frame di uno stream audio
+ */
+struct StreamFormat {
+    /// Frequenza di campionamento (Hz)
+    uint32_t sampleRateHz = spec::SAMPLE_RATE_MUSIC_HZ;
+    
+    /// Numero di canali
+    uint8_t channels = 2;
+
+    /// Bitrate fisso di codifica (kbps, 0 = quello del nodo)
+    uint32_t bitrateKbps = 0;
+};
+
 /**
  * @brief Struttura per la sincronizzazione dell'audio
+ *
+ * Ogni stream ha il proprio codificatore e decodificatore LC3, così il
+ * Master può trasmettere insieme, ad esempio, la musica e gli annunci
+ * vocali. Gli stream senza un formato dedicato usano quello del nodo.
  */
 class AudioSync {
 public:
@@ -109,8 +283,9 @@ public:
      * @brief Crea una nuova istanza del sincronizzatore audio
      * @param syncManager Manager di sincronizzazione
      * @param isMusic Flag che indica se l'audio è musicale (48kHz) o vocale (16kHz)
+     * @param channels Numero di canali dei frame audio
      */
-    AudioSync(std::shared_ptr<SyncManager> syncManager, bool isMusic);
+    AudioSync(std::shared_ptr<SyncManager> syncManager, bool isMusic, uint8_t channels = 2);
     
     /**
      * @brief Avvia la riproduzione sincronizzata
@@ -123,6 +298,53 @@ public:
      */
     void stopPlayback();
     
+    /**
+     * @brief Impone il buffer di jitter al prossimo avvio invece di quello ottimale
+     *
+     * Il buffer imposto non si adatta durante la riproduzione.
+     *
+     * In modalità di latenza rigorosa il buffer imposto viene limitato al
+     * budget di latenza.
+     *
+     * @param bufferMs Buffer in millisecondi (std::nullopt per tornare a quello ottimale)
+     */
+    void setBufferOverride(std::optional<uint32_t> bufferMs);
+    
+    /**
+     * @brief Imposta i parametri del buffer di jitter adattivo
+     *
+     * In modalità di latenza rigorosa il buffer massimo viene limitato al
+     * budget di latenza.
+     *
+     * @param config Parametri di adattamento
+     */
+    void setJitterBufferConfig(const JitterBufferConfig& config);
+    
+    /**
+     * @brief Registra l'arrivo di un frame nel buffer di jitter
+     *
+     * Fuori dalla riproduzione i frame non vengono contati.
+     *
+     * @param streamId Stream del frame
+     * @param sequence Numero di sequenza del frame
+     * @param playoutTimeUs Istante di riproduzione del frame (µs)
+     * @param arrivalUs Istante di arrivo sull'orologio sincronizzato (µs)
+     * @return Nuova dimensione del buffer (ms) se è stato ridimensionato
+     */
+    std::optional<uint32_t> recordFrame(StreamId streamId, uint32_t sequence, uint64_t playoutTimeUs, 
+                                        uint64_t arrivalUs);
+    
+    /**
+     * @brief Ottiene la dimensione corrente del buffer di jitter
+     * @return Dimensione in millisecondi
+     */
+    uint32_t getJitterBuffer() const;
+    
+    /**
+     * @brief Ottiene dimensione, jitter, perdita e contatori del buffer di jitter
+     */
+    JitterBufferStats getJitterBufferStats() const;
+    
     /**
      * @brief Aggiusta il bitrate in base alle condizioni della rete
      * @param networkQuality Qualità della rete (0.0-1.0)
@@ -141,21 +363,198 @@ public:
      */
     bool isPlaybackSynchronized() const;
     
+    /**
+     * @brief Reagisce a un cambio di stato della sincronizzazione
+     *
+     * Alla perdita della sincronizzazione la riproduzione viene sospesa;
+     * al recupero riprende solo se era stata sospesa per questo motivo.
+     *
+     * @param synchronized Nuovo stato della sincronizzazione
+     */
+    void handleSyncState(bool synchronized);
+    
+    /**
+     * @brief Verifica se la riproduzione è sospesa in attesa dei beacon
+     * @return true se la riproduzione riprenderà al recupero della sincronizzazione
+     */
+    bool isPausedForSync() const;
+    
+    /**
+     * @brief Mette in pausa l'uscita su comando del Master
+     *
+     * I frame continuano ad essere decodificati, così il decoder resta
+     * allineato allo stream, ma non vanno consegnati all'uscita. Un nuovo
+     * avvio o un arresto tolgono la pausa.
+     */
+    void pausePlayback();
+    
+    /**
+     * @brief Riprende l'uscita messa in pausa con pausePlayback()
+     */
+    void resumePlayback();
+    
+    /**
+     * @brief Verifica se l'uscita è in pausa su comando del Master
+     */
+    bool isPaused() const;
+    
+    /**
+     * @brief Imposta il volume applicato ai frame decodificati
+     * @param volumePercent Volume lineare in percentuale (0 silenzia l'uscita)
+     * @throws std::invalid_argument se il volume supera 100
+     */
+    void setVolume(uint8_t volumePercent);
+    
+    /**
+     * @brief Ottiene il volume applicato ai frame decodificati
+     * @return Volume in percentuale
+     */
+    uint8_t getVolume() const;
+    
+    /**
+     * @brief Imposta il formato dei frame di uno stream
+     *
+     * I codificatori già creati per lo stream vengono scartati.
+     *
+     * @param streamId Stream da configurare
+     * @param isMusic Flag che indica se lo stream è musicale (48kHz) o vocale (16kHz)
+     * @param channels Numero di canali dei frame
+     */
+    void setStreamFormat(StreamId streamId, bool isMusic, uint8_t channels);
+    
+    /**
+     * @brief Ottiene il formato dei frame di uno stream
+     * @param streamId Stream richiesto
+     * @return Formato dedicato, o quello del nodo se lo stream non ne ha uno
+     */
+    StreamFormat getStreamFormat(StreamId streamId) const;
+    
+    /**
+     * @brief Cambia il formato del nodo, usato dagli stream senza un formato dedicato
+     *
+     * I codificatori di quegli stream vengono scartati e ricreati al
+     * prossimo frame; il numero di canali non cambia.
+     *
+     * @param isMusic Flag che indica se l'audio è musicale (48kHz) o vocale (16kHz)
+     */
+    void setMusicMode(bool isMusic);
+    
+    /**
+     * @brief Fissa il bitrate di codifica di uno stream
+     *
+     * Uno stream con bitrate fisso (es. un livello simulcast) non segue
+     * gli aggiustamenti di adjustBitrate().
+     *
+     * @param streamId Stream da configurare
+     * @param bitrateKbps Bitrate in kbps (0 per tornare a quello del nodo)
+     */
+    void setStreamBitrate(StreamId streamId, uint32_t bitrateKbps);
+    
+    /**
+     * @brief Codifica in LC3 un frame PCM di 10ms prodotto dal master
+     * @param frame Frame nel formato dello stream
+     * @param streamId Stream a cui appartiene il frame
+     * @return Frame codificato al bitrate corrente
+     * @throws std::invalid_argument se il frame non ha il formato atteso
+     * @throws std::runtime_error se la libreria LC3 non è stata compilata
+     */
+    std::vector<uint8_t> encodeFrame(const AudioFrame& frame, StreamId streamId = 0);
+    
+    /**
+     * @brief Decodifica un frame LC3 ricevuto da un sink
+     * @param payload Frame codificato
+     * @param playoutTimeUs Istante di riproduzione del frame (µs)
+     * @param streamId Stream a cui appartiene il frame
+     * @return Frame PCM
+     * @throws std::invalid_argument se il frame codificato non è valido
+     * @throws std::runtime_error se la libreria LC3 non è stata compilata
+     */
+    AudioFrame decodeFrame(const std::vector<uint8_t>& payload, uint64_t playoutTimeUs, StreamId streamId = 0);
+    
+    /**
+     * @brief Ricostruisce un frame perso in rete
+     * @param playoutTimeUs Istante di riproduzione del frame perso (µs)
+     * @param streamId Stream a cui appartiene il frame
+     * @return Frame PCM ricostruito dai precedenti
+     * @throws std::runtime_error se la libreria LC3 non è stata compilata
+     */
+    AudioFrame concealFrame(uint64_t playoutTimeUs, StreamId streamId = 0);
+    
+    /**
+     * @brief Ottiene la frequenza di campionamento dei frame
+     * @return Frequenza in Hz
+     */
+    uint32_t getSampleRate() const;
+    
+    /**
+     * @brief Ottiene il bitrate di codifica corrente
+     * @return Bitrate in kbps
+     */
+    uint32_t getBitrate() const;
+    
 private:
+    /**
+     * @brief Stato LC3 di uno stream
+     */
+    struct StreamCodec {
+        StreamFormat format;
+        std::unique_ptr<Lc3Encoder> encoder;
+        std::unique_ptr<Lc3Decoder> decoder;
+    };
+    
+    /**
+     * @brief Ottiene lo stato LC3 di uno stream, creandolo con il formato del nodo
+     */
+    StreamCodec& codecFor(StreamId streamId);
+    
+    /**
+     * @brief Bitrate corrente per una frequenza di campionamento
+     */
+    uint32_t bitrateFor(uint32_t sampleRateHz) const;
+    
+    /**
+     * @brief Bitrate di codifica di uno stream, fisso o quello del nodo
+     */
+    uint32_t bitrateFor(const StreamFormat& format) const;
+
     /// Manager di sincronizzazione globale
     std::shared_ptr<SyncManager> syncManager;
     
-    /// Buffer di jitter in millisecondi
-    uint32_t jitterBuffer;
+    /// Buffer di jitter, dimensionato all'avvio e adattato durante la riproduzione
+    AdaptiveJitterBuffer jitterBuffer;
+    
+    /// Buffer imposto da un gruppo esteso (es. modalità festa)
+    std::optional<uint32_t> bufferOverride;
     
     /// Flag che indica se l'audio è in riproduzione
     bool isPlaying;
     
+    /// Riproduzione sospesa per perdita della sincronizzazione
+    bool pausedForSync = false;
+    
+    /// Uscita in pausa su comando (letta dal thread di decodifica)
+    std::atomic<bool> paused{false};
+    
+    /// Volume dei frame decodificati in percentuale (letto dal thread di decodifica)
+    std::atomic<uint8_t> volumePercent{100};
+    
     /// Formato audio (sezione 4.1 del PAPER.md)
     uint32_t sampleRate;
     
     /// Bitrate in kbps
     uint32_t bitrate;
+    
+    /// Bitrate ridotto per una rete debole
+    bool reducedBitrate = false;
+    
+    /// Numero di canali dei frame audio
+    uint8_t channels;
+    
+    /// Stream con un formato diverso da quello del nodo
+    std::map<StreamId, StreamFormat> streamFormats;
+    
+    /// Codificatori e decodificatori LC3 per stream, creati al primo frame
+    std::map<StreamId, StreamCodec> streamCodecs;
 };
 
 } // namespace saber
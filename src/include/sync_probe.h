@@ -0,0 +1,178 @@
+#ifndef SABER_SYNC_PROBE_H
+#define SABER_SYNC_PROBE_H
+
+#include <cstdint>
+#include <optional>
+#include <string>
+#include <vector>
+
+namespace saber {
+
+/**
+ * @brief Stima dello sfasamento rispetto all'asimmetria dei collegamenti
+ */
+enum class AsymmetryMode {
+    /// Transito uguale nei due versi (metà del tempo di andata e ritorno)
+    Symmetric,
+    /// Asimmetria stimata dai ritardi minimi osservati in ciascun verso
+    MinimumDelay
+};
+
+/**
+ * @brief Converte un modo di stima in un nome stabile
+ */
+std::string asymmetryModeToString(AsymmetryMode mode);
+
+/**
+ * @brief Interpreta il nome di un modo di stima
+ * @param value "symmetric" o "min_delay"
+ * @return Modo corrispondente, o nullopt se il nome è sconosciuto
+ */
+std::optional<AsymmetryMode> asymmetryModeFromString(const std::string& value);
+
+/**
+ * @brief Esito di una misura di sincronizzazione tra due sink
+ */
+struct SyncProbeResult {
+    /// Sink misurato rispetto al nodo locale
+    std::string peer;
+
+    /// Scambi completati
+    size_t samples = 0;
+
+    /// Sfasamento medio dell'orologio del peer rispetto al locale (µs, positivo se in anticipo)
+    int64_t meanSkewUs = 0;
+
+    /// Sfasamento minimo osservato (µs)
+    int64_t minSkewUs = 0;
+
+    /// Sfasamento massimo osservato (µs)
+    int64_t maxSkewUs = 0;
+
+    /// Tempo di andata e ritorno medio, escluso il tempo di risposta del peer (µs)
+    uint64_t meanRoundTripUs = 0;
+
+    /// Transito medio di andata meno quello di ritorno, stimato dai ritardi minimi (µs)
+    int64_t asymmetryUs = 0;
+
+    /// Modo di stima usato per gli sfasamenti
+    AsymmetryMode mode = AsymmetryMode::Symmetric;
+
+    /// Tutti gli sfasamenti entro la tolleranza di jitter della specifica
+    bool withinTolerance = false;
+
+    /// Sfasamento misurato acusticamente tra i due clic (µs), se riportato
+    std::optional<int64_t> acousticSkewUs;
+
+    /// Fine della misura (ms, orologio della rete)
+    uint64_t measuredAtMs = 0;
+};
+
+/**
+ * @brief Misura dello sfasamento tra gli orologi di due sink
+ *
+ * Il nodo locale invia al peer pacchetti di innesco con il proprio
+ * istante di invio; il peer risponde con gli istanti di ricezione e di
+ * risposta sul proprio orologio. Da ogni scambio si ricava lo sfasamento
+ * come in NTP, compensando il tempo di transito simmetrico, così che la
+ * tolleranza di ±5 ms della specifica diventi un numero misurato per
+ * ogni installazione.
+ *
+ * Sui collegamenti BLE le ritrasmissioni allungano spesso un solo verso
+ * e la metà del tempo di andata e ritorno sbaglia lo sfasamento di metà
+ * dell'asimmetria. Il ritardo minimo osservato in ciascun verso è invece
+ * quasi privo di ritrasmissioni, e quindi simmetrico: con
+ * AsymmetryMode::MinimumDelay lo sfasamento si ricava dagli scambi più
+ * rapidi di ogni verso e l'asimmetria media stimata corregge ogni scambio.
+ */
+class SyncProbe {
+public:
+    /**
+     * @brief Crea una misura
+     * @param peer Sink da misurare
+     * @param sampleCount Scambi da completare
+     * @param toleranceMs Sfasamento massimo ammesso (ms)
+     * @param mode Stima dello sfasamento rispetto all'asimmetria
+     */
+    SyncProbe(const std::string& peer, size_t sampleCount, uint32_t toleranceMs,
+              AsymmetryMode mode = AsymmetryMode::Symmetric);
+
+    /**
+     * @brief Sfasamento stimato da uno scambio
+     * @param sentUs Invio dell'innesco (orologio locale, µs)
+     * @param peerReceivedUs Ricezione dell'innesco (orologio del peer, µs)
+     * @param peerRepliedUs Invio della risposta (orologio del peer, µs)
+     * @param receivedUs Ricezione della risposta (orologio locale, µs)
+     * @return Sfasamento del peer rispetto al locale (µs)
+     */
+    static int64_t skewUs(uint64_t sentUs, uint64_t peerReceivedUs, uint64_t peerRepliedUs, uint64_t receivedUs);
+
+    /**
+     * @brief Tempo di andata e ritorno di uno scambio, escluso il tempo di risposta del peer
+     */
+    static uint64_t roundTripUs(uint64_t sentUs, uint64_t peerReceivedUs, uint64_t peerRepliedUs,
+                                uint64_t receivedUs);
+
+    /**
+     * @brief Registra uno scambio completato
+     * @return Sfasamento dello scambio (µs)
+     */
+    int64_t addSample(uint64_t sentUs, uint64_t peerReceivedUs, uint64_t peerRepliedUs, uint64_t receivedUs);
+
+    /**
+     * @brief Assegna il numero del prossimo innesco
+     */
+    uint32_t nextProbeId();
+
+    /**
+     * @brief Numero di inneschi inviati
+     */
+    uint32_t getProbesSent() const;
+
+    /**
+     * @brief Verifica se sono stati completati tutti gli scambi
+     */
+    bool isComplete() const;
+
+    /**
+     * @brief Sink misurato
+     */
+    const std::string& getPeer() const;
+
+    /**
+     * @brief Riassume gli scambi completati
+     * @param measuredAtMs Istante della misura (ms, orologio della rete)
+     * @return Esito della misura
+     */
+    SyncProbeResult result(uint64_t measuredAtMs) const;
+
+private:
+    /// Sink misurato
+    std::string peer;
+
+    /// Scambi da completare
+    size_t sampleCount;
+
+    /// Sfasamento massimo ammesso (µs)
+    int64_t toleranceUs;
+
+    /// Stima dello sfasamento rispetto all'asimmetria
+    AsymmetryMode mode;
+
+    /// Inneschi inviati
+    uint32_t probesSent;
+
+    /// Sfasamenti misurati (µs)
+    std::vector<int64_t> skews;
+
+    /// Tempi di andata e ritorno misurati (µs)
+    std::vector<uint64_t> roundTrips;
+
+    /// Transiti di andata e di ritorno, ciascuno falsato dallo sfasamento (µs)
+    std::vector<int64_t> outbounds;
+    std::vector<int64_t> inbounds;
+};
+
+} // namespace saber
+
+#endif // SABER_SYNC_PROBE_H
@@ -0,0 +1,142 @@
+#ifndef SABER_TIMESTAMP_H
+#define SABER_TIMESTAMP_H
+
+#include <cstdint>
+#include <optional>
+#include <string>
+
+namespace saber {
+
+/**
+ * @brief Ampiezza del timestamp di riproduzione nei pacchetti Audio
+ *
+ * Un timestamp completo a 64 bit occupa otto byte di ogni frame, troppi
+ * per il payload BLE. I frame portano quindi solo i bit meno significativi
+ * del timestamp (in µs), ricostruiti dal ricevitore a partire dall'ultimo
+ * valore noto; un frame con il timestamp completo (ancora) viene inviato
+ * periodicamente e dopo ogni discontinuità.
+ */
+enum class TimestampWidth : uint8_t {
+    /// Timestamp completo a 64 bit (ancora)
+    Full = 0,
+    /// 16 bit: si riavvolge ogni 65 ms
+    Bits16 = 16,
+    /// 24 bit: si riavvolge ogni 16,7 s
+    Bits24 = 24
+};
+
+/**
+ * @brief Converte un'ampiezza in numero di bit
+ * @param width Ampiezza del timestamp
+ * @return Numero di bit trasmessi
+ */
+uint8_t timestampBits(TimestampWidth width);
+
+/**
+ * @brief Converte un numero di bit nell'ampiezza corrispondente
+ * @param bits Numero di bit (16, 24 o 64)
+ * @return Ampiezza, o nullopt se il valore non è supportato
+ */
+std::optional<TimestampWidth> timestampWidthFromBits(uint32_t bits);
+
+/**
+ * @brief Tronca un timestamp ai bit meno significativi
+ * @param timestampUs Timestamp completo in µs
+ * @param width Ampiezza compressa
+ * @return Bit trasmessi nel pacchetto; con TimestampWidth::Full i 32 bit bassi, senza troncamento ulteriore
+ */
+uint32_t compressTimestamp(uint64_t timestampUs, TimestampWidth width);
+
+/**
+ * @brief Ricostruisce un timestamp compresso vicino ad un riferimento
+ *
+ * Restituisce il valore con i bit indicati più vicino al riferimento, in
+ * avanti o all'indietro di al massimo mezzo periodo: gestisce sia il
+ * riavvolgimento del contatore sia i frame arrivati fuori ordine.
+ *
+ * @param compact Bit ricevuti
+ * @param width Ampiezza compressa
+ * @param referenceUs Ultimo timestamp completo noto
+ * @return Timestamp completo in µs
+ */
+uint64_t expandTimestamp(uint32_t compact, TimestampWidth width, uint64_t referenceUs);
+
+/**
+ * @brief Sceglie l'ampiezza del timestamp dei frame di uno stream
+ */
+class TimestampEncoder {
+public:
+    /**
+     * @brief Crea un codificatore
+     * @param width Ampiezza dei frame compressi
+     * @param anchorInterval Frame tra due ancore consecutive
+     */
+    explicit TimestampEncoder(TimestampWidth width = TimestampWidth::Bits24, uint32_t anchorInterval = 50);
+
+    /**
+     * @brief Decide l'ampiezza per il prossimo frame
+     *
+     * Il primo frame, uno ogni anchorInterval e ogni frame distante dal
+     * precedente almeno mezzo periodo sono inviati come ancora.
+     *
+     * @param timestampUs Timestamp completo del frame
+     * @return Ampiezza da usare nel pacchetto
+     */
+    TimestampWidth next(uint64_t timestampUs);
+
+    /**
+     * @brief Forza un'ancora al prossimo frame (es. dopo un cambio brano)
+     */
+    void reset();
+
+private:
+    /// Ampiezza dei frame compressi
+    TimestampWidth width;
+
+    /// Frame tra due ancore
+    uint32_t anchorInterval;
+
+    /// Frame dall'ultima ancora
+    uint32_t sinceAnchor;
+
+    /// Timestamp del frame precedente
+    std::optional<uint64_t> previousUs;
+};
+
+/**
+ * @brief Ricostruisce i timestamp dei frame di uno stream
+ *
+ * Il riferimento è l'ultimo timestamp ricostruito, fatto avanzare del
+ * tempo locale trascorso dal suo arrivo: una raffica di frame persi più
+ * lunga di mezzo periodo non sposta la ricostruzione sul periodo
+ * sbagliato, perché i timestamp dello stream scorrono con l'orologio.
+ */
+class TimestampDecoder {
+public:
+    /**
+     * @brief Decodifica il timestamp di un frame
+     * @param width Ampiezza del timestamp nel pacchetto
+     * @param value Timestamp completo (ancora) o bit compressi
+     * @param arrivalUs Istante di arrivo del frame (µs, orologio monotono locale; nullopt se ignoto)
+     * @return Timestamp completo, o nullopt se non è ancora arrivata un'ancora
+     */
+    std::optional<uint64_t> decode(TimestampWidth width, uint64_t value,
+                                   std::optional<int64_t> arrivalUs = std::nullopt);
+
+    /**
+     * @brief Verifica se è stata ricevuta almeno un'ancora
+     * @return true se i frame compressi possono essere decodificati
+     */
+    bool isAnchored() const;
+
+private:
+    /// Ultimo timestamp ricostruito, usato come riferimento
+    std::optional<uint64_t> referenceUs;
+
+    /// Istante di arrivo del frame del riferimento (µs, orologio monotono locale)
+    std::optional<int64_t> referenceArrivalUs;
+};
+
+} // namespace saber
+
+#endif // SABER_TIMESTAMP_H
@@ -0,0 +1,183 @@
+#ifndef SABER_TIMING_H
+#define SABER_TIMING_H
+
+#include <array>
+#include <chrono>
+#include <cstdint>
+#include <fstream>
+#include <mutex>
+#include <optional>
+#include <string>
+#include <vector>
+
+namespace saber {
+
+/**
+ * @brief Fasi della pipeline audio, dalla sorgente al diffusore
+ */
+enum class PipelineStage : uint8_t {
+    Encode = 0,
+    Encrypt,
+    Send,
+    Receive,
+    Decrypt,
+    Decode,
+    Resample,
+    Render,
+};
+
+/// Numero di fasi della pipeline
+const size_t PIPELINE_STAGE_COUNT = 8;
+
+/**
+ * @brief Nome di una fase della pipeline
+ * @param stage Fase
+ * @return Nome in minuscolo (es. "decode")
+ */
+std::string pipelineStageToString(PipelineStage stage);
+
+/**
+ * @brief Interpreta il nome di una fase della pipeline
+ * @param value Nome della fase
+ * @return Fase, oppure std::nullopt se il nome non è valido
+ */
+std::optional<PipelineStage> pipelineStageFromString(const std::string& value);
+
+/**
+ * @brief Percentili dei tempi di una fase
+ */
+struct StageTiming {
+    /// Fase misurata
+    PipelineStage stage = PipelineStage::Encode;
+
+    /// Campioni nella finestra
+    size_t samples = 0;
+
+    /// Campioni registrati dall'avvio
+    uint64_t total = 0;
+
+    /// Mediana (µs)
+    uint64_t p50Us = 0;
+
+    /// 95° percentile (µs)
+    uint64_t p95Us = 0;
+
+    /// 99° percentile (µs)
+    uint64_t p99Us = 0;
+
+    /// Massimo nella finestra (µs)
+    uint64_t maxUs = 0;
+};
+
+/**
+ * @brief Profilatore dei tempi della pipeline audio
+ *
+ * Conserva gli ultimi campioni di ogni fase per calcolarne i percentili,
+ * così da capire in quale fase un sink perde le scadenze di riproduzione.
+ * Se è indicato un file di tracciamento, ogni campione vi viene aggiunto
+ * nel formato "pila compressa" (es. "saber;sink-1;decode 412") letto
+ * direttamente da flamegraph.pl e inferno.
+ */
+class PipelineProfiler {
+public:
+    /**
+     * @brief Crea un profilatore
+     * @param windowSamples Campioni conservati per fase
+     * @param tracePrefix Radice delle pile scritte nel file di tracciamento
+     */
+    explicit PipelineProfiler(size_t windowSamples = 1024, const std::string& tracePrefix = "saber");
+
+    /**
+     * @brief Registra la durata di una fase
+     * @param stage Fase
+     * @param durationUs Durata in microsecondi
+     */
+    void record(PipelineStage stage, uint64_t durationUs);
+
+    /**
+     * @brief Percentili di una fase
+     * @param stage Fase
+     * @return Tempi della fase nella finestra
+     */
+    StageTiming timing(PipelineStage stage) const;
+
+    /**
+     * @brief Percentili delle fasi con almeno un campione
+     * @return Tempi in ordine di pipeline
+     */
+    std::vector<StageTiming> timings() const;
+
+    /**
+     * @brief Attiva o disattiva il tracciamento su file
+     * @param path File a cui aggiungere i campioni (vuoto per disattivare)
+     * @return true se il file è stato aperto o il tracciamento disattivato
+     */
+    bool setTraceFile(const std::string& path);
+
+    /**
+     * @brief Svuota le finestre di tutte le fasi
+     */
+    void reset();
+
+private:
+    /**
+     * @brief Finestra circolare dei campioni di una fase
+     */
+    struct StageWindow {
+        /// Campioni (µs)
+        std::vector<uint64_t> samples;
+
+        /// Posizione del prossimo campione una volta piena
+        size_t next = 0;
+
+        /// Campioni registrati dall'avvio
+        uint64_t total = 0;
+    };
+
+    /// Campioni conservati per fase
+    size_t windowSamples;
+
+    /// Radice delle pile tracciate
+    std::string tracePrefix;
+
+    /// Finestre per fase
+    std::array<StageWindow, PIPELINE_STAGE_COUNT> windows;
+
+    /// File di tracciamento
+    std::ofstream traceFile;
+
+    /// Mutex per finestre e file
+    mutable std::mutex timingMutex;
+};
+
+/**
+ * @brief Misura la durata di un blocco e la registra alla sua uscita
+ */
+class StageTimer {
+public:
+    /**
+     * @brief Avvia la misura
+     * @param profiler Profilatore (nessuna misura se nullptr)
+     * @param stage Fase misurata
+     */
+    StageTimer(PipelineProfiler* profiler, PipelineStage stage);
+
+    ~StageTimer();
+
+    StageTimer(const StageTimer&) = delete;
+    StageTimer& operator=(const StageTimer&) = delete;
+
+private:
+    /// Profilatore
+    PipelineProfiler* profiler;
+
+    /// Fase misurata
+    PipelineStage stage;
+
+    /// Inizio della misura
+    std::chrono::steady_clock::time_point start;
+};
+
+} // namespace saber
+
+#endif // SABER_TIMING_H
@@ -0,0 +1,303 @@
+#ifndef SABER_TRACING_H
+#define SABER_TRACING_H
+
+#include <array>
+#include <atomic>
+#include <condition_variable>
+#include <cstdint>
+#include <deque>
+#include <functional>
+#include <map>
+#include <memory>
+#include <mutex>
+#include <optional>
+#include <string>
+#include <thread>
+#include <vector>
+
+#include "rng.h"
+
+namespace saber {
+
+/**
+ * @brief Contesto di tracciamento propagato tra i nodi
+ *
+ * Viaggia nell'intestazione dei pacchetti mesh, fuori dalla firma e dalla
+ * cifratura come il percorso registrato: alterarlo rovina solo la
+ * diagnostica, non il contenuto del pacchetto.
+ */
+struct TraceContext {
+    /// ID della traccia distribuita (W3C/OpenTelemetry)
+    std::array<uint8_t, 16> traceId{};
+
+    /// ID dello span che ha inviato il pacchetto
+    std::array<uint8_t, 8> spanId{};
+
+    /**
+     * @brief Verifica che gli ID non siano nulli
+     */
+    bool isValid() const;
+
+    /**
+     * @brief ID della traccia in esadecimale (32 caratteri)
+     */
+    std::string traceIdHex() const;
+
+    /**
+     * @brief ID dello span in esadecimale (16 caratteri)
+     */
+    std::string spanIdHex() const;
+
+    bool operator==(const TraceContext& other) const;
+};
+
+/**
+ * @brief Ruolo di uno span in una richiesta tra nodi (valori OTLP)
+ */
+enum class SpanKind : uint8_t {
+    /// Operazione interna al nodo
+    Internal = 1,
+    /// Invio di un comando nella mesh
+    Producer = 4,
+    /// Gestione di un comando ricevuto dalla mesh
+    Consumer = 5
+};
+
+/**
+ * @brief Operazione tracciata su un nodo
+ */
+struct TraceSpan {
+    /// Nome dell'operazione (es. "playback.barrier")
+    std::string name;
+
+    /// Ruolo dello span
+    SpanKind kind = SpanKind::Internal;
+
+    /// Contesto dello span
+    TraceContext context;
+
+    /// Span padre, anche di un altro nodo
+    std::optional<std::array<uint8_t, 8>> parentSpanId;
+
+    /// Inizio sull'orologio della rete (µs dall'epoca Unix)
+    uint64_t startUs = 0;
+
+    /// Fine sull'orologio della rete (µs dall'epoca Unix, 0 se in corso)
+    uint64_t endUs = 0;
+
+    /// Attributi dell'operazione
+    std::map<std::string, std::string> attributes;
+
+    /// Descrizione dell'errore, se l'operazione è fallita
+    std::optional<std::string> error;
+
+    /// Lo span viene registrato (false con il tracciamento disattivato)
+    bool recording = false;
+};
+
+/// Span conclusi conservati in attesa dell'esportazione
+const size_t MAX_PENDING_SPANS = 4096;
+
+/**
+ * @brief Creazione e raccolta degli span del nodo locale
+ *
+ * Gli istanti vengono presi dall'orologio sincronizzato della rete, così
+ * gli span di nodi diversi si allineano nella stessa traccia. Con il
+ * tracciamento disattivato gli span non vengono registrati e non
+ * propagano alcun contesto.
+ */
+class Tracer {
+public:
+    /// Orologio degli span in µs dall'epoca Unix
+    using Clock = std::function<uint64_t()>;
+
+    /**
+     * @brief Crea un tracciatore
+     * @param random Sorgente degli ID (quella del sistema se nullptr)
+     * @param clock Orologio degli span (quello di sistema se vuoto)
+     */
+    explicit Tracer(std::shared_ptr<RandomSource> random = nullptr, Clock clock = nullptr);
+
+    /**
+     * @brief Attiva o disattiva la registrazione degli span
+     */
+    void setEnabled(bool enabled);
+
+    /**
+     * @brief Verifica se gli span vengono registrati
+     */
+    bool isEnabled() const;
+
+    /**
+     * @brief Apre uno span
+     * @param name Nome dell'operazione
+     * @param kind Ruolo dello span
+     * @param parent Contesto del padre (una nuova traccia se assente o non valido)
+     * @return Span aperto, da chiudere con endSpan()
+     */
+    TraceSpan startSpan(const std::string& name, SpanKind kind = SpanKind::Internal,
+                        const std::optional<TraceContext>& parent = std::nullopt);
+
+    /**
+     * @brief Chiude uno span e lo accoda per l'esportazione
+     * @param span Span da chiudere (ignorato se non registrato)
+     */
+    void endSpan(TraceSpan& span);
+
+    /**
+     * @brief Preleva gli span conclusi, dal più vecchio
+     */
+    std::vector<TraceSpan> takeFinished();
+
+    /**
+     * @brief Span scartati perché la coda di esportazione era piena
+     */
+    uint64_t getDroppedSpans() const;
+
+private:
+    /// Sorgente degli ID
+    std::shared_ptr<RandomSource> random;
+
+    /// Orologio degli span
+    Clock clock;
+
+    /// Registrazione attiva
+    std::atomic<bool> enabled{false};
+
+    /// Span conclusi in attesa dell'esportazione
+    std::deque<TraceSpan> finished;
+
+    /// Span scartati per coda piena
+    uint64_t droppedSpans = 0;
+
+    /// Mutex per proteggere l'accesso concorrente
+    mutable std::mutex tracerMutex;
+};
+
+/**
+ * @brief Rende uno span il contesto corrente del thread
+ *
+ * I pacchetti generati dal thread mentre lo scope è attivo portano il
+ * contesto nell'intestazione. Gli scope si annidano e ripristinano il
+ * contesto precedente alla distruzione.
+ */
+class SpanScope {
+public:
+    /**
+     * @brief Attiva il contesto di uno span (nessun effetto se non registrato)
+     */
+    explicit SpanScope(const TraceSpan& span);
+
+    ~SpanScope();
+
+    SpanScope(const SpanScope&) = delete;
+    SpanScope& operator=(const SpanScope&) = delete;
+
+    /**
+     * @brief Contesto corrente del thread
+     */
+    static std::optional<TraceContext> current();
+
+private:
+    /// Contesto da ripristinare
+    std::optional<TraceContext> previous;
+};
+
+/**
+ * @brief Codifica gli span nel formato OTLP/HTTP JSON
+ * @param spans Span conclusi
+ * @param nodeId Nodo che li ha registrati (service.instance.id)
+ * @return Corpo della richiesta ExportTraceServiceRequest
+ */
+std::string encodeOtlpJson(const std::vector<TraceSpan>& spans, const std::string& nodeId);
+
+/**
+ * @brief Esportazione periodica degli span verso un collettore OTLP/HTTP
+ *
+ * Un thread dedicato preleva gli span dal tracciatore e li invia con una
+ * POST JSON, così la rete e il thread di runtime non attendono mai il
+ * collettore. Gli span di un invio fallito vanno persi.
+ */
+class OtlpExporter {
+public:
+    /**
+     * @brief Crea un esportatore
+     * @param endpoint URL del collettore (es. "http://collector:4318/v1/traces", solo http)
+     * @param nodeId Nodo che registra gli span
+     * @param intervalMs Intervallo tra le esportazioni (ms)
+     * @throws std::invalid_argument se l'URL non è valido
+     */
+    OtlpExporter(const std::string& endpoint, const std::string& nodeId, uint32_t intervalMs);
+
+    ~OtlpExporter();
+
+    /**
+     * @brief Avvia il thread di esportazione
+     * @param tracer Tracciatore da cui prelevare gli span
+     */
+    void start(std::shared_ptr<Tracer> tracer);
+
+    /**
+     * @brief Esporta gli span rimasti e ferma il thread
+     */
+    void stop();
+
+    /**
+     * @brief Invia subito un gruppo di span al collettore
+     * @param spans Span da inviare
+     * @return true se il collettore ha risposto con un codice 2xx
+     */
+    bool exportSpans(const std::vector<TraceSpan>& spans);
+
+    /**
+     * @brief Span inviati con successo
+     */
+    uint64_t getExportedSpans() const;
+
+    /**
+     * @brief Invii falliti
+     */
+    uint64_t getFailedExports() const;
+
+private:
+    /// Ciclo del thread di esportazione
+    void run();
+
+    /// Host del collettore
+    std::string host;
+
+    /// Porta del collettore
+    uint16_t port = 4318;
+
+    /// Percorso della richiesta
+    std::string path;
+
+    /// Nodo che registra gli span
+    std::string nodeId;
+
+    /// Intervallo tra le esportazioni
+    uint32_t intervalMs;
+
+    /// Tracciatore da cui prelevare gli span
+    std::shared_ptr<Tracer> tracer;
+
+    /// Thread di esportazione
+    std::thread exportThread;
+
+    /// Richiesta di arresto
+    bool stopping = false;
+
+    /// Mutex e condition variable per l'arresto
+    std::mutex exportMutex;
+    std::condition_variable exportCondition;
+
+    /// Span inviati con successo
+    std::atomic<uint64_t> exportedSpans{0};
+
+    /// Invii falliti
+    std::atomic<uint64_t> failedExports{0};
+};
+
+} // namespace saber
+
+#endif // SABER_TRACING_H
@@ -0,0 +1,195 @@
+#ifndef SABER_TRANSPORT_H
+#define SABER_TRANSPORT_H
+
+#include <cstdint>
+#include <functional>
+#include <map>
+#include <mutex>
+#include <optional>
+#include <string>
+#include <vector>
+
+namespace saber {
+
+/**
+ * @brief Tipo di collegamento verso un nodo
+ */
+enum class TransportKind : uint8_t {
+    /// Bluetooth LE Audio
+    Ble = 0,
+    /// UDP/IP (Wi-Fi o Ethernet)
+    Udp = 1
+};
+
+/**
+ * @brief Converte un tipo di collegamento nella sua rappresentazione testuale
+ * @param kind Tipo di collegamento
+ * @return Nome del collegamento ("ble" o "udp")
+ */
+std::string transportKindToString(TransportKind kind);
+
+/**
+ * @brief Interpreta il nome di un tipo di collegamento
+ * @param value "ble" o "udp"
+ * @return Tipo corrispondente, o nullopt se il nome è sconosciuto
+ */
+std::optional<TransportKind> transportKindFromString(const std::string& value);
+
+/**
+ * @brief Evento di cambio del collegamento attivo verso un nodo
+ */
+struct FailoverEvent {
+    /// Nodo remoto
+    std::string peer;
+    
+    /// Collegamento abbandonato
+    TransportKind from;
+    
+    /// Collegamento adottato
+    TransportKind to;
+    
+    /// Motivo del cambio ("down", "degraded", "stale" o "better")
+    std::string reason;
+    
+    /// Timestamp dell'evento in millisecondi
+    uint64_t timestamp;
+};
+
+/**
+ * @brief Contatori della selezione dei collegamenti
+ */
+struct TransportStats {
+    /// Cambi di collegamento per nodo remoto
+    std::map<std::string, uint64_t> failovers;
+    
+    /// Pacchetti instradati per tipo di collegamento
+    std::map<TransportKind, uint64_t> packetsRouted;
+    
+    /// Collegamento attivo per nodo remoto
+    std::map<std::string, TransportKind> active;
+};
+
+/**
+ * @brief Selezione automatica del collegamento verso ogni nodo
+ *
+ * Quando un nodo è raggiungibile sia via BLE sia via UDP, i trasporti
+ * riportano periodicamente qualità e latenza di ciascun percorso. Ogni
+ * pacchetto viene instradato sul percorso attivo, che cambia subito se
+ * cade o si degrada e solo con un margine di isteresi se l'altro diventa
+ * migliore, per non alternare i percorsi a metà di uno stream.
+ */
+class TransportSelector {
+public:
+    /**
+     * @brief Tipo di callback per gli eventi di cambio collegamento
+     */
+    using FailoverHandler = std::function<void(const FailoverEvent&)>;
+    
+    /**
+     * @brief Crea un selettore
+     * @param degradedQuality Qualità sotto cui il percorso attivo è abbandonato
+     * @param switchMargin Vantaggio di punteggio richiesto per passare ad un percorso migliore
+     * @param staleMs Intervallo senza rapporti oltre cui un percorso è considerato perso
+     */
+    explicit TransportSelector(double degradedQuality = 0.5, double switchMargin = 0.15, 
+                               uint32_t staleMs = 2000);
+    
+    /**
+     * @brief Aggiorna le misure di un percorso
+     * @param peer Nodo remoto
+     * @param kind Tipo di collegamento
+     * @param linkQuality Qualità del collegamento (0-1, es. frazione di pacchetti consegnati)
+     * @param latencyMs Latenza misurata sul percorso
+     * @param nowMs Istante della misura (ms, orologio monotono)
+     */
+    void reportPath(const std::string& peer, TransportKind kind, double linkQuality, 
+                    uint32_t latencyMs, int64_t nowMs);
+    
+    /**
+     * @brief Segnala la caduta di un percorso
+     * @param peer Nodo remoto
+     * @param kind Tipo di collegamento
+     */
+    void reportPathDown(const std::string& peer, TransportKind kind);
+    
+    /**
+     * @brief Sceglie il collegamento per il prossimo pacchetto verso un nodo
+     * @param peer Nodo remoto
+     * @param nowMs Istante di invio (ms, orologio monotono)
+     * @return Collegamento da usare, o nullopt se nessun percorso è disponibile
+     */
+    std::optional<TransportKind> route(const std::string& peer, int64_t nowMs);
+    
+    /**
+     * @brief Ottiene il collegamento attivo verso un nodo senza instradare
+     * @param peer Nodo remoto
+     * @return Collegamento attivo, o nullopt se non ancora scelto
+     */
+    std::optional<TransportKind> activeTransport(const std::string& peer) const;
+    
+    /**
+     * @brief Ottiene i contatori della selezione
+     * @return Cambi di collegamento, pacchetti per collegamento e percorsi attivi
+     */
+    TransportStats getStats() const;
+    
+    /**
+     * @brief Imposta il gestore degli eventi di cambio collegamento
+     * @param handler Funzione invocata ad ogni cambio
+     */
+    void setFailoverHandler(FailoverHandler handler);
+    
+private:
+    /**
+     * @brief Misure di un percorso
+     */
+    struct Path {
+        double linkQuality = 0.0;
+        uint32_t latencyMs = 0;
+        bool up = false;
+        int64_t updatedMs = 0;
+    };
+    
+    /**
+     * @brief Stato dei percorsi verso un nodo
+     */
+    struct PeerState {
+        std::map<TransportKind, Path> paths;
+        std::optional<TransportKind> active;
+    };
+    
+    /// Soglia di degrado
+    double degradedQuality;
+    
+    /// Margine di isteresi
+    double switchMargin;
+    
+    /// Intervallo di validità delle misure
+    uint32_t staleMs;
+    
+    /// Stato per nodo remoto
+    std::map<std::string, PeerState> peers;
+    
+    /// Contatori
+    TransportStats stats;
+    
+    /// Gestore degli eventi
+    FailoverHandler failoverHandler;
+    
+    /// Mutex per lo stato
+    mutable std::mutex selectorMutex;
+    
+    /**
+     * @brief Punteggio di un percorso: qualità penalizzata dalla latenza
+     */
+    static double score(const Path& path);
+    
+    /**
+     * @brief Verifica se un percorso è utilizzabile
+     */
+    bool usable(const Path& path, int64_t nowMs) const;
+};
+
+} // namespace saber
+
+#endif // SABER_TRANSPORT_H
@@ -0,0 +1,150 @@
+#ifndef SABER_UDP_TRANSPORT_H
+#define SABER_UDP_TRANSPORT_H
+
+#include <atomic>
+#include <cstdint>
+#include <functional>
+#include <memory>
+#include <mutex>
+#include <string>
+#include <thread>
+#include <vector>
+
+namespace saber {
+
+/// Dimensione massima di un datagramma UDP su IPv4
+constexpr size_t MAX_UDP_DATAGRAM_BYTES = 65507;
+
+/**
+ * @brief Parametri del trasporto UDP multicast
+ */
+struct UdpTransportConfig {
+    /// Gruppo multicast IPv4 condiviso dai nodi della rete
+    std::string multicastAddress = "239.69.83.1";
+
+    /// Porta UDP del gruppo
+    uint16_t port = 5405;
+
+    /// Indirizzo dell'interfaccia su cui unirsi al gruppo ("0.0.0.0" = scelta del sistema)
+    std::string interfaceAddress = "0.0.0.0";
+
+    /// TTL dei datagrammi (1 = solo la rete locale)
+    uint8_t multicastTtl = 1;
+};
+
+/**
+ * @brief Contatori del trasporto UDP
+ */
+struct UdpTransportStats {
+    /// Datagrammi inviati
+    uint64_t datagramsSent = 0;
+
+    /// Datagrammi ricevuti (compresi i propri, ricevuti in loopback)
+    uint64_t datagramsReceived = 0;
+
+    /// Byte inviati
+    uint64_t bytesSent = 0;
+
+    /// Byte ricevuti
+    uint64_t bytesReceived = 0;
+
+    /// Invii falliti o pacchetti oltre MAX_UDP_DATAGRAM_BYTES
+    uint64_t sendErrors = 0;
+};
+
+/**
+ * @brief Trasporto dei pacchetti mesh su UDP multicast (Wi-Fi o Ethernet)
+ *
+ * Alternativa al BLE per gli ambienti senza hardware LE Audio e per i
+ * test end-to-end: ogni pacchetto codificato viaggia in un datagramma
+ * verso il gruppo multicast, che tutti i nodi della rete ascoltano. Il
+ * loopback multicast resta attivo così che più nodi possano girare sulla
+ * stessa macchina; i propri pacchetti vanno scartati dal ricevente.
+ */
+class UdpTransport {
+public:
+    /**
+     * @brief Tipo di callback per i datagrammi ricevuti
+     */
+    using ReceiveHandler = std::function<void(const std::vector<uint8_t>&)>;
+
+    /**
+     * @brief Crea il trasporto, senza aprire il socket
+     * @param config Gruppo, porta e interfaccia
+     * @throws std::invalid_argument se l'indirizzo non è un gruppo multicast IPv4 o l'interfaccia non è valida
+     */
+    explicit UdpTransport(const UdpTransportConfig& config);
+
+    /**
+     * @brief Distruttore, ferma il trasporto se in esecuzione
+     */
+    ~UdpTransport();
+
+    UdpTransport(const UdpTransport&) = delete;
+    UdpTransport& operator=(const UdpTransport&) = delete;
+
+    /**
+     * @brief Apre il socket, si unisce al gruppo e avvia la ricezione
+     * @param handler Funzione invocata dal thread di ricezione per ogni datagramma
+     * @return true se il trasporto è attivo
+     */
+    bool start(ReceiveHandler handler);
+
+    /**
+     * @brief Lascia il gruppo e ferma la ricezione
+     */
+    void stop();
+
+    /**
+     * @brief Verifica se il trasporto è attivo
+     */
+    bool isRunning() const;
+
+    /**
+     * @brief Invia un pacchetto codificato al gruppo
+     * @param datagram Pacchetto codificato
+     * @return true se il datagramma è stato consegnato al sistema
+     */
+    bool send(const std::vector<uint8_t>& datagram);
+
+    /**
+     * @brief Ottiene i parametri del trasporto
+     */
+    const UdpTransportConfig& getConfig() const;
+
+    /**
+     * @brief Ottiene i contatori del trasporto
+     */
+    UdpTransportStats getStats() const;
+
+private:
+    /**
+     * @brief Ciclo del thread di ricezione
+     */
+    void runReceiveLoop();
+
+    /// Parametri del trasporto
+    UdpTransportConfig config;
+
+    /// Socket UDP
+    intptr_t socketHandle;
+
+    /// Flag per il thread di ricezione
+    std::atomic<bool> running;
+
+    /// Thread di ricezione
+    std::unique_ptr<std::thread> receiveThread;
+
+    /// Gestore dei datagrammi ricevuti
+    ReceiveHandler receiveHandler;
+
+    /// Contatori
+    UdpTransportStats stats;
+
+    /// Mutex per i contatori
+    mutable std::mutex statsMutex;
+};
+
+} // namespace saber
+
+#endif // SABER_UDP_TRANSPORT_H
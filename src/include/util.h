@@ -0,0 +1,110 @@
+#ifndef SABER_UTIL_H
+#define SABER_UTIL_H
+
+// Funzioni di servizio comuni ai moduli del protocollo (uso interno)
+
+#include <chrono>
+#include <cstdint>
+#include <cstdio>
+#include <optional>
+#include <string>
+#include <vector>
+
+namespace saber {
+
+/**
+ * @brief Timestamp monotono in millisecondi
+ */
+inline int64_t steadyMillis() {
+    return std::chrono::duration_cast<std::chrono::milliseconds>(
+        std::chrono::steady_clock::now().time_since_epoch()).count();
+}
+
+/**
+ * @brief Rappresentazione esadecimale minuscola di una sequenza di byte
+ * @param data Primo byte
+ * @param size Numero di byte
+ */
+inline std::string toHex(const uint8_t* data, size_t size) {
+    static const char digits[] = "0123456789abcdef";
+    std::string hex;
+    hex.reserve(size * 2);
+    for (size_t i = 0; i < size; ++i) {
+        hex += digits[data[i] >> 4];
+        hex += digits[data[i] & 0x0F];
+    }
+    return hex;
+}
+
+/**
+ * @brief Rappresentazione esadecimale di un contenitore di byte (vettore o array)
+ */
+template <typename Bytes>
+std::string toHex(const Bytes& bytes) {
+    return toHex(bytes.data(), bytes.size());
+}
+
+/**
+ * @brief Decodifica una stringa esadecimale, maiuscola o minuscola
+ * @param hex Testo da decodificare
+ * @return Byte, o std::nullopt se la lunghezza è dispari o un carattere non è esadecimale
+ */
+inline std::optional<std::vector<uint8_t>> fromHex(const std::string& hex) {
+    if (hex.size() % 2 != 0) {
+        return std::nullopt;
+    }
+    auto nibble = [](char c) -> int {
+        if (c >= '0' && c <= '9') return c - '0';
+        if (c >= 'a' && c <= 'f') return c - 'a' + 10;
+        if (c >= 'A' && c <= 'F') return c - 'A' + 10;
+        return -1;
+    };
+    std::vector<uint8_t> bytes;
+    bytes.reserve(hex.size() / 2);
+    for (size_t i = 0; i < hex.size(); i += 2) {
+        int high = nibble(hex[i]);
+        int low = nibble(hex[i + 1]);
+        if (high < 0 || low < 0) {
+            return std::nullopt;
+        }
+        bytes.push_back(static_cast<uint8_t>((high << 4) | low));
+    }
+    return bytes;
+}
+
+/**
+ * @brief Escape di una stringa da inserire tra virgolette in un documento JSON
+ *
+ * Copre tutte le sequenze della specifica: virgolette, barra rovesciata,
+ * le forme brevi \b \f \n \r \t e \u00XX per gli altri caratteri di
+ * controllo. I byte UTF-8 passano invariati.
+ */
+inline std::string jsonEscape(const std::string& value) {
+    std::string escaped;
+    escaped.reserve(value.size());
+    for (char c : value) {
+        switch (c) {
+            case '"': escaped += "\\\""; break;
+            case '\\': escaped += "\\\\"; break;
+            case '\b': escaped += "\\b"; break;
+            case '\f': escaped += "\\f"; break;
+            case '\n': escaped += "\\n"; break;
+            case '\r': escaped += "\\r"; break;
+            case '\t': escaped += "\\t"; break;
+            default:
+                if (static_cast<unsigned char>(c) < 0x20) {
+                    char code[7];
+                    std::snprintf(code, sizeof(code), "\\u%04x", static_cast<unsigned char>(c));
+                    escaped += code;
+                } else {
+                    escaped += c;
+                }
+                break;
+        }
+    }
+    return escaped;
+}
+
+} // namespace saber
+
+#endif // SABER_UTIL_H
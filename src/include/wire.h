@@ -0,0 +1,159 @@
+#ifndef SABER_WIRE_H
+#define SABER_WIRE_H
+
+#include <cstdint>
+#include <map>
+#include <stdexcept>
+#include <string>
+#include <vector>
+
+namespace saber {
+
+/**
+ * @brief Scrittore di campi binari in formato big-endian
+ *
+ * Usato per la codifica canonica dei pacchetti: la stessa sequenza di
+ * campi produce sempre gli stessi byte, requisito per firme e MAC.
+ */
+class ByteWriter {
+public:
+    void putU8(uint8_t value) {
+        bytes.push_back(value);
+    }
+    
+    void putU16(uint16_t value) {
+        putU8(static_cast<uint8_t>(value >> 8));
+        putU8(static_cast<uint8_t>(value));
+    }
+    
+    void putU32(uint32_t value) {
+        putU16(static_cast<uint16_t>(value >> 16));
+        putU16(static_cast<uint16_t>(value));
+    }
+    
+    void putU64(uint64_t value) {
+        putU32(static_cast<uint32_t>(value >> 32));
+        putU32(static_cast<uint32_t>(value));
+    }
+    
+    /// Stringa con prefisso di lunghezza a 16 bit
+    void putString(const std::string& value) {
+        putU16(static_cast<uint16_t>(value.size()));
+        bytes.insert(bytes.end(), value.begin(), value.begin() + static_cast<uint16_t>(value.size()));
+    }
+    
+    /// Sequenza di byte con prefisso di lunghezza a 32 bit
+    void putBytes(const std::vector<uint8_t>& value) {
+        putU32(static_cast<uint32_t>(value.size()));
+        bytes.insert(bytes.end(), value.begin(), value.end());
+    }
+    
+    void putStringList(const std::vector<std::string>& values) {
+        putU16(static_cast<uint16_t>(values.size()));
+        for (const auto& value : values) {
+            putString(value);
+        }
+    }
+    
+    void putStringMap(const std::map<std::string, std::string>& values) {
+        putU16(static_cast<uint16_t>(values.size()));
+        for (const auto& pair : values) {
+            putString(pair.first);
+            putString(pair.second);
+        }
+    }
+    
+    /// Byte prodotti finora
+    const std::vector<uint8_t>& data() const {
+        return bytes;
+    }
+    
+private:
+    std::vector<uint8_t> bytes;
+};
+
+/**
+ * @brief Lettore di campi binari in formato big-endian, duale di ByteWriter
+ *
+ * Ogni lettura oltre la fine del buffer solleva std::out_of_range, così un
+ * pacchetto troncato non viene mai interpretato parzialmente.
+ */
+class ByteReader {
+public:
+    explicit ByteReader(const std::vector<uint8_t>& bytes)
+        : bytes(bytes), offset(0) {}
+    
+    uint8_t getU8() {
+        require(1);
+        return bytes[offset++];
+    }
+    
+    uint16_t getU16() {
+        uint16_t high = getU8();
+        return static_cast<uint16_t>((high << 8) | getU8());
+    }
+    
+    uint32_t getU32() {
+        uint32_t high = getU16();
+        return (high << 16) | getU16();
+    }
+    
+    uint64_t getU64() {
+        uint64_t high = getU32();
+        return (high << 32) | getU32();
+    }
+    
+    std::string getString() {
+        uint16_t length = getU16();
+        require(length);
+        std::string value(bytes.begin() + offset, bytes.begin() + offset + length);
+        offset += length;
+        return value;
+    }
+    
+    std::vector<uint8_t> getBytes() {
+        uint32_t length = getU32();
+        require(length);
+        std::vector<uint8_t> value(bytes.begin() + offset, bytes.begin() + offset + length);
+        offset += length;
+        return value;
+    }
+    
+    std::vector<std::string> getStringList() {
+        uint16_t count = getU16();
+        std::vector<std::string> values;
+        for (uint16_t i = 0; i < count; ++i) {
+            values.push_back(getString());
+        }
+        return values;
+    }
+    
+    std::map<std::string, std::string> getStringMap() {
+        uint16_t count = getU16();
+        std::map<std::string, std::string> values;
+        for (uint16_t i = 0; i < count; ++i) {
+            std::string key = getString();
+            values[key] = getString();
+        }
+        return values;
+    }
+    
+    /// Byte ancora da leggere
+    size_t remaining() const {
+        return bytes.size() - offset;
+    }
+    
+private:
+    void require(size_t count) const {
+        if (count > bytes.size() - offset) {
+            throw std::out_of_range("Buffer troncato");
+        }
+    }
+    
+    const std::vector<uint8_t>& bytes;
+    size_t offset;
+};
+
+} // namespace saber
+
+#endif // SABER_WIRE_H
@@ -0,0 +1,50 @@
+#include "a2dp_bridge.h"
+
+#include <algorithm>
+
+namespace saber {
+
+// Implementazione di A2dpBridge
+A2dpBridge::A2dpBridge(const std::string& device, uint32_t latencyMs, uint32_t targetBufferMs)
+    : targetBufferMs(targetBufferMs) {
+    stats.device = device;
+    stats.latencyMs = latencyMs;
+}
+
+uint64_t A2dpBridge::emitTimeUs(uint64_t playoutTimeUs) const {
+    uint64_t latencyUs = static_cast<uint64_t>(stats.latencyMs) * 1000;
+    return playoutTimeUs > latencyUs ? playoutTimeUs - latencyUs : 0;
+}
+
+uint64_t A2dpBridge::forward(uint64_t playoutTimeUs, uint64_t nowUs) {
+    uint64_t emitUs = emitTimeUs(playoutTimeUs);
+    int64_t leadMs = (static_cast<int64_t>(emitUs) - static_cast<int64_t>(nowUs)) / 1000;
+
+    std::lock_guard<std::mutex> lock(bridgeMutex);
+    stats.framesForwarded++;
+    stats.lastLeadMs = leadMs;
+    if (leadMs < 0) {
+        stats.lateFrames++;
+    }
+    return emitUs;
+}
+
+uint32_t A2dpBridge::reportedLatencyMs(uint32_t meshLatencyMs) const {
+    return meshLatencyMs + stats.latencyMs;
+}
+
+uint8_t A2dpBridge::bufferLevel() const {
+    std::lock_guard<std::mutex> lock(bridgeMutex);
+    if (stats.framesForwarded == 0 || stats.lastLeadMs <= 0 || targetBufferMs == 0) {
+        return 0;
+    }
+    int64_t percent = stats.lastLeadMs * 100 / targetBufferMs;
+    return static_cast<uint8_t>(std::min<int64_t>(percent, 100));
+}
+
+A2dpBridgeStats A2dpBridge::getStats() const {
+    std::lock_guard<std::mutex> lock(bridgeMutex);
+    return stats;
+}
+
+} // namespace saber
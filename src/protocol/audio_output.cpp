@@ -0,0 +1,259 @@
+#include "audio_output.h"
+
+#include <algorithm>
+#include <chrono>
+#include <stdexcept>
+#include <vector>
+
+namespace saber {
+
+namespace {
+
+/// Durata di un frame (µs)
+const uint64_t FRAME_US = 10000;
+
+/// Ritardo oltre l'istante di riproduzione dopo cui un frame viene scartato (µs)
+const uint64_t LATE_TOLERANCE_US = FRAME_US;
+
+/// Attesa massima del thread di riproduzione, così segue i salti dell'orologio della rete (µs)
+const uint64_t MAX_WAIT_US = 5000;
+
+/// Vuoti nella linea temporale più brevi di così non diventano silenzio (µs)
+const uint64_t MIN_GAP_US = FRAME_US / 2;
+
+/// Silenzio massimo inserito in un file per un solo vuoto, contro i salti dell'orologio (µs)
+const uint64_t MAX_GAP_US = 10000000;
+
+/// Lunghezza dell'intestazione WAV (byte)
+const size_t WAV_HEADER_SIZE = 44;
+
+void appendU32(std::vector<uint8_t>& out, uint32_t value) {
+    for (int i = 0; i < 4; ++i) {
+        out.push_back(static_cast<uint8_t>(value >> (8 * i)));
+    }
+}
+
+void appendU16(std::vector<uint8_t>& out, uint16_t value) {
+    out.push_back(static_cast<uint8_t>(value));
+    out.push_back(static_cast<uint8_t>(value >> 8));
+}
+
+} // namespace
+
+uint64_t AudioOutput::getLatencyUs() const {
+    return 0;
+}
+
+void AudioOutput::flush() {
+}
+
+// Implementazione di NullAudioOutput
+void NullAudioOutput::write(const AudioFrame& frame) {
+    {
+        std::lock_guard<std::mutex> lock(outputMutex);
+        lastPlayoutTimeUs = frame.playoutTimeUs;
+    }
+    framesWritten++;
+}
+
+uint64_t NullAudioOutput::getFramesWritten() const {
+    return framesWritten;
+}
+
+std::optional<uint64_t> NullAudioOutput::getLastPlayoutTimeUs() const {
+    std::lock_guard<std::mutex> lock(outputMutex);
+    return lastPlayoutTimeUs;
+}
+
+// Implementazione di WavFileOutput
+WavFileOutput::WavFileOutput(const std::string& path)
+    : file(path, std::ios::binary | std::ios::trunc) {
+    if (!file) {
+        throw std::runtime_error("impossibile creare " + path);
+    }
+    std::lock_guard<std::mutex> lock(outputMutex);
+    writeHeaderLocked();
+}
+
+WavFileOutput::~WavFileOutput() {
+    flush();
+}
+
+void WavFileOutput::write(const AudioFrame& frame) {
+    std::lock_guard<std::mutex> lock(outputMutex);
+    if (sampleRate == 0) {
+        sampleRate = frame.sampleRate;
+        channels = frame.channels;
+    } else if (frame.sampleRate != sampleRate || frame.channels != channels) {
+        framesRejected++;
+        return;
+    }
+    if (channels == 0) {
+        return;
+    }
+
+    // Un frame successivo alla fine del precedente lascia un vuoto di silenzio
+    if (nextPlayoutTimeUs && frame.playoutTimeUs > *nextPlayoutTimeUs + MIN_GAP_US) {
+        uint64_t gapUs = std::min(frame.playoutTimeUs - *nextPlayoutTimeUs, MAX_GAP_US);
+        uint64_t silence = gapUs * sampleRate / 1000000;
+        std::vector<char> zeros(static_cast<size_t>(silence) * channels * 2, 0);
+        file.write(zeros.data(), static_cast<std::streamsize>(zeros.size()));
+        samplesWritten += silence;
+    }
+
+    std::vector<uint8_t> bytes;
+    bytes.reserve(frame.samples.size() * 2);
+    for (int16_t sample : frame.samples) {
+        appendU16(bytes, static_cast<uint16_t>(sample));
+    }
+    file.write(reinterpret_cast<const char*>(bytes.data()), static_cast<std::streamsize>(bytes.size()));
+    uint64_t frameSamples = frame.samples.size() / channels;
+    samplesWritten += frameSamples;
+    nextPlayoutTimeUs = frame.playoutTimeUs + frameSamples * 1000000 / sampleRate;
+}
+
+void WavFileOutput::flush() {
+    std::lock_guard<std::mutex> lock(outputMutex);
+    writeHeaderLocked();
+    file.flush();
+}
+
+uint64_t WavFileOutput::getSamplesWritten() const {
+    std::lock_guard<std::mutex> lock(outputMutex);
+    return samplesWritten;
+}
+
+uint64_t WavFileOutput::getFramesRejected() const {
+    std::lock_guard<std::mutex> lock(outputMutex);
+    return framesRejected;
+}
+
+void WavFileOutput::writeHeaderLocked() {
+    uint32_t dataSize = static_cast<uint32_t>(std::min<uint64_t>(samplesWritten * channels * 2,
+                                                                 UINT32_MAX - WAV_HEADER_SIZE));
+    std::vector<uint8_t> header;
+    header.reserve(WAV_HEADER_SIZE);
+    header.insert(header.end(), {'R', 'I', 'F', 'F'});
+    appendU32(header, static_cast<uint32_t>(WAV_HEADER_SIZE - 8) + dataSize);
+    header.insert(header.end(), {'W', 'A', 'V', 'E', 'f', 'm', 't', ' '});
+    appendU32(header, 16);
+    appendU16(header, 1);
+    appendU16(header, channels);
+    appendU32(header, sampleRate);
+    appendU32(header, sampleRate * channels * 2);
+    appendU16(header, static_cast<uint16_t>(channels * 2));
+    appendU16(header, 16);
+    header.insert(header.end(), {'d', 'a', 't', 'a'});
+    appendU32(header, dataSize);
+
+    auto end = file.tellp();
+    file.seekp(0);
+    file.write(reinterpret_cast<const char*>(header.data()), static_cast<std::streamsize>(header.size()));
+    if (end > static_cast<std::streamoff>(WAV_HEADER_SIZE)) {
+        file.seekp(end);
+    }
+}
+
+// Implementazione di AudioRenderer
+AudioRenderer::AudioRenderer(std::shared_ptr<AudioOutput> output, Clock clock, size_t capacity)
+    : output(std::move(output)), clock(std::move(clock)), capacity(capacity) {
+}
+
+AudioRenderer::~AudioRenderer() {
+    stop();
+}
+
+void AudioRenderer::start() {
+    std::lock_guard<std::mutex> lock(queueMutex);
+    if (running) {
+        return;
+    }
+    running = true;
+    worker = std::thread(&AudioRenderer::run, this);
+}
+
+void AudioRenderer::stop() {
+    {
+        std::lock_guard<std::mutex> lock(queueMutex);
+        if (!running) {
+            return;
+        }
+        running = false;
+    }
+    queueChanged.notify_all();
+    if (worker.joinable()) {
+        worker.join();
+    }
+    output->flush();
+}
+
+bool AudioRenderer::submit(const AudioFrame& frame) {
+    uint64_t nowUs = clock();
+    {
+        std::lock_guard<std::mutex> lock(queueMutex);
+        if (isLateLocked(frame, nowUs)) {
+            stats.framesLate++;
+            return false;
+        }
+        if (queue.size() >= capacity) {
+            stats.framesDropped++;
+            return false;
+        }
+        queue.emplace(frame.playoutTimeUs, frame);
+    }
+    queueChanged.notify_one();
+    return true;
+}
+
+std::shared_ptr<AudioOutput> AudioRenderer::getOutput() const {
+    return output;
+}
+
+AudioOutputStats AudioRenderer::getStats() const {
+    std::lock_guard<std::mutex> lock(queueMutex);
+    AudioOutputStats current = stats;
+    current.queued = queue.size();
+    return current;
+}
+
+bool AudioRenderer::isLateLocked(const AudioFrame& frame, uint64_t nowUs) const {
+    uint64_t latencyUs = output->getLatencyUs();
+    uint64_t writeAtUs = frame.playoutTimeUs > latencyUs ? frame.playoutTimeUs - latencyUs : 0;
+    return nowUs > writeAtUs + LATE_TOLERANCE_US;
+}
+
+void AudioRenderer::run() {
+    std::unique_lock<std::mutex> lock(queueMutex);
+    while (running) {
+        if (queue.empty()) {
+            queueChanged.wait(lock);
+            continue;
+        }
+
+        auto next = queue.begin();
+        uint64_t nowUs = clock();
+        uint64_t latencyUs = output->getLatencyUs();
+        uint64_t writeAtUs = next->first > latencyUs ? next->first - latencyUs : 0;
+        if (nowUs < writeAtUs) {
+            // Un frame più vicino consegnato nel frattempo risveglia il thread
+            uint64_t waitUs = std::min(writeAtUs - nowUs, MAX_WAIT_US);
+            queueChanged.wait_for(lock, std::chrono::microseconds(waitUs));
+            continue;
+        }
+
+        AudioFrame frame = std::move(next->second);
+        queue.erase(next);
+        if (isLateLocked(frame, nowUs)) {
+            stats.framesLate++;
+            continue;
+        }
+
+        // L'uscita può bloccare: la coda resta libera per il sink
+        lock.unlock();
+        output->write(frame);
+        lock.lock();
+        stats.framesPlayed++;
+    }
+}
+
+} // namespace saber
@@ -0,0 +1,187 @@
+#include "audio_packet.h"
+#include "crypto.h"
+#include "wire.h"
+
+#include <stdexcept>
+
+namespace saber {
+
+namespace {
+
+/// Durata di un frame (µs)
+const uint64_t FRAME_US = spec::LC3_FRAME_DURATION_US;
+
+/// Frame consecutivi persi oltre i quali il vuoto non viene ricostruito
+const uint32_t MAX_CONCEALED_PACKETS = 5;
+
+/// Salto di sequenza oltre cui lo stream riparte, come dopo un riavvio del Master (10 s di frame)
+const int64_t MAX_SEQUENCE_JUMP = 1000;
+
+/**
+ * @brief Serializza l'intestazione in chiaro, usata anche come AAD
+ */
+std::vector<uint8_t> encodeHeader(const AudioPacket& packet) {
+    ByteWriter writer;
+    writer.putU8(AUDIO_PACKET_VERSION);
+    writer.putU16(packet.streamId);
+    writer.putU32(packet.sequence);
+    writer.putU64(packet.presentationTimeUs);
+    return writer.data();
+}
+
+/**
+ * @brief Distanza di un numero di sequenza dal prossimo atteso, con il giro del contatore
+ */
+int64_t sequenceDelta(uint32_t sequence, uint32_t next) {
+    return static_cast<int32_t>(sequence - next);
+}
+
+} // namespace
+
+// Implementazione di AudioPacket
+std::vector<uint8_t> AudioPacket::seal(MeshCrypto& crypto) const {
+    std::vector<uint8_t> data = encodeHeader(*this);
+    std::vector<uint8_t> sealed = crypto.encrypt(payload, data);
+    data.insert(data.end(), sealed.begin(), sealed.end());
+    return data;
+}
+
+AudioPacket AudioPacket::open(const std::vector<uint8_t>& data, MeshCrypto& crypto, const std::string& senderId) {
+    AudioPacket packet = peekHeader(data);
+    std::vector<uint8_t> header(data.begin(), data.begin() + AUDIO_PACKET_HEADER_BYTES);
+    std::vector<uint8_t> sealed(data.begin() + AUDIO_PACKET_HEADER_BYTES, data.end());
+    packet.payload = senderId.empty() ? crypto.decrypt(sealed, header) : crypto.decryptFrom(senderId, sealed, header);
+    return packet;
+}
+
+AudioPacket AudioPacket::peekHeader(const std::vector<uint8_t>& data) {
+    ByteReader reader(data);
+    uint8_t version = reader.getU8();
+    if (version != AUDIO_PACKET_VERSION) {
+        throw std::invalid_argument("Versione del pacchetto audio non supportata: " + std::to_string(version));
+    }
+    AudioPacket packet;
+    packet.streamId = reader.getU16();
+    packet.sequence = reader.getU32();
+    packet.presentationTimeUs = reader.getU64();
+    return packet;
+}
+
+// Implementazione di AudioPacketReorderer
+AudioPacketReorderer::AudioPacketReorderer(PacketHandler onPacket, LossHandler onLoss, size_t depth)
+    : onPacket(std::move(onPacket)), onLoss(std::move(onLoss)), depth(depth) {
+    if (depth == 0) {
+        throw std::invalid_argument("La profondità del riordino deve essere positiva");
+    }
+}
+
+bool AudioPacketReorderer::push(const AudioPacket& packet) {
+    std::vector<Release> releases;
+    {
+        std::lock_guard<std::mutex> lock(reorderMutex);
+        auto found = streams.find(packet.streamId);
+        if (found == streams.end()) {
+            found = streams.emplace(packet.streamId, StreamState{packet.sequence, {}}).first;
+        }
+        StreamState& state = found->second;
+
+        int64_t delta = sequenceDelta(packet.sequence, state.next);
+        if (delta > MAX_SEQUENCE_JUMP || delta < -MAX_SEQUENCE_JUMP) {
+            // Lo stream è ripartito: i frame trattenuti escono e la sequenza segue il nuovo frame
+            releaseLocked(packet.streamId, state, true, releases);
+            state.next = packet.sequence;
+            delta = 0;
+        }
+        if (delta < 0) {
+            stats.late++;
+            return false;
+        }
+        if (state.pending.count(packet.sequence)) {
+            stats.duplicates++;
+            return false;
+        }
+        for (const auto& pending : state.pending) {
+            if (sequenceDelta(pending.first, state.next) > delta) {
+                stats.reordered++;
+                break;
+            }
+        }
+        state.pending.emplace(packet.sequence, packet);
+        releaseLocked(packet.streamId, state, false, releases);
+    }
+    dispatch(releases);
+    return true;
+}
+
+void AudioPacketReorderer::flush() {
+    std::vector<Release> releases;
+    {
+        std::lock_guard<std::mutex> lock(reorderMutex);
+        for (auto& stream : streams) {
+            releaseLocked(stream.first, stream.second, true, releases);
+        }
+    }
+    dispatch(releases);
+}
+
+void AudioPacketReorderer::reset(StreamId streamId) {
+    std::lock_guard<std::mutex> lock(reorderMutex);
+    streams.erase(streamId);
+}
+
+AudioReorderStats AudioPacketReorderer::getStats() const {
+    std::lock_guard<std::mutex> lock(reorderMutex);
+    return stats;
+}
+
+void AudioPacketReorderer::releaseLocked(StreamId streamId, StreamState& state, bool drain,
+                                         std::vector<Release>& out) {
+    while (!state.pending.empty()) {
+        auto ready = state.pending.find(state.next);
+        if (ready != state.pending.end()) {
+            out.push_back({ready->second, streamId, ready->first, ready->second.presentationTimeUs});
+            state.pending.erase(ready);
+            state.next++;
+            stats.delivered++;
+            continue;
+        }
+        if (!drain && state.pending.size() <= depth) {
+            break;
+        }
+
+        // Il frame trattenuto più vicino chiude il vuoto: quelli prima sono persi
+        auto first = state.pending.begin();
+        for (auto it = state.pending.begin(); it != state.pending.end(); ++it) {
+            if (sequenceDelta(it->first, state.next) < sequenceDelta(first->first, state.next)) {
+                first = it;
+            }
+        }
+        uint32_t gap = first->first - state.next;
+        if (gap <= MAX_CONCEALED_PACKETS && onLoss) {
+            for (uint32_t i = 0; i < gap; ++i) {
+                uint64_t offsetUs = static_cast<uint64_t>(gap - i) * FRAME_US;
+                uint64_t presentationTimeUs = first->second.presentationTimeUs > offsetUs
+                                                  ? first->second.presentationTimeUs - offsetUs
+                                                  : 0;
+                out.push_back({std::nullopt, streamId, state.next + i, presentationTimeUs});
+            }
+            stats.concealed += gap;
+        }
+        stats.lost += gap;
+        state.next = first->first;
+    }
+}
+
+void AudioPacketReorderer::dispatch(const std::vector<Release>& releases) const {
+    for (const auto& release : releases) {
+        if (release.packet) {
+            if (onPacket) {
+                onPacket(*release.packet);
+            }
+        } else if (onLoss) {
+            onLoss(release.streamId, release.sequence, release.presentationTimeUs);
+        }
+    }
+}
+
+} // namespace saber
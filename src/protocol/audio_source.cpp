@@ -0,0 +1,322 @@
+#include "audio_source.h"
+
+#include <algorithm>
+#include <chrono>
+#include <stdexcept>
+
+namespace saber {
+
+namespace {
+
+/// Durata di un frame (µs)
+const uint64_t FRAME_US = 10000;
+
+/// Ritardo oltre cui la lettura riparte dall'istante corrente invece di recuperare i frame persi (µs)
+const uint64_t MAX_CATCH_UP_US = 100000;
+
+/**
+ * @brief Verifica il formato di una sorgente
+ */
+void checkFormat(uint32_t sampleRate, uint8_t channels) {
+    if (sampleRate == 0 || sampleRate % 100 != 0) {
+        throw std::invalid_argument("frequenza di campionamento non valida: " + std::to_string(sampleRate));
+    }
+    if (channels == 0) {
+        throw std::invalid_argument("numero di canali non valido");
+    }
+}
+
+uint32_t readU32(const uint8_t* data) {
+    return static_cast<uint32_t>(data[0]) | static_cast<uint32_t>(data[1]) << 8 |
+           static_cast<uint32_t>(data[2]) << 16 | static_cast<uint32_t>(data[3]) << 24;
+}
+
+uint16_t readU16(const uint8_t* data) {
+    return static_cast<uint16_t>(data[0] | data[1] << 8);
+}
+
+} // namespace
+
+bool AudioSource::isFinished() const {
+    return false;
+}
+
+// Implementazione di PcmFileSource
+PcmFileSource::PcmFileSource(const std::string& path, uint32_t sampleRate, uint8_t channels, bool loop)
+    : PcmFileSource(path, sampleRate, channels, loop, 0, std::nullopt) {
+}
+
+PcmFileSource::PcmFileSource(const std::string& path, uint32_t sampleRate, uint8_t channels, bool loop,
+                             uint64_t dataOffset, std::optional<uint64_t> dataSize)
+    : file(path, std::ios::binary), sampleRate(sampleRate), channels(channels), loop(loop), dataOffset(dataOffset) {
+    checkFormat(sampleRate, channels);
+    if (!file) {
+        throw std::runtime_error("impossibile aprire " + path);
+    }
+    file.seekg(0, std::ios::end);
+    uint64_t fileSize = static_cast<uint64_t>(file.tellg());
+    uint64_t available = fileSize > dataOffset ? fileSize - dataOffset : 0;
+    // Un WAV troncato dichiara più campioni di quelli presenti
+    this->dataSize = std::min(dataSize.value_or(available), available);
+    this->dataSize -= this->dataSize % (2 * static_cast<uint64_t>(channels));
+    file.seekg(static_cast<std::streamoff>(dataOffset));
+}
+
+std::shared_ptr<PcmFileSource> PcmFileSource::fromWav(const std::string& path, bool loop) {
+    std::ifstream wav(path, std::ios::binary);
+    if (!wav) {
+        throw std::runtime_error("impossibile aprire " + path);
+    }
+    uint8_t riff[12];
+    if (!wav.read(reinterpret_cast<char*>(riff), sizeof(riff)) || std::string(riff, riff + 4) != "RIFF"
+        || std::string(riff + 8, riff + 12) != "WAVE") {
+        throw std::runtime_error(path + " non è un file WAV");
+    }
+
+    // Scorre i blocchi fino ai campioni, dopo aver letto il formato
+    std::optional<uint32_t> sampleRate;
+    uint16_t channels = 0;
+    uint64_t offset = sizeof(riff);
+    uint8_t header[8];
+    while (wav.read(reinterpret_cast<char*>(header), sizeof(header))) {
+        std::string id(header, header + 4);
+        uint32_t size = readU32(header + 4);
+        offset += sizeof(header);
+        if (id == "fmt ") {
+            uint8_t format[16];
+            if (size < sizeof(format) || !wav.read(reinterpret_cast<char*>(format), sizeof(format))) {
+                throw std::runtime_error(path + ": blocco fmt non valido");
+            }
+            if (readU16(format) != 1 || readU16(format + 14) != 16) {
+                throw std::runtime_error(path + ": sono supportati solo i WAV PCM a 16 bit");
+            }
+            channels = readU16(format + 2);
+            sampleRate = readU32(format + 4);
+            if (channels == 0 || channels > UINT8_MAX) {
+                throw std::runtime_error(path + ": numero di canali non supportato");
+            }
+        } else if (id == "data") {
+            if (!sampleRate) {
+                throw std::runtime_error(path + ": campioni prima del blocco fmt");
+            }
+            try {
+                return std::shared_ptr<PcmFileSource>(new PcmFileSource(
+                    path, *sampleRate, static_cast<uint8_t>(channels), loop, offset, size));
+            } catch (const std::invalid_argument& e) {
+                throw std::runtime_error(path + ": " + e.what());
+            }
+        }
+        // I blocchi hanno lunghezza pari
+        offset += size + (size & 1);
+        wav.seekg(static_cast<std::streamoff>(offset));
+    }
+    throw std::runtime_error(path + ": nessun campione nel file");
+}
+
+uint32_t PcmFileSource::getSampleRate() const {
+    return sampleRate;
+}
+
+uint8_t PcmFileSource::getChannels() const {
+    return channels;
+}
+
+std::optional<AudioFrame> PcmFileSource::nextFrame() {
+    if (isFinished()) {
+        return std::nullopt;
+    }
+    AudioFrame frame;
+    frame.sampleRate = sampleRate;
+    frame.channels = channels;
+    frame.samples.assign(frame.samplesPerChannel() * channels, 0);
+
+    std::vector<uint8_t> bytes(frame.samples.size() * 2);
+    size_t filled = 0;
+    while (filled < bytes.size() && position < dataSize) {
+        size_t chunk = static_cast<size_t>(std::min<uint64_t>(bytes.size() - filled, dataSize - position));
+        file.read(reinterpret_cast<char*>(bytes.data() + filled), static_cast<std::streamsize>(chunk));
+        size_t got = static_cast<size_t>(file.gcount());
+        filled += got;
+        position += got;
+        if (got < chunk) {
+            // File accorciato durante la lettura: i campioni finiscono qui
+            dataSize = position;
+        }
+        if (loop && position >= dataSize && dataSize > 0) {
+            file.clear();
+            file.seekg(static_cast<std::streamoff>(dataOffset));
+            position = 0;
+        }
+    }
+
+    for (size_t i = 0; i < filled / 2; ++i) {
+        frame.samples[i] = static_cast<int16_t>(readU16(bytes.data() + 2 * i));
+    }
+    return frame;
+}
+
+bool PcmFileSource::isFinished() const {
+    return position >= dataSize && !(loop && dataSize > 0);
+}
+
+// Implementazione di RingBufferSource
+RingBufferSource::RingBufferSource(uint32_t sampleRate, uint8_t channels, uint32_t capacityMs)
+    : sampleRate(sampleRate), channels(channels) {
+    checkFormat(sampleRate, channels);
+    if (capacityMs < 10) {
+        throw std::invalid_argument("il buffer deve contenere almeno un frame");
+    }
+    buffer.resize(static_cast<size_t>(static_cast<uint64_t>(sampleRate) * capacityMs / 1000) * channels);
+}
+
+size_t RingBufferSource::push(const std::vector<int16_t>& samples) {
+    if (samples.size() % channels != 0) {
+        throw std::invalid_argument("i campioni devono coprire tutti i canali");
+    }
+    std::lock_guard<std::mutex> lock(bufferMutex);
+    if (closed) {
+        throw std::logic_error("sorgente chiusa");
+    }
+    size_t accepted = std::min(samples.size(), buffer.size() - count);
+    for (size_t i = 0; i < accepted; ++i) {
+        buffer[(head + count + i) % buffer.size()] = samples[i];
+    }
+    count += accepted;
+    dropped += samples.size() - accepted;
+    return accepted;
+}
+
+size_t RingBufferSource::available() const {
+    std::lock_guard<std::mutex> lock(bufferMutex);
+    return count;
+}
+
+uint64_t RingBufferSource::getDropped() const {
+    std::lock_guard<std::mutex> lock(bufferMutex);
+    return dropped;
+}
+
+void RingBufferSource::clear() {
+    std::lock_guard<std::mutex> lock(bufferMutex);
+    head = 0;
+    count = 0;
+}
+
+void RingBufferSource::close() {
+    std::lock_guard<std::mutex> lock(bufferMutex);
+    closed = true;
+}
+
+uint32_t RingBufferSource::getSampleRate() const {
+    return sampleRate;
+}
+
+uint8_t RingBufferSource::getChannels() const {
+    return channels;
+}
+
+std::optional<AudioFrame> RingBufferSource::nextFrame() {
+    AudioFrame frame;
+    frame.sampleRate = sampleRate;
+    frame.channels = channels;
+    size_t needed = frame.samplesPerChannel() * channels;
+
+    std::lock_guard<std::mutex> lock(bufferMutex);
+    // Dopo la chiusura l'ultimo frame incompleto viene completato con il silenzio
+    if (count == 0 || (count < needed && !closed)) {
+        return std::nullopt;
+    }
+    size_t taken = std::min(count, needed);
+    frame.samples.assign(needed, 0);
+    for (size_t i = 0; i < taken; ++i) {
+        frame.samples[i] = buffer[(head + i) % buffer.size()];
+    }
+    head = (head + taken) % buffer.size();
+    count -= taken;
+    return frame;
+}
+
+bool RingBufferSource::isFinished() const {
+    std::lock_guard<std::mutex> lock(bufferMutex);
+    return closed && count == 0;
+}
+
+// Implementazione di AudioSourcePump
+AudioSourcePump::AudioSourcePump(std::shared_ptr<AudioSource> source, Clock clock, uint64_t leadUs,
+                                 FrameHandler handler)
+    : source(std::move(source)), clock(std::move(clock)), leadUs(leadUs), handler(std::move(handler)) {
+}
+
+AudioSourcePump::~AudioSourcePump() {
+    stop();
+}
+
+void AudioSourcePump::start() {
+    if (running.exchange(true)) {
+        return;
+    }
+    worker = std::thread(&AudioSourcePump::run, this);
+}
+
+void AudioSourcePump::stop() {
+    running = false;
+    if (worker.joinable()) {
+        worker.join();
+    }
+}
+
+std::shared_ptr<AudioSource> AudioSourcePump::getSource() const {
+    return source;
+}
+
+void AudioSourcePump::setLeadUs(uint64_t leadUs) {
+    this->leadUs = leadUs;
+}
+
+AudioSourceStats AudioSourcePump::getStats() const {
+    std::lock_guard<std::mutex> lock(statsMutex);
+    return stats;
+}
+
+void AudioSourcePump::run() {
+    using SteadyClock = std::chrono::steady_clock;
+    auto deadline = SteadyClock::now();
+    uint64_t appliedLeadUs = leadUs;
+    uint64_t playoutTimeUs = clock() + appliedLeadUs;
+
+    while (running) {
+        uint64_t currentLeadUs = leadUs;
+        if (currentLeadUs != appliedLeadUs) {
+            playoutTimeUs = playoutTimeUs - appliedLeadUs + currentLeadUs;
+            appliedLeadUs = currentLeadUs;
+        }
+        std::optional<AudioFrame> frame = source->nextFrame();
+        if (frame) {
+            frame->playoutTimeUs = playoutTimeUs;
+            handler(*frame);
+        }
+        {
+            std::lock_guard<std::mutex> lock(statsMutex);
+            if (frame) {
+                stats.framesSent++;
+            } else if (source->isFinished()) {
+                stats.finished = true;
+                break;
+            } else {
+                stats.underruns++;
+            }
+        }
+
+        playoutTimeUs += FRAME_US;
+        deadline += std::chrono::microseconds(FRAME_US);
+        auto now = SteadyClock::now();
+        if (now - deadline > std::chrono::microseconds(MAX_CATCH_UP_US)) {
+            // Thread rimasto fermo: si riparte da adesso senza raffiche di frame
+            deadline = now;
+            playoutTimeUs = clock() + appliedLeadUs;
+        }
+        std::this_thread::sleep_until(deadline);
+    }
+}
+
+} // namespace saber
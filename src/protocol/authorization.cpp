@@ -0,0 +1,218 @@
+#include "authorization.h"
+#include "log.h"
+#include "util.h"
+
+#include <chrono>
+#include <future>
+#include <vector>
+
+namespace saber {
+
+std::string authorizationActionToString(AuthorizationAction action) {
+    switch (action) {
+        case AuthorizationAction::Join:
+            return "join";
+        case AuthorizationAction::Command:
+            return "command";
+    }
+    return "unknown";
+}
+
+std::optional<AuthorizationAction> authorizationActionFromString(const std::string& name) {
+    if (name == "join") {
+        return AuthorizationAction::Join;
+    }
+    if (name == "command") {
+        return AuthorizationAction::Command;
+    }
+    return std::nullopt;
+}
+
+// Implementazione di FunctionAuthorizationHook
+FunctionAuthorizationHook::FunctionAuthorizationHook(Function function) : function(std::move(function)) {}
+
+void FunctionAuthorizationHook::authorize(const AuthorizationRequest& request, Responder respond) {
+    function(request, std::move(respond));
+}
+
+// Implementazione di AuthorizationGate
+AuthorizationGate::AuthorizationGate(uint32_t timeoutMs, bool allowOnTimeout)
+    : timeoutMs(timeoutMs), allowOnTimeout(allowOnTimeout), link(std::make_shared<Link>()) {
+    link->gate = this;
+    worker = std::thread(&AuthorizationGate::run, this);
+}
+
+AuthorizationGate::~AuthorizationGate() {
+    // Le risposte successive non raggiungono più il gate
+    {
+        std::lock_guard<std::mutex> lock(link->mutex);
+        link->gate = nullptr;
+    }
+    {
+        std::lock_guard<std::mutex> lock(mutex);
+        stopping = true;
+    }
+    wakeup.notify_all();
+    if (worker.joinable()) {
+        worker.join();
+    }
+    cancelPending("gate chiuso");
+}
+
+void AuthorizationGate::setHook(std::shared_ptr<AuthorizationHook> hook) {
+    std::lock_guard<std::mutex> lock(mutex);
+    this->hook = std::move(hook);
+}
+
+bool AuthorizationGate::hasHook() const {
+    std::lock_guard<std::mutex> lock(mutex);
+    return hook != nullptr;
+}
+
+void AuthorizationGate::setTimeout(uint32_t timeoutMs, bool allowOnTimeout) {
+    std::lock_guard<std::mutex> lock(mutex);
+    this->timeoutMs = timeoutMs;
+    this->allowOnTimeout = allowOnTimeout;
+}
+
+bool AuthorizationGate::submit(AuthorizationRequest request, Completion completion) {
+    {
+        std::lock_guard<std::mutex> lock(mutex);
+        if (!hook || stopping) {
+            return false;
+        }
+        uint64_t id = nextId++;
+        request.id = id;
+        pending[id] = {std::move(request), std::move(completion), steadyMillis() + timeoutMs};
+        queue.push_back(id);
+        stats.requests++;
+        stats.pending = pending.size();
+    }
+    wakeup.notify_one();
+    return true;
+}
+
+std::optional<AuthorizationDecision> AuthorizationGate::decide(AuthorizationRequest request) {
+    // Ogni richiesta accettata riceve una decisione, al più allo scadere dell'attesa
+    auto promise = std::make_shared<std::promise<AuthorizationDecision>>();
+    auto future = promise->get_future();
+    if (!submit(std::move(request), [promise](const AuthorizationDecision& decision) {
+            promise->set_value(decision);
+        })) {
+        return std::nullopt;
+    }
+    return future.get();
+}
+
+void AuthorizationGate::cancelPending(const std::string& reason) {
+    std::vector<uint64_t> ids;
+    {
+        std::lock_guard<std::mutex> lock(mutex);
+        for (const auto& entry : pending) {
+            ids.push_back(entry.first);
+        }
+        queue.clear();
+    }
+    for (uint64_t id : ids) {
+        resolve(id, {false, reason}, false);
+    }
+}
+
+AuthorizationStats AuthorizationGate::getStats() const {
+    std::lock_guard<std::mutex> lock(mutex);
+    return stats;
+}
+
+void AuthorizationGate::run() {
+    std::unique_lock<std::mutex> lock(mutex);
+    while (!stopping) {
+        // Le richieste scadute ricevono la decisione di default
+        int64_t now = steadyMillis();
+        std::vector<uint64_t> expired;
+        std::optional<int64_t> nextDeadline;
+        for (const auto& entry : pending) {
+            if (entry.second.deadlineMs <= now) {
+                expired.push_back(entry.first);
+            } else if (!nextDeadline || entry.second.deadlineMs < *nextDeadline) {
+                nextDeadline = entry.second.deadlineMs;
+            }
+        }
+        if (!expired.empty()) {
+            AuthorizationDecision decision{allowOnTimeout, "nessuna decisione entro il tempo massimo"};
+            lock.unlock();
+            for (uint64_t id : expired) {
+                resolve(id, decision, true);
+            }
+            lock.lock();
+            continue;
+        }
+
+        if (!queue.empty()) {
+            uint64_t id = queue.front();
+            queue.pop_front();
+            auto it = pending.find(id);
+            if (it == pending.end()) {
+                continue;
+            }
+            AuthorizationRequest request = it->second.request;
+            auto current = hook;
+            lock.unlock();
+
+            // La politica è chiamata senza mutex: può rispondere subito o più tardi
+            if (!current) {
+                resolve(id, {true, "nessuna politica"}, false);
+            } else {
+                std::weak_ptr<Link> weak = link;
+                auto respond = [weak, id](const AuthorizationDecision& decision) {
+                    if (auto shared = weak.lock()) {
+                        std::lock_guard<std::mutex> guard(shared->mutex);
+                        if (shared->gate) {
+                            shared->gate->resolve(id, decision, false);
+                        }
+                    }
+                };
+                try {
+                    current->authorize(request, respond);
+                } catch (const std::exception& e) {
+                    SABER_LOG(Warn, "security", "Errore della politica di autorizzazione: " << e.what());
+                    resolve(id, {false, std::string("errore della politica: ") + e.what()}, false);
+                }
+            }
+            lock.lock();
+            continue;
+        }
+
+        if (nextDeadline) {
+            wakeup.wait_for(lock, std::chrono::milliseconds(*nextDeadline - now));
+        } else {
+            wakeup.wait(lock);
+        }
+    }
+}
+
+void AuthorizationGate::resolve(uint64_t id, const AuthorizationDecision& decision, bool timedOut) {
+    Completion completion;
+    {
+        std::lock_guard<std::mutex> lock(mutex);
+        auto it = pending.find(id);
+        if (it == pending.end()) {
+            return;
+        }
+        completion = std::move(it->second.completion);
+        pending.erase(it);
+        if (decision.allowed) {
+            stats.allowed++;
+        } else {
+            stats.denied++;
+        }
+        if (timedOut) {
+            stats.timedOut++;
+        }
+        stats.pending = pending.size();
+    }
+    if (completion) {
+        completion(decision);
+    }
+}
+
+} // namespace saber
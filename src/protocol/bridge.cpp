@@ -0,0 +1,159 @@
+#include "bridge.h"
+#include "log.h"
+#include "saber_protocol.h"
+
+#include <algorithm>
+#include <optional>
+#include <stdexcept>
+
+namespace saber {
+
+NetworkBridge::NetworkBridge(std::shared_ptr<SaberProtocol> first, std::shared_ptr<SaberProtocol> second,
+                             const BridgeConfig& config)
+    : first(std::move(first)), second(std::move(second)), config(config) {
+    if (!this->first || !this->second) {
+        throw std::invalid_argument("Il ponte richiede il protocollo di entrambe le reti");
+    }
+    if (this->first == this->second) {
+        throw std::invalid_argument("Il ponte deve collegare due reti diverse");
+    }
+    if (config.bridgeId.empty()) {
+        throw std::invalid_argument("ID del ponte mancante");
+    }
+}
+
+NetworkBridge::~NetworkBridge() {
+    stop();
+}
+
+void NetworkBridge::start() {
+    {
+        std::lock_guard<std::mutex> lock(bridgeMutex);
+        if (running) {
+            return;
+        }
+        running = true;
+        worker = std::thread(&NetworkBridge::run, this);
+    }
+    first->setBridgeHandler([this](const MeshPacket& packet) { enqueue(true, packet); });
+    second->setBridgeHandler([this](const MeshPacket& packet) { enqueue(false, packet); });
+    SABER_LOG(Info, "bridge", "Ponte " << config.bridgeId << " avviato");
+}
+
+void NetworkBridge::stop() {
+    {
+        std::lock_guard<std::mutex> lock(bridgeMutex);
+        if (!running) {
+            return;
+        }
+        running = false;
+        queue.clear();
+    }
+    first->setBridgeHandler(nullptr);
+    second->setBridgeHandler(nullptr);
+    queueChanged.notify_all();
+    if (worker.joinable()) {
+        worker.join();
+    }
+    SABER_LOG(Info, "bridge", "Ponte " << config.bridgeId << " fermato");
+}
+
+bool NetworkBridge::isRunning() const {
+    std::lock_guard<std::mutex> lock(bridgeMutex);
+    return running;
+}
+
+const BridgeConfig& NetworkBridge::getConfig() const {
+    return config;
+}
+
+BridgeStats NetworkBridge::getStats() const {
+    std::lock_guard<std::mutex> lock(bridgeMutex);
+    return stats;
+}
+
+void NetworkBridge::enqueue(bool forward, const MeshPacket& packet) {
+    StreamId streamId = 0;
+    if (packet.getType() == MeshPacketType::Audio) {
+        streamId = packet.getAudioData().streamId;
+    } else if (packet.getType() == MeshPacketType::Metadata) {
+        streamId = packet.getMetadata().streamId;
+    } else {
+        return;
+    }
+    const auto& routes = forward ? config.forward : config.reverse;
+    if (routes.count(streamId) == 0) {
+        return;
+    }
+
+    {
+        std::lock_guard<std::mutex> lock(bridgeMutex);
+        // Un pacchetto già passato da qui ha fatto il giro delle reti
+        if (packet.hasCrossedBridge(config.bridgeId) || packet.getBridges().size() >= MAX_BRIDGE_HOPS) {
+            stats.loopsPrevented++;
+            return;
+        }
+        if (!running || queue.size() >= BRIDGE_QUEUE_CAPACITY) {
+            stats.dropped++;
+            return;
+        }
+        queue.push_back({forward, packet});
+    }
+    queueChanged.notify_one();
+}
+
+void NetworkBridge::bridge(const PendingPacket& pending) {
+    const auto& source = pending.forward ? first : second;
+    const auto& destination = pending.forward ? second : first;
+    const auto& routes = pending.forward ? config.forward : config.reverse;
+
+    std::optional<MeshPacket> outgoing;
+    if (pending.packet.getType() == MeshPacketType::Audio) {
+        const auto& frame = pending.packet.getAudioData();
+        // L'istante resta alla stessa distanza da adesso, sull'orologio dell'altra rete
+        int64_t leadUs = static_cast<int64_t>(frame.playoutTimeUs)
+                         - static_cast<int64_t>(source->getNetworkTimeUs());
+        int64_t playoutTimeUs = std::max<int64_t>(static_cast<int64_t>(destination->getNetworkTimeUs()) + leadUs, 0);
+        outgoing = MeshPacket::createAudio(routes.at(frame.streamId), frame.frameSequence,
+                                           static_cast<uint64_t>(playoutTimeUs), frame.payload);
+    } else {
+        StreamMetadata metadata = pending.packet.getMetadata();
+        metadata.streamId = routes.at(metadata.streamId);
+        outgoing = MeshPacket::createMetadata(metadata);
+    }
+    for (const auto& bridgeId : pending.packet.getBridges()) {
+        outgoing->addBridge(bridgeId);
+    }
+    outgoing->addBridge(config.bridgeId);
+
+    bool sent = destination->sendBridgedPacket(*outgoing);
+    std::lock_guard<std::mutex> lock(bridgeMutex);
+    if (!sent) {
+        stats.dropped++;
+    } else if (outgoing->getType() == MeshPacketType::Metadata) {
+        stats.metadataBridged++;
+    } else if (pending.forward) {
+        stats.framesForwarded++;
+    } else {
+        stats.framesReversed++;
+    }
+}
+
+void NetworkBridge::run() {
+    std::unique_lock<std::mutex> lock(bridgeMutex);
+    while (running) {
+        if (queue.empty()) {
+            queueChanged.wait(lock);
+            continue;
+        }
+        PendingPacket pending = std::move(queue.front());
+        queue.pop_front();
+
+        // La rete di destinazione può bloccare: la coda resta libera per le reti
+        lock.unlock();
+        bridge(pending);
+        lock.lock();
+    }
+}
+
+} // namespace saber
@@ -0,0 +1,122 @@
+#include "clock_discipline.h"
+
+#include <algorithm>
+#include <cmath>
+
+namespace saber {
+
+namespace {
+
+/// Rumore di misura di un beacon: risoluzione al millisecondo e jitter della rete (µs²)
+const double MEASUREMENT_VARIANCE = 1000.0 * 1000.0;
+
+/// Rumore di processo dell'offset (µs² al secondo)
+const double OFFSET_NOISE = 100.0;
+
+/// Rumore di processo della deriva, per seguire le variazioni termiche (ppm² al secondo)
+const double SKEW_NOISE = 0.01;
+
+/// Incertezza iniziale della deriva (ppm²)
+const double INITIAL_SKEW_VARIANCE = 100.0 * 100.0;
+
+/// Deriva massima credibile di un quarzo (ppm)
+const double MAX_SKEW_PPM = 1000.0;
+
+double elapsedSeconds(int64_t fromUs, int64_t toUs) {
+    // Un orologio di sistema riportato indietro non fa avanzare il filtro
+    return toUs > fromUs ? static_cast<double>(toUs - fromUs) / 1e6 : 0.0;
+}
+
+} // namespace
+
+// Implementazione di ClockDiscipline
+ClockDiscipline::ClockDiscipline(int64_t stepThresholdUs, uint32_t maxSlewPpm)
+    : stepThresholdUs(stepThresholdUs), maxSlewPpm(maxSlewPpm) {
+}
+
+bool ClockDiscipline::update(int64_t localUs, int64_t measuredOffsetUs) {
+    updates++;
+    if (!locked) {
+        skewPpm = 0.0;
+        p11 = INITIAL_SKEW_VARIANCE;
+        step(localUs, measuredOffsetUs);
+        return true;
+    }
+
+    // Predizione fino alla ricezione del beacon
+    double dt = elapsedSeconds(referenceUs, localUs);
+    appliedUs = appliedAfter(dt);
+    estimateUs += skewPpm * dt;
+    p00 += dt * (2.0 * p01 + dt * p11) + OFFSET_NOISE * dt;
+    p01 += dt * p11;
+    p11 += SKEW_NOISE * dt;
+    referenceUs = std::max(referenceUs, localUs);
+
+    double innovation = static_cast<double>(measuredOffsetUs) - estimateUs;
+    if (stepThresholdUs == 0 || std::fabs(innovation) > static_cast<double>(stepThresholdUs)) {
+        // Master cambiato o orologio locale spostato: inseguire piano sarebbe peggio
+        step(localUs, measuredOffsetUs);
+        return true;
+    }
+
+    // Correzione con il guadagno di Kalman
+    double s = p00 + MEASUREMENT_VARIANCE;
+    double k0 = p00 / s;
+    double k1 = p01 / s;
+    estimateUs += k0 * innovation;
+    skewPpm = std::clamp(skewPpm + k1 * innovation, -MAX_SKEW_PPM, MAX_SKEW_PPM);
+    p11 -= k1 * p01;
+    p01 *= 1.0 - k0;
+    p00 *= 1.0 - k0;
+
+    // Uno scarto che il recupero graduale non ha assorbito viene chiuso di colpo
+    if (std::fabs(estimateUs - appliedUs) > static_cast<double>(stepThresholdUs)) {
+        appliedUs = estimateUs;
+        steps++;
+        return true;
+    }
+    return false;
+}
+
+void ClockDiscipline::step(int64_t localUs, int64_t offsetUs) {
+    locked = true;
+    referenceUs = localUs;
+    estimateUs = static_cast<double>(offsetUs);
+    appliedUs = estimateUs;
+    p00 = MEASUREMENT_VARIANCE;
+    p01 = 0.0;
+    steps++;
+}
+
+int64_t ClockDiscipline::offsetAt(int64_t localUs) const {
+    return static_cast<int64_t>(std::llround(appliedAfter(elapsedSeconds(referenceUs, localUs))));
+}
+
+ClockStats ClockDiscipline::stats(int64_t localUs) const {
+    double dt = elapsedSeconds(referenceUs, localUs);
+    ClockStats result;
+    result.offsetUs = static_cast<int64_t>(std::llround(appliedAfter(dt)));
+    result.estimatedOffsetUs = static_cast<int64_t>(std::llround(estimateUs + skewPpm * dt));
+    result.skewPpm = skewPpm;
+    result.updates = updates;
+    result.steps = steps;
+    return result;
+}
+
+bool ClockDiscipline::isLocked() const {
+    return locked;
+}
+
+void ClockDiscipline::reset() {
+    *this = ClockDiscipline(stepThresholdUs, maxSlewPpm);
+}
+
+double ClockDiscipline::appliedAfter(double elapsedS) const {
+    // L'orologio segue la deriva stimata e recupera lo scarto con velocità limitata
+    double follow = appliedUs + skewPpm * elapsedS;
+    double gap = estimateUs - appliedUs;
+    double allowed = static_cast<double>(maxSlewPpm) * elapsedS;
+    return follow + std::clamp(gap, -allowed, allowed);
+}
+
+} // namespace saber
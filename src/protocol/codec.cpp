@@ -0,0 +1,181 @@
+#include "codec.h"
+
+#include <algorithm>
+#include <stdexcept>
+#include <string>
+
+#ifdef SABER_WITH_LC3
+#include <lc3.h>
+#endif
+
+namespace saber {
+
+namespace {
+
+const int FRAME_US = static_cast<int>(spec::LC3_FRAME_DURATION_US);
+
+void checkFormat(uint32_t sampleRate, uint8_t channels) {
+    switch (sampleRate) {
+        case 8000: case 16000: case 24000: case 32000: case 48000:
+            break;
+        default:
+            throw std::invalid_argument("Frequenza di campionamento non supportata da LC3: "
+                                        + std::to_string(sampleRate));
+    }
+    if (channels == 0) {
+        throw std::invalid_argument("Il frame deve avere almeno un canale");
+    }
+}
+
+#ifndef SABER_WITH_LC3
+[[noreturn]] void lc3Missing() {
+    throw std::runtime_error("Supporto LC3 non compilato (SABER_ENABLE_LC3)");
+}
+#endif
+
+} // namespace
+
+size_t AudioFrame::samplesPerChannel() const {
+    return static_cast<size_t>(sampleRate) * spec::LC3_FRAME_DURATION_US / 1000000;
+}
+
+void applyVolume(AudioFrame& frame, uint8_t volumePercent) {
+    if (volumePercent >= 100) {
+        return;
+    }
+    for (auto& sample : frame.samples) {
+        sample = static_cast<int16_t>(static_cast<int32_t>(sample) * volumePercent / 100);
+    }
+}
+
+bool lc3Available() {
+#ifdef SABER_WITH_LC3
+    return true;
+#else
+    return false;
+#endif
+}
+
+// Implementazione di Lc3Encoder
+struct Lc3Encoder::State {
+#ifdef SABER_WITH_LC3
+    /// Memoria e handle di un codificatore per canale
+    std::vector<std::vector<uint8_t>> memory;
+    std::vector<lc3_encoder_t> encoders;
+#endif
+};
+
+Lc3Encoder::Lc3Encoder(uint32_t sampleRate, uint8_t channels, uint32_t bitrateKbps)
+    : state(std::make_unique<State>()), sampleRate(sampleRate), channels(channels), channelBytes(0) {
+    checkFormat(sampleRate, channels);
+#ifdef SABER_WITH_LC3
+    int sr = static_cast<int>(sampleRate);
+    for (uint8_t ch = 0; ch < channels; ++ch) {
+        state->memory.emplace_back(lc3_encoder_size(FRAME_US, sr));
+        state->encoders.push_back(lc3_setup_encoder(FRAME_US, sr, sr, state->memory.back().data()));
+    }
+    setBitrate(bitrateKbps);
+#else
+    (void)bitrateKbps;
+    lc3Missing();
+#endif
+}
+
+Lc3Encoder::~Lc3Encoder() = default;
+
+std::vector<uint8_t> Lc3Encoder::encode(const AudioFrame& frame) {
+    if (frame.sampleRate != sampleRate || frame.channels != channels) {
+        throw std::invalid_argument("Formato del frame diverso da quello del codificatore");
+    }
+    if (frame.samples.size() != frame.samplesPerChannel() * channels) {
+        throw std::invalid_argument("Il frame deve contenere " + std::to_string(frame.samplesPerChannel())
+                                    + " campioni per canale");
+    }
+
+    std::vector<uint8_t> payload(channelBytes * channels);
+#ifdef SABER_WITH_LC3
+    for (uint8_t ch = 0; ch < channels; ++ch) {
+        lc3_encode(state->encoders[ch], LC3_PCM_FORMAT_S16, frame.samples.data() + ch, channels,
+                   static_cast<int>(channelBytes), payload.data() + ch * channelBytes);
+    }
+#endif
+    return payload;
+}
+
+void Lc3Encoder::setBitrate(uint32_t bitrateKbps) {
+    // Il bitrate complessivo è diviso tra i canali e limitato a quanto ammesso da LC3
+#ifdef SABER_WITH_LC3
+    int bytes = lc3_frame_bytes(FRAME_US, static_cast<int>(bitrateKbps * 1000 / channels));
+    channelBytes = static_cast<size_t>(std::max(LC3_MIN_FRAME_BYTES, std::min(LC3_MAX_FRAME_BYTES, bytes)));
+#else
+    (void)bitrateKbps;
+#endif
+}
+
+size_t Lc3Encoder::frameBytes() const {
+    return channelBytes * channels;
+}
+
+// Implementazione di Lc3Decoder
+struct Lc3Decoder::State {
+#ifdef SABER_WITH_LC3
+    /// Memoria e handle di un decodificatore per canale
+    std::vector<std::vector<uint8_t>> memory;
+    std::vector<lc3_decoder_t> decoders;
+#endif
+};
+
+Lc3Decoder::Lc3Decoder(uint32_t sampleRate, uint8_t channels)
+    : state(std::make_unique<State>()), sampleRate(sampleRate), channels(channels) {
+    checkFormat(sampleRate, channels);
+#ifdef SABER_WITH_LC3
+    int sr = static_cast<int>(sampleRate);
+    for (uint8_t ch = 0; ch < channels; ++ch) {
+        state->memory.emplace_back(lc3_decoder_size(FRAME_US, sr));
+        state->decoders.push_back(lc3_setup_decoder(FRAME_US, sr, sr, state->memory.back().data()));
+    }
+#else
+    lc3Missing();
+#endif
+}
+
+Lc3Decoder::~Lc3Decoder() = default;
+
+AudioFrame Lc3Decoder::decode(const std::vector<uint8_t>& payload, uint64_t playoutTimeUs) {
+    if (payload.empty() || payload.size() % channels != 0) {
+        throw std::invalid_argument("Frame LC3 non divisibile tra " + std::to_string(channels) + " canali");
+    }
+
+    AudioFrame frame;
+    frame.playoutTimeUs = playoutTimeUs;
+    frame.sampleRate = sampleRate;
+    frame.channels = channels;
+    frame.samples.resize(frame.samplesPerChannel() * channels);
+#ifdef SABER_WITH_LC3
+    size_t channelBytes = payload.size() / channels;
+    for (uint8_t ch = 0; ch < channels; ++ch) {
+        if (lc3_decode(state->decoders[ch], payload.data() + ch * channelBytes, static_cast<int>(channelBytes),
+                       LC3_PCM_FORMAT_S16, frame.samples.data() + ch, channels) < 0) {
+            throw std::invalid_argument("Frame LC3 non valido");
+        }
+    }
+#endif
+    return frame;
+}
+
+AudioFrame Lc3Decoder::conceal(uint64_t playoutTimeUs) {
+    AudioFrame frame;
+    frame.playoutTimeUs = playoutTimeUs;
+    frame.sampleRate = sampleRate;
+    frame.channels = channels;
+    frame.samples.resize(frame.samplesPerChannel() * channels);
+#ifdef SABER_WITH_LC3
+    // Senza dati in ingresso la libreria estrapola il frame dai precedenti
+    for (uint8_t ch = 0; ch < channels; ++ch) {
+        lc3_decode(state->decoders[ch], nullptr, 0, LC3_PCM_FORMAT_S16, frame.samples.data() + ch, channels);
+    }
+#endif
+    return frame;
+}
+
+} // namespace saber
@@ -0,0 +1,1219 @@
+#include "config.h"
+#include "crypto.h"
+#include "log.h"
+#include "rng.h"
+#include "saber_protocol.h"
+#include "util.h"
+
+#include <algorithm>
+#include <cstdlib>
+#include <cstring>
+#include <fstream>
+#include <iostream>
+#include <sstream>
+
+#include <openssl/evp.h>
+
+namespace saber {
+
+namespace {
+
+// Rimuove spazi iniziali e finali
+std::string trim(const std::string& value) {
+    const char* whitespace = " \t\r\n";
+    auto start = value.find_first_not_of(whitespace);
+    if (start == std::string::npos) {
+        return "";
+    }
+    auto end = value.find_last_not_of(whitespace);
+    return value.substr(start, end - start + 1);
+}
+
+// Stringa tra virgolette con le sequenze di escape \\ \" \n \t \r
+std::string parseQuoted(const std::string& text, int lineNumber) {
+    std::string value;
+    for (size_t i = 1; i < text.size(); ++i) {
+        char c = text[i];
+        if (c == '"') {
+            if (i + 1 != text.size()) {
+                throw ConfigError("Caratteri dopo la stringa alla riga " + std::to_string(lineNumber));
+            }
+            return value;
+        }
+        if (c != '\\') {
+            value += c;
+            continue;
+        }
+        if (++i == text.size()) {
+            break;
+        }
+        switch (text[i]) {
+            case '\\': value += '\\'; break;
+            case '"': value += '"'; break;
+            case 'n': value += '\n'; break;
+            case 't': value += '\t'; break;
+            case 'r': value += '\r'; break;
+            default:
+                throw ConfigError("Sequenza di escape non supportata '\\" + std::string(1, text[i]) +
+                                  "' alla riga " + std::to_string(lineNumber));
+        }
+    }
+    throw ConfigError("Stringa non terminata alla riga " + std::to_string(lineNumber));
+}
+
+// Elenco di stream separati da virgole (es. "1, 2")
+std::set<StreamId> parseStreamList(const std::string& key, const std::string& text) {
+    std::set<StreamId> streams;
+    std::stringstream stream(text);
+    std::string item;
+    while (std::getline(stream, item, ',')) {
+        item = trim(item);
+        if (item.empty()) {
+            continue;
+        }
+        char* end = nullptr;
+        unsigned long value = std::strtoul(item.c_str(), &end, 10);
+        if (*end != '\0' || item[0] == '-' || value > 0xFFFF) {
+            throw ConfigError("Stream non valido in " + key + ": " + item);
+        }
+        streams.insert(static_cast<StreamId>(value));
+    }
+    return streams;
+}
+
+} // namespace
+
+// Implementazione di ConfigError
+ConfigError::ConfigError(const std::string& message)
+    : std::runtime_error(message) {}
+
+// Implementazione di SecretString
+SecretString::SecretString(std::string value)
+    : value(std::move(value)) {}
+
+const std::string& SecretString::reveal() const {
+    return value;
+}
+
+bool SecretString::empty() const {
+    return value.empty();
+}
+
+std::ostream& operator<<(std::ostream& os, const SecretString& secret) {
+    return os << (secret.empty() ? "<vuoto>" : "<segreto>");
+}
+
+// Implementazione di ConfigFile
+ConfigFile ConfigFile::load(const std::string& path) {
+    std::ifstream file(path);
+    if (!file) {
+        throw ConfigError("Impossibile aprire il file di configurazione: " + path);
+    }
+    std::stringstream buffer;
+    buffer << file.rdbuf();
+    return parse(buffer.str());
+}
+
+ConfigFile ConfigFile::parse(const std::string& text) {
+    ConfigFile config;
+    std::istringstream input(text);
+    std::string line;
+    std::string section;
+    int lineNumber = 0;
+    
+    while (std::getline(input, line)) {
+        lineNumber++;
+        
+        // Rimuovo i commenti che non si trovano dentro una stringa
+        bool inString = false;
+        for (size_t i = 0; i < line.size(); ++i) {
+            if (inString && line[i] == '\\') {
+                ++i;
+            } else if (line[i] == '"') {
+                inString = !inString;
+            } else if (line[i] == '#' && !inString) {
+                line = line.substr(0, i);
+                break;
+            }
+        }
+        
+        line = trim(line);
+        if (line.empty()) {
+            continue;
+        }
+        
+        if (line.front() == '[') {
+            if (line.back() != ']') {
+                throw ConfigError("Sezione non valida alla riga " + std::to_string(lineNumber));
+            }
+            section = trim(line.substr(1, line.size() - 2));
+            continue;
+        }
+        
+        auto eq = line.find('=');
+        if (eq == std::string::npos) {
+            throw ConfigError("Atteso 'chiave = valore' alla riga " + std::to_string(lineNumber));
+        }
+        
+        std::string key = trim(line.substr(0, eq));
+        std::string value = trim(line.substr(eq + 1));
+        if (key.empty()) {
+            throw ConfigError("Chiave mancante alla riga " + std::to_string(lineNumber));
+        }
+        if (!value.empty() && value.front() == '"') {
+            value = parseQuoted(value, lineNumber);
+        }
+        
+        config.values[section.empty() ? key : section + "." + key] = value;
+    }
+    
+    return config;
+}
+
+bool ConfigFile::has(const std::string& key) const {
+    return values.count(key) > 0;
+}
+
+std::optional<std::string> ConfigFile::getString(const std::string& key) const {
+    auto it = values.find(key);
+    if (it == values.end()) {
+        return std::nullopt;
+    }
+    return it->second;
+}
+
+std::optional<int64_t> ConfigFile::getInt(const std::string& key) const {
+    auto value = getString(key);
+    if (!value) {
+        return std::nullopt;
+    }
+    try {
+        size_t parsed = 0;
+        int64_t result = std::stoll(*value, &parsed);
+        if (parsed == value->size()) {
+            return result;
+        }
+    } catch (const std::exception&) {
+    }
+    throw ConfigError("Valore intero non valido per " + key);
+}
+
+std::optional<bool> ConfigFile::getBool(const std::string& key) const {
+    auto value = getString(key);
+    if (!value) {
+        return std::nullopt;
+    }
+    if (*value == "true") {
+        return true;
+    }
+    if (*value == "false") {
+        return false;
+    }
+    throw ConfigError("Valore booleano non valido per " + key);
+}
+
+std::vector<std::string> ConfigFile::keys() const {
+    std::vector<std::string> result;
+    for (const auto& pair : values) {
+        result.push_back(pair.first);
+    }
+    return result;
+}
+
+// Implementazione di Keystore
+Keystore::Keystore(const std::string& path, const std::array<uint8_t, 32>& key)
+    : path(path), key(key) {}
+
+Keystore Keystore::open(const std::string& path) {
+    if (const char* hexKey = std::getenv("SABER_KEYSTORE_KEY")) {
+        std::array<uint8_t, 32> key;
+        auto bytes = fromHex(hexKey);
+        if (!bytes || bytes->size() != key.size()) {
+            throw ConfigError("SABER_KEYSTORE_KEY deve contenere 32 byte in esadecimale");
+        }
+        std::copy(bytes->begin(), bytes->end(), key.begin());
+        return open(path, key);
+    }
+    
+    const char* passphrase = std::getenv("SABER_KEYSTORE_PASSPHRASE");
+    if (!passphrase) {
+        throw ConfigError("Chiave del keystore non disponibile: impostare SABER_KEYSTORE_KEY "
+                          "o SABER_KEYSTORE_PASSPHRASE");
+    }
+    
+    // Il sale e il numero di iterazioni sono nell'intestazione dell'archivio
+    std::vector<uint8_t> salt;
+    uint32_t iterations = KDF_ITERATIONS;
+    std::optional<ConfigFile> existing;
+    if (std::ifstream file{path}) {
+        std::stringstream buffer;
+        buffer << file.rdbuf();
+        existing = ConfigFile::parse(buffer.str());
+        if (auto storedSalt = existing->getString("kdf.salt")) {
+            auto storedBytes = fromHex(*storedSalt);
+            if (!storedBytes) {
+                throw ConfigError("Sale del keystore non valido: " + path);
+            }
+            salt = std::move(*storedBytes);
+            if (salt.size() < KDF_SALT_BYTES) {
+                throw ConfigError("Sale del keystore troppo corto: " + path);
+            }
+            auto storedIterations = existing->getInt("kdf.iterations");
+            if (!storedIterations || *storedIterations < 1 || *storedIterations > 0x7FFFFFFF) {
+                throw ConfigError("kdf.iterations non valido nel keystore: " + path);
+            }
+            iterations = static_cast<uint32_t>(*storedIterations);
+        } else if (!existing->keys().empty()) {
+            throw ConfigError("Formato del keystore non riconosciuto (manca kdf.salt): " + path);
+        }
+    }
+    if (salt.empty()) {
+        salt.resize(KDF_SALT_BYTES);
+        defaultRandomSource()->fill(salt.data(), salt.size());
+    }
+    
+    auto keystore = open(path, deriveKey(passphrase, salt, iterations));
+    keystore.salt = salt;
+    keystore.iterations = iterations;
+    
+    return keystore;
+}
+
+Keystore Keystore::open(const std::string& path, const std::array<uint8_t, 32>& key) {
+    Keystore keystore(path, key);
+    
+    std::ifstream file(path);
+    if (!file) {
+        // Un archivio inesistente è semplicemente vuoto
+        return keystore;
+    }
+    
+    std::stringstream buffer;
+    buffer << file.rdbuf();
+    auto parsed = ConfigFile::parse(buffer.str());
+    for (const auto& name : parsed.keys()) {
+        if (isReservedName(name)) {
+            continue;
+        }
+        auto bytes = fromHex(*parsed.getString(name));
+        if (!bytes) {
+            throw ConfigError("Valore esadecimale non valido per il segreto " + name);
+        }
+        keystore.entries[name] = std::move(*bytes);
+    }
+    
+    return keystore;
+}
+
+std::array<uint8_t, 32> Keystore::deriveKey(const std::string& passphrase, const std::vector<uint8_t>& salt,
+                                            uint32_t iterations) {
+    std::array<uint8_t, 32> derived;
+    if (PKCS5_PBKDF2_HMAC(passphrase.data(), static_cast<int>(passphrase.size()), salt.data(),
+                          static_cast<int>(salt.size()), static_cast<int>(iterations), EVP_sha256(),
+                          static_cast<int>(derived.size()), derived.data()) != 1) {
+        throw ConfigError("Derivazione della chiave del keystore non riuscita");
+    }
+    return derived;
+}
+
+bool Keystore::isReservedName(const std::string& name) {
+    return name.compare(0, 4, "kdf.") == 0;
+}
+
+SecretString Keystore::get(const std::string& name) const {
+    auto it = entries.find(name);
+    if (it == entries.end()) {
+        throw ConfigError("Segreto non presente nel keystore: " + name);
+    }
+    
+    try {
+        auto crypto = MeshCrypto::withNetworkKey(key);
+        auto plain = crypto.decrypt(it->second);
+        return SecretString(std::string(plain.begin(), plain.end()));
+    } catch (const CryptoError&) {
+        throw ConfigError("Impossibile decifrare il segreto " + name + ": chiave del keystore errata?");
+    }
+}
+
+void Keystore::put(const std::string& name, const SecretString& value) {
+    if (isReservedName(name)) {
+        throw ConfigError("Nome riservato all'intestazione del keystore: " + name);
+    }
+    // La chiave dura quanto l'archivio: ogni voce riceve un nonce casuale di 96 bit
+    auto crypto = MeshCrypto::withNetworkKey(key);
+    const auto& plain = value.reveal();
+    entries[name] = crypto.encryptWithRandomNonce(std::vector<uint8_t>(plain.begin(), plain.end()));
+}
+
+void Keystore::save() const {
+    // Scrittura su file temporaneo e rinomina per non lasciare archivi troncati
+    std::string tmpPath = path + ".tmp";
+    {
+        std::ofstream file(tmpPath, std::ios::trunc);
+        if (!file) {
+            throw ConfigError("Impossibile scrivere il keystore: " + path);
+        }
+        file << "# Keystore SABER: valori cifrati con AES-256-GCM\n";
+        if (!salt.empty()) {
+            file << "kdf.iterations = " << iterations << "\n";
+            file << "kdf.salt = \"" << toHex(salt) << "\"\n";
+        }
+        for (const auto& pair : entries) {
+            file << pair.first << " = \"" << toHex(pair.second) << "\"\n";
+        }
+    }
+    if (std::rename(tmpPath.c_str(), path.c_str()) != 0) {
+        std::remove(path.c_str());
+        if (std::rename(tmpPath.c_str(), path.c_str()) != 0) {
+            throw ConfigError("Impossibile sostituire il keystore: " + path);
+        }
+    }
+}
+
+SecretString resolveSecret(const std::string& reference, const Keystore* keystore) {
+    const std::string envPrefix = "env:";
+    const std::string keystorePrefix = "keystore:";
+    
+    if (reference.compare(0, envPrefix.size(), envPrefix) == 0) {
+        std::string name = reference.substr(envPrefix.size());
+        const char* value = std::getenv(name.c_str());
+        if (!value) {
+            throw ConfigError("Variabile d'ambiente non impostata: " + name);
+        }
+        return SecretString(value);
+    }
+    
+    if (reference.compare(0, keystorePrefix.size(), keystorePrefix) == 0) {
+        if (!keystore) {
+            throw ConfigError("Riferimento al keystore senza security.keystore configurato");
+        }
+        return keystore->get(reference.substr(keystorePrefix.size()));
+    }
+    
+    return SecretString(reference);
+}
+
+// Caricamento di SaberConfig da file
+bool SaberConfig::isLiveReloadable(const std::string& key) {
+    static const char* const livePrefixes[] = {
+        "log.filter",
+        "degradation.",
+        "security.ids_",
+        "security.send_rejects",
+        "security.replay_window",
+        "provisioning.closed_attempts_per_minute",
+        "provisioning.max_nodes",
+        "provisioning.max_sinks",
+        "mesh.heartbeat_timeout_ms",
+        "mesh.evict_after_ms",
+        "mesh.reliable_",
+        "ratelimit.",
+        "authorization.",
+        "diagnostics.record_paths",
+        "audio.repair_",
+        "audio.jitter_",
+        "audio.equalization_",
+        "survey.",
+        "sync.exchange_interval_ms",
+        "sync.mesh_beacon_interval_ms",
+        "discovery.interval_ms",
+        "tracing.enabled",
+        "standby.",
+        "simulcast.",
+        "flow.",
+        "source",
+        "zone.",
+        "config.watch_interval_ms",
+    };
+    for (const char* prefix : livePrefixes) {
+        if (key.compare(0, std::strlen(prefix), prefix) == 0) {
+            return true;
+        }
+    }
+    return false;
+}
+
+SaberConfig SaberConfig::fromFile(const std::string& path) {
+    auto file = ConfigFile::load(path);
+    SaberConfig config = defaultConfig();
+    config.configFile = path;
+    
+    if (auto id = file.getString("node.id")) {
+        config.nodeId = *id;
+    }
+    if (auto role = file.getString("node.role")) {
+        auto parsed = nodeRoleFromString(*role);
+        if (!parsed) {
+            throw ConfigError("Ruolo non valido: " + *role);
+        }
+        config.role = *parsed;
+    }
+    // Il profilo fornisce i valori di partenza, sovrascrivibili dalle chiavi seguenti
+    if (auto profileName = file.getString("node.profile")) {
+        auto profile = findProfile(*profileName);
+        if (!profile) {
+            throw ConfigError("Profilo sconosciuto: " + *profileName);
+        }
+        config.applyProfile(*profile);
+    }
+    if (auto address = file.getString("node.bt_address")) {
+        config.btAddress = *address;
+    }
+    if (auto music = file.getBool("node.music_mode")) {
+        config.isMusicMode = *music;
+    }
+    if (auto port = file.getInt("health.port")) {
+        if (*port <= 0 || *port > 65535) {
+            throw ConfigError("health.port deve essere compreso tra 1 e 65535");
+        }
+        config.healthPort = static_cast<uint16_t>(*port);
+    }
+    if (auto bindAddress = file.getString("health.bind_address")) {
+        config.healthBindAddress = *bindAddress;
+    }
+    if (auto port = file.getInt("control.port")) {
+        if (*port <= 0 || *port > 65535) {
+            throw ConfigError("control.port deve essere compreso tra 1 e 65535");
+        }
+        config.controlPort = static_cast<uint16_t>(*port);
+    }
+    if (auto bindAddress = file.getString("control.bind_address")) {
+        config.controlBindAddress = *bindAddress;
+    }
+    if (auto tokenFile = file.getString("control.token_file")) {
+        config.controlTokenFile = *tokenFile;
+    }
+    if (auto ttl = file.getInt("control.token_ttl_s")) {
+        if (*ttl <= 0) {
+            throw ConfigError("control.token_ttl_s deve essere positivo");
+        }
+        config.controlTokenTtlSeconds = static_cast<uint64_t>(*ttl);
+    }
+    if (auto ttl = file.getInt("security.pairing_token_ttl_s")) {
+        if (*ttl <= 0) {
+            throw ConfigError("security.pairing_token_ttl_s deve essere positivo");
+        }
+        config.pairingTokenTtlSeconds = static_cast<uint64_t>(*ttl);
+    }
+    if (auto required = file.getBool("preflight.require_bluetooth")) {
+        config.requireBluetooth = *required;
+    }
+    if (auto required = file.getBool("preflight.require_audio_device")) {
+        config.requireAudioDevice = *required;
+    }
+    if (auto resolution = file.getInt("preflight.max_clock_resolution_us")) {
+        if (*resolution <= 0) {
+            throw ConfigError("preflight.max_clock_resolution_us deve essere positivo");
+        }
+        config.maxClockResolutionUs = static_cast<uint32_t>(*resolution);
+    }
+    if (auto attempts = file.getInt("provisioning.closed_attempts_per_minute")) {
+        if (*attempts < 0) {
+            throw ConfigError("Valore negativo per provisioning.closed_attempts_per_minute");
+        }
+        config.joinAttemptsPerMinute = static_cast<uint32_t>(*attempts);
+    }
+    if (auto timeout = file.getInt("authorization.timeout_ms")) {
+        if (*timeout <= 0) {
+            throw ConfigError("authorization.timeout_ms deve essere positivo");
+        }
+        config.authorizationTimeoutMs = static_cast<uint32_t>(*timeout);
+    }
+    if (auto onTimeout = file.getString("authorization.on_timeout")) {
+        if (*onTimeout != "allow" && *onTimeout != "deny") {
+            throw ConfigError("authorization.on_timeout deve essere \"allow\" o \"deny\": " + *onTimeout);
+        }
+        config.authorizationAllowOnTimeout = *onTimeout == "allow";
+    }
+    if (auto maxNodes = file.getInt("provisioning.max_nodes")) {
+        if (*maxNodes < 0) {
+            throw ConfigError("Valore negativo per provisioning.max_nodes");
+        }
+        config.maxNodes = static_cast<uint32_t>(*maxNodes);
+    }
+    if (auto maxSinks = file.getInt("provisioning.max_sinks")) {
+        if (*maxSinks < 0) {
+            throw ConfigError("Valore negativo per provisioning.max_sinks");
+        }
+        config.maxSinks = static_cast<uint32_t>(*maxSinks);
+    }
+    if (auto timeout = file.getInt("mesh.heartbeat_timeout_ms")) {
+        if (*timeout <= 0) {
+            throw ConfigError("mesh.heartbeat_timeout_ms deve essere positivo");
+        }
+        config.heartbeatTimeoutMs = static_cast<uint32_t>(*timeout);
+    }
+    if (auto evictAfter = file.getInt("mesh.evict_after_ms")) {
+        if (*evictAfter < 0) {
+            throw ConfigError("Valore negativo per mesh.evict_after_ms");
+        }
+        config.nodeEvictAfterMs = static_cast<uint32_t>(*evictAfter);
+    }
+    if (auto enabled = file.getBool("mesh.reliable_enabled")) {
+        config.reliable.enabled = *enabled;
+    }
+    if (auto timeout = file.getInt("mesh.reliable_timeout_ms")) {
+        if (*timeout <= 0) {
+            throw ConfigError("mesh.reliable_timeout_ms deve essere positivo");
+        }
+        config.reliable.initialTimeoutMs = static_cast<uint32_t>(*timeout);
+    }
+    if (auto maxTimeout = file.getInt("mesh.reliable_max_timeout_ms")) {
+        if (*maxTimeout <= 0) {
+            throw ConfigError("mesh.reliable_max_timeout_ms deve essere positivo");
+        }
+        config.reliable.maxTimeoutMs = static_cast<uint32_t>(*maxTimeout);
+    }
+    if (config.reliable.maxTimeoutMs < config.reliable.initialTimeoutMs) {
+        throw ConfigError("mesh.reliable_max_timeout_ms non può essere minore di mesh.reliable_timeout_ms");
+    }
+    if (auto attempts = file.getInt("mesh.reliable_max_attempts")) {
+        if (*attempts <= 0) {
+            throw ConfigError("mesh.reliable_max_attempts deve essere positivo");
+        }
+        config.reliable.maxAttempts = static_cast<uint32_t>(*attempts);
+    }
+    // Limite di traffico di ogni tipo di pacchetto nella sezione [ratelimit.<tipo>]
+    for (const auto& key : file.keys()) {
+        if (key.compare(0, 10, "ratelimit.") != 0) {
+            continue;
+        }
+        auto dot = key.rfind('.');
+        std::string name = key.substr(10, dot > 10 ? dot - 10 : 0);
+        auto type = meshPacketTypeFromString(name);
+        if (!type) {
+            throw ConfigError("Tipo di pacchetto sconosciuto per " + key);
+        }
+        if (*type == MeshPacketType::Join || *type == MeshPacketType::Pairing) {
+            throw ConfigError(key + ": le richieste di ingresso seguono provisioning.closed_attempts_per_minute");
+        }
+        auto value = *file.getInt(key);
+        std::string field = key.substr(dot + 1);
+        RateLimit& limit = config.rateLimits[*type];
+        if (field == "per_second") {
+            if (value < 0) {
+                throw ConfigError("Valore negativo per " + key);
+            }
+            limit.packetsPerSecond = static_cast<double>(value);
+        } else if (field == "burst") {
+            if (value <= 0) {
+                throw ConfigError(key + " deve essere positivo");
+            }
+            limit.burst = static_cast<uint32_t>(value);
+        } else {
+            throw ConfigError("Chiave di limite sconosciuta: " + key);
+        }
+    }
+    for (auto it = config.rateLimits.begin(); it != config.rateLimits.end();) {
+        // Un limite a zero toglie il limite; senza raffica se ne concede un secondo
+        if (it->second.packetsPerSecond <= 0.0) {
+            it = config.rateLimits.erase(it);
+            continue;
+        }
+        if (it->second.burst == 0) {
+            it->second.burst = static_cast<uint32_t>(it->second.packetsPerSecond);
+        }
+        ++it;
+    }
+    if (auto stateFile = file.getString("state.file")) {
+        config.stateFile = *stateFile;
+    }
+#ifndef SABER_MINIMAL_SINK
+    if (auto scheduleFile = file.getString("schedule.file")) {
+        config.scheduleFile = *scheduleFile;
+    }
+    if (auto offset = file.getString("schedule.utc_offset")) {
+        auto parsed = Scheduler::parseUtcOffset(*offset);
+        if (!parsed) {
+            throw ConfigError("Scostamento da UTC non valido (es. \"+01:00\"): " + *offset);
+        }
+        config.scheduleUtcOffsetMinutes = *parsed;
+    }
+#else
+    // La programmazione oraria appartiene al Master, assente nella build minimale
+    if (file.getString("schedule.file") || file.getString("schedule.utc_offset")) {
+        SABER_LOG(Warn, "config", "Programmazione oraria ignorata nella build minimal-sink");
+    }
+#endif
+    if (auto lead = file.getInt("schedule.start_lead_ms")) {
+        if (*lead < 0) {
+            throw ConfigError("Valore negativo per schedule.start_lead_ms");
+        }
+        config.startBarrierLeadMs = static_cast<uint32_t>(*lead);
+    }
+    if (auto enabled = file.getBool("degradation.enabled")) {
+        config.degradation.enabled = *enabled;
+    }
+    const std::map<std::string, uint32_t*> degradationValues = {
+        {"degradation.bad_quality_percent", &config.degradation.badQualityPercent},
+        {"degradation.good_quality_percent", &config.degradation.goodQualityPercent},
+        {"degradation.escalate_after_ms", &config.degradation.escalateAfterMs},
+        {"degradation.recover_after_ms", &config.degradation.recoverAfterMs},
+        {"degradation.buffer_step_ms", &config.degradation.bufferStepMs},
+        {"degradation.max_suspended_sinks", &config.degradation.maxSuspendedSinks},
+    };
+    for (const auto& entry : degradationValues) {
+        if (auto value = file.getInt(entry.first)) {
+            if (*value < 0) {
+                throw ConfigError("Valore negativo per " + entry.first);
+            }
+            *entry.second = static_cast<uint32_t>(*value);
+        }
+    }
+    if (config.degradation.goodQualityPercent < config.degradation.badQualityPercent 
+        || config.degradation.goodQualityPercent > 100) {
+        throw ConfigError("degradation.good_quality_percent deve essere tra bad_quality_percent e 100");
+    }
+    const std::map<std::string, uint32_t*> intercomValues = {
+        {"intercom.bitrate_kbps", &config.intercom.bitrateKbps},
+        {"intercom.buffer_ms", &config.intercom.bufferMs},
+        {"intercom.beacon_interval_ms", &config.intercom.beaconIntervalMs},
+        {"intercom.ducking_db", &config.intercom.duckingDb},
+    };
+    for (const auto& entry : intercomValues) {
+        if (auto value = file.getInt(entry.first)) {
+            if (*value < 0) {
+                throw ConfigError("Valore negativo per " + entry.first);
+            }
+            *entry.second = static_cast<uint32_t>(*value);
+        }
+    }
+    if (config.intercom.bitrateKbps == 0) {
+        throw ConfigError("intercom.bitrate_kbps deve essere positivo");
+    }
+    if (auto enabled = file.getBool("standby.enabled")) {
+        config.standby.enabled = *enabled;
+    }
+    const std::map<std::string, uint32_t*> standbyValues = {
+        {"standby.idle_minutes", &config.standby.idleMinutes},
+        {"standby.preroll_ms", &config.standby.prerollMs},
+        {"standby.report_interval_ms", &config.standby.reportIntervalMs},
+    };
+    for (const auto& entry : standbyValues) {
+        if (auto value = file.getInt(entry.first)) {
+            if (*value < 0) {
+                throw ConfigError("Valore negativo per " + entry.first);
+            }
+            *entry.second = static_cast<uint32_t>(*value);
+        }
+    }
+    if (config.standby.idleMinutes == 0) {
+        throw ConfigError("standby.idle_minutes deve essere positivo");
+    }
+    const std::map<std::string, uint32_t*> simulcastValues = {
+        {"simulcast.headroom_percent", &config.simulcast.headroomPercent},
+        {"simulcast.upgrade_after_ms", &config.simulcast.upgradeAfterMs},
+        {"simulcast.loss_threshold_percent", &config.simulcast.lossThresholdPercent},
+    };
+    for (const auto& entry : simulcastValues) {
+        if (auto value = file.getInt(entry.first)) {
+            if (*value < 0) {
+                throw ConfigError("Valore negativo per " + entry.first);
+            }
+            *entry.second = static_cast<uint32_t>(*value);
+        }
+    }
+    if (config.simulcast.lossThresholdPercent == 0 || config.simulcast.lossThresholdPercent >= 100) {
+        throw ConfigError("simulcast.loss_threshold_percent deve essere compreso tra 1 e 99");
+    }
+    if (auto enabled = file.getBool("flow.enabled")) {
+        config.flow.enabled = *enabled;
+    }
+    const std::map<std::string, uint32_t*> flowValues = {
+        {"flow.low_watermark_percent", &config.flow.lowWatermarkPercent},
+        {"flow.high_watermark_percent", &config.flow.highWatermarkPercent},
+        {"flow.recover_after_ms", &config.flow.recoverAfterMs},
+        {"flow.report_timeout_ms", &config.flow.reportTimeoutMs},
+    };
+    for (const auto& entry : flowValues) {
+        if (auto value = file.getInt(entry.first)) {
+            if (*value < 0) {
+                throw ConfigError("Valore negativo per " + entry.first);
+            }
+            *entry.second = static_cast<uint32_t>(*value);
+        }
+    }
+    if (config.flow.lowWatermarkPercent == 0 || config.flow.lowWatermarkPercent >= 100 ||
+        config.flow.highWatermarkPercent <= 100 || config.flow.highWatermarkPercent > 255) {
+        throw ConfigError("flow.low_watermark_percent deve essere tra 1 e 99 e flow.high_watermark_percent "
+                          "tra 101 e 255");
+    }
+    const std::map<std::string, uint32_t*> sourceValues = {
+        {"sources.stall_ms", &config.sources.stallMs},
+        {"sources.silence_ms", &config.sources.silenceMs},
+        {"sources.failback_ms", &config.sources.failbackMs},
+    };
+    for (const auto& entry : sourceValues) {
+        if (auto value = file.getInt(entry.first)) {
+            if (*value < 0) {
+                throw ConfigError("Valore negativo per " + entry.first);
+            }
+            *entry.second = static_cast<uint32_t>(*value);
+        }
+    }
+    if (config.sources.stallMs == 0) {
+        throw ConfigError("sources.stall_ms deve essere positivo");
+    }
+    if (auto threshold = file.getInt("sources.silence_threshold")) {
+        if (*threshold < 0 || *threshold > 32767) {
+            throw ConfigError("sources.silence_threshold deve essere compreso tra 0 e 32767");
+        }
+        config.sources.silenceThreshold = static_cast<uint16_t>(*threshold);
+    }
+    if (auto failback = file.getBool("sources.auto_failback")) {
+        config.sources.autoFailback = *failback;
+    }
+    // Ogni sorgente ha la propria sezione [source.<nome>]
+    std::map<std::string, AudioSourceConfig> sources;
+    for (const auto& key : file.keys()) {
+        if (key.compare(0, 7, "source.") != 0) {
+            continue;
+        }
+        auto dot = key.rfind('.');
+        std::string name = key.substr(7, dot > 7 ? dot - 7 : 0);
+        std::string field = key.substr(dot + 1);
+        if (name.empty()) {
+            throw ConfigError("Chiave di sorgente senza nome: " + key);
+        }
+        AudioSourceConfig& source = sources[name];
+        source.name = name;
+        if (field == "kind") {
+            auto kind = sourceKindFromString(*file.getString(key));
+            if (!kind) {
+                throw ConfigError("Tipo di sorgente sconosciuto per " + key + " (capture, file, rtp o tone)");
+            }
+            source.kind = *kind;
+        } else if (field == "location") {
+            source.location = *file.getString(key);
+        } else if (field == "priority") {
+            auto priority = *file.getInt(key);
+            if (priority < 0) {
+                throw ConfigError("Valore negativo per " + key);
+            }
+            source.priority = static_cast<uint32_t>(priority);
+        } else {
+            throw ConfigError("Chiave di sorgente sconosciuta: " + key);
+        }
+    }
+    for (const auto& entry : sources) {
+        if (!file.has("source." + entry.first + ".kind")) {
+            throw ConfigError("source." + entry.first + ".kind mancante");
+        }
+        config.sources.sources.push_back(entry.second);
+    }
+    std::stable_sort(config.sources.sources.begin(), config.sources.sources.end(),
+        [](const AudioSourceConfig& a, const AudioSourceConfig& b) { return a.priority < b.priority; });
+    // Ritardo acustico di ogni zona nella sezione [zone.<nome>]
+    for (const auto& key : file.keys()) {
+        if (key.compare(0, 5, "zone.") != 0) {
+            continue;
+        }
+        auto dot = key.rfind('.');
+        std::string name = key.substr(5, dot > 5 ? dot - 5 : 0);
+        if (name.empty()) {
+            throw ConfigError("Chiave di zona senza nome: " + key);
+        }
+        if (key.substr(dot + 1) != "delay_ms") {
+            throw ConfigError("Chiave di zona sconosciuta: " + key);
+        }
+        auto delay = *file.getInt(key);
+        if (delay < 0 || delay > static_cast<int64_t>(MAX_ZONE_DELAY_MS)) {
+            throw ConfigError(key + " deve essere compreso tra 0 e " + std::to_string(MAX_ZONE_DELAY_MS));
+        }
+        config.zoneDelays[name] = static_cast<uint32_t>(delay);
+    }
+    if (auto journalFile = file.getString("events.journal_file")) {
+        config.eventJournalFile = *journalFile;
+    }
+    if (auto entries = file.getInt("events.max_entries")) {
+        if (*entries <= 0) {
+            throw ConfigError("events.max_entries deve essere positivo");
+        }
+        config.eventJournalMaxEntries = static_cast<uint32_t>(*entries);
+    }
+    if (auto window = file.getInt("diagnostics.profile_window")) {
+        if (*window <= 0) {
+            throw ConfigError("diagnostics.profile_window deve essere positivo");
+        }
+        config.profileWindowSamples = static_cast<uint32_t>(*window);
+    }
+    if (auto traceFile = file.getString("diagnostics.trace_file")) {
+        config.pipelineTraceFile = *traceFile;
+    }
+    if (auto recordPaths = file.getBool("diagnostics.record_paths")) {
+        config.recordPaths = *recordPaths;
+    }
+    if (auto interval = file.getInt("config.watch_interval_ms")) {
+        if (*interval < 0) {
+            throw ConfigError("Valore negativo per config.watch_interval_ms");
+        }
+        config.configWatchIntervalMs = static_cast<uint32_t>(*interval);
+    }
+    if (auto filter = file.getString("log.filter")) {
+        config.logFilter = *filter;
+    }
+    if (auto sendRejects = file.getBool("security.send_rejects")) {
+        config.sendRejects = *sendRejects;
+    }
+    if (auto encrypt = file.getBool("security.encrypt_packets")) {
+        config.encryptPackets = *encrypt;
+    }
+    if (auto sessions = file.getBool("security.session_keys")) {
+        config.sessionKeys = *sessions;
+    }
+    if (auto mode = file.getString("sync.asymmetry_mode")) {
+        auto parsed = asymmetryModeFromString(*mode);
+        if (!parsed) {
+            throw ConfigError("Modo di stima dell'asimmetria sconosciuto: " + *mode + " (symmetric o min_delay)");
+        }
+        config.syncProbeAsymmetry = *parsed;
+    }
+    if (auto interval = file.getInt("sync.exchange_interval_ms")) {
+        if (*interval < 0) {
+            throw ConfigError("Valore negativo per sync.exchange_interval_ms");
+        }
+        config.timeExchangeIntervalMs = static_cast<uint32_t>(*interval);
+    }
+    if (auto interval = file.getInt("sync.mesh_beacon_interval_ms")) {
+        if (*interval < 0) {
+            throw ConfigError("Valore negativo per sync.mesh_beacon_interval_ms");
+        }
+        config.meshBeaconIntervalMs = static_cast<uint32_t>(*interval);
+    }
+    if (auto interval = file.getInt("survey.interval_ms")) {
+        if (*interval <= 0) {
+            throw ConfigError("survey.interval_ms deve essere positivo");
+        }
+        config.surveyIntervalMs = static_cast<uint32_t>(*interval);
+    }
+    if (auto rssi = file.getInt("survey.min_rssi_dbm")) {
+        config.surveyMinRssiDbm = static_cast<int32_t>(*rssi);
+    }
+    if (auto enabled = file.getBool("discovery.enabled")) {
+        config.discoveryEnabled = *enabled;
+    }
+    if (auto interval = file.getInt("discovery.interval_ms")) {
+        if (*interval <= 0) {
+            throw ConfigError("discovery.interval_ms deve essere positivo");
+        }
+        config.discoveryIntervalMs = static_cast<uint32_t>(*interval);
+    }
+    if (auto stale = file.getInt("discovery.stale_ms")) {
+        if (*stale <= 0) {
+            throw ConfigError("discovery.stale_ms deve essere positivo");
+        }
+        config.discoveryStaleMs = static_cast<uint32_t>(*stale);
+    }
+    if (auto enabled = file.getBool("tracing.enabled")) {
+        config.tracingEnabled = *enabled;
+    }
+    if (auto endpoint = file.getString("tracing.otlp_endpoint")) {
+        config.otlpEndpoint = *endpoint;
+    }
+    if (auto interval = file.getInt("tracing.export_interval_ms")) {
+        if (*interval <= 0) {
+            throw ConfigError("tracing.export_interval_ms deve essere positivo");
+        }
+        config.otlpExportIntervalMs = static_cast<uint32_t>(*interval);
+    }
+    if (auto loss = file.getInt("survey.max_loss_percent")) {
+        if (*loss < 0 || *loss > 100) {
+            throw ConfigError("survey.max_loss_percent deve essere tra 0 e 100");
+        }
+        config.surveyMaxLossPercent = static_cast<uint32_t>(*loss);
+    }
+    if (auto window = file.getInt("security.replay_window")) {
+        if (*window <= 0) {
+            throw ConfigError("security.replay_window deve essere positivo");
+        }
+        config.replayWindow = static_cast<uint32_t>(*window);
+    }
+    if (auto nonceFile = file.getString("security.nonce_file")) {
+        config.nonceFile = *nonceFile;
+    }
+    if (auto enabled = file.getBool("security.ids_enabled")) {
+        config.intrusion.enabled = *enabled;
+    }
+    if (auto quarantine = file.getBool("security.ids_auto_quarantine")) {
+        config.intrusion.autoQuarantine = *quarantine;
+    }
+    const std::pair<const char*, uint32_t*> intrusionKeys[] = {
+        {"security.ids_window_ms", &config.intrusion.windowMs},
+        {"security.ids_beacon_threshold", &config.intrusion.beaconThreshold},
+        {"security.ids_signature_threshold", &config.intrusion.signatureFailureThreshold},
+        {"security.ids_replay_threshold", &config.intrusion.replayThreshold},
+        {"security.ids_route_flap_threshold", &config.intrusion.routeFlapThreshold},
+    };
+    for (const auto& key : intrusionKeys) {
+        if (auto value = file.getInt(key.first)) {
+            if (*value < 0) {
+                throw ConfigError(std::string("Valore negativo per ") + key.first);
+            }
+            *key.second = static_cast<uint32_t>(*value);
+        }
+    }
+    if (config.intrusion.windowMs == 0) {
+        throw ConfigError("security.ids_window_ms deve essere positivo");
+    }
+    if (auto granularity = file.getString("security.frame_encryption")) {
+        auto parsed = encryptionGranularityFromString(*granularity);
+        if (!parsed) {
+            throw ConfigError("Granularità di cifratura non valida: " + *granularity);
+        }
+        config.frameEncryption = *parsed;
+    }
+    if (auto bits = file.getInt("audio.timestamp_bits")) {
+        auto width = timestampWidthFromBits(static_cast<uint32_t>(*bits));
+        if (!width) {
+            throw ConfigError("Ampiezza del timestamp non valida (16, 24 o 64): " + std::to_string(*bits));
+        }
+        config.audioTimestampWidth = *width;
+    }
+    if (auto frames = file.getInt("audio.timestamp_anchor_frames")) {
+        if (*frames <= 0) {
+            throw ConfigError("audio.timestamp_anchor_frames deve essere positivo");
+        }
+        config.timestampAnchorFrames = static_cast<uint32_t>(*frames);
+    }
+    if (auto output = file.getString("audio.output")) {
+        if (*output != "local" && *output != "rtp") {
+            throw ConfigError("Uscita audio non valida (local o rtp): " + *output);
+        }
+        config.audioOutput = *output;
+    }
+    if (auto policy = file.getString("audio.late_policy")) {
+        if (*policy != "drop" && *policy != "late" && *policy != "resync") {
+            throw ConfigError("Politica per i frame in ritardo non valida (drop, late o resync): " + *policy);
+        }
+        config.lateFramePolicy = *policy;
+    }
+    if (auto tolerance = file.getInt("audio.late_tolerance_ms")) {
+        if (*tolerance < 0) {
+            throw ConfigError("Valore negativo per audio.late_tolerance_ms");
+        }
+        config.lateFrameToleranceMs = static_cast<uint32_t>(*tolerance);
+    }
+    if (auto streams = file.getString("audio.published_streams")) {
+        config.publishedStreams = parseStreamList("audio.published_streams", *streams);
+    }
+    if (auto streams = file.getString("audio.listen_streams")) {
+        config.listenStreams = parseStreamList("audio.listen_streams", *streams);
+    }
+    if (auto streams = file.getString("audio.voice_streams")) {
+        config.voiceStreams = parseStreamList("audio.voice_streams", *streams);
+    }
+    if (auto phantom = file.getBool("audio.phantom")) {
+        config.phantomSink = *phantom;
+    }
+    if (auto device = file.getString("audio.a2dp_device")) {
+        config.a2dpDevice = *device;
+    }
+    if (auto latency = file.getInt("audio.a2dp_latency_ms")) {
+        if (*latency < 0) {
+            throw ConfigError("Valore negativo per audio.a2dp_latency_ms");
+        }
+        config.a2dpLatencyMs = static_cast<uint32_t>(*latency);
+    }
+    if (auto address = file.getString("audio.rtp_address")) {
+        config.rtpTarget.address = *address;
+    }
+    if (auto port = file.getInt("audio.rtp_port")) {
+        if (*port <= 0 || *port > 65535) {
+            throw ConfigError("Porta RTP non valida: " + std::to_string(*port));
+        }
+        config.rtpTarget.port = static_cast<uint16_t>(*port);
+    }
+    if (auto payloadType = file.getInt("audio.rtp_payload_type")) {
+        if (*payloadType < 96 || *payloadType > 127) {
+            throw ConfigError("audio.rtp_payload_type deve essere dinamico (96-127)");
+        }
+        config.rtpTarget.payloadType = static_cast<uint8_t>(*payloadType);
+    }
+    if (auto encoding = file.getString("audio.rtp_encoding")) {
+        if (*encoding != "L24" && *encoding != "L16") {
+            throw ConfigError("Formato RTP non valido (L24 o L16): " + *encoding);
+        }
+        config.rtpTarget.encoding = *encoding;
+    }
+    if (auto packetTime = file.getInt("audio.rtp_packet_time_us")) {
+        if (*packetTime <= 0) {
+            throw ConfigError("audio.rtp_packet_time_us deve essere positivo");
+        }
+        config.rtpTarget.packetTimeUs = static_cast<uint32_t>(*packetTime);
+    }
+    if (auto offset = file.getInt("audio.rtp_link_offset_ms")) {
+        if (*offset < 0) {
+            throw ConfigError("Valore negativo per audio.rtp_link_offset_ms");
+        }
+        config.rtpTarget.linkOffsetMs = static_cast<uint32_t>(*offset);
+    }
+    if (auto ttl = file.getInt("audio.rtp_ttl")) {
+        if (*ttl <= 0 || *ttl > 255) {
+            throw ConfigError("audio.rtp_ttl deve essere compreso tra 1 e 255");
+        }
+        config.rtpTarget.multicastTtl = static_cast<uint8_t>(*ttl);
+    }
+    if (auto kind = file.getString("transport.kind")) {
+        auto transport = transportKindFromString(*kind);
+        if (!transport) {
+            throw ConfigError("Collegamento sconosciuto (ble o udp): " + *kind);
+        }
+        config.transport = *transport;
+    }
+    if (auto address = file.getString("transport.multicast_address")) {
+        config.udp.multicastAddress = *address;
+    }
+    if (auto port = file.getInt("transport.port")) {
+        if (*port <= 0 || *port > 65535) {
+            throw ConfigError("Porta UDP non valida: " + std::to_string(*port));
+        }
+        config.udp.port = static_cast<uint16_t>(*port);
+    }
+    if (auto local = file.getString("transport.interface")) {
+        config.udp.interfaceAddress = *local;
+    }
+    if (auto ttl = file.getInt("transport.multicast_ttl")) {
+        if (*ttl <= 0 || *ttl > 255) {
+            throw ConfigError("transport.multicast_ttl deve essere compreso tra 1 e 255");
+        }
+        config.udp.multicastTtl = static_cast<uint8_t>(*ttl);
+    }
+    if (config.transport == TransportKind::Udp) {
+        try {
+            UdpTransport validated(config.udp);
+        } catch (const std::invalid_argument& e) {
+            throw ConfigError(e.what());
+        }
+    }
+    if (auto enabled = file.getBool("audio.repair_enabled")) {
+        config.repair.enabled = *enabled;
+    }
+    if (auto window = file.getInt("audio.repair_window_ms")) {
+        if (*window < 0) {
+            throw ConfigError("Valore negativo per audio.repair_window_ms");
+        }
+        config.repair.windowMs = static_cast<uint32_t>(*window);
+    }
+    if (auto frames = file.getInt("audio.repair_cache_frames")) {
+        if (*frames < 0) {
+            throw ConfigError("Valore negativo per audio.repair_cache_frames");
+        }
+        config.repair.cacheFrames = static_cast<uint32_t>(*frames);
+    }
+    if (auto enabled = file.getBool("audio.equalization_enabled")) {
+        config.delayEqualization.enabled = *enabled;
+    }
+    if (auto hold = file.getInt("audio.equalization_hold_ms")) {
+        if (*hold < 0) {
+            throw ConfigError("Valore negativo per audio.equalization_hold_ms");
+        }
+        config.delayEqualization.holdMs = static_cast<uint32_t>(*hold);
+    }
+    if (auto adaptive = file.getBool("audio.jitter_adaptive")) {
+        config.jitterBuffer.adaptive = *adaptive;
+    }
+    const std::map<std::string, uint32_t*> jitterSettings = {
+        {"audio.jitter_min_ms", &config.jitterBuffer.minMs},
+        {"audio.jitter_max_ms", &config.jitterBuffer.maxMs},
+        {"audio.jitter_step_ms", &config.jitterBuffer.stepMs},
+        {"audio.jitter_shrink_after_ms", &config.jitterBuffer.shrinkAfterMs},
+        {"audio.jitter_max_loss_percent", &config.jitterBuffer.maxLossPercent},
+    };
+    for (const auto& entry : jitterSettings) {
+        if (auto value = file.getInt(entry.first)) {
+            if (*value < 0) {
+                throw ConfigError("Valore negativo per " + entry.first);
+            }
+            *entry.second = static_cast<uint32_t>(*value);
+        }
+    }
+    if (config.jitterBuffer.minMs > config.jitterBuffer.maxMs) {
+        throw ConfigError("audio.jitter_min_ms supera audio.jitter_max_ms");
+    }
+    if (config.jitterBuffer.stepMs == 0) {
+        throw ConfigError("audio.jitter_step_ms deve essere positivo");
+    }
+    
+    const std::map<std::string, uint32_t*> specOverrides = {
+        {"spec.jitter_tolerance_ms", &config.spec.jitterToleranceMs},
+        {"spec.latency_budget_ms", &config.spec.latencyBudgetMs},
+        {"spec.buffer_margin_ms", &config.spec.bufferMarginMs},
+        {"spec.default_buffer_ms", &config.spec.defaultBufferMs},
+        {"spec.min_buffer_ms", &config.spec.minBufferMs},
+        {"spec.beacon_timeout_ms", &config.spec.beaconTimeoutMs},
+        {"spec.clock_step_threshold_ms", &config.spec.clockStepThresholdMs},
+        {"spec.clock_max_slew_ppm", &config.spec.clockMaxSlewPpm},
+    };
+    bool specOverridden = false;
+    for (const auto& entry : specOverrides) {
+        if (auto value = file.getInt(entry.first)) {
+            if (*value < 0) {
+                throw ConfigError("Valore negativo per " + entry.first);
+            }
+            *entry.second = static_cast<uint32_t>(*value);
+            specOverridden = true;
+        }
+    }
+    if (auto mode = file.getString("spec.latency_mode")) {
+        auto parsed = latencyModeFromString(*mode);
+        if (!parsed) {
+            throw ConfigError("Modalità di latenza sconosciuta: " + *mode + " (best_effort o strict)");
+        }
+        config.spec.latencyMode = *parsed;
+    }
+    try {
+        config.spec.validate();
+    } catch (const std::invalid_argument& e) {
+        throw ConfigError(e.what());
+    }
+    // In modalità rigorosa si rifiutano i percorsi audio che non possono rientrare nel budget
+    if (config.spec.latencyMode == LatencyMode::Strict) {
+        if (config.a2dpDevice && config.spec.defaultBufferMs + config.a2dpLatencyMs > config.spec.latencyBudgetMs) {
+            throw ConfigError("audio.a2dp_latency_ms supera il budget di latenza in modalità rigorosa");
+        }
+        if (config.intercom.bufferMs > config.spec.latencyBudgetMs) {
+            throw ConfigError("intercom.buffer_ms supera il budget di latenza in modalità rigorosa");
+        }
+    }
+    // I valori scelti da un profilo sono già coerenti: si segnalano solo le sovrascritture esplicite
+    if (specOverridden) {
+        for (const auto& deviation : config.spec.deviations()) {
+            SABER_LOG(Warn, "config", "Parametro fuori specifica " << deviation);
+        }
+    }
+    
+    // I segreti possono essere indiretti tramite ambiente o keystore
+    std::optional<Keystore> keystore;
+    if (auto keystorePath = file.getString("security.keystore")) {
+        keystore = Keystore::open(*keystorePath);
+        config.keystoreFile = *keystorePath;
+    }
+    const Keystore* store = keystore ? &*keystore : nullptr;
+    
+    const std::map<std::string, SecretString*> secrets = {
+        {"security.passphrase", &config.networkPassphrase},
+        {"security.broadcast_code", &config.broadcastCode},
+        {"mqtt.password", &config.mqttPassword},
+        {"security.identity_key", &config.identityKey},
+        {"security.network_key", &config.networkKey},
+    };
+    for (const auto& secret : secrets) {
+        if (auto reference = file.getString(secret.first)) {
+            const std::string& value = *reference;
+            if (value.rfind("env:", 0) != 0 && value.rfind("keystore:", 0) != 0) {
+                SABER_LOG(Warn, "config", secret.first 
+                          << " è salvato in chiaro nel file di configurazione");
+            } else {
+                config.secretReferences[secret.first] = value;
+            }
+            *secret.second = resolveSecret(value, store);
+        }
+    }
+    if (auto username = file.getString("mqtt.username")) {
+        config.mqttUsername = *username;
+    }
+    
+    return config;
+}
+
+} // namespace saber
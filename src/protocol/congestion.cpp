@@ -0,0 +1,45 @@
+#include "congestion.h"
+
+namespace saber {
+
+std::string congestionStateToString(CongestionState state) {
+    return state == CongestionState::Congested ? "congested" : "clear";
+}
+
+// Implementazione di CongestionController
+CongestionController::CongestionController(double enterThreshold, double exitThreshold)
+    : enterThreshold(enterThreshold),
+      exitThreshold(exitThreshold < enterThreshold ? exitThreshold : enterThreshold),
+      state(CongestionState::Clear),
+      utilization(0.0) {
+}
+
+bool CongestionController::update(double airtimeUtilization) {
+    utilization = airtimeUtilization;
+    
+    CongestionState previous = state;
+    if (state == CongestionState::Clear && utilization >= enterThreshold) {
+        state = CongestionState::Congested;
+    } else if (state == CongestionState::Congested && utilization < exitThreshold) {
+        state = CongestionState::Clear;
+    }
+    return state != previous;
+}
+
+CongestionState CongestionController::getState() const {
+    return state;
+}
+
+double CongestionController::getUtilization() const {
+    return utilization;
+}
+
+uint32_t CongestionController::recommendedBitrateKbps(bool isMusicMode) const {
+    // Su rete debole la musica scende al bitrate della voce (sezione 4.1)
+    if (state == CongestionState::Congested) {
+        return isMusicMode ? spec::BITRATE_VOICE_KBPS : spec::BITRATE_VOICE_REDUCED_KBPS;
+    }
+    return isMusicMode ? spec::BITRATE_MUSIC_KBPS : spec::BITRATE_VOICE_KBPS;
+}
+
+} // namespace saber
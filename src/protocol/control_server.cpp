@@ -0,0 +1,369 @@
+#include "control_server.h"
+#include "log.h"
+#include "socket_compat.h"
+
+#include <chrono>
+#include <cstring>
+#include <iostream>
+#include <sstream>
+
+namespace saber {
+
+namespace {
+
+const intptr_t INVALID_SOCKET_HANDLE = -1;
+
+// Limite di una riga di comando, oltre il quale la connessione viene chiusa
+const size_t MAX_LINE_LENGTH = 4096;
+
+} // namespace
+
+// Implementazione di ControlServer
+ControlServer::ControlServer(const std::string& bindAddress, uint16_t port)
+    : bindAddress(bindAddress), port(port), listenSocket(INVALID_SOCKET_HANDLE), running(false) {
+}
+
+ControlServer::~ControlServer() {
+    stop();
+}
+
+void ControlServer::addCommand(const std::string& name, CommandHandler handler) {
+    std::lock_guard<std::mutex> lock(commandsMutex);
+    commands[name] = handler;
+}
+
+void ControlServer::setRequiredScope(const std::string& command, TokenScope scope) {
+    std::lock_guard<std::mutex> lock(commandsMutex);
+    requiredScopes[command] = scope;
+}
+
+void ControlServer::setAuthenticator(Authenticator handler) {
+    std::lock_guard<std::mutex> lock(commandsMutex);
+    authenticator = handler;
+}
+
+void ControlServer::setCommandAuthorizer(CommandAuthorizer authorizer) {
+    std::lock_guard<std::mutex> lock(commandsMutex);
+    commandAuthorizer = authorizer;
+}
+
+TokenScope ControlServer::requiredScope(const std::string& name, const std::vector<std::string>& args) const {
+    if (!args.empty()) {
+        auto it = requiredScopes.find(name + " " + args[0]);
+        if (it != requiredScopes.end()) {
+            return it->second;
+        }
+    }
+    auto it = requiredScopes.find(name);
+    return it != requiredScopes.end() ? it->second : TokenScope::Admin;
+}
+
+std::string ControlServer::execute(const std::string& line, TokenScope scope) {
+    std::istringstream input(line);
+    std::string name;
+    input >> name;
+    if (name.empty()) {
+        return "error comando vuoto";
+    }
+
+    std::vector<std::string> args;
+    std::string arg;
+    while (input >> arg) {
+        args.push_back(arg);
+    }
+
+    CommandHandler handler;
+    TokenScope required = TokenScope::Admin;
+    {
+        std::lock_guard<std::mutex> lock(commandsMutex);
+        auto it = commands.find(name);
+        if (it != commands.end()) {
+            handler = it->second;
+        }
+        required = requiredScope(name, args);
+    }
+    if (!handler) {
+        return "error comando sconosciuto: " + name;
+    }
+    if (static_cast<uint8_t>(scope) < static_cast<uint8_t>(required)) {
+        return "error permesso negato: richiesto ambito " + tokenScopeToString(required);
+    }
+
+    try {
+        std::string result = handler(args);
+        return result.empty() ? "ok" : "ok " + result;
+    } catch (const std::exception& e) {
+        return std::string("error ") + e.what();
+    }
+}
+
+bool ControlServer::start() {
+    if (running) {
+        return true;
+    }
+
+#ifdef SABER_MINIMAL_SINK
+    // Un sink minimale riceve i comandi solo dalla rete mesh
+    SABER_LOG(Error, "control", "Socket di controllo non disponibile nella build minimal-sink");
+    return false;
+#else
+#ifdef _WIN32
+    WSADATA wsaData;
+    if (WSAStartup(MAKEWORD(2, 2), &wsaData) != 0) {
+        SABER_LOG(Error, "control", "Impossibile inizializzare Winsock");
+        return false;
+    }
+#endif
+
+    auto sock = socket(AF_INET, SOCK_STREAM, 0);
+    if (sock < 0) {
+        SABER_LOG(Error, "control", "Impossibile creare il socket di controllo");
+        return false;
+    }
+
+    int reuse = 1;
+    setsockopt(sock, SOL_SOCKET, SO_REUSEADDR, reinterpret_cast<const char*>(&reuse), sizeof(reuse));
+
+    sockaddr_in addr;
+    std::memset(&addr, 0, sizeof(addr));
+    addr.sin_family = AF_INET;
+    addr.sin_port = htons(port);
+    if (inet_pton(AF_INET, bindAddress.c_str(), &addr.sin_addr) != 1) {
+        SABER_LOG(Error, "control", "Indirizzo del socket di controllo non valido: " << bindAddress);
+        SABER_CLOSE_SOCKET(sock);
+        return false;
+    }
+
+    if (bind(sock, reinterpret_cast<sockaddr*>(&addr), sizeof(addr)) != 0 || listen(sock, 4) != 0) {
+        SABER_LOG(Error, "control", "Impossibile mettersi in ascolto su " << bindAddress << ":" << port);
+        SABER_CLOSE_SOCKET(sock);
+        return false;
+    }
+
+    listenSocket = static_cast<intptr_t>(sock);
+    running = true;
+    serverThread = std::make_unique<std::thread>(&ControlServer::runServerLoop, this);
+    return true;
+#endif
+}
+
+void ControlServer::stop() {
+    if (!running.exchange(false)) {
+        return;
+    }
+
+    if (serverThread && serverThread->joinable()) {
+        serverThread->join();
+    }
+
+    SABER_CLOSE_SOCKET(listenSocket);
+    listenSocket = INVALID_SOCKET_HANDLE;
+
+#ifdef _WIN32
+    WSACleanup();
+#endif
+}
+
+bool ControlServer::isRunning() const {
+    return running;
+}
+
+#ifndef SABER_MINIMAL_SINK
+void ControlServer::runServerLoop() {
+    while (running) {
+        // Attesa con timeout per poter osservare il flag di arresto
+        fd_set readSet;
+        FD_ZERO(&readSet);
+        FD_SET(listenSocket, &readSet);
+        timeval timeout{0, 200000};
+
+        int ready = select(static_cast<int>(listenSocket) + 1, &readSet, nullptr, nullptr, &timeout);
+        if (ready <= 0) {
+            continue;
+        }
+
+        sockaddr_in clientAddr;
+        socklen_t clientLen = sizeof(clientAddr);
+        auto client = accept(listenSocket, reinterpret_cast<sockaddr*>(&clientAddr), &clientLen);
+        if (client < 0) {
+            continue;
+        }
+
+        reapWorkers(false);
+        if (workers.size() >= CONTROL_MAX_CONNECTIONS) {
+            SABER_LOG(Warn, "control", "Connessione rifiutata: troppe connessioni aperte");
+            std::string response = "error troppe connessioni\n";
+            send(client, response.data(), static_cast<int>(response.size()), 0);
+            SABER_CLOSE_SOCKET(client);
+            continue;
+        }
+
+        // Ogni connessione ha il proprio thread: un client lento non blocca gli altri
+        auto finished = std::make_shared<std::atomic<bool>>(false);
+        std::thread thread([this, client, finished]() {
+            handleConnection(static_cast<intptr_t>(client));
+            SABER_CLOSE_SOCKET(client);
+            *finished = true;
+        });
+        workers.push_back({std::move(thread), finished});
+    }
+    reapWorkers(true);
+}
+
+void ControlServer::reapWorkers(bool all) {
+    for (auto it = workers.begin(); it != workers.end();) {
+        if (all || *it->finished) {
+            it->thread.join();
+            it = workers.erase(it);
+        } else {
+            ++it;
+        }
+    }
+}
+
+std::string ControlServer::handleLine(const std::string& line, std::string& token) {
+    Authenticator verify;
+    {
+        std::lock_guard<std::mutex> lock(commandsMutex);
+        verify = authenticator;
+    }
+    if (!verify) {
+        if (auto denied = checkCommandAuthorization(line, token)) {
+            return "error non autorizzato: " + *denied;
+        }
+        return execute(line);
+    }
+
+    // Il token presentato con "auth" vale per il resto della connessione
+    std::istringstream input(line);
+    std::string name;
+    input >> name;
+    if (name == "auth") {
+        std::string candidate;
+        input >> candidate;
+        auto scope = verify(candidate);
+        if (!scope) {
+            token.clear();
+            return "error token non valido";
+        }
+        token = candidate;
+        return "ok " + tokenScopeToString(*scope);
+    }
+
+    if (token.empty()) {
+        return "error autenticazione richiesta: auth <token>";
+    }
+    auto scope = verify(token);
+    if (!scope) {
+        token.clear();
+        return "error token scaduto o revocato";
+    }
+    // Un token senza l'ambito richiesto viene respinto da execute() senza consultare la politica
+    if (*scope == TokenScope::Admin) {
+        if (auto denied = checkCommandAuthorization(line, token)) {
+            return "error non autorizzato: " + *denied;
+        }
+    }
+    return execute(line, *scope);
+}
+
+std::optional<std::string> ControlServer::checkCommandAuthorization(const std::string& line, 
+                                                                   const std::string& token) const {
+    std::istringstream input(line);
+    std::string name;
+    input >> name;
+    std::vector<std::string> args;
+    std::string arg;
+    while (input >> arg) {
+        args.push_back(arg);
+    }
+
+    CommandAuthorizer authorize;
+    {
+        std::lock_guard<std::mutex> lock(commandsMutex);
+        if (!commandAuthorizer || commands.count(name) == 0 || requiredScope(name, args) != TokenScope::Admin) {
+            return std::nullopt;
+        }
+        authorize = commandAuthorizer;
+    }
+    return authorize(line, token);
+}
+
+void ControlServer::handleConnection(intptr_t clientSocket) {
+    // Anche l'invio ha un limite: un client che non legge le risposte non tiene occupato il thread
+#ifdef _WIN32
+    DWORD sendTimeout = CONTROL_SEND_TIMEOUT_MS;
+#else
+    timeval sendTimeout{CONTROL_SEND_TIMEOUT_MS / 1000, (CONTROL_SEND_TIMEOUT_MS % 1000) * 1000};
+#endif
+    setsockopt(clientSocket, SOL_SOCKET, SO_SNDTIMEO, reinterpret_cast<const char*>(&sendTimeout),
+               sizeof(sendTimeout));
+
+    bool requiresAuth;
+    {
+        std::lock_guard<std::mutex> lock(commandsMutex);
+        requiresAuth = static_cast<bool>(authenticator);
+    }
+
+    std::string token;
+    std::string pending;
+    char buffer[512];
+    auto connectedAt = std::chrono::steady_clock::now();
+    auto lastActivity = connectedAt;
+
+    while (running) {
+        // Chi non presenta un token in tempo o resta inattivo libera il posto
+        auto now = std::chrono::steady_clock::now();
+        if (requiresAuth && token.empty() && now - connectedAt > std::chrono::milliseconds(CONTROL_AUTH_TIMEOUT_MS)) {
+            SABER_LOG(Debug, "control", "Connessione chiusa senza autenticazione");
+            return;
+        }
+        if (now - lastActivity > std::chrono::milliseconds(CONTROL_IDLE_TIMEOUT_MS)) {
+            SABER_LOG(Debug, "control", "Connessione inattiva chiusa");
+            return;
+        }
+
+        // Timeout sulla lettura per non bloccare l'arresto con un client inattivo
+        fd_set readSet;
+        FD_ZERO(&readSet);
+        FD_SET(clientSocket, &readSet);
+        timeval timeout{0, 200000};
+
+        int ready = select(static_cast<int>(clientSocket) + 1, &readSet, nullptr, nullptr, &timeout);
+        if (ready < 0) {
+            return;
+        }
+        if (ready == 0) {
+            continue;
+        }
+
+        auto received = recv(clientSocket, buffer, sizeof(buffer), 0);
+        if (received <= 0) {
+            return;
+        }
+        pending.append(buffer, static_cast<size_t>(received));
+        lastActivity = std::chrono::steady_clock::now();
+
+        size_t newline;
+        while ((newline = pending.find('\n')) != std::string::npos) {
+            std::string line = pending.substr(0, newline);
+            pending.erase(0, newline + 1);
+            if (!line.empty() && line.back() == '\r') {
+                line.pop_back();
+            }
+
+            std::string response = handleLine(line, token) + "\n";
+            if (send(clientSocket, response.data(), static_cast<int>(response.size()), 0) 
+                != static_cast<int>(response.size())) {
+                return;
+            }
+        }
+
+        if (pending.size() > MAX_LINE_LENGTH) {
+            return;
+        }
+    }
+}
+#endif
+
+} // namespace saber
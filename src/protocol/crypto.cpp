@@ -1,15 +1,29 @@
 #include "crypto.h"
+#include "log.h"
+#include "util.h"
 
+#include <algorithm>
 #include <chrono>
+#include <cstdio>
 #include <cstring>
+#include <filesystem>
+#include <fstream>
+#include <iostream>
 #include <random>
+#include <sstream>
 #include <stdexcept>
 
+#ifdef _WIN32
+#include <io.h>
+#else
+#include <fcntl.h>
+#include <unistd.h>
+#endif
+
 // Utilizziamo OpenSSL per le operazioni crittografiche
 #include <openssl/aes.h>
 #include <openssl/evp.h>
 #include <openssl/hmac.h>
-#include <openssl/rand.h>
 #include <openssl/sha.h>
 
 // Utilizziamo libsodium per Ed25519 e X25519
@@ -17,6 +31,271 @@
 
 namespace saber {
 
+namespace {
+
+// Versione del formato dei token di sicurezza
+const uint8_t SECURITY_TOKEN_VERSION = 2;
+
+// Byte casuali dell'identificatore di un token
+const size_t TOKEN_ID_BYTES = 16;
+
+// Intestazione del token: versione, ID, ambito, emissione e scadenza
+const size_t TOKEN_HEADER_BYTES = 1 + TOKEN_ID_BYTES + 1 + 8 + 8;
+
+// Codifica little-endian di un intero a 64 bit
+void appendU64(std::vector<uint8_t>& out, uint64_t value) {
+    for (int i = 0; i < 8; ++i) {
+        out.push_back(static_cast<uint8_t>(value >> (8 * i)));
+    }
+}
+
+// Decodifica little-endian di un intero a 64 bit
+uint64_t readU64(const uint8_t* data) {
+    uint64_t value = 0;
+    for (int i = 0; i < 8; ++i) {
+        value |= static_cast<uint64_t>(data[i]) << (8 * i);
+    }
+    return value;
+}
+
+/**
+ * @brief Lettore dei campi little-endian di un token
+ *
+ * Una lettura oltre la fine del contenuto rende il token non valido
+ * invece di uscire dal buffer.
+ */
+class TokenReader {
+public:
+    explicit TokenReader(const std::vector<uint8_t>& bytes)
+        : bytes(bytes), offset(0) {}
+    
+    const uint8_t* take(size_t size) {
+        if (bytes.size() - offset < size) {
+            throw CryptoError(CryptoError::Type::Verification, "Token troncato");
+        }
+        const uint8_t* field = bytes.data() + offset;
+        offset += size;
+        return field;
+    }
+    
+    uint8_t getU8() {
+        return *take(1);
+    }
+    
+    uint64_t getU64() {
+        return readU64(take(8));
+    }
+    
+    std::string getRest() {
+        std::string rest(bytes.begin() + offset, bytes.end());
+        offset = bytes.size();
+        return rest;
+    }
+    
+private:
+    const std::vector<uint8_t>& bytes;
+    size_t offset;
+};
+
+// Forza su disco i dati del file, non solo nella cache del sistema
+bool syncFile(std::FILE* file) {
+    if (std::fflush(file) != 0) {
+        return false;
+    }
+#ifdef _WIN32
+    return _commit(_fileno(file)) == 0;
+#else
+    return fsync(fileno(file)) == 0;
+#endif
+}
+
+// Su POSIX la rinomina è persistente solo dopo la sincronizzazione della directory
+void syncDirectory(const std::string& path) {
+#ifndef _WIN32
+    auto directory = std::filesystem::path(path).parent_path();
+    int fd = open(directory.empty() ? "." : directory.c_str(), O_RDONLY);
+    if (fd >= 0) {
+        fsync(fd);
+        close(fd);
+    }
+#else
+    (void)path;
+#endif
+}
+
+} // namespace
+
+std::string tokenScopeToString(TokenScope scope) {
+    switch (scope) {
+        case TokenScope::ReadOnly: return "read-only";
+        case TokenScope::Admin: return "admin";
+        default: return "unknown";
+    }
+}
+
+std::optional<TokenScope> tokenScopeFromString(const std::string& value) {
+    if (value == "read-only") return TokenScope::ReadOnly;
+    if (value == "admin") return TokenScope::Admin;
+    return std::nullopt;
+}
+
+// Implementazione di SessionManager
+SessionManager::~SessionManager() {
+    clear();
+}
+
+std::optional<std::array<uint8_t, 32>> SessionManager::find(const std::string& nodeId, uint32_t epoch,
+                                                            const std::vector<uint8_t>& peerKey) {
+    auto it = sessions.find(nodeId);
+    if (it == sessions.end() || it->second.epoch != epoch || it->second.peerKey != peerKey) {
+        return std::nullopt;
+    }
+    stats.cacheHits++;
+    return it->second.key;
+}
+
+void SessionManager::store(const std::string& nodeId, uint32_t epoch, const std::vector<uint8_t>& peerKey,
+                           const std::array<uint8_t, 32>& key) {
+    forget(nodeId);
+    sessions.emplace(nodeId, Session{key, epoch, peerKey});
+    stats.derivations++;
+}
+
+bool SessionManager::forget(const std::string& nodeId) {
+    auto it = sessions.find(nodeId);
+    if (it == sessions.end()) {
+        return false;
+    }
+    sodium_memzero(it->second.key.data(), it->second.key.size());
+    sessions.erase(it);
+    return true;
+}
+
+void SessionManager::clear() {
+    for (auto& entry : sessions) {
+        sodium_memzero(entry.second.key.data(), entry.second.key.size());
+    }
+    sessions.clear();
+}
+
+std::vector<std::string> SessionManager::getPeers() const {
+    std::vector<std::string> peers;
+    for (const auto& entry : sessions) {
+        peers.push_back(entry.first);
+    }
+    return peers;
+}
+
+SessionStats SessionManager::getStats() const {
+    SessionStats result = stats;
+    result.sessions = sessions.size();
+    return result;
+}
+
+// Implementazione di NonceCounterStore
+NonceCounterStore::NonceCounterStore(const std::string& path, uint64_t block) : path(path), block(block) {
+    if (block == 0) {
+        throw std::invalid_argument("Il blocco di contatori riservati deve contenere almeno un nonce");
+    }
+    
+    std::ifstream file(path);
+    if (!file) {
+        if (std::filesystem::exists(path)) {
+            throw CryptoError(CryptoError::Type::Encryption, "Contatori dei nonce illeggibili in " + path);
+        }
+        return;
+    }
+    // Un file malformato non viene ignorato: ripartire da zero riuserebbe i nonce
+    std::string line;
+    size_t number = 0;
+    while (std::getline(file, line)) {
+        number++;
+        if (line.empty()) {
+            continue;
+        }
+        std::istringstream fields(line);
+        std::string keyId;
+        std::string salt;
+        uint64_t limit = 0;
+        std::string rest;
+        if (!(fields >> keyId >> salt >> limit) || (fields >> rest) || salt.size() != 8 
+            || salt.find_first_not_of("0123456789abcdef") != std::string::npos) {
+            throw CryptoError(CryptoError::Type::Encryption, 
+                              "Riga " + std::to_string(number) + " non valida nei contatori dei nonce " + path);
+        }
+        Entry entry{{}, limit};
+        for (size_t i = 0; i < entry.salt.size(); ++i) {
+            entry.salt[i] = static_cast<uint8_t>(std::stoul(salt.substr(i * 2, 2), nullptr, 16));
+        }
+        entries[keyId] = entry;
+    }
+}
+
+NonceCounterStore::Reservation NonceCounterStore::reserve(const std::string& keyId, RandomSource& rng) {
+    auto it = entries.find(keyId);
+    Entry previous{};
+    if (it != entries.end()) {
+        previous = it->second;
+    } else {
+        // Il contatore zero non viene mai usato, come senza archivio
+        rng.fill(previous.salt.data(), previous.salt.size());
+        previous.limit = 1;
+    }
+    if (previous.limit == UINT64_MAX) {
+        throw CryptoError(CryptoError::Type::Encryption, "Contatori dei nonce esauriti per la chiave " + keyId);
+    }
+    
+    Reservation reservation{previous.salt, previous.limit, 
+                            previous.limit + std::min(block, UINT64_MAX - previous.limit)};
+    entries[keyId] = Entry{reservation.salt, reservation.limit};
+    if (!save()) {
+        // Il blocco non è stato salvato: resta valido il limite precedente
+        if (it != entries.end()) {
+            entries[keyId] = previous;
+        } else {
+            entries.erase(keyId);
+        }
+        throw CryptoError(CryptoError::Type::Encryption, "Impossibile salvare i contatori dei nonce in " + path);
+    }
+    return reservation;
+}
+
+std::optional<uint64_t> NonceCounterStore::getLimit(const std::string& keyId) const {
+    auto it = entries.find(keyId);
+    if (it == entries.end()) {
+        return std::nullopt;
+    }
+    return it->second.limit;
+}
+
+const std::string& NonceCounterStore::getPath() const {
+    return path;
+}
+
+bool NonceCounterStore::save() const {
+    std::ostringstream content;
+    for (const auto& entry : entries) {
+        content << entry.first << " " << toHex(entry.second.salt.data(), entry.second.salt.size()) << " " 
+                << entry.second.limit << "\n";
+    }
+    std::string text = content.str();
+    
+    // Il nuovo limite è su disco prima della rinomina: mai un file troncato al suo posto
+    std::string tmpPath = path + ".tmp";
+    std::FILE* file = std::fopen(tmpPath.c_str(), "wb");
+    if (!file) {
+        return false;
+    }
+    bool written = std::fwrite(text.data(), 1, text.size(), file) == text.size() && syncFile(file);
+    std::fclose(file);
+    if (!written || std::rename(tmpPath.c_str(), path.c_str()) != 0) {
+        std::remove(tmpPath.c_str());
+        return false;
+    }
+    syncDirectory(path);
+    return true;
+}
+
 // Implementazione di CryptoError
 CryptoError::CryptoError(Type type, const std::string& message)
     : std::runtime_error(message), type(type) {}
@@ -26,9 +305,10 @@ CryptoError::Type CryptoError::getType() const {
 }
 
 // Implementazione di MeshCrypto
-MeshCrypto::MeshCrypto() 
+MeshCrypto::MeshCrypto(std::shared_ptr<RandomSource> rng) 
     : signingKeys(std::make_unique<SigningKeys>()),
       exchangeKeys(std::make_unique<ExchangeKeys>()),
+      rng(rng ? rng : defaultRandomSource()),
       nonceCounter(0) {
     
     // Inizializza libsodium se necessario
@@ -37,42 +317,136 @@ MeshCrypto::MeshCrypto()
     }
     
     // Genera una chiave casuale per la rete
-    RAND_bytes(networkKey.data(), networkKey.size());
+    this->rng->fill(networkKey.data(), networkKey.size());
     
-    // Genera le chiavi di firma Ed25519
-    crypto_sign_keypair(signingKeys->publicKey, signingKeys->secretKey);
+    // Genera le chiavi di firma Ed25519 da una seed della sorgente casuale
+    std::array<uint8_t, 32> seed;
+    this->rng->fill(seed.data(), seed.size());
+    crypto_sign_seed_keypair(signingKeys->publicKey, signingKeys->secretKey, seed.data());
+    sodium_memzero(seed.data(), seed.size());
     
-    // Genera le chiavi per lo scambio X25519
-    crypto_box_keypair(exchangeKeys->publicKey, exchangeKeys->secretKey);
+    // Le chiavi per lo scambio X25519 seguono l'identità
+    deriveExchangeKeys();
+    
+    this->rng->fill(nonceSalt.data(), nonceSalt.size());
 }
 
-MeshCrypto MeshCrypto::withNetworkKey(const std::array<uint8_t, 32>& networkKey) {
-    MeshCrypto crypto;
+MeshCrypto MeshCrypto::withNetworkKey(const std::array<uint8_t, 32>& networkKey,
+                                      std::shared_ptr<RandomSource> rng) {
+    MeshCrypto crypto(rng);
     crypto.networkKey = networkKey;
     return crypto;
 }
 
-uint64_t MeshCrypto::currentTimestamp() {
+void MeshCrypto::setNetworkKey(const std::array<uint8_t, 32>& key) {
+    networkKey = key;
+    keyEpoch++;
+    if (nonceStore) {
+        reserveNonces();
+    }
+}
+
+std::array<uint8_t, 32> MeshCrypto::getNetworkKey() const {
+    return networkKey;
+}
+
+uint32_t MeshCrypto::getKeyEpoch() const {
+    return keyEpoch;
+}
+
+void MeshCrypto::deriveExchangeKeys() {
+    crypto_sign_ed25519_sk_to_curve25519(exchangeKeys->secretKey, signingKeys->secretKey);
+    crypto_scalarmult_base(exchangeKeys->publicKey, exchangeKeys->secretKey);
+}
+
+uint64_t MeshCrypto::currentTimestamp() const {
+    if (clock) {
+        return clock();
+    }
     auto now = std::chrono::system_clock::now();
     auto duration = now.time_since_epoch();
     return std::chrono::duration_cast<std::chrono::milliseconds>(duration).count();
 }
 
+void MeshCrypto::setClock(Clock clock) {
+    this->clock = std::move(clock);
+}
+
+void MeshCrypto::setNonceStore(std::shared_ptr<NonceCounterStore> store) {
+    nonceStore = std::move(store);
+    if (nonceStore) {
+        reserveNonces();
+    } else {
+        // Senza archivio un nuovo prefisso casuale separa i nonce da quelli già riservati
+        rng->fill(nonceSalt.data(), nonceSalt.size());
+        nonceCounter = 0;
+        nonceLimit = UINT64_MAX;
+    }
+}
+
+std::shared_ptr<NonceCounterStore> MeshCrypto::getNonceStore() const {
+    return nonceStore;
+}
+
+uint64_t MeshCrypto::getNonceCounter() const {
+    return nonceCounter;
+}
+
+void MeshCrypto::reserveNonces() {
+    // Il key-ID è derivato con un'etichetta: nel file non compare un'impronta riusata altrove
+    std::vector<uint8_t> material{'S', 'A', 'B', 'E', 'R', '-', 'N', 'O', 'N', 'C', 'E'};
+    material.insert(material.end(), networkKey.begin(), networkKey.end());
+    std::string id = keyId(material);
+    sodium_memzero(material.data(), material.size());
+    
+    auto reservation = nonceStore->reserve(id, *rng);
+    nonceSalt = reservation.salt;
+    nonceCounter = reservation.first - 1;
+    nonceLimit = reservation.limit;
+    SABER_LOG(Debug, "security", "Riservati i nonce " << reservation.first << "-" << reservation.limit - 1 
+              << " della chiave " << id);
+}
+
 std::array<uint8_t, 12> MeshCrypto::generateNonce() {
+    if (nonceCounter == UINT64_MAX) {
+        throw CryptoError(CryptoError::Type::Encryption, "Contatori dei nonce esauriti");
+    }
+    if (nonceStore && nonceCounter + 1 >= nonceLimit) {
+        reserveNonces();
+    }
     nonceCounter++;
-    uint64_t timestamp = currentTimestamp();
     
     std::array<uint8_t, 12> nonce;
-    std::memcpy(nonce.data(), &timestamp, 8);
-    std::memcpy(nonce.data() + 8, &nonceCounter, 4);
+    std::memcpy(nonce.data(), nonceSalt.data(), nonceSalt.size());
+    for (int i = 0; i < 8; ++i) {
+        nonce[4 + i] = static_cast<uint8_t>(nonceCounter >> (8 * i));
+    }
     
     return nonce;
 }
 
-std::vector<uint8_t> MeshCrypto::encrypt(const std::vector<uint8_t>& payload) {
+std::vector<uint8_t> MeshCrypto::encrypt(const std::vector<uint8_t>& payload, const std::vector<uint8_t>& aad) {
+    return encryptWithKey(networkKey, payload, aad);
+}
+
+std::vector<uint8_t> MeshCrypto::encryptWithRandomNonce(const std::vector<uint8_t>& payload,
+                                                        const std::vector<uint8_t>& aad) {
+    std::array<uint8_t, 12> nonce;
+    rng->fill(nonce.data(), nonce.size());
+    return sealWithNonce(networkKey, nonce, payload, aad);
+}
+
+std::vector<uint8_t> MeshCrypto::encryptWithKey(const std::array<uint8_t, 32>& key,
+                                                const std::vector<uint8_t>& payload,
+                                                const std::vector<uint8_t>& aad) {
     // Genera un nonce unico
-    auto nonce = generateNonce();
-    
+    return sealWithNonce(key, generateNonce(), payload, aad);
+}
+
+std::vector<uint8_t> MeshCrypto::sealWithNonce(const std::array<uint8_t, 32>& key,
+                                               const std::array<uint8_t, 12>& nonce,
+                                               const std::vector<uint8_t>& payload,
+                                               const std::vector<uint8_t>& aad) {
     // Prepara il contesto EVP per AES-GCM
     EVP_CIPHER_CTX *ctx = EVP_CIPHER_CTX_new();
     if (!ctx) {
@@ -80,14 +454,20 @@ std::vector<uint8_t> MeshCrypto::encrypt(const std::vector<uint8_t>& payload) {
     }
     
     // Inizializza la cifratura
-    if (EVP_EncryptInit_ex(ctx, EVP_aes_256_gcm(), nullptr, networkKey.data(), nonce.data()) != 1) {
+    if (EVP_EncryptInit_ex(ctx, EVP_aes_256_gcm(), nullptr, key.data(), nonce.data()) != 1) {
         EVP_CIPHER_CTX_free(ctx);
         throw CryptoError(CryptoError::Type::Encryption, "Impossibile inizializzare la cifratura");
     }
     
+    // I dati autenticati entrano nel tag senza essere cifrati
+    int len = 0;
+    if (!aad.empty() && EVP_EncryptUpdate(ctx, nullptr, &len, aad.data(), aad.size()) != 1) {
+        EVP_CIPHER_CTX_free(ctx);
+        throw CryptoError(CryptoError::Type::Encryption, "Impossibile autenticare i dati associati");
+    }
+    
     // Alloca spazio per il testo cifrato
     std::vector<uint8_t> ciphertext(payload.size() + EVP_CIPHER_CTX_block_size(ctx));
-    int len = 0;
     
     // Cifra il payload
     if (EVP_EncryptUpdate(ctx, ciphertext.data(), &len, payload.data(), payload.size()) != 1) {
@@ -131,7 +511,14 @@ std::vector<uint8_t> MeshCrypto::encrypt(const std::vector<uint8_t>& payload) {
     return result;
 }
 
-std::vector<uint8_t> MeshCrypto::decrypt(const std::vector<uint8_t>& encryptedData) {
+std::vector<uint8_t> MeshCrypto::decrypt(const std::vector<uint8_t>& encryptedData,
+                                         const std::vector<uint8_t>& aad) {
+    return decryptWithKey(networkKey, encryptedData, aad);
+}
+
+std::vector<uint8_t> MeshCrypto::decryptWithKey(const std::array<uint8_t, 32>& key,
+                                                const std::vector<uint8_t>& encryptedData,
+                                                const std::vector<uint8_t>& aad) {
     if (encryptedData.size() < 12 + 16) { // nonce + tag minimo
         throw CryptoError(CryptoError::Type::Decryption, "Dati cifrati troppo corti");
     }
@@ -153,7 +540,7 @@ std::vector<uint8_t> MeshCrypto::decrypt(const std::vector<uint8_t>& encryptedDa
     }
     
     // Inizializza la decifratura
-    if (EVP_DecryptInit_ex(ctx, EVP_aes_256_gcm(), nullptr, networkKey.data(), nonce.data()) != 1) {
+    if (EVP_DecryptInit_ex(ctx, EVP_aes_256_gcm(), nullptr, key.data(), nonce.data()) != 1) {
         EVP_CIPHER_CTX_free(ctx);
         throw CryptoError(CryptoError::Type::Decryption, "Impossibile inizializzare la decifratura");
     }
@@ -164,9 +551,14 @@ std::vector<uint8_t> MeshCrypto::decrypt(const std::vector<uint8_t>& encryptedDa
         throw CryptoError(CryptoError::Type::Decryption, "Impossibile impostare il tag di autenticazione");
     }
     
+    int len = 0;
+    if (!aad.empty() && EVP_DecryptUpdate(ctx, nullptr, &len, aad.data(), aad.size()) != 1) {
+        EVP_CIPHER_CTX_free(ctx);
+        throw CryptoError(CryptoError::Type::Decryption, "Impossibile autenticare i dati associati");
+    }
+    
     // Alloca spazio per il testo in chiaro
     std::vector<uint8_t> plaintext(ciphertext.size());
-    int len = 0;
     
     // Decifra il ciphertext
     if (EVP_DecryptUpdate(ctx, plaintext.data(), &len, ciphertext.data(), ciphertext.size()) != 1) {
@@ -190,6 +582,153 @@ std::vector<uint8_t> MeshCrypto::decrypt(const std::vector<uint8_t>& encryptedDa
     return plaintext;
 }
 
+std::vector<uint8_t> MeshCrypto::decryptFrom(const std::string& senderId,
+                                             const std::vector<uint8_t>& encryptedData,
+                                             const std::vector<uint8_t>& aad) {
+    SABER_LOG_SPAN("security", "decrypt", "sender=" << senderId << " bytes=" << encryptedData.size());
+    // Solo un pacchetto autentico può far avanzare la finestra
+    auto plaintext = decrypt(encryptedData, aad);
+    rejectReplay(senderId, encryptedData);
+    return plaintext;
+}
+
+std::vector<uint8_t> MeshCrypto::encryptFor(const std::string& nodeId, const std::vector<uint8_t>& payload,
+                                            const std::vector<uint8_t>& aad) {
+    return encryptWithKey(sessionKey(nodeId), payload, aad);
+}
+
+std::vector<uint8_t> MeshCrypto::decryptFromPeer(const std::string& nodeId,
+                                                 const std::vector<uint8_t>& encryptedData,
+                                                 const std::vector<uint8_t>& aad) {
+    SABER_LOG_SPAN("security", "decrypt", "sender=" << nodeId << " bytes=" << encryptedData.size() << " session");
+    auto plaintext = decryptWithKey(sessionKey(nodeId), encryptedData, aad);
+    rejectReplay(nodeId, encryptedData);
+    return plaintext;
+}
+
+void MeshCrypto::rejectReplay(const std::string& senderId, const std::vector<uint8_t>& encryptedData) {
+    std::array<uint8_t, 12> nonce;
+    std::copy_n(encryptedData.begin(), nonce.size(), nonce.begin());
+    
+    if (auto reason = checkReplay(senderId, nonce)) {
+        replayRejections++;
+        emitSecurityEvent({
+            SecurityEvent::Type::ReplayRejected,
+            senderId,
+            {},
+            *reason,
+            currentTimestamp()
+        });
+        throw CryptoError(CryptoError::Type::Replay, "Pacchetto ripetuto da " + senderId + ": " + *reason);
+    }
+}
+
+bool MeshCrypto::hasSessionPeer(const std::string& nodeId) const {
+    return knownPublicKeys.count(nodeId) > 0 && !isQuarantined(nodeId);
+}
+
+std::array<uint8_t, 32> MeshCrypto::sessionKey(const std::string& nodeId) {
+    auto peer = knownPublicKeys.find(nodeId);
+    if (peer == knownPublicKeys.end() || isQuarantined(nodeId)) {
+        throw CryptoError(CryptoError::Type::KeyExchange, "Nessuna chiave d'identità attendibile per " + nodeId);
+    }
+    const std::vector<uint8_t>& peerKey = peer->second;
+    if (auto cached = sessions.find(nodeId, keyEpoch, peerKey)) {
+        return *cached;
+    }
+    
+    std::vector<uint8_t> peerExchange(crypto_scalarmult_BYTES);
+    if (peerKey.size() != crypto_sign_PUBLICKEYBYTES 
+        || crypto_sign_ed25519_pk_to_curve25519(peerExchange.data(), peerKey.data()) != 0) {
+        throw CryptoError(CryptoError::Type::KeyExchange, "Chiave d'identità non valida per " + nodeId);
+    }
+    std::array<uint8_t, 32> shared = keyExchange(peerExchange);
+    
+    // Le identità in ordine danno la stessa chiave ai due lati; la chiave di rete la lega all'epoca
+    std::vector<uint8_t> localKey = getPublicKey();
+    const std::vector<uint8_t>& low = std::min(localKey, peerKey);
+    const std::vector<uint8_t>& high = std::max(localKey, peerKey);
+    std::string domain = "SABER-SESSION";
+    std::vector<uint8_t> info(domain.begin(), domain.end());
+    info.insert(info.end(), networkKey.begin(), networkKey.end());
+    info.insert(info.end(), low.begin(), low.end());
+    info.insert(info.end(), high.begin(), high.end());
+    
+    std::array<uint8_t, 32> key;
+    unsigned int keyLen = key.size();
+    HMAC(EVP_sha256(), shared.data(), shared.size(), info.data(), info.size(), key.data(), &keyLen);
+    sodium_memzero(shared.data(), shared.size());
+    
+    sessions.store(nodeId, keyEpoch, peerKey, key);
+    SABER_LOG(Debug, "security", "Chiave di sessione derivata per " << nodeId << " (epoca " << keyEpoch << ")");
+    return key;
+}
+
+SessionManager& MeshCrypto::getSessions() {
+    return sessions;
+}
+
+const SessionManager& MeshCrypto::getSessions() const {
+    return sessions;
+}
+
+std::optional<std::string> MeshCrypto::checkReplay(const std::string& senderId,
+                                                   const std::array<uint8_t, 12>& nonce) {
+    std::array<uint8_t, 4> salt;
+    std::copy_n(nonce.begin(), salt.size(), salt.begin());
+    uint64_t counter = readU64(nonce.data() + salt.size());
+    
+    auto it = replayStates.find(senderId);
+    if (it == replayStates.end()) {
+        it = replayStates.emplace(senderId, ReplayState{}).first;
+        it->second.salt = salt;
+    } else if (it->second.salt != salt) {
+        auto& retired = it->second.retiredSalts;
+        if (std::find(retired.begin(), retired.end(), salt) != retired.end()) {
+            return "nonce di un'istanza precedente del mittente";
+        }
+        // Il mittente è ripartito con un nuovo prefisso
+        retired.push_back(it->second.salt);
+        if (retired.size() > REPLAY_RETIRED_SALTS) {
+            retired.erase(retired.begin());
+        }
+        it->second.salt = salt;
+        it->second.highest = 0;
+        it->second.seen.clear();
+    }
+    
+    ReplayState& state = it->second;
+    if (state.highest >= replayWindow && counter <= state.highest - replayWindow) {
+        return "nonce " + std::to_string(counter) + " oltre la finestra di " + std::to_string(replayWindow);
+    }
+    if (!state.seen.insert(counter).second) {
+        return "nonce " + std::to_string(counter) + " già ricevuto";
+    }
+    
+    if (counter > state.highest) {
+        state.highest = counter;
+        if (state.highest >= replayWindow) {
+            state.seen.erase(state.seen.begin(), state.seen.upper_bound(state.highest - replayWindow));
+        }
+    }
+    return std::nullopt;
+}
+
+void MeshCrypto::setReplayWindow(uint32_t nonces) {
+    if (nonces == 0) {
+        throw std::invalid_argument("La finestra anti-replay deve contenere almeno un nonce");
+    }
+    replayWindow = nonces;
+}
+
+uint32_t MeshCrypto::getReplayWindow() const {
+    return replayWindow;
+}
+
+uint64_t MeshCrypto::getReplayRejections() const {
+    return replayRejections;
+}
+
 std::vector<uint8_t> MeshCrypto::sign(const std::vector<uint8_t>& message) {
     std::vector<uint8_t> signature(crypto_sign_BYTES);
     unsigned long long signatureLen;
@@ -206,6 +745,12 @@ std::vector<uint8_t> MeshCrypto::sign(const std::vector<uint8_t>& message) {
 
 bool MeshCrypto::verify(const std::string& nodeId, const std::vector<uint8_t>& message, 
                        const std::vector<uint8_t>& signature) {
+    SABER_LOG_SPAN("security", "verify", "node=" << nodeId << " bytes=" << message.size());
+    if (isQuarantined(nodeId)) {
+        throw CryptoError(CryptoError::Type::Verification, 
+                         "Nodo in quarantena per conflitto di chiavi: " + nodeId);
+    }
+    
     auto it = knownPublicKeys.find(nodeId);
     if (it == knownPublicKeys.end()) {
         throw CryptoError(CryptoError::Type::Verification, 
@@ -224,13 +769,149 @@ bool MeshCrypto::verify(const std::string& nodeId, const std::vector<uint8_t>& m
                          "Formato firma non valido");
     }
     
-    return crypto_sign_verify_detached(signature.data(), 
-                                      message.data(), message.size(), 
+    bool valid = crypto_sign_verify_detached(signature.data(), 
+                                             message.data(), message.size(), 
+                                             publicKey.data()) == 0;
+    if (!valid) {
+        SABER_LOG(Debug, "security", "Firma non valida per la chiave " << keyId(publicKey));
+    }
+    return valid;
+}
+
+bool MeshCrypto::verifyWithKey(const std::vector<uint8_t>& publicKey, const std::vector<uint8_t>& message,
+                               const std::vector<uint8_t>& signature) {
+    if (publicKey.size() != crypto_sign_PUBLICKEYBYTES || signature.size() != crypto_sign_BYTES) {
+        return false;
+    }
+    return crypto_sign_verify_detached(signature.data(), message.data(), message.size(), 
                                       publicKey.data()) == 0;
 }
 
 void MeshCrypto::registerNodeKey(const std::string& nodeId, const std::vector<uint8_t>& publicKey) {
-    knownPublicKeys[nodeId] = publicKey;
+    // Un nodo già in quarantena accumula le chiavi rivendicate
+    auto conflict = keyConflicts.find(nodeId);
+    if (conflict != keyConflicts.end()) {
+        auto& keys = conflict->second;
+        if (std::find(keys.begin(), keys.end(), publicKey) == keys.end()) {
+            keys.push_back(publicKey);
+        }
+        return;
+    }
+    
+    auto it = knownPublicKeys.find(nodeId);
+    if (it == knownPublicKeys.end()) {
+        knownPublicKeys[nodeId] = publicKey;
+        return;
+    }
+    
+    if (it->second == publicKey) {
+        return;
+    }
+    
+    // Stesso ID con chiave diversa: collisione accidentale o impersonificazione
+    std::vector<std::vector<uint8_t>> keys = {it->second, publicKey};
+    knownPublicKeys.erase(it);
+    keyConflicts[nodeId] = keys;
+    sessions.forget(nodeId);
+    
+    emitSecurityEvent({
+        SecurityEvent::Type::KeyConflict,
+        nodeId,
+        {keyId(keys[0]), keyId(keys[1])},
+        "ID nodo rivendicato con chiavi diverse, nodo in quarantena",
+        currentTimestamp()
+    });
+}
+
+void MeshCrypto::setSecurityEventHandler(SecurityEventHandler handler) {
+    securityEventHandler = handler;
+}
+
+void MeshCrypto::emitSecurityEvent(const SecurityEvent& event) {
+    SABER_LOG(Warn, "security", "Evento di sicurezza per il nodo " << event.nodeId << ": " << event.detail);
+    if (securityEventHandler) {
+        securityEventHandler(event);
+    }
+}
+
+std::string MeshCrypto::keyId(const std::vector<uint8_t>& publicKey) {
+    std::array<uint8_t, 32> digest;
+    SHA256(publicKey.data(), publicKey.size(), digest.data());
+    
+    static const char* digits = "0123456789abcdef";
+    std::string id;
+    for (size_t i = 0; i < 8; ++i) {
+        id += digits[digest[i] >> 4];
+        id += digits[digest[i] & 0x0F];
+    }
+    return id;
+}
+
+bool MeshCrypto::isQuarantined(const std::string& nodeId) const {
+    return keyConflicts.count(nodeId) > 0 || imposedQuarantine.count(nodeId) > 0;
+}
+
+void MeshCrypto::quarantineNode(const std::string& nodeId, const std::string& reason) {
+    if (!imposedQuarantine.emplace(nodeId, reason).second) {
+        return;
+    }
+    emitSecurityEvent({
+        SecurityEvent::Type::NodeQuarantined,
+        nodeId,
+        {},
+        "Nodo in quarantena: " + reason,
+        currentTimestamp()
+    });
+}
+
+bool MeshCrypto::releaseQuarantine(const std::string& nodeId) {
+    if (imposedQuarantine.erase(nodeId) == 0) {
+        return false;
+    }
+    emitSecurityEvent({
+        SecurityEvent::Type::QuarantineReleased,
+        nodeId,
+        {},
+        "Quarantena rimossa dall'operatore",
+        currentTimestamp()
+    });
+    return true;
+}
+
+std::vector<std::string> MeshCrypto::getQuarantinedNodes() const {
+    std::set<std::string> nodes;
+    for (const auto& pair : keyConflicts) {
+        nodes.insert(pair.first);
+    }
+    for (const auto& pair : imposedQuarantine) {
+        nodes.insert(pair.first);
+    }
+    return std::vector<std::string>(nodes.begin(), nodes.end());
+}
+
+std::vector<std::vector<uint8_t>> MeshCrypto::getConflictingKeys(const std::string& nodeId) const {
+    auto it = keyConflicts.find(nodeId);
+    if (it == keyConflicts.end()) {
+        return {};
+    }
+    return it->second;
+}
+
+void MeshCrypto::resolveKeyConflict(const std::string& nodeId, const std::vector<uint8_t>& trustedKey) {
+    if (trustedKey.size() != crypto_sign_PUBLICKEYBYTES) {
+        throw CryptoError(CryptoError::Type::Verification, "Formato chiave pubblica non valido");
+    }
+    
+    keyConflicts.erase(nodeId);
+    knownPublicKeys[nodeId] = trustedKey;
+    
+    emitSecurityEvent({
+        SecurityEvent::Type::KeyConflictResolved,
+        nodeId,
+        {keyId(trustedKey)},
+        "Conflitto di chiavi risolto dall'operatore",
+        currentTimestamp()
+    });
 }
 
 std::array<uint8_t, 32> MeshCrypto::hash(const std::vector<uint8_t>& data) {
@@ -276,32 +957,42 @@ std::vector<uint8_t> MeshCrypto::getPublicKey() const {
                                signingKeys->publicKey + crypto_sign_PUBLICKEYBYTES);
 }
 
+void MeshCrypto::setIdentitySeed(const std::array<uint8_t, 32>& seed) {
+    crypto_sign_seed_keypair(signingKeys->publicKey, signingKeys->secretKey, seed.data());
+    // Con l'identità cambiano le chiavi di scambio e quindi tutte le sessioni
+    deriveExchangeKeys();
+    sessions.clear();
+}
+
+std::map<std::string, std::vector<uint8_t>> MeshCrypto::getKnownPublicKeys() const {
+    return knownPublicKeys;
+}
+
 std::array<uint8_t, 32> MeshCrypto::getExchangePublicKey() const {
     std::array<uint8_t, 32> publicKey;
     std::memcpy(publicKey.data(), exchangeKeys->publicKey, publicKey.size());
     return publicKey;
 }
 
-std::vector<uint8_t> MeshCrypto::generateSecurityToken(const std::string& nodeId, uint64_t ttlSeconds) {
-    // Crea un token con ID nodo, timestamp e scadenza
+std::vector<uint8_t> MeshCrypto::generateSecurityToken(const std::string& nodeId, uint64_t ttlSeconds,
+                                                   TokenScope scope) {
+    // Crea un token con versione, ID casuale, ambito, timestamp e scadenza
     uint64_t timestamp = currentTimestamp();
     uint64_t expiry = timestamp + (ttlSeconds * 1000); // Converto i secondi in ms
     
+    std::array<uint8_t, TOKEN_ID_BYTES> tokenId;
+    rng->fill(tokenId.data(), tokenId.size());
+    
     // Crea il payload del token
     std::vector<uint8_t> tokenData;
-    
-    // Aggiungi l'ID del nodo
+    tokenData.push_back(SECURITY_TOKEN_VERSION);
+    tokenData.insert(tokenData.end(), tokenId.begin(), tokenId.end());
+    tokenData.push_back(static_cast<uint8_t>(scope));
+    appendU64(tokenData, timestamp);
+    appendU64(tokenData, expiry);
     tokenData.insert(tokenData.end(), nodeId.begin(), nodeId.end());
     
-    // Aggiungi il timestamp
-    const uint8_t* timestampBytes = reinterpret_cast<const uint8_t*>(&timestamp);
-    tokenData.insert(tokenData.end(), timestampBytes, timestampBytes + sizeof(timestamp));
-    
-    // Aggiungi la scadenza
-    const uint8_t* expiryBytes = reinterpret_cast<const uint8_t*>(&expiry);
-    tokenData.insert(tokenData.end(), expiryBytes, expiryBytes + sizeof(expiry));
-    
-    // Firma il token
+    // Firma il token con la chiave d'identità del nodo
     auto signature = sign(tokenData);
     
     // Aggiungi la firma al token
@@ -311,12 +1002,11 @@ std::vector<uint8_t> MeshCrypto::generateSecurityToken(const std::string& nodeId
     return encrypt(tokenData);
 }
 
-std::pair<std::string, uint64_t> MeshCrypto::verifySecurityToken(const std::vector<uint8_t>& token) {
+SecurityToken MeshCrypto::verifySecurityToken(const std::vector<uint8_t>& token) {
     // Decifra il token
     auto decrypted = decrypt(token);
     
-    const size_t minSize = 8 + 8 + crypto_sign_BYTES; // timestamp + expiry + signature
-    if (decrypted.size() < minSize) {
+    if (decrypted.size() < TOKEN_HEADER_BYTES + crypto_sign_BYTES) {
         throw CryptoError(CryptoError::Type::Verification, "Formato token non valido");
     }
     
@@ -324,25 +1014,62 @@ std::pair<std::string, uint64_t> MeshCrypto::verifySecurityToken(const std::vect
     std::vector<uint8_t> signature(decrypted.end() - crypto_sign_BYTES, decrypted.end());
     std::vector<uint8_t> data(decrypted.begin(), decrypted.end() - crypto_sign_BYTES);
     
-    // Estrai i campi dal token
-    size_t nodeIdSize = data.size() - 16; // 8 byte timestamp + 8 byte expiry
-    std::string nodeId(data.begin(), data.begin() + nodeIdSize);
+    // Il token è valido solo se firmato dalla chiave d'identità di questo nodo
+    if (crypto_sign_verify_detached(signature.data(), data.data(), data.size(),
+                                    signingKeys->publicKey) != 0) {
+        throw CryptoError(CryptoError::Type::Verification, "Firma non valida");
+    }
     
-    uint64_t expiry;
-    std::memcpy(&expiry, data.data() + nodeIdSize + 8, sizeof(expiry));
+    SecurityToken result = decodeSecurityToken(data);
     
     // Verifica la scadenza
-    uint64_t current = currentTimestamp();
-    if (current > expiry) {
+    if (currentTimestamp() > result.expiry) {
         throw CryptoError(CryptoError::Type::Verification, "Token scaduto");
     }
     
-    // Verifica la firma
-    if (!verify(nodeId, data, signature)) {
-        throw CryptoError(CryptoError::Type::Verification, "Firma non valida");
+    // Verifica la revoca
+    if (revocations->isRevoked(result.tokenId, result.nodeId, result.issuedAt)) {
+        throw CryptoError(CryptoError::Type::Verification, "Token revocato");
     }
     
-    return {nodeId, expiry};
+    return result;
+}
+
+SecurityToken MeshCrypto::decodeSecurityToken(const std::vector<uint8_t>& payload) {
+    TokenReader reader(payload);
+    if (reader.getU8() != SECURITY_TOKEN_VERSION) {
+        throw CryptoError(CryptoError::Type::Verification, "Versione del token non supportata");
+    }
+    
+    SecurityToken result;
+    result.tokenId = toHex(reader.take(TOKEN_ID_BYTES), TOKEN_ID_BYTES);
+    uint8_t scope = reader.getU8();
+    if (scope != static_cast<uint8_t>(TokenScope::ReadOnly) && scope != static_cast<uint8_t>(TokenScope::Admin)) {
+        throw CryptoError(CryptoError::Type::Verification, "Ambito del token non valido");
+    }
+    result.scope = static_cast<TokenScope>(scope);
+    result.issuedAt = reader.getU64();
+    result.expiry = reader.getU64();
+    result.nodeId = reader.getRest();
+    return result;
+}
+
+void MeshCrypto::revokeSecurityToken(const std::string& tokenId) {
+    revocations->add(Revocation{RevocationKind::Token, tokenId, currentTimestamp()});
+}
+
+bool MeshCrypto::isTokenRevoked(const std::string& tokenId) const {
+    return revocations->isTokenRevoked(tokenId);
+}
+
+void MeshCrypto::setRevocationList(std::shared_ptr<RevocationList> list) {
+    if (list) {
+        revocations = std::move(list);
+    }
+}
+
+std::shared_ptr<RevocationList> MeshCrypto::getRevocationList() const {
+    return revocations;
 }
 
 } // namespace saber
@@ -0,0 +1,99 @@
+#include "degradation.h"
+
+#include <algorithm>
+
+namespace saber {
+
+std::string degradationLevelToString(DegradationLevel level) {
+    switch (level) {
+        case DegradationLevel::Nominal: return "nominal";
+        case DegradationLevel::ReducedBitrate: return "reduced_bitrate";
+        case DegradationLevel::StrongerFec: return "stronger_fec";
+        case DegradationLevel::LargerBuffer: return "larger_buffer";
+        case DegradationLevel::VoiceSampleRate: return "voice_sample_rate";
+        case DegradationLevel::SuspendedSinks: return "suspended_sinks";
+        default: return "unknown";
+    }
+}
+
+// Implementazione di DegradationLadder
+DegradationLadder::DegradationLadder(const DegradationConfig& config)
+    : config(config), level(DegradationLevel::Nominal) {
+}
+
+std::optional<DegradationLevel> DegradationLadder::update(double quality, int64_t nowMs) {
+    double percent = quality * 100.0;
+
+    if (percent < config.badQualityPercent) {
+        goodSinceMs.reset();
+        if (!badSinceMs) {
+            badSinceMs = nowMs;
+        }
+        if (level != MAX_DEGRADATION_LEVEL && nowMs - *badSinceMs >= config.escalateAfterMs) {
+            // Il gradino successivo deve dimostrare da capo di essere necessario
+            level = static_cast<DegradationLevel>(static_cast<uint8_t>(level) + 1);
+            badSinceMs = nowMs;
+            return level;
+        }
+        return std::nullopt;
+    }
+
+    badSinceMs.reset();
+    if (percent < config.goodQualityPercent) {
+        // Qualità intermedia: si resta sul gradino corrente
+        goodSinceMs.reset();
+        return std::nullopt;
+    }
+
+    if (!goodSinceMs) {
+        goodSinceMs = nowMs;
+    }
+    if (level != DegradationLevel::Nominal && nowMs - *goodSinceMs >= config.recoverAfterMs) {
+        level = static_cast<DegradationLevel>(static_cast<uint8_t>(level) - 1);
+        goodSinceMs = nowMs;
+        return level;
+    }
+    return std::nullopt;
+}
+
+DegradationLevel DegradationLadder::getLevel() const {
+    return level;
+}
+
+DegradationSettings DegradationLadder::settingsFor(DegradationLevel level, bool isMusicMode, uint32_t baseBufferMs,
+                                                   uint32_t maxBufferMs, bool baseFec) const {
+    DegradationSettings settings;
+    settings.level = level;
+    settings.bitrateKbps = isMusicMode ? spec::BITRATE_MUSIC_KBPS : spec::BITRATE_VOICE_KBPS;
+    settings.fecEnabled = baseFec;
+    settings.bufferMs = baseBufferMs;
+    settings.sampleRateHz = isMusicMode ? spec::SAMPLE_RATE_MUSIC_HZ : spec::SAMPLE_RATE_VOICE_HZ;
+
+    if (level >= DegradationLevel::ReducedBitrate) {
+        settings.bitrateKbps = isMusicMode ? spec::BITRATE_VOICE_KBPS : spec::BITRATE_VOICE_REDUCED_KBPS;
+    }
+    if (level >= DegradationLevel::StrongerFec) {
+        settings.fecEnabled = true;
+    }
+    if (level >= DegradationLevel::LargerBuffer) {
+        settings.bufferMs = std::min(baseBufferMs + config.bufferStepMs, std::max(baseBufferMs, maxBufferMs));
+    }
+    if (level >= DegradationLevel::VoiceSampleRate) {
+        settings.sampleRateHz = spec::SAMPLE_RATE_VOICE_HZ;
+        settings.bitrateKbps = spec::BITRATE_VOICE_REDUCED_KBPS;
+    }
+    return settings;
+}
+
+const DegradationConfig& DegradationLadder::getConfig() const {
+    return config;
+}
+
+void DegradationLadder::setConfig(const DegradationConfig& config) {
+    this->config = config;
+    // I tempi di permanenza ripartono con le nuove soglie
+    badSinceMs.reset();
+    goodSinceMs.reset();
+}
+
+} // namespace saber
@@ -0,0 +1,145 @@
+#include "discovery.h"
+#include "log.h"
+
+#include <algorithm>
+#include <stdexcept>
+
+namespace saber {
+
+namespace {
+
+/// Tipo AD dei flag di visibilità
+const uint8_t AD_FLAGS = 0x01;
+
+/// Tipo AD dei dati di servizio con UUID a 128 bit
+const uint8_t AD_SERVICE_DATA_128 = 0x21;
+
+/// LE General Discoverable, BR/EDR non supportato
+const uint8_t AD_FLAGS_VALUE = 0x06;
+
+/// Byte del UUID del servizio nell'ordine della radio (little endian)
+const std::vector<uint8_t>& serviceUuidBytes() {
+    static const std::vector<uint8_t> bytes = [] {
+        std::string hex(SABER_SERVICE_UUID);
+        hex.erase(std::remove(hex.begin(), hex.end(), '-'), hex.end());
+        std::vector<uint8_t> result;
+        for (size_t i = 0; i + 1 < hex.size(); i += 2) {
+            result.push_back(static_cast<uint8_t>(std::stoul(hex.substr(i, 2), nullptr, 16)));
+        }
+        std::reverse(result.begin(), result.end());
+        return result;
+    }();
+    return bytes;
+}
+
+} // namespace
+
+std::vector<uint8_t> encodeAdvertisement(const DiscoveryAdvertisement& advertisement) {
+    if (advertisement.nodeId.empty()) {
+        throw std::invalid_argument("ID del nodo vuoto nell'annuncio");
+    }
+
+    const auto& uuid = serviceUuidBytes();
+    size_t serviceDataLength = 1 + uuid.size() + 2 + advertisement.nodeId.size();
+    if (3 + 1 + serviceDataLength > MAX_ADVERTISEMENT_BYTES) {
+        throw std::invalid_argument("ID del nodo troppo lungo per l'annuncio: " + advertisement.nodeId);
+    }
+
+    std::vector<uint8_t> data = {2, AD_FLAGS, AD_FLAGS_VALUE};
+    data.push_back(static_cast<uint8_t>(serviceDataLength));
+    data.push_back(AD_SERVICE_DATA_128);
+    data.insert(data.end(), uuid.begin(), uuid.end());
+    data.push_back(DISCOVERY_VERSION);
+    data.push_back(static_cast<uint8_t>(advertisement.role));
+    data.insert(data.end(), advertisement.nodeId.begin(), advertisement.nodeId.end());
+    return data;
+}
+
+std::optional<DiscoveryAdvertisement> parseAdvertisement(const std::vector<uint8_t>& data) {
+    const auto& uuid = serviceUuidBytes();
+    size_t offset = 0;
+    while (offset < data.size()) {
+        size_t length = data[offset];
+        // Una lunghezza nulla segna il riempimento finale
+        if (length == 0) {
+            break;
+        }
+        if (offset + 1 + length > data.size()) {
+            return std::nullopt;
+        }
+
+        auto begin = data.begin() + static_cast<std::ptrdiff_t>(offset) + 2;
+        auto end = data.begin() + static_cast<std::ptrdiff_t>(offset + 1 + length);
+        if (data[offset + 1] == AD_SERVICE_DATA_128 && length >= 1 + uuid.size() + 2
+            && std::equal(uuid.begin(), uuid.end(), begin)) {
+            auto field = begin + static_cast<std::ptrdiff_t>(uuid.size());
+            uint8_t version = field[0];
+            uint8_t role = field[1];
+            std::string nodeId(field + 2, end);
+            if (version != DISCOVERY_VERSION || role > static_cast<uint8_t>(NodeRole::Sink) || nodeId.empty()) {
+                return std::nullopt;
+            }
+            return DiscoveryAdvertisement{nodeId, static_cast<NodeRole>(role)};
+        }
+        offset += 1 + length;
+    }
+    return std::nullopt;
+}
+
+// Implementazione di NodeDiscovery
+NodeDiscovery::NodeDiscovery(const std::string& localNodeId, NodeRole localRole, uint32_t staleMs)
+    : localNodeId(localNodeId),
+      localAdvertisement(encodeAdvertisement({localNodeId, localRole})),
+      staleMs(staleMs) {
+}
+
+const std::vector<uint8_t>& NodeDiscovery::advertisement() const {
+    return localAdvertisement;
+}
+
+std::optional<DiscoveredNode> NodeDiscovery::observe(const std::string& address, const std::vector<uint8_t>& data,
+                                                     int32_t rssiDbm, uint64_t nowMs) {
+    auto advertisement = parseAdvertisement(data);
+    if (!advertisement || advertisement->nodeId == localNodeId) {
+        return std::nullopt;
+    }
+
+    std::lock_guard<std::mutex> lock(discoveryMutex);
+    auto& node = nodes[advertisement->nodeId];
+    if (node.advertisements == 0) {
+        SABER_LOG(Info, "discovery", "Scoperto il nodo " << advertisement->nodeId << " ("
+                  << nodeRoleToString(advertisement->role) << ", " << rssiDbm << " dBm)");
+    }
+    node.nodeId = advertisement->nodeId;
+    node.role = advertisement->role;
+    node.address = address;
+    node.rssiDbm = rssiDbm;
+    node.lastSeenMs = nowMs;
+    node.advertisements++;
+    return node;
+}
+
+std::vector<std::string> NodeDiscovery::expire(uint64_t nowMs) {
+    std::lock_guard<std::mutex> lock(discoveryMutex);
+    std::vector<std::string> expired;
+    for (auto it = nodes.begin(); it != nodes.end();) {
+        if (it->second.lastSeenMs + staleMs < nowMs) {
+            expired.push_back(it->first);
+            it = nodes.erase(it);
+        } else {
+            ++it;
+        }
+    }
+    return expired;
+}
+
+std::vector<DiscoveredNode> NodeDiscovery::getNodes() const {
+    std::lock_guard<std::mutex> lock(discoveryMutex);
+    std::vector<DiscoveredNode> result;
+    for (const auto& entry : nodes) {
+        result.push_back(entry.second);
+    }
+    return result;
+}
+
+} // namespace saber
@@ -0,0 +1,143 @@
+#include "event_bus.h"
+#include "log.h"
+
+namespace saber {
+
+std::string protocolEventTypeToString(ProtocolEvent::Type type) {
+    switch (type) {
+        case ProtocolEvent::Type::NodeJoined:
+            return "node_joined";
+        case ProtocolEvent::Type::NodeLeft:
+            return "node_left";
+        case ProtocolEvent::Type::SyncLost:
+            return "sync_lost";
+        case ProtocolEvent::Type::LatencyAlert:
+            return "latency_alert";
+    }
+    return "unknown";
+}
+
+// Implementazione di EventBus
+EventBus::EventBus(size_t maxPending) : maxPending(maxPending > 0 ? maxPending : 1) {
+    worker = std::thread(&EventBus::run, this);
+}
+
+EventBus::~EventBus() {
+    {
+        std::lock_guard<std::mutex> lock(mutex);
+        stopping = true;
+    }
+    wakeup.notify_all();
+    if (worker.joinable()) {
+        worker.join();
+    }
+}
+
+uint32_t EventBus::subscribe(ProtocolEvent::Type type, Callback callback) {
+    std::lock_guard<std::mutex> lock(mutex);
+    uint32_t id = nextId++;
+    subscriptions[id] = {type, std::move(callback), nullptr};
+    return id;
+}
+
+uint32_t EventBus::addHandler(std::shared_ptr<EventHandler> handler) {
+    std::lock_guard<std::mutex> lock(mutex);
+    uint32_t id = nextId++;
+    subscriptions[id] = {ProtocolEvent::Type::NodeJoined, nullptr, std::move(handler)};
+    return id;
+}
+
+bool EventBus::unsubscribe(uint32_t id) {
+    std::lock_guard<std::mutex> lock(mutex);
+    return subscriptions.erase(id) > 0;
+}
+
+void EventBus::publish(const ProtocolEvent& event) {
+    {
+        std::lock_guard<std::mutex> lock(mutex);
+        if (subscriptions.empty()) {
+            return;
+        }
+        if (pending.size() >= maxPending) {
+            pending.pop_front();
+            dropped++;
+            delivered++;
+        }
+        pending.push_back(event);
+        published++;
+    }
+    wakeup.notify_one();
+}
+
+void EventBus::flush() {
+    std::unique_lock<std::mutex> lock(mutex);
+    uint64_t target = published;
+    idle.wait(lock, [this, target] { return delivered >= target || stopping; });
+}
+
+uint64_t EventBus::getDroppedEvents() const {
+    std::lock_guard<std::mutex> lock(mutex);
+    return dropped;
+}
+
+void EventBus::run() {
+    std::unique_lock<std::mutex> lock(mutex);
+    while (true) {
+        wakeup.wait(lock, [this] { return stopping || !pending.empty(); });
+        if (pending.empty()) {
+            break;
+        }
+        ProtocolEvent event = std::move(pending.front());
+        pending.pop_front();
+
+        // I gestori vengono chiamati senza il mutex: possono registrarsi o annullarsi
+        lock.unlock();
+        deliver(event);
+        lock.lock();
+
+        delivered++;
+        idle.notify_all();
+    }
+    idle.notify_all();
+}
+
+void EventBus::deliver(const ProtocolEvent& event) {
+    std::vector<Subscription> targets;
+    {
+        std::lock_guard<std::mutex> lock(mutex);
+        for (const auto& subscription : subscriptions) {
+            if (subscription.second.handler || subscription.second.type == event.type) {
+                targets.push_back(subscription.second);
+            }
+        }
+    }
+
+    for (const auto& target : targets) {
+        try {
+            if (target.callback) {
+                target.callback(event);
+                continue;
+            }
+            switch (event.type) {
+                case ProtocolEvent::Type::NodeJoined:
+                    target.handler->onNodeJoined(event.nodeId);
+                    break;
+                case ProtocolEvent::Type::NodeLeft:
+                    target.handler->onNodeLeft(event.nodeId);
+                    break;
+                case ProtocolEvent::Type::SyncLost:
+                    target.handler->onSyncLost();
+                    break;
+                case ProtocolEvent::Type::LatencyAlert:
+                    target.handler->onLatencyAlert(event.nodeId, event.latencyMs, event.budgetMs);
+                    break;
+            }
+        } catch (const std::exception& e) {
+            // Un gestore difettoso non deve fermare la consegna agli altri
+            SABER_LOG(Warn, "events", "Gestore di " << protocolEventTypeToString(event.type)
+                      << " fallito: " << e.what());
+        }
+    }
+}
+
+} // namespace saber
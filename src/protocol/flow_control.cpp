@@ -0,0 +1,129 @@
+#include "flow_control.h"
+
+namespace saber {
+
+std::string flowStateToString(FlowState state) {
+    switch (state) {
+        case FlowState::Normal:
+            return "normal";
+        case FlowState::LowHeadroom:
+            return "low_headroom";
+        case FlowState::Overflow:
+            return "overflow";
+    }
+    return "normal";
+}
+
+std::string framePriorityToString(FramePriority priority) {
+    switch (priority) {
+        case FramePriority::Low:
+            return "low";
+        case FramePriority::Normal:
+            return "normal";
+        case FramePriority::High:
+            return "high";
+    }
+    return "normal";
+}
+
+std::optional<FramePriority> framePriorityFromString(const std::string& name) {
+    if (name == "low") {
+        return FramePriority::Low;
+    }
+    if (name == "normal") {
+        return FramePriority::Normal;
+    }
+    if (name == "high") {
+        return FramePriority::High;
+    }
+    return std::nullopt;
+}
+
+// Implementazione di FlowController
+FlowController::FlowController(const FlowConfig& config) : config(config) {
+}
+
+void FlowController::setConfig(const FlowConfig& updated) {
+    std::lock_guard<std::mutex> lock(flowMutex);
+    config = updated;
+}
+
+FlowConfig FlowController::getConfig() const {
+    std::lock_guard<std::mutex> lock(flowMutex);
+    return config;
+}
+
+void FlowController::reportBuffer(const std::string& nodeId, uint8_t levelPercent, uint64_t nowMs) {
+    std::lock_guard<std::mutex> lock(flowMutex);
+    // Un sink senza audio riporta 0: non ha un buffer da proteggere
+    if (levelPercent == 0) {
+        reports.erase(nodeId);
+        return;
+    }
+    reports[nodeId] = Report{levelPercent, nowMs};
+}
+
+std::optional<FlowState> FlowController::update(uint64_t nowMs) {
+    std::lock_guard<std::mutex> lock(flowMutex);
+    FlowState target = FlowState::Normal;
+    if (config.enabled) {
+        for (auto it = reports.begin(); it != reports.end();) {
+            if (nowMs - it->second.receivedAtMs >= config.reportTimeoutMs) {
+                it = reports.erase(it);
+                continue;
+            }
+            if (it->second.levelPercent < config.lowWatermarkPercent) {
+                target = FlowState::LowHeadroom;
+            } else if (it->second.levelPercent > config.highWatermarkPercent && target == FlowState::Normal) {
+                target = FlowState::Overflow;
+            }
+            ++it;
+        }
+    }
+
+    // Si peggiora subito, si torna alla norma solo dopo un periodo stabile
+    if (target != FlowState::Normal) {
+        normalSinceMs.reset();
+    } else if (!normalSinceMs) {
+        normalSinceMs = nowMs;
+    }
+    if (target == state) {
+        return std::nullopt;
+    }
+    if (target == FlowState::Normal && config.enabled && nowMs - *normalSinceMs < config.recoverAfterMs) {
+        return std::nullopt;
+    }
+    state = target;
+    transitions++;
+    return state;
+}
+
+FlowState FlowController::getState() const {
+    std::lock_guard<std::mutex> lock(flowMutex);
+    return state;
+}
+
+bool FlowController::admit(FramePriority priority) {
+    std::lock_guard<std::mutex> lock(flowMutex);
+    if (state != FlowState::Overflow || priority != FramePriority::Low) {
+        return true;
+    }
+    dropped[priority]++;
+    return false;
+}
+
+FlowStatus FlowController::getStatus(uint64_t nowMs) const {
+    std::lock_guard<std::mutex> lock(flowMutex);
+    FlowStatus status;
+    status.state = state;
+    for (const auto& entry : reports) {
+        if (nowMs - entry.second.receivedAtMs < config.reportTimeoutMs) {
+            status.bufferLevels[entry.first] = entry.second.levelPercent;
+        }
+    }
+    status.droppedFrames = dropped;
+    status.transitions = transitions;
+    return status;
+}
+
+} // namespace saber
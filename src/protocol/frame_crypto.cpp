@@ -0,0 +1,128 @@
+#include "frame_crypto.h"
+#include "crypto.h"
+#include "wire.h"
+
+#include <stdexcept>
+
+namespace saber {
+
+std::string encryptionGranularityToString(EncryptionGranularity granularity) {
+    switch (granularity) {
+        case EncryptionGranularity::PerFrame:
+            return "per_frame";
+        case EncryptionGranularity::PerTransportFrame:
+            return "transport";
+    }
+    return "per_frame";
+}
+
+std::optional<EncryptionGranularity> encryptionGranularityFromString(const std::string& value) {
+    if (value == "per_frame") {
+        return EncryptionGranularity::PerFrame;
+    }
+    if (value == "transport") {
+        return EncryptionGranularity::PerTransportFrame;
+    }
+    return std::nullopt;
+}
+
+uint8_t capabilityFor(EncryptionGranularity granularity) {
+    return granularity == EncryptionGranularity::PerTransportFrame ? CAP_ENCRYPT_TRANSPORT
+                                                                   : CAP_ENCRYPT_PER_FRAME;
+}
+
+std::optional<EncryptionGranularity> negotiateGranularity(uint8_t localCaps, uint8_t peerCaps,
+                                                          EncryptionGranularity preferred) {
+    uint8_t common = localCaps & peerCaps;
+    if (common & capabilityFor(preferred)) {
+        return preferred;
+    }
+
+    EncryptionGranularity fallback = preferred == EncryptionGranularity::PerFrame
+                                         ? EncryptionGranularity::PerTransportFrame
+                                         : EncryptionGranularity::PerFrame;
+    if (common & capabilityFor(fallback)) {
+        return fallback;
+    }
+    return std::nullopt;
+}
+
+// Implementazione di AudioFrameSealer
+AudioFrameSealer::AudioFrameSealer(std::shared_ptr<MeshCrypto> crypto, EncryptionGranularity granularity)
+    : crypto(std::move(crypto)),
+      granularity(granularity) {
+    if (!this->crypto) {
+        throw std::invalid_argument("Gestore crittografico mancante");
+    }
+}
+
+std::vector<uint8_t> AudioFrameSealer::seal(const std::vector<std::vector<uint8_t>>& frames) const {
+    if (frames.size() > UINT16_MAX) {
+        throw std::invalid_argument("Troppi frame in un frame di trasporto");
+    }
+
+    ByteWriter writer;
+    writer.putU8(static_cast<uint8_t>(granularity));
+    writer.putU16(static_cast<uint16_t>(frames.size()));
+
+    if (granularity == EncryptionGranularity::PerFrame) {
+        for (const auto& frame : frames) {
+            writer.putBytes(crypto->encrypt(frame));
+        }
+    } else {
+        ByteWriter plain;
+        for (const auto& frame : frames) {
+            plain.putBytes(frame);
+        }
+        writer.putBytes(crypto->encrypt(plain.data()));
+    }
+
+    return writer.data();
+}
+
+std::vector<std::vector<uint8_t>> AudioFrameSealer::open(const std::vector<uint8_t>& transportFrame,
+                                                         const std::string& senderId) const {
+    auto decrypt = [&](const std::vector<uint8_t>& blob) {
+        return senderId.empty() ? crypto->decrypt(blob) : crypto->decryptFrom(senderId, blob);
+    };
+
+    ByteReader reader(transportFrame);
+    uint8_t mode = reader.getU8();
+    uint16_t count = reader.getU16();
+
+    std::vector<std::vector<uint8_t>> frames;
+    frames.reserve(count);
+
+    if (mode == static_cast<uint8_t>(EncryptionGranularity::PerFrame)) {
+        for (uint16_t i = 0; i < count; ++i) {
+            frames.push_back(decrypt(reader.getBytes()));
+        }
+    } else if (mode == static_cast<uint8_t>(EncryptionGranularity::PerTransportFrame)) {
+        std::vector<uint8_t> plain = decrypt(reader.getBytes());
+        ByteReader inner(plain);
+        for (uint16_t i = 0; i < count; ++i) {
+            frames.push_back(inner.getBytes());
+        }
+    } else {
+        throw CryptoError(CryptoError::Type::Decryption, "Granularità di cifratura sconosciuta");
+    }
+
+    return frames;
+}
+
+EncryptionGranularity AudioFrameSealer::getGranularity() const {
+    return granularity;
+}
+
+size_t AudioFrameSealer::overheadBytes(EncryptionGranularity granularity, size_t frameCount) {
+    // Intestazione: granularità + numero di frame
+    size_t header = 1 + 2;
+
+    if (granularity == EncryptionGranularity::PerFrame) {
+        return header + frameCount * (4 + AEAD_OVERHEAD_BYTES);
+    }
+    // Prefissi di lunghezza interni più un solo blob cifrato
+    return header + frameCount * 4 + 4 + AEAD_OVERHEAD_BYTES;
+}
+
+} // namespace saber
@@ -0,0 +1,225 @@
+#include "http_server.h"
+#include "log.h"
+#include "socket_compat.h"
+
+#include <algorithm>
+#include <chrono>
+#include <cstring>
+#include <iostream>
+#include <sstream>
+
+namespace saber {
+
+namespace {
+
+const intptr_t INVALID_SOCKET_HANDLE = -1;
+
+// Tempo concesso ad un client per inviare la riga di richiesta, oltre il quale la connessione viene chiusa
+const int64_t REQUEST_TIMEOUT_MS = 1000;
+
+// Limite della richiesta letta prima della riga di richiesta completa
+const size_t MAX_REQUEST_BYTES = 4096;
+
+std::string statusText(int status) {
+    switch (status) {
+        case 200: return "OK";
+        case 404: return "Not Found";
+        case 405: return "Method Not Allowed";
+        case 503: return "Service Unavailable";
+        default: return "Internal Server Error";
+    }
+}
+
+} // namespace
+
+// Implementazione di HttpServer
+HttpServer::HttpServer(const std::string& bindAddress, uint16_t port)
+    : bindAddress(bindAddress), port(port), listenSocket(INVALID_SOCKET_HANDLE), running(false) {
+}
+
+HttpServer::~HttpServer() {
+    stop();
+}
+
+void HttpServer::addRoute(const std::string& path, RouteHandler handler) {
+    std::lock_guard<std::mutex> lock(routesMutex);
+    routes[path] = handler;
+}
+
+bool HttpServer::start() {
+    if (running) {
+        return true;
+    }
+    
+#ifdef _WIN32
+    WSADATA wsaData;
+    if (WSAStartup(MAKEWORD(2, 2), &wsaData) != 0) {
+        SABER_LOG(Error, "http", "Impossibile inizializzare Winsock");
+        return false;
+    }
+#endif
+    
+    auto sock = socket(AF_INET, SOCK_STREAM, 0);
+    if (sock < 0) {
+        SABER_LOG(Error, "http", "Impossibile creare il socket HTTP");
+        return false;
+    }
+    
+    int reuse = 1;
+    setsockopt(sock, SOL_SOCKET, SO_REUSEADDR, reinterpret_cast<const char*>(&reuse), sizeof(reuse));
+    
+    sockaddr_in addr;
+    std::memset(&addr, 0, sizeof(addr));
+    addr.sin_family = AF_INET;
+    addr.sin_port = htons(port);
+    if (inet_pton(AF_INET, bindAddress.c_str(), &addr.sin_addr) != 1) {
+        SABER_LOG(Error, "http", "Indirizzo HTTP non valido: " << bindAddress);
+        SABER_CLOSE_SOCKET(sock);
+        return false;
+    }
+    
+    if (bind(sock, reinterpret_cast<sockaddr*>(&addr), sizeof(addr)) != 0 || listen(sock, 8) != 0) {
+        SABER_LOG(Error, "http", "Impossibile mettersi in ascolto su " << bindAddress << ":" << port);
+        SABER_CLOSE_SOCKET(sock);
+        return false;
+    }
+    
+    listenSocket = static_cast<intptr_t>(sock);
+    running = true;
+    serverThread = std::make_unique<std::thread>(&HttpServer::runServerLoop, this);
+    return true;
+}
+
+void HttpServer::stop() {
+    if (!running.exchange(false)) {
+        return;
+    }
+    
+    if (serverThread && serverThread->joinable()) {
+        serverThread->join();
+    }
+    
+    SABER_CLOSE_SOCKET(listenSocket);
+    listenSocket = INVALID_SOCKET_HANDLE;
+    
+#ifdef _WIN32
+    WSACleanup();
+#endif
+}
+
+bool HttpServer::isRunning() const {
+    return running;
+}
+
+void HttpServer::runServerLoop() {
+    while (running) {
+        // Attesa con timeout per poter osservare il flag di arresto
+        fd_set readSet;
+        FD_ZERO(&readSet);
+        FD_SET(listenSocket, &readSet);
+        timeval timeout{0, 200000};
+        
+        int ready = select(static_cast<int>(listenSocket) + 1, &readSet, nullptr, nullptr, &timeout);
+        if (ready <= 0) {
+            continue;
+        }
+        
+        sockaddr_in clientAddr;
+        socklen_t clientLen = sizeof(clientAddr);
+        auto client = accept(listenSocket, reinterpret_cast<sockaddr*>(&clientAddr), &clientLen);
+        if (client < 0) {
+            continue;
+        }
+        
+        handleConnection(static_cast<intptr_t>(client));
+        SABER_CLOSE_SOCKET(client);
+    }
+}
+
+void HttpServer::handleConnection(intptr_t clientSocket) {
+    // Anche l'invio ha un limite: un client che non legge non blocca il server
+#ifdef _WIN32
+    DWORD sendTimeout = REQUEST_TIMEOUT_MS;
+#else
+    timeval sendTimeout{REQUEST_TIMEOUT_MS / 1000, (REQUEST_TIMEOUT_MS % 1000) * 1000};
+#endif
+    setsockopt(clientSocket, SOL_SOCKET, SO_SNDTIMEO, reinterpret_cast<const char*>(&sendTimeout), 
+               sizeof(sendTimeout));
+    
+    // Le connessioni sono servite una alla volta: un client inattivo viene chiuso
+    // alla scadenza invece di bloccare gli altri controlli e l'arresto
+    auto deadline = std::chrono::steady_clock::now() + std::chrono::milliseconds(REQUEST_TIMEOUT_MS);
+    std::string buffer;
+    char chunk[1024];
+    while (buffer.find('\n') == std::string::npos) {
+        auto remaining = std::chrono::duration_cast<std::chrono::microseconds>(
+            deadline - std::chrono::steady_clock::now()).count();
+        if (!running || remaining <= 0) {
+            SABER_LOG(Debug, "http", "Connessione chiusa senza una richiesta completa");
+            return;
+        }
+        
+        fd_set readSet;
+        FD_ZERO(&readSet);
+        FD_SET(clientSocket, &readSet);
+        remaining = std::min<int64_t>(remaining, 200000);
+        timeval timeout{0, static_cast<long>(remaining)};
+        
+        int ready = select(static_cast<int>(clientSocket) + 1, &readSet, nullptr, nullptr, &timeout);
+        if (ready < 0) {
+            return;
+        }
+        if (ready == 0) {
+            continue;
+        }
+        
+        auto received = recv(clientSocket, chunk, sizeof(chunk), 0);
+        if (received <= 0) {
+            return;
+        }
+        buffer.append(chunk, static_cast<size_t>(received));
+        if (buffer.size() > MAX_REQUEST_BYTES) {
+            return;
+        }
+    }
+    
+    // Request line: METODO PERCORSO VERSIONE
+    std::istringstream request(buffer);
+    std::string method, path;
+    request >> method >> path;
+    
+    // Ignoro eventuali query string
+    auto query = path.find('?');
+    if (query != std::string::npos) {
+        path = path.substr(0, query);
+    }
+    
+    HttpResponse response{404, "text/plain", "not found\n"};
+    if (method != "GET") {
+        response = {405, "text/plain", "method not allowed\n"};
+    } else {
+        RouteHandler handler;
+        {
+            std::lock_guard<std::mutex> lock(routesMutex);
+            auto it = routes.find(path);
+            if (it != routes.end()) {
+                handler = it->second;
+            }
+        }
+        if (handler) {
+            response = handler();
+        }
+    }
+    
+    std::ostringstream out;
+    out << "HTTP/1.1 " << response.status << " " << statusText(response.status) << "\r\n"
+        << "Content-Type: " << response.contentType << "\r\n"
+        << "Content-Length: " << response.body.size() << "\r\n"
+        << "Connection: close\r\n\r\n"
+        << response.body;
+    
+    std::string payload = out.str();
+    send(clientSocket, payload.data(), static_cast<int>(payload.size()), 0);
+}
+
+} // namespace saber
@@ -0,0 +1,50 @@
+#include "intercom.h"
+
+#include <cmath>
+
+namespace saber {
+
+bool IntercomSession::involves(const std::string& nodeId) const {
+    return !nodeId.empty() && (nodeId == nodeA || nodeId == nodeB);
+}
+
+std::optional<StreamId> IntercomSession::talkStream(const std::string& nodeId) const {
+    if (!involves(nodeId)) {
+        return std::nullopt;
+    }
+    return nodeId == nodeA ? INTERCOM_STREAM_BASE : static_cast<StreamId>(INTERCOM_STREAM_BASE + 1);
+}
+
+std::optional<StreamId> IntercomSession::listenStream(const std::string& nodeId) const {
+    if (!involves(nodeId)) {
+        return std::nullopt;
+    }
+    return nodeId == nodeA ? static_cast<StreamId>(INTERCOM_STREAM_BASE + 1) : INTERCOM_STREAM_BASE;
+}
+
+std::optional<std::string> IntercomSession::speakerOf(StreamId streamId) const {
+    if (streamId == INTERCOM_STREAM_BASE) {
+        return nodeA;
+    }
+    if (streamId == INTERCOM_STREAM_BASE + 1) {
+        return nodeB;
+    }
+    return std::nullopt;
+}
+
+bool isIntercomStream(StreamId streamId) {
+    return streamId == INTERCOM_STREAM_BASE || streamId == INTERCOM_STREAM_BASE + 1;
+}
+
+void duckFrame(AudioFrame& frame, uint32_t attenuationDb) {
+    if (attenuationDb == 0) {
+        return;
+    }
+    // Un'attenuazione riduce sempre l'ampiezza, quindi non serve saturare
+    double gain = std::pow(10.0, -static_cast<double>(attenuationDb) / 20.0);
+    for (auto& sample : frame.samples) {
+        sample = static_cast<int16_t>(std::lround(sample * gain));
+    }
+}
+
+} // namespace saber
@@ -0,0 +1,104 @@
+#include "intrusion.h"
+#include "log.h"
+
+namespace saber {
+
+std::string intrusionAlertTypeToString(IntrusionAlert::Type type) {
+    switch (type) {
+        case IntrusionAlert::Type::RogueBeacon:
+            return "rogue_beacon";
+        case IntrusionAlert::Type::SignatureBurst:
+            return "signature_burst";
+        case IntrusionAlert::Type::NonceReuse:
+            return "nonce_reuse";
+        case IntrusionAlert::Type::RouteFlapping:
+            return "route_flapping";
+    }
+    return "unknown";
+}
+
+// Implementazione di IntrusionDetector
+IntrusionDetector::IntrusionDetector(const IntrusionConfig& config)
+    : config(config) {
+}
+
+std::optional<IntrusionAlert> IntrusionDetector::observe(IntrusionAlert::Type type, const std::string& nodeId,
+                                                         uint64_t nowMs) {
+    IntrusionAlert alert;
+    AlertHandler handler;
+    uint32_t windowMs;
+    {
+        std::lock_guard<std::mutex> lock(detectorMutex);
+        uint32_t threshold = thresholdFor(type);
+        if (!config.enabled || threshold == 0 || nodeId.empty()) {
+            return std::nullopt;
+        }
+
+        auto& recent = observations[{type, nodeId}];
+        recent.push_back(nowMs);
+        while (!recent.empty() && recent.front() + config.windowMs < nowMs) {
+            recent.pop_front();
+        }
+        if (recent.size() < threshold) {
+            return std::nullopt;
+        }
+
+        alert.type = type;
+        alert.nodeId = nodeId;
+        alert.count = static_cast<uint32_t>(recent.size());
+        alert.timestamp = nowMs;
+        alert.quarantine = config.autoQuarantine;
+        recent.clear();
+
+        alerts.push_back(alert);
+        if (alerts.size() > MAX_INTRUSION_ALERTS) {
+            alerts.pop_front();
+        }
+        handler = alertHandler;
+        windowMs = config.windowMs;
+    }
+
+    SABER_LOG(Warn, "security", "Possibile intrusione da " << nodeId << ": "
+              << intrusionAlertTypeToString(type) << " (" << alert.count << " in " << windowMs << "ms)");
+    if (handler) {
+        handler(alert);
+    }
+    return alert;
+}
+
+void IntrusionDetector::setAlertHandler(AlertHandler handler) {
+    std::lock_guard<std::mutex> lock(detectorMutex);
+    alertHandler = std::move(handler);
+}
+
+void IntrusionDetector::setConfig(const IntrusionConfig& config) {
+    std::lock_guard<std::mutex> lock(detectorMutex);
+    this->config = config;
+    observations.clear();
+}
+
+IntrusionConfig IntrusionDetector::getConfig() const {
+    std::lock_guard<std::mutex> lock(detectorMutex);
+    return config;
+}
+
+std::vector<IntrusionAlert> IntrusionDetector::getAlerts() const {
+    std::lock_guard<std::mutex> lock(detectorMutex);
+    return std::vector<IntrusionAlert>(alerts.begin(), alerts.end());
+}
+
+uint32_t IntrusionDetector::thresholdFor(IntrusionAlert::Type type) const {
+    switch (type) {
+        case IntrusionAlert::Type::RogueBeacon:
+            return config.beaconThreshold;
+        case IntrusionAlert::Type::SignatureBurst:
+            return config.signatureFailureThreshold;
+        case IntrusionAlert::Type::NonceReuse:
+            return config.replayThreshold;
+        case IntrusionAlert::Type::RouteFlapping:
+            return config.routeFlapThreshold;
+    }
+    return 0;
+}
+
+} // namespace saber
@@ -0,0 +1,141 @@
+#include "jitter_buffer.h"
+
+#include <algorithm>
+#include <cmath>
+
+namespace saber {
+
+namespace {
+
+// Frame per finestra su cui si misura la perdita
+const uint32_t LOSS_WINDOW_FRAMES = 100;
+
+// Un buco più lungo è una ripartenza dello stream, non una perdita
+const int32_t MAX_COUNTED_GAP = 1000;
+
+// Il buffer deve coprire tre volte il jitter stimato
+const double JITTER_COVERAGE = 3.0;
+
+} // namespace
+
+// Implementazione di AdaptiveJitterBuffer
+AdaptiveJitterBuffer::AdaptiveJitterBuffer(const JitterBufferConfig& config, uint32_t bufferMs)
+    : config(config), bufferMs(bufferMs) {
+    reset(bufferMs);
+}
+
+void AdaptiveJitterBuffer::setConfig(const JitterBufferConfig& updated) {
+    std::lock_guard<std::mutex> lock(bufferMutex);
+    config = updated;
+    bufferMs = std::min(std::max(bufferMs, config.minMs), std::max(config.minMs, config.maxMs));
+}
+
+JitterBufferConfig AdaptiveJitterBuffer::getConfig() const {
+    std::lock_guard<std::mutex> lock(bufferMutex);
+    return config;
+}
+
+void AdaptiveJitterBuffer::reset(uint32_t initialMs, bool pin) {
+    std::lock_guard<std::mutex> lock(bufferMutex);
+    // Una dimensione imposta vale così com'è, anche fuori dagli estremi
+    bufferMs = pin ? initialMs : std::min(std::max(initialMs, config.minMs), std::max(config.minMs, config.maxMs));
+    pinned = pin;
+    jitterUs = 0.0;
+    streams.clear();
+    windowFrames = 0;
+    windowLost = 0;
+    lastChangeUs.reset();
+    maxLeadUs = 0;
+    stats = JitterBufferStats();
+}
+
+std::optional<uint32_t> AdaptiveJitterBuffer::recordFrame(StreamId streamId, uint32_t sequence, 
+                                                          uint64_t playoutTimeUs, uint64_t arrivalUs) {
+    std::lock_guard<std::mutex> lock(bufferMutex);
+    stats.frames++;
+    windowFrames++;
+    
+    int64_t leadUs = static_cast<int64_t>(playoutTimeUs) - static_cast<int64_t>(arrivalUs);
+    bool late = leadUs < 0;
+    bool early = leadUs > static_cast<int64_t>(bufferMs) * 1000;
+    if (late) {
+        stats.underruns++;
+    } else if (early) {
+        stats.overruns++;
+    }
+    maxLeadUs = std::max(maxLeadUs, leadUs);
+    stats.leadMs = leadUs / 1000;
+    
+    auto it = streams.find(streamId);
+    if (it != streams.end()) {
+        int32_t gap = static_cast<int32_t>(sequence - it->second.sequence);
+        if (gap <= 0) {
+            // Duplicato o fuori ordine: non entra nelle stime
+            return std::nullopt;
+        }
+        if (gap > 1 && gap <= MAX_COUNTED_GAP) {
+            stats.lost += gap - 1;
+            windowLost += gap - 1;
+        }
+        // Jitter come in RFC 3550: scarto tra intervallo di arrivo e intervallo di riproduzione
+        int64_t transit = static_cast<int64_t>(arrivalUs - it->second.arrivalUs) 
+                        - static_cast<int64_t>(playoutTimeUs - it->second.playoutTimeUs);
+        jitterUs += (std::abs(static_cast<double>(transit)) - jitterUs) / 16.0;
+    }
+    streams[streamId] = StreamState{sequence, playoutTimeUs, arrivalUs};
+    if (!lastChangeUs) {
+        lastChangeUs = arrivalUs;
+    }
+    
+    bool lossy = false;
+    if (windowFrames + windowLost >= LOSS_WINDOW_FRAMES) {
+        stats.lossPercent = 100.0 * windowLost / (windowFrames + windowLost);
+        lossy = stats.lossPercent > config.maxLossPercent;
+        windowFrames = 0;
+        windowLost = 0;
+    }
+    if (!config.adaptive || pinned) {
+        return std::nullopt;
+    }
+    
+    // Il buffer deve contenere i frame più in anticipo e assorbire il jitter
+    auto neededMs = static_cast<int64_t>(std::ceil(std::max(JITTER_COVERAGE * jitterUs, 
+                                                            static_cast<double>(maxLeadUs)) / 1000.0));
+    if (late || early || lossy || neededMs > bufferMs) {
+        // Anche al massimo, finché c'è motivo di crescere il buffer non si riduce
+        return resizeTo(static_cast<int64_t>(bufferMs) + config.stepMs, arrivalUs);
+    }
+    if (arrivalUs - *lastChangeUs >= static_cast<uint64_t>(config.shrinkAfterMs) * 1000 
+        && static_cast<int64_t>(bufferMs) - config.stepMs >= neededMs) {
+        return resizeTo(static_cast<int64_t>(bufferMs) - config.stepMs, arrivalUs);
+    }
+    return std::nullopt;
+}
+
+uint32_t AdaptiveJitterBuffer::getBufferMs() const {
+    std::lock_guard<std::mutex> lock(bufferMutex);
+    return bufferMs;
+}
+
+JitterBufferStats AdaptiveJitterBuffer::getStats() const {
+    std::lock_guard<std::mutex> lock(bufferMutex);
+    JitterBufferStats result = stats;
+    result.bufferMs = bufferMs;
+    result.jitterMs = jitterUs / 1000.0;
+    return result;
+}
+
+std::optional<uint32_t> AdaptiveJitterBuffer::resizeTo(int64_t targetMs, uint64_t nowUs) {
+    int64_t maxMs = std::max(config.minMs, config.maxMs);
+    auto clamped = static_cast<uint32_t>(std::min<int64_t>(std::max<int64_t>(targetMs, config.minMs), maxMs));
+    lastChangeUs = nowUs;
+    maxLeadUs = 0;
+    if (clamped == bufferMs) {
+        return std::nullopt;
+    }
+    bufferMs = clamped;
+    stats.resizes++;
+    return bufferMs;
+}
+
+} // namespace saber
@@ -0,0 +1,183 @@
+#include "journal.h"
+#include "log.h"
+
+#include <cstdio>
+#include <iostream>
+#include <sstream>
+
+namespace saber {
+
+namespace {
+
+// I campi sono separati da tabulazioni: tabulazioni e a capo vengono protetti
+std::string escapeField(const std::string& value) {
+    std::string escaped;
+    escaped.reserve(value.size());
+    for (char c : value) {
+        switch (c) {
+            case '\\': escaped += "\\\\"; break;
+            case '\t': escaped += "\\t"; break;
+            case '\n': escaped += "\\n"; break;
+            default: escaped += c; break;
+        }
+    }
+    return escaped;
+}
+
+std::string unescapeField(const std::string& value) {
+    std::string result;
+    result.reserve(value.size());
+    for (size_t i = 0; i < value.size(); ++i) {
+        if (value[i] == '\\' && i + 1 < value.size()) {
+            char next = value[++i];
+            result += next == 't' ? '\t' : next == 'n' ? '\n' : next;
+        } else {
+            result += value[i];
+        }
+    }
+    return result;
+}
+
+std::string formatEvent(const JournalEvent& event) {
+    return std::to_string(event.cursor) + "\t" + std::to_string(event.timestamp) + "\t"
+         + escapeField(event.category) + "\t" + escapeField(event.type) + "\t"
+         + escapeField(event.nodeId) + "\t" + escapeField(event.detail);
+}
+
+bool parseEvent(const std::string& line, JournalEvent& event) {
+    std::vector<std::string> fields;
+    std::istringstream input(line);
+    std::string field;
+    while (std::getline(input, field, '\t')) {
+        fields.push_back(field);
+    }
+    if (fields.size() == 5) {
+        fields.emplace_back();
+    }
+    if (fields.size() != 6) {
+        return false;
+    }
+    try {
+        event.cursor = std::stoull(fields[0]);
+        event.timestamp = std::stoull(fields[1]);
+    } catch (const std::exception&) {
+        return false;
+    }
+    event.category = unescapeField(fields[2]);
+    event.type = unescapeField(fields[3]);
+    event.nodeId = unescapeField(fields[4]);
+    event.detail = unescapeField(fields[5]);
+    return event.cursor > 0;
+}
+
+} // namespace
+
+// Implementazione di EventJournal
+EventJournal::EventJournal(const std::string& path, size_t maxEntries)
+    : path(path), maxEntries(maxEntries == 0 ? 1 : maxEntries), nextCursor(1), fileLines(0) {
+    if (path.empty()) {
+        return;
+    }
+
+    std::ifstream existing(path);
+    std::string line;
+    while (std::getline(existing, line)) {
+        JournalEvent event;
+        // Un'ultima riga troncata da uno spegnimento viene ignorata
+        if (!parseEvent(line, event) || event.cursor < nextCursor) {
+            continue;
+        }
+        events.push_back(event);
+        nextCursor = event.cursor + 1;
+        fileLines++;
+        if (events.size() > this->maxEntries) {
+            events.pop_front();
+        }
+    }
+    existing.close();
+
+    if (fileLines > this->maxEntries) {
+        compactLocked();
+    } else {
+        file.open(path, std::ios::app);
+    }
+    if (!file.is_open()) {
+        SABER_LOG(Error, "journal", "Impossibile aprire il diario degli eventi: " << path);
+    }
+}
+
+uint64_t EventJournal::append(const std::string& category, const std::string& type, const std::string& nodeId,
+                              const std::string& detail, uint64_t timestamp) {
+    std::lock_guard<std::mutex> lock(journalMutex);
+    JournalEvent event{nextCursor++, timestamp, category, type, nodeId, detail};
+    events.push_back(event);
+    if (events.size() > maxEntries) {
+        events.pop_front();
+    }
+
+    if (file.is_open()) {
+        file << formatEvent(event) << "\n";
+        file.flush();
+        if (++fileLines > maxEntries * 2) {
+            compactLocked();
+        }
+    }
+    return event.cursor;
+}
+
+JournalPage EventJournal::since(uint64_t cursor, size_t limit) const {
+    std::lock_guard<std::mutex> lock(journalMutex);
+    JournalPage page;
+    if (cursor >= nextCursor) {
+        // Cursore di un diario precedente (file rimosso): si riparte dall'inizio
+        cursor = 0;
+        page.truncated = true;
+    }
+    page.nextCursor = cursor;
+    if (events.empty()) {
+        return page;
+    }
+
+    // Il client ha perso eventi se il più vecchio conservato non segue il suo cursore
+    page.truncated = page.truncated || events.front().cursor > cursor + 1;
+    for (const auto& event : events) {
+        if (event.cursor <= cursor) {
+            continue;
+        }
+        if (page.events.size() >= limit) {
+            page.hasMore = true;
+            break;
+        }
+        page.events.push_back(event);
+        page.nextCursor = event.cursor;
+    }
+    return page;
+}
+
+uint64_t EventJournal::latestCursor() const {
+    std::lock_guard<std::mutex> lock(journalMutex);
+    return nextCursor - 1;
+}
+
+void EventJournal::compactLocked() {
+    if (file.is_open()) {
+        file.close();
+    }
+
+    // Scrittura su file temporaneo e rinomina per non perdere il diario
+    std::string tmpPath = path + ".tmp";
+    {
+        std::ofstream compacted(tmpPath, std::ios::trunc);
+        for (const auto& event : events) {
+            compacted << formatEvent(event) << "\n";
+        }
+    }
+    if (std::rename(tmpPath.c_str(), path.c_str()) != 0) {
+        std::remove(path.c_str());
+        std::rename(tmpPath.c_str(), path.c_str());
+    }
+    fileLines = events.size();
+    file.open(path, std::ios::app);
+}
+
+} // namespace saber
@@ -0,0 +1,184 @@
+#include "log.h"
+
+#include <iostream>
+#include <stdexcept>
+#include <vector>
+
+namespace saber {
+
+namespace {
+
+/// Span aperti nel thread corrente, dal più esterno
+thread_local std::vector<std::string> openSpans;
+
+std::string trim(const std::string& value) {
+    auto begin = value.find_first_not_of(" \t");
+    if (begin == std::string::npos) {
+        return "";
+    }
+    auto end = value.find_last_not_of(" \t");
+    return value.substr(begin, end - begin + 1);
+}
+
+} // namespace
+
+std::string logLevelToString(LogLevel level) {
+    switch (level) {
+        case LogLevel::Off: return "off";
+        case LogLevel::Error: return "error";
+        case LogLevel::Warn: return "warn";
+        case LogLevel::Info: return "info";
+        case LogLevel::Debug: return "debug";
+        case LogLevel::Trace: return "trace";
+    }
+    return "info";
+}
+
+std::optional<LogLevel> logLevelFromString(const std::string& value) {
+    static const std::map<std::string, LogLevel> levels = {
+        {"off", LogLevel::Off},
+        {"error", LogLevel::Error},
+        {"warn", LogLevel::Warn},
+        {"info", LogLevel::Info},
+        {"debug", LogLevel::Debug},
+        {"trace", LogLevel::Trace},
+    };
+    auto it = levels.find(value);
+    if (it == levels.end()) {
+        return std::nullopt;
+    }
+    return it->second;
+}
+
+// Implementazione di LogFilter
+LogFilter::LogFilter(LogLevel defaultLevel)
+    : defaultLevel(defaultLevel) {
+}
+
+LogFilter LogFilter::parse(const std::string& spec) {
+    LogFilter filter;
+    std::istringstream directives(spec);
+    std::string directive;
+
+    while (std::getline(directives, directive, ',')) {
+        directive = trim(directive);
+        if (directive.empty()) {
+            continue;
+        }
+
+        auto eq = directive.find('=');
+        std::string levelName = eq == std::string::npos ? directive : trim(directive.substr(eq + 1));
+        auto level = logLevelFromString(levelName);
+        if (!level) {
+            throw std::invalid_argument("Livello di log non valido: " + levelName);
+        }
+
+        if (eq == std::string::npos) {
+            filter.defaultLevel = *level;
+        } else {
+            std::string target = trim(directive.substr(0, eq));
+            if (target.empty()) {
+                throw std::invalid_argument("Target di log vuoto in: " + directive);
+            }
+            filter.targets[target] = *level;
+        }
+    }
+
+    return filter;
+}
+
+void LogFilter::setLevel(const std::string& target, LogLevel level) {
+    if (target.empty()) {
+        defaultLevel = level;
+    } else {
+        targets[target] = level;
+    }
+}
+
+LogLevel LogFilter::levelFor(const std::string& target) const {
+    auto it = targets.find(target);
+    return it != targets.end() ? it->second : defaultLevel;
+}
+
+bool LogFilter::enabled(const std::string& target, LogLevel level) const {
+    return level != LogLevel::Off && level <= levelFor(target);
+}
+
+std::string LogFilter::toString() const {
+    std::string result = logLevelToString(defaultLevel);
+    for (const auto& pair : targets) {
+        result += "," + pair.first + "=" + logLevelToString(pair.second);
+    }
+    return result;
+}
+
+// Implementazione di Logger
+Logger& Logger::instance() {
+    static Logger logger;
+    return logger;
+}
+
+void Logger::setFilter(const LogFilter& newFilter) {
+    std::lock_guard<std::mutex> lock(logMutex);
+    filter = newFilter;
+}
+
+LogFilter Logger::getFilter() const {
+    std::lock_guard<std::mutex> lock(logMutex);
+    return filter;
+}
+
+void Logger::setLevel(const std::string& target, LogLevel level) {
+    std::lock_guard<std::mutex> lock(logMutex);
+    filter.setLevel(target, level);
+}
+
+void Logger::setSink(LogSink newSink) {
+    std::lock_guard<std::mutex> lock(logMutex);
+    sink = std::move(newSink);
+}
+
+bool Logger::enabled(const std::string& target, LogLevel level) const {
+    std::lock_guard<std::mutex> lock(logMutex);
+    return filter.enabled(target, level);
+}
+
+void Logger::write(const std::string& target, LogLevel level, const std::string& message) {
+    std::string text = LogSpan::current() + message;
+    // I resoconti su più righe terminano già con un a capo
+    while (!text.empty() && text.back() == '\n') {
+        text.pop_back();
+    }
+
+    LogSink destination;
+    {
+        std::lock_guard<std::mutex> lock(logMutex);
+        if (!sink) {
+            std::ostream& out = level <= LogLevel::Warn ? std::cerr : std::cout;
+            out << "[" << logLevelToString(level) << " " << target << "] " << text << std::endl;
+            return;
+        }
+        destination = sink;
+    }
+    // Fuori dal lock: la destinazione può richiedere altri lock (es. il GIL di Python)
+    destination(target, level, text);
+}
+
+// Implementazione di LogSpan
+LogSpan::LogSpan(const std::string& name, const std::string& fields) {
+    openSpans.push_back(fields.empty() ? name : name + "{" + fields + "}");
+}
+
+LogSpan::~LogSpan() {
+    openSpans.pop_back();
+}
+
+std::string LogSpan::current() {
+    std::string prefix;
+    for (const auto& span : openSpans) {
+        prefix += span + ": ";
+    }
+    return prefix;
+}
+
+} // namespace saber
@@ -1,15 +1,213 @@
 #include "../include/mesh.h"
+#include "../include/crypto.h"
+#include "../include/intercom.h"
+#include "../include/log.h"
+#include "../include/spec.h"
+#include "../include/util.h"
+#include "../include/wire.h"
 
 #include <algorithm>
+#include <cctype>
 #include <chrono>
 #include <cstring>
 #include <iostream>
+#include <limits>
+#include <queue>
 #include <random>
+#include <sstream>
 #include <stdexcept>
 #include <thread>
+#include <tuple>
 
 namespace saber {
 
+namespace {
+
+// Timestamp monotono in microsecondi per i ritardi di inoltro
+int64_t steadyMicros() {
+    return std::chrono::duration_cast<std::chrono::microseconds>(
+        std::chrono::steady_clock::now().time_since_epoch()).count();
+}
+
+// Istante corrente sull'orologio della rete, confrontabile con i tempi di riproduzione
+uint64_t networkMicros() {
+    return std::chrono::duration_cast<std::chrono::microseconds>(
+        std::chrono::system_clock::now().time_since_epoch()).count();
+}
+
+// Le conferme delle richieste di ingresso viaggiano in chiaro come le richieste stesse
+bool travelsInClear(const MeshPacket& packet) {
+    MeshPacketType type = packet.getType();
+    return type == MeshPacketType::Join || type == MeshPacketType::Pairing
+        || (type == MeshPacketType::Ack && packet.getAckData().ackedType == MeshPacketType::Join);
+}
+
+// Il traffico rivolto ad un solo nodo: comandi con destinatario e conferme all'origine
+std::string unicastPeer(const MeshPacket& packet) {
+    if (packet.getType() == MeshPacketType::Command) {
+        auto params = packet.getCommandData().second;
+        auto target = params.find("target");
+        return target != params.end() ? target->second : "";
+    }
+    if (packet.getType() == MeshPacketType::Ack) {
+        return packet.getAckData().origin;
+    }
+    return "";
+}
+
+} // namespace
+
+std::string nodeRoleToString(NodeRole role) {
+    switch (role) {
+        case NodeRole::Master:
+            return "master";
+        case NodeRole::Repeater:
+            return "repeater";
+        case NodeRole::Sink:
+            return "sink";
+    }
+    return "unknown";
+}
+
+std::string meshPacketTypeToString(MeshPacketType type) {
+    switch (type) {
+        case MeshPacketType::Ping:
+            return "ping";
+        case MeshPacketType::Command:
+            return "command";
+        case MeshPacketType::Status:
+            return "status";
+        case MeshPacketType::TimeBeacon:
+            return "time_beacon";
+        case MeshPacketType::EmergencySync:
+            return "emergency_sync";
+        case MeshPacketType::Subscribe:
+            return "subscribe";
+        case MeshPacketType::Unsubscribe:
+            return "unsubscribe";
+        case MeshPacketType::Reject:
+            return "reject";
+        case MeshPacketType::Metadata:
+            return "metadata";
+        case MeshPacketType::Artwork:
+            return "artwork";
+        case MeshPacketType::Audio:
+            return "audio";
+        case MeshPacketType::Nack:
+            return "nack";
+        case MeshPacketType::Join:
+            return "join";
+        case MeshPacketType::Pairing:
+            return "pairing";
+        case MeshPacketType::Ack:
+            return "ack";
+    }
+    return "unknown";
+}
+
+std::optional<MeshPacketType> meshPacketTypeFromString(const std::string& name) {
+    for (int value = static_cast<int>(MeshPacketType::Ping); value <= static_cast<int>(MeshPacketType::Ack);
+         ++value) {
+        auto type = static_cast<MeshPacketType>(value);
+        if (meshPacketTypeToString(type) == name) {
+            return type;
+        }
+    }
+    return std::nullopt;
+}
+
+std::string rejectReasonToString(RejectReason reason) {
+    switch (reason) {
+        case RejectReason::BadSignature:
+            return "bad_signature";
+        case RejectReason::Replay:
+            return "replay";
+        case RejectReason::RateLimited:
+            return "rate_limited";
+        case RejectReason::UnknownStream:
+            return "unknown_stream";
+        case RejectReason::UnknownSender:
+            return "unknown_sender";
+        case RejectReason::WrongNetworkKey:
+            return "wrong_network_key";
+        case RejectReason::StaleEpoch:
+            return "stale_epoch";
+        case RejectReason::Quarantined:
+            return "quarantined";
+        case RejectReason::ProvisioningClosed:
+            return "provisioning_closed";
+        case RejectReason::CapacityExceeded:
+            return "capacity_exceeded";
+        case RejectReason::TtlExpired:
+            return "ttl_expired";
+        case RejectReason::RoutingLoop:
+            return "routing_loop";
+        case RejectReason::Unauthorized:
+            return "unauthorized";
+    }
+    return "unknown";
+}
+
+std::string pairingStepToString(PairingStep step) {
+    switch (step) {
+        case PairingStep::Hello:
+            return "hello";
+        case PairingStep::Challenge:
+            return "challenge";
+        case PairingStep::Proof:
+            return "proof";
+        case PairingStep::Welcome:
+            return "welcome";
+        case PairingStep::Failed:
+            return "failed";
+    }
+    return "unknown";
+}
+
+uint16_t shortNodeId(const std::string& nodeId) {
+    uint32_t hash = 2166136261u;
+    for (unsigned char c : nodeId) {
+        hash = (hash ^ c) * 16777619u;
+    }
+    return static_cast<uint16_t>(hash ^ (hash >> 16));
+}
+
+std::string meshEventTypeToString(MeshEvent::Type type) {
+    switch (type) {
+        case MeshEvent::Type::NodeJoined:
+            return "node_joined";
+        case MeshEvent::Type::NodeLost:
+            return "node_lost";
+        case MeshEvent::Type::NodeReturned:
+            return "node_returned";
+        case MeshEvent::Type::NodeEvicted:
+            return "node_evicted";
+        case MeshEvent::Type::ZoneChanged:
+            return "zone_changed";
+        case MeshEvent::Type::AdmissionRejected:
+            return "admission_rejected";
+        case MeshEvent::Type::DeliveryFailed:
+            return "delivery_failed";
+    }
+    return "unknown";
+}
+
+std::optional<NodeRole> nodeRoleFromString(const std::string& name) {
+    std::string lower = name;
+    std::transform(lower.begin(), lower.end(), lower.begin(),
+                   [](unsigned char c) { return static_cast<char>(std::tolower(c)); });
+    if (lower == "master") {
+        return NodeRole::Master;
+    }
+    if (lower == "repeater") {
+        return NodeRole::Repeater;
+    }
+    if (lower == "sink") {
+        return NodeRole::Sink;
+    }
+    return std::nullopt;
+}
+
 // Implementazione di Node
 Node::Node(const std::string& id, NodeRole role)
     : id(id), role(role), latency(0), bufferState(100) {
@@ -31,16 +229,66 @@ uint32_t Node::getLatency() const {
     return latency;
 }
 
+uint8_t Node::getBufferState() const {
+    return bufferState;
+}
+
+void Node::setSignalStrength(int32_t rssiDbm) {
+    signalStrength = rssiDbm;
+}
+
+std::optional<int32_t> Node::getSignalStrength() const {
+    return signalStrength;
+}
+
+void Node::recordPacketSuccess(double successRate) {
+    successRate = std::clamp(successRate, 0.0, 1.0);
+    if (packetSuccessRate) {
+        packetSuccessRate = *packetSuccessRate + LINK_SUCCESS_SMOOTHING * (successRate - *packetSuccessRate);
+    } else {
+        packetSuccessRate = successRate;
+    }
+}
+
+std::optional<double> Node::getPacketSuccessRate() const {
+    return packetSuccessRate;
+}
+
+double Node::getLinkQuality() const {
+    double quality = packetSuccessRate.value_or(1.0);
+    if (signalStrength) {
+        double strength = static_cast<double>(*signalStrength - LINK_RSSI_WEAK_DBM) / 
+                          (LINK_RSSI_STRONG_DBM - LINK_RSSI_WEAK_DBM);
+        quality *= 0.5 + 0.5 * std::clamp(strength, 0.0, 1.0);
+    }
+    return quality;
+}
+
+bool Node::hasPinged() const {
+    return lastPing.has_value();
+}
+
+std::optional<int64_t> Node::getLastSeenMs() const {
+    if (!lastPing) {
+        return std::nullopt;
+    }
+    return std::chrono::duration_cast<std::chrono::milliseconds>(std::chrono::steady_clock::now() - *lastPing).count();
+}
+
 bool Node::isActive() const {
     if (!lastPing) {
         return false;
     }
     
     auto now = std::chrono::steady_clock::now();
-    auto diff = std::chrono::duration_cast<std::chrono::seconds>(now - *lastPing).count();
+    auto diff = std::chrono::duration_cast<std::chrono::milliseconds>(now - *lastPing).count();
     
-    // Consideriamo attivo un nodo che ha inviato un ping negli ultimi 30 secondi
-    return diff < 30;
+    // Consideriamo attivo un nodo che ha inviato un ping entro il timeout
+    return diff < static_cast<int64_t>(heartbeatTimeoutMs);
+}
+
+void Node::setHeartbeatTimeout(uint32_t timeoutMs) {
+    heartbeatTimeoutMs = timeoutMs;
 }
 
 // Implementazione di MeshPacket
@@ -62,6 +310,34 @@ MeshPacket::MeshPacket(MeshPacketType type) : type(type) {
         case MeshPacketType::EmergencySync:
             new (&data.emergencySync) EmergencySyncData();
             break;
+        case MeshPacketType::Subscribe:
+        case MeshPacketType::Unsubscribe:
+            new (&data.subscription) SubscriptionData();
+            break;
+        case MeshPacketType::Reject:
+            new (&data.reject) RejectInfo();
+            break;
+        case MeshPacketType::Metadata:
+            new (&data.metadata) StreamMetadata();
+            break;
+        case MeshPacketType::Artwork:
+            new (&data.artwork) ArtworkData();
+            break;
+        case MeshPacketType::Audio:
+            new (&data.audio) AudioFrameInfo();
+            break;
+        case MeshPacketType::Nack:
+            new (&data.nack) NackInfo();
+            break;
+        case MeshPacketType::Join:
+            new (&data.join) JoinInfo();
+            break;
+        case MeshPacketType::Pairing:
+            new (&data.pairing) PairingInfo();
+            break;
+        case MeshPacketType::Ack:
+            new (&data.ack) AckInfo();
+            break;
     }
 }
 
@@ -71,6 +347,7 @@ MeshPacket::~MeshPacket() {
 
 MeshPacket::MeshPacket(const MeshPacket& other) : type(other.type) {
     copyDataFromOther(other);
+    copyHeaderFromOther(other);
 }
 
 MeshPacket& MeshPacket::operator=(const MeshPacket& other) {
@@ -78,12 +355,15 @@ MeshPacket& MeshPacket::operator=(const MeshPacket& other) {
         destroyData();
         type = other.type;
         copyDataFromOther(other);
+        copyHeaderFromOther(other);
     }
     return *this;
 }
 
 MeshPacket::MeshPacket(MeshPacket&& other) noexcept : type(other.type) {
     copyDataFromOther(other);
+    copyHeaderFromOther(other);
+    other.destroyData();
     other.type = MeshPacketType::Ping; // Reset other to a known state
     new (&other.data.ping) PingData(); // Initialize with empty data
 }
@@ -93,6 +373,8 @@ MeshPacket& MeshPacket::operator=(MeshPacket&& other) noexcept {
         destroyData();
         type = other.type;
         copyDataFromOther(other);
+        copyHeaderFromOther(other);
+        other.destroyData();
         other.type = MeshPacketType::Ping; // Reset other to a known state
         new (&other.data.ping) PingData(); // Initialize with empty data
     }
@@ -116,9 +398,54 @@ void MeshPacket::copyDataFromOther(const MeshPacket& other) {
         case MeshPacketType::EmergencySync:
             new (&data.emergencySync) EmergencySyncData(other.data.emergencySync);
             break;
+        case MeshPacketType::Subscribe:
+        case MeshPacketType::Unsubscribe:
+            new (&data.subscription) SubscriptionData(other.data.subscription);
+            break;
+        case MeshPacketType::Reject:
+            new (&data.reject) RejectInfo(other.data.reject);
+            break;
+        case MeshPacketType::Metadata:
+            new (&data.metadata) StreamMetadata(other.data.metadata);
+            break;
+        case MeshPacketType::Artwork:
+            new (&data.artwork) ArtworkData(other.data.artwork);
+            break;
+        case MeshPacketType::Audio:
+            new (&data.audio) AudioFrameInfo(other.data.audio);
+            break;
+        case MeshPacketType::Nack:
+            new (&data.nack) NackInfo(other.data.nack);
+            break;
+        case MeshPacketType::Join:
+            new (&data.join) JoinInfo(other.data.join);
+            break;
+        case MeshPacketType::Pairing:
+            new (&data.pairing) PairingInfo(other.data.pairing);
+            break;
+        case MeshPacketType::Ack:
+            new (&data.ack) AckInfo(other.data.ack);
+            break;
     }
 }
 
+void MeshPacket::copyHeaderFromOther(const MeshPacket& other) {
+    source = other.source;
+    sequence = other.sequence;
+    originTtl = other.originTtl;
+    ttl = other.ttl;
+    recordPath = other.recordPath;
+    path = other.path;
+    traceContext = other.traceContext;
+    bridges = other.bridges;
+    relayDelayUs = other.relayDelayUs;
+    arrivalUs = other.arrivalUs;
+    senderRole = other.senderRole;
+    signature = other.signature;
+    sealed = other.sealed;
+    sessionPeer = other.sessionPeer;
+}
+
 void MeshPacket::destroyData() {
     switch (type) {
         case MeshPacketType::Ping:
@@ -136,6 +463,34 @@ void MeshPacket::destroyData() {
         case MeshPacketType::EmergencySync:
             data.emergencySync.~EmergencySyncData();
             break;
+        case MeshPacketType::Subscribe:
+        case MeshPacketType::Unsubscribe:
+            data.subscription.~SubscriptionData();
+            break;
+        case MeshPacketType::Reject:
+            data.reject.~RejectInfo();
+            break;
+        case MeshPacketType::Metadata:
+            data.metadata.~StreamMetadata();
+            break;
+        case MeshPacketType::Artwork:
+            data.artwork.~ArtworkData();
+            break;
+        case MeshPacketType::Audio:
+            data.audio.~AudioFrameInfo();
+            break;
+        case MeshPacketType::Nack:
+            data.nack.~NackInfo();
+            break;
+        case MeshPacketType::Join:
+            data.join.~JoinInfo();
+            break;
+        case MeshPacketType::Pairing:
+            data.pairing.~PairingInfo();
+            break;
+        case MeshPacketType::Ack:
+            data.ack.~AckInfo();
+            break;
     }
 }
 
@@ -154,6 +509,13 @@ MeshPacket MeshPacket::createCommand(const std::string& cmdType,
     return packet;
 }
 
+MeshPacket MeshPacket::createZoneCommand(const std::string& zone, const std::string& cmdType,
+                                       const std::map<std::string, std::string>& params) {
+    MeshPacket packet = createCommand(cmdType, params);
+    packet.data.command.params["zone"] = zone;
+    return packet;
+}
+
 MeshPacket MeshPacket::createStatus(const std::string& nodeId, uint8_t buffer, uint32_t latency) {
     MeshPacket packet(MeshPacketType::Status);
     packet.data.status.nodeId = nodeId;
@@ -176,6 +538,31 @@ MeshPacket MeshPacket::createEmergencySync(uint64_t masterTime,
     return packet;
 }
 
+MeshPacket MeshPacket::createSubscribe(const std::string& nodeId, StreamId streamId) {
+    MeshPacket packet(MeshPacketType::Subscribe);
+    packet.data.subscription.nodeId = nodeId;
+    packet.data.subscription.streamId = streamId;
+    return packet;
+}
+
+MeshPacket MeshPacket::createUnsubscribe(const std::string& nodeId, StreamId streamId) {
+    MeshPacket packet(MeshPacketType::Unsubscribe);
+    packet.data.subscription.nodeId = nodeId;
+    packet.data.subscription.streamId = streamId;
+    return packet;
+}
+
+MeshPacket MeshPacket::createReject(const MeshPacket& rejected, RejectReason reason,
+                                   const std::string& detail) {
+    MeshPacket packet(MeshPacketType::Reject);
+    packet.data.reject.origin = rejected.getSource();
+    packet.data.reject.rejectedType = rejected.getType();
+    packet.data.reject.rejectedSequence = rejected.getSequence();
+    packet.data.reject.reason = reason;
+    packet.data.reject.detail = detail;
+    return packet;
+}
+
 MeshPacketType MeshPacket::getType() const {
     return type;
 }
@@ -194,6 +581,17 @@ std::pair<std::string, std::map<std::string, std::string>> MeshPacket::getComman
     return {data.command.cmdType, data.command.params};
 }
 
+std::optional<std::string> MeshPacket::getCommandZone() const {
+    if (type != MeshPacketType::Command) {
+        throw std::runtime_error("Pacchetto non è di tipo Command");
+    }
+    auto it = data.command.params.find("zone");
+    if (it == data.command.params.end()) {
+        return std::nullopt;
+    }
+    return it->second;
+}
+
 std::tuple<std::string, uint8_t, uint32_t> MeshPacket::getStatusData() const {
     if (type != MeshPacketType::Status) {
         throw std::runtime_error("Pacchetto non è di tipo Status");
@@ -215,135 +613,2650 @@ std::pair<uint64_t, std::vector<std::string>> MeshPacket::getEmergencySyncData()
     return {data.emergencySync.masterTime, data.emergencySync.targetNodes};
 }
 
-// Implementazione di MeshNetwork
-MeshNetwork::MeshNetwork(const Node& localNode) 
-    : localNode(localNode), running(false) {
-    // Registra il nodo locale
-    nodes[localNode.id] = localNode;
+std::pair<std::string, StreamId> MeshPacket::getSubscriptionData() const {
+    if (type != MeshPacketType::Subscribe && type != MeshPacketType::Unsubscribe) {
+        throw std::runtime_error("Pacchetto non è di tipo Subscribe/Unsubscribe");
+    }
+    return {data.subscription.nodeId, data.subscription.streamId};
 }
 
-MeshNetwork::~MeshNetwork() {
-    stop();
+MeshPacket::RejectInfo MeshPacket::getRejectData() const {
+    if (type != MeshPacketType::Reject) {
+        throw std::runtime_error("Pacchetto non è di tipo Reject");
+    }
+    return data.reject;
 }
 
-void MeshNetwork::start() {
-    std::lock_guard<std::mutex> lock(networkMutex);
-    if (running) {
-        return;
+MeshPacket MeshPacket::createMetadata(const StreamMetadata& metadata) {
+    MeshPacket packet(MeshPacketType::Metadata);
+    packet.data.metadata = metadata;
+    return packet;
+}
+
+StreamMetadata MeshPacket::getMetadata() const {
+    if (type != MeshPacketType::Metadata) {
+        throw std::runtime_error("Pacchetto non è di tipo Metadata");
     }
-    
-    running = true;
-    networkThread = std::make_unique<std::thread>(&MeshNetwork::runNetworkLoop, this);
+    return data.metadata;
 }
 
-void MeshNetwork::stop() {
-    {
-        std::lock_guard<std::mutex> lock(networkMutex);
-        if (!running) {
-            return;
-        }
-        
-        running = false;
+MeshPacket MeshPacket::createArtwork(const std::string& artworkHash, const std::vector<uint8_t>& image) {
+    MeshPacket packet(MeshPacketType::Artwork);
+    packet.data.artwork.hash = artworkHash;
+    packet.data.artwork.image = image;
+    return packet;
+}
+
+std::pair<std::string, std::vector<uint8_t>> MeshPacket::getArtworkData() const {
+    if (type != MeshPacketType::Artwork) {
+        throw std::runtime_error("Pacchetto non è di tipo Artwork");
     }
-    
-    // Notifica il thread di uscire
-    queueCondition.notify_all();
-    
-    if (networkThread && networkThread->joinable()) {
-        networkThread->join();
+    return {data.artwork.hash, data.artwork.image};
+}
+
+MeshPacket MeshPacket::createAudio(StreamId streamId, uint32_t frameSequence, uint64_t playoutTimeUs,
+                                   const std::vector<uint8_t>& payload, TimestampWidth width) {
+    MeshPacket packet(MeshPacketType::Audio);
+    packet.data.audio.streamId = streamId;
+    packet.data.audio.frameSequence = frameSequence;
+    packet.data.audio.playoutTimeUs = playoutTimeUs;
+    packet.data.audio.timestampWidth = width;
+    packet.data.audio.compactTimestamp = compressTimestamp(playoutTimeUs, width);
+    packet.data.audio.payload = payload;
+    return packet;
+}
+
+void MeshPacket::resolvePlayoutTime(uint64_t playoutTimeUs) {
+    if (type != MeshPacketType::Audio) {
+        throw std::runtime_error("Pacchetto non è di tipo Audio");
     }
+    data.audio.playoutTimeUs = playoutTimeUs;
 }
 
-void MeshNetwork::sendPacket(const MeshPacket& packet) {
-    std::lock_guard<std::mutex> lock(queueMutex);
-    packetQueue.push_back(packet);
-    queueCondition.notify_one();
+const MeshPacket::AudioFrameInfo& MeshPacket::getAudioData() const {
+    if (type != MeshPacketType::Audio) {
+        throw std::runtime_error("Pacchetto non è di tipo Audio");
+    }
+    return data.audio;
 }
 
-void MeshNetwork::registerNode(const std::string& nodeId, NodeRole role) {
-    std::lock_guard<std::mutex> lock(networkMutex);
-    if (nodes.find(nodeId) == nodes.end()) {
-        nodes[nodeId] = Node(nodeId, role);
+MeshPacket MeshPacket::createNack(StreamId streamId, const std::string& responder,
+                                  const std::vector<uint32_t>& frames) {
+    MeshPacket packet(MeshPacketType::Nack);
+    packet.data.nack.streamId = streamId;
+    packet.data.nack.responder = responder;
+    packet.data.nack.frames = frames;
+    return packet;
+}
+
+MeshPacket::NackInfo MeshPacket::getNackData() const {
+    if (type != MeshPacketType::Nack) {
+        throw std::runtime_error("Pacchetto non è di tipo Nack");
     }
+    return data.nack;
 }
 
-void MeshNetwork::updateNodeStatus(const std::string& nodeId, uint8_t bufferState, uint32_t latency) {
-    std::lock_guard<std::mutex> lock(networkMutex);
-    auto it = nodes.find(nodeId);
-    if (it != nodes.end()) {
-        it->second.updateBufferState(bufferState);
-        it->second.setLatency(latency);
-        it->second.updatePing();
+MeshPacket MeshPacket::createJoin(NodeRole role, const std::vector<uint8_t>& publicKey) {
+    MeshPacket packet(MeshPacketType::Join);
+    packet.data.join.role = role;
+    packet.data.join.publicKey = publicKey;
+    return packet;
+}
+
+MeshPacket::JoinInfo MeshPacket::getJoinData() const {
+    if (type != MeshPacketType::Join) {
+        throw std::runtime_error("Pacchetto non è di tipo Join");
     }
+    return data.join;
 }
 
-std::vector<std::string> MeshNetwork::getActiveNodes() const {
-    std::lock_guard<std::mutex> lock(networkMutex);
-    std::vector<std::string> activeNodes;
-    
-    for (const auto& pair : nodes) {
-        if (pair.second.isActive()) {
-            activeNodes.push_back(pair.first);
-        }
+MeshPacket MeshPacket::createAck(const MeshPacket& acked) {
+    MeshPacket packet(MeshPacketType::Ack);
+    packet.data.ack.origin = acked.getSource();
+    packet.data.ack.sequence = acked.getSequence();
+    packet.data.ack.ackedType = acked.getType();
+    return packet;
+}
+
+MeshPacket::AckInfo MeshPacket::getAckData() const {
+    if (type != MeshPacketType::Ack) {
+        throw std::runtime_error("Pacchetto non è di tipo Ack");
     }
-    
-    return activeNodes;
+    return data.ack;
 }
 
-void MeshNetwork::setPacketHandler(PacketHandler handler) {
-    std::lock_guard<std::mutex> lock(networkMutex);
-    packetHandler = handler;
+MeshPacket MeshPacket::createPairing(PairingStep step, const std::string& peer, const std::vector<uint8_t>& payload) {
+    MeshPacket packet(MeshPacketType::Pairing);
+    packet.data.pairing.step = step;
+    packet.data.pairing.peer = peer;
+    packet.data.pairing.payload = payload;
+    return packet;
 }
 
-void MeshNetwork::runNetworkLoop() {
-    while (running) {
-        std::vector<MeshPacket> packetsToProcess;
-        
-        {
-            std::unique_lock<std::mutex> lock(queueMutex);
-            queueCondition.wait_for(lock, std::chrono::milliseconds(100), 
-                                   [this] { return !packetQueue.empty() || !running; });
-            
-            if (!running) {
-                break;
-            }
-            
-            if (!packetQueue.empty()) {
-                packetsToProcess.swap(packetQueue);
-            }
-        }
-        
-        for (const auto& packet : packetsToProcess) {
-            processPacket(packet);
-        }
+MeshPacket::PairingInfo MeshPacket::getPairingData() const {
+    if (type != MeshPacketType::Pairing) {
+        throw std::runtime_error("Pacchetto non è di tipo Pairing");
     }
+    return data.pairing;
 }
 
-void MeshNetwork::processPacket(const MeshPacket& packet) {
-    std::lock_guard<std::mutex> lock(networkMutex);
-    
-    // Elabora il pacchetto in base al tipo
-    switch (packet.getType()) {
-        case MeshPacketType::Ping: {
-            auto [source, timestamp] = packet.getPingData();
-            auto it = nodes.find(source);
-            if (it != nodes.end()) {
-                it->second.updatePing();
-            }
-            break;
-        }
-        case MeshPacketType::Status: {
-            auto [nodeId, buffer, latency] = packet.getStatusData();
-            updateNodeStatus(nodeId, buffer, latency);
-            break;
-        }
-        default:
-            break;
+void MeshPacket::setHeader(const std::string& source, uint32_t sequence, uint8_t ttl) {
+    this->source = source;
+    this->sequence = sequence;
+    this->originTtl = ttl;
+    this->ttl = ttl;
+}
+
+const std::string& MeshPacket::getSource() const {
+    return source;
+}
+
+uint32_t MeshPacket::getSequence() const {
+    return sequence;
+}
+
+uint8_t MeshPacket::getTtl() const {
+    return ttl;
+}
+
+uint8_t MeshPacket::getOriginTtl() const {
+    return originTtl;
+}
+
+bool MeshPacket::decrementTtl() {
+    if (ttl == 0) {
+        return false;
     }
-    
-    // Inoltra il pacchetto al gestore registrato
-    if (packetHandler) {
-        packetHandler(packet);
+    ttl--;
+    return ttl > 0;
+}
+
+uint8_t MeshPacket::getHopCount() const {
+    return originTtl > ttl ? originTtl - ttl : 0;
+}
+
+void MeshPacket::setPathRecording(bool enabled) {
+    recordPath = enabled;
+    if (!enabled) {
+        path.clear();
+    }
+}
+
+bool MeshPacket::isPathRecording() const {
+    return recordPath;
+}
+
+bool MeshPacket::appendHop(uint16_t shortId) {
+    if (std::find(path.begin(), path.end(), shortId) != path.end() || path.size() >= MAX_RECORDED_HOPS) {
+        return false;
+    }
+    path.push_back(shortId);
+    return true;
+}
+
+const std::vector<uint16_t>& MeshPacket::getPath() const {
+    return path;
+}
+
+void MeshPacket::setTraceContext(const std::optional<TraceContext>& context) {
+    traceContext = context;
+}
+
+const std::optional<TraceContext>& MeshPacket::getTraceContext() const {
+    return traceContext;
+}
+
+bool MeshPacket::addBridge(const std::string& bridgeId) {
+    if (hasCrossedBridge(bridgeId) || bridges.size() >= MAX_BRIDGE_HOPS) {
+        return false;
+    }
+    bridges.push_back(bridgeId);
+    return true;
+}
+
+bool MeshPacket::hasCrossedBridge(const std::string& bridgeId) const {
+    return std::find(bridges.begin(), bridges.end(), bridgeId) != bridges.end();
+}
+
+const std::vector<std::string>& MeshPacket::getBridges() const {
+    return bridges;
+}
+
+void MeshPacket::addRelayDelay(uint64_t delayUs) {
+    relayDelayUs = static_cast<uint32_t>(std::min<uint64_t>(static_cast<uint64_t>(relayDelayUs) + delayUs,
+                                                            std::numeric_limits<uint32_t>::max()));
+}
+
+uint32_t MeshPacket::getRelayDelayUs() const {
+    return relayDelayUs;
+}
+
+void MeshPacket::setArrivalTime(int64_t steadyUs) {
+    arrivalUs = steadyUs;
+}
+
+std::optional<int64_t> MeshPacket::getArrivalTime() const {
+    return arrivalUs;
+}
+
+void MeshPacket::setSenderRole(NodeRole role) {
+    senderRole = role;
+}
+
+std::optional<NodeRole> MeshPacket::getSenderRole() const {
+    return senderRole;
+}
+
+size_t MeshPacket::encodedSize() const {
+    return encode().size();
+}
+
+std::vector<uint8_t> MeshPacket::signingBytes() const {
+    ByteWriter writer;
+    
+    // Dominio di firma, per non confondere i pacchetti con altri messaggi firmati
+    writer.putString("SABER-PKT");
+    
+    // Intestazione
+    writer.putU8(static_cast<uint8_t>(type));
+    writer.putString(source);
+    writer.putU32(sequence);
+    writer.putU8(originTtl);
+    
+    // I ponti compaiono solo nei pacchetti riproposti da un'altra rete
+    if (!bridges.empty()) {
+        writer.putStringList(bridges);
+    }
+    
+    // Contenuto specifico del tipo
+    writeContent(writer);
+    
+    return writer.data();
+}
+
+void MeshPacket::writeContent(ByteWriter& writer) const {
+    switch (type) {
+        case MeshPacketType::Ping:
+            writer.putString(data.ping.source);
+            writer.putU64(data.ping.timestamp);
+            break;
+        case MeshPacketType::Command:
+            writer.putString(data.command.cmdType);
+            writer.putStringMap(data.command.params);
+            break;
+        case MeshPacketType::Status:
+            writer.putString(data.status.nodeId);
+            writer.putU8(data.status.buffer);
+            writer.putU32(data.status.latency);
+            break;
+        case MeshPacketType::TimeBeacon:
+            writer.putU64(data.timeBeacon.masterTime);
+            break;
+        case MeshPacketType::EmergencySync:
+            writer.putU64(data.emergencySync.masterTime);
+            writer.putStringList(data.emergencySync.targetNodes);
+            break;
+        case MeshPacketType::Subscribe:
+        case MeshPacketType::Unsubscribe:
+            writer.putString(data.subscription.nodeId);
+            writer.putU16(data.subscription.streamId);
+            break;
+        case MeshPacketType::Reject:
+            writer.putString(data.reject.origin);
+            writer.putU8(static_cast<uint8_t>(data.reject.rejectedType));
+            writer.putU32(data.reject.rejectedSequence);
+            writer.putU8(static_cast<uint8_t>(data.reject.reason));
+            writer.putString(data.reject.detail);
+            break;
+        case MeshPacketType::Metadata:
+            writer.putU16(data.metadata.streamId);
+            writer.putU32(data.metadata.revision);
+            writer.putString(data.metadata.title);
+            writer.putString(data.metadata.artist);
+            writer.putString(data.metadata.album);
+            writer.putString(data.metadata.artworkHash);
+            break;
+        case MeshPacketType::Artwork:
+            writer.putString(data.artwork.hash);
+            writer.putBytes(data.artwork.image);
+            break;
+        case MeshPacketType::Audio:
+            writer.putU16(data.audio.streamId);
+            writer.putU32(data.audio.frameSequence);
+            writer.putU8(static_cast<uint8_t>(data.audio.timestampWidth));
+            switch (data.audio.timestampWidth) {
+                case TimestampWidth::Full:
+                    writer.putU64(data.audio.playoutTimeUs);
+                    break;
+                case TimestampWidth::Bits16:
+                    writer.putU16(static_cast<uint16_t>(data.audio.compactTimestamp));
+                    break;
+                case TimestampWidth::Bits24:
+                    writer.putU8(static_cast<uint8_t>(data.audio.compactTimestamp >> 16));
+                    writer.putU16(static_cast<uint16_t>(data.audio.compactTimestamp));
+                    break;
+            }
+            writer.putBytes(data.audio.payload);
+            break;
+        case MeshPacketType::Nack:
+            writer.putU16(data.nack.streamId);
+            writer.putString(data.nack.responder);
+            writer.putU16(static_cast<uint16_t>(data.nack.frames.size()));
+            for (uint32_t frame : data.nack.frames) {
+                writer.putU32(frame);
+            }
+            break;
+        case MeshPacketType::Join:
+            writer.putU8(static_cast<uint8_t>(data.join.role));
+            writer.putBytes(data.join.publicKey);
+            break;
+        case MeshPacketType::Pairing:
+            writer.putU8(static_cast<uint8_t>(data.pairing.step));
+            writer.putString(data.pairing.peer);
+            writer.putBytes(data.pairing.payload);
+            break;
+        case MeshPacketType::Ack:
+            writer.putString(data.ack.origin);
+            writer.putU32(data.ack.sequence);
+            writer.putU8(static_cast<uint8_t>(data.ack.ackedType));
+            break;
+    }
+}
+
+MeshPacket MeshPacket::readContent(MeshPacketType type, ByteReader& reader) {
+    switch (type) {
+        case MeshPacketType::Ping: {
+            std::string source = reader.getString();
+            return createPing(source, reader.getU64());
+        }
+        case MeshPacketType::Command: {
+            std::string cmdType = reader.getString();
+            return createCommand(cmdType, reader.getStringMap());
+        }
+        case MeshPacketType::Status: {
+            std::string nodeId = reader.getString();
+            uint8_t buffer = reader.getU8();
+            return createStatus(nodeId, buffer, reader.getU32());
+        }
+        case MeshPacketType::TimeBeacon:
+            return createTimeBeacon(reader.getU64());
+        case MeshPacketType::EmergencySync: {
+            uint64_t masterTime = reader.getU64();
+            return createEmergencySync(masterTime, reader.getStringList());
+        }
+        case MeshPacketType::Subscribe:
+        case MeshPacketType::Unsubscribe: {
+            MeshPacket packet(type);
+            packet.data.subscription.nodeId = reader.getString();
+            packet.data.subscription.streamId = reader.getU16();
+            return packet;
+        }
+        case MeshPacketType::Reject: {
+            MeshPacket packet(type);
+            packet.data.reject.origin = reader.getString();
+            uint8_t rejectedType = reader.getU8();
+            if (rejectedType > static_cast<uint8_t>(MeshPacketType::Ack)) {
+                throw std::invalid_argument("Tipo del pacchetto scartato non valido");
+            }
+            packet.data.reject.rejectedType = static_cast<MeshPacketType>(rejectedType);
+            packet.data.reject.rejectedSequence = reader.getU32();
+            packet.data.reject.reason = static_cast<RejectReason>(reader.getU8());
+            packet.data.reject.detail = reader.getString();
+            return packet;
+        }
+        case MeshPacketType::Metadata: {
+            StreamMetadata metadata;
+            metadata.streamId = reader.getU16();
+            metadata.revision = reader.getU32();
+            metadata.title = reader.getString();
+            metadata.artist = reader.getString();
+            metadata.album = reader.getString();
+            metadata.artworkHash = reader.getString();
+            return createMetadata(metadata);
+        }
+        case MeshPacketType::Artwork: {
+            std::string hash = reader.getString();
+            return createArtwork(hash, reader.getBytes());
+        }
+        case MeshPacketType::Audio: {
+            MeshPacket packet(type);
+            packet.data.audio.streamId = reader.getU16();
+            packet.data.audio.frameSequence = reader.getU32();
+            packet.data.audio.playoutTimeUs = 0;
+            packet.data.audio.compactTimestamp = 0;
+            switch (reader.getU8()) {
+                case static_cast<uint8_t>(TimestampWidth::Full):
+                    packet.data.audio.timestampWidth = TimestampWidth::Full;
+                    packet.data.audio.playoutTimeUs = reader.getU64();
+                    break;
+                case static_cast<uint8_t>(TimestampWidth::Bits16):
+                    packet.data.audio.timestampWidth = TimestampWidth::Bits16;
+                    packet.data.audio.compactTimestamp = reader.getU16();
+                    break;
+                case static_cast<uint8_t>(TimestampWidth::Bits24): {
+                    packet.data.audio.timestampWidth = TimestampWidth::Bits24;
+                    uint32_t high = reader.getU8();
+                    packet.data.audio.compactTimestamp = (high << 16) | reader.getU16();
+                    break;
+                }
+                default:
+                    throw std::invalid_argument("Ampiezza del timestamp non valida");
+            }
+            packet.data.audio.payload = reader.getBytes();
+            return packet;
+        }
+        case MeshPacketType::Nack: {
+            StreamId streamId = reader.getU16();
+            std::string responder = reader.getString();
+            uint16_t count = reader.getU16();
+            std::vector<uint32_t> frames;
+            for (uint16_t i = 0; i < count; ++i) {
+                frames.push_back(reader.getU32());
+            }
+            return createNack(streamId, responder, frames);
+        }
+        case MeshPacketType::Join: {
+            uint8_t role = reader.getU8();
+            if (role > static_cast<uint8_t>(NodeRole::Sink)) {
+                throw std::invalid_argument("Ruolo non valido");
+            }
+            return createJoin(static_cast<NodeRole>(role), reader.getBytes());
+        }
+        case MeshPacketType::Pairing: {
+            uint8_t step = reader.getU8();
+            if (step > static_cast<uint8_t>(PairingStep::Failed)) {
+                throw std::invalid_argument("Fase di abbinamento non valida");
+            }
+            std::string peer = reader.getString();
+            return createPairing(static_cast<PairingStep>(step), peer, reader.getBytes());
+        }
+        case MeshPacketType::Ack: {
+            MeshPacket packet(type);
+            packet.data.ack.origin = reader.getString();
+            packet.data.ack.sequence = reader.getU32();
+            uint8_t ackedType = reader.getU8();
+            if (ackedType > static_cast<uint8_t>(MeshPacketType::Ack)) {
+                throw std::invalid_argument("Tipo del pacchetto confermato non valido");
+            }
+            packet.data.ack.ackedType = static_cast<MeshPacketType>(ackedType);
+            return packet;
+        }
+    }
+    throw std::invalid_argument("Tipo di pacchetto non valido");
+}
+
+std::vector<uint8_t> MeshPacket::encode() const {
+    ByteWriter writer;
+    writer.putU8(WIRE_FORMAT_VERSION);
+    writer.putU8(static_cast<uint8_t>(type));
+    writer.putString(source);
+    writer.putU32(sequence);
+    writer.putU8(originTtl);
+    writer.putU8(ttl);
+    writer.putU8((recordPath ? 0x01 : 0x00) | (isEncrypted() ? 0x02 : 0x00) | (traceContext ? 0x04 : 0x00)
+                 | (bridges.empty() ? 0x00 : 0x08) | (relayDelayUs == 0 ? 0x00 : 0x10)
+                 | (sessionPeer.empty() ? 0x00 : 0x20));
+    if (recordPath) {
+        writer.putU8(static_cast<uint8_t>(path.size()));
+        for (uint16_t hop : path) {
+            writer.putU16(hop);
+        }
+    }
+    if (traceContext) {
+        for (uint8_t byte : traceContext->traceId) {
+            writer.putU8(byte);
+        }
+        for (uint8_t byte : traceContext->spanId) {
+            writer.putU8(byte);
+        }
+    }
+    if (!bridges.empty()) {
+        writer.putStringList(bridges);
+    }
+    if (relayDelayUs != 0) {
+        writer.putU32(relayDelayUs);
+    }
+    if (!sessionPeer.empty()) {
+        writer.putString(sessionPeer);
+    }
+    if (isEncrypted()) {
+        writer.putBytes(sealed);
+        return writer.data();
+    }
+    writeContent(writer);
+    writer.putBytes(signature);
+    return writer.data();
+}
+
+MeshPacket MeshPacket::decode(const std::vector<uint8_t>& bytes) {
+    ByteReader reader(bytes);
+    try {
+        uint8_t version = reader.getU8();
+        if (version < MIN_WIRE_FORMAT_VERSION || version > WIRE_FORMAT_VERSION) {
+            throw std::invalid_argument("Versione del formato non supportata: " + std::to_string(version));
+        }
+        uint8_t type = reader.getU8();
+        if (type > static_cast<uint8_t>(MeshPacketType::Ack)) {
+            throw std::invalid_argument("Tipo di pacchetto non valido: " + std::to_string(type));
+        }
+        std::string source = reader.getString();
+        uint32_t sequence = reader.getU32();
+        uint8_t originTtl = reader.getU8();
+        uint8_t ttl = reader.getU8();
+        // Un Repeater può solo decrementare il TTL firmato all'origine
+        if (ttl > originTtl) {
+            throw std::invalid_argument("TTL superiore a quello d'origine: " + std::to_string(ttl));
+        }
+        
+        // Dalla versione 2 il percorso registrato segue l'intestazione
+        bool recordPath = false;
+        bool encrypted = false;
+        std::vector<uint16_t> path;
+        std::optional<TraceContext> traceContext;
+        std::vector<std::string> bridges;
+        uint32_t relayDelayUs = 0;
+        std::string sessionPeer;
+        if (version >= 2) {
+            uint8_t flags = reader.getU8();
+            if (flags & ~0x3F) {
+                throw std::invalid_argument("Flag del pacchetto sconosciuti: " + std::to_string(flags));
+            }
+            recordPath = flags & 0x01;
+            encrypted = flags & 0x02;
+            if (recordPath) {
+                uint8_t hops = reader.getU8();
+                if (hops > MAX_RECORDED_HOPS) {
+                    throw std::invalid_argument("Percorso registrato troppo lungo: " + std::to_string(hops));
+                }
+                for (uint8_t i = 0; i < hops; ++i) {
+                    path.push_back(reader.getU16());
+                }
+            }
+            if (flags & 0x04) {
+                traceContext.emplace();
+                for (auto& byte : traceContext->traceId) {
+                    byte = reader.getU8();
+                }
+                for (auto& byte : traceContext->spanId) {
+                    byte = reader.getU8();
+                }
+            }
+            if (flags & 0x08) {
+                bridges = reader.getStringList();
+                if (bridges.empty() || bridges.size() > MAX_BRIDGE_HOPS) {
+                    throw std::invalid_argument("Numero di ponti non valido: " + std::to_string(bridges.size()));
+                }
+            }
+            if (flags & 0x10) {
+                relayDelayUs = reader.getU32();
+            }
+            if (flags & 0x20) {
+                sessionPeer = reader.getString();
+                if (!encrypted || sessionPeer.empty()) {
+                    throw std::invalid_argument("Destinatario di sessione non valido");
+                }
+            }
+        }
+        
+        // Il contenuto di un pacchetto cifrato resta da aprire con open()
+        MeshPacket packet = encrypted ? MeshPacket(static_cast<MeshPacketType>(type))
+                                      : readContent(static_cast<MeshPacketType>(type), reader);
+        packet.source = source;
+        packet.sequence = sequence;
+        packet.originTtl = originTtl;
+        packet.ttl = ttl;
+        packet.recordPath = recordPath;
+        packet.path = std::move(path);
+        packet.traceContext = traceContext;
+        packet.bridges = std::move(bridges);
+        packet.relayDelayUs = relayDelayUs;
+        packet.sessionPeer = std::move(sessionPeer);
+        if (encrypted) {
+            packet.sealed = reader.getBytes();
+            if (packet.sealed.empty()) {
+                throw std::invalid_argument("Contenuto cifrato vuoto");
+            }
+        } else {
+            packet.signature = reader.getBytes();
+        }
+        
+        if (reader.remaining() != 0) {
+            throw std::invalid_argument("Byte in eccesso dopo il pacchetto");
+        }
+        return packet;
+    } catch (const std::out_of_range&) {
+        throw std::invalid_argument("Pacchetto troncato");
+    }
+}
+
+void MeshPacket::sign(MeshCrypto& crypto) {
+    signature = crypto.sign(signingBytes());
+}
+
+bool MeshPacket::verifySignature(MeshCrypto& crypto) const {
+    if (signature.empty() || source.empty()) {
+        return false;
+    }
+    return crypto.verify(source, signingBytes(), signature);
+}
+
+bool MeshPacket::isSigned() const {
+    return !signature.empty();
+}
+
+const std::vector<uint8_t>& MeshPacket::getSignature() const {
+    return signature;
+}
+
+void MeshPacket::seal(MeshCrypto& crypto) {
+    ByteWriter plain;
+    writeContent(plain);
+    plain.putBytes(signature);
+    sealed = crypto.encrypt(plain.data(), std::vector<uint8_t>(source.begin(), source.end()));
+    sessionPeer.clear();
+}
+
+void MeshPacket::sealFor(MeshCrypto& crypto, const std::string& peer) {
+    ByteWriter plain;
+    writeContent(plain);
+    plain.putBytes(signature);
+    sealed = crypto.encryptFor(peer, plain.data(), std::vector<uint8_t>(source.begin(), source.end()));
+    sessionPeer = peer;
+}
+
+const std::string& MeshPacket::getSessionPeer() const {
+    return sessionPeer;
+}
+
+MeshPacket MeshPacket::open(MeshCrypto& crypto) const {
+    if (!isEncrypted()) {
+        throw std::runtime_error("Il pacchetto non è cifrato");
+    }
+    
+    // Solo un blob autentico della sorgente indicata fa avanzare la finestra anti-replay
+    std::vector<uint8_t> aad(source.begin(), source.end());
+    auto plain = sessionPeer.empty() ? crypto.decryptFrom(source, sealed, aad)
+                                     : crypto.decryptFromPeer(source, sealed, aad);
+    ByteReader reader(plain);
+    try {
+        MeshPacket packet = readContent(type, reader);
+        packet.copyHeaderFromOther(*this);
+        packet.signature = reader.getBytes();
+        if (reader.remaining() != 0) {
+            throw std::invalid_argument("Byte in eccesso nel contenuto cifrato");
+        }
+        return packet;
+    } catch (const std::out_of_range&) {
+        throw std::invalid_argument("Contenuto cifrato troncato");
+    }
+}
+
+bool MeshPacket::isEncrypted() const {
+    return !sealed.empty();
+}
+
+// Implementazione di MeshNetwork
+MeshNetwork::MeshNetwork(const Node& localNode) 
+    : localNode(localNode), running(false) {
+    // Registra il nodo locale
+    nodes.emplace(localNode.id, localNode);
+}
+
+MeshNetwork::~MeshNetwork() {
+    stop();
+}
+
+void MeshNetwork::start() {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    if (running) {
+        return;
+    }
+    
+    running = true;
+    networkThread = std::make_unique<std::thread>(&MeshNetwork::runNetworkLoop, this);
+}
+
+void MeshNetwork::stop() {
+    {
+        std::lock_guard<std::mutex> lock(networkMutex);
+        if (!running) {
+            return;
+        }
+        
+        running = false;
+        // Gli invii asincroni ancora senza conferma non ne riceveranno più
+        for (auto& entry : pendingDeliveries) {
+            entry.second.set_value(false);
+        }
+        pendingDeliveries.clear();
+    }
+    
+    // Il thread verifica il flag sotto queueMutex: passarci garantisce che
+    // la notifica non vada persa tra la verifica e l'attesa
+    {
+        std::lock_guard<std::mutex> lock(queueMutex);
+    }
+    queueCondition.notify_all();
+    
+    if (networkThread && networkThread->joinable()) {
+        networkThread->join();
+    }
+}
+
+bool MeshNetwork::isRunning() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    return running;
+}
+
+void MeshNetwork::sendPacket(const MeshPacket& packet) {
+    dispatchPacket(packet, nullptr);
+}
+
+std::future<bool> MeshNetwork::sendPacketAsync(const MeshPacket& packet) {
+    std::promise<bool> delivery;
+    std::future<bool> result = delivery.get_future();
+    dispatchPacket(packet, &delivery);
+    return result;
+}
+
+bool MeshNetwork::isNetworkThread() const {
+    return networkThread && networkThread->get_id() == std::this_thread::get_id();
+}
+
+void MeshNetwork::dispatchPacket(const MeshPacket& packet, std::promise<bool>* delivery) {
+    MeshPacket outgoing = packet;
+    {
+        std::lock_guard<std::mutex> lock(networkMutex);
+        if (outgoing.getSource().empty()) {
+            outgoing.setHeader(localNode.id, ++nextSequence, DEFAULT_PACKET_TTL);
+            outgoing.setPathRecording(pathRecording);
+            if (!outgoing.getTraceContext() && outgoing.getType() != MeshPacketType::Audio) {
+                outgoing.setTraceContext(SpanScope::current());
+            }
+        }
+        // I comandi attendono la conferma di ogni nodo attivo; la copia conservata è ancora in chiaro
+        if (outgoing.getType() == MeshPacketType::Command && outgoing.getSource() == localNode.id) {
+            std::set<std::string> recipients;
+            for (const auto& pair : nodes) {
+                if (pair.first != localNode.id && pair.second.isActive()) {
+                    recipients.insert(pair.first);
+                }
+            }
+            if (!recipients.empty() && reliableSender.track(outgoing, recipients, steadyMillis()) && delivery) {
+                pendingDeliveries.insert_or_assign(outgoing.getSequence(), std::move(*delivery));
+                delivery = nullptr;
+            }
+        }
+        sealLocked(outgoing);
+    }
+    
+    size_t size = outgoing.encodedSize();
+    int64_t now = steadyMillis();
+    stats.recordSent(size, now);
+    if (outgoing.getType() == MeshPacketType::Metadata) {
+        stats.recordStream(outgoing.getMetadata().streamId, size, now);
+    } else if (outgoing.getType() == MeshPacketType::Audio) {
+        stats.recordStream(outgoing.getAudioData().streamId, size, now);
+        std::lock_guard<std::mutex> lock(networkMutex);
+        cacheAudioLocked(outgoing);
+        routeAudioLocked(outgoing);
+    }
+    enqueuePacket(std::move(outgoing));
+    if (delivery) {
+        delivery->set_value(true);
+    }
+}
+
+void MeshNetwork::resolveDeliveryLocked(uint32_t sequence, bool delivered) {
+    auto it = pendingDeliveries.find(sequence);
+    if (it != pendingDeliveries.end()) {
+        it->second.set_value(delivered);
+        pendingDeliveries.erase(it);
+    }
+}
+
+void MeshNetwork::enqueuePacket(MeshPacket packet) {
+    LinkSender sender;
+    {
+        std::lock_guard<std::mutex> lock(queueMutex);
+        sender = linkSender;
+        if (!sender) {
+            pushPacketLocked(std::move(packet));
+            return;
+        }
+        pushPacketLocked(packet);
+    }
+    // La trasmissione avviene fuori dal lock della coda
+    sender(packet.encode());
+}
+
+void MeshNetwork::setLinkSender(LinkSender sender) {
+    std::lock_guard<std::mutex> lock(queueMutex);
+    linkSender = sender;
+}
+
+bool MeshNetwork::receiveFromLink(const std::vector<uint8_t>& datagram) {
+    std::optional<MeshPacket> packet;
+    try {
+        packet = MeshPacket::decode(datagram);
+    } catch (const std::invalid_argument& e) {
+        SABER_LOG(Debug, "mesh", "Datagramma non valido dal collegamento: " << e.what());
+        return false;
+    }
+    // Il collegamento restituisce anche i pacchetti del nodo, già elaborati all'invio
+    if (packet->getSource() == localNode.id) {
+        return false;
+    }
+    packet->setArrivalTime(steadyMicros());
+    std::lock_guard<std::mutex> lock(queueMutex);
+    pushPacketLocked(std::move(*packet));
+    return true;
+}
+
+void MeshNetwork::pushPacketLocked(MeshPacket packet) {
+    MeshPacketType type = packet.getType();
+    if (!packetQueue.push(std::move(packet))) {
+        SABER_LOG(Debug, "mesh", "Coda " << packetClassToString(packetClassOf(type)) 
+                  << " piena, scartato il pacchetto più vecchio");
+    }
+    queueCondition.notify_one();
+}
+
+std::map<PacketClass, PacketQueueStats> MeshNetwork::getQueueStats() const {
+    std::lock_guard<std::mutex> lock(queueMutex);
+    return packetQueue.getStats();
+}
+
+bool MeshNetwork::registerNode(const std::string& nodeId, NodeRole role) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    if (nodes.find(nodeId) != nodes.end()) {
+        return true;
+    }
+    if (auto limit = capacityExceededLocked(role)) {
+        rejectedNodes++;
+        emitEventLocked(MeshEvent::Type::AdmissionRejected, nodeId, *limit);
+        return false;
+    }
+    addNodeLocked(nodeId, role);
+    admittedNodes++;
+    emitEventLocked(MeshEvent::Type::NodeJoined, nodeId, "registrato come " + nodeRoleToString(role));
+    return true;
+}
+
+void MeshNetwork::updateNodeStatus(const std::string& nodeId, uint8_t bufferState, uint32_t latency) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    updateNodeStatusLocked(nodeId, bufferState, latency);
+}
+
+void MeshNetwork::updateNodeStatusLocked(const std::string& nodeId, uint8_t bufferState, uint32_t latency) {
+    auto it = nodes.find(nodeId);
+    if (it != nodes.end()) {
+        it->second.updateBufferState(bufferState);
+        it->second.setLatency(latency);
+        touchNodeLocked(it->second);
+    }
+}
+
+void MeshNetwork::touchNodeLocked(Node& node) {
+    if (!node.isActive()) {
+        treesDirty = true;
+    }
+    node.updatePing();
+}
+
+std::vector<std::string> MeshNetwork::getActiveNodes() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    std::vector<std::string> activeNodes;
+    
+    for (const auto& pair : nodes) {
+        if (pair.second.isActive()) {
+            activeNodes.push_back(pair.first);
+        }
+    }
+    
+    return activeNodes;
+}
+
+std::vector<MeshNodeInfo> MeshNetwork::getActiveNodeInfo() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    std::vector<MeshNodeInfo> activeNodes;
+    
+    for (const auto& pair : nodes) {
+        if (!pair.second.isActive()) {
+            continue;
+        }
+        MeshNodeInfo info;
+        info.nodeId = pair.first;
+        info.role = pair.second.role;
+        info.latencyMs = pair.second.getLatency();
+        info.bufferState = pair.second.getBufferState();
+        info.lastSeenMs = pair.second.getLastSeenMs();
+        info.linkQuality = pair.second.getLinkQuality();
+        info.active = true;
+        activeNodes.push_back(std::move(info));
+    }
+    
+    return activeNodes;
+}
+
+std::map<std::string, NodeRole> MeshNetwork::getNodeRoles() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    std::map<std::string, NodeRole> roles;
+    
+    for (const auto& pair : nodes) {
+        roles[pair.first] = pair.second.role;
+    }
+    
+    return roles;
+}
+
+void MeshNetwork::setPacketHandler(PacketHandler handler) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    packetHandler = handler;
+}
+
+void MeshNetwork::setCrypto(std::shared_ptr<MeshCrypto> crypto) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    this->crypto = crypto;
+}
+
+void MeshNetwork::setProfiler(std::shared_ptr<PipelineProfiler> profiler) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    this->profiler = profiler;
+}
+
+void MeshNetwork::setIntrusionDetector(std::shared_ptr<IntrusionDetector> detector) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    intrusionDetector = detector;
+}
+
+void MeshNetwork::setTracer(std::shared_ptr<Tracer> tracer) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    this->tracer = tracer;
+}
+
+void MeshNetwork::setSendRejects(bool enabled) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    sendRejects = enabled;
+}
+
+void MeshNetwork::setRequireSignatures(bool required) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    requireSignatures = required;
+}
+
+void MeshNetwork::setPacketEncryption(bool enabled) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    encryptPackets = enabled;
+}
+
+bool MeshNetwork::isPacketEncryption() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    return encryptPackets;
+}
+
+void MeshNetwork::setSessionKeys(bool enabled) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    sessionKeys = enabled;
+}
+
+bool MeshNetwork::isSessionKeys() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    return sessionKeys;
+}
+
+SessionStats MeshNetwork::getSessionStats() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    return crypto ? crypto->getSessions().getStats() : SessionStats{};
+}
+
+void MeshNetwork::publishStream(StreamId streamId) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    publishedStreams.insert(streamId);
+}
+
+std::map<RejectReason, uint64_t> MeshNetwork::getDropCounters() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    return dropCounters;
+}
+
+void MeshNetwork::setRateLimits(const std::map<MeshPacketType, RateLimit>& limits) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    rateLimiter.setLimits(limits);
+}
+
+std::map<MeshPacketType, RateLimit> MeshNetwork::getRateLimits() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    return rateLimiter.getLimits();
+}
+
+RateLimitStats MeshNetwork::getRateLimitStats() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    return rateLimiter.getStats();
+}
+
+void MeshNetwork::assignZone(const std::string& nodeId, const std::string& zone) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    assignZoneLocked(nodeId, zone);
+}
+
+void MeshNetwork::assignZoneLocked(const std::string& nodeId, const std::string& zone) {
+    auto it = nodeZones.find(nodeId);
+    if (it != nodeZones.end() && it->second == zone) {
+        return;
+    }
+    std::string previous = it != nodeZones.end() ? it->second : "";
+    nodeZones[nodeId] = zone;
+    emitEventLocked(MeshEvent::Type::ZoneChanged, nodeId, 
+                    (previous.empty() ? "" : "da " + previous + " ") + "a " + zone);
+}
+
+std::optional<std::string> MeshNetwork::getZone(const std::string& nodeId) const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    auto it = nodeZones.find(nodeId);
+    if (it == nodeZones.end()) {
+        return std::nullopt;
+    }
+    return it->second;
+}
+
+std::map<std::string, std::string> MeshNetwork::getZones() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    return nodeZones;
+}
+
+std::map<std::string, uint32_t> MeshNetwork::getNodeLatencies() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    std::map<std::string, uint32_t> latencies;
+    for (const auto& pair : nodes) {
+        if (pair.second.isActive()) {
+            latencies[pair.first] = pair.second.getLatency();
+        }
+    }
+    return latencies;
+}
+
+void MeshNetwork::updateSignalStrength(const std::string& nodeId, int32_t rssiDbm) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    updateLinkLocked(nodeId, [rssiDbm](Node& node) { node.setSignalStrength(rssiDbm); });
+}
+
+std::map<std::string, int32_t> MeshNetwork::getSignalStrengths() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    std::map<std::string, int32_t> strengths;
+    for (const auto& pair : nodes) {
+        if (auto rssi = pair.second.getSignalStrength()) {
+            strengths[pair.first] = *rssi;
+        }
+    }
+    return strengths;
+}
+
+void MeshNetwork::recordStreamTraffic(StreamId streamId, size_t bytes) {
+    stats.recordStream(streamId, bytes, steadyMillis());
+}
+
+BandwidthReport MeshNetwork::getBandwidthReport() {
+    std::map<std::string, std::string> zones;
+    {
+        std::lock_guard<std::mutex> lock(networkMutex);
+        zones = nodeZones;
+    }
+    return stats.report(zones, steadyMillis());
+}
+
+void MeshNetwork::recordNodeLatency(const std::string& nodeId, uint32_t latencyMs) {
+    latencyStats.recordLatency(nodeId, latencyMs);
+}
+
+NetworkStats MeshNetwork::getNetworkStats() const {
+    return latencyStats.report();
+}
+
+double MeshNetwork::getAirtimeUtilization() {
+    return stats.airtimeUtilization(steadyMillis());
+}
+
+std::map<std::string, LinkQuality> MeshNetwork::getLinkQualities() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    std::map<std::string, LinkQuality> qualities;
+    for (const auto& pair : nodes) {
+        LinkQuality quality;
+        quality.rssiDbm = pair.second.getSignalStrength();
+        quality.packetSuccessRate = pair.second.getPacketSuccessRate();
+        quality.score = pair.second.getLinkQuality();
+        qualities[pair.first] = quality;
+    }
+    return qualities;
+}
+
+void MeshNetwork::reportLinkQuality(const std::string& peer, TransportKind kind, double linkQuality, 
+                                    uint32_t latencyMs, std::optional<int32_t> rssiDbm) {
+    transports.reportPath(peer, kind, linkQuality, latencyMs, steadyMillis());
+    
+    std::lock_guard<std::mutex> lock(networkMutex);
+    updateLinkLocked(peer, [linkQuality, rssiDbm](Node& node) {
+        node.recordPacketSuccess(linkQuality);
+        if (rssiDbm) {
+            node.setSignalStrength(*rssiDbm);
+        }
+    });
+}
+
+void MeshNetwork::reportLinkDown(const std::string& peer, TransportKind kind) {
+    transports.reportPathDown(peer, kind);
+}
+
+std::optional<TransportKind> MeshNetwork::routeTo(const std::string& peer) {
+    return transports.route(peer, steadyMillis());
+}
+
+TransportStats MeshNetwork::getTransportStats() const {
+    return transports.getStats();
+}
+
+void MeshNetwork::setFailoverHandler(TransportSelector::FailoverHandler handler) {
+    transports.setFailoverHandler(handler);
+}
+
+void MeshNetwork::setEventHandler(EventHandler handler) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    eventHandler = handler;
+}
+
+void MeshNetwork::setLivenessConfig(uint32_t heartbeatTimeoutMs, uint32_t evictAfterMs) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    this->heartbeatTimeoutMs = heartbeatTimeoutMs;
+    this->evictAfterMs = evictAfterMs;
+    for (auto& pair : nodes) {
+        pair.second.setHeartbeatTimeout(heartbeatTimeoutMs);
+    }
+}
+
+std::vector<std::string> MeshNetwork::getEvictedNodes() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    std::vector<std::string> nodeIds;
+    for (const auto& pair : evictedNodes) {
+        nodeIds.push_back(pair.first);
+    }
+    return nodeIds;
+}
+
+void MeshNetwork::checkNodeLiveness() {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    int64_t now = steadyMillis();
+    for (auto it = nodes.begin(); it != nodes.end();) {
+        const std::string nodeId = it->first;
+        // Un nodo mai sentito non è ancora perso: conta solo la transizione
+        bool active = it->second.isActive();
+        auto lost = lostNodes.find(nodeId);
+        if (!active && lost == lostNodes.end() && it->second.hasPinged() && nodeId != localNode.id) {
+            lostNodes[nodeId] = now;
+            treesDirty = true;
+            emitEventLocked(MeshEvent::Type::NodeLost, nodeId, "nessuna risposta entro il timeout");
+        } else if (active && lost != lostNodes.end()) {
+            lostNodes.erase(lost);
+            treesDirty = true;
+            emitEventLocked(MeshEvent::Type::NodeReturned, nodeId, "di nuovo attivo");
+        } else if (!active && lost != lostNodes.end() && evictAfterMs > 0 && now - lost->second >= evictAfterMs) {
+            // Fuori dalla tabella dei nodi e dai percorsi, ma con la chiave ancora registrata per il rientro
+            evictedNodes[nodeId] = it->second.role;
+            lostNodes.erase(lost);
+            heardNeighbors.erase(nodeId);
+            observedHops.erase(nodeId);
+            routeFailures.erase(nodeId);
+            relayedBeacons.erase(nodeId);
+            it = nodes.erase(it);
+            evictedCount++;
+            treesDirty = true;
+            SABER_LOG(Info, "mesh", "Nodo " << nodeId << " tolto dalla rete dopo " << evictAfterMs 
+                      << " ms senza risposta");
+            emitEventLocked(MeshEvent::Type::NodeEvicted, nodeId, "nessuna risposta per " 
+                            + std::to_string(evictAfterMs) + " ms dalla perdita");
+            continue;
+        }
+        ++it;
+    }
+}
+
+Node& MeshNetwork::addNodeLocked(const std::string& nodeId, NodeRole role) {
+    auto inserted = nodes.emplace(nodeId, Node(nodeId, role)).first;
+    inserted->second.setHeartbeatTimeout(heartbeatTimeoutMs);
+    evictedNodes.erase(nodeId);
+    treesDirty = true;
+    return inserted->second;
+}
+
+bool MeshNetwork::isMemberLocked(const std::string& nodeId) const {
+    return nodes.count(nodeId) > 0 || evictedNodes.count(nodeId) > 0;
+}
+
+bool MeshNetwork::rejoinLocked(const MeshPacket& packet, bool authenticated) {
+    const std::string& nodeId = packet.getSource();
+    NodeRole role = evictedNodes.at(nodeId);
+    if (auto limit = capacityExceededLocked(role)) {
+        rejectedNodes++;
+        dropPacketLocked(packet, RejectReason::CapacityExceeded, authenticated, *limit);
+        emitEventLocked(MeshEvent::Type::AdmissionRejected, nodeId, *limit);
+        return false;
+    }
+    addNodeLocked(nodeId, role).updatePing();
+    SABER_LOG(Info, "mesh", "Nodo " << nodeId << " (" << nodeRoleToString(role) << ") rientrato nella rete");
+    emitEventLocked(MeshEvent::Type::NodeReturned, nodeId, "rientrato dopo la rimozione per inattività");
+    return true;
+}
+
+void MeshNetwork::emitEventLocked(MeshEvent::Type type, const std::string& nodeId, const std::string& detail) {
+    if (!eventHandler) {
+        return;
+    }
+    uint64_t timestamp = std::chrono::duration_cast<std::chrono::milliseconds>(
+        std::chrono::system_clock::now().time_since_epoch()).count();
+    eventHandler(MeshEvent{type, nodeId, detail, timestamp});
+}
+
+void MeshNetwork::relayBeaconLocked(const MeshPacket& received, bool authenticated) {
+    // Lo stesso beacon arriva anche dagli altri Repeater: si inoltra solo la prima copia
+    auto last = relayedBeacons.find(received.getSource());
+    if (last != relayedBeacons.end() && static_cast<int32_t>(received.getSequence() - last->second) <= 0) {
+        return;
+    }
+    relayedBeacons[received.getSource()] = received.getSequence();
+    
+    // Si inoltra la copia ricevuta, ancora cifrata: firma e cifratura restano quelle del Master
+    MeshPacket relayed = received;
+    if (!prepareForwardLocked(relayed, authenticated)) {
+        return;
+    }
+    if (auto arrival = relayed.getArrivalTime()) {
+        relayed.addRelayDelay(static_cast<uint64_t>(std::max<int64_t>(steadyMicros() - *arrival, 0)));
+    }
+    
+    LinkSender sender;
+    {
+        std::lock_guard<std::mutex> lock(queueMutex);
+        sender = linkSender;
+    }
+    if (sender) {
+        sender(relayed.encode());
+    }
+}
+
+void MeshNetwork::routeAudioLocked(const MeshPacket& packet) {
+    // Ogni frame sceglie il collegamento al momento dell'invio: un percorso
+    // degradato viene abbandonato anche a metà stream
+    const auto& tree = distributionTreeLocked(packet.getAudioData().streamId);
+    auto children = tree.children.find(localNode.id);
+    if (children == tree.children.end()) {
+        return;
+    }
+    for (const auto& child : children->second) {
+        if (!transports.route(child, steadyMillis())) {
+            SABER_LOG(Trace, "mesh", "Nessun collegamento disponibile verso " << child);
+        }
+    }
+}
+
+void MeshNetwork::setRepairConfig(const RepairConfig& config) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    repairConfig = config;
+    nackTracker.setWindowMs(config.windowMs);
+    if (!config.enabled) {
+        repairCache.clear();
+    }
+}
+
+bool MeshNetwork::requestJoin() {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    if (!crypto) {
+        return false;
+    }
+    
+    MeshPacket join = MeshPacket::createJoin(localNode.role, crypto->getPublicKey());
+    join.setHeader(localNode.id, ++nextSequence, DEFAULT_PACKET_TTL);
+    join.sign(*crypto);
+    
+    // Il Join viene ritrasmesso finché un Master non lo prende in carico; lo span
+    // copre il primo invio, l'esito è nello span del master
+    std::optional<TraceSpan> span;
+    if (tracer && tracer->isEnabled()) {
+        span = tracer->startSpan("join.request", SpanKind::Producer, SpanScope::current());
+        span->attributes["saber.node.role"] = nodeRoleToString(localNode.role);
+        join.setTraceContext(span->context);
+    }
+    stats.recordSent(join.encodedSize(), steadyMillis());
+    reliableSender.track(join, {}, steadyMillis());
+    enqueuePacket(std::move(join));
+    if (span) {
+        tracer->endSpan(*span);
+    }
+    return true;
+}
+
+void MeshNetwork::openProvisioning(uint32_t durationMs) {
+    provisioning.open(durationMs, steadyMillis());
+    SABER_LOG(Info, "mesh", "Finestra di provisioning aperta per " << durationMs / 1000 << " s");
+}
+
+void MeshNetwork::closeProvisioning() {
+    provisioning.close();
+    SABER_LOG(Info, "mesh", "Finestra di provisioning chiusa");
+}
+
+ProvisioningStatus MeshNetwork::getProvisioningStatus() const {
+    return provisioning.status(steadyMillis());
+}
+
+void MeshNetwork::setJoinRateLimit(uint32_t attemptsPerMinute) {
+    provisioning.setClosedRateLimit(attemptsPerMinute);
+}
+
+void MeshNetwork::setJoinAuthorizer(JoinAuthorizer authorizer) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    joinAuthorizer = std::move(authorizer);
+}
+
+std::vector<std::string> MeshNetwork::getPendingJoins() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    std::vector<std::string> nodeIds;
+    for (const auto& entry : pendingJoins) {
+        nodeIds.push_back(entry.first);
+    }
+    return nodeIds;
+}
+
+std::string MeshNetwork::admitPairedNode(const std::string& nodeId, NodeRole role,
+                                         const std::vector<uint8_t>& publicKey) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    // Come per il Join, un nodo già membro non può sostituire la propria chiave
+    if (isMemberLocked(nodeId)) {
+        return "already_member";
+    }
+    if (auto limit = capacityExceededLocked(role)) {
+        rejectedNodes++;
+        emitEventLocked(MeshEvent::Type::AdmissionRejected, nodeId, *limit);
+        return "capacity_exceeded";
+    }
+    if (crypto) {
+        crypto->registerNodeKey(nodeId, publicKey);
+    }
+    addNodeLocked(nodeId, role);
+    admittedNodes++;
+    provisioning.recordAccepted();
+    SABER_LOG(Info, "mesh", "Nodo " << nodeId << " (" << nodeRoleToString(role) << ") abbinato alla rete");
+    emitEventLocked(MeshEvent::Type::NodeJoined, nodeId, "abbinato con il PIN");
+    return "admitted";
+}
+
+void MeshNetwork::joinPairedNetwork(const std::string& masterId, const std::vector<uint8_t>& masterPublicKey,
+                                    const std::array<uint8_t, 32>& networkKey) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    // La chiave cambia sotto il mutex della rete: nessun pacchetto viene cifrato nel frattempo
+    if (crypto) {
+        crypto->setNetworkKey(networkKey);
+        crypto->registerNodeKey(masterId, masterPublicKey);
+    }
+    if (nodes.count(masterId) == 0) {
+        addNodeLocked(masterId, NodeRole::Master);
+        emitEventLocked(MeshEvent::Type::NodeJoined, masterId, "Master dell'abbinamento");
+    }
+    SABER_LOG(Info, "mesh", "Abbinato alla rete del Master " << masterId);
+}
+
+void MeshNetwork::handleJoinLocked(const MeshPacket& packet) {
+    const std::string& nodeId = packet.getSource();
+    if (nodeId.empty() || nodeId == localNode.id) {
+        return;
+    }
+    
+    std::string result;
+    if (!tracer || !tracer->isEnabled()) {
+        result = admitJoinLocked(packet);
+    } else {
+        TraceSpan span = tracer->startSpan("join.admit", SpanKind::Consumer, packet.getTraceContext());
+        span.attributes["saber.node.id"] = nodeId;
+        result = admitJoinLocked(packet);
+        span.attributes["saber.join.result"] = result;
+        if (result != "admitted" && result != "already_member" && result != "authorization_pending") {
+            span.error = result;
+        }
+        tracer->endSpan(span);
+    }
+    
+    // Una richiesta presa in carico viene confermata: il nuovo nodo smette di ritrasmetterla
+    if (result == "admitted" || result == "already_member" || result == "authorization_pending") {
+        sendAckLocked(packet);
+    }
+}
+
+std::string MeshNetwork::admitJoinLocked(const MeshPacket& packet) {
+    const std::string& nodeId = packet.getSource();
+    
+    // L'ammissione precede qualsiasi verifica: a finestra chiusa non si fa lavoro crittografico
+    switch (provisioning.admit(steadyMillis())) {
+        case JoinDecision::Throttle:
+            dropCounters[RejectReason::RateLimited]++;
+            return "throttled";
+        case JoinDecision::Refuse:
+            dropPacketLocked(packet, RejectReason::ProvisioningClosed, false);
+            return "provisioning_closed";
+        case JoinDecision::Accept:
+            break;
+    }
+    
+    // Un nodo già membro, anche se tolto per inattività, non può sostituire la propria chiave con un Join
+    if (isMemberLocked(nodeId)) {
+        SABER_LOG(Debug, "mesh", "Richiesta di ingresso ignorata: " << nodeId << " è già membro");
+        return "already_member";
+    }
+    
+    auto join = packet.getJoinData();
+    if (!packet.isSigned() || 
+        !MeshCrypto::verifyWithKey(join.publicKey, packet.signingBytes(), packet.getSignature())) {
+        dropPacketLocked(packet, RejectReason::BadSignature, false, "richiesta di ingresso");
+        return "bad_signature";
+    }
+    
+    // La firma precede il controllo di capienza: solo i rifiuti autentici diventano eventi
+    if (auto limit = capacityExceededLocked(join.role)) {
+        rejectedNodes++;
+        dropPacketLocked(packet, RejectReason::CapacityExceeded, true, *limit);
+        emitEventLocked(MeshEvent::Type::AdmissionRejected, nodeId, *limit);
+        return "capacity_exceeded";
+    }
+    
+    // La politica esterna decide per ultima, sulle sole richieste autentiche; i
+    // Join ripetuti durante l'attesa non generano nuove richieste
+    if (joinAuthorizer) {
+        if (pendingJoins.count(nodeId) > 0) {
+            return "authorization_pending";
+        }
+        pendingJoins.emplace(nodeId, packet);
+        bool submitted = joinAuthorizer(nodeId, join.role, [this, nodeId](bool allowed, const std::string& reason) {
+            resolveJoin(nodeId, allowed, reason);
+        });
+        if (submitted) {
+            SABER_LOG(Debug, "mesh", "Richiesta di ingresso di " << nodeId << " in attesa di autorizzazione");
+            return "authorization_pending";
+        }
+        pendingJoins.erase(nodeId);
+    }
+    
+    completeJoinLocked(packet, "ammesso dalla finestra di provisioning");
+    return "admitted";
+}
+
+void MeshNetwork::completeJoinLocked(const MeshPacket& packet, const std::string& detail) {
+    const std::string& nodeId = packet.getSource();
+    auto join = packet.getJoinData();
+    if (crypto) {
+        crypto->registerNodeKey(nodeId, join.publicKey);
+    }
+    addNodeLocked(nodeId, join.role);
+    admittedNodes++;
+    provisioning.recordAccepted();
+    SABER_LOG(Info, "mesh", "Nodo " << nodeId << " (" << nodeRoleToString(join.role) << ") ammesso nella rete");
+    emitEventLocked(MeshEvent::Type::NodeJoined, nodeId, detail);
+}
+
+void MeshNetwork::resolveJoin(const std::string& nodeId, bool allowed, const std::string& reason) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    auto it = pendingJoins.find(nodeId);
+    if (it == pendingJoins.end()) {
+        return;
+    }
+    MeshPacket packet = it->second;
+    pendingJoins.erase(it);
+    
+    if (!allowed) {
+        rejectedNodes++;
+        dropPacketLocked(packet, RejectReason::Unauthorized, true, reason);
+        emitEventLocked(MeshEvent::Type::AdmissionRejected, nodeId, "non autorizzato: " + reason);
+        return;
+    }
+    
+    // Durante l'attesa la rete può essersi riempita
+    if (nodes.count(nodeId) > 0) {
+        return;
+    }
+    if (auto limit = capacityExceededLocked(packet.getJoinData().role)) {
+        rejectedNodes++;
+        dropPacketLocked(packet, RejectReason::CapacityExceeded, true, *limit);
+        emitEventLocked(MeshEvent::Type::AdmissionRejected, nodeId, *limit);
+        return;
+    }
+    completeJoinLocked(packet, reason.empty() ? "autorizzato dalla politica esterna" 
+                                              : "autorizzato dalla politica esterna: " + reason);
+}
+
+void MeshNetwork::setCapacityLimits(uint32_t maxNodes, uint32_t maxSinks) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    this->maxNodes = maxNodes;
+    this->maxSinks = maxSinks;
+}
+
+AdmissionStats MeshNetwork::getAdmissionStats() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    AdmissionStats stats;
+    stats.maxNodes = maxNodes;
+    stats.maxSinks = maxSinks;
+    stats.nodes = static_cast<uint32_t>(nodes.size());
+    for (const auto& pair : nodes) {
+        if (pair.second.role == NodeRole::Sink) {
+            stats.sinks++;
+        }
+    }
+    stats.admitted = admittedNodes;
+    stats.rejected = rejectedNodes;
+    stats.evicted = evictedCount;
+    return stats;
+}
+
+std::optional<std::string> MeshNetwork::capacityExceededLocked(NodeRole role) const {
+    if (maxNodes > 0 && nodes.size() >= maxNodes) {
+        return "rete al completo (" + std::to_string(maxNodes) + " nodi)";
+    }
+    if (role == NodeRole::Sink && maxSinks > 0) {
+        size_t sinks = std::count_if(nodes.begin(), nodes.end(), [](const auto& pair) {
+            return pair.second.role == NodeRole::Sink;
+        });
+        if (sinks >= maxSinks) {
+            return "sink al completo (" + std::to_string(maxSinks) + " sink)";
+        }
+    }
+    return std::nullopt;
+}
+
+void MeshNetwork::setPathRecording(bool enabled) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    pathRecording = enabled;
+}
+
+bool MeshNetwork::isPathRecording() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    return pathRecording;
+}
+
+std::vector<RouteTrace> MeshNetwork::getRouteTraces() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    std::vector<RouteTrace> traces;
+    for (const auto& entry : routeTraces) {
+        traces.push_back(entry.second);
+    }
+    return traces;
+}
+
+std::map<std::string, std::string> MeshNetwork::getRouteFailures() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    return routeFailures;
+}
+
+std::string MeshNetwork::resolveShortId(uint16_t shortId) const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    return resolveShortIdLocked(shortId);
+}
+
+std::string MeshNetwork::resolveShortIdLocked(uint16_t shortId) const {
+    if (shortNodeId(localNode.id) == shortId) {
+        return localNode.id;
+    }
+    for (const auto& entry : nodes) {
+        if (shortNodeId(entry.first) == shortId) {
+            return entry.first;
+        }
+    }
+    static const char* digits = "0123456789abcdef";
+    std::string hex = "#";
+    for (int shift = 12; shift >= 0; shift -= 4) {
+        hex += digits[(shortId >> shift) & 0x0F];
+    }
+    return hex;
+}
+
+bool MeshNetwork::prepareForwardLocked(MeshPacket& packet, bool authenticated) {
+    if (!packet.decrementTtl()) {
+        dropPacketLocked(packet, RejectReason::TtlExpired, authenticated, describePathLocked(packet));
+        return false;
+    }
+    if (packet.isPathRecording() && !packet.appendHop(shortNodeId(localNode.id))) {
+        dropPacketLocked(packet, RejectReason::RoutingLoop, authenticated, describePathLocked(packet));
+        return false;
+    }
+    return true;
+}
+
+std::string MeshNetwork::describePathLocked(const MeshPacket& packet) const {
+    std::string description = packet.getSource();
+    for (uint16_t hop : packet.getPath()) {
+        description += " > " + resolveShortIdLocked(hop);
+    }
+    return description + " (" + std::to_string(packet.getHopCount()) + " hop)";
+}
+
+RepairStats MeshNetwork::getRepairStats() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    RepairStats result = repairStats;
+    const RepairStats& sink = nackTracker.getStats();
+    result.nacksSent = sink.nacksSent;
+    result.repaired = sink.repaired;
+    result.expired = sink.expired;
+    return result;
+}
+
+void MeshNetwork::cacheAudioLocked(const MeshPacket& packet) {
+    if (!repairConfig.enabled || repairConfig.cacheFrames == 0) {
+        return;
+    }
+    
+    const auto& frame = packet.getAudioData();
+    auto& frames = repairCache[frame.streamId];
+    frames.insert_or_assign(frame.frameSequence, packet);
+    while (frames.size() > repairConfig.cacheFrames) {
+        frames.erase(frames.begin());
+    }
+}
+
+void MeshNetwork::requestRepairLocked(const MeshPacket& packet) {
+    if (!repairConfig.enabled) {
+        return;
+    }
+    
+    const auto& frame = packet.getAudioData();
+    auto missing = nackTracker.onFrame(frame.streamId, frame.frameSequence, steadyMillis());
+    if (missing.empty()) {
+        return;
+    }
+    
+    // Il nodo a monte più vicino è il genitore nell'albero di distribuzione
+    std::string responder = packet.getSource();
+    const auto& tree = distributionTreeLocked(frame.streamId);
+    for (const auto& entry : tree.children) {
+        if (std::find(entry.second.begin(), entry.second.end(), localNode.id) != entry.second.end()) {
+            responder = entry.first;
+            break;
+        }
+    }
+    
+    SABER_LOG(Debug, "mesh", "NACK a " << responder << " per " << missing.size() 
+              << " frame dello stream " << frame.streamId);
+    MeshPacket nack = MeshPacket::createNack(frame.streamId, responder, missing);
+    nack.setHeader(localNode.id, ++nextSequence, DEFAULT_PACKET_TTL);
+    if (!sealLocked(nack) && crypto) {
+        nack.sign(*crypto);
+    }
+    enqueuePacket(std::move(nack));
+}
+
+void MeshNetwork::setReliableConfig(const ReliableConfig& config) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    reliableSender.setConfig(config);
+    if (!config.enabled) {
+        // I pacchetti in attesa sono stati scartati senza conferma
+        for (auto& entry : pendingDeliveries) {
+            entry.second.set_value(false);
+        }
+        pendingDeliveries.clear();
+    }
+}
+
+ReliableStats MeshNetwork::getReliableStats() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    ReliableStats result = reliableSender.getStats();
+    result.acksSent = reliableStats.acksSent;
+    result.duplicates = reliableStats.duplicates;
+    return result;
+}
+
+void MeshNetwork::retransmitPending() {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    int64_t now = steadyMillis();
+    for (auto& packet : reliableSender.due(now)) {
+        SABER_LOG(Debug, "mesh", "Ritrasmissione del pacchetto " << packet.getSequence() << " ("
+                  << meshPacketTypeToString(packet.getType()) << ") in attesa di conferma");
+        // Cifrato di nuovo con un nonce fresco, altrimenti la finestra anti-replay lo scarterebbe
+        sealLocked(packet);
+        stats.recordSent(packet.encodedSize(), now);
+        enqueuePacket(std::move(packet));
+    }
+    
+    for (const auto& failure : reliableSender.takeFailures()) {
+        std::string type = meshPacketTypeToString(failure.packet.getType());
+        std::string missing;
+        for (const auto& nodeId : failure.missing) {
+            missing += (missing.empty() ? "" : ", ") + nodeId;
+        }
+        SABER_LOG(Warn, "mesh", "Pacchetto " << failure.packet.getSequence() << " (" << type << ") non confermato dopo "
+                  << reliableSender.getConfig().maxAttempts << " invii" << (missing.empty() ? "" : " da " + missing));
+        resolveDeliveryLocked(failure.packet.getSequence(), false);
+        for (const auto& nodeId : failure.missing) {
+            emitEventLocked(MeshEvent::Type::DeliveryFailed, nodeId, 
+                            type + " " + std::to_string(failure.packet.getSequence()) + " non confermato");
+        }
+    }
+}
+
+void MeshNetwork::sendAckLocked(const MeshPacket& packet) {
+    if (!reliableSender.getConfig().enabled) {
+        return;
+    }
+    
+    MeshPacket ack = MeshPacket::createAck(packet);
+    ack.setHeader(localNode.id, ++nextSequence, DEFAULT_PACKET_TTL);
+    if (!sealLocked(ack) && crypto) {
+        ack.sign(*crypto);
+    }
+    stats.recordSent(ack.encodedSize(), steadyMillis());
+    reliableStats.acksSent++;
+    enqueuePacket(std::move(ack));
+}
+
+void MeshNetwork::handleNackLocked(const MeshPacket& packet) {
+    auto nack = packet.getNackData();
+    if (!repairConfig.enabled || nack.responder != localNode.id) {
+        return;
+    }
+    
+    // Latenza verso il sink richiedente, per stimare l'arrivo della ritrasmissione
+    uint64_t latencyUs = 0;
+    auto requester = nodes.find(packet.getSource());
+    if (requester != nodes.end()) {
+        latencyUs = static_cast<uint64_t>(requester->second.getLatency()) * 1000;
+    }
+    
+    auto cached = repairCache.find(nack.streamId);
+    uint64_t now = networkMicros();
+    for (uint32_t sequence : nack.frames) {
+        repairStats.nacksReceived++;
+        
+        if (cached == repairCache.end() || cached->second.count(sequence) == 0) {
+            repairStats.cacheMisses++;
+            continue;
+        }
+        
+        const MeshPacket& frame = cached->second.at(sequence);
+        if (!arrivesBeforePlayout(now, frame.getAudioData().playoutTimeUs, latencyUs)) {
+            // Troppo tardi: il sink maschererà la perdita
+            repairStats.refusedLate++;
+            continue;
+        }
+        
+        // Il frame viene ritrasmesso una sola volta, con la firma originale
+        enqueuePacket(frame);
+        cached->second.erase(sequence);
+        repairStats.retransmitted++;
+    }
+}
+
+bool MeshNetwork::updateStreamMetadata(const StreamMetadata& metadata) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    return updateStreamMetadataLocked(metadata);
+}
+
+bool MeshNetwork::updateStreamMetadataLocked(const StreamMetadata& metadata) {
+    // Le revisioni ricevute fuori ordine non sovrascrivono il brano corrente
+    auto it = streamMetadata.find(metadata.streamId);
+    if (it != streamMetadata.end() && it->second.revision >= metadata.revision) {
+        return false;
+    }
+    streamMetadata[metadata.streamId] = metadata;
+    return true;
+}
+
+std::optional<StreamMetadata> MeshNetwork::getStreamMetadata(StreamId streamId) const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    auto it = streamMetadata.find(streamId);
+    if (it == streamMetadata.end()) {
+        return std::nullopt;
+    }
+    return it->second;
+}
+
+std::map<StreamId, StreamMetadata> MeshNetwork::getAllStreamMetadata() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    return streamMetadata;
+}
+
+std::optional<RejectReason> MeshNetwork::checkAuthenticityLocked(const MeshPacket& packet, bool& verified) {
+    verified = false;
+    // I nodi in quarantena vengono ignorati finché l'operatore non interviene
+    if (crypto && !packet.getSource().empty() && crypto->isQuarantined(packet.getSource())) {
+        return RejectReason::Quarantined;
+    }
+    
+    if (!packet.isSigned()) {
+        if (requireSignatures) {
+            return RejectReason::BadSignature;
+        }
+        return std::nullopt;
+    }
+    
+    if (!crypto) {
+        return std::nullopt;
+    }
+    
+    try {
+        if (!packet.verifySignature(*crypto)) {
+            return RejectReason::BadSignature;
+        }
+    } catch (const CryptoError&) {
+        // La sorgente non ha una chiave pubblica registrata
+        return RejectReason::UnknownSender;
+    }
+    
+    verified = true;
+    return std::nullopt;
+}
+
+bool MeshNetwork::sealLocked(MeshPacket& packet) {
+    if (!encryptPackets || !crypto || packet.isEncrypted() || travelsInClear(packet)) {
+        return false;
+    }
+    
+    // Sign-then-encrypt: la firma della sorgente viaggia dentro il blob cifrato
+    if (packet.getSource() != localNode.id) {
+        packet.seal(*crypto);
+        return true;
+    }
+    packet.sign(*crypto);
+    
+    // Il traffico per un solo peer usa la chiave della coppia, se il peer ha un'identità attendibile
+    std::string peer = unicastPeer(packet);
+    if (sessionKeys && !peer.empty() && peer != localNode.id && crypto->hasSessionPeer(peer)) {
+        packet.sealFor(*crypto, peer);
+    } else {
+        packet.seal(*crypto);
+    }
+    return true;
+}
+
+void MeshNetwork::dropPacketLocked(const MeshPacket& packet, RejectReason reason, bool authenticated,
+                                   const std::string& detail) {
+    dropCounters[reason]++;
+    SABER_LOG(Warn, "mesh", "Pacchetto da " << packet.getSource() << " scartato: " 
+              << rejectReasonToString(reason) << (detail.empty() ? "" : " (" + detail + ")"));
+    if (reason == RejectReason::BadSignature && intrusionDetector) {
+        intrusionDetector->observe(IntrusionAlert::Type::SignatureBurst, packet.getSource(), steadyMillis());
+    }
+    
+    // Non si risponde mai ad un Reject, per evitare cicli tra nodi; un pacchetto non autenticato
+    // può avere una sorgente falsa e rispondergli farebbe del nodo un riflettore verso la vittima
+    if (!sendRejects || !authenticated || packet.getType() == MeshPacketType::Reject || 
+        packet.getSource().empty() || packet.getSource() == localNode.id) {
+        return;
+    }
+    
+    MeshPacket reject = MeshPacket::createReject(packet, reason, detail);
+    reject.setHeader(localNode.id, ++nextSequence, DEFAULT_PACKET_TTL);
+    if (!sealLocked(reject) && crypto) {
+        reject.sign(*crypto);
+    }
+    enqueuePacket(std::move(reject));
+}
+
+void MeshNetwork::subscribe(const std::string& nodeId, StreamId streamId) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    streamSubscriptions[streamId].insert(nodeId);
+    treesDirty = true;
+}
+
+void MeshNetwork::unsubscribe(const std::string& nodeId, StreamId streamId) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    auto it = streamSubscriptions.find(streamId);
+    if (it != streamSubscriptions.end()) {
+        it->second.erase(nodeId);
+        if (it->second.empty()) {
+            streamSubscriptions.erase(it);
+        }
+    }
+    treesDirty = true;
+}
+
+std::vector<std::string> MeshNetwork::getSubscribers(StreamId streamId) const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    auto it = streamSubscriptions.find(streamId);
+    if (it == streamSubscriptions.end()) {
+        return {};
+    }
+    return std::vector<std::string>(it->second.begin(), it->second.end());
+}
+
+std::vector<std::string> MeshNetwork::getForwardTargets(StreamId streamId) const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    
+    // Solo Master e Repeater inoltrano l'audio
+    if (localNode.role == NodeRole::Sink) {
+        return {};
+    }
+    
+    // Inoltro solo verso i figli nell'albero di distribuzione dello stream
+    const auto& tree = distributionTreeLocked(streamId);
+    auto it = tree.children.find(localNode.id);
+    if (it == tree.children.end()) {
+        return {};
+    }
+    
+    return it->second;
+}
+
+void MeshNetwork::addLink(const std::string& nodeA, const std::string& nodeB) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    links[nodeA].insert(nodeB);
+    links[nodeB].insert(nodeA);
+    treesDirty = true;
+}
+
+void MeshNetwork::removeLink(const std::string& nodeA, const std::string& nodeB) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    links[nodeA].erase(nodeB);
+    links[nodeB].erase(nodeA);
+    treesDirty = true;
+}
+
+DistributionTree MeshNetwork::getDistributionTree(StreamId streamId) const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    return distributionTreeLocked(streamId);
+}
+
+std::set<std::string> MeshNetwork::neighborsLocked(const std::string& nodeId, 
+                                                   const std::string& root) const {
+    if (!links.empty()) {
+        auto it = links.find(nodeId);
+        return it != links.end() ? it->second : std::set<std::string>{};
+    }
+    
+    // Topologia a stella: il Master raggiunge direttamente tutti i nodi
+    std::set<std::string> neighbors;
+    if (nodeId == root) {
+        for (const auto& pair : nodes) {
+            if (pair.first != root) {
+                neighbors.insert(pair.first);
+            }
+        }
+    } else {
+        neighbors.insert(root);
+    }
+    return neighbors;
+}
+
+double MeshNetwork::linkCostLocked(const std::string& from, const std::string& to) const {
+    const std::string* peer = from == localNode.id ? &to : to == localNode.id ? &from : nullptr;
+    if (!peer) {
+        return 1.0;
+    }
+    auto it = nodes.find(*peer);
+    if (it == nodes.end()) {
+        return 1.0;
+    }
+    return 1.0 / std::max(it->second.getLinkQuality(), MIN_LINK_QUALITY);
+}
+
+void MeshNetwork::updateLinkLocked(const std::string& nodeId, const std::function<void(Node&)>& update) {
+    auto it = nodes.find(nodeId);
+    if (it == nodes.end()) {
+        return;
+    }
+    // Gli alberi si ricalcolano solo quando la qualità cambia di un decimo,
+    // così le piccole oscillazioni delle misure non spostano i percorsi
+    auto band = [](double quality) { return static_cast<int>(quality * 10); };
+    int before = band(it->second.getLinkQuality());
+    update(it->second);
+    if (band(it->second.getLinkQuality()) != before) {
+        treesDirty = true;
+    }
+}
+
+const DistributionTree& MeshNetwork::distributionTreeLocked(StreamId streamId) const {
+    if (treesDirty) {
+        distributionTrees.clear();
+        treesDirty = false;
+    }
+    
+    auto cached = distributionTrees.find(streamId);
+    if (cached != distributionTrees.end()) {
+        return cached->second;
+    }
+    
+    // La radice è il Master della rete
+    std::string root = localNode.id;
+    if (localNode.role != NodeRole::Master) {
+        for (const auto& pair : nodes) {
+            if (pair.second.role == NodeRole::Master) {
+                root = pair.first;
+                break;
+            }
+        }
+    }
+    
+    DistributionTree tree{streamId, root, {}};
+    
+    // Percorsi di costo minimo dalla radice: ogni nodo viene agganciato al vicino da cui
+    // lo raggiunge con il percorso più affidabile. A parità di costo vince il primo vicino
+    // scoperto, quindi con collegamenti perfetti l'albero è quello della visita in ampiezza
+    using Candidate = std::tuple<double, uint64_t, std::string>;
+    std::priority_queue<Candidate, std::vector<Candidate>, std::greater<Candidate>> frontier;
+    std::map<std::string, std::string> parents;
+    std::map<std::string, double> costs;
+    uint64_t discovered = 0;
+    frontier.emplace(0.0, discovered++, root);
+    parents[root] = root;
+    costs[root] = 0.0;
+    
+    while (!frontier.empty()) {
+        double cost = std::get<0>(frontier.top());
+        std::string current = std::get<2>(frontier.top());
+        frontier.pop();
+        if (cost > costs[current]) {
+            continue;
+        }
+        
+        // I sink non inoltrano traffico
+        auto currentIt = nodes.find(current);
+        if (current != root && currentIt != nodes.end() && 
+            currentIt->second.role == NodeRole::Sink) {
+            continue;
+        }
+        
+        for (const auto& neighbor : neighborsLocked(current, root)) {
+            if (nodes.count(neighbor) == 0) {
+                continue;
+            }
+            double reached = cost + linkCostLocked(current, neighbor);
+            auto known = costs.find(neighbor);
+            if (known == costs.end() || reached < known->second) {
+                costs[neighbor] = reached;
+                parents[neighbor] = current;
+                frontier.emplace(reached, discovered++, neighbor);
+            }
+        }
+    }
+    
+    // Potatura: si mantengono solo i rami verso sink attivi sottoscritti
+    auto subscribers = streamSubscriptions.find(streamId);
+    if (subscribers != streamSubscriptions.end()) {
+        std::set<std::pair<std::string, std::string>> edges;
+        for (const auto& nodeId : subscribers->second) {
+            auto nodeIt = nodes.find(nodeId);
+            if (nodeIt == nodes.end() || !nodeIt->second.isActive() || parents.count(nodeId) == 0) {
+                continue;
+            }
+            
+            std::string child = nodeId;
+            while (child != root) {
+                const std::string& parent = parents[child];
+                if (!edges.insert({parent, child}).second) {
+                    break;
+                }
+                child = parent;
+            }
+        }
+        
+        for (const auto& edge : edges) {
+            tree.children[edge.first].push_back(edge.second);
+        }
+    }
+    
+    return distributionTrees.emplace(streamId, std::move(tree)).first->second;
+}
+
+std::string MeshNetwork::exportTopologyJson() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    std::ostringstream json;
+    
+    json << "{\"nodes\":[";
+    bool first = true;
+    for (const auto& pair : nodes) {
+        json << (first ? "" : ",") << "{\"id\":\"" << jsonEscape(pair.first) 
+             << "\",\"role\":\"" << nodeRoleToString(pair.second.role) 
+             << "\",\"active\":" << (pair.second.isActive() ? "true" : "false") << "}";
+        first = false;
+    }
+    
+    json << "],\"links\":[";
+    first = true;
+    for (const auto& pair : links) {
+        for (const auto& neighbor : pair.second) {
+            // Ogni collegamento viene esportato una sola volta
+            if (pair.first < neighbor) {
+                json << (first ? "" : ",") << "[\"" << jsonEscape(pair.first) 
+                     << "\",\"" << jsonEscape(neighbor) << "\"]";
+                first = false;
+            }
+        }
+    }
+    
+    json << "],\"trees\":[";
+    first = true;
+    for (const auto& subscription : streamSubscriptions) {
+        const auto& tree = distributionTreeLocked(subscription.first);
+        json << (first ? "" : ",") << "{\"stream_id\":" << tree.streamId 
+             << ",\"root\":\"" << jsonEscape(tree.root) << "\",\"children\":{";
+        bool firstParent = true;
+        for (const auto& parent : tree.children) {
+            json << (firstParent ? "" : ",") << "\"" << jsonEscape(parent.first) << "\":[";
+            for (size_t i = 0; i < parent.second.size(); ++i) {
+                json << (i ? "," : "") << "\"" << jsonEscape(parent.second[i]) << "\"";
+            }
+            json << "]";
+            firstParent = false;
+        }
+        json << "}}";
+        first = false;
+    }
+    json << "]}";
+    
+    return json.str();
+}
+
+TopologyGraph MeshNetwork::getTopology() const {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    TopologyGraph graph;
+    graph.localNode = localNode.id;
+
+    TopologyNode local;
+    local.id = localNode.id;
+    local.role = localNode.role;
+    local.active = true;
+    local.local = true;
+    graph.nodes.push_back(local);
+    for (const auto& pair : nodes) {
+        if (pair.first == localNode.id) {
+            continue;
+        }
+        TopologyNode node;
+        node.id = pair.first;
+        node.role = pair.second.role;
+        node.active = pair.second.isActive();
+        auto hops = observedHops.find(pair.first);
+        if (hops != observedHops.end()) {
+            node.hops = hops->second;
+        }
+        graph.nodes.push_back(node);
+    }
+    std::sort(graph.nodes.begin(), graph.nodes.end(),
+              [](const TopologyNode& a, const TopologyNode& b) { return a.id < b.id; });
+
+    int64_t now = steadyMillis();
+    for (const auto& pair : heardNeighbors) {
+        uint64_t age = static_cast<uint64_t>(now - pair.second);
+        if (age >= TOPOLOGY_LINK_TIMEOUT_MS) {
+            continue;
+        }
+        TopologyLink link;
+        link.from = pair.first;
+        link.to = localNode.id;
+        auto node = nodes.find(pair.first);
+        if (node != nodes.end()) {
+            link.rssiDbm = node->second.getSignalStrength();
+        }
+        link.ageMs = age;
+        graph.links.push_back(link);
+    }
+    return graph;
+}
+
+std::string topologyToDot(const TopologyGraph& graph) {
+    // Gli ID vanno tra virgolette: possono contenere trattini e punti
+    auto quote = [](const std::string& value) {
+        std::string quoted = "\"";
+        for (char c : value) {
+            if (c == '"' || c == '\\') {
+                quoted += '\\';
+            }
+            quoted += c;
+        }
+        return quoted + "\"";
+    };
+    auto label = [&quote](const std::string& first, const std::string& second) {
+        std::string text = quote(first);
+        return text.substr(0, text.size() - 1) + "\\n" + second + "\"";
+    };
+
+    std::ostringstream dot;
+    dot << "digraph saber {\n";
+    for (const auto& node : graph.nodes) {
+        dot << "  " << quote(node.id) << " [label=" << label(node.id, nodeRoleToString(node.role))
+            << (node.role == NodeRole::Master ? " shape=doublecircle" : " shape=circle");
+        if (node.local) {
+            dot << " penwidth=2";
+        }
+        if (!node.active) {
+            dot << " style=dashed";
+        }
+        dot << "];\n";
+    }
+    for (const auto& link : graph.links) {
+        dot << "  " << quote(link.from) << " -> " << quote(link.to);
+        if (link.rssiDbm) {
+            dot << " [label=" << quote(std::to_string(*link.rssiDbm) + " dBm") << "]";
+        }
+        dot << ";\n";
+    }
+    dot << "}\n";
+    return dot.str();
+}
+
+std::string topologyToJson(const TopologyGraph& graph) {
+    std::ostringstream json;
+    json << "{\"local\":\"" << jsonEscape(graph.localNode) << "\",\"nodes\":[";
+    for (size_t i = 0; i < graph.nodes.size(); ++i) {
+        const auto& node = graph.nodes[i];
+        json << (i ? "," : "") << "{\"id\":\"" << jsonEscape(node.id)
+             << "\",\"role\":\"" << nodeRoleToString(node.role)
+             << "\",\"active\":" << (node.active ? "true" : "false")
+             << ",\"hops\":" << (node.hops ? std::to_string(*node.hops) : "null") << "}";
+    }
+    json << "],\"links\":[";
+    for (size_t i = 0; i < graph.links.size(); ++i) {
+        const auto& link = graph.links[i];
+        json << (i ? "," : "") << "{\"from\":\"" << jsonEscape(link.from)
+             << "\",\"to\":\"" << jsonEscape(link.to)
+             << "\",\"rssi_dbm\":" << (link.rssiDbm ? std::to_string(*link.rssiDbm) : "null")
+             << ",\"age_ms\":" << link.ageMs << "}";
+    }
+    json << "]}";
+    return json.str();
+}
+
+std::string encodeNeighborReport(const std::vector<TopologyLink>& links) {
+    std::string text;
+    for (const auto& link : links) {
+        if (!text.empty()) {
+            text += ",";
+        }
+        text += link.from + ":" + (link.rssiDbm ? std::to_string(*link.rssiDbm) : "") + ":" +
+                std::to_string(link.ageMs);
+    }
+    return text;
+}
+
+std::optional<std::vector<TopologyLink>> parseNeighborReport(const std::string& reporter, const std::string& text) {
+    std::vector<TopologyLink> links;
+    std::stringstream stream(text);
+    std::string item;
+    while (std::getline(stream, item, ',')) {
+        // I campi numerici sono in coda: l'ID del nodo può contenere ':'
+        auto ageColon = item.rfind(':');
+        auto rssiColon = ageColon == std::string::npos || ageColon == 0 ? std::string::npos
+                                                                        : item.rfind(':', ageColon - 1);
+        if (rssiColon == std::string::npos || rssiColon == 0) {
+            return std::nullopt;
+        }
+        TopologyLink link;
+        link.from = item.substr(0, rssiColon);
+        link.to = reporter;
+        try {
+            size_t used = 0;
+            std::string age = item.substr(ageColon + 1);
+            link.ageMs = std::stoull(age, &used);
+            if (used != age.size() || age.front() == '-') {
+                return std::nullopt;
+            }
+            std::string rssi = item.substr(rssiColon + 1, ageColon - rssiColon - 1);
+            if (!rssi.empty()) {
+                link.rssiDbm = std::stoi(rssi, &used);
+                if (used != rssi.size()) {
+                    return std::nullopt;
+                }
+            }
+        } catch (const std::exception&) {
+            return std::nullopt;
+        }
+        links.push_back(link);
+    }
+    return links;
+}
+
+void MeshNetwork::runNetworkLoop() {
+    while (running) {
+        std::optional<MeshPacket> packet;
+        
+        {
+            std::unique_lock<std::mutex> lock(queueMutex);
+            queueCondition.wait_for(lock, std::chrono::milliseconds(100), 
+                                   [this] { return !packetQueue.empty() || !running; });
+            
+            if (!running) {
+                break;
+            }
+            
+            // Un pacchetto alla volta: la sincronizzazione arrivata nel frattempo passa davanti al resto
+            packet = packetQueue.pop();
+        }
+        
+        if (packet) {
+            processPacket(*packet);
+        }
+    }
+}
+
+void MeshNetwork::processPacket(const MeshPacket& received) {
+    std::lock_guard<std::mutex> lock(networkMutex);
+    SABER_LOG_SPAN("mesh", "packet", "type=" << meshPacketTypeToString(received.getType()) 
+                   << " source=" << received.getSource() << " seq=" << received.getSequence());
+    
+    // I pacchetti altrui cifrati vengono aperti prima di ogni altra verifica;
+    // quelli locali portano ancora il contenuto in chiaro
+    std::optional<MeshPacket> opened;
+    bool foreign = received.getSource() != localNode.id;
+    if (foreign && received.isEncrypted()) {
+        if (!crypto) {
+            dropPacketLocked(received, RejectReason::WrongNetworkKey, false, "nessuna chiave di rete");
+            return;
+        }
+        // Un pacchetto cifrato per un altro peer non è destinato a questo nodo
+        if (!received.getSessionPeer().empty() && received.getSessionPeer() != localNode.id) {
+            return;
+        }
+        try {
+            opened = received.open(*crypto);
+        } catch (const CryptoError& e) {
+            // Una copia inoltrata di un pacchetto già ricevuto per un'altra via è attesa
+            if (e.getType() == CryptoError::Type::Replay && received.getHopCount() > 0) {
+                dropCounters[RejectReason::Replay]++;
+                return;
+            }
+            RejectReason reason = RejectReason::WrongNetworkKey;
+            if (e.getType() == CryptoError::Type::Replay) {
+                reason = RejectReason::Replay;
+            } else if (e.getType() == CryptoError::Type::KeyExchange) {
+                reason = RejectReason::UnknownSender;
+            }
+            dropPacketLocked(received, reason, false, e.what());
+            return;
+        } catch (const std::invalid_argument& e) {
+            dropPacketLocked(received, RejectReason::WrongNetworkKey, false, e.what());
+            return;
+        }
+    } else if (foreign && encryptPackets && !travelsInClear(received)) {
+        dropPacketLocked(received, RejectReason::WrongNetworkKey, false, "pacchetto in chiaro");
+        return;
+    }
+    const MeshPacket& packet = opened ? *opened : received;
+    
+    // Le richieste di ingresso arrivano da nodi ancora sconosciuti
+    if (packet.getType() == MeshPacketType::Join) {
+        handleJoinLocked(packet);
+        return;
+    }
+    
+    // L'abbinamento è autenticato dal PIN e non viene inoltrato: solo tra vicini diretti
+    if (packet.getType() == MeshPacketType::Pairing) {
+        if (foreign && packetHandler) {
+            packetHandler(packet);
+        }
+        return;
+    }
+    
+    // La conferma di un Join arriva da un Master di cui il nuovo nodo non conosce ancora la
+    // chiave: viene accettata senza verifica perché al più interrompe le ritrasmissioni
+    if (packet.getType() == MeshPacketType::Ack && packet.getAckData().ackedType == MeshPacketType::Join) {
+        auto ack = packet.getAckData();
+        if (foreign && ack.origin == localNode.id) {
+            reliableSender.acknowledge(ack.sequence, packet.getSource());
+        }
+        return;
+    }
+    
+    // Verifica dell'autenticità prima di qualsiasi elaborazione; solo un pacchetto aperto con la
+    // chiave di rete o con la firma verificata ha una sorgente di cui fidarsi per le risposte
+    bool verified = false;
+    if (auto reason = checkAuthenticityLocked(packet, verified)) {
+        dropPacketLocked(packet, *reason, false);
+        return;
+    }
+    bool authenticated = verified || opened.has_value();
+    
+    // Il limite di traffico segue l'autenticazione: un falso mittente non consuma il limite altrui.
+    // Nessun log né Reject, che moltiplicherebbero il lavoro causato dall'inondazione
+    if (foreign && !rateLimiter.allow(packet.getSource(), packet.getType(), steadyMillis())) {
+        dropCounters[RejectReason::RateLimited]++;
+        return;
+    }
+    
+    // Un nodo tolto per inattività rientra al primo pacchetto autentico, con la chiave già registrata
+    if (foreign && evictedNodes.count(packet.getSource()) > 0 && !rejoinLocked(packet, authenticated)) {
+        return;
+    }
+    
+    SABER_LOG(Trace, "mesh", "Pacchetto " << packet.getSequence() << " da " << packet.getSource());
+    
+    // Un pacchetto altrui non arriva mai con TTL esaurito, né un pacchetto locale torna indietro
+    if (packet.getSource() != localNode.id && packet.getTtl() == 0) {
+        dropPacketLocked(packet, RejectReason::TtlExpired, authenticated, describePathLocked(packet));
+        return;
+    }
+    if (packet.getSource() == localNode.id && packet.getHopCount() > 0) {
+        dropPacketLocked(packet, RejectReason::RoutingLoop, authenticated, describePathLocked(packet));
+        return;
+    }
+    
+    // Ricezione di un frame audio: timestamp, riparazione, inoltro e consegna al gestore
+    std::optional<StageTimer> receiveTimer;
+    if (packet.getType() == MeshPacketType::Audio && packet.getSource() != localNode.id) {
+        receiveTimer.emplace(profiler.get(), PipelineStage::Receive);
+    }
+    
+    // Contabilità della banda: i pacchetti locali sono già contati in invio
+    if (packet.getSource() != localNode.id) {
+        size_t size = packet.encodedSize();
+        int64_t now = steadyMillis();
+        stats.recordReceived(packet.getSource(), size, now);
+        if (packet.getType() == MeshPacketType::Metadata) {
+            stats.recordStream(packet.getMetadata().streamId, size, now);
+        } else if (packet.getType() == MeshPacketType::Audio) {
+            const auto& frame = packet.getAudioData();
+            stats.recordStream(frame.streamId, size, now);
+            latencyStats.recordFrame(packet.getSource(), frame.streamId, frame.frameSequence);
+        }
+
+        // Il vicino ascoltato è il mittente, o l'ultimo nodo che ha inoltrato il pacchetto
+        observedHops[packet.getSource()] = packet.getHopCount();
+        const auto& path = packet.getPath();
+        if (packet.getHopCount() == 0) {
+            heardNeighbors[packet.getSource()] = now;
+        } else if (path.size() == packet.getHopCount()) {
+            std::string neighbor = resolveShortIdLocked(path.back());
+            if (neighbor != localNode.id && neighbor.front() != '#') {
+                heardNeighbors[neighbor] = now;
+            }
+        }
+    }
+    
+    // I frame audio compressi ricevono il timestamp completo prima di ogni elaborazione
+    std::optional<MeshPacket> resolved;
+    if (packet.getType() == MeshPacketType::Audio && packet.getSource() != localNode.id) {
+        const auto& frame = packet.getAudioData();
+        uint64_t value = frame.timestampWidth == TimestampWidth::Full ? frame.playoutTimeUs 
+                                                                     : frame.compactTimestamp;
+        auto playoutTime = timestampDecoders[frame.streamId].decode(frame.timestampWidth, value,
+                                                                    packet.getArrivalTime());
+        if (!playoutTime) {
+            SABER_LOG(Debug, "mesh", "Frame " << frame.frameSequence << " dello stream " << frame.streamId
+                      << " scartato in attesa di un timestamp completo");
+            return;
+        }
+        resolved = packet;
+        resolved->resolvePlayoutTime(*playoutTime);
+    }
+    const MeshPacket& current = resolved ? *resolved : packet;
+    
+    // Ogni copia di un comando viene confermata, perché la conferma precedente può essere andata
+    // persa; l'effetto del comando, anche di una ritrasmissione locale, si produce una volta sola
+    if (packet.getType() == MeshPacketType::Command) {
+        if (foreign) {
+            sendAckLocked(packet);
+        }
+        if (!duplicateFilter.insert(packet, steadyMillis())) {
+            if (foreign) {
+                reliableStats.duplicates++;
+            }
+            return;
+        }
+    }
+    
+    // Elabora il pacchetto in base al tipo
+    switch (packet.getType()) {
+        case MeshPacketType::Ping: {
+            auto [source, timestamp] = packet.getPingData();
+            auto it = nodes.find(source);
+            if (it != nodes.end()) {
+                touchNodeLocked(it->second);
+            }
+            break;
+        }
+        case MeshPacketType::Status: {
+            auto [nodeId, buffer, latency] = packet.getStatusData();
+            updateNodeStatusLocked(nodeId, buffer, latency);
+            if (nodeId != localNode.id) {
+                latencyStats.recordLatency(nodeId, latency);
+            }
+            break;
+        }
+        case MeshPacketType::TimeBeacon: {
+            // Solo il Master scandisce il tempo della rete
+            auto it = nodes.find(packet.getSource());
+            bool fromMaster = packet.getSource() == localNode.id ? localNode.role == NodeRole::Master
+                            : it != nodes.end() && it->second.role == NodeRole::Master;
+            if (!fromMaster) {
+                if (intrusionDetector) {
+                    intrusionDetector->observe(IntrusionAlert::Type::RogueBeacon, packet.getSource(), steadyMillis());
+                }
+                dropPacketLocked(packet, RejectReason::Unauthorized, authenticated, "beacon non inviato dal Master");
+                return;
+            }
+            if (foreign && localNode.role == NodeRole::Repeater) {
+                relayBeaconLocked(received, authenticated);
+            }
+            break;
+        }
+        case MeshPacketType::EmergencySync: {
+            // Un riallineamento forzato sposta di colpo gli orologi: solo il Master può chiederlo
+            auto it = nodes.find(packet.getSource());
+            if (packet.getSource() != localNode.id && (it == nodes.end() || it->second.role != NodeRole::Master)) {
+                if (intrusionDetector) {
+                    intrusionDetector->observe(IntrusionAlert::Type::RogueBeacon, packet.getSource(), steadyMillis());
+                }
+                dropPacketLocked(packet, RejectReason::Unauthorized, authenticated,
+                                 "riallineamento non inviato dal Master");
+                return;
+            }
+            break;
+        }
+        case MeshPacketType::Subscribe: {
+            auto [nodeId, streamId] = packet.getSubscriptionData();
+            // Un nodo sottoscrive solo sé stesso: il mittente è quello autenticato
+            if (nodeId != packet.getSource()) {
+                dropPacketLocked(packet, RejectReason::Unauthorized, authenticated,
+                                 "sottoscrizione per conto di " + nodeId);
+                return;
+            }
+            // Gli stream dell'intercom non vengono pubblicati dal Master
+            if (!publishedStreams.empty() && publishedStreams.count(streamId) == 0 && !isIntercomStream(streamId)) {
+                dropPacketLocked(packet, RejectReason::UnknownStream, authenticated,
+                                 "stream " + std::to_string(streamId) + " non pubblicato");
+                return;
+            }
+            streamSubscriptions[streamId].insert(nodeId);
+            treesDirty = true;
+            break;
+        }
+        case MeshPacketType::Unsubscribe: {
+            auto [nodeId, streamId] = packet.getSubscriptionData();
+            if (nodeId != packet.getSource()) {
+                dropPacketLocked(packet, RejectReason::Unauthorized, authenticated,
+                                 "sottoscrizione per conto di " + nodeId);
+                return;
+            }
+            auto it = streamSubscriptions.find(streamId);
+            if (it != streamSubscriptions.end()) {
+                it->second.erase(nodeId);
+                if (it->second.empty()) {
+                    streamSubscriptions.erase(it);
+                }
+            }
+            treesDirty = true;
+            break;
+        }
+        case MeshPacketType::Reject: {
+            auto reject = packet.getRejectData();
+            if (reject.origin == localNode.id) {
+                SABER_LOG(Warn, "mesh", "Pacchetto " << reject.rejectedSequence << " rifiutato da " 
+                          << packet.getSource() << ": " << rejectReasonToString(reject.reason)
+                          << (reject.detail.empty() ? "" : " (" + reject.detail + ")"));
+                if (reject.reason == RejectReason::TtlExpired || reject.reason == RejectReason::RoutingLoop) {
+                    routeFailures[packet.getSource()] = rejectReasonToString(reject.reason) 
+                                                      + (reject.detail.empty() ? "" : " " + reject.detail);
+                }
+            }
+            break;
+        }
+        case MeshPacketType::Audio: {
+            if (packet.getSource() == localNode.id) {
+                break;
+            }
+            if (localNode.role == NodeRole::Repeater) {
+                MeshPacket forwarded = current;
+                if (prepareForwardLocked(forwarded, authenticated)) {
+                    cacheAudioLocked(forwarded);
+                    routeAudioLocked(forwarded);
+                }
+            } else if (localNode.role == NodeRole::Sink) {
+                requestRepairLocked(current);
+            }
+            RouteTrace& trace = routeTraces[current.getAudioData().streamId];
+            trace.streamId = current.getAudioData().streamId;
+            trace.source = current.getSource();
+            trace.hops = current.getHopCount();
+            trace.ttl = current.getTtl();
+            trace.path = current.getPath();
+            trace.observedAtMs = std::chrono::duration_cast<std::chrono::milliseconds>(
+                std::chrono::system_clock::now().time_since_epoch()).count();
+            break;
+        }
+        case MeshPacketType::Nack: {
+            handleNackLocked(packet);
+            break;
+        }
+        case MeshPacketType::Ack: {
+            auto ack = packet.getAckData();
+            if (foreign && ack.origin == localNode.id && reliableSender.acknowledge(ack.sequence, packet.getSource())) {
+                resolveDeliveryLocked(ack.sequence, true);
+            }
+            break;
+        }
+        case MeshPacketType::Command: {
+            // Solo il Master decide l'appartenenza alle zone
+            auto it = nodes.find(packet.getSource());
+            auto [cmdType, params] = packet.getCommandData();
+            if (cmdType == "zone.assign" && it != nodes.end() && it->second.role == NodeRole::Master
+                && !params["node"].empty() && !params["zone"].empty()) {
+                assignZoneLocked(params["node"], params["zone"]);
+            }
+            break;
+        }
+        case MeshPacketType::Metadata: {
+            auto metadata = packet.getMetadata();
+            if (updateStreamMetadataLocked(metadata)) {
+                SABER_LOG(Debug, "mesh", "Stream " << metadata.streamId << ": \"" << metadata.title 
+                          << "\" di " << metadata.artist);
+            }
+            break;
+        }
+        default:
+            break;
+    }
+    
+    // Inoltra il pacchetto al gestore registrato; i comandi portano il ruolo del mittente, che il
+    // gestore non può chiedere alla rete mentre il mutex è occupato
+    if (packetHandler) {
+        auto sender = nodes.find(current.getSource());
+        if (current.getType() == MeshPacketType::Command && sender != nodes.end()) {
+            MeshPacket command = current;
+            command.setSenderRole(sender->second.role);
+            packetHandler(command);
+        } else {
+            packetHandler(current);
+        }
     }
 }
 
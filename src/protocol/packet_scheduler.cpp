@@ -0,0 +1,141 @@
+#include "mesh.h"
+
+#include <algorithm>
+
+namespace saber {
+
+namespace {
+
+/// Classi servite a giro, con il numero di estrazioni per giro
+const std::array<std::pair<PacketClass, uint32_t>, 3> WEIGHTED_CLASSES = {{
+    {PacketClass::Audio, 8},
+    {PacketClass::Status, 2},
+    {PacketClass::Command, 1},
+}};
+
+size_t indexOf(PacketClass packetClass) {
+    return static_cast<size_t>(packetClass);
+}
+
+} // namespace
+
+std::string packetClassToString(PacketClass packetClass) {
+    switch (packetClass) {
+        case PacketClass::Sync:
+            return "sync";
+        case PacketClass::Audio:
+            return "audio";
+        case PacketClass::Status:
+            return "status";
+        case PacketClass::Command:
+            return "command";
+    }
+    return "command";
+}
+
+std::optional<PacketClass> packetClassFromString(const std::string& name) {
+    if (name == "sync") {
+        return PacketClass::Sync;
+    }
+    if (name == "audio") {
+        return PacketClass::Audio;
+    }
+    if (name == "status") {
+        return PacketClass::Status;
+    }
+    if (name == "command") {
+        return PacketClass::Command;
+    }
+    return std::nullopt;
+}
+
+PacketClass packetClassOf(MeshPacketType type) {
+    switch (type) {
+        case MeshPacketType::TimeBeacon:
+        case MeshPacketType::EmergencySync:
+            return PacketClass::Sync;
+        case MeshPacketType::Audio:
+        case MeshPacketType::Nack:
+            // Una ritrasmissione serve solo se arriva prima dell'istante di riproduzione
+            return PacketClass::Audio;
+        case MeshPacketType::Status:
+        case MeshPacketType::Ping:
+            return PacketClass::Status;
+        default:
+            return PacketClass::Command;
+    }
+}
+
+// Implementazione di PacketScheduler
+PacketScheduler::PacketScheduler(size_t capacity) : capacity(capacity) {
+}
+
+bool PacketScheduler::push(MeshPacket packet) {
+    size_t index = indexOf(packetClassOf(packet.getType()));
+    auto& queue = queues[index];
+    auto& classStats = stats[index];
+    bool kept = true;
+    if (capacity > 0 && queue.size() >= capacity) {
+        // Il pacchetto più vecchio è quello con meno probabilità di servire ancora
+        queue.pop_front();
+        classStats.dropped++;
+        kept = false;
+    }
+    queue.push_back(std::move(packet));
+    classStats.queued = queue.size();
+    classStats.peak = std::max(classStats.peak, queue.size());
+    return kept;
+}
+
+std::optional<MeshPacket> PacketScheduler::pop() {
+    if (!queues[indexOf(PacketClass::Sync)].empty()) {
+        return take(PacketClass::Sync);
+    }
+
+    // Due passate: la seconda parte da un giro nuovo se le classi in attesa hanno esaurito le estrazioni
+    for (int pass = 0; pass < 2; ++pass) {
+        for (const auto& weighted : WEIGHTED_CLASSES) {
+            size_t index = indexOf(weighted.first);
+            if (!queues[index].empty() && credits[index] > 0) {
+                credits[index]--;
+                return take(weighted.first);
+            }
+        }
+        for (const auto& weighted : WEIGHTED_CLASSES) {
+            credits[indexOf(weighted.first)] = weighted.second;
+        }
+    }
+    return std::nullopt;
+}
+
+bool PacketScheduler::empty() const {
+    return size() == 0;
+}
+
+size_t PacketScheduler::size() const {
+    size_t total = 0;
+    for (const auto& queue : queues) {
+        total += queue.size();
+    }
+    return total;
+}
+
+std::map<PacketClass, PacketQueueStats> PacketScheduler::getStats() const {
+    std::map<PacketClass, PacketQueueStats> result;
+    for (size_t index = 0; index < stats.size(); ++index) {
+        result[static_cast<PacketClass>(index)] = stats[index];
+    }
+    return result;
+}
+
+MeshPacket PacketScheduler::take(PacketClass packetClass) {
+    size_t index = indexOf(packetClass);
+    auto& queue = queues[index];
+    MeshPacket packet = std::move(queue.front());
+    queue.pop_front();
+    stats[index].queued = queue.size();
+    stats[index].dequeued++;
+    return packet;
+}
+
+} // namespace saber
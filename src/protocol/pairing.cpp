@@ -0,0 +1,366 @@
+#include "pairing.h"
+#include "crypto.h"
+
+#include <algorithm>
+#include <cctype>
+#include <sstream>
+
+#include <openssl/evp.h>
+#include <openssl/hmac.h>
+#include <sodium.h>
+
+namespace saber {
+
+namespace {
+
+using Key = std::array<uint8_t, 32>;
+
+/// Prefisso del codice di abbinamento mostrato come QR
+const std::string PAIRING_URI_PREFIX = "saber://pair?";
+
+/// Byte dell'identificatore di sessione
+const size_t SESSION_ID_BYTES = 16;
+
+/// Hello: sessione, chiave effimera, ruolo, identità del nuovo nodo
+const size_t HELLO_BYTES = SESSION_ID_BYTES + 32 + 1 + crypto_sign_PUBLICKEYBYTES;
+
+/// Sfida: chiave effimera e identità del Master
+const size_t CHALLENGE_BYTES = 32 + crypto_sign_PUBLICKEYBYTES;
+
+Key hmacSha256(const uint8_t* key, size_t keySize, const std::vector<uint8_t>& data) {
+    Key result;
+    unsigned int size = result.size();
+    HMAC(EVP_sha256(), key, static_cast<int>(keySize), data.data(), data.size(), result.data(), &size);
+    return result;
+}
+
+Key subkey(const Key& key, const std::string& label) {
+    return hmacSha256(key.data(), key.size(), std::vector<uint8_t>(label.begin(), label.end()));
+}
+
+void appendString(std::vector<uint8_t>& out, const std::string& value) {
+    out.push_back(static_cast<uint8_t>(value.size() >> 8));
+    out.push_back(static_cast<uint8_t>(value.size()));
+    out.insert(out.end(), value.begin(), value.end());
+}
+
+// Generatore del PIN (CPace su ristretto255): senza il PIN i messaggi sono punti qualsiasi
+std::optional<Key> pinGenerator(const std::string& pin, const uint8_t* sessionId, const std::string& nodeId) {
+    std::string label = "saber-pairing-cpace-v1";
+    std::vector<uint8_t> data(label.begin(), label.end());
+    appendString(data, pin);
+    data.insert(data.end(), sessionId, sessionId + SESSION_ID_BYTES);
+    appendString(data, nodeId);
+    uint8_t hash[crypto_hash_sha512_BYTES];
+    crypto_hash_sha512(hash, data.data(), data.size());
+    Key generator;
+    crypto_core_ristretto255_from_hash(generator.data(), hash);
+    sodium_memzero(hash, sizeof(hash));
+    if (!crypto_core_ristretto255_is_valid_point(generator.data())) {
+        return std::nullopt;
+    }
+    return generator;
+}
+
+// Scalare effimero estratto dalla sorgente casuale
+Key randomScalar(RandomSource& rng) {
+    uint8_t wide[crypto_core_ristretto255_NONREDUCEDSCALARBYTES];
+    rng.fill(wide, sizeof(wide));
+    Key scalar;
+    crypto_core_ristretto255_scalar_reduce(scalar.data(), wide);
+    sodium_memzero(wide, sizeof(wide));
+    return scalar;
+}
+
+// Chiave effimera di un lato: lo scalare moltiplicato per il generatore del PIN
+std::optional<Key> ephemeralKey(const Key& scalar, const std::optional<Key>& generator) {
+    Key point;
+    if (!generator || crypto_scalarmult_ristretto255(point.data(), scalar.data(), generator->data()) != 0) {
+        return std::nullopt;
+    }
+    return point;
+}
+
+std::vector<uint8_t> transcript(const uint8_t* sessionId, const std::string& nodeId, const std::string& masterId,
+                                const Key& joinerKeyShare, const Key& masterKeyShare, NodeRole role,
+                                const std::vector<uint8_t>& joinerKey, const std::vector<uint8_t>& masterKey) {
+    std::string label = "saber-pairing-v1";
+    std::vector<uint8_t> data(label.begin(), label.end());
+    data.insert(data.end(), sessionId, sessionId + SESSION_ID_BYTES);
+    appendString(data, nodeId);
+    appendString(data, masterId);
+    data.insert(data.end(), joinerKeyShare.begin(), joinerKeyShare.end());
+    data.insert(data.end(), masterKeyShare.begin(), masterKeyShare.end());
+    data.push_back(static_cast<uint8_t>(role));
+    data.insert(data.end(), joinerKey.begin(), joinerKey.end());
+    data.insert(data.end(), masterKey.begin(), masterKey.end());
+    return data;
+}
+
+// Chiave della sessione dal Diffie-Hellman sul generatore del PIN e dall'intera trascrizione
+std::optional<Key> deriveSessionKey(const Key& scalar, const Key& peerKey, const std::vector<uint8_t>& data) {
+    Key shared;
+    // Un punto non valido o l'identità non portano a nessuna chiave
+    if (!crypto_core_ristretto255_is_valid_point(peerKey.data()) ||
+        crypto_scalarmult_ristretto255(shared.data(), scalar.data(), peerKey.data()) != 0) {
+        return std::nullopt;
+    }
+    Key key = hmacSha256(shared.data(), shared.size(), data);
+    sodium_memzero(shared.data(), shared.size());
+    return key;
+}
+
+bool isPin(const std::string& text) {
+    return text.size() == PAIRING_PIN_DIGITS &&
+           std::all_of(text.begin(), text.end(), [](unsigned char c) { return std::isdigit(c) != 0; });
+}
+
+} // namespace
+
+std::string pairingStateToString(PairingState state) {
+    switch (state) {
+        case PairingState::Idle:
+            return "idle";
+        case PairingState::Open:
+            return "open";
+        case PairingState::Waiting:
+            return "waiting";
+        case PairingState::Paired:
+            return "paired";
+        case PairingState::Failed:
+            return "failed";
+    }
+    return "idle";
+}
+
+std::string generatePairingPin(RandomSource& rng) {
+    uint32_t limit = 1;
+    for (size_t i = 0; i < PAIRING_PIN_DIGITS; ++i) {
+        limit *= 10;
+    }
+    // Estrazione con rifiuto: ogni PIN ha la stessa probabilità
+    const uint32_t bound = UINT32_MAX - UINT32_MAX % limit;
+    uint32_t value = 0;
+    do {
+        rng.fill(reinterpret_cast<uint8_t*>(&value), sizeof(value));
+    } while (value >= bound);
+    std::string pin = std::to_string(value % limit);
+    return std::string(PAIRING_PIN_DIGITS - pin.size(), '0') + pin;
+}
+
+std::string pairingUri(const std::string& masterId, const std::string& pin) {
+    return PAIRING_URI_PREFIX + "master=" + masterId + "&pin=" + pin;
+}
+
+std::optional<PairingCode> parsePairingCode(const std::string& code) {
+    if (isPin(code)) {
+        return PairingCode{code, ""};
+    }
+    if (code.compare(0, PAIRING_URI_PREFIX.size(), PAIRING_URI_PREFIX) != 0) {
+        return std::nullopt;
+    }
+    PairingCode parsed;
+    std::stringstream stream(code.substr(PAIRING_URI_PREFIX.size()));
+    std::string field;
+    while (std::getline(stream, field, '&')) {
+        auto equals = field.find('=');
+        std::string name = field.substr(0, equals);
+        std::string value = equals == std::string::npos ? "" : field.substr(equals + 1);
+        if (name == "pin") {
+            parsed.pin = value;
+        } else if (name == "master") {
+            parsed.masterId = value;
+        }
+    }
+    if (!isPin(parsed.pin)) {
+        return std::nullopt;
+    }
+    return parsed;
+}
+
+// Implementazione di PairingJoiner
+PairingJoiner::PairingJoiner(const std::string& nodeId, NodeRole role, const std::vector<uint8_t>& publicKey,
+                             const std::string& pin, std::shared_ptr<RandomSource> rng)
+    : nodeId(nodeId), role(role), publicKey(publicKey), pin(pin) {
+    if (!rng) {
+        rng = defaultRandomSource();
+    }
+    rng->fill(sessionId.data(), sessionId.size());
+    secretKey = randomScalar(*rng);
+    if (auto key = ephemeralKey(secretKey, pinGenerator(pin, sessionId.data(), nodeId))) {
+        keyShare = *key;
+    }
+}
+
+PairingJoiner::~PairingJoiner() {
+    sodium_memzero(secretKey.data(), secretKey.size());
+    if (sessionKey) {
+        sodium_memzero(sessionKey->data(), sessionKey->size());
+    }
+}
+
+std::vector<uint8_t> PairingJoiner::hello() const {
+    std::vector<uint8_t> message(sessionId.begin(), sessionId.end());
+    message.insert(message.end(), keyShare.begin(), keyShare.end());
+    message.push_back(static_cast<uint8_t>(role));
+    message.insert(message.end(), publicKey.begin(), publicKey.end());
+    return message;
+}
+
+std::optional<std::vector<uint8_t>> PairingJoiner::respond(const std::string& master,
+                                                           const std::vector<uint8_t>& challenge) {
+    if (challenge.size() != CHALLENGE_BYTES) {
+        return std::nullopt;
+    }
+    Key masterShare;
+    std::copy(challenge.begin(), challenge.begin() + 32, masterShare.begin());
+    std::vector<uint8_t> masterKey(challenge.begin() + 32, challenge.end());
+
+    auto key = deriveSessionKey(secretKey, masterShare, transcript(sessionId.data(), nodeId, master, keyShare,
+                                                                   masterShare, role, publicKey, masterKey));
+    if (!key) {
+        return std::nullopt;
+    }
+    masterId = master;
+    masterPublicKey = masterKey;
+    sessionKey = key;
+    Key proof = subkey(*key, "proof");
+    return std::vector<uint8_t>(proof.begin(), proof.end());
+}
+
+std::optional<PairingCredentials> PairingJoiner::complete(const std::vector<uint8_t>& welcome) const {
+    if (!sessionKey) {
+        return std::nullopt;
+    }
+    std::vector<uint8_t> plain;
+    try {
+        auto cipher = MeshCrypto::withNetworkKey(subkey(*sessionKey, "welcome"));
+        plain = cipher.decrypt(welcome);
+    } catch (const CryptoError&) {
+        // Il Master non conosce il PIN, o il messaggio è stato alterato
+        return std::nullopt;
+    }
+    if (plain.size() < 32) {
+        return std::nullopt;
+    }
+
+    PairingCredentials credentials;
+    credentials.masterId = masterId;
+    credentials.masterPublicKey = masterPublicKey;
+    std::copy(plain.begin(), plain.begin() + 32, credentials.networkKey.begin());
+    credentials.token.assign(plain.begin() + 32, plain.end());
+    sodium_memzero(plain.data(), plain.size());
+    return credentials;
+}
+
+// Implementazione di PairingResponder
+PairingResponder::PairingResponder(const std::string& masterId, const std::vector<uint8_t>& publicKey,
+                                   const std::string& pin, std::shared_ptr<RandomSource> rng)
+    : masterId(masterId), publicKey(publicKey), pin(pin), rng(rng ? rng : defaultRandomSource()) {
+}
+
+PairingResponder::~PairingResponder() {
+    for (auto& session : sessions) {
+        sodium_memzero(session.second.sessionKey.data(), session.second.sessionKey.size());
+    }
+}
+
+std::optional<std::vector<uint8_t>> PairingResponder::challenge(const std::string& nodeId,
+                                                                const std::vector<uint8_t>& hello) {
+    if (isExhausted() || nodeId.empty() || hello.size() != HELLO_BYTES) {
+        return std::nullopt;
+    }
+    const uint8_t* sessionId = hello.data();
+    Key joinerShare;
+    std::copy(hello.begin() + SESSION_ID_BYTES, hello.begin() + SESSION_ID_BYTES + 32, joinerShare.begin());
+    uint8_t role = hello[SESSION_ID_BYTES + 32];
+    if (role > static_cast<uint8_t>(NodeRole::Sink)) {
+        return std::nullopt;
+    }
+
+    Session session;
+    session.request.nodeId = nodeId;
+    session.request.role = static_cast<NodeRole>(role);
+    session.request.publicKey.assign(hello.begin() + SESSION_ID_BYTES + 33, hello.end());
+
+    Key secretKey = randomScalar(*rng);
+    auto masterShare = ephemeralKey(secretKey, pinGenerator(pin, sessionId, nodeId));
+    std::optional<Key> key;
+    if (masterShare) {
+        key = deriveSessionKey(secretKey, joinerShare, transcript(sessionId, nodeId, masterId, joinerShare,
+                                                                  *masterShare, session.request.role,
+                                                                  session.request.publicKey, publicKey));
+    }
+    sodium_memzero(secretKey.data(), secretKey.size());
+    if (!key) {
+        return std::nullopt;
+    }
+    session.sessionKey = *key;
+    session.order = nextOrder++;
+
+    // Ogni sfida è un tentativo sul PIN: conta come fallita finché la prova giusta non arriva
+    failures++;
+
+    // Un nuovo Hello dello stesso nodo ricomincia l'abbinamento; oltre il limite cade il più vecchio
+    sessions.erase(nodeId);
+    if (sessions.size() >= PAIRING_MAX_SESSIONS) {
+        auto oldest = std::min_element(sessions.begin(), sessions.end(), [](const auto& a, const auto& b) {
+            return a.second.order < b.second.order;
+        });
+        sessions.erase(oldest);
+    }
+    sessions[nodeId] = session;
+
+    std::vector<uint8_t> message(masterShare->begin(), masterShare->end());
+    message.insert(message.end(), publicKey.begin(), publicKey.end());
+    return message;
+}
+
+std::optional<PairingRequest> PairingResponder::verify(const std::string& nodeId, const std::vector<uint8_t>& proof) {
+    auto session = sessions.find(nodeId);
+    if (session == sessions.end() || session->second.verified) {
+        return std::nullopt;
+    }
+    Key expected = subkey(session->second.sessionKey, "proof");
+    if (proof.size() != expected.size() || sodium_memcmp(proof.data(), expected.data(), expected.size()) != 0) {
+        // Il tentativo è già stato contato dalla sfida
+        sodium_memzero(session->second.sessionKey.data(), session->second.sessionKey.size());
+        sessions.erase(session);
+        return std::nullopt;
+    }
+    session->second.verified = true;
+    failures--;
+    return session->second.request;
+}
+
+std::optional<std::vector<uint8_t>> PairingResponder::welcome(const std::string& nodeId,
+                                                              const std::array<uint8_t, 32>& networkKey,
+                                                              const std::vector<uint8_t>& token) {
+    auto session = sessions.find(nodeId);
+    if (session == sessions.end() || !session->second.verified) {
+        return std::nullopt;
+    }
+    std::vector<uint8_t> plain(networkKey.begin(), networkKey.end());
+    plain.insert(plain.end(), token.begin(), token.end());
+    auto cipher = MeshCrypto::withNetworkKey(subkey(session->second.sessionKey, "welcome"), rng);
+    auto message = cipher.encrypt(plain);
+    sodium_memzero(plain.data(), plain.size());
+    sodium_memzero(session->second.sessionKey.data(), session->second.sessionKey.size());
+    sessions.erase(session);
+    return message;
+}
+
+bool PairingResponder::isPending(const std::string& nodeId) const {
+    auto session = sessions.find(nodeId);
+    return session != sessions.end() && !session->second.verified;
+}
+
+uint32_t PairingResponder::getFailures() const {
+    return failures;
+}
+
+bool PairingResponder::isExhausted() const {
+    return failures >= PAIRING_MAX_FAILURES;
+}
+
+} // namespace saber
@@ -0,0 +1,59 @@
+#include "phantom.h"
+
+#include <algorithm>
+
+namespace saber {
+
+// Implementazione di PhantomSink
+PhantomSink::PhantomSink(uint32_t targetBufferMs)
+    : targetBufferMs(targetBufferMs), lastLeadMs(0), started(false), statusReportsSent(0) {
+}
+
+void PhantomSink::consume(StreamId streamId, uint64_t playoutTimeUs, size_t payloadSize, uint64_t nowUs) {
+    std::lock_guard<std::mutex> lock(phantomMutex);
+    int64_t leadMs = (static_cast<int64_t>(playoutTimeUs) - static_cast<int64_t>(nowUs)) / 1000;
+
+    auto inserted = streams.emplace(streamId, PhantomStreamStats{});
+    PhantomStreamStats& stream = inserted.first->second;
+    stream.streamId = streamId;
+    stream.minLeadMs = inserted.second ? leadMs : std::min(stream.minLeadMs, leadMs);
+    stream.lastLeadMs = leadMs;
+    stream.framesConsumed++;
+    stream.bytesConsumed += payloadSize;
+    if (leadMs < 0) {
+        stream.lateFrames++;
+    }
+
+    lastLeadMs = leadMs;
+    started = true;
+}
+
+uint8_t PhantomSink::bufferLevel() const {
+    std::lock_guard<std::mutex> lock(phantomMutex);
+    if (!started || lastLeadMs <= 0 || targetBufferMs == 0) {
+        return 0;
+    }
+    int64_t percent = lastLeadMs * 100 / targetBufferMs;
+    return static_cast<uint8_t>(std::min<int64_t>(percent, 100));
+}
+
+void PhantomSink::recordStatusSent() {
+    std::lock_guard<std::mutex> lock(phantomMutex);
+    statusReportsSent++;
+}
+
+uint64_t PhantomSink::getStatusReportsSent() const {
+    std::lock_guard<std::mutex> lock(phantomMutex);
+    return statusReportsSent;
+}
+
+std::vector<PhantomStreamStats> PhantomSink::getStats() const {
+    std::lock_guard<std::mutex> lock(phantomMutex);
+    std::vector<PhantomStreamStats> result;
+    for (const auto& stream : streams) {
+        result.push_back(stream.second);
+    }
+    return result;
+}
+
+} // namespace saber
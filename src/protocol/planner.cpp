@@ -0,0 +1,156 @@
+#include "planner.h"
+
+#include <algorithm>
+#include <iterator>
+
+namespace saber {
+
+// Implementazione di LatencyPlanner
+LatencyPlanner::LatencyPlanner(uint32_t budgetMs, uint32_t marginMs, LatencyMode mode)
+    : budgetMs(budgetMs), marginMs(marginMs), mode(mode) {
+}
+
+uint32_t LatencyPlanner::requiredBufferMs(uint32_t latencyMs) const {
+    return latencyMs + marginMs;
+}
+
+std::vector<std::string> LatencyPlanner::rejectedSinks(const std::vector<std::string>& members,
+                                                       const std::map<std::string, uint32_t>& latencies) const {
+    std::vector<std::string> rejected;
+    if (mode != LatencyMode::Strict) {
+        return rejected;
+    }
+    for (const auto& nodeId : members) {
+        auto it = latencies.find(nodeId);
+        if (it != latencies.end() && requiredBufferMs(it->second) > budgetMs) {
+            rejected.push_back(nodeId);
+        }
+    }
+    return rejected;
+}
+
+uint32_t LatencyPlanner::zoneBufferMs(const std::vector<std::string>& members,
+                                      const std::map<std::string, uint32_t>& latencies) const {
+    uint32_t buffer = 0;
+    for (const auto& nodeId : members) {
+        auto it = latencies.find(nodeId);
+        if (it == latencies.end()) {
+            continue;
+        }
+        uint32_t required = requiredBufferMs(it->second);
+        // Un sink rifiutato non può trascinare la zona oltre il budget
+        if (mode == LatencyMode::Strict && required > budgetMs) {
+            continue;
+        }
+        buffer = std::max(buffer, required);
+    }
+    return buffer;
+}
+
+std::vector<GroupSplitSuggestion> LatencyPlanner::suggestSplits(
+    const std::map<std::string, std::string>& zones,
+    const std::map<std::string, uint32_t>& latencies) const {
+    
+    // Raggruppo i nodi per zona
+    std::map<std::string, std::vector<std::string>> members;
+    for (const auto& pair : zones) {
+        members[pair.second].push_back(pair.first);
+    }
+    
+    std::vector<GroupSplitSuggestion> suggestions;
+    for (const auto& zone : members) {
+        uint32_t zoneBuffer = zoneBufferMs(zone.second, latencies);
+        if (zoneBuffer <= budgetMs || zone.second.size() < 2) {
+            continue;
+        }
+        
+        // Individuo il sink più lento della zona
+        std::string slowest;
+        uint32_t slowestLatency = 0;
+        for (const auto& nodeId : zone.second) {
+            auto it = latencies.find(nodeId);
+            if (it != latencies.end() && (slowest.empty() || it->second > slowestLatency)) {
+                slowest = nodeId;
+                slowestLatency = it->second;
+            }
+        }
+        
+        std::vector<std::string> others;
+        std::copy_if(zone.second.begin(), zone.second.end(), std::back_inserter(others),
+                     [&slowest](const std::string& nodeId) { return nodeId != slowest; });
+        
+        // Conviene separare solo se senza quel sink la zona rientra nel budget
+        uint32_t withoutBuffer = zoneBufferMs(others, latencies);
+        if (withoutBuffer > budgetMs) {
+            continue;
+        }
+        
+        suggestions.push_back({
+            zone.first,
+            slowest,
+            zone.first + "-delayed",
+            zoneBuffer,
+            withoutBuffer,
+            requiredBufferMs(slowestLatency)
+        });
+    }
+    
+    return suggestions;
+}
+
+uint32_t LatencyPlanner::getBudgetMs() const {
+    return budgetMs;
+}
+
+LatencyMode LatencyPlanner::getMode() const {
+    return mode;
+}
+
+// Implementazione di DelayEqualizer
+DelayEqualizer::DelayEqualizer(const DelayEqualizationConfig& config) : config(config) {
+}
+
+void DelayEqualizer::setConfig(const DelayEqualizationConfig& config) {
+    this->config = config;
+    if (!config.enabled) {
+        deadlineMs = 0;
+        lowerSinceMs.reset();
+    }
+}
+
+const DelayEqualizationConfig& DelayEqualizer::getConfig() const {
+    return config;
+}
+
+std::optional<uint32_t> DelayEqualizer::update(uint32_t requiredMs, int64_t nowMs) {
+    const uint32_t frameMs = spec::LC3_FRAME_DURATION_US / 1000;
+    requiredMs = (requiredMs + frameMs - 1) / frameMs * frameMs;
+    if (requiredMs >= deadlineMs) {
+        lowerSinceMs.reset();
+        if (requiredMs == deadlineMs) {
+            return std::nullopt;
+        }
+        deadlineMs = requiredMs;
+        return deadlineMs;
+    }
+    
+    // La scadenza scende alla richiesta più alta del periodo, così non deve risalire subito
+    if (!lowerSinceMs) {
+        lowerSinceMs = nowMs;
+        lowerTargetMs = requiredMs;
+        return std::nullopt;
+    }
+    lowerTargetMs = std::max(lowerTargetMs, requiredMs);
+    if (nowMs - *lowerSinceMs < static_cast<int64_t>(config.holdMs)) {
+        return std::nullopt;
+    }
+    deadlineMs = lowerTargetMs;
+    lowerSinceMs.reset();
+    return deadlineMs;
+}
+
+uint32_t DelayEqualizer::getDeadlineMs() const {
+    return deadlineMs;
+}
+
+} // namespace saber
@@ -0,0 +1,232 @@
+#include "playback_command.h"
+
+#include <algorithm>
+#include <stdexcept>
+
+namespace saber {
+
+namespace {
+
+/// Prefisso dei comandi di riproduzione sulla mesh
+const std::string PLAYBACK_PREFIX = "playback.";
+
+/**
+ * @brief Legge un intero senza segno, rifiutando segni e caratteri in coda
+ */
+std::optional<uint64_t> parseUnsigned(const std::map<std::string, std::string>& params, const std::string& key) {
+    auto it = params.find(key);
+    if (it == params.end() || it->second.empty() || it->second.front() == '-') {
+        return std::nullopt;
+    }
+    try {
+        size_t used = 0;
+        uint64_t value = std::stoull(it->second, &used);
+        if (used != it->second.size()) {
+            return std::nullopt;
+        }
+        return value;
+    } catch (const std::exception&) {
+        return std::nullopt;
+    }
+}
+
+} // namespace
+
+std::string playbackActionToString(PlaybackAction action) {
+    switch (action) {
+        case PlaybackAction::Start:
+            return "start";
+        case PlaybackAction::Stop:
+            return "stop";
+        case PlaybackAction::Pause:
+            return "pause";
+        case PlaybackAction::Resume:
+            return "resume";
+        case PlaybackAction::Seek:
+            return "seek";
+        case PlaybackAction::Volume:
+            return "volume";
+    }
+    return "start";
+}
+
+std::optional<PlaybackAction> playbackActionFromString(const std::string& name) {
+    for (auto action : {PlaybackAction::Start, PlaybackAction::Stop, PlaybackAction::Pause, PlaybackAction::Resume,
+                        PlaybackAction::Seek, PlaybackAction::Volume}) {
+        if (playbackActionToString(action) == name) {
+            return action;
+        }
+    }
+    return std::nullopt;
+}
+
+std::map<std::string, std::string> encodePlaybackCommand(const PlaybackCommand& command) {
+    std::map<std::string, std::string> params = {{"at", std::to_string(command.atMs)}};
+    if (command.id != 0) {
+        params["id"] = std::to_string(command.id);
+    }
+    switch (command.action) {
+        case PlaybackAction::Start:
+            params["playlist"] = command.playlist;
+            break;
+        case PlaybackAction::Seek:
+            params["position_ms"] = std::to_string(command.positionMs);
+            break;
+        case PlaybackAction::Volume:
+            params["volume"] = std::to_string(command.volumePercent);
+            break;
+        default:
+            break;
+    }
+    return params;
+}
+
+std::optional<PlaybackCommand> parsePlaybackCommand(const std::string& cmdType, const std::string& zone,
+                                                    const std::map<std::string, std::string>& params) {
+    if (cmdType.compare(0, PLAYBACK_PREFIX.size(), PLAYBACK_PREFIX) != 0) {
+        return std::nullopt;
+    }
+    auto action = playbackActionFromString(cmdType.substr(PLAYBACK_PREFIX.size()));
+    auto atMs = parseUnsigned(params, "at");
+    if (!action || !atMs) {
+        return std::nullopt;
+    }
+
+    PlaybackCommand command;
+    command.action = *action;
+    command.zone = zone;
+    command.atMs = *atMs;
+    // Un mittente che non numera i comandi non si aspetta conferme
+    if (params.count("id") != 0) {
+        auto id = parseUnsigned(params, "id");
+        if (!id || *id > UINT32_MAX) {
+            return std::nullopt;
+        }
+        command.id = static_cast<uint32_t>(*id);
+    }
+    if (command.action == PlaybackAction::Start) {
+        auto playlist = params.find("playlist");
+        command.playlist = playlist != params.end() ? playlist->second : "";
+    } else if (command.action == PlaybackAction::Seek) {
+        auto position = parseUnsigned(params, "position_ms");
+        if (!position) {
+            return std::nullopt;
+        }
+        command.positionMs = *position;
+    } else if (command.action == PlaybackAction::Volume) {
+        auto volume = parseUnsigned(params, "volume");
+        if (!volume || *volume > 100) {
+            return std::nullopt;
+        }
+        command.volumePercent = static_cast<uint8_t>(*volume);
+    }
+    return command;
+}
+
+std::string commandOutcomeToString(CommandOutcome outcome) {
+    switch (outcome) {
+        case CommandOutcome::Pending:
+            return "pending";
+        case CommandOutcome::Applied:
+            return "applied";
+        case CommandOutcome::Failed:
+            return "failed";
+        case CommandOutcome::TimedOut:
+            return "timed_out";
+    }
+    return "pending";
+}
+
+std::optional<CommandOutcome> commandOutcomeFromString(const std::string& name) {
+    for (auto outcome : {CommandOutcome::Pending, CommandOutcome::Applied, CommandOutcome::Failed,
+                         CommandOutcome::TimedOut}) {
+        if (commandOutcomeToString(outcome) == name) {
+            return outcome;
+        }
+    }
+    return std::nullopt;
+}
+
+bool CommandStatus::isComplete() const {
+    return std::none_of(nodes.begin(), nodes.end(), [](const auto& node) {
+        return node.second.outcome == CommandOutcome::Pending;
+    });
+}
+
+// Implementazione di CommandTracker
+CommandTracker::CommandTracker(size_t capacity) : capacity(capacity) {
+}
+
+PlaybackCommand CommandTracker::issue(PlaybackCommand command, const std::set<std::string>& nodes, uint64_t nowMs) {
+    command.id = nextId++;
+    if (nextId == 0) {
+        nextId = 1;
+    }
+
+    CommandStatus status;
+    status.command = command;
+    status.issuedAtMs = nowMs;
+    for (const auto& nodeId : nodes) {
+        status.nodes[nodeId].updatedAtMs = nowMs;
+    }
+    commands.push_back(std::move(status));
+    while (commands.size() > capacity) {
+        commands.pop_front();
+    }
+    return command;
+}
+
+bool CommandTracker::acknowledge(const std::string& nodeId, uint32_t id, CommandOutcome outcome,
+                                 const std::string& detail, uint64_t nowMs) {
+    CommandStatus* status = find(id);
+    if (!status) {
+        return false;
+    }
+    // Una conferma tardiva corregge anche un nodo già dato per non raggiunto
+    auto& node = status->nodes[nodeId];
+    node.outcome = outcome;
+    node.updatedAtMs = nowMs;
+    node.detail = outcome == CommandOutcome::Failed ? detail : "";
+    return true;
+}
+
+size_t CommandTracker::expire(uint64_t nowMs) {
+    size_t expired = 0;
+    for (auto& status : commands) {
+        if (nowMs < status.command.atMs + PLAYBACK_COMMAND_ACK_TIMEOUT_MS) {
+            continue;
+        }
+        for (auto& node : status.nodes) {
+            if (node.second.outcome == CommandOutcome::Pending) {
+                node.second.outcome = CommandOutcome::TimedOut;
+                node.second.updatedAtMs = nowMs;
+                expired++;
+            }
+        }
+    }
+    return expired;
+}
+
+std::optional<CommandStatus> CommandTracker::get(uint32_t id) const {
+    for (const auto& status : commands) {
+        if (status.command.id == id) {
+            return status;
+        }
+    }
+    return std::nullopt;
+}
+
+std::vector<CommandStatus> CommandTracker::recent() const {
+    return std::vector<CommandStatus>(commands.begin(), commands.end());
+}
+
+CommandStatus* CommandTracker::find(uint32_t id) {
+    for (auto& status : commands) {
+        if (status.command.id == id) {
+            return &status;
+        }
+    }
+    return nullptr;
+}
+
+} // namespace saber
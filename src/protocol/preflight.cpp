@@ -0,0 +1,238 @@
+#include "preflight.h"
+#include "socket_compat.h"
+
+#include <algorithm>
+#include <cerrno>
+#include <chrono>
+#include <cstring>
+#include <filesystem>
+#include <fstream>
+#include <sstream>
+
+namespace saber {
+
+namespace {
+
+// Campioni usati per stimare la risoluzione dell'orologio
+const int CLOCK_SAMPLES = 2000;
+
+// Legge la prima riga di un file di sistema (vuota se non leggibile)
+std::string readFirstLine(const std::filesystem::path& path) {
+    std::ifstream file(path);
+    std::string line;
+    std::getline(file, line);
+    return line;
+}
+
+// Verifica che un nome inizi con un prefisso
+bool startsWith(const std::string& value, const std::string& prefix) {
+    return value.compare(0, prefix.size(), prefix) == 0;
+}
+
+// Avviso per una verifica non disponibile sulla piattaforma corrente
+PreflightIssue unsupported(PreflightCheck check, const std::string& what) {
+    return {check, false, "Impossibile verificare " + what + " su questa piattaforma",
+            "Verificare manualmente prima dell'avvio"};
+}
+
+} // namespace
+
+std::string preflightCheckToString(PreflightCheck check) {
+    switch (check) {
+        case PreflightCheck::BluetoothAdapter: return "bluetooth";
+        case PreflightCheck::AudioDevice: return "audio";
+        case PreflightCheck::PortBindable: return "port";
+        case PreflightCheck::ClockResolution: return "clock";
+        default: return "unknown";
+    }
+}
+
+// Implementazione di PreflightReport
+bool PreflightReport::passed() const {
+    return std::none_of(issues.begin(), issues.end(), [](const PreflightIssue& issue) {
+        return issue.fatal;
+    });
+}
+
+std::string PreflightReport::summary() const {
+    std::ostringstream out;
+    for (const auto& issue : issues) {
+        out << (issue.fatal ? "[errore] " : "[avviso] ") << preflightCheckToString(issue.check) << ": "
+            << issue.message << " -> " << issue.remedy << "\n";
+    }
+    return out.str();
+}
+
+PreflightReport runPreflight(const PreflightRequirements& requirements) {
+    PreflightReport report;
+    
+    if (requirements.bluetooth) {
+        probeBluetoothAdapter(report.issues);
+    }
+    if (requirements.audioOutput) {
+        probeAudioDevice(report.issues);
+    }
+    for (const auto& port : requirements.ports) {
+        probePort(port, report.issues);
+    }
+    
+    report.clockResolutionUs = measureClockResolutionUs();
+    if (report.clockResolutionUs > requirements.maxClockResolutionUs) {
+        std::ostringstream message;
+        message << "Risoluzione dell'orologio monotono di " << report.clockResolutionUs 
+                << " µs, oltre il limite di " << requirements.maxClockResolutionUs << " µs";
+        report.issues.push_back({PreflightCheck::ClockResolution, true, message.str(),
+            "Abilitare una sorgente di clock ad alta risoluzione (es. tsc) o avviare il nodo "
+            "fuori da ambienti che limitano i timer"});
+    }
+    
+    return report;
+}
+
+void probeBluetoothAdapter(std::vector<PreflightIssue>& issues) {
+#ifdef __linux__
+    namespace fs = std::filesystem;
+    std::error_code ec;
+    
+    bool adapterFound = false;
+    for (const auto& entry : fs::directory_iterator("/sys/class/bluetooth", ec)) {
+        if (startsWith(entry.path().filename().string(), "hci")) {
+            adapterFound = true;
+            break;
+        }
+    }
+    if (!adapterFound) {
+        issues.push_back({PreflightCheck::BluetoothAdapter, true, "Nessun adattatore Bluetooth trovato",
+            "Collegare un adattatore e verificare che il driver (es. btusb) sia caricato"});
+        return;
+    }
+    
+    // Un adattatore spento compare come interruttore rfkill bloccato
+    for (const auto& entry : fs::directory_iterator("/sys/class/rfkill", ec)) {
+        if (readFirstLine(entry.path() / "type") != "bluetooth") {
+            continue;
+        }
+        if (readFirstLine(entry.path() / "hard") == "1") {
+            issues.push_back({PreflightCheck::BluetoothAdapter, true, 
+                "Adattatore Bluetooth spento dall'interruttore hardware",
+                "Attivare la radio dall'interruttore fisico o dal firmware del dispositivo"});
+            return;
+        }
+        if (readFirstLine(entry.path() / "soft") == "1") {
+            issues.push_back({PreflightCheck::BluetoothAdapter, true, "Adattatore Bluetooth bloccato via software",
+                "Eseguire 'rfkill unblock bluetooth' e accendere l'adattatore"});
+            return;
+        }
+    }
+#else
+    issues.push_back(unsupported(PreflightCheck::BluetoothAdapter, "l'adattatore Bluetooth"));
+#endif
+}
+
+void probeAudioDevice(std::vector<PreflightIssue>& issues) {
+#ifdef __linux__
+    namespace fs = std::filesystem;
+    std::error_code ec;
+    
+    if (!fs::exists("/dev/snd", ec)) {
+        issues.push_back({PreflightCheck::AudioDevice, true, "Nessun dispositivo audio (/dev/snd assente)",
+            "Verificare che il driver ALSA della scheda audio sia caricato"});
+        return;
+    }
+    
+    bool playbackFound = false;
+    bool accessible = false;
+    for (const auto& entry : fs::directory_iterator("/dev/snd", ec)) {
+        std::string name = entry.path().filename().string();
+        // I dispositivi di riproduzione terminano con 'p' (es. pcmC0D0p)
+        if (!startsWith(name, "pcmC") || name.back() != 'p') {
+            continue;
+        }
+        playbackFound = true;
+        if (access(entry.path().c_str(), R_OK | W_OK) == 0) {
+            accessible = true;
+            break;
+        }
+    }
+    if (!playbackFound) {
+        issues.push_back({PreflightCheck::AudioDevice, true, "Nessun dispositivo di riproduzione audio",
+            "Collegare un'uscita audio o abilitarla nella configurazione ALSA"});
+    } else if (!accessible) {
+        issues.push_back({PreflightCheck::AudioDevice, true, "Dispositivi di riproduzione non accessibili",
+            "Aggiungere l'utente del servizio al gruppo 'audio'"});
+    }
+#else
+    issues.push_back(unsupported(PreflightCheck::AudioDevice, "il dispositivo audio"));
+#endif
+}
+
+void probePort(const PortRequirement& requirement, std::vector<PreflightIssue>& issues) {
+    std::string endpoint = requirement.bindAddress + ":" + std::to_string(requirement.port);
+    
+    sockaddr_in addr;
+    std::memset(&addr, 0, sizeof(addr));
+    addr.sin_family = AF_INET;
+    addr.sin_port = htons(requirement.port);
+    if (inet_pton(AF_INET, requirement.bindAddress.c_str(), &addr.sin_addr) != 1) {
+        issues.push_back({PreflightCheck::PortBindable, true, 
+            "Indirizzo non valido per il servizio " + requirement.service + ": " + requirement.bindAddress,
+            "Indicare un indirizzo IPv4 valido per il servizio " + requirement.service});
+        return;
+    }
+    
+#ifdef _WIN32
+    WSADATA wsaData;
+    if (WSAStartup(MAKEWORD(2, 2), &wsaData) != 0) {
+        issues.push_back(unsupported(PreflightCheck::PortBindable, "la porta " + endpoint));
+        return;
+    }
+#endif
+    
+    auto sock = socket(AF_INET, SOCK_STREAM, 0);
+    if (sock < 0) {
+        issues.push_back({PreflightCheck::PortBindable, true, "Impossibile creare un socket TCP",
+            "Verificare i limiti sui descrittori di file del processo"});
+    } else {
+        int reuse = 1;
+        setsockopt(sock, SOL_SOCKET, SO_REUSEADDR, reinterpret_cast<const char*>(&reuse), sizeof(reuse));
+        if (bind(sock, reinterpret_cast<sockaddr*>(&addr), sizeof(addr)) != 0) {
+            int error = errno;
+            std::string message = "Porta " + endpoint + " del servizio " + requirement.service + " non disponibile";
+            std::string remedy = "Liberare la porta o sceglierne un'altra in " + requirement.configKey;
+            if (error == EADDRINUSE) {
+                message = "Porta " + endpoint + " del servizio " + requirement.service + " già in uso";
+                remedy = "Arrestare il processo che la occupa o cambiare " + requirement.configKey;
+            } else if (error == EACCES) {
+                message = "Permessi insufficienti per la porta " + endpoint;
+                remedy = "Usare una porta superiore a 1024 in " + requirement.configKey;
+            } else if (error == EADDRNOTAVAIL) {
+                message = "Indirizzo " + requirement.bindAddress + " non assegnato ad alcuna interfaccia";
+                remedy = "Correggere l'indirizzo di ascolto del servizio " + requirement.service;
+            }
+            issues.push_back({PreflightCheck::PortBindable, true, message, remedy});
+        }
+        SABER_CLOSE_SOCKET(sock);
+    }
+    
+#ifdef _WIN32
+    WSACleanup();
+#endif
+}
+
+double measureClockResolutionUs() {
+    using clock = std::chrono::steady_clock;
+    
+    // Minimo incremento non nullo osservato tra letture consecutive
+    auto smallest = clock::duration::max();
+    for (int i = 0; i < CLOCK_SAMPLES; ++i) {
+        auto start = clock::now();
+        auto next = clock::now();
+        while (next == start) {
+            next = clock::now();
+        }
+        smallest = std::min(smallest, next - start);
+    }
+    return std::chrono::duration<double, std::micro>(smallest).count();
+}
+
+} // namespace saber
@@ -0,0 +1,69 @@
+#include "profile.h"
+#include "spec.h"
+
+namespace saber {
+
+const std::vector<Profile>& builtinProfiles() {
+    // Tutti i profili restano entro il budget di latenza e i bitrate LC3 della specifica
+    static const std::vector<Profile> profiles = {
+        {
+            "home-music",
+            "Musica multi-stanza: qualità piena e buffer ampio contro le pareti",
+            true,                           // isMusicMode
+            "lc3",                          // codec
+            10000,                          // frameDurationUs
+            spec::BITRATE_MUSIC_KBPS,       // bitrateKbps
+            30,                             // bufferTargetMs
+            spec::LATENCY_BUDGET_MS,        // latencyBudgetMs
+            LatencyMode::BestEffort,        // latencyMode
+            true,                           // fecEnabled
+            spec::BEACON_INTERVAL_MS        // beaconIntervalMs
+        },
+        {
+            "conference-voice",
+            "Voce in sala riunioni: 16kHz, robusto alle perdite",
+            false,                          // isMusicMode
+            "lc3",                          // codec
+            10000,                          // frameDurationUs
+            spec::BITRATE_VOICE_KBPS,       // bitrateKbps
+            spec::DEFAULT_BUFFER_MS,        // bufferTargetMs
+            spec::LATENCY_BUDGET_MS,        // latencyBudgetMs
+            LatencyMode::BestEffort,        // latencyMode
+            true,                           // fecEnabled
+            spec::BEACON_INTERVAL_MS        // beaconIntervalMs
+        },
+        {
+            "stage-monitor",
+            "Monitor da palco: latenza minima, frame corti e beacon più frequenti",
+            true,                           // isMusicMode
+            "lc3",                          // codec
+            7500,                           // frameDurationUs
+            spec::BITRATE_MUSIC_KBPS,       // bitrateKbps
+            10,                             // bufferTargetMs
+            20,                             // latencyBudgetMs
+            LatencyMode::Strict,            // latencyMode
+            false,                          // fecEnabled
+            spec::BEACON_INTERVAL_MS / 2    // beaconIntervalMs
+        },
+    };
+    return profiles;
+}
+
+std::optional<Profile> findProfile(const std::string& name) {
+    for (const auto& profile : builtinProfiles()) {
+        if (profile.name == name) {
+            return profile;
+        }
+    }
+    return std::nullopt;
+}
+
+std::vector<std::string> profileNames() {
+    std::vector<std::string> names;
+    for (const auto& profile : builtinProfiles()) {
+        names.push_back(profile.name);
+    }
+    return names;
+}
+
+} // namespace saber
@@ -0,0 +1,72 @@
+#include "provisioning.h"
+
+#include <algorithm>
+
+namespace saber {
+
+// Implementazione di ProvisioningWindow
+ProvisioningWindow::ProvisioningWindow(uint32_t closedAttemptsPerMinute)
+    : closedAttemptsPerMinute(closedAttemptsPerMinute),
+      tokens(closedAttemptsPerMinute) {
+}
+
+void ProvisioningWindow::open(uint32_t durationMs, int64_t nowMs) {
+    std::lock_guard<std::mutex> lock(windowMutex);
+    openUntilMs = nowMs + durationMs;
+}
+
+void ProvisioningWindow::close() {
+    std::lock_guard<std::mutex> lock(windowMutex);
+    openUntilMs = 0;
+}
+
+bool ProvisioningWindow::isOpen(int64_t nowMs) const {
+    std::lock_guard<std::mutex> lock(windowMutex);
+    return nowMs < openUntilMs;
+}
+
+JoinDecision ProvisioningWindow::admit(int64_t nowMs) {
+    std::lock_guard<std::mutex> lock(windowMutex);
+    if (nowMs < openUntilMs) {
+        return JoinDecision::Accept;
+    }
+    
+    // Secchio di gettoni: si ricarica di closedAttemptsPerMinute al minuto
+    if (lastRefillMs != 0) {
+        double refill = static_cast<double>(nowMs - lastRefillMs) * closedAttemptsPerMinute / 60000.0;
+        tokens = std::min<double>(closedAttemptsPerMinute, tokens + refill);
+    }
+    lastRefillMs = nowMs;
+    
+    if (tokens >= 1.0) {
+        tokens -= 1.0;
+        refused++;
+        return JoinDecision::Refuse;
+    }
+    throttled++;
+    return JoinDecision::Throttle;
+}
+
+void ProvisioningWindow::recordAccepted() {
+    std::lock_guard<std::mutex> lock(windowMutex);
+    accepted++;
+}
+
+void ProvisioningWindow::setClosedRateLimit(uint32_t attemptsPerMinute) {
+    std::lock_guard<std::mutex> lock(windowMutex);
+    closedAttemptsPerMinute = attemptsPerMinute;
+    tokens = std::min<double>(tokens, attemptsPerMinute);
+}
+
+ProvisioningStatus ProvisioningWindow::status(int64_t nowMs) const {
+    std::lock_guard<std::mutex> lock(windowMutex);
+    ProvisioningStatus result;
+    result.open = nowMs < openUntilMs;
+    result.remainingMs = result.open ? openUntilMs - nowMs : 0;
+    result.accepted = accepted;
+    result.refused = refused;
+    result.throttled = throttled;
+    return result;
+}
+
+} // namespace saber
@@ -0,0 +1,80 @@
+#include "mesh.h"
+
+#include <algorithm>
+
+namespace saber {
+
+namespace {
+
+/// Intervallo tra le pulizie dei secchi tornati pieni (ms)
+const int64_t SWEEP_INTERVAL_MS = 10000;
+
+} // namespace
+
+std::map<MeshPacketType, RateLimit> defaultRateLimits() {
+    return {
+        {MeshPacketType::Ping, {5.0, 20}},
+        {MeshPacketType::Status, {5.0, 20}},
+    };
+}
+
+// Implementazione di PacketRateLimiter
+PacketRateLimiter::PacketRateLimiter(std::map<MeshPacketType, RateLimit> limits) : limits(std::move(limits)) {
+}
+
+void PacketRateLimiter::setLimits(std::map<MeshPacketType, RateLimit> limits) {
+    this->limits = std::move(limits);
+    buckets.clear();
+}
+
+const std::map<MeshPacketType, RateLimit>& PacketRateLimiter::getLimits() const {
+    return limits;
+}
+
+bool PacketRateLimiter::allow(const std::string& source, MeshPacketType type, int64_t nowMs) {
+    auto limit = limits.find(type);
+    if (limit == limits.end() || limit->second.packetsPerSecond <= 0.0) {
+        return true;
+    }
+    const RateLimit& rate = limit->second;
+
+    // Un secchio pieno non serve più: si riparte pieni al prossimo pacchetto
+    if (nowMs - lastSweepMs >= SWEEP_INTERVAL_MS) {
+        for (auto it = buckets.begin(); it != buckets.end();) {
+            auto bucketLimit = limits.find(it->first.second);
+            double refill = static_cast<double>(nowMs - it->second.lastRefillMs) / 1000.0
+                            * (bucketLimit != limits.end() ? bucketLimit->second.packetsPerSecond : 0.0);
+            if (bucketLimit == limits.end() || it->second.tokens + refill >= bucketLimit->second.burst) {
+                it = buckets.erase(it);
+            } else {
+                ++it;
+            }
+        }
+        lastSweepMs = nowMs;
+    }
+
+    auto found = buckets.find({source, type});
+    if (found == buckets.end()) {
+        found = buckets.emplace(std::make_pair(source, type), Bucket{static_cast<double>(rate.burst), nowMs}).first;
+    }
+    Bucket& bucket = found->second;
+    double refill = static_cast<double>(std::max<int64_t>(nowMs - bucket.lastRefillMs, 0)) / 1000.0
+                    * rate.packetsPerSecond;
+    bucket.tokens = std::min<double>(rate.burst, bucket.tokens + refill);
+    bucket.lastRefillMs = nowMs;
+
+    if (bucket.tokens >= 1.0) {
+        bucket.tokens -= 1.0;
+        return true;
+    }
+    stats.dropped++;
+    stats.droppedByType[type]++;
+    stats.droppedBySource[source]++;
+    return false;
+}
+
+const RateLimitStats& PacketRateLimiter::getStats() const {
+    return stats;
+}
+
+} // namespace saber
@@ -0,0 +1,143 @@
+#include "mesh.h"
+
+#include <algorithm>
+
+namespace saber {
+
+namespace {
+
+/// Intervallo tra le pulizie delle voci scadute del filtro dei duplicati (ms)
+const int64_t SWEEP_INTERVAL_MS = 1000;
+
+/**
+ * @brief Hash FNV-1a a 64 bit dei byte firmati di un pacchetto
+ */
+uint64_t contentHash(const MeshPacket& packet) {
+    uint64_t hash = 14695981039346656037ULL;
+    for (uint8_t byte : packet.signingBytes()) {
+        hash ^= byte;
+        hash *= 1099511628211ULL;
+    }
+    return hash;
+}
+
+} // namespace
+
+// Implementazione di ReliableSender
+ReliableSender::ReliableSender(const ReliableConfig& config) : config(config) {
+}
+
+void ReliableSender::setConfig(const ReliableConfig& config) {
+    this->config = config;
+    if (!config.enabled) {
+        pendingPackets.clear();
+    }
+}
+
+const ReliableConfig& ReliableSender::getConfig() const {
+    return config;
+}
+
+bool ReliableSender::track(const MeshPacket& packet, const std::set<std::string>& recipients, int64_t nowMs) {
+    if (!config.enabled) {
+        return false;
+    }
+    stats.sent++;
+    if (config.maxAttempts <= 1) {
+        // Un solo invio: nessuna ritrasmissione da attendere
+        return false;
+    }
+    uint32_t timeoutMs = std::max<uint32_t>(config.initialTimeoutMs, 1);
+    pendingPackets.insert_or_assign(packet.getSequence(),
+                                    Pending{packet, recipients, 1, timeoutMs, nowMs + timeoutMs});
+    return true;
+}
+
+bool ReliableSender::acknowledge(uint32_t sequence, const std::string& from) {
+    auto it = pendingPackets.find(sequence);
+    if (it == pendingPackets.end()) {
+        return false;
+    }
+    // Senza destinatari indicati basta la prima conferma
+    auto& missing = it->second.missing;
+    if (!missing.empty()) {
+        missing.erase(from);
+        if (!missing.empty()) {
+            return false;
+        }
+    }
+    pendingPackets.erase(it);
+    stats.acknowledged++;
+    return true;
+}
+
+std::vector<MeshPacket> ReliableSender::due(int64_t nowMs) {
+    std::vector<MeshPacket> packets;
+    for (auto it = pendingPackets.begin(); it != pendingPackets.end();) {
+        Pending& pending = it->second;
+        if (nowMs < pending.nextMs) {
+            ++it;
+            continue;
+        }
+        if (pending.attempts >= config.maxAttempts) {
+            stats.failed++;
+            failures.push_back(ReliableFailure{std::move(pending.packet), std::move(pending.missing)});
+            it = pendingPackets.erase(it);
+            continue;
+        }
+        // Attesa esponenziale: ogni tentativo raddoppia la precedente fino al massimo
+        pending.attempts++;
+        uint32_t ceiling = std::max(config.maxTimeoutMs, config.initialTimeoutMs);
+        pending.timeoutMs = static_cast<uint32_t>(std::min<uint64_t>(uint64_t(pending.timeoutMs) * 2, ceiling));
+        pending.nextMs = nowMs + pending.timeoutMs;
+        stats.retransmitted++;
+        packets.push_back(pending.packet);
+        ++it;
+    }
+    return packets;
+}
+
+std::vector<ReliableFailure> ReliableSender::takeFailures() {
+    std::vector<ReliableFailure> result;
+    result.swap(failures);
+    return result;
+}
+
+size_t ReliableSender::pending() const {
+    return pendingPackets.size();
+}
+
+const ReliableStats& ReliableSender::getStats() const {
+    return stats;
+}
+
+// Implementazione di DuplicateFilter
+DuplicateFilter::DuplicateFilter(uint32_t windowMs) : windowMs(windowMs) {
+}
+
+bool DuplicateFilter::insert(const MeshPacket& packet, int64_t nowMs) {
+    if (nowMs - lastSweepMs >= SWEEP_INTERVAL_MS) {
+        lastSweepMs = nowMs;
+        for (auto it = seen.begin(); it != seen.end();) {
+            if (nowMs - it->second > static_cast<int64_t>(windowMs)) {
+                it = seen.erase(it);
+            } else {
+                ++it;
+            }
+        }
+    }
+
+    auto key = std::make_tuple(packet.getSource(), packet.getSequence(), contentHash(packet));
+    auto it = seen.find(key);
+    if (it != seen.end() && nowMs - it->second <= static_cast<int64_t>(windowMs)) {
+        return false;
+    }
+    seen[key] = nowMs;
+    return true;
+}
+
+size_t DuplicateFilter::size() const {
+    return seen.size();
+}
+
+} // namespace saber
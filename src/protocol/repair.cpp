@@ -0,0 +1,75 @@
+#include "repair.h"
+
+namespace saber {
+
+// Implementazione di NackTracker
+NackTracker::NackTracker(uint32_t windowMs, uint32_t maxGap)
+    : windowMs(windowMs), maxGap(maxGap) {
+}
+
+std::vector<uint32_t> NackTracker::onFrame(StreamId streamId, uint32_t frameSequence, int64_t nowMs) {
+    expire(nowMs);
+
+    StreamState& state = streams[streamId];
+    std::vector<uint32_t> nacks;
+
+    if (!state.started) {
+        state.started = true;
+        state.expected = frameSequence + 1;
+        return nacks;
+    }
+
+    // Differenza con segno per gestire il riavvolgimento della sequenza
+    int32_t delta = static_cast<int32_t>(frameSequence - state.expected);
+
+    if (delta < 0) {
+        // Frame in ritardo: è una riparazione se era tra quelli mancanti
+        if (state.missing.erase(frameSequence) > 0) {
+            stats.repaired++;
+        }
+        return nacks;
+    }
+
+    if (static_cast<uint32_t>(delta) > maxGap) {
+        // Salto troppo ampio: lo stream è ripartito, non si chiede nulla
+        stats.expired += state.missing.size();
+        state.missing.clear();
+    } else {
+        for (uint32_t seq = state.expected; seq != frameSequence; ++seq) {
+            state.missing[seq] = nowMs;
+            nacks.push_back(seq);
+        }
+        stats.nacksSent += nacks.size();
+    }
+
+    state.expected = frameSequence + 1;
+    return nacks;
+}
+
+void NackTracker::expire(int64_t nowMs) {
+    for (auto& entry : streams) {
+        auto& missing = entry.second.missing;
+        for (auto it = missing.begin(); it != missing.end();) {
+            if (nowMs - it->second > static_cast<int64_t>(windowMs)) {
+                stats.expired++;
+                it = missing.erase(it);
+            } else {
+                ++it;
+            }
+        }
+    }
+}
+
+const RepairStats& NackTracker::getStats() const {
+    return stats;
+}
+
+void NackTracker::setWindowMs(uint32_t windowMs) {
+    this->windowMs = windowMs;
+}
+
+bool arrivesBeforePlayout(uint64_t nowUs, uint64_t playoutTimeUs, uint64_t latencyUs) {
+    return nowUs + latencyUs <= playoutTimeUs;
+}
+
+} // namespace saber
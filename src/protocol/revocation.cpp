@@ -0,0 +1,130 @@
+#include "revocation.h"
+
+#include <sstream>
+
+namespace saber {
+
+std::string revocationKindToString(RevocationKind kind) {
+    switch (kind) {
+        case RevocationKind::Token:
+            return "token";
+        case RevocationKind::Node:
+            return "node";
+    }
+    return "token";
+}
+
+std::optional<RevocationKind> revocationKindFromString(const std::string& name) {
+    if (name == "token") {
+        return RevocationKind::Token;
+    }
+    if (name == "node") {
+        return RevocationKind::Node;
+    }
+    return std::nullopt;
+}
+
+std::string encodeRevocations(const std::vector<Revocation>& entries) {
+    std::string text;
+    for (const auto& entry : entries) {
+        if (!text.empty()) {
+            text += ",";
+        }
+        text += revocationKindToString(entry.kind) + ":" + entry.subject + ":" + std::to_string(entry.revokedAtMs);
+    }
+    return text;
+}
+
+std::optional<std::vector<Revocation>> parseRevocations(const std::string& text) {
+    std::vector<Revocation> entries;
+    std::stringstream stream(text);
+    std::string item;
+    while (std::getline(stream, item, ',')) {
+        // Tipo in testa e istante in coda: l'ID del nodo può contenere ':'
+        auto kindColon = item.find(':');
+        auto timeColon = item.rfind(':');
+        if (kindColon == std::string::npos || timeColon <= kindColon + 1) {
+            return std::nullopt;
+        }
+        auto kind = revocationKindFromString(item.substr(0, kindColon));
+        if (!kind) {
+            return std::nullopt;
+        }
+        Revocation entry;
+        entry.kind = *kind;
+        entry.subject = item.substr(kindColon + 1, timeColon - kindColon - 1);
+        try {
+            size_t used = 0;
+            std::string revokedAt = item.substr(timeColon + 1);
+            entry.revokedAtMs = std::stoull(revokedAt, &used);
+            if (used != revokedAt.size() || revokedAt.front() == '-') {
+                return std::nullopt;
+            }
+        } catch (const std::exception&) {
+            return std::nullopt;
+        }
+        entries.push_back(entry);
+    }
+    return entries;
+}
+
+// Implementazione di RevocationList
+bool RevocationList::add(const Revocation& entry) {
+    std::lock_guard<std::mutex> lock(revocationMutex);
+    if (entry.subject.empty()) {
+        return false;
+    }
+    if (entry.kind == RevocationKind::Token) {
+        // Un token revocato resta revocato: conta solo la prima revoca
+        return tokens.emplace(entry.subject, entry.revokedAtMs).second;
+    }
+    auto node = nodes.find(entry.subject);
+    if (node != nodes.end() && node->second >= entry.revokedAtMs) {
+        return false;
+    }
+    nodes[entry.subject] = entry.revokedAtMs;
+    return true;
+}
+
+std::vector<Revocation> RevocationList::merge(const std::vector<Revocation>& entries) {
+    std::vector<Revocation> applied;
+    for (const auto& entry : entries) {
+        if (add(entry)) {
+            applied.push_back(entry);
+        }
+    }
+    return applied;
+}
+
+bool RevocationList::isRevoked(const std::string& tokenId, const std::string& nodeId, uint64_t issuedAtMs) const {
+    std::lock_guard<std::mutex> lock(revocationMutex);
+    if (tokens.count(tokenId) > 0) {
+        return true;
+    }
+    auto node = nodes.find(nodeId);
+    return node != nodes.end() && issuedAtMs <= node->second;
+}
+
+bool RevocationList::isTokenRevoked(const std::string& tokenId) const {
+    std::lock_guard<std::mutex> lock(revocationMutex);
+    return tokens.count(tokenId) > 0;
+}
+
+std::vector<Revocation> RevocationList::entries() const {
+    std::lock_guard<std::mutex> lock(revocationMutex);
+    std::vector<Revocation> result;
+    for (const auto& token : tokens) {
+        result.push_back(Revocation{RevocationKind::Token, token.first, token.second});
+    }
+    for (const auto& node : nodes) {
+        result.push_back(Revocation{RevocationKind::Node, node.first, node.second});
+    }
+    return result;
+}
+
+size_t RevocationList::size() const {
+    std::lock_guard<std::mutex> lock(revocationMutex);
+    return tokens.size() + nodes.size();
+}
+
+} // namespace saber
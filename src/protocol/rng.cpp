@@ -0,0 +1,47 @@
+#include "rng.h"
+
+#include <stdexcept>
+
+#include <openssl/rand.h>
+
+namespace saber {
+
+// Implementazione di OsRandomSource
+void OsRandomSource::fill(uint8_t* data, size_t size) {
+    if (size > 0 && RAND_bytes(data, static_cast<int>(size)) != 1) {
+        throw std::runtime_error("Generatore casuale del sistema non disponibile");
+    }
+}
+
+std::shared_ptr<RandomSource> defaultRandomSource() {
+    static std::shared_ptr<RandomSource> source = std::make_shared<OsRandomSource>();
+    return source;
+}
+
+#ifdef SABER_WITH_SEEDED_RNG
+// Implementazione di SeededRandomSource
+SeededRandomSource::SeededRandomSource(uint64_t seed)
+    : seed(seed), engine(seed) {
+}
+
+void SeededRandomSource::fill(uint8_t* data, size_t size) {
+    std::lock_guard<std::mutex> lock(engineMutex);
+    for (size_t i = 0; i < size; i += 8) {
+        uint64_t value = engine();
+        for (size_t j = 0; j < 8 && i + j < size; ++j) {
+            data[i + j] = static_cast<uint8_t>(value >> (8 * j));
+        }
+    }
+}
+
+void SeededRandomSource::reset() {
+    std::lock_guard<std::mutex> lock(engineMutex);
+    engine.seed(seed);
+}
+
+uint64_t SeededRandomSource::getSeed() const {
+    return seed;
+}
+#endif
+
+} // namespace saber
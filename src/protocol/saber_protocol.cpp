@@ -1,12 +1,155 @@
+#include "log.h"
 #include "saber_protocol.h"
+#include "util.h"
 
+#include <algorithm>
 #include <chrono>
+#include <cstdio>
+#include <filesystem>
+#include <fstream>
 #include <iostream>
 #include <random>
+#include <sstream>
 #include <thread>
 
 namespace saber {
 
+namespace {
+
+// Orologio di sistema in millisecondi, lo stesso dell'emissione dei token
+uint64_t systemMillis() {
+    return std::chrono::duration_cast<std::chrono::milliseconds>(
+        std::chrono::system_clock::now().time_since_epoch()).count();
+}
+
+// Intervallo oltre il quale il thread di runtime è considerato bloccato
+const int64_t RUNTIME_STALL_MS = 1000;
+
+// Numero massimo di copertine mantenute in memoria
+const size_t MAX_ARTWORK_ENTRIES = 16;
+
+// Intervallo tra due pacchetti Status di un sink fantasma
+const int64_t PHANTOM_STATUS_INTERVAL_MS = 1000;
+
+// Intervallo tra i pacchetti Status del ponte A2DP
+const int64_t BRIDGE_STATUS_INTERVAL_MS = 1000;
+
+// Frame persi oltre cui non si tenta la ricostruzione (salto di sequenza o nuovo avvio)
+const uint32_t MAX_CONCEALED_FRAMES = 5;
+
+// Intervallo senza frame dopo cui la sessione di uno stream è conclusa
+const uint32_t SESSION_IDLE_MS = 2000;
+
+// Numero massimo di resoconti di sessione conservati
+const size_t MAX_SESSION_REPORTS = 256;
+
+// Anticipo del clic di una misura acustica, perché l'innesco raggiunga il peer
+const uint64_t SYNC_CLICK_LEAD_US = 500000;
+
+// Durata concessa ad ogni scambio di una misura di sincronizzazione (un innesco per ciclo)
+const int64_t SYNC_PROBE_STEP_MS = 200;
+
+// Silenzio dopo un riallineamento d'emergenza, mentre il buffer di jitter si riempie di frame allineati
+const uint64_t EMERGENCY_SYNC_MUTE_US = 200000;
+
+// Intervallo minimo tra due riallineamenti d'emergenza dello stesso sink
+const int64_t EMERGENCY_SYNC_COOLDOWN_MS = 5000;
+
+// Elenco separato da virgole (es. zone della modalità festa)
+std::vector<std::string> splitList(const std::string& text) {
+    std::vector<std::string> items;
+    std::istringstream input(text);
+    std::string item;
+    while (std::getline(input, item, ',')) {
+        if (!item.empty()) {
+            items.push_back(item);
+        }
+    }
+    return items;
+}
+
+std::string joinList(const std::vector<std::string>& items) {
+    std::string text;
+    for (const auto& item : items) {
+        text += (text.empty() ? "" : ",") + item;
+    }
+    return text;
+}
+
+// Valori grezzi di un file di configurazione, per riconoscere le chiavi cambiate
+std::map<std::string, std::string> rawConfigValues(const ConfigFile& file) {
+    std::map<std::string, std::string> values;
+    for (const auto& key : file.keys()) {
+        values[key] = file.getString(key).value_or("");
+    }
+    return values;
+}
+
+// Riepilogo di una sessione su una riga (giornale e comando "sessions")
+std::string sessionSummary(const SessionReport& report) {
+    return "stream=" + std::to_string(report.streamId)
+         + " played=" + std::to_string(report.framesPlayed)
+         + " concealed=" + std::to_string(report.framesConcealed)
+         + " dropped=" + std::to_string(report.framesDropped)
+         + " offset=" + std::to_string(report.averageOffsetMs) + "ms"
+         + " rebuffers=" + std::to_string(report.rebufferCount)
+         + " max_latency=" + std::to_string(report.maxLatencyMs) + "ms";
+}
+
+// Comandi che riconfigurano il nodo: li può inviare solo il Master
+bool isMasterCommand(const std::string& cmdType) {
+    static const std::set<std::string> commands = {
+        "log.set", "bass.configure", "zone.delay", "playout.deadline", "simulcast.announce",
+        "degrade.apply", "audio.profile", "party.start", "party.stop",
+    };
+    return commands.count(cmdType) != 0 || cmdType.compare(0, 9, "playback.") == 0;
+}
+
+// Hash esadecimale che identifica una copertina
+std::string artworkHashHex(MeshCrypto& crypto, const std::vector<uint8_t>& image) {
+    return toHex(crypto.hash(image));
+}
+
+// Nome stabile di un evento di sicurezza nel diario
+std::string securityEventTypeToString(SecurityEvent::Type type) {
+    switch (type) {
+        case SecurityEvent::Type::KeyConflict:
+            return "key_conflict";
+        case SecurityEvent::Type::KeyConflictResolved:
+            return "key_conflict_resolved";
+        case SecurityEvent::Type::ReplayRejected:
+            return "replay_rejected";
+        case SecurityEvent::Type::IntrusionDetected:
+            return "intrusion_detected";
+        case SecurityEvent::Type::NodeQuarantined:
+            return "node_quarantined";
+        case SecurityEvent::Type::QuarantineReleased:
+            return "quarantine_released";
+    }
+    return "unknown";
+}
+
+// Chiude uno span all'uscita dallo scope, anche sui ritorni anticipati
+class SpanGuard {
+public:
+    SpanGuard(Tracer& tracer, TraceSpan& span) : tracer(tracer), span(span) {}
+    ~SpanGuard() { tracer.endSpan(span); }
+
+private:
+    Tracer& tracer;
+    TraceSpan& span;
+};
+
+} // namespace
+
+bool minimalSinkBuild() {
+#ifdef SABER_MINIMAL_SINK
+    return true;
+#else
+    return false;
+#endif
+}
+
 // Implementazione di SaberConfig
 SaberConfig SaberConfig::defaultConfig() {
     // Genera un UUID semplificato per l'ID del nodo
@@ -23,17 +166,178 @@ SaberConfig SaberConfig::defaultConfig() {
     };
 }
 
+SaberConfig SaberConfig::forProfile(const std::string& profileName, NodeRole role) {
+    auto profile = findProfile(profileName);
+    if (!profile) {
+        throw std::invalid_argument("Profilo sconosciuto: " + profileName);
+    }
+    
+    SaberConfig config = defaultConfig();
+    config.role = role;
+    config.applyProfile(*profile);
+    return config;
+}
+
+void SaberConfig::applyProfile(const Profile& selected) {
+    profile = selected.name;
+    isMusicMode = selected.isMusicMode;
+    codec = selected.codec;
+    frameDurationUs = selected.frameDurationUs;
+    bitrateKbps = selected.bitrateKbps;
+    fecEnabled = selected.fecEnabled;
+    beaconIntervalMs = selected.beaconIntervalMs;
+    
+    spec.latencyBudgetMs = selected.latencyBudgetMs;
+    spec.latencyMode = selected.latencyMode;
+    if (spec.bufferMarginMs >= spec.latencyBudgetMs) {
+        spec.bufferMarginMs = spec.latencyBudgetMs / 2;
+    }
+    
+    // Un'ancora del timestamp circa ogni mezzo secondo
+    timestampAnchorFrames = std::max<uint32_t>(1, 500000 / selected.frameDurationUs);
+    
+    // Frame ancora riproducibili entro il budget di latenza
+    uint32_t framesInFlight = (selected.latencyBudgetMs * 1000 + selected.frameDurationUs - 1) 
+                            / selected.frameDurationUs;
+    
+    switch (role) {
+        case NodeRole::Sink:
+            // La riparazione deve arrivare prima che il buffer si svuoti
+            spec.defaultBufferMs = std::min(selected.bufferTargetMs, spec.latencyBudgetMs);
+            repair.windowMs = std::min(repair.windowMs, spec.defaultBufferMs / 2);
+            break;
+        case NodeRole::Master:
+        case NodeRole::Repeater:
+            repair.cacheFrames = std::max<uint32_t>(8, framesInFlight * 2);
+            break;
+    }
+}
+
+PreflightRequirements SaberConfig::preflightRequirements() const {
+    PreflightRequirements requirements;
+    requirements.bluetooth = requireBluetooth || btAddress.has_value() || a2dpDevice.has_value();
+    // Un sink con uscita RTP non usa la scheda audio locale
+    requirements.audioOutput = requireAudioDevice && audioOutput != "rtp" && !phantomSink;
+    requirements.maxClockResolutionUs = maxClockResolutionUs;
+    
+    if (controlPort) {
+        requirements.ports.push_back({"control", controlBindAddress, *controlPort, "control.port"});
+    }
+#ifdef SABER_WITH_HTTP
+    if (healthPort) {
+        requirements.ports.push_back({"health", healthBindAddress, *healthPort, "health.port"});
+    }
+#endif
+    return requirements;
+}
+
+// Implementazione di LifecycleError
+LifecycleError::LifecycleError(Type type, const std::string& message)
+    : std::runtime_error(message), type(type) {}
+
+LifecycleError::Type LifecycleError::getType() const {
+    return type;
+}
+
 // Implementazione di SaberProtocol
 SaberProtocol::SaberProtocol(const SaberConfig& config)
     : config(config),
-      syncManager(std::make_shared<SyncManager>()),
-      running(false) {
+      authorizationGate(config.authorizationTimeoutMs, config.authorizationAllowOnTimeout),
+      syncManager(std::make_shared<SyncManager>(config.spec)),
+      planner(config.spec.latencyBudgetMs, config.spec.bufferMarginMs, config.spec.latencyMode),
+      journal(std::make_unique<EventJournal>(config.eventJournalFile.value_or(""), 
+                                             config.eventJournalMaxEntries)),
+      profiler(std::make_shared<PipelineProfiler>(config.profileWindowSamples, "saber;" + config.nodeId)),
+      intrusionDetector(std::make_shared<IntrusionDetector>(config.intrusion)),
+      tracer(std::make_shared<Tracer>(config.randomSource, [sync = syncManager] { return sync->nowUs(); })),
+      degradationLadder(config.degradation),
+      running(false),
+      state(ProtocolState::Stopped),
+      lastRuntimeTick(0) {
+    tracer->setEnabled(config.tracingEnabled);
+    standbyController.setConfig(config.standby);
+    sourceSelector.setConfig(config.sources);
+    flowController.setConfig(config.flow);
+    
+    // Stato del file di configurazione da cui confrontare le modifiche successive
+    if (config.configFile) {
+        std::error_code error;
+        auto modified = std::filesystem::last_write_time(*config.configFile, error);
+        if (!error) {
+            configFileTime = modified;
+        }
+        try {
+            loadedConfigValues = rawConfigValues(ConfigFile::load(*config.configFile));
+        } catch (const ConfigError& e) {
+            SABER_LOG(Error, "protocol", "File di configurazione non più leggibile: " << e.what());
+        }
+    }
 }
 
 SaberProtocol::~SaberProtocol() {
-    // Ferma il thread di runtime se è in esecuzione
+    ProtocolState previous = state.exchange(ProtocolState::ShuttingDown);
+    stopServices();
+    // Senza shutdown() i thread che chiamano il protocollo vanno fermati
+    // prima che i membri vengano distrutti
+    if (previous == ProtocolState::Running) {
+        if (udpTransport) {
+            udpTransport->stop();
+        }
+        if (simNetwork) {
+            simNetwork->detach(config.nodeId);
+        }
+        if (meshNetwork) {
+            meshNetwork->stop();
+        }
+        if (otlpExporter) {
+            otlpExporter->stop();
+        }
+    }
+    // Le decisioni in attesa raggiungono la rete mesh finché esiste ancora
+    if (meshNetwork) {
+        meshNetwork->setJoinAuthorizer(nullptr);
+    }
+    authorizationGate.cancelPending("protocollo arrestato");
+    // Il gestore di sincronizzazione è condiviso e può sopravvivere al protocollo
+    syncManager->setSyncStateHandler(nullptr);
+}
+
+void SaberProtocol::stopServices() {
+    // Le sorgenti audio smettono di trasmettere prima della rete; i loro
+    // thread possono attendere protocolMutex, quindi si fermano fuori dal lock
+    std::map<StreamId, std::unique_ptr<AudioSourcePump>> sources;
+    {
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        sources.swap(audioSources);
+    }
+    sources.clear();
+    
+    std::map<StreamId, std::shared_ptr<AudioRenderer>> outputs;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        outputs.swap(audioOutputs);
+    }
+    for (auto& output : outputs) {
+        output.second->stop();
+    }
+    
+    if (controlServer) {
+        controlServer->stop();
+    }
+    
+#ifdef SABER_WITH_HTTP
+    if (healthServer) {
+        healthServer->stop();
+    }
+#endif
+    
+    // Ferma il thread di runtime se è in esecuzione, interrompendone l'attesa
     if (running) {
-        running = false;
+        {
+            std::lock_guard<std::mutex> lock(runtimeMutex);
+            running = false;
+        }
+        runtimeWakeup.notify_all();
         if (runtimeThread && runtimeThread->joinable()) {
             runtimeThread->join();
         }
@@ -41,52 +345,698 @@ SaberProtocol::~SaberProtocol() {
 }
 
 bool SaberProtocol::initialize() {
-    std::cout << "Inizializzazione SABER Protocol con ID " << config.nodeId << std::endl;
+    if (state == ProtocolState::Running) {
+        SABER_LOG(Error, "protocol", "Protocollo SABER già inizializzato");
+        return false;
+    }
+    
+#ifdef SABER_MINIMAL_SINK
+    if (config.role != NodeRole::Sink) {
+        SABER_LOG(Error, "protocol", "La build minimal-sink supporta solo il ruolo " 
+                  << nodeRoleToString(NodeRole::Sink));
+        return false;
+    }
+#endif
+    
+    SABER_LOG(Info, "protocol", "Inizializzazione SABER Protocol con ID " << config.nodeId);
+    state = ProtocolState::Initializing;
+    
+    // Verifica dei prerequisiti prima di allocare qualsiasi risorsa
+    PreflightReport report = runPreflight(config.preflightRequirements());
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        preflightReport = report;
+    }
+    if (!report.issues.empty()) {
+        SABER_LOG(Warn, "protocol", "Verifica dell'ambiente:\n" << report.summary());
+    }
+    if (!report.passed()) {
+        SABER_LOG(Error, "protocol", "Inizializzazione annullata: prerequisiti non soddisfatti");
+        state = ProtocolState::Stopped;
+        return false;
+    }
     
     // Creazione del nodo locale per la rete mesh
     Node localNode(config.nodeId, config.role);
     
+    // Filtro iniziale dei log
+    try {
+        Logger::instance().setFilter(LogFilter::parse(config.logFilter));
+    } catch (const std::invalid_argument& e) {
+        SABER_LOG(Error, "protocol", "Filtro dei log non valido, uso il predefinito: " << e.what());
+    }
+    
     // Creazione della rete mesh
     meshNetwork = std::make_unique<MeshNetwork>(localNode);
+    if (config.discoveryEnabled) {
+        try {
+            discovery = std::make_unique<NodeDiscovery>(config.nodeId, config.role, config.discoveryStaleMs);
+        } catch (const std::invalid_argument& e) {
+            SABER_LOG(Error, "protocol", "Scoperta BLE dei nodi disattivata: " << e.what());
+        }
+    }
+    if (config.otlpEndpoint) {
+        try {
+            otlpExporter = std::make_unique<OtlpExporter>(*config.otlpEndpoint, config.nodeId, 
+                                                          config.otlpExportIntervalMs);
+            otlpExporter->start(tracer);
+        } catch (const std::invalid_argument& e) {
+            SABER_LOG(Error, "protocol", "Esportazione OTLP disattivata: " << e.what());
+        }
+    }
+    if (config.phantomSink) {
+        if (config.role == NodeRole::Sink) {
+            phantom = std::make_unique<PhantomSink>(config.spec.defaultBufferMs);
+            SABER_LOG(Info, "protocol", "Sink fantasma: i flussi vengono consumati senza riprodurre audio");
+        } else {
+            SABER_LOG(Error, "protocol", "Modalità sink fantasma ignorata: il nodo non è un sink");
+        }
+    }
+    if (config.a2dpDevice) {
+        if (config.role == NodeRole::Repeater) {
+            a2dpBridge = std::make_unique<A2dpBridge>(*config.a2dpDevice, config.a2dpLatencyMs, 
+                                                      config.spec.defaultBufferMs);
+            SABER_LOG(Info, "protocol", "Ponte A2DP verso " << *config.a2dpDevice << " (latenza " 
+                      << config.a2dpLatencyMs << " ms)");
+        } else {
+            SABER_LOG(Error, "protocol", "Ponte A2DP ignorato: il nodo non è un Repeater");
+        }
+    }
+    if (config.role == NodeRole::Sink) {
+        sessionTracker = std::make_unique<SessionTracker>(config.nodeId, config.spec.defaultBufferMs);
+    }
+    // Il sincronizzatore audio serve già al thread di rete per decodificare i frame
+    audioSync = std::make_unique<AudioSync>(syncManager, config.isMusicMode);
+    audioSync->setJitterBufferConfig(config.jitterBuffer);
+    for (StreamId streamId : config.voiceStreams) {
+        audioSync->setStreamFormat(streamId, false, 1);
+    }
+    syncManager->setSyncStateHandler([this](bool synchronized) {
+        journal->append("sync", synchronized ? "regained" : "lost", config.nodeId, "", syncManager->now());
+        if (!synchronized) {
+            eventBus.publish({ProtocolEvent::Type::SyncLost, config.nodeId, 0, 0, syncManager->now()});
+        }
+        {
+            std::lock_guard<std::mutex> lock(protocolMutex);
+            if (audioSync) {
+                audioSync->handleSyncState(synchronized);
+            }
+        }
+        std::function<void(bool)> handler;
+        {
+            std::lock_guard<std::mutex> lock(eventsMutex);
+            handler = syncStateHandler;
+        }
+        if (handler) {
+            handler(synchronized);
+        }
+    });
+    
+    meshNetwork->setPacketHandler([this](const MeshPacket& packet) {
+        if (packet.getType() == MeshPacketType::Pairing) {
+            // Le risposte richiedono la rete: le invia il thread di runtime
+            std::lock_guard<std::mutex> lock(eventsMutex);
+            if (pendingPairing.size() < PAIRING_MAX_SESSIONS * 4) {
+                pendingPairing.push_back(packet);
+            }
+            return;
+        }
+        if (packet.getType() == MeshPacketType::EmergencySync) {
+            handleEmergencySync(packet);
+            return;
+        }
+        if (packet.getType() == MeshPacketType::TimeBeacon) {
+            // La rete lascia passare solo i beacon del Master, anche quelli inoltrati dai Repeater
+            if (config.role != NodeRole::Master) {
+                syncManager->handleTimeBeacon(packet.getTimeBeaconData(), packet.getRelayDelayUs());
+            }
+            return;
+        }
+        if (packet.getType() == MeshPacketType::Status) {
+            auto [nodeId, buffer, latency] = packet.getStatusData();
+            checkLatencyAlert(nodeId, latency);
+            if (config.role == NodeRole::Master && nodeId != config.nodeId) {
+                flowController.reportBuffer(nodeId, buffer, static_cast<uint64_t>(steadyMillis()));
+            }
+        }
+        if (packet.getType() == MeshPacketType::Audio && packet.getSource() != config.nodeId) {
+            const auto& frame = packet.getAudioData();
+            if (isIntercomStream(frame.streamId)) {
+                // La voce dell'intercom non passa dalla pipeline della musica
+                decodeIntercomFrame(frame);
+            } else {
+                // Di uno stream simulcast passa solo il livello scelto, con l'identificatore del gruppo
+                StreamId streamId = frame.streamId;
+                auto verdict = acceptSimulcastFrame(frame, streamId);
+                if (verdict == SimulcastVerdict::Prime && !phantom && (config.role == NodeRole::Sink || a2dpBridge)) {
+                    primeAudioDecoder(frame);
+                } else if (verdict == SimulcastVerdict::Play) {
+                    if (sessionTracker) {
+                        sessionTracker->recordFrame(streamId, frame.frameSequence, frame.playoutTimeUs, 
+                                                    syncManager->now() * 1000);
+                    }
+                    if (phantom) {
+                        phantom->consume(streamId, frame.playoutTimeUs, frame.payload.size(), 
+                                         syncManager->now() * 1000);
+                    } else if (config.role == NodeRole::Sink || a2dpBridge) {
+                        if (auto resized = audioSync->recordFrame(streamId, frame.frameSequence, 
+                                                                  frame.playoutTimeUs, syncManager->nowUs())) {
+                            SABER_LOG(Debug, "audio", "Buffer di jitter portato a " << *resized << "ms");
+                        }
+                        decodeAudioFrame(frame, streamId);
+                    }
+                }
+            }
+        }
+        if ((packet.getType() == MeshPacketType::Audio || packet.getType() == MeshPacketType::Metadata)
+            && packet.getSource() != config.nodeId) {
+            BridgeHandler bridge;
+            {
+                std::lock_guard<std::mutex> lock(eventsMutex);
+                bridge = bridgeHandler;
+            }
+            if (bridge) {
+                bridge(packet);
+            }
+        }
+        handleAdminCommand(packet);
+    });
     
     try {
+        // Identità crittografica del nodo
+        crypto = std::make_shared<MeshCrypto>(config.randomSource);
+        if (!config.identityKey.empty()) {
+            auto seed = fromHex(config.identityKey.reveal());
+            if (!seed || seed->size() != 32) {
+                throw std::invalid_argument("security.identity_key deve essere una seed di 32 byte in esadecimale");
+            }
+            std::array<uint8_t, 32> identitySeed;
+            std::copy(seed->begin(), seed->end(), identitySeed.begin());
+            crypto->setIdentitySeed(identitySeed);
+        }
+        if (!config.networkKey.empty()) {
+            auto key = fromHex(config.networkKey.reveal());
+            if (!key || key->size() != 32) {
+                throw std::invalid_argument("security.network_key deve essere una chiave di 32 byte in esadecimale");
+            }
+            std::array<uint8_t, 32> networkKey;
+            std::copy(key->begin(), key->end(), networkKey.begin());
+            crypto->setNetworkKey(networkKey);
+        }
+        if (importedState && !importedState->publicKey.empty() 
+            && importedState->publicKey != crypto->getPublicKey()) {
+            SABER_LOG(Warn, "protocol", "Identità diversa da quella esportata (key-ID " 
+                      << MeshCrypto::keyId(importedState->publicKey) << "), i nodi noti la rifiuteranno");
+        }
+        crypto->setSecurityEventHandler([this](const SecurityEvent& event) {
+            journal->append("security", securityEventTypeToString(event.type), event.nodeId, 
+                            event.detail, event.timestamp);
+            if (event.type == SecurityEvent::Type::ReplayRejected) {
+                intrusionDetector->observe(IntrusionAlert::Type::NonceReuse, event.nodeId, steadyMillis());
+            }
+            std::lock_guard<std::mutex> lock(eventsMutex);
+            securityEvents.push_back(event);
+        });
+        crypto->setReplayWindow(config.replayWindow);
+        if (config.nonceFile) {
+            crypto->setNonceStore(std::make_shared<NonceCounterStore>(*config.nonceFile));
+        }
+        crypto->setRevocationList(revocationList);
+        // Chiamato anche dal thread di rete con il suo mutex occupato: niente chiamate alla mesh
+        intrusionDetector->setAlertHandler([this](const IntrusionAlert& alert) {
+            std::string detail = intrusionAlertTypeToString(alert.type) + " (" + std::to_string(alert.count) 
+                               + " in " + std::to_string(config.intrusion.windowMs) + " ms)";
+            SecurityEvent event{SecurityEvent::Type::IntrusionDetected, alert.nodeId, {}, detail, syncManager->now()};
+            journal->append("security", securityEventTypeToString(event.type), event.nodeId, 
+                            event.detail, event.timestamp);
+            {
+                std::lock_guard<std::mutex> lock(eventsMutex);
+                securityEvents.push_back(event);
+            }
+            if (alert.quarantine && alert.nodeId != config.nodeId) {
+                crypto->quarantineNode(alert.nodeId, detail);
+            }
+        });
+        meshNetwork->setIntrusionDetector(intrusionDetector);
+        meshNetwork->setTracer(tracer);
+        meshNetwork->setCrypto(crypto);
+        meshNetwork->setSendRejects(config.sendRejects);
+        meshNetwork->setPacketEncryption(config.encryptPackets);
+        meshNetwork->setSessionKeys(config.sessionKeys);
+        meshNetwork->setPathRecording(config.recordPaths);
+        meshNetwork->setRepairConfig(config.repair);
+        meshNetwork->setReliableConfig(config.reliable);
+        meshNetwork->setJoinRateLimit(config.joinAttemptsPerMinute);
+        meshNetwork->setCapacityLimits(config.maxNodes, config.maxSinks);
+        meshNetwork->setLivenessConfig(config.heartbeatTimeoutMs, config.nodeEvictAfterMs);
+        meshNetwork->setRateLimits(config.rateLimits);
+        meshNetwork->setJoinAuthorizer([this](const std::string& nodeId, NodeRole role,
+                                              std::function<void(bool, const std::string&)> decide) {
+            AuthorizationRequest request;
+            request.action = AuthorizationAction::Join;
+            request.nodeId = nodeId;
+            request.role = role;
+            return authorizationGate.submit(request, [decide](const AuthorizationDecision& decision) {
+                decide(decision.allowed, decision.reason);
+            });
+        });
+        meshNetwork->setProfiler(profiler);
+        meshNetwork->setFailoverHandler([this](const FailoverEvent& event) {
+            SABER_LOG(Warn, "transport", "Collegamento verso " << event.peer << " passato da " 
+                      << transportKindToString(event.from) << " a " << transportKindToString(event.to)
+                      << " (" << event.reason << ")");
+            journal->append("mesh", "failover", event.peer, transportKindToString(event.from) + " -> " 
+                            + transportKindToString(event.to) + " (" + event.reason + ")", event.timestamp);
+            intrusionDetector->observe(IntrusionAlert::Type::RouteFlapping, event.peer, steadyMillis());
+            std::lock_guard<std::mutex> lock(eventsMutex);
+            failoverEvents.push_back(event);
+        });
+        meshNetwork->setEventHandler([this](const MeshEvent& event) {
+            journal->append("mesh", meshEventTypeToString(event.type), event.nodeId, event.detail, 
+                            event.timestamp);
+            // Il bus accoda soltanto: il mutex della rete è occupato
+            if (event.type == MeshEvent::Type::NodeJoined || event.type == MeshEvent::Type::NodeReturned) {
+                eventBus.publish({ProtocolEvent::Type::NodeJoined, event.nodeId, 0, 0, event.timestamp});
+            } else if (event.type == MeshEvent::Type::NodeLost) {
+                eventBus.publish({ProtocolEvent::Type::NodeLeft, event.nodeId, 0, 0, event.timestamp});
+            }
+        });
+        
+        // Rete simulata dei test di integrazione, poi UDP multicast al posto del BLE gestito dall'applicazione
+        if (simNetwork) {
+            simNetwork->attach(config.nodeId, [this](const std::vector<uint8_t>& datagram) {
+                meshNetwork->receiveFromLink(datagram);
+            });
+            meshNetwork->setLinkSender([network = simNetwork.get(), nodeId = config.nodeId](
+                    const std::vector<uint8_t>& datagram) {
+                network->send(nodeId, datagram);
+            });
+            SABER_LOG(Info, "protocol", "Rete mesh simulata");
+        } else if (config.transport == TransportKind::Udp) {
+            udpTransport = std::make_unique<UdpTransport>(config.udp);
+            if (!udpTransport->start([this](const std::vector<uint8_t>& datagram) {
+                    meshNetwork->receiveFromLink(datagram);
+                })) {
+                throw std::runtime_error("trasporto UDP non disponibile su " + config.udp.multicastAddress + ":" 
+                                         + std::to_string(config.udp.port));
+            }
+            meshNetwork->setLinkSender([transport = udpTransport.get()](const std::vector<uint8_t>& datagram) {
+                transport->send(datagram);
+            });
+            SABER_LOG(Info, "protocol", "Rete mesh su UDP multicast " << config.udp.multicastAddress << ":" 
+                      << config.udp.port);
+        }
+        
         // Avvio mesh network
         meshNetwork->start();
+        
+        // Nodi noti e zone dell'ultima esecuzione, poi quelli ereditati dal nodo esportato
+        restoreState();
+        if (importedState) {
+            for (const auto& peer : importedState->peers) {
+                if (!peer.publicKey.empty()) {
+                    crypto->registerNodeKey(peer.nodeId, peer.publicKey);
+                }
+                meshNetwork->registerNode(peer.nodeId, peer.role);
+            }
+            for (const auto& zone : importedState->zones) {
+                meshNetwork->assignZone(zone.first, zone.second);
+            }
+            journal->append("state", "imported", config.nodeId, std::to_string(importedState->peers.size()) 
+                            + " nodi, " + std::to_string(importedState->zones.size()) + " zone", 
+                            syncManager->now());
+            importedState.reset();
+        }
     } catch (const std::exception& e) {
-        std::cerr << "Errore durante l'avvio della rete mesh: " << e.what() << std::endl;
+        SABER_LOG(Error, "protocol", "Errore durante l'avvio della rete mesh: " << e.what());
+        if (udpTransport) {
+            meshNetwork->setLinkSender(nullptr);
+            udpTransport.reset();
+        }
+        if (simNetwork) {
+            meshNetwork->setLinkSender(nullptr);
+            simNetwork->detach(config.nodeId);
+        }
+        state = ProtocolState::Stopped;
         return false;
     }
     
-    // Inizializzazione del sincronizzatore audio
-    audioSync = std::make_unique<AudioSync>(syncManager, config.isMusicMode);
+    // Stream trasmessi dal Master e ascoltati dai sink
+    if (config.role == NodeRole::Master) {
+        for (StreamId streamId : config.publishedStreams) {
+            meshNetwork->publishStream(streamId);
+        }
+    } else if (config.role == NodeRole::Sink) {
+        for (StreamId streamId : config.listenStreams) {
+            subscribeStream(streamId);
+        }
+    }
+    
+    if (config.pipelineTraceFile && !profiler->setTraceFile(*config.pipelineTraceFile)) {
+        SABER_LOG(Error, "protocol", "Impossibile aprire il file di tracciamento: " << *config.pipelineTraceFile);
+    }
+    
+#ifndef SABER_MINIMAL_SINK
+    // Programmazione oraria persistita dal Master
+    scheduler.setUtcOffset(config.scheduleUtcOffsetMinutes);
+    if (config.scheduleFile) {
+        try {
+            scheduler.load(*config.scheduleFile);
+        } catch (const std::invalid_argument& e) {
+            SABER_LOG(Error, "protocol", "Programmazione non caricata: " << e.what());
+        }
+    }
+#endif
+    
+    // Il dispatcher dei comandi serve anche alla programmazione, con o senza socket
+    controlServer = std::make_unique<ControlServer>(config.controlBindAddress, config.controlPort.value_or(0));
+    controlServer->addCommand("log", [this](const std::vector<std::string>& args) {
+        return runLogCommand(args);
+    });
+    controlServer->addCommand("config", [this](const std::vector<std::string>& args) {
+        return runConfigCommand(args);
+    });
+    controlServer->addCommand("token", [this](const std::vector<std::string>& args) {
+        return runTokenCommand(args);
+    });
+    controlServer->addCommand("pair", [this](const std::vector<std::string>& args) {
+        return runPairCommand(args);
+    });
+    controlServer->addCommand("status", [this](const std::vector<std::string>&) {
+        return std::string(isReady() ? "ready" : "not-ready") 
+             + " synchronized=" + (isSynchronized() ? "1" : "0")
+             + " power=" + powerStateToString(getPowerState());
+    });
+    controlServer->addCommand("provision", [this](const std::vector<std::string>& args) {
+        return runProvisionCommand(args);
+    });
+    controlServer->addCommand("events", [this](const std::vector<std::string>& args) {
+        return runEventsCommand(args);
+    });
+    controlServer->addCommand("playback", [this](const std::vector<std::string>& args) {
+        return runPlaybackCommand(args);
+    });
+    controlServer->addCommand("degradation", [this](const std::vector<std::string>&) {
+        DegradationSettings settings = getDegradationSettings();
+        std::string suspended;
+        for (const auto& nodeId : getSuspendedSinks()) {
+            suspended += (suspended.empty() ? "" : ",") + nodeId;
+        }
+        return "level=" + degradationLevelToString(settings.level) 
+             + " bitrate=" + std::to_string(settings.bitrateKbps) + "kbps"
+             + " fec=" + (settings.fecEnabled ? "1" : "0")
+             + " buffer=" + std::to_string(settings.bufferMs) + "ms"
+             + " rate=" + std::to_string(settings.sampleRateHz) + "Hz"
+             + " suspended=" + (suspended.empty() ? "-" : suspended);
+    });
+    controlServer->addCommand("party", [this](const std::vector<std::string>& args) {
+        return runPartyCommand(args);
+    });
+    controlServer->addCommand("intercom", [this](const std::vector<std::string>& args) {
+        return runIntercomCommand(args);
+    });
+    controlServer->addCommand("standby", [this](const std::vector<std::string>& args) {
+        return runStandbyCommand(args);
+    });
+    controlServer->addCommand("source", [this](const std::vector<std::string>& args) {
+        return runSourceCommand(args);
+    });
+    controlServer->addCommand("zone", [this](const std::vector<std::string>& args) {
+        return runZoneCommand(args);
+    });
+    controlServer->addCommand("simulcast", [this](const std::vector<std::string>& args) {
+        return runSimulcastCommand(args);
+    });
+    controlServer->addCommand("topology", [this](const std::vector<std::string>& args) {
+        return runTopologyCommand(args);
+    });
+    controlServer->addCommand("flow", [this](const std::vector<std::string>& args) {
+        return runFlowCommand(args);
+    });
+#ifndef SABER_MINIMAL_SINK
+    controlServer->addCommand("schedule", [this](const std::vector<std::string>& args) {
+        return runScheduleCommand(args);
+    });
+#endif
+    controlServer->addCommand("timings", [this](const std::vector<std::string>& args) {
+        return runTimingsCommand(args);
+    });
+    controlServer->addCommand("sessions", [this](const std::vector<std::string>& args) {
+        return runSessionsCommand(args);
+    });
+    controlServer->addCommand("syncprobe", [this](const std::vector<std::string>& args) {
+        return runSyncProbeCommand(args);
+    });
+    controlServer->addCommand("survey", [this](const std::vector<std::string>& args) {
+        return runSurveyCommand(args);
+    });
+    controlServer->addCommand("discovery", [this](const std::vector<std::string>& args) {
+        return runDiscoveryCommand(args);
+    });
+    controlServer->setRequiredScope("log get", TokenScope::ReadOnly);
+    controlServer->setRequiredScope("provision status", TokenScope::ReadOnly);
+#ifndef SABER_MINIMAL_SINK
+    controlServer->setRequiredScope("schedule list", TokenScope::ReadOnly);
+#endif
+    controlServer->setRequiredScope("party status", TokenScope::ReadOnly);
+    controlServer->setRequiredScope("intercom status", TokenScope::ReadOnly);
+    controlServer->setRequiredScope("standby status", TokenScope::ReadOnly);
+    controlServer->setRequiredScope("source status", TokenScope::ReadOnly);
+    controlServer->setRequiredScope("zone delays", TokenScope::ReadOnly);
+    controlServer->setRequiredScope("playback status", TokenScope::ReadOnly);
+    controlServer->setRequiredScope("simulcast status", TokenScope::ReadOnly);
+    controlServer->setRequiredScope("topology", TokenScope::ReadOnly);
+    controlServer->setRequiredScope("flow status", TokenScope::ReadOnly);
+    controlServer->setRequiredScope("token crl", TokenScope::ReadOnly);
+    controlServer->setRequiredScope("pair status", TokenScope::ReadOnly);
+    controlServer->setRequiredScope("degradation", TokenScope::ReadOnly);
+    controlServer->setRequiredScope("events", TokenScope::ReadOnly);
+    controlServer->setRequiredScope("status", TokenScope::ReadOnly);
+    controlServer->setRequiredScope("timings show", TokenScope::ReadOnly);
+    controlServer->setRequiredScope("sessions", TokenScope::ReadOnly);
+    controlServer->setRequiredScope("syncprobe show", TokenScope::ReadOnly);
+    controlServer->addCommand("trace", [this](const std::vector<std::string>& args) {
+        return runTraceCommand(args);
+    });
+    controlServer->setRequiredScope("survey report", TokenScope::ReadOnly);
+    controlServer->setRequiredScope("discovery list", TokenScope::ReadOnly);
+    controlServer->setRequiredScope("trace local", TokenScope::ReadOnly);
+    controlServer->setAuthenticator([this](const std::string& token) -> std::optional<TokenScope> {
+        auto verified = verifyControlToken(token);
+        if (!verified) {
+            return std::nullopt;
+        }
+        return verified->scope;
+    });
+    controlServer->setCommandAuthorizer([this](const std::string& line, 
+                                               const std::string& token) -> std::optional<std::string> {
+        return authorizeCommand(line, token);
+    });
+    if (config.controlPort) {
+        writeControlTokenFile();
+        if (!controlServer->start()) {
+            SABER_LOG(Error, "protocol", "Impossibile avviare il socket di controllo");
+        }
+    }
     
     // Avvio thread di runtime
     running = true;
+    lastRuntimeTick = steadyMillis();
     runtimeThread = std::make_unique<std::thread>([this]() {
         while (running) {
+            lastRuntimeTick = steadyMillis();
+            
             // Esegui operazioni periodiche qui
-            std::this_thread::sleep_for(std::chrono::milliseconds(100));
+            flushArtworkReplies();
+            syncManager->checkBeaconTimeout();
+            watchConfigFile();
+            updateCongestion();
+            meshNetwork->checkNodeLiveness();
+            meshNetwork->retransmitPending();
+#ifndef SABER_MINIMAL_SINK
+            runDueSchedule();
+#endif
+            runPendingPlayback();
+            runPendingParty();
+            runPendingIntercom();
+            updateStandby();
+            updateSources();
+            propagateZones();
+            propagateZoneDelays();
+            updateDelayEqualization();
+            announceSimulcast();
+            reportNeighbors();
+            updateSimulcast();
+            updateDegradation();
+            applyDegradation();
+            applyAudioProfile();
+            reportPhantomStatus();
+            reportBridgeStatus();
+            reportBufferStatus();
+            updateFlowControl();
+            announceRevocations();
+            applyRevocations();
+            runPairing();
+            finishIdleSessions();
+            persistState();
+            runSyncProbes();
+            runTimeExchange();
+            recoverDesyncedNodes();
+            runSurvey();
+            runDiscovery();
+            flushTraceReplies();
+            
+            std::unique_lock<std::mutex> lock(runtimeMutex);
+            runtimeWakeup.wait_for(lock, std::chrono::milliseconds(100), [this] { return !running; });
         }
     });
     
-    std::cout << "Protocollo SABER inizializzato correttamente" << std::endl;
+#ifdef SABER_WITH_HTTP
+    if (config.healthPort) {
+        healthServer = std::make_unique<HttpServer>(config.healthBindAddress, *config.healthPort);
+        healthServer->addRoute("/healthz", [this]() {
+            return isLive() ? HttpResponse{200, "text/plain", "ok\n"}
+                            : HttpResponse{503, "text/plain", "not live\n"};
+        });
+        healthServer->addRoute("/readyz", [this]() {
+            return isReady() ? HttpResponse{200, "text/plain", "ready\n"}
+                             : HttpResponse{503, "text/plain", "not ready\n"};
+        });
+        healthServer->addRoute("/metrics", [this]() {
+            return HttpResponse{200, "text/plain; version=0.0.4", getPrometheusMetrics()};
+        });
+        if (!healthServer->start()) {
+            SABER_LOG(Error, "protocol", "Impossibile avviare gli endpoint di health-check");
+        }
+    }
+#endif
+    
+    state = ProtocolState::Running;
+    SABER_LOG(Info, "protocol", "Protocollo SABER inizializzato correttamente");
     return true;
 }
 
+void SaberProtocol::shutdown() {
+    // I thread interni non possono attendere la propria terminazione
+    if (runtimeThread && runtimeThread->get_id() == std::this_thread::get_id()) {
+        throw LifecycleError(LifecycleError::Type::InvalidThread, 
+                             "Arresto richiesto dal thread di runtime del protocollo");
+    }
+    if (meshNetwork && meshNetwork->isNetworkThread()) {
+        throw LifecycleError(LifecycleError::Type::InvalidThread, 
+                             "Arresto richiesto dal thread della rete mesh");
+    }
+    ProtocolState expected = ProtocolState::Running;
+    if (!state.compare_exchange_strong(expected, ProtocolState::ShuttingDown)) {
+        throw LifecycleError(LifecycleError::Type::NotRunning, "Protocollo SABER non in esecuzione");
+    }
+    
+    SABER_LOG(Info, "protocol", "Arresto SABER Protocol con ID " << config.nodeId);
+    
+    // Le sessioni aperte vengono chiuse finché la rete può ancora inoltrarne i resoconti
+    stopAudioPlayback();
+    stopServices();
+    persistState();
+    if (udpTransport) {
+        udpTransport->stop();
+    }
+    if (simNetwork) {
+        simNetwork->detach(config.nodeId);
+    }
+    meshNetwork->stop();
+    authorizationGate.cancelPending("protocollo arrestato");
+    // Gli span della fase di arresto vengono esportati prima di liberare la rete
+    if (otlpExporter) {
+        otlpExporter->stop();
+    }
+    
+    {
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        meshNetwork.reset();
+        audioSync.reset();
+        phantom.reset();
+        a2dpBridge.reset();
+        sessionTracker.reset();
+        stateStore.reset();
+        udpTransport.reset();
+        discovery.reset();
+        otlpExporter.reset();
+        controlServer.reset();
+#ifdef SABER_WITH_HTTP
+        healthServer.reset();
+#endif
+        lastDecodedSequences.clear();
+    }
+    
+    state = ProtocolState::Stopped;
+    journal->append("protocol", "shutdown", config.nodeId, "", syncManager->now());
+    SABER_LOG(Info, "protocol", "Protocollo SABER arrestato");
+}
+
+void SaberProtocol::restart() {
+    if (state == ProtocolState::Running) {
+        shutdown();
+    }
+    if (!initialize()) {
+        std::string reason = "Reinizializzazione del protocollo SABER non riuscita";
+        PreflightReport report = getPreflightReport();
+        if (!report.passed()) {
+            reason += ":\n" + report.summary();
+        }
+        throw LifecycleError(LifecycleError::Type::InitializationFailed, reason);
+    }
+}
+
+std::future<bool> SaberProtocol::initializeAsync() {
+    return std::async(std::launch::async, [this]() {
+        return initialize();
+    });
+}
+
+void SaberProtocol::setRole(NodeRole role) {
+    bool wasRunning = state == ProtocolState::Running;
+    if (wasRunning) {
+        shutdown();
+    }
+    {
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        config.role = role;
+    }
+    if (wasRunning) {
+        restart();
+    }
+}
+
+NodeRole SaberProtocol::getRole() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    return config.role;
+}
+
+PreflightReport SaberProtocol::getPreflightReport() const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    return preflightReport;
+}
+
 std::shared_ptr<SyncManager> SaberProtocol::getSyncManager() const {
     return syncManager;
 }
 
+std::shared_ptr<MeshCrypto> SaberProtocol::getCrypto() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    return crypto;
+}
+
 bool SaberProtocol::startAudioPlayback() {
     std::lock_guard<std::mutex> lock(protocolMutex);
     
     if (!audioSync) {
-        std::cerr << "Sincronizzatore audio non inizializzato" << std::endl;
+        SABER_LOG(Error, "protocol", "Sincronizzatore audio non inizializzato");
         return false;
     }
     
     if (audioSync->startPlayback()) {
-        std::cout << "Avvio riproduzione audio sincronizzata" << std::endl;
+        SABER_LOG(Info, "protocol", (phantom ? "Avvio riproduzione simulata (sink fantasma)" 
+                                             : "Avvio riproduzione audio sincronizzata"));
         return true;
     } else {
         return false;
@@ -97,12 +1047,15 @@ bool SaberProtocol::stopAudioPlayback() {
     std::lock_guard<std::mutex> lock(protocolMutex);
     
     if (!audioSync) {
-        std::cerr << "Sincronizzatore audio non inizializzato" << std::endl;
+        SABER_LOG(Error, "protocol", "Sincronizzatore audio non inizializzato");
         return false;
     }
     
     audioSync->stopPlayback();
-    std::cout << "Arresto riproduzione audio" << std::endl;
+    SABER_LOG(Info, "protocol", "Arresto riproduzione audio");
+    if (sessionTracker && meshNetwork) {
+        publishSessionReports(sessionTracker->finishAll());
+    }
     return true;
 }
 
@@ -110,6 +1063,83 @@ bool SaberProtocol::updateTimeSync(uint64_t masterTime) {
     return syncManager->handleTimeBeacon(masterTime);
 }
 
+bool SaberProtocol::requestEmergencySync(const std::vector<std::string>& nodeIds) {
+    if (config.role != NodeRole::Master) {
+        SABER_LOG(Error, "protocol", "Solo il Master può riallineare gli orologi dei sink");
+        return false;
+    }
+    
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    uint64_t masterTime = syncManager->now();
+    meshNetwork->sendPacket(MeshPacket::createEmergencySync(masterTime, nodeIds));
+    {
+        std::lock_guard<std::mutex> eventsLock(eventsMutex);
+        emergencySyncStats.sent++;
+        emergencySyncStats.nodesTargeted += nodeIds.size();
+    }
+    std::string targets = nodeIds.empty() ? "all" : joinList(nodeIds);
+    journal->append("sync", "emergency_sent", config.nodeId, targets, masterTime);
+    SABER_LOG(Warn, "sync", "Riallineamento d'emergenza degli orologi inviato a " << targets);
+    return true;
+}
+
+EmergencySyncStats SaberProtocol::getEmergencySyncStats() const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    return emergencySyncStats;
+}
+
+void SaberProtocol::handleEmergencySync(const MeshPacket& packet) {
+    auto [masterTime, targets] = packet.getEmergencySyncData();
+    if (config.role == NodeRole::Master || 
+        (!targets.empty() && std::find(targets.begin(), targets.end(), config.nodeId) == targets.end())) {
+        return;
+    }
+    if (!syncManager->emergencySync(masterTime)) {
+        SABER_LOG(Warn, "sync", "Riallineamento d'emergenza da " << packet.getSource() << " non applicato");
+        return;
+    }
+    
+    // Dopo il salto dell'orologio i frame prossimi alla riproduzione cadono fuori posto:
+    // si tace finché il buffer di jitter non si riempie di frame allineati
+    uint64_t now = syncManager->now();
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        emergencyMuteUntilUs = syncManager->nowUs() + EMERGENCY_SYNC_MUTE_US;
+        emergencySyncStats.applied++;
+        emergencySyncStats.lastAppliedAtMs = now;
+    }
+    journal->append("sync", "emergency", packet.getSource(), "", now);
+    SABER_LOG(Warn, "sync", "Orologio riallineato d'emergenza dal Master " << packet.getSource());
+}
+
+void SaberProtocol::recoverDesyncedNodes() {
+    if (config.role != NodeRole::Master) {
+        return;
+    }
+    
+    std::vector<std::string> targets;
+    int64_t now = steadyMillis();
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        for (const auto& nodeId : desyncedNodes) {
+            auto last = lastEmergencySyncMs.find(nodeId);
+            if (last == lastEmergencySyncMs.end() || now - last->second >= EMERGENCY_SYNC_COOLDOWN_MS) {
+                lastEmergencySyncMs[nodeId] = now;
+                targets.push_back(nodeId);
+            }
+        }
+        desyncedNodes.clear();
+    }
+    if (!targets.empty()) {
+        requestEmergencySync(targets);
+    }
+}
+
 uint32_t SaberProtocol::getCurrentLatency() const {
     std::lock_guard<std::mutex> lock(protocolMutex);
     
@@ -125,12 +1155,15 @@ bool SaberProtocol::registerNode(const std::string& nodeId, NodeRole role,
     std::lock_guard<std::mutex> lock(protocolMutex);
     
     if (!meshNetwork) {
-        std::cerr << "Rete mesh non inizializzata" << std::endl;
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
         return false;
     }
     
     // Invece di creare un oggetto Node, passa direttamente i parametri
-    meshNetwork->registerNode(nodeId, role);
+    if (!meshNetwork->registerNode(nodeId, role)) {
+        SABER_LOG(Error, "protocol", "Nodo " << nodeId << " respinto: rete al completo");
+        return false;
+    }
     return true;
 }
 
@@ -138,7 +1171,7 @@ std::vector<std::string> SaberProtocol::getActiveNodes() const {
     std::lock_guard<std::mutex> lock(protocolMutex);
     
     if (!meshNetwork) {
-        std::cerr << "Rete mesh non inizializzata" << std::endl;
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
         return {};
     }
     
@@ -146,97 +1179,5441 @@ std::vector<std::string> SaberProtocol::getActiveNodes() const {
     return meshNetwork->getActiveNodes();
 }
 
-bool SaberProtocol::isSynchronized() const {
-    return syncManager->isSynchronized();
+std::vector<MeshNodeInfo> SaberProtocol::getActiveNodeInfo() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return {};
+    }
+    
+    return meshNetwork->getActiveNodeInfo();
 }
 
-// Funzioni di utilità
-std::unique_ptr<SaberProtocol> startMaster(const std::optional<std::string>& nodeId, 
-                         const std::optional<std::string>& btAddress) {
-    // Genera un ID se non fornito
-    std::string id;
-    if (nodeId) {
-        id = *nodeId;
-    } else {
-        std::random_device rd;
-        std::mt19937 gen(rd());
-        std::uniform_int_distribution<> dis(0, 0xFFFFFF);
-        id = "master-" + std::to_string(dis(gen));
+std::vector<std::string> SaberProtocol::getEvictedNodes() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return {};
     }
     
-    SaberConfig config = {
-        id,                  // nodeId
-        NodeRole::Master,    // role
-        btAddress,           // btAddress
-        true                 // isMusicMode
+    return meshNetwork->getEvictedNodes();
+}
+
+bool SaberProtocol::subscribeStream(StreamId streamId) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    meshNetwork->sendPacket(MeshPacket::createSubscribe(config.nodeId, streamId));
+    subscribedStreams.insert(streamId);
+    return true;
+}
+
+bool SaberProtocol::publishStream(StreamId streamId, bool isMusic) {
+    if (config.role != NodeRole::Master) {
+        SABER_LOG(Error, "protocol", "Solo il Master può pubblicare stream");
+        return false;
+    }
+    setStreamFormat(streamId, isMusic);
+    
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    meshNetwork->publishStream(streamId);
+    config.publishedStreams.insert(streamId);
+    return true;
+}
+
+void SaberProtocol::setStreamFormat(StreamId streamId, bool isMusic) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    // Il formato resta nella configurazione, così vale anche dopo un riavvio
+    if (isMusic) {
+        config.voiceStreams.erase(streamId);
+    } else {
+        config.voiceStreams.insert(streamId);
+    }
+    if (audioSync) {
+        audioSync->setStreamFormat(streamId, isMusic, isMusic ? 2 : 1);
+    }
+}
+
+bool SaberProtocol::setAudioProfile(AudioProfile profile) {
+    if (config.role != NodeRole::Master) {
+        SABER_LOG(Error, "protocol", "Solo il Master può cambiare il profilo audio");
+        return false;
+    }
+    
+    bool isMusic = profile == AudioProfile::Music;
+    uint32_t sampleRate = isMusic ? spec::SAMPLE_RATE_MUSIC_HZ : spec::SAMPLE_RATE_VOICE_HZ;
+    uint8_t channels = isMusic ? 2 : 1;
+    std::vector<StreamId> streams;
+    std::vector<std::unique_ptr<AudioSourcePump>> detached;
+    {
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        if (!meshNetwork || !audioSync) {
+            SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+            return false;
+        }
+        
+        // Gli stream dell'interfono restano sempre vocali
+        std::vector<std::string> ids;
+        for (StreamId streamId : config.publishedStreams) {
+            if (!isIntercomStream(streamId)) {
+                streams.push_back(streamId);
+                ids.push_back(std::to_string(streamId));
+            }
+        }
+        
+        // Una sorgente non può cambiare formato: viene staccata
+        for (StreamId streamId : streams) {
+            auto source = audioSources.find(streamId);
+            if (source == audioSources.end()) {
+                continue;
+            }
+            auto input = source->second->getSource();
+            if (input->getSampleRate() != sampleRate || input->getChannels() != channels) {
+                SABER_LOG(Warn, "protocol", "Sorgente dello stream " << streamId << " staccata: il formato ("
+                          << input->getSampleRate() << " Hz, " << static_cast<int>(input->getChannels())
+                          << " canali) non è quello del profilo " << audioProfileToString(profile));
+                detached.push_back(std::move(source->second));
+                audioSources.erase(source);
+            }
+        }
+        
+        config.isMusicMode = isMusic;
+        meshNetwork->sendPacket(MeshPacket::createCommand("audio.profile", {
+            {"profile", audioProfileToString(profile)},
+            {"streams", joinList(ids)}
+        }));
+    }
+    
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        pendingAudioProfile = PendingAudioProfile{profile, streams};
+    }
+    
+    // Il thread di una sorgente staccata può essere in attesa di protocolMutex
+    detached.clear();
+    return true;
+}
+
+AudioProfile SaberProtocol::getAudioProfile() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    return config.isMusicMode ? AudioProfile::Music : AudioProfile::Voice;
+}
+
+bool SaberProtocol::unsubscribeStream(StreamId streamId) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    meshNetwork->sendPacket(MeshPacket::createUnsubscribe(config.nodeId, streamId));
+    subscribedStreams.erase(streamId);
+    // Anche i livelli simulcast ricevuti al posto dello stream vengono abbandonati
+    std::vector<StreamId> layers;
+    {
+        std::lock_guard<std::mutex> eventsLock(eventsMutex);
+        auto subscribed = simulcastLayers.find(streamId);
+        if (subscribed != simulcastLayers.end()) {
+            layers = subscribed->second;
+            simulcastLayers.erase(subscribed);
+        }
+        simulcastReceivers.erase(streamId);
+    }
+    for (StreamId layer : layers) {
+        if (layer != streamId) {
+            meshNetwork->sendPacket(MeshPacket::createUnsubscribe(config.nodeId, layer));
+        }
+    }
+    if (sessionTracker) {
+        if (auto report = sessionTracker->finish(streamId)) {
+            publishSessionReports({*report});
+        }
+    }
+    return true;
+}
+
+std::string SaberProtocol::exportTopology() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return {};
+    }
+    
+    return meshNetwork->exportTopologyJson();
+}
+
+TopologyGraph SaberProtocol::getTopology() const {
+    TopologyGraph graph;
+    {
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        if (!meshNetwork) {
+            SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+            return {};
+        }
+        graph = meshNetwork->getTopology();
+    }
+    
+    // I collegamenti riportati invecchiano anche dopo la ricezione del resoconto
+    int64_t now = steadyMillis();
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    for (const auto& report : neighborReports) {
+        if (report.first == graph.localNode) {
+            continue;
+        }
+        uint64_t elapsed = static_cast<uint64_t>(now - report.second.second);
+        for (TopologyLink link : report.second.first) {
+            link.ageMs += elapsed;
+            if (link.ageMs < TOPOLOGY_LINK_TIMEOUT_MS) {
+                graph.links.push_back(link);
+            }
+        }
+    }
+    return graph;
+}
+
+std::string SaberProtocol::exportTopologyGraph(const std::string& format) const {
+    if (format == "dot") {
+        return topologyToDot(getTopology());
+    }
+    if (format == "json") {
+        return topologyToJson(getTopology());
+    }
+    throw std::invalid_argument("Formato della topologia non supportato: " + format);
+}
+
+std::map<std::string, uint64_t> SaberProtocol::getDropCounters() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    std::map<std::string, uint64_t> counters;
+    if (meshNetwork) {
+        for (const auto& pair : meshNetwork->getDropCounters()) {
+            counters[rejectReasonToString(pair.first)] = pair.second;
+        }
+    }
+    return counters;
+}
+
+RateLimitStats SaberProtocol::getRateLimitStats() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return {};
+    }
+    
+    return meshNetwork->getRateLimitStats();
+}
+
+AdmissionStats SaberProtocol::getAdmissionStats() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return {};
+    }
+    
+    return meshNetwork->getAdmissionStats();
+}
+
+std::map<PacketClass, PacketQueueStats> SaberProtocol::getPacketQueueStats() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return {};
+    }
+    
+    return meshNetwork->getQueueStats();
+}
+
+bool SaberProtocol::setCapacityLimits(uint32_t maxNodes, uint32_t maxSinks) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    config.maxNodes = maxNodes;
+    config.maxSinks = maxSinks;
+    meshNetwork->setCapacityLimits(maxNodes, maxSinks);
+    return true;
+}
+
+JournalPage SaberProtocol::getEventsSince(uint64_t cursor, size_t limit) const {
+    return journal->since(cursor, limit);
+}
+
+std::string SaberProtocol::runEventsCommand(const std::vector<std::string>& args) {
+    // Uso: events since <cursore> [limite]
+    if ((args.size() != 2 && args.size() != 3) || args[0] != "since") {
+        throw std::invalid_argument("uso: events since <cursore> [limite]");
+    }
+    size_t limit = args.size() == 3 ? std::stoul(args[2]) : 256;
+    JournalPage page = getEventsSince(std::stoull(args[1]), limit);
+    
+    std::string result = "next=" + std::to_string(page.nextCursor) 
+                       + " more=" + (page.hasMore ? "1" : "0")
+                       + " truncated=" + (page.truncated ? "1" : "0");
+    for (const auto& event : page.events) {
+        result += "; " + std::to_string(event.cursor) + " " + std::to_string(event.timestamp) + " " 
+                + event.category + " " + event.type + " " + event.nodeId + " " + event.detail;
+    }
+    return result;
+}
+
+std::vector<SecurityEvent> SaberProtocol::getSecurityEvents() const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    return securityEvents;
+}
+
+bool SaberProtocol::reportLinkQuality(const std::string& peer, TransportKind kind, double linkQuality,
+                                      uint32_t latencyMs, std::optional<int32_t> rssiDbm) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    meshNetwork->reportLinkQuality(peer, kind, linkQuality, latencyMs, rssiDbm);
+    return true;
+}
+
+std::map<std::string, LinkQuality> SaberProtocol::getLinkQualities() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return {};
+    }
+    
+    return meshNetwork->getLinkQualities();
+}
+
+bool SaberProtocol::reportLinkDown(const std::string& peer, TransportKind kind) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    meshNetwork->reportLinkDown(peer, kind);
+    return true;
+}
+
+TransportStats SaberProtocol::getTransportStats() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return {};
+    }
+    
+    return meshNetwork->getTransportStats();
+}
+
+std::vector<FailoverEvent> SaberProtocol::getFailoverEvents() const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    return failoverEvents;
+}
+
+std::vector<std::string> SaberProtocol::getQuarantinedNodes() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    return crypto ? crypto->getQuarantinedNodes() : std::vector<std::string>{};
+}
+
+bool SaberProtocol::resolveKeyConflict(const std::string& nodeId, const std::vector<uint8_t>& trustedKey) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!crypto) {
+        SABER_LOG(Error, "protocol", "Gestore crittografico non inizializzato");
+        return false;
+    }
+    
+    try {
+        crypto->resolveKeyConflict(nodeId, trustedKey);
+    } catch (const CryptoError& e) {
+        SABER_LOG(Error, "protocol", "Impossibile risolvere il conflitto: " << e.what());
+        return false;
+    }
+    return true;
+}
+
+std::vector<IntrusionAlert> SaberProtocol::getIntrusionAlerts() const {
+    return intrusionDetector->getAlerts();
+}
+
+bool SaberProtocol::releaseQuarantine(const std::string& nodeId) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!crypto) {
+        SABER_LOG(Error, "protocol", "Gestore crittografico non inizializzato");
+        return false;
+    }
+    
+    return crypto->releaseQuarantine(nodeId);
+}
+
+ConfigReloadReport SaberProtocol::reloadConfig() {
+    std::lock_guard<std::mutex> reloadLock(reloadMutex);
+    ConfigReloadReport report;
+    if (!config.configFile) {
+        report.error = "configurazione non caricata da file";
+        return report;
+    }
+    
+    // Il file viene validato per intero prima di toccare la configurazione in uso
+    SaberConfig updated;
+    std::map<std::string, std::string> values;
+    try {
+        updated = SaberConfig::fromFile(*config.configFile);
+        values = rawConfigValues(ConfigFile::load(*config.configFile));
+        LogFilter::parse(updated.logFilter);
+    } catch (const std::exception& e) {
+        report.error = e.what();
+        SABER_LOG(Warn, "config", "Configurazione non ricaricata: " << e.what());
+        journal->append("config", "reload_failed", config.nodeId, e.what(), syncManager->now());
+        return report;
+    }
+    
+    std::set<std::string> changed;
+    for (const auto& entry : values) {
+        auto previous = loadedConfigValues.find(entry.first);
+        if (previous == loadedConfigValues.end() || previous->second != entry.second) {
+            changed.insert(entry.first);
+        }
+    }
+    for (const auto& entry : loadedConfigValues) {
+        if (values.count(entry.first) == 0) {
+            changed.insert(entry.first);
+        }
+    }
+    auto changedWith = [&changed](const std::string& prefix) {
+        return std::any_of(changed.begin(), changed.end(), [&prefix](const std::string& key) {
+            return key.compare(0, prefix.size(), prefix) == 0;
+        });
     };
+    for (const auto& key : changed) {
+        (SaberConfig::isLiveReloadable(key) ? report.applied : report.restartRequired).push_back(key);
+    }
+    
+    {
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        if (changedWith("log.filter")) {
+            config.logFilter = updated.logFilter;
+            Logger::instance().setFilter(LogFilter::parse(config.logFilter));
+        }
+        if (changedWith("degradation.")) {
+            config.degradation = updated.degradation;
+            std::lock_guard<std::mutex> eventsLock(eventsMutex);
+            pendingDegradationConfig = config.degradation;
+        }
+        if (changedWith("security.ids_")) {
+            config.intrusion = updated.intrusion;
+            intrusionDetector->setConfig(config.intrusion);
+        }
+        if (changedWith("security.replay_window")) {
+            config.replayWindow = updated.replayWindow;
+            if (crypto) {
+                crypto->setReplayWindow(config.replayWindow);
+            }
+        }
+        if (changedWith("survey.")) {
+            config.surveyIntervalMs = updated.surveyIntervalMs;
+            config.surveyMinRssiDbm = updated.surveyMinRssiDbm;
+            config.surveyMaxLossPercent = updated.surveyMaxLossPercent;
+        }
+        config.timeExchangeIntervalMs = updated.timeExchangeIntervalMs;
+        config.meshBeaconIntervalMs = updated.meshBeaconIntervalMs;
+        config.sendRejects = updated.sendRejects;
+        config.joinAttemptsPerMinute = updated.joinAttemptsPerMinute;
+        config.maxNodes = updated.maxNodes;
+        config.maxSinks = updated.maxSinks;
+        config.heartbeatTimeoutMs = updated.heartbeatTimeoutMs;
+        config.nodeEvictAfterMs = updated.nodeEvictAfterMs;
+        config.rateLimits = updated.rateLimits;
+        config.recordPaths = updated.recordPaths;
+        config.configWatchIntervalMs = updated.configWatchIntervalMs;
+        config.discoveryIntervalMs = updated.discoveryIntervalMs;
+        config.tracingEnabled = updated.tracingEnabled;
+        tracer->setEnabled(config.tracingEnabled);
+        if (changedWith("standby.")) {
+            config.standby = updated.standby;
+            standbyController.setConfig(config.standby);
+        }
+        if (changedWith("source")) {
+            config.sources = updated.sources;
+            sourceSelector.setConfig(config.sources);
+        }
+        if (changedWith("zone.")) {
+            config.zoneDelays = updated.zoneDelays;
+        }
+        if (changedWith("flow.")) {
+            config.flow = updated.flow;
+            flowController.setConfig(config.flow);
+        }
+        if (changedWith("simulcast.")) {
+            config.simulcast = updated.simulcast;
+            std::lock_guard<std::mutex> eventsLock(eventsMutex);
+            for (auto& entry : simulcastReceivers) {
+                entry.second->setConfig(config.simulcast);
+            }
+        }
+        if (changedWith("authorization.")) {
+            config.authorizationTimeoutMs = updated.authorizationTimeoutMs;
+            config.authorizationAllowOnTimeout = updated.authorizationAllowOnTimeout;
+            authorizationGate.setTimeout(config.authorizationTimeoutMs, config.authorizationAllowOnTimeout);
+        }
+        if (changedWith("audio.repair_")) {
+            config.repair = updated.repair;
+        }
+        if (changedWith("audio.equalization_")) {
+            config.delayEqualization = updated.delayEqualization;
+        }
+        if (changedWith("mesh.reliable_")) {
+            config.reliable = updated.reliable;
+        }
+        if (changedWith("audio.jitter_")) {
+            config.jitterBuffer = updated.jitterBuffer;
+            if (audioSync) {
+                audioSync->setJitterBufferConfig(config.jitterBuffer);
+            }
+        }
+        if (meshNetwork) {
+            meshNetwork->setSendRejects(config.sendRejects);
+            meshNetwork->setJoinRateLimit(config.joinAttemptsPerMinute);
+            meshNetwork->setCapacityLimits(config.maxNodes, config.maxSinks);
+            meshNetwork->setLivenessConfig(config.heartbeatTimeoutMs, config.nodeEvictAfterMs);
+            if (changedWith("ratelimit.")) {
+                meshNetwork->setRateLimits(config.rateLimits);
+            }
+            meshNetwork->setPathRecording(config.recordPaths);
+            meshNetwork->setRepairConfig(config.repair);
+            meshNetwork->setReliableConfig(config.reliable);
+        }
+    }
+    // Le chiavi da riavvio restano al valore in uso e vengono riportate anche ai prossimi ricaricamenti
+    for (const auto& key : report.restartRequired) {
+        auto previous = loadedConfigValues.find(key);
+        if (previous != loadedConfigValues.end()) {
+            values[key] = previous->second;
+        } else {
+            values.erase(key);
+        }
+    }
+    loadedConfigValues = values;
+    
+    std::string summary = "applied=" + (report.applied.empty() ? "-" : joinList(report.applied))
+                        + " restart=" + (report.restartRequired.empty() ? "-" : joinList(report.restartRequired));
+    if (!report.restartRequired.empty()) {
+        SABER_LOG(Warn, "config", "Chiavi che richiedono un riavvio: " << joinList(report.restartRequired));
+    }
+    SABER_LOG(Info, "config", "Configurazione ricaricata: " << summary);
+    journal->append("config", "reloaded", config.nodeId, summary, syncManager->now());
+    return report;
+}
+
+std::string SaberProtocol::runConfigCommand(const std::vector<std::string>& args) {
+    // Uso: config reload
+    if (args.size() != 1 || args[0] != "reload") {
+        throw std::invalid_argument("uso: config reload");
+    }
+    ConfigReloadReport report = reloadConfig();
+    if (report.error) {
+        throw std::runtime_error("configurazione non valida: " + *report.error);
+    }
+    return "applied=" + (report.applied.empty() ? "-" : joinList(report.applied))
+         + " restart=" + (report.restartRequired.empty() ? "-" : joinList(report.restartRequired));
+}
+
+void SaberProtocol::watchConfigFile() {
+    if (!config.configFile || config.configWatchIntervalMs == 0) {
+        return;
+    }
+    int64_t now = steadyMillis();
+    if (now - lastConfigCheckMs < config.configWatchIntervalMs) {
+        return;
+    }
+    lastConfigCheckMs = now;
+    
+    std::error_code error;
+    auto modified = std::filesystem::last_write_time(*config.configFile, error);
+    if (error || modified == configFileTime) {
+        return;
+    }
+    configFileTime = modified;
+    reloadConfig();
+}
+
+bool SaberProtocol::setLogFilter(const std::string& filter, bool meshWide,
+                                 const std::optional<std::string>& targetNode) {
+    LogFilter parsed;
+    try {
+        parsed = LogFilter::parse(filter);
+    } catch (const std::invalid_argument& e) {
+        SABER_LOG(Error, "protocol", e.what());
+        return false;
+    }
+    
+    bool local = !targetNode || *targetNode == config.nodeId;
+    if (local) {
+        Logger::instance().setFilter(parsed);
+        SABER_LOG(Info, "protocol", "Filtro dei log impostato a " << parsed.toString());
+    }
+    if (!meshWide && local) {
+        return true;
+    }
+    
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    std::map<std::string, std::string> params = {{"filter", filter}};
+    if (targetNode) {
+        params["target"] = *targetNode;
+    }
+    meshNetwork->sendPacket(MeshPacket::createCommand("log.set", params));
+    return true;
+}
+
+std::string SaberProtocol::getLogFilter() const {
+    return Logger::instance().getFilter().toString();
+}
+
+void SaberProtocol::handleAdminCommand(const MeshPacket& packet) {
+    if (packet.getSource() == config.nodeId) {
+        return;
+    }
+    
+    if (packet.getType() == MeshPacketType::Artwork) {
+        auto [hash, image] = packet.getArtworkData();
+        // Solo le copertine il cui contenuto corrisponde all'hash annunciato
+        if (!crypto || artworkHashHex(*crypto, image) != hash) {
+            SABER_LOG(Warn, "protocol", "Copertina con hash non corrispondente da " << packet.getSource());
+            return;
+        }
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        if (artworkStore.size() >= MAX_ARTWORK_ENTRIES && artworkStore.count(hash) == 0) {
+            artworkStore.erase(artworkStore.begin());
+        }
+        artworkStore[hash] = image;
+        return;
+    }
+    
+    if (packet.getType() != MeshPacketType::Command) {
+        return;
+    }
+    
+    auto [cmdType, params] = packet.getCommandData();
+    
+    auto target = params.find("target");
+    if (target != params.end() && target->second != config.nodeId) {
+        return;
+    }
+    
+    // Un nodo ammesso non può riconfigurare la rete: il ruolo del mittente lo registra la mesh
+    if (isMasterCommand(cmdType) && packet.getSenderRole() != NodeRole::Master) {
+        SABER_LOG(Warn, "security", "Comando " << cmdType << " ignorato da " << packet.getSource() 
+                  << ": non è il Master");
+        return;
+    }
+    
+    // I pacchetti inviati durante la gestione proseguono la traccia del mittente
+    TraceSpan span = tracer->startSpan("command " + cmdType, SpanKind::Consumer, packet.getTraceContext());
+    span.attributes["saber.command.source"] = packet.getSource();
+    SpanGuard spanGuard(*tracer, span);
+    SpanScope spanScope(span);
+    
+    if (cmdType == "log.set") {
+        try {
+            Logger::instance().setFilter(LogFilter::parse(params["filter"]));
+            SABER_LOG(Info, "protocol", "Filtro dei log impostato da " << packet.getSource() 
+                      << " a " << params["filter"]);
+        } catch (const std::invalid_argument& e) {
+            SABER_LOG(Warn, "protocol", "Filtro dei log non valido da " << packet.getSource() 
+                      << ": " << e.what());
+        }
+    } else if (cmdType == "bass.configure") {
+        BassSettings settings;
+        settings.role = params["role"];
+        try {
+            settings.crossoverHz = std::stof(params["crossover_hz"]);
+        } catch (const std::exception&) {
+            SABER_LOG(Warn, "protocol", "Crossover non valido da " << packet.getSource());
+            return;
+        }
+        {
+            std::lock_guard<std::mutex> lock(eventsMutex);
+            bassSettings = settings;
+        }
+        SABER_LOG(Info, "protocol", "Gestione dei bassi: " << settings.role << " a " 
+                  << settings.crossoverHz << "Hz");
+    } else if (cmdType == "zone.delay") {
+        uint32_t delayMs;
+        try {
+            delayMs = static_cast<uint32_t>(std::stoul(params["delay_ms"]));
+        } catch (const std::exception&) {
+            SABER_LOG(Warn, "protocol", "Ritardo di zona non valido da " << packet.getSource());
+            return;
+        }
+        if (delayMs > MAX_ZONE_DELAY_MS) {
+            SABER_LOG(Warn, "protocol", "Ritardo di zona " << delayMs << "ms oltre il massimo da " 
+                      << packet.getSource());
+            return;
+        }
+        {
+            std::lock_guard<std::mutex> lock(eventsMutex);
+            acousticDelayMs = delayMs;
+        }
+        SABER_LOG(Info, "protocol", "Ritardo acustico della zona " << params["zone"] << ": " << delayMs << "ms");
+    } else if (cmdType == "playout.deadline") {
+        uint32_t deadlineMs;
+        try {
+            deadlineMs = static_cast<uint32_t>(std::stoul(params["deadline_ms"]));
+        } catch (const std::exception&) {
+            SABER_LOG(Warn, "protocol", "Scadenza di riproduzione non valida da " << packet.getSource());
+            return;
+        }
+        {
+            std::lock_guard<std::mutex> lock(eventsMutex);
+            if (playoutDeadlineMs == deadlineMs) {
+                return;
+            }
+            playoutDeadlineMs = deadlineMs;
+        }
+        SABER_LOG(Info, "protocol", "Scadenza di riproduzione comune di " << deadlineMs << "ms da " 
+                  << packet.getSource());
+    } else if (cmdType == "simulcast.announce") {
+        StreamId streamId;
+        try {
+            streamId = static_cast<StreamId>(std::stoul(params["stream"]));
+        } catch (const std::exception&) {
+            SABER_LOG(Warn, "protocol", "Gruppo simulcast non valido da " << packet.getSource());
+            return;
+        }
+        // Un annuncio senza livelli ritira il gruppo: i sink tornano allo stream stesso
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        if (params["layers"].empty()) {
+            announcedSimulcast.erase(streamId);
+            return;
+        }
+        auto layers = parseSimulcastLayers(params["layers"]);
+        SimulcastGroup group{streamId, layers.value_or(std::vector<SimulcastLayer>{})};
+        try {
+            validateSimulcastGroup(group);
+        } catch (const std::invalid_argument& e) {
+            SABER_LOG(Warn, "protocol", "Gruppo simulcast non valido da " << packet.getSource() << ": " << e.what());
+            return;
+        }
+        announcedSimulcast[streamId] = group;
+    } else if (cmdType == "topology.neighbors") {
+        auto links = parseNeighborReport(packet.getSource(), params["peers"]);
+        if (!links) {
+            SABER_LOG(Warn, "protocol", "Resoconto dei vicini non valido da " << packet.getSource());
+            return;
+        }
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        neighborReports[packet.getSource()] = {*links, steadyMillis()};
+    } else if (cmdType == "crl.revoke") {
+        auto entries = parseRevocations(params["entries"]);
+        if (!entries) {
+            SABER_LOG(Warn, "security", "Lista di revoca non valida da " << packet.getSource());
+            return;
+        }
+        // Il ruolo del mittente si verifica nel thread di runtime: qui il mutex della rete è occupato
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        pendingRevocations.emplace_back(packet.getSource(), *entries);
+    } else if (cmdType == "artwork.get") {
+        // La risposta parte dal thread di runtime: qui il mutex della rete è occupato
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        if (artworkStore.count(params["hash"]) != 0) {
+            pendingArtworkReplies.push_back(params["hash"]);
+        }
+    } else if (cmdType.compare(0, 9, "playback.") == 0) {
+        // L'appartenenza alla zona viene verificata alla barriera, fuori dal mutex della rete
+        auto command = parsePlaybackCommand(cmdType, packet.getCommandZone().value_or("all"), params);
+        if (!command) {
+            SABER_LOG(Warn, "protocol", "Comando di riproduzione " << cmdType << " non valido da " 
+                      << packet.getSource());
+            span.error = "comando non valido";
+            return;
+        }
+        PendingPlayback pending{*command, packet.getSource()};
+        if (span.recording) {
+            pending.span = tracer->startSpan("playback.barrier", SpanKind::Internal, span.context);
+            pending.span->attributes["saber.zone"] = command->zone;
+        }
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        pendingPlayback.push_back(pending);
+    } else if (cmdType == "command.ack") {
+        // Solo il nodo può dire se ha eseguito il comando; l'attesa e la scadenza sono del mittente
+        auto outcome = commandOutcomeFromString(params["outcome"]);
+        if (!outcome || (*outcome != CommandOutcome::Applied && *outcome != CommandOutcome::Failed)) {
+            SABER_LOG(Warn, "protocol", "Conferma di comando non valida da " << packet.getSource());
+            return;
+        }
+        uint32_t id;
+        try {
+            id = static_cast<uint32_t>(std::stoul(params["id"]));
+        } catch (const std::exception&) {
+            SABER_LOG(Warn, "protocol", "Conferma di comando non valida da " << packet.getSource());
+            return;
+        }
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        commandTracker.acknowledge(packet.getSource(), id, *outcome, params["detail"], syncManager->now());
+    } else if (cmdType == "degrade.apply") {
+        DegradationSettings settings;
+        try {
+            int level = std::stoi(params["level"]);
+            if (level < 0 || level > static_cast<int>(MAX_DEGRADATION_LEVEL)) {
+                throw std::out_of_range("gradino");
+            }
+            settings.level = static_cast<DegradationLevel>(level);
+            settings.bitrateKbps = static_cast<uint32_t>(std::stoul(params["bitrate_kbps"]));
+            settings.fecEnabled = params["fec"] == "1";
+            settings.bufferMs = static_cast<uint32_t>(std::stoul(params["buffer_ms"]));
+            settings.sampleRateHz = static_cast<uint32_t>(std::stoul(params["sample_rate"]));
+        } catch (const std::exception&) {
+            SABER_LOG(Warn, "protocol", "Gradino di degrado non valido da " << packet.getSource());
+            return;
+        }
+        SABER_LOG(Info, "protocol", "Gradino di degrado " << degradationLevelToString(settings.level) 
+                  << " imposto da " << packet.getSource());
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        degradationSettings = settings;
+        suspendedSinks = splitList(params["suspended"]);
+        degradationPending = true;
+    } else if (cmdType == "audio.profile") {
+        PendingAudioProfile pending;
+        try {
+            auto profile = audioProfileFromString(params["profile"]);
+            if (!profile) {
+                throw std::invalid_argument("profilo");
+            }
+            pending.profile = *profile;
+            for (const auto& id : splitList(params["streams"])) {
+                pending.streams.push_back(static_cast<StreamId>(std::stoul(id)));
+            }
+        } catch (const std::exception&) {
+            SABER_LOG(Warn, "protocol", "Profilo audio non valido da " << packet.getSource());
+            return;
+        }
+        SABER_LOG(Info, "protocol", "Profilo audio " << params["profile"] << " imposto da " << packet.getSource());
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        pendingAudioProfile = std::move(pending);
+    } else if (cmdType == "session.report") {
+        if (config.role != NodeRole::Master) {
+            return;
+        }
+        SessionReport report;
+        try {
+            report.nodeId = packet.getSource();
+            report.streamId = static_cast<StreamId>(std::stoul(params["stream"]));
+            report.startedAtMs = std::stoull(params["started"]);
+            report.endedAtMs = std::stoull(params["ended"]);
+            report.framesPlayed = std::stoull(params["played"]);
+            report.framesConcealed = std::stoull(params["concealed"]);
+            report.framesDropped = std::stoull(params["dropped"]);
+            report.averageOffsetMs = std::stoll(params["offset_ms"]);
+            report.rebufferCount = std::stoull(params["rebuffers"]);
+            report.maxLatencyMs = static_cast<uint32_t>(std::stoul(params["max_latency_ms"]));
+        } catch (const std::exception&) {
+            SABER_LOG(Warn, "protocol", "Resoconto di sessione non valido da " << packet.getSource());
+            return;
+        }
+        journal->append("session", "report", report.nodeId, sessionSummary(report), report.endedAtMs);
+        storeSessionReport(report);
+    } else if (cmdType == "sync.probe") {
+        // Il ricevimento è marcato subito, la risposta parte dal thread di runtime
+        uint64_t receivedUs = syncManager->nowUs();
+        std::function<void(const std::string&, uint64_t)> clickHandler;
+        uint64_t clickAtUs = 0;
+        {
+            std::lock_guard<std::mutex> lock(eventsMutex);
+            pendingProbeReplies.push_back({packet.getSource(), params["probe"], params["sent"], receivedUs});
+            if (params.count("click_at") != 0) {
+                clickHandler = syncClickHandler;
+            }
+        }
+        if (clickHandler) {
+            try {
+                clickAtUs = std::stoull(params["click_at"]);
+            } catch (const std::exception&) {
+                SABER_LOG(Warn, "protocol", "Istante del clic non valido da " << packet.getSource());
+                return;
+            }
+            clickHandler(packet.getSource(), clickAtUs);
+        }
+    } else if (cmdType == "sync.probe_reply") {
+        uint64_t receivedUs = syncManager->nowUs();
+        uint64_t sentUs, peerReceivedUs, peerRepliedUs;
+        try {
+            sentUs = std::stoull(params["sent"]);
+            peerReceivedUs = std::stoull(params["received"]);
+            peerRepliedUs = std::stoull(params["replied"]);
+        } catch (const std::exception&) {
+            SABER_LOG(Warn, "protocol", "Risposta di misura non valida da " << packet.getSource());
+            return;
+        }
+        uint64_t roundTripUs = SyncProbe::roundTripUs(sentUs, peerReceivedUs, peerRepliedUs, receivedUs);
+        meshNetwork->recordNodeLatency(packet.getSource(), static_cast<uint32_t>(roundTripUs / 2000));
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        auto active = activeSyncProbes.find(packet.getSource());
+        if (active != activeSyncProbes.end() && !active->second.probe.isComplete()) {
+            active->second.probe.addSample(sentUs, peerReceivedUs, peerRepliedUs, receivedUs);
+        }
+    } else if (cmdType == "sync.time_request") {
+        // Come per le misure di sincronizzazione la risposta parte dal thread di runtime
+        if (config.role != NodeRole::Master) {
+            return;
+        }
+        uint64_t receivedUs = syncManager->nowUs();
+        // Un sink che si crede sincronizzato riporta il proprio orologio all'arrivo previsto della richiesta
+        bool desynced = false;
+        if (params.count("synced") != 0) {
+            try {
+                desynced = syncManager->isNodeOutOfSync(packet.getSource(), std::stoull(params["synced"]) / 1000);
+            } catch (const std::exception&) {
+                SABER_LOG(Warn, "protocol", "Orologio riportato non valido da " << packet.getSource());
+            }
+        }
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        pendingTimeReplies.push_back({packet.getSource(), "", params["sent"], receivedUs});
+        if (desynced) {
+            desyncedNodes.insert(packet.getSource());
+        }
+    } else if (cmdType == "sync.time_reply") {
+        // Lo scambio usa l'orologio locale non corretto: l'offset misurato è quello assoluto
+        uint64_t receivedUs = syncManager->localUs();
+        uint64_t sentUs, masterReceivedUs, masterRepliedUs;
+        try {
+            sentUs = std::stoull(params["sent"]);
+            masterReceivedUs = std::stoull(params["received"]);
+            masterRepliedUs = std::stoull(params["replied"]);
+        } catch (const std::exception&) {
+            SABER_LOG(Warn, "protocol", "Risposta allo scambio temporale non valida da " << packet.getSource());
+            return;
+        }
+        if (syncManager->handleTimeExchange(packet.getSource(), sentUs, masterReceivedUs, masterRepliedUs, receivedUs)) {
+            auto exchange = syncManager->getLastExchange();
+            meshNetwork->recordNodeLatency(packet.getSource(), static_cast<uint32_t>(exchange->roundTripUs / 2000));
+        }
+    } else if (cmdType == "survey.probe") {
+        // Come per le misure di sincronizzazione la risposta parte dal thread di runtime
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        pendingSurveyReplies.emplace_back(packet.getSource(), params["probe"]);
+    } else if (cmdType == "survey.reply") {
+        uint32_t probeId;
+        try {
+            probeId = static_cast<uint32_t>(std::stoul(params["probe"]));
+        } catch (const std::exception&) {
+            SABER_LOG(Warn, "protocol", "Risposta al sondaggio non valida da " << packet.getSource());
+            return;
+        }
+        std::function<std::optional<int32_t>(const std::string&)> provider;
+        {
+            std::lock_guard<std::mutex> lock(eventsMutex);
+            provider = rssiProvider;
+        }
+        std::optional<int32_t> rssiDbm = provider ? provider(packet.getSource()) : std::nullopt;
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        if (coverageSurvey) {
+            coverageSurvey->recordReply(packet.getSource(), probeId, rssiDbm);
+        }
+    } else if (cmdType == "trace.request") {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        pendingTraceReplies.push_back(packet.getSource());
+    } else if (cmdType == "trace.reply") {
+        RouteTrace trace;
+        try {
+            trace.streamId = static_cast<StreamId>(std::stoul(params["stream"]));
+            trace.source = params["source"];
+            trace.hops = static_cast<uint8_t>(std::stoul(params["hops"]));
+            trace.ttl = static_cast<uint8_t>(std::stoul(params["ttl"]));
+            trace.observedAtMs = std::stoull(params["at"]);
+            for (const auto& hop : splitList(params["path"])) {
+                trace.path.push_back(static_cast<uint16_t>(std::stoul(hop, nullptr, 16)));
+            }
+        } catch (const std::exception&) {
+            SABER_LOG(Warn, "protocol", "Percorso non valido da " << packet.getSource());
+            return;
+        }
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        remoteRouteTraces[packet.getSource()][trace.streamId] = trace;
+    } else if (cmdType == "party.start" || cmdType == "party.stop") {
+        PendingParty pending{cmdType == "party.start", PartyMode(), 0};
+        try {
+            pending.atMs = std::stoull(params["at"]);
+            if (pending.start) {
+                pending.party.zones = splitList(params["zones"]);
+                pending.party.streamId = static_cast<StreamId>(std::stoul(params["stream"]));
+                pending.party.bufferMs = static_cast<uint32_t>(std::stoul(params["buffer_ms"]));
+                pending.party.rejected = splitList(params["rejected"]);
+                pending.party.playlist = params["playlist"];
+                pending.party.startAtMs = pending.atMs;
+            }
+        } catch (const std::exception&) {
+            SABER_LOG(Warn, "protocol", "Modalità festa non valida da " << packet.getSource());
+            return;
+        }
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        pendingParty.push_back(pending);
+    } else if (cmdType == "intercom.start") {
+        IntercomSession session;
+        session.nodeA = params["a"];
+        session.nodeB = params["b"];
+        try {
+            session.startedAtMs = std::stoull(params["at"]);
+        } catch (const std::exception&) {
+            session.startedAtMs = 0;
+        }
+        if (session.nodeA.empty() || session.nodeB.empty() || session.nodeA == session.nodeB) {
+            SABER_LOG(Warn, "protocol", "Intercom non valido da " << packet.getSource());
+            return;
+        }
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        pendingIntercom.push_back(session);
+    } else if (cmdType == "intercom.stop") {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        pendingIntercom.push_back(std::nullopt);
+    } else if (cmdType == "standby.wake") {
+        // Il risveglio avviene nel thread di runtime, che può chiamare l'host e la rete
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        pendingWake = true;
+    } else if (cmdType == "intercom.talk") {
+        // Solo i nodi della conversazione possono premere il push-to-talk
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        if (intercomSession && intercomSession->involves(packet.getSource())) {
+            if (params["on"] == "1") {
+                intercomSession->talking.insert(packet.getSource());
+            } else {
+                intercomSession->talking.erase(packet.getSource());
+            }
+        }
+    }
+}
+
+void SaberProtocol::setStandbyHandler(std::function<void(bool)> handler) {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    standbyHandler = std::move(handler);
+}
+
+PowerState SaberProtocol::getPowerState() const {
+    return standbyController.getState();
+}
+
+void SaberProtocol::wakeFromStandby() {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    pendingWake = true;
+}
+
+bool SaberProtocol::wakeSinks(const std::string& nodeId) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    std::map<std::string, std::string> params;
+    if (!nodeId.empty()) {
+        params["target"] = nodeId;
+    }
+    meshNetwork->sendPacket(MeshPacket::createCommand("standby.wake", params));
+    return true;
+}
+
+void SaberProtocol::updateStandby() {
+    if (config.role != NodeRole::Sink) {
+        return;
+    }
+    
+    bool streaming;
+    std::optional<std::string> localZone;
+    {
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        if (!meshNetwork) {
+            return;
+        }
+        streaming = !subscribedStreams.empty();
+        localZone = meshNetwork->getZone(config.nodeId);
+    }
+    
+    bool wakeRequested;
+    std::function<void(bool)> handler;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        // Un avvio di gruppo per la zona risveglia il sink subito, non alla barriera
+        wakeRequested = pendingWake || std::any_of(pendingPlayback.begin(), pendingPlayback.end(),
+            [&](const PendingPlayback& pending) {
+                return pending.command.action == PlaybackAction::Start
+                    && (pending.command.zone == "all" || pending.command.zone == localZone);
+            });
+        pendingWake = false;
+        handler = standbyHandler;
+    }
+    
+    auto apply = [&](PowerState state) {
+        journal->append("power", powerStateToString(state), config.nodeId, "", syncManager->now());
+        if (state == PowerState::Standby) {
+            if (handler) {
+                handler(true);
+            }
+        } else if (state == PowerState::Waking) {
+            if (handler) {
+                handler(false);
+            }
+            // Rientro rapido: annuncio e stato partono subito invece che all'intervallo diradato
+            lastAdvertisementMs = 0;
+            std::lock_guard<std::mutex> lock(protocolMutex);
+            if (meshNetwork) {
+                uint32_t latency = audioSync ? audioSync->getCurrentLatency() : 0;
+                meshNetwork->sendPacket(MeshPacket::createStatus(config.nodeId, 0, latency));
+            }
+        }
+    };
+    
+    uint64_t now = static_cast<uint64_t>(steadyMillis());
+    if (wakeRequested) {
+        if (auto state = standbyController.wake(now)) {
+            apply(*state);
+        }
+    }
+    if (auto state = standbyController.update(streaming, now)) {
+        apply(*state);
+    }
+}
+
+std::string SaberProtocol::runStandbyCommand(const std::vector<std::string>& args) {
+    // Uso: standby status | standby wake [nodo]
+    if (args.size() == 1 && args[0] == "status") {
+        return "power=" + powerStateToString(getPowerState());
+    }
+    if (!args.empty() && args[0] == "wake" && args.size() <= 2) {
+        if (config.role != NodeRole::Master) {
+            wakeFromStandby();
+            return "ok";
+        }
+        if (!wakeSinks(args.size() == 2 ? args[1] : "")) {
+            throw std::invalid_argument("rete mesh non inizializzata");
+        }
+        return "ok";
+    }
+    throw std::invalid_argument("uso: standby status | standby wake [nodo]");
+}
+
+bool SaberProtocol::sendSourceFrame(const std::string& source, StreamId streamId, const AudioFrame& frame) {
+    if (!sourceSelector.offerFrame(source, frame, static_cast<uint64_t>(steadyMillis()))) {
+        return false;
+    }
+    return sendPcmFrame(streamId, frame);
+}
+
+void SaberProtocol::switchSource(const std::string& source) {
+    if (auto change = sourceSelector.switchTo(source)) {
+        recordSourceSwitch(*change);
+    }
+}
+
+void SaberProtocol::resumeAutomaticSource() {
+    sourceSelector.resumeAutomatic();
+}
+
+std::optional<std::string> SaberProtocol::getActiveSource() const {
+    return sourceSelector.getActive();
+}
+
+std::vector<SourceStatus> SaberProtocol::getSourceStatus() const {
+    return sourceSelector.getStatus(static_cast<uint64_t>(steadyMillis()));
+}
+
+void SaberProtocol::updateSources() {
+    if (config.role != NodeRole::Master) {
+        return;
+    }
+    if (auto change = sourceSelector.update(static_cast<uint64_t>(steadyMillis()))) {
+        recordSourceSwitch(*change);
+    }
+}
+
+void SaberProtocol::recordSourceSwitch(const SourceSwitch& change) {
+    std::string from = change.from.empty() ? "-" : change.from;
+    std::string to = change.to.empty() ? "-" : change.to;
+    if (change.reason == "stalled" || change.reason == "silent") {
+        SABER_LOG(Warn, "source", "Sorgente " << from << " ferma (" << change.reason << "), passo a " << to);
+    } else {
+        SABER_LOG(Info, "source", "Sorgente attiva: " << to << " (" << change.reason << ")");
+    }
+    journal->append("source", change.reason, config.nodeId, "from=" + from + " to=" + to, syncManager->now());
+}
+
+std::string SaberProtocol::runSourceCommand(const std::vector<std::string>& args) {
+    // Uso: source status | source switch <nome> | source auto
+    if (args.size() == 1 && args[0] == "status") {
+        std::string result = "active=" + getActiveSource().value_or("-") 
+                           + " mode=" + (sourceSelector.isManual() ? "manual" : "auto");
+        for (const auto& source : getSourceStatus()) {
+            result += "; " + source.name + " " + sourceKindToString(source.kind) 
+                    + " priority=" + std::to_string(source.priority)
+                    + " healthy=" + (source.healthy ? "1" : "0")
+                    + " idle=" + (source.idleMs ? std::to_string(*source.idleMs) + "ms" : "-");
+        }
+        return result;
+    }
+    if (args.size() == 2 && args[0] == "switch") {
+        switchSource(args[1]);
+        return "ok";
+    }
+    if (args.size() == 1 && args[0] == "auto") {
+        resumeAutomaticSource();
+        return "ok";
+    }
+    throw std::invalid_argument("uso: source status | source switch <nome> | source auto");
+}
+
+std::string SaberProtocol::runZoneCommand(const std::vector<std::string>& args) {
+    // Uso: zone delays | zone delay <zona> <ms>
+    if (args.size() == 1 && args[0] == "delays") {
+        std::string result;
+        for (const auto& entry : getZoneDelays()) {
+            result += (result.empty() ? "" : " ") + entry.first + "=" + std::to_string(entry.second) + "ms";
+        }
+        return result.empty() ? "-" : result;
+    }
+    if (args.size() == 3 && args[0] == "delay") {
+        uint32_t delayMs;
+        try {
+            delayMs = static_cast<uint32_t>(std::stoul(args[2]));
+        } catch (const std::exception&) {
+            throw std::invalid_argument("ritardo non valido: " + args[2]);
+        }
+        if (!setZoneDelay(args[1], delayMs)) {
+            throw std::invalid_argument("solo il Master imposta i ritardi di zona");
+        }
+        return "ok";
+    }
+    throw std::invalid_argument("uso: zone delays | zone delay <zona> <ms>");
+}
+
+bool SaberProtocol::setZoneDelay(const std::string& zone, uint32_t delayMs) {
+    if (delayMs > MAX_ZONE_DELAY_MS) {
+        throw std::invalid_argument("Ritardo di zona oltre " + std::to_string(MAX_ZONE_DELAY_MS) + "ms");
+    }
+    {
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        if (config.role != NodeRole::Master) {
+            SABER_LOG(Error, "protocol", "Solo il Master imposta i ritardi di zona");
+            return false;
+        }
+        if (delayMs == 0) {
+            config.zoneDelays.erase(zone);
+        } else {
+            config.zoneDelays[zone] = delayMs;
+        }
+    }
+    SABER_LOG(Info, "protocol", "Ritardo acustico della zona " << zone << " impostato a " << delayMs << "ms");
+    journal->append("mesh", "zone_delay", config.nodeId, "zone=" + zone + " delay_ms=" + std::to_string(delayMs),
+                    syncManager->now());
+    return true;
+}
+
+std::map<std::string, uint32_t> SaberProtocol::getZoneDelays() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    return config.zoneDelays;
+}
+
+uint32_t SaberProtocol::getAcousticDelayMs() const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    return acousticDelayMs;
+}
+
+DelayEqualizationStatus SaberProtocol::getDelayEqualization() const {
+    std::map<std::string, uint32_t> latencies;
+    {
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        if (config.role == NodeRole::Master && meshNetwork) {
+            latencies = meshNetwork->getNodeLatencies();
+        }
+    }
+    latencies.erase(config.nodeId);
+    
+    DelayEqualizationStatus status;
+    status.latencyMs = localLatencyMs();
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        status.deadlineMs = playoutDeadlineMs;
+    }
+    uint32_t slowestMs = 0;
+    for (const auto& entry : latencies) {
+        status.sinkDelaysMs[entry.first] = entry.second < status.deadlineMs ? status.deadlineMs - entry.second : 0;
+        if (status.slowestSink.empty() || entry.second > slowestMs) {
+            status.slowestSink = entry.first;
+            slowestMs = entry.second;
+        }
+    }
+    status.localDelayMs = status.latencyMs < status.deadlineMs ? status.deadlineMs - status.latencyMs : 0;
+    return status;
+}
+
+uint32_t SaberProtocol::localLatencyMs() const {
+    uint32_t latency = audioSync ? audioSync->getCurrentLatency() : 0;
+    uint64_t outputUs = 0;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        for (const auto& output : audioOutputs) {
+            outputUs = std::max(outputUs, output.second->getOutput()->getLatencyUs());
+        }
+    }
+    return latency + static_cast<uint32_t>(outputUs / 1000);
+}
+
+void SaberProtocol::updateDelayEqualization() {
+    std::map<std::string, uint32_t> latencies;
+    std::vector<std::string> active;
+    DelayEqualizationConfig settings;
+    uint32_t requiredMs;
+    uint32_t budgetMs;
+    {
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        if (config.role != NodeRole::Master || !meshNetwork) {
+            return;
+        }
+        settings = config.delayEqualization;
+        latencies = meshNetwork->getNodeLatencies();
+        active = meshNetwork->getActiveNodes();
+        latencies.erase(config.nodeId);
+        
+        // Senza equalizzazione l'anticipo resta quello di specifica
+        requiredMs = config.spec.defaultBufferMs;
+        if (settings.enabled) {
+            std::vector<std::string> sinks;
+            for (const auto& entry : latencies) {
+                sinks.push_back(entry.first);
+            }
+            requiredMs = std::max(requiredMs, planner.zoneBufferMs(sinks, latencies));
+        }
+        budgetMs = planner.getBudgetMs();
+    }
+    
+    const DelayEqualizationConfig& current = delayEqualizer.getConfig();
+    if (current.enabled != settings.enabled || current.holdMs != settings.holdMs) {
+        delayEqualizer.setConfig(settings);
+    }
+    auto deadline = delayEqualizer.update(requiredMs, steadyMillis());
+    if (deadline) {
+        if (*deadline > budgetMs) {
+            SABER_LOG(Warn, "protocol", "Scadenza di riproduzione di " << *deadline 
+                      << "ms oltre il budget di latenza di " << budgetMs << "ms");
+        } else {
+            SABER_LOG(Info, "protocol", "Scadenza di riproduzione comune a " << *deadline << "ms");
+        }
+        journal->append("latency", "deadline", config.nodeId, std::to_string(*deadline) + "ms", 
+                        syncManager->now());
+        // Le sorgenti in trasmissione spostano l'istante dei frame successivi
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        for (const auto& source : audioSources) {
+            source.second->setLeadUs(static_cast<uint64_t>(*deadline) * 1000);
+        }
+        std::lock_guard<std::mutex> eventsLock(eventsMutex);
+        playoutDeadlineMs = *deadline;
+    }
+    
+    // Come per i ritardi di zona, i nodi tornati attivi ricevono di nuovo la scadenza
+    std::set<std::string> targets;
+    for (const auto& nodeId : active) {
+        if (nodeId != config.nodeId) {
+            targets.insert(nodeId);
+        }
+    }
+    bool missing = std::any_of(targets.begin(), targets.end(), 
+                               [this](const std::string& nodeId) { return deadlineNodes.count(nodeId) == 0; });
+    deadlineNodes = targets;
+    if ((!deadline && !missing) || targets.empty()) {
+        return;
+    }
+    meshNetwork->sendPacket(MeshPacket::createCommand("playout.deadline", {
+        {"deadline_ms", std::to_string(delayEqualizer.getDeadlineMs())}
+    }));
+}
+
+void SaberProtocol::propagateZoneDelays() {
+    std::map<std::string, uint32_t> delays;
+    std::map<std::string, std::string> zones;
+    std::vector<std::string> active;
+    {
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        if (config.role != NodeRole::Master || !meshNetwork) {
+            return;
+        }
+        delays = config.zoneDelays;
+        zones = meshNetwork->getZones();
+        active = meshNetwork->getActiveNodes();
+    }
+    
+    // I nodi non più attivi vengono dimenticati: al rientro ricevono di nuovo il ritardo
+    for (auto it = propagatedZoneDelays.begin(); it != propagatedZoneDelays.end();) {
+        if (std::find(active.begin(), active.end(), it->first) == active.end()) {
+            it = propagatedZoneDelays.erase(it);
+        } else {
+            ++it;
+        }
+    }
+    
+    // Un sink senza ritardo per la propria zona riceve 0, così ne perde uno vecchio
+    for (const auto& nodeId : active) {
+        auto zone = zones.find(nodeId);
+        if (nodeId == config.nodeId || zone == zones.end()) {
+            continue;
+        }
+        auto delay = delays.find(zone->second);
+        uint32_t delayMs = delay != delays.end() ? delay->second : 0;
+        auto sent = propagatedZoneDelays.find(nodeId);
+        if (sent != propagatedZoneDelays.end() ? sent->second == delayMs : delayMs == 0) {
+            continue;
+        }
+        meshNetwork->sendPacket(MeshPacket::createCommand("zone.delay", {
+            {"target", nodeId}, {"zone", zone->second}, {"delay_ms", std::to_string(delayMs)},
+        }));
+        propagatedZoneDelays[nodeId] = delayMs;
+    }
+}
+
+void SaberProtocol::propagateZones() {
+    std::map<std::string, std::string> zones;
+    std::vector<std::string> active;
+    {
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        if (config.role != NodeRole::Master || !meshNetwork) {
+            return;
+        }
+        zones = meshNetwork->getZones();
+        active = meshNetwork->getActiveNodes();
+    }
+    
+    // Come per i ritardi, un nodo tornato attivo riceve di nuovo la propria zona
+    for (auto it = propagatedZones.begin(); it != propagatedZones.end();) {
+        if (std::find(active.begin(), active.end(), it->first) == active.end()) {
+            it = propagatedZones.erase(it);
+        } else {
+            ++it;
+        }
+    }
+    
+    for (const auto& nodeId : active) {
+        auto zone = zones.find(nodeId);
+        if (nodeId == config.nodeId || zone == zones.end()) {
+            continue;
+        }
+        auto sent = propagatedZones.find(nodeId);
+        if (sent != propagatedZones.end() && sent->second == zone->second) {
+            continue;
+        }
+        meshNetwork->sendPacket(MeshPacket::createZoneCommand(zone->second, "zone.assign", {{"node", nodeId}}));
+        propagatedZones[nodeId] = zone->second;
+    }
+}
+
+bool SaberProtocol::publishSimulcast(const SimulcastGroup& group, bool isMusic) {
+    validateSimulcastGroup(group);
+    if (config.role != NodeRole::Master) {
+        SABER_LOG(Error, "protocol", "Solo il Master può pubblicare stream");
+        return false;
+    }
+    for (const auto& layer : group.layers) {
+        setStreamFormat(layer.streamId, isMusic);
+    }
+    
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    // I livelli sono numerati in parallelo, così i sink passano dall'uno all'altro senza buchi
+    uint32_t sequence = audioSequences[group.streamId];
+    for (const auto& layer : group.layers) {
+        meshNetwork->publishStream(layer.streamId);
+        config.publishedStreams.insert(layer.streamId);
+        audioSync->setStreamBitrate(layer.streamId, layer.bitrateKbps);
+        audioSequences[layer.streamId] = sequence;
+    }
+    simulcastGroups[group.streamId] = group;
+    
+    std::string layers = encodeSimulcastLayers(group.layers);
+    meshNetwork->sendPacket(MeshPacket::createCommand("simulcast.announce", {
+        {"stream", std::to_string(group.streamId)}, {"layers", layers},
+    }));
+    SABER_LOG(Info, "protocol", "Stream " << group.streamId << " in simulcast: " << layers);
+    journal->append("audio", "simulcast", config.nodeId, "stream=" + std::to_string(group.streamId) + 
+                    " layers=" + layers, syncManager->now());
+    return true;
+}
+
+bool SaberProtocol::stopSimulcast(StreamId streamId) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    auto group = simulcastGroups.find(streamId);
+    if (group == simulcastGroups.end()) {
+        return false;
+    }
+    if (audioSync) {
+        audioSync->setStreamBitrate(streamId, 0);
+    }
+    simulcastGroups.erase(group);
+    if (meshNetwork) {
+        meshNetwork->sendPacket(MeshPacket::createCommand("simulcast.announce", {
+            {"stream", std::to_string(streamId)}, {"layers", ""},
+        }));
+    }
+    journal->append("audio", "simulcast_stopped", config.nodeId, "stream=" + std::to_string(streamId),
+                    syncManager->now());
+    return true;
+}
+
+std::vector<SimulcastGroup> SaberProtocol::getSimulcastGroups() const {
+    std::vector<SimulcastGroup> groups;
+    if (config.role == NodeRole::Master) {
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        for (const auto& entry : simulcastGroups) {
+            groups.push_back(entry.second);
+        }
+    } else {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        for (const auto& entry : announcedSimulcast) {
+            groups.push_back(entry.second);
+        }
+    }
+    return groups;
+}
+
+void SaberProtocol::setSimulcastBudget(std::optional<uint32_t> budgetKbps) {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    simulcastBudgetKbps = budgetKbps;
+}
+
+std::vector<SimulcastStatus> SaberProtocol::getSimulcastStatus() const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    std::vector<SimulcastStatus> statuses;
+    for (const auto& entry : simulcastReceivers) {
+        statuses.push_back(entry.second->getStatus());
+    }
+    return statuses;
+}
+
+std::string SaberProtocol::runSimulcastCommand(const std::vector<std::string>& args) {
+    // Uso: simulcast status | simulcast budget <kbps|auto>
+    if (args.size() == 1 && args[0] == "status") {
+        std::string result;
+        if (config.role == NodeRole::Master) {
+            for (const auto& group : getSimulcastGroups()) {
+                result += (result.empty() ? "" : " ") + std::to_string(group.streamId) + "=" +
+                          encodeSimulcastLayers(group.layers);
+            }
+        } else {
+            for (const auto& status : getSimulcastStatus()) {
+                result += (result.empty() ? "" : " ") + std::to_string(status.streamId) + "=" +
+                          std::to_string(status.activeLayer) + "@" + std::to_string(status.bitrateKbps) + "kbps";
+                if (status.pendingLayer) {
+                    result += "->" + std::to_string(*status.pendingLayer);
+                }
+            }
+        }
+        return result.empty() ? "-" : result;
+    }
+    if (args.size() == 2 && args[0] == "budget") {
+        if (args[1] == "auto") {
+            setSimulcastBudget(std::nullopt);
+            return "ok";
+        }
+        try {
+            setSimulcastBudget(static_cast<uint32_t>(std::stoul(args[1])));
+        } catch (const std::exception&) {
+            throw std::invalid_argument("banda non valida: " + args[1]);
+        }
+        return "ok";
+    }
+    throw std::invalid_argument("uso: simulcast status | simulcast budget <kbps|auto>");
+}
+
+std::string SaberProtocol::runTopologyCommand(const std::vector<std::string>& args) {
+    // Uso: topology | topology dot | topology json
+    if (args.size() == 1 && (args[0] == "dot" || args[0] == "json")) {
+        return exportTopologyGraph(args[0]);
+    }
+    if (!args.empty()) {
+        throw std::invalid_argument("uso: topology | topology dot | topology json");
+    }
+    std::string result;
+    for (const auto& link : getTopology().links) {
+        result += (result.empty() ? "" : " ") + link.from + ">" + link.to;
+        if (link.rssiDbm) {
+            result += "@" + std::to_string(*link.rssiDbm) + "dBm";
+        }
+    }
+    return result.empty() ? "-" : result;
+}
+
+void SaberProtocol::reportNeighbors() {
+    int64_t now = steadyMillis();
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    if (!meshNetwork || now - lastNeighborReportMs < TOPOLOGY_REPORT_INTERVAL_MS) {
+        return;
+    }
+    // Anche un resoconto vuoto viene inviato: ritira i collegamenti riportati in precedenza
+    lastNeighborReportMs = now;
+    meshNetwork->sendPacket(MeshPacket::createCommand("topology.neighbors", {
+        {"peers", encodeNeighborReport(meshNetwork->getTopology().links)},
+    }));
+}
+
+void SaberProtocol::announceSimulcast() {
+    int64_t now = steadyMillis();
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    if (config.role != NodeRole::Master || !meshNetwork || simulcastGroups.empty() ||
+        now - lastSimulcastAnnounceMs < SIMULCAST_ANNOUNCE_INTERVAL_MS) {
+        return;
+    }
+    // L'annuncio periodico raggiunge anche i sink entrati dopo la pubblicazione
+    lastSimulcastAnnounceMs = now;
+    for (const auto& entry : simulcastGroups) {
+        meshNetwork->sendPacket(MeshPacket::createCommand("simulcast.announce", {
+            {"stream", std::to_string(entry.first)}, {"layers", encodeSimulcastLayers(entry.second.layers)},
+        }));
+    }
+}
+
+void SaberProtocol::updateSimulcast() {
+    std::set<StreamId> subscribed;
+    SimulcastConfig simulcastConfig;
+    {
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        if (config.role != NodeRole::Sink || !meshNetwork) {
+            return;
+        }
+        subscribed = subscribedStreams;
+        simulcastConfig = config.simulcast;
+    }
+    
+    uint64_t now = static_cast<uint64_t>(steadyMillis());
+    std::vector<StreamId> subscribe;
+    std::vector<StreamId> unsubscribe;
+    std::vector<SimulcastGroup> created;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        // Un gruppo ritirato o cambiato riporta il sink allo stream stesso
+        for (auto it = simulcastReceivers.begin(); it != simulcastReceivers.end();) {
+            auto announced = announcedSimulcast.find(it->first);
+            auto current = it->second->getGroup();
+            bool unchanged = announced != announcedSimulcast.end() && subscribed.count(it->first) != 0 &&
+                             encodeSimulcastLayers(announced->second.layers) == encodeSimulcastLayers(current.layers);
+            if (unchanged) {
+                ++it;
+                continue;
+            }
+            bool base = false;
+            for (StreamId layer : simulcastLayers[it->first]) {
+                if (layer == it->first) {
+                    base = true;
+                } else {
+                    unsubscribe.push_back(layer);
+                }
+            }
+            if (!base && subscribed.count(it->first) != 0) {
+                subscribe.push_back(it->first);
+            }
+            simulcastLayers.erase(it->first);
+            it = simulcastReceivers.erase(it);
+        }
+        
+        // Un ricevitore per ogni stream sottoscritto di cui il Master ha annunciato i livelli
+        for (const auto& entry : announcedSimulcast) {
+            if (subscribed.count(entry.first) == 0 || simulcastReceivers.count(entry.first) != 0) {
+                continue;
+            }
+            simulcastReceivers[entry.first] = std::make_unique<SimulcastReceiver>(entry.second, simulcastConfig);
+            simulcastLayers[entry.first] = {entry.first};
+            created.push_back(entry.second);
+        }
+        
+        // Durante un cambio il sink riceve sia il livello corrente sia quello richiesto
+        for (auto& entry : simulcastReceivers) {
+            if (!entry.second->update(simulcastBudgetKbps, now)) {
+                continue;
+            }
+            auto wanted = entry.second->wantedLayers();
+            auto& current = simulcastLayers[entry.first];
+            for (StreamId layer : wanted) {
+                if (std::find(current.begin(), current.end(), layer) == current.end()) {
+                    subscribe.push_back(layer);
+                }
+            }
+            for (StreamId layer : current) {
+                if (std::find(wanted.begin(), wanted.end(), layer) == wanted.end()) {
+                    unsubscribe.push_back(layer);
+                }
+            }
+            current = wanted;
+        }
+    }
+    
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    if (!meshNetwork) {
+        return;
+    }
+    // I livelli hanno il formato dello stream del gruppo
+    for (const auto& group : created) {
+        StreamFormat format = audioSync->getStreamFormat(group.streamId);
+        for (const auto& layer : group.layers) {
+            if (layer.streamId != group.streamId) {
+                audioSync->setStreamFormat(layer.streamId, format.sampleRateHz == spec::SAMPLE_RATE_MUSIC_HZ,
+                                           format.channels);
+            }
+        }
+    }
+    for (StreamId layer : subscribe) {
+        meshNetwork->sendPacket(MeshPacket::createSubscribe(config.nodeId, layer));
+    }
+    for (StreamId layer : unsubscribe) {
+        meshNetwork->sendPacket(MeshPacket::createUnsubscribe(config.nodeId, layer));
+    }
+}
+
+SimulcastVerdict SaberProtocol::acceptSimulcastFrame(const MeshPacket::AudioFrameInfo& info, StreamId& streamId) {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    for (auto& entry : simulcastReceivers) {
+        if (entry.second->hasLayer(info.streamId)) {
+            streamId = entry.first;
+            return entry.second->accept(info.streamId, info.frameSequence, info.playoutTimeUs,
+                                        static_cast<uint64_t>(steadyMillis()));
+        }
+    }
+    return SimulcastVerdict::Play;
+}
+
+bool SaberProtocol::configureBassManagement(const std::string& zone, const std::string& subwooferNodeId,
+                                            float crossoverHz) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    if (crossoverHz <= 0.0f) {
+        SABER_LOG(Error, "protocol", "Frequenza di crossover non valida");
+        return false;
+    }
+    
+    auto zones = meshNetwork->getZones();
+    auto sub = zones.find(subwooferNodeId);
+    if (sub == zones.end() || sub->second != zone) {
+        SABER_LOG(Error, "protocol", "Il subwoofer " << subwooferNodeId << " non appartiene alla zona " << zone);
+        return false;
+    }
+    
+    // Tutti i sink della zona ricevono lo stesso crossover, filtrato localmente
+    for (const auto& member : zones) {
+        if (member.second != zone) {
+            continue;
+        }
+        std::string role = member.first == subwooferNodeId ? "subwoofer" : "satellite";
+        meshNetwork->sendPacket(MeshPacket::createCommand("bass.configure", {
+            {"target", member.first},
+            {"role", role},
+            {"crossover_hz", std::to_string(crossoverHz)},
+        }));
+    }
+    return true;
+}
+
+BassSettings SaberProtocol::getBassSettings() const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    return bassSettings;
+}
+
+bool SaberProtocol::publishStreamMetadata(const StreamMetadata& metadata, const std::vector<uint8_t>& artwork) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork || !crypto) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    StreamMetadata published = metadata;
+    auto current = meshNetwork->getStreamMetadata(metadata.streamId);
+    published.revision = current ? current->revision + 1 : 1;
+    published.artworkHash.clear();
+    
+    if (!artwork.empty()) {
+        published.artworkHash = artworkHashHex(*crypto, artwork);
+        std::lock_guard<std::mutex> eventsLock(eventsMutex);
+        if (artworkStore.size() >= MAX_ARTWORK_ENTRIES && artworkStore.count(published.artworkHash) == 0) {
+            artworkStore.erase(artworkStore.begin());
+        }
+        artworkStore[published.artworkHash] = artwork;
+    }
+    
+    meshNetwork->updateStreamMetadata(published);
+    meshNetwork->sendPacket(MeshPacket::createMetadata(published));
+    return true;
+}
+
+std::optional<StreamMetadata> SaberProtocol::getStreamMetadata(StreamId streamId) const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return std::nullopt;
+    }
+    
+    return meshNetwork->getStreamMetadata(streamId);
+}
+
+bool SaberProtocol::requestArtwork(const std::string& artworkHash) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    if (artworkHash.empty()) {
+        return false;
+    }
+    
+    {
+        std::lock_guard<std::mutex> eventsLock(eventsMutex);
+        if (artworkStore.count(artworkHash) != 0) {
+            return true;
+        }
+    }
+    
+    meshNetwork->sendPacket(MeshPacket::createCommand("artwork.get", {{"hash", artworkHash}}));
+    return true;
+}
+
+std::optional<std::vector<uint8_t>> SaberProtocol::getArtwork(const std::string& artworkHash) const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    auto it = artworkStore.find(artworkHash);
+    if (it == artworkStore.end()) {
+        return std::nullopt;
+    }
+    return it->second;
+}
+
+NodeInfo SaberProtocol::getNodeInfo() const {
+    NodeInfo info;
+    info.nodeId = config.nodeId;
+    info.role = config.role;
+    info.isSynchronized = isSynchronized();
+    info.latency = getCurrentLatency();
+    
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        info.activePlaylist = activePlaylist;
+    }
+    
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    if (meshNetwork) {
+        info.nowPlaying = meshNetwork->getAllStreamMetadata();
+    }
+    return info;
+}
+
+BandwidthReport SaberProtocol::getBandwidthReport() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return {};
+    }
+    
+    return meshNetwork->getBandwidthReport();
+}
+
+NetworkStats SaberProtocol::getNetworkStats() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return {};
+    }
+    
+    return meshNetwork->getNetworkStats();
+}
+
+std::string SaberProtocol::getPrometheusMetrics() const {
+    std::string body = MeshStats::toPrometheus(getBandwidthReport(), config.nodeId);
+    body += LatencyStats::toPrometheus(getNetworkStats(), config.nodeId);
+    body += "# HELP saber_synchronized Orologio allineato al Master (1) o in attesa di sincronizzazione (0)\n";
+    body += "# TYPE saber_synchronized gauge\n";
+    body += "saber_synchronized{node=\"" + config.nodeId + "\"} " + (isSynchronized() ? "1" : "0") + "\n";
+    body += "# HELP saber_latency_ms Latenza corrente del nodo locale\n";
+    body += "# TYPE saber_latency_ms gauge\n";
+    body += "saber_latency_ms{node=\"" + config.nodeId + "\"} " + std::to_string(getCurrentLatency()) + "\n";
+    auto peers = getActiveNodeInfo();
+    body += "# HELP saber_peer_buffer_percent Buffer disponibile riportato da ciascun nodo attivo\n";
+    body += "# TYPE saber_peer_buffer_percent gauge\n";
+    for (const auto& peer : peers) {
+        body += "saber_peer_buffer_percent{node=\"" + config.nodeId + "\",peer=\"" + peer.nodeId + "\"} " 
+              + std::to_string(peer.bufferState) + "\n";
+    }
+    body += "# HELP saber_peer_last_seen_ms Tempo trascorso dall'ultimo ping di ciascun nodo attivo\n";
+    body += "# TYPE saber_peer_last_seen_ms gauge\n";
+    for (const auto& peer : peers) {
+        body += "saber_peer_last_seen_ms{node=\"" + config.nodeId + "\",peer=\"" + peer.nodeId + "\"} " 
+              + std::to_string(peer.lastSeenMs.value_or(0)) + "\n";
+    }
+    body += "# HELP saber_congested Canale radio congestionato (1) o libero (0)\n";
+    body += "# TYPE saber_congested gauge\n";
+    body += "saber_congested{node=\"" + config.nodeId + "\"} " 
+          + (getCongestionState() == CongestionState::Congested ? "1" : "0") + "\n";
+    AdmissionStats admission = getAdmissionStats();
+    body += "# HELP saber_mesh_nodes Nodi membri della rete per ruolo\n";
+    body += "# TYPE saber_mesh_nodes gauge\n";
+    body += "saber_mesh_nodes{node=\"" + config.nodeId + "\",role=\"all\"} " 
+          + std::to_string(admission.nodes) + "\n";
+    body += "saber_mesh_nodes{node=\"" + config.nodeId + "\",role=\"sink\"} " 
+          + std::to_string(admission.sinks) + "\n";
+    body += "# HELP saber_admission_rejected_total Nodi respinti perché la rete era al completo\n";
+    body += "# TYPE saber_admission_rejected_total counter\n";
+    body += "saber_admission_rejected_total{node=\"" + config.nodeId + "\"} " 
+          + std::to_string(admission.rejected) + "\n";
+    auto drops = getDropCounters();
+    body += "# HELP saber_packets_dropped_total Pacchetti scartati per motivo\n";
+    body += "# TYPE saber_packets_dropped_total counter\n";
+    for (const auto& counter : drops) {
+        body += "saber_packets_dropped_total{node=\"" + config.nodeId + "\",reason=\"" + counter.first 
+              + "\"} " + std::to_string(counter.second) + "\n";
+    }
+    // Gli scarti dovuti a firme, chiavi ed epoche, per gli allarmi sulla sicurezza
+    body += "# HELP saber_crypto_failures_total Pacchetti respinti dalle verifiche crittografiche per motivo\n";
+    body += "# TYPE saber_crypto_failures_total counter\n";
+    for (RejectReason reason : {RejectReason::BadSignature, RejectReason::UnknownSender,
+                                RejectReason::WrongNetworkKey, RejectReason::StaleEpoch}) {
+        std::string name = rejectReasonToString(reason);
+        auto counter = drops.find(name);
+        body += "saber_crypto_failures_total{node=\"" + config.nodeId + "\",reason=\"" + name + "\"} " 
+              + std::to_string(counter != drops.end() ? counter->second : 0) + "\n";
+    }
+    body += "# HELP saber_packets_rate_limited_total Pacchetti oltre il limite di traffico per tipo\n";
+    body += "# TYPE saber_packets_rate_limited_total counter\n";
+    for (const auto& counter : getRateLimitStats().droppedByType) {
+        body += "saber_packets_rate_limited_total{node=\"" + config.nodeId + "\",type=\"" 
+              + meshPacketTypeToString(counter.first) + "\"} " + std::to_string(counter.second) + "\n";
+    }
+    auto queues = getPacketQueueStats();
+    body += "# HELP saber_packet_queue_depth Pacchetti in attesa di elaborazione per classe di priorità\n";
+    body += "# TYPE saber_packet_queue_depth gauge\n";
+    for (const auto& queue : queues) {
+        body += "saber_packet_queue_depth{node=\"" + config.nodeId + "\",class=\"" 
+              + packetClassToString(queue.first) + "\"} " + std::to_string(queue.second.queued) + "\n";
+    }
+    body += "# HELP saber_packets_processed_total Pacchetti estratti dalla coda ed elaborati per classe di priorità\n";
+    body += "# TYPE saber_packets_processed_total counter\n";
+    for (const auto& queue : queues) {
+        body += "saber_packets_processed_total{node=\"" + config.nodeId + "\",class=\"" 
+              + packetClassToString(queue.first) + "\"} " + std::to_string(queue.second.dequeued) + "\n";
+    }
+    body += "# HELP saber_packet_queue_dropped_total Pacchetti scartati a coda piena per classe di priorità\n";
+    body += "# TYPE saber_packet_queue_dropped_total counter\n";
+    for (const auto& queue : queues) {
+        body += "saber_packet_queue_dropped_total{node=\"" + config.nodeId + "\",class=\"" 
+              + packetClassToString(queue.first) + "\"} " + std::to_string(queue.second.dropped) + "\n";
+    }
+    RepairStats repair = getRepairStats();
+    const std::pair<const char*, uint64_t> repairCounters[] = {
+        {"saber_repair_nacks_sent_total", repair.nacksSent},
+        {"saber_repair_nacks_received_total", repair.nacksReceived},
+        {"saber_repair_retransmitted_total", repair.retransmitted},
+        {"saber_repair_refused_late_total", repair.refusedLate},
+        {"saber_repair_cache_misses_total", repair.cacheMisses},
+        {"saber_repair_repaired_total", repair.repaired},
+        {"saber_repair_expired_total", repair.expired},
+    };
+    for (const auto& counter : repairCounters) {
+        body += std::string("# TYPE ") + counter.first + " counter\n";
+        body += std::string(counter.first) + "{node=\"" + config.nodeId + "\"} " 
+              + std::to_string(counter.second) + "\n";
+    }
+    ReliableStats reliable = getReliableStats();
+    const std::pair<const char*, uint64_t> reliableCounters[] = {
+        {"saber_reliable_sent_total", reliable.sent},
+        {"saber_reliable_retransmitted_total", reliable.retransmitted},
+        {"saber_reliable_acknowledged_total", reliable.acknowledged},
+        {"saber_reliable_failed_total", reliable.failed},
+        {"saber_reliable_acks_sent_total", reliable.acksSent},
+        {"saber_reliable_duplicates_total", reliable.duplicates},
+    };
+    for (const auto& counter : reliableCounters) {
+        body += std::string("# TYPE ") + counter.first + " counter\n";
+        body += std::string(counter.first) + "{node=\"" + config.nodeId + "\"} " 
+              + std::to_string(counter.second) + "\n";
+    }
+    SessionStats sessions = getSessionStats();
+    body += "# HELP saber_session_key_derivations_total Chiavi di sessione derivate con i peer\n";
+    body += "# TYPE saber_session_key_derivations_total counter\n";
+    body += "saber_session_key_derivations_total{node=\"" + config.nodeId + "\"} " 
+          + std::to_string(sessions.derivations) + "\n";
+    body += "# HELP saber_session_keys Peer con una chiave di sessione in cache\n";
+    body += "# TYPE saber_session_keys gauge\n";
+    body += "saber_session_keys{node=\"" + config.nodeId + "\"} " + std::to_string(sessions.sessions) + "\n";
+    body += "# HELP saber_standby Sink in standby (1) o con il dispositivo audio acceso (0)\n";
+    body += "# TYPE saber_standby gauge\n";
+    body += "saber_standby{node=\"" + config.nodeId + "\"} " 
+          + (getPowerState() == PowerState::Standby ? "1" : "0") + "\n";
+    body += "# HELP saber_degradation_level Gradino della scala di degrado (0 = nominale)\n";
+    body += "# TYPE saber_degradation_level gauge\n";
+    body += "saber_degradation_level{node=\"" + config.nodeId + "\"} " 
+          + std::to_string(static_cast<int>(getDegradationSettings().level)) + "\n";
+    auto clockStats = getSyncManager()->getClockStats();
+    body += "# HELP saber_playout_deadline_ms Scadenza di riproduzione comune a tutti i sink\n";
+    body += "# TYPE saber_playout_deadline_ms gauge\n";
+    body += "saber_playout_deadline_ms{node=\"" + config.nodeId + "\"} " 
+          + std::to_string(getDelayEqualization().deadlineMs) + "\n";
+    body += "# HELP saber_clock_skew_ppm Deriva stimata dell'orologio locale rispetto al Master\n";
+    body += "# TYPE saber_clock_skew_ppm gauge\n";
+    body += "saber_clock_skew_ppm{node=\"" + config.nodeId + "\"} "
+          + std::to_string(clockStats.skewPpm) + "\n";
+    body += "# TYPE saber_clock_steps_total counter\n";
+    body += "saber_clock_steps_total{node=\"" + config.nodeId + "\"} "
+          + std::to_string(clockStats.steps) + "\n";
+    auto jitter = getJitterBufferStats();
+    body += "# HELP saber_jitter_buffer_ms Dimensione corrente del buffer di jitter\n";
+    body += "# TYPE saber_jitter_buffer_ms gauge\n";
+    body += "saber_jitter_buffer_ms{node=\"" + config.nodeId + "\"} " 
+          + std::to_string(jitter.bufferMs) + "\n";
+    body += "# TYPE saber_jitter_buffer_underruns_total counter\n";
+    body += "saber_jitter_buffer_underruns_total{node=\"" + config.nodeId + "\"} " 
+          + std::to_string(jitter.underruns) + "\n";
+    body += "# TYPE saber_jitter_buffer_overruns_total counter\n";
+    body += "saber_jitter_buffer_overruns_total{node=\"" + config.nodeId + "\"} " 
+          + std::to_string(jitter.overruns) + "\n";
+    auto timings = getPipelineTimings();
+    if (!timings.empty()) {
+        body += "# HELP saber_stage_duration_us Durata delle fasi della pipeline audio\n";
+        body += "# TYPE saber_stage_duration_us summary\n";
+    }
+    for (const auto& timing : timings) {
+        std::string labels = "node=\"" + config.nodeId + "\",stage=\"" 
+                           + pipelineStageToString(timing.stage) + "\"";
+        const std::pair<const char*, uint64_t> quantiles[] = {
+            {"0.5", timing.p50Us}, {"0.95", timing.p95Us}, {"0.99", timing.p99Us},
+        };
+        for (const auto& quantile : quantiles) {
+            body += "saber_stage_duration_us{" + labels + ",quantile=\"" + quantile.first + "\"} " 
+                  + std::to_string(quantile.second) + "\n";
+        }
+        body += "saber_stage_duration_us_count{" + labels + "} " + std::to_string(timing.total) + "\n";
+    }
+    auto phantomStats = getPhantomStats();
+    if (!phantomStats.empty()) {
+        body += "# TYPE saber_phantom_frames_total counter\n";
+        body += "# TYPE saber_phantom_late_frames_total counter\n";
+        body += "# TYPE saber_phantom_lead_ms gauge\n";
+    }
+    for (const auto& stream : phantomStats) {
+        std::string labels = "{node=\"" + config.nodeId + "\",stream=\"" 
+                           + std::to_string(stream.streamId) + "\"} ";
+        body += "saber_phantom_frames_total" + labels + std::to_string(stream.framesConsumed) + "\n";
+        body += "saber_phantom_late_frames_total" + labels + std::to_string(stream.lateFrames) + "\n";
+        body += "saber_phantom_lead_ms" + labels + std::to_string(stream.lastLeadMs) + "\n";
+    }
+    if (auto bridge = getA2dpBridgeStats()) {
+        std::string labels = "{node=\"" + config.nodeId + "\",device=\"" + bridge->device + "\"} ";
+        body += "# HELP saber_a2dp_late_frames_total Frame arrivati dopo l'istante di emissione verso le cuffie\n";
+        body += "# TYPE saber_a2dp_late_frames_total counter\n";
+        body += "saber_a2dp_late_frames_total" + labels + std::to_string(bridge->lateFrames) + "\n";
+        body += "# TYPE saber_a2dp_lead_ms gauge\n";
+        body += "saber_a2dp_lead_ms" + labels + std::to_string(bridge->lastLeadMs) + "\n";
+    }
+    auto syncProbes = getSyncProbeResults();
+    if (!syncProbes.empty()) {
+        body += "# HELP saber_sync_skew_us Sfasamento misurato dell'orologio di un altro sink\n";
+        body += "# TYPE saber_sync_skew_us gauge\n";
+        body += "# TYPE saber_sync_within_tolerance gauge\n";
+    }
+    for (const auto& probe : syncProbes) {
+        std::string labels = "{node=\"" + config.nodeId + "\",peer=\"" + probe.peer + "\"} ";
+        body += "saber_sync_skew_us" + labels + std::to_string(probe.meanSkewUs) + "\n";
+        body += "saber_sync_within_tolerance" + labels + (probe.withinTolerance ? "1" : "0") + "\n";
+    }
+    // Ultima sessione conclusa di ogni sink, per individuare i diffusori problematici
+    std::map<std::string, SessionReport> lastSessions;
+    for (const auto& report : getSessionReports()) {
+        lastSessions[report.nodeId] = report;
+    }
+    if (!lastSessions.empty()) {
+        body += "# HELP saber_session_frames Frame dell'ultima sessione conclusa per esito\n";
+        body += "# TYPE saber_session_frames gauge\n";
+        body += "# TYPE saber_session_rebuffers gauge\n";
+        body += "# TYPE saber_session_max_latency_ms gauge\n";
+    }
+    for (const auto& entry : lastSessions) {
+        const SessionReport& report = entry.second;
+        std::string labels = "node=\"" + report.nodeId + "\",stream=\"" 
+                           + std::to_string(report.streamId) + "\"";
+        body += "saber_session_frames{" + labels + ",outcome=\"played\"} " 
+              + std::to_string(report.framesPlayed) + "\n";
+        body += "saber_session_frames{" + labels + ",outcome=\"concealed\"} " 
+              + std::to_string(report.framesConcealed) + "\n";
+        body += "saber_session_frames{" + labels + ",outcome=\"dropped\"} " 
+              + std::to_string(report.framesDropped) + "\n";
+        body += "saber_session_rebuffers{" + labels + "} " + std::to_string(report.rebufferCount) + "\n";
+        body += "saber_session_max_latency_ms{" + labels + "} " 
+              + std::to_string(report.maxLatencyMs) + "\n";
+    }
+    return body;
+}
+
+std::string SaberProtocol::exportState() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    NodeState exported;
+    exported.exportedAtMs = syncManager->now();
+    exported.nodeId = config.nodeId;
+    exported.role = config.role;
+    exported.profile = config.profile;
+    exported.latencyMode = config.spec.latencyMode;
+    exported.publishedStreams = config.publishedStreams;
+    exported.listenStreams = config.listenStreams;
+    exported.voiceStreams = config.voiceStreams;
+    exported.keystoreFile = config.keystoreFile;
+    
+    // I segreti non lasciano mai il nodo: solo il riferimento con cui sono stati configurati
+    const std::map<std::string, const SecretString*> secrets = {
+        {"security.passphrase", &config.networkPassphrase},
+        {"security.broadcast_code", &config.broadcastCode},
+        {"mqtt.password", &config.mqttPassword},
+        {"security.identity_key", &config.identityKey},
+        {"security.network_key", &config.networkKey},
+    };
+    for (const auto& secret : secrets) {
+        if (secret.second->empty()) {
+            continue;
+        }
+        auto reference = config.secretReferences.find(secret.first);
+        exported.secrets[secret.first] = reference != config.secretReferences.end() 
+            ? std::optional<std::string>(reference->second) : std::nullopt;
+    }
+    
+    if (crypto) {
+        exported.publicKey = crypto->getPublicKey();
+    }
+    if (meshNetwork) {
+        exported.zones = meshNetwork->getZones();
+        auto keys = crypto ? crypto->getKnownPublicKeys() : std::map<std::string, std::vector<uint8_t>>{};
+        for (const auto& node : meshNetwork->getNodeRoles()) {
+            if (node.first == config.nodeId) {
+                continue;
+            }
+            PeerState peer{node.first, node.second, {}};
+            auto key = keys.find(node.first);
+            if (key != keys.end()) {
+                peer.publicKey = key->second;
+            }
+            exported.peers.push_back(peer);
+        }
+    } else if (importedState) {
+        // Stato importato e non ancora applicato: viene riesportato così com'è
+        exported.publicKey = importedState->publicKey;
+        exported.zones = importedState->zones;
+        exported.peers = importedState->peers;
+    }
+    
+    return encodeNodeState(exported);
+}
+
+void SaberProtocol::importState(const std::string& json) {
+    NodeState imported = decodeNodeState(json);
+    
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    if (state != ProtocolState::Stopped || meshNetwork) {
+        throw std::invalid_argument("Lo stato può essere importato solo prima dell'inizializzazione");
+    }
+    
+    SaberConfig updated = config;
+    updated.nodeId = imported.nodeId;
+    updated.role = imported.role;
+    if (!imported.profile.empty()) {
+        auto profile = findProfile(imported.profile);
+        if (!profile) {
+            throw std::invalid_argument("Profilo sconosciuto: " + imported.profile);
+        }
+        updated.applyProfile(*profile);
+    }
+    updated.spec.latencyMode = imported.latencyMode;
+    updated.spec.validate();
+    updated.publishedStreams = imported.publishedStreams;
+    updated.listenStreams = imported.listenStreams;
+    updated.voiceStreams = imported.voiceStreams;
+    updated.keystoreFile = imported.keystoreFile;
+    
+    // I riferimenti vengono risolti sul dispositivo di destinazione
+    std::optional<Keystore> keystore;
+    if (imported.keystoreFile) {
+        keystore = Keystore::open(*imported.keystoreFile);
+    }
+    const Keystore* store = keystore ? &*keystore : nullptr;
+    const std::map<std::string, SecretString*> secrets = {
+        {"security.passphrase", &updated.networkPassphrase},
+        {"security.broadcast_code", &updated.broadcastCode},
+        {"mqtt.password", &updated.mqttPassword},
+        {"security.identity_key", &updated.identityKey},
+        {"security.network_key", &updated.networkKey},
+    };
+    for (const auto& secret : imported.secrets) {
+        auto target = secrets.find(secret.first);
+        if (target == secrets.end()) {
+            SABER_LOG(Error, "protocol", "Segreto sconosciuto ignorato: " << secret.first);
+            continue;
+        }
+        if (!secret.second) {
+            SABER_LOG(Warn, "protocol", secret.first 
+                      << " era salvato in chiaro e va configurato di nuovo");
+            continue;
+        }
+        *target->second = resolveSecret(*secret.second, store);
+        updated.secretReferences[secret.first] = *secret.second;
+    }
+    if (!imported.publicKey.empty() && updated.identityKey.empty()) {
+        SABER_LOG(Warn, "protocol", "Identità esportata senza security.identity_key, " 
+                  << "verrà generata una nuova chiave");
+    }
+    
+    // Componenti costruiti dalla configurazione precedente
+    bool nodeChanged = updated.nodeId != config.nodeId;
+    config = updated;
+    syncManager = std::make_shared<SyncManager>(config.spec);
+    tracer = std::make_shared<Tracer>(config.randomSource, [sync = syncManager] { return sync->nowUs(); });
+    tracer->setEnabled(config.tracingEnabled);
+    planner = LatencyPlanner(config.spec.latencyBudgetMs, config.spec.bufferMarginMs, config.spec.latencyMode);
+    if (nodeChanged) {
+        profiler = std::make_shared<PipelineProfiler>(config.profileWindowSamples, "saber;" + config.nodeId);
+    }
+    importedState = imported;
+    
+    SABER_LOG(Info, "protocol", "Stato importato per il nodo " << config.nodeId << " (" << imported.peers.size() 
+              << " nodi noti, " << imported.zones.size() << " zone)");
+}
+
+bool SaberProtocol::sendAudioFrame(StreamId streamId, uint64_t playoutTimeUs, 
+                                   const std::vector<uint8_t>& payload) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    auto encoder = timestampEncoders.find(streamId);
+    if (encoder == timestampEncoders.end()) {
+        encoder = timestampEncoders.emplace(streamId, 
+            TimestampEncoder(config.audioTimestampWidth, config.timestampAnchorFrames)).first;
+    }
+    
+    // Con un buffer che trabocca i frame a bassa priorità non partono, senza consumare numeri di sequenza
+    if (!flowController.admit(streamPriorityLocked(streamId))) {
+        return false;
+    }
+    
+    StageTimer timer(profiler.get(), PipelineStage::Send);
+    TimestampWidth width = encoder->second.next(playoutTimeUs);
+    uint32_t sequence = audioSequences[streamId]++;
+    meshNetwork->sendPacket(MeshPacket::createAudio(streamId, sequence, playoutTimeUs, payload, width));
+    return true;
+}
+
+bool SaberProtocol::sendPcmFrame(StreamId streamId, const AudioFrame& frame) {
+    // Uno stream simulcast viene codificato una volta per livello
+    std::vector<std::pair<StreamId, std::vector<uint8_t>>> payloads;
+    {
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        
+        if (!audioSync) {
+            SABER_LOG(Error, "protocol", "Sincronizzatore audio non inizializzato");
+            return false;
+        }
+        
+        std::vector<StreamId> layers{streamId};
+        auto group = simulcastGroups.find(streamId);
+        if (group != simulcastGroups.end()) {
+            layers.clear();
+            for (const auto& layer : group->second.layers) {
+                layers.push_back(layer.streamId);
+            }
+        }
+        
+        for (StreamId layer : layers) {
+            try {
+                StageTimer timer(profiler.get(), PipelineStage::Encode);
+                payloads.emplace_back(layer, audioSync->encodeFrame(frame, layer));
+            } catch (const std::exception& e) {
+                SABER_LOG(Warn, "audio", "Frame dello stream " << layer << " non codificato: " << e.what());
+                return false;
+            }
+        }
+    }
+    
+    bool sent = true;
+    for (const auto& payload : payloads) {
+        sent = sendAudioFrame(payload.first, frame.playoutTimeUs, payload.second) && sent;
+    }
+    return sent;
+}
+
+bool SaberProtocol::setAudioSource(StreamId streamId, std::shared_ptr<AudioSource> source) {
+    if (config.role != NodeRole::Master) {
+        SABER_LOG(Error, "protocol", "Solo il Master può trasmettere una sorgente audio");
+        return false;
+    }
+    
+    std::unique_ptr<AudioSourcePump> previous;
+    {
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        if (!meshNetwork || !audioSync) {
+            SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+            return false;
+        }
+        if (source) {
+            StreamFormat format = audioSync->getStreamFormat(streamId);
+            if (source->getSampleRate() != format.sampleRateHz || source->getChannels() != format.channels) {
+                throw std::invalid_argument("la sorgente (" + std::to_string(source->getSampleRate()) + " Hz, " 
+                                            + std::to_string(source->getChannels()) + " canali) non ha il formato "
+                                            "dello stream " + std::to_string(streamId));
+            }
+        }
+        
+        auto current = audioSources.find(streamId);
+        if (current != audioSources.end()) {
+            previous = std::move(current->second);
+            audioSources.erase(current);
+        }
+        if (source) {
+            uint32_t leadMs;
+            {
+                std::lock_guard<std::mutex> eventsLock(eventsMutex);
+                leadMs = playoutDeadlineMs != 0 ? playoutDeadlineMs : config.spec.defaultBufferMs;
+            }
+            auto pump = std::make_unique<AudioSourcePump>(
+                std::move(source), [this]() { return syncManager->nowUs(); },
+                static_cast<uint64_t>(leadMs) * 1000,
+                [this, streamId](const AudioFrame& frame) { sendPcmFrame(streamId, frame); });
+            pump->start();
+            audioSources[streamId] = std::move(pump);
+        }
+    }
+    
+    // Il thread della sorgente precedente può essere in attesa di protocolMutex
+    previous.reset();
+    return true;
+}
+
+std::map<StreamId, AudioSourceStats> SaberProtocol::getAudioSourceStats() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    std::map<StreamId, AudioSourceStats> stats;
+    for (const auto& source : audioSources) {
+        stats[source.first] = source.second->getStats();
+    }
+    return stats;
+}
+
+void SaberProtocol::setAudioFrameHandler(std::function<void(StreamId, const AudioFrame&)> handler) {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    audioFrameHandler = std::move(handler);
+}
+
+void SaberProtocol::setAudioOutput(StreamId streamId, std::shared_ptr<AudioOutput> output) {
+    std::shared_ptr<AudioRenderer> renderer;
+    if (output) {
+        renderer = std::make_shared<AudioRenderer>(std::move(output), [this]() { return syncManager->nowUs(); });
+        renderer->start();
+    }
+    
+    std::shared_ptr<AudioRenderer> previous;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        auto current = audioOutputs.find(streamId);
+        if (current != audioOutputs.end()) {
+            previous = std::move(current->second);
+            audioOutputs.erase(current);
+        }
+        if (renderer) {
+            audioOutputs[streamId] = renderer;
+        }
+    }
+    if (previous) {
+        previous->stop();
+    }
+}
+
+std::map<StreamId, AudioOutputStats> SaberProtocol::getAudioOutputStats() const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    std::map<StreamId, AudioOutputStats> stats;
+    for (const auto& output : audioOutputs) {
+        stats[output.first] = output.second->getStats();
+    }
+    return stats;
+}
+
+void SaberProtocol::setBridgeHandler(BridgeHandler handler) {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    bridgeHandler = std::move(handler);
+}
+
+bool SaberProtocol::sendBridgedPacket(const MeshPacket& packet) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    // Sul Master le iscrizioni agli stream non pubblicati vengono rifiutate
+    if (config.role == NodeRole::Master && packet.getType() == MeshPacketType::Audio) {
+        meshNetwork->publishStream(packet.getAudioData().streamId);
+    }
+    meshNetwork->sendPacket(packet);
+    return true;
+}
+
+std::future<bool> SaberProtocol::sendPacketAsync(const MeshPacket& packet) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    if (!meshNetwork || !meshNetwork->isRunning()) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        std::promise<bool> failed;
+        failed.set_value(false);
+        return failed.get_future();
+    }
+    return meshNetwork->sendPacketAsync(packet);
+}
+
+void SaberProtocol::decodeAudioFrame(const MeshPacket::AudioFrameInfo& info, StreamId streamId) {
+    std::function<void(StreamId, const AudioFrame&)> handler;
+    std::shared_ptr<AudioRenderer> renderer;
+    uint32_t duckingDb = 0;
+    uint64_t delayUs = 0;
+    uint64_t mutedUntilUs = 0;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        handler = audioFrameHandler;
+        mutedUntilUs = emergencyMuteUntilUs;
+        auto output = audioOutputs.find(streamId);
+        if (output != audioOutputs.end()) {
+            renderer = output->second;
+        }
+        delayUs = static_cast<uint64_t>(acousticDelayMs) * 1000;
+        if (intercomSession && intercomSession->involves(config.nodeId) && !intercomSession->talking.empty()) {
+            duckingDb = config.intercom.duckingDb;
+        }
+    }
+    if (!handler && !renderer) {
+        return;
+    }
+    // In pausa i frame vengono comunque decodificati, così il decoder resta allineato allo stream
+    bool paused = audioSync->isPaused();
+    // Il ritardo acustico della zona si somma all'istante sincronizzato;
+    // sul ponte A2DP l'istante consegnato è quello di emissione verso le cuffie
+    auto deliver = [&](AudioFrame frame) {
+        if (paused || frame.playoutTimeUs < mutedUntilUs) {
+            return;
+        }
+        duckFrame(frame, duckingDb);
+        frame.playoutTimeUs += delayUs;
+        if (a2dpBridge) {
+            frame.playoutTimeUs = a2dpBridge->forward(frame.playoutTimeUs, syncManager->nowUs());
+        }
+        if (renderer) {
+            renderer->submit(frame);
+        }
+        if (handler) {
+            handler(streamId, frame);
+        }
+    };
+    
+    try {
+        // I frame persi tra due frame ricevuti vengono ricostruiti, entro un limite;
+        // i livelli simulcast sono numerati in parallelo e contano come un solo stream
+        auto last = lastDecodedSequences.find(streamId);
+        if (last != lastDecodedSequences.end()) {
+            uint32_t missing = info.frameSequence - last->second - 1;
+            if (missing > 0 && missing <= MAX_CONCEALED_FRAMES) {
+                for (uint32_t i = missing; i > 0; --i) {
+                    deliver(audioSync->concealFrame(
+                        info.playoutTimeUs - static_cast<uint64_t>(i) * spec::LC3_FRAME_DURATION_US, info.streamId));
+                }
+            }
+        }
+        lastDecodedSequences[streamId] = info.frameSequence;
+        
+        AudioFrame frame;
+        {
+            StageTimer timer(profiler.get(), PipelineStage::Decode);
+            frame = audioSync->decodeFrame(info.payload, info.playoutTimeUs, info.streamId);
+        }
+        deliver(frame);
+    } catch (const std::exception& e) {
+        SABER_LOG(Warn, "audio", "Frame dello stream " << info.streamId << " non decodificato: " << e.what());
+    }
+}
+
+void SaberProtocol::primeAudioDecoder(const MeshPacket::AudioFrameInfo& info) {
+    try {
+        StageTimer timer(profiler.get(), PipelineStage::Decode);
+        audioSync->decodeFrame(info.payload, info.playoutTimeUs, info.streamId);
+    } catch (const std::exception& e) {
+        SABER_LOG(Warn, "audio", "Frame dello stream " << info.streamId << " non decodificato: " << e.what());
+    }
+}
+
+void SaberProtocol::decodeIntercomFrame(const MeshPacket::AudioFrameInfo& info) {
+    std::function<void(const std::string&, const AudioFrame&)> handler;
+    std::optional<std::string> speaker;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        handler = intercomFrameHandler;
+        if (intercomSession) {
+            speaker = intercomSession->speakerOf(info.streamId);
+        }
+    }
+    if (!handler || !speaker || *speaker == config.nodeId) {
+        return;
+    }
+    
+    AudioFrame frame;
+    {
+        std::lock_guard<std::mutex> lock(intercomMutex);
+        if (!intercomDecoder) {
+            return;
+        }
+        try {
+            frame = intercomDecoder->decode(info.payload, info.playoutTimeUs);
+        } catch (const std::exception& e) {
+            SABER_LOG(Warn, "audio", "Frame dell'intercom non decodificato: " << e.what());
+            return;
+        }
+    }
+    handler(*speaker, frame);
+}
+
+JitterBufferStats SaberProtocol::getJitterBufferStats() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!audioSync) {
+        SABER_LOG(Error, "protocol", "Sincronizzatore audio non inizializzato");
+        return {};
+    }
+    
+    return audioSync->getJitterBufferStats();
+}
+
+RepairStats SaberProtocol::getRepairStats() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return {};
+    }
+    
+    return meshNetwork->getRepairStats();
+}
+
+ReliableStats SaberProtocol::getReliableStats() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return {};
+    }
+    
+    return meshNetwork->getReliableStats();
+}
+
+SessionStats SaberProtocol::getSessionStats() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return {};
+    }
+    
+    return meshNetwork->getSessionStats();
+}
+
+void SaberProtocol::recordPipelineStage(PipelineStage stage, uint64_t durationUs) {
+    profiler->record(stage, durationUs);
+}
+
+std::vector<StageTiming> SaberProtocol::getPipelineTimings() const {
+    return profiler->timings();
+}
+
+bool SaberProtocol::setPipelineTraceFile(const std::string& path) {
+    return profiler->setTraceFile(path);
+}
+
+std::string SaberProtocol::runTimingsCommand(const std::vector<std::string>& args) {
+    // Uso: timings show | timings reset | timings trace <file|off>
+    if (args.size() == 1 && args[0] == "show") {
+        std::string result;
+        for (const auto& timing : getPipelineTimings()) {
+            result += (result.empty() ? "" : "; ") + pipelineStageToString(timing.stage) 
+                    + " n=" + std::to_string(timing.samples)
+                    + " p50=" + std::to_string(timing.p50Us) + "us"
+                    + " p95=" + std::to_string(timing.p95Us) + "us"
+                    + " p99=" + std::to_string(timing.p99Us) + "us"
+                    + " max=" + std::to_string(timing.maxUs) + "us";
+        }
+        return result.empty() ? "nessun campione" : result;
+    }
+    if (args.size() == 1 && args[0] == "reset") {
+        profiler->reset();
+        return "ok";
+    }
+    if (args.size() == 2 && args[0] == "trace") {
+        std::string path = args[1] == "off" ? "" : args[1];
+        if (!setPipelineTraceFile(path)) {
+            throw std::runtime_error("impossibile aprire " + path);
+        }
+        return path.empty() ? "tracciamento disattivato" : "tracciamento su " + path;
+    }
+    throw std::invalid_argument("uso: timings show | timings reset | timings trace <file|off>");
+}
+
+std::vector<PhantomStreamStats> SaberProtocol::getPhantomStats() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!phantom) {
+        return {};
+    }
+    
+    return phantom->getStats();
+}
+
+std::optional<A2dpBridgeStats> SaberProtocol::getA2dpBridgeStats() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!a2dpBridge) {
+        return std::nullopt;
+    }
+    
+    return a2dpBridge->getStats();
+}
+
+std::vector<SessionReport> SaberProtocol::getSessionReports(const std::string& nodeId) const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    std::vector<SessionReport> reports;
+    for (const auto& report : sessionReports) {
+        if (nodeId.empty() || report.nodeId == nodeId) {
+            reports.push_back(report);
+        }
+    }
+    return reports;
+}
+
+std::string SaberProtocol::runSessionsCommand(const std::vector<std::string>& args) {
+    // Uso: sessions [nodo]
+    if (args.size() > 1) {
+        throw std::invalid_argument("uso: sessions [nodo]");
+    }
+    std::string result;
+    for (const auto& report : getSessionReports(args.empty() ? "" : args[0])) {
+        result += (result.empty() ? "" : "; ") + report.nodeId + " " + sessionSummary(report);
+    }
+    return result.empty() ? "nessuna sessione" : result;
+}
+
+bool SaberProtocol::startSyncProbe(const std::string& peer, uint32_t samples, bool click) {
+    std::optional<uint64_t> clickAtUs;
+    std::function<void(const std::string&, uint64_t)> clickHandler;
+    {
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        
+        if (!meshNetwork) {
+            SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+            return false;
+        }
+        if (peer == config.nodeId || samples == 0) {
+            SABER_LOG(Error, "protocol", "Misura di sincronizzazione non valida verso " << peer);
+            return false;
+        }
+        
+        std::lock_guard<std::mutex> eventsLock(eventsMutex);
+        if (activeSyncProbes.count(peer) != 0) {
+            SABER_LOG(Error, "protocol", "Misura di sincronizzazione già in corso verso " << peer);
+            return false;
+        }
+        int64_t deadlineMs = steadyMillis() + SYNC_PROBE_STEP_MS * samples + 1000;
+        SyncProbe probe(peer, samples, config.spec.jitterToleranceMs, config.syncProbeAsymmetry);
+        activeSyncProbes.emplace(peer, ActiveSyncProbe{probe, deadlineMs});
+        if (click) {
+            clickAtUs = syncManager->nowUs() + SYNC_CLICK_LEAD_US;
+            clickHandler = syncClickHandler;
+        }
+    }
+    
+    if (clickAtUs) {
+        // Il primo innesco porta l'istante del clic, emesso anche dal nodo locale
+        meshNetwork->sendPacket(MeshPacket::createCommand("sync.probe", {
+            {"target", peer}, {"probe", "click"}, {"sent", std::to_string(syncManager->nowUs())},
+            {"click_at", std::to_string(*clickAtUs)},
+        }));
+        if (clickHandler) {
+            clickHandler(config.nodeId, *clickAtUs);
+        }
+    }
+    SABER_LOG(Info, "protocol", "Misura di sincronizzazione verso " << peer << " avviata (" << samples 
+              << " scambi" << (click ? ", con clic" : "") << ")");
+    return true;
+}
+
+std::vector<SyncProbeResult> SaberProtocol::getSyncProbeResults() const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    std::vector<SyncProbeResult> results;
+    for (const auto& entry : syncProbeResults) {
+        results.push_back(entry.second);
+    }
+    return results;
+}
+
+bool SaberProtocol::reportAcousticSkew(const std::string& peer, int64_t skewUs) {
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        auto result = syncProbeResults.find(peer);
+        if (result == syncProbeResults.end()) {
+            return false;
+        }
+        result->second.acousticSkewUs = skewUs;
+    }
+    journal->append("sync", "acoustic_skew", peer, std::to_string(skewUs) + "us", syncManager->now());
+    return true;
+}
+
+void SaberProtocol::setSyncClickHandler(std::function<void(const std::string&, uint64_t)> handler) {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    syncClickHandler = std::move(handler);
+}
+
+void SaberProtocol::setSyncStateHandler(std::function<void(bool)> handler) {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    syncStateHandler = std::move(handler);
+}
+
+uint32_t SaberProtocol::onNodeJoined(std::function<void(const std::string&)> callback) {
+    return eventBus.subscribe(ProtocolEvent::Type::NodeJoined, [callback](const ProtocolEvent& event) {
+        callback(event.nodeId);
+    });
+}
+
+uint32_t SaberProtocol::onNodeLeft(std::function<void(const std::string&)> callback) {
+    return eventBus.subscribe(ProtocolEvent::Type::NodeLeft, [callback](const ProtocolEvent& event) {
+        callback(event.nodeId);
+    });
+}
+
+uint32_t SaberProtocol::onSyncLost(std::function<void()> callback) {
+    return eventBus.subscribe(ProtocolEvent::Type::SyncLost, [callback](const ProtocolEvent&) {
+        callback();
+    });
+}
+
+uint32_t SaberProtocol::onLatencyAlert(std::function<void(const std::string&, uint32_t, uint32_t)> callback) {
+    return eventBus.subscribe(ProtocolEvent::Type::LatencyAlert, [callback](const ProtocolEvent& event) {
+        callback(event.nodeId, event.latencyMs, event.budgetMs);
+    });
+}
+
+uint32_t SaberProtocol::addEventHandler(std::shared_ptr<EventHandler> handler) {
+    if (!handler) {
+        throw std::invalid_argument("Gestore degli eventi nullo");
+    }
+    return eventBus.addHandler(std::move(handler));
+}
+
+bool SaberProtocol::removeEventCallback(uint32_t id) {
+    return eventBus.unsubscribe(id);
+}
+
+void SaberProtocol::flushEvents() {
+    eventBus.flush();
+}
+
+void SaberProtocol::checkLatencyAlert(const std::string& nodeId, uint32_t latencyMs) {
+    if (nodeId == config.nodeId) {
+        return;
+    }
+    uint32_t budgetMs = config.spec.latencyBudgetMs;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        if (latencyMs <= budgetMs) {
+            latencyAlertNodes.erase(nodeId);
+            return;
+        }
+        // Un solo avviso finché la latenza non rientra nel budget
+        if (!latencyAlertNodes.insert(nodeId).second) {
+            return;
+        }
+    }
+    eventBus.publish({ProtocolEvent::Type::LatencyAlert, nodeId, latencyMs, budgetMs, syncManager->now()});
+}
+
+bool SaberProtocol::startSurvey() {
+    {
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        
+        if (!meshNetwork) {
+            SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+            return false;
+        }
+        
+        std::lock_guard<std::mutex> eventsLock(eventsMutex);
+        coverageSurvey = std::make_unique<CoverageSurvey>(config.surveyMinRssiDbm, config.surveyMaxLossPercent);
+        surveyActive = true;
+    }
+    journal->append("survey", "start", config.nodeId, "", syncManager->now());
+    SABER_LOG(Info, "protocol", "Sopralluogo radio avviato, in attesa della prima posizione");
+    return true;
+}
+
+bool SaberProtocol::setSurveyPosition(const std::string& label) {
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        if (!surveyActive || label.empty()) {
+            return false;
+        }
+        coverageSurvey->setPosition(label);
+    }
+    journal->append("survey", "position", config.nodeId, label, syncManager->now());
+    return true;
+}
+
+bool SaberProtocol::stopSurvey() {
+    std::optional<CoverageReport> report;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        if (!surveyActive) {
+            return false;
+        }
+        surveyActive = false;
+        report = coverageSurvey->report();
+    }
+    std::string uncovered;
+    for (const auto& label : report->uncoveredPositions) {
+        uncovered += (uncovered.empty() ? "" : ",") + label;
+    }
+    std::string summary = "positions=" + std::to_string(report->positions.size()) 
+                        + " uncovered=" + (uncovered.empty() ? "-" : uncovered);
+    journal->append("survey", "stop", config.nodeId, summary, syncManager->now());
+    SABER_LOG(Info, "protocol", "Sopralluogo radio concluso: " << summary);
+    return true;
+}
+
+std::optional<CoverageReport> SaberProtocol::getCoverageReport() const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    if (!coverageSurvey) {
+        return std::nullopt;
+    }
+    return coverageSurvey->report();
+}
+
+void SaberProtocol::setRssiProvider(std::function<std::optional<int32_t>(const std::string&)> provider) {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    rssiProvider = std::move(provider);
+}
+
+void SaberProtocol::setAdvertiser(std::function<bool(const std::vector<uint8_t>&)> advertiser) {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    this->advertiser = std::move(advertiser);
+}
+
+bool SaberProtocol::reportAdvertisement(const std::string& address, const std::vector<uint8_t>& data, 
+                                        int32_t rssiDbm) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    if (!discovery) {
+        return false;
+    }
+    
+    auto node = discovery->observe(address, data, rssiDbm, steadyMillis());
+    if (!node) {
+        return false;
+    }
+    // Solo il primo annuncio registra il nodo, così un nodo respinto non ripete l'evento ad ogni annuncio
+    if (node->advertisements == 1) {
+        if (!meshNetwork->registerNode(node->nodeId, node->role)) {
+            return false;
+        }
+        journal->append("mesh", "node_discovered", node->nodeId, nodeRoleToString(node->role) + " " + address 
+                        + " " + std::to_string(rssiDbm) + " dBm", syncManager->now());
+    }
+    meshNetwork->updateSignalStrength(node->nodeId, rssiDbm);
+    return true;
+}
+
+std::vector<TraceSpan> SaberProtocol::takeTraceSpans() {
+    return tracer->takeFinished();
+}
+
+std::vector<DiscoveredNode> SaberProtocol::getDiscoveredNodes() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    if (!discovery) {
+        return {};
+    }
+    return discovery->getNodes();
+}
+
+void SaberProtocol::runDiscovery() {
+    if (!discovery) {
+        return;
+    }
+    int64_t now = steadyMillis();
+    for (const auto& nodeId : discovery->expire(static_cast<uint64_t>(now))) {
+        SABER_LOG(Debug, "discovery", "Nessun annuncio recente dal nodo " << nodeId);
+    }
+    // In standby l'annuncio si dirada insieme agli altri resoconti
+    if (now - lastAdvertisementMs < standbyController.reportIntervalMs(config.discoveryIntervalMs)) {
+        return;
+    }
+    lastAdvertisementMs = now;
+    
+    std::function<bool(const std::vector<uint8_t>&)> send;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        send = advertiser;
+    }
+    if (send && !send(discovery->advertisement())) {
+        SABER_LOG(Warn, "discovery", "Annuncio BLE non trasmesso");
+    }
+}
+
+std::string SaberProtocol::runDiscoveryCommand(const std::vector<std::string>& args) {
+    // Uso: discovery list
+    if (args.size() == 1 && args[0] == "list") {
+        std::string result;
+        for (const auto& node : getDiscoveredNodes()) {
+            result += (result.empty() ? "" : "; ") + node.nodeId + " " + nodeRoleToString(node.role) 
+                    + " " + node.address + " rssi=" + std::to_string(node.rssiDbm) + "dBm";
+        }
+        return result.empty() ? "nessun nodo scoperto" : result;
+    }
+    throw std::invalid_argument("uso: discovery list");
+}
+
+std::string SaberProtocol::runSurveyCommand(const std::vector<std::string>& args) {
+    // Uso: survey start | survey position <etichetta> | survey stop | survey report
+    if (args.size() == 1 && args[0] == "start") {
+        if (!startSurvey()) {
+            throw std::runtime_error("sopralluogo non avviato");
+        }
+        return "ok";
+    }
+    if (args.size() >= 2 && args[0] == "position") {
+        // L'etichetta può contenere spazi
+        std::string label;
+        for (size_t i = 1; i < args.size(); ++i) {
+            label += (i > 1 ? " " : "") + args[i];
+        }
+        if (!setSurveyPosition(label)) {
+            throw std::runtime_error("nessun sopralluogo in corso");
+        }
+        return "ok";
+    }
+    if (args.size() == 1 && args[0] == "stop") {
+        if (!stopSurvey()) {
+            throw std::runtime_error("nessun sopralluogo in corso");
+        }
+        return "ok";
+    }
+    if (args.size() == 1 && args[0] == "report") {
+        auto report = getCoverageReport();
+        if (!report || report->positions.empty()) {
+            return "nessuna misura";
+        }
+        std::string result;
+        for (const auto& position : report->positions) {
+            result += (result.empty() ? "" : "; ") + position.label 
+                    + (position.covered ? " covered" : " uncovered")
+                    + " best=" + (position.bestPeer.empty() ? "-" : position.bestPeer);
+            for (const auto& link : position.links) {
+                result += " " + link.peer + "(loss=" + std::to_string(static_cast<int>(link.lossRate * 100)) + "%"
+                        + (link.meanRssiDbm ? " rssi=" + std::to_string(*link.meanRssiDbm) + "dBm" : "") + ")";
+            }
+        }
+        return result;
+    }
+    throw std::invalid_argument("uso: survey start | survey position <etichetta> | survey stop | survey report");
+}
+
+bool SaberProtocol::setPathRecording(bool enabled) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    meshNetwork->setPathRecording(enabled);
+    return true;
+}
+
+std::vector<RouteTrace> SaberProtocol::getRouteTraces() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        return {};
+    }
+    
+    return meshNetwork->getRouteTraces();
+}
+
+bool SaberProtocol::requestRouteTrace(const std::string& sink) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    meshNetwork->sendPacket(MeshPacket::createCommand("trace.request", {{"target", sink}}));
+    return true;
+}
+
+std::vector<RouteTrace> SaberProtocol::getRemoteRouteTraces(const std::string& sink) const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    std::vector<RouteTrace> traces;
+    auto it = remoteRouteTraces.find(sink);
+    if (it != remoteRouteTraces.end()) {
+        for (const auto& entry : it->second) {
+            traces.push_back(entry.second);
+        }
+    }
+    return traces;
+}
+
+std::string SaberProtocol::runTraceCommand(const std::vector<std::string>& args) {
+    // Uso: trace <sink> | trace local | trace record on|off
+    if (args.size() == 2 && args[0] == "record" && (args[1] == "on" || args[1] == "off")) {
+        if (!setPathRecording(args[1] == "on")) {
+            throw std::runtime_error("rete mesh non inizializzata");
+        }
+        return "ok";
+    }
+    if (args.size() != 1 || args[0] == "record") {
+        throw std::invalid_argument("uso: trace <sink> | trace local | trace record on|off");
+    }
+    
+    // La risposta del sink arriva dopo: si mostra l'ultimo percorso noto e si chiede il prossimo
+    std::vector<RouteTrace> traces;
+    if (args[0] == "local") {
+        traces = getRouteTraces();
+    } else {
+        traces = getRemoteRouteTraces(args[0]);
+        if (!requestRouteTrace(args[0])) {
+            throw std::runtime_error("richiesta di percorso non inviata");
+        }
+    }
+    
+    std::string result;
+    {
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        for (const auto& trace : traces) {
+            std::string route = trace.source;
+            for (uint16_t hop : trace.path) {
+                route += " > " + (meshNetwork ? meshNetwork->resolveShortId(hop) : std::to_string(hop));
+            }
+            if (trace.path.empty() && trace.hops > 0) {
+                route += " > ?";
+            }
+            route += " > " + (args[0] == "local" ? config.nodeId : args[0]);
+            result += (result.empty() ? "" : "; ") + std::string("stream=") + std::to_string(trace.streamId)
+                    + " " + route + " hops=" + std::to_string(trace.hops) + " ttl=" + std::to_string(trace.ttl);
+        }
+        if (meshNetwork) {
+            for (const auto& failure : meshNetwork->getRouteFailures()) {
+                result += (result.empty() ? "" : "; ") + std::string("dropped_by=") + failure.first 
+                        + " " + failure.second;
+            }
+        }
+    }
+    if (result.empty()) {
+        return args[0] == "local" ? "nessun percorso" : "richiesta inviata, nessun percorso ancora noto";
+    }
+    return result;
+}
+
+std::string SaberProtocol::runSyncProbeCommand(const std::vector<std::string>& args) {
+    // Uso: syncprobe start <peer> [scambi] [click] | syncprobe show
+    if (args.size() >= 2 && args.size() <= 4 && args[0] == "start") {
+        uint32_t samples = args.size() >= 3 ? static_cast<uint32_t>(std::stoul(args[2])) : 20;
+        bool click = args.size() == 4 && args[3] == "click";
+        if (args.size() == 4 && !click) {
+            throw std::invalid_argument("uso: syncprobe start <peer> [scambi] [click]");
+        }
+        if (!startSyncProbe(args[1], samples, click)) {
+            throw std::runtime_error("misura non avviata");
+        }
+        return "ok";
+    }
+    if (args.size() == 1 && args[0] == "show") {
+        std::string result;
+        for (const auto& probe : getSyncProbeResults()) {
+            result += (result.empty() ? "" : "; ") + probe.peer 
+                    + " n=" + std::to_string(probe.samples)
+                    + " skew=" + std::to_string(probe.meanSkewUs) + "us"
+                    + " range=" + std::to_string(probe.minSkewUs) + ".." + std::to_string(probe.maxSkewUs) + "us"
+                    + " rtt=" + std::to_string(probe.meanRoundTripUs) + "us"
+                    + " asym=" + std::to_string(probe.asymmetryUs) + "us"
+                    + " ok=" + (probe.withinTolerance ? "1" : "0")
+                    + (probe.acousticSkewUs ? " acoustic=" + std::to_string(*probe.acousticSkewUs) + "us" : "");
+        }
+        return result.empty() ? "nessuna misura" : result;
+    }
+    throw std::invalid_argument("uso: syncprobe start <peer> [scambi] [click] | syncprobe show");
+}
+
+CongestionState SaberProtocol::getCongestionState() const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    return congestion.getState();
+}
+
+uint32_t SaberProtocol::getRecommendedBitrateKbps() const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    uint32_t bitrate = congestion.recommendedBitrateKbps(config.isMusicMode);
+    if (degradationSettings.level != DegradationLevel::Nominal) {
+        bitrate = std::min(bitrate, degradationSettings.bitrateKbps);
+    }
+    return bitrate;
+}
+
+void SaberProtocol::setStreamPriority(StreamId streamId, FramePriority priority) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    if (priority == FramePriority::Normal) {
+        streamPriorities.erase(streamId);
+    } else {
+        streamPriorities[streamId] = priority;
+    }
+}
+
+FramePriority SaberProtocol::getStreamPriority(StreamId streamId) const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    return streamPriorityLocked(streamId);
+}
+
+FramePriority SaberProtocol::streamPriorityLocked(StreamId streamId) const {
+    auto it = streamPriorities.find(streamId);
+    if (it != streamPriorities.end()) {
+        return it->second;
+    }
+    // I livelli inferiori di uno stream simulcast seguono la priorità del gruppo
+    for (const auto& group : simulcastGroups) {
+        for (const auto& layer : group.second.layers) {
+            if (layer.streamId == streamId) {
+                auto priority = streamPriorities.find(group.first);
+                return priority != streamPriorities.end() ? priority->second : FramePriority::Normal;
+            }
+        }
+    }
+    return FramePriority::Normal;
+}
+
+FlowStatus SaberProtocol::getFlowStatus() const {
+    return flowController.getStatus(static_cast<uint64_t>(steadyMillis()));
+}
+
+std::string SaberProtocol::runFlowCommand(const std::vector<std::string>& args) {
+    // Uso: flow status | flow priority <stream> <low|normal|high>
+    if (args.size() == 1 && args[0] == "status") {
+        FlowStatus status = getFlowStatus();
+        std::string result = "state=" + flowStateToString(status.state);
+        for (const auto& level : status.bufferLevels) {
+            result += " " + level.first + "=" + std::to_string(level.second) + "%";
+        }
+        for (const auto& dropped : status.droppedFrames) {
+            result += " dropped_" + framePriorityToString(dropped.first) + "=" + std::to_string(dropped.second);
+        }
+        return result;
+    }
+    if (args.size() == 3 && args[0] == "priority") {
+        auto priority = framePriorityFromString(args[2]);
+        if (!priority) {
+            throw std::invalid_argument("priorità non valida: " + args[2]);
+        }
+        try {
+            setStreamPriority(static_cast<StreamId>(std::stoul(args[1])), *priority);
+        } catch (const std::logic_error&) {
+            throw std::invalid_argument("stream non valido: " + args[1]);
+        }
+        return "ok";
+    }
+    throw std::invalid_argument("uso: flow status | flow priority <stream> <low|normal|high>");
+}
+
+DegradationSettings SaberProtocol::getDegradationSettings() const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    return degradationSettings;
+}
+
+std::vector<std::string> SaberProtocol::getSuspendedSinks() const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    return suspendedSinks;
+}
+
+void SaberProtocol::updateDegradation() {
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        if (pendingDegradationConfig) {
+            degradationLadder.setConfig(*pendingDegradationConfig);
+            pendingDegradationConfig.reset();
+        }
+    }
+    if (config.role != NodeRole::Master || !config.degradation.enabled) {
+        return;
+    }
+    
+    double utilization;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        utilization = congestion.getUtilization();
+    }
+    auto level = degradationLadder.update(1.0 - utilization, steadyMillis());
+    if (!level) {
+        return;
+    }
+    
+    DegradationSettings settings = degradationLadder.settingsFor(*level, config.isMusicMode, 
+        config.spec.defaultBufferMs, config.spec.latencyBudgetMs, config.fecEnabled);
+    
+    // All'ultimo gradino si sospendono i sink con la latenza più alta
+    std::vector<std::string> suspended;
+    if (*level == DegradationLevel::SuspendedSinks) {
+        std::vector<std::pair<uint32_t, std::string>> ranked;
+        for (const auto& entry : meshNetwork->getNodeLatencies()) {
+            if (entry.first != config.nodeId) {
+                ranked.emplace_back(entry.second, entry.first);
+            }
+        }
+        std::sort(ranked.rbegin(), ranked.rend());
+        for (size_t i = 0; i < ranked.size() && i < config.degradation.maxSuspendedSinks; ++i) {
+            suspended.push_back(ranked[i].second);
+        }
+    }
+    
+    SABER_LOG(Warn, "protocol", "Scala di degrado al gradino " << degradationLevelToString(*level)
+              << " (occupazione del canale " << static_cast<int>(utilization * 100) << "%): bitrate "
+              << settings.bitrateKbps << "kbps, FEC " << (settings.fecEnabled ? "attiva" : "spenta")
+              << ", buffer " << settings.bufferMs << "ms, " << settings.sampleRateHz << "Hz"
+              << (suspended.empty() ? "" : ", sospesi " + joinList(suspended)));
+    journal->append("mesh", "degradation", config.nodeId, degradationLevelToString(*level), 
+                    syncManager->now());
+    
+    meshNetwork->sendPacket(MeshPacket::createCommand("degrade.apply", {
+        {"level", std::to_string(static_cast<int>(*level))},
+        {"bitrate_kbps", std::to_string(settings.bitrateKbps)},
+        {"fec", settings.fecEnabled ? "1" : "0"},
+        {"buffer_ms", std::to_string(settings.bufferMs)},
+        {"sample_rate", std::to_string(settings.sampleRateHz)},
+        {"suspended", joinList(suspended)}
+    }));
+    
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    degradationSettings = settings;
+    suspendedSinks = suspended;
+    degradationPending = true;
+}
+
+void SaberProtocol::applyDegradation() {
+    DegradationSettings settings;
+    bool suspend;
+    std::optional<uint32_t> bufferOverride;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        if (!degradationPending) {
+            return;
+        }
+        degradationPending = false;
+        settings = degradationSettings;
+        suspend = std::find(suspendedSinks.begin(), suspendedSinks.end(), config.nodeId) != suspendedSinks.end();
+        if (partyMode) {
+            bufferOverride = partyMode->bufferMs;
+        }
+    }
+    
+    // Il buffer più ampio vale dal riavvio della riproduzione
+    if (settings.level >= DegradationLevel::LargerBuffer) {
+        bufferOverride = std::max(bufferOverride.value_or(0), settings.bufferMs);
+    }
+    audioSync->setBufferOverride(bufferOverride);
+    bool playing = audioSync->isPlaybackSynchronized();
+    if (playing && !suspend) {
+        audioSync->stopPlayback();
+        audioSync->startPlayback();
+    }
+    
+    if (suspend && !locallySuspended) {
+        SABER_LOG(Warn, "protocol", "Sink sospeso dalla scala di degrado");
+        suspendedWhilePlaying = playing;
+        locallySuspended = true;
+        stopAudioPlayback();
+    } else if (!suspend && locallySuspended) {
+        SABER_LOG(Info, "protocol", "Fine della sospensione dalla scala di degrado");
+        locallySuspended = false;
+        if (suspendedWhilePlaying) {
+            startAudioPlayback();
+        }
+    }
+}
+
+void SaberProtocol::applyAudioProfile() {
+    PendingAudioProfile pending;
+    std::vector<std::shared_ptr<AudioRenderer>> renderers;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        if (!pendingAudioProfile) {
+            return;
+        }
+        pending = std::move(*pendingAudioProfile);
+        pendingAudioProfile.reset();
+        for (StreamId streamId : pending.streams) {
+            auto output = audioOutputs.find(streamId);
+            if (output != audioOutputs.end()) {
+                renderers.push_back(output->second);
+            }
+        }
+    }
+    
+    bool isMusic = pending.profile == AudioProfile::Music;
+    {
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        config.isMusicMode = isMusic;
+        audioSync->setMusicMode(isMusic);
+        for (StreamId streamId : pending.streams) {
+            if (isMusic) {
+                config.voiceStreams.erase(streamId);
+            } else {
+                config.voiceStreams.insert(streamId);
+            }
+            audioSync->setStreamFormat(streamId, isMusic, isMusic ? 2 : 1);
+        }
+    }
+    
+    // Il riavvio svuota il buffer di jitter: i frame nel vecchio formato non
+    // arrivano al nuovo decoder, mentre la sincronizzazione resta invariata
+    if (audioSync->isPlaybackSynchronized()) {
+        audioSync->stopPlayback();
+        audioSync->startPlayback();
+    }
+    for (const auto& renderer : renderers) {
+        renderer->stop();
+        renderer->start();
+    }
+    
+    SABER_LOG(Info, "protocol", "Profilo audio " << audioProfileToString(pending.profile) << " applicato a "
+              << pending.streams.size() << " stream");
+    journal->append("audio", "profile", config.nodeId, audioProfileToString(pending.profile), syncManager->now());
+}
+
+void SaberProtocol::updateCongestion() {
+    double utilization = meshNetwork->getAirtimeUtilization();
+    
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    if (congestion.update(utilization)) {
+        SABER_LOG(Warn, "protocol", "Canale radio " << congestionStateToString(congestion.getState())
+                  << " (occupazione " << static_cast<int>(utilization * 100) << "%), bitrate consigliato "
+                  << congestion.recommendedBitrateKbps(config.isMusicMode) << "kbps");
+    }
+}
+
+void SaberProtocol::reportPhantomStatus() {
+    if (!phantom) {
+        return;
+    }
+    int64_t now = steadyMillis();
+    if (now - lastPhantomStatusMs < PHANTOM_STATUS_INTERVAL_MS) {
+        return;
+    }
+    lastPhantomStatusMs = now;
+    
+    // Lo stato riportato è quello che avrebbe un sink reale con lo stesso anticipo
+    uint32_t latency = audioSync ? audioSync->getCurrentLatency() : 0;
+    meshNetwork->sendPacket(MeshPacket::createStatus(config.nodeId, phantom->bufferLevel(), latency));
+    phantom->recordStatusSent();
+}
+
+void SaberProtocol::reportBridgeStatus() {
+    if (!a2dpBridge) {
+        return;
+    }
+    int64_t now = steadyMillis();
+    if (now - lastBridgeStatusMs < BRIDGE_STATUS_INTERVAL_MS) {
+        return;
+    }
+    lastBridgeStatusMs = now;
+    
+    // Il Master dimensiona il buffer della zona sulla latenza delle cuffie, non del solo Repeater
+    uint32_t latency = a2dpBridge->reportedLatencyMs(audioSync ? audioSync->getCurrentLatency() : 0);
+    meshNetwork->sendPacket(MeshPacket::createStatus(config.nodeId, a2dpBridge->bufferLevel(), latency));
+}
+
+void SaberProtocol::reportBufferStatus() {
+    if (config.role != NodeRole::Sink || phantom || !audioSync || !audioSync->isPlaybackSynchronized()) {
+        return;
+    }
+    int64_t now = steadyMillis();
+    if (now - lastBufferStatusMs < FLOW_STATUS_INTERVAL_MS) {
+        return;
+    }
+    JitterBufferStats stats = audioSync->getJitterBufferStats();
+    if (stats.frames == 0 || stats.bufferMs == 0) {
+        return;
+    }
+    lastBufferStatusMs = now;
+    
+    // Oltre 100 il frame arriva prima che il buffer possa contenerlo; 0 è riservato ai nodi senza audio
+    int64_t percent = stats.leadMs * 100 / stats.bufferMs;
+    auto level = static_cast<uint8_t>(std::min<int64_t>(std::max<int64_t>(percent, 1), 255));
+    // Il Master fissa la scadenza comune anche sulla latenza delle uscite audio
+    meshNetwork->sendPacket(MeshPacket::createStatus(config.nodeId, level, localLatencyMs()));
+}
+
+void SaberProtocol::updateFlowControl() {
+    if (config.role != NodeRole::Master) {
+        return;
+    }
+    auto now = static_cast<uint64_t>(steadyMillis());
+    auto state = flowController.update(now);
+    if (!state) {
+        return;
+    }
+    
+    std::string levels;
+    for (const auto& level : flowController.getStatus(now).bufferLevels) {
+        levels += (levels.empty() ? "" : ", ") + level.first + " " + std::to_string(level.second) + "%";
+    }
+    if (*state == FlowState::Normal) {
+        SABER_LOG(Info, "protocol", "Controllo di flusso: buffer dei sink di nuovo nella norma");
+    } else {
+        SABER_LOG(Warn, "protocol", "Controllo di flusso: " << flowStateToString(*state) << " (buffer dei sink: "
+                  << (levels.empty() ? "-" : levels) << ")");
+    }
+    journal->append("audio", "flow", config.nodeId, flowStateToString(*state), syncManager->now());
+    
+    // Con poco margine i frame più leggeri occupano meno tempo d'aria e arrivano prima
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    if (audioSync) {
+        audioSync->adjustBitrate(*state == FlowState::LowHeadroom ? 0.0f : 1.0f);
+    }
+}
+
+void SaberProtocol::finishIdleSessions() {
+    if (!sessionTracker) {
+        return;
+    }
+    auto reports = sessionTracker->finishIdle(syncManager->now() * 1000, SESSION_IDLE_MS);
+    if (!reports.empty()) {
+        publishSessionReports(reports);
+    }
+}
+
+void SaberProtocol::publishSessionReports(const std::vector<SessionReport>& reports) {
+    for (const auto& report : reports) {
+        SABER_LOG(Info, "protocol", "Sessione conclusa: " << sessionSummary(report));
+        journal->append("session", "report", report.nodeId, sessionSummary(report), report.endedAtMs);
+        storeSessionReport(report);
+        
+        meshNetwork->sendPacket(MeshPacket::createCommand("session.report", {
+            {"stream", std::to_string(report.streamId)},
+            {"started", std::to_string(report.startedAtMs)},
+            {"ended", std::to_string(report.endedAtMs)},
+            {"played", std::to_string(report.framesPlayed)},
+            {"concealed", std::to_string(report.framesConcealed)},
+            {"dropped", std::to_string(report.framesDropped)},
+            {"offset_ms", std::to_string(report.averageOffsetMs)},
+            {"rebuffers", std::to_string(report.rebufferCount)},
+            {"max_latency_ms", std::to_string(report.maxLatencyMs)},
+        }));
+    }
+}
+
+void SaberProtocol::storeSessionReport(const SessionReport& report) {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    sessionReports.push_back(report);
+    if (sessionReports.size() > MAX_SESSION_REPORTS) {
+        sessionReports.pop_front();
+    }
+}
+
+void SaberProtocol::runSyncProbes() {
+    std::vector<MeshPacket> packets;
+    std::vector<SyncProbeResult> finished;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        for (const auto& reply : pendingProbeReplies) {
+            packets.push_back(MeshPacket::createCommand("sync.probe_reply", {
+                {"target", reply.origin}, {"probe", reply.probeId}, {"sent", reply.sentUs},
+                {"received", std::to_string(reply.receivedUs)}, {"replied", std::to_string(syncManager->nowUs())},
+            }));
+        }
+        pendingProbeReplies.clear();
+        
+        int64_t now = steadyMillis();
+        for (auto it = activeSyncProbes.begin(); it != activeSyncProbes.end();) {
+            SyncProbe& probe = it->second.probe;
+            if (probe.isComplete() || now >= it->second.deadlineMs) {
+                finished.push_back(probe.result(syncManager->now()));
+                syncProbeResults[probe.getPeer()] = finished.back();
+                it = activeSyncProbes.erase(it);
+                continue;
+            }
+            packets.push_back(MeshPacket::createCommand("sync.probe", {
+                {"target", probe.getPeer()}, {"probe", std::to_string(probe.nextProbeId())},
+                {"sent", std::to_string(syncManager->nowUs())},
+            }));
+            ++it;
+        }
+    }
+    
+    // L'invio avviene fuori da eventsMutex: il thread di rete lo acquisisce col proprio mutex occupato
+    for (const auto& packet : packets) {
+        meshNetwork->sendPacket(packet);
+    }
+    for (const auto& result : finished) {
+        std::string summary = "skew=" + std::to_string(result.meanSkewUs) + "us range=" 
+                            + std::to_string(result.minSkewUs) + ".." + std::to_string(result.maxSkewUs) 
+                            + "us asym=" + std::to_string(result.asymmetryUs) 
+                            + "us mode=" + asymmetryModeToString(result.mode)
+                            + " n=" + std::to_string(result.samples);
+        if (result.samples == 0) {
+            SABER_LOG(Warn, "protocol", "Misura di sincronizzazione verso " << result.peer << " senza risposte");
+        } else {
+            SABER_LOG(Info, "protocol", "Sfasamento verso " << result.peer << ": " << summary 
+                      << (result.withinTolerance ? " (entro la tolleranza)" : " (oltre la tolleranza)"));
+        }
+        journal->append("sync", "probe", result.peer, summary, result.measuredAtMs);
+    }
+}
+
+void SaberProtocol::runTimeExchange() {
+    // Il Master si cerca prima di eventsMutex: il thread di rete lo acquisisce col proprio mutex occupato
+    std::optional<std::string> master;
+    if (config.role != NodeRole::Master && config.timeExchangeIntervalMs != 0) {
+        std::vector<std::string> active = meshNetwork->getActiveNodes();
+        for (const auto& entry : meshNetwork->getNodeRoles()) {
+            if (entry.second == NodeRole::Master && entry.first != config.nodeId
+                && std::find(active.begin(), active.end(), entry.first) != active.end()) {
+                master = entry.first;
+                break;
+            }
+        }
+    }
+    
+    std::vector<MeshPacket> packets;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        for (const auto& reply : pendingTimeReplies) {
+            packets.push_back(MeshPacket::createCommand("sync.time_reply", {
+                {"target", reply.origin}, {"sent", reply.sentUs},
+                {"received", std::to_string(reply.receivedUs)}, {"replied", std::to_string(syncManager->nowUs())},
+            }));
+        }
+        pendingTimeReplies.clear();
+        
+        int64_t now = steadyMillis();
+        if (master && now - lastTimeExchangeMs >= config.timeExchangeIntervalMs) {
+            lastTimeExchangeMs = now;
+            std::map<std::string, std::string> request = {
+                {"target", *master}, {"sent", std::to_string(syncManager->localUs())},
+            };
+            if (syncManager->isSynchronized()) {
+                // Con l'orologio sincronizzato all'arrivo il Master verifica l'allineamento del sink
+                auto exchange = syncManager->getLastExchange();
+                uint64_t transitUs = exchange ? exchange->roundTripUs / 2 : 0;
+                request["synced"] = std::to_string(syncManager->nowUs() + transitUs);
+            }
+            packets.push_back(MeshPacket::createCommand("sync.time_request", request));
+        }
+        
+        // I sink oltre il primo hop non raggiungono il Master con gli scambi: il beacon arriva tramite i Repeater
+        if (config.role == NodeRole::Master && config.meshBeaconIntervalMs != 0
+            && now - lastBeaconMs >= config.meshBeaconIntervalMs) {
+            lastBeaconMs = now;
+            packets.push_back(MeshPacket::createTimeBeacon(syncManager->now()));
+        }
+    }
+    
+    for (const auto& packet : packets) {
+        meshNetwork->sendPacket(packet);
+    }
+}
+
+void SaberProtocol::runSurvey() {
+    // I nodi si leggono prima di eventsMutex: il thread di rete lo acquisisce col proprio mutex occupato
+    std::vector<std::string> peers = meshNetwork->getActiveNodes();
+    std::vector<MeshPacket> packets;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        for (const auto& reply : pendingSurveyReplies) {
+            packets.push_back(MeshPacket::createCommand("survey.reply", {
+                {"target", reply.first}, {"probe", reply.second},
+            }));
+        }
+        pendingSurveyReplies.clear();
+        
+        int64_t now = steadyMillis();
+        if (surveyActive && coverageSurvey && now - lastSurveyProbeMs >= config.surveyIntervalMs) {
+            lastSurveyProbeMs = now;
+            for (const auto& peer : peers) {
+                if (peer == config.nodeId) {
+                    continue;
+                }
+                if (auto probeId = coverageSurvey->recordProbe(peer)) {
+                    packets.push_back(MeshPacket::createCommand("survey.probe", {
+                        {"target", peer}, {"probe", std::to_string(*probeId)},
+                    }));
+                }
+            }
+        }
+    }
+    
+    for (const auto& packet : packets) {
+        meshNetwork->sendPacket(packet);
+    }
+}
+
+void SaberProtocol::flushTraceReplies() {
+    std::vector<std::string> requesters;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        requesters.swap(pendingTraceReplies);
+    }
+    if (requesters.empty()) {
+        return;
+    }
+    
+    std::vector<RouteTrace> traces = meshNetwork->getRouteTraces();
+    for (const auto& requester : requesters) {
+        for (const auto& trace : traces) {
+            std::string path;
+            for (uint16_t hop : trace.path) {
+                char hex[5];
+                std::snprintf(hex, sizeof(hex), "%04x", hop);
+                path += (path.empty() ? "" : ",") + std::string(hex);
+            }
+            meshNetwork->sendPacket(MeshPacket::createCommand("trace.reply", {
+                {"target", requester}, {"stream", std::to_string(trace.streamId)},
+                {"source", trace.source}, {"hops", std::to_string(trace.hops)},
+                {"ttl", std::to_string(trace.ttl)}, {"path", path},
+                {"at", std::to_string(trace.observedAtMs)},
+            }));
+        }
+    }
+}
+
+void SaberProtocol::flushArtworkReplies() {
+    std::vector<std::pair<std::string, std::vector<uint8_t>>> replies;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        for (const auto& hash : pendingArtworkReplies) {
+            auto it = artworkStore.find(hash);
+            if (it != artworkStore.end()) {
+                replies.emplace_back(it->first, it->second);
+            }
+        }
+        pendingArtworkReplies.clear();
+    }
+    
+    for (const auto& reply : replies) {
+        meshNetwork->sendPacket(MeshPacket::createArtwork(reply.first, reply.second));
+    }
+}
+
+bool SaberProtocol::openProvisioning(uint32_t durationSeconds) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    meshNetwork->openProvisioning(durationSeconds * 1000);
+    return true;
+}
+
+bool SaberProtocol::closeProvisioning() {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    meshNetwork->closeProvisioning();
+    return true;
+}
+
+ProvisioningStatus SaberProtocol::getProvisioningStatus() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return {};
+    }
+    
+    return meshNetwork->getProvisioningStatus();
+}
+
+bool SaberProtocol::requestJoin() {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    return meshNetwork->requestJoin();
+}
+
+std::string SaberProtocol::runProvisionCommand(const std::vector<std::string>& args) {
+    // Uso: provision open <durata>[s|m] | provision close | provision status
+    if (args.size() == 2 && args[0] == "open") {
+        std::string duration = args[1];
+        uint32_t multiplier = 1;
+        if (!duration.empty() && (duration.back() == 's' || duration.back() == 'm')) {
+            multiplier = duration.back() == 'm' ? 60 : 1;
+            duration.pop_back();
+        }
+        uint32_t seconds = static_cast<uint32_t>(std::stoul(duration)) * multiplier;
+        if (seconds == 0) {
+            throw std::invalid_argument("durata non valida: " + args[1]);
+        }
+        openProvisioning(seconds);
+        return "aperta per " + std::to_string(seconds) + "s";
+    }
+    if (args.size() == 1 && args[0] == "close") {
+        closeProvisioning();
+        return "chiusa";
+    }
+    if (args.size() == 1 && args[0] == "status") {
+        ProvisioningStatus status = getProvisioningStatus();
+        AdmissionStats admission = getAdmissionStats();
+        auto limit = [](uint32_t value) { return value > 0 ? std::to_string(value) : std::string("-"); };
+        return std::string(status.open ? "aperta" : "chiusa") 
+             + " remaining_s=" + std::to_string(status.remainingMs / 1000)
+             + " accepted=" + std::to_string(status.accepted)
+             + " refused=" + std::to_string(status.refused)
+             + " throttled=" + std::to_string(status.throttled)
+             + " nodes=" + std::to_string(admission.nodes) + "/" + limit(admission.maxNodes)
+             + " sinks=" + std::to_string(admission.sinks) + "/" + limit(admission.maxSinks)
+             + " capacity_rejected=" + std::to_string(admission.rejected);
+    }
+    throw std::invalid_argument("uso: provision open <durata>[s|m] | provision close | provision status");
+}
+
+bool SaberProtocol::startGroupPlayback(const std::string& zone, const std::string& playlist) {
+    // Lo span del Master copre l'intera attesa fino alla propria barriera
+    TraceSpan span = tracer->startSpan("group.start", SpanKind::Producer, SpanScope::current());
+    span.attributes["saber.zone"] = zone;
+    PlaybackCommand command;
+    command.action = PlaybackAction::Start;
+    command.zone = zone;
+    command.playlist = playlist;
+    return sendPlaybackCommand(command, span);
+}
+
+bool SaberProtocol::stopGroupPlayback(const std::string& zone) {
+    PlaybackCommand command;
+    command.action = PlaybackAction::Stop;
+    command.zone = zone;
+    return sendPlaybackCommand(command);
+}
+
+bool SaberProtocol::pauseGroupPlayback(const std::string& zone) {
+    PlaybackCommand command;
+    command.action = PlaybackAction::Pause;
+    command.zone = zone;
+    return sendPlaybackCommand(command);
+}
+
+bool SaberProtocol::resumeGroupPlayback(const std::string& zone) {
+    PlaybackCommand command;
+    command.action = PlaybackAction::Resume;
+    command.zone = zone;
+    return sendPlaybackCommand(command);
+}
+
+bool SaberProtocol::setZoneVolume(const std::string& zone, uint8_t volumePercent) {
+    if (volumePercent > 100) {
+        throw std::invalid_argument("Volume oltre il 100%");
+    }
+    PlaybackCommand command;
+    command.action = PlaybackAction::Volume;
+    command.zone = zone;
+    command.volumePercent = volumePercent;
+    return sendPlaybackCommand(command);
+}
+
+bool SaberProtocol::seekGroupPlayback(const std::string& zone, uint64_t positionMs) {
+    PlaybackCommand command;
+    command.action = PlaybackAction::Seek;
+    command.zone = zone;
+    command.positionMs = positionMs;
+    return sendPlaybackCommand(command);
+}
+
+uint8_t SaberProtocol::getVolume() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    return audioSync ? audioSync->getVolume() : 100;
+}
+
+bool SaberProtocol::isPlaybackPaused() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    return audioSync && audioSync->isPaused();
+}
+
+void SaberProtocol::setPlaybackCommandHandler(std::function<void(const PlaybackCommand&)> handler) {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    playbackCommandHandler = std::move(handler);
+}
+
+std::vector<CommandStatus> SaberProtocol::getCommandStatuses() const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    return commandTracker.recent();
+}
+
+std::optional<CommandStatus> SaberProtocol::getCommandStatus(uint32_t id) const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    return commandTracker.get(id);
+}
+
+bool SaberProtocol::sendPlaybackCommand(PlaybackCommand command, std::optional<TraceSpan> span) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    // Si attende la conferma dei nodi della zona attivi adesso
+    std::set<std::string> expected;
+    auto zones = meshNetwork->getZones();
+    for (const auto& nodeId : meshNetwork->getActiveNodes()) {
+        auto zone = zones.find(nodeId);
+        bool inZone = command.zone == "all" || (zone != zones.end() && zone->second == command.zone);
+        if (nodeId != config.nodeId && inZone) {
+            expected.insert(nodeId);
+        }
+    }
+    
+    uint64_t now = syncManager->now();
+    command.atMs = now + config.startBarrierLeadMs;
+    {
+        std::lock_guard<std::mutex> eventsLock(eventsMutex);
+        command = commandTracker.issue(command, expected, now);
+    }
+    MeshPacket packet = MeshPacket::createZoneCommand(
+        command.zone, "playback." + playbackActionToString(command.action), encodePlaybackCommand(command));
+    if (span) {
+        SpanScope spanScope(*span);
+        meshNetwork->sendPacket(packet);
+    } else {
+        meshNetwork->sendPacket(packet);
+    }
+    
+    std::lock_guard<std::mutex> eventsLock(eventsMutex);
+    pendingPlayback.push_back({command, "", span});
+    return true;
+}
+
+void SaberProtocol::runPendingPlayback() {
+    std::vector<PendingPlayback> ready;
+    uint64_t now = syncManager->now();
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        size_t expired = commandTracker.expire(now);
+        if (expired > 0) {
+            SABER_LOG(Warn, "protocol", expired << " nodi non hanno confermato un comando di riproduzione");
+        }
+        // Prendo le barriere che scadono entro il prossimo giro del thread di runtime
+        for (auto it = pendingPlayback.begin(); it != pendingPlayback.end();) {
+            if (it->command.atMs <= now + 100) {
+                ready.push_back(*it);
+                it = pendingPlayback.erase(it);
+            } else {
+                ++it;
+            }
+        }
+    }
+    if (ready.empty()) {
+        return;
+    }
+    
+    std::sort(ready.begin(), ready.end(), [](const PendingPlayback& a, const PendingPlayback& b) {
+        return a.command.atMs < b.command.atMs;
+    });
+    
+    std::string localZone;
+    {
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        if (meshNetwork) {
+            auto zones = meshNetwork->getZones();
+            auto it = zones.find(config.nodeId);
+            if (it != zones.end()) {
+                localZone = it->second;
+            }
+        }
+    }
+    
+    for (auto& pending : ready) {
+        const PlaybackCommand& command = pending.command;
+        // Il Master è la sorgente di ogni gruppo; i sink seguono solo la propria zona.
+        // Pausa e volume riguardano solo chi riproduce, quindi il Master solo se è nella zona
+        bool inZone = command.zone == "all" || command.zone == localZone;
+        bool isSource = config.role == NodeRole::Master && (command.action == PlaybackAction::Start
+                     || command.action == PlaybackAction::Stop || command.action == PlaybackAction::Seek);
+        if (!inZone && !isSource) {
+            continue;
+        }
+        
+        now = syncManager->now();
+        if (command.atMs > now) {
+            std::this_thread::sleep_for(std::chrono::milliseconds(command.atMs - now));
+        }
+        if (pending.span) {
+            // Il ritardo sulla barriera è ciò che la traccia deve mostrare
+            now = syncManager->now();
+            pending.span->attributes["saber.barrier.late_ms"] = 
+                std::to_string(now > command.atMs ? now - command.atMs : 0);
+            tracer->endSpan(*pending.span);
+        }
+        
+        auto failure = applyPlaybackCommand(command);
+        if (failure) {
+            SABER_LOG(Warn, "protocol", "Comando " << playbackActionToString(command.action) << " del gruppo " 
+                      << command.zone << " non eseguito: " << *failure);
+        }
+        journal->append("audio", "playback_" + playbackActionToString(command.action), config.nodeId,
+                        "zone=" + command.zone + (failure ? " error=" + *failure : ""), syncManager->now());
+        
+        // La conferma torna solo al mittente che numera i comandi
+        if (command.id != 0 && !pending.origin.empty()) {
+            std::lock_guard<std::mutex> lock(protocolMutex);
+            if (meshNetwork) {
+                meshNetwork->sendPacket(MeshPacket::createCommand("command.ack", {
+                    {"target", pending.origin}, {"id", std::to_string(command.id)},
+                    {"outcome", commandOutcomeToString(failure ? CommandOutcome::Failed : CommandOutcome::Applied)},
+                    {"detail", failure.value_or("")},
+                }));
+            }
+        }
+    }
+}
+
+std::optional<std::string> SaberProtocol::applyPlaybackCommand(const PlaybackCommand& command) {
+    std::function<void(const PlaybackCommand&)> handler;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        handler = playbackCommandHandler;
+    }
+    
+    switch (command.action) {
+        case PlaybackAction::Start: {
+            {
+                std::lock_guard<std::mutex> lock(eventsMutex);
+                activePlaylist = command.playlist;
+            }
+            SABER_LOG(Info, "protocol", "Avvio del gruppo " << command.zone 
+                      << (command.playlist.empty() ? "" : " con la playlist " + command.playlist));
+            if (!startAudioPlayback()) {
+                return "riproduzione non avviata";
+            }
+            break;
+        }
+        case PlaybackAction::Stop: {
+            {
+                std::lock_guard<std::mutex> lock(eventsMutex);
+                activePlaylist.clear();
+            }
+            SABER_LOG(Info, "protocol", "Arresto del gruppo " << command.zone);
+            stopAudioPlayback();
+            break;
+        }
+        case PlaybackAction::Pause:
+        case PlaybackAction::Resume:
+        case PlaybackAction::Volume: {
+            std::lock_guard<std::mutex> lock(protocolMutex);
+            if (!audioSync) {
+                return "sincronizzatore audio non inizializzato";
+            }
+            if (command.action == PlaybackAction::Pause) {
+                audioSync->pausePlayback();
+            } else if (command.action == PlaybackAction::Resume) {
+                audioSync->resumePlayback();
+            } else {
+                audioSync->setVolume(command.volumePercent);
+            }
+            SABER_LOG(Info, "protocol", "Gruppo " << command.zone << ": " << playbackActionToString(command.action)
+                      << (command.action == PlaybackAction::Volume 
+                          ? " al " + std::to_string(command.volumePercent) + "%" : ""));
+            break;
+        }
+        case PlaybackAction::Seek: {
+            // Lo stream non ha posizione: senza l'applicazione non c'è niente da spostare
+            if (!handler) {
+                return "nessun gestore dei comandi di riproduzione";
+            }
+            SABER_LOG(Info, "protocol", "Gruppo " << command.zone << " spostato a " << command.positionMs << "ms");
+            break;
+        }
+    }
+    
+    if (handler) {
+        try {
+            handler(command);
+        } catch (const std::exception& e) {
+            return e.what();
+        }
+    }
+    return std::nullopt;
+}
+
+bool SaberProtocol::startPartyMode(const std::vector<std::string>& zones, StreamId streamId,
+                                   const std::string& playlist) {
+    if (zones.empty()) {
+        throw std::invalid_argument("nessuna zona indicata");
+    }
+    
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    // Un solo buffer per tutte le zone, dimensionato sul sink più lento
+    bool everyZone = std::find(zones.begin(), zones.end(), "all") != zones.end();
+    std::vector<std::string> members;
+    for (const auto& entry : meshNetwork->getZones()) {
+        if (everyZone || std::find(zones.begin(), zones.end(), entry.second) != zones.end()) {
+            members.push_back(entry.first);
+        }
+    }
+    auto latencies = meshNetwork->getNodeLatencies();
+    uint32_t bufferMs = std::max(config.spec.defaultBufferMs, planner.zoneBufferMs(members, latencies));
+    if (bufferMs > planner.getBudgetMs()) {
+        SABER_LOG(Warn, "protocol", "Buffer della modalità festa di " << bufferMs 
+                  << "ms oltre il budget di latenza");
+    }
+    // In modalità rigorosa i sink troppo lenti restano fuori invece di alzare il buffer
+    std::vector<std::string> rejected = planner.rejectedSinks(members, latencies);
+    {
+        std::lock_guard<std::mutex> eventsLock(eventsMutex);
+        bool starting = std::any_of(pendingParty.begin(), pendingParty.end(), 
+                                    [](const PendingParty& pending) { return pending.start; });
+        if (partyMode || starting) {
+            throw std::invalid_argument("modalità festa già attiva");
+        }
+    }
+    for (const auto& nodeId : rejected) {
+        SABER_LOG(Warn, "protocol", "Sink " << nodeId << " escluso dalla modalità festa: latenza di " 
+                  << latencies[nodeId] << "ms oltre il budget di " << planner.getBudgetMs() << "ms");
+        journal->append("latency", "sink_rejected", nodeId, "party", syncManager->now());
+    }
+    
+    PartyMode party{zones, streamId, bufferMs, rejected, playlist, syncManager->now() + config.startBarrierLeadMs};
+    meshNetwork->sendPacket(MeshPacket::createCommand("party.start", {
+        {"zones", joinList(zones)}, {"stream", std::to_string(streamId)}, 
+        {"buffer_ms", std::to_string(bufferMs)}, {"rejected", joinList(rejected)}, {"playlist", playlist}, 
+        {"at", std::to_string(party.startAtMs)}
+    }));
+    journal->append("mesh", "party_started", config.nodeId, joinList(zones) + " -> stream " 
+                    + std::to_string(streamId), syncManager->now());
+    
+    std::lock_guard<std::mutex> eventsLock(eventsMutex);
+    pendingParty.push_back({true, party, party.startAtMs});
+    return true;
+}
+
+bool SaberProtocol::stopPartyMode() {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    std::lock_guard<std::mutex> eventsLock(eventsMutex);
+    bool starting = std::any_of(pendingParty.begin(), pendingParty.end(), 
+                                [](const PendingParty& pending) { return pending.start; });
+    if (!partyMode && !starting) {
+        return false;
+    }
+    
+    uint64_t atMs = syncManager->now() + config.startBarrierLeadMs;
+    meshNetwork->sendPacket(MeshPacket::createCommand("party.stop", {{"at", std::to_string(atMs)}}));
+    journal->append("mesh", "party_stopped", config.nodeId, "", syncManager->now());
+    pendingParty.push_back({false, PartyMode(), atMs});
+    return true;
+}
+
+std::optional<PartyMode> SaberProtocol::getPartyMode() const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    return partyMode;
+}
+
+void SaberProtocol::runPendingParty() {
+    std::vector<PendingParty> ready;
+    uint64_t now = syncManager->now();
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        for (auto it = pendingParty.begin(); it != pendingParty.end();) {
+            if (it->atMs <= now + 100) {
+                ready.push_back(*it);
+                it = pendingParty.erase(it);
+            } else {
+                ++it;
+            }
+        }
+    }
+    if (ready.empty()) {
+        return;
+    }
+    
+    std::sort(ready.begin(), ready.end(), [](const PendingParty& a, const PendingParty& b) {
+        return a.atMs < b.atMs;
+    });
+    
+    std::optional<std::string> localZone;
+    {
+        std::lock_guard<std::mutex> lock(protocolMutex);
+        if (meshNetwork) {
+            localZone = meshNetwork->getZone(config.nodeId);
+        }
+    }
+    
+    for (const auto& pending : ready) {
+        const auto& zones = pending.party.zones;
+        const auto& rejected = pending.party.rejected;
+        bool member = config.role == NodeRole::Master 
+                   || ((std::find(zones.begin(), zones.end(), "all") != zones.end()
+                        || (localZone && std::find(zones.begin(), zones.end(), *localZone) != zones.end()))
+                       && std::find(rejected.begin(), rejected.end(), config.nodeId) == rejected.end());
+        bool joined;
+        {
+            std::lock_guard<std::mutex> lock(eventsMutex);
+            joined = partyMode.has_value();
+        }
+        if ((pending.start && (!member || joined)) || (!pending.start && !joined)) {
+            continue;
+        }
+        
+        std::set<StreamId> restore;
+        if (pending.start && config.role == NodeRole::Sink) {
+            // Il cambio di stream precede la barriera, così il nuovo albero è pronto all'avvio
+            {
+                std::lock_guard<std::mutex> lock(protocolMutex);
+                restore = subscribedStreams;
+            }
+            for (StreamId streamId : restore) {
+                if (streamId != pending.party.streamId && !isIntercomStream(streamId)) {
+                    unsubscribeStream(streamId);
+                }
+            }
+            subscribeStream(pending.party.streamId);
+        }
+        
+        now = syncManager->now();
+        if (pending.atMs > now) {
+            std::this_thread::sleep_for(std::chrono::milliseconds(pending.atMs - now));
+        }
+        
+        if (pending.start) {
+            bool wasPlaying = audioSync->isPlaybackSynchronized();
+            audioSync->stopPlayback();
+            audioSync->setBufferOverride(pending.party.bufferMs);
+            {
+                std::lock_guard<std::mutex> lock(eventsMutex);
+                partyMode = pending.party;
+                partyRestoreStreams = restore;
+                partyResumePlayback = wasPlaying;
+                activePlaylist = pending.party.playlist;
+            }
+            SABER_LOG(Info, "protocol", "Modalità festa: zone " << joinList(zones) << " sullo stream " 
+                      << pending.party.streamId << " con buffer di " << pending.party.bufferMs << "ms");
+            startAudioPlayback();
+        } else {
+            std::optional<PartyMode> party;
+            bool resume;
+            {
+                std::lock_guard<std::mutex> lock(eventsMutex);
+                party.swap(partyMode);
+                restore.swap(partyRestoreStreams);
+                resume = partyResumePlayback;
+                activePlaylist.clear();
+            }
+            stopAudioPlayback();
+            audioSync->setBufferOverride(std::nullopt);
+            
+            // Ogni zona torna ai propri stream
+            if (config.role == NodeRole::Sink) {
+                if (restore.count(party->streamId) == 0) {
+                    unsubscribeStream(party->streamId);
+                }
+                for (StreamId streamId : restore) {
+                    subscribeStream(streamId);
+                }
+            }
+            SABER_LOG(Info, "protocol", "Fine della modalità festa sullo stream " << party->streamId);
+            if (resume) {
+                startAudioPlayback();
+            }
+        }
+    }
+}
+
+std::string SaberProtocol::runPartyCommand(const std::vector<std::string>& args) {
+    // Uso: party start <zona,zona|all> <stream> [playlist] | party stop | party status
+    if ((args.size() == 3 || args.size() == 4) && args[0] == "start") {
+        StreamId streamId = static_cast<StreamId>(std::stoul(args[2]));
+        if (!startPartyMode(splitList(args[1]), streamId, args.size() == 4 ? args[3] : "")) {
+            throw std::invalid_argument("rete mesh non inizializzata");
+        }
+        return "modalità festa per " + args[1] + " tra " + std::to_string(config.startBarrierLeadMs) + "ms";
+    }
+    if (args.size() == 1 && args[0] == "stop") {
+        if (!stopPartyMode()) {
+            throw std::invalid_argument("modalità festa non attiva");
+        }
+        return "fine della modalità festa tra " + std::to_string(config.startBarrierLeadMs) + "ms";
+    }
+    if (args.size() == 1 && args[0] == "status") {
+        auto party = getPartyMode();
+        if (!party) {
+            return "non attiva";
+        }
+        return "zone=" + joinList(party->zones) + " stream=" + std::to_string(party->streamId) 
+             + " buffer=" + std::to_string(party->bufferMs) + "ms" 
+             + (party->rejected.empty() ? "" : " rejected=" + joinList(party->rejected))
+             + (party->playlist.empty() ? "" : " playlist=" + party->playlist);
+    }
+    throw std::invalid_argument("uso: party start <zona,zona|all> <stream> [playlist] | party stop | party status");
+}
+
+bool SaberProtocol::startIntercom(const std::string& nodeA, const std::string& nodeB) {
+    if (nodeA.empty() || nodeB.empty()) {
+        throw std::invalid_argument("nodo dell'intercom non indicato");
+    }
+    if (nodeA == nodeB) {
+        throw std::invalid_argument("l'intercom richiede due nodi diversi");
+    }
+    
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    std::lock_guard<std::mutex> eventsLock(eventsMutex);
+    bool starting = std::any_of(pendingIntercom.begin(), pendingIntercom.end(), 
+                                [](const std::optional<IntercomSession>& pending) { return pending.has_value(); });
+    if (intercomSession || starting) {
+        throw std::invalid_argument("intercom già attivo");
+    }
+    
+    IntercomSession session;
+    session.nodeA = nodeA;
+    session.nodeB = nodeB;
+    session.startedAtMs = syncManager->now();
+    meshNetwork->sendPacket(MeshPacket::createCommand("intercom.start", {
+        {"a", nodeA}, {"b", nodeB}, {"at", std::to_string(session.startedAtMs)}
+    }));
+    journal->append("mesh", "intercom_started", config.nodeId, nodeA + " <-> " + nodeB, syncManager->now());
+    pendingIntercom.push_back(session);
+    return true;
+}
+
+bool SaberProtocol::stopIntercom() {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    std::lock_guard<std::mutex> eventsLock(eventsMutex);
+    bool starting = std::any_of(pendingIntercom.begin(), pendingIntercom.end(), 
+                                [](const std::optional<IntercomSession>& pending) { return pending.has_value(); });
+    if (!intercomSession && !starting) {
+        return false;
+    }
+    
+    meshNetwork->sendPacket(MeshPacket::createCommand("intercom.stop", {}));
+    journal->append("mesh", "intercom_stopped", config.nodeId, "", syncManager->now());
+    pendingIntercom.push_back(std::nullopt);
+    return true;
+}
+
+bool SaberProtocol::setIntercomTalking(bool talking) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    std::lock_guard<std::mutex> eventsLock(eventsMutex);
+    if (!intercomSession || !intercomSession->involves(config.nodeId)) {
+        return false;
+    }
+    
+    meshNetwork->sendPacket(MeshPacket::createCommand("intercom.talk", {{"on", talking ? "1" : "0"}}));
+    if (talking) {
+        intercomSession->talking.insert(config.nodeId);
+    } else {
+        intercomSession->talking.erase(config.nodeId);
+    }
+    return true;
+}
+
+bool SaberProtocol::sendIntercomFrame(const AudioFrame& frame) {
+    std::optional<StreamId> streamId;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        if (intercomSession && intercomSession->talking.count(config.nodeId) > 0) {
+            streamId = intercomSession->talkStream(config.nodeId);
+        }
+    }
+    if (!streamId) {
+        return false;
+    }
+    
+    std::vector<uint8_t> payload;
+    {
+        std::lock_guard<std::mutex> lock(intercomMutex);
+        if (!intercomEncoder) {
+            return false;
+        }
+        try {
+            payload = intercomEncoder->encode(frame);
+        } catch (const std::exception& e) {
+            SABER_LOG(Warn, "audio", "Frame dell'intercom non codificato: " << e.what());
+            return false;
+        }
+    }
+    
+    // Buffer minimo: la voce viene riprodotta appena arriva
+    uint64_t playoutTimeUs = syncManager->nowUs() + static_cast<uint64_t>(config.intercom.bufferMs) * 1000;
+    return sendAudioFrame(*streamId, playoutTimeUs, payload);
+}
+
+void SaberProtocol::setIntercomFrameHandler(std::function<void(const std::string&, const AudioFrame&)> handler) {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    intercomFrameHandler = std::move(handler);
+}
+
+std::optional<IntercomSession> SaberProtocol::getIntercomSession() const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    return intercomSession;
+}
+
+uint32_t SaberProtocol::getBeaconIntervalMs() const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    if (intercomSession && config.intercom.beaconIntervalMs > 0) {
+        return std::min(config.beaconIntervalMs, config.intercom.beaconIntervalMs);
+    }
+    return config.beaconIntervalMs;
+}
+
+void SaberProtocol::runPendingIntercom() {
+    std::vector<std::optional<IntercomSession>> ready;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        ready.swap(pendingIntercom);
+    }
+    
+    for (const auto& pending : ready) {
+        std::optional<IntercomSession> current = getIntercomSession();
+        if (pending.has_value() == current.has_value()) {
+            continue;
+        }
+        
+        if (pending) {
+            // I nodi coinvolti ascoltano l'altro e preparano i codec della voce
+            if (auto listen = pending->listenStream(config.nodeId)) {
+                subscribeStream(*listen);
+                std::lock_guard<std::mutex> lock(intercomMutex);
+                try {
+                    intercomEncoder = std::make_unique<Lc3Encoder>(INTERCOM_SAMPLE_RATE_HZ, 1, 
+                                                                   config.intercom.bitrateKbps);
+                    intercomDecoder = std::make_unique<Lc3Decoder>(INTERCOM_SAMPLE_RATE_HZ, 1);
+                } catch (const std::exception& e) {
+                    SABER_LOG(Warn, "audio", "Codec dell'intercom non disponibile: " << e.what());
+                }
+            }
+            {
+                std::lock_guard<std::mutex> lock(eventsMutex);
+                intercomSession = pending;
+            }
+            SABER_LOG(Info, "protocol", "Intercom tra " << pending->nodeA << " e " << pending->nodeB);
+        } else {
+            {
+                std::lock_guard<std::mutex> lock(eventsMutex);
+                intercomSession.reset();
+            }
+            if (auto listen = current->listenStream(config.nodeId)) {
+                unsubscribeStream(*listen);
+                std::lock_guard<std::mutex> lock(intercomMutex);
+                intercomEncoder.reset();
+                intercomDecoder.reset();
+            }
+            SABER_LOG(Info, "protocol", "Fine dell'intercom tra " << current->nodeA << " e " << current->nodeB);
+        }
+    }
+}
+
+std::string SaberProtocol::runIntercomCommand(const std::vector<std::string>& args) {
+    // Uso: intercom start <nodo> <nodo> | intercom stop | intercom talk on|off | intercom status
+    if (args.size() == 3 && args[0] == "start") {
+        if (!startIntercom(args[1], args[2])) {
+            throw std::invalid_argument("rete mesh non inizializzata");
+        }
+        return "intercom tra " + args[1] + " e " + args[2];
+    }
+    if (args.size() == 1 && args[0] == "stop") {
+        if (!stopIntercom()) {
+            throw std::invalid_argument("intercom non attivo");
+        }
+        return "fine dell'intercom";
+    }
+    if (args.size() == 2 && args[0] == "talk" && (args[1] == "on" || args[1] == "off")) {
+        if (!setIntercomTalking(args[1] == "on")) {
+            throw std::invalid_argument("il nodo non partecipa all'intercom");
+        }
+        return args[1] == "on" ? "in trasmissione" : "in ascolto";
+    }
+    if (args.size() == 1 && args[0] == "status") {
+        auto session = getIntercomSession();
+        if (!session) {
+            return "non attivo";
+        }
+        std::vector<std::string> talking(session->talking.begin(), session->talking.end());
+        return "nodi=" + session->nodeA + "," + session->nodeB 
+             + " parlano=" + (talking.empty() ? "-" : joinList(talking))
+             + " beacon=" + std::to_string(getBeaconIntervalMs()) + "ms";
+    }
+    throw std::invalid_argument("uso: intercom start <nodo> <nodo> | intercom stop | intercom talk on|off | intercom status");
+}
+
+std::string SaberProtocol::runPlaybackCommand(const std::vector<std::string>& args) {
+    // Uso: playback start <zona> [playlist] | playback stop|pause|resume <zona> | playback seek <zona> <ms> |
+    //      playback volume <zona> <0-100> | playback status
+    const std::string usage = "uso: playback start <zona> [playlist] | playback stop|pause|resume <zona> | "
+                              "playback seek <zona> <ms> | playback volume <zona> <0-100> | playback status";
+    if (args.size() == 1 && args[0] == "status") {
+        std::string result;
+        for (const auto& status : getCommandStatuses()) {
+            result += (result.empty() ? "#" : "; #") + std::to_string(status.command.id) + " " 
+                    + playbackActionToString(status.command.action) + " " + status.command.zone + ":";
+            for (const auto& node : status.nodes) {
+                result += " " + node.first + "=" + commandOutcomeToString(node.second.outcome)
+                        + (node.second.detail.empty() ? "" : "(" + node.second.detail + ")");
+            }
+        }
+        return result.empty() ? "nessun comando" : result;
+    }
+    if (args.size() < 2) {
+        throw std::invalid_argument(usage);
+    }
+    
+    const std::string& zone = args[1];
+    bool sent;
+    std::string result;
+    if ((args.size() == 2 || args.size() == 3) && args[0] == "start") {
+        sent = startGroupPlayback(zone, args.size() == 3 ? args[2] : "");
+        result = "avvio di " + zone;
+    } else if (args.size() == 2 && args[0] == "stop") {
+        sent = stopGroupPlayback(zone);
+        result = "arresto di " + zone;
+    } else if (args.size() == 2 && args[0] == "pause") {
+        sent = pauseGroupPlayback(zone);
+        result = "pausa di " + zone;
+    } else if (args.size() == 2 && args[0] == "resume") {
+        sent = resumeGroupPlayback(zone);
+        result = "ripresa di " + zone;
+    } else if (args.size() == 3 && (args[0] == "seek" || args[0] == "volume")) {
+        // std::stoull accetta il segno meno e riavvolge il valore
+        unsigned long long value;
+        try {
+            if (args[2].front() == '-') {
+                throw std::out_of_range(args[2]);
+            }
+            value = std::stoull(args[2]);
+        } catch (const std::exception&) {
+            throw std::invalid_argument(args[0] + " non valido: " + args[2]);
+        }
+        if (args[0] == "seek") {
+            sent = seekGroupPlayback(zone, value);
+            result = "spostamento di " + zone + " a " + args[2] + "ms";
+        } else {
+            if (value > 100) {
+                throw std::invalid_argument("volume non valido: " + args[2]);
+            }
+            sent = setZoneVolume(zone, static_cast<uint8_t>(value));
+            result = "volume di " + zone + " al " + args[2] + "%";
+        }
+    } else {
+        throw std::invalid_argument(usage);
+    }
+    if (!sent) {
+        throw std::invalid_argument("rete mesh non inizializzata");
+    }
+    return result + " tra " + std::to_string(config.startBarrierLeadMs) + "ms";
+}
+
+#ifndef SABER_MINIMAL_SINK
+uint32_t SaberProtocol::addScheduledAction(uint16_t minuteOfDay, uint8_t days, const std::string& command) {
+    if (command.compare(0, 8, "schedule") == 0) {
+        throw std::invalid_argument("una programmazione non può modificare la programmazione");
+    }
+    uint32_t id = scheduler.add(minuteOfDay, days, command);
+    saveSchedule();
+    return id;
+}
+
+bool SaberProtocol::updateScheduledAction(uint32_t id, uint16_t minuteOfDay, uint8_t days, 
+                                          const std::string& command) {
+    if (command.compare(0, 8, "schedule") == 0) {
+        throw std::invalid_argument("una programmazione non può modificare la programmazione");
+    }
+    if (!scheduler.update(id, minuteOfDay, days, command)) {
+        return false;
+    }
+    saveSchedule();
+    return true;
+}
+
+bool SaberProtocol::removeScheduledAction(uint32_t id) {
+    if (!scheduler.remove(id)) {
+        return false;
+    }
+    saveSchedule();
+    return true;
+}
+
+std::vector<ScheduleEntry> SaberProtocol::getSchedule() const {
+    return scheduler.entries();
+}
+
+void SaberProtocol::restoreState() {
+    if (!config.stateFile) {
+        return;
+    }
+    stateStore = std::make_unique<StateStore>(*config.stateFile);
+    StateRecoveryReport report = stateStore->recover();
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        stateRecovery = report;
+    }
+    
+    size_t peers = 0;
+    size_t zones = 0;
+    size_t revocations = 0;
+    for (const auto& entry : stateStore->entries()) {
+        auto dot = entry.first.find('.');
+        std::string kind = entry.first.substr(0, dot);
+        std::string nodeId = dot == std::string::npos ? "" : entry.first.substr(dot + 1);
+        if (nodeId.empty() || nodeId == config.nodeId) {
+            continue;
+        }
+        if (kind == "peer") {
+            // Valore: "<ruolo> <chiave pubblica in esadecimale>"
+            auto space = entry.second.find(' ');
+            auto role = nodeRoleFromString(entry.second.substr(0, space));
+            auto key = fromHex(space == std::string::npos ? "" : entry.second.substr(space + 1));
+            if (!role || !key) {
+                SABER_LOG(Warn, "state", "Nodo persistito non valido: " << nodeId);
+                continue;
+            }
+            if (!key->empty()) {
+                crypto->registerNodeKey(nodeId, *key);
+            }
+            meshNetwork->registerNode(nodeId, *role);
+            peers++;
+        } else if (kind == "zone") {
+            meshNetwork->assignZone(nodeId, entry.second);
+            zones++;
+        } else if (kind == "crl") {
+            // Chiave: "crl.<token|node>.<oggetto>", valore: istante della revoca
+            auto separator = nodeId.find('.');
+            auto revocationKind = revocationKindFromString(nodeId.substr(0, separator));
+            uint64_t revokedAt = 0;
+            try {
+                revokedAt = std::stoull(entry.second);
+            } catch (const std::exception&) {
+                revocationKind.reset();
+            }
+            if (!revocationKind || separator == std::string::npos) {
+                SABER_LOG(Warn, "state", "Revoca persistita non valida: " << nodeId);
+                continue;
+            }
+            revocationList->add(Revocation{*revocationKind, nodeId.substr(separator + 1), revokedAt});
+            revocations++;
+        }
+    }
+    
+    std::string detail = std::to_string(peers) + " nodi, " + std::to_string(zones) + " zone, " 
+                       + std::to_string(revocations) + " revoche";
+    if (report.fromBackup || report.discardedBytes > 0) {
+        detail += " (snapshot precedente: " + std::string(report.fromBackup ? "sì" : "no") + ", byte scartati: " 
+                + std::to_string(report.discardedBytes) + ")";
+        SABER_LOG(Warn, "state", "Stato ripristinato dopo un'interruzione: " << detail);
+    } else {
+        SABER_LOG(Info, "state", "Stato ripristinato: " << detail);
+    }
+    journal->append("state", "recovered", config.nodeId, detail, syncManager->now());
+}
+
+void SaberProtocol::persistState() {
+    if (!stateStore || !meshNetwork) {
+        return;
+    }
+    std::map<std::string, std::string> current;
+    auto keys = crypto ? crypto->getKnownPublicKeys() : std::map<std::string, std::vector<uint8_t>>{};
+    for (const auto& node : meshNetwork->getNodeRoles()) {
+        if (node.first == config.nodeId) {
+            continue;
+        }
+        auto key = keys.find(node.first);
+        current["peer." + node.first] = nodeRoleToString(node.second) + " " 
+                                      + (key != keys.end() ? toHex(key->second) : "");
+    }
+    for (const auto& zone : meshNetwork->getZones()) {
+        current["zone." + zone.first] = zone.second;
+    }
+    for (const auto& entry : revocationList->entries()) {
+        current["crl." + revocationKindToString(entry.kind) + "." + entry.subject] = std::to_string(entry.revokedAtMs);
+    }
+    
+    // Solo le differenze finiscono nel registro
+    auto stored = stateStore->entries();
+    bool written = true;
+    for (const auto& entry : current) {
+        auto previous = stored.find(entry.first);
+        if (previous == stored.end() || previous->second != entry.second) {
+            written = stateStore->put(entry.first, entry.second) && written;
+        }
+    }
+    for (const auto& entry : stored) {
+        if (current.count(entry.first) == 0) {
+            written = stateStore->erase(entry.first) && written;
+        }
+    }
+    if (!written) {
+        SABER_LOG(Warn, "state", "Impossibile scrivere lo stato in " << stateStore->getPath());
+    }
+}
+
+std::optional<StateRecoveryReport> SaberProtocol::getStateRecovery() const {
+    std::lock_guard<std::mutex> lock(eventsMutex);
+    return stateRecovery;
+}
+
+std::optional<UdpTransportStats> SaberProtocol::getUdpTransportStats() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    if (!udpTransport) {
+        return std::nullopt;
+    }
+    return udpTransport->getStats();
+}
+
+void SaberProtocol::setSimNetwork(std::shared_ptr<SimNetwork> network) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    simNetwork = std::move(network);
+}
+
+void SaberProtocol::setAuthorizationHook(std::shared_ptr<AuthorizationHook> hook) {
+    authorizationGate.setHook(std::move(hook));
+}
+
+void SaberProtocol::setAuthorizationHandler(FunctionAuthorizationHook::Function handler) {
+    if (!handler) {
+        authorizationGate.setHook(nullptr);
+        return;
+    }
+    authorizationGate.setHook(std::make_shared<FunctionAuthorizationHook>(std::move(handler)));
+}
+
+AuthorizationStats SaberProtocol::getAuthorizationStats() const {
+    return authorizationGate.getStats();
+}
+
+std::optional<std::string> SaberProtocol::authorizeCommand(const std::string& line, const std::string& token) {
+    AuthorizationRequest request;
+    request.action = AuthorizationAction::Command;
+    request.command = line;
+    if (auto verified = token.empty() ? std::nullopt : verifyControlToken(token)) {
+        request.nodeId = verified->nodeId;
+        request.tokenId = verified->tokenId;
+    }
+    
+    // Il thread della connessione attende la decisione, al più per authorizationTimeoutMs
+    auto decision = authorizationGate.decide(request);
+    if (!decision || decision->allowed) {
+        return std::nullopt;
+    }
+    std::string reason = decision->reason.empty() ? "negato dalla politica" : decision->reason;
+    SABER_LOG(Warn, "security", "Comando non autorizzato: " << line << " (" << reason << ")");
+    journal->append("security", "command_denied", request.nodeId.empty() ? config.nodeId : request.nodeId,
+                    line + " (" + reason + ")", syncManager->now());
+    return reason;
+}
+
+void SaberProtocol::saveSchedule() {
+    if (config.scheduleFile && !scheduler.save(*config.scheduleFile)) {
+        SABER_LOG(Error, "protocol", "Impossibile salvare la programmazione in " << *config.scheduleFile);
+    }
+}
+
+void SaberProtocol::runDueSchedule() {
+    if (config.role != NodeRole::Master || !controlServer) {
+        return;
+    }
+    for (const auto& entry : scheduler.due(syncManager->now())) {
+        std::string reply = controlServer->execute(entry.command, TokenScope::Admin);
+        SABER_LOG(Info, "schedule", "Azione " << entry.id << " (" << entry.command << "): " << reply);
+    }
+}
+
+std::string SaberProtocol::runScheduleCommand(const std::vector<std::string>& args) {
+    // Uso: schedule list | schedule add <HH:MM> [giorni] <comando> 
+    //    | schedule edit <id> <HH:MM> [giorni] <comando> | schedule remove <id>
+    const std::string usage = "uso: schedule list | schedule add <HH:MM> [giorni] <comando> | "
+                              "schedule edit <id> <HH:MM> [giorni] <comando> | schedule remove <id>";
+    if (args.size() == 1 && args[0] == "list") {
+        std::string result;
+        for (const auto& entry : getSchedule()) {
+            result += (result.empty() ? "" : "; ") + entry.toString();
+        }
+        return result.empty() ? "nessuna azione" : result;
+    }
+    if (args.size() == 2 && args[0] == "remove") {
+        if (!removeScheduledAction(static_cast<uint32_t>(std::stoul(args[1])))) {
+            throw std::invalid_argument("azione sconosciuta: " + args[1]);
+        }
+        return "";
+    }
+    
+    bool edit = !args.empty() && args[0] == "edit";
+    if (args.empty() || (args[0] != "add" && !edit)) {
+        throw std::invalid_argument(usage);
+    }
+    size_t next = edit ? 2 : 1;
+    if (args.size() < next + 2) {
+        throw std::invalid_argument(usage);
+    }
+    auto minute = Scheduler::parseTimeOfDay(args[next]);
+    if (!minute) {
+        throw std::invalid_argument("orario non valido: " + args[next]);
+    }
+    next++;
+    uint8_t days = SCHEDULE_EVERY_DAY;
+    if (auto parsed = Scheduler::parseDays(args[next])) {
+        days = *parsed;
+        next++;
+    }
+    if (next >= args.size()) {
+        throw std::invalid_argument(usage);
+    }
+    std::string command;
+    for (size_t i = next; i < args.size(); ++i) {
+        command += (command.empty() ? "" : " ") + args[i];
+    }
+    
+    if (edit) {
+        if (!updateScheduledAction(static_cast<uint32_t>(std::stoul(args[1])), *minute, days, command)) {
+            throw std::invalid_argument("azione sconosciuta: " + args[1]);
+        }
+        return "";
+    }
+    return "id " + std::to_string(addScheduledAction(*minute, days, command));
+}
+#endif
+
+std::string SaberProtocol::issueControlToken(TokenScope scope, uint64_t ttlSeconds) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
     
-    auto protocol = std::make_unique<SaberProtocol>(config);
-    if (!protocol->initialize()) {
-        throw std::runtime_error("Impossibile inizializzare il protocollo SABER");
+    if (!crypto) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return {};
     }
     
-    std::cout << "Nodo Master (UCB) avviato" << std::endl;
-    return protocol;
+    return toHex(crypto->generateSecurityToken(config.nodeId, ttlSeconds, scope));
 }
 
-std::unique_ptr<SaberProtocol> startRepeater(const std::optional<std::string>& nodeId,
-                           const std::optional<std::string>& btAddress) {
+std::optional<SecurityToken> SaberProtocol::verifyControlToken(const std::string& token) const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    auto bytes = fromHex(token);
+    if (!crypto || !bytes) {
+        return std::nullopt;
+    }
+    
+    try {
+        SecurityToken verified = crypto->verifySecurityToken(*bytes);
+        // I token valgono solo per il nodo che li ha emessi
+        if (verified.nodeId != config.nodeId) {
+            return std::nullopt;
+        }
+        return verified;
+    } catch (const CryptoError& e) {
+        SABER_LOG(Warn, "control", "Token di controllo rifiutato: " << e.what());
+        return std::nullopt;
+    }
+}
+
+bool SaberProtocol::revokeControlToken(const std::string& tokenId) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!crypto) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    recordRevocationLocked(Revocation{RevocationKind::Token, tokenId, systemMillis()});
+    return true;
+}
+
+bool SaberProtocol::revokeNodeTokens(const std::string& nodeId) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!crypto) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    recordRevocationLocked(Revocation{RevocationKind::Node, nodeId, systemMillis()});
+    return true;
+}
+
+std::vector<Revocation> SaberProtocol::getRevocations() const {
+    return revocationList->entries();
+}
+
+bool SaberProtocol::recordRevocationLocked(const Revocation& entry) {
+    if (!revocationList->add(entry)) {
+        return false;
+    }
+    SABER_LOG(Info, "security", "Revocato " << revocationKindToString(entry.kind) << " " << entry.subject);
+    journal->append("security", "revoked", config.nodeId, 
+                    revocationKindToString(entry.kind) + " " + entry.subject, syncManager->now());
+    // Solo il Master diffonde: gli altri nodi scartano le revoche degli altri ruoli
+    if (config.role == NodeRole::Master && meshNetwork) {
+        sendRevocationsLocked({entry});
+    }
+    return true;
+}
+
+void SaberProtocol::sendRevocationsLocked(const std::vector<Revocation>& entries) {
+    for (size_t first = 0; first < entries.size(); first += REVOCATION_ENTRIES_PER_PACKET) {
+        size_t last = std::min(entries.size(), first + REVOCATION_ENTRIES_PER_PACKET);
+        std::vector<Revocation> chunk(entries.begin() + first, entries.begin() + last);
+        meshNetwork->sendPacket(MeshPacket::createCommand("crl.revoke", {
+            {"entries", encodeRevocations(chunk)},
+        }));
+    }
+}
+
+void SaberProtocol::announceRevocations() {
+    int64_t now = steadyMillis();
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    if (config.role != NodeRole::Master || !meshNetwork || revocationList->size() == 0 ||
+        now - lastRevocationAnnounceMs < REVOCATION_ANNOUNCE_INTERVAL_MS) {
+        return;
+    }
+    // La ritrasmissione raggiunge anche i nodi entrati o riavviati dopo la revoca
+    lastRevocationAnnounceMs = now;
+    sendRevocationsLocked(revocationList->entries());
+}
+
+void SaberProtocol::applyRevocations() {
+    std::vector<std::pair<std::string, std::vector<Revocation>>> received;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        received.swap(pendingRevocations);
+    }
+    if (received.empty()) {
+        return;
+    }
+    
+    auto roles = meshNetwork->getNodeRoles();
+    for (const auto& batch : received) {
+        auto role = roles.find(batch.first);
+        if (role == roles.end() || role->second != NodeRole::Master) {
+            SABER_LOG(Warn, "security", "Lista di revoca ignorata da " << batch.first << ": non è il Master");
+            continue;
+        }
+        for (const auto& entry : revocationList->merge(batch.second)) {
+            SABER_LOG(Info, "security", "Revocato dal Master " << revocationKindToString(entry.kind) << " " 
+                      << entry.subject);
+            journal->append("security", "revoked", batch.first, 
+                            revocationKindToString(entry.kind) + " " + entry.subject, syncManager->now());
+        }
+    }
+}
+
+std::optional<PairingCode> SaberProtocol::openPairing(uint32_t durationSeconds) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork || !crypto) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return std::nullopt;
+    }
+    if (config.role != NodeRole::Master) {
+        SABER_LOG(Error, "protocol", "Solo il Master può aprire l'abbinamento");
+        return std::nullopt;
+    }
+    
+    auto rng = config.randomSource ? config.randomSource : defaultRandomSource();
+    pairingPin = generatePairingPin(*rng);
+    pairingResponder = std::make_unique<PairingResponder>(config.nodeId, crypto->getPublicKey(), pairingPin, rng);
+    pairingUntilMs = steadyMillis() + static_cast<int64_t>(durationSeconds) * 1000;
+    SABER_LOG(Info, "security", "Abbinamento aperto per " << durationSeconds << "s");
+    journal->append("security", "pairing_opened", config.nodeId, std::to_string(durationSeconds) + "s", 
+                    syncManager->now());
+    return PairingCode{pairingPin, config.nodeId};
+}
+
+bool SaberProtocol::closePairing() {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    if (!pairingResponder) {
+        return false;
+    }
+    closePairingLocked("chiuso dall'operatore");
+    return true;
+}
+
+bool SaberProtocol::startPairing(const std::string& code) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork || !crypto) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    if (config.role == NodeRole::Master) {
+        SABER_LOG(Error, "protocol", "Il Master non si abbina ad un'altra rete");
+        return false;
+    }
+    auto parsed = parsePairingCode(code);
+    if (!parsed) {
+        SABER_LOG(Error, "protocol", "Codice di abbinamento non valido");
+        return false;
+    }
+    
+    pairingJoiner = std::make_unique<PairingJoiner>(config.nodeId, config.role, crypto->getPublicKey(), parsed->pin,
+                                                    config.randomSource);
+    // Con il solo PIN risponde il primo Master con l'abbinamento aperto
+    pairingMasterId = parsed->masterId;
+    pairingDeadlineMs = steadyMillis() + PAIRING_STEP_TIMEOUT_MS;
+    pairingStatus.state = PairingState::Waiting;
+    pairingStatus.detail.clear();
+    sendPairingLocked(PairingStep::Hello, pairingMasterId, pairingJoiner->hello());
+    return true;
+}
+
+PairingStatus SaberProtocol::getPairingStatus() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    PairingStatus status = pairingStatus;
+    if (pairingResponder) {
+        status.state = PairingState::Open;
+        status.uri = pairingUri(config.nodeId, pairingPin);
+        status.remainingMs = std::max<int64_t>(0, pairingUntilMs - steadyMillis());
+        status.failures = pairingResponder->getFailures();
+    }
+    return status;
+}
+
+void SaberProtocol::runPairing() {
+    std::vector<MeshPacket> received;
+    {
+        std::lock_guard<std::mutex> lock(eventsMutex);
+        received.swap(pendingPairing);
+    }
+    
+    int64_t now = steadyMillis();
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    if (pairingResponder && now >= pairingUntilMs) {
+        closePairingLocked("PIN scaduto");
+    }
+    if (pairingJoiner && now >= pairingDeadlineMs) {
+        failPairingLocked("nessuna risposta dal Master");
+    }
+    
+    for (const auto& packet : received) {
+        const auto& info = packet.getPairingData();
+        // Un passo rivolto ad un altro nodo non riguarda il nodo locale
+        if (!info.peer.empty() && info.peer != config.nodeId) {
+            continue;
+        }
+        if (pairingResponder) {
+            handlePairingRequestLocked(packet.getSource(), info);
+        } else if (pairingJoiner) {
+            handlePairingReplyLocked(packet.getSource(), info);
+        }
+    }
+}
+
+void SaberProtocol::handlePairingRequestLocked(const std::string& nodeId, const MeshPacket::PairingInfo& info) {
+    if (info.step == PairingStep::Hello) {
+        if (auto challenge = pairingResponder->challenge(nodeId, info.payload)) {
+            sendPairingLocked(PairingStep::Challenge, nodeId, *challenge);
+        }
+        return;
+    }
+    if (info.step != PairingStep::Proof) {
+        return;
+    }
+    
+    bool pending = pairingResponder->isPending(nodeId);
+    auto request = pairingResponder->verify(nodeId, info.payload);
+    if (!request) {
+        // Una prova senza sessione non è un tentativo sul PIN
+        if (!pending) {
+            return;
+        }
+        SABER_LOG(Warn, "security", "Abbinamento di " << nodeId << " rifiutato: PIN errato");
+        journal->append("security", "pairing_failed", nodeId, "PIN errato", syncManager->now());
+        std::string reason = "PIN errato";
+        sendPairingLocked(PairingStep::Failed, nodeId, std::vector<uint8_t>(reason.begin(), reason.end()));
+        if (pairingResponder->isExhausted()) {
+            closePairingLocked("troppe prove errate");
+        }
+        return;
+    }
+    
+    // Un secondo Master dividerebbe la rete: l'abbinamento accetta solo gli altri ruoli
+    std::string result = request->role == NodeRole::Master 
+        ? "role_not_allowed" : meshNetwork->admitPairedNode(request->nodeId, request->role, request->publicKey);
+    if (result != "admitted") {
+        SABER_LOG(Warn, "security", "Abbinamento di " << nodeId << " rifiutato: " << result);
+        journal->append("security", "pairing_failed", nodeId, result, syncManager->now());
+        sendPairingLocked(PairingStep::Failed, nodeId, std::vector<uint8_t>(result.begin(), result.end()));
+        return;
+    }
+    
+    auto token = crypto->generateSecurityToken(nodeId, config.pairingTokenTtlSeconds, TokenScope::ReadOnly);
+    if (auto welcome = pairingResponder->welcome(nodeId, crypto->getNetworkKey(), token)) {
+        sendPairingLocked(PairingStep::Welcome, nodeId, *welcome);
+    }
+    pairingStatus.paired++;
+    journal->append("security", "paired", nodeId, nodeRoleToString(request->role), syncManager->now());
+    // Il PIN vale per un solo nodo
+    closePairingLocked("nodo " + nodeId + " abbinato");
+}
+
+void SaberProtocol::handlePairingReplyLocked(const std::string& masterId, const MeshPacket::PairingInfo& info) {
+    if (!pairingMasterId.empty() && masterId != pairingMasterId) {
+        return;
+    }
+    
+    switch (info.step) {
+        case PairingStep::Challenge: {
+            auto proof = pairingJoiner->respond(masterId, info.payload);
+            if (!proof) {
+                return;
+            }
+            // Da qui in poi conta solo il Master che ha risposto per primo
+            pairingMasterId = masterId;
+            pairingDeadlineMs = steadyMillis() + PAIRING_STEP_TIMEOUT_MS;
+            sendPairingLocked(PairingStep::Proof, masterId, *proof);
+            break;
+        }
+        case PairingStep::Welcome: {
+            auto credentials = pairingJoiner->complete(info.payload);
+            if (!credentials) {
+                failPairingLocked("benvenuto non valido");
+                return;
+            }
+            meshNetwork->joinPairedNetwork(credentials->masterId, credentials->masterPublicKey, 
+                                           credentials->networkKey);
+            pairingJoiner.reset();
+            pairingStatus.state = PairingState::Paired;
+            pairingStatus.masterId = credentials->masterId;
+            pairingStatus.token = toHex(credentials->token);
+            pairingStatus.detail.clear();
+            SABER_LOG(Info, "security", "Abbinato al Master " << credentials->masterId);
+            journal->append("security", "paired", credentials->masterId, nodeRoleToString(config.role), 
+                            syncManager->now());
+            break;
+        }
+        case PairingStep::Failed:
+            // Il motivo non è autenticato: vale solo come indicazione per l'operatore
+            failPairingLocked("rifiutato dal Master: " + std::string(info.payload.begin(), info.payload.end()));
+            break;
+        default:
+            break;
+    }
+}
+
+void SaberProtocol::sendPairingLocked(PairingStep step, const std::string& peer, 
+                                      const std::vector<uint8_t>& payload) {
+    meshNetwork->sendPacket(MeshPacket::createPairing(step, peer, payload));
+}
+
+void SaberProtocol::closePairingLocked(const std::string& reason) {
+    pairingResponder.reset();
+    pairingPin.clear();
+    SABER_LOG(Info, "security", "Abbinamento chiuso: " << reason);
+    journal->append("security", "pairing_closed", config.nodeId, reason, syncManager->now());
+}
+
+void SaberProtocol::failPairingLocked(const std::string& reason) {
+    pairingJoiner.reset();
+    pairingStatus.state = PairingState::Failed;
+    pairingStatus.detail = reason;
+    SABER_LOG(Warn, "security", "Abbinamento fallito: " << reason);
+    journal->append("security", "pairing_failed", pairingMasterId, reason, syncManager->now());
+}
+
+void SaberProtocol::writeControlTokenFile() {
+    if (!config.controlTokenFile) {
+        return;
+    }
+    
+    std::string token = issueControlToken(TokenScope::Admin, config.controlTokenTtlSeconds);
+    std::ofstream file(*config.controlTokenFile, std::ios::trunc);
+    if (!file || token.empty()) {
+        SABER_LOG(Error, "protocol", "Impossibile scrivere il token di controllo in " << *config.controlTokenFile);
+        return;
+    }
+    file << token << "\n";
+    file.close();
+    
+    // Il token concede pieni poteri: leggibile solo dal proprietario
+    std::error_code ec;
+    std::filesystem::permissions(*config.controlTokenFile,
+                                 std::filesystem::perms::owner_read | std::filesystem::perms::owner_write,
+                                 std::filesystem::perm_options::replace, ec);
+}
+
+std::string SaberProtocol::runTokenCommand(const std::vector<std::string>& args) {
+    // Uso: token issue <read-only|admin> [ttl_s] | token revoke <id> | token revoke-node <nodo> | token crl
+    if (args.size() >= 2 && args.size() <= 3 && args[0] == "issue") {
+        auto scope = tokenScopeFromString(args[1]);
+        if (!scope) {
+            throw std::invalid_argument("ambito non valido: " + args[1]);
+        }
+        uint64_t ttl = args.size() == 3 ? std::stoull(args[2]) : config.controlTokenTtlSeconds;
+        std::string token = issueControlToken(*scope, ttl);
+        auto issued = verifyControlToken(token);
+        if (!issued) {
+            throw std::runtime_error("emissione del token fallita");
+        }
+        // L'ID serve per la revoca
+        return token + " id=" + issued->tokenId;
+    }
+    if (args.size() == 2 && args[0] == "revoke") {
+        revokeControlToken(args[1]);
+        return "revocato " + args[1];
+    }
+    if (args.size() == 2 && args[0] == "revoke-node") {
+        revokeNodeTokens(args[1]);
+        return "revocati i token di " + args[1];
+    }
+    if (args.size() == 1 && args[0] == "crl") {
+        std::string result;
+        for (const auto& entry : getRevocations()) {
+            result += (result.empty() ? "" : "; ") + revocationKindToString(entry.kind) + " " + entry.subject 
+                    + " " + std::to_string(entry.revokedAtMs);
+        }
+        return result.empty() ? "nessuna revoca" : result;
+    }
+    throw std::invalid_argument("uso: token issue <read-only|admin> [ttl_s] | token revoke <id> | "
+                                "token revoke-node <nodo> | token crl");
+}
+
+std::string SaberProtocol::runPairCommand(const std::vector<std::string>& args) {
+    // Uso: pair open [durata_s] | pair close | pair status | pair join <codice>
+    if (!args.empty() && args.size() <= 2 && args[0] == "open") {
+        uint32_t duration = args.size() == 2 ? static_cast<uint32_t>(std::stoul(args[1])) : 120;
+        auto code = openPairing(duration);
+        if (!code) {
+            throw std::runtime_error("abbinamento non disponibile: solo il Master può aprirlo");
+        }
+        return "pin=" + code->pin + " uri=" + pairingUri(code->masterId, code->pin);
+    }
+    if (args.size() == 1 && args[0] == "close") {
+        return closePairing() ? "abbinamento chiuso" : "nessun abbinamento aperto";
+    }
+    if (args.size() == 1 && args[0] == "status") {
+        auto status = getPairingStatus();
+        std::string result = "state=" + pairingStateToString(status.state) 
+                           + " paired=" + std::to_string(status.paired);
+        if (status.state == PairingState::Open) {
+            result += " remaining_ms=" + std::to_string(status.remainingMs) 
+                    + " failures=" + std::to_string(status.failures);
+        }
+        if (!status.masterId.empty()) {
+            result += " master=" + status.masterId;
+        }
+        if (!status.detail.empty()) {
+            result += "; " + status.detail;
+        }
+        return result;
+    }
+    if (args.size() == 2 && args[0] == "join") {
+        if (!startPairing(args[1])) {
+            throw std::invalid_argument("abbinamento non avviato: codice non valido o nodo Master");
+        }
+        return "abbinamento avviato";
+    }
+    throw std::invalid_argument("uso: pair open [durata_s] | pair close | pair status | pair join <codice>");
+}
+
+std::string SaberProtocol::runLogCommand(const std::vector<std::string>& args) {
+    // Uso: log get | log set <filtro> [--mesh | --node <id>]
+    if (args.size() == 1 && args[0] == "get") {
+        return getLogFilter();
+    }
+    if (args.size() < 2 || args[0] != "set") {
+        throw std::invalid_argument("uso: log get | log set <filtro> [--mesh | --node <id>]");
+    }
+    
+    bool meshWide = false;
+    std::optional<std::string> targetNode;
+    if (args.size() == 3 && args[2] == "--mesh") {
+        meshWide = true;
+    } else if (args.size() == 4 && args[2] == "--node") {
+        targetNode = args[3];
+    } else if (args.size() != 2) {
+        throw std::invalid_argument("opzione non valida per log set");
+    }
+    
+    if (!setLogFilter(args[1], meshWide, targetNode)) {
+        throw std::invalid_argument("filtro non applicato: " + args[1]);
+    }
+    return targetNode ? "inviato a " + *targetNode : getLogFilter();
+}
+
+std::optional<EncryptionGranularity> SaberProtocol::negotiateFrameEncryption(uint8_t peerCapabilities) const {
+    // Ogni nodo sa aprire entrambi i formati; la preferenza decide cosa trasmettere
+    const uint8_t localCapabilities = CAP_ENCRYPT_PER_FRAME | CAP_ENCRYPT_TRANSPORT;
+    return negotiateGranularity(localCapabilities, peerCapabilities, config.frameEncryption);
+}
+
+bool SaberProtocol::assignZone(const std::string& nodeId, const std::string& zone) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    meshNetwork->assignZone(nodeId, zone);
+    return true;
+}
+
+std::vector<GroupSplitSuggestion> SaberProtocol::suggestGroupSplits() const {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        return {};
+    }
+    
+    return planner.suggestSplits(meshNetwork->getZones(), meshNetwork->getNodeLatencies());
+}
+
+bool SaberProtocol::applyGroupSplit(const GroupSplitSuggestion& suggestion) {
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    
+    if (!meshNetwork) {
+        SABER_LOG(Error, "protocol", "Rete mesh non inizializzata");
+        return false;
+    }
+    
+    // Il suggerimento potrebbe essere superato da una riassegnazione successiva
+    if (meshNetwork->getZone(suggestion.nodeId) != suggestion.zone) {
+        SABER_LOG(Error, "protocol", "Suggerimento non più valido per il nodo " << suggestion.nodeId);
+        return false;
+    }
+    
+    meshNetwork->assignZone(suggestion.nodeId, suggestion.suggestedZone);
+    SABER_LOG(Info, "protocol", "Nodo " << suggestion.nodeId << " spostato nel gruppo " 
+              << suggestion.suggestedZone);
+    return true;
+}
+
+bool SaberProtocol::isSynchronized() const {
+    return syncManager->isSynchronized();
+}
+
+uint64_t SaberProtocol::getNetworkTimeUs() const {
+    return syncManager->nowUs();
+}
+
+ProtocolState SaberProtocol::getState() const {
+    return state;
+}
+
+bool SaberProtocol::isLive() const {
+    if (state != ProtocolState::Running) {
+        return false;
+    }
+    
+    // Un thread di runtime che non avanza indica un nodo bloccato
+    return steadyMillis() - lastRuntimeTick < RUNTIME_STALL_MS;
+}
+
+bool SaberProtocol::isReady() const {
+    if (!isLive() || !isSynchronized()) {
+        return false;
+    }
+    
+    std::lock_guard<std::mutex> lock(protocolMutex);
+    return meshNetwork && meshNetwork->isRunning();
+}
+
+// Funzioni di utilità
+std::unique_ptr<SaberProtocol> startNode(SaberConfig config) {
     // Genera un ID se non fornito
-    std::string id;
-    if (nodeId) {
-        id = *nodeId;
-    } else {
+    if (config.nodeId.empty()) {
         std::random_device rd;
         std::mt19937 gen(rd());
         std::uniform_int_distribution<> dis(0, 0xFFFFFF);
-        id = "repeater-" + std::to_string(dis(gen));
+        config.nodeId = nodeRoleToString(config.role) + "-" + std::to_string(dis(gen));
     }
-    
-    SaberConfig config = {
-        id,                  // nodeId
-        NodeRole::Repeater,  // role
-        btAddress,           // btAddress
-        true                 // isMusicMode
-    };
+    NodeRole role = config.role;
     
     auto protocol = std::make_unique<SaberProtocol>(config);
     if (!protocol->initialize()) {
         throw std::runtime_error("Impossibile inizializzare il protocollo SABER");
     }
     
-    std::cout << "Nodo Repeater avviato" << std::endl;
+    switch (role) {
+        case NodeRole::Master:
+            SABER_LOG(Info, "protocol", "Nodo Master (UCB) avviato");
+            break;
+        case NodeRole::Repeater:
+            SABER_LOG(Info, "protocol", "Nodo Repeater avviato");
+            break;
+        case NodeRole::Sink:
+            SABER_LOG(Info, "protocol", "Nodo Sink avviato");
+            break;
+    }
     return protocol;
 }
 
+std::unique_ptr<SaberProtocol> startMaster(const std::optional<std::string>& nodeId, 
+                         const std::optional<std::string>& btAddress) {
+    SaberConfig config = {
+        nodeId.value_or(""),  // nodeId
+        NodeRole::Master,     // role
+        btAddress,            // btAddress
+        true                  // isMusicMode
+    };
+    return startNode(config);
+}
+
+std::unique_ptr<SaberProtocol> startRepeater(const std::optional<std::string>& nodeId,
+                           const std::optional<std::string>& btAddress) {
+    SaberConfig config = {
+        nodeId.value_or(""),  // nodeId
+        NodeRole::Repeater,   // role
+        btAddress,            // btAddress
+        true                  // isMusicMode
+    };
+    return startNode(config);
+}
+
 std::unique_ptr<SaberProtocol> startSink(const std::optional<std::string>& nodeId,
                        const std::optional<std::string>& btAddress,
                        bool isMusic) {
-    // Genera un ID se non fornito
-    std::string id;
-    if (nodeId) {
-        id = *nodeId;
-    } else {
-        std::random_device rd;
-        std::mt19937 gen(rd());
-        std::uniform_int_distribution<> dis(0, 0xFFFFFF);
-        id = "sink-" + std::to_string(dis(gen));
-    }
-    
     SaberConfig config = {
-        id,                  // nodeId
-        NodeRole::Sink,      // role
-        btAddress,           // btAddress
-        isMusic              // isMusicMode
+        nodeId.value_or(""),  // nodeId
+        NodeRole::Sink,       // role
+        btAddress,            // btAddress
+        isMusic               // isMusicMode
+    };
+    return startNode(config);
+}
+
+NodeCommandLine parseNodeCommandLine(NodeRole role, const std::vector<std::string>& args) {
+    auto value = [&args](const std::string& name) -> std::optional<std::string> {
+        for (size_t i = 0; i + 1 < args.size(); ++i) {
+            if (args[i] == name) {
+                return args[i + 1];
+            }
+        }
+        return std::nullopt;
+    };
+    auto flag = [&args](const std::string& name) {
+        return std::find(args.begin(), args.end(), name) != args.end();
     };
     
-    auto protocol = std::make_unique<SaberProtocol>(config);
-    if (!protocol->initialize()) {
-        throw std::runtime_error("Impossibile inizializzare il protocollo SABER");
+    NodeCommandLine command;
+    if (auto interval = value("--interval")) {
+        size_t parsed = 0;
+        try {
+            command.intervalSeconds = std::stoi(*interval, &parsed);
+        } catch (const std::exception&) {
+            parsed = 0;
+        }
+        if (parsed != interval->size() || command.intervalSeconds <= 0) {
+            throw std::invalid_argument("--interval richiede un numero di secondi positivo: " + *interval);
+        }
+    }
+    if (flag("--music") && flag("--voice")) {
+        throw std::invalid_argument("--music e --voice non possono essere usati insieme");
     }
     
-    std::cout << "Nodo Sink avviato" << std::endl;
-    return protocol;
+    // Il file di configurazione fa da base, le opzioni hanno la precedenza
+    auto configPath = value("--config");
+    command.config = configPath ? SaberConfig::fromFile(*configPath) : SaberConfig::defaultConfig();
+    command.config.role = role;
+    if (!configPath) {
+        command.config.nodeId.clear();
+    }
+    if (auto nodeId = value("--id")) {
+        command.config.nodeId = *nodeId;
+    }
+    if (auto btAddress = value("--bt")) {
+        command.config.btAddress = *btAddress;
+    }
+    if (auto transport = value("--transport")) {
+        auto kind = transportKindFromString(*transport);
+        if (!kind) {
+            throw std::invalid_argument("Collegamento sconosciuto: " + *transport);
+        }
+        command.config.transport = *kind;
+    }
+    if (flag("--music") || flag("--voice")) {
+        command.config.isMusicMode = flag("--music");
+    }
+    
+    command.playPath = value("--play").value_or("");
+    if (!command.playPath.empty() && role != NodeRole::Master) {
+        throw std::invalid_argument("Solo il Master può trasmettere un file");
+    }
+    command.recordPath = value("--record").value_or("");
+    if (!command.recordPath.empty() && role != NodeRole::Sink) {
+        throw std::invalid_argument("Solo un sink può registrare l'audio riprodotto");
+    }
+    command.loop = flag("--loop");
+    command.plain = flag("--plain");
+    return command;
 }
 
 } // namespace saber
@@ -0,0 +1,286 @@
+#include "scheduler.h"
+
+#include <algorithm>
+#include <cstdio>
+#include <fstream>
+#include <sstream>
+#include <stdexcept>
+
+namespace saber {
+
+namespace {
+
+const uint64_t MS_PER_MINUTE = 60000;
+const uint64_t MS_PER_DAY = 24 * 60 * MS_PER_MINUTE;
+
+// Ritardo oltre il quale un'azione mancata non viene più eseguita
+const uint64_t MAX_CATCHUP_MS = MS_PER_MINUTE;
+
+const char* const DAY_NAMES[] = {"mon", "tue", "wed", "thu", "fri", "sat", "sun"};
+
+std::optional<int> dayIndex(const std::string& name) {
+    for (int i = 0; i < 7; ++i) {
+        if (name == DAY_NAMES[i]) {
+            return i;
+        }
+    }
+    return std::nullopt;
+}
+
+// Il primo giorno dell'epoca Unix (1970-01-01) era un giovedì
+int weekdayOf(int64_t epochDay) {
+    return static_cast<int>(((epochDay + 3) % 7 + 7) % 7);
+}
+
+} // namespace
+
+// Implementazione di ScheduleEntry
+std::string ScheduleEntry::toString() const {
+    char time[6];
+    std::snprintf(time, sizeof(time), "%02u:%02u", minuteOfDay / 60u, minuteOfDay % 60u);
+    return std::to_string(id) + " " + time + " " + Scheduler::daysToString(days) + " " + command;
+}
+
+// Implementazione di Scheduler
+Scheduler::Scheduler(int32_t utcOffsetMinutes)
+    : nextId(1), utcOffsetMinutes(utcOffsetMinutes), lastCheckMs(0) {
+}
+
+uint32_t Scheduler::add(uint16_t minuteOfDay, uint8_t days, const std::string& command) {
+    validate(minuteOfDay, days, command);
+    std::lock_guard<std::mutex> lock(scheduleMutex);
+    uint32_t id = nextId++;
+    schedule[id] = ScheduleEntry{id, minuteOfDay, days, command};
+    return id;
+}
+
+bool Scheduler::update(uint32_t id, uint16_t minuteOfDay, uint8_t days, const std::string& command) {
+    validate(minuteOfDay, days, command);
+    std::lock_guard<std::mutex> lock(scheduleMutex);
+    auto it = schedule.find(id);
+    if (it == schedule.end()) {
+        return false;
+    }
+    it->second = ScheduleEntry{id, minuteOfDay, days, command};
+    return true;
+}
+
+bool Scheduler::remove(uint32_t id) {
+    std::lock_guard<std::mutex> lock(scheduleMutex);
+    return schedule.erase(id) > 0;
+}
+
+std::vector<ScheduleEntry> Scheduler::entries() const {
+    std::lock_guard<std::mutex> lock(scheduleMutex);
+    std::vector<ScheduleEntry> result;
+    for (const auto& entry : schedule) {
+        result.push_back(entry.second);
+    }
+    return result;
+}
+
+void Scheduler::setUtcOffset(int32_t minutes) {
+    std::lock_guard<std::mutex> lock(scheduleMutex);
+    utcOffsetMinutes = minutes;
+}
+
+int32_t Scheduler::getUtcOffset() const {
+    std::lock_guard<std::mutex> lock(scheduleMutex);
+    return utcOffsetMinutes;
+}
+
+std::vector<ScheduleEntry> Scheduler::due(uint64_t nowUtcMs) {
+    std::lock_guard<std::mutex> lock(scheduleMutex);
+    std::vector<ScheduleEntry> result;
+
+    // All'avvio e dopo un salto all'indietro dell'orologio riparto da adesso
+    if (lastCheckMs == 0 || nowUtcMs < lastCheckMs) {
+        lastCheckMs = nowUtcMs;
+        return result;
+    }
+
+    int64_t offsetMs = static_cast<int64_t>(utcOffsetMinutes) * static_cast<int64_t>(MS_PER_MINUTE);
+    int64_t localMs = static_cast<int64_t>(nowUtcMs) + offsetMs;
+    int64_t today = localMs / static_cast<int64_t>(MS_PER_DAY);
+
+    for (const auto& pair : schedule) {
+        const ScheduleEntry& entry = pair.second;
+
+        // Occorrenza più recente non successiva ad adesso
+        int64_t day = today;
+        int64_t occurrence = day * static_cast<int64_t>(MS_PER_DAY) + entry.minuteOfDay * static_cast<int64_t>(MS_PER_MINUTE);
+        if (occurrence > localMs) {
+            day--;
+            occurrence -= static_cast<int64_t>(MS_PER_DAY);
+        }
+        if ((entry.days & (1u << weekdayOf(day))) == 0) {
+            continue;
+        }
+
+        uint64_t occurrenceUtc = static_cast<uint64_t>(occurrence - offsetMs);
+        if (occurrenceUtc > lastCheckMs && nowUtcMs - occurrenceUtc <= MAX_CATCHUP_MS) {
+            result.push_back(entry);
+        }
+    }
+
+    lastCheckMs = nowUtcMs;
+    return result;
+}
+
+bool Scheduler::load(const std::string& path) {
+    std::ifstream file(path);
+    if (!file) {
+        return false;
+    }
+
+    std::map<uint32_t, ScheduleEntry> loaded;
+    uint32_t maxId = 0;
+    std::string line;
+    while (std::getline(file, line)) {
+        if (line.empty() || line[0] == '#') {
+            continue;
+        }
+
+        std::istringstream input(line);
+        uint32_t id = 0;
+        std::string time, days, command;
+        input >> id >> time >> days;
+        std::getline(input >> std::ws, command);
+
+        auto minute = parseTimeOfDay(time);
+        auto mask = parseDays(days);
+        if (id == 0 || !minute || !mask) {
+            throw std::invalid_argument("Riga della programmazione non valida: " + line);
+        }
+        validate(*minute, *mask, command);
+        loaded[id] = ScheduleEntry{id, *minute, *mask, command};
+        maxId = std::max(maxId, id);
+    }
+
+    std::lock_guard<std::mutex> lock(scheduleMutex);
+    schedule = loaded;
+    nextId = maxId + 1;
+    return true;
+}
+
+bool Scheduler::save(const std::string& path) const {
+    // Scrittura su file temporaneo e rinomina per non lasciare file troncati
+    std::string tmpPath = path + ".tmp";
+    {
+        std::ofstream file(tmpPath, std::ios::trunc);
+        if (!file) {
+            return false;
+        }
+        file << "# Programmazione SABER: <id> <HH:MM> <giorni> <comando>\n";
+        for (const auto& entry : entries()) {
+            file << entry.toString() << "\n";
+        }
+    }
+    if (std::rename(tmpPath.c_str(), path.c_str()) != 0) {
+        std::remove(path.c_str());
+        if (std::rename(tmpPath.c_str(), path.c_str()) != 0) {
+            return false;
+        }
+    }
+    return true;
+}
+
+std::optional<uint16_t> Scheduler::parseTimeOfDay(const std::string& text) {
+    unsigned hours = 0, minutes = 0;
+    char colon = 0;
+    std::istringstream input(text);
+    if (!(input >> hours >> colon >> minutes) || colon != ':' || !input.eof()) {
+        return std::nullopt;
+    }
+    if (hours > 23 || minutes > 59) {
+        return std::nullopt;
+    }
+    return static_cast<uint16_t>(hours * 60 + minutes);
+}
+
+std::optional<uint8_t> Scheduler::parseDays(const std::string& text) {
+    if (text == "daily") {
+        return SCHEDULE_EVERY_DAY;
+    }
+    if (text == "weekdays") {
+        return static_cast<uint8_t>(0x1F);
+    }
+    if (text == "weekend") {
+        return static_cast<uint8_t>(0x60);
+    }
+
+    uint8_t mask = 0;
+    std::istringstream input(text);
+    std::string item;
+    while (std::getline(input, item, ',')) {
+        auto dash = item.find('-');
+        if (dash == std::string::npos) {
+            auto day = dayIndex(item);
+            if (!day) {
+                return std::nullopt;
+            }
+            mask |= static_cast<uint8_t>(1u << *day);
+            continue;
+        }
+        auto first = dayIndex(item.substr(0, dash));
+        auto last = dayIndex(item.substr(dash + 1));
+        if (!first || !last) {
+            return std::nullopt;
+        }
+        // Gli intervalli possono attraversare la domenica (es. "fri-mon")
+        for (int day = *first;; day = (day + 1) % 7) {
+            mask |= static_cast<uint8_t>(1u << day);
+            if (day == *last) {
+                break;
+            }
+        }
+    }
+    if (mask == 0) {
+        return std::nullopt;
+    }
+    return mask;
+}
+
+std::optional<int32_t> Scheduler::parseUtcOffset(const std::string& text) {
+    if (text == "Z" || text == "UTC") {
+        return 0;
+    }
+    if (text.size() < 2 || (text[0] != '+' && text[0] != '-')) {
+        return std::nullopt;
+    }
+    auto minutes = parseTimeOfDay(text.substr(1));
+    if (!minutes || *minutes > 14 * 60) {
+        return std::nullopt;
+    }
+    return text[0] == '-' ? -static_cast<int32_t>(*minutes) : static_cast<int32_t>(*minutes);
+}
+
+std::string Scheduler::daysToString(uint8_t days) {
+    if ((days & SCHEDULE_EVERY_DAY) == SCHEDULE_EVERY_DAY) {
+        return "daily";
+    }
+    std::string result;
+    for (int i = 0; i < 7; ++i) {
+        if (days & (1u << i)) {
+            if (!result.empty()) {
+                result += ",";
+            }
+            result += DAY_NAMES[i];
+        }
+    }
+    return result;
+}
+
+void Scheduler::validate(uint16_t minuteOfDay, uint8_t days, const std::string& command) {
+    if (minuteOfDay >= 24 * 60) {
+        throw std::invalid_argument("orario non valido");
+    }
+    if ((days & SCHEDULE_EVERY_DAY) == 0) {
+        throw std::invalid_argument("nessun giorno selezionato");
+    }
+    if (command.empty() || command.find('\n') != std::string::npos) {
+        throw std::invalid_argument("comando non valido");
+    }
+}
+
+} // namespace saber
@@ -0,0 +1,97 @@
+#include "session.h"
+
+#include <algorithm>
+
+namespace saber {
+
+namespace {
+
+// Salto di sequenza oltre cui lo stream è considerato riavviato, non lacunoso
+const uint32_t MAX_SEQUENCE_GAP = 50;
+
+} // namespace
+
+// Implementazione di SessionTracker
+SessionTracker::SessionTracker(const std::string& nodeId, uint32_t targetBufferMs)
+    : nodeId(nodeId), targetBufferMs(targetBufferMs) {
+}
+
+void SessionTracker::recordFrame(StreamId streamId, uint32_t sequence, uint64_t playoutTimeUs, uint64_t nowUs) {
+    std::lock_guard<std::mutex> lock(sessionMutex);
+    int64_t leadMs = (static_cast<int64_t>(playoutTimeUs) - static_cast<int64_t>(nowUs)) / 1000;
+
+    auto inserted = sessions.emplace(streamId, Session{});
+    Session& session = inserted.first->second;
+    if (inserted.second) {
+        session.report.nodeId = nodeId;
+        session.report.streamId = streamId;
+        session.report.startedAtMs = nowUs / 1000;
+    } else {
+        uint32_t gap = sequence - session.lastSequence - 1;
+        if (gap > 0 && gap <= MAX_SEQUENCE_GAP) {
+            session.report.framesConcealed += gap;
+        }
+    }
+    session.lastSequence = sequence;
+    session.report.endedAtMs = nowUs / 1000;
+    session.offsetSumMs += leadMs;
+
+    bool late = leadMs < 0;
+    if (late) {
+        session.report.framesDropped++;
+        if (!session.late && session.report.framesPlayed > 0) {
+            session.report.rebufferCount++;
+        }
+    } else {
+        session.report.framesPlayed++;
+    }
+    session.late = late;
+
+    int64_t latencyMs = static_cast<int64_t>(targetBufferMs) - leadMs;
+    session.report.maxLatencyMs = std::max(session.report.maxLatencyMs,
+                                           static_cast<uint32_t>(std::max<int64_t>(latencyMs, 0)));
+}
+
+std::optional<SessionReport> SessionTracker::finish(StreamId streamId) {
+    std::lock_guard<std::mutex> lock(sessionMutex);
+    auto it = sessions.find(streamId);
+    if (it == sessions.end()) {
+        return std::nullopt;
+    }
+    SessionReport report = close(it->second);
+    sessions.erase(it);
+    return report;
+}
+
+std::vector<SessionReport> SessionTracker::finishAll() {
+    std::lock_guard<std::mutex> lock(sessionMutex);
+    std::vector<SessionReport> reports;
+    for (const auto& entry : sessions) {
+        reports.push_back(close(entry.second));
+    }
+    sessions.clear();
+    return reports;
+}
+
+std::vector<SessionReport> SessionTracker::finishIdle(uint64_t nowUs, uint32_t idleMs) {
+    std::lock_guard<std::mutex> lock(sessionMutex);
+    std::vector<SessionReport> reports;
+    for (auto it = sessions.begin(); it != sessions.end();) {
+        if (nowUs / 1000 >= it->second.report.endedAtMs + idleMs) {
+            reports.push_back(close(it->second));
+            it = sessions.erase(it);
+        } else {
+            ++it;
+        }
+    }
+    return reports;
+}
+
+SessionReport SessionTracker::close(const Session& session) const {
+    SessionReport report = session.report;
+    uint64_t frames = report.framesPlayed + report.framesDropped;
+    report.averageOffsetMs = frames > 0 ? session.offsetSumMs / static_cast<int64_t>(frames) : 0;
+    return report;
+}
+
+} // namespace saber
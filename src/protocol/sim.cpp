@@ -0,0 +1,159 @@
+#include "sim.h"
+
+#include <algorithm>
+
+namespace saber {
+
+SimNetwork::SimNetwork(uint64_t seed, const SimLinkConfig& defaults) : defaults(defaults), engine(seed) {
+}
+
+void SimNetwork::attach(const std::string& nodeId, ReceiveHandler handler) {
+    std::lock_guard<std::mutex> lock(simMutex);
+    nodes[nodeId] = std::move(handler);
+}
+
+void SimNetwork::detach(const std::string& nodeId) {
+    std::lock_guard<std::mutex> delivery(deliveryMutex);
+    std::lock_guard<std::mutex> lock(simMutex);
+    nodes.erase(nodeId);
+}
+
+std::vector<std::string> SimNetwork::getNodes() const {
+    std::lock_guard<std::mutex> lock(simMutex);
+    std::vector<std::string> ids;
+    for (const auto& node : nodes) {
+        ids.push_back(node.first);
+    }
+    return ids;
+}
+
+bool SimNetwork::send(const std::string& from, const std::vector<uint8_t>& datagram) {
+    std::lock_guard<std::mutex> lock(simMutex);
+    if (nodes.count(from) == 0) {
+        return false;
+    }
+    stats.packetsSent++;
+
+    for (const auto& node : nodes) {
+        const std::string& to = node.first;
+        if (to == from) {
+            continue;
+        }
+        if (!canReachLocked(from, to)) {
+            stats.partitioned++;
+            continue;
+        }
+        // Estrazioni sempre nello stesso ordine, anche quando non servono,
+        // così cambiare un collegamento non sposta la sequenza degli altri
+        const SimLinkConfig& link = linkLocked(from, to);
+        double draw = static_cast<double>(engine() >> 11) / static_cast<double>(1ULL << 53);
+        uint64_t jitter = engine() % (static_cast<uint64_t>(link.jitterMs) + 1);
+        if (draw < link.lossRate) {
+            stats.lost++;
+            continue;
+        }
+        inFlight.emplace(std::make_pair(clockMs + link.latencyMs + jitter, nextSequence++),
+                         Delivery{from, to, datagram});
+        stats.inFlight++;
+    }
+    return true;
+}
+
+void SimNetwork::setDefaultLink(const SimLinkConfig& config) {
+    std::lock_guard<std::mutex> lock(simMutex);
+    defaults = config;
+}
+
+void SimNetwork::setLink(const std::string& from, const std::string& to, const SimLinkConfig& config) {
+    std::lock_guard<std::mutex> lock(simMutex);
+    links[{from, to}] = config;
+}
+
+void SimNetwork::clearLink(const std::string& from, const std::string& to) {
+    std::lock_guard<std::mutex> lock(simMutex);
+    links.erase({from, to});
+}
+
+SimLinkConfig SimNetwork::getLink(const std::string& from, const std::string& to) const {
+    std::lock_guard<std::mutex> lock(simMutex);
+    return linkLocked(from, to);
+}
+
+void SimNetwork::partition(const std::set<std::string>& side) {
+    std::lock_guard<std::mutex> lock(simMutex);
+    lastPartition++;
+    for (const auto& nodeId : side) {
+        partitions[nodeId] = lastPartition;
+    }
+}
+
+void SimNetwork::heal() {
+    std::lock_guard<std::mutex> lock(simMutex);
+    partitions.clear();
+}
+
+bool SimNetwork::canReach(const std::string& from, const std::string& to) const {
+    std::lock_guard<std::mutex> lock(simMutex);
+    return canReachLocked(from, to);
+}
+
+size_t SimNetwork::advance(uint32_t ms) {
+    std::lock_guard<std::mutex> delivery(deliveryMutex);
+    std::unique_lock<std::mutex> lock(simMutex);
+    uint64_t target = clockMs + ms;
+    size_t delivered = 0;
+
+    while (!inFlight.empty() && inFlight.begin()->first.first <= target) {
+        auto next = inFlight.begin();
+        clockMs = std::max(clockMs, next->first.first);
+        Delivery copy = std::move(next->second);
+        inFlight.erase(next);
+        stats.inFlight--;
+
+        auto node = nodes.find(copy.to);
+        if (node == nodes.end()) {
+            continue;
+        }
+        if (!canReachLocked(copy.from, copy.to)) {
+            stats.partitioned++;
+            continue;
+        }
+        stats.delivered++;
+        delivered++;
+
+        // Il gestore viene invocato fuori dal lock: può trasmettere a sua volta
+        ReceiveHandler handler = node->second;
+        lock.unlock();
+        if (handler) {
+            handler(copy.datagram);
+        }
+        lock.lock();
+    }
+    clockMs = target;
+    return delivered;
+}
+
+uint64_t SimNetwork::now() const {
+    std::lock_guard<std::mutex> lock(simMutex);
+    return clockMs;
+}
+
+SimStats SimNetwork::getStats() const {
+    std::lock_guard<std::mutex> lock(simMutex);
+    return stats;
+}
+
+const SimLinkConfig& SimNetwork::linkLocked(const std::string& from, const std::string& to) const {
+    auto it = links.find({from, to});
+    return it != links.end() ? it->second : defaults;
+}
+
+bool SimNetwork::canReachLocked(const std::string& from, const std::string& to) const {
+    auto fromPart = partitions.find(from);
+    auto toPart = partitions.find(to);
+    uint32_t fromId = fromPart != partitions.end() ? fromPart->second : 0;
+    uint32_t toId = toPart != partitions.end() ? toPart->second : 0;
+    return fromId == toId;
+}
+
+} // namespace saber
@@ -0,0 +1,240 @@
+#include "simulcast.h"
+#include "log.h"
+
+#include <set>
+#include <sstream>
+#include <stdexcept>
+
+namespace saber {
+
+void validateSimulcastGroup(const SimulcastGroup& group) {
+    if (group.layers.size() < 2 || group.layers.size() > MAX_SIMULCAST_LAYERS) {
+        throw std::invalid_argument("Un gruppo simulcast ha da 2 a " + std::to_string(MAX_SIMULCAST_LAYERS) +
+                                    " livelli");
+    }
+    if (group.layers.front().streamId != group.streamId) {
+        throw std::invalid_argument("Il primo livello deve essere lo stream " + std::to_string(group.streamId));
+    }
+    std::set<StreamId> seen;
+    for (size_t i = 0; i < group.layers.size(); ++i) {
+        const auto& layer = group.layers[i];
+        if (!seen.insert(layer.streamId).second) {
+            throw std::invalid_argument("Stream " + std::to_string(layer.streamId) + " ripetuto nei livelli");
+        }
+        if (layer.bitrateKbps == 0 || (i > 0 && layer.bitrateKbps >= group.layers[i - 1].bitrateKbps)) {
+            throw std::invalid_argument("I bitrate dei livelli devono essere positivi e decrescenti");
+        }
+    }
+}
+
+std::string encodeSimulcastLayers(const std::vector<SimulcastLayer>& layers) {
+    std::string text;
+    for (const auto& layer : layers) {
+        if (!text.empty()) {
+            text += ",";
+        }
+        text += std::to_string(layer.streamId) + ":" + std::to_string(layer.bitrateKbps);
+    }
+    return text;
+}
+
+std::optional<std::vector<SimulcastLayer>> parseSimulcastLayers(const std::string& text) {
+    std::vector<SimulcastLayer> layers;
+    std::stringstream stream(text);
+    std::string item;
+    while (std::getline(stream, item, ',')) {
+        auto colon = item.find(':');
+        if (colon == std::string::npos) {
+            return std::nullopt;
+        }
+        try {
+            size_t used = 0;
+            unsigned long streamId = std::stoul(item.substr(0, colon), &used);
+            if (used != colon || streamId > 0xFFFF) {
+                return std::nullopt;
+            }
+            std::string bitrate = item.substr(colon + 1);
+            unsigned long kbps = std::stoul(bitrate, &used);
+            if (used != bitrate.size() || kbps > 0xFFFFFFFFul) {
+                return std::nullopt;
+            }
+            layers.push_back({static_cast<StreamId>(streamId), static_cast<uint32_t>(kbps)});
+        } catch (const std::exception&) {
+            return std::nullopt;
+        }
+    }
+    if (layers.empty()) {
+        return std::nullopt;
+    }
+    return layers;
+}
+
+// Implementazione di SimulcastReceiver
+SimulcastReceiver::SimulcastReceiver(const SimulcastGroup& group, const SimulcastConfig& config)
+    : group(group), config(config) {
+    validateSimulcastGroup(group);
+}
+
+void SimulcastReceiver::setConfig(const SimulcastConfig& config) {
+    std::lock_guard<std::mutex> lock(receiverMutex);
+    this->config = config;
+}
+
+SimulcastGroup SimulcastReceiver::getGroup() const {
+    std::lock_guard<std::mutex> lock(receiverMutex);
+    return group;
+}
+
+bool SimulcastReceiver::hasLayer(StreamId streamId) const {
+    std::lock_guard<std::mutex> lock(receiverMutex);
+    return indexOf(streamId).has_value();
+}
+
+SimulcastVerdict SimulcastReceiver::accept(StreamId layer, uint32_t sequence, uint64_t playoutTimeUs,
+                                           uint64_t nowMs) {
+    std::lock_guard<std::mutex> lock(receiverMutex);
+    auto index = indexOf(layer);
+    if (!index) {
+        return SimulcastVerdict::Drop;
+    }
+
+    SimulcastVerdict verdict = SimulcastVerdict::Play;
+    if (pending && *index == *pending) {
+        SABER_LOG(Info, "simulcast", "Stream " << group.streamId << ": livello " << group.layers[active].bitrateKbps
+                  << "kbps -> " << group.layers[*pending].bitrateKbps << "kbps");
+        active = *pending;
+        pending.reset();
+        switches++;
+        resetWindow();
+        cleanSinceMs = nowMs;
+        // Il frame già riprodotto dal livello precedente prepara solo il decodificatore
+        if (lastPlayoutUs && playoutTimeUs <= *lastPlayoutUs) {
+            verdict = SimulcastVerdict::Prime;
+        }
+    }
+    if (*index != active) {
+        return SimulcastVerdict::Drop;
+    }
+
+    if (!windowFirst) {
+        windowFirst = sequence;
+        windowLast = sequence;
+    } else if (static_cast<int32_t>(sequence - windowLast) > 0) {
+        windowLast = sequence;
+    }
+    windowReceived++;
+    if (!lastPlayoutUs || playoutTimeUs > *lastPlayoutUs) {
+        lastPlayoutUs = playoutTimeUs;
+    }
+    return verdict;
+}
+
+bool SimulcastReceiver::update(std::optional<uint32_t> budgetKbps, uint64_t nowMs) {
+    std::lock_guard<std::mutex> lock(receiverMutex);
+    bool changed = false;
+    if (pending && nowMs - pendingSinceMs >= SIMULCAST_SWITCH_TIMEOUT_MS) {
+        SABER_LOG(Warn, "simulcast", "Stream " << group.streamId << ": nessun frame dal livello "
+                  << group.layers[*pending].streamId << ", cambio annullato");
+        pending.reset();
+        changed = true;
+    }
+
+    // La perdita viene valutata su finestre abbastanza lunghe da essere significative
+    if (windowFirst) {
+        uint32_t expected = windowLast - *windowFirst + 1;
+        if (expected >= SIMULCAST_MIN_WINDOW_FRAMES) {
+            loss = windowReceived < expected ? static_cast<double>(expected - windowReceived) / expected : 0.0;
+            if (loss * 100.0 > config.lossThresholdPercent) {
+                estimatedKbps = static_cast<uint32_t>(group.layers[active].bitrateKbps * (1.0 - loss));
+                cleanSinceMs = nowMs;
+            }
+            resetWindow();
+        }
+    }
+    if (!cleanSinceMs) {
+        cleanSinceMs = nowMs;
+    }
+    bool clean = nowMs - *cleanSinceMs >= config.upgradeAfterMs;
+    // Dopo un periodo pulito il limite stimato cade e si prova il livello superiore
+    if (clean) {
+        estimatedKbps.reset();
+    }
+
+    std::optional<uint32_t> budget = budgetKbps;
+    if (estimatedKbps && (!budget || *estimatedKbps < *budget)) {
+        budget = estimatedKbps;
+    }
+    lastBudgetKbps = budget;
+
+    // Un cambio alla volta
+    if (pending) {
+        return changed;
+    }
+    size_t target = active;
+    if (!fits(active, budget)) {
+        target = group.layers.size() - 1;
+        for (size_t i = active + 1; i < group.layers.size(); ++i) {
+            if (fits(i, budget)) {
+                target = i;
+                break;
+            }
+        }
+    } else if (active > 0 && clean && fits(active - 1, budget)) {
+        target = active - 1;
+    }
+    if (target != active) {
+        pending = target;
+        pendingSinceMs = nowMs;
+        changed = true;
+    }
+    return changed;
+}
+
+std::vector<StreamId> SimulcastReceiver::wantedLayers() const {
+    std::lock_guard<std::mutex> lock(receiverMutex);
+    std::vector<StreamId> layers{group.layers[active].streamId};
+    if (pending) {
+        layers.push_back(group.layers[*pending].streamId);
+    }
+    return layers;
+}
+
+SimulcastStatus SimulcastReceiver::getStatus() const {
+    std::lock_guard<std::mutex> lock(receiverMutex);
+    SimulcastStatus status;
+    status.streamId = group.streamId;
+    status.activeLayer = group.layers[active].streamId;
+    if (pending) {
+        status.pendingLayer = group.layers[*pending].streamId;
+    }
+    status.bitrateKbps = group.layers[active].bitrateKbps;
+    status.budgetKbps = lastBudgetKbps;
+    status.lossPercent = loss * 100.0;
+    status.switches = switches;
+    return status;
+}
+
+std::optional<size_t> SimulcastReceiver::indexOf(StreamId layer) const {
+    for (size_t i = 0; i < group.layers.size(); ++i) {
+        if (group.layers[i].streamId == layer) {
+            return i;
+        }
+    }
+    return std::nullopt;
+}
+
+bool SimulcastReceiver::fits(size_t index, std::optional<uint32_t> budgetKbps) const {
+    if (!budgetKbps) {
+        return true;
+    }
+    uint64_t required = static_cast<uint64_t>(group.layers[index].bitrateKbps) * (100 + config.headroomPercent);
+    return required <= static_cast<uint64_t>(*budgetKbps) * 100;
+}
+
+void SimulcastReceiver::resetWindow() {
+    windowFirst.reset();
+    windowLast = 0;
+    windowReceived = 0;
+}
+
+} // namespace saber
@@ -0,0 +1,261 @@
+#include "source_selector.h"
+
+#include <algorithm>
+#include <cmath>
+#include <cstdlib>
+#include <stdexcept>
+
+namespace saber {
+
+namespace {
+
+const double TWO_PI = 6.283185307179586;
+
+// Un frame è silenzio se nessun campione supera la soglia
+bool isSilent(const AudioFrame& frame, uint16_t threshold) {
+    for (int16_t sample : frame.samples) {
+        if (std::abs(static_cast<int>(sample)) > threshold) {
+            return false;
+        }
+    }
+    return true;
+}
+
+} // namespace
+
+std::string sourceKindToString(SourceKind kind) {
+    switch (kind) {
+        case SourceKind::Capture:
+            return "capture";
+        case SourceKind::FileQueue:
+            return "file";
+        case SourceKind::Rtp:
+            return "rtp";
+        case SourceKind::TestTone:
+            return "tone";
+    }
+    return "unknown";
+}
+
+std::optional<SourceKind> sourceKindFromString(const std::string& value) {
+    for (SourceKind kind : {SourceKind::Capture, SourceKind::FileQueue, SourceKind::Rtp, SourceKind::TestTone}) {
+        if (sourceKindToString(kind) == value) {
+            return kind;
+        }
+    }
+    return std::nullopt;
+}
+
+// Implementazione di SourceSelector
+SourceSelector::SourceSelector(const SourceSelectionConfig& config) {
+    setConfig(config);
+}
+
+void SourceSelector::setConfig(const SourceSelectionConfig& updated) {
+    std::lock_guard<std::mutex> lock(selectorMutex);
+    config = updated;
+    std::map<std::string, SourceHealth> kept;
+    for (const auto& source : config.sources) {
+        auto it = health.find(source.name);
+        kept[source.name] = it != health.end() ? it->second : SourceHealth();
+    }
+    health = std::move(kept);
+}
+
+SourceSelectionConfig SourceSelector::getConfig() const {
+    std::lock_guard<std::mutex> lock(selectorMutex);
+    return config;
+}
+
+bool SourceSelector::offerFrame(const std::string& name, const AudioFrame& frame, uint64_t nowMs) {
+    std::lock_guard<std::mutex> lock(selectorMutex);
+    auto it = health.find(name);
+    if (it == health.end()) {
+        return false;
+    }
+    SourceHealth& source = it->second;
+    bool resumed = !source.lastFrameMs || nowMs - *source.lastFrameMs > config.stallMs;
+    source.frames++;
+    source.lastFrameMs = nowMs;
+    // Una sorgente che riparte ha silenceMs per far sentire qualcosa
+    if (resumed || !isSilent(frame, config.silenceThreshold)) {
+        source.lastAudibleMs = nowMs;
+    }
+    return active == name;
+}
+
+std::optional<SourceSwitch> SourceSelector::update(uint64_t nowMs) {
+    std::lock_guard<std::mutex> lock(selectorMutex);
+    for (auto& entry : health) {
+        if (!faultOf(entry.first, nowMs).empty()) {
+            entry.second.healthySinceMs.reset();
+        } else if (!entry.second.healthySinceMs) {
+            entry.second.healthySinceMs = nowMs;
+        }
+    }
+
+    if (active && !findSource(*active)) {
+        SourceSwitch change{*active, "", "removed"};
+        active = pickHealthy(nowMs, "");
+        manual = false;
+        change.to = active.value_or("");
+        return change;
+    }
+
+    if (!active) {
+        auto pick = pickHealthy(nowMs, "");
+        if (!pick) {
+            return std::nullopt;
+        }
+        active = pick;
+        return SourceSwitch{"", *pick, "initial"};
+    }
+
+    std::string fault = faultOf(*active, nowMs);
+    if (!fault.empty()) {
+        // Senza alternative si resta sulla sorgente corrente: riprenderà da sola
+        auto pick = pickHealthy(nowMs, *active);
+        if (!pick) {
+            return std::nullopt;
+        }
+        SourceSwitch change{*active, *pick, fault};
+        active = pick;
+        manual = false;
+        return change;
+    }
+
+    if (!config.autoFailback || manual) {
+        return std::nullopt;
+    }
+    const AudioSourceConfig* preferred = findSource(*active);
+    for (const auto& source : config.sources) {
+        const SourceHealth& candidate = health[source.name];
+        if (source.priority < preferred->priority && candidate.healthySinceMs
+            && nowMs - *candidate.healthySinceMs >= config.failbackMs) {
+            preferred = &source;
+        }
+    }
+    if (preferred->name == *active) {
+        return std::nullopt;
+    }
+    SourceSwitch change{*active, preferred->name, "failback"};
+    active = preferred->name;
+    return change;
+}
+
+std::optional<SourceSwitch> SourceSelector::switchTo(const std::string& name) {
+    std::lock_guard<std::mutex> lock(selectorMutex);
+    if (!findSource(name)) {
+        throw std::invalid_argument("Sorgente sconosciuta: " + name);
+    }
+    manual = true;
+    if (active == name) {
+        return std::nullopt;
+    }
+    SourceSwitch change{active.value_or(""), name, "manual"};
+    active = name;
+    return change;
+}
+
+void SourceSelector::resumeAutomatic() {
+    std::lock_guard<std::mutex> lock(selectorMutex);
+    manual = false;
+}
+
+bool SourceSelector::isManual() const {
+    std::lock_guard<std::mutex> lock(selectorMutex);
+    return manual;
+}
+
+std::optional<std::string> SourceSelector::getActive() const {
+    std::lock_guard<std::mutex> lock(selectorMutex);
+    return active;
+}
+
+std::vector<SourceStatus> SourceSelector::getStatus(uint64_t nowMs) const {
+    std::lock_guard<std::mutex> lock(selectorMutex);
+    std::vector<SourceStatus> result;
+    for (const auto& source : config.sources) {
+        const SourceHealth& state = health.at(source.name);
+        SourceStatus status;
+        status.name = source.name;
+        status.kind = source.kind;
+        status.priority = source.priority;
+        status.active = active == source.name;
+        status.healthy = faultOf(source.name, nowMs).empty();
+        status.frames = state.frames;
+        if (state.lastFrameMs) {
+            status.idleMs = nowMs - *state.lastFrameMs;
+        }
+        result.push_back(status);
+    }
+    return result;
+}
+
+std::string SourceSelector::faultOf(const std::string& name, uint64_t nowMs) const {
+    auto it = health.find(name);
+    if (it == health.end() || !it->second.lastFrameMs || nowMs - *it->second.lastFrameMs > config.stallMs) {
+        return "stalled";
+    }
+    if (config.silenceMs != 0 && nowMs - *it->second.lastAudibleMs > config.silenceMs) {
+        return "silent";
+    }
+    return "";
+}
+
+std::optional<std::string> SourceSelector::pickHealthy(uint64_t nowMs, const std::string& after) const {
+    std::optional<uint32_t> best;
+    for (const auto& source : config.sources) {
+        if (source.name != after && faultOf(source.name, nowMs).empty()) {
+            best = best ? std::min(*best, source.priority) : source.priority;
+        }
+    }
+    if (!best) {
+        return std::nullopt;
+    }
+
+    // A parità di priorità si prosegue dopo la sorgente lasciata, a rotazione
+    auto afterIt = std::find_if(config.sources.begin(), config.sources.end(),
+        [&](const AudioSourceConfig& source) { return source.name == after; });
+    size_t start = afterIt != config.sources.end() ? afterIt - config.sources.begin() + 1 : 0;
+    for (size_t i = 0; i < config.sources.size(); i++) {
+        const auto& source = config.sources[(start + i) % config.sources.size()];
+        if (source.name != after && source.priority == *best && faultOf(source.name, nowMs).empty()) {
+            return source.name;
+        }
+    }
+    return std::nullopt;
+}
+
+const AudioSourceConfig* SourceSelector::findSource(const std::string& name) const {
+    for (const auto& source : config.sources) {
+        if (source.name == name) {
+            return &source;
+        }
+    }
+    return nullptr;
+}
+
+// Implementazione di ToneGenerator
+ToneGenerator::ToneGenerator(float frequencyHz, uint32_t sampleRate, uint8_t channels, int16_t amplitude)
+    : frequencyHz(frequencyHz), sampleRate(sampleRate), channels(channels), amplitude(amplitude) {
+}
+
+AudioFrame ToneGenerator::next(uint64_t playoutTimeUs) {
+    AudioFrame frame;
+    frame.playoutTimeUs = playoutTimeUs;
+    frame.sampleRate = sampleRate;
+    frame.channels = channels;
+    size_t count = frame.samplesPerChannel();
+    frame.samples.reserve(count * channels);
+    const double step = TWO_PI * frequencyHz / sampleRate;
+    for (size_t i = 0; i < count; i++) {
+        auto value = static_cast<int16_t>(amplitude * std::sin(phase));
+        frame.samples.insert(frame.samples.end(), channels, value);
+        // La fase resta in [0, 2π) per non perdere precisione nelle trasmissioni lunghe
+        phase = std::fmod(phase + step, TWO_PI);
+    }
+    return frame;
+}
+
+} // namespace saber
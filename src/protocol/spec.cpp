@@ -0,0 +1,73 @@
+#include "spec.h"
+
+#include <stdexcept>
+
+namespace saber {
+namespace spec {
+
+namespace {
+
+void addDeviation(std::vector<std::string>& out, const char* name, uint32_t value, uint32_t expected) {
+    if (value != expected) {
+        out.push_back(std::string(name) + "=" + std::to_string(value) + 
+                      " (spec: " + std::to_string(expected) + ")");
+    }
+}
+
+} // namespace
+
+} // namespace spec
+
+std::string latencyModeToString(LatencyMode mode) {
+    return mode == LatencyMode::Strict ? "strict" : "best_effort";
+}
+
+std::optional<LatencyMode> latencyModeFromString(const std::string& value) {
+    if (value == "best_effort") {
+        return LatencyMode::BestEffort;
+    }
+    if (value == "strict") {
+        return LatencyMode::Strict;
+    }
+    return std::nullopt;
+}
+
+namespace spec {
+
+// Implementazione di Parameters
+std::vector<std::string> Parameters::deviations() const {
+    std::vector<std::string> result;
+    addDeviation(result, "jitter_tolerance_ms", jitterToleranceMs, JITTER_TOLERANCE_MS);
+    addDeviation(result, "latency_budget_ms", latencyBudgetMs, LATENCY_BUDGET_MS);
+    addDeviation(result, "buffer_margin_ms", bufferMarginMs, BUFFER_MARGIN_MS);
+    addDeviation(result, "default_buffer_ms", defaultBufferMs, DEFAULT_BUFFER_MS);
+    return result;
+}
+
+void Parameters::validate() const {
+    if (latencyBudgetMs == 0) {
+        throw std::invalid_argument("Il budget di latenza deve essere maggiore di zero");
+    }
+    if (latencyMode == LatencyMode::Strict && latencyBudgetMs > LATENCY_BUDGET_MS) {
+        throw std::invalid_argument("In modalità rigorosa il budget di latenza non può superare i " 
+                                    + std::to_string(LATENCY_BUDGET_MS) + " ms della specifica");
+    }
+    if (bufferMarginMs >= latencyBudgetMs) {
+        throw std::invalid_argument("Il margine del buffer deve essere inferiore al budget di latenza");
+    }
+    if (defaultBufferMs == 0 || defaultBufferMs > latencyBudgetMs) {
+        throw std::invalid_argument("Il buffer predefinito deve essere compreso nel budget di latenza");
+    }
+    if (minBufferMs > defaultBufferMs) {
+        throw std::invalid_argument("Il buffer minimo non può superare il buffer predefinito");
+    }
+    if (beaconTimeoutMs != 0 && beaconTimeoutMs <= BEACON_INTERVAL_MS) {
+        throw std::invalid_argument("Il timeout dei beacon deve superare l'intervallo tra beacon");
+    }
+    if (clockMaxSlewPpm == 0 || clockMaxSlewPpm > 100000) {
+        throw std::invalid_argument("La velocità di recupero dell'orologio deve essere tra 1 e 100000 ppm");
+    }
+}
+
+} // namespace spec
+} // namespace saber
@@ -0,0 +1,97 @@
+#include "standby.h"
+#include "log.h"
+
+#include <algorithm>
+
+namespace saber {
+
+std::string powerStateToString(PowerState state) {
+    switch (state) {
+        case PowerState::Active:
+            return "active";
+        case PowerState::Standby:
+            return "standby";
+        case PowerState::Waking:
+            return "waking";
+    }
+    return "unknown";
+}
+
+// Implementazione di StandbyController
+StandbyController::StandbyController(const StandbyConfig& config)
+    : config(config) {
+}
+
+void StandbyController::setConfig(const StandbyConfig& config) {
+    std::lock_guard<std::mutex> lock(standbyMutex);
+    this->config = config;
+}
+
+std::optional<PowerState> StandbyController::update(bool streaming, uint64_t nowMs) {
+    std::lock_guard<std::mutex> lock(standbyMutex);
+    switch (state) {
+        case PowerState::Active:
+            if (streaming) {
+                idleSinceMs.reset();
+                return std::nullopt;
+            }
+            if (!idleSinceMs) {
+                idleSinceMs = nowMs;
+            }
+            if (config.enabled && nowMs - *idleSinceMs >= static_cast<uint64_t>(config.idleMinutes) * 60000) {
+                state = PowerState::Standby;
+                SABER_LOG(Info, "standby", "Nessuno stream da " << config.idleMinutes << " minuti: standby");
+                return state;
+            }
+            return std::nullopt;
+        case PowerState::Standby:
+            // Una sottoscrizione arrivata senza risveglio esplicito riaccende comunque il dispositivo
+            if (streaming) {
+                state = PowerState::Waking;
+                wakingSinceMs = nowMs;
+                return state;
+            }
+            return std::nullopt;
+        case PowerState::Waking:
+            if (nowMs - wakingSinceMs >= config.prerollMs) {
+                state = PowerState::Active;
+                // La finestra di inattività riparte dal risveglio
+                idleSinceMs = nowMs;
+                SABER_LOG(Info, "standby", "Dispositivo audio pronto dopo " << nowMs - wakingSinceMs << " ms");
+                return state;
+            }
+            return std::nullopt;
+    }
+    return std::nullopt;
+}
+
+std::optional<PowerState> StandbyController::wake(uint64_t nowMs) {
+    std::lock_guard<std::mutex> lock(standbyMutex);
+    if (state == PowerState::Active) {
+        // Un risveglio da attivo rinvia lo standby
+        idleSinceMs = nowMs;
+        return std::nullopt;
+    }
+    if (state == PowerState::Waking) {
+        return std::nullopt;
+    }
+    state = PowerState::Waking;
+    wakingSinceMs = nowMs;
+    SABER_LOG(Info, "standby", "Risveglio dallo standby");
+    return state;
+}
+
+PowerState StandbyController::getState() const {
+    std::lock_guard<std::mutex> lock(standbyMutex);
+    return state;
+}
+
+uint32_t StandbyController::reportIntervalMs(uint32_t activeIntervalMs) const {
+    std::lock_guard<std::mutex> lock(standbyMutex);
+    if (state != PowerState::Standby) {
+        return activeIntervalMs;
+    }
+    return std::max(activeIntervalMs, config.reportIntervalMs);
+}
+
+} // namespace saber
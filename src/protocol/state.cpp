@@ -0,0 +1,451 @@
+#include "state.h"
+#include "crypto.h"
+#include "util.h"
+
+#include <cstdlib>
+#include <sstream>
+#include <stdexcept>
+
+namespace saber {
+
+namespace {
+
+/// Identificatore del formato, per riconoscere documenti di altra natura
+const char* const STATE_FORMAT = "saber-node-state";
+
+std::string streamArray(const std::set<StreamId>& streams) {
+    std::string json = "[";
+    for (StreamId streamId : streams) {
+        json += (json.size() > 1 ? "," : "") + std::to_string(streamId);
+    }
+    return json + "]";
+}
+
+/**
+ * @brief Valore JSON letto dal documento di stato
+ */
+struct JsonValue {
+    enum class Type { Null, Bool, Number, String, Array, Object };
+
+    Type type = Type::Null;
+    bool boolean = false;
+    double number = 0.0;
+    std::string string;
+    std::vector<JsonValue> array;
+    std::map<std::string, JsonValue> object;
+};
+
+/**
+ * @brief Lettore JSON ricorsivo, sufficiente per i documenti di stato
+ */
+class JsonReader {
+public:
+    explicit JsonReader(const std::string& text) : text(text) {}
+
+    JsonValue parseDocument() {
+        JsonValue value = parseValue(0);
+        skipWhitespace();
+        if (pos != text.size()) {
+            fail("contenuto dopo la fine del documento");
+        }
+        return value;
+    }
+
+private:
+    /// Annidamento massimo accettato
+    static constexpr int MAX_DEPTH = 16;
+
+    const std::string& text;
+    size_t pos = 0;
+
+    [[noreturn]] void fail(const std::string& reason) const {
+        throw std::invalid_argument("documento di stato non valido: " + reason + " (posizione "
+                                    + std::to_string(pos) + ")");
+    }
+
+    void skipWhitespace() {
+        while (pos < text.size() && (text[pos] == ' ' || text[pos] == '\t' || text[pos] == '\n' || text[pos] == '\r')) {
+            pos++;
+        }
+    }
+
+    bool consume(const std::string& literal) {
+        if (text.compare(pos, literal.size(), literal) == 0) {
+            pos += literal.size();
+            return true;
+        }
+        return false;
+    }
+
+    JsonValue parseValue(int depth) {
+        if (depth > MAX_DEPTH) {
+            fail("annidamento eccessivo");
+        }
+        skipWhitespace();
+        if (pos >= text.size()) {
+            fail("fine inattesa");
+        }
+        JsonValue value;
+        char c = text[pos];
+        if (c == '{') {
+            value.type = JsonValue::Type::Object;
+            pos++;
+            skipWhitespace();
+            if (consume("}")) {
+                return value;
+            }
+            do {
+                skipWhitespace();
+                if (pos >= text.size() || text[pos] != '"') {
+                    fail("chiave attesa");
+                }
+                std::string key = parseString();
+                skipWhitespace();
+                if (!consume(":")) {
+                    fail("':' atteso");
+                }
+                value.object[key] = parseValue(depth + 1);
+                skipWhitespace();
+            } while (consume(","));
+            if (!consume("}")) {
+                fail("'}' atteso");
+            }
+        } else if (c == '[') {
+            value.type = JsonValue::Type::Array;
+            pos++;
+            skipWhitespace();
+            if (consume("]")) {
+                return value;
+            }
+            do {
+                value.array.push_back(parseValue(depth + 1));
+                skipWhitespace();
+            } while (consume(","));
+            if (!consume("]")) {
+                fail("']' atteso");
+            }
+        } else if (c == '"') {
+            value.type = JsonValue::Type::String;
+            value.string = parseString();
+        } else if (consume("true")) {
+            value.type = JsonValue::Type::Bool;
+            value.boolean = true;
+        } else if (consume("false")) {
+            value.type = JsonValue::Type::Bool;
+        } else if (consume("null")) {
+            value.type = JsonValue::Type::Null;
+        } else {
+            const char* start = text.c_str() + pos;
+            char* end = nullptr;
+            value.number = std::strtod(start, &end);
+            if (end == start) {
+                fail("valore non riconosciuto");
+            }
+            value.type = JsonValue::Type::Number;
+            pos += static_cast<size_t>(end - start);
+        }
+        return value;
+    }
+
+    std::string parseString() {
+        pos++;
+        std::string result;
+        while (pos < text.size() && text[pos] != '"') {
+            char c = text[pos++];
+            if (c != '\\') {
+                result += c;
+                continue;
+            }
+            if (pos >= text.size()) {
+                break;
+            }
+            char escaped = text[pos++];
+            switch (escaped) {
+                case '"': result += '"'; break;
+                case '\\': result += '\\'; break;
+                case '/': result += '/'; break;
+                case 'b': result += '\b'; break;
+                case 'f': result += '\f'; break;
+                case 'n': result += '\n'; break;
+                case 't': result += '\t'; break;
+                case 'r': result += '\r'; break;
+                case 'u': appendUtf8(result, parseCodePoint()); break;
+                default: fail("sequenza di escape non supportata");
+            }
+        }
+        if (!consume("\"")) {
+            fail("stringa non terminata");
+        }
+        return result;
+    }
+
+    // Quattro cifre esadecimali dopo \u
+    uint32_t parseHex4() {
+        if (pos + 4 > text.size()) {
+            fail("sequenza \\u troncata");
+        }
+        uint32_t unit = 0;
+        for (int i = 0; i < 4; ++i) {
+            char c = text[pos++];
+            unit <<= 4;
+            if (c >= '0' && c <= '9') {
+                unit |= static_cast<uint32_t>(c - '0');
+            } else if (c >= 'a' && c <= 'f') {
+                unit |= static_cast<uint32_t>(c - 'a' + 10);
+            } else if (c >= 'A' && c <= 'F') {
+                unit |= static_cast<uint32_t>(c - 'A' + 10);
+            } else {
+                fail("cifra esadecimale non valida in \\u");
+            }
+        }
+        return unit;
+    }
+
+    // Code point di una sequenza \uXXXX, ricomponendo le coppie surrogate UTF-16
+    uint32_t parseCodePoint() {
+        uint32_t unit = parseHex4();
+        if (unit >= 0xDC00 && unit <= 0xDFFF) {
+            fail("surrogato basso isolato");
+        }
+        if (unit < 0xD800 || unit > 0xDBFF) {
+            return unit;
+        }
+        if (!consume("\\u")) {
+            fail("surrogato alto senza surrogato basso");
+        }
+        uint32_t low = parseHex4();
+        if (low < 0xDC00 || low > 0xDFFF) {
+            fail("surrogato alto senza surrogato basso");
+        }
+        return 0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00);
+    }
+
+    static void appendUtf8(std::string& out, uint32_t codePoint) {
+        if (codePoint < 0x80) {
+            out += static_cast<char>(codePoint);
+        } else if (codePoint < 0x800) {
+            out += static_cast<char>(0xC0 | (codePoint >> 6));
+            out += static_cast<char>(0x80 | (codePoint & 0x3F));
+        } else if (codePoint < 0x10000) {
+            out += static_cast<char>(0xE0 | (codePoint >> 12));
+            out += static_cast<char>(0x80 | ((codePoint >> 6) & 0x3F));
+            out += static_cast<char>(0x80 | (codePoint & 0x3F));
+        } else {
+            out += static_cast<char>(0xF0 | (codePoint >> 18));
+            out += static_cast<char>(0x80 | ((codePoint >> 12) & 0x3F));
+            out += static_cast<char>(0x80 | ((codePoint >> 6) & 0x3F));
+            out += static_cast<char>(0x80 | (codePoint & 0x3F));
+        }
+    }
+};
+
+const JsonValue& field(const JsonValue& object, const std::string& name, JsonValue::Type type) {
+    auto it = object.object.find(name);
+    if (it == object.object.end() || it->second.type != type) {
+        throw std::invalid_argument("documento di stato non valido: campo " + name + " mancante o di tipo errato");
+    }
+    return it->second;
+}
+
+const JsonValue* optionalField(const JsonValue& object, const std::string& name, JsonValue::Type type) {
+    auto it = object.object.find(name);
+    if (it == object.object.end() || it->second.type == JsonValue::Type::Null) {
+        return nullptr;
+    }
+    if (it->second.type != type) {
+        throw std::invalid_argument("documento di stato non valido: campo " + name + " di tipo errato");
+    }
+    return &it->second;
+}
+
+NodeRole roleField(const JsonValue& object) {
+    const std::string& name = field(object, "role", JsonValue::Type::String).string;
+    auto role = nodeRoleFromString(name);
+    if (!role) {
+        throw std::invalid_argument("documento di stato non valido: ruolo sconosciuto " + name);
+    }
+    return *role;
+}
+
+// Chiave pubblica verificata contro il suo key-ID
+std::vector<uint8_t> publicKeyField(const JsonValue& object) {
+    const JsonValue* hex = optionalField(object, "public_key", JsonValue::Type::String);
+    if (!hex) {
+        return {};
+    }
+    auto decoded = fromHex(hex->string);
+    if (!decoded) {
+        throw std::invalid_argument("documento di stato non valido: chiave esadecimale non valida");
+    }
+    std::vector<uint8_t> publicKey = std::move(*decoded);
+    const JsonValue* keyId = optionalField(object, "key_id", JsonValue::Type::String);
+    if (keyId && keyId->string != MeshCrypto::keyId(publicKey)) {
+        throw std::invalid_argument("documento di stato non valido: key-ID non corrispondente alla chiave");
+    }
+    return publicKey;
+}
+
+std::set<StreamId> streamsField(const JsonValue& object, const std::string& name) {
+    std::set<StreamId> streams;
+    const JsonValue* array = optionalField(object, name, JsonValue::Type::Array);
+    if (!array) {
+        return streams;
+    }
+    for (const auto& item : array->array) {
+        if (item.type != JsonValue::Type::Number || item.number < 0 || item.number > 0xFFFF) {
+            throw std::invalid_argument("documento di stato non valido: stream non valido in " + name);
+        }
+        streams.insert(static_cast<StreamId>(item.number));
+    }
+    return streams;
+}
+
+} // namespace
+
+std::string encodeNodeState(const NodeState& state) {
+    std::ostringstream json;
+    json << "{\"format\":\"" << STATE_FORMAT << "\",\"version\":" << state.version
+         << ",\"exported_at_ms\":" << state.exportedAtMs;
+
+    json << ",\"identity\":{\"node_id\":\"" << jsonEscape(state.nodeId)
+         << "\",\"role\":\"" << nodeRoleToString(state.role) << "\"";
+    if (!state.publicKey.empty()) {
+        json << ",\"public_key\":\"" << toHex(state.publicKey)
+             << "\",\"key_id\":\"" << MeshCrypto::keyId(state.publicKey) << "\"";
+    }
+    json << "}";
+
+    json << ",\"config\":{\"profile\":\"" << jsonEscape(state.profile)
+         << "\",\"latency_mode\":\"" << latencyModeToString(state.latencyMode)
+         << "\",\"published_streams\":" << streamArray(state.publishedStreams)
+         << ",\"listen_streams\":" << streamArray(state.listenStreams)
+         << ",\"voice_streams\":" << streamArray(state.voiceStreams);
+    if (state.keystoreFile) {
+        json << ",\"keystore\":\"" << jsonEscape(*state.keystoreFile) << "\"";
+    }
+    json << "}";
+
+    json << ",\"secrets\":{";
+    bool first = true;
+    for (const auto& secret : state.secrets) {
+        json << (first ? "" : ",") << "\"" << jsonEscape(secret.first) << "\":";
+        if (secret.second) {
+            json << "\"" << jsonEscape(*secret.second) << "\"";
+        } else {
+            json << "null";
+        }
+        first = false;
+    }
+
+    json << "},\"groups\":{";
+    first = true;
+    for (const auto& zone : state.zones) {
+        json << (first ? "" : ",") << "\"" << jsonEscape(zone.first) << "\":\"" << jsonEscape(zone.second) << "\"";
+        first = false;
+    }
+
+    json << "},\"peers\":[";
+    first = true;
+    for (const auto& peer : state.peers) {
+        json << (first ? "" : ",") << "{\"node_id\":\"" << jsonEscape(peer.nodeId)
+             << "\",\"role\":\"" << nodeRoleToString(peer.role) << "\"";
+        if (!peer.publicKey.empty()) {
+            json << ",\"public_key\":\"" << toHex(peer.publicKey)
+                 << "\",\"key_id\":\"" << MeshCrypto::keyId(peer.publicKey) << "\"";
+        }
+        json << "}";
+        first = false;
+    }
+    json << "]}";
+    return json.str();
+}
+
+NodeState decodeNodeState(const std::string& json) {
+    JsonValue document = JsonReader(json).parseDocument();
+    if (document.type != JsonValue::Type::Object) {
+        throw std::invalid_argument("documento di stato non valido: oggetto atteso");
+    }
+    if (field(document, "format", JsonValue::Type::String).string != STATE_FORMAT) {
+        throw std::invalid_argument("documento di stato non valido: formato sconosciuto");
+    }
+    double version = field(document, "version", JsonValue::Type::Number).number;
+    if (version < 1 || version > NODE_STATE_VERSION) {
+        throw std::invalid_argument("versione del documento di stato non supportata: "
+                                    + std::to_string(static_cast<long long>(version)));
+    }
+
+    NodeState state;
+    state.version = static_cast<uint32_t>(version);
+    if (const JsonValue* exportedAt = optionalField(document, "exported_at_ms", JsonValue::Type::Number)) {
+        state.exportedAtMs = static_cast<uint64_t>(exportedAt->number);
+    }
+
+    const JsonValue& identity = field(document, "identity", JsonValue::Type::Object);
+    state.nodeId = field(identity, "node_id", JsonValue::Type::String).string;
+    if (state.nodeId.empty()) {
+        throw std::invalid_argument("documento di stato non valido: ID del nodo vuoto");
+    }
+    state.role = roleField(identity);
+    state.publicKey = publicKeyField(identity);
+
+    if (const JsonValue* config = optionalField(document, "config", JsonValue::Type::Object)) {
+        if (const JsonValue* profile = optionalField(*config, "profile", JsonValue::Type::String)) {
+            state.profile = profile->string;
+        }
+        if (const JsonValue* mode = optionalField(*config, "latency_mode", JsonValue::Type::String)) {
+            auto parsed = latencyModeFromString(mode->string);
+            if (!parsed) {
+                throw std::invalid_argument("documento di stato non valido: modalità di latenza " + mode->string);
+            }
+            state.latencyMode = *parsed;
+        }
+        state.publishedStreams = streamsField(*config, "published_streams");
+        state.listenStreams = streamsField(*config, "listen_streams");
+        state.voiceStreams = streamsField(*config, "voice_streams");
+        if (const JsonValue* keystore = optionalField(*config, "keystore", JsonValue::Type::String)) {
+            state.keystoreFile = keystore->string;
+        }
+    }
+
+    if (const JsonValue* secrets = optionalField(document, "secrets", JsonValue::Type::Object)) {
+        for (const auto& secret : secrets->object) {
+            if (secret.second.type == JsonValue::Type::Null) {
+                state.secrets[secret.first] = std::nullopt;
+                continue;
+            }
+            const std::string& reference = secret.second.string;
+            // Un valore che non è un riferimento sarebbe un segreto in chiaro
+            if (secret.second.type != JsonValue::Type::String
+                || (reference.rfind("keystore:", 0) != 0 && reference.rfind("env:", 0) != 0)) {
+                throw std::invalid_argument("documento di stato non valido: " + secret.first
+                                            + " non è un riferimento keystore: o env:");
+            }
+            state.secrets[secret.first] = reference;
+        }
+    }
+
+    if (const JsonValue* groups = optionalField(document, "groups", JsonValue::Type::Object)) {
+        for (const auto& zone : groups->object) {
+            if (zone.second.type != JsonValue::Type::String) {
+                throw std::invalid_argument("documento di stato non valido: zona di " + zone.first);
+            }
+            state.zones[zone.first] = zone.second.string;
+        }
+    }
+
+    if (const JsonValue* peers = optionalField(document, "peers", JsonValue::Type::Array)) {
+        for (const auto& item : peers->array) {
+            if (item.type != JsonValue::Type::Object) {
+                throw std::invalid_argument("documento di stato non valido: nodo atteso in peers");
+            }
+            PeerState peer;
+            peer.nodeId = field(item, "node_id", JsonValue::Type::String).string;
+            peer.role = roleField(item);
+            peer.publicKey = publicKeyField(item);
+            state.peers.push_back(peer);
+        }
+    }
+    return state;
+}
+
+} // namespace saber
@@ -0,0 +1,384 @@
+#include "log.h"
+#include "state_store.h"
+
+#include <array>
+#include <cstdio>
+#include <filesystem>
+#include <fstream>
+#include <iostream>
+#include <sstream>
+#include <stdexcept>
+#include <vector>
+
+#ifdef _WIN32
+#include <io.h>
+#else
+#include <fcntl.h>
+#include <unistd.h>
+#endif
+
+namespace saber {
+
+namespace {
+
+const char* const SNAPSHOT_MAGIC = "SABER-STATE";
+const uint32_t SNAPSHOT_VERSION = 1;
+
+// CRC-32 (IEEE 802.3), lo stesso di zlib
+uint32_t crc32(const std::string& data) {
+    static const auto table = [] {
+        std::array<uint32_t, 256> entries{};
+        for (uint32_t i = 0; i < 256; ++i) {
+            uint32_t value = i;
+            for (int bit = 0; bit < 8; ++bit) {
+                value = (value & 1) ? 0xEDB88320u ^ (value >> 1) : value >> 1;
+            }
+            entries[i] = value;
+        }
+        return entries;
+    }();
+    uint32_t crc = 0xFFFFFFFFu;
+    for (unsigned char c : data) {
+        crc = table[(crc ^ c) & 0xFF] ^ (crc >> 8);
+    }
+    return crc ^ 0xFFFFFFFFu;
+}
+
+std::string crcHex(const std::string& data) {
+    char buffer[9];
+    std::snprintf(buffer, sizeof(buffer), "%08x", crc32(data));
+    return buffer;
+}
+
+// I campi sono separati da tabulazioni: tabulazioni e a capo vengono protetti
+std::string escapeField(const std::string& value) {
+    std::string escaped;
+    escaped.reserve(value.size());
+    for (char c : value) {
+        switch (c) {
+            case '\\': escaped += "\\\\"; break;
+            case '\t': escaped += "\\t"; break;
+            case '\n': escaped += "\\n"; break;
+            case '\r': escaped += "\\r"; break;
+            default: escaped += c; break;
+        }
+    }
+    return escaped;
+}
+
+std::string unescapeField(const std::string& value) {
+    std::string result;
+    result.reserve(value.size());
+    for (size_t i = 0; i < value.size(); ++i) {
+        if (value[i] == '\\' && i + 1 < value.size()) {
+            char next = value[++i];
+            result += next == 't' ? '\t' : next == 'n' ? '\n' : next == 'r' ? '\r' : next;
+        } else {
+            result += value[i];
+        }
+    }
+    return result;
+}
+
+std::vector<std::string> splitFields(const std::string& line) {
+    std::vector<std::string> fields;
+    std::istringstream input(line);
+    std::string field;
+    while (std::getline(input, field, '\t')) {
+        fields.push_back(field);
+    }
+    if (!line.empty() && line.back() == '\t') {
+        fields.emplace_back();
+    }
+    return fields;
+}
+
+std::optional<std::string> readFile(const std::string& path) {
+    std::ifstream file(path, std::ios::binary);
+    if (!file) {
+        return std::nullopt;
+    }
+    std::ostringstream content;
+    content << file.rdbuf();
+    return content.str();
+}
+
+// Forza su disco i dati del file, non solo nella cache del sistema
+bool syncFile(std::FILE* file) {
+    if (std::fflush(file) != 0) {
+        return false;
+    }
+#ifdef _WIN32
+    return _commit(_fileno(file)) == 0;
+#else
+    return fsync(fileno(file)) == 0;
+#endif
+}
+
+// Su POSIX la rinomina è persistente solo dopo la sincronizzazione della directory
+void syncDirectory(const std::string& path) {
+#ifndef _WIN32
+    auto directory = std::filesystem::path(path).parent_path();
+    int fd = open(directory.empty() ? "." : directory.c_str(), O_RDONLY);
+    if (fd >= 0) {
+        fsync(fd);
+        close(fd);
+    }
+#else
+    (void)path;
+#endif
+}
+
+/**
+ * @brief Snapshot letto dal disco
+ */
+struct Snapshot {
+    uint64_t sequence = 0;
+    std::map<std::string, std::string> values;
+};
+
+std::optional<Snapshot> loadSnapshot(const std::string& path) {
+    auto content = readFile(path);
+    if (!content) {
+        return std::nullopt;
+    }
+    auto headerEnd = content->find('\n');
+    if (headerEnd == std::string::npos) {
+        return std::nullopt;
+    }
+    auto header = splitFields(content->substr(0, headerEnd));
+    std::string body = content->substr(headerEnd + 1);
+    if (header.size() != 5 || header[0] != SNAPSHOT_MAGIC || header[4] != crcHex(body)) {
+        return std::nullopt;
+    }
+
+    Snapshot snapshot;
+    uint64_t count = 0;
+    try {
+        if (std::stoul(header[1]) != SNAPSHOT_VERSION) {
+            return std::nullopt;
+        }
+        snapshot.sequence = std::stoull(header[2]);
+        count = std::stoull(header[3]);
+    } catch (const std::exception&) {
+        return std::nullopt;
+    }
+    std::istringstream input(body);
+    std::string line;
+    while (std::getline(input, line)) {
+        auto fields = splitFields(line);
+        if (fields.size() != 2) {
+            return std::nullopt;
+        }
+        snapshot.values[unescapeField(fields[0])] = unescapeField(fields[1]);
+    }
+    if (snapshot.values.size() != count) {
+        return std::nullopt;
+    }
+    return snapshot;
+}
+
+} // namespace
+
+// Implementazione di StateStore
+StateStore::StateStore(const std::string& path, uint32_t compactAfter)
+    : path(path), compactAfter(compactAfter == 0 ? 1 : compactAfter) {}
+
+StateStore::~StateStore() {
+    if (wal) {
+        std::fclose(wal);
+    }
+}
+
+StateRecoveryReport StateStore::recover() {
+    std::lock_guard<std::mutex> lock(storeMutex);
+    StateRecoveryReport report;
+    if (wal) {
+        std::fclose(wal);
+        wal = nullptr;
+    }
+
+    // Snapshot principale, o quello precedente se una sostituzione è stata interrotta
+    bool mainExists = std::filesystem::exists(path);
+    auto snapshot = loadSnapshot(path);
+    if (!snapshot) {
+        snapshot = loadSnapshot(path + ".bak");
+        report.fromBackup = snapshot.has_value();
+        if (mainExists) {
+            SABER_LOG(Error, "state", "Snapshot dello stato corrotto: " << path
+                      << (report.fromBackup ? ", uso quello precedente" : ""));
+        }
+    }
+    report.snapshotLoaded = snapshot.has_value();
+    values = snapshot ? snapshot->values : std::map<std::string, std::string>{};
+    sequence = snapshot ? snapshot->sequence : 0;
+
+    // I record già contenuti nello snapshot vengono saltati, gli altri devono seguirlo senza buchi
+    std::string walPath = path + ".wal";
+    std::string content = readFile(walPath).value_or("");
+    size_t offset = 0;
+    walRecords = 0;
+    while (offset < content.size()) {
+        auto end = content.find('\n', offset);
+        if (end == std::string::npos) {
+            break;
+        }
+        auto fields = splitFields(content.substr(offset, end - offset));
+        if (fields.size() != 5) {
+            break;
+        }
+        std::string record = fields[0] + "\t" + fields[1] + "\t" + fields[2] + "\t" + fields[3];
+        uint64_t recordSequence = 0;
+        try {
+            recordSequence = std::stoull(fields[0]);
+        } catch (const std::exception&) {
+            break;
+        }
+        if (fields[4] != crcHex(record) || (fields[1] != "P" && fields[1] != "D")) {
+            break;
+        }
+        if (recordSequence > sequence) {
+            if (recordSequence != sequence + 1) {
+                break;
+            }
+            if (fields[1] == "P") {
+                values[unescapeField(fields[2])] = unescapeField(fields[3]);
+            } else {
+                values.erase(unescapeField(fields[2]));
+            }
+            sequence = recordSequence;
+            report.replayedRecords++;
+        }
+        walRecords++;
+        offset = end + 1;
+    }
+    if (offset < content.size()) {
+        report.discardedBytes = content.size() - offset;
+        std::error_code error;
+        std::filesystem::resize_file(walPath, offset, error);
+        if (error) {
+            SABER_LOG(Error, "state", "Impossibile troncare il registro dello stato: " << walPath);
+        }
+    }
+
+    wal = std::fopen(walPath.c_str(), "ab");
+    if (!wal) {
+        SABER_LOG(Error, "state", "Impossibile aprire il registro dello stato: " << walPath);
+    }
+    // Lo snapshot corrotto viene sostituito subito, lasciando intatto quello precedente
+    if (report.fromBackup || report.discardedBytes > 0) {
+        if (mainExists && report.fromBackup) {
+            std::remove(path.c_str());
+        }
+        compactLocked();
+    }
+    report.entries = values.size();
+    return report;
+}
+
+std::optional<std::string> StateStore::get(const std::string& key) const {
+    std::lock_guard<std::mutex> lock(storeMutex);
+    auto it = values.find(key);
+    if (it == values.end()) {
+        return std::nullopt;
+    }
+    return it->second;
+}
+
+std::map<std::string, std::string> StateStore::entries() const {
+    std::lock_guard<std::mutex> lock(storeMutex);
+    return values;
+}
+
+bool StateStore::put(const std::string& key, const std::string& value) {
+    if (key.empty()) {
+        throw std::invalid_argument("Chiave dello stato vuota");
+    }
+    std::lock_guard<std::mutex> lock(storeMutex);
+    values[key] = value;
+    return appendLocked('P', key, value);
+}
+
+bool StateStore::erase(const std::string& key) {
+    std::lock_guard<std::mutex> lock(storeMutex);
+    values.erase(key);
+    return appendLocked('D', key, "");
+}
+
+bool StateStore::compact() {
+    std::lock_guard<std::mutex> lock(storeMutex);
+    return compactLocked();
+}
+
+const std::string& StateStore::getPath() const {
+    return path;
+}
+
+bool StateStore::appendLocked(char operation, const std::string& key, const std::string& value) {
+    if (!wal) {
+        return false;
+    }
+    std::string record = std::to_string(++sequence) + "\t" + operation + "\t" + escapeField(key) + "\t"
+                       + escapeField(value);
+    std::string line = record + "\t" + crcHex(record) + "\n";
+    if (std::fwrite(line.data(), 1, line.size(), wal) != line.size() || !syncFile(wal)) {
+        return false;
+    }
+    if (++walRecords >= compactAfter) {
+        return compactLocked();
+    }
+    return true;
+}
+
+bool StateStore::compactLocked() {
+    std::string body;
+    for (const auto& entry : values) {
+        body += escapeField(entry.first) + "\t" + escapeField(entry.second) + "\n";
+    }
+    std::string header = std::string(SNAPSHOT_MAGIC) + "\t" + std::to_string(SNAPSHOT_VERSION) + "\t"
+                       + std::to_string(sequence) + "\t" + std::to_string(values.size()) + "\t" + crcHex(body);
+
+    // Snapshot completo su disco prima della rinomina: mai uno snapshot troncato al suo posto
+    std::string tmpPath = path + ".tmp";
+    std::FILE* file = std::fopen(tmpPath.c_str(), "wb");
+    if (!file) {
+        return false;
+    }
+    std::string content = header + "\n" + body;
+    bool written = std::fwrite(content.data(), 1, content.size(), file) == content.size() && syncFile(file);
+    std::fclose(file);
+    if (!written) {
+        std::remove(tmpPath.c_str());
+        return false;
+    }
+
+    // Lo snapshot precedente resta disponibile finché il nuovo non è al suo posto
+    std::string backupPath = path + ".bak";
+    if (std::filesystem::exists(path)) {
+        std::remove(backupPath.c_str());
+        if (std::rename(path.c_str(), backupPath.c_str()) != 0) {
+            std::remove(tmpPath.c_str());
+            return false;
+        }
+    }
+    if (std::rename(tmpPath.c_str(), path.c_str()) != 0) {
+        return false;
+    }
+    syncDirectory(path);
+
+    // Solo ora i record del registro sono superflui
+    std::string walPath = path + ".wal";
+    if (wal) {
+        std::fclose(wal);
+    }
+    wal = std::fopen(walPath.c_str(), "wb");
+    if (!wal) {
+        SABER_LOG(Error, "state", "Impossibile aprire il registro dello stato: " << walPath);
+        return false;
+    }
+    syncFile(wal);
+    walRecords = 0;
+    return true;
+}
+
+} // namespace saber
@@ -0,0 +1,294 @@
+#include "stats.h"
+
+#include <algorithm>
+#include <sstream>
+#include <utility>
+#include <vector>
+
+namespace saber {
+
+namespace {
+
+// Scrive una famiglia di metriche Prometheus (byte, pacchetti, tempo d'aria)
+void writeFamily(std::ostringstream& out, const std::string& prefix, const std::string& help,
+                 const std::vector<std::pair<std::string, BandwidthUsage>>& entries) {
+    out << "# HELP " << prefix << "_bytes " << help << " (byte nella finestra)\n";
+    out << "# TYPE " << prefix << "_bytes gauge\n";
+    for (const auto& entry : entries) {
+        out << prefix << "_bytes{" << entry.first << "} " << entry.second.bytes << "\n";
+    }
+    out << "# HELP " << prefix << "_packets " << help << " (pacchetti nella finestra)\n";
+    out << "# TYPE " << prefix << "_packets gauge\n";
+    for (const auto& entry : entries) {
+        out << prefix << "_packets{" << entry.first << "} " << entry.second.packets << "\n";
+    }
+    out << "# HELP " << prefix << "_airtime_ratio " << help << " (frazione del canale)\n";
+    out << "# TYPE " << prefix << "_airtime_ratio gauge\n";
+    for (const auto& entry : entries) {
+        out << prefix << "_airtime_ratio{" << entry.first << "} " << entry.second.airtimeFraction << "\n";
+    }
+}
+
+// Percentile per rango su campioni già ordinati
+uint32_t percentile(const std::vector<uint32_t>& sorted, unsigned percent) {
+    size_t rank = (sorted.size() * percent + 99) / 100;
+    return sorted[std::max<size_t>(rank, 1) - 1];
+}
+
+} // namespace
+
+// Implementazione di MeshStats
+MeshStats::MeshStats(uint32_t windowMs, uint32_t linkRateKbps, uint32_t packetOverheadBytes)
+    : windowMs(windowMs == 0 ? 1 : windowMs),
+      linkRateKbps(linkRateKbps == 0 ? 1 : linkRateKbps),
+      packetOverheadBytes(packetOverheadBytes) {
+}
+
+void MeshStats::recordReceived(const std::string& neighborId, size_t bytes, int64_t nowMs) {
+    std::lock_guard<std::mutex> lock(statsMutex);
+    Sample sample{nowMs, static_cast<uint32_t>(bytes)};
+    neighborSamples[neighborId].push_back(sample);
+    channelSamples.push_back(sample);
+}
+
+void MeshStats::recordSent(size_t bytes, int64_t nowMs) {
+    std::lock_guard<std::mutex> lock(statsMutex);
+    channelSamples.push_back(Sample{nowMs, static_cast<uint32_t>(bytes)});
+}
+
+void MeshStats::recordStream(StreamId streamId, size_t bytes, int64_t nowMs) {
+    std::lock_guard<std::mutex> lock(statsMutex);
+    streamSamples[streamId].push_back(Sample{nowMs, static_cast<uint32_t>(bytes)});
+}
+
+BandwidthReport MeshStats::report(const std::map<std::string, std::string>& zones, int64_t nowMs) {
+    std::lock_guard<std::mutex> lock(statsMutex);
+    BandwidthReport result;
+    result.windowMs = windowMs;
+
+    for (auto it = streamSamples.begin(); it != streamSamples.end();) {
+        expire(it->second, nowMs);
+        if (it->second.empty()) {
+            it = streamSamples.erase(it);
+            continue;
+        }
+        result.streams[it->first] = summarize(it->second);
+        ++it;
+    }
+
+    for (auto it = neighborSamples.begin(); it != neighborSamples.end();) {
+        expire(it->second, nowMs);
+        if (it->second.empty()) {
+            it = neighborSamples.erase(it);
+            continue;
+        }
+        BandwidthUsage usage = summarize(it->second);
+        result.neighbors[it->first] = usage;
+
+        auto zone = zones.find(it->first);
+        if (zone != zones.end()) {
+            BandwidthUsage& zoneUsage = result.zones[zone->second];
+            zoneUsage.bytes += usage.bytes;
+            zoneUsage.packets += usage.packets;
+            zoneUsage.airtimeUs += usage.airtimeUs;
+            zoneUsage.bytesPerSecond += usage.bytesPerSecond;
+            zoneUsage.airtimeFraction += usage.airtimeFraction;
+        }
+        ++it;
+    }
+
+    expire(channelSamples, nowMs);
+    result.total = summarize(channelSamples);
+    return result;
+}
+
+double MeshStats::airtimeUtilization(int64_t nowMs) {
+    std::lock_guard<std::mutex> lock(statsMutex);
+    expire(channelSamples, nowMs);
+    return summarize(channelSamples).airtimeFraction;
+}
+
+std::string MeshStats::toPrometheus(const BandwidthReport& report, const std::string& nodeId) {
+    std::ostringstream out;
+    std::string node = "node=\"" + nodeId + "\"";
+
+    std::vector<std::pair<std::string, BandwidthUsage>> entries;
+    for (const auto& entry : report.streams) {
+        entries.emplace_back(node + ",stream=\"" + std::to_string(entry.first) + "\"", entry.second);
+    }
+    writeFamily(out, "saber_stream", "Traffico per stream", entries);
+
+    entries.clear();
+    for (const auto& entry : report.neighbors) {
+        entries.emplace_back(node + ",neighbor=\"" + entry.first + "\"", entry.second);
+    }
+    writeFamily(out, "saber_neighbor", "Traffico ricevuto per nodo vicino", entries);
+
+    entries.clear();
+    for (const auto& entry : report.zones) {
+        entries.emplace_back(node + ",zone=\"" + entry.first + "\"", entry.second);
+    }
+    writeFamily(out, "saber_zone", "Traffico ricevuto per zona", entries);
+
+    writeFamily(out, "saber_channel", "Traffico complessivo sul canale radio", {{node, report.total}});
+    return out.str();
+}
+
+void MeshStats::expire(Window& window, int64_t nowMs) const {
+    while (!window.empty() && nowMs - window.front().timeMs >= static_cast<int64_t>(windowMs)) {
+        window.pop_front();
+    }
+}
+
+BandwidthUsage MeshStats::summarize(const Window& window) const {
+    BandwidthUsage usage;
+    for (const auto& sample : window) {
+        usage.bytes += sample.bytes;
+        usage.packets++;
+        usage.airtimeUs += airtimeUs(sample.bytes);
+    }
+    usage.bytesPerSecond = static_cast<double>(usage.bytes) * 1000.0 / windowMs;
+    usage.airtimeFraction = static_cast<double>(usage.airtimeUs) / (windowMs * 1000.0);
+    return usage;
+}
+
+uint64_t MeshStats::airtimeUs(uint32_t bytes) const {
+    // kbps equivale a bit per millisecondo
+    uint64_t bits = static_cast<uint64_t>(bytes + packetOverheadBytes) * 8;
+    return bits * 1000 / linkRateKbps;
+}
+
+// Implementazione di LatencyStats
+LatencyStats::LatencyStats(size_t windowSamples, uint32_t bucketMs)
+    : windowSamples(windowSamples == 0 ? 1 : windowSamples), bucketMs(bucketMs == 0 ? 1 : bucketMs) {
+}
+
+void LatencyStats::recordLatency(const std::string& nodeId, uint32_t latencyMs) {
+    std::lock_guard<std::mutex> lock(statsMutex);
+    auto& latencies = nodes[nodeId].latencies;
+    latencies.push_back(latencyMs);
+    if (latencies.size() > windowSamples) {
+        latencies.pop_front();
+    }
+}
+
+void LatencyStats::recordFrame(const std::string& nodeId, StreamId streamId, uint32_t frameSequence) {
+    std::lock_guard<std::mutex> lock(statsMutex);
+    SequenceWindow& window = nodes[nodeId].sequences[streamId];
+    if (!window.received.empty()) {
+        uint32_t last = *window.received.rbegin();
+        // Un frame molto più vecchio della finestra indica una sorgente riavviata
+        if (frameSequence < last && last - frameSequence >= windowSamples) {
+            window.received.clear();
+        }
+    }
+    if (window.received.empty() || frameSequence < window.first) {
+        window.first = frameSequence;
+    }
+    window.received.insert(frameSequence);
+
+    uint32_t last = *window.received.rbegin();
+    while (last - *window.received.begin() >= windowSamples) {
+        window.received.erase(window.received.begin());
+    }
+}
+
+void LatencyStats::reset() {
+    std::lock_guard<std::mutex> lock(statsMutex);
+    nodes.clear();
+}
+
+NetworkStats LatencyStats::report() const {
+    std::lock_guard<std::mutex> lock(statsMutex);
+    NetworkStats result;
+    result.windowSamples = windowSamples;
+    result.bucketMs = bucketMs;
+
+    std::vector<uint32_t> all;
+    for (const auto& node : nodes) {
+        NodeNetworkStats& stats = result.nodes[node.first];
+        std::vector<uint32_t> samples(node.second.latencies.begin(), node.second.latencies.end());
+        all.insert(all.end(), samples.begin(), samples.end());
+        summarizeLatency(std::move(samples), stats);
+
+        for (const auto& stream : node.second.sequences) {
+            const SequenceWindow& window = stream.second;
+            if (window.received.empty()) {
+                continue;
+            }
+            uint64_t span = static_cast<uint64_t>(*window.received.rbegin()) - window.first + 1;
+            uint64_t expected = std::min<uint64_t>(span, windowSamples);
+            stats.framesReceived += window.received.size();
+            stats.framesLost += expected - window.received.size();
+        }
+        uint64_t expected = stats.framesReceived + stats.framesLost;
+        stats.lossRate = expected == 0 ? 0.0 : static_cast<double>(stats.framesLost) / expected;
+
+        result.overall.framesReceived += stats.framesReceived;
+        result.overall.framesLost += stats.framesLost;
+    }
+
+    summarizeLatency(std::move(all), result.overall);
+    uint64_t expected = result.overall.framesReceived + result.overall.framesLost;
+    result.overall.lossRate = expected == 0 ? 0.0 : static_cast<double>(result.overall.framesLost) / expected;
+    return result;
+}
+
+std::string LatencyStats::toPrometheus(const NetworkStats& stats, const std::string& nodeId) {
+    std::ostringstream out;
+    out << "# HELP saber_peer_latency_ms Latenza per nodo (percentili sulla finestra)\n";
+    out << "# TYPE saber_peer_latency_ms gauge\n";
+    for (const auto& entry : stats.nodes) {
+        if (entry.second.samples == 0) {
+            continue;
+        }
+        std::string labels = "node=\"" + nodeId + "\",peer=\"" + entry.first + "\"";
+        out << "saber_peer_latency_ms{" << labels << ",quantile=\"0.5\"} " << entry.second.p50LatencyMs << "\n";
+        out << "saber_peer_latency_ms{" << labels << ",quantile=\"0.95\"} " << entry.second.p95LatencyMs << "\n";
+        out << "saber_peer_latency_ms{" << labels << ",quantile=\"0.99\"} " << entry.second.p99LatencyMs << "\n";
+    }
+    out << "# HELP saber_peer_jitter_variance_ms2 Varianza della latenza per nodo\n";
+    out << "# TYPE saber_peer_jitter_variance_ms2 gauge\n";
+    for (const auto& entry : stats.nodes) {
+        if (entry.second.samples > 0) {
+            out << "saber_peer_jitter_variance_ms2{node=\"" << nodeId << "\",peer=\"" << entry.first << "\"} "
+                << entry.second.jitterVarianceMs2 << "\n";
+        }
+    }
+    out << "# HELP saber_peer_loss_ratio Stima della perdita dei frame audio per nodo\n";
+    out << "# TYPE saber_peer_loss_ratio gauge\n";
+    for (const auto& entry : stats.nodes) {
+        if (entry.second.framesReceived > 0) {
+            out << "saber_peer_loss_ratio{node=\"" << nodeId << "\",peer=\"" << entry.first << "\"} "
+                << entry.second.lossRate << "\n";
+        }
+    }
+    return out.str();
+}
+
+void LatencyStats::summarizeLatency(std::vector<uint32_t> samples, NodeNetworkStats& stats) const {
+    stats.samples = samples.size();
+    if (samples.empty()) {
+        return;
+    }
+
+    std::sort(samples.begin(), samples.end());
+    double sum = 0.0;
+    for (uint32_t sample : samples) {
+        sum += sample;
+        stats.histogram[sample / bucketMs * bucketMs]++;
+    }
+    stats.meanLatencyMs = sum / samples.size();
+    double squares = 0.0;
+    for (uint32_t sample : samples) {
+        double delta = sample - stats.meanLatencyMs;
+        squares += delta * delta;
+    }
+    stats.jitterVarianceMs2 = squares / samples.size();
+    stats.p50LatencyMs = percentile(samples, 50);
+    stats.p95LatencyMs = percentile(samples, 95);
+    stats.p99LatencyMs = percentile(samples, 99);
+    stats.maxLatencyMs = samples.back();
+}
+
+} // namespace saber
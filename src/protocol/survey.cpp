@@ -0,0 +1,111 @@
+#include "survey.h"
+
+#include <algorithm>
+#include <climits>
+#include <numeric>
+#include <stdexcept>
+
+namespace saber {
+
+namespace {
+
+// Sondaggi senza risposta conservati al massimo, i più vecchi restano persi
+const size_t MAX_PENDING_PROBES = 4096;
+
+// Un collegamento è migliore se perde meno, poi se ha RSSI più alto
+bool betterLink(const SurveyLink& a, const SurveyLink& b) {
+    if (a.lossRate != b.lossRate) {
+        return a.lossRate < b.lossRate;
+    }
+    return a.meanRssiDbm.value_or(INT32_MIN) > b.meanRssiDbm.value_or(INT32_MIN);
+}
+
+} // namespace
+
+// Implementazione di CoverageSurvey
+CoverageSurvey::CoverageSurvey(int32_t minRssiDbm, uint32_t maxLossPercent)
+    : minRssiDbm(minRssiDbm),
+      maxLossPercent(std::min<uint32_t>(maxLossPercent, 100)),
+      lastProbeId(0) {
+}
+
+void CoverageSurvey::setPosition(const std::string& label) {
+    if (label.empty()) {
+        throw std::invalid_argument("Etichetta della posizione vuota");
+    }
+    if (samples.count(label) == 0) {
+        order.push_back(label);
+        samples[label];
+    }
+    position = label;
+}
+
+const std::string& CoverageSurvey::getPosition() const {
+    return position;
+}
+
+std::optional<uint32_t> CoverageSurvey::recordProbe(const std::string& peer) {
+    if (position.empty()) {
+        return std::nullopt;
+    }
+    samples[position][peer].probesSent++;
+    pending[++lastProbeId] = {position, peer};
+    if (pending.size() > MAX_PENDING_PROBES) {
+        pending.erase(pending.begin());
+    }
+    return lastProbeId;
+}
+
+bool CoverageSurvey::recordReply(const std::string& peer, uint32_t probeId, std::optional<int32_t> rssiDbm) {
+    auto it = pending.find(probeId);
+    if (it == pending.end() || it->second.peer != peer) {
+        return false;
+    }
+    LinkSamples& link = samples[it->second.position][peer];
+    link.replies++;
+    if (rssiDbm) {
+        link.rssiDbm.push_back(*rssiDbm);
+    }
+    pending.erase(it);
+    return true;
+}
+
+CoverageReport CoverageSurvey::report() const {
+    CoverageReport report;
+    for (const auto& label : order) {
+        SurveyPosition entry;
+        entry.label = label;
+        std::optional<SurveyLink> best;
+        for (const auto& [peer, raw] : samples.at(label)) {
+            SurveyLink link;
+            link.peer = peer;
+            link.probesSent = raw.probesSent;
+            link.replies = raw.replies;
+            link.lossRate = raw.probesSent == 0 ? 1.0
+                          : 1.0 - static_cast<double>(raw.replies) / raw.probesSent;
+            if (!raw.rssiDbm.empty()) {
+                int64_t total = std::accumulate(raw.rssiDbm.begin(), raw.rssiDbm.end(), int64_t{0});
+                link.meanRssiDbm = static_cast<int32_t>(total / static_cast<int64_t>(raw.rssiDbm.size()));
+                link.minRssiDbm = *std::min_element(raw.rssiDbm.begin(), raw.rssiDbm.end());
+            }
+
+            bool usable = link.replies > 0 && link.lossRate * 100.0 <= maxLossPercent
+                       && (!link.meanRssiDbm || *link.meanRssiDbm >= minRssiDbm);
+            entry.covered = entry.covered || usable;
+            if (link.replies > 0 && (!best || betterLink(link, *best))) {
+                best = link;
+            }
+            entry.links.push_back(link);
+        }
+        if (best) {
+            entry.bestPeer = best->peer;
+        }
+        if (!entry.covered) {
+            report.uncoveredPositions.push_back(label);
+        }
+        report.positions.push_back(entry);
+    }
+    return report;
+}
+
+} // namespace saber
@@ -1,50 +1,119 @@
 #include "sync.h"
+#include "log.h"
 
 #include <algorithm>
 #include <chrono>
 #include <iostream>
 #include <numeric>
+#include <stdexcept>
 
 namespace saber {
 
+namespace {
+
+int64_t systemTimeUs() {
+    auto duration = std::chrono::system_clock::now().time_since_epoch();
+    return std::chrono::duration_cast<std::chrono::microseconds>(duration).count();
+}
+
+} // namespace
+
 // Implementazione di SyncManager
-SyncManager::SyncManager()
-    : timeOffset(std::make_shared<int64_t>(0)),
+SyncManager::SyncManager(const spec::Parameters& params)
+    : clock(static_cast<int64_t>(params.clockStepThresholdMs) * 1000, params.clockMaxSlewPpm),
       lastBeacon(std::make_shared<std::optional<std::chrono::steady_clock::time_point>>(std::nullopt)),
       nodeLatencies(std::make_shared<std::map<std::string, uint32_t>>()),
       isSynced(std::make_shared<bool>(false)),
-      maxJitterMs(5) { // Come da PAPER.md sezione 4.2, la tolleranza jitter è < ±5 ms
+      params(params) {
+    params.validate();
+}
+
+const spec::Parameters& SyncManager::getParameters() const {
+    return params;
 }
 
 uint64_t SyncManager::now() const {
-    auto systemTime = std::chrono::system_clock::now();
-    auto duration = systemTime.time_since_epoch();
-    uint64_t currentTime = std::chrono::duration_cast<std::chrono::milliseconds>(duration).count();
+    return nowUs() / 1000;
+}
+
+uint64_t SyncManager::nowUs() const {
+    int64_t currentTime = systemTimeUs();
     
-    // Applico l'offset di sincronizzazione
+    // Applico l'offset di sincronizzazione, corretto gradualmente dalla disciplina
     std::lock_guard<std::mutex> lock(syncMutex);
-    int64_t offset = *timeOffset;
+    return static_cast<uint64_t>(currentTime + clock.offsetAt(currentTime));
+}
+
+uint64_t SyncManager::localUs() const {
+    return static_cast<uint64_t>(systemTimeUs());
+}
+
+bool SyncManager::handleTimeBeacon(uint64_t masterTime, uint32_t relayDelayUs) {
+    SABER_LOG_SPAN("sync", "beacon", "master_time=" << masterTime << " relay_delay_us=" << relayDelayUs);
+    int64_t currentTime = systemTimeUs();
     
-    if (offset >= 0) {
-        return currentTime + static_cast<uint64_t>(offset);
-    } else {
-        return currentTime - static_cast<uint64_t>(-offset);
+    // Il beacon è partito dal master un transito fa: se è stato misurato lo compenso
+    int64_t transitUs = 0;
+    {
+        std::lock_guard<std::mutex> lock(syncMutex);
+        if (lastExchange) {
+            transitUs = static_cast<int64_t>(lastExchange->roundTripUs / 2);
+        }
     }
+    int64_t measuredOffset = static_cast<int64_t>(masterTime) * 1000 + transitUs + relayDelayUs - currentTime;
+    return synchronize(currentTime, measuredOffset, false);
 }
 
-bool SyncManager::handleTimeBeacon(uint64_t masterTime) {
-    auto systemTime = std::chrono::system_clock::now();
-    auto duration = systemTime.time_since_epoch();
-    uint64_t currentTime = std::chrono::duration_cast<std::chrono::milliseconds>(duration).count();
+bool SyncManager::handleTimeExchange(const std::string& masterId, uint64_t sentUs, uint64_t masterReceivedUs,
+                                     uint64_t masterRepliedUs, uint64_t receivedUs) {
+    SABER_LOG_SPAN("sync", "exchange", "master=" << masterId);
+    // Un tempo di andata e ritorno negativo indica istanti incoerenti (es. risposta duplicata)
+    if (receivedUs < sentUs || masterRepliedUs < masterReceivedUs 
+        || receivedUs - sentUs < masterRepliedUs - masterReceivedUs) {
+        SABER_LOG(Warn, "sync", "Scambio temporale incoerente con " << masterId << ", scartato");
+        return false;
+    }
     
-    // Calcolo l'offset necessario per sincronizzarsi col master
-    int64_t calculatedOffset = static_cast<int64_t>(masterTime) - static_cast<int64_t>(currentTime);
+    TimeExchange exchange;
+    int64_t outbound = static_cast<int64_t>(masterReceivedUs) - static_cast<int64_t>(sentUs);
+    int64_t inbound = static_cast<int64_t>(masterRepliedUs) - static_cast<int64_t>(receivedUs);
+    exchange.offsetUs = (outbound + inbound) / 2;
+    exchange.roundTripUs = (receivedUs - sentUs) - (masterRepliedUs - masterReceivedUs);
+    {
+        std::lock_guard<std::mutex> lock(syncMutex);
+        lastExchange = exchange;
+        (*nodeLatencies)[masterId] = static_cast<uint32_t>(exchange.roundTripUs / 2000);
+    }
     
+    SABER_LOG(Trace, "sync", "Scambio temporale con " << masterId << ", offset " << exchange.offsetUs 
+              << "us, andata e ritorno " << exchange.roundTripUs << "us");
+    return synchronize(systemTimeUs(), exchange.offsetUs, false);
+}
+
+std::optional<TimeExchange> SyncManager::getLastExchange() const {
+    std::lock_guard<std::mutex> lock(syncMutex);
+    return lastExchange;
+}
+
+bool SyncManager::synchronize(int64_t currentTime, int64_t measuredOffset, bool forceStep) {
+    bool regained;
+    bool stepped;
+    ClockStats stats;
+    SyncStateHandler handler;
     {
         std::lock_guard<std::mutex> lock(syncMutex);
+        regained = !*isSynced;
+        handler = syncStateHandler;
         
-        // Aggiorno l'offset
-        *timeOffset = calculatedOffset;
+        // Aggiorno la stima dell'orologio
+        bool firstBeacon = !clock.isLocked();
+        if (forceStep) {
+            clock.step(currentTime, measuredOffset);
+            stepped = true;
+        } else {
+            stepped = clock.update(currentTime, measuredOffset) && !firstBeacon;
+        }
+        stats = clock.stats(currentTime);
         
         // Aggiorno il timestamp dell'ultimo beacon
         *lastBeacon = std::chrono::steady_clock::now();
@@ -53,9 +122,25 @@ bool SyncManager::handleTimeBeacon(uint64_t masterTime) {
         *isSynced = true;
     }
     
+    if (stepped) {
+        SABER_LOG(Info, "sync", "Orologio riallineato di colpo al master, offset " 
+                  << stats.offsetUs / 1000 << "ms");
+    }
+    SABER_LOG(Trace, "sync", "Sincronizzazione col master, offset misurato " << measuredOffset / 1000 
+              << "ms, applicato " << stats.offsetUs / 1000 << "ms, deriva " << stats.skewPpm << "ppm");
+    if (regained && handler) {
+        handler(true);
+    }
+    
     return true;
 }
 
+ClockStats SyncManager::getClockStats() const {
+    int64_t currentTime = systemTimeUs();
+    std::lock_guard<std::mutex> lock(syncMutex);
+    return clock.stats(currentTime);
+}
+
 bool SyncManager::isSynchronized() const {
     std::lock_guard<std::mutex> lock(syncMutex);
     
@@ -65,9 +150,44 @@ bool SyncManager::isSynchronized() const {
     // Verifico lo stato del flag di sincronizzazione
     bool synced = *isSynced;
     
+    // Un Master che tace da oltre il timeout non sincronizza più il nodo
+    if (hasBeacon && params.beaconTimeoutMs != 0) {
+        auto silence = std::chrono::duration_cast<std::chrono::milliseconds>(
+            std::chrono::steady_clock::now() - **lastBeacon).count();
+        synced = synced && silence <= params.beaconTimeoutMs;
+    }
+    
     return hasBeacon && synced;
 }
 
+bool SyncManager::checkBeaconTimeout() {
+    SyncStateHandler handler;
+    {
+        std::lock_guard<std::mutex> lock(syncMutex);
+        if (!*isSynced || !lastBeacon->has_value() || params.beaconTimeoutMs == 0) {
+            return false;
+        }
+        auto silence = std::chrono::duration_cast<std::chrono::milliseconds>(
+            std::chrono::steady_clock::now() - **lastBeacon).count();
+        if (silence <= params.beaconTimeoutMs) {
+            return false;
+        }
+        *isSynced = false;
+        handler = syncStateHandler;
+    }
+    
+    SABER_LOG(Warn, "sync", "Nessun beacon dal master da oltre " << params.beaconTimeoutMs << "ms");
+    if (handler) {
+        handler(false);
+    }
+    return true;
+}
+
+void SyncManager::setSyncStateHandler(SyncStateHandler handler) {
+    std::lock_guard<std::mutex> lock(syncMutex);
+    syncStateHandler = std::move(handler);
+}
+
 void SyncManager::updateNodeLatency(const std::string& nodeId, uint32_t latency) {
     std::lock_guard<std::mutex> lock(syncMutex);
     (*nodeLatencies)[nodeId] = latency;
@@ -95,14 +215,14 @@ bool SyncManager::isNodeOutOfSync(const std::string& nodeId, uint64_t reportedTi
                         (reportedTime - currentTime);
     
     // Se la differenza è maggiore del jitter massimo, il nodo è desincronizzato
-    return timeDiff > maxJitterMs;
+    return timeDiff > params.jitterToleranceMs;
 }
 
 uint32_t SyncManager::calculateBufferAdjustment(uint32_t nodeLatency) const {
     // Imposta un buffer leggermente superiore alla latenza per evitare interruzioni
-    // Mantenendo comunque sotto la soglia dei 40ms (sezione 4.1 del PAPER.md)
-    uint32_t bufferSize = nodeLatency + 10;
-    return std::min(bufferSize, 40u); // Limito al massimo a 40ms come da specifiche
+    // Mantenendo comunque sotto il budget di latenza
+    uint32_t bufferSize = std::max(nodeLatency + params.bufferMarginMs, params.minBufferMs);
+    return std::min(bufferSize, params.latencyBudgetMs);
 }
 
 uint32_t SyncManager::getOptimalBufferSize() const {
@@ -110,13 +230,21 @@ uint32_t SyncManager::getOptimalBufferSize() const {
     if (avgLatency) {
         return calculateBufferAdjustment(static_cast<uint32_t>(*avgLatency));
     } else {
-        return 20; // Valore di default in assenza di misurazioni
+        return params.defaultBufferMs;
     }
 }
 
 bool SyncManager::emergencySync(uint64_t masterTime) {
     // In caso di emergenza, forza la sincronizzazione
-    bool result = handleTimeBeacon(masterTime);
+    int64_t currentTime = systemTimeUs();
+    int64_t transitUs = 0;
+    {
+        std::lock_guard<std::mutex> lock(syncMutex);
+        if (lastExchange) {
+            transitUs = static_cast<int64_t>(lastExchange->roundTripUs / 2);
+        }
+    }
+    bool result = synchronize(currentTime, static_cast<int64_t>(masterTime) * 1000 + transitUs - currentTime, true);
     
     // Reset delle latenze
     std::lock_guard<std::mutex> lock(syncMutex);
@@ -125,45 +253,111 @@ bool SyncManager::emergencySync(uint64_t masterTime) {
     return result;
 }
 
+std::string audioProfileToString(AudioProfile profile) {
+    return profile == AudioProfile::Music ? "music" : "voice";
+}
+
+std::optional<AudioProfile> audioProfileFromString(const std::string& name) {
+    if (name == "music") {
+        return AudioProfile::Music;
+    }
+    if (name == "voice") {
+        return AudioProfile::Voice;
+    }
+    return std::nullopt;
+}
+
 // Implementazione di AudioSync
-AudioSync::AudioSync(std::shared_ptr<SyncManager> syncManager, bool isMusic)
+AudioSync::AudioSync(std::shared_ptr<SyncManager> syncManager, bool isMusic, uint8_t channels)
     : syncManager(syncManager),
-      jitterBuffer(20), // Valore iniziale di default
+      jitterBuffer(JitterBufferConfig(), syncManager->getParameters().defaultBufferMs),
       isPlaying(false),
-      sampleRate(isMusic ? 48000 : 16000),
-      bitrate(isMusic ? 128 : 64) {
+      sampleRate(isMusic ? spec::SAMPLE_RATE_MUSIC_HZ : spec::SAMPLE_RATE_VOICE_HZ),
+      bitrate(isMusic ? spec::BITRATE_MUSIC_KBPS : spec::BITRATE_VOICE_KBPS),
+      channels(channels) {
+    setJitterBufferConfig(JitterBufferConfig());
 }
 
 bool AudioSync::startPlayback() {
     if (!syncManager->isSynchronized()) {
-        std::cerr << "Impossibile avviare la riproduzione: dispositivo non sincronizzato" << std::endl;
+        SABER_LOG(Error, "sync", "Impossibile avviare la riproduzione: dispositivo non sincronizzato");
         return false;
     }
     
-    // Aggiorno il buffer di jitter in base alle latenze attuali
-    jitterBuffer = syncManager->getOptimalBufferSize();
+    // Aggiorno il buffer di jitter in base alle latenze attuali, salvo un buffer comune imposto
+    jitterBuffer.reset(bufferOverride.value_or(syncManager->getOptimalBufferSize()), bufferOverride.has_value());
     
     isPlaying = true;
-    std::cout << "Avvio riproduzione con buffer di " << jitterBuffer << "ms" << std::endl;
+    paused = false;
+    SABER_LOG(Info, "sync", "Avvio riproduzione con buffer di " << jitterBuffer.getBufferMs() << "ms");
     
     return true;
 }
 
 void AudioSync::stopPlayback() {
     isPlaying = false;
+    pausedForSync = false;
+    paused = false;
+}
+
+void AudioSync::setBufferOverride(std::optional<uint32_t> bufferMs) {
+    const spec::Parameters& params = syncManager->getParameters();
+    if (bufferMs && params.latencyMode == LatencyMode::Strict && *bufferMs > params.latencyBudgetMs) {
+        SABER_LOG(Warn, "sync", "Buffer di " << *bufferMs << "ms limitato al budget di latenza di " 
+                  << params.latencyBudgetMs << "ms");
+        bufferMs = params.latencyBudgetMs;
+    }
+    bufferOverride = bufferMs;
+}
+
+void AudioSync::setJitterBufferConfig(const JitterBufferConfig& config) {
+    const spec::Parameters& params = syncManager->getParameters();
+    JitterBufferConfig applied = config;
+    if (params.latencyMode == LatencyMode::Strict && applied.maxMs > params.latencyBudgetMs) {
+        applied.maxMs = params.latencyBudgetMs;
+    }
+    jitterBuffer.setConfig(applied);
+}
+
+std::optional<uint32_t> AudioSync::recordFrame(StreamId streamId, uint32_t sequence, uint64_t playoutTimeUs, 
+                                               uint64_t arrivalUs) {
+    if (!isPlaying) {
+        return std::nullopt;
+    }
+    return jitterBuffer.recordFrame(streamId, sequence, playoutTimeUs, arrivalUs);
+}
+
+uint32_t AudioSync::getJitterBuffer() const {
+    return jitterBuffer.getBufferMs();
+}
+
+JitterBufferStats AudioSync::getJitterBufferStats() const {
+    return jitterBuffer.getStats();
 }
 
 void AudioSync::adjustBitrate(float networkQuality) {
-    // networkQuality è un valore da 0.0 a 1.0
-    if (networkQuality < 0.5) {
-        // Riduco il bitrate in caso di rete debole
-        bitrate = (sampleRate == 48000) ? 64 : 32;
-    } else {
-        // Ripristino il bitrate normale
-        bitrate = (sampleRate == 48000) ? 128 : 64;
+    // networkQuality è un valore da 0.0 a 1.0: sotto 0.5 riduco il bitrate di ogni stream
+    reducedBitrate = networkQuality < 0.5;
+    bitrate = bitrateFor(sampleRate);
+    
+    for (auto& entry : streamCodecs) {
+        if (entry.second.encoder && entry.second.format.bitrateKbps == 0) {
+            entry.second.encoder->setBitrate(bitrateFor(entry.second.format));
+        }
     }
     
-    std::cout << "Bitrate aggiustato a " << bitrate << "kbps" << std::endl;
+    SABER_LOG(Info, "sync", "Bitrate aggiustato a " << bitrate << "kbps");
+}
+
+uint32_t AudioSync::bitrateFor(uint32_t sampleRateHz) const {
+    if (sampleRateHz == spec::SAMPLE_RATE_MUSIC_HZ) {
+        return reducedBitrate ? spec::BITRATE_VOICE_KBPS : spec::BITRATE_MUSIC_KBPS;
+    }
+    return reducedBitrate ? spec::BITRATE_VOICE_REDUCED_KBPS : spec::BITRATE_VOICE_KBPS;
+}
+
+uint32_t AudioSync::bitrateFor(const StreamFormat& format) const {
+    return format.bitrateKbps != 0 ? format.bitrateKbps : bitrateFor(format.sampleRateHz);
 }
 
 uint32_t AudioSync::getCurrentLatency() const {
@@ -175,4 +369,135 @@ bool AudioSync::isPlaybackSynchronized() const {
     return syncManager->isSynchronized() && isPlaying;
 }
 
+void AudioSync::handleSyncState(bool synchronized) {
+    if (!synchronized && isPlaying) {
+        isPlaying = false;
+        pausedForSync = true;
+        SABER_LOG(Warn, "sync", "Riproduzione sospesa: sincronizzazione persa");
+    } else if (synchronized && pausedForSync) {
+        pausedForSync = false;
+        startPlayback();
+    }
+}
+
+bool AudioSync::isPausedForSync() const {
+    return pausedForSync;
+}
+
+void AudioSync::pausePlayback() {
+    paused = true;
+}
+
+void AudioSync::resumePlayback() {
+    paused = false;
+}
+
+bool AudioSync::isPaused() const {
+    return paused;
+}
+
+void AudioSync::setVolume(uint8_t volume) {
+    if (volume > 100) {
+        throw std::invalid_argument("Volume oltre il 100%");
+    }
+    volumePercent = volume;
+}
+
+uint8_t AudioSync::getVolume() const {
+    return volumePercent;
+}
+
+void AudioSync::setStreamFormat(StreamId streamId, bool isMusic, uint8_t channels) {
+    StreamFormat format;
+    format.sampleRateHz = isMusic ? spec::SAMPLE_RATE_MUSIC_HZ : spec::SAMPLE_RATE_VOICE_HZ;
+    format.channels = channels;
+    // Il bitrate fisso sopravvive ad un cambio di formato
+    auto it = streamFormats.find(streamId);
+    if (it != streamFormats.end()) {
+        format.bitrateKbps = it->second.bitrateKbps;
+    }
+    streamFormats[streamId] = format;
+    streamCodecs.erase(streamId);
+}
+
+void AudioSync::setStreamBitrate(StreamId streamId, uint32_t bitrateKbps) {
+    auto it = streamFormats.find(streamId);
+    if (it == streamFormats.end()) {
+        it = streamFormats.emplace(streamId, getStreamFormat(streamId)).first;
+    }
+    it->second.bitrateKbps = bitrateKbps;
+    auto codec = streamCodecs.find(streamId);
+    if (codec != streamCodecs.end()) {
+        codec->second.format.bitrateKbps = bitrateKbps;
+        if (codec->second.encoder) {
+            codec->second.encoder->setBitrate(bitrateFor(codec->second.format));
+        }
+    }
+}
+
+void AudioSync::setMusicMode(bool isMusic) {
+    sampleRate = isMusic ? spec::SAMPLE_RATE_MUSIC_HZ : spec::SAMPLE_RATE_VOICE_HZ;
+    bitrate = bitrateFor(sampleRate);
+    for (auto it = streamCodecs.begin(); it != streamCodecs.end();) {
+        if (streamFormats.count(it->first) == 0) {
+            it = streamCodecs.erase(it);
+        } else {
+            ++it;
+        }
+    }
+}
+
+StreamFormat AudioSync::getStreamFormat(StreamId streamId) const {
+    auto it = streamFormats.find(streamId);
+    if (it != streamFormats.end()) {
+        return it->second;
+    }
+    return StreamFormat{sampleRate, channels};
+}
+
+AudioSync::StreamCodec& AudioSync::codecFor(StreamId streamId) {
+    auto it = streamCodecs.find(streamId);
+    if (it == streamCodecs.end()) {
+        it = streamCodecs.emplace(streamId, StreamCodec{getStreamFormat(streamId), nullptr, nullptr}).first;
+    }
+    return it->second;
+}
+
+std::vector<uint8_t> AudioSync::encodeFrame(const AudioFrame& frame, StreamId streamId) {
+    auto& codec = codecFor(streamId);
+    if (!codec.encoder) {
+        codec.encoder = std::make_unique<Lc3Encoder>(codec.format.sampleRateHz, codec.format.channels, 
+                                                     bitrateFor(codec.format));
+    }
+    return codec.encoder->encode(frame);
+}
+
+AudioFrame AudioSync::decodeFrame(const std::vector<uint8_t>& payload, uint64_t playoutTimeUs, StreamId streamId) {
+    auto& codec = codecFor(streamId);
+    if (!codec.decoder) {
+        codec.decoder = std::make_unique<Lc3Decoder>(codec.format.sampleRateHz, codec.format.channels);
+    }
+    AudioFrame frame = codec.decoder->decode(payload, playoutTimeUs);
+    applyVolume(frame, volumePercent);
+    return frame;
+}
+
+AudioFrame AudioSync::concealFrame(uint64_t playoutTimeUs, StreamId streamId) {
+    auto& codec = codecFor(streamId);
+    if (!codec.decoder) {
+        codec.decoder = std::make_unique<Lc3Decoder>(codec.format.sampleRateHz, codec.format.channels);
+    }
+    AudioFrame frame = codec.decoder->conceal(playoutTimeUs);
+    applyVolume(frame, volumePercent);
+    return frame;
+}
+
+uint32_t AudioSync::getSampleRate() const {
+    return sampleRate;
+}
+
+uint32_t AudioSync::getBitrate() const {
+    return bitrate;
+}
+
 } // namespace saber
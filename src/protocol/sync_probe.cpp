@@ -0,0 +1,110 @@
+#include "sync_probe.h"
+
+#include <algorithm>
+#include <cstdlib>
+
+namespace saber {
+
+std::string asymmetryModeToString(AsymmetryMode mode) {
+    return mode == AsymmetryMode::MinimumDelay ? "min_delay" : "symmetric";
+}
+
+std::optional<AsymmetryMode> asymmetryModeFromString(const std::string& value) {
+    if (value == "symmetric") {
+        return AsymmetryMode::Symmetric;
+    }
+    if (value == "min_delay") {
+        return AsymmetryMode::MinimumDelay;
+    }
+    return std::nullopt;
+}
+
+// Implementazione di SyncProbe
+SyncProbe::SyncProbe(const std::string& peer, size_t sampleCount, uint32_t toleranceMs, AsymmetryMode mode)
+    : peer(peer),
+      sampleCount(sampleCount == 0 ? 1 : sampleCount),
+      toleranceUs(static_cast<int64_t>(toleranceMs) * 1000),
+      mode(mode),
+      probesSent(0) {
+}
+
+int64_t SyncProbe::skewUs(uint64_t sentUs, uint64_t peerReceivedUs, uint64_t peerRepliedUs, uint64_t receivedUs) {
+    // Con transito simmetrico i due ritardi si compensano
+    int64_t outbound = static_cast<int64_t>(peerReceivedUs) - static_cast<int64_t>(sentUs);
+    int64_t inbound = static_cast<int64_t>(peerRepliedUs) - static_cast<int64_t>(receivedUs);
+    return (outbound + inbound) / 2;
+}
+
+uint64_t SyncProbe::roundTripUs(uint64_t sentUs, uint64_t peerReceivedUs, uint64_t peerRepliedUs,
+                                uint64_t receivedUs) {
+    int64_t total = static_cast<int64_t>(receivedUs) - static_cast<int64_t>(sentUs);
+    int64_t held = static_cast<int64_t>(peerRepliedUs) - static_cast<int64_t>(peerReceivedUs);
+    return static_cast<uint64_t>(std::max<int64_t>(total - held, 0));
+}
+
+int64_t SyncProbe::addSample(uint64_t sentUs, uint64_t peerReceivedUs, uint64_t peerRepliedUs,
+                             uint64_t receivedUs) {
+    int64_t skew = skewUs(sentUs, peerReceivedUs, peerRepliedUs, receivedUs);
+    skews.push_back(skew);
+    roundTrips.push_back(roundTripUs(sentUs, peerReceivedUs, peerRepliedUs, receivedUs));
+    outbounds.push_back(static_cast<int64_t>(peerReceivedUs) - static_cast<int64_t>(sentUs));
+    inbounds.push_back(static_cast<int64_t>(receivedUs) - static_cast<int64_t>(peerRepliedUs));
+    return skew;
+}
+
+uint32_t SyncProbe::nextProbeId() {
+    return probesSent++;
+}
+
+uint32_t SyncProbe::getProbesSent() const {
+    return probesSent;
+}
+
+bool SyncProbe::isComplete() const {
+    return skews.size() >= sampleCount;
+}
+
+const std::string& SyncProbe::getPeer() const {
+    return peer;
+}
+
+SyncProbeResult SyncProbe::result(uint64_t measuredAtMs) const {
+    SyncProbeResult result;
+    result.peer = peer;
+    result.samples = skews.size();
+    result.measuredAtMs = measuredAtMs;
+    if (skews.empty()) {
+        return result;
+    }
+
+    int64_t skewSum = 0;
+    uint64_t roundTripSum = 0;
+    int64_t outboundSum = 0;
+    int64_t inboundSum = 0;
+    for (size_t i = 0; i < skews.size(); ++i) {
+        skewSum += skews[i];
+        roundTripSum += roundTrips[i];
+        outboundSum += outbounds[i];
+        inboundSum += inbounds[i];
+    }
+    int64_t count = static_cast<int64_t>(skews.size());
+
+    // Gli scambi più rapidi di ogni verso hanno transiti quasi uguali
+    int64_t minOutbound = *std::min_element(outbounds.begin(), outbounds.end());
+    int64_t minInbound = *std::min_element(inbounds.begin(), inbounds.end());
+    int64_t baseSkew = (minOutbound - minInbound) / 2;
+    result.asymmetryUs = (outboundSum - inboundSum) / count - 2 * baseSkew;
+    result.mode = mode;
+
+    // La correzione sposta tutti gli scambi: la media coincide con lo sfasamento dei ritardi minimi
+    int64_t correction = mode == AsymmetryMode::MinimumDelay ? result.asymmetryUs / 2 : 0;
+    auto range = std::minmax_element(skews.begin(), skews.end());
+    result.meanSkewUs = skewSum / count - correction;
+    result.minSkewUs = *range.first - correction;
+    result.maxSkewUs = *range.second - correction;
+    result.meanRoundTripUs = roundTripSum / skews.size();
+    result.withinTolerance = std::llabs(result.minSkewUs) <= toleranceUs && std::llabs(result.maxSkewUs) <= toleranceUs;
+    return result;
+}
+
+} // namespace saber
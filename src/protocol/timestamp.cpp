@@ -0,0 +1,106 @@
+#include "timestamp.h"
+
+namespace saber {
+
+uint8_t timestampBits(TimestampWidth width) {
+    return width == TimestampWidth::Full ? 64 : static_cast<uint8_t>(width);
+}
+
+std::optional<TimestampWidth> timestampWidthFromBits(uint32_t bits) {
+    switch (bits) {
+        case 16: return TimestampWidth::Bits16;
+        case 24: return TimestampWidth::Bits24;
+        case 64: return TimestampWidth::Full;
+        default: return std::nullopt;
+    }
+}
+
+uint32_t compressTimestamp(uint64_t timestampUs, TimestampWidth width) {
+    // Il timestamp completo viaggia per intero: nessuna maschera, che richiederebbe uno shift di 64 bit
+    if (width == TimestampWidth::Full) {
+        return static_cast<uint32_t>(timestampUs);
+    }
+    uint64_t mask = (uint64_t(1) << timestampBits(width)) - 1;
+    return static_cast<uint32_t>(timestampUs & mask);
+}
+
+uint64_t expandTimestamp(uint32_t compact, TimestampWidth width, uint64_t referenceUs) {
+    if (width == TimestampWidth::Full) {
+        return compact;
+    }
+    
+    uint64_t period = uint64_t(1) << timestampBits(width);
+    uint64_t mask = period - 1;
+    
+    // Distanza in avanti dal riferimento, modulo il periodo
+    uint64_t forward = (static_cast<uint64_t>(compact) - referenceUs) & mask;
+    if (forward < period / 2) {
+        return referenceUs + forward;
+    }
+    
+    // Più vicino all'indietro: frame in ritardo rispetto al riferimento
+    uint64_t backward = period - forward;
+    return backward <= referenceUs ? referenceUs - backward : referenceUs + forward;
+}
+
+// Implementazione di TimestampEncoder
+TimestampEncoder::TimestampEncoder(TimestampWidth width, uint32_t anchorInterval)
+    : width(width), anchorInterval(anchorInterval == 0 ? 1 : anchorInterval), sinceAnchor(0) {
+}
+
+TimestampWidth TimestampEncoder::next(uint64_t timestampUs) {
+    bool anchor = width == TimestampWidth::Full || !previousUs || sinceAnchor + 1 >= anchorInterval;
+    
+    // Un salto di mezzo periodo o più non sarebbe ricostruibile dal ricevitore
+    if (!anchor) {
+        uint64_t halfPeriod = uint64_t(1) << (timestampBits(width) - 1);
+        uint64_t delta = timestampUs > *previousUs ? timestampUs - *previousUs : *previousUs - timestampUs;
+        anchor = delta >= halfPeriod;
+    }
+    
+    previousUs = timestampUs;
+    if (anchor) {
+        sinceAnchor = 0;
+        return TimestampWidth::Full;
+    }
+    sinceAnchor++;
+    return width;
+}
+
+void TimestampEncoder::reset() {
+    previousUs.reset();
+    sinceAnchor = 0;
+}
+
+// Implementazione di TimestampDecoder
+std::optional<uint64_t> TimestampDecoder::decode(TimestampWidth width, uint64_t value,
+                                                 std::optional<int64_t> arrivalUs) {
+    if (width == TimestampWidth::Full) {
+        referenceUs = value;
+        referenceArrivalUs = arrivalUs;
+        return value;
+    }
+    if (!referenceUs) {
+        return std::nullopt;
+    }
+    
+    // Il tempo trascorso dall'arrivo del riferimento copre i frame persi nel frattempo
+    uint64_t expectedUs = *referenceUs;
+    if (arrivalUs && referenceArrivalUs && *arrivalUs > *referenceArrivalUs) {
+        expectedUs += static_cast<uint64_t>(*arrivalUs - *referenceArrivalUs);
+    }
+    
+    uint64_t decoded = expandTimestamp(static_cast<uint32_t>(value), width, expectedUs);
+    // Il riferimento segue solo i frame in avanti, per non arretrare sui ritardatari
+    if (decoded > *referenceUs) {
+        referenceUs = decoded;
+        referenceArrivalUs = arrivalUs;
+    }
+    return decoded;
+}
+
+bool TimestampDecoder::isAnchored() const {
+    return referenceUs.has_value();
+}
+
+} // namespace saber
@@ -0,0 +1,131 @@
+#include "timing.h"
+
+#include <algorithm>
+
+namespace saber {
+
+namespace {
+
+const char* const STAGE_NAMES[PIPELINE_STAGE_COUNT] = {
+    "encode", "encrypt", "send", "receive", "decrypt", "decode", "resample", "render",
+};
+
+// Percentile per rango su campioni già ordinati
+uint64_t percentile(const std::vector<uint64_t>& sorted, unsigned percent) {
+    size_t rank = (sorted.size() * percent + 99) / 100;
+    return sorted[std::max<size_t>(rank, 1) - 1];
+}
+
+} // namespace
+
+std::string pipelineStageToString(PipelineStage stage) {
+    size_t index = static_cast<size_t>(stage);
+    return index < PIPELINE_STAGE_COUNT ? STAGE_NAMES[index] : "unknown";
+}
+
+std::optional<PipelineStage> pipelineStageFromString(const std::string& value) {
+    for (size_t i = 0; i < PIPELINE_STAGE_COUNT; ++i) {
+        if (value == STAGE_NAMES[i]) {
+            return static_cast<PipelineStage>(i);
+        }
+    }
+    return std::nullopt;
+}
+
+// Implementazione di PipelineProfiler
+PipelineProfiler::PipelineProfiler(size_t windowSamples, const std::string& tracePrefix)
+    : windowSamples(windowSamples == 0 ? 1 : windowSamples), tracePrefix(tracePrefix) {
+}
+
+void PipelineProfiler::record(PipelineStage stage, uint64_t durationUs) {
+    size_t index = static_cast<size_t>(stage);
+    if (index >= PIPELINE_STAGE_COUNT) {
+        return;
+    }
+
+    std::lock_guard<std::mutex> lock(timingMutex);
+    StageWindow& window = windows[index];
+    if (window.samples.size() < windowSamples) {
+        window.samples.push_back(durationUs);
+    } else {
+        window.samples[window.next] = durationUs;
+        window.next = (window.next + 1) % windowSamples;
+    }
+    window.total++;
+
+    if (traceFile.is_open()) {
+        traceFile << tracePrefix << ";" << STAGE_NAMES[index] << " " << durationUs << "\n";
+    }
+}
+
+StageTiming PipelineProfiler::timing(PipelineStage stage) const {
+    StageTiming result;
+    result.stage = stage;
+    size_t index = static_cast<size_t>(stage);
+    if (index >= PIPELINE_STAGE_COUNT) {
+        return result;
+    }
+
+    std::vector<uint64_t> sorted;
+    {
+        std::lock_guard<std::mutex> lock(timingMutex);
+        sorted = windows[index].samples;
+        result.total = windows[index].total;
+    }
+    result.samples = sorted.size();
+    if (sorted.empty()) {
+        return result;
+    }
+
+    std::sort(sorted.begin(), sorted.end());
+    result.p50Us = percentile(sorted, 50);
+    result.p95Us = percentile(sorted, 95);
+    result.p99Us = percentile(sorted, 99);
+    result.maxUs = sorted.back();
+    return result;
+}
+
+std::vector<StageTiming> PipelineProfiler::timings() const {
+    std::vector<StageTiming> result;
+    for (size_t i = 0; i < PIPELINE_STAGE_COUNT; ++i) {
+        StageTiming stageTiming = timing(static_cast<PipelineStage>(i));
+        if (stageTiming.samples > 0) {
+            result.push_back(stageTiming);
+        }
+    }
+    return result;
+}
+
+bool PipelineProfiler::setTraceFile(const std::string& path) {
+    std::lock_guard<std::mutex> lock(timingMutex);
+    if (traceFile.is_open()) {
+        traceFile.close();
+    }
+    if (path.empty()) {
+        return true;
+    }
+    traceFile.open(path, std::ios::app);
+    return traceFile.is_open();
+}
+
+void PipelineProfiler::reset() {
+    std::lock_guard<std::mutex> lock(timingMutex);
+    for (auto& window : windows) {
+        window = StageWindow();
+    }
+}
+
+// Implementazione di StageTimer
+StageTimer::StageTimer(PipelineProfiler* profiler, PipelineStage stage)
+    : profiler(profiler), stage(stage), start(std::chrono::steady_clock::now()) {
+}
+
+StageTimer::~StageTimer() {
+    if (profiler) {
+        auto elapsed = std::chrono::steady_clock::now() - start;
+        profiler->record(stage, static_cast<uint64_t>(
+            std::chrono::duration_cast<std::chrono::microseconds>(elapsed).count()));
+    }
+}
+
+} // namespace saber
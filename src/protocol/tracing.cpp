@@ -0,0 +1,328 @@
+#include "tracing.h"
+#include "log.h"
+#include "socket_compat.h"
+#include "util.h"
+
+#include <algorithm>
+#include <chrono>
+#include <cstring>
+#include <sstream>
+#include <stdexcept>
+
+namespace saber {
+
+namespace {
+
+/// Contesto dello span attivo sul thread
+thread_local std::optional<TraceContext> currentContext;
+
+/// Attesa massima delle risposte del collettore (ms)
+const uint32_t OTLP_TIMEOUT_MS = 2000;
+
+template <size_t N>
+bool isZero(const std::array<uint8_t, N>& bytes) {
+    for (uint8_t byte : bytes) {
+        if (byte != 0) {
+            return false;
+        }
+    }
+    return true;
+}
+
+std::string stringAttribute(const std::string& key, const std::string& value) {
+    return "{\"key\":\"" + jsonEscape(key) + "\",\"value\":{\"stringValue\":\"" + jsonEscape(value) + "\"}}";
+}
+
+uint64_t systemMicros() {
+    return std::chrono::duration_cast<std::chrono::microseconds>(
+        std::chrono::system_clock::now().time_since_epoch()).count();
+}
+
+} // namespace
+
+// Implementazione di TraceContext
+bool TraceContext::isValid() const {
+    return !isZero(traceId) && !isZero(spanId);
+}
+
+std::string TraceContext::traceIdHex() const {
+    return toHex(traceId);
+}
+
+std::string TraceContext::spanIdHex() const {
+    return toHex(spanId);
+}
+
+bool TraceContext::operator==(const TraceContext& other) const {
+    return traceId == other.traceId && spanId == other.spanId;
+}
+
+// Implementazione di Tracer
+Tracer::Tracer(std::shared_ptr<RandomSource> random, Clock clock)
+    : random(random ? std::move(random) : defaultRandomSource()),
+      clock(clock ? std::move(clock) : Clock(systemMicros)) {
+}
+
+void Tracer::setEnabled(bool enabled) {
+    this->enabled = enabled;
+}
+
+bool Tracer::isEnabled() const {
+    return enabled;
+}
+
+TraceSpan Tracer::startSpan(const std::string& name, SpanKind kind, const std::optional<TraceContext>& parent) {
+    TraceSpan span;
+    span.name = name;
+    span.kind = kind;
+    if (!enabled) {
+        return span;
+    }
+
+    span.recording = true;
+    span.startUs = clock();
+    if (parent && parent->isValid()) {
+        span.context.traceId = parent->traceId;
+        span.parentSpanId = parent->spanId;
+    } else {
+        do {
+            random->fill(span.context.traceId.data(), span.context.traceId.size());
+        } while (isZero(span.context.traceId));
+    }
+    do {
+        random->fill(span.context.spanId.data(), span.context.spanId.size());
+    } while (isZero(span.context.spanId));
+    return span;
+}
+
+void Tracer::endSpan(TraceSpan& span) {
+    if (!span.recording || span.endUs != 0) {
+        return;
+    }
+    span.endUs = std::max(clock(), span.startUs);
+
+    std::lock_guard<std::mutex> lock(tracerMutex);
+    finished.push_back(span);
+    if (finished.size() > MAX_PENDING_SPANS) {
+        finished.pop_front();
+        droppedSpans++;
+    }
+}
+
+std::vector<TraceSpan> Tracer::takeFinished() {
+    std::lock_guard<std::mutex> lock(tracerMutex);
+    std::vector<TraceSpan> spans(finished.begin(), finished.end());
+    finished.clear();
+    return spans;
+}
+
+uint64_t Tracer::getDroppedSpans() const {
+    std::lock_guard<std::mutex> lock(tracerMutex);
+    return droppedSpans;
+}
+
+// Implementazione di SpanScope
+SpanScope::SpanScope(const TraceSpan& span)
+    : previous(currentContext) {
+    if (span.recording) {
+        currentContext = span.context;
+    }
+}
+
+SpanScope::~SpanScope() {
+    currentContext = previous;
+}
+
+std::optional<TraceContext> SpanScope::current() {
+    return currentContext;
+}
+
+std::string encodeOtlpJson(const std::vector<TraceSpan>& spans, const std::string& nodeId) {
+    std::ostringstream json;
+    json << "{\"resourceSpans\":[{\"resource\":{\"attributes\":["
+         << stringAttribute("service.name", "saber") << ","
+         << stringAttribute("service.instance.id", nodeId)
+         << "]},\"scopeSpans\":[{\"scope\":{\"name\":\"saber\"},\"spans\":[";
+    bool first = true;
+    for (const auto& span : spans) {
+        json << (first ? "" : ",")
+             << "{\"traceId\":\"" << span.context.traceIdHex() << "\""
+             << ",\"spanId\":\"" << span.context.spanIdHex() << "\"";
+        if (span.parentSpanId) {
+            json << ",\"parentSpanId\":\"" << toHex(*span.parentSpanId) << "\"";
+        }
+        json << ",\"name\":\"" << jsonEscape(span.name) << "\""
+             << ",\"kind\":" << static_cast<int>(span.kind)
+             << ",\"startTimeUnixNano\":\"" << span.startUs * 1000 << "\""
+             << ",\"endTimeUnixNano\":\"" << span.endUs * 1000 << "\""
+             << ",\"attributes\":[";
+        bool firstAttribute = true;
+        for (const auto& attribute : span.attributes) {
+            json << (firstAttribute ? "" : ",") << stringAttribute(attribute.first, attribute.second);
+            firstAttribute = false;
+        }
+        json << "]";
+        if (span.error) {
+            json << ",\"status\":{\"code\":2,\"message\":\"" << jsonEscape(*span.error) << "\"}";
+        }
+        json << "}";
+        first = false;
+    }
+    json << "]}]}]}";
+    return json.str();
+}
+
+// Implementazione di OtlpExporter
+OtlpExporter::OtlpExporter(const std::string& endpoint, const std::string& nodeId, uint32_t intervalMs)
+    : nodeId(nodeId), intervalMs(intervalMs) {
+    const std::string scheme = "http://";
+    if (endpoint.compare(0, scheme.size(), scheme) != 0) {
+        throw std::invalid_argument("endpoint OTLP non supportato (solo http://): " + endpoint);
+    }
+    std::string rest = endpoint.substr(scheme.size());
+    size_t slash = rest.find('/');
+    std::string authority = rest.substr(0, slash);
+    path = slash == std::string::npos ? "/v1/traces" : rest.substr(slash);
+
+    size_t colon = authority.rfind(':');
+    host = authority.substr(0, colon);
+    if (colon != std::string::npos) {
+        try {
+            unsigned long value = std::stoul(authority.substr(colon + 1));
+            if (value == 0 || value > 65535) {
+                throw std::out_of_range("porta");
+            }
+            port = static_cast<uint16_t>(value);
+        } catch (const std::exception&) {
+            throw std::invalid_argument("porta OTLP non valida: " + endpoint);
+        }
+    }
+    if (host.empty()) {
+        throw std::invalid_argument("host OTLP mancante: " + endpoint);
+    }
+}
+
+OtlpExporter::~OtlpExporter() {
+    stop();
+}
+
+void OtlpExporter::start(std::shared_ptr<Tracer> tracer) {
+    if (exportThread.joinable()) {
+        return;
+    }
+    this->tracer = std::move(tracer);
+    stopping = false;
+    exportThread = std::thread(&OtlpExporter::run, this);
+}
+
+void OtlpExporter::stop() {
+    {
+        std::lock_guard<std::mutex> lock(exportMutex);
+        stopping = true;
+    }
+    exportCondition.notify_all();
+    if (exportThread.joinable()) {
+        exportThread.join();
+    }
+}
+
+void OtlpExporter::run() {
+    bool last = false;
+    while (!last) {
+        {
+            std::unique_lock<std::mutex> lock(exportMutex);
+            exportCondition.wait_for(lock, std::chrono::milliseconds(intervalMs), [this] { return stopping; });
+            last = stopping;
+        }
+        auto spans = tracer->takeFinished();
+        if (!spans.empty()) {
+            exportSpans(spans);
+        }
+    }
+}
+
+bool OtlpExporter::exportSpans(const std::vector<TraceSpan>& spans) {
+    std::string body = encodeOtlpJson(spans, nodeId);
+    std::ostringstream request;
+    request << "POST " << path << " HTTP/1.1\r\n"
+            << "Host: " << host << ":" << port << "\r\n"
+            << "Content-Type: application/json\r\n"
+            << "Content-Length: " << body.size() << "\r\n"
+            << "Connection: close\r\n\r\n"
+            << body;
+    std::string data = request.str();
+
+    addrinfo hints;
+    std::memset(&hints, 0, sizeof(hints));
+    hints.ai_family = AF_UNSPEC;
+    hints.ai_socktype = SOCK_STREAM;
+    addrinfo* addresses = nullptr;
+    if (getaddrinfo(host.c_str(), std::to_string(port).c_str(), &hints, &addresses) != 0 || !addresses) {
+        SABER_LOG(Warn, "tracing", "Collettore OTLP " << host << " non risolto");
+        failedExports++;
+        return false;
+    }
+
+    auto sock = socket(addresses->ai_family, addresses->ai_socktype, addresses->ai_protocol);
+    bool connected = false;
+    if (sock >= 0) {
+#ifdef _WIN32
+        DWORD timeout = OTLP_TIMEOUT_MS;
+#else
+        timeval timeout{OTLP_TIMEOUT_MS / 1000, (OTLP_TIMEOUT_MS % 1000) * 1000};
+#endif
+        setsockopt(sock, SOL_SOCKET, SO_RCVTIMEO, reinterpret_cast<const char*>(&timeout), sizeof(timeout));
+        setsockopt(sock, SOL_SOCKET, SO_SNDTIMEO, reinterpret_cast<const char*>(&timeout), sizeof(timeout));
+        connected = connect(sock, addresses->ai_addr, static_cast<socklen_t>(addresses->ai_addrlen)) == 0;
+    }
+    freeaddrinfo(addresses);
+    if (!connected) {
+        if (sock >= 0) {
+            SABER_CLOSE_SOCKET(sock);
+        }
+        SABER_LOG(Warn, "tracing", "Collettore OTLP " << host << ":" << port << " non raggiungibile");
+        failedExports++;
+        return false;
+    }
+
+    size_t sent = 0;
+    while (sent < data.size()) {
+        auto result = send(sock, data.data() + sent, static_cast<int>(data.size() - sent), 0);
+        if (result <= 0) {
+            break;
+        }
+        sent += static_cast<size_t>(result);
+    }
+
+    // Basta la riga di stato: "HTTP/1.1 200 OK"
+    char buffer[64];
+    std::string status;
+    while (sent == data.size() && status.find("\r\n") == std::string::npos) {
+        auto received = recv(sock, buffer, sizeof(buffer), 0);
+        if (received <= 0) {
+            break;
+        }
+        status.append(buffer, static_cast<size_t>(received));
+    }
+    SABER_CLOSE_SOCKET(sock);
+
+    size_t space = status.find(' ');
+    bool accepted = space != std::string::npos && status.size() > space + 1 && status[space + 1] == '2';
+    if (!accepted) {
+        SABER_LOG(Warn, "tracing", "Esportazione OTLP rifiutata: " << status.substr(0, status.find("\r\n")));
+        failedExports++;
+        return false;
+    }
+    exportedSpans += spans.size();
+    return true;
+}
+
+uint64_t OtlpExporter::getExportedSpans() const {
+    return exportedSpans;
+}
+
+uint64_t OtlpExporter::getFailedExports() const {
+    return failedExports;
+}
+
+} // namespace saber
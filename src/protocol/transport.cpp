@@ -0,0 +1,142 @@
+#include "transport.h"
+
+#include <chrono>
+
+namespace saber {
+
+namespace {
+
+// Latenza che annulla il punteggio di un percorso perfetto
+const double LATENCY_PENALTY_MS = 100.0;
+
+uint64_t wallClockMillis() {
+    return std::chrono::duration_cast<std::chrono::milliseconds>(
+        std::chrono::system_clock::now().time_since_epoch()).count();
+}
+
+} // namespace
+
+std::string transportKindToString(TransportKind kind) {
+    return kind == TransportKind::Udp ? "udp" : "ble";
+}
+
+std::optional<TransportKind> transportKindFromString(const std::string& value) {
+    for (TransportKind kind : {TransportKind::Ble, TransportKind::Udp}) {
+        if (transportKindToString(kind) == value) {
+            return kind;
+        }
+    }
+    return std::nullopt;
+}
+
+// Implementazione di TransportSelector
+TransportSelector::TransportSelector(double degradedQuality, double switchMargin, uint32_t staleMs)
+    : degradedQuality(degradedQuality), switchMargin(switchMargin), staleMs(staleMs) {
+}
+
+void TransportSelector::reportPath(const std::string& peer, TransportKind kind, double linkQuality,
+                                   uint32_t latencyMs, int64_t nowMs) {
+    std::lock_guard<std::mutex> lock(selectorMutex);
+    Path& path = peers[peer].paths[kind];
+    path.linkQuality = linkQuality;
+    path.latencyMs = latencyMs;
+    path.up = true;
+    path.updatedMs = nowMs;
+}
+
+void TransportSelector::reportPathDown(const std::string& peer, TransportKind kind) {
+    std::lock_guard<std::mutex> lock(selectorMutex);
+    peers[peer].paths[kind].up = false;
+}
+
+std::optional<TransportKind> TransportSelector::route(const std::string& peer, int64_t nowMs) {
+    std::optional<FailoverEvent> event;
+    FailoverHandler handler;
+    std::optional<TransportKind> chosen;
+    
+    {
+        std::lock_guard<std::mutex> lock(selectorMutex);
+        auto it = peers.find(peer);
+        if (it == peers.end()) {
+            return std::nullopt;
+        }
+        PeerState& state = it->second;
+        
+        // Miglior percorso utilizzabile
+        std::optional<TransportKind> best;
+        for (const auto& entry : state.paths) {
+            if (usable(entry.second, nowMs) && (!best || score(entry.second) > score(state.paths[*best]))) {
+                best = entry.first;
+            }
+        }
+        
+        if (!state.active) {
+            state.active = best;
+        } else if (best && *best != *state.active) {
+            const Path& current = state.paths[*state.active];
+            std::string reason;
+            if (!current.up) {
+                reason = "down";
+            } else if (nowMs - current.updatedMs > static_cast<int64_t>(staleMs)) {
+                reason = "stale";
+            } else if (current.linkQuality < degradedQuality) {
+                reason = "degraded";
+            } else if (score(state.paths[*best]) > score(current) + switchMargin) {
+                reason = "better";
+            }
+            
+            if (!reason.empty()) {
+                event = FailoverEvent{peer, *state.active, *best, reason, wallClockMillis()};
+                state.active = best;
+                stats.failovers[peer]++;
+                handler = failoverHandler;
+            }
+        } else if (!best) {
+            // Nessun percorso utilizzabile: si resta sull'ultimo scelto finché uno non torna
+            if (!state.paths[*state.active].up) {
+                return std::nullopt;
+            }
+        }
+        
+        chosen = state.active;
+        if (chosen) {
+            stats.packetsRouted[*chosen]++;
+            stats.active[peer] = *chosen;
+        }
+    }
+    
+    // Il gestore viene invocato fuori dal lock
+    if (event && handler) {
+        handler(*event);
+    }
+    return chosen;
+}
+
+std::optional<TransportKind> TransportSelector::activeTransport(const std::string& peer) const {
+    std::lock_guard<std::mutex> lock(selectorMutex);
+    auto it = peers.find(peer);
+    if (it == peers.end()) {
+        return std::nullopt;
+    }
+    return it->second.active;
+}
+
+TransportStats TransportSelector::getStats() const {
+    std::lock_guard<std::mutex> lock(selectorMutex);
+    return stats;
+}
+
+void TransportSelector::setFailoverHandler(FailoverHandler handler) {
+    std::lock_guard<std::mutex> lock(selectorMutex);
+    failoverHandler = handler;
+}
+
+double TransportSelector::score(const Path& path) {
+    return path.linkQuality - path.latencyMs / LATENCY_PENALTY_MS;
+}
+
+bool TransportSelector::usable(const Path& path, int64_t nowMs) const {
+    return path.up && nowMs - path.updatedMs <= static_cast<int64_t>(staleMs);
+}
+
+} // namespace saber
@@ -0,0 +1,202 @@
+#include "log.h"
+#include "udp_transport.h"
+#include "socket_compat.h"
+
+#include <cstring>
+#include <iostream>
+#include <stdexcept>
+
+namespace saber {
+
+namespace {
+
+const intptr_t INVALID_SOCKET_HANDLE = -1;
+
+// Attesa massima della ricezione, per osservare il flag di arresto
+const long RECEIVE_POLL_US = 200000;
+
+bool parseAddress(const std::string& text, in_addr& address) {
+    return inet_pton(AF_INET, text.c_str(), &address) == 1;
+}
+
+} // namespace
+
+// Implementazione di UdpTransport
+UdpTransport::UdpTransport(const UdpTransportConfig& config)
+    : config(config), socketHandle(INVALID_SOCKET_HANDLE), running(false) {
+    in_addr group;
+    if (!parseAddress(config.multicastAddress, group) || (ntohl(group.s_addr) >> 28) != 0xE) {
+        throw std::invalid_argument("Gruppo multicast IPv4 non valido: " + config.multicastAddress);
+    }
+    in_addr local;
+    if (!parseAddress(config.interfaceAddress, local)) {
+        throw std::invalid_argument("Indirizzo dell'interfaccia non valido: " + config.interfaceAddress);
+    }
+    if (config.port == 0) {
+        throw std::invalid_argument("Porta UDP non valida: 0");
+    }
+}
+
+UdpTransport::~UdpTransport() {
+    stop();
+}
+
+bool UdpTransport::start(ReceiveHandler handler) {
+    if (running) {
+        return true;
+    }
+
+#ifdef _WIN32
+    WSADATA wsaData;
+    if (WSAStartup(MAKEWORD(2, 2), &wsaData) != 0) {
+        SABER_LOG(Error, "transport", "Impossibile inizializzare Winsock");
+        return false;
+    }
+#endif
+
+    auto sock = socket(AF_INET, SOCK_DGRAM, 0);
+    if (sock < 0) {
+        SABER_LOG(Error, "transport", "Impossibile creare il socket UDP");
+        return false;
+    }
+
+    // Più nodi sulla stessa macchina condividono la porta del gruppo
+    int reuse = 1;
+    setsockopt(sock, SOL_SOCKET, SO_REUSEADDR, reinterpret_cast<const char*>(&reuse), sizeof(reuse));
+#ifdef SO_REUSEPORT
+    setsockopt(sock, SOL_SOCKET, SO_REUSEPORT, reinterpret_cast<const char*>(&reuse), sizeof(reuse));
+#endif
+
+    sockaddr_in addr;
+    std::memset(&addr, 0, sizeof(addr));
+    addr.sin_family = AF_INET;
+    addr.sin_port = htons(config.port);
+    addr.sin_addr.s_addr = htonl(INADDR_ANY);
+    if (bind(sock, reinterpret_cast<sockaddr*>(&addr), sizeof(addr)) != 0) {
+        SABER_LOG(Error, "transport", "Impossibile mettersi in ascolto sulla porta UDP " << config.port);
+        SABER_CLOSE_SOCKET(sock);
+        return false;
+    }
+
+    ip_mreq membership;
+    std::memset(&membership, 0, sizeof(membership));
+    parseAddress(config.multicastAddress, membership.imr_multiaddr);
+    parseAddress(config.interfaceAddress, membership.imr_interface);
+    if (setsockopt(sock, IPPROTO_IP, IP_ADD_MEMBERSHIP, reinterpret_cast<const char*>(&membership),
+                   sizeof(membership)) != 0) {
+        SABER_LOG(Error, "transport", "Impossibile unirsi al gruppo multicast " << config.multicastAddress);
+        SABER_CLOSE_SOCKET(sock);
+        return false;
+    }
+    int ttl = config.multicastTtl;
+    setsockopt(sock, IPPROTO_IP, IP_MULTICAST_TTL, reinterpret_cast<const char*>(&ttl), sizeof(ttl));
+    int loop = 1;
+    setsockopt(sock, IPPROTO_IP, IP_MULTICAST_LOOP, reinterpret_cast<const char*>(&loop), sizeof(loop));
+    if (membership.imr_interface.s_addr != htonl(INADDR_ANY)) {
+        setsockopt(sock, IPPROTO_IP, IP_MULTICAST_IF, reinterpret_cast<const char*>(&membership.imr_interface),
+                   sizeof(membership.imr_interface));
+    }
+
+    socketHandle = static_cast<intptr_t>(sock);
+    receiveHandler = handler;
+    running = true;
+    receiveThread = std::make_unique<std::thread>(&UdpTransport::runReceiveLoop, this);
+    return true;
+}
+
+void UdpTransport::stop() {
+    if (!running.exchange(false)) {
+        return;
+    }
+
+    if (receiveThread && receiveThread->joinable()) {
+        receiveThread->join();
+    }
+
+    ip_mreq membership;
+    std::memset(&membership, 0, sizeof(membership));
+    parseAddress(config.multicastAddress, membership.imr_multiaddr);
+    parseAddress(config.interfaceAddress, membership.imr_interface);
+    setsockopt(socketHandle, IPPROTO_IP, IP_DROP_MEMBERSHIP, reinterpret_cast<const char*>(&membership),
+               sizeof(membership));
+    SABER_CLOSE_SOCKET(socketHandle);
+    socketHandle = INVALID_SOCKET_HANDLE;
+
+#ifdef _WIN32
+    WSACleanup();
+#endif
+}
+
+bool UdpTransport::isRunning() const {
+    return running;
+}
+
+bool UdpTransport::send(const std::vector<uint8_t>& datagram) {
+    if (!running) {
+        return false;
+    }
+    if (datagram.size() > MAX_UDP_DATAGRAM_BYTES) {
+        std::lock_guard<std::mutex> lock(statsMutex);
+        stats.sendErrors++;
+        return false;
+    }
+
+    sockaddr_in group;
+    std::memset(&group, 0, sizeof(group));
+    group.sin_family = AF_INET;
+    group.sin_port = htons(config.port);
+    parseAddress(config.multicastAddress, group.sin_addr);
+    auto sent = sendto(socketHandle, reinterpret_cast<const char*>(datagram.data()),
+                       static_cast<int>(datagram.size()), 0, reinterpret_cast<sockaddr*>(&group), sizeof(group));
+
+    std::lock_guard<std::mutex> lock(statsMutex);
+    if (sent != static_cast<decltype(sent)>(datagram.size())) {
+        stats.sendErrors++;
+        return false;
+    }
+    stats.datagramsSent++;
+    stats.bytesSent += datagram.size();
+    return true;
+}
+
+const UdpTransportConfig& UdpTransport::getConfig() const {
+    return config;
+}
+
+UdpTransportStats UdpTransport::getStats() const {
+    std::lock_guard<std::mutex> lock(statsMutex);
+    return stats;
+}
+
+void UdpTransport::runReceiveLoop() {
+    std::vector<uint8_t> buffer(MAX_UDP_DATAGRAM_BYTES);
+    while (running) {
+        // Attesa con timeout per poter osservare il flag di arresto
+        fd_set readSet;
+        FD_ZERO(&readSet);
+        FD_SET(socketHandle, &readSet);
+        timeval timeout{0, RECEIVE_POLL_US};
+
+        int ready = select(static_cast<int>(socketHandle) + 1, &readSet, nullptr, nullptr, &timeout);
+        if (ready <= 0) {
+            continue;
+        }
+
+        auto received = recvfrom(socketHandle, reinterpret_cast<char*>(buffer.data()),
+                                 static_cast<int>(buffer.size()), 0, nullptr, nullptr);
+        if (received <= 0) {
+            continue;
+        }
+        std::vector<uint8_t> datagram(buffer.begin(), buffer.begin() + received);
+        {
+            std::lock_guard<std::mutex> lock(statsMutex);
+            stats.datagramsReceived++;
+            stats.bytesReceived += datagram.size();
+        }
+        if (receiveHandler) {
+            receiveHandler(datagram);
+        }
+    }
+}
+
+} // namespace saber
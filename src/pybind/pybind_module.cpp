@@ -7,9 +7,137 @@
 #include "mesh.h"
 #include "sync.h"
 #include "saber_protocol.h"
+#include "audio_packet.h"
+#include "bridge.h"
 
 namespace py = pybind11;
 
+namespace {
+
+/**
+ * @brief Esegue un metodo bloccante nell'executor del loop asyncio in corso
+ * @return Future da attendere con await
+ */
+py::object runInExecutor(const py::object& self, const char* method, const py::args& args, 
+                         const py::kwargs& kwargs) {
+    py::object call = py::module_::import("functools").attr("partial")(self.attr(method), *args, **kwargs);
+    py::object loop = py::module_::import("asyncio").attr("get_running_loop")();
+    return loop.attr("run_in_executor")(py::none(), call);
+}
+
+/**
+ * @brief Attende un future del protocollo nell'executor di un loop asyncio
+ * @return Future da attendere con await
+ */
+py::object awaitInExecutor(const py::object& loop, std::future<bool> future) {
+    auto pending = std::make_shared<std::future<bool>>(std::move(future));
+    py::cpp_function wait([pending]() {
+        py::gil_scoped_release release;
+        return pending->get();
+    });
+    return loop.attr("run_in_executor")(py::none(), wait);
+}
+
+/**
+ * @brief Segnala la chiamata sincrona di un metodo bloccante dal thread di un loop asyncio in corso
+ */
+void warnIfLoopRunning(const char* method) {
+    try {
+        py::module_::import("asyncio").attr("get_running_loop")();
+    } catch (const py::error_already_set& e) {
+        if (e.matches(PyExc_RuntimeError)) {
+            return;
+        }
+        throw;
+    }
+    std::string message = std::string(method) + "() blocca il loop asyncio in corso, usare " + method + "_async()";
+    if (PyErr_WarnEx(PyExc_RuntimeWarning, message.c_str(), 1) < 0) {
+        throw py::error_already_set();
+    }
+}
+
+/**
+ * @brief Adatta una funzione Python alla politica di autorizzazione
+ *
+ * La funzione riceve la richiesta e respond(allowed, reason=""), da
+ * chiamare subito o più tardi da qualsiasi thread.
+ */
+saber::FunctionAuthorizationHook::Function authorizationHandler(
+        std::function<void(const saber::AuthorizationRequest&, py::object)> handler) {
+    if (!handler) {
+        return nullptr;
+    }
+    return [handler](const saber::AuthorizationRequest& request, saber::AuthorizationHook::Responder respond) {
+        py::gil_scoped_acquire gil;
+        py::cpp_function reply([respond](bool allowed, const std::string& reason) {
+            py::gil_scoped_release release;
+            respond({allowed, reason});
+        }, py::arg("allowed"), py::arg("reason") = "");
+        handler(request, reply);
+    };
+}
+
+/**
+ * @brief Copia un buffer di byte in un oggetto bytes di Python
+ */
+template <typename Buffer>
+py::bytes toBytes(const Buffer& buffer) {
+    return py::bytes(reinterpret_cast<const char*>(buffer.data()), buffer.size());
+}
+
+/**
+ * @brief Copia un oggetto bytes di Python in un buffer di byte
+ */
+std::vector<uint8_t> fromBytes(const py::bytes& bytes) {
+    std::string data = bytes;
+    return std::vector<uint8_t>(data.begin(), data.end());
+}
+
+/**
+ * @brief Livello numerico del modulo logging di Python per un livello di log
+ *
+ * Il livello trace, che Python non prevede, corrisponde a 5 (sotto DEBUG).
+ */
+int pythonLogLevel(saber::LogLevel level) {
+    switch (level) {
+        case saber::LogLevel::Error: return 40;
+        case saber::LogLevel::Warn: return 30;
+        case saber::LogLevel::Info: return 20;
+        case saber::LogLevel::Debug: return 10;
+        case saber::LogLevel::Trace: return 5;
+        case saber::LogLevel::Off: break;
+    }
+    return 0;
+}
+
+/**
+ * @brief Inoltra i log del protocollo al modulo logging di Python
+ *
+ * Ogni target diventa il logger "saber.<target>"; il filtro del protocollo
+ * resta valido e decide quali messaggi arrivano a Python.
+ */
+void forwardLogsToPython(bool enabled) {
+    if (!enabled) {
+        saber::Logger::instance().setSink(nullptr);
+        return;
+    }
+    saber::Logger::instance().setSink([](const std::string& target, saber::LogLevel level,
+                                         const std::string& message) {
+        if (!Py_IsInitialized()) {
+            return;
+        }
+        py::gil_scoped_acquire gil;
+        try {
+            py::module_::import("logging").attr("getLogger")("saber." + target)
+                .attr("log")(pythonLogLevel(level), message);
+        } catch (const py::error_already_set&) {
+            // Un gestore Python che fallisce non deve interrompere il thread del protocollo
+        }
+    });
+}
+
+} // namespace
+
 PYBIND11_MODULE(saber_protocol, m) {
     m.doc() = "SABER Protocol: Sistema di sincronizzazione audio per reti mesh";
     
@@ -26,10 +154,210 @@ PYBIND11_MODULE(saber_protocol, m) {
         .def("update_buffer_state", &saber::Node::updateBufferState)
         .def("set_latency", &saber::Node::setLatency)
         .def("get_latency", &saber::Node::getLatency)
+        .def("get_buffer_state", &saber::Node::getBufferState)
+        .def("set_signal_strength", &saber::Node::setSignalStrength)
+        .def("get_signal_strength", &saber::Node::getSignalStrength)
+        .def("record_packet_success", &saber::Node::recordPacketSuccess)
+        .def("get_packet_success_rate", &saber::Node::getPacketSuccessRate)
+        .def("get_link_quality", &saber::Node::getLinkQuality)
         .def("is_active", &saber::Node::isActive)
+        .def("set_heartbeat_timeout", &saber::Node::setHeartbeatTimeout)
+        .def("get_last_seen_ms", &saber::Node::getLastSeenMs)
         .def_readwrite("id", &saber::Node::id)
         .def_readwrite("role", &saber::Node::role);
     
+    py::class_<saber::LinkQuality>(m, "LinkQuality")
+        .def_readonly("rssi_dbm", &saber::LinkQuality::rssiDbm)
+        .def_readonly("packet_success_rate", &saber::LinkQuality::packetSuccessRate)
+        .def_readonly("score", &saber::LinkQuality::score);
+    
+    // Stato di un nodo della rete, restituito da SaberProtocol.get_active_node_info
+    py::class_<saber::MeshNodeInfo>(m, "NodeInfo")
+        .def_readonly("node_id", &saber::MeshNodeInfo::nodeId)
+        .def_readonly("role", &saber::MeshNodeInfo::role)
+        .def_readonly("latency_ms", &saber::MeshNodeInfo::latencyMs)
+        .def_readonly("buffer_state", &saber::MeshNodeInfo::bufferState)
+        .def_readonly("last_seen_ms", &saber::MeshNodeInfo::lastSeenMs)
+        .def_readonly("link_quality", &saber::MeshNodeInfo::linkQuality)
+        .def_readonly("active", &saber::MeshNodeInfo::active)
+        .def("to_dict", [](const saber::MeshNodeInfo& self) {
+            py::dict result;
+            result["node_id"] = self.nodeId;
+            result["role"] = saber::nodeRoleToString(self.role);
+            result["latency_ms"] = self.latencyMs;
+            result["buffer_state"] = self.bufferState;
+            result["last_seen_ms"] = self.lastSeenMs;
+            result["link_quality"] = self.linkQuality;
+            result["active"] = self.active;
+            return result;
+        });
+    
+    // Esporre la scoperta dei nodi via BLE
+    py::class_<saber::DiscoveryAdvertisement>(m, "DiscoveryAdvertisement")
+        .def(py::init<>())
+        .def_readwrite("node_id", &saber::DiscoveryAdvertisement::nodeId)
+        .def_readwrite("role", &saber::DiscoveryAdvertisement::role);
+    
+    py::class_<saber::DiscoveredNode>(m, "DiscoveredNode")
+        .def_readonly("node_id", &saber::DiscoveredNode::nodeId)
+        .def_readonly("role", &saber::DiscoveredNode::role)
+        .def_readonly("address", &saber::DiscoveredNode::address)
+        .def_readonly("rssi_dbm", &saber::DiscoveredNode::rssiDbm)
+        .def_readonly("last_seen_ms", &saber::DiscoveredNode::lastSeenMs)
+        .def_readonly("advertisements", &saber::DiscoveredNode::advertisements);
+    
+    py::class_<saber::NodeDiscovery>(m, "NodeDiscovery")
+        .def(py::init<const std::string&, saber::NodeRole, uint32_t>(),
+             py::arg("local_node_id"), py::arg("local_role"), py::arg("stale_ms") = saber::DEFAULT_DISCOVERY_STALE_MS)
+        .def("advertisement", &saber::NodeDiscovery::advertisement)
+        .def("observe", &saber::NodeDiscovery::observe)
+        .def("expire", &saber::NodeDiscovery::expire)
+        .def("get_nodes", &saber::NodeDiscovery::getNodes);
+    
+    m.attr("SABER_SERVICE_UUID") = saber::SABER_SERVICE_UUID;
+    m.attr("MAX_ADVERTISEMENT_BYTES") = saber::MAX_ADVERTISEMENT_BYTES;
+    m.def("encode_advertisement", &saber::encodeAdvertisement);
+    m.def("parse_advertisement", &saber::parseAdvertisement);
+    
+    // Esporre MeshPacketType
+    py::enum_<saber::MeshPacketType>(m, "MeshPacketType")
+        .value("Ping", saber::MeshPacketType::Ping)
+        .value("Command", saber::MeshPacketType::Command)
+        .value("Status", saber::MeshPacketType::Status)
+        .value("TimeBeacon", saber::MeshPacketType::TimeBeacon)
+        .value("EmergencySync", saber::MeshPacketType::EmergencySync)
+        .value("Subscribe", saber::MeshPacketType::Subscribe)
+        .value("Unsubscribe", saber::MeshPacketType::Unsubscribe)
+        .value("Reject", saber::MeshPacketType::Reject)
+        .value("Metadata", saber::MeshPacketType::Metadata)
+        .value("Artwork", saber::MeshPacketType::Artwork)
+        .value("Audio", saber::MeshPacketType::Audio)
+        .value("Nack", saber::MeshPacketType::Nack)
+        .value("Join", saber::MeshPacketType::Join)
+        .value("Pairing", saber::MeshPacketType::Pairing)
+        .value("Ack", saber::MeshPacketType::Ack);
+    
+    m.def("mesh_packet_type_to_string", &saber::meshPacketTypeToString);
+    m.def("mesh_packet_type_from_string", &saber::meshPacketTypeFromString);
+    
+    // Esporre RejectReason
+    py::enum_<saber::RejectReason>(m, "RejectReason")
+        .value("BadSignature", saber::RejectReason::BadSignature)
+        .value("Replay", saber::RejectReason::Replay)
+        .value("RateLimited", saber::RejectReason::RateLimited)
+        .value("UnknownStream", saber::RejectReason::UnknownStream)
+        .value("UnknownSender", saber::RejectReason::UnknownSender)
+        .value("WrongNetworkKey", saber::RejectReason::WrongNetworkKey)
+        .value("StaleEpoch", saber::RejectReason::StaleEpoch)
+        .value("Quarantined", saber::RejectReason::Quarantined)
+        .value("ProvisioningClosed", saber::RejectReason::ProvisioningClosed)
+        .value("CapacityExceeded", saber::RejectReason::CapacityExceeded)
+        .value("TtlExpired", saber::RejectReason::TtlExpired)
+        .value("RoutingLoop", saber::RejectReason::RoutingLoop)
+        .value("Unauthorized", saber::RejectReason::Unauthorized);
+    
+    m.def("short_node_id", &saber::shortNodeId);
+    
+    // Esporre i timestamp compressi dei frame audio
+    py::enum_<saber::TimestampWidth>(m, "TimestampWidth")
+        .value("Full", saber::TimestampWidth::Full)
+        .value("Bits16", saber::TimestampWidth::Bits16)
+        .value("Bits24", saber::TimestampWidth::Bits24);
+    
+    m.def("compress_timestamp", &saber::compressTimestamp);
+    m.def("expand_timestamp", &saber::expandTimestamp);
+    
+    py::class_<saber::TimestampEncoder>(m, "TimestampEncoder")
+        .def(py::init<saber::TimestampWidth, uint32_t>(),
+             py::arg("width") = saber::TimestampWidth::Bits24, py::arg("anchor_interval") = 50)
+        .def("next", &saber::TimestampEncoder::next)
+        .def("reset", &saber::TimestampEncoder::reset);
+    
+    py::class_<saber::TimestampDecoder>(m, "TimestampDecoder")
+        .def(py::init<>())
+        .def("decode", &saber::TimestampDecoder::decode, 
+             py::arg("width"), py::arg("value"), py::arg("arrival_us") = py::none())
+        .def("is_anchored", &saber::TimestampDecoder::isAnchored);
+    
+    py::class_<saber::MeshPacket::AudioFrameInfo>(m, "AudioFrameInfo")
+        .def_readonly("stream_id", &saber::MeshPacket::AudioFrameInfo::streamId)
+        .def_readonly("frame_sequence", &saber::MeshPacket::AudioFrameInfo::frameSequence)
+        .def_readonly("playout_time_us", &saber::MeshPacket::AudioFrameInfo::playoutTimeUs)
+        .def_readonly("timestamp_width", &saber::MeshPacket::AudioFrameInfo::timestampWidth)
+        .def_readonly("compact_timestamp", &saber::MeshPacket::AudioFrameInfo::compactTimestamp)
+        .def_readonly("payload", &saber::MeshPacket::AudioFrameInfo::payload);
+    
+    py::class_<saber::MeshPacket::AckInfo>(m, "AckInfo")
+        .def_readonly("origin", &saber::MeshPacket::AckInfo::origin)
+        .def_readonly("sequence", &saber::MeshPacket::AckInfo::sequence)
+        .def_readonly("acked_type", &saber::MeshPacket::AckInfo::ackedType);
+    
+    py::class_<saber::MeshPacket::RejectInfo>(m, "RejectInfo")
+        .def_readonly("origin", &saber::MeshPacket::RejectInfo::origin)
+        .def_readonly("rejected_type", &saber::MeshPacket::RejectInfo::rejectedType)
+        .def_readonly("rejected_sequence", &saber::MeshPacket::RejectInfo::rejectedSequence)
+        .def_readonly("reason", &saber::MeshPacket::RejectInfo::reason)
+        .def_readonly("detail", &saber::MeshPacket::RejectInfo::detail);
+    
+    // Esporre MeshPacket
+    py::class_<saber::MeshPacket>(m, "MeshPacket")
+        .def_static("create_ping", &saber::MeshPacket::createPing)
+        .def_static("create_command", &saber::MeshPacket::createCommand)
+        .def_static("create_zone_command", &saber::MeshPacket::createZoneCommand,
+                    py::arg("zone"), py::arg("cmd_type"), py::arg("params") = std::map<std::string, std::string>{})
+        .def_static("create_status", &saber::MeshPacket::createStatus)
+        .def_static("create_time_beacon", &saber::MeshPacket::createTimeBeacon)
+        .def_static("create_emergency_sync", &saber::MeshPacket::createEmergencySync)
+        .def_static("create_subscribe", &saber::MeshPacket::createSubscribe)
+        .def_static("create_unsubscribe", &saber::MeshPacket::createUnsubscribe)
+        .def_static("create_audio", &saber::MeshPacket::createAudio,
+                    py::arg("stream_id"), py::arg("frame_sequence"), py::arg("playout_time_us"),
+                    py::arg("payload"), py::arg("width") = saber::TimestampWidth::Full)
+        .def_static("create_ack", &saber::MeshPacket::createAck)
+        .def_static("create_join", &saber::MeshPacket::createJoin)
+        .def("get_audio_data", &saber::MeshPacket::getAudioData)
+        .def("get_ack_data", &saber::MeshPacket::getAckData)
+        .def("get_reject_data", &saber::MeshPacket::getRejectData)
+        .def("get_subscription_data", &saber::MeshPacket::getSubscriptionData)
+        .def("get_command_zone", &saber::MeshPacket::getCommandZone)
+        .def("encoded_size", &saber::MeshPacket::encodedSize)
+        .def("encode", [](const saber::MeshPacket& self) {
+            std::vector<uint8_t> bytes = self.encode();
+            return py::bytes(reinterpret_cast<const char*>(bytes.data()), bytes.size());
+        })
+        .def_static("decode", [](const py::bytes& bytes) {
+            std::string data = bytes;
+            return saber::MeshPacket::decode(std::vector<uint8_t>(data.begin(), data.end()));
+        })
+        .def("get_type", &saber::MeshPacket::getType)
+        .def("set_header", &saber::MeshPacket::setHeader)
+        .def("get_source", &saber::MeshPacket::getSource)
+        .def("get_sequence", &saber::MeshPacket::getSequence)
+        .def("get_ttl", &saber::MeshPacket::getTtl)
+        .def("get_origin_ttl", &saber::MeshPacket::getOriginTtl)
+        .def("decrement_ttl", &saber::MeshPacket::decrementTtl)
+        .def("get_hop_count", &saber::MeshPacket::getHopCount)
+        .def("set_path_recording", &saber::MeshPacket::setPathRecording)
+        .def("is_path_recording", &saber::MeshPacket::isPathRecording)
+        .def("append_hop", &saber::MeshPacket::appendHop)
+        .def("get_path", &saber::MeshPacket::getPath)
+        .def("set_trace_context", &saber::MeshPacket::setTraceContext)
+        .def("get_trace_context", &saber::MeshPacket::getTraceContext)
+        .def("add_bridge", &saber::MeshPacket::addBridge)
+        .def("has_crossed_bridge", &saber::MeshPacket::hasCrossedBridge)
+        .def("get_bridges", &saber::MeshPacket::getBridges)
+        .def("add_relay_delay", &saber::MeshPacket::addRelayDelay)
+        .def("get_relay_delay_us", &saber::MeshPacket::getRelayDelayUs)
+        .def("signing_bytes", &saber::MeshPacket::signingBytes)
+        .def("sign", &saber::MeshPacket::sign)
+        .def("verify_signature", &saber::MeshPacket::verifySignature)
+        .def("is_signed", &saber::MeshPacket::isSigned)
+        .def("seal", &saber::MeshPacket::seal)
+        .def("seal_for", &saber::MeshPacket::sealFor)
+        .def("get_session_peer", &saber::MeshPacket::getSessionPeer)
+        .def("open", &saber::MeshPacket::open)
+        .def("is_encrypted", &saber::MeshPacket::isEncrypted);
+    
     // Esporre CryptoError
     py::class_<saber::CryptoError>(m, "CryptoError")
         .def(py::init<saber::CryptoError::Type, const std::string&>())
@@ -42,14 +370,708 @@ PYBIND11_MODULE(saber_protocol, m) {
         .value("Signature", saber::CryptoError::Type::Signature)
         .value("Verification", saber::CryptoError::Type::Verification)
         .value("KeyExchange", saber::CryptoError::Type::KeyExchange)
-        .value("Hash", saber::CryptoError::Type::Hash);
+        .value("Hash", saber::CryptoError::Type::Hash)
+        .value("Replay", saber::CryptoError::Type::Replay);
     
-    // Esporre MeshCrypto
-    py::class_<saber::MeshCrypto>(m, "MeshCrypto")
+    // Esporre SecurityEvent
+    py::class_<saber::SecurityEvent> securityEvent(m, "SecurityEvent");
+    py::enum_<saber::SecurityEvent::Type>(securityEvent, "Type")
+        .value("KeyConflict", saber::SecurityEvent::Type::KeyConflict)
+        .value("KeyConflictResolved", saber::SecurityEvent::Type::KeyConflictResolved)
+        .value("ReplayRejected", saber::SecurityEvent::Type::ReplayRejected)
+        .value("IntrusionDetected", saber::SecurityEvent::Type::IntrusionDetected)
+        .value("NodeQuarantined", saber::SecurityEvent::Type::NodeQuarantined)
+        .value("QuarantineReleased", saber::SecurityEvent::Type::QuarantineReleased);
+    securityEvent
+        .def_readonly("type", &saber::SecurityEvent::type)
+        .def_readonly("node_id", &saber::SecurityEvent::nodeId)
+        .def_readonly("key_ids", &saber::SecurityEvent::keyIds)
+        .def_readonly("detail", &saber::SecurityEvent::detail)
+        .def_readonly("timestamp", &saber::SecurityEvent::timestamp);
+    
+    // Esporre le sorgenti casuali
+    py::class_<saber::RandomSource, std::shared_ptr<saber::RandomSource>>(m, "RandomSource")
+        .def("fill", [](saber::RandomSource& source, size_t size) {
+            std::string bytes(size, '\0');
+            source.fill(reinterpret_cast<uint8_t*>(&bytes[0]), size);
+            return py::bytes(bytes);
+        });
+    
+    py::class_<saber::OsRandomSource, saber::RandomSource, std::shared_ptr<saber::OsRandomSource>>(m, "OsRandomSource")
+        .def(py::init<>());
+    
+#ifdef SABER_WITH_SEEDED_RNG
+    py::class_<saber::SeededRandomSource, saber::RandomSource, 
+               std::shared_ptr<saber::SeededRandomSource>>(m, "SeededRandomSource")
+        .def(py::init<uint64_t>())
+        .def("reset", &saber::SeededRandomSource::reset)
+        .def("get_seed", &saber::SeededRandomSource::getSeed);
+#endif
+    
+    // Esporre il tracciamento distribuito
+    py::class_<saber::TraceContext>(m, "TraceContext")
+        .def(py::init<>())
+        .def_readwrite("trace_id", &saber::TraceContext::traceId)
+        .def_readwrite("span_id", &saber::TraceContext::spanId)
+        .def("is_valid", &saber::TraceContext::isValid)
+        .def("trace_id_hex", &saber::TraceContext::traceIdHex)
+        .def("span_id_hex", &saber::TraceContext::spanIdHex)
+        .def("__eq__", [](const saber::TraceContext& self, const saber::TraceContext& other) { return self == other; });
+    
+    py::enum_<saber::SpanKind>(m, "SpanKind")
+        .value("Internal", saber::SpanKind::Internal)
+        .value("Producer", saber::SpanKind::Producer)
+        .value("Consumer", saber::SpanKind::Consumer);
+    
+    py::class_<saber::TraceSpan>(m, "TraceSpan")
+        .def_readonly("name", &saber::TraceSpan::name)
+        .def_readonly("kind", &saber::TraceSpan::kind)
+        .def_readonly("context", &saber::TraceSpan::context)
+        .def_readonly("parent_span_id", &saber::TraceSpan::parentSpanId)
+        .def_readonly("start_us", &saber::TraceSpan::startUs)
+        .def_readonly("end_us", &saber::TraceSpan::endUs)
+        .def_readwrite("attributes", &saber::TraceSpan::attributes)
+        .def_readwrite("error", &saber::TraceSpan::error)
+        .def_readonly("recording", &saber::TraceSpan::recording);
+    
+    py::class_<saber::Tracer, std::shared_ptr<saber::Tracer>>(m, "Tracer")
+        .def(py::init<std::shared_ptr<saber::RandomSource>, saber::Tracer::Clock>(),
+             py::arg("random") = nullptr, py::arg("clock") = nullptr)
+        .def("set_enabled", &saber::Tracer::setEnabled)
+        .def("is_enabled", &saber::Tracer::isEnabled)
+        .def("start_span", &saber::Tracer::startSpan,
+             py::arg("name"), py::arg("kind") = saber::SpanKind::Internal, py::arg("parent") = std::nullopt)
+        .def("end_span", &saber::Tracer::endSpan)
+        .def("take_finished", &saber::Tracer::takeFinished)
+        .def("get_dropped_spans", &saber::Tracer::getDroppedSpans);
+    
+    py::class_<saber::OtlpExporter>(m, "OtlpExporter")
+        .def(py::init<const std::string&, const std::string&, uint32_t>())
+        .def("export_spans", &saber::OtlpExporter::exportSpans, py::call_guard<py::gil_scoped_release>())
+        .def("get_exported_spans", &saber::OtlpExporter::getExportedSpans)
+        .def("get_failed_exports", &saber::OtlpExporter::getFailedExports);
+    
+    m.attr("MAX_PENDING_SPANS") = saber::MAX_PENDING_SPANS;
+    m.def("encode_otlp_json", &saber::encodeOtlpJson);
+    
+    // Esporre i livelli di log e l'inoltro al modulo logging di Python
+    m.def("set_log_level", [](const std::string& level, const std::string& target) {
+        auto parsed = saber::logLevelFromString(level);
+        if (!parsed) {
+            throw py::value_error("Livello di log non valido: " + level);
+        }
+        saber::Logger::instance().setLevel(target, *parsed);
+    }, py::arg("level"), py::arg("target") = "");
+    m.def("get_log_level", [](const std::string& target) {
+        return saber::logLevelToString(saber::Logger::instance().getFilter().levelFor(target));
+    }, py::arg("target") = "");
+    m.def("forward_logs_to_python", &forwardLogsToPython, py::arg("enabled") = true);
+    // All'uscita l'interprete non può più ricevere messaggi dai thread del protocollo
+    py::module_::import("atexit").attr("register")(py::cpp_function([]() {
+        saber::Logger::instance().setSink(nullptr);
+    }));
+    
+    // Esporre la finestra di provisioning
+    py::class_<saber::ProvisioningStatus>(m, "ProvisioningStatus")
+        .def_readonly("open", &saber::ProvisioningStatus::open)
+        .def_readonly("remaining_ms", &saber::ProvisioningStatus::remainingMs)
+        .def_readonly("accepted", &saber::ProvisioningStatus::accepted)
+        .def_readonly("refused", &saber::ProvisioningStatus::refused)
+        .def_readonly("throttled", &saber::ProvisioningStatus::throttled);
+
+    py::enum_<saber::JoinDecision>(m, "JoinDecision")
+        .value("Accept", saber::JoinDecision::Accept)
+        .value("Refuse", saber::JoinDecision::Refuse)
+        .value("Throttle", saber::JoinDecision::Throttle);
+
+    py::class_<saber::ProvisioningWindow>(m, "ProvisioningWindow")
+        .def(py::init<uint32_t>(), py::arg("closed_attempts_per_minute") = 6)
+        .def("open", &saber::ProvisioningWindow::open)
+        .def("close", &saber::ProvisioningWindow::close)
+        .def("is_open", &saber::ProvisioningWindow::isOpen)
+        .def("admit", &saber::ProvisioningWindow::admit)
+        .def("record_accepted", &saber::ProvisioningWindow::recordAccepted)
+        .def("set_closed_rate_limit", &saber::ProvisioningWindow::setClosedRateLimit)
+        .def("status", &saber::ProvisioningWindow::status);
+
+    // Esporre il conteggio delle ammissioni alla rete
+    py::class_<saber::RouteTrace>(m, "RouteTrace")
+        .def_readonly("stream_id", &saber::RouteTrace::streamId)
+        .def_readonly("source", &saber::RouteTrace::source)
+        .def_readonly("hops", &saber::RouteTrace::hops)
+        .def_readonly("ttl", &saber::RouteTrace::ttl)
+        .def_readonly("path", &saber::RouteTrace::path)
+        .def_readonly("observed_at_ms", &saber::RouteTrace::observedAtMs);
+    
+    // Esporre il grafo della topologia, modificabile prima dell'esportazione
+    m.attr("TOPOLOGY_REPORT_INTERVAL_MS") = saber::TOPOLOGY_REPORT_INTERVAL_MS;
+    m.attr("TOPOLOGY_LINK_TIMEOUT_MS") = saber::TOPOLOGY_LINK_TIMEOUT_MS;
+    
+    py::class_<saber::TopologyNode>(m, "TopologyNode")
+        .def(py::init<>())
+        .def_readwrite("id", &saber::TopologyNode::id)
+        .def_readwrite("role", &saber::TopologyNode::role)
+        .def_readwrite("active", &saber::TopologyNode::active)
+        .def_readwrite("local", &saber::TopologyNode::local)
+        .def_readwrite("hops", &saber::TopologyNode::hops);
+    
+    py::class_<saber::TopologyLink>(m, "TopologyLink")
+        .def(py::init<>())
+        .def_readwrite("from_node", &saber::TopologyLink::from)
+        .def_readwrite("to_node", &saber::TopologyLink::to)
+        .def_readwrite("rssi_dbm", &saber::TopologyLink::rssiDbm)
+        .def_readwrite("age_ms", &saber::TopologyLink::ageMs);
+    
+    py::class_<saber::TopologyGraph>(m, "TopologyGraph")
+        .def(py::init<>())
+        .def_readwrite("local_node", &saber::TopologyGraph::localNode)
+        .def_readwrite("nodes", &saber::TopologyGraph::nodes)
+        .def_readwrite("links", &saber::TopologyGraph::links);
+    
+    m.def("topology_to_dot", &saber::topologyToDot);
+    m.def("topology_to_json", &saber::topologyToJson);
+    m.def("encode_neighbor_report", &saber::encodeNeighborReport);
+    m.def("parse_neighbor_report", &saber::parseNeighborReport);
+    
+    py::class_<saber::AdmissionStats>(m, "AdmissionStats")
+        .def_readonly("max_nodes", &saber::AdmissionStats::maxNodes)
+        .def_readonly("max_sinks", &saber::AdmissionStats::maxSinks)
+        .def_readonly("nodes", &saber::AdmissionStats::nodes)
+        .def_readonly("sinks", &saber::AdmissionStats::sinks)
+        .def_readonly("admitted", &saber::AdmissionStats::admitted)
+        .def_readonly("rejected", &saber::AdmissionStats::rejected)
+        .def_readonly("evicted", &saber::AdmissionStats::evicted);
+    
+    // Esporre le code dei pacchetti per classe di priorità
+    m.attr("PACKET_QUEUE_CAPACITY") = saber::PACKET_QUEUE_CAPACITY;
+    
+    py::enum_<saber::PacketClass>(m, "PacketClass")
+        .value("Sync", saber::PacketClass::Sync)
+        .value("Audio", saber::PacketClass::Audio)
+        .value("Status", saber::PacketClass::Status)
+        .value("Command", saber::PacketClass::Command);
+    
+    m.def("packet_class_to_string", &saber::packetClassToString);
+    m.def("packet_class_from_string", &saber::packetClassFromString);
+    m.def("packet_class_of", &saber::packetClassOf);
+    
+    py::class_<saber::PacketQueueStats>(m, "PacketQueueStats")
+        .def_readonly("queued", &saber::PacketQueueStats::queued)
+        .def_readonly("peak", &saber::PacketQueueStats::peak)
+        .def_readonly("dequeued", &saber::PacketQueueStats::dequeued)
+        .def_readonly("dropped", &saber::PacketQueueStats::dropped);
+    
+    py::class_<saber::PacketScheduler>(m, "PacketScheduler")
+        .def(py::init<size_t>(), py::arg("capacity") = saber::PACKET_QUEUE_CAPACITY)
+        .def("push", &saber::PacketScheduler::push)
+        .def("pop", &saber::PacketScheduler::pop)
+        .def("empty", &saber::PacketScheduler::empty)
+        .def("size", &saber::PacketScheduler::size)
+        .def("get_stats", &saber::PacketScheduler::getStats);
+    
+    // Esporre i limiti di traffico in ingresso
+    py::class_<saber::RateLimit>(m, "RateLimit")
+        .def(py::init<>())
+        .def(py::init([](double packetsPerSecond, uint32_t burst) {
+            return saber::RateLimit{packetsPerSecond, burst};
+        }), py::arg("packets_per_second"), py::arg("burst"))
+        .def_readwrite("packets_per_second", &saber::RateLimit::packetsPerSecond)
+        .def_readwrite("burst", &saber::RateLimit::burst);
+    
+    m.def("default_rate_limits", &saber::defaultRateLimits);
+    
+    py::class_<saber::RateLimitStats>(m, "RateLimitStats")
+        .def_readonly("dropped", &saber::RateLimitStats::dropped)
+        .def_readonly("dropped_by_type", &saber::RateLimitStats::droppedByType)
+        .def_readonly("dropped_by_source", &saber::RateLimitStats::droppedBySource);
+    
+    py::class_<saber::PacketRateLimiter>(m, "PacketRateLimiter")
+        .def(py::init<std::map<saber::MeshPacketType, saber::RateLimit>>(),
+             py::arg("limits") = saber::defaultRateLimits())
+        .def("set_limits", &saber::PacketRateLimiter::setLimits)
+        .def("get_limits", &saber::PacketRateLimiter::getLimits)
+        .def("allow", &saber::PacketRateLimiter::allow)
+        .def("get_stats", &saber::PacketRateLimiter::getStats);
+    
+    // Esporre il diario degli eventi
+    py::class_<saber::JournalEvent>(m, "JournalEvent")
+        .def_readonly("cursor", &saber::JournalEvent::cursor)
+        .def_readonly("timestamp", &saber::JournalEvent::timestamp)
+        .def_readonly("category", &saber::JournalEvent::category)
+        .def_readonly("type", &saber::JournalEvent::type)
+        .def_readonly("node_id", &saber::JournalEvent::nodeId)
+        .def_readonly("detail", &saber::JournalEvent::detail);
+    
+    py::class_<saber::JournalPage>(m, "JournalPage")
+        .def_readonly("events", &saber::JournalPage::events)
+        .def_readonly("next_cursor", &saber::JournalPage::nextCursor)
+        .def_readonly("has_more", &saber::JournalPage::hasMore)
+        .def_readonly("truncated", &saber::JournalPage::truncated);
+    
+    py::class_<saber::EventJournal>(m, "EventJournal")
+        .def(py::init<const std::string&, size_t>(), py::arg("path") = "", py::arg("max_entries") = 10000)
+        .def("append", &saber::EventJournal::append)
+        .def("since", &saber::EventJournal::since, py::arg("cursor"), py::arg("limit") = 256)
+        .def("latest_cursor", &saber::EventJournal::latestCursor);
+    
+    // Esporre la modalità festa
+    py::class_<saber::PartyMode>(m, "PartyMode")
+        .def_readonly("zones", &saber::PartyMode::zones)
+        .def_readonly("stream_id", &saber::PartyMode::streamId)
+        .def_readonly("buffer_ms", &saber::PartyMode::bufferMs)
+        .def_readonly("rejected", &saber::PartyMode::rejected)
+        .def_readonly("playlist", &saber::PartyMode::playlist)
+        .def_readonly("start_at_ms", &saber::PartyMode::startAtMs);
+    
+    // Esporre la modalità intercom
+    py::class_<saber::IntercomConfig>(m, "IntercomConfig")
         .def(py::init<>())
-        .def_static("with_network_key", &saber::MeshCrypto::withNetworkKey)
-        .def("encrypt", &saber::MeshCrypto::encrypt)
-        .def("decrypt", &saber::MeshCrypto::decrypt)
+        .def_readwrite("bitrate_kbps", &saber::IntercomConfig::bitrateKbps)
+        .def_readwrite("buffer_ms", &saber::IntercomConfig::bufferMs)
+        .def_readwrite("beacon_interval_ms", &saber::IntercomConfig::beaconIntervalMs)
+        .def_readwrite("ducking_db", &saber::IntercomConfig::duckingDb);
+    
+    py::class_<saber::IntercomSession>(m, "IntercomSession")
+        .def(py::init<>())
+        .def_readwrite("node_a", &saber::IntercomSession::nodeA)
+        .def_readwrite("node_b", &saber::IntercomSession::nodeB)
+        .def_readwrite("talking", &saber::IntercomSession::talking)
+        .def_readwrite("started_at_ms", &saber::IntercomSession::startedAtMs)
+        .def("involves", &saber::IntercomSession::involves)
+        .def("talk_stream", &saber::IntercomSession::talkStream)
+        .def("listen_stream", &saber::IntercomSession::listenStream)
+        .def("speaker_of", &saber::IntercomSession::speakerOf);
+    
+    m.attr("INTERCOM_STREAM_BASE") = saber::INTERCOM_STREAM_BASE;
+    m.attr("INTERCOM_SAMPLE_RATE_HZ") = saber::INTERCOM_SAMPLE_RATE_HZ;
+    m.def("is_intercom_stream", &saber::isIntercomStream);
+    m.def("duck_frame", &saber::duckFrame);
+    
+    // Esporre lo standby dei sink
+    py::enum_<saber::PowerState>(m, "PowerState")
+        .value("Active", saber::PowerState::Active)
+        .value("Standby", saber::PowerState::Standby)
+        .value("Waking", saber::PowerState::Waking);
+    
+    py::class_<saber::StandbyConfig>(m, "StandbyConfig")
+        .def(py::init<>())
+        .def_readwrite("enabled", &saber::StandbyConfig::enabled)
+        .def_readwrite("idle_minutes", &saber::StandbyConfig::idleMinutes)
+        .def_readwrite("preroll_ms", &saber::StandbyConfig::prerollMs)
+        .def_readwrite("report_interval_ms", &saber::StandbyConfig::reportIntervalMs);
+    
+    py::class_<saber::StandbyController>(m, "StandbyController")
+        .def(py::init<const saber::StandbyConfig&>(), py::arg("config") = saber::StandbyConfig())
+        .def("set_config", &saber::StandbyController::setConfig)
+        .def("update", &saber::StandbyController::update)
+        .def("wake", &saber::StandbyController::wake)
+        .def("get_state", &saber::StandbyController::getState)
+        .def("report_interval_ms", &saber::StandbyController::reportIntervalMs);
+    
+    m.def("power_state_to_string", &saber::powerStateToString);
+    
+    // Esporre gli stream simulcast
+    m.attr("MAX_SIMULCAST_LAYERS") = saber::MAX_SIMULCAST_LAYERS;
+    m.attr("SIMULCAST_SWITCH_TIMEOUT_MS") = saber::SIMULCAST_SWITCH_TIMEOUT_MS;
+    
+    py::class_<saber::SimulcastLayer>(m, "SimulcastLayer")
+        .def(py::init<>())
+        .def_readwrite("stream_id", &saber::SimulcastLayer::streamId)
+        .def_readwrite("bitrate_kbps", &saber::SimulcastLayer::bitrateKbps);
+    
+    py::class_<saber::SimulcastGroup>(m, "SimulcastGroup")
+        .def(py::init<>())
+        .def_readwrite("stream_id", &saber::SimulcastGroup::streamId)
+        .def_readwrite("layers", &saber::SimulcastGroup::layers);
+    
+    m.def("validate_simulcast_group", &saber::validateSimulcastGroup);
+    m.def("encode_simulcast_layers", &saber::encodeSimulcastLayers);
+    m.def("parse_simulcast_layers", &saber::parseSimulcastLayers);
+    
+    py::class_<saber::SimulcastConfig>(m, "SimulcastConfig")
+        .def(py::init<>())
+        .def_readwrite("headroom_percent", &saber::SimulcastConfig::headroomPercent)
+        .def_readwrite("upgrade_after_ms", &saber::SimulcastConfig::upgradeAfterMs)
+        .def_readwrite("loss_threshold_percent", &saber::SimulcastConfig::lossThresholdPercent);
+    
+    py::enum_<saber::SimulcastVerdict>(m, "SimulcastVerdict")
+        .value("Play", saber::SimulcastVerdict::Play)
+        .value("Prime", saber::SimulcastVerdict::Prime)
+        .value("Drop", saber::SimulcastVerdict::Drop);
+    
+    py::class_<saber::SimulcastStatus>(m, "SimulcastStatus")
+        .def_readonly("stream_id", &saber::SimulcastStatus::streamId)
+        .def_readonly("active_layer", &saber::SimulcastStatus::activeLayer)
+        .def_readonly("pending_layer", &saber::SimulcastStatus::pendingLayer)
+        .def_readonly("bitrate_kbps", &saber::SimulcastStatus::bitrateKbps)
+        .def_readonly("budget_kbps", &saber::SimulcastStatus::budgetKbps)
+        .def_readonly("loss_percent", &saber::SimulcastStatus::lossPercent)
+        .def_readonly("switches", &saber::SimulcastStatus::switches);
+    
+    py::class_<saber::SimulcastReceiver>(m, "SimulcastReceiver")
+        .def(py::init<const saber::SimulcastGroup&, const saber::SimulcastConfig&>(),
+             py::arg("group"), py::arg("config") = saber::SimulcastConfig())
+        .def("set_config", &saber::SimulcastReceiver::setConfig)
+        .def("get_group", &saber::SimulcastReceiver::getGroup)
+        .def("has_layer", &saber::SimulcastReceiver::hasLayer)
+        .def("accept", &saber::SimulcastReceiver::accept, py::arg("layer"), py::arg("sequence"),
+             py::arg("playout_time_us"), py::arg("now_ms"))
+        .def("update", &saber::SimulcastReceiver::update, py::arg("budget_kbps"), py::arg("now_ms"))
+        .def("wanted_layers", &saber::SimulcastReceiver::wantedLayers)
+        .def("get_status", &saber::SimulcastReceiver::getStatus);
+    
+    // Esporre il controllo di flusso sul riempimento dei buffer dei sink
+    m.attr("FLOW_STATUS_INTERVAL_MS") = saber::FLOW_STATUS_INTERVAL_MS;
+    
+    py::enum_<saber::FlowState>(m, "FlowState")
+        .value("Normal", saber::FlowState::Normal)
+        .value("LowHeadroom", saber::FlowState::LowHeadroom)
+        .value("Overflow", saber::FlowState::Overflow);
+    
+    py::enum_<saber::FramePriority>(m, "FramePriority")
+        .value("Low", saber::FramePriority::Low)
+        .value("Normal", saber::FramePriority::Normal)
+        .value("High", saber::FramePriority::High);
+    
+    m.def("flow_state_to_string", &saber::flowStateToString);
+    m.def("frame_priority_to_string", &saber::framePriorityToString);
+    m.def("frame_priority_from_string", &saber::framePriorityFromString);
+    
+    py::class_<saber::FlowConfig>(m, "FlowConfig")
+        .def(py::init<>())
+        .def_readwrite("enabled", &saber::FlowConfig::enabled)
+        .def_readwrite("low_watermark_percent", &saber::FlowConfig::lowWatermarkPercent)
+        .def_readwrite("high_watermark_percent", &saber::FlowConfig::highWatermarkPercent)
+        .def_readwrite("recover_after_ms", &saber::FlowConfig::recoverAfterMs)
+        .def_readwrite("report_timeout_ms", &saber::FlowConfig::reportTimeoutMs);
+    
+    py::class_<saber::FlowStatus>(m, "FlowStatus")
+        .def_readonly("state", &saber::FlowStatus::state)
+        .def_readonly("buffer_levels", &saber::FlowStatus::bufferLevels)
+        .def_readonly("dropped_frames", &saber::FlowStatus::droppedFrames)
+        .def_readonly("transitions", &saber::FlowStatus::transitions);
+    
+    py::class_<saber::FlowController>(m, "FlowController")
+        .def(py::init<const saber::FlowConfig&>(), py::arg("config") = saber::FlowConfig())
+        .def("set_config", &saber::FlowController::setConfig)
+        .def("get_config", &saber::FlowController::getConfig)
+        .def("report_buffer", &saber::FlowController::reportBuffer, py::arg("node_id"), py::arg("level_percent"),
+             py::arg("now_ms"))
+        .def("update", &saber::FlowController::update, py::arg("now_ms"))
+        .def("get_state", &saber::FlowController::getState)
+        .def("admit", &saber::FlowController::admit)
+        .def("get_status", &saber::FlowController::getStatus, py::arg("now_ms"));
+    
+    // Esporre la lista di revoca dei token
+    m.attr("REVOCATION_ANNOUNCE_INTERVAL_MS") = saber::REVOCATION_ANNOUNCE_INTERVAL_MS;
+    m.attr("REVOCATION_ENTRIES_PER_PACKET") = saber::REVOCATION_ENTRIES_PER_PACKET;
+    
+    py::enum_<saber::RevocationKind>(m, "RevocationKind")
+        .value("Token", saber::RevocationKind::Token)
+        .value("Node", saber::RevocationKind::Node);
+    
+    m.def("revocation_kind_to_string", &saber::revocationKindToString);
+    m.def("revocation_kind_from_string", &saber::revocationKindFromString);
+    
+    py::class_<saber::Revocation>(m, "Revocation")
+        .def(py::init<>())
+        .def_readwrite("kind", &saber::Revocation::kind)
+        .def_readwrite("subject", &saber::Revocation::subject)
+        .def_readwrite("revoked_at_ms", &saber::Revocation::revokedAtMs);
+    
+    m.def("encode_revocations", &saber::encodeRevocations);
+    m.def("parse_revocations", &saber::parseRevocations);
+    
+    py::class_<saber::RevocationList, std::shared_ptr<saber::RevocationList>>(m, "RevocationList")
+        .def(py::init<>())
+        .def("add", &saber::RevocationList::add)
+        .def("merge", &saber::RevocationList::merge)
+        .def("is_revoked", &saber::RevocationList::isRevoked, py::arg("token_id"), py::arg("node_id"),
+             py::arg("issued_at_ms"))
+        .def("is_token_revoked", &saber::RevocationList::isTokenRevoked)
+        .def("entries", &saber::RevocationList::entries)
+        .def("size", &saber::RevocationList::size);
+    
+    // Esporre l'abbinamento con PIN
+    m.attr("PAIRING_PIN_DIGITS") = saber::PAIRING_PIN_DIGITS;
+    m.attr("PAIRING_MAX_FAILURES") = saber::PAIRING_MAX_FAILURES;
+    m.attr("PAIRING_MAX_SESSIONS") = saber::PAIRING_MAX_SESSIONS;
+    m.attr("PAIRING_STEP_TIMEOUT_MS") = saber::PAIRING_STEP_TIMEOUT_MS;
+    
+    py::enum_<saber::PairingStep>(m, "PairingStep")
+        .value("Hello", saber::PairingStep::Hello)
+        .value("Challenge", saber::PairingStep::Challenge)
+        .value("Proof", saber::PairingStep::Proof)
+        .value("Welcome", saber::PairingStep::Welcome)
+        .value("Failed", saber::PairingStep::Failed);
+    
+    m.def("pairing_step_to_string", &saber::pairingStepToString);
+    
+    py::enum_<saber::PairingState>(m, "PairingState")
+        .value("Idle", saber::PairingState::Idle)
+        .value("Open", saber::PairingState::Open)
+        .value("Waiting", saber::PairingState::Waiting)
+        .value("Paired", saber::PairingState::Paired)
+        .value("Failed", saber::PairingState::Failed);
+    
+    m.def("pairing_state_to_string", &saber::pairingStateToString);
+    m.def("generate_pairing_pin", &saber::generatePairingPin);
+    m.def("pairing_uri", &saber::pairingUri);
+    m.def("parse_pairing_code", &saber::parsePairingCode);
+    
+    py::class_<saber::PairingCode>(m, "PairingCode")
+        .def_readonly("pin", &saber::PairingCode::pin)
+        .def_readonly("master_id", &saber::PairingCode::masterId);
+    
+    py::class_<saber::PairingStatus>(m, "PairingStatus")
+        .def_readonly("state", &saber::PairingStatus::state)
+        .def_readonly("uri", &saber::PairingStatus::uri)
+        .def_readonly("remaining_ms", &saber::PairingStatus::remainingMs)
+        .def_readonly("failures", &saber::PairingStatus::failures)
+        .def_readonly("paired", &saber::PairingStatus::paired)
+        .def_readonly("master_id", &saber::PairingStatus::masterId)
+        .def_readonly("token", &saber::PairingStatus::token)
+        .def_readonly("detail", &saber::PairingStatus::detail);
+    
+    py::class_<saber::PairingCredentials>(m, "PairingCredentials")
+        .def_readonly("master_id", &saber::PairingCredentials::masterId)
+        .def_readonly("master_public_key", &saber::PairingCredentials::masterPublicKey)
+        .def_readonly("network_key", &saber::PairingCredentials::networkKey)
+        .def_readonly("token", &saber::PairingCredentials::token);
+    
+    py::class_<saber::PairingRequest>(m, "PairingRequest")
+        .def_readonly("node_id", &saber::PairingRequest::nodeId)
+        .def_readonly("role", &saber::PairingRequest::role)
+        .def_readonly("public_key", &saber::PairingRequest::publicKey);
+    
+    py::class_<saber::PairingJoiner>(m, "PairingJoiner")
+        .def(py::init<const std::string&, saber::NodeRole, const std::vector<uint8_t>&, const std::string&,
+                      std::shared_ptr<saber::RandomSource>>(),
+             py::arg("node_id"), py::arg("role"), py::arg("public_key"), py::arg("pin"), py::arg("rng") = nullptr)
+        .def("hello", &saber::PairingJoiner::hello)
+        .def("respond", &saber::PairingJoiner::respond)
+        .def("complete", &saber::PairingJoiner::complete);
+    
+    py::class_<saber::PairingResponder>(m, "PairingResponder")
+        .def(py::init<const std::string&, const std::vector<uint8_t>&, const std::string&,
+                      std::shared_ptr<saber::RandomSource>>(),
+             py::arg("master_id"), py::arg("public_key"), py::arg("pin"), py::arg("rng") = nullptr)
+        .def("challenge", &saber::PairingResponder::challenge)
+        .def("verify", &saber::PairingResponder::verify)
+        .def("welcome", &saber::PairingResponder::welcome)
+        .def("is_pending", &saber::PairingResponder::isPending)
+        .def("get_failures", &saber::PairingResponder::getFailures)
+        .def("is_exhausted", &saber::PairingResponder::isExhausted);
+    
+    // Esporre i comandi di riproduzione e le loro conferme
+    m.attr("PLAYBACK_COMMAND_HISTORY") = saber::PLAYBACK_COMMAND_HISTORY;
+    m.attr("PLAYBACK_COMMAND_ACK_TIMEOUT_MS") = saber::PLAYBACK_COMMAND_ACK_TIMEOUT_MS;
+    
+    py::enum_<saber::PlaybackAction>(m, "PlaybackAction")
+        .value("Start", saber::PlaybackAction::Start)
+        .value("Stop", saber::PlaybackAction::Stop)
+        .value("Pause", saber::PlaybackAction::Pause)
+        .value("Resume", saber::PlaybackAction::Resume)
+        .value("Seek", saber::PlaybackAction::Seek)
+        .value("Volume", saber::PlaybackAction::Volume);
+    
+    m.def("playback_action_to_string", &saber::playbackActionToString);
+    m.def("playback_action_from_string", &saber::playbackActionFromString);
+    
+    py::class_<saber::PlaybackCommand>(m, "PlaybackCommand")
+        .def(py::init<>())
+        .def_readwrite("id", &saber::PlaybackCommand::id)
+        .def_readwrite("action", &saber::PlaybackCommand::action)
+        .def_readwrite("zone", &saber::PlaybackCommand::zone)
+        .def_readwrite("at_ms", &saber::PlaybackCommand::atMs)
+        .def_readwrite("playlist", &saber::PlaybackCommand::playlist)
+        .def_readwrite("volume_percent", &saber::PlaybackCommand::volumePercent)
+        .def_readwrite("position_ms", &saber::PlaybackCommand::positionMs);
+    
+    m.def("encode_playback_command", &saber::encodePlaybackCommand);
+    m.def("parse_playback_command", &saber::parsePlaybackCommand);
+    
+    py::enum_<saber::CommandOutcome>(m, "CommandOutcome")
+        .value("Pending", saber::CommandOutcome::Pending)
+        .value("Applied", saber::CommandOutcome::Applied)
+        .value("Failed", saber::CommandOutcome::Failed)
+        .value("TimedOut", saber::CommandOutcome::TimedOut);
+    
+    m.def("command_outcome_to_string", &saber::commandOutcomeToString);
+    m.def("command_outcome_from_string", &saber::commandOutcomeFromString);
+    
+    py::class_<saber::CommandNodeStatus>(m, "CommandNodeStatus")
+        .def_readonly("outcome", &saber::CommandNodeStatus::outcome)
+        .def_readonly("updated_at_ms", &saber::CommandNodeStatus::updatedAtMs)
+        .def_readonly("detail", &saber::CommandNodeStatus::detail);
+    
+    py::class_<saber::CommandStatus>(m, "CommandStatus")
+        .def_readonly("command", &saber::CommandStatus::command)
+        .def_readonly("issued_at_ms", &saber::CommandStatus::issuedAtMs)
+        .def_readonly("nodes", &saber::CommandStatus::nodes)
+        .def("is_complete", &saber::CommandStatus::isComplete);
+    
+    py::class_<saber::CommandTracker>(m, "CommandTracker")
+        .def(py::init<size_t>(), py::arg("capacity") = saber::PLAYBACK_COMMAND_HISTORY)
+        .def("issue", &saber::CommandTracker::issue, py::arg("command"), py::arg("nodes"), py::arg("now_ms"))
+        .def("acknowledge", &saber::CommandTracker::acknowledge, py::arg("node_id"), py::arg("id"),
+             py::arg("outcome"), py::arg("detail"), py::arg("now_ms"))
+        .def("expire", &saber::CommandTracker::expire, py::arg("now_ms"))
+        .def("get", &saber::CommandTracker::get)
+        .def("recent", &saber::CommandTracker::recent);
+    
+    // Esporre le sorgenti audio del Master
+    py::enum_<saber::SourceKind>(m, "SourceKind")
+        .value("Capture", saber::SourceKind::Capture)
+        .value("FileQueue", saber::SourceKind::FileQueue)
+        .value("Rtp", saber::SourceKind::Rtp)
+        .value("TestTone", saber::SourceKind::TestTone);
+    
+    py::class_<saber::AudioSourceConfig>(m, "AudioSourceConfig")
+        .def(py::init<>())
+        .def_readwrite("name", &saber::AudioSourceConfig::name)
+        .def_readwrite("kind", &saber::AudioSourceConfig::kind)
+        .def_readwrite("location", &saber::AudioSourceConfig::location)
+        .def_readwrite("priority", &saber::AudioSourceConfig::priority);
+    
+    py::class_<saber::SourceSelectionConfig>(m, "SourceSelectionConfig")
+        .def(py::init<>())
+        .def_readwrite("sources", &saber::SourceSelectionConfig::sources)
+        .def_readwrite("stall_ms", &saber::SourceSelectionConfig::stallMs)
+        .def_readwrite("silence_ms", &saber::SourceSelectionConfig::silenceMs)
+        .def_readwrite("silence_threshold", &saber::SourceSelectionConfig::silenceThreshold)
+        .def_readwrite("failback_ms", &saber::SourceSelectionConfig::failbackMs)
+        .def_readwrite("auto_failback", &saber::SourceSelectionConfig::autoFailback);
+    
+    py::class_<saber::SourceStatus>(m, "SourceStatus")
+        .def_readonly("name", &saber::SourceStatus::name)
+        .def_readonly("kind", &saber::SourceStatus::kind)
+        .def_readonly("priority", &saber::SourceStatus::priority)
+        .def_readonly("active", &saber::SourceStatus::active)
+        .def_readonly("healthy", &saber::SourceStatus::healthy)
+        .def_readonly("frames", &saber::SourceStatus::frames)
+        .def_readonly("idle_ms", &saber::SourceStatus::idleMs);
+    
+    py::class_<saber::SourceSwitch>(m, "SourceSwitch")
+        .def_readonly("from_source", &saber::SourceSwitch::from)
+        .def_readonly("to_source", &saber::SourceSwitch::to)
+        .def_readonly("reason", &saber::SourceSwitch::reason);
+    
+    py::class_<saber::SourceSelector>(m, "SourceSelector")
+        .def(py::init<const saber::SourceSelectionConfig&>(), py::arg("config") = saber::SourceSelectionConfig())
+        .def("set_config", &saber::SourceSelector::setConfig)
+        .def("get_config", &saber::SourceSelector::getConfig)
+        .def("offer_frame", &saber::SourceSelector::offerFrame, py::arg("name"), py::arg("frame"), py::arg("now_ms"))
+        .def("update", &saber::SourceSelector::update, py::arg("now_ms"))
+        .def("switch_to", &saber::SourceSelector::switchTo, py::arg("name"))
+        .def("resume_automatic", &saber::SourceSelector::resumeAutomatic)
+        .def("is_manual", &saber::SourceSelector::isManual)
+        .def("get_active", &saber::SourceSelector::getActive)
+        .def("get_status", &saber::SourceSelector::getStatus, py::arg("now_ms"));
+    
+    py::class_<saber::ToneGenerator>(m, "ToneGenerator")
+        .def(py::init<float, uint32_t, uint8_t, int16_t>(), py::arg("frequency_hz") = 1000.0f,
+             py::arg("sample_rate") = saber::spec::SAMPLE_RATE_MUSIC_HZ, py::arg("channels") = 2,
+             py::arg("amplitude") = 8192)
+        .def("next", &saber::ToneGenerator::next, py::arg("playout_time_us"));
+    
+    m.def("source_kind_to_string", &saber::sourceKindToString);
+    m.def("source_kind_from_string", &saber::sourceKindFromString);
+    
+    // Esporre la programmazione oraria
+#ifndef SABER_MINIMAL_SINK
+    py::class_<saber::ScheduleEntry>(m, "ScheduleEntry")
+        .def_readonly("id", &saber::ScheduleEntry::id)
+        .def_readonly("minute_of_day", &saber::ScheduleEntry::minuteOfDay)
+        .def_readonly("days", &saber::ScheduleEntry::days)
+        .def_readonly("command", &saber::ScheduleEntry::command)
+        .def("__str__", &saber::ScheduleEntry::toString);
+    
+    py::class_<saber::Scheduler>(m, "Scheduler")
+        .def(py::init<int32_t>(), py::arg("utc_offset_minutes") = 0)
+        .def("add", &saber::Scheduler::add)
+        .def("update", &saber::Scheduler::update)
+        .def("remove", &saber::Scheduler::remove)
+        .def("entries", &saber::Scheduler::entries)
+        .def("set_utc_offset", &saber::Scheduler::setUtcOffset)
+        .def("get_utc_offset", &saber::Scheduler::getUtcOffset)
+        .def("due", &saber::Scheduler::due)
+        .def("load", &saber::Scheduler::load)
+        .def("save", &saber::Scheduler::save)
+        .def_static("parse_time_of_day", &saber::Scheduler::parseTimeOfDay)
+        .def_static("parse_days", &saber::Scheduler::parseDays)
+        .def_static("parse_utc_offset", &saber::Scheduler::parseUtcOffset)
+        .def_static("days_to_string", &saber::Scheduler::daysToString);
+#endif
+    
+    // Esporre i token di sicurezza
+    py::enum_<saber::TokenScope>(m, "TokenScope")
+        .value("ReadOnly", saber::TokenScope::ReadOnly)
+        .value("Admin", saber::TokenScope::Admin);
+    
+    py::class_<saber::SecurityToken>(m, "SecurityToken")
+        .def_readonly("token_id", &saber::SecurityToken::tokenId)
+        .def_readonly("node_id", &saber::SecurityToken::nodeId)
+        .def_readonly("scope", &saber::SecurityToken::scope)
+        .def_readonly("issued_at", &saber::SecurityToken::issuedAt)
+        .def_readonly("expiry", &saber::SecurityToken::expiry);
+    
+    // Esporre le chiavi di sessione
+    py::class_<saber::SessionStats>(m, "SessionStats")
+        .def_readonly("derivations", &saber::SessionStats::derivations)
+        .def_readonly("cache_hits", &saber::SessionStats::cacheHits)
+        .def_readonly("sessions", &saber::SessionStats::sessions);
+    
+    py::class_<saber::SessionManager>(m, "SessionManager")
+        .def("forget", &saber::SessionManager::forget)
+        .def("clear", &saber::SessionManager::clear)
+        .def("get_peers", &saber::SessionManager::getPeers)
+        .def("get_stats", &saber::SessionManager::getStats);
+    
+    // Esporre i contatori persistenti dei nonce
+    py::class_<saber::NonceCounterStore, std::shared_ptr<saber::NonceCounterStore>> nonceStore(m, "NonceCounterStore");
+    
+    py::class_<saber::NonceCounterStore::Reservation>(nonceStore, "Reservation")
+        .def_property_readonly("salt", [](const saber::NonceCounterStore::Reservation& self) {
+            return py::bytes(reinterpret_cast<const char*>(self.salt.data()), self.salt.size());
+        })
+        .def_readonly("first", &saber::NonceCounterStore::Reservation::first)
+        .def_readonly("limit", &saber::NonceCounterStore::Reservation::limit);
+    
+    nonceStore
+        .def(py::init<const std::string&, uint64_t>(), 
+             py::arg("path"), py::arg("block") = saber::NONCE_RESERVATION_BLOCK)
+        .def("reserve", [](saber::NonceCounterStore& self, const std::string& keyId,
+                           std::shared_ptr<saber::RandomSource> rng) {
+            return self.reserve(keyId, rng ? *rng : *saber::defaultRandomSource());
+        }, py::arg("key_id"), py::arg("rng") = nullptr)
+        .def("get_limit", &saber::NonceCounterStore::getLimit)
+        .def("get_path", &saber::NonceCounterStore::getPath);
+    m.attr("NONCE_RESERVATION_BLOCK") = saber::NONCE_RESERVATION_BLOCK;
+    
+    // Esporre MeshCrypto
+    py::class_<saber::MeshCrypto, std::shared_ptr<saber::MeshCrypto>>(m, "MeshCrypto")
+        .def(py::init<std::shared_ptr<saber::RandomSource>>(), py::arg("rng") = nullptr)
+        .def_static("with_network_key", &saber::MeshCrypto::withNetworkKey,
+                    py::arg("network_key"), py::arg("rng") = nullptr)
+        .def("set_network_key", &saber::MeshCrypto::setNetworkKey)
+        .def("get_network_key", &saber::MeshCrypto::getNetworkKey)
+        .def("get_key_epoch", &saber::MeshCrypto::getKeyEpoch)
+        .def("has_session_peer", &saber::MeshCrypto::hasSessionPeer)
+        .def("get_sessions", py::overload_cast<>(&saber::MeshCrypto::getSessions),
+             py::return_value_policy::reference_internal)
+        .def("encrypt", &saber::MeshCrypto::encrypt, py::arg("payload"), py::arg("aad") = std::vector<uint8_t>())
+        .def("decrypt", &saber::MeshCrypto::decrypt,
+             py::arg("encrypted_data"), py::arg("aad") = std::vector<uint8_t>())
+        .def("decrypt_from", &saber::MeshCrypto::decryptFrom,
+             py::arg("sender_id"), py::arg("encrypted_data"), py::arg("aad") = std::vector<uint8_t>())
+        .def("set_replay_window", &saber::MeshCrypto::setReplayWindow)
+        .def("get_replay_window", &saber::MeshCrypto::getReplayWindow)
+        .def("get_replay_rejections", &saber::MeshCrypto::getReplayRejections)
+        .def("set_nonce_store", &saber::MeshCrypto::setNonceStore)
+        .def("get_nonce_store", &saber::MeshCrypto::getNonceStore)
+        .def("set_clock", &saber::MeshCrypto::setClock)
+        .def("get_nonce_counter", &saber::MeshCrypto::getNonceCounter)
         .def("sign", &saber::MeshCrypto::sign)
         .def("verify", &saber::MeshCrypto::verify)
         .def("register_node_key", &saber::MeshCrypto::registerNodeKey)
@@ -57,15 +1079,152 @@ PYBIND11_MODULE(saber_protocol, m) {
         .def("key_exchange", &saber::MeshCrypto::keyExchange)
         .def("get_public_key", &saber::MeshCrypto::getPublicKey)
         .def("get_exchange_public_key", &saber::MeshCrypto::getExchangePublicKey)
-        .def("generate_security_token", &saber::MeshCrypto::generateSecurityToken)
-        .def("verify_security_token", &saber::MeshCrypto::verifySecurityToken);
+        .def("generate_security_token", &saber::MeshCrypto::generateSecurityToken,
+             py::arg("node_id"), py::arg("ttl_seconds"), py::arg("scope") = saber::TokenScope::Admin)
+        .def("verify_security_token", &saber::MeshCrypto::verifySecurityToken)
+        .def_static("decode_security_token", &saber::MeshCrypto::decodeSecurityToken)
+        .def("revoke_security_token", &saber::MeshCrypto::revokeSecurityToken)
+        .def("is_token_revoked", &saber::MeshCrypto::isTokenRevoked)
+        .def("set_revocation_list", &saber::MeshCrypto::setRevocationList)
+        .def("get_revocation_list", &saber::MeshCrypto::getRevocationList)
+        .def("set_security_event_handler", &saber::MeshCrypto::setSecurityEventHandler)
+        .def_static("key_id", &saber::MeshCrypto::keyId)
+        .def("is_quarantined", &saber::MeshCrypto::isQuarantined)
+        .def("get_quarantined_nodes", &saber::MeshCrypto::getQuarantinedNodes)
+        .def("get_conflicting_keys", &saber::MeshCrypto::getConflictingKeys)
+        .def("resolve_key_conflict", &saber::MeshCrypto::resolveKeyConflict)
+        .def("quarantine_node", &saber::MeshCrypto::quarantineNode)
+        .def("release_quarantine", &saber::MeshCrypto::releaseQuarantine)
+        // Varianti per bytes: chi passa bytes riceve bytes, le liste continuano a funzionare come prima
+        .def("encrypt", [](saber::MeshCrypto& self, const py::bytes& payload, const py::bytes& aad) {
+            return toBytes(self.encrypt(fromBytes(payload), fromBytes(aad)));
+        }, py::arg("payload"), py::arg("aad") = py::bytes())
+        .def("decrypt", [](saber::MeshCrypto& self, const py::bytes& encryptedData, const py::bytes& aad) {
+            return toBytes(self.decrypt(fromBytes(encryptedData), fromBytes(aad)));
+        }, py::arg("encrypted_data"), py::arg("aad") = py::bytes())
+        .def("decrypt_from", [](saber::MeshCrypto& self, const std::string& senderId,
+                                const py::bytes& encryptedData, const py::bytes& aad) {
+            return toBytes(self.decryptFrom(senderId, fromBytes(encryptedData), fromBytes(aad)));
+        }, py::arg("sender_id"), py::arg("encrypted_data"), py::arg("aad") = py::bytes())
+        .def("session_key", [](saber::MeshCrypto& self, const std::string& nodeId) {
+            return toBytes(self.sessionKey(nodeId));
+        }, py::arg("node_id"))
+        .def("encrypt_for", [](saber::MeshCrypto& self, const std::string& nodeId, const py::bytes& payload,
+                               const py::bytes& aad) {
+            return toBytes(self.encryptFor(nodeId, fromBytes(payload), fromBytes(aad)));
+        }, py::arg("node_id"), py::arg("payload"), py::arg("aad") = py::bytes())
+        .def("decrypt_from_peer", [](saber::MeshCrypto& self, const std::string& nodeId,
+                                     const py::bytes& encryptedData, const py::bytes& aad) {
+            return toBytes(self.decryptFromPeer(nodeId, fromBytes(encryptedData), fromBytes(aad)));
+        }, py::arg("node_id"), py::arg("encrypted_data"), py::arg("aad") = py::bytes())
+        .def("sign", [](saber::MeshCrypto& self, const py::bytes& message) {
+            return toBytes(self.sign(fromBytes(message)));
+        }, py::arg("message"))
+        .def("verify", [](saber::MeshCrypto& self, const std::string& nodeId, const py::bytes& message,
+                          const py::bytes& signature) {
+            return self.verify(nodeId, fromBytes(message), fromBytes(signature));
+        }, py::arg("node_id"), py::arg("message"), py::arg("signature"))
+        .def("register_node_key", [](saber::MeshCrypto& self, const std::string& nodeId, const py::bytes& publicKey) {
+            self.registerNodeKey(nodeId, fromBytes(publicKey));
+        }, py::arg("node_id"), py::arg("public_key"))
+        .def("hash", [](saber::MeshCrypto& self, const py::bytes& data) {
+            return toBytes(self.hash(fromBytes(data)));
+        }, py::arg("data"))
+        .def("key_exchange", [](saber::MeshCrypto& self, const py::bytes& peerPublic) {
+            return toBytes(self.keyExchange(fromBytes(peerPublic)));
+        }, py::arg("peer_public"))
+        .def("verify_security_token", [](saber::MeshCrypto& self, const py::bytes& token) {
+            return self.verifySecurityToken(fromBytes(token));
+        }, py::arg("token"))
+        .def_static("decode_security_token", [](const py::bytes& payload) {
+            return saber::MeshCrypto::decodeSecurityToken(fromBytes(payload));
+        }, py::arg("payload"))
+        .def("export_public_keys", [](const saber::MeshCrypto& self) {
+            py::dict nodes;
+            for (const auto& entry : self.getKnownPublicKeys()) {
+                nodes[py::str(entry.first)] = toBytes(entry.second);
+            }
+            py::dict keys;
+            keys["public_key"] = toBytes(self.getPublicKey());
+            keys["exchange_public_key"] = toBytes(self.getExchangePublicKey());
+            keys["nodes"] = nodes;
+            return keys;
+        });
+    
+    // Esporre il rilevatore di intrusioni
+    py::class_<saber::IntrusionConfig>(m, "IntrusionConfig")
+        .def(py::init<>())
+        .def_readwrite("enabled", &saber::IntrusionConfig::enabled)
+        .def_readwrite("window_ms", &saber::IntrusionConfig::windowMs)
+        .def_readwrite("beacon_threshold", &saber::IntrusionConfig::beaconThreshold)
+        .def_readwrite("signature_failure_threshold", &saber::IntrusionConfig::signatureFailureThreshold)
+        .def_readwrite("replay_threshold", &saber::IntrusionConfig::replayThreshold)
+        .def_readwrite("route_flap_threshold", &saber::IntrusionConfig::routeFlapThreshold)
+        .def_readwrite("auto_quarantine", &saber::IntrusionConfig::autoQuarantine);
+    
+    py::class_<saber::IntrusionAlert> intrusionAlert(m, "IntrusionAlert");
+    py::enum_<saber::IntrusionAlert::Type>(intrusionAlert, "Type")
+        .value("RogueBeacon", saber::IntrusionAlert::Type::RogueBeacon)
+        .value("SignatureBurst", saber::IntrusionAlert::Type::SignatureBurst)
+        .value("NonceReuse", saber::IntrusionAlert::Type::NonceReuse)
+        .value("RouteFlapping", saber::IntrusionAlert::Type::RouteFlapping);
+    intrusionAlert
+        .def_readonly("type", &saber::IntrusionAlert::type)
+        .def_readonly("node_id", &saber::IntrusionAlert::nodeId)
+        .def_readonly("count", &saber::IntrusionAlert::count)
+        .def_readonly("timestamp", &saber::IntrusionAlert::timestamp)
+        .def_readonly("quarantine", &saber::IntrusionAlert::quarantine);
+    
+    py::class_<saber::IntrusionDetector>(m, "IntrusionDetector")
+        .def(py::init<const saber::IntrusionConfig&>(), py::arg("config") = saber::IntrusionConfig())
+        .def("observe", &saber::IntrusionDetector::observe)
+        .def("set_alert_handler", &saber::IntrusionDetector::setAlertHandler)
+        .def("set_config", &saber::IntrusionDetector::setConfig)
+        .def("get_config", &saber::IntrusionDetector::getConfig)
+        .def("get_alerts", &saber::IntrusionDetector::getAlerts);
+    
+    // Esporre ClockStats
+    py::class_<saber::ClockStats>(m, "ClockStats")
+        .def(py::init<>())
+        .def_readonly("offset_us", &saber::ClockStats::offsetUs)
+        .def_readonly("estimated_offset_us", &saber::ClockStats::estimatedOffsetUs)
+        .def_readonly("skew_ppm", &saber::ClockStats::skewPpm)
+        .def_readonly("updates", &saber::ClockStats::updates)
+        .def_readonly("steps", &saber::ClockStats::steps);
+    
+    // Esporre ClockDiscipline
+    py::class_<saber::ClockDiscipline>(m, "ClockDiscipline")
+        .def(py::init<int64_t, uint32_t>(), py::arg("step_threshold_us") = 100000, py::arg("max_slew_ppm") = 500)
+        .def("update", &saber::ClockDiscipline::update, py::arg("local_us"), py::arg("measured_offset_us"))
+        .def("step", &saber::ClockDiscipline::step, py::arg("local_us"), py::arg("offset_us"))
+        .def("offset_at", &saber::ClockDiscipline::offsetAt, py::arg("local_us"))
+        .def("stats", &saber::ClockDiscipline::stats, py::arg("local_us"))
+        .def("is_locked", &saber::ClockDiscipline::isLocked)
+        .def("reset", &saber::ClockDiscipline::reset);
+    
+    // Esporre TimeExchange
+    py::class_<saber::TimeExchange>(m, "TimeExchange")
+        .def(py::init<>())
+        .def_readonly("offset_us", &saber::TimeExchange::offsetUs)
+        .def_readonly("round_trip_us", &saber::TimeExchange::roundTripUs);
     
     // Esporre SyncManager
     py::class_<saber::SyncManager, std::shared_ptr<saber::SyncManager>>(m, "SyncManager")
         .def(py::init<>())
+        .def(py::init<const saber::spec::Parameters&>(), py::arg("params"))
+        .def("get_parameters", &saber::SyncManager::getParameters)
         .def("now", &saber::SyncManager::now)
-        .def("handle_time_beacon", &saber::SyncManager::handleTimeBeacon)
+        .def("now_us", &saber::SyncManager::nowUs)
+        .def("local_us", &saber::SyncManager::localUs)
+        .def("handle_time_beacon", &saber::SyncManager::handleTimeBeacon,
+             py::arg("master_time"), py::arg("relay_delay_us") = 0)
+        .def("handle_time_exchange", &saber::SyncManager::handleTimeExchange, py::arg("master_id"),
+             py::arg("sent_us"), py::arg("master_received_us"), py::arg("master_replied_us"), py::arg("received_us"))
+        .def("get_last_exchange", &saber::SyncManager::getLastExchange)
+        .def("get_clock_stats", &saber::SyncManager::getClockStats)
         .def("is_synchronized", &saber::SyncManager::isSynchronized)
+        .def("check_beacon_timeout", &saber::SyncManager::checkBeaconTimeout)
+        .def("set_sync_state_handler", &saber::SyncManager::setSyncStateHandler)
         .def("update_node_latency", &saber::SyncManager::updateNodeLatency)
         .def("get_average_latency", &saber::SyncManager::getAverageLatency)
         .def("is_node_out_of_sync", &saber::SyncManager::isNodeOutOfSync)
@@ -73,52 +1232,1373 @@ PYBIND11_MODULE(saber_protocol, m) {
         .def("get_optimal_buffer_size", &saber::SyncManager::getOptimalBufferSize)
         .def("emergency_sync", &saber::SyncManager::emergencySync);
     
+    // Esporre AudioFrame
+    py::class_<saber::AudioFrame>(m, "AudioFrame")
+        .def(py::init<>())
+        .def_readwrite("playout_time_us", &saber::AudioFrame::playoutTimeUs)
+        .def_readwrite("sample_rate", &saber::AudioFrame::sampleRate)
+        .def_readwrite("channels", &saber::AudioFrame::channels)
+        .def_readwrite("samples", &saber::AudioFrame::samples)
+        .def("samples_per_channel", &saber::AudioFrame::samplesPerChannel);
+    
+    m.def("lc3_available", &saber::lc3Available);
+    m.def("apply_volume", &saber::applyVolume);
+    
+    // Esporre le sorgenti audio del Master
+    m.attr("DEFAULT_SOURCE_BUFFER_MS") = saber::DEFAULT_SOURCE_BUFFER_MS;
+    
+    py::class_<saber::AudioSource, std::shared_ptr<saber::AudioSource>>(m, "AudioSource")
+        .def("get_sample_rate", &saber::AudioSource::getSampleRate)
+        .def("get_channels", &saber::AudioSource::getChannels)
+        .def("next_frame", &saber::AudioSource::nextFrame)
+        .def("is_finished", &saber::AudioSource::isFinished);
+    
+    py::class_<saber::PcmFileSource, saber::AudioSource, std::shared_ptr<saber::PcmFileSource>>(m, "PcmFileSource")
+        .def(py::init<const std::string&, uint32_t, uint8_t, bool>(),
+             py::arg("path"), py::arg("sample_rate"), py::arg("channels"), py::arg("loop") = false)
+        .def_static("from_wav", &saber::PcmFileSource::fromWav, py::arg("path"), py::arg("loop") = false);
+    
+    py::class_<saber::RingBufferSource, saber::AudioSource, std::shared_ptr<saber::RingBufferSource>>(
+            m, "RingBufferSource")
+        .def(py::init<uint32_t, uint8_t, uint32_t>(),
+             py::arg("sample_rate"), py::arg("channels"), py::arg("capacity_ms") = saber::DEFAULT_SOURCE_BUFFER_MS)
+        .def("push", &saber::RingBufferSource::push)
+        .def("push", [](saber::RingBufferSource& self, const py::bytes& pcm) {
+            // Campioni a 16 bit little-endian, come in un file PCM grezzo
+            std::vector<uint8_t> data = fromBytes(pcm);
+            if (data.size() % 2 != 0) {
+                throw std::invalid_argument("i campioni PCM occupano due byte ciascuno");
+            }
+            std::vector<int16_t> samples(data.size() / 2);
+            for (size_t i = 0; i < samples.size(); ++i) {
+                samples[i] = static_cast<int16_t>(data[2 * i] | data[2 * i + 1] << 8);
+            }
+            return self.push(samples);
+        })
+        .def("available", &saber::RingBufferSource::available)
+        .def("get_dropped", &saber::RingBufferSource::getDropped)
+        .def("clear", &saber::RingBufferSource::clear)
+        .def("close", &saber::RingBufferSource::close);
+    
+    py::class_<saber::AudioSourceStats>(m, "AudioSourceStats")
+        .def_readonly("frames_sent", &saber::AudioSourceStats::framesSent)
+        .def_readonly("underruns", &saber::AudioSourceStats::underruns)
+        .def_readonly("finished", &saber::AudioSourceStats::finished);
+    
+    // Esporre le uscite audio dei sink
+    py::class_<saber::AudioOutput, std::shared_ptr<saber::AudioOutput>>(m, "AudioOutput")
+        .def("write", &saber::AudioOutput::write)
+        .def("get_latency_us", &saber::AudioOutput::getLatencyUs)
+        .def("flush", &saber::AudioOutput::flush);
+    
+    py::class_<saber::NullAudioOutput, saber::AudioOutput, std::shared_ptr<saber::NullAudioOutput>>(
+            m, "NullAudioOutput")
+        .def(py::init<>())
+        .def("get_frames_written", &saber::NullAudioOutput::getFramesWritten)
+        .def("get_last_playout_time_us", &saber::NullAudioOutput::getLastPlayoutTimeUs);
+    
+    py::class_<saber::WavFileOutput, saber::AudioOutput, std::shared_ptr<saber::WavFileOutput>>(m, "WavFileOutput")
+        .def(py::init<const std::string&>(), py::arg("path"))
+        .def("get_samples_written", &saber::WavFileOutput::getSamplesWritten)
+        .def("get_frames_rejected", &saber::WavFileOutput::getFramesRejected);
+    
+    py::class_<saber::AudioOutputStats>(m, "AudioOutputStats")
+        .def_readonly("frames_played", &saber::AudioOutputStats::framesPlayed)
+        .def_readonly("frames_late", &saber::AudioOutputStats::framesLate)
+        .def_readonly("frames_dropped", &saber::AudioOutputStats::framesDropped)
+        .def_readonly("queued", &saber::AudioOutputStats::queued);
+    
+    // Esporre il profilo audio
+    py::enum_<saber::AudioProfile>(m, "AudioProfile")
+        .value("Music", saber::AudioProfile::Music)
+        .value("Voice", saber::AudioProfile::Voice);
+    
+    // Esporre il formato degli stream
+    py::class_<saber::StreamFormat>(m, "StreamFormat")
+        .def(py::init<>())
+        .def_readwrite("sample_rate_hz", &saber::StreamFormat::sampleRateHz)
+        .def_readwrite("channels", &saber::StreamFormat::channels)
+        .def_readwrite("bitrate_kbps", &saber::StreamFormat::bitrateKbps);
+    
+    // Esporre il buffer di jitter adattivo
+    py::class_<saber::JitterBufferConfig>(m, "JitterBufferConfig")
+        .def(py::init<>())
+        .def_readwrite("adaptive", &saber::JitterBufferConfig::adaptive)
+        .def_readwrite("min_ms", &saber::JitterBufferConfig::minMs)
+        .def_readwrite("max_ms", &saber::JitterBufferConfig::maxMs)
+        .def_readwrite("step_ms", &saber::JitterBufferConfig::stepMs)
+        .def_readwrite("shrink_after_ms", &saber::JitterBufferConfig::shrinkAfterMs)
+        .def_readwrite("max_loss_percent", &saber::JitterBufferConfig::maxLossPercent);
+    
+    py::class_<saber::JitterBufferStats>(m, "JitterBufferStats")
+        .def_readonly("buffer_ms", &saber::JitterBufferStats::bufferMs)
+        .def_readonly("jitter_ms", &saber::JitterBufferStats::jitterMs)
+        .def_readonly("lead_ms", &saber::JitterBufferStats::leadMs)
+        .def_readonly("loss_percent", &saber::JitterBufferStats::lossPercent)
+        .def_readonly("frames", &saber::JitterBufferStats::frames)
+        .def_readonly("lost", &saber::JitterBufferStats::lost)
+        .def_readonly("underruns", &saber::JitterBufferStats::underruns)
+        .def_readonly("overruns", &saber::JitterBufferStats::overruns)
+        .def_readonly("resizes", &saber::JitterBufferStats::resizes);
+    
+    py::class_<saber::AdaptiveJitterBuffer>(m, "AdaptiveJitterBuffer")
+        .def(py::init<const saber::JitterBufferConfig&, uint32_t>(),
+             py::arg("config") = saber::JitterBufferConfig(), py::arg("buffer_ms") = saber::spec::DEFAULT_BUFFER_MS)
+        .def("set_config", &saber::AdaptiveJitterBuffer::setConfig)
+        .def("get_config", &saber::AdaptiveJitterBuffer::getConfig)
+        .def("reset", &saber::AdaptiveJitterBuffer::reset, py::arg("buffer_ms"), py::arg("pinned") = false)
+        .def("record_frame", &saber::AdaptiveJitterBuffer::recordFrame, py::arg("stream_id"), py::arg("sequence"),
+             py::arg("playout_time_us"), py::arg("arrival_us"))
+        .def("get_buffer_ms", &saber::AdaptiveJitterBuffer::getBufferMs)
+        .def("get_stats", &saber::AdaptiveJitterBuffer::getStats);
+    
     // Esporre AudioSync
     py::class_<saber::AudioSync>(m, "AudioSync")
-        .def(py::init<std::shared_ptr<saber::SyncManager>, bool>())
+        .def(py::init<std::shared_ptr<saber::SyncManager>, bool, uint8_t>(),
+             py::arg("sync_manager"), py::arg("is_music"), py::arg("channels") = 2)
         .def("start_playback", &saber::AudioSync::startPlayback)
         .def("stop_playback", &saber::AudioSync::stopPlayback)
         .def("adjust_bitrate", &saber::AudioSync::adjustBitrate)
         .def("get_current_latency", &saber::AudioSync::getCurrentLatency)
-        .def("is_playback_synchronized", &saber::AudioSync::isPlaybackSynchronized);
+        .def("is_playback_synchronized", &saber::AudioSync::isPlaybackSynchronized)
+        .def("handle_sync_state", &saber::AudioSync::handleSyncState)
+        .def("is_paused_for_sync", &saber::AudioSync::isPausedForSync)
+        .def("pause_playback", &saber::AudioSync::pausePlayback)
+        .def("resume_playback", &saber::AudioSync::resumePlayback)
+        .def("is_paused", &saber::AudioSync::isPaused)
+        .def("set_volume", &saber::AudioSync::setVolume)
+        .def("get_volume", &saber::AudioSync::getVolume)
+        .def("set_jitter_buffer_config", &saber::AudioSync::setJitterBufferConfig)
+        .def("record_frame", &saber::AudioSync::recordFrame, py::arg("stream_id"), py::arg("sequence"),
+             py::arg("playout_time_us"), py::arg("arrival_us"))
+        .def("get_jitter_buffer", &saber::AudioSync::getJitterBuffer)
+        .def("get_jitter_buffer_stats", &saber::AudioSync::getJitterBufferStats)
+        .def("set_stream_format", &saber::AudioSync::setStreamFormat)
+        .def("get_stream_format", &saber::AudioSync::getStreamFormat)
+        .def("set_stream_bitrate", &saber::AudioSync::setStreamBitrate)
+        .def("set_music_mode", &saber::AudioSync::setMusicMode)
+        .def("encode_frame", [](saber::AudioSync& self, const saber::AudioFrame& frame, saber::StreamId streamId) {
+            auto payload = self.encodeFrame(frame, streamId);
+            return py::bytes(reinterpret_cast<const char*>(payload.data()), payload.size());
+        }, py::arg("frame"), py::arg("stream_id") = 0)
+        .def("decode_frame", [](saber::AudioSync& self, const py::bytes& payload, uint64_t playoutTimeUs,
+                                saber::StreamId streamId) {
+            std::string data = payload;
+            return self.decodeFrame(std::vector<uint8_t>(data.begin(), data.end()), playoutTimeUs, streamId);
+        }, py::arg("payload"), py::arg("playout_time_us"), py::arg("stream_id") = 0)
+        .def("conceal_frame", &saber::AudioSync::concealFrame,
+             py::arg("playout_time_us"), py::arg("stream_id") = 0)
+        .def("get_sample_rate", &saber::AudioSync::getSampleRate)
+        .def("get_bitrate", &saber::AudioSync::getBitrate);
+    
+    // Esporre DistributionTree
+    py::class_<saber::DistributionTree>(m, "DistributionTree")
+        .def_readonly("stream_id", &saber::DistributionTree::streamId)
+        .def_readonly("root", &saber::DistributionTree::root)
+        .def_readonly("children", &saber::DistributionTree::children);
+    
+    // Esporre ProtocolState
+    py::enum_<saber::ProtocolState>(m, "ProtocolState")
+        .value("Stopped", saber::ProtocolState::Stopped)
+        .value("Initializing", saber::ProtocolState::Initializing)
+        .value("Running", saber::ProtocolState::Running)
+        .value("ShuttingDown", saber::ProtocolState::ShuttingDown);
+    
+    // Esporre i modi di rispetto del budget di latenza
+    py::enum_<saber::LatencyMode>(m, "LatencyMode")
+        .value("BestEffort", saber::LatencyMode::BestEffort)
+        .value("Strict", saber::LatencyMode::Strict);
+    
+    m.def("latency_mode_to_string", &saber::latencyModeToString);
+    m.def("latency_mode_from_string", &saber::latencyModeFromString);
+    
+    // Esporre GroupSplitSuggestion
+    py::class_<saber::GroupSplitSuggestion>(m, "GroupSplitSuggestion")
+        .def_readonly("zone", &saber::GroupSplitSuggestion::zone)
+        .def_readonly("node_id", &saber::GroupSplitSuggestion::nodeId)
+        .def_readonly("suggested_zone", &saber::GroupSplitSuggestion::suggestedZone)
+        .def_readonly("zone_buffer_ms", &saber::GroupSplitSuggestion::zoneBufferMs)
+        .def_readonly("buffer_without_node_ms", &saber::GroupSplitSuggestion::bufferWithoutNodeMs)
+        .def_readonly("node_buffer_ms", &saber::GroupSplitSuggestion::nodeBufferMs);
+    
+    // Esporre LatencyPlanner
+    py::class_<saber::LatencyPlanner>(m, "LatencyPlanner")
+        .def(py::init<uint32_t, uint32_t, saber::LatencyMode>(),
+             py::arg("budget_ms") = saber::spec::LATENCY_BUDGET_MS,
+             py::arg("margin_ms") = saber::spec::BUFFER_MARGIN_MS,
+             py::arg("mode") = saber::LatencyMode::BestEffort)
+        .def("required_buffer_ms", &saber::LatencyPlanner::requiredBufferMs)
+        .def("rejected_sinks", &saber::LatencyPlanner::rejectedSinks)
+        .def("zone_buffer_ms", &saber::LatencyPlanner::zoneBufferMs)
+        .def("suggest_splits", &saber::LatencyPlanner::suggestSplits)
+        .def("get_budget_ms", &saber::LatencyPlanner::getBudgetMs)
+        .def("get_mode", &saber::LatencyPlanner::getMode);
+    
+    // Esporre l'equalizzazione dei ritardi tra i sink
+    py::class_<saber::DelayEqualizationConfig>(m, "DelayEqualizationConfig")
+        .def(py::init<>())
+        .def_readwrite("enabled", &saber::DelayEqualizationConfig::enabled)
+        .def_readwrite("hold_ms", &saber::DelayEqualizationConfig::holdMs);
+    
+    py::class_<saber::DelayEqualizer>(m, "DelayEqualizer")
+        .def(py::init<const saber::DelayEqualizationConfig&>(),
+             py::arg("config") = saber::DelayEqualizationConfig())
+        .def("set_config", &saber::DelayEqualizer::setConfig)
+        .def("get_config", &saber::DelayEqualizer::getConfig)
+        .def("update", &saber::DelayEqualizer::update, py::arg("required_ms"), py::arg("now_ms"))
+        .def("get_deadline_ms", &saber::DelayEqualizer::getDeadlineMs);
+    
+    py::class_<saber::DelayEqualizationStatus>(m, "DelayEqualizationStatus")
+        .def_readonly("deadline_ms", &saber::DelayEqualizationStatus::deadlineMs)
+        .def_readonly("slowest_sink", &saber::DelayEqualizationStatus::slowestSink)
+        .def_readonly("sink_delays_ms", &saber::DelayEqualizationStatus::sinkDelaysMs)
+        .def_readonly("latency_ms", &saber::DelayEqualizationStatus::latencyMs)
+        .def_readonly("local_delay_ms", &saber::DelayEqualizationStatus::localDelayMs);
+    
+    // Esporre EncryptionGranularity
+    py::enum_<saber::EncryptionGranularity>(m, "EncryptionGranularity")
+        .value("PER_FRAME", saber::EncryptionGranularity::PerFrame)
+        .value("PER_TRANSPORT_FRAME", saber::EncryptionGranularity::PerTransportFrame)
+        .export_values();
+    
+    m.attr("CAP_ENCRYPT_PER_FRAME") = saber::CAP_ENCRYPT_PER_FRAME;
+    m.attr("CAP_ENCRYPT_TRANSPORT") = saber::CAP_ENCRYPT_TRANSPORT;
+    m.attr("DEFAULT_REPLAY_WINDOW") = saber::DEFAULT_REPLAY_WINDOW;
+    m.def("negotiate_granularity", &saber::negotiateGranularity);
+    
+    // Esporre AudioFrameSealer
+    py::class_<saber::AudioFrameSealer>(m, "AudioFrameSealer")
+        .def(py::init<std::shared_ptr<saber::MeshCrypto>, saber::EncryptionGranularity>())
+        .def("seal", &saber::AudioFrameSealer::seal)
+        .def("open", &saber::AudioFrameSealer::open, py::arg("transport_frame"), py::arg("sender_id") = "")
+        .def("get_granularity", &saber::AudioFrameSealer::getGranularity)
+        .def_static("overhead_bytes", &saber::AudioFrameSealer::overheadBytes);
+    
+    // Esporre il formato dei frame audio cifrati e il riordino sul sink
+    m.attr("AUDIO_PACKET_VERSION") = saber::AUDIO_PACKET_VERSION;
+    m.attr("AUDIO_PACKET_HEADER_BYTES") = saber::AUDIO_PACKET_HEADER_BYTES;
+    m.attr("DEFAULT_REORDER_DEPTH") = saber::DEFAULT_REORDER_DEPTH;
+    
+    py::class_<saber::AudioPacket>(m, "AudioPacket")
+        .def(py::init([](saber::StreamId streamId, uint32_t sequence, uint64_t presentationTimeUs,
+                         const py::bytes& payload) {
+            return saber::AudioPacket{streamId, sequence, presentationTimeUs, fromBytes(payload)};
+        }), py::arg("stream_id") = 0, py::arg("sequence") = 0, py::arg("presentation_time_us") = 0,
+            py::arg("payload") = py::bytes())
+        .def_readwrite("stream_id", &saber::AudioPacket::streamId)
+        .def_readwrite("sequence", &saber::AudioPacket::sequence)
+        .def_readwrite("presentation_time_us", &saber::AudioPacket::presentationTimeUs)
+        .def_property("payload",
+            [](const saber::AudioPacket& self) { return toBytes(self.payload); },
+            [](saber::AudioPacket& self, const py::bytes& payload) { self.payload = fromBytes(payload); })
+        .def("seal", [](const saber::AudioPacket& self, saber::MeshCrypto& crypto) {
+            return toBytes(self.seal(crypto));
+        })
+        .def_static("open", [](const py::bytes& data, saber::MeshCrypto& crypto, const std::string& senderId) {
+            return saber::AudioPacket::open(fromBytes(data), crypto, senderId);
+        }, py::arg("data"), py::arg("crypto"), py::arg("sender_id") = "")
+        .def_static("peek_header", [](const py::bytes& data) {
+            return saber::AudioPacket::peekHeader(fromBytes(data));
+        });
+    
+    py::class_<saber::AudioReorderStats>(m, "AudioReorderStats")
+        .def_readonly("delivered", &saber::AudioReorderStats::delivered)
+        .def_readonly("reordered", &saber::AudioReorderStats::reordered)
+        .def_readonly("lost", &saber::AudioReorderStats::lost)
+        .def_readonly("concealed", &saber::AudioReorderStats::concealed)
+        .def_readonly("late", &saber::AudioReorderStats::late)
+        .def_readonly("duplicates", &saber::AudioReorderStats::duplicates);
+    
+    py::class_<saber::AudioPacketReorderer>(m, "AudioPacketReorderer")
+        .def(py::init<saber::AudioPacketReorderer::PacketHandler, saber::AudioPacketReorderer::LossHandler, size_t>(),
+             py::arg("on_packet"), py::arg("on_loss") = nullptr, py::arg("depth") = saber::DEFAULT_REORDER_DEPTH)
+        .def("push", &saber::AudioPacketReorderer::push)
+        .def("flush", &saber::AudioPacketReorderer::flush)
+        .def("reset", &saber::AudioPacketReorderer::reset)
+        .def("get_stats", &saber::AudioPacketReorderer::getStats);
+    
+    // Esporre i parametri di specifica
+    py::class_<saber::spec::Parameters>(m, "SpecParameters")
+        .def(py::init<>())
+        .def_readwrite("jitter_tolerance_ms", &saber::spec::Parameters::jitterToleranceMs)
+        .def_readwrite("latency_budget_ms", &saber::spec::Parameters::latencyBudgetMs)
+        .def_readwrite("buffer_margin_ms", &saber::spec::Parameters::bufferMarginMs)
+        .def_readwrite("default_buffer_ms", &saber::spec::Parameters::defaultBufferMs)
+        .def_readwrite("min_buffer_ms", &saber::spec::Parameters::minBufferMs)
+        .def_readwrite("beacon_timeout_ms", &saber::spec::Parameters::beaconTimeoutMs)
+        .def_readwrite("clock_step_threshold_ms", &saber::spec::Parameters::clockStepThresholdMs)
+        .def_readwrite("clock_max_slew_ppm", &saber::spec::Parameters::clockMaxSlewPpm)
+        .def_readwrite("latency_mode", &saber::spec::Parameters::latencyMode)
+        .def("deviations", &saber::spec::Parameters::deviations)
+        .def("validate", &saber::spec::Parameters::validate);
+    
+    m.attr("SPEC_BEACON_INTERVAL_MS") = saber::spec::BEACON_INTERVAL_MS;
+    m.attr("SPEC_JITTER_TOLERANCE_MS") = saber::spec::JITTER_TOLERANCE_MS;
+    m.attr("SPEC_LATENCY_BUDGET_MS") = saber::spec::LATENCY_BUDGET_MS;
+    m.attr("SPEC_SAMPLE_RATE_MUSIC_HZ") = saber::spec::SAMPLE_RATE_MUSIC_HZ;
+    m.attr("SPEC_SAMPLE_RATE_VOICE_HZ") = saber::spec::SAMPLE_RATE_VOICE_HZ;
+    m.attr("SPEC_BITRATE_MUSIC_KBPS") = saber::spec::BITRATE_MUSIC_KBPS;
+    m.attr("SPEC_BITRATE_VOICE_KBPS") = saber::spec::BITRATE_VOICE_KBPS;
+    
+    // Esporre la contabilità della banda
+    py::class_<saber::BandwidthUsage>(m, "BandwidthUsage")
+        .def_readonly("bytes", &saber::BandwidthUsage::bytes)
+        .def_readonly("packets", &saber::BandwidthUsage::packets)
+        .def_readonly("airtime_us", &saber::BandwidthUsage::airtimeUs)
+        .def_readonly("bytes_per_second", &saber::BandwidthUsage::bytesPerSecond)
+        .def_readonly("airtime_fraction", &saber::BandwidthUsage::airtimeFraction);
+    
+    py::class_<saber::BandwidthReport>(m, "BandwidthReport")
+        .def_readonly("window_ms", &saber::BandwidthReport::windowMs)
+        .def_readonly("streams", &saber::BandwidthReport::streams)
+        .def_readonly("neighbors", &saber::BandwidthReport::neighbors)
+        .def_readonly("zones", &saber::BandwidthReport::zones)
+        .def_readonly("total", &saber::BandwidthReport::total);
+    
+    py::class_<saber::MeshStats>(m, "MeshStats")
+        .def(py::init<uint32_t, uint32_t, uint32_t>(),
+             py::arg("window_ms") = 5000, py::arg("link_rate_kbps") = 2000, py::arg("packet_overhead_bytes") = 16)
+        .def("record_received", &saber::MeshStats::recordReceived)
+        .def("record_sent", &saber::MeshStats::recordSent)
+        .def("record_stream", &saber::MeshStats::recordStream)
+        .def("report", &saber::MeshStats::report)
+        .def("airtime_utilization", &saber::MeshStats::airtimeUtilization)
+        .def_static("to_prometheus", &saber::MeshStats::toPrometheus);
+    
+    // Esporre le statistiche di latenza e perdita
+    py::class_<saber::NodeNetworkStats>(m, "NodeNetworkStats")
+        .def_readonly("samples", &saber::NodeNetworkStats::samples)
+        .def_readonly("mean_latency_ms", &saber::NodeNetworkStats::meanLatencyMs)
+        .def_readonly("p50_latency_ms", &saber::NodeNetworkStats::p50LatencyMs)
+        .def_readonly("p95_latency_ms", &saber::NodeNetworkStats::p95LatencyMs)
+        .def_readonly("p99_latency_ms", &saber::NodeNetworkStats::p99LatencyMs)
+        .def_readonly("max_latency_ms", &saber::NodeNetworkStats::maxLatencyMs)
+        .def_readonly("jitter_variance_ms2", &saber::NodeNetworkStats::jitterVarianceMs2)
+        .def_readonly("histogram", &saber::NodeNetworkStats::histogram)
+        .def_readonly("frames_received", &saber::NodeNetworkStats::framesReceived)
+        .def_readonly("frames_lost", &saber::NodeNetworkStats::framesLost)
+        .def_readonly("loss_rate", &saber::NodeNetworkStats::lossRate);
+    
+    py::class_<saber::NetworkStats>(m, "NetworkStats")
+        .def_readonly("window_samples", &saber::NetworkStats::windowSamples)
+        .def_readonly("bucket_ms", &saber::NetworkStats::bucketMs)
+        .def_readonly("nodes", &saber::NetworkStats::nodes)
+        .def_readonly("overall", &saber::NetworkStats::overall);
+    
+    py::class_<saber::LatencyStats>(m, "LatencyStats")
+        .def(py::init<size_t, uint32_t>(), py::arg("window_samples") = 256, py::arg("bucket_ms") = 5)
+        .def("record_latency", &saber::LatencyStats::recordLatency)
+        .def("record_frame", &saber::LatencyStats::recordFrame)
+        .def("reset", &saber::LatencyStats::reset)
+        .def("report", &saber::LatencyStats::report)
+        .def_static("to_prometheus", &saber::LatencyStats::toPrometheus);
+    
+    // Esporre il documento di stato per migrare un Master
+    m.attr("NODE_STATE_VERSION") = saber::NODE_STATE_VERSION;
+    
+    py::class_<saber::PeerState>(m, "PeerState")
+        .def(py::init<>())
+        .def_readwrite("node_id", &saber::PeerState::nodeId)
+        .def_readwrite("role", &saber::PeerState::role)
+        .def_readwrite("public_key", &saber::PeerState::publicKey);
+    
+    py::class_<saber::NodeState>(m, "NodeState")
+        .def(py::init<>())
+        .def_readwrite("version", &saber::NodeState::version)
+        .def_readwrite("exported_at_ms", &saber::NodeState::exportedAtMs)
+        .def_readwrite("node_id", &saber::NodeState::nodeId)
+        .def_readwrite("role", &saber::NodeState::role)
+        .def_readwrite("public_key", &saber::NodeState::publicKey)
+        .def_readwrite("profile", &saber::NodeState::profile)
+        .def_readwrite("latency_mode", &saber::NodeState::latencyMode)
+        .def_readwrite("published_streams", &saber::NodeState::publishedStreams)
+        .def_readwrite("listen_streams", &saber::NodeState::listenStreams)
+        .def_readwrite("voice_streams", &saber::NodeState::voiceStreams)
+        .def_readwrite("keystore_file", &saber::NodeState::keystoreFile)
+        .def_readwrite("secrets", &saber::NodeState::secrets)
+        .def_readwrite("zones", &saber::NodeState::zones)
+        .def_readwrite("peers", &saber::NodeState::peers);
+    
+    m.def("encode_node_state", &saber::encodeNodeState);
+    m.def("decode_node_state", &saber::decodeNodeState);
+    
+    // Esporre l'archivio dello stato persistito
+    m.attr("DEFAULT_STATE_COMPACT_RECORDS") = saber::DEFAULT_STATE_COMPACT_RECORDS;
+    
+    py::class_<saber::StateRecoveryReport>(m, "StateRecoveryReport")
+        .def_readonly("snapshot_loaded", &saber::StateRecoveryReport::snapshotLoaded)
+        .def_readonly("from_backup", &saber::StateRecoveryReport::fromBackup)
+        .def_readonly("replayed_records", &saber::StateRecoveryReport::replayedRecords)
+        .def_readonly("discarded_bytes", &saber::StateRecoveryReport::discardedBytes)
+        .def_readonly("entries", &saber::StateRecoveryReport::entries);
+    
+    py::class_<saber::StateStore>(m, "StateStore")
+        .def(py::init<const std::string&, uint32_t>(),
+             py::arg("path"), py::arg("compact_after") = saber::DEFAULT_STATE_COMPACT_RECORDS)
+        .def("recover", &saber::StateStore::recover)
+        .def("get", &saber::StateStore::get)
+        .def("entries", &saber::StateStore::entries)
+        .def("put", &saber::StateStore::put)
+        .def("erase", &saber::StateStore::erase)
+        .def("compact", &saber::StateStore::compact)
+        .def("get_path", &saber::StateStore::getPath);
+    
+    py::enum_<saber::CongestionState>(m, "CongestionState")
+        .value("Clear", saber::CongestionState::Clear)
+        .value("Congested", saber::CongestionState::Congested);
+    
+    py::class_<saber::CongestionController>(m, "CongestionController")
+        .def(py::init<double, double>(), py::arg("enter_threshold") = 0.8, py::arg("exit_threshold") = 0.6)
+        .def("update", &saber::CongestionController::update)
+        .def("get_state", &saber::CongestionController::getState)
+        .def("get_utilization", &saber::CongestionController::getUtilization)
+        .def("recommended_bitrate_kbps", &saber::CongestionController::recommendedBitrateKbps);
+    
+    // Esporre la finestra di riparazione dei frame audio
+    py::class_<saber::RtpTarget>(m, "RtpTarget")
+        .def(py::init<>())
+        .def_readwrite("address", &saber::RtpTarget::address)
+        .def_readwrite("port", &saber::RtpTarget::port)
+        .def_readwrite("payload_type", &saber::RtpTarget::payloadType)
+        .def_readwrite("encoding", &saber::RtpTarget::encoding)
+        .def_readwrite("packet_time_us", &saber::RtpTarget::packetTimeUs)
+        .def_readwrite("link_offset_ms", &saber::RtpTarget::linkOffsetMs)
+        .def_readwrite("multicast_ttl", &saber::RtpTarget::multicastTtl);
+    
+    py::class_<saber::RepairConfig>(m, "RepairConfig")
+        .def(py::init<>())
+        .def_readwrite("enabled", &saber::RepairConfig::enabled)
+        .def_readwrite("window_ms", &saber::RepairConfig::windowMs)
+        .def_readwrite("cache_frames", &saber::RepairConfig::cacheFrames);
+    
+    py::class_<saber::ReliableConfig>(m, "ReliableConfig")
+        .def(py::init<>())
+        .def_readwrite("enabled", &saber::ReliableConfig::enabled)
+        .def_readwrite("initial_timeout_ms", &saber::ReliableConfig::initialTimeoutMs)
+        .def_readwrite("max_timeout_ms", &saber::ReliableConfig::maxTimeoutMs)
+        .def_readwrite("max_attempts", &saber::ReliableConfig::maxAttempts);
+    
+    // Esporre la scala di degrado
+    py::enum_<saber::DegradationLevel>(m, "DegradationLevel")
+        .value("Nominal", saber::DegradationLevel::Nominal)
+        .value("ReducedBitrate", saber::DegradationLevel::ReducedBitrate)
+        .value("StrongerFec", saber::DegradationLevel::StrongerFec)
+        .value("LargerBuffer", saber::DegradationLevel::LargerBuffer)
+        .value("VoiceSampleRate", saber::DegradationLevel::VoiceSampleRate)
+        .value("SuspendedSinks", saber::DegradationLevel::SuspendedSinks);
+    
+    py::class_<saber::DegradationConfig>(m, "DegradationConfig")
+        .def(py::init<>())
+        .def_readwrite("enabled", &saber::DegradationConfig::enabled)
+        .def_readwrite("bad_quality_percent", &saber::DegradationConfig::badQualityPercent)
+        .def_readwrite("good_quality_percent", &saber::DegradationConfig::goodQualityPercent)
+        .def_readwrite("escalate_after_ms", &saber::DegradationConfig::escalateAfterMs)
+        .def_readwrite("recover_after_ms", &saber::DegradationConfig::recoverAfterMs)
+        .def_readwrite("buffer_step_ms", &saber::DegradationConfig::bufferStepMs)
+        .def_readwrite("max_suspended_sinks", &saber::DegradationConfig::maxSuspendedSinks);
+    
+    py::class_<saber::DegradationSettings>(m, "DegradationSettings")
+        .def_readonly("level", &saber::DegradationSettings::level)
+        .def_readonly("bitrate_kbps", &saber::DegradationSettings::bitrateKbps)
+        .def_readonly("fec_enabled", &saber::DegradationSettings::fecEnabled)
+        .def_readonly("buffer_ms", &saber::DegradationSettings::bufferMs)
+        .def_readonly("sample_rate_hz", &saber::DegradationSettings::sampleRateHz);
+    
+    py::class_<saber::DegradationLadder>(m, "DegradationLadder")
+        .def(py::init<const saber::DegradationConfig&>(), py::arg("config") = saber::DegradationConfig())
+        .def("update", &saber::DegradationLadder::update)
+        .def("get_level", &saber::DegradationLadder::getLevel)
+        .def("settings_for", &saber::DegradationLadder::settingsFor);
+    
+    py::class_<saber::RepairStats>(m, "RepairStats")
+        .def_readonly("nacks_sent", &saber::RepairStats::nacksSent)
+        .def_readonly("nacks_received", &saber::RepairStats::nacksReceived)
+        .def_readonly("retransmitted", &saber::RepairStats::retransmitted)
+        .def_readonly("refused_late", &saber::RepairStats::refusedLate)
+        .def_readonly("cache_misses", &saber::RepairStats::cacheMisses)
+        .def_readonly("repaired", &saber::RepairStats::repaired)
+        .def_readonly("expired", &saber::RepairStats::expired);
+    
+    py::class_<saber::NackTracker>(m, "NackTracker")
+        .def(py::init<uint32_t, uint32_t>(), py::arg("window_ms") = 20, py::arg("max_gap") = 32)
+        .def("on_frame", &saber::NackTracker::onFrame)
+        .def("expire", &saber::NackTracker::expire)
+        .def("get_stats", &saber::NackTracker::getStats)
+        .def("set_window_ms", &saber::NackTracker::setWindowMs);
+    
+    m.def("arrives_before_playout", &saber::arrivesBeforePlayout);
+    
+    py::class_<saber::ReliableStats>(m, "ReliableStats")
+        .def_readonly("sent", &saber::ReliableStats::sent)
+        .def_readonly("retransmitted", &saber::ReliableStats::retransmitted)
+        .def_readonly("acknowledged", &saber::ReliableStats::acknowledged)
+        .def_readonly("failed", &saber::ReliableStats::failed)
+        .def_readonly("acks_sent", &saber::ReliableStats::acksSent)
+        .def_readonly("duplicates", &saber::ReliableStats::duplicates);
+    
+    // Esporre il sink fantasma
+    py::class_<saber::PhantomStreamStats>(m, "PhantomStreamStats")
+        .def_readonly("stream_id", &saber::PhantomStreamStats::streamId)
+        .def_readonly("frames_consumed", &saber::PhantomStreamStats::framesConsumed)
+        .def_readonly("bytes_consumed", &saber::PhantomStreamStats::bytesConsumed)
+        .def_readonly("late_frames", &saber::PhantomStreamStats::lateFrames)
+        .def_readonly("last_lead_ms", &saber::PhantomStreamStats::lastLeadMs)
+        .def_readonly("min_lead_ms", &saber::PhantomStreamStats::minLeadMs);
+    
+    py::class_<saber::PhantomSink>(m, "PhantomSink")
+        .def(py::init<uint32_t>(), py::arg("target_buffer_ms"))
+        .def("consume", &saber::PhantomSink::consume)
+        .def("buffer_level", &saber::PhantomSink::bufferLevel)
+        .def("record_status_sent", &saber::PhantomSink::recordStatusSent)
+        .def("get_status_reports_sent", &saber::PhantomSink::getStatusReportsSent)
+        .def("get_stats", &saber::PhantomSink::getStats);
+    
+    // Esporre il ponte verso le cuffie A2DP
+    py::class_<saber::A2dpBridgeStats>(m, "A2dpBridgeStats")
+        .def_readonly("device", &saber::A2dpBridgeStats::device)
+        .def_readonly("latency_ms", &saber::A2dpBridgeStats::latencyMs)
+        .def_readonly("frames_forwarded", &saber::A2dpBridgeStats::framesForwarded)
+        .def_readonly("late_frames", &saber::A2dpBridgeStats::lateFrames)
+        .def_readonly("last_lead_ms", &saber::A2dpBridgeStats::lastLeadMs);
+    
+    py::class_<saber::A2dpBridge>(m, "A2dpBridge")
+        .def(py::init<const std::string&, uint32_t, uint32_t>(), 
+             py::arg("device"), py::arg("latency_ms"), py::arg("target_buffer_ms"))
+        .def("emit_time_us", &saber::A2dpBridge::emitTimeUs)
+        .def("forward", &saber::A2dpBridge::forward)
+        .def("reported_latency_ms", &saber::A2dpBridge::reportedLatencyMs)
+        .def("buffer_level", &saber::A2dpBridge::bufferLevel)
+        .def("get_stats", &saber::A2dpBridge::getStats);
+    
+    // Esporre i resoconti di sessione
+    py::class_<saber::SessionReport>(m, "SessionReport")
+        .def_readonly("node_id", &saber::SessionReport::nodeId)
+        .def_readonly("stream_id", &saber::SessionReport::streamId)
+        .def_readonly("started_at_ms", &saber::SessionReport::startedAtMs)
+        .def_readonly("ended_at_ms", &saber::SessionReport::endedAtMs)
+        .def_readonly("frames_played", &saber::SessionReport::framesPlayed)
+        .def_readonly("frames_concealed", &saber::SessionReport::framesConcealed)
+        .def_readonly("frames_dropped", &saber::SessionReport::framesDropped)
+        .def_readonly("average_offset_ms", &saber::SessionReport::averageOffsetMs)
+        .def_readonly("rebuffer_count", &saber::SessionReport::rebufferCount)
+        .def_readonly("max_latency_ms", &saber::SessionReport::maxLatencyMs);
+    
+    py::class_<saber::SessionTracker>(m, "SessionTracker")
+        .def(py::init<const std::string&, uint32_t>())
+        .def("record_frame", &saber::SessionTracker::recordFrame)
+        .def("finish", &saber::SessionTracker::finish)
+        .def("finish_all", &saber::SessionTracker::finishAll)
+        .def("finish_idle", &saber::SessionTracker::finishIdle);
+    
+    // Esporre i riallineamenti d'emergenza degli orologi
+    py::class_<saber::EmergencySyncStats>(m, "EmergencySyncStats")
+        .def_readonly("sent", &saber::EmergencySyncStats::sent)
+        .def_readonly("nodes_targeted", &saber::EmergencySyncStats::nodesTargeted)
+        .def_readonly("applied", &saber::EmergencySyncStats::applied)
+        .def_readonly("last_applied_at_ms", &saber::EmergencySyncStats::lastAppliedAtMs);
+    
+    // Esporre la misura di sincronizzazione tra sink
+    py::enum_<saber::AsymmetryMode>(m, "AsymmetryMode")
+        .value("Symmetric", saber::AsymmetryMode::Symmetric)
+        .value("MinimumDelay", saber::AsymmetryMode::MinimumDelay);
+    
+    m.def("asymmetry_mode_to_string", &saber::asymmetryModeToString);
+    m.def("asymmetry_mode_from_string", &saber::asymmetryModeFromString);
+    
+    py::class_<saber::SyncProbeResult>(m, "SyncProbeResult")
+        .def_readonly("peer", &saber::SyncProbeResult::peer)
+        .def_readonly("samples", &saber::SyncProbeResult::samples)
+        .def_readonly("mean_skew_us", &saber::SyncProbeResult::meanSkewUs)
+        .def_readonly("min_skew_us", &saber::SyncProbeResult::minSkewUs)
+        .def_readonly("max_skew_us", &saber::SyncProbeResult::maxSkewUs)
+        .def_readonly("mean_round_trip_us", &saber::SyncProbeResult::meanRoundTripUs)
+        .def_readonly("asymmetry_us", &saber::SyncProbeResult::asymmetryUs)
+        .def_readonly("mode", &saber::SyncProbeResult::mode)
+        .def_readonly("within_tolerance", &saber::SyncProbeResult::withinTolerance)
+        .def_readonly("acoustic_skew_us", &saber::SyncProbeResult::acousticSkewUs)
+        .def_readonly("measured_at_ms", &saber::SyncProbeResult::measuredAtMs);
+    
+    py::class_<saber::SyncProbe>(m, "SyncProbe")
+        .def(py::init<const std::string&, size_t, uint32_t, saber::AsymmetryMode>(),
+             py::arg("peer"), py::arg("sample_count"), py::arg("tolerance_ms"),
+             py::arg("mode") = saber::AsymmetryMode::Symmetric)
+        .def_static("skew_us", &saber::SyncProbe::skewUs)
+        .def_static("round_trip_us", &saber::SyncProbe::roundTripUs)
+        .def("add_sample", &saber::SyncProbe::addSample)
+        .def("next_probe_id", &saber::SyncProbe::nextProbeId)
+        .def("get_probes_sent", &saber::SyncProbe::getProbesSent)
+        .def("is_complete", &saber::SyncProbe::isComplete)
+        .def("result", &saber::SyncProbe::result);
+    
+    // Esporre il sopralluogo radio
+    py::class_<saber::SurveyLink>(m, "SurveyLink")
+        .def_readonly("peer", &saber::SurveyLink::peer)
+        .def_readonly("probes_sent", &saber::SurveyLink::probesSent)
+        .def_readonly("replies", &saber::SurveyLink::replies)
+        .def_readonly("loss_rate", &saber::SurveyLink::lossRate)
+        .def_readonly("mean_rssi_dbm", &saber::SurveyLink::meanRssiDbm)
+        .def_readonly("min_rssi_dbm", &saber::SurveyLink::minRssiDbm);
+    
+    py::class_<saber::SurveyPosition>(m, "SurveyPosition")
+        .def_readonly("label", &saber::SurveyPosition::label)
+        .def_readonly("links", &saber::SurveyPosition::links)
+        .def_readonly("best_peer", &saber::SurveyPosition::bestPeer)
+        .def_readonly("covered", &saber::SurveyPosition::covered);
+    
+    py::class_<saber::CoverageReport>(m, "CoverageReport")
+        .def_readonly("positions", &saber::CoverageReport::positions)
+        .def_readonly("uncovered_positions", &saber::CoverageReport::uncoveredPositions);
+    
+    py::class_<saber::CoverageSurvey>(m, "CoverageSurvey")
+        .def(py::init<int32_t, uint32_t>(), py::arg("min_rssi_dbm") = -80, py::arg("max_loss_percent") = 10)
+        .def("set_position", &saber::CoverageSurvey::setPosition)
+        .def("get_position", &saber::CoverageSurvey::getPosition)
+        .def("record_probe", &saber::CoverageSurvey::recordProbe)
+        .def("record_reply", &saber::CoverageSurvey::recordReply)
+        .def("report", &saber::CoverageSurvey::report);
+    
+    // Esporre il profilatore della pipeline audio
+    py::enum_<saber::PipelineStage>(m, "PipelineStage")
+        .value("Encode", saber::PipelineStage::Encode)
+        .value("Encrypt", saber::PipelineStage::Encrypt)
+        .value("Send", saber::PipelineStage::Send)
+        .value("Receive", saber::PipelineStage::Receive)
+        .value("Decrypt", saber::PipelineStage::Decrypt)
+        .value("Decode", saber::PipelineStage::Decode)
+        .value("Resample", saber::PipelineStage::Resample)
+        .value("Render", saber::PipelineStage::Render);
+    
+    py::class_<saber::StageTiming>(m, "StageTiming")
+        .def_readonly("stage", &saber::StageTiming::stage)
+        .def_readonly("samples", &saber::StageTiming::samples)
+        .def_readonly("total", &saber::StageTiming::total)
+        .def_readonly("p50_us", &saber::StageTiming::p50Us)
+        .def_readonly("p95_us", &saber::StageTiming::p95Us)
+        .def_readonly("p99_us", &saber::StageTiming::p99Us)
+        .def_readonly("max_us", &saber::StageTiming::maxUs);
+    
+    py::class_<saber::PipelineProfiler>(m, "PipelineProfiler")
+        .def(py::init<size_t, const std::string&>(), py::arg("window_samples") = 1024, 
+             py::arg("trace_prefix") = "saber")
+        .def("record", &saber::PipelineProfiler::record)
+        .def("timing", &saber::PipelineProfiler::timing)
+        .def("timings", &saber::PipelineProfiler::timings)
+        .def("set_trace_file", &saber::PipelineProfiler::setTraceFile)
+        .def("reset", &saber::PipelineProfiler::reset);
+    
+    // Esporre StreamMetadata
+    py::class_<saber::StreamMetadata>(m, "StreamMetadata")
+        .def(py::init<>())
+        .def_readwrite("stream_id", &saber::StreamMetadata::streamId)
+        .def_readonly("revision", &saber::StreamMetadata::revision)
+        .def_readwrite("title", &saber::StreamMetadata::title)
+        .def_readwrite("artist", &saber::StreamMetadata::artist)
+        .def_readwrite("album", &saber::StreamMetadata::album)
+        .def_readonly("artwork_hash", &saber::StreamMetadata::artworkHash);
+    
+    // Esporre BassSettings
+    py::class_<saber::BassSettings>(m, "BassSettings")
+        .def_readonly("role", &saber::BassSettings::role)
+        .def_readonly("crossover_hz", &saber::BassSettings::crossoverHz);
+    
+    // Esporre la selezione dei collegamenti
+    py::enum_<saber::TransportKind>(m, "TransportKind")
+        .value("Ble", saber::TransportKind::Ble)
+        .value("Udp", saber::TransportKind::Udp);
+    
+    py::class_<saber::FailoverEvent>(m, "FailoverEvent")
+        .def_readonly("peer", &saber::FailoverEvent::peer)
+        .def_readonly("from_transport", &saber::FailoverEvent::from)
+        .def_readonly("to_transport", &saber::FailoverEvent::to)
+        .def_readonly("reason", &saber::FailoverEvent::reason)
+        .def_readonly("timestamp", &saber::FailoverEvent::timestamp);
+    
+    py::class_<saber::TransportStats>(m, "TransportStats")
+        .def_readonly("failovers", &saber::TransportStats::failovers)
+        .def_readonly("packets_routed", &saber::TransportStats::packetsRouted)
+        .def_readonly("active", &saber::TransportStats::active);
+    
+    py::class_<saber::TransportSelector>(m, "TransportSelector")
+        .def(py::init<double, double, uint32_t>(),
+             py::arg("degraded_quality") = 0.5, py::arg("switch_margin") = 0.15, py::arg("stale_ms") = 2000)
+        .def("report_path", &saber::TransportSelector::reportPath)
+        .def("report_path_down", &saber::TransportSelector::reportPathDown)
+        .def("route", &saber::TransportSelector::route)
+        .def("active_transport", &saber::TransportSelector::activeTransport)
+        .def("get_stats", &saber::TransportSelector::getStats)
+        .def("set_failover_handler", &saber::TransportSelector::setFailoverHandler);
+    
+    m.def("transport_kind_to_string", &saber::transportKindToString);
+    m.def("transport_kind_from_string", &saber::transportKindFromString);
+    
+    // Esporre il trasporto UDP multicast
+    m.attr("MAX_UDP_DATAGRAM_BYTES") = saber::MAX_UDP_DATAGRAM_BYTES;
+    
+    py::class_<saber::UdpTransportConfig>(m, "UdpTransportConfig")
+        .def(py::init<>())
+        .def_readwrite("multicast_address", &saber::UdpTransportConfig::multicastAddress)
+        .def_readwrite("port", &saber::UdpTransportConfig::port)
+        .def_readwrite("interface_address", &saber::UdpTransportConfig::interfaceAddress)
+        .def_readwrite("multicast_ttl", &saber::UdpTransportConfig::multicastTtl);
+    
+    py::class_<saber::UdpTransportStats>(m, "UdpTransportStats")
+        .def_readonly("datagrams_sent", &saber::UdpTransportStats::datagramsSent)
+        .def_readonly("datagrams_received", &saber::UdpTransportStats::datagramsReceived)
+        .def_readonly("bytes_sent", &saber::UdpTransportStats::bytesSent)
+        .def_readonly("bytes_received", &saber::UdpTransportStats::bytesReceived)
+        .def_readonly("send_errors", &saber::UdpTransportStats::sendErrors);
+    
+    py::class_<saber::UdpTransport>(m, "UdpTransport")
+        .def(py::init<const saber::UdpTransportConfig&>())
+        .def("start", [](saber::UdpTransport& self, std::function<void(py::bytes)> handler) {
+            // Il gestore Python riceve i datagrammi come bytes, dal thread di ricezione
+            return self.start([handler](const std::vector<uint8_t>& datagram) {
+                py::gil_scoped_acquire gil;
+                handler(py::bytes(reinterpret_cast<const char*>(datagram.data()), datagram.size()));
+            });
+        })
+        .def("stop", &saber::UdpTransport::stop, py::call_guard<py::gil_scoped_release>())
+        .def("is_running", &saber::UdpTransport::isRunning)
+        .def("send", [](saber::UdpTransport& self, const py::bytes& datagram) {
+            std::string data = datagram;
+            return self.send(std::vector<uint8_t>(data.begin(), data.end()));
+        })
+        .def("get_config", &saber::UdpTransport::getConfig)
+        .def("get_stats", &saber::UdpTransport::getStats);
+    
+    // Esporre la rete simulata per i test di integrazione
+    py::class_<saber::SimLinkConfig>(m, "SimLinkConfig")
+        .def(py::init<>())
+        .def_readwrite("latency_ms", &saber::SimLinkConfig::latencyMs)
+        .def_readwrite("jitter_ms", &saber::SimLinkConfig::jitterMs)
+        .def_readwrite("loss_rate", &saber::SimLinkConfig::lossRate);
+    
+    py::class_<saber::SimStats>(m, "SimStats")
+        .def_readonly("packets_sent", &saber::SimStats::packetsSent)
+        .def_readonly("delivered", &saber::SimStats::delivered)
+        .def_readonly("lost", &saber::SimStats::lost)
+        .def_readonly("partitioned", &saber::SimStats::partitioned)
+        .def_readonly("in_flight", &saber::SimStats::inFlight);
+    
+    py::class_<saber::SimNetwork, std::shared_ptr<saber::SimNetwork>>(m, "SimNetwork")
+        .def(py::init<uint64_t, const saber::SimLinkConfig&>(),
+             py::arg("seed") = 0, py::arg("defaults") = saber::SimLinkConfig())
+        .def("attach", [](saber::SimNetwork& self, const std::string& nodeId, std::function<void(py::bytes)> handler) {
+            // Il gestore Python riceve i pacchetti come bytes durante advance()
+            self.attach(nodeId, [handler](const std::vector<uint8_t>& datagram) {
+                py::gil_scoped_acquire gil;
+                handler(toBytes(datagram));
+            });
+        })
+        .def("detach", &saber::SimNetwork::detach, py::call_guard<py::gil_scoped_release>())
+        .def("get_nodes", &saber::SimNetwork::getNodes)
+        .def("send", [](saber::SimNetwork& self, const std::string& from, const py::bytes& datagram) {
+            return self.send(from, fromBytes(datagram));
+        })
+        .def("set_default_link", &saber::SimNetwork::setDefaultLink)
+        .def("set_link", &saber::SimNetwork::setLink)
+        .def("clear_link", &saber::SimNetwork::clearLink)
+        .def("get_link", &saber::SimNetwork::getLink)
+        .def("partition", &saber::SimNetwork::partition)
+        .def("heal", &saber::SimNetwork::heal)
+        .def("can_reach", &saber::SimNetwork::canReach)
+        .def("advance", &saber::SimNetwork::advance, py::call_guard<py::gil_scoped_release>())
+        .def("now", &saber::SimNetwork::now)
+        .def("get_stats", &saber::SimNetwork::getStats);
+    
+    // Esporre la politica di autorizzazione esterna
+    m.attr("DEFAULT_AUTHORIZATION_TIMEOUT_MS") = saber::DEFAULT_AUTHORIZATION_TIMEOUT_MS;
+    
+    py::enum_<saber::AuthorizationAction>(m, "AuthorizationAction")
+        .value("Join", saber::AuthorizationAction::Join)
+        .value("Command", saber::AuthorizationAction::Command);
+    
+    m.def("authorization_action_to_string", &saber::authorizationActionToString);
+    m.def("authorization_action_from_string", &saber::authorizationActionFromString);
+    
+    py::class_<saber::AuthorizationRequest>(m, "AuthorizationRequest")
+        .def(py::init<>())
+        .def_readonly("id", &saber::AuthorizationRequest::id)
+        .def_readwrite("action", &saber::AuthorizationRequest::action)
+        .def_readwrite("node_id", &saber::AuthorizationRequest::nodeId)
+        .def_readwrite("role", &saber::AuthorizationRequest::role)
+        .def_readwrite("command", &saber::AuthorizationRequest::command)
+        .def_readwrite("token_id", &saber::AuthorizationRequest::tokenId);
+    
+    py::class_<saber::AuthorizationDecision>(m, "AuthorizationDecision")
+        .def(py::init<>())
+        .def_readwrite("allowed", &saber::AuthorizationDecision::allowed)
+        .def_readwrite("reason", &saber::AuthorizationDecision::reason);
+    
+    py::class_<saber::AuthorizationStats>(m, "AuthorizationStats")
+        .def_readonly("requests", &saber::AuthorizationStats::requests)
+        .def_readonly("allowed", &saber::AuthorizationStats::allowed)
+        .def_readonly("denied", &saber::AuthorizationStats::denied)
+        .def_readonly("timed_out", &saber::AuthorizationStats::timedOut)
+        .def_readonly("pending", &saber::AuthorizationStats::pending);
+    
+    py::class_<saber::AuthorizationGate>(m, "AuthorizationGate")
+        .def(py::init<uint32_t, bool>(), 
+             py::arg("timeout_ms") = saber::DEFAULT_AUTHORIZATION_TIMEOUT_MS, py::arg("allow_on_timeout") = false)
+        .def("set_handler", [](saber::AuthorizationGate& self, 
+                               std::function<void(const saber::AuthorizationRequest&, py::object)> handler) {
+            auto function = authorizationHandler(handler);
+            self.setHook(function ? std::make_shared<saber::FunctionAuthorizationHook>(function) : nullptr);
+        })
+        .def("has_hook", &saber::AuthorizationGate::hasHook)
+        .def("set_timeout", &saber::AuthorizationGate::setTimeout)
+        .def("decide", &saber::AuthorizationGate::decide, py::call_guard<py::gil_scoped_release>())
+        .def("cancel_pending", &saber::AuthorizationGate::cancelPending, py::call_guard<py::gil_scoped_release>())
+        .def("get_stats", &saber::AuthorizationGate::getStats);
+    
+    // Esporre la verifica dell'ambiente
+    py::enum_<saber::PreflightCheck>(m, "PreflightCheck")
+        .value("BluetoothAdapter", saber::PreflightCheck::BluetoothAdapter)
+        .value("AudioDevice", saber::PreflightCheck::AudioDevice)
+        .value("PortBindable", saber::PreflightCheck::PortBindable)
+        .value("ClockResolution", saber::PreflightCheck::ClockResolution);
+    
+    py::class_<saber::PreflightIssue>(m, "PreflightIssue")
+        .def_readonly("check", &saber::PreflightIssue::check)
+        .def_readonly("fatal", &saber::PreflightIssue::fatal)
+        .def_readonly("message", &saber::PreflightIssue::message)
+        .def_readonly("remedy", &saber::PreflightIssue::remedy);
+    
+    py::class_<saber::PreflightReport>(m, "PreflightReport")
+        .def_readonly("issues", &saber::PreflightReport::issues)
+        .def_readonly("clock_resolution_us", &saber::PreflightReport::clockResolutionUs)
+        .def("passed", &saber::PreflightReport::passed)
+        .def("summary", &saber::PreflightReport::summary);
+    
+    // Esporre i profili d'uso
+    py::class_<saber::Profile>(m, "Profile")
+        .def_readonly("name", &saber::Profile::name)
+        .def_readonly("description", &saber::Profile::description)
+        .def_readonly("is_music_mode", &saber::Profile::isMusicMode)
+        .def_readonly("codec", &saber::Profile::codec)
+        .def_readonly("frame_duration_us", &saber::Profile::frameDurationUs)
+        .def_readonly("bitrate_kbps", &saber::Profile::bitrateKbps)
+        .def_readonly("buffer_target_ms", &saber::Profile::bufferTargetMs)
+        .def_readonly("latency_budget_ms", &saber::Profile::latencyBudgetMs)
+        .def_readonly("latency_mode", &saber::Profile::latencyMode)
+        .def_readonly("fec_enabled", &saber::Profile::fecEnabled)
+        .def_readonly("beacon_interval_ms", &saber::Profile::beaconIntervalMs);
+    
+    m.def("list_profiles", &saber::profileNames);
+    m.def("find_profile", &saber::findProfile);
+    
+    m.attr("MAX_ZONE_DELAY_MS") = saber::MAX_ZONE_DELAY_MS;
+    
+    // Esporre Keystore (i segreti sono restituiti in chiaro)
+    py::class_<saber::Keystore>(m, "Keystore")
+        .def_static("open", py::overload_cast<const std::string&>(&saber::Keystore::open))
+        .def_static("open", py::overload_cast<const std::string&, const std::array<uint8_t, 32>&>(
+                                &saber::Keystore::open))
+        .def("get", [](const saber::Keystore& self, const std::string& name) {
+            return self.get(name).reveal();
+        })
+        .def("put", [](saber::Keystore& self, const std::string& name, const std::string& value) {
+            self.put(name, saber::SecretString(value));
+        })
+        .def("save", &saber::Keystore::save);
     
     // Esporre SaberConfig
     py::class_<saber::SaberConfig>(m, "SaberConfig")
         .def(py::init<>())
         .def_static("default_config", &saber::SaberConfig::defaultConfig)
+        .def_static("from_file", &saber::SaberConfig::fromFile)
+        .def_static("for_profile", &saber::SaberConfig::forProfile)
+        .def("apply_profile", &saber::SaberConfig::applyProfile)
+        .def_static("is_live_reloadable", &saber::SaberConfig::isLiveReloadable)
         .def_readwrite("node_id", &saber::SaberConfig::nodeId)
         .def_readwrite("role", &saber::SaberConfig::role)
         .def_readwrite("bt_address", &saber::SaberConfig::btAddress)
-        .def_readwrite("is_music_mode", &saber::SaberConfig::isMusicMode);
+        .def_readwrite("is_music_mode", &saber::SaberConfig::isMusicMode)
+        .def_readwrite("health_port", &saber::SaberConfig::healthPort)
+        .def_readwrite("health_bind_address", &saber::SaberConfig::healthBindAddress)
+        .def_readwrite("mqtt_username", &saber::SaberConfig::mqttUsername)
+        .def_readwrite("keystore_file", &saber::SaberConfig::keystoreFile)
+        .def_readwrite("secret_references", &saber::SaberConfig::secretReferences)
+        .def_readwrite("send_rejects", &saber::SaberConfig::sendRejects)
+        .def_readwrite("encrypt_packets", &saber::SaberConfig::encryptPackets)
+        .def_readwrite("session_keys", &saber::SaberConfig::sessionKeys)
+        .def_readwrite("control_port", &saber::SaberConfig::controlPort)
+        .def_readwrite("control_bind_address", &saber::SaberConfig::controlBindAddress)
+        .def_readwrite("control_token_file", &saber::SaberConfig::controlTokenFile)
+        .def_readwrite("control_token_ttl_seconds", &saber::SaberConfig::controlTokenTtlSeconds)
+        .def_readwrite("pairing_token_ttl_seconds", &saber::SaberConfig::pairingTokenTtlSeconds)
+        .def_readwrite("log_filter", &saber::SaberConfig::logFilter)
+        .def_readwrite("frame_encryption", &saber::SaberConfig::frameEncryption)
+        .def_readwrite("spec", &saber::SaberConfig::spec)
+        .def_readwrite("repair", &saber::SaberConfig::repair)
+        .def_readwrite("jitter_buffer", &saber::SaberConfig::jitterBuffer)
+        .def_readwrite("delay_equalization", &saber::SaberConfig::delayEqualization)
+        .def_readwrite("intrusion", &saber::SaberConfig::intrusion)
+        .def_readwrite("audio_timestamp_width", &saber::SaberConfig::audioTimestampWidth)
+        .def_readwrite("timestamp_anchor_frames", &saber::SaberConfig::timestampAnchorFrames)
+        .def_readwrite("published_streams", &saber::SaberConfig::publishedStreams)
+        .def_readwrite("listen_streams", &saber::SaberConfig::listenStreams)
+        .def_readwrite("voice_streams", &saber::SaberConfig::voiceStreams)
+        .def_readonly("profile", &saber::SaberConfig::profile)
+        .def_readwrite("codec", &saber::SaberConfig::codec)
+        .def_readwrite("frame_duration_us", &saber::SaberConfig::frameDurationUs)
+        .def_readwrite("bitrate_kbps", &saber::SaberConfig::bitrateKbps)
+        .def_readwrite("fec_enabled", &saber::SaberConfig::fecEnabled)
+        .def_readwrite("degradation", &saber::SaberConfig::degradation)
+        .def_readwrite("beacon_interval_ms", &saber::SaberConfig::beaconIntervalMs)
+        .def_readwrite("intercom", &saber::SaberConfig::intercom)
+        .def_readwrite("standby", &saber::SaberConfig::standby)
+        .def_readwrite("simulcast", &saber::SaberConfig::simulcast)
+        .def_readwrite("flow", &saber::SaberConfig::flow)
+        .def_readwrite("sources", &saber::SaberConfig::sources)
+        .def_readwrite("zone_delays", &saber::SaberConfig::zoneDelays)
+        .def_readwrite("require_bluetooth", &saber::SaberConfig::requireBluetooth)
+        .def_readwrite("require_audio_device", &saber::SaberConfig::requireAudioDevice)
+        .def_readwrite("audio_output", &saber::SaberConfig::audioOutput)
+        .def_readwrite("rtp_target", &saber::SaberConfig::rtpTarget)
+        .def_readwrite("transport", &saber::SaberConfig::transport)
+        .def_readwrite("udp", &saber::SaberConfig::udp)
+        .def_readwrite("phantom_sink", &saber::SaberConfig::phantomSink)
+        .def_readwrite("a2dp_device", &saber::SaberConfig::a2dpDevice)
+        .def_readwrite("a2dp_latency_ms", &saber::SaberConfig::a2dpLatencyMs)
+        .def_readwrite("late_frame_policy", &saber::SaberConfig::lateFramePolicy)
+        .def_readwrite("late_frame_tolerance_ms", &saber::SaberConfig::lateFrameToleranceMs)
+        .def_readwrite("max_clock_resolution_us", &saber::SaberConfig::maxClockResolutionUs)
+        .def_readwrite("join_attempts_per_minute", &saber::SaberConfig::joinAttemptsPerMinute)
+        .def_readwrite("max_nodes", &saber::SaberConfig::maxNodes)
+        .def_readwrite("max_sinks", &saber::SaberConfig::maxSinks)
+        .def_readwrite("heartbeat_timeout_ms", &saber::SaberConfig::heartbeatTimeoutMs)
+        .def_readwrite("node_evict_after_ms", &saber::SaberConfig::nodeEvictAfterMs)
+        .def_readwrite("reliable", &saber::SaberConfig::reliable)
+        .def_readwrite("rate_limits", &saber::SaberConfig::rateLimits)
+        .def_readwrite("authorization_timeout_ms", &saber::SaberConfig::authorizationTimeoutMs)
+        .def_readwrite("authorization_allow_on_timeout", &saber::SaberConfig::authorizationAllowOnTimeout)
+        .def_readwrite("survey_interval_ms", &saber::SaberConfig::surveyIntervalMs)
+        .def_readwrite("sync_probe_asymmetry", &saber::SaberConfig::syncProbeAsymmetry)
+        .def_readwrite("time_exchange_interval_ms", &saber::SaberConfig::timeExchangeIntervalMs)
+        .def_readwrite("mesh_beacon_interval_ms", &saber::SaberConfig::meshBeaconIntervalMs)
+        .def_readwrite("discovery_enabled", &saber::SaberConfig::discoveryEnabled)
+        .def_readwrite("discovery_interval_ms", &saber::SaberConfig::discoveryIntervalMs)
+        .def_readwrite("discovery_stale_ms", &saber::SaberConfig::discoveryStaleMs)
+        .def_readwrite("tracing_enabled", &saber::SaberConfig::tracingEnabled)
+        .def_readwrite("otlp_endpoint", &saber::SaberConfig::otlpEndpoint)
+        .def_readwrite("otlp_export_interval_ms", &saber::SaberConfig::otlpExportIntervalMs)
+        .def_readwrite("survey_min_rssi_dbm", &saber::SaberConfig::surveyMinRssiDbm)
+        .def_readwrite("survey_max_loss_percent", &saber::SaberConfig::surveyMaxLossPercent)
+        .def_readwrite("replay_window", &saber::SaberConfig::replayWindow)
+        .def_readwrite("nonce_file", &saber::SaberConfig::nonceFile)
+        .def_readwrite("event_journal_file", &saber::SaberConfig::eventJournalFile)
+        .def_readwrite("event_journal_max_entries", &saber::SaberConfig::eventJournalMaxEntries)
+        .def_readwrite("profile_window_samples", &saber::SaberConfig::profileWindowSamples)
+        .def_readwrite("pipeline_trace_file", &saber::SaberConfig::pipelineTraceFile)
+        .def_readwrite("record_paths", &saber::SaberConfig::recordPaths)
+        .def_readwrite("schedule_file", &saber::SaberConfig::scheduleFile)
+        .def_readwrite("state_file", &saber::SaberConfig::stateFile)
+        .def_readwrite("schedule_utc_offset_minutes", &saber::SaberConfig::scheduleUtcOffsetMinutes)
+        .def_readwrite("start_barrier_lead_ms", &saber::SaberConfig::startBarrierLeadMs)
+        .def_readwrite("random_source", &saber::SaberConfig::randomSource)
+        .def_readwrite("config_file", &saber::SaberConfig::configFile)
+        .def_readwrite("config_watch_interval_ms", &saber::SaberConfig::configWatchIntervalMs);
+    
+    py::class_<saber::ConfigReloadReport>(m, "ConfigReloadReport")
+        .def(py::init<>())
+        .def_readonly("applied", &saber::ConfigReloadReport::applied)
+        .def_readonly("restart_required", &saber::ConfigReloadReport::restartRequired)
+        .def_readonly("error", &saber::ConfigReloadReport::error);
+    
+    // Esporre LifecycleError::Type
+    py::enum_<saber::LifecycleError::Type>(m, "LifecycleErrorType")
+        .value("NotRunning", saber::LifecycleError::Type::NotRunning)
+        .value("InitializationFailed", saber::LifecycleError::Type::InitializationFailed)
+        .value("InvalidThread", saber::LifecycleError::Type::InvalidThread);
+    
+    // Esporre LifecycleError come eccezione Python con il tipo nell'attributo "type"
+    static py::exception<saber::LifecycleError> lifecycleError(m, "LifecycleError", PyExc_RuntimeError);
+    py::register_exception_translator([](std::exception_ptr error) {
+        try {
+            if (error) {
+                std::rethrow_exception(error);
+            }
+        } catch (const saber::LifecycleError& e) {
+            py::object instance = lifecycleError(e.what());
+            instance.attr("type") = py::cast(e.getType());
+            PyErr_SetObject(lifecycleError.ptr(), instance.ptr());
+        }
+    });
     
-    // Esporre SaberProtocol
-    py::class_<saber::SaberProtocol>(m, "SaberProtocol")
+    // Esporre SaberProtocol: i metodi rilasciano il GIL, così i thread del protocollo
+    // possono richiamare i gestori Python mentre un altro thread Python attende
+    const py::call_guard<py::gil_scoped_release> releaseGil{};
+    
+    // Esporre il bus degli eventi: le funzioni Python vengono chiamate dal suo thread
+    py::enum_<saber::ProtocolEvent::Type>(m, "ProtocolEventType")
+        .value("NodeJoined", saber::ProtocolEvent::Type::NodeJoined)
+        .value("NodeLeft", saber::ProtocolEvent::Type::NodeLeft)
+        .value("SyncLost", saber::ProtocolEvent::Type::SyncLost)
+        .value("LatencyAlert", saber::ProtocolEvent::Type::LatencyAlert);
+    
+    py::class_<saber::ProtocolEvent>(m, "ProtocolEvent")
+        .def(py::init<>())
+        .def_readwrite("type", &saber::ProtocolEvent::type)
+        .def_readwrite("node_id", &saber::ProtocolEvent::nodeId)
+        .def_readwrite("latency_ms", &saber::ProtocolEvent::latencyMs)
+        .def_readwrite("budget_ms", &saber::ProtocolEvent::budgetMs)
+        .def_readwrite("timestamp", &saber::ProtocolEvent::timestamp);
+    
+    m.def("protocol_event_type_to_string", &saber::protocolEventTypeToString);
+    
+    py::class_<saber::EventBus>(m, "EventBus")
+        .def(py::init<size_t>(), py::arg("max_pending") = 1024)
+        .def("subscribe", &saber::EventBus::subscribe)
+        .def("unsubscribe", &saber::EventBus::unsubscribe)
+        .def("publish", &saber::EventBus::publish)
+        .def("flush", &saber::EventBus::flush, releaseGil)
+        .def("get_dropped_events", &saber::EventBus::getDroppedEvents);
+    
+    // Esporre il ponte tra due reti
+    m.attr("MAX_BRIDGE_HOPS") = saber::MAX_BRIDGE_HOPS;
+    
+    py::class_<saber::BridgeConfig>(m, "BridgeConfig")
+        .def(py::init<>())
+        .def_readwrite("bridge_id", &saber::BridgeConfig::bridgeId)
+        .def_readwrite("forward", &saber::BridgeConfig::forward)
+        .def_readwrite("reverse", &saber::BridgeConfig::reverse);
+    
+    py::class_<saber::BridgeStats>(m, "BridgeStats")
+        .def_readonly("frames_forwarded", &saber::BridgeStats::framesForwarded)
+        .def_readonly("frames_reversed", &saber::BridgeStats::framesReversed)
+        .def_readonly("metadata_bridged", &saber::BridgeStats::metadataBridged)
+        .def_readonly("loops_prevented", &saber::BridgeStats::loopsPrevented)
+        .def_readonly("dropped", &saber::BridgeStats::dropped);
+    
+    py::class_<saber::NetworkBridge, std::shared_ptr<saber::NetworkBridge>>(m, "NetworkBridge")
+        .def(py::init<std::shared_ptr<saber::SaberProtocol>, std::shared_ptr<saber::SaberProtocol>,
+                      const saber::BridgeConfig&>(), py::arg("first"), py::arg("second"), py::arg("config"))
+        .def("start", &saber::NetworkBridge::start, releaseGil)
+        .def("stop", &saber::NetworkBridge::stop, releaseGil)
+        .def("is_running", &saber::NetworkBridge::isRunning)
+        .def("get_config", &saber::NetworkBridge::getConfig)
+        .def("get_stats", &saber::NetworkBridge::getStats);
+    
+    // Esporre MeshNetwork da sola, senza protocollo, per provarne le regole di instradamento
+    py::class_<saber::MeshNetwork, std::shared_ptr<saber::MeshNetwork>>(m, "MeshNetwork")
+        .def(py::init<const saber::Node&>(), py::arg("local_node"))
+        .def("start", &saber::MeshNetwork::start, releaseGil)
+        .def("stop", &saber::MeshNetwork::stop, releaseGil)
+        .def("is_running", &saber::MeshNetwork::isRunning)
+        .def("set_crypto", &saber::MeshNetwork::setCrypto, releaseGil)
+        .def("set_link_sender", [](saber::MeshNetwork& self, std::function<void(py::bytes)> sender) {
+            // I pacchetti in uscita arrivano come bytes, dal thread della rete
+            self.setLinkSender([sender](const std::vector<uint8_t>& datagram) {
+                py::gil_scoped_acquire gil;
+                sender(py::bytes(reinterpret_cast<const char*>(datagram.data()), datagram.size()));
+            });
+        })
+        .def("receive_from_link", [](saber::MeshNetwork& self, const py::bytes& datagram) {
+            return self.receiveFromLink(fromBytes(datagram));
+        })
+        .def("set_send_rejects", &saber::MeshNetwork::setSendRejects, releaseGil)
+        .def("set_require_signatures", &saber::MeshNetwork::setRequireSignatures, releaseGil)
+        .def("get_drop_counters", &saber::MeshNetwork::getDropCounters, releaseGil)
+        .def("register_node", &saber::MeshNetwork::registerNode, releaseGil)
+        .def("update_node_status", &saber::MeshNetwork::updateNodeStatus, releaseGil)
+        .def("get_active_nodes", &saber::MeshNetwork::getActiveNodes, releaseGil)
+        .def("set_liveness_config", &saber::MeshNetwork::setLivenessConfig, releaseGil)
+        .def("check_node_liveness", &saber::MeshNetwork::checkNodeLiveness, releaseGil)
+        .def("get_evicted_nodes", &saber::MeshNetwork::getEvictedNodes, releaseGil)
+        .def("open_provisioning", &saber::MeshNetwork::openProvisioning, releaseGil)
+        .def("set_capacity_limits", &saber::MeshNetwork::setCapacityLimits, releaseGil)
+        .def("get_admission_stats", &saber::MeshNetwork::getAdmissionStats, releaseGil)
+        .def("subscribe", &saber::MeshNetwork::subscribe, releaseGil)
+        .def("unsubscribe", &saber::MeshNetwork::unsubscribe, releaseGil)
+        .def("get_subscribers", &saber::MeshNetwork::getSubscribers, releaseGil)
+        .def("get_forward_targets", &saber::MeshNetwork::getForwardTargets, releaseGil)
+        .def("get_distribution_tree", &saber::MeshNetwork::getDistributionTree, releaseGil)
+        .def("add_link", &saber::MeshNetwork::addLink, releaseGil)
+        .def("remove_link", &saber::MeshNetwork::removeLink, releaseGil);
+    
+    py::class_<saber::SaberProtocol, std::shared_ptr<saber::SaberProtocol>> protocolClass(m, "SaberProtocol");
+    protocolClass
         .def(py::init<const saber::SaberConfig&>())
-        .def("initialize", &saber::SaberProtocol::initialize)
-        .def("get_sync_manager", &saber::SaberProtocol::getSyncManager)
-        .def("start_audio_playback", &saber::SaberProtocol::startAudioPlayback)
-        .def("stop_audio_playback", &saber::SaberProtocol::stopAudioPlayback)
-        .def("update_time_sync", &saber::SaberProtocol::updateTimeSync)
-        .def("get_current_latency", &saber::SaberProtocol::getCurrentLatency)
-        .def("register_node", &saber::SaberProtocol::registerNode,
+        .def("initialize", &saber::SaberProtocol::initialize, releaseGil)
+        .def("shutdown", &saber::SaberProtocol::shutdown, releaseGil)
+        .def("restart", &saber::SaberProtocol::restart, releaseGil)
+        .def("set_role", &saber::SaberProtocol::setRole, releaseGil)
+        .def("get_role", &saber::SaberProtocol::getRole, releaseGil)
+        .def("get_sync_manager", &saber::SaberProtocol::getSyncManager, releaseGil)
+        .def("start_audio_playback", &saber::SaberProtocol::startAudioPlayback, releaseGil)
+        .def("stop_audio_playback", &saber::SaberProtocol::stopAudioPlayback, releaseGil)
+        .def("update_time_sync", &saber::SaberProtocol::updateTimeSync, releaseGil)
+        .def("request_emergency_sync", &saber::SaberProtocol::requestEmergencySync, releaseGil)
+        .def("get_emergency_sync_stats", &saber::SaberProtocol::getEmergencySyncStats, releaseGil)
+        .def("get_current_latency", &saber::SaberProtocol::getCurrentLatency, releaseGil)
+        .def("register_node", &saber::SaberProtocol::registerNode, releaseGil,
              py::arg("node_id"), py::arg("role"), py::arg("address") = py::none())
-        .def("get_active_nodes", &saber::SaberProtocol::getActiveNodes)
-        .def("is_synchronized", &saber::SaberProtocol::isSynchronized);
+        .def("get_active_nodes", &saber::SaberProtocol::getActiveNodes, releaseGil)
+        .def("get_active_node_info", &saber::SaberProtocol::getActiveNodeInfo, releaseGil)
+        .def("get_evicted_nodes", &saber::SaberProtocol::getEvictedNodes, releaseGil)
+        .def("subscribe_stream", &saber::SaberProtocol::subscribeStream, releaseGil)
+        .def("unsubscribe_stream", &saber::SaberProtocol::unsubscribeStream, releaseGil)
+        .def("publish_stream", &saber::SaberProtocol::publishStream, releaseGil,
+             py::arg("stream_id"), py::arg("is_music") = true)
+        .def("set_stream_format", &saber::SaberProtocol::setStreamFormat, releaseGil)
+        .def("set_audio_profile", &saber::SaberProtocol::setAudioProfile, releaseGil)
+        .def("get_audio_profile", &saber::SaberProtocol::getAudioProfile, releaseGil)
+        .def("publish_simulcast", &saber::SaberProtocol::publishSimulcast, releaseGil,
+             py::arg("group"), py::arg("is_music") = true)
+        .def("stop_simulcast", &saber::SaberProtocol::stopSimulcast, releaseGil)
+        .def("get_simulcast_groups", &saber::SaberProtocol::getSimulcastGroups, releaseGil)
+        .def("set_simulcast_budget", &saber::SaberProtocol::setSimulcastBudget, releaseGil)
+        .def("get_simulcast_status", &saber::SaberProtocol::getSimulcastStatus, releaseGil)
+        .def("export_topology", &saber::SaberProtocol::exportTopology, releaseGil)
+        .def("get_topology", &saber::SaberProtocol::getTopology, releaseGil)
+        .def("export_topology_graph", &saber::SaberProtocol::exportTopologyGraph, releaseGil)
+        .def("get_drop_counters", &saber::SaberProtocol::getDropCounters, releaseGil)
+        .def("get_rate_limit_stats", &saber::SaberProtocol::getRateLimitStats, releaseGil)
+        .def("get_admission_stats", &saber::SaberProtocol::getAdmissionStats, releaseGil)
+        .def("set_capacity_limits", &saber::SaberProtocol::setCapacityLimits, releaseGil)
+        .def("get_packet_queue_stats", &saber::SaberProtocol::getPacketQueueStats, releaseGil)
+        .def("get_security_events", &saber::SaberProtocol::getSecurityEvents, releaseGil)
+        .def("get_quarantined_nodes", &saber::SaberProtocol::getQuarantinedNodes, releaseGil)
+        .def("report_link_quality", &saber::SaberProtocol::reportLinkQuality,
+             py::arg("peer"), py::arg("kind"), py::arg("link_quality"), py::arg("latency_ms"),
+             py::arg("rssi_dbm") = std::nullopt, releaseGil)
+        .def("get_link_qualities", &saber::SaberProtocol::getLinkQualities, releaseGil)
+        .def("report_link_down", &saber::SaberProtocol::reportLinkDown, releaseGil)
+        .def("get_transport_stats", &saber::SaberProtocol::getTransportStats, releaseGil)
+        .def("get_failover_events", &saber::SaberProtocol::getFailoverEvents, releaseGil)
+        .def("get_events_since", &saber::SaberProtocol::getEventsSince, releaseGil,
+             py::arg("cursor"), py::arg("limit") = 256)
+        .def("resolve_key_conflict", &saber::SaberProtocol::resolveKeyConflict, releaseGil)
+        .def("get_intrusion_alerts", &saber::SaberProtocol::getIntrusionAlerts, releaseGil)
+        .def("release_quarantine", &saber::SaberProtocol::releaseQuarantine, releaseGil)
+        .def("reload_config", &saber::SaberProtocol::reloadConfig, releaseGil)
+        .def("set_log_filter", &saber::SaberProtocol::setLogFilter, releaseGil,
+             py::arg("filter"), py::arg("mesh_wide") = false, py::arg("target_node") = py::none())
+        .def("get_log_filter", &saber::SaberProtocol::getLogFilter, releaseGil)
+        .def("open_provisioning", &saber::SaberProtocol::openProvisioning, releaseGil)
+        .def("close_provisioning", &saber::SaberProtocol::closeProvisioning, releaseGil)
+        .def("get_provisioning_status", &saber::SaberProtocol::getProvisioningStatus, releaseGil)
+        .def("request_join", &saber::SaberProtocol::requestJoin, releaseGil)
+        .def("issue_control_token", &saber::SaberProtocol::issueControlToken, releaseGil)
+        .def("verify_control_token", &saber::SaberProtocol::verifyControlToken, releaseGil)
+        .def("revoke_control_token", &saber::SaberProtocol::revokeControlToken, releaseGil)
+        .def("revoke_node_tokens", &saber::SaberProtocol::revokeNodeTokens, releaseGil)
+        .def("get_revocations", &saber::SaberProtocol::getRevocations, releaseGil)
+        .def("open_pairing", &saber::SaberProtocol::openPairing, releaseGil)
+        .def("close_pairing", &saber::SaberProtocol::closePairing, releaseGil)
+        .def("start_pairing", &saber::SaberProtocol::startPairing, releaseGil)
+        .def("get_pairing_status", &saber::SaberProtocol::getPairingStatus, releaseGil)
+        .def("configure_bass_management", &saber::SaberProtocol::configureBassManagement, releaseGil)
+        .def("get_bass_settings", &saber::SaberProtocol::getBassSettings, releaseGil)
+        .def("set_zone_delay", &saber::SaberProtocol::setZoneDelay, releaseGil)
+        .def("get_zone_delays", &saber::SaberProtocol::getZoneDelays, releaseGil)
+        .def("get_acoustic_delay_ms", &saber::SaberProtocol::getAcousticDelayMs, releaseGil)
+        .def("get_delay_equalization", &saber::SaberProtocol::getDelayEqualization, releaseGil)
+        .def("publish_stream_metadata", &saber::SaberProtocol::publishStreamMetadata, releaseGil,
+             py::arg("metadata"), py::arg("artwork") = std::vector<uint8_t>())
+        .def("get_stream_metadata", &saber::SaberProtocol::getStreamMetadata, releaseGil)
+        .def("request_artwork", &saber::SaberProtocol::requestArtwork, releaseGil)
+        .def("get_artwork", [](const saber::SaberProtocol& self, const std::string& artworkHash) -> py::object {
+            auto artwork = self.getArtwork(artworkHash);
+            if (!artwork) {
+                return py::none();
+            }
+            return py::bytes(reinterpret_cast<const char*>(artwork->data()), artwork->size());
+        })
+        .def("get_bandwidth_report", &saber::SaberProtocol::getBandwidthReport, releaseGil)
+        .def("get_network_stats", &saber::SaberProtocol::getNetworkStats, releaseGil)
+        .def("get_prometheus_metrics", &saber::SaberProtocol::getPrometheusMetrics, releaseGil)
+        .def("export_state", &saber::SaberProtocol::exportState, releaseGil)
+        .def("import_state", &saber::SaberProtocol::importState, releaseGil)
+        .def("get_congestion_state", &saber::SaberProtocol::getCongestionState, releaseGil)
+        .def("get_recommended_bitrate_kbps", &saber::SaberProtocol::getRecommendedBitrateKbps, releaseGil)
+        .def("set_stream_priority", &saber::SaberProtocol::setStreamPriority, releaseGil)
+        .def("get_stream_priority", &saber::SaberProtocol::getStreamPriority, releaseGil)
+        .def("get_flow_status", &saber::SaberProtocol::getFlowStatus, releaseGil)
+        .def("get_degradation_settings", &saber::SaberProtocol::getDegradationSettings, releaseGil)
+        .def("get_suspended_sinks", &saber::SaberProtocol::getSuspendedSinks, releaseGil)
+        .def("get_repair_stats", &saber::SaberProtocol::getRepairStats, releaseGil)
+        .def("get_reliable_stats", &saber::SaberProtocol::getReliableStats, releaseGil)
+        .def("get_session_stats", &saber::SaberProtocol::getSessionStats, releaseGil)
+        .def("get_jitter_buffer_stats", &saber::SaberProtocol::getJitterBufferStats, releaseGil)
+        .def("get_phantom_stats", &saber::SaberProtocol::getPhantomStats, releaseGil)
+        .def("get_a2dp_bridge_stats", &saber::SaberProtocol::getA2dpBridgeStats, releaseGil)
+        .def("get_session_reports", &saber::SaberProtocol::getSessionReports, releaseGil, py::arg("node_id") = "")
+        .def("start_sync_probe", &saber::SaberProtocol::startSyncProbe, releaseGil,
+             py::arg("peer"), py::arg("samples") = 20, py::arg("click") = false)
+        .def("get_sync_probe_results", &saber::SaberProtocol::getSyncProbeResults, releaseGil)
+        .def("report_acoustic_skew", &saber::SaberProtocol::reportAcousticSkew, releaseGil)
+        .def("set_sync_click_handler", &saber::SaberProtocol::setSyncClickHandler)
+        .def("set_sync_state_handler", &saber::SaberProtocol::setSyncStateHandler)
+        .def("on_node_joined", &saber::SaberProtocol::onNodeJoined)
+        .def("on_node_left", &saber::SaberProtocol::onNodeLeft)
+        .def("on_sync_lost", &saber::SaberProtocol::onSyncLost)
+        .def("on_latency_alert", &saber::SaberProtocol::onLatencyAlert)
+        .def("remove_event_callback", &saber::SaberProtocol::removeEventCallback)
+        .def("flush_events", &saber::SaberProtocol::flushEvents, releaseGil)
+        .def("start_survey", &saber::SaberProtocol::startSurvey, releaseGil)
+        .def("set_survey_position", &saber::SaberProtocol::setSurveyPosition, releaseGil)
+        .def("stop_survey", &saber::SaberProtocol::stopSurvey, releaseGil)
+        .def("get_coverage_report", &saber::SaberProtocol::getCoverageReport, releaseGil)
+        .def("set_rssi_provider", &saber::SaberProtocol::setRssiProvider)
+        .def("set_advertiser", &saber::SaberProtocol::setAdvertiser)
+        .def("report_advertisement", &saber::SaberProtocol::reportAdvertisement, releaseGil)
+        .def("get_discovered_nodes", &saber::SaberProtocol::getDiscoveredNodes, releaseGil)
+        .def("take_trace_spans", &saber::SaberProtocol::takeTraceSpans, releaseGil)
+        .def("set_path_recording", &saber::SaberProtocol::setPathRecording, releaseGil)
+        .def("get_route_traces", &saber::SaberProtocol::getRouteTraces, releaseGil)
+        .def("request_route_trace", &saber::SaberProtocol::requestRouteTrace, releaseGil)
+        .def("get_remote_route_traces", &saber::SaberProtocol::getRemoteRouteTraces, releaseGil)
+        .def("record_pipeline_stage", &saber::SaberProtocol::recordPipelineStage, releaseGil)
+        .def("get_pipeline_timings", &saber::SaberProtocol::getPipelineTimings, releaseGil)
+        .def("set_pipeline_trace_file", &saber::SaberProtocol::setPipelineTraceFile, releaseGil)
+        .def("send_audio_frame", &saber::SaberProtocol::sendAudioFrame, releaseGil)
+        .def("send_pcm_frame", &saber::SaberProtocol::sendPcmFrame, releaseGil)
+        .def("set_audio_source", &saber::SaberProtocol::setAudioSource, releaseGil)
+        .def("get_audio_source_stats", &saber::SaberProtocol::getAudioSourceStats, releaseGil)
+        .def("set_audio_output", &saber::SaberProtocol::setAudioOutput, releaseGil)
+        .def("get_audio_output_stats", &saber::SaberProtocol::getAudioOutputStats, releaseGil)
+        .def("set_audio_frame_handler", &saber::SaberProtocol::setAudioFrameHandler)
+        .def("get_node_info", [](const saber::SaberProtocol& self) {
+            auto info = self.getNodeInfo();
+            py::dict nowPlaying;
+            for (const auto& entry : info.nowPlaying) {
+                py::dict track;
+                track["title"] = entry.second.title;
+                track["artist"] = entry.second.artist;
+                track["album"] = entry.second.album;
+                track["artwork_hash"] = entry.second.artworkHash;
+                track["revision"] = entry.second.revision;
+                nowPlaying[py::int_(entry.first)] = track;
+            }
+            py::dict result;
+            result["node_id"] = info.nodeId;
+            result["role"] = saber::nodeRoleToString(info.role);
+            result["is_synchronized"] = info.isSynchronized;
+            result["latency"] = info.latency;
+            result["now_playing"] = nowPlaying;
+            result["active_playlist"] = info.activePlaylist;
+            return result;
+        })
+        .def("negotiate_frame_encryption", &saber::SaberProtocol::negotiateFrameEncryption, releaseGil)
+        .def("assign_zone", &saber::SaberProtocol::assignZone, releaseGil)
+        .def("suggest_group_splits", &saber::SaberProtocol::suggestGroupSplits, releaseGil)
+        .def("apply_group_split", &saber::SaberProtocol::applyGroupSplit, releaseGil)
+        .def("start_group_playback", &saber::SaberProtocol::startGroupPlayback, releaseGil,
+             py::arg("zone"), py::arg("playlist") = "")
+        .def("stop_group_playback", &saber::SaberProtocol::stopGroupPlayback, releaseGil)
+        .def("pause_group_playback", &saber::SaberProtocol::pauseGroupPlayback, releaseGil)
+        .def("resume_group_playback", &saber::SaberProtocol::resumeGroupPlayback, releaseGil)
+        .def("set_zone_volume", &saber::SaberProtocol::setZoneVolume, releaseGil)
+        .def("get_volume", &saber::SaberProtocol::getVolume, releaseGil)
+        .def("is_playback_paused", &saber::SaberProtocol::isPlaybackPaused, releaseGil)
+        .def("seek_group_playback", &saber::SaberProtocol::seekGroupPlayback, releaseGil)
+        .def("set_playback_command_handler", &saber::SaberProtocol::setPlaybackCommandHandler)
+        .def("get_command_statuses", &saber::SaberProtocol::getCommandStatuses, releaseGil)
+        .def("get_command_status", &saber::SaberProtocol::getCommandStatus, releaseGil)
+        .def("start_party_mode", &saber::SaberProtocol::startPartyMode, releaseGil,
+             py::arg("zones"), py::arg("stream_id"), py::arg("playlist") = "")
+        .def("stop_party_mode", &saber::SaberProtocol::stopPartyMode, releaseGil)
+        .def("get_party_mode", &saber::SaberProtocol::getPartyMode, releaseGil)
+        .def("start_intercom", &saber::SaberProtocol::startIntercom, releaseGil)
+        .def("stop_intercom", &saber::SaberProtocol::stopIntercom, releaseGil)
+        .def("set_intercom_talking", &saber::SaberProtocol::setIntercomTalking, releaseGil)
+        .def("send_intercom_frame", &saber::SaberProtocol::sendIntercomFrame, releaseGil)
+        .def("set_intercom_frame_handler", &saber::SaberProtocol::setIntercomFrameHandler)
+        .def("get_intercom_session", &saber::SaberProtocol::getIntercomSession, releaseGil)
+        .def("get_beacon_interval_ms", &saber::SaberProtocol::getBeaconIntervalMs, releaseGil)
+        .def("set_standby_handler", &saber::SaberProtocol::setStandbyHandler)
+        .def("get_power_state", &saber::SaberProtocol::getPowerState, releaseGil)
+        .def("wake_from_standby", &saber::SaberProtocol::wakeFromStandby, releaseGil)
+        .def("wake_sinks", &saber::SaberProtocol::wakeSinks, releaseGil, py::arg("node_id") = "")
+        .def("send_source_frame", &saber::SaberProtocol::sendSourceFrame, releaseGil)
+        .def("switch_source", &saber::SaberProtocol::switchSource, releaseGil)
+        .def("resume_automatic_source", &saber::SaberProtocol::resumeAutomaticSource, releaseGil)
+        .def("get_active_source", &saber::SaberProtocol::getActiveSource, releaseGil)
+        .def("get_source_status", &saber::SaberProtocol::getSourceStatus, releaseGil)
+        .def("is_synchronized", &saber::SaberProtocol::isSynchronized, releaseGil)
+        .def("get_network_time_us", &saber::SaberProtocol::getNetworkTimeUs, releaseGil)
+        .def("get_state", &saber::SaberProtocol::getState, releaseGil)
+        .def("get_preflight_report", &saber::SaberProtocol::getPreflightReport, releaseGil)
+        .def("get_state_recovery", &saber::SaberProtocol::getStateRecovery, releaseGil)
+        .def("get_udp_transport_stats", &saber::SaberProtocol::getUdpTransportStats, releaseGil)
+        .def("set_sim_network", &saber::SaberProtocol::setSimNetwork)
+        .def("set_authorization_handler", [](saber::SaberProtocol& self, 
+                                             std::function<void(const saber::AuthorizationRequest&, py::object)> handler) {
+            self.setAuthorizationHandler(authorizationHandler(handler));
+        })
+        .def("get_authorization_stats", &saber::SaberProtocol::getAuthorizationStats, releaseGil)
+        .def("is_live", &saber::SaberProtocol::isLive, releaseGil)
+        .def("is_ready", &saber::SaberProtocol::isReady, releaseGil);
+    
+    // La programmazione oraria appartiene al Master, assente nella build minimal-sink
+#ifndef SABER_MINIMAL_SINK
+    protocolClass
+        .def("add_scheduled_action", &saber::SaberProtocol::addScheduledAction, releaseGil)
+        .def("update_scheduled_action", &saber::SaberProtocol::updateScheduledAction, releaseGil)
+        .def("remove_scheduled_action", &saber::SaberProtocol::removeScheduledAction, releaseGil)
+        .def("get_schedule", &saber::SaberProtocol::getSchedule, releaseGil);
+#endif
+    
+    m.def("minimal_sink_build", &saber::minimalSinkBuild);
+    
+    // Varianti attendibili da asyncio dei metodi che possono bloccare a lungo; la versione
+    // sincrona chiamata dal thread di un loop in corso lo fermerebbe e viene segnalata
+    for (const char* method : {"initialize", "shutdown", "restart", "set_role", "start_audio_playback", 
+                               "stop_audio_playback", "start_group_playback", "stop_group_playback", 
+                               "pause_group_playback", "resume_group_playback", "seek_group_playback", 
+                               "start_party_mode", "stop_party_mode"}) {
+        py::object blocking = protocolClass.attr(method);
+        protocolClass.attr(method) = py::cpp_function(
+            [method, blocking](const py::object& self, const py::args& args, const py::kwargs& kwargs) {
+                warnIfLoopRunning(method);
+                return blocking(self, *args, **kwargs);
+            }, py::name(method), py::is_method(protocolClass));
+        protocolClass.def((std::string(method) + "_async").c_str(), 
+                          [method](const py::object& self, const py::args& args, const py::kwargs& kwargs) {
+            return runInExecutor(self, method, args, kwargs);
+        });
+    }
+    protocolClass.def("send_packet_async", [](saber::SaberProtocol& self, const saber::MeshPacket& packet) {
+        py::object loop = py::module_::import("asyncio").attr("get_running_loop")();
+        std::future<bool> delivery;
+        {
+            py::gil_scoped_release release;
+            delivery = self.sendPacketAsync(packet);
+        }
+        return awaitInExecutor(loop, std::move(delivery));
+    });
     
     // Esporre funzioni di utilità
-    m.def("start_master", &saber::startMaster, 
+    m.def("start_master", &saber::startMaster, releaseGil, 
           py::arg("node_id") = py::none(), 
           py::arg("bt_address") = py::none(),
           py::return_value_policy::take_ownership);
     
-    m.def("start_repeater", &saber::startRepeater,
+    m.def("start_repeater", &saber::startRepeater, releaseGil,
           py::arg("node_id") = py::none(),
           py::arg("bt_address") = py::none(),
           py::return_value_policy::take_ownership);
     
-    m.def("start_sink", &saber::startSink,
+    m.def("start_sink", &saber::startSink, releaseGil,
           py::arg("node_id") = py::none(),
           py::arg("bt_address") = py::none(),
           py::arg("is_music") = true,
           py::return_value_policy::take_ownership);
+    
+    m.def("start_node", &saber::startNode, releaseGil,
+          py::arg("config"),
+          py::return_value_policy::take_ownership);
+    
+    // Esporre la lettura delle opzioni della riga di comando "saber"
+    py::class_<saber::NodeCommandLine>(m, "NodeCommandLine")
+        .def_readonly("config", &saber::NodeCommandLine::config)
+        .def_readonly("play_path", &saber::NodeCommandLine::playPath)
+        .def_readonly("loop", &saber::NodeCommandLine::loop)
+        .def_readonly("record_path", &saber::NodeCommandLine::recordPath)
+        .def_readonly("interval_seconds", &saber::NodeCommandLine::intervalSeconds)
+        .def_readonly("plain", &saber::NodeCommandLine::plain);
+    
+    m.def("parse_node_command_line", &saber::parseNodeCommandLine, py::arg("role"), py::arg("args"));
 }
@@ -0,0 +1,24 @@
+// Fuzzing della decodifica dei pacchetti mesh (libFuzzer)
+// Ogni input deve essere rifiutato con std::invalid_argument oppure
+// decodificato in un pacchetto la cui codifica è stabile
+
+#include "mesh.h"
+
+#include <cstdint>
+#include <cstdlib>
+#include <stdexcept>
+#include <vector>
+
+extern "C" int LLVMFuzzerTestOneInput(const uint8_t* data, size_t size) {
+    std::vector<uint8_t> bytes(data, data + size);
+    try {
+        saber::MeshPacket packet = saber::MeshPacket::decode(bytes);
+        std::vector<uint8_t> encoded = packet.encode();
+        if (saber::MeshPacket::decode(encoded).encode() != encoded) {
+            std::abort();
+        }
+    } catch (const std::invalid_argument&) {
+        // Input non valido rifiutato: comportamento atteso
+    }
+    return 0;
+}
@@ -0,0 +1,178 @@
+#!/usr/bin/env python3
+# Genera le annotazioni di tipo (.pyi) del modulo saber_protocol a partire dalle firme di pybind11
+#
+# Uso:
+#   python gen_stubs.py <cartella del modulo compilato> [file .pyi]
+#
+# Senza file di uscita scrive saber_protocol.pyi nella cartella del modulo, dove gli IDE lo trovano.
+# I tipi C++ che pybind11 non sa nominare in Python diventano Any.
+
+import ast
+import importlib
+import inspect
+import os
+import re
+import sys
+
+MODULE_NAME = "saber_protocol"
+
+HEADER = [
+    "# Annotazioni di tipo del modulo saber_protocol",
+    "# File generato da src/tools/gen_stubs.py: non modificare a mano",
+    "",
+    "from typing import Any, Callable, ClassVar, Dict, Iterable, Iterator, List, Optional, Set, Tuple, Union, overload",
+    "",
+]
+
+# Metodi speciali che hanno senso per chi usa il modulo
+DUNDERS = {"__init__", "__call__", "__eq__", "__int__", "__len__", "__iter__", "__getitem__", "__contains__",
+           "__enter__", "__exit__", "__hash__"}
+
+def split_top_level(text, separator=","):
+    """Divide il testo sui separatori che non stanno tra parentesi o virgolette"""
+    parts, depth, quote, start = [], 0, None, 0
+    for i, char in enumerate(text):
+        if quote:
+            if char == quote:
+                quote = None
+        elif char in "'\"":
+            quote = char
+        elif char in "([{<":
+            depth += 1
+        elif char in ")]}>" and not (char == ">" and text[i - 1] == "-"):
+            depth -= 1
+        elif char == separator and depth == 0:
+            parts.append(text[start:i])
+            start = i + 1
+    parts.append(text[start:])
+    return [part.strip() for part in parts if part.strip()]
+
+def clean_type(text):
+    """Toglie il nome del modulo dai tipi e sostituisce quelli che Python non conosce"""
+    text = text.strip().replace(MODULE_NAME + ".", "")
+    # pybind11 scrive i tipi senza binding come nomi C++, ad esempio std::function<...> o saber::Foo
+    if "::" in text or re.search(r"[<>]", text.replace("->", "")):
+        return "Any"
+    text = re.sub(r"\bnumpy\.\w+\b", "Any", text)
+    return text or "Any"
+
+def parse_signature(line, name):
+    """Interpreta una firma di pybind11: nome(a: T, b: U = valore) -> R"""
+    match = re.match(r"^(?:\d+\.\s+)?" + re.escape(name) + r"\((.*)\)\s*->\s*(.+)$", line.strip())
+    if not match:
+        return None
+    params = []
+    for param in split_top_level(match.group(1)):
+        if param in ("*args", "**kwargs", "*"):
+            params.append(param)
+            continue
+        default = ""
+        parts = split_top_level(param, "=")
+        if len(parts) > 1:
+            param, default = parts[0], " = ..."
+        if ":" in param:
+            param_name, param_type = param.split(":", 1)
+            params.append("{}: {}{}".format(param_name.strip(), clean_type(param_type), default))
+        else:
+            params.append(param.strip() + default)
+    return params, clean_type(match.group(2))
+
+def signatures(obj, name):
+    """Firme di una funzione pybind11, una per ogni sovraccarico"""
+    doc = inspect.getdoc(obj) or ""
+    lines = doc.splitlines()
+    overloaded = len(lines) > 1 and lines[1].strip() == "Overloaded function."
+    found = []
+    for line in lines[2:] if overloaded else lines[:1]:
+        parsed = parse_signature(line, name)
+        if parsed:
+            found.append(parsed)
+    return found or [(["*args: Any", "**kwargs: Any"], "Any")]
+
+def function_stub(obj, name, indent="", method=False, static=False):
+    """Righe dello stub di una funzione o di un metodo"""
+    lines = []
+    overloads = signatures(obj, name)
+    for params, result in overloads:
+        if method and not static and params and params[0].startswith("self"):
+            params = ["self"] + params[1:]
+        elif method and not static:
+            params = ["self"] + params
+        if len(overloads) > 1:
+            lines.append(indent + "@overload")
+        if static:
+            lines.append(indent + "@staticmethod")
+        lines.append("{}def {}({}) -> {}: ...".format(indent, name, ", ".join(params), result))
+    return lines
+
+def property_stub(prop, name, indent):
+    """Righe dello stub di una proprietà: attributo se scrivibile, @property altrimenti"""
+    result = "Any"
+    doc = inspect.getdoc(prop.fget) if prop.fget else None
+    if doc:
+        match = re.search(r"->\s*(.+)$", doc.splitlines()[0])
+        if match:
+            result = clean_type(match.group(1))
+    if prop.fset is not None:
+        return ["{}{}: {}".format(indent, name, result)]
+    return [indent + "@property", "{}def {}(self) -> {}: ...".format(indent, name, result)]
+
+def class_stub(cls, module):
+    """Righe dello stub di una classe, di un enum o di un'eccezione"""
+    bases = [base.__name__ for base in cls.__bases__
+             if getattr(module, base.__name__, None) is base or base.__module__ == "builtins"]
+    bases = [base for base in bases if base != "object"]
+    lines = ["class {}{}:".format(cls.__name__, "(" + ", ".join(bases) + ")" if bases else "")]
+    members = getattr(cls, "__members__", None) if not issubclass(cls, BaseException) else None
+    body = []
+    if members:
+        for member in members:
+            body.append("    {}: ClassVar[{}]".format(member, cls.__name__))
+    for name, value in sorted(cls.__dict__.items()):
+        if (name.startswith("_") and name not in DUNDERS) or (members and name in members):
+            continue
+        if isinstance(value, property):
+            body.extend(property_stub(value, name, "    "))
+        elif isinstance(value, staticmethod):
+            body.extend(function_stub(value.__func__, name, "    ", method=True, static=True))
+        elif callable(value):
+            body.extend(function_stub(value, name, "    ", method=True))
+        elif not members:
+            body.append("    {}: {}".format(name, clean_type(type(value).__name__)))
+    return lines + (body or ["    ..."])
+
+def generate(module):
+    """Testo dello stub del modulo"""
+    lines = list(HEADER)
+    for name in sorted(dir(module)):
+        value = getattr(module, name)
+        if name.startswith("_") or inspect.ismodule(value):
+            continue
+        if isinstance(value, type):
+            lines.extend(class_stub(value, module))
+            lines.append("")
+        elif callable(value):
+            lines.extend(function_stub(value, name))
+            lines.append("")
+        else:
+            lines.append("{}: {}".format(name, clean_type(type(value).__name__)))
+            lines.append("")
+    text = "\n".join(lines).rstrip() + "\n"
+    # Uno stub che Python non sa leggere è peggio di nessuno stub
+    ast.parse(text)
+    return text
+
+def main(argv):
+    if len(argv) not in (2, 3):
+        print("Uso: gen_stubs.py <cartella del modulo> [file .pyi]", file=sys.stderr)
+        return 2
+    sys.path.insert(0, os.path.abspath(argv[1]))
+    module = importlib.import_module(MODULE_NAME)
+    output = argv[2] if len(argv) == 3 else os.path.join(argv[1], MODULE_NAME + ".pyi")
+    with open(output, "w") as stub:
+        stub.write(generate(module))
+    print("Annotazioni di tipo scritte in " + output)
+    return 0
+
+if __name__ == "__main__":
+    sys.exit(main(sys.argv))
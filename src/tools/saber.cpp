@@ -0,0 +1,148 @@
+// Riga di comando per eseguire un nodo SABER
+//
+// Uso:
+//   saber master|repeater|sink [--id <nodeId>] [--bt <indirizzo>] [--transport ble|udp]
+//                              [--music | --voice] [--config file.toml] [--interval s] [--plain]
+//                              [--play file.wav [--loop]] [--record file.wav]
+//
+// Il nodo resta attivo fino a Ctrl+C. Nel frattempo il cruscotto mostra lo
+// stato della sincronizzazione, i nodi della rete e le loro latenze,
+// ridisegnato ogni --interval secondi; con --plain i resoconti vengono
+// accodati senza ripulire il terminale (utile per i log di servizio).
+// Il Master con --play trasmette il file WAV sullo stream 0, che deve
+// averne il formato (48kHz stereo in modalità musica); il sink con
+// --record registra lo stream 0 nel file WAV, seguendone la riproduzione.
+
+#include "saber_protocol.h"
+
+#include <atomic>
+#include <chrono>
+#include <csignal>
+#include <iomanip>
+#include <iostream>
+#include <memory>
+#include <sstream>
+#include <string>
+#include <thread>
+#include <vector>
+
+using namespace saber;
+
+namespace {
+
+// Stream su cui il Master trasmette il file indicato con --play e che il sink registra con --record
+const StreamId PLAY_STREAM = 0;
+
+// Sequenza ANSI: cancella lo schermo e riporta il cursore in alto a sinistra
+const char* const CLEAR_SCREEN = "\033[2J\033[H";
+
+std::atomic<bool> stopRequested{false};
+
+void requestStop(int) {
+    stopRequested = true;
+}
+
+void printUsage() {
+    std::cerr << "Uso: saber master|repeater|sink [--id <nodeId>] [--bt <indirizzo>] [--transport ble|udp]\n"
+              << "                              [--music | --voice] [--config file.toml] [--interval s] [--plain]\n"
+              << "                              [--play file.wav [--loop]] [--record file.wav]" << std::endl;
+}
+
+std::string renderDashboard(const SaberProtocol& protocol) {
+    std::ostringstream out;
+    NodeInfo info = protocol.getNodeInfo();
+    out << "SABER " << info.nodeId << " (" << nodeRoleToString(info.role) << ")\n"
+        << "stato: " << (protocol.isReady() ? "pronto" : "in attesa")
+        << "  sincronizzato: " << (info.isSynchronized ? "sì" : "no")
+        << "  latenza: " << info.latency << "ms"
+        << "  alimentazione: " << powerStateToString(protocol.getPowerState()) << "\n\n";
+
+    TopologyGraph topology = protocol.getTopology();
+    NetworkStats stats = protocol.getNetworkStats();
+    out << std::left << std::setw(24) << "NODO" << std::setw(10) << "RUOLO" << std::setw(8) << "ATTIVO"
+        << std::setw(6) << "HOP" << std::setw(10) << "P50" << std::setw(10) << "P95" << "PERDITA\n";
+    for (const auto& node : topology.nodes) {
+        out << std::setw(24) << (node.local ? node.id + " *" : node.id)
+            << std::setw(10) << nodeRoleToString(node.role) << std::setw(8) << (node.active || node.local ? "+" : "-")
+            << std::setw(6) << (node.hops ? std::to_string(*node.hops) : "-");
+        auto latency = stats.nodes.find(node.id);
+        if (latency == stats.nodes.end() || latency->second.samples == 0) {
+            out << std::setw(10) << "-" << std::setw(10) << "-" << "-\n";
+            continue;
+        }
+        std::ostringstream loss;
+        loss << std::fixed << std::setprecision(1) << latency->second.lossRate * 100.0 << "%";
+        out << std::setw(10) << (std::to_string(latency->second.p50LatencyMs) + "ms")
+            << std::setw(10) << (std::to_string(latency->second.p95LatencyMs) + "ms") << loss.str() << "\n";
+    }
+    return out.str();
+}
+
+int runNode(NodeRole role, const std::vector<std::string>& args) {
+    NodeCommandLine command;
+    try {
+        command = parseNodeCommandLine(role, args);
+    } catch (const std::invalid_argument& e) {
+        std::cerr << e.what() << std::endl;
+        printUsage();
+        return 2;
+    }
+
+    std::unique_ptr<SaberProtocol> protocol;
+    try {
+        protocol = startNode(command.config);
+        if (!command.playPath.empty()) {
+            protocol->setAudioSource(PLAY_STREAM, PcmFileSource::fromWav(command.playPath, command.loop));
+        }
+        if (!command.recordPath.empty()) {
+            protocol->setAudioOutput(PLAY_STREAM, std::make_shared<WavFileOutput>(command.recordPath));
+        }
+    } catch (const std::exception& e) {
+        std::cerr << e.what() << std::endl;
+        if (protocol) {
+            protocol->shutdown();
+        }
+        return 1;
+    }
+
+    std::signal(SIGINT, requestStop);
+    std::signal(SIGTERM, requestStop);
+    while (!stopRequested) {
+        std::cout << (command.plain ? "" : CLEAR_SCREEN) << renderDashboard(*protocol) << std::endl;
+        // Attese brevi: Ctrl+C interrompe il nodo senza aspettare il prossimo aggiornamento
+        for (int waited = 0; waited < command.intervalSeconds * 10 && !stopRequested; ++waited) {
+            std::this_thread::sleep_for(std::chrono::milliseconds(100));
+        }
+    }
+
+    std::cout << "Arresto del nodo " << protocol->getNodeInfo().nodeId << std::endl;
+    protocol->shutdown();
+    return 0;
+}
+
+} // namespace
+
+int main(int argc, char** argv) {
+    std::vector<std::string> args(argv + 1, argv + argc);
+    if (args.empty()) {
+        printUsage();
+        return 2;
+    }
+
+    std::string mode = args[0];
+    args.erase(args.begin());
+    auto role = nodeRoleFromString(mode);
+    if (!role) {
+        std::cerr << "Ruolo sconosciuto: " << mode << std::endl;
+        printUsage();
+        return 2;
+    }
+
+    try {
+        return runNode(*role, args);
+    } catch (const std::exception& e) {
+        // File di configurazione illeggibile o non valido
+        std::cerr << e.what() << std::endl;
+        return 2;
+    }
+}
@@ -0,0 +1,396 @@
+// Harness di test di integrazione per laboratori LAN multi-macchina
+//
+// Uso:
+//   saber-test peer --id <nodeId> --role master|repeater|sink [--port 7800] [--profile home-music] [--seed N]
+//   saber-test orchestrator --peer host:porta [--peer ...] [--stream-seconds 60] [--report file.json]
+//
+// L'orchestratore pilota i peer via UDP con un protocollo a righe
+// ("<seq> <comando> [argomenti]" -> "<seq> ok|fail <dettaglio>"), esegue lo
+// scenario join -> sync -> stream -> kill repeater -> verifica, ed emette un
+// report JSON pass/fail. Ogni peer esegue un nodo SABER reale in-process.
+
+#include "saber_protocol.h"
+#include "socket_compat.h"
+#include "util.h"
+
+#include <algorithm>
+#include <chrono>
+#include <cstring>
+#include <fstream>
+#include <iostream>
+#include <memory>
+#include <sstream>
+#include <string>
+#include <thread>
+#include <vector>
+
+using namespace saber;
+
+namespace {
+
+const uint16_t DEFAULT_PEER_PORT = 7800;
+const int REQUEST_TIMEOUT_MS = 2000;
+const int REQUEST_RETRIES = 3;
+const int STATUS_POLL_SECONDS = 5;
+
+// Stream usato dallo scenario
+const StreamId SCENARIO_STREAM = 1;
+
+uint64_t wallClockMillis() {
+    return std::chrono::duration_cast<std::chrono::milliseconds>(
+        std::chrono::system_clock::now().time_since_epoch()).count();
+}
+
+std::string optionValue(const std::vector<std::string>& args, const std::string& name,
+                        const std::string& fallback = "") {
+    for (size_t i = 0; i + 1 < args.size(); ++i) {
+        if (args[i] == name) {
+            return args[i + 1];
+        }
+    }
+    return fallback;
+}
+
+std::vector<std::string> optionValues(const std::vector<std::string>& args, const std::string& name) {
+    std::vector<std::string> values;
+    for (size_t i = 0; i + 1 < args.size(); ++i) {
+        if (args[i] == name) {
+            values.push_back(args[i + 1]);
+        }
+    }
+    return values;
+}
+
+intptr_t openUdpSocket(uint16_t port) {
+#ifdef _WIN32
+    WSADATA wsaData;
+    WSAStartup(MAKEWORD(2, 2), &wsaData);
+#endif
+    auto sock = socket(AF_INET, SOCK_DGRAM, 0);
+    if (sock < 0) {
+        return -1;
+    }
+
+    sockaddr_in addr;
+    std::memset(&addr, 0, sizeof(addr));
+    addr.sin_family = AF_INET;
+    addr.sin_addr.s_addr = htonl(INADDR_ANY);
+    addr.sin_port = htons(port);
+    if (bind(sock, reinterpret_cast<sockaddr*>(&addr), sizeof(addr)) != 0) {
+        SABER_CLOSE_SOCKET(sock);
+        return -1;
+    }
+    return static_cast<intptr_t>(sock);
+}
+
+bool waitReadable(intptr_t sock, int timeoutMs) {
+    fd_set readSet;
+    FD_ZERO(&readSet);
+    FD_SET(sock, &readSet);
+    timeval timeout{timeoutMs / 1000, (timeoutMs % 1000) * 1000};
+    return select(static_cast<int>(sock) + 1, &readSet, nullptr, nullptr, &timeout) > 0;
+}
+
+// Nodo pilotato dall'orchestratore
+class Peer {
+public:
+    Peer(const std::string& nodeId, NodeRole role, const std::string& profile,
+         std::shared_ptr<RandomSource> randomSource)
+        : nodeId(nodeId), role(role), profile(profile), randomSource(randomSource) {}
+
+    std::string handle(const std::string& command, const std::vector<std::string>& args) {
+        if (command == "join") {
+            SaberConfig config = profile.empty() ? SaberConfig::defaultConfig() 
+                                                 : SaberConfig::forProfile(profile, role);
+            config.nodeId = nodeId;
+            config.role = role;
+            config.randomSource = randomSource;
+            protocol = std::make_unique<SaberProtocol>(config);
+            if (!protocol->initialize()) {
+                for (const auto& issue : protocol->getPreflightReport().issues) {
+                    if (issue.fatal) {
+                        return "fail " + issue.message;
+                    }
+                }
+                return "fail inizializzazione fallita";
+            }
+            return "ok " + nodeId;
+        }
+        if (command == "shutdown") {
+            protocol.reset();
+            return "ok arresto";
+        }
+        if (command == "kill") {
+            // Termina il nodo solo se ha il ruolo richiesto
+            if (!args.empty() && args[0] != nodeRoleToString(role)) {
+                return "ok non coinvolto";
+            }
+            protocol.reset();
+            return "ok terminato";
+        }
+        if (!protocol) {
+            return command == "status" ? "ok terminato" : "fail nodo non attivo";
+        }
+        if (command == "sync") {
+            uint64_t masterTime = args.empty() ? wallClockMillis() : std::stoull(args[0]);
+            protocol->updateTimeSync(masterTime);
+            return protocol->isSynchronized() ? "ok sincronizzato" : "fail non sincronizzato";
+        }
+        if (command == "stream") {
+            if (role == NodeRole::Sink && !protocol->subscribeStream(SCENARIO_STREAM)) {
+                return "fail sottoscrizione fallita";
+            }
+            return protocol->startAudioPlayback() ? "ok riproduzione avviata" : "fail riproduzione non avviata";
+        }
+        if (command == "status") {
+            if (!protocol->isLive()) {
+                return "fail nodo non vivo";
+            }
+            if (!protocol->isSynchronized()) {
+                return "fail nodo desincronizzato";
+            }
+            return "ok latenza " + std::to_string(protocol->getCurrentLatency()) + "ms";
+        }
+        return "fail comando sconosciuto: " + command;
+    }
+
+private:
+    std::string nodeId;
+    NodeRole role;
+    std::string profile;
+    std::shared_ptr<RandomSource> randomSource;
+    std::unique_ptr<SaberProtocol> protocol;
+};
+
+int runPeer(const std::vector<std::string>& args) {
+    std::string nodeId = optionValue(args, "--id");
+    auto role = nodeRoleFromString(optionValue(args, "--role", "sink"));
+    uint16_t port = static_cast<uint16_t>(std::stoi(optionValue(args, "--port", std::to_string(DEFAULT_PEER_PORT))));
+    std::string profile = optionValue(args, "--profile");
+    if (nodeId.empty() || !role) {
+        std::cerr << "Uso: saber-test peer --id <nodeId> --role master|repeater|sink [--port N] [--profile nome] [--seed N]" 
+                  << std::endl;
+        return 2;
+    }
+    if (!profile.empty() && !findProfile(profile)) {
+        std::cerr << "Profilo sconosciuto: " << profile << std::endl;
+        return 2;
+    }
+
+    intptr_t sock = openUdpSocket(port);
+    if (sock < 0) {
+        std::cerr << "Impossibile aprire la porta UDP " << port << std::endl;
+        return 1;
+    }
+
+    // Con --seed chiavi e nonce del nodo sono riproducibili tra esecuzioni
+    std::shared_ptr<RandomSource> randomSource;
+    std::string seed = optionValue(args, "--seed");
+    if (!seed.empty()) {
+        // FNV-1a dell'ID: seed diverse per nodo, stabili tra piattaforme
+        uint64_t nodeSeed = std::stoull(seed) ^ 0xcbf29ce484222325ULL;
+        for (char c : nodeId) {
+            nodeSeed = (nodeSeed ^ static_cast<uint8_t>(c)) * 0x100000001b3ULL;
+        }
+        randomSource = std::make_shared<SeededRandomSource>(nodeSeed);
+    }
+
+    Peer peer(nodeId, *role, profile, randomSource);
+    std::cout << "Peer " << nodeId << " in ascolto sulla porta " << port << std::endl;
+
+    std::string lastSeq;
+    std::string lastResponse;
+    char buffer[1024];
+    while (true) {
+        sockaddr_in from;
+        socklen_t fromLen = sizeof(from);
+        auto received = recvfrom(sock, buffer, sizeof(buffer) - 1, 0, reinterpret_cast<sockaddr*>(&from), &fromLen);
+        if (received <= 0) {
+            continue;
+        }
+        buffer[received] = '\0';
+
+        std::istringstream request(buffer);
+        std::string seq, command, arg;
+        request >> seq >> command;
+        std::vector<std::string> commandArgs;
+        while (request >> arg) {
+            commandArgs.push_back(arg);
+        }
+
+        // Una richiesta ritrasmessa riceve la stessa risposta senza rieseguire il comando
+        if (seq != lastSeq) {
+            lastSeq = seq;
+            lastResponse = seq + " " + peer.handle(command, commandArgs);
+        }
+        sendto(sock, lastResponse.data(), static_cast<int>(lastResponse.size()), 0,
+               reinterpret_cast<sockaddr*>(&from), fromLen);
+
+        if (command == "shutdown") {
+            break;
+        }
+    }
+
+    SABER_CLOSE_SOCKET(sock);
+    return 0;
+}
+
+struct StepResult {
+    std::string step;
+    std::string peer;
+    bool ok;
+    std::string detail;
+};
+
+class Orchestrator {
+public:
+    // La sequenza parte dall'orologio per non collidere con esecuzioni precedenti
+    explicit Orchestrator(const std::vector<std::string>& peers) : peers(peers), sequence(wallClockMillis()) {
+        sock = openUdpSocket(0);
+    }
+
+    ~Orchestrator() {
+        if (sock >= 0) {
+            SABER_CLOSE_SOCKET(sock);
+        }
+    }
+
+    bool isOpen() const {
+        return sock >= 0;
+    }
+
+    // Esegue un passo su tutti i peer e registra gli esiti
+    bool step(const std::string& name, const std::string& command) {
+        bool allOk = true;
+        for (const auto& peer : peers) {
+            StepResult result{name, peer, false, ""};
+            std::string response;
+            if (!request(peer, command, response)) {
+                result.detail = "nessuna risposta";
+            } else {
+                result.ok = response.rfind("ok", 0) == 0;
+                result.detail = response.size() > 3 ? response.substr(result.ok ? 3 : 5) : "";
+            }
+            allOk = allOk && result.ok;
+            results.push_back(result);
+        }
+        return allOk;
+    }
+
+    std::string report(const std::string& scenario, bool passed) const {
+        std::ostringstream json;
+        json << "{\"scenario\":\"" << jsonEscape(scenario) << "\",\"passed\":" << (passed ? "true" : "false")
+             << ",\"steps\":[";
+        for (size_t i = 0; i < results.size(); ++i) {
+            const auto& r = results[i];
+            json << (i ? "," : "") << "{\"step\":\"" << jsonEscape(r.step) << "\",\"peer\":\""
+                 << jsonEscape(r.peer) << "\",\"ok\":" << (r.ok ? "true" : "false") << ",\"detail\":\""
+                 << jsonEscape(r.detail) << "\"}";
+        }
+        json << "]}";
+        return json.str();
+    }
+
+private:
+    bool request(const std::string& peer, const std::string& command, std::string& response) {
+        auto colon = peer.rfind(':');
+        sockaddr_in addr;
+        std::memset(&addr, 0, sizeof(addr));
+        addr.sin_family = AF_INET;
+        addr.sin_port = htons(colon == std::string::npos ? DEFAULT_PEER_PORT
+                                                         : static_cast<uint16_t>(std::stoi(peer.substr(colon + 1))));
+        if (inet_pton(AF_INET, peer.substr(0, colon).c_str(), &addr.sin_addr) != 1) {
+            return false;
+        }
+
+        std::string seq = std::to_string(++sequence);
+        std::string payload = seq + " " + command;
+        char buffer[1024];
+
+        for (int attempt = 0; attempt < REQUEST_RETRIES; ++attempt) {
+            sendto(sock, payload.data(), static_cast<int>(payload.size()), 0,
+                   reinterpret_cast<sockaddr*>(&addr), sizeof(addr));
+
+            while (waitReadable(sock, REQUEST_TIMEOUT_MS)) {
+                auto received = recv(sock, buffer, sizeof(buffer) - 1, 0);
+                if (received <= 0) {
+                    break;
+                }
+                buffer[received] = '\0';
+                std::string reply(buffer);
+                // Scarto risposte tardive a richieste precedenti
+                if (reply.rfind(seq + " ", 0) == 0) {
+                    response = reply.substr(seq.size() + 1);
+                    return true;
+                }
+            }
+        }
+        return false;
+    }
+
+    std::vector<std::string> peers;
+    intptr_t sock;
+    uint64_t sequence;
+    std::vector<StepResult> results;
+};
+
+int runOrchestrator(const std::vector<std::string>& args) {
+    auto peers = optionValues(args, "--peer");
+    int streamSeconds = std::stoi(optionValue(args, "--stream-seconds", "60"));
+    std::string reportPath = optionValue(args, "--report");
+    if (peers.empty()) {
+        std::cerr << "Uso: saber-test orchestrator --peer host:porta [--peer ...] "
+                  << "[--stream-seconds N] [--report file.json]" << std::endl;
+        return 2;
+    }
+
+    Orchestrator orchestrator(peers);
+    if (!orchestrator.isOpen()) {
+        std::cerr << "Impossibile aprire il socket UDP dell'orchestratore" << std::endl;
+        return 1;
+    }
+
+    bool passed = orchestrator.step("join", "join")
+               && orchestrator.step("sync", "sync " + std::to_string(wallClockMillis()))
+               && orchestrator.step("stream", "stream");
+
+    // Durante lo streaming tutti i nodi devono restare vivi e sincronizzati
+    for (int elapsed = 0; passed && elapsed < streamSeconds; elapsed += STATUS_POLL_SECONDS) {
+        std::this_thread::sleep_for(std::chrono::seconds(std::min(STATUS_POLL_SECONDS, streamSeconds - elapsed)));
+        passed = orchestrator.step("stream-status", "status");
+    }
+
+    // Dopo la perdita di un repeater i nodi restanti devono continuare
+    passed = passed
+          && orchestrator.step("kill-repeater", "kill repeater")
+          && orchestrator.step("post-kill-status", "status");
+
+    std::string report = orchestrator.report("lan-join-sync-stream-kill", passed);
+    std::cout << report << std::endl;
+    if (!reportPath.empty()) {
+        std::ofstream(reportPath) << report << std::endl;
+    }
+    return passed ? 0 : 1;
+}
+
+} // namespace
+
+int main(int argc, char** argv) {
+    std::vector<std::string> args(argv + 1, argv + argc);
+    if (args.empty()) {
+        std::cerr << "Uso: saber-test peer|orchestrator [opzioni]" << std::endl;
+        return 2;
+    }
+
+    std::string mode = args[0];
+    args.erase(args.begin());
+    if (mode == "peer") {
+        return runPeer(args);
+    }
+    if (mode == "orchestrator") {
+        return runOrchestrator(args);
+    }
+
+    std::cerr << "Modalità sconosciuta: " << mode << std::endl;
+    return 2;
+}
@@ -0,0 +1,45 @@
+# Supporto comune ai test unitari del protocollo SABER
+# Raccoglie le basi condivise dai moduli di test, come il file di configurazione temporaneo
+
+import os
+import sys
+import tempfile
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+from saber_protocol import SaberConfig
+
+class ConfigFileTestCase(unittest.TestCase):
+    """Base comune: un file di configurazione temporaneo per ogni test
+
+    HEADER viene anteposto al testo passato a load(): ogni modulo vi mette
+    la sezione [node] e, se serve, l'intestazione della sezione verificata.
+    """
+
+    HEADER = ""
+
+    def setUp(self):
+        handle, self.path = tempfile.mkstemp(suffix=".toml")
+        os.close(handle)
+        self.addCleanup(os.remove, self.path)
+
+    def write(self, text):
+        with open(self.path, "w") as file:
+            file.write(text)
+
+    def load(self, text):
+        self.write(self.HEADER + text)
+        return SaberConfig.from_file(self.path)
+
+def load_config(text):
+    """Legge una configurazione dal testo passando per un file temporaneo"""
+    handle, path = tempfile.mkstemp(suffix=".toml")
+    os.close(handle)
+    try:
+        with open(path, "w") as file:
+            file.write(text)
+        return SaberConfig.from_file(path)
+    finally:
+        os.remove(path)
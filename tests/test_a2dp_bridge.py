@@ -0,0 +1,50 @@
+# Test unitari per il ponte verso cuffie A2DP del protocollo SABER
+# Verifica l'anticipo dell'emissione e la latenza riportata al Master
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import A2dpBridge
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+NOW_US = 1700000000 * 1000 * 1000
+DEVICE = "00:1A:7D:DA:71:13"
+
+class TestA2dpBridge(unittest.TestCase):
+    """Test per la ritrasmissione dei flussi verso le cuffie A2DP"""
+
+    def setUp(self):
+        self.bridge = A2dpBridge(DEVICE, 150, 60)
+
+    def test_emit_time(self):
+        """Ogni frame viene emesso in anticipo della latenza del collegamento"""
+        self.assertEqual(self.bridge.emit_time_us(NOW_US + 200000), NOW_US + 50000)
+        self.assertEqual(self.bridge.emit_time_us(100000), 0)
+
+    def test_reported_latency(self):
+        """La latenza riportata al Master include quella delle cuffie"""
+        self.assertEqual(self.bridge.reported_latency_ms(12), 162)
+
+    def test_forward_counts_late_frames(self):
+        """Un frame il cui istante di emissione è già passato è in ritardo"""
+        self.assertEqual(self.bridge.forward(NOW_US + 180000, NOW_US), NOW_US + 30000)
+        self.assertEqual(self.bridge.buffer_level(), 50)
+        self.bridge.forward(NOW_US + 100000, NOW_US)
+        stats = self.bridge.get_stats()
+        self.assertEqual(stats.device, DEVICE)
+        self.assertEqual(stats.latency_ms, 150)
+        self.assertEqual(stats.frames_forwarded, 2)
+        self.assertEqual(stats.late_frames, 1)
+        self.assertEqual(stats.last_lead_ms, -50)
+        self.assertEqual(self.bridge.buffer_level(), 0)
+
+if __name__ == "__main__":
+    unittest.main()
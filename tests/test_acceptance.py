@@ -0,0 +1,163 @@
+# Test di accettazione end-to-end della riproduzione sincronizzata
+# Avvia Master, Repeater e Sink nello stesso processo sul trasporto UDP multicast in loopback,
+# trasmette 10 secondi di audio generato e verifica lo scarto tra i nodi e la continuità della riproduzione
+
+import math
+import os
+import random
+import sys
+import threading
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (AudioFrame, NodeRole, SaberConfig, SaberProtocol, TransportKind,
+                                UdpTransportConfig, lc3_available)
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+# Parametri dello scenario
+STREAM_ID = 1
+STREAM_SECONDS = 10
+FRAME_US = 10000
+SAMPLE_RATE = 48000
+CHANNELS = 2
+# Anticipo dell'istante di riproduzione rispetto all'invio, come il buffer di un sink
+PLAYOUT_LEAD_US = 150000
+# Promessa del protocollo: i nodi riproducono lo stesso frame entro 5 ms
+MAX_SKEW_US = 5000
+JOIN_TIMEOUT_S = 10.0
+SYNC_TIMEOUT_S = 10.0
+
+def tone_samples(frequency=1000.0):
+    """Campioni di 10ms di una sinusoide a 1 kHz, che in 10ms compie un numero intero di periodi"""
+    samples = []
+    for i in range(SAMPLE_RATE // 100):
+        value = int(8000 * math.sin(2 * math.pi * frequency * i / SAMPLE_RATE))
+        samples.extend([value] * CHANNELS)
+    return samples
+
+def wait_until(condition, timeout_s, action=None, interval_s=0.1):
+    """Attende una condizione, ripetendo un'azione ad ogni secondo"""
+    deadline = time.monotonic() + timeout_s
+    last_action = 0.0
+    while time.monotonic() < deadline:
+        if condition():
+            return True
+        if action and time.monotonic() - last_action >= 1.0:
+            action()
+            last_action = time.monotonic()
+        time.sleep(interval_s)
+    return condition()
+
+class PlayoutRecorder:
+    """Uscita audio nulla: registra gli istanti di riproduzione dei frame consegnati dal sink"""
+
+    def __init__(self):
+        self.lock = threading.Lock()
+        self.playouts = []
+
+    def __call__(self, stream_id, frame):
+        if stream_id == STREAM_ID:
+            with self.lock:
+                self.playouts.append(frame.playout_time_us)
+
+    def unconcealed_gaps(self):
+        """Buchi tra due frame consegnati che la ricostruzione non ha colmato"""
+        with self.lock:
+            playouts = sorted(set(self.playouts))
+        return [(a, b) for a, b in zip(playouts, playouts[1:]) if b - a > FRAME_US]
+
+class TestSynchronizedPlayback(unittest.TestCase):
+    """Test della riproduzione sincronizzata di Master, Repeater e Sink"""
+
+    def setUp(self):
+        if not lc3_available():
+            self.skipTest("libreria LC3 non compilata")
+        udp = UdpTransportConfig()
+        udp.port = random.randint(20000, 40000)
+        self.nodes = {}
+        for node_id, role in (("master-1", NodeRole.Master), ("repeater-1", NodeRole.Repeater),
+                              ("sink-1", NodeRole.Sink)):
+            config = SaberConfig.default_config()
+            config.node_id = node_id
+            config.role = role
+            config.transport = TransportKind.Udp
+            config.udp = udp
+            protocol = SaberProtocol(config)
+            if not protocol.initialize():
+                self.skipTest("multicast non disponibile")
+            self.nodes[role] = protocol
+            self.addCleanup(protocol.shutdown)
+
+    def test_three_node_playback(self):
+        """Dieci secondi di audio: scarto tra i nodi sotto 5 ms e nessun buco non ricostruito"""
+        master = self.nodes[NodeRole.Master]
+        repeater = self.nodes[NodeRole.Repeater]
+        sink = self.nodes[NodeRole.Sink]
+        followers = (repeater, sink)
+
+        # Ingresso nella rete con la finestra di provisioning aperta
+        self.assertTrue(master.open_provisioning(60))
+        def request_joins():
+            for node in followers:
+                node.request_join()
+        joined = lambda: {"repeater-1", "sink-1"} <= set(master.get_active_nodes())
+        self.assertTrue(wait_until(joined, JOIN_TIMEOUT_S, request_joins), "ingresso nella rete non completato")
+
+        # Sincronizzazione degli orologi tramite lo scambio temporale con il Master
+        self.assertTrue(wait_until(lambda: all(node.is_synchronized() for node in followers), SYNC_TIMEOUT_S),
+                        "sincronizzazione non raggiunta")
+
+        recorder = PlayoutRecorder()
+        sink.set_audio_frame_handler(recorder)
+        self.assertTrue(master.publish_stream(STREAM_ID))
+        self.assertTrue(sink.subscribe_stream(STREAM_ID))
+
+        master_clock = master.get_sync_manager()
+        clocks = [node.get_sync_manager() for node in followers]
+        frame = AudioFrame()
+        frame.sample_rate = SAMPLE_RATE
+        frame.channels = CHANNELS
+        frame.samples = tone_samples()
+
+        # Lo scarto di un nodo è la differenza tra il suo orologio di rete e quello del Master,
+        # letto tra due letture del Master: ogni nodo riproduce un frame quando il proprio orologio
+        # raggiunge l'istante di riproduzione, quindi lo scarto tra gli orologi è quello dell'audio
+        skews = []
+        sent = 0
+        frames = STREAM_SECONDS * 1000000 // FRAME_US
+        base_us = master_clock.now_us() + PLAYOUT_LEAD_US
+        started = time.monotonic()
+        for index in range(frames):
+            delay = started + index * FRAME_US / 1e6 - time.monotonic()
+            if delay > 0:
+                time.sleep(delay)
+            frame.playout_time_us = base_us + index * FRAME_US
+            if master.send_pcm_frame(STREAM_ID, frame):
+                sent += 1
+            if index % 10 == 0:
+                for clock in clocks:
+                    before = master_clock.now_us()
+                    follower = clock.now_us()
+                    after = master_clock.now_us()
+                    skews.append(abs(follower - (before + after) // 2))
+
+        # Gli ultimi frame arrivano entro l'anticipo di riproduzione
+        time.sleep(PLAYOUT_LEAD_US / 1e6 + 0.2)
+
+        self.assertEqual(sent, frames)
+        self.assertLess(max(skews), MAX_SKEW_US, "scarto massimo tra i nodi: %d us" % max(skews))
+        self.assertGreater(len(recorder.playouts), frames * 9 // 10, "frame riprodotti dal sink")
+        self.assertEqual(recorder.unconcealed_gaps(), [])
+        # Il sink riproduce i frame all'istante deciso dal Master
+        first = min(recorder.playouts)
+        self.assertEqual((first - base_us) % FRAME_US, 0)
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,106 @@
+# Test unitari per le varianti asyncio dell'API Python del protocollo SABER
+# Verifica che i metodi bloccanti siano attendibili senza fermare il loop degli eventi
+
+import asyncio
+import os
+import sys
+import unittest
+import warnings
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (SaberConfig, SaberProtocol, NodeRole, LifecycleError,
+                                LifecycleErrorType, MeshPacket, SimNetwork)
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestAsyncApi(unittest.TestCase):
+    """Test per i metodi *_async di SaberProtocol senza avviare la rete"""
+
+    def setUp(self):
+        config = SaberConfig.default_config()
+        config.role = NodeRole.Sink
+        self.protocol = SaberProtocol(config)
+
+    def test_result_is_returned(self):
+        """Il valore del metodo bloccante diventa il risultato del future"""
+        async def run():
+            return await self.protocol.stop_audio_playback_async()
+        self.assertFalse(asyncio.run(run()))
+
+    def test_arguments_are_forwarded(self):
+        """Gli argomenti raggiungono il metodo bloccante"""
+        async def run():
+            await self.protocol.set_role_async(NodeRole.Repeater)
+            return self.protocol.get_role()
+        self.assertEqual(asyncio.run(run()), NodeRole.Repeater)
+
+    def test_errors_are_raised(self):
+        """Gli errori strutturati vengono sollevati dall'await"""
+        async def run():
+            await self.protocol.shutdown_async()
+        with self.assertRaises(LifecycleError) as context:
+            asyncio.run(run())
+        self.assertEqual(context.exception.type, LifecycleErrorType.NotRunning)
+
+    def test_loop_keeps_running(self):
+        """Il loop degli eventi continua a servire altri task durante l'attesa"""
+        async def run():
+            ticks = 0
+            async def tick():
+                nonlocal ticks
+                ticks += 1
+            pending = self.protocol.stop_audio_playback_async()
+            await asyncio.gather(pending, tick(), tick())
+            return ticks
+        self.assertEqual(asyncio.run(run()), 2)
+
+    def test_requires_running_loop(self):
+        """Fuori da un loop asyncio le varianti attendibili non sono utilizzabili"""
+        with self.assertRaises(RuntimeError):
+            self.protocol.stop_audio_playback_async()
+
+    def test_sync_call_in_loop_warns(self):
+        """Un metodo bloccante chiamato dal thread del loop viene segnalato"""
+        async def run():
+            return self.protocol.stop_audio_playback()
+        with self.assertWarns(RuntimeWarning) as context:
+            self.assertFalse(asyncio.run(run()))
+        self.assertIn("stop_audio_playback_async", str(context.warning))
+
+    def test_sync_call_outside_loop(self):
+        """Fuori da un loop asyncio i metodi bloccanti non segnalano nulla"""
+        with warnings.catch_warnings():
+            warnings.simplefilter("error")
+            self.assertFalse(self.protocol.stop_audio_playback())
+
+    def test_send_packet_without_network(self):
+        """Senza rete mesh l'invio asincrono si risolve subito con False"""
+        async def run():
+            return await self.protocol.send_packet_async(MeshPacket.create_command("volume.set", {"level": "3"}))
+        self.assertFalse(asyncio.run(run()))
+
+class TestSendPacketAsync(unittest.TestCase):
+    """Test per l'invio asincrono con la rete avviata"""
+
+    def test_delivered(self):
+        """Senza nodi attivi da cui attendere conferma il comando è consegnato appena trasmesso"""
+        config = SaberConfig.default_config()
+        config.node_id = "async-master"
+        config.role = NodeRole.Master
+        protocol = SaberProtocol(config)
+        protocol.set_sim_network(SimNetwork(1))
+        if not protocol.initialize():
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        self.addCleanup(protocol.shutdown)
+
+        async def run():
+            return await protocol.send_packet_async(MeshPacket.create_command("volume.set", {"level": "3"}))
+        self.assertTrue(asyncio.run(run()))
+
+if __name__ == '__main__':
+    unittest.main()
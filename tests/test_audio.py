@@ -155,5 +155,53 @@ class TestAudioSync(unittest.TestCase):
             self.assertGreaterEqual(latency, buffer_ms - 5, 
                                  f"La latenza ({latency}ms) dovrebbe essere almeno {buffer_ms}ms")
 
+class TestLateFramePolicy(unittest.TestCase):
+    """Test per la gestione dei frame arrivati in ritardo"""
+    
+    NOW = 10000  # ms sull'orologio sincronizzato
+    
+    def setUp(self):
+        """Inizializza un controller con l'orologio fermo"""
+        self.audio = AudioController()
+        self.audio.initialize(DEFAULT_SAMPLE_RATE_MUSIC, 2)
+        self.audio.set_time_provider(lambda: self.NOW)
+        self.frame = [0.0] * (480 * 2)  # 10ms stereo
+    
+    def test_on_time(self):
+        """Un frame in anticipo viene scritto e contato come puntuale"""
+        self.assertTrue(self.audio.play_audio_buffer(self.frame, self.NOW + 20))
+        self.assertEqual(self.audio.get_late_frame_stats()["on_time"], 1)
+    
+    def test_drop(self):
+        """Con la politica drop un frame in ritardo viene scartato"""
+        self.assertTrue(self.audio.set_late_frame_policy("drop"))
+        self.assertFalse(self.audio.play_audio_buffer(self.frame, self.NOW - 5))
+        self.assertEqual(self.audio.get_late_frame_stats()["dropped"], 1)
+    
+    def test_play_late(self):
+        """Con la politica late il frame viene riprodotto e la timeline scorre"""
+        self.assertTrue(self.audio.set_late_frame_policy("late"))
+        self.assertTrue(self.audio.play_audio_buffer(self.frame, self.NOW - 5))
+        stats = self.audio.get_late_frame_stats()
+        self.assertEqual(stats["played_late"], 1)
+        self.assertEqual(stats["slid_ms"], 5)
+    
+    def test_resync(self):
+        """Con la politica resync viene scritta solo la parte ancora utile"""
+        self.assertTrue(self.audio.set_late_frame_policy("resync"))
+        self.assertTrue(self.audio.play_audio_buffer(self.frame, self.NOW - 5))
+        self.assertFalse(self.audio.play_audio_buffer(self.frame, self.NOW - 50))
+        self.assertEqual(self.audio.get_late_frame_stats()["resyncs"], 2)
+    
+    def test_tolerance(self):
+        """Un ritardo entro la tolleranza non applica la politica"""
+        self.audio.set_late_frame_policy("drop", 10)
+        self.assertTrue(self.audio.play_audio_buffer(self.frame, self.NOW - 5))
+        self.assertEqual(self.audio.get_late_frame_stats()["dropped"], 0)
+    
+    def test_invalid_policy(self):
+        """Una politica sconosciuta viene rifiutata"""
+        self.assertFalse(self.audio.set_late_frame_policy("skip"))
+
 if __name__ == "__main__":
     unittest.main()
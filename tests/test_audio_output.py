@@ -0,0 +1,99 @@
+# Test unitari per le uscite audio dei sink
+# Verifica la registrazione su file WAV, l'uscita nulla e il collegamento agli stream riprodotti
+
+import os
+import shutil
+import sys
+import tempfile
+import unittest
+import wave
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import AudioFrame, NodeRole, NullAudioOutput, SaberConfig, SaberProtocol, WavFileOutput
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def frame(playout_time_us, value=1, sample_rate=16000, channels=1):
+    result = AudioFrame()
+    result.playout_time_us = playout_time_us
+    result.sample_rate = sample_rate
+    result.channels = channels
+    result.samples = [value] * (sample_rate // 100 * channels)
+    return result
+
+class TestWavFileOutput(unittest.TestCase):
+    """Test per la registrazione su file"""
+
+    def setUp(self):
+        self.directory = tempfile.mkdtemp()
+        self.path = os.path.join(self.directory, "uscita.wav")
+
+    def tearDown(self):
+        shutil.rmtree(self.directory)
+
+    def test_format(self):
+        """Il file prende il formato del primo frame e l'intestazione segue i campioni"""
+        output = WavFileOutput(self.path)
+        output.write(frame(0, 7))
+        output.write(frame(10000, 8))
+        output.flush()
+        with wave.open(self.path, "rb") as recorded:
+            self.assertEqual(recorded.getframerate(), 16000)
+            self.assertEqual(recorded.getnchannels(), 1)
+            self.assertEqual(recorded.getnframes(), 320)
+
+    def test_gap(self):
+        """Un vuoto nella riproduzione diventa silenzio"""
+        output = WavFileOutput(self.path)
+        output.write(frame(0))
+        output.write(frame(30000))
+        self.assertEqual(output.get_samples_written(), 640)
+
+    def test_rejected(self):
+        """I frame in un formato diverso dal primo vengono scartati"""
+        output = WavFileOutput(self.path)
+        output.write(frame(0))
+        output.write(frame(10000, sample_rate=48000, channels=2))
+        self.assertEqual(output.get_frames_rejected(), 1)
+        self.assertEqual(output.get_samples_written(), 160)
+
+    def test_invalid(self):
+        """Un percorso non scrivibile viene rifiutato"""
+        with self.assertRaises(RuntimeError):
+            WavFileOutput(os.path.join(self.directory, "mancante", "uscita.wav"))
+
+class TestNullAudioOutput(unittest.TestCase):
+    """Test per l'uscita nulla"""
+
+    def test_count(self):
+        """I frame vengono contati e l'ultimo istante ricordato"""
+        output = NullAudioOutput()
+        self.assertIsNone(output.get_last_playout_time_us())
+        output.write(frame(1000))
+        output.write(frame(11000))
+        self.assertEqual(output.get_frames_written(), 2)
+        self.assertEqual(output.get_last_playout_time_us(), 11000)
+        self.assertEqual(output.get_latency_us(), 0)
+
+class TestProtocolAudioOutput(unittest.TestCase):
+    """Test per il collegamento delle uscite senza avviare la rete"""
+
+    def test_attach(self):
+        """Un'uscita collegata compare nei contatori finché non viene scollegata"""
+        config = SaberConfig.default_config()
+        config.role = NodeRole.Sink
+        protocol = SaberProtocol(config)
+        protocol.set_audio_output(0, NullAudioOutput())
+        stats = protocol.get_audio_output_stats()
+        self.assertEqual(list(stats), [0])
+        self.assertEqual(stats[0].frames_played, 0)
+        protocol.set_audio_output(0, None)
+        self.assertEqual(protocol.get_audio_output_stats(), {})
+
+if __name__ == "__main__":
+    unittest.main()
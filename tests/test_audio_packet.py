@@ -0,0 +1,127 @@
+# Test unitari per il formato dei frame audio cifrati
+# Verifica la cifratura con intestazione autenticata, il riordino sul sink e la segnalazione dei frame persi
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import AUDIO_PACKET_HEADER_BYTES, AudioPacket, AudioPacketReorderer, MeshCrypto
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+# Chiave di rete condivisa dai nodi del test
+NETWORK_KEY = list(range(32))
+
+def make_crypto():
+    return MeshCrypto.with_network_key(NETWORK_KEY)
+
+def make_packet(sequence, stream_id=1):
+    return AudioPacket(stream_id, sequence, 1000000 + sequence * 10000, bytes([sequence % 256]) * 20)
+
+class TestAudioPacket(unittest.TestCase):
+    """Test per la serializzazione e la cifratura"""
+
+    def test_round_trip(self):
+        """Un pacchetto aperto con la stessa chiave restituisce il frame"""
+        crypto = make_crypto()
+        data = AudioPacket(3, 42, 123456, b"lc3").seal(crypto)
+        packet = AudioPacket.open(data, crypto)
+        self.assertEqual((packet.stream_id, packet.sequence, packet.presentation_time_us), (3, 42, 123456))
+        self.assertEqual(packet.payload, b"lc3")
+        self.assertNotIn(b"lc3", data[AUDIO_PACKET_HEADER_BYTES:])
+
+    def test_peek_header(self):
+        """L'intestazione si legge senza la chiave"""
+        header = AudioPacket.peek_header(AudioPacket(3, 42, 123456, b"lc3").seal(make_crypto()))
+        self.assertEqual((header.stream_id, header.sequence, header.presentation_time_us), (3, 42, 123456))
+        self.assertEqual(header.payload, b"")
+
+    def test_tampered_header(self):
+        """Una sequenza alterata fa fallire la decifratura"""
+        crypto = make_crypto()
+        data = bytearray(AudioPacket(3, 42, 123456, b"lc3").seal(crypto))
+        data[6] ^= 1
+        with self.assertRaises(Exception):
+            AudioPacket.open(bytes(data), crypto)
+
+    def test_replay(self):
+        """Con il mittente indicato un pacchetto ripetuto viene rifiutato"""
+        crypto = make_crypto()
+        data = make_packet(1).seal(crypto)
+        AudioPacket.open(data, crypto, "master")
+        with self.assertRaises(Exception):
+            AudioPacket.open(data, crypto, "master")
+
+    def test_invalid(self):
+        """Pacchetti troncati o di un'altra versione vengono rifiutati"""
+        data = make_packet(1).seal(make_crypto())
+        with self.assertRaises(IndexError):
+            AudioPacket.peek_header(data[:5])
+        with self.assertRaises(ValueError):
+            AudioPacket.peek_header(b"\x09" + data[1:])
+
+class TestAudioPacketReorderer(unittest.TestCase):
+    """Test per il riordino dei frame sul sink"""
+
+    def setUp(self):
+        self.events = []
+        self.reorderer = AudioPacketReorderer(
+            lambda packet: self.events.append(("frame", packet.sequence)),
+            lambda stream_id, sequence, time_us: self.events.append(("lost", sequence, time_us)),
+            2)
+
+    def test_reorder(self):
+        """I frame fuori ordine vengono consegnati in sequenza"""
+        for sequence in [0, 2, 1, 3]:
+            self.assertTrue(self.reorderer.push(make_packet(sequence)))
+        self.assertEqual(self.events, [("frame", 0), ("frame", 1), ("frame", 2), ("frame", 3)])
+        self.assertEqual(self.reorderer.get_stats().reordered, 1)
+
+    def test_loss(self):
+        """Un frame mancante oltre la profondità viene segnalato al suo posto"""
+        for sequence in [0, 2, 3, 4]:
+            self.reorderer.push(make_packet(sequence))
+        self.assertEqual(self.events, [("frame", 0), ("lost", 1, 1010000), ("frame", 2), ("frame", 3), ("frame", 4)])
+        stats = self.reorderer.get_stats()
+        self.assertEqual((stats.delivered, stats.lost, stats.concealed), (4, 1, 1))
+
+        # Il frame arrivato dopo la segnalazione non viene più consegnato
+        self.assertFalse(self.reorderer.push(make_packet(1)))
+        self.assertEqual(self.reorderer.get_stats().late, 1)
+
+    def test_duplicate(self):
+        """Un frame trattenuto ricevuto due volte viene scartato"""
+        self.reorderer.push(make_packet(0))
+        self.assertTrue(self.reorderer.push(make_packet(2)))
+        self.assertFalse(self.reorderer.push(make_packet(2)))
+        self.assertEqual(self.reorderer.get_stats().duplicates, 1)
+
+    def test_flush(self):
+        """Lo svuotamento consegna i frame trattenuti segnalando i vuoti"""
+        self.reorderer.push(make_packet(0))
+        self.reorderer.push(make_packet(2))
+        self.reorderer.flush()
+        self.assertEqual(self.events, [("frame", 0), ("lost", 1, 1010000), ("frame", 2)])
+
+    def test_restart(self):
+        """Un salto di sequenza molto ampio fa ripartire lo stream senza ricostruzione"""
+        self.reorderer.push(make_packet(5000))
+        self.assertTrue(self.reorderer.push(make_packet(0)))
+        self.assertEqual(self.events, [("frame", 5000), ("frame", 0)])
+        self.assertEqual(self.reorderer.get_stats().lost, 0)
+
+    def test_streams(self):
+        """Gli stream vengono riordinati separatamente"""
+        self.reorderer.push(make_packet(0, 1))
+        self.reorderer.push(make_packet(7, 2))
+        self.reorderer.push(make_packet(1, 1))
+        self.assertEqual(self.events, [("frame", 0), ("frame", 7), ("frame", 1)])
+
+if __name__ == "__main__":
+    unittest.main()
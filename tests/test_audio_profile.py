@@ -0,0 +1,68 @@
+# Test unitari per il cambio del profilo audio durante la trasmissione
+# Verifica il formato del nodo, il comando riservato al Master e l'applicazione agli stream pubblicati
+
+import os
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (AudioProfile, AudioSync, NodeRole, SaberConfig, SaberProtocol, SimNetwork,
+                                SyncManager, decode_node_state)
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestMusicMode(unittest.TestCase):
+    """Test per il formato del nodo in AudioSync"""
+
+    def test_default_format(self):
+        """Il cambio vale per gli stream senza formato dedicato, che mantengono i canali"""
+        sync = AudioSync(SyncManager(), True, 2)
+        sync.set_stream_format(2, True, 2)
+        sync.set_music_mode(False)
+        self.assertEqual(sync.get_stream_format(1).sample_rate_hz, 16000)
+        self.assertEqual(sync.get_stream_format(1).channels, 2)
+        self.assertEqual(sync.get_stream_format(2).sample_rate_hz, 48000)
+        sync.set_music_mode(True)
+        self.assertEqual(sync.get_stream_format(1).sample_rate_hz, 48000)
+
+class TestSetAudioProfile(unittest.TestCase):
+    """Test per il cambio di profilo del protocollo"""
+
+    def start(self, node_id, role):
+        config = SaberConfig.default_config()
+        config.node_id = node_id
+        config.role = role
+        protocol = SaberProtocol(config)
+        protocol.set_sim_network(SimNetwork(1))
+        if not protocol.initialize():
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        self.addCleanup(protocol.shutdown)
+        return protocol
+
+    def test_master_only(self):
+        """Un sink non può cambiare il profilo della rete"""
+        sink = self.start("profile-sink", NodeRole.Sink)
+        self.assertFalse(sink.set_audio_profile(AudioProfile.Voice))
+        self.assertEqual(sink.get_audio_profile(), AudioProfile.Music)
+
+    def test_switch(self):
+        """Il profilo vocale passa gli stream pubblicati a 16kHz mono e il ritorno li riporta a 48kHz"""
+        master = self.start("profile-master", NodeRole.Master)
+        self.assertTrue(master.publish_stream(1))
+        self.assertTrue(master.set_audio_profile(AudioProfile.Voice))
+        self.assertEqual(master.get_audio_profile(), AudioProfile.Voice)
+        time.sleep(0.3)
+        self.assertIn(1, decode_node_state(master.export_state()).voice_streams)
+        self.assertTrue(master.set_audio_profile(AudioProfile.Music))
+        self.assertEqual(master.get_audio_profile(), AudioProfile.Music)
+        time.sleep(0.3)
+        self.assertNotIn(1, decode_node_state(master.export_state()).voice_streams)
+
+if __name__ == "__main__":
+    unittest.main()
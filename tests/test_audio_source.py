@@ -0,0 +1,142 @@
+# Test unitari per le sorgenti audio del Master
+# Verifica la lettura dei file WAV e PCM, il buffer alimentato dall'applicazione e il collegamento agli stream
+
+import os
+import shutil
+import struct
+import sys
+import tempfile
+import unittest
+import wave
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import NodeRole, PcmFileSource, RingBufferSource, SaberConfig, SaberProtocol
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestPcmFileSource(unittest.TestCase):
+    """Test per la lettura dei file"""
+
+    def setUp(self):
+        self.directory = tempfile.mkdtemp()
+
+    def tearDown(self):
+        shutil.rmtree(self.directory)
+
+    def write_wav(self, samples, sample_rate=48000, channels=2):
+        path = os.path.join(self.directory, "brano.wav")
+        with wave.open(path, "wb") as output:
+            output.setnchannels(channels)
+            output.setsampwidth(2)
+            output.setframerate(sample_rate)
+            output.writeframes(struct.pack("<%dh" % len(samples), *samples))
+        return path
+
+    def test_wav(self):
+        """Il formato viene dal file e l'ultimo frame è completato con il silenzio"""
+        source = PcmFileSource.from_wav(self.write_wav(list(range(-500, 1000)), 16000, 1))
+        self.assertEqual(source.get_sample_rate(), 16000)
+        self.assertEqual(source.get_channels(), 1)
+
+        frames = []
+        while not source.is_finished():
+            frames.append(source.next_frame())
+        self.assertEqual(len(frames), 10)
+        self.assertEqual(frames[0].samples[:3], [-500, -499, -498])
+        self.assertEqual(frames[-1].samples[59], 999)
+        self.assertEqual(frames[-1].samples[60:], [0] * 100)
+        self.assertIsNone(source.next_frame())
+
+    def test_loop(self):
+        """In ripetizione la lettura riparte dall'inizio"""
+        source = PcmFileSource.from_wav(self.write_wav([1] * 960 + [2] * 960), loop=True)
+        values = [source.next_frame().samples[0] for _ in range(3)]
+        self.assertEqual(values, [1, 2, 1])
+        self.assertFalse(source.is_finished())
+
+    def test_raw(self):
+        """Un file grezzo si legge con il formato indicato"""
+        path = os.path.join(self.directory, "brano.pcm")
+        with open(path, "wb") as output:
+            output.write(struct.pack("<4h", 1, -1, 2, -2))
+        source = PcmFileSource(path, 48000, 2)
+        self.assertEqual(source.next_frame().samples[:4], [1, -1, 2, -2])
+        self.assertTrue(source.is_finished())
+
+    def test_invalid(self):
+        """File mancanti, non WAV o in formati non supportati vengono rifiutati"""
+        with self.assertRaises(RuntimeError):
+            PcmFileSource.from_wav(os.path.join(self.directory, "mancante.wav"))
+        path = os.path.join(self.directory, "testo.wav")
+        with open(path, "w") as output:
+            output.write("non audio")
+        with self.assertRaises(RuntimeError):
+            PcmFileSource.from_wav(path)
+        with self.assertRaises(ValueError):
+            PcmFileSource(path, 44123, 2)
+
+class TestRingBufferSource(unittest.TestCase):
+    """Test per la sorgente alimentata dall'applicazione"""
+
+    def test_frames(self):
+        """I campioni diventano frame solo quando ne formano uno intero"""
+        source = RingBufferSource(16000, 1)
+        self.assertEqual(source.push(list(range(100))), 100)
+        self.assertIsNone(source.next_frame())
+        source.push(struct.pack("<60h", *range(100, 160)))
+        self.assertEqual(source.next_frame().samples, list(range(160)))
+        self.assertEqual(source.available(), 0)
+
+    def test_overflow(self):
+        """I campioni oltre la capacità vengono scartati"""
+        source = RingBufferSource(16000, 1, 10)
+        self.assertEqual(source.push([7] * 200), 160)
+        self.assertEqual(source.get_dropped(), 40)
+        source.clear()
+        self.assertEqual(source.available(), 0)
+
+    def test_close(self):
+        """Dopo la chiusura il resto esce completato con il silenzio e la sorgente finisce"""
+        source = RingBufferSource(16000, 1)
+        source.push([5] * 10)
+        source.close()
+        self.assertFalse(source.is_finished())
+        frame = source.next_frame()
+        self.assertEqual(frame.samples, [5] * 10 + [0] * 150)
+        self.assertTrue(source.is_finished())
+        with self.assertRaises(RuntimeError):
+            source.push([1])
+
+    def test_invalid(self):
+        """Campioni che non coprono tutti i canali vengono rifiutati"""
+        source = RingBufferSource(48000, 2)
+        with self.assertRaises(ValueError):
+            source.push([1, 2, 3])
+        with self.assertRaises(ValueError):
+            RingBufferSource(48000, 0)
+
+class TestProtocolAudioSource(unittest.TestCase):
+    """Test per il collegamento delle sorgenti senza avviare la rete"""
+
+    def protocol(self, role):
+        config = SaberConfig.default_config()
+        config.role = role
+        return SaberProtocol(config)
+
+    def test_without_network(self):
+        """Senza rete nessuna sorgente viene collegata"""
+        protocol = self.protocol(NodeRole.Master)
+        self.assertFalse(protocol.set_audio_source(0, RingBufferSource(48000, 2)))
+        self.assertEqual(protocol.get_audio_source_stats(), {})
+
+    def test_not_master(self):
+        """Solo il Master trasmette una sorgente"""
+        self.assertFalse(self.protocol(NodeRole.Sink).set_audio_source(0, RingBufferSource(48000, 2)))
+
+if __name__ == "__main__":
+    unittest.main()
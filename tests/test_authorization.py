@@ -0,0 +1,135 @@
+# Test unitari per la politica di autorizzazione esterna
+# Verifica decisioni immediate e differite, scadenza dell'attesa e configurazione
+
+import os
+import sys
+import threading
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (AuthorizationAction, AuthorizationGate, AuthorizationRequest, NodeRole,
+                                SaberConfig, SaberProtocol, authorization_action_from_string,
+                                authorization_action_to_string)
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def join_request(node_id, role=NodeRole.Sink):
+    request = AuthorizationRequest()
+    request.action = AuthorizationAction.Join
+    request.node_id = node_id
+    request.role = role
+    return request
+
+class TestAuthorizationGate(unittest.TestCase):
+    """Test per la consultazione della politica"""
+
+    def test_without_handler(self):
+        """Senza politica non c'è nulla da decidere"""
+        gate = AuthorizationGate()
+        self.assertFalse(gate.has_hook())
+        self.assertIsNone(gate.decide(join_request("sink-1")))
+        self.assertEqual(gate.get_stats().requests, 0)
+
+    def test_immediate_decision(self):
+        """La politica risponde subito, consentendo o negando con un motivo"""
+        gate = AuthorizationGate()
+        seen = []
+        def handler(request, respond):
+            seen.append((request.action, request.node_id, request.role))
+            if request.node_id == "sink-1":
+                respond(True)
+            else:
+                respond(False, "non in elenco")
+        gate.set_handler(handler)
+        self.assertTrue(gate.decide(join_request("sink-1")).allowed)
+        decision = gate.decide(join_request("sink-9", NodeRole.Repeater))
+        self.assertFalse(decision.allowed)
+        self.assertEqual(decision.reason, "non in elenco")
+        self.assertEqual(seen, [(AuthorizationAction.Join, "sink-1", NodeRole.Sink),
+                                (AuthorizationAction.Join, "sink-9", NodeRole.Repeater)])
+        stats = gate.get_stats()
+        self.assertEqual((stats.requests, stats.allowed, stats.denied, stats.pending), (2, 1, 1, 0))
+
+    def test_deferred_decision(self):
+        """La decisione può arrivare più tardi da un altro thread; conta solo la prima"""
+        gate = AuthorizationGate()
+        def handler(request, respond):
+            def answer():
+                respond(True, "approvato dall'operatore")
+                respond(False, "ignorata")
+            threading.Timer(0.05, answer).start()
+        gate.set_handler(handler)
+        decision = gate.decide(join_request("sink-2"))
+        self.assertTrue(decision.allowed)
+        self.assertEqual(decision.reason, "approvato dall'operatore")
+        self.assertEqual(gate.get_stats().denied, 0)
+
+    def test_timeout(self):
+        """Senza risposta vale la decisione di default"""
+        gate = AuthorizationGate(50)
+        gate.set_handler(lambda request, respond: None)
+        self.assertFalse(gate.decide(join_request("sink-3")).allowed)
+        gate.set_timeout(50, True)
+        self.assertTrue(gate.decide(join_request("sink-3")).allowed)
+        self.assertEqual(gate.get_stats().timed_out, 2)
+
+    def test_handler_error(self):
+        """Un errore della politica nega la richiesta"""
+        gate = AuthorizationGate()
+        def handler(request, respond):
+            raise RuntimeError("directory non raggiungibile")
+        gate.set_handler(handler)
+        decision = gate.decide(join_request("sink-4"))
+        self.assertFalse(decision.allowed)
+        self.assertIn("directory non raggiungibile", decision.reason)
+
+    def test_cancel(self):
+        """Le richieste in attesa vengono negate all'annullamento"""
+        gate = AuthorizationGate(60000)
+        submitted = threading.Event()
+        gate.set_handler(lambda request, respond: submitted.set())
+        result = []
+        waiter = threading.Thread(target=lambda: result.append(gate.decide(join_request("sink-5"))))
+        waiter.start()
+        self.assertTrue(submitted.wait(2.0))
+        gate.cancel_pending("arresto")
+        waiter.join(2.0)
+        self.assertFalse(result[0].allowed)
+        self.assertEqual(result[0].reason, "arresto")
+
+    def test_names(self):
+        """I nomi delle operazioni sono reversibili"""
+        for action in (AuthorizationAction.Join, AuthorizationAction.Command):
+            self.assertEqual(authorization_action_from_string(authorization_action_to_string(action)), action)
+        self.assertIsNone(authorization_action_from_string("reboot"))
+
+class TestConfigFile(ConfigFileTestCase):
+    """Test per l'attesa della politica letta dal file di configurazione"""
+
+    HEADER = '[node]\nid = "master-1"\nrole = "master"\n\n[authorization]\n'
+
+    def test_authorization(self):
+        """La sezione [authorization] imposta attesa e decisione allo scadere"""
+        config = self.load('timeout_ms = 2000\non_timeout = "allow"\n')
+        self.assertEqual(config.authorization_timeout_ms, 2000)
+        self.assertTrue(config.authorization_allow_on_timeout)
+        with self.assertRaises(RuntimeError):
+            self.load('on_timeout = "ask"\n')
+        with self.assertRaises(RuntimeError):
+            self.load('timeout_ms = 0\n')
+
+    def test_protocol(self):
+        """La politica si imposta anche prima di initialize()"""
+        protocol = SaberProtocol(SaberConfig.default_config())
+        protocol.set_authorization_handler(lambda request, respond: respond(True))
+        self.assertEqual(protocol.get_authorization_stats().requests, 0)
+        protocol.set_authorization_handler(None)
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,128 @@
+# Test unitari per la contabilità della banda e del tempo d'aria
+# Verifica i conteggi per stream, nodo vicino e zona, la finestra mobile e l'isteresi della congestione
+
+import os
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (SPEC_BITRATE_MUSIC_KBPS, SPEC_BITRATE_VOICE_KBPS, CongestionController,
+                                CongestionState, MeshStats, NodeRole, SaberConfig, SaberProtocol, SimNetwork)
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+ZONES = {"sink-1": "sala", "sink-2": "sala", "sink-3": "cucina"}
+
+class TestMeshStats(unittest.TestCase):
+    """Test per i contatori di banda"""
+
+    def setUp(self):
+        # Finestra di 1 s su un collegamento a 1000 kbps: 125 byte occupano 1 ms di canale
+        self.stats = MeshStats(1000, 1000, 0)
+        self.stats.record_received("sink-1", 125, 0)
+        self.stats.record_received("sink-1", 125, 100)
+        self.stats.record_received("sink-2", 250, 200)
+        self.stats.record_sent(125, 300)
+        self.stats.record_stream(1, 100, 0)
+
+    def test_report(self):
+        """Il traffico viene ripartito per stream, vicino e zona"""
+        report = self.stats.report(ZONES, 500)
+        self.assertEqual(report.window_ms, 1000)
+        self.assertEqual(report.streams[1].bytes, 100)
+
+        sink = report.neighbors["sink-1"]
+        self.assertEqual((sink.bytes, sink.packets, sink.airtime_us), (250, 2, 2000))
+        self.assertAlmostEqual(sink.bytes_per_second, 250.0)
+        self.assertAlmostEqual(sink.airtime_fraction, 0.002)
+
+        self.assertEqual(list(report.zones), ["sala"])
+        self.assertEqual((report.zones["sala"].bytes, report.zones["sala"].packets), (500, 3))
+
+        # Il totale comprende anche il traffico trasmesso
+        self.assertEqual((report.total.bytes, report.total.packets, report.total.airtime_us), (625, 4, 5000))
+        self.assertAlmostEqual(self.stats.airtime_utilization(500), 0.005)
+
+    def test_window(self):
+        """I campioni più vecchi della finestra non vengono più contati"""
+        report = self.stats.report(ZONES, 1100)
+        self.assertNotIn("sink-1", report.neighbors)
+        self.assertEqual(report.streams, {})
+        self.assertEqual(report.total.bytes, 375)
+        self.assertEqual(self.stats.report(ZONES, 5000).total.packets, 0)
+
+    def test_packet_overhead(self):
+        """L'overhead di trasporto pesa sul tempo d'aria ma non sui byte"""
+        stats = MeshStats(1000, 1000, 16)
+        stats.record_sent(125, 0)
+        total = stats.report({}, 0).total
+        self.assertEqual((total.bytes, total.airtime_us), (125, 1128))
+
+    def test_prometheus(self):
+        """Il resoconto viene esportato con le etichette di nodo, vicino e zona"""
+        text = MeshStats.to_prometheus(self.stats.report(ZONES, 500), "master-1")
+        self.assertIn('saber_neighbor_bytes{node="master-1",neighbor="sink-1"} 250', text)
+        self.assertIn('saber_zone_packets{node="master-1",zone="sala"} 3', text)
+        self.assertIn('saber_stream_bytes{node="master-1",stream="1"} 100', text)
+        self.assertIn("# TYPE saber_channel_airtime_ratio gauge", text)
+
+class TestCongestionController(unittest.TestCase):
+    """Test per la riduzione del bitrate sul canale saturo"""
+
+    def test_hysteresis(self):
+        """Si entra in congestione sopra la soglia alta e se ne esce solo sotto quella bassa"""
+        controller = CongestionController(0.8, 0.6)
+        self.assertFalse(controller.update(0.7))
+        self.assertEqual(controller.recommended_bitrate_kbps(True), SPEC_BITRATE_MUSIC_KBPS)
+
+        self.assertTrue(controller.update(0.85))
+        self.assertEqual(controller.get_state(), CongestionState.Congested)
+        self.assertEqual(controller.recommended_bitrate_kbps(True), SPEC_BITRATE_VOICE_KBPS)
+
+        self.assertFalse(controller.update(0.7))
+        self.assertEqual(controller.get_state(), CongestionState.Congested)
+        self.assertTrue(controller.update(0.5))
+        self.assertEqual(controller.get_state(), CongestionState.Clear)
+        self.assertAlmostEqual(controller.get_utilization(), 0.5)
+
+class TestProtocolBandwidth(unittest.TestCase):
+    """Test per il resoconto della banda di un nodo"""
+
+    def start(self, node_id, role, network):
+        config = SaberConfig.default_config()
+        config.node_id = node_id
+        config.role = role
+        protocol = SaberProtocol(config)
+        protocol.set_sim_network(network)
+        if not protocol.initialize():
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        self.addCleanup(protocol.shutdown)
+        return protocol
+
+    def test_without_network(self):
+        """Senza rete mesh il resoconto è vuoto e il canale è libero"""
+        protocol = SaberProtocol(SaberConfig.default_config())
+        self.assertEqual(protocol.get_bandwidth_report().total.packets, 0)
+        self.assertEqual(protocol.get_congestion_state(), CongestionState.Clear)
+
+    def test_neighbor_traffic(self):
+        """Il traffico del sink compare tra i vicini del Master"""
+        network = SimNetwork(1)
+        master = self.start("bw-master", NodeRole.Master, network)
+        self.start("bw-sink", NodeRole.Sink, network)
+        deadline = time.monotonic() + 5.0
+        while time.monotonic() < deadline and "bw-sink" not in master.get_bandwidth_report().neighbors:
+            network.advance(50)
+            time.sleep(0.05)
+        report = master.get_bandwidth_report()
+        self.assertGreater(report.neighbors["bw-sink"].packets, 0)
+        self.assertGreaterEqual(report.total.bytes, report.neighbors["bw-sink"].bytes)
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,141 @@
+# Test unitari per la gestione dei bassi per zona
+# Verifica i filtri complementari di subwoofer e satelliti e la distribuzione del crossover ai sink
+
+import math
+import os
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src', 'control'))
+
+try:
+    # Importo i moduli da testare
+    from libpy_audio import BassManager, BassRole
+    from saber_protocol import NodeRole, SaberConfig, SaberProtocol, SimNetwork
+except ImportError:
+    print("Errore: impossibile importare i moduli audio. Assicurati di averli compilati.")
+    sys.exit(1)
+
+SAMPLE_RATE = 48000
+CROSSOVER_HZ = 80.0
+
+def stereo_sine(frequency, seconds=1.0):
+    samples = []
+    for n in range(int(SAMPLE_RATE * seconds)):
+        value = 0.5 * math.sin(2.0 * math.pi * frequency * n / SAMPLE_RATE)
+        samples.extend((value, value))
+    return samples
+
+def rms(samples):
+    # Si scarta il primo mezzo secondo, in cui i filtri sono ancora a regime transitorio
+    tail = samples[len(samples) // 2:]
+    return math.sqrt(sum(sample * sample for sample in tail) / len(tail))
+
+def filtered(role, samples):
+    manager = BassManager(SAMPLE_RATE, 2)
+    manager.configure(role, CROSSOVER_HZ)
+    return manager.process(samples)
+
+class TestCrossover(unittest.TestCase):
+    """Test per i filtri Linkwitz-Riley applicati sui sink"""
+
+    def test_full_range(self):
+        """Senza gestione dei bassi il segnale resta invariato"""
+        samples = [0.5, -0.25, 0.125, -1.0] * 100
+        manager = BassManager(SAMPLE_RATE, 2)
+        self.assertEqual(manager.role(), BassRole.FullRange)
+        self.assertEqual(manager.process(samples), samples)
+
+    def test_band_split(self):
+        """Il subwoofer riceve i bassi e i satelliti le frequenze sopra il crossover"""
+        low = stereo_sine(20.0)
+        high = stereo_sine(2000.0)
+        self.assertGreater(rms(filtered(BassRole.Subwoofer, low)), 0.8 * rms(low))
+        self.assertLess(rms(filtered(BassRole.Satellite, low)), 0.1 * rms(low))
+        self.assertGreater(rms(filtered(BassRole.Satellite, high)), 0.95 * rms(high))
+        self.assertLess(rms(filtered(BassRole.Subwoofer, high)), 0.01 * rms(high))
+
+    def test_flat_sum(self):
+        """La somma di subwoofer e satellite ha la stessa ampiezza del segnale originale"""
+        for frequency in (40.0, CROSSOVER_HZ, 160.0):
+            samples = stereo_sine(frequency)
+            total = [a + b for a, b in zip(filtered(BassRole.Subwoofer, samples),
+                                           filtered(BassRole.Satellite, samples))]
+            self.assertAlmostEqual(rms(total), rms(samples), delta=0.02 * rms(samples), msg=frequency)
+
+    def test_subwoofer_mono(self):
+        """Il subwoofer riproduce il mix mono su tutti i canali"""
+        samples = []
+        for n in range(SAMPLE_RATE // 10):
+            value = math.sin(2.0 * math.pi * 30.0 * n / SAMPLE_RATE)
+            samples.extend((value, -0.5 * value))
+        output = filtered(BassRole.Subwoofer, samples)
+        self.assertEqual(output[0::2], output[1::2])
+
+    def test_invalid(self):
+        """Crossover fuori banda e campioni non allineati ai canali vengono rifiutati"""
+        manager = BassManager(SAMPLE_RATE, 2)
+        for crossover in (0.0, -80.0, SAMPLE_RATE / 2.0):
+            with self.assertRaises(ValueError, msg=crossover):
+                manager.configure(BassRole.Satellite, crossover)
+        with self.assertRaises(ValueError):
+            manager.process([0.0, 0.0, 0.0])
+        with self.assertRaises(ValueError):
+            BassManager(SAMPLE_RATE, 0)
+
+class TestBassDistribution(unittest.TestCase):
+    """Test per la configurazione dei bassi inviata dal Master"""
+
+    def start(self, node_id, role, network):
+        config = SaberConfig.default_config()
+        config.node_id = node_id
+        config.role = role
+        protocol = SaberProtocol(config)
+        protocol.set_sim_network(network)
+        if not protocol.initialize():
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        self.addCleanup(protocol.shutdown)
+        return protocol
+
+    def test_without_network(self):
+        """Senza rete mesh la configurazione non viene inviata"""
+        self.assertFalse(SaberProtocol(SaberConfig.default_config()).configure_bass_management("sala", "sub-1", 80.0))
+
+    def test_validation(self):
+        """Crossover non positivo o subwoofer fuori dalla zona vengono rifiutati"""
+        master = self.start("bass-master", NodeRole.Master, SimNetwork(1))
+        master.assign_zone("sub-1", "sala")
+        master.assign_zone("sat-1", "cucina")
+        self.assertFalse(master.configure_bass_management("sala", "sub-1", 0.0))
+        self.assertFalse(master.configure_bass_management("sala", "sat-1", 80.0))
+        self.assertFalse(master.configure_bass_management("sala", "sconosciuto", 80.0))
+        self.assertTrue(master.configure_bass_management("sala", "sub-1", 80.0))
+
+    def test_roles_reach_sinks(self):
+        """Il subwoofer e i satelliti della zona ricevono il proprio ruolo e lo stesso crossover"""
+        network = SimNetwork(1)
+        master = self.start("bass-master", NodeRole.Master, network)
+        sub = self.start("sub-1", NodeRole.Sink, network)
+        satellite = self.start("sat-1", NodeRole.Sink, network)
+        self.assertEqual(sub.get_bass_settings().role, "full_range")
+        master.assign_zone("sub-1", "sala")
+        master.assign_zone("sat-1", "sala")
+
+        # Il comando viene ripetuto finché i sink non conoscono la chiave del Master
+        deadline = time.monotonic() + 5.0
+        while time.monotonic() < deadline:
+            self.assertTrue(master.configure_bass_management("sala", "sub-1", 90.0))
+            network.advance(50)
+            if sub.get_bass_settings().role != "full_range" and satellite.get_bass_settings().role != "full_range":
+                break
+            time.sleep(0.05)
+        self.assertEqual(sub.get_bass_settings().role, "subwoofer")
+        self.assertEqual(satellite.get_bass_settings().role, "satellite")
+        self.assertAlmostEqual(sub.get_bass_settings().crossover_hz, 90.0)
+        self.assertAlmostEqual(satellite.get_bass_settings().crossover_hz, 90.0)
+
+if __name__ == "__main__":
+    unittest.main()
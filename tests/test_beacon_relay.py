@@ -0,0 +1,77 @@
+# Test unitari per l'inoltro dei TimeBeacon attraverso i Repeater
+# Verifica che un sink fuori portata dal Master si sincronizzi con i beacon ritrasmessi da un Repeater
+
+import os
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import NodeRole, SaberConfig, SaberProtocol, SimLinkConfig, SimNetwork, SyncManager
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def make_config(node_id, role):
+    config = SaberConfig.default_config()
+    config.node_id = node_id
+    config.role = role
+    return config
+
+class TestRelayDelay(unittest.TestCase):
+    """Test per la compensazione del ritardo degli inoltri"""
+
+    def test_compensation(self):
+        """Il ritardo dei Repeater sposta in avanti il tempo del Master"""
+        master_ms = int(time.time() * 1000)
+        direct = SyncManager()
+        relayed = SyncManager()
+        self.assertTrue(direct.handle_time_beacon(master_ms))
+        self.assertTrue(relayed.handle_time_beacon(master_ms, 50000))
+        self.assertAlmostEqual(relayed.now() - direct.now(), 50, delta=5)
+
+class TestBeaconRelay(unittest.TestCase):
+    """Test per l'inoltro tra nodi collegati alla stessa rete simulata"""
+
+    def start(self, config, network):
+        protocol = SaberProtocol(config)
+        protocol.set_sim_network(network)
+        if not protocol.initialize():
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        self.addCleanup(protocol.shutdown)
+        return protocol
+
+    def run_until(self, network, condition, timeout=5.0):
+        deadline = time.monotonic() + timeout
+        while time.monotonic() < deadline and not condition():
+            network.advance(50)
+            time.sleep(0.05)
+        return condition()
+
+    def test_two_hops(self):
+        """Il sink oltre il Repeater si sincronizza senza sentire il Master"""
+        network = SimNetwork(5)
+        unreachable = SimLinkConfig()
+        unreachable.loss_rate = 1.0
+        network.set_link("relay-master", "relay-sink", unreachable)
+        network.set_link("relay-sink", "relay-master", unreachable)
+
+        master_config = make_config("relay-master", NodeRole.Master)
+        master_config.mesh_beacon_interval_ms = 200
+        sink_config = make_config("relay-sink", NodeRole.Sink)
+        sink_config.time_exchange_interval_ms = 0
+        self.start(master_config, network)
+        repeater = self.start(make_config("relay-repeater", NodeRole.Repeater), network)
+        sink = self.start(sink_config, network)
+        # I nodi accettano i beacon solo da un Master conosciuto
+        repeater.register_node("relay-master", NodeRole.Master)
+        sink.register_node("relay-master", NodeRole.Master)
+
+        self.assertTrue(self.run_until(network, sink.is_synchronized))
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,104 @@
+# Test unitari per il ponte tra due reti SABER
+# Verifica la configurazione del ponte e il passaggio degli stream selezionati tra due reti simulate
+
+import os
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import BridgeConfig, NetworkBridge, NodeRole, SaberConfig, SaberProtocol, SimNetwork
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def make_protocol(node_id, role):
+    config = SaberConfig.default_config()
+    config.node_id = node_id
+    config.role = role
+    return SaberProtocol(config)
+
+def make_config(bridge_id="piano-1", forward=None, reverse=None):
+    config = BridgeConfig()
+    config.bridge_id = bridge_id
+    config.forward = forward or {}
+    config.reverse = reverse or {}
+    return config
+
+class TestBridgeConfig(unittest.TestCase):
+    """Test per la creazione del ponte senza avviare le reti"""
+
+    def test_invalid(self):
+        """Il ponte richiede due reti diverse e un ID"""
+        first = make_protocol("ponte-a", NodeRole.Repeater)
+        second = make_protocol("ponte-b", NodeRole.Repeater)
+        with self.assertRaises(ValueError):
+            NetworkBridge(first, first, make_config())
+        with self.assertRaises(ValueError):
+            NetworkBridge(first, second, make_config(""))
+
+    def test_start_stop(self):
+        """Il ponte si avvia e si ferma senza reti in esecuzione"""
+        bridge = NetworkBridge(make_protocol("ponte-a", NodeRole.Repeater),
+                               make_protocol("ponte-b", NodeRole.Repeater), make_config(forward={1: 5}))
+        bridge.start()
+        self.assertTrue(bridge.is_running())
+        self.assertEqual(bridge.get_config().forward, {1: 5})
+        bridge.stop()
+        self.assertFalse(bridge.is_running())
+        self.assertEqual(bridge.get_stats().frames_forwarded, 0)
+
+class TestBridgeNetworks(unittest.TestCase):
+    """Test per il passaggio dei frame tra due reti simulate"""
+
+    def start(self, node_id, role, network):
+        protocol = make_protocol(node_id, role)
+        protocol.set_sim_network(network)
+        if not protocol.initialize():
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        self.addCleanup(protocol.shutdown)
+        return protocol
+
+    def run_until(self, networks, condition, action=None, timeout=5.0):
+        deadline = time.monotonic() + timeout
+        while time.monotonic() < deadline and not condition():
+            if action:
+                action()
+            for network in networks:
+                network.advance(50)
+            time.sleep(0.05)
+        return condition()
+
+    def test_forward(self):
+        """Solo gli stream selezionati passano dalla prima alla seconda rete"""
+        first_floor = SimNetwork(1)
+        second_floor = SimNetwork(2)
+        master = self.start("piano1-master", NodeRole.Master, first_floor)
+        first = self.start("ponte-piano1", NodeRole.Repeater, first_floor)
+        second = self.start("ponte-piano2", NodeRole.Repeater, second_floor)
+        self.start("piano2-master", NodeRole.Master, second_floor)
+
+        bridge = NetworkBridge(first, second, make_config(forward={1: 5}))
+        bridge.start()
+        self.addCleanup(bridge.stop)
+
+        self.assertTrue(master.publish_stream(1))
+        self.assertTrue(master.publish_stream(2))
+
+        def send():
+            now = master.get_network_time_us()
+            master.send_audio_frame(1, now + 100000, [1, 2, 3])
+            master.send_audio_frame(2, now + 100000, [4, 5, 6])
+
+        self.assertTrue(self.run_until([first_floor, second_floor],
+                                       lambda: bridge.get_stats().frames_forwarded > 0, send))
+        stats = bridge.get_stats()
+        self.assertEqual(stats.frames_reversed, 0)
+        self.assertEqual(stats.dropped, 0)
+
+if __name__ == "__main__":
+    unittest.main()
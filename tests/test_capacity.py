@@ -0,0 +1,199 @@
+# Test unitari per la capienza della rete mesh del protocollo SABER
+# Verifica il rifiuto dei nodi oltre i limiti, il conteggio del nodo locale e le chiavi del file
+
+import os
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (MeshCrypto, MeshNetwork, MeshPacket, MeshPacketType, Node, NodeRole,
+                                RejectReason, SaberConfig, SaberProtocol, SimNetwork)
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestCapacityLimits(unittest.TestCase):
+    """Test per i limiti di ammissione di una rete senza protocollo"""
+
+    def setUp(self):
+        self.sink = MeshCrypto()
+        crypto = MeshCrypto()
+        crypto.register_node_key("sink-1", self.sink.get_public_key())
+        self.network = MeshNetwork(Node("master-1", NodeRole.Master))
+        self.network.set_crypto(crypto)
+        self.network.set_send_rejects(True)
+        self.sent = []
+        self.network.set_link_sender(self.sent.append)
+        self.network.start()
+        self.addCleanup(self.network.stop)
+
+    def rejects(self):
+        return [packet.get_reject_data() for packet in map(MeshPacket.decode, list(self.sent))
+                if packet.get_type() == MeshPacketType.Reject]
+
+    def wait_for(self, condition, what):
+        deadline = time.monotonic() + 2.0
+        while time.monotonic() < deadline:
+            if condition():
+                return
+            time.sleep(0.01)
+        self.fail(what)
+
+    def join(self, node_id, sequence, role=NodeRole.Sink):
+        joiner = MeshCrypto()
+        packet = MeshPacket.create_join(role, joiner.get_public_key())
+        packet.set_header(node_id, sequence, 8)
+        packet.sign(joiner)
+        self.assertTrue(self.network.receive_from_link(packet.encode()))
+
+    def test_local_node_counts(self):
+        """Il nodo locale occupa un posto: con un solo nodo ammesso nessun altro entra"""
+        self.network.set_capacity_limits(1, 0)
+        self.assertFalse(self.network.register_node("sink-1", NodeRole.Sink))
+        stats = self.network.get_admission_stats()
+        self.assertEqual((stats.nodes, stats.admitted, stats.rejected), (1, 0, 1))
+
+    def test_node_limit(self):
+        """Raggiunto max_nodes un nodo di qualsiasi ruolo viene respinto"""
+        self.network.set_capacity_limits(2, 0)
+        self.assertTrue(self.network.register_node("sink-1", NodeRole.Sink))
+        self.assertFalse(self.network.register_node("repeater-1", NodeRole.Repeater))
+        # Un nodo già membro non viene contato due volte
+        self.assertTrue(self.network.register_node("sink-1", NodeRole.Sink))
+        stats = self.network.get_admission_stats()
+        self.assertEqual((stats.max_nodes, stats.nodes, stats.admitted, stats.rejected), (2, 2, 1, 1))
+
+    def test_sink_limit_allows_repeater(self):
+        """Il limite sui sink non ferma gli altri ruoli"""
+        self.network.set_capacity_limits(0, 1)
+        self.assertTrue(self.network.register_node("sink-1", NodeRole.Sink))
+        self.assertFalse(self.network.register_node("sink-2", NodeRole.Sink))
+        self.assertTrue(self.network.register_node("repeater-1", NodeRole.Repeater))
+        stats = self.network.get_admission_stats()
+        self.assertEqual((stats.nodes, stats.sinks, stats.rejected), (3, 1, 1))
+
+    def test_raised_limit_admits(self):
+        """Dopo l'aumento del limite un nodo respinto entra al tentativo successivo"""
+        self.network.set_capacity_limits(2, 0)
+        self.assertTrue(self.network.register_node("sink-1", NodeRole.Sink))
+        self.assertFalse(self.network.register_node("sink-2", NodeRole.Sink))
+        self.network.set_capacity_limits(3, 0)
+        self.assertTrue(self.network.register_node("sink-2", NodeRole.Sink))
+        stats = self.network.get_admission_stats()
+        self.assertEqual((stats.nodes, stats.admitted, stats.rejected), (3, 2, 1))
+
+    def test_join_rejected(self):
+        """Un Join autentico oltre il limite riceve un Reject per capienza ed entra dopo l'aumento"""
+        self.network.open_provisioning(60000)
+        self.network.set_capacity_limits(1, 0)
+        self.join("sink-9", 1)
+        self.wait_for(lambda: self.network.get_drop_counters().get(RejectReason.CapacityExceeded, 0) > 0,
+                      "Join non respinto per capienza")
+        self.wait_for(lambda: self.rejects(), "nessun Reject inviato")
+        reject = self.rejects()[0]
+        self.assertEqual(reject.reason, RejectReason.CapacityExceeded)
+        self.assertEqual((reject.origin, reject.rejected_type), ("sink-9", MeshPacketType.Join))
+        self.assertEqual(self.network.get_admission_stats().rejected, 1)
+
+        self.network.set_capacity_limits(2, 0)
+        self.join("sink-9", 2)
+        self.wait_for(lambda: self.network.get_admission_stats().admitted == 1, "Join non ammesso")
+        self.assertEqual(self.network.get_admission_stats().nodes, 2)
+
+    def test_eviction_frees_slot(self):
+        """Un nodo tolto per inattività libera il posto e al rientro trova la rete al completo"""
+        self.network.set_liveness_config(20, 20)
+        self.network.set_capacity_limits(2, 0)
+        self.assertTrue(self.network.register_node("sink-1", NodeRole.Sink))
+        self.network.update_node_status("sink-1", 50, 10)
+        self.assertFalse(self.network.register_node("sink-2", NodeRole.Sink))
+
+        time.sleep(0.05)
+        self.network.check_node_liveness()
+        time.sleep(0.05)
+        self.network.check_node_liveness()
+        self.assertEqual(self.network.get_evicted_nodes(), ["sink-1"])
+        self.assertTrue(self.network.register_node("sink-2", NodeRole.Sink))
+
+        # Il primo pacchetto autentico del nodo tolto non trova posto
+        packet = MeshPacket.create_command("volume", {"level": "50"})
+        packet.set_header("sink-1", 1, 8)
+        packet.sign(self.sink)
+        self.assertTrue(self.network.receive_from_link(packet.encode()))
+        self.wait_for(lambda: any(r.reason == RejectReason.CapacityExceeded for r in self.rejects()),
+                      "rientro non respinto per capienza")
+        self.assertEqual(self.network.get_evicted_nodes(), ["sink-1"])
+        self.assertEqual(self.network.get_admission_stats().rejected, 2)
+
+class TestProtocolCapacity(unittest.TestCase):
+    """Test per la capienza applicata dal Master alle richieste di ingresso"""
+
+    def start(self, node_id, role, network, max_nodes=None):
+        config = SaberConfig.default_config()
+        config.node_id = node_id
+        config.role = role
+        if max_nodes is not None:
+            config.max_nodes = max_nodes
+        protocol = SaberProtocol(config)
+        protocol.set_sim_network(network)
+        if not protocol.initialize():
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        self.addCleanup(protocol.shutdown)
+        return protocol
+
+    def join_until(self, sink, network, condition):
+        deadline = time.monotonic() + 5.0
+        while time.monotonic() < deadline and not condition():
+            self.assertTrue(sink.request_join())
+            network.advance(50)
+            time.sleep(0.05)
+        return condition()
+
+    def test_join_rejected_then_admitted(self):
+        """Il Master al completo respinge il Join con un evento e lo ammette dopo l'aumento del limite"""
+        network = SimNetwork(1)
+        master = self.start("cap-master", NodeRole.Master, network, max_nodes=1)
+        sink = self.start("cap-sink", NodeRole.Sink, network)
+        self.assertTrue(master.open_provisioning(60))
+
+        self.assertTrue(self.join_until(sink, network,
+                                        lambda: master.get_admission_stats().rejected > 0))
+        self.assertGreater(master.get_drop_counters().get("capacity_exceeded", 0), 0)
+        events = master.get_events_since(0).events
+        self.assertTrue(any(e.type == "admission_rejected" and e.node_id == "cap-sink" for e in events))
+
+        self.assertTrue(master.set_capacity_limits(2, 0))
+        self.assertTrue(self.join_until(sink, network,
+                                        lambda: master.get_admission_stats().admitted > 0))
+        stats = master.get_admission_stats()
+        self.assertEqual((stats.max_nodes, stats.nodes), (2, 2))
+
+    def test_without_network(self):
+        """Senza rete mesh i limiti non possono essere cambiati"""
+        self.assertFalse(SaberProtocol(SaberConfig.default_config()).set_capacity_limits(4, 2))
+
+class TestCapacityConfig(ConfigFileTestCase):
+    """Test per le chiavi di capienza nel file"""
+
+    HEADER = '[node]\nid = "master-1"\nrole = "master"\n\n[provisioning]\n'
+
+    def test_keys(self):
+        """provisioning.max_nodes e max_sinks sostituiscono i limiti predefiniti"""
+        config = self.load("max_nodes = 12\nmax_sinks = 8\n")
+        self.assertEqual((config.max_nodes, config.max_sinks), (12, 8))
+        self.assertEqual(self.load("max_nodes = 0\n").max_nodes, 0)
+
+    def test_negative(self):
+        """Un limite negativo rende il file non valido"""
+        for key in ("max_nodes", "max_sinks"):
+            with self.assertRaises(RuntimeError, msg=key):
+                self.load(key + " = -1\n")
+
+if __name__ == "__main__":
+    unittest.main()
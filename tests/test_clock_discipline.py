@@ -0,0 +1,135 @@
+# Test unitari per la disciplina dell'orologio di SyncManager
+# Verifica la stima della deriva, il recupero graduale degli scarti e il riallineamento oltre la soglia
+
+import os
+import random
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import ClockDiscipline, SpecParameters, SyncManager
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+# Istante locale di partenza e intervallo tra beacon (µs)
+START_US = 1_700_000_000_000_000
+BEACON_US = 100_000
+
+def drifting_beacons(discipline, skew_ppm, count, base_offset_us=5_000_000, noise_us=500):
+    """Invia beacon da un Master che deriva di skew_ppm, con rumore di misura"""
+    rng = random.Random(7)
+    local_us = START_US
+    for index in range(count):
+        elapsed_s = index * BEACON_US / 1e6
+        measured = base_offset_us + int(skew_ppm * elapsed_s) + rng.randint(-noise_us, noise_us)
+        discipline.update(local_us, measured)
+        local_us += BEACON_US
+    return local_us
+
+class TestClockDiscipline(unittest.TestCase):
+    """Test per il filtro di offset e deriva"""
+
+    def test_first_beacon_steps(self):
+        """Il primo beacon allinea l'orologio di colpo"""
+        discipline = ClockDiscipline()
+        self.assertFalse(discipline.is_locked())
+        self.assertTrue(discipline.update(START_US, 1234))
+        self.assertTrue(discipline.is_locked())
+        self.assertEqual(discipline.offset_at(START_US), 1234)
+
+    def test_skew_estimate(self):
+        """La deriva del quarzo viene stimata dai beacon rumorosi"""
+        discipline = ClockDiscipline()
+        local_us = drifting_beacons(discipline, 40.0, 600)
+        stats = discipline.stats(local_us)
+        self.assertAlmostEqual(stats.skew_ppm, 40.0, delta=5.0)
+        self.assertEqual(stats.steps, 1)
+        self.assertEqual(stats.updates, 600)
+        # Tra un beacon e l'altro l'orologio segue la deriva stimata
+        expected = 5_000_000 + 40.0 * (local_us - START_US) / 1e6
+        self.assertAlmostEqual(discipline.offset_at(local_us), expected, delta=1000)
+
+    def test_small_error_is_slewed(self):
+        """Uno scarto sotto la soglia non sposta l'orologio di colpo"""
+        discipline = ClockDiscipline(step_threshold_us=100_000, max_slew_ppm=500)
+        discipline.update(START_US, 0)
+        before = discipline.offset_at(START_US + BEACON_US)
+        self.assertFalse(discipline.update(START_US + BEACON_US, 20_000))
+        self.assertEqual(discipline.offset_at(START_US + BEACON_US), before)
+        # Dopo un secondo il recupero, oltre alla deriva stimata, non supera i 500 ppm
+        skew = discipline.stats(START_US + BEACON_US).skew_ppm
+        later = discipline.offset_at(START_US + BEACON_US + 1_000_000)
+        self.assertGreater(later, before + skew)
+        self.assertLessEqual(later - before, skew + 500 + 1)
+
+    def test_large_error_steps(self):
+        """Uno scarto oltre la soglia riallinea l'orologio di colpo"""
+        discipline = ClockDiscipline(step_threshold_us=100_000)
+        discipline.update(START_US, 0)
+        self.assertTrue(discipline.update(START_US + BEACON_US, 250_000))
+        self.assertEqual(discipline.offset_at(START_US + BEACON_US), 250_000)
+        self.assertEqual(discipline.stats(START_US + BEACON_US).steps, 2)
+
+    def test_zero_threshold_always_steps(self):
+        """Con soglia zero ogni beacon riallinea l'orologio, come senza disciplina"""
+        discipline = ClockDiscipline(step_threshold_us=0)
+        discipline.update(START_US, 0)
+        self.assertTrue(discipline.update(START_US + BEACON_US, 300))
+        self.assertEqual(discipline.offset_at(START_US + BEACON_US), 300)
+
+    def test_monotonic(self):
+        """L'orologio sincronizzato non torna mai indietro mentre recupera uno scarto"""
+        discipline = ClockDiscipline()
+        discipline.update(START_US, 0)
+        discipline.update(START_US + BEACON_US, -50_000)
+        previous = None
+        for step_us in range(0, 2_000_000, 1_000):
+            local_us = START_US + BEACON_US + step_us
+            clock = local_us + discipline.offset_at(local_us)
+            if previous is not None:
+                self.assertGreaterEqual(clock, previous)
+            previous = clock
+
+    def test_reset(self):
+        """Dopo il reset la disciplina attende un nuovo primo beacon"""
+        discipline = ClockDiscipline()
+        drifting_beacons(discipline, 20.0, 50)
+        discipline.reset()
+        self.assertFalse(discipline.is_locked())
+        self.assertEqual(discipline.stats(START_US).updates, 0)
+
+class TestSyncManagerClock(unittest.TestCase):
+    """Test per l'orologio di SyncManager"""
+
+    def test_first_beacon_aligns_now(self):
+        """Il primo beacon porta subito now() sul tempo del Master"""
+        manager = SyncManager()
+        master = int(time.time() * 1000) + 3_000
+        manager.handle_time_beacon(master)
+        self.assertAlmostEqual(manager.now(), master, delta=50)
+        self.assertEqual(manager.get_clock_stats().steps, 1)
+
+    def test_emergency_sync_steps(self):
+        """La sincronizzazione di emergenza riallinea l'orologio di colpo"""
+        manager = SyncManager()
+        manager.handle_time_beacon(int(time.time() * 1000))
+        master = int(time.time() * 1000) + 40
+        manager.emergency_sync(master)
+        self.assertAlmostEqual(manager.now(), master, delta=20)
+        self.assertEqual(manager.get_clock_stats().steps, 2)
+
+    def test_invalid_slew(self):
+        """Una velocità di recupero nulla viene rifiutata"""
+        params = SpecParameters()
+        params.clock_max_slew_ppm = 0
+        with self.assertRaises(ValueError):
+            SyncManager(params)
+
+if __name__ == "__main__":
+    unittest.main()
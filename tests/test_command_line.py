@@ -0,0 +1,118 @@
+# Test unitari per l'avvio dei nodi e le opzioni della riga di comando "saber"
+# Verifica l'ID generato dal ruolo, la precedenza delle opzioni sul file e le combinazioni rifiutate
+
+import os
+import re
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (NodeRole, SaberConfig, TransportKind, parse_node_command_line, start_master,
+                                start_node, start_sink)
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestStartNode(unittest.TestCase):
+    """Test per l'avvio di un nodo da una configurazione"""
+
+    def start(self, starter, *args):
+        try:
+            protocol = starter(*args)
+        except RuntimeError:
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        self.addCleanup(protocol.shutdown)
+        return protocol
+
+    def test_generated_id(self):
+        """Senza ID il nodo riceve un identificatore <ruolo>-<numero>"""
+        for role in (NodeRole.Master, NodeRole.Repeater, NodeRole.Sink):
+            config = SaberConfig.default_config()
+            config.node_id = ""
+            config.role = role
+            protocol = self.start(start_node, config)
+            self.assertEqual(protocol.get_role(), role)
+            node_id = protocol.get_node_info()["node_id"]
+            self.assertRegex(node_id, r"^" + re.escape(protocol.get_node_info()["role"]) + r"-\d+$")
+
+    def test_explicit_id(self):
+        """Un ID indicato viene mantenuto anche dalle scorciatoie per ruolo"""
+        master = self.start(start_master, "cli-master")
+        self.assertEqual(master.get_role(), NodeRole.Master)
+        self.assertEqual(master.get_node_info()["node_id"], "cli-master")
+        sink = self.start(start_sink, "cli-sink")
+        self.assertEqual(sink.get_role(), NodeRole.Sink)
+        self.assertEqual(sink.get_node_info()["node_id"], "cli-sink")
+
+class TestCommandLine(ConfigFileTestCase):
+    """Test per la lettura delle opzioni della riga di comando"""
+
+    HEADER = '[node]\nid = "file-node"\nrole = "sink"\nmusic_mode = false\n\n[transport]\nkind = "udp"\n'
+
+    def test_defaults(self):
+        """Senza opzioni l'ID resta vuoto per essere generato e il cruscotto si aggiorna ogni secondo"""
+        command = parse_node_command_line(NodeRole.Repeater, [])
+        self.assertEqual(command.config.role, NodeRole.Repeater)
+        self.assertEqual(command.config.node_id, "")
+        self.assertEqual((command.interval_seconds, command.plain, command.loop), (1, False, False))
+        self.assertEqual((command.play_path, command.record_path), ("", ""))
+
+    def test_config_is_base(self):
+        """Il file fornisce i valori non indicati, il ruolo viene sempre dalla riga di comando"""
+        self.load("")
+        command = parse_node_command_line(NodeRole.Master, ["--config", self.path])
+        self.assertEqual(command.config.role, NodeRole.Master)
+        self.assertEqual(command.config.node_id, "file-node")
+        self.assertEqual(command.config.transport, TransportKind.Udp)
+        self.assertFalse(command.config.is_music_mode)
+
+    def test_options_override_config(self):
+        """--id, --transport e --music sostituiscono i valori del file"""
+        self.load("")
+        command = parse_node_command_line(NodeRole.Sink, ["--config", self.path, "--id", "cli-sink",
+                                                          "--transport", "ble", "--music", "--bt", "00:11"])
+        self.assertEqual(command.config.node_id, "cli-sink")
+        self.assertEqual(command.config.transport, TransportKind.Ble)
+        self.assertTrue(command.config.is_music_mode)
+        self.assertEqual(command.config.bt_address, "00:11")
+        self.assertFalse(parse_node_command_line(NodeRole.Sink, ["--voice"]).config.is_music_mode)
+
+    def test_playback_options(self):
+        """--play spetta al Master e --record al sink, con intervallo e modo semplice"""
+        command = parse_node_command_line(NodeRole.Master, ["--play", "brano.wav", "--loop",
+                                                            "--interval", "5", "--plain"])
+        self.assertEqual((command.play_path, command.loop), ("brano.wav", True))
+        self.assertEqual((command.interval_seconds, command.plain), (5, True))
+        self.assertEqual(parse_node_command_line(NodeRole.Sink, ["--record", "uscita.wav"]).record_path,
+                         "uscita.wav")
+
+    def test_usage_errors(self):
+        """Le combinazioni non valide vengono rifiutate (uscita 2 della riga di comando)"""
+        cases = [
+            (NodeRole.Sink, ["--music", "--voice"]),
+            (NodeRole.Sink, ["--interval", "0"]),
+            (NodeRole.Sink, ["--interval", "-3"]),
+            (NodeRole.Sink, ["--interval", "uno"]),
+            (NodeRole.Sink, ["--transport", "wifi"]),
+            (NodeRole.Sink, ["--play", "brano.wav"]),
+            (NodeRole.Repeater, ["--play", "brano.wav"]),
+            (NodeRole.Master, ["--record", "uscita.wav"]),
+            (NodeRole.Repeater, ["--record", "uscita.wav"]),
+        ]
+        for role, args in cases:
+            with self.assertRaises(ValueError, msg=" ".join(args)):
+                parse_node_command_line(role, args)
+
+    def test_invalid_config(self):
+        """Un file di configurazione non valido viene segnalato"""
+        self.write("[node\n")
+        with self.assertRaises(RuntimeError):
+            parse_node_command_line(NodeRole.Sink, ["--config", self.path])
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,149 @@
+# Test unitari per il file di configurazione e il keystore del protocollo SABER
+# Verifica la lettura dei file validi, il rifiuto di quelli malformati e la derivazione della chiave con sale
+
+import os
+import sys
+import tempfile
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import Keystore, NodeRole
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+BASE = """
+[node]
+id = "sink-1"
+role = "sink"
+"""
+
+class TestValidFile(ConfigFileTestCase):
+    """Test per i file di configurazione validi"""
+
+    def test_basic_values(self):
+        """Sezioni, interi e commenti vengono letti"""
+        config = self.load(BASE + "\n[health]\nport = 8080  # porta locale\n")
+        self.assertEqual(config.node_id, "sink-1")
+        self.assertEqual(config.role, NodeRole.Sink)
+        self.assertEqual(config.health_port, 8080)
+
+    def test_escape_sequences(self):
+        """Le sequenze di escape nelle stringhe vengono interpretate"""
+        config = self.load(BASE + '\n[mqtt]\nusername = "a\\"b\\\\c # d"\n')
+        self.assertEqual(config.mqtt_username, 'a"b\\c # d')
+
+    def test_port_bounds(self):
+        """Le porte agli estremi dell'intervallo sono accettate"""
+        self.assertEqual(self.load(BASE + "\n[control]\nport = 1\n").control_port, 1)
+        self.assertEqual(self.load(BASE + "\n[control]\nport = 65535\n").control_port, 65535)
+
+class TestMalformedFile(ConfigFileTestCase):
+    """Test per i file di configurazione malformati"""
+
+    def test_malformed_lines(self):
+        """Sezioni aperte, righe senza '=' e chiavi vuote vengono rifiutate"""
+        for line in ("[health", "port 8080", "= 8080"):
+            with self.assertRaises(RuntimeError, msg=line):
+                self.load(BASE + "\n" + line + "\n")
+
+    def test_bad_strings(self):
+        """Escape sconosciuti, stringhe non terminate e testo dopo la stringa vengono rifiutati"""
+        for value in ('"a\\qb"', '"aperta', '"a" b', '"fine\\"'):
+            with self.assertRaises(RuntimeError, msg=value):
+                self.load(BASE + "\n[mqtt]\nusername = " + value + "\n")
+
+    def test_out_of_range_ports(self):
+        """Porte fuori da 1-65535 vengono rifiutate invece di essere troncate"""
+        for section in ("health", "control"):
+            for port in (0, -1, 65536, 70000):
+                with self.assertRaises(RuntimeError, msg=section + " " + str(port)):
+                    self.load(BASE + "\n[" + section + "]\nport = " + str(port) + "\n")
+
+class TestKeystore(unittest.TestCase):
+    """Test per la derivazione della chiave del keystore dalla passphrase"""
+
+    def setUp(self):
+        directory = tempfile.TemporaryDirectory()
+        self.addCleanup(directory.cleanup)
+        self.path = os.path.join(directory.name, "secrets.keystore")
+        for name in ("SABER_KEYSTORE_KEY", "SABER_KEYSTORE_PASSPHRASE"):
+            previous = os.environ.pop(name, None)
+            if previous is not None:
+                self.addCleanup(os.environ.__setitem__, name, previous)
+        os.environ["SABER_KEYSTORE_PASSPHRASE"] = "correct horse"
+        self.addCleanup(os.environ.pop, "SABER_KEYSTORE_PASSPHRASE", None)
+
+    def read_header(self):
+        with open(self.path) as file:
+            return dict(line.split(" = ", 1) for line in file.read().splitlines()
+                        if line.startswith("kdf."))
+
+    def test_salt_in_header(self):
+        """Il sale casuale viene salvato nell'intestazione e riusato all'apertura"""
+        keystore = Keystore.open(self.path)
+        keystore.put("mqtt", "segreto")
+        keystore.save()
+        header = self.read_header()
+        self.assertEqual(len(header['kdf.salt'].strip('"')), 32)
+        self.assertGreater(int(header['kdf.iterations']), 0)
+        self.assertEqual(Keystore.open(self.path).get("mqtt"), "segreto")
+
+    def test_salts_differ(self):
+        """Due archivi con la stessa passphrase hanno sali diversi"""
+        salts = []
+        for _ in range(2):
+            keystore = Keystore.open(self.path)
+            keystore.put("mqtt", "segreto")
+            keystore.save()
+            salts.append(self.read_header()['kdf.salt'])
+            os.remove(self.path)
+        self.assertNotEqual(salts[0], salts[1])
+
+    def test_nonces_differ(self):
+        """Due voci cifrate con la stessa chiave, anche da aperture diverse, hanno nonce diversi"""
+        keystore = Keystore.open(self.path)
+        keystore.put("mqtt", "segreto")
+        keystore.put("api", "segreto")
+        keystore.save()
+        keystore = Keystore.open(self.path)
+        keystore.put("token", "segreto")
+        keystore.save()
+        with open(self.path) as file:
+            entries = dict(line.split(" = ", 1) for line in file.read().splitlines()
+                           if " = " in line and not line.startswith("kdf."))
+        # Il nonce occupa i primi 12 byte: anche gli 8 oltre il prefisso devono cambiare
+        nonces = [value.strip('"')[:24] for value in entries.values()]
+        self.assertEqual(len(nonces), 3)
+        self.assertEqual(len(set(nonces)), 3)
+        self.assertEqual(len({nonce[8:] for nonce in nonces}), 3)
+        self.assertEqual(Keystore.open(self.path).get("token"), "segreto")
+
+    def test_wrong_passphrase(self):
+        """Una passphrase errata non decifra i segreti"""
+        keystore = Keystore.open(self.path)
+        keystore.put("mqtt", "segreto")
+        keystore.save()
+        os.environ["SABER_KEYSTORE_PASSPHRASE"] = "wrong horse"
+        with self.assertRaises(RuntimeError):
+            Keystore.open(self.path).get("mqtt")
+
+    def test_unsalted_refused(self):
+        """Un archivio con segreti ma senza intestazione kdf.salt ha un formato sconosciuto"""
+        with open(self.path, "w") as file:
+            file.write('mqtt = "' + "00" * 40 + '"\n')
+        with self.assertRaises(RuntimeError):
+            Keystore.open(self.path)
+
+    def test_reserved_names(self):
+        """I nomi dell'intestazione non possono essere usati per i segreti"""
+        with self.assertRaises(RuntimeError):
+            Keystore.open(self.path).put("kdf.salt", "00")
+
+if __name__ == "__main__":
+    unittest.main()
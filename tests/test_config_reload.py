@@ -0,0 +1,80 @@
+# Test unitari per il ricaricamento a caldo della configurazione del protocollo SABER
+# Verifica la classificazione delle chiavi e il resoconto delle modifiche
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import SaberConfig, SaberProtocol
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+BASE = """
+[node]
+id = "sink-1"
+role = "sink"
+
+[degradation]
+enabled = true
+"""
+
+class TestLiveReloadableKeys(unittest.TestCase):
+    """Test per la classificazione delle chiavi ricaricabili"""
+
+    def test_live_keys(self):
+        """Filtro di log, degradazione e soglie di sicurezza si applicano a caldo"""
+        for key in ("log.filter", "degradation.enabled", "security.ids_window_ms",
+                    "diagnostics.record_paths"):
+            self.assertTrue(SaberConfig.is_live_reloadable(key), key)
+
+    def test_restart_keys(self):
+        """Identità, ruolo e chiavi richiedono un riavvio"""
+        for key in ("node.id", "node.role", "security.keystore", "degradation"):
+            self.assertFalse(SaberConfig.is_live_reloadable(key), key)
+
+class TestReloadConfig(ConfigFileTestCase):
+    """Test per il ricaricamento del file senza avviare la rete"""
+
+    def setUp(self):
+        super().setUp()
+        self.write(BASE)
+        config = SaberConfig.from_file(self.path)
+        self.assertEqual(config.config_file, self.path)
+        self.protocol = SaberProtocol(config)
+
+    def test_unchanged_file(self):
+        """Un file invariato non produce modifiche"""
+        report = self.protocol.reload_config()
+        self.assertIsNone(report.error)
+        self.assertEqual(report.applied, [])
+        self.assertEqual(report.restart_required, [])
+
+    def test_live_and_restart_changes(self):
+        """Le modifiche vengono divise tra applicate e da riavvio"""
+        self.write(BASE.replace('id = "sink-1"', 'id = "sink-2"') + '\n[log]\nfilter = "warn"\n')
+        report = self.protocol.reload_config()
+        self.assertIsNone(report.error)
+        self.assertEqual(report.applied, ["log.filter"])
+        self.assertEqual(report.restart_required, ["node.id"])
+
+    def test_invalid_file_rejected(self):
+        """Un file non valido non modifica la configurazione in uso"""
+        self.write(BASE + '\n[log]\nfilter = "rumore"\n')
+        report = self.protocol.reload_config()
+        self.assertIsNotNone(report.error)
+        self.assertEqual(report.applied, [])
+
+    def test_without_file(self):
+        """Una configurazione non caricata da file non può essere ricaricata"""
+        report = SaberProtocol(SaberConfig.default_config()).reload_config()
+        self.assertIsNotNone(report.error)
+
+if __name__ == "__main__":
+    unittest.main()
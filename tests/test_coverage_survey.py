@@ -0,0 +1,88 @@
+# Test unitari per il sopralluogo radio del protocollo SABER
+# Verifica l'attribuzione dei sondaggi alle posizioni e il resoconto di copertura
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import CoverageSurvey
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestCoverageSurvey(unittest.TestCase):
+    """Test per il resoconto di copertura di un sopralluogo"""
+    
+    def setUp(self):
+        self.survey = CoverageSurvey(-75, 20)
+    
+    def probe(self, peer, rssi=None, replied=True):
+        probe_id = self.survey.record_probe(peer)
+        if replied:
+            self.assertTrue(self.survey.record_reply(peer, probe_id, rssi))
+    
+    def test_no_position_no_probe(self):
+        """Senza una posizione etichettata i sondaggi non vengono registrati"""
+        self.assertIsNone(self.survey.record_probe("master-1"))
+        self.assertEqual(self.survey.report().positions, [])
+    
+    def test_covered_position(self):
+        """Un nodo fisso con buon RSSI e senza perdite copre la posizione"""
+        self.survey.set_position("palco")
+        for _ in range(5):
+            self.probe("master-1", -60)
+            self.probe("repeater-1", -85)
+        position = self.survey.report().positions[0]
+        self.assertEqual(position.label, "palco")
+        self.assertTrue(position.covered)
+        self.assertEqual(position.best_peer, "master-1")
+        self.assertEqual([link.peer for link in position.links], ["master-1", "repeater-1"])
+        self.assertEqual(position.links[0].mean_rssi_dbm, -60)
+        self.assertEqual(self.survey.report().uncovered_positions, [])
+    
+    def test_weak_and_lossy_position(self):
+        """RSSI debole o troppe perdite lasciano la posizione scoperta"""
+        self.survey.set_position("bar")
+        self.probe("master-1", -90)
+        self.probe("repeater-1", -60)
+        self.probe("repeater-1", replied=False)
+        report = self.survey.report()
+        link = report.positions[0].links[1]
+        self.assertEqual(link.probes_sent, 2)
+        self.assertEqual(link.replies, 1)
+        self.assertAlmostEqual(link.loss_rate, 0.5)
+        self.assertFalse(report.positions[0].covered)
+        self.assertEqual(report.uncovered_positions, ["bar"])
+    
+    def test_reply_counts_for_probe_position(self):
+        """Una risposta tardiva vale per la posizione da cui è partito il sondaggio"""
+        self.survey.set_position("ingresso")
+        probe_id = self.survey.record_probe("master-1")
+        self.survey.set_position("terrazza")
+        self.assertTrue(self.survey.record_reply("master-1", probe_id, -70))
+        self.assertFalse(self.survey.record_reply("master-1", probe_id, -70))
+        positions = self.survey.report().positions
+        self.assertEqual([p.label for p in positions], ["ingresso", "terrazza"])
+        self.assertEqual(positions[0].links[0].replies, 1)
+        self.assertEqual(positions[1].links, [])
+    
+    def test_loss_only_without_rssi(self):
+        """Senza RSSI dalla radio conta solo la perdita"""
+        self.survey.set_position("cucina")
+        self.probe("master-1")
+        position = self.survey.report().positions[0]
+        self.assertIsNone(position.links[0].mean_rssi_dbm)
+        self.assertTrue(position.covered)
+    
+    def test_empty_label_rejected(self):
+        """Un'etichetta vuota non è valida"""
+        with self.assertRaises(ValueError):
+            self.survey.set_position("")
+
+if __name__ == '__main__':
+    unittest.main()
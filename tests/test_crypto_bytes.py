@@ -0,0 +1,73 @@
+# Test unitari per l'interfaccia a bytes di MeshCrypto
+# Verifica cifratura, firma, scambio di chiavi ed esportazione delle chiavi pubbliche con oggetti bytes
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import MeshCrypto
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+NETWORK_KEY = [7] * 32
+
+class TestCryptoBytes(unittest.TestCase):
+    """Test per i metodi di MeshCrypto che accettano e restituiscono bytes"""
+
+    def setUp(self):
+        """Crea due nodi con la stessa chiave di rete"""
+        self.sender = MeshCrypto.with_network_key(NETWORK_KEY)
+        self.receiver = MeshCrypto.with_network_key(NETWORK_KEY)
+
+    def test_encrypt_round_trip(self):
+        """Un payload bytes torna uguale, anche con dati associati"""
+        encrypted = self.sender.encrypt(b"audio", b"header")
+        self.assertIsInstance(encrypted, bytes)
+        self.assertEqual(self.receiver.decrypt(encrypted, b"header"), b"audio")
+        with self.assertRaises(RuntimeError):
+            self.receiver.decrypt(self.sender.encrypt(b"audio", b"header"), b"altro")
+
+    def test_decrypt_from(self):
+        """La decifratura per mittente accetta e restituisce bytes"""
+        encrypted = self.sender.encrypt(b"\x00\x01\x02")
+        self.assertEqual(self.receiver.decrypt_from("node-1", encrypted), b"\x00\x01\x02")
+
+    def test_lists_unchanged(self):
+        """Le liste di interi continuano a restituire liste"""
+        self.assertEqual(self.receiver.decrypt(self.sender.encrypt([1, 2, 3])), [1, 2, 3])
+        self.assertIsInstance(self.sender.hash([1, 2, 3]), list)
+
+    def test_sign_verify(self):
+        """Una firma bytes si verifica con la chiave esportata"""
+        keys = self.sender.export_public_keys()
+        self.receiver.register_node_key("master-1", keys["public_key"])
+        signature = self.sender.sign(b"messaggio")
+        self.assertIsInstance(signature, bytes)
+        self.assertTrue(self.receiver.verify("master-1", b"messaggio", signature))
+        self.assertFalse(self.receiver.verify("master-1", b"alterato", signature))
+
+    def test_key_exchange(self):
+        """Lo scambio di chiavi produce lo stesso segreto sui due lati"""
+        sender_keys = self.sender.export_public_keys()
+        receiver_keys = self.receiver.export_public_keys()
+        shared = self.sender.key_exchange(receiver_keys["exchange_public_key"])
+        self.assertIsInstance(shared, bytes)
+        self.assertEqual(len(shared), 32)
+        self.assertEqual(shared, self.receiver.key_exchange(sender_keys["exchange_public_key"]))
+
+    def test_export_public_keys(self):
+        """L'esportazione riporta le chiavi locali e quelle dei nodi noti"""
+        self.receiver.register_node_key("master-1", self.sender.export_public_keys()["public_key"])
+        keys = self.receiver.export_public_keys()
+        self.assertEqual(keys["public_key"], bytes(self.receiver.get_public_key()))
+        self.assertEqual(keys["exchange_public_key"], bytes(self.receiver.get_exchange_public_key()))
+        self.assertEqual(keys["nodes"], {"master-1": bytes(self.sender.get_public_key())})
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,81 @@
+# Test unitari per la scala di degrado del protocollo SABER
+# Verifica l'ordine dei gradini, i tempi di permanenza e il ritorno ai parametri nominali
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import DegradationConfig, DegradationLadder, DegradationLevel
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestDegradationLadder(unittest.TestCase):
+    """Test per la scala di degrado sotto una rete scarsa"""
+
+    def setUp(self):
+        config = DegradationConfig()
+        config.escalate_after_ms = 1000
+        config.recover_after_ms = 3000
+        self.ladder = DegradationLadder(config)
+
+    def test_short_dip_ignored(self):
+        """Un calo più breve del tempo di permanenza non cambia gradino"""
+        self.assertIsNone(self.ladder.update(0.1, 0))
+        self.assertIsNone(self.ladder.update(0.1, 500))
+        self.assertIsNone(self.ladder.update(0.9, 800))
+        self.assertIsNone(self.ladder.update(0.1, 1500))
+        self.assertEqual(self.ladder.get_level(), DegradationLevel.Nominal)
+
+    def test_escalates_in_order(self):
+        """La scala sale un gradino alla volta nell'ordine previsto"""
+        levels = []
+        for now in range(0, 6000, 100):
+            level = self.ladder.update(0.1, now)
+            if level is not None:
+                levels.append(level)
+        self.assertEqual(levels, [DegradationLevel.ReducedBitrate, DegradationLevel.StrongerFec,
+                                  DegradationLevel.LargerBuffer, DegradationLevel.VoiceSampleRate,
+                                  DegradationLevel.SuspendedSinks])
+
+    def test_recovers(self):
+        """Con qualità buona la scala torna indietro un gradino alla volta"""
+        self.ladder.update(0.1, 0)
+        self.ladder.update(0.1, 1000)
+        self.ladder.update(0.1, 2000)
+        self.assertEqual(self.ladder.get_level(), DegradationLevel.StrongerFec)
+        self.assertIsNone(self.ladder.update(0.9, 2100))
+        self.assertEqual(self.ladder.update(0.9, 5100), DegradationLevel.ReducedBitrate)
+        self.assertEqual(self.ladder.update(0.9, 8100), DegradationLevel.Nominal)
+
+    def test_hysteresis(self):
+        """Una qualità intermedia non fa né salire né scendere"""
+        self.ladder.update(0.1, 0)
+        self.ladder.update(0.1, 1000)
+        for now in range(1000, 10000, 500):
+            self.assertIsNone(self.ladder.update(0.4, now))
+        self.assertEqual(self.ladder.get_level(), DegradationLevel.ReducedBitrate)
+
+    def test_settings(self):
+        """Ogni gradino mantiene le misure dei precedenti"""
+        nominal = self.ladder.settings_for(DegradationLevel.Nominal, True, 40, 100, False)
+        self.assertEqual(nominal.sample_rate_hz, 48000)
+        self.assertFalse(nominal.fec_enabled)
+        voice = self.ladder.settings_for(DegradationLevel.VoiceSampleRate, True, 40, 100, False)
+        self.assertTrue(voice.fec_enabled)
+        self.assertEqual(voice.buffer_ms, 60)
+        self.assertEqual(voice.sample_rate_hz, 16000)
+        self.assertLess(voice.bitrate_kbps, nominal.bitrate_kbps)
+
+    def test_buffer_capped_by_budget(self):
+        """Il buffer aumentato non supera il budget di latenza"""
+        settings = self.ladder.settings_for(DegradationLevel.LargerBuffer, True, 90, 100, False)
+        self.assertEqual(settings.buffer_ms, 100)
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,105 @@
+# Test unitari per l'equalizzazione dei ritardi tra i sink
+# Verifica la scadenza comune di riproduzione, la configurazione e lo stato esposto dal Master
+
+import os
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (DelayEqualizationConfig, DelayEqualizer, NodeRole, SaberConfig, SaberProtocol,
+                                SimNetwork)
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestDelayEqualizer(unittest.TestCase):
+    """Test per la scadenza comune calcolata dal Master"""
+
+    def setUp(self):
+        config = DelayEqualizationConfig()
+        config.hold_ms = 1000
+        self.equalizer = DelayEqualizer(config)
+
+    def test_raise(self):
+        """La scadenza sale subito, arrotondata alla durata del frame"""
+        self.assertEqual(self.equalizer.update(55, 0), 60)
+        self.assertEqual(self.equalizer.update(60, 10), None)
+        self.assertEqual(self.equalizer.update(81, 20), 90)
+        self.assertEqual(self.equalizer.get_deadline_ms(), 90)
+
+    def test_lower(self):
+        """La scadenza scende solo dopo il periodo di attesa, alla richiesta più alta del periodo"""
+        self.equalizer.update(90, 0)
+        self.assertEqual(self.equalizer.update(40, 100), None)
+        self.assertEqual(self.equalizer.update(50, 600), None)
+        self.assertEqual(self.equalizer.update(30, 1100), 50)
+        self.assertEqual(self.equalizer.get_deadline_ms(), 50)
+
+    def test_raise_cancels_lower(self):
+        """Una richiesta più alta durante l'attesa annulla la discesa"""
+        self.equalizer.update(90, 0)
+        self.equalizer.update(40, 100)
+        self.equalizer.update(90, 500)
+        self.assertEqual(self.equalizer.update(40, 1200), None)
+        self.assertEqual(self.equalizer.get_deadline_ms(), 90)
+
+    def test_disable(self):
+        """Disattivare l'equalizzazione azzera la scadenza"""
+        self.equalizer.update(90, 0)
+        config = DelayEqualizationConfig()
+        config.enabled = False
+        self.equalizer.set_config(config)
+        self.assertEqual(self.equalizer.get_deadline_ms(), 0)
+        self.assertFalse(self.equalizer.get_config().enabled)
+
+class TestEqualizationConfig(ConfigFileTestCase):
+    """Test per la configurazione letta dal file"""
+
+    HEADER = '[node]\nid = "master-1"\nrole = "master"\n\n'
+
+    def test_defaults(self):
+        """Senza chiavi l'equalizzazione è attiva con dieci secondi di attesa prima di scendere"""
+        equalization = self.load('').delay_equalization
+        self.assertTrue(equalization.enabled)
+        self.assertEqual(equalization.hold_ms, 10000)
+
+    def test_values(self):
+        """Le chiavi audio.equalization_* impostano attivazione e attesa, modificabili senza riavvio"""
+        equalization = self.load('[audio]\nequalization_enabled = false\nequalization_hold_ms = 2000\n'
+                                 ).delay_equalization
+        self.assertFalse(equalization.enabled)
+        self.assertEqual(equalization.hold_ms, 2000)
+        self.assertTrue(SaberConfig.is_live_reloadable("audio.equalization_hold_ms"))
+
+    def test_invalid(self):
+        """Un'attesa negativa viene rifiutata"""
+        with self.assertRaises(RuntimeError):
+            self.load('[audio]\nequalization_hold_ms = -1\n')
+
+class TestEqualizationStatus(unittest.TestCase):
+    """Test per lo stato esposto dal protocollo"""
+
+    def test_master(self):
+        """Il Master fissa una scadenza e la pubblica tra le metriche"""
+        config = SaberConfig.default_config()
+        config.node_id = "equalization-master"
+        config.role = NodeRole.Master
+        protocol = SaberProtocol(config)
+        protocol.set_sim_network(SimNetwork(1))
+        if not protocol.initialize():
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        self.addCleanup(protocol.shutdown)
+        time.sleep(0.3)
+        status = protocol.get_delay_equalization()
+        self.assertGreater(status.deadline_ms, 0)
+        self.assertEqual(status.sink_delays_ms, {})
+        self.assertIn("# TYPE saber_playout_deadline_ms gauge", protocol.get_prometheus_metrics())
+
+if __name__ == "__main__":
+    unittest.main()
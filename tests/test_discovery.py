@@ -0,0 +1,98 @@
+# Test unitari per la scoperta dei nodi via BLE del protocollo SABER
+# Verifica il formato degli annunci, la scansione e la scadenza dei nodi
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (MAX_ADVERTISEMENT_BYTES, DiscoveryAdvertisement, Node, NodeDiscovery,
+                                NodeRole, encode_advertisement, parse_advertisement)
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def advertisement(node_id, role):
+    entry = DiscoveryAdvertisement()
+    entry.node_id = node_id
+    entry.role = role
+    return encode_advertisement(entry)
+
+class TestAdvertisementFormat(unittest.TestCase):
+    """Test per la codifica delle strutture AD"""
+
+    def test_roundtrip(self):
+        """ID e ruolo sopravvivono alla codifica"""
+        parsed = parse_advertisement(advertisement("repeater-1", NodeRole.Repeater))
+        self.assertEqual(parsed.node_id, "repeater-1")
+        self.assertEqual(parsed.role, NodeRole.Repeater)
+
+    def test_padding_ignored(self):
+        """Gli zeri di riempimento dopo le strutture AD non contano"""
+        data = advertisement("sink-1", NodeRole.Sink) + [0, 0, 0]
+        self.assertEqual(parse_advertisement(data).node_id, "sink-1")
+
+    def test_foreign_advertisements(self):
+        """Annunci di altri servizi o troncati vengono ignorati"""
+        self.assertIsNone(parse_advertisement([2, 0x01, 0x06, 3, 0x03, 0x0F, 0x18]))
+        self.assertIsNone(parse_advertisement(advertisement("sink-1", NodeRole.Sink)[:-3] + [40]))
+        self.assertIsNone(parse_advertisement([]))
+
+    def test_node_id_limits(self):
+        """ID vuoti o troppo lunghi non possono essere annunciati"""
+        with self.assertRaises(ValueError):
+            advertisement("", NodeRole.Sink)
+        with self.assertRaises(ValueError):
+            advertisement("x" * MAX_ADVERTISEMENT_BYTES, NodeRole.Sink)
+
+class TestNodeDiscovery(unittest.TestCase):
+    """Test per la registrazione dei nodi scoperti"""
+
+    def setUp(self):
+        self.discovery = NodeDiscovery("master-1", NodeRole.Master, 1000)
+
+    def test_local_advertisement(self):
+        """L'annuncio del nodo locale contiene ID e ruolo"""
+        parsed = parse_advertisement(self.discovery.advertisement())
+        self.assertEqual(parsed.node_id, "master-1")
+        self.assertEqual(parsed.role, NodeRole.Master)
+
+    def test_observe_peer(self):
+        """Un annuncio di un altro nodo ne registra ruolo, indirizzo e RSSI"""
+        data = advertisement("sink-1", NodeRole.Sink)
+        node = self.discovery.observe("AA:BB:CC:DD:EE:01", data, -60, 100)
+        self.assertEqual(node.role, NodeRole.Sink)
+        self.assertEqual(node.advertisements, 1)
+        node = self.discovery.observe("AA:BB:CC:DD:EE:02", data, -72, 200)
+        self.assertEqual(node.address, "AA:BB:CC:DD:EE:02")
+        self.assertEqual(node.rssi_dbm, -72)
+        self.assertEqual(node.advertisements, 2)
+        self.assertEqual([entry.node_id for entry in self.discovery.get_nodes()], ["sink-1"])
+
+    def test_own_advertisement_ignored(self):
+        """Il nodo non scopre sé stesso"""
+        self.assertIsNone(self.discovery.observe("AA:BB:CC:DD:EE:00", self.discovery.advertisement(), -40, 0))
+
+    def test_expire(self):
+        """I nodi senza annunci recenti vengono dimenticati"""
+        self.discovery.observe("AA:BB:CC:DD:EE:01", advertisement("sink-1", NodeRole.Sink), -60, 0)
+        self.discovery.observe("AA:BB:CC:DD:EE:02", advertisement("sink-2", NodeRole.Sink), -60, 900)
+        self.assertEqual(self.discovery.expire(1500), ["sink-1"])
+        self.assertEqual([entry.node_id for entry in self.discovery.get_nodes()], ["sink-2"])
+
+class TestSignalStrength(unittest.TestCase):
+    """Test per la potenza del segnale dei nodi"""
+
+    def test_signal_strength(self):
+        """La potenza del segnale è assente finché non viene misurata"""
+        node = Node("sink-1", NodeRole.Sink)
+        self.assertIsNone(node.get_signal_strength())
+        node.set_signal_strength(-65)
+        self.assertEqual(node.get_signal_strength(), -65)
+
+if __name__ == "__main__":
+    unittest.main()
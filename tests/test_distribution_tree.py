@@ -0,0 +1,59 @@
+# Test unitari per gli alberi di distribuzione degli stream
+# Verifica la potatura verso i sottoscrittori attivi e il ricalcolo quando un nodo cambia attività
+
+import os
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import MeshNetwork, Node, NodeRole
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestDistributionTree(unittest.TestCase):
+    """Test per l'albero di distribuzione calcolato dal Master"""
+
+    def setUp(self):
+        self.network = MeshNetwork(Node("master-1", NodeRole.Master))
+        for node_id in ("sink-1", "sink-2"):
+            self.network.register_node(node_id, NodeRole.Sink)
+
+    def test_pruned_to_active_subscribers(self):
+        """Solo i sink attivi e sottoscritti ricevono lo stream"""
+        self.network.update_node_status("sink-1", 50, 10)
+        self.network.update_node_status("sink-2", 50, 10)
+        self.network.subscribe("sink-1", 1)
+        self.assertEqual(self.network.get_forward_targets(1), ["sink-1"])
+        self.assertEqual(self.network.get_forward_targets(2), [])
+
+    def test_subscribe_before_first_ping(self):
+        """Un sink sottoscritto prima del suo primo segno di vita entra nell'albero appena risponde"""
+        self.network.subscribe("sink-1", 1)
+        self.assertEqual(self.network.get_forward_targets(1), [])
+        self.network.update_node_status("sink-1", 50, 10)
+        self.assertEqual(self.network.get_forward_targets(1), ["sink-1"])
+        self.assertEqual(self.network.get_distribution_tree(1).children, {"master-1": ["sink-1"]})
+
+    def test_lost_and_returned(self):
+        """Un sink perso esce dall'albero e vi rientra quando torna attivo"""
+        self.network.set_liveness_config(50, 0)
+        self.network.update_node_status("sink-1", 50, 10)
+        self.network.subscribe("sink-1", 1)
+        self.assertEqual(self.network.get_forward_targets(1), ["sink-1"])
+
+        time.sleep(0.1)
+        self.network.check_node_liveness()
+        self.assertEqual(self.network.get_forward_targets(1), [])
+
+        self.network.update_node_status("sink-1", 50, 10)
+        self.network.check_node_liveness()
+        self.assertEqual(self.network.get_forward_targets(1), ["sink-1"])
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,76 @@
+# Test unitari per il riallineamento d'emergenza degli orologi
+# Verifica che solo il Master lo richieda e che il sink indicato lo applichi attraverso la rete simulata
+
+import os
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import NodeRole, SaberConfig, SaberProtocol, SimNetwork
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def make_protocol(node_id, role):
+    config = SaberConfig.default_config()
+    config.node_id = node_id
+    config.role = role
+    return SaberProtocol(config)
+
+class TestEmergencySyncRequest(unittest.TestCase):
+    """Test per la richiesta senza avviare la rete"""
+
+    def test_not_master(self):
+        """Solo il Master riallinea gli orologi"""
+        self.assertFalse(make_protocol("sink-1", NodeRole.Sink).request_emergency_sync(["sink-1"]))
+
+    def test_without_network(self):
+        """Senza rete nessun riallineamento parte"""
+        protocol = make_protocol("master", NodeRole.Master)
+        self.assertFalse(protocol.request_emergency_sync([]))
+        stats = protocol.get_emergency_sync_stats()
+        self.assertEqual((stats.sent, stats.nodes_targeted, stats.applied), (0, 0, 0))
+
+class TestEmergencySyncRecovery(unittest.TestCase):
+    """Test per il riallineamento tra nodi collegati alla stessa rete simulata"""
+
+    def start(self, node_id, role, network):
+        protocol = make_protocol(node_id, role)
+        protocol.set_sim_network(network)
+        if not protocol.initialize():
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        self.addCleanup(protocol.shutdown)
+        return protocol
+
+    def run_until(self, network, condition, timeout=5.0):
+        deadline = time.monotonic() + timeout
+        while time.monotonic() < deadline and not condition():
+            network.advance(50)
+            time.sleep(0.05)
+        return condition()
+
+    def test_targeted(self):
+        """Solo il sink indicato applica il riallineamento"""
+        network = SimNetwork(3)
+        master = self.start("sim-master", NodeRole.Master, network)
+        targeted = self.start("sim-sink-1", NodeRole.Sink, network)
+        other = self.start("sim-sink-2", NodeRole.Sink, network)
+        self.assertTrue(self.run_until(network, lambda: len(master.get_topology().nodes) == 3))
+        # I sink accettano il riallineamento solo da un Master conosciuto
+        targeted.register_node("sim-master", NodeRole.Master)
+        other.register_node("sim-master", NodeRole.Master)
+
+        self.assertTrue(master.request_emergency_sync(["sim-sink-1"]))
+        self.assertTrue(self.run_until(network, lambda: targeted.get_emergency_sync_stats().applied == 1))
+        self.assertGreater(targeted.get_emergency_sync_stats().last_applied_at_ms, 0)
+        self.assertEqual(other.get_emergency_sync_stats().applied, 0)
+        stats = master.get_emergency_sync_stats()
+        self.assertEqual((stats.sent, stats.nodes_targeted), (1, 1))
+
+if __name__ == "__main__":
+    unittest.main()
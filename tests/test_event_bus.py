@@ -0,0 +1,117 @@
+# Test unitari per il bus degli eventi del protocollo SABER
+# Verifica la consegna sul thread dedicato, la registrazione per tipo e la coda limitata
+
+import os
+import sys
+import threading
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (EventBus, ProtocolEvent, ProtocolEventType, SaberConfig, SaberProtocol,
+                                protocol_event_type_to_string)
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def event(event_type, node_id="sink-1", latency_ms=0):
+    result = ProtocolEvent()
+    result.type = event_type
+    result.node_id = node_id
+    result.latency_ms = latency_ms
+    return result
+
+class TestEventBus(unittest.TestCase):
+    """Test per la consegna degli eventi"""
+
+    def test_delivery_by_type(self):
+        """Ogni funzione riceve solo gli eventi del tipo registrato, in ordine"""
+        bus = EventBus()
+        joined = []
+        left = []
+        bus.subscribe(ProtocolEventType.NodeJoined, lambda e: joined.append(e.node_id))
+        bus.subscribe(ProtocolEventType.NodeLeft, lambda e: left.append(e.node_id))
+        bus.publish(event(ProtocolEventType.NodeJoined, "sink-1"))
+        bus.publish(event(ProtocolEventType.NodeLeft, "sink-2"))
+        bus.publish(event(ProtocolEventType.NodeJoined, "sink-3"))
+        bus.flush()
+        self.assertEqual(joined, ["sink-1", "sink-3"])
+        self.assertEqual(left, ["sink-2"])
+
+    def test_dedicated_thread(self):
+        """Le funzioni vengono chiamate dal thread del bus"""
+        bus = EventBus()
+        threads = []
+        bus.subscribe(ProtocolEventType.SyncLost, lambda e: threads.append(threading.get_ident()))
+        bus.publish(event(ProtocolEventType.SyncLost))
+        bus.flush()
+        self.assertEqual(len(threads), 1)
+        self.assertNotEqual(threads[0], threading.get_ident())
+
+    def test_unsubscribe(self):
+        """Una registrazione annullata non riceve più eventi"""
+        bus = EventBus()
+        received = []
+        subscription = bus.subscribe(ProtocolEventType.LatencyAlert, lambda e: received.append(e.latency_ms))
+        bus.publish(event(ProtocolEventType.LatencyAlert, latency_ms=55))
+        bus.flush()
+        self.assertTrue(bus.unsubscribe(subscription))
+        self.assertFalse(bus.unsubscribe(subscription))
+        bus.publish(event(ProtocolEventType.LatencyAlert, latency_ms=60))
+        bus.flush()
+        self.assertEqual(received, [55])
+
+    def test_failing_callback(self):
+        """Un'eccezione in una funzione non ferma la consegna alle altre"""
+        bus = EventBus()
+        received = []
+        def failing(_):
+            raise RuntimeError("errore del gestore")
+        bus.subscribe(ProtocolEventType.NodeJoined, failing)
+        bus.subscribe(ProtocolEventType.NodeJoined, lambda e: received.append(e.node_id))
+        bus.publish(event(ProtocolEventType.NodeJoined))
+        bus.flush()
+        self.assertEqual(received, ["sink-1"])
+
+    def test_bounded_queue(self):
+        """Con la coda piena gli eventi più vecchi vengono scartati"""
+        bus = EventBus(max_pending=2)
+        release = threading.Event()
+        received = []
+        def slow(e):
+            release.wait(5)
+            received.append(e.node_id)
+        bus.subscribe(ProtocolEventType.NodeJoined, slow)
+        for index in range(5):
+            bus.publish(event(ProtocolEventType.NodeJoined, "sink-%d" % index))
+        release.set()
+        bus.flush()
+        self.assertGreater(bus.get_dropped_events(), 0)
+        self.assertEqual(received[-1], "sink-4")
+
+    def test_names(self):
+        """I tipi di evento hanno nomi stabili"""
+        self.assertEqual(protocol_event_type_to_string(ProtocolEventType.LatencyAlert), "latency_alert")
+
+class TestProtocolCallbacks(unittest.TestCase):
+    """Test per la registrazione delle funzioni sul protocollo"""
+
+    def test_register_and_remove(self):
+        """Le funzioni registrate possono essere rimosse con il loro identificatore"""
+        protocol = SaberProtocol(SaberConfig.default_config())
+        ids = [
+            protocol.on_node_joined(lambda node: None),
+            protocol.on_node_left(lambda node: None),
+            protocol.on_sync_lost(lambda: None),
+            protocol.on_latency_alert(lambda node, latency, budget: None),
+        ]
+        self.assertEqual(len(set(ids)), 4)
+        protocol.flush_events()
+        self.assertTrue(protocol.remove_event_callback(ids[0]))
+        self.assertFalse(protocol.remove_event_callback(ids[0]))
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,76 @@
+# Test unitari per il diario degli eventi del protocollo SABER
+# Verifica cursori, paginazione e persistenza tra un riavvio e l'altro
+
+import os
+import sys
+import tempfile
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import EventJournal
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestEventJournal(unittest.TestCase):
+    """Test per il recupero degli eventi a partire da un cursore"""
+
+    def setUp(self):
+        self.path = os.path.join(tempfile.mkdtemp(), "events.log")
+
+    def test_cursors_increase(self):
+        """Ogni evento riceve un cursore successivo al precedente"""
+        journal = EventJournal(self.path)
+        first = journal.append("mesh", "node_joined", "sink-1", "", 1000)
+        second = journal.append("security", "key_conflict", "sink-2", "", 1001)
+        self.assertEqual(second, first + 1)
+        self.assertEqual(journal.latest_cursor(), second)
+
+    def test_since_cursor(self):
+        """Un client riceve solo gli eventi successivi al suo cursore"""
+        journal = EventJournal(self.path)
+        seen = journal.append("mesh", "node_joined", "sink-1", "", 1000)
+        journal.append("mesh", "node_lost", "sink-1", "", 2000)
+        page = journal.since(seen)
+        self.assertEqual([event.type for event in page.events], ["node_lost"])
+        self.assertEqual(page.next_cursor, seen + 1)
+        self.assertFalse(page.truncated)
+
+    def test_pagination(self):
+        """Il limite divide gli eventi in pagine senza perderne"""
+        journal = EventJournal(self.path)
+        for i in range(5):
+            journal.append("mesh", "zone_changed", "sink-%d" % i, "a patio", i)
+        page = journal.since(0, 2)
+        self.assertTrue(page.has_more)
+        page = journal.since(page.next_cursor, 10)
+        self.assertEqual(len(page.events), 3)
+        self.assertFalse(page.has_more)
+
+    def test_survives_restart(self):
+        """I cursori proseguono dopo la riapertura del file"""
+        journal = EventJournal(self.path)
+        journal.append("mesh", "node_joined", "sink-1", "riga\tcon\ntabulazioni", 1000)
+        last = journal.append("mesh", "node_lost", "sink-1", "", 2000)
+        del journal
+
+        reopened = EventJournal(self.path)
+        self.assertEqual(reopened.latest_cursor(), last)
+        self.assertEqual(reopened.since(0).events[0].detail, "riga\tcon\ntabulazioni")
+        self.assertEqual(reopened.append("mesh", "node_returned", "sink-1", "", 3000), last + 1)
+
+    def test_truncated_when_evicted(self):
+        """Un cursore troppo vecchio viene segnalato come troncato"""
+        journal = EventJournal(self.path, 2)
+        for i in range(5):
+            journal.append("mesh", "node_lost", "sink-%d" % i, "", i)
+        page = journal.since(1)
+        self.assertTrue(page.truncated)
+        self.assertEqual([event.node_id for event in page.events], ["sink-3", "sink-4"])
+
+if __name__ == "__main__":
+    unittest.main()
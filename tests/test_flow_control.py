@@ -0,0 +1,121 @@
+# Test unitari per il controllo di flusso del Master
+# Verifica gli stati guidati dal buffer dei sink, lo scarto per priorità e la configurazione
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (FlowConfig, FlowController, FlowState, FramePriority, SaberConfig, SaberProtocol,
+                                flow_state_to_string, frame_priority_from_string, frame_priority_to_string)
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def make_controller(recover_after_ms=1000):
+    config = FlowConfig()
+    config.recover_after_ms = recover_after_ms
+    return FlowController(config)
+
+class TestFlowController(unittest.TestCase):
+    """Test per gli stati del controllo di flusso"""
+
+    def test_low_headroom(self):
+        """Un sink con poco margine porta subito allo stato di margine scarso"""
+        controller = make_controller()
+        controller.report_buffer("sink-1", 80, 0)
+        self.assertIsNone(controller.update(0))
+        controller.report_buffer("sink-1", 20, 100)
+        self.assertEqual(controller.update(100), FlowState.LowHeadroom)
+        self.assertEqual(controller.get_status(100).buffer_levels, {"sink-1": 20})
+
+    def test_recovery(self):
+        """Il ritorno alla norma richiede un periodo con tutti i buffer nella norma"""
+        controller = make_controller()
+        controller.report_buffer("sink-1", 20, 0)
+        controller.update(0)
+        controller.report_buffer("sink-1", 90, 100)
+        self.assertIsNone(controller.update(100))
+        self.assertIsNone(controller.update(1000))
+        self.assertEqual(controller.update(1100), FlowState.Normal)
+        self.assertEqual(controller.get_status(1100).transitions, 2)
+
+    def test_overflow_drops_low_priority(self):
+        """Un buffer che trabocca scarta solo i frame a bassa priorità"""
+        controller = make_controller()
+        self.assertTrue(controller.admit(FramePriority.Low))
+        controller.report_buffer("sink-1", 200, 0)
+        self.assertEqual(controller.update(0), FlowState.Overflow)
+        self.assertFalse(controller.admit(FramePriority.Low))
+        self.assertTrue(controller.admit(FramePriority.Normal))
+        self.assertTrue(controller.admit(FramePriority.High))
+        self.assertEqual(controller.get_status(0).dropped_frames, {FramePriority.Low: 1})
+
+    def test_low_headroom_prevails(self):
+        """Il margine scarso prevale su un buffer che trabocca"""
+        controller = make_controller()
+        controller.report_buffer("sink-1", 200, 0)
+        controller.report_buffer("sink-2", 10, 0)
+        self.assertEqual(controller.update(0), FlowState.LowHeadroom)
+
+    def test_ignored_reports(self):
+        """I nodi senza audio e i resoconti scaduti non contano"""
+        controller = make_controller()
+        controller.report_buffer("sink-1", 0, 0)
+        self.assertIsNone(controller.update(0))
+        controller.report_buffer("sink-1", 20, 0)
+        controller.update(0)
+        timeout = FlowConfig().report_timeout_ms
+        self.assertEqual(controller.get_status(timeout).buffer_levels, {})
+        self.assertIsNone(controller.update(timeout))
+        self.assertEqual(controller.update(timeout + 1000), FlowState.Normal)
+
+    def test_disabled(self):
+        """Con il controllo disattivato lo stato resta normale"""
+        config = FlowConfig()
+        config.enabled = False
+        controller = FlowController(config)
+        controller.report_buffer("sink-1", 10, 0)
+        self.assertIsNone(controller.update(0))
+        self.assertEqual(controller.get_state(), FlowState.Normal)
+
+    def test_names(self):
+        """I nomi di stati e priorità"""
+        self.assertEqual(flow_state_to_string(FlowState.LowHeadroom), "low_headroom")
+        for priority in (FramePriority.Low, FramePriority.Normal, FramePriority.High):
+            self.assertEqual(frame_priority_from_string(frame_priority_to_string(priority)), priority)
+        self.assertIsNone(frame_priority_from_string("urgent"))
+
+class TestConfigFile(ConfigFileTestCase):
+    """Test per le soglie lette dal file di configurazione"""
+
+    HEADER = '[node]\nid = "master-1"\nrole = "master"\n\n[flow]\n'
+
+    def test_flow(self):
+        """La sezione [flow] imposta soglie e tempi"""
+        config = self.load('enabled = false\nlow_watermark_percent = 40\nhigh_watermark_percent = 180\n'
+                           'recover_after_ms = 5000\n')
+        self.assertFalse(config.flow.enabled)
+        self.assertEqual(config.flow.low_watermark_percent, 40)
+        self.assertEqual(config.flow.high_watermark_percent, 180)
+        self.assertEqual(config.flow.recover_after_ms, 5000)
+        with self.assertRaises(RuntimeError):
+            self.load('low_watermark_percent = 100\n')
+        with self.assertRaises(RuntimeError):
+            self.load('high_watermark_percent = 90\n')
+
+    def test_protocol(self):
+        """Le priorità degli stream si impostano anche prima di initialize()"""
+        protocol = SaberProtocol(SaberConfig.default_config())
+        self.assertEqual(protocol.get_stream_priority(1), FramePriority.Normal)
+        protocol.set_stream_priority(1, FramePriority.Low)
+        self.assertEqual(protocol.get_stream_priority(1), FramePriority.Low)
+        self.assertEqual(protocol.get_flow_status().state, FlowState.Normal)
+
+if __name__ == "__main__":
+    unittest.main()
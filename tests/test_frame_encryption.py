@@ -0,0 +1,99 @@
+# Test unitari per la granularità della cifratura dei frame audio
+# Verifica la negoziazione tra nodi, l'apertura indipendente dalla granularità e l'overhead dichiarato
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (CAP_ENCRYPT_PER_FRAME, CAP_ENCRYPT_TRANSPORT, AudioFrameSealer,
+                                EncryptionGranularity, MeshCrypto, negotiate_granularity)
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+NETWORK_KEY = [3] * 32
+
+# Tre frame LC3 di dimensioni diverse
+FRAMES = [[1] * 40, [2] * 60, [3] * 50]
+
+BOTH = CAP_ENCRYPT_PER_FRAME | CAP_ENCRYPT_TRANSPORT
+
+class TestNegotiation(unittest.TestCase):
+    """Test per la scelta della granularità comune"""
+
+    def test_preferred_when_shared(self):
+        """La granularità preferita vale se entrambi i nodi la supportano"""
+        self.assertEqual(negotiate_granularity(BOTH, BOTH, EncryptionGranularity.PER_TRANSPORT_FRAME),
+                         EncryptionGranularity.PER_TRANSPORT_FRAME)
+
+    def test_fallback(self):
+        """Un nodo che sa aprire solo frame singoli fa ripiegare sull'altra granularità"""
+        self.assertEqual(negotiate_granularity(BOTH, CAP_ENCRYPT_PER_FRAME,
+                                               EncryptionGranularity.PER_TRANSPORT_FRAME),
+                         EncryptionGranularity.PER_FRAME)
+
+    def test_nothing_in_common(self):
+        """Senza capacità comuni non si concorda nulla"""
+        self.assertIsNone(negotiate_granularity(CAP_ENCRYPT_PER_FRAME, CAP_ENCRYPT_TRANSPORT,
+                                                EncryptionGranularity.PER_FRAME))
+
+class TestAudioFrameSealer(unittest.TestCase):
+    """Test per la cifratura dei frame di trasporto"""
+
+    def sealer(self, granularity):
+        return AudioFrameSealer(MeshCrypto.with_network_key(NETWORK_KEY), granularity)
+
+    def test_round_trip_and_overhead(self):
+        """Il ricevitore apre entrambe le granularità e l'overhead è quello dichiarato"""
+        receiver = self.sealer(EncryptionGranularity.PER_FRAME)
+        for granularity in (EncryptionGranularity.PER_FRAME, EncryptionGranularity.PER_TRANSPORT_FRAME):
+            sealed = self.sealer(granularity).seal(FRAMES)
+            self.assertEqual(receiver.open(sealed), FRAMES)
+            self.assertEqual(len(sealed) - sum(map(len, FRAMES)),
+                             AudioFrameSealer.overhead_bytes(granularity, len(FRAMES)))
+        self.assertLess(AudioFrameSealer.overhead_bytes(EncryptionGranularity.PER_TRANSPORT_FRAME, 3),
+                        AudioFrameSealer.overhead_bytes(EncryptionGranularity.PER_FRAME, 3))
+
+    def test_tampered_and_truncated(self):
+        """Un frame alterato non si decifra, uno troncato viene rifiutato"""
+        for granularity in (EncryptionGranularity.PER_FRAME, EncryptionGranularity.PER_TRANSPORT_FRAME):
+            sealer = self.sealer(granularity)
+            sealed = sealer.seal(FRAMES)
+            tampered = list(sealed)
+            tampered[-1] ^= 1
+            with self.assertRaises(RuntimeError):
+                sealer.open(tampered)
+            with self.assertRaises(IndexError):
+                sealer.open(sealed[:10])
+
+    def test_replay(self):
+        """Con il mittente indicato lo stesso frame di trasporto si apre una sola volta"""
+        sealed = self.sealer(EncryptionGranularity.PER_TRANSPORT_FRAME).seal(FRAMES)
+        receiver = self.sealer(EncryptionGranularity.PER_FRAME)
+        self.assertEqual(receiver.open(sealed, "master-1"), FRAMES)
+        with self.assertRaises(RuntimeError):
+            receiver.open(sealed, "master-1")
+
+class TestFrameEncryptionConfig(ConfigFileTestCase):
+    """Test per la granularità letta dal file"""
+
+    HEADER = '[node]\nid = "master-1"\nrole = "master"\n\n[security]\n'
+
+    def load(self, value):
+        return super().load('frame_encryption = "%s"\n' % value)
+
+    def test_values(self):
+        """Sono accettati solo i nomi per_frame e transport"""
+        self.assertEqual(self.load("per_frame").frame_encryption, EncryptionGranularity.PER_FRAME)
+        self.assertEqual(self.load("transport").frame_encryption, EncryptionGranularity.PER_TRANSPORT_FRAME)
+        with self.assertRaises(RuntimeError):
+            self.load("per_packet")
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,54 @@
+# Test unitari per i suggerimenti di separazione dei sink lenti
+# Verifica quando conviene spostare un sink in un gruppo ritardato e i buffer stimati
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import LatencyPlanner
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestGroupSplit(unittest.TestCase):
+    """Test per i suggerimenti del pianificatore"""
+
+    def setUp(self):
+        self.planner = LatencyPlanner(40, 10)
+
+    def test_single_slow_sink(self):
+        """Il sink che da solo porta la zona oltre il budget viene spostato nel gruppo ritardato"""
+        zones = {"sink-1": "sala", "sink-2": "sala", "sink-3": "sala", "sink-4": "cucina"}
+        latencies = {"sink-1": 10, "sink-2": 20, "sink-3": 60, "sink-4": 5}
+        suggestions = self.planner.suggest_splits(zones, latencies)
+        self.assertEqual(len(suggestions), 1)
+        suggestion = suggestions[0]
+        self.assertEqual(suggestion.zone, "sala")
+        self.assertEqual(suggestion.node_id, "sink-3")
+        self.assertEqual(suggestion.suggested_zone, "sala-delayed")
+        self.assertEqual(suggestion.zone_buffer_ms, 70)
+        self.assertEqual(suggestion.buffer_without_node_ms, 30)
+        self.assertEqual(suggestion.node_buffer_ms, 70)
+
+    def test_zone_within_budget(self):
+        """Una zona entro il budget non riceve suggerimenti"""
+        zones = {"sink-1": "sala", "sink-2": "sala"}
+        self.assertEqual(self.planner.suggest_splits(zones, {"sink-1": 10, "sink-2": 30}), [])
+
+    def test_several_slow_sinks(self):
+        """Se la zona resta oltre il budget anche senza il sink più lento, la separazione non serve"""
+        zones = {"sink-1": "sala", "sink-2": "sala", "sink-3": "sala"}
+        latencies = {"sink-1": 10, "sink-2": 45, "sink-3": 60}
+        self.assertEqual(self.planner.suggest_splits(zones, latencies), [])
+
+    def test_single_member_zone(self):
+        """Un sink da solo nella zona non ha nessuno da cui separarsi"""
+        self.assertEqual(self.planner.suggest_splits({"sink-1": "sala"}, {"sink-1": 80}), [])
+
+if __name__ == "__main__":
+    unittest.main()
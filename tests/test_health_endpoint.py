@@ -0,0 +1,83 @@
+# Test unitari per gli endpoint di health-check del protocollo SABER
+# Verifica che un client inattivo non blocchi i controlli né l'arresto del nodo
+
+import os
+import socket
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import NodeRole, ProtocolState, SaberConfig, SaberProtocol, SimNetwork
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def free_port():
+    with socket.socket(socket.AF_INET, socket.SOCK_STREAM) as probe:
+        probe.bind(("127.0.0.1", 0))
+        return probe.getsockname()[1]
+
+def get(port, path):
+    with socket.create_connection(("127.0.0.1", port), timeout=5) as client:
+        client.sendall(("GET " + path + " HTTP/1.1\r\nHost: localhost\r\n\r\n").encode())
+        response = b""
+        while True:
+            chunk = client.recv(1024)
+            if not chunk:
+                return response.decode()
+            response += chunk
+
+class TestHealthEndpoint(unittest.TestCase):
+    """Test per il server HTTP degli endpoint /healthz e /readyz"""
+
+    def setUp(self):
+        self.port = free_port()
+        config = SaberConfig.default_config()
+        config.node_id = "health-master"
+        config.role = NodeRole.Master
+        config.health_port = self.port
+        config.health_bind_address = "127.0.0.1"
+        self.protocol = SaberProtocol(config)
+        self.protocol.set_sim_network(SimNetwork(1))
+        if not self.protocol.initialize():
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        try:
+            socket.create_connection(("127.0.0.1", self.port), timeout=1).close()
+        except OSError:
+            self.protocol.shutdown()
+            self.skipTest("endpoint HTTP non compilati (SABER_ENABLE_HTTP)")
+
+    def tearDown(self):
+        if self.protocol.get_state() == ProtocolState.Running:
+            self.protocol.shutdown()
+
+    def test_healthz(self):
+        """Un nodo avviato risponde a /healthz"""
+        self.assertTrue(get(self.port, "/healthz").startswith("HTTP/1.1 200 OK"))
+        self.assertIn("404 Not Found", get(self.port, "/missing"))
+
+    def test_stalled_client(self):
+        """Un client che non invia nulla viene chiuso e non blocca i controlli successivi"""
+        stalled = socket.create_connection(("127.0.0.1", self.port), timeout=5)
+        self.addCleanup(stalled.close)
+        started = time.monotonic()
+        self.assertTrue(get(self.port, "/healthz").startswith("HTTP/1.1 200 OK"))
+        self.assertLess(time.monotonic() - started, 3.0)
+        self.assertEqual(stalled.recv(1), b"")
+
+    def test_shutdown_with_stalled_client(self):
+        """L'arresto non attende la scadenza di un client inattivo"""
+        stalled = socket.create_connection(("127.0.0.1", self.port), timeout=5)
+        self.addCleanup(stalled.close)
+        time.sleep(0.1)
+        started = time.monotonic()
+        self.protocol.shutdown()
+        self.assertLess(time.monotonic() - started, 1.0)
+
+if __name__ == "__main__":
+    unittest.main()
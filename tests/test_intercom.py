@@ -0,0 +1,99 @@
+# Test unitari per la modalità intercom del protocollo SABER
+# Verifica gli stream dei due nodi, il ducking della musica e i controlli di avvio
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (INTERCOM_STREAM_BASE, AudioFrame, IntercomConfig, IntercomSession,
+                                SaberConfig, SaberProtocol, duck_frame, is_intercom_stream)
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestIntercomSession(unittest.TestCase):
+    """Test per gli stream della conversazione"""
+
+    def setUp(self):
+        self.session = IntercomSession()
+        self.session.node_a = "kitchen"
+        self.session.node_b = "garage"
+
+    def test_streams_are_crossed(self):
+        """Ogni nodo ascolta lo stream su cui parla l'altro"""
+        self.assertEqual(self.session.talk_stream("kitchen"), INTERCOM_STREAM_BASE)
+        self.assertEqual(self.session.talk_stream("garage"), INTERCOM_STREAM_BASE + 1)
+        self.assertEqual(self.session.listen_stream("kitchen"), self.session.talk_stream("garage"))
+        self.assertEqual(self.session.listen_stream("garage"), self.session.talk_stream("kitchen"))
+        self.assertEqual(self.session.speaker_of(INTERCOM_STREAM_BASE + 1), "garage")
+
+    def test_other_nodes(self):
+        """I nodi esterni alla conversazione non hanno stream"""
+        self.assertFalse(self.session.involves("living"))
+        self.assertIsNone(self.session.talk_stream("living"))
+        self.assertIsNone(self.session.listen_stream("living"))
+        self.assertIsNone(self.session.speaker_of(1))
+
+    def test_reserved_streams(self):
+        """Solo i due stream dell'intercom sono riservati"""
+        self.assertTrue(is_intercom_stream(INTERCOM_STREAM_BASE))
+        self.assertTrue(is_intercom_stream(INTERCOM_STREAM_BASE + 1))
+        self.assertFalse(is_intercom_stream(INTERCOM_STREAM_BASE + 2))
+        self.assertFalse(is_intercom_stream(1))
+
+class TestDucking(unittest.TestCase):
+    """Test per l'attenuazione della musica"""
+
+    def frame(self, samples):
+        frame = AudioFrame()
+        frame.sample_rate = 48000
+        frame.channels = 1
+        frame.samples = samples
+        return frame
+
+    def test_attenuation(self):
+        """20 dB riducono l'ampiezza di dieci volte"""
+        frame = self.frame([10000, -10000, 0])
+        duck_frame(frame, 20)
+        self.assertEqual(frame.samples, [1000, -1000, 0])
+
+    def test_zero_leaves_frame(self):
+        """Un'attenuazione nulla lascia il frame invariato"""
+        frame = self.frame([32767, -32768])
+        duck_frame(frame, 0)
+        self.assertEqual(frame.samples, [32767, -32768])
+
+class TestIntercomControl(unittest.TestCase):
+    """Test per l'avvio dell'intercom senza avviare la rete"""
+
+    def setUp(self):
+        self.protocol = SaberProtocol(SaberConfig.default_config())
+
+    def test_defaults(self):
+        """La voce usa un bitrate ridotto e beacon più fitti"""
+        config = IntercomConfig()
+        self.assertEqual(config.bitrate_kbps, 32)
+        self.assertLess(config.beacon_interval_ms, SaberConfig.default_config().beacon_interval_ms)
+
+    def test_invalid_nodes(self):
+        """Nodi mancanti o coincidenti vengono rifiutati"""
+        with self.assertRaises(ValueError):
+            self.protocol.start_intercom("kitchen", "kitchen")
+        with self.assertRaises(ValueError):
+            self.protocol.start_intercom("", "garage")
+
+    def test_inactive(self):
+        """Senza conversazione i beacon restano quelli della configurazione"""
+        self.assertIsNone(self.protocol.get_intercom_session())
+        self.assertFalse(self.protocol.stop_intercom())
+        self.assertFalse(self.protocol.set_intercom_talking(True))
+        self.assertEqual(self.protocol.get_beacon_interval_ms(),
+                         SaberConfig.default_config().beacon_interval_ms)
+
+if __name__ == "__main__":
+    unittest.main()
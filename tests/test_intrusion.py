@@ -0,0 +1,96 @@
+# Test unitari per il rilevamento delle intrusioni del protocollo SABER
+# Verifica soglie, finestre, allarmi e quarantena automatica
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (IntrusionAlert, IntrusionConfig, IntrusionDetector, MeshCrypto,
+                                SecurityEvent)
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+Type = IntrusionAlert.Type
+
+class TestIntrusionDetector(unittest.TestCase):
+    """Test per le euristiche del rilevatore di intrusioni"""
+
+    def detector(self, **values):
+        config = IntrusionConfig()
+        config.window_ms = 1000
+        for name, value in values.items():
+            setattr(config, name, value)
+        return IntrusionDetector(config)
+
+    def test_alert_at_threshold(self):
+        """L'allarme scatta quando il nodo raggiunge la soglia"""
+        detector = self.detector(beacon_threshold=3)
+        self.assertIsNone(detector.observe(Type.RogueBeacon, "rogue-1", 0))
+        self.assertIsNone(detector.observe(Type.RogueBeacon, "rogue-1", 100))
+        alert = detector.observe(Type.RogueBeacon, "rogue-1", 200)
+        self.assertEqual(alert.type, Type.RogueBeacon)
+        self.assertEqual(alert.node_id, "rogue-1")
+        self.assertEqual(alert.count, 3)
+        self.assertFalse(alert.quarantine)
+
+    def test_window_expires(self):
+        """Le anomalie fuori dalla finestra non contano"""
+        detector = self.detector(signature_failure_threshold=2)
+        detector.observe(Type.SignatureBurst, "node-1", 0)
+        self.assertIsNone(detector.observe(Type.SignatureBurst, "node-1", 1500))
+        self.assertIsNotNone(detector.observe(Type.SignatureBurst, "node-1", 1600))
+
+    def test_nodes_counted_separately(self):
+        """Le anomalie di nodi diversi non si sommano"""
+        detector = self.detector(replay_threshold=2)
+        detector.observe(Type.NonceReuse, "node-1", 0)
+        self.assertIsNone(detector.observe(Type.NonceReuse, "node-2", 10))
+
+    def test_one_alert_per_burst(self):
+        """Dopo un allarme il conteggio riparte da zero"""
+        detector = self.detector(route_flap_threshold=2)
+        results = [detector.observe(Type.RouteFlapping, "peer-1", t) for t in range(0, 50, 10)]
+        self.assertEqual(sum(result is not None for result in results), 2)
+        self.assertEqual(len(detector.get_alerts()), 2)
+
+    def test_disabled(self):
+        """Soglia a zero o rilevatore disattivato non producono allarmi"""
+        detector = self.detector(beacon_threshold=0)
+        self.assertIsNone(detector.observe(Type.RogueBeacon, "rogue-1", 0))
+        detector = self.detector(enabled=False, beacon_threshold=1)
+        self.assertIsNone(detector.observe(Type.RogueBeacon, "rogue-1", 0))
+
+    def test_handler_and_quarantine_flag(self):
+        """Il gestore riceve l'allarme con la richiesta di quarantena"""
+        detector = self.detector(beacon_threshold=1, auto_quarantine=True)
+        alerts = []
+        detector.set_alert_handler(alerts.append)
+        detector.observe(Type.RogueBeacon, "rogue-1", 0)
+        self.assertEqual(len(alerts), 1)
+        self.assertTrue(alerts[0].quarantine)
+
+class TestImposedQuarantine(unittest.TestCase):
+    """Test per la quarantena per comportamento anomalo"""
+
+    def test_quarantine_and_release(self):
+        """Un nodo in quarantena resta tale finché l'operatore non la rimuove"""
+        crypto = MeshCrypto()
+        events = []
+        crypto.set_security_event_handler(events.append)
+        crypto.quarantine_node("rogue-1", "rogue_beacon")
+        self.assertTrue(crypto.is_quarantined("rogue-1"))
+        self.assertEqual(crypto.get_quarantined_nodes(), ["rogue-1"])
+        self.assertTrue(crypto.release_quarantine("rogue-1"))
+        self.assertFalse(crypto.is_quarantined("rogue-1"))
+        self.assertFalse(crypto.release_quarantine("rogue-1"))
+        self.assertEqual([event.type for event in events],
+                         [SecurityEvent.Type.NodeQuarantined, SecurityEvent.Type.QuarantineReleased])
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,156 @@
+# Test unitari per il buffer di jitter adattivo dei sink
+# Verifica la crescita e la riduzione entro gli estremi, la stima di jitter e perdita e i contatori
+
+import os
+import random
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (AdaptiveJitterBuffer, AudioSync, JitterBufferConfig, LatencyMode,
+                                SpecParameters, SyncManager)
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+START_US = 1_700_000_000_000_000
+FRAME_US = 10_000
+
+def stream(buffer, count, lead_us, transit, skip=()):
+    """Frame ogni 10ms riprodotti lead_us dopo l'invio; transit(i) dà il ritardo di rete (µs)"""
+    resizes = []
+    for index in range(count):
+        if index in skip:
+            continue
+        sent = START_US + index * FRAME_US
+        resized = buffer.record_frame(1, index, sent + lead_us, sent + transit(index))
+        if resized is not None:
+            resizes.append((index * FRAME_US // 1000, resized))
+    return resizes
+
+class TestAdaptiveJitterBuffer(unittest.TestCase):
+    """Test per l'adattamento del buffer di jitter"""
+
+    def test_shrinks_on_calm_network(self):
+        """Senza jitter né perdite il buffer si riduce di un passo ogni shrink_after_ms"""
+        buffer = AdaptiveJitterBuffer(JitterBufferConfig(), 40)
+        resizes = stream(buffer, 4000, 14_000, lambda i: 2_000 + (i % 2) * 200)
+        # L'anticipo dei frame (12ms) impedisce di scendere a 10ms
+        self.assertEqual(resizes, [(10_000, 30), (20_000, 20)])
+        stats = buffer.get_stats()
+        self.assertEqual(stats.underruns, 0)
+        self.assertEqual(stats.overruns, 0)
+        self.assertLess(stats.jitter_ms, 0.5)
+
+    def test_grows_with_jitter(self):
+        """Un jitter ampio fa crescere il buffer fino a coprirlo"""
+        rng = random.Random(1)
+        buffer = AdaptiveJitterBuffer()
+        stream(buffer, 1000, 40_000, lambda i: rng.randint(0, 15_000))
+        stats = buffer.get_stats()
+        self.assertGreater(stats.jitter_ms, 3.0)
+        self.assertEqual(stats.buffer_ms, 40)
+
+    def test_grows_on_late_frame(self):
+        """Un frame arrivato dopo il suo istante di riproduzione è un buco e fa crescere il buffer"""
+        buffer = AdaptiveJitterBuffer()
+        resizes = stream(buffer, 10, 5_000, lambda i: 9_000 if i == 4 else 2_000)
+        self.assertEqual(resizes, [(40, 30)])
+        self.assertEqual(buffer.get_stats().underruns, 1)
+
+    def test_grows_on_loss(self):
+        """Una perdita oltre max_loss_percent fa crescere il buffer per le ritrasmissioni"""
+        buffer = AdaptiveJitterBuffer()
+        stream(buffer, 1000, 18_000, lambda i: 2_000, skip={i for i in range(1000) if i % 20 == 5})
+        stats = buffer.get_stats()
+        self.assertEqual(stats.lost, 50)
+        self.assertAlmostEqual(stats.loss_percent, 5.0)
+        self.assertEqual(stats.buffer_ms, 40)
+
+    def test_counts_overrun(self):
+        """Un frame in anticipo oltre la dimensione del buffer è contato e il buffer cresce"""
+        buffer = AdaptiveJitterBuffer(JitterBufferConfig(), 20)
+        self.assertEqual(buffer.record_frame(1, 0, START_US + 35_000, START_US), 30)
+        self.assertEqual(buffer.get_stats().overruns, 1)
+
+    def test_bounds_and_fixed(self):
+        """La dimensione resta entro gli estremi e non cambia se l'adattamento è spento"""
+        config = JitterBufferConfig()
+        config.max_ms = 30
+        buffer = AdaptiveJitterBuffer(config, 100)
+        self.assertEqual(buffer.get_buffer_ms(), 30)
+        stream(buffer, 20, 5_000, lambda i: 9_000)
+        self.assertEqual(buffer.get_buffer_ms(), 30)
+        config.adaptive = False
+        fixed = AdaptiveJitterBuffer(config, 20)
+        self.assertEqual(stream(fixed, 20, 5_000, lambda i: 9_000), [])
+        self.assertEqual(fixed.get_stats().underruns, 20)
+
+    def test_pinned_reset(self):
+        """Una dimensione imposta non si adatta, ma i frame vengono contati"""
+        buffer = AdaptiveJitterBuffer()
+        buffer.reset(60, True)
+        self.assertEqual(stream(buffer, 10, 5_000, lambda i: 9_000), [])
+        stats = buffer.get_stats()
+        self.assertEqual(stats.buffer_ms, 60)
+        self.assertEqual(stats.frames, 10)
+
+class TestAudioSyncJitter(unittest.TestCase):
+    """Test per il buffer di jitter di AudioSync"""
+
+    def test_records_only_while_playing(self):
+        """I frame contano solo durante la riproduzione, che riparte dal buffer ottimale"""
+        manager = SyncManager()
+        audio = AudioSync(manager, True)
+        self.assertIsNone(audio.record_frame(1, 0, START_US + 5_000, START_US + 9_000))
+        self.assertEqual(audio.get_jitter_buffer_stats().frames, 0)
+        manager.handle_time_beacon(int(time.time() * 1000))
+        self.assertTrue(audio.start_playback())
+        self.assertEqual(audio.get_jitter_buffer(), 20)
+        self.assertEqual(audio.record_frame(1, 0, START_US + 5_000, START_US + 9_000), 30)
+        self.assertEqual(audio.get_jitter_buffer_stats().underruns, 1)
+
+    def test_strict_mode_caps_maximum(self):
+        """In modalità rigorosa il buffer massimo è limitato al budget di latenza"""
+        params = SpecParameters()
+        params.latency_mode = LatencyMode.Strict
+        manager = SyncManager(params)
+        audio = AudioSync(manager, True)
+        config = JitterBufferConfig()
+        config.max_ms = 200
+        audio.set_jitter_buffer_config(config)
+        manager.handle_time_beacon(int(time.time() * 1000))
+        self.assertTrue(audio.start_playback())
+        for index in range(50):
+            audio.record_frame(1, index, START_US, START_US + 100_000)
+        self.assertLessEqual(audio.get_jitter_buffer(), params.latency_budget_ms)
+
+class TestConfigFile(ConfigFileTestCase):
+    """Test per il buffer di jitter letto dal file di configurazione"""
+
+    HEADER = '[node]\nid = "sink-1"\n\n[audio]\n'
+
+    def test_settings(self):
+        """Le chiavi audio.jitter_* impostano l'adattamento"""
+        config = self.load('jitter_adaptive = false\njitter_min_ms = 20\njitter_max_ms = 60\n'
+                           'jitter_shrink_after_ms = 5000\n')
+        self.assertFalse(config.jitter_buffer.adaptive)
+        self.assertEqual(config.jitter_buffer.min_ms, 20)
+        self.assertEqual(config.jitter_buffer.max_ms, 60)
+        self.assertEqual(config.jitter_buffer.shrink_after_ms, 5000)
+
+    def test_invalid_bounds(self):
+        """Un minimo oltre il massimo e un passo nullo vengono rifiutati"""
+        with self.assertRaises(RuntimeError):
+            self.load('jitter_min_ms = 80\njitter_max_ms = 40\n')
+        with self.assertRaises(RuntimeError):
+            self.load('jitter_step_ms = 0\n')
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,77 @@
+# Test unitari per il fissaggio delle chiavi pubbliche dei nodi
+# Verifica la quarantena di un ID rivendicato con chiavi diverse e la risoluzione dell'operatore
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import MeshCrypto, MeshPacket, SecurityEvent
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def signed_command(signer, sequence):
+    packet = MeshPacket.create_command("volume", {"level": "50"})
+    packet.set_header("sink-1", sequence, 8)
+    packet.sign(signer)
+    return packet
+
+class TestKeyPinning(unittest.TestCase):
+    """Test per la prima chiave fissata e i conflitti successivi"""
+
+    def setUp(self):
+        self.receiver = MeshCrypto()
+        self.events = []
+        self.receiver.set_security_event_handler(self.events.append)
+        self.original = MeshCrypto()
+        self.impostor = MeshCrypto()
+        self.receiver.register_node_key("sink-1", self.original.get_public_key())
+
+    def test_same_key_accepted(self):
+        """La stessa chiave registrata di nuovo non crea conflitti"""
+        self.receiver.register_node_key("sink-1", self.original.get_public_key())
+        self.assertFalse(self.receiver.is_quarantined("sink-1"))
+        self.assertEqual(self.events, [])
+        self.assertTrue(signed_command(self.original, 1).verify_signature(self.receiver))
+
+    def test_conflict_quarantines(self):
+        """Un ID rivendicato con un'altra chiave va in quarantena e nessuna delle due vale"""
+        self.receiver.register_node_key("sink-1", self.impostor.get_public_key())
+        self.assertTrue(self.receiver.is_quarantined("sink-1"))
+        self.assertEqual(self.receiver.get_quarantined_nodes(), ["sink-1"])
+        self.assertEqual(self.receiver.get_conflicting_keys("sink-1"),
+                         [self.original.get_public_key(), self.impostor.get_public_key()])
+        self.assertEqual(self.events[-1].type, SecurityEvent.Type.KeyConflict)
+        self.assertEqual(self.events[-1].key_ids, [MeshCrypto.key_id(self.original.get_public_key()),
+                                                   MeshCrypto.key_id(self.impostor.get_public_key())])
+        for signer in (self.original, self.impostor):
+            with self.assertRaises(RuntimeError):
+                signed_command(signer, 1).verify_signature(self.receiver)
+
+        # Ripresentare la chiave originale non basta a uscire dalla quarantena
+        self.receiver.register_node_key("sink-1", self.original.get_public_key())
+        self.assertTrue(self.receiver.is_quarantined("sink-1"))
+
+    def test_operator_resolution(self):
+        """L'operatore sceglie la chiave attendibile e l'altra resta rifiutata"""
+        self.receiver.register_node_key("sink-1", self.impostor.get_public_key())
+        self.receiver.resolve_key_conflict("sink-1", self.original.get_public_key())
+        self.assertFalse(self.receiver.is_quarantined("sink-1"))
+        self.assertEqual(self.events[-1].type, SecurityEvent.Type.KeyConflictResolved)
+        self.assertTrue(signed_command(self.original, 2).verify_signature(self.receiver))
+        self.assertFalse(signed_command(self.impostor, 3).verify_signature(self.receiver))
+
+    def test_invalid_resolution_key(self):
+        """Una chiave di formato non valido non risolve il conflitto"""
+        self.receiver.register_node_key("sink-1", self.impostor.get_public_key())
+        with self.assertRaises(RuntimeError):
+            self.receiver.resolve_key_conflict("sink-1", [1, 2, 3])
+        self.assertTrue(self.receiver.is_quarantined("sink-1"))
+
+if __name__ == "__main__":
+    unittest.main()
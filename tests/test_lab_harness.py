@@ -0,0 +1,101 @@
+# Test unitari per l'harness saber-test dei laboratori LAN
+# Verifica il protocollo a righe del peer, le ritrasmissioni e il report dell'orchestratore
+
+import json
+import os
+import socket
+import subprocess
+import tempfile
+import unittest
+
+# Eseguibile compilato con -DSABER_BUILD_TOOLS=ON; SABER_TEST_BIN ne indica il percorso
+ROOT = os.path.join(os.path.dirname(__file__), '..')
+CANDIDATES = [os.environ.get("SABER_TEST_BIN", ""),
+              os.path.join(ROOT, 'build', 'saber-test'),
+              os.path.join(ROOT, 'build', 'src', 'saber-test'),
+              os.path.join(ROOT, 'src', 'build', 'saber-test')]
+SABER_TEST = next((path for path in CANDIDATES if path and os.access(path, os.X_OK)), None)
+
+def free_udp_port():
+    with socket.socket(socket.AF_INET, socket.SOCK_DGRAM) as probe:
+        probe.bind(("127.0.0.1", 0))
+        return probe.getsockname()[1]
+
+@unittest.skipIf(SABER_TEST is None, "saber-test non compilato (SABER_BUILD_TOOLS)")
+class TestUsage(unittest.TestCase):
+    """Test per gli argomenti della riga di comando"""
+
+    def run_tool(self, *args):
+        return subprocess.run([SABER_TEST] + list(args), capture_output=True, text=True, timeout=10)
+
+    def test_invalid_arguments(self):
+        """Modalità, ruoli, profili e peer mancanti terminano con codice 2"""
+        for args in ((), ("replay",), ("peer", "--role", "sink"), ("peer", "--id", "n1", "--role", "bridge"),
+                     ("peer", "--id", "n1", "--profile", "inesistente"), ("orchestrator",)):
+            result = self.run_tool(*args)
+            self.assertEqual(result.returncode, 2, args)
+            self.assertTrue(result.stderr, args)
+
+    def test_unreachable_peer(self):
+        """Un peer che non risponde fa fallire lo scenario con un report JSON"""
+        handle, report_path = tempfile.mkstemp(suffix=".json")
+        os.close(handle)
+        self.addCleanup(os.remove, report_path)
+        peer = "127.0.0.1:%d" % free_udp_port()
+        result = subprocess.run([SABER_TEST, "orchestrator", "--peer", peer,
+                                 "--stream-seconds", "0", "--report", report_path],
+                                capture_output=True, text=True, timeout=30)
+        self.assertEqual(result.returncode, 1)
+        with open(report_path) as file:
+            report = json.load(file)
+        self.assertEqual(report["scenario"], "lan-join-sync-stream-kill")
+        self.assertFalse(report["passed"])
+        self.assertEqual(report["steps"], [{"step": "join", "peer": peer, "ok": False,
+                                             "detail": "nessuna risposta"}])
+
+@unittest.skipIf(SABER_TEST is None, "saber-test non compilato (SABER_BUILD_TOOLS)")
+class TestPeer(unittest.TestCase):
+    """Test per il protocollo a righe del peer"""
+
+    def setUp(self):
+        self.port = free_udp_port()
+        self.peer = subprocess.Popen([SABER_TEST, "peer", "--id", "lab-sink", "--role", "sink",
+                                      "--port", str(self.port), "--seed", "7"],
+                                     stdout=subprocess.PIPE, stderr=subprocess.DEVNULL, text=True)
+        self.addCleanup(self.stop)
+        # Il peer annuncia la porta quando è pronto a ricevere
+        self.assertIn("in ascolto", self.peer.stdout.readline())
+        self.client = socket.socket(socket.AF_INET, socket.SOCK_DGRAM)
+        self.client.settimeout(5)
+        self.addCleanup(self.client.close)
+
+    def stop(self):
+        if self.peer.poll() is None:
+            self.peer.kill()
+        self.peer.wait(timeout=5)
+        self.peer.stdout.close()
+
+    def request(self, line):
+        self.client.sendto(line.encode(), ("127.0.0.1", self.port))
+        return self.client.recv(1024).decode()
+
+    def test_inactive_node(self):
+        """Prima del join lo stato è "terminato" e gli altri comandi falliscono"""
+        self.assertEqual(self.request("1 status"), "1 ok terminato")
+        self.assertEqual(self.request("2 stream"), "2 fail nodo non attivo")
+
+    def test_retransmission(self):
+        """Una richiesta ritrasmessa riceve la stessa risposta senza rieseguire il comando"""
+        self.assertEqual(self.request("10 stream"), "10 fail nodo non attivo")
+        # Stessa sequenza: la risposta è quella memorizzata, non quella di "status"
+        self.assertEqual(self.request("10 status"), "10 fail nodo non attivo")
+        self.assertEqual(self.request("11 status"), "11 ok terminato")
+        self.assertEqual(self.request("12 kill repeater"), "12 ok non coinvolto")
+
+    def test_shutdown(self):
+        """Il comando shutdown risponde e termina il processo"""
+        self.assertEqual(self.request("5 shutdown"), "5 ok arresto")
+        self.assertEqual(self.peer.wait(timeout=5), 0)
+
+if __name__ == "__main__":
+    unittest.main()
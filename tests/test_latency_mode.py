@@ -0,0 +1,91 @@
+# Test unitari per la modalità di latenza rigorosa del protocollo SABER
+# Verifica il rifiuto dei sink lenti, il limite del budget e la scelta per profilo
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (LatencyMode, LatencyPlanner, NodeRole, SaberConfig, SpecParameters,
+                                find_profile, latency_mode_from_string, latency_mode_to_string)
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+ZONES = {"sink-1": "sala", "sink-2": "sala", "sink-3": "sala"}
+LATENCIES = {"sink-1": 10, "sink-2": 15, "sink-3": 45}
+
+class TestPlanner(unittest.TestCase):
+    """Test per il pianificatore nei due modi"""
+
+    def test_best_effort(self):
+        """Al meglio il sink lento alza il buffer della zona e viene suggerita una separazione"""
+        planner = LatencyPlanner(40, 10)
+        members = list(ZONES)
+        self.assertEqual(planner.rejected_sinks(members, LATENCIES), [])
+        self.assertEqual(planner.zone_buffer_ms(members, LATENCIES), 55)
+        self.assertEqual(len(planner.suggest_splits(ZONES, LATENCIES)), 1)
+
+    def test_strict_rejects_slow_sinks(self):
+        """In modalità rigorosa il sink lento viene rifiutato e il buffer resta nel budget"""
+        planner = LatencyPlanner(40, 10, LatencyMode.Strict)
+        members = list(ZONES)
+        self.assertEqual(planner.get_mode(), LatencyMode.Strict)
+        self.assertEqual(planner.rejected_sinks(members, LATENCIES), ["sink-3"])
+        self.assertEqual(planner.zone_buffer_ms(members, LATENCIES), 25)
+        self.assertEqual(planner.suggest_splits(ZONES, LATENCIES), [])
+
+class TestSpecParameters(unittest.TestCase):
+    """Test per la validazione dei parametri in modalità rigorosa"""
+
+    def test_budget_above_spec_refused(self):
+        """In modalità rigorosa il budget non può superare i 40 ms della specifica"""
+        params = SpecParameters()
+        params.latency_budget_ms = 80
+        params.validate()
+        params.latency_mode = LatencyMode.Strict
+        with self.assertRaises(ValueError):
+            params.validate()
+
+    def test_names(self):
+        """I modi hanno nomi stabili per la configurazione"""
+        self.assertEqual(latency_mode_to_string(LatencyMode.BestEffort), "best_effort")
+        self.assertEqual(latency_mode_from_string("strict"), LatencyMode.Strict)
+        self.assertIsNone(latency_mode_from_string("rigoroso"))
+
+class TestProfiles(unittest.TestCase):
+    """Test per la scelta del modo tramite profilo"""
+
+    def test_profile_modes(self):
+        """Il monitor da palco è rigoroso, la musica in casa è al meglio"""
+        self.assertEqual(find_profile("stage-monitor").latency_mode, LatencyMode.Strict)
+        self.assertEqual(find_profile("home-music").latency_mode, LatencyMode.BestEffort)
+        config = SaberConfig.for_profile("stage-monitor", NodeRole.Sink)
+        self.assertEqual(config.spec.latency_mode, LatencyMode.Strict)
+
+class TestConfigFile(ConfigFileTestCase):
+    """Test per le configurazioni rifiutate in modalità rigorosa"""
+
+    HEADER = '[node]\nid = "sink-1"\nrole = "sink"\n'
+
+    def test_override(self):
+        """Il modo del profilo può essere sovrascritto"""
+        config = self.load('profile = "stage-monitor"\n\n[spec]\nlatency_mode = "best_effort"\n')
+        self.assertEqual(config.spec.latency_mode, LatencyMode.BestEffort)
+        with self.assertRaises(RuntimeError):
+            self.load('\n[spec]\nlatency_mode = "rigoroso"\n')
+
+    def test_a2dp_refused(self):
+        """Un ponte A2DP oltre il budget viene rifiutato solo in modalità rigorosa"""
+        audio = '\n[audio]\na2dp_device = "00:11:22:33:44:55"\na2dp_latency_ms = 150\n'
+        self.load(audio)
+        with self.assertRaises(RuntimeError):
+            self.load(audio + '\n[spec]\nlatency_mode = "strict"\n')
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,133 @@
+# Test unitari per la codifica LC3 del protocollo SABER
+# Verifica il formato dei frame di 10ms, la dimensione dei frame codificati e la decodifica
+
+import math
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import AudioFrame, AudioSync, SyncManager, lc3_available
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def sine_frame(sample_rate, channels, playout_time_us=0, frequency=440.0):
+    """Crea un frame di 10ms con una sinusoide su tutti i canali"""
+    frame = AudioFrame()
+    frame.sample_rate = sample_rate
+    frame.channels = channels
+    frame.playout_time_us = playout_time_us
+    samples = []
+    for i in range(frame.samples_per_channel()):
+        value = int(8000 * math.sin(2 * math.pi * frequency * i / sample_rate))
+        samples.extend([value] * channels)
+    frame.samples = samples
+    return frame
+
+class TestAudioFrame(unittest.TestCase):
+    """Test per il formato dei frame PCM"""
+
+    def test_samples_per_channel(self):
+        """Un frame dura 10ms alla propria frequenza di campionamento"""
+        frame = AudioFrame()
+        frame.sample_rate = 48000
+        self.assertEqual(frame.samples_per_channel(), 480)
+        frame.sample_rate = 16000
+        self.assertEqual(frame.samples_per_channel(), 160)
+
+@unittest.skipUnless(lc3_available(), "saber_protocol compilato senza SABER_ENABLE_LC3")
+class TestLc3Codec(unittest.TestCase):
+    """Test per la codifica e decodifica LC3 in AudioSync"""
+
+    def setUp(self):
+        self.master = AudioSync(SyncManager(), True, 2)
+        self.sink = AudioSync(SyncManager(), True, 2)
+
+    def test_frame_size_follows_bitrate(self):
+        """A 128kbps un frame stereo di 10ms occupa 160 byte"""
+        payload = self.master.encode_frame(sine_frame(48000, 2))
+        self.assertEqual(len(payload), 160)
+
+    def test_roundtrip(self):
+        """Il sink ricostruisce un frame del formato atteso con lo stesso istante"""
+        for n in range(5):
+            payload = self.master.encode_frame(sine_frame(48000, 2, n * 10000))
+            frame = self.sink.decode_frame(payload, n * 10000)
+        self.assertEqual(frame.sample_rate, 48000)
+        self.assertEqual(frame.channels, 2)
+        self.assertEqual(frame.playout_time_us, 40000)
+        self.assertEqual(len(frame.samples), 960)
+        self.assertGreater(max(abs(s) for s in frame.samples), 1000)
+
+    def test_bitrate_reduced_on_weak_network(self):
+        """Su rete debole i frame codificati si riducono"""
+        self.master.adjust_bitrate(0.2)
+        self.assertEqual(self.master.get_bitrate(), 64)
+        payload = self.master.encode_frame(sine_frame(48000, 2))
+        self.assertEqual(len(payload), 80)
+
+    def test_wrong_format_rejected(self):
+        """Un frame con un formato diverso da quello dello stream viene rifiutato"""
+        with self.assertRaises(ValueError):
+            self.master.encode_frame(sine_frame(16000, 2))
+        frame = sine_frame(48000, 2)
+        frame.samples = frame.samples[:100]
+        with self.assertRaises(ValueError):
+            self.master.encode_frame(frame)
+
+    def test_concealment(self):
+        """Un frame perso viene ricostruito con la durata di un frame"""
+        self.sink.decode_frame(self.master.encode_frame(sine_frame(48000, 2)), 0)
+        frame = self.sink.conceal_frame(10000)
+        self.assertEqual(frame.playout_time_us, 10000)
+        self.assertEqual(len(frame.samples), 960)
+
+class TestStreamFormat(unittest.TestCase):
+    """Test per il formato dei frame di ogni stream"""
+
+    def test_default_format(self):
+        """Gli stream senza formato dedicato usano quello del nodo"""
+        sync = AudioSync(SyncManager(), True, 2)
+        sync.set_stream_format(2, False, 1)
+        self.assertEqual(sync.get_stream_format(1).sample_rate_hz, 48000)
+        self.assertEqual(sync.get_stream_format(1).channels, 2)
+        self.assertEqual(sync.get_stream_format(2).sample_rate_hz, 16000)
+        self.assertEqual(sync.get_stream_format(2).channels, 1)
+
+@unittest.skipUnless(lc3_available(), "saber_protocol compilato senza SABER_ENABLE_LC3")
+class TestStreamMultiplexing(unittest.TestCase):
+    """Test per la musica e gli annunci vocali trasmessi insieme"""
+
+    def setUp(self):
+        self.master = AudioSync(SyncManager(), True, 2)
+        self.sink = AudioSync(SyncManager(), True, 2)
+        for sync in (self.master, self.sink):
+            sync.set_stream_format(2, False, 1)
+
+    def test_interleaved_streams(self):
+        """Frame alternati di due stream mantengono ciascuno il proprio formato"""
+        for n in range(3):
+            music = self.master.encode_frame(sine_frame(48000, 2, n * 10000), 1)
+            voice = self.master.encode_frame(sine_frame(16000, 1, n * 10000), 2)
+            self.assertEqual(len(music), 160)
+            self.assertEqual(len(voice), 80)
+            self.assertEqual(self.sink.decode_frame(music, n * 10000, 1).channels, 2)
+            self.assertEqual(self.sink.decode_frame(voice, n * 10000, 2).sample_rate, 16000)
+
+    def test_voice_stream_rejects_music(self):
+        """Lo stream vocale accetta solo frame a 16kHz mono"""
+        with self.assertRaises(ValueError):
+            self.master.encode_frame(sine_frame(48000, 2), 2)
+
+    def test_bitrate_reduced_on_every_stream(self):
+        """Su rete debole si riduce anche il bitrate degli annunci"""
+        self.master.adjust_bitrate(0.2)
+        self.assertEqual(len(self.master.encode_frame(sine_frame(16000, 1), 2)), 40)
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,70 @@
+# Test unitari per il ciclo di vita del protocollo SABER
+# Verifica gli errori strutturati delle transizioni e il cambio di ruolo a protocollo fermo
+
+import gc
+import os
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (SaberConfig, SaberProtocol, ProtocolState, NodeRole,
+                                LifecycleError, LifecycleErrorType)
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestLifecycle(unittest.TestCase):
+    """Test per le transizioni del ciclo di vita senza avviare la rete"""
+
+    def setUp(self):
+        config = SaberConfig.default_config()
+        config.role = NodeRole.Sink
+        self.protocol = SaberProtocol(config)
+
+    def test_shutdown_when_stopped(self):
+        """Arrestare un protocollo mai avviato produce un errore con il relativo tipo"""
+        with self.assertRaises(LifecycleError) as context:
+            self.protocol.shutdown()
+        self.assertEqual(context.exception.type, LifecycleErrorType.NotRunning)
+        self.assertIsInstance(context.exception, RuntimeError)
+        self.assertEqual(self.protocol.get_state(), ProtocolState.Stopped)
+
+    def test_set_role_when_stopped(self):
+        """A protocollo fermo il nuovo ruolo viene solo registrato per il prossimo avvio"""
+        self.protocol.set_role(NodeRole.Repeater)
+        self.assertEqual(self.protocol.get_role(), NodeRole.Repeater)
+        self.assertEqual(self.protocol.get_state(), ProtocolState.Stopped)
+
+class TestRunningLifecycle(unittest.TestCase):
+    """Test per l'arresto dei thread di un protocollo avviato"""
+
+    def start(self):
+        config = SaberConfig.default_config()
+        config.role = NodeRole.Sink
+        protocol = SaberProtocol(config)
+        if not protocol.initialize():
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        return protocol
+
+    def test_shutdown_is_prompt(self):
+        """L'arresto interrompe l'attesa dei thread senza aspettarne il giro successivo"""
+        protocol = self.start()
+        started = time.monotonic()
+        protocol.shutdown()
+        self.assertLess(time.monotonic() - started, 1.0)
+        self.assertEqual(protocol.get_state(), ProtocolState.Stopped)
+
+    def test_drop_without_shutdown(self):
+        """Un protocollo avviato e poi rilasciato ferma i propri thread"""
+        protocol = self.start()
+        self.assertEqual(protocol.get_state(), ProtocolState.Running)
+        del protocol
+        gc.collect()
+
+if __name__ == '__main__':
+    unittest.main()
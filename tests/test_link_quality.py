@@ -0,0 +1,59 @@
+# Test unitari per la qualità dei collegamenti
+# Verifica la media del tasso di consegna, il peso dell'RSSI nel punteggio e la consultazione dal protocollo
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import Node, NodeRole, SaberConfig, SaberProtocol, TransportKind
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestNodeLinkQuality(unittest.TestCase):
+    """Test per le misure del collegamento di un nodo"""
+
+    def test_unmeasured(self):
+        """Un nodo mai misurato non viene penalizzato"""
+        node = Node("sink-1", NodeRole.Sink)
+        self.assertIsNone(node.get_packet_success_rate())
+        self.assertIsNone(node.get_signal_strength())
+        self.assertEqual(node.get_link_quality(), 1.0)
+
+    def test_success_rate(self):
+        """La prima misura vale per intero, le successive vengono mediate"""
+        node = Node("sink-1", NodeRole.Sink)
+        node.record_packet_success(0.5)
+        self.assertAlmostEqual(node.get_packet_success_rate(), 0.5)
+        node.record_packet_success(1.0)
+        self.assertAlmostEqual(node.get_packet_success_rate(), 0.65)
+        node.record_packet_success(3.0)
+        self.assertLessEqual(node.get_packet_success_rate(), 1.0)
+
+    def test_signal(self):
+        """Un segnale debole dimezza al più la qualità, uno forte non la riduce"""
+        node = Node("sink-1", NodeRole.Sink)
+        node.record_packet_success(0.8)
+        node.set_signal_strength(-50)
+        self.assertAlmostEqual(node.get_link_quality(), 0.8)
+        node.set_signal_strength(-75)
+        self.assertAlmostEqual(node.get_link_quality(), 0.6)
+        node.set_signal_strength(-100)
+        self.assertAlmostEqual(node.get_link_quality(), 0.4)
+
+class TestProtocolLinkQuality(unittest.TestCase):
+    """Test per la consultazione senza avviare la rete"""
+
+    def test_without_network(self):
+        """Senza rete non ci sono misure né resoconti accettati"""
+        protocol = SaberProtocol(SaberConfig.default_config())
+        self.assertEqual(protocol.get_link_qualities(), {})
+        self.assertFalse(protocol.report_link_quality("sink-1", TransportKind.Udp, 0.9, 12, rssi_dbm=-70))
+
+if __name__ == "__main__":
+    unittest.main()
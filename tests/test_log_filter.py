@@ -0,0 +1,132 @@
+# Test unitari per i filtri dei log modificabili a runtime
+# Verifica la sintassi dei filtri, il comando "log" del socket di controllo e la chiave log.filter
+
+import os
+import socket
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import NodeRole, SaberConfig, SaberProtocol, SimNetwork, TokenScope, get_log_level
+    from helpers import load_config
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def free_port():
+    with socket.socket(socket.AF_INET, socket.SOCK_STREAM) as probe:
+        probe.bind(("127.0.0.1", 0))
+        return probe.getsockname()[1]
+
+class TestSetLogFilter(unittest.TestCase):
+    """Test per il filtro applicato localmente"""
+
+    def setUp(self):
+        self.protocol = SaberProtocol(SaberConfig.default_config())
+        self.addCleanup(self.protocol.set_log_filter, self.protocol.get_log_filter())
+
+    def test_targets(self):
+        """Il filtro sostituisce quello attivo e vale per target"""
+        self.assertTrue(self.protocol.set_log_filter("mesh=debug, sync=trace"))
+        self.assertEqual(self.protocol.get_log_filter(), "info,mesh=debug,sync=trace")
+        self.assertEqual(get_log_level("sync"), "trace")
+        self.assertEqual(get_log_level("audio"), "info")
+
+        self.assertTrue(self.protocol.set_log_filter("warn"))
+        self.assertEqual(self.protocol.get_log_filter(), "warn")
+        self.assertEqual(get_log_level("mesh"), "warn")
+
+    def test_invalid(self):
+        """Un filtro non valido viene rifiutato e quello attivo resta invariato"""
+        self.protocol.set_log_filter("info,mesh=debug")
+        for spec in ("mesh=verbose", "=debug", "loud"):
+            self.assertFalse(self.protocol.set_log_filter(spec), spec)
+        self.assertEqual(self.protocol.get_log_filter(), "info,mesh=debug")
+
+    def test_mesh_wide_without_network(self):
+        """Senza rete mesh il filtro non può essere propagato"""
+        self.assertFalse(self.protocol.set_log_filter("debug", True))
+
+class TestControlSocket(unittest.TestCase):
+    """Test per il comando "log" del socket di controllo"""
+
+    def setUp(self):
+        self.port = free_port()
+        config = SaberConfig.default_config()
+        config.node_id = "log-master"
+        config.role = NodeRole.Master
+        config.control_port = self.port
+        config.control_bind_address = "127.0.0.1"
+        config.log_filter = "info"
+        self.protocol = SaberProtocol(config)
+        self.protocol.set_sim_network(SimNetwork(1))
+        self.addCleanup(self.protocol.set_log_filter, self.protocol.get_log_filter())
+        if not self.protocol.initialize():
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        self.addCleanup(self.protocol.shutdown)
+        try:
+            self.client = socket.create_connection(("127.0.0.1", self.port), timeout=5)
+        except OSError:
+            self.skipTest("socket di controllo non disponibile")
+        self.addCleanup(self.client.close)
+        self.lines = self.client.makefile("r")
+        self.addCleanup(self.lines.close)
+
+    def command(self, line):
+        self.client.sendall((line + "\n").encode())
+        return self.lines.readline().rstrip("\n")
+
+    def test_requires_token(self):
+        """Senza token i comandi vengono respinti"""
+        self.assertTrue(self.command("log get").startswith("error"))
+
+    def test_set_and_get(self):
+        """L'operatore imposta il filtro e lo rilegge dal socket"""
+        token = self.protocol.issue_control_token(TokenScope.Admin, 60)
+        self.assertEqual(self.command("auth " + token), "ok admin")
+        self.assertEqual(self.command("log set mesh=debug,sync=trace"), "ok info,mesh=debug,sync=trace")
+        self.assertEqual(self.command("log get"), "ok info,mesh=debug,sync=trace")
+        self.assertEqual(self.protocol.get_log_filter(), "info,mesh=debug,sync=trace")
+        self.assertEqual(self.command("log set debug --mesh"), "ok debug")
+        self.assertEqual(self.command("log set trace --node sink-1"), "ok inviato a sink-1")
+        self.assertEqual(self.protocol.get_log_filter(), "debug")
+
+    def test_idle_client_does_not_block(self):
+        """Un client che non si autentica non blocca le altre connessioni"""
+        idle = socket.create_connection(("127.0.0.1", self.port), timeout=5)
+        self.addCleanup(idle.close)
+        other = socket.create_connection(("127.0.0.1", self.port), timeout=5)
+        self.addCleanup(other.close)
+        lines = other.makefile("r")
+        self.addCleanup(lines.close)
+        token = self.protocol.issue_control_token(TokenScope.Admin, 60)
+        other.sendall(("auth " + token + "\n").encode())
+        self.assertEqual(lines.readline().rstrip("\n"), "ok admin")
+
+    def test_invalid_commands(self):
+        """Filtri e opzioni non validi producono una risposta di errore"""
+        token = self.protocol.issue_control_token(TokenScope.Admin, 60)
+        self.command("auth " + token)
+        self.assertTrue(self.command("log set mesh=verbose").startswith("error"))
+        self.assertTrue(self.command("log set debug --everywhere").startswith("error"))
+        self.assertTrue(self.command("log").startswith("error"))
+        self.assertEqual(self.protocol.get_log_filter(), "info")
+
+class TestLogFilterConfig(unittest.TestCase):
+    """Test per le chiavi dei log e del socket di controllo nel file"""
+
+    def test_keys(self):
+        """log.filter, control.port e control.bind_address vengono letti dal file"""
+        config = load_config('[node]\nid = "sink-1"\nrole = "sink"\n\n'
+                             '[log]\nfilter = "warn,mesh=debug"\n\n'
+                             '[control]\nport = 7700\nbind_address = "127.0.0.1"\n')
+        self.assertEqual(config.log_filter, "warn,mesh=debug")
+        self.assertEqual(config.control_port, 7700)
+        self.assertEqual(config.control_bind_address, "127.0.0.1")
+
+if __name__ == "__main__":
+    unittest.main()
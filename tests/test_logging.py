@@ -0,0 +1,70 @@
+# Test unitari per i log strutturati del protocollo SABER
+# Verifica i livelli per target e l'inoltro dei messaggi al modulo logging di Python
+
+import logging
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import SaberConfig, forward_logs_to_python, get_log_level, set_log_level
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestLogLevel(unittest.TestCase):
+    """Test per i livelli di log per target"""
+
+    def setUp(self):
+        self.addCleanup(set_log_level, get_log_level())
+        self.addCleanup(set_log_level, get_log_level("sync"), "sync")
+
+    def test_target(self):
+        """Il livello di un target non cambia quello predefinito"""
+        set_log_level("info")
+        set_log_level("trace", "sync")
+        self.assertEqual(get_log_level("sync"), "trace")
+        self.assertEqual(get_log_level("mesh"), "info")
+        set_log_level("warn")
+        self.assertEqual(get_log_level("mesh"), "warn")
+        self.assertEqual(get_log_level("sync"), "trace")
+
+    def test_invalid(self):
+        """Un livello sconosciuto viene rifiutato"""
+        with self.assertRaises(ValueError):
+            set_log_level("verbose")
+
+class TestForwardLogs(ConfigFileTestCase):
+    """Test per l'inoltro dei log al modulo logging"""
+
+    def setUp(self):
+        super().setUp()
+        self.write('[node]\nid = "master-1"\nrole = "master"\n\n[mqtt]\npassword = "segreta"\n')
+        self.addCleanup(forward_logs_to_python, False)
+        self.addCleanup(set_log_level, get_log_level("config"), "config")
+
+    def test_forward(self):
+        """Gli avvisi arrivano al logger saber.<target> con il livello corrispondente"""
+        forward_logs_to_python()
+        with self.assertLogs("saber.config", "WARNING") as captured:
+            SaberConfig.from_file(self.path)
+        self.assertTrue(any("mqtt.password" in message for message in captured.output))
+        self.assertEqual(captured.records[0].levelno, logging.WARNING)
+
+    def test_filtered(self):
+        """Il filtro del protocollo decide quali messaggi arrivano a Python"""
+        forward_logs_to_python()
+        set_log_level("error", "config")
+        logger = logging.getLogger("saber.config")
+        with self.assertLogs(logger, "DEBUG") as captured:
+            SaberConfig.from_file(self.path)
+            logger.debug("sentinella")
+        self.assertEqual([record.getMessage() for record in captured.records], ["sentinella"])
+
+if __name__ == "__main__":
+    unittest.main()
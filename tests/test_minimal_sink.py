@@ -0,0 +1,50 @@
+# Test unitari per la build minimal-sink del protocollo SABER
+# Verifica l'assenza delle parti del Master e la dimensione del modulo per le schede dei sink
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    import saber_protocol
+    from saber_protocol import NodeRole, SaberConfig, SaberProtocol, minimal_sink_build
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+MINIMAL = minimal_sink_build()
+
+# Dimensione massima del modulo compilato in Release per una cassa su scheda ARM
+MAX_MODULE_BYTES = int(os.environ.get("SABER_MINIMAL_SINK_MAX_BYTES", 4 * 1024 * 1024))
+
+class TestBuildFlavour(unittest.TestCase):
+    """Test per la coerenza tra opzione di compilazione e API esposta"""
+
+    def test_master_api_matches_build(self):
+        """La programmazione oraria del Master esiste solo fuori dalla build minimale"""
+        self.assertEqual(hasattr(saber_protocol, "Scheduler"), not MINIMAL)
+        self.assertEqual(hasattr(SaberProtocol, "get_schedule"), not MINIMAL)
+
+@unittest.skipUnless(MINIMAL, "compilato senza SABER_MINIMAL_SINK")
+class TestMinimalSink(unittest.TestCase):
+    """Test per la build minimal-sink"""
+
+    def test_module_size(self):
+        """Il modulo resta entro la dimensione prevista per le schede dei sink"""
+        size = os.path.getsize(saber_protocol.__file__)
+        self.assertLessEqual(size, MAX_MODULE_BYTES,
+                             "modulo di %d byte, previsti al massimo %d" % (size, MAX_MODULE_BYTES))
+
+    def test_master_role_refused(self):
+        """Solo il ruolo Sink può essere avviato"""
+        config = SaberConfig.default_config()
+        config.role = NodeRole.Master
+        protocol = SaberProtocol(config)
+        self.assertFalse(protocol.initialize())
+
+if __name__ == "__main__":
+    unittest.main()
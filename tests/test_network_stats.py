@@ -0,0 +1,112 @@
+# Test unitari per le statistiche di rete del protocollo SABER
+# Verifica percentili, jitter, istogrammi e stima della perdita dei frame per nodo
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import LatencyStats
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestLatencyHistogram(unittest.TestCase):
+    """Test per gli istogrammi di latenza"""
+
+    def test_percentiles(self):
+        """I percentili sono calcolati sui campioni del nodo"""
+        stats = LatencyStats()
+        for latency in range(1, 101):
+            stats.record_latency("sink-1", latency)
+        node = stats.report().nodes["sink-1"]
+        self.assertEqual(node.samples, 100)
+        self.assertEqual(node.p50_latency_ms, 50)
+        self.assertEqual(node.p95_latency_ms, 95)
+        self.assertEqual(node.p99_latency_ms, 99)
+        self.assertEqual(node.max_latency_ms, 100)
+
+    def test_jitter_variance(self):
+        """La varianza misura la dispersione della latenza"""
+        stats = LatencyStats()
+        for latency in [10, 20, 10, 20]:
+            stats.record_latency("sink-1", latency)
+        for latency in [15, 15, 15, 15]:
+            stats.record_latency("sink-2", latency)
+        report = stats.report()
+        self.assertAlmostEqual(report.nodes["sink-1"].mean_latency_ms, 15.0)
+        self.assertAlmostEqual(report.nodes["sink-1"].jitter_variance_ms2, 25.0)
+        self.assertAlmostEqual(report.nodes["sink-2"].jitter_variance_ms2, 0.0)
+        self.assertAlmostEqual(report.overall.jitter_variance_ms2, 12.5)
+
+    def test_histogram(self):
+        """I campioni vengono raccolti in intervalli dell'ampiezza configurata"""
+        stats = LatencyStats(bucket_ms=10)
+        for latency in [3, 7, 12, 35]:
+            stats.record_latency("sink-1", latency)
+        self.assertEqual(stats.report().nodes["sink-1"].histogram, {0: 2, 10: 1, 30: 1})
+
+    def test_rolling_window(self):
+        """Solo gli ultimi campioni restano nella finestra"""
+        stats = LatencyStats(window_samples=4)
+        for latency in [100, 100, 10, 10, 10, 10]:
+            stats.record_latency("sink-1", latency)
+        node = stats.report().nodes["sink-1"]
+        self.assertEqual(node.samples, 4)
+        self.assertEqual(node.max_latency_ms, 10)
+
+class TestLossEstimate(unittest.TestCase):
+    """Test per la stima della perdita dei frame"""
+
+    def test_gaps(self):
+        """I numeri di sequenza mancanti contano come persi"""
+        stats = LatencyStats()
+        for sequence in [0, 1, 2, 4, 5, 8, 9]:
+            stats.record_frame("master-1", 1, sequence)
+        node = stats.report().nodes["master-1"]
+        self.assertEqual(node.frames_received, 7)
+        self.assertEqual(node.frames_lost, 3)
+        self.assertAlmostEqual(node.loss_rate, 0.3)
+
+    def test_repaired_frames(self):
+        """Un frame riparato o fuori ordine non resta perso"""
+        stats = LatencyStats()
+        for sequence in [0, 2, 1, 3]:
+            stats.record_frame("master-1", 1, sequence)
+        self.assertEqual(stats.report().nodes["master-1"].frames_lost, 0)
+
+    def test_streams_kept_apart(self):
+        """Gli stream di una sorgente hanno sequenze indipendenti"""
+        stats = LatencyStats()
+        for sequence in range(10):
+            stats.record_frame("master-1", 1, sequence)
+            stats.record_frame("master-1", 2, 100 + sequence)
+        self.assertEqual(stats.report().nodes["master-1"].frames_lost, 0)
+
+    def test_source_restart(self):
+        """Una sorgente riavviata non viene scambiata per una perdita"""
+        stats = LatencyStats(window_samples=16)
+        for sequence in range(1000, 1010):
+            stats.record_frame("master-1", 1, sequence)
+        for sequence in range(5):
+            stats.record_frame("master-1", 1, sequence)
+        node = stats.report().nodes["master-1"]
+        self.assertEqual(node.frames_received, 5)
+        self.assertEqual(node.frames_lost, 0)
+
+    def test_prometheus(self):
+        """Le statistiche vengono esportate come metriche per nodo"""
+        stats = LatencyStats()
+        stats.record_latency("sink-1", 12)
+        stats.record_frame("sink-1", 1, 0)
+        stats.record_frame("sink-1", 1, 2)
+        body = LatencyStats.to_prometheus(stats.report(), "master-1")
+        self.assertIn('saber_peer_latency_ms{node="master-1",peer="sink-1",quantile="0.5"} 12', body)
+        self.assertIn('saber_peer_loss_ratio{node="master-1",peer="sink-1"} 0.333', body)
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,80 @@
+# Test unitari per il rilevamento dei nodi che non rispondono
+# Verifica il timeout di heartbeat, la configurazione della rimozione dei nodi persi e i contatori
+
+import os
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import Node, NodeRole, SaberConfig, SaberProtocol, SimNetwork
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestHeartbeat(unittest.TestCase):
+    """Test per il timeout di heartbeat di un nodo"""
+
+    def test_timeout(self):
+        """Un nodo resta attivo solo entro il timeout dall'ultimo ping"""
+        node = Node("sink-1", NodeRole.Sink)
+        node.set_heartbeat_timeout(50)
+        self.assertFalse(node.is_active())
+        node.update_ping()
+        self.assertTrue(node.is_active())
+        time.sleep(0.1)
+        self.assertFalse(node.is_active())
+
+class TestLivenessConfig(ConfigFileTestCase):
+    """Test per il timeout e la rimozione letti dal file di configurazione"""
+
+    HEADER = '[node]\nid = "master-1"\nrole = "master"\n\n'
+
+    def test_defaults(self):
+        """Senza sezione [mesh] valgono il timeout della specifica e la rimozione dopo cinque minuti"""
+        config = self.load('')
+        self.assertEqual(config.heartbeat_timeout_ms, 30000)
+        self.assertEqual(config.node_evict_after_ms, 300000)
+
+    def test_values(self):
+        """La sezione [mesh] imposta timeout e rimozione, modificabili senza riavvio"""
+        config = self.load('[mesh]\nheartbeat_timeout_ms = 2000\nevict_after_ms = 0\n')
+        self.assertEqual(config.heartbeat_timeout_ms, 2000)
+        self.assertEqual(config.node_evict_after_ms, 0)
+        self.assertTrue(SaberConfig.is_live_reloadable("mesh.heartbeat_timeout_ms"))
+        self.assertTrue(SaberConfig.is_live_reloadable("mesh.evict_after_ms"))
+
+    def test_invalid(self):
+        """Un timeout nullo o una rimozione negativa vengono rifiutati"""
+        for text in ('[mesh]\nheartbeat_timeout_ms = 0\n', '[mesh]\nevict_after_ms = -1\n'):
+            with self.assertRaises(RuntimeError):
+                self.load(text)
+
+class TestEviction(unittest.TestCase):
+    """Test per i nodi tolti dalla rete"""
+
+    def test_never_heard(self):
+        """Un nodo registrato ma mai sentito non viene perso né tolto"""
+        config = SaberConfig.default_config()
+        config.node_id = "liveness-master"
+        config.role = NodeRole.Master
+        config.heartbeat_timeout_ms = 10
+        config.node_evict_after_ms = 10
+        protocol = SaberProtocol(config)
+        protocol.set_sim_network(SimNetwork(1))
+        if not protocol.initialize():
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        self.addCleanup(protocol.shutdown)
+        self.assertTrue(protocol.register_node("liveness-sink", NodeRole.Sink))
+        time.sleep(0.2)
+        self.assertEqual(protocol.get_evicted_nodes(), [])
+        self.assertEqual(protocol.get_admission_stats().evicted, 0)
+        self.assertEqual(protocol.get_admission_stats().sinks, 1)
+
+if __name__ == "__main__":
+    unittest.main()
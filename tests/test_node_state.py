@@ -0,0 +1,124 @@
+# Test unitari per l'esportazione e l'importazione dello stato di un nodo SABER
+# Verifica il formato versionato, i riferimenti ai segreti e la migrazione di un Master
+
+import json
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (NODE_STATE_VERSION, LatencyMode, NodeRole, NodeState, PeerState,
+                                SaberConfig, SaberProtocol, decode_node_state, encode_node_state)
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def master_state():
+    state = NodeState()
+    state.node_id = "master-1"
+    state.role = NodeRole.Master
+    state.public_key = list(range(32))
+    state.profile = "home-music"
+    state.published_streams = {1, 2}
+    state.secrets = {"security.passphrase": "env:SABER_PASSPHRASE", "mqtt.password": None}
+    state.zones = {"sink-1": "sala"}
+    peer = PeerState()
+    peer.node_id = "sink-1"
+    peer.role = NodeRole.Sink
+    peer.public_key = [7] * 32
+    state.peers = [peer]
+    return state
+
+class TestNodeStateDocument(unittest.TestCase):
+    """Test per il documento di stato"""
+
+    def test_roundtrip(self):
+        """Un documento codificato viene riletto senza perdite"""
+        decoded = decode_node_state(encode_node_state(master_state()))
+        self.assertEqual(decoded.version, NODE_STATE_VERSION)
+        self.assertEqual(decoded.node_id, "master-1")
+        self.assertEqual(decoded.role, NodeRole.Master)
+        self.assertEqual(decoded.public_key, list(range(32)))
+        self.assertEqual(decoded.published_streams, {1, 2})
+        self.assertEqual(decoded.secrets, {"security.passphrase": "env:SABER_PASSPHRASE", "mqtt.password": None})
+        self.assertEqual(decoded.zones, {"sink-1": "sala"})
+        self.assertEqual(decoded.peers[0].node_id, "sink-1")
+        self.assertEqual(decoded.peers[0].public_key, [7] * 32)
+
+    def test_control_characters_roundtrip(self):
+        """Caratteri di controllo e virgolette sopravvivono alla codifica in entrambi i versi"""
+        state = master_state()
+        label = "sala\t1\r\n\b\f\x01\x1f \"citata\" \\ è"
+        state.zones = {"sink-1": label}
+        encoded = encode_node_state(state)
+        # Il documento è JSON valido anche per un lettore esterno
+        self.assertEqual(json.loads(encoded)["zones"]["sink-1"], label)
+        self.assertEqual(decode_node_state(encoded).zones, {"sink-1": label})
+
+        # Anche le sequenze \uXXXX, comprese le coppie surrogate, vengono rilette
+        document = json.loads(encoded)
+        document["zones"]["sink-1"] = label + " \U0001F3B5"
+        decoded = decode_node_state(json.dumps(document, ensure_ascii=True))
+        self.assertEqual(decoded.zones, {"sink-1": label + " \U0001F3B5"})
+
+    def test_key_ids(self):
+        """Le chiavi sono accompagnate dal key-ID, verificato alla lettura"""
+        document = json.loads(encode_node_state(master_state()))
+        self.assertEqual(len(document["identity"]["key_id"]), 16)
+        document["peers"][0]["key_id"] = "0000000000000000"
+        with self.assertRaises(ValueError):
+            decode_node_state(json.dumps(document))
+
+    def test_newer_version_refused(self):
+        """Un documento di una versione successiva viene rifiutato"""
+        document = json.loads(encode_node_state(master_state()))
+        document["version"] = NODE_STATE_VERSION + 1
+        with self.assertRaises(ValueError):
+            decode_node_state(json.dumps(document))
+
+    def test_plain_secret_refused(self):
+        """Un segreto in chiaro non è un riferimento valido"""
+        document = json.loads(encode_node_state(master_state()))
+        document["secrets"]["security.passphrase"] = "segreto"
+        with self.assertRaises(ValueError):
+            decode_node_state(json.dumps(document))
+
+    def test_malformed(self):
+        """Documenti malformati o di altro formato vengono rifiutati"""
+        for text in ["", "{", "[]", '{"format":"altro","version":1}']:
+            with self.assertRaises(ValueError):
+                decode_node_state(text)
+
+class TestMigration(unittest.TestCase):
+    """Test per la migrazione dello stato tra due istanze"""
+
+    def test_import_then_export(self):
+        """Lo stato importato prima dell'avvio viene applicato alla configurazione"""
+        os.environ["SABER_PASSPHRASE"] = "passphrase-di-prova"
+        protocol = SaberProtocol(SaberConfig.default_config())
+        protocol.import_state(encode_node_state(master_state()))
+        exported = decode_node_state(protocol.export_state())
+        self.assertEqual(exported.node_id, "master-1")
+        self.assertEqual(exported.role, NodeRole.Master)
+        self.assertEqual(exported.profile, "home-music")
+        self.assertEqual(exported.latency_mode, LatencyMode.BestEffort)
+        self.assertEqual(exported.secrets, {"security.passphrase": "env:SABER_PASSPHRASE"})
+        self.assertEqual([peer.node_id for peer in exported.peers], ["sink-1"])
+
+    def test_unresolvable_secret(self):
+        """Un riferimento non risolvibile lascia invariata la configurazione"""
+        state = master_state()
+        state.secrets = {"security.passphrase": "env:SABER_VARIABILE_ASSENTE"}
+        protocol = SaberProtocol(SaberConfig.default_config())
+        before = protocol.export_state()
+        with self.assertRaises(RuntimeError):
+            protocol.import_state(encode_node_state(state))
+        self.assertEqual(json.loads(protocol.export_state())["identity"],
+                         json.loads(before)["identity"])
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,144 @@
+# Test unitari per i contatori persistenti dei nonce
+# Verifica che dopo un riavvio i nonce riprendano oltre gli ultimi usati con la stessa chiave
+
+import os
+import shutil
+import sys
+import tempfile
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    import saber_protocol
+    from saber_protocol import MeshCrypto, NonceCounterStore
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+NETWORK_KEY = [7] * 32
+
+def nonce_counter(blob):
+    """Contatore little-endian dopo il prefisso di 4 byte del nonce"""
+    return int.from_bytes(blob[4:12], "little")
+
+class TestNonceCounterStore(unittest.TestCase):
+    """Test per l'archivio dei limiti riservati"""
+
+    def setUp(self):
+        self.directory = tempfile.mkdtemp()
+        self.path = os.path.join(self.directory, "nonces")
+
+    def tearDown(self):
+        shutil.rmtree(self.directory)
+
+    def test_reserve(self):
+        """Ogni riserva parte dal limite della precedente e resta valida dopo la riapertura"""
+        store = NonceCounterStore(self.path, 4)
+        first = store.reserve("k1")
+        second = store.reserve("k1")
+        self.assertEqual((first.first, first.limit), (1, 5))
+        self.assertEqual((second.first, second.limit), (5, 9))
+        self.assertEqual(first.salt, second.salt)
+        reopened = NonceCounterStore(self.path, 4)
+        self.assertEqual(reopened.get_limit("k1"), 9)
+        self.assertEqual(reopened.reserve("k1").first, 9)
+        self.assertIsNone(reopened.get_limit("k2"))
+
+    def test_malformed(self):
+        """Un file illeggibile non fa ripartire i contatori da zero"""
+        with open(self.path, "w") as file:
+            file.write("k1 zz 10\n")
+        with self.assertRaises(RuntimeError):
+            NonceCounterStore(self.path)
+
+    def test_unwritable(self):
+        """Una riserva che non può essere salvata non viene concessa"""
+        store = NonceCounterStore(os.path.join(self.directory, "missing", "nonces"))
+        with self.assertRaises(RuntimeError):
+            store.reserve("k1")
+
+class TestPersistentNonces(unittest.TestCase):
+    """Test per i nonce generati con l'archivio"""
+
+    def setUp(self):
+        self.directory = tempfile.mkdtemp()
+        self.path = os.path.join(self.directory, "nonces")
+
+    def tearDown(self):
+        shutil.rmtree(self.directory)
+
+    def start(self, now_ms=None):
+        crypto = MeshCrypto.with_network_key(NETWORK_KEY)
+        if now_ms is not None:
+            crypto.set_clock(lambda: now_ms)
+        crypto.set_nonce_store(NonceCounterStore(self.path, 4))
+        return crypto
+
+    def test_restart(self):
+        """Il nodo riavviato prosegue prefisso e contatore, e il ricevitore lo accetta"""
+        receiver = MeshCrypto.with_network_key(NETWORK_KEY)
+        sender = self.start()
+        for _ in range(10):
+            last = sender.encrypt(b"frame")
+            receiver.decrypt_from("sink-1", last)
+        self.assertEqual(nonce_counter(last), 10)
+        restarted = self.start()
+        blob = restarted.encrypt(b"frame")
+        self.assertEqual(blob[:4], last[:4])
+        self.assertGreater(nonce_counter(blob), 10)
+        self.assertEqual(receiver.decrypt_from("sink-1", blob), b"frame")
+
+    def test_clock_regression(self):
+        """Un orologio che torna indietro tra un avvio e l'altro non fa riusare né arretrare i nonce"""
+        receiver = MeshCrypto.with_network_key(NETWORK_KEY)
+        seen = set()
+        last = 0
+        for now_ms in (1700000000000, 1700000000000 - 3600 * 1000, 0):
+            sender = self.start(now_ms)
+            for _ in range(6):
+                blob = sender.encrypt(b"frame")
+                self.assertNotIn(blob[:12], seen)
+                self.assertGreater(nonce_counter(blob), last)
+                seen.add(blob[:12])
+                last = nonce_counter(blob)
+                self.assertEqual(receiver.decrypt_from("sink-1", blob), b"frame")
+
+    def test_key_change(self):
+        """Tornando ad una chiave già usata i contatori riprendono oltre i precedenti"""
+        crypto = self.start()
+        before = nonce_counter(crypto.encrypt(b"frame"))
+        crypto.set_network_key([9] * 32)
+        self.assertEqual(nonce_counter(crypto.encrypt(b"frame")), 1)
+        crypto.set_network_key(NETWORK_KEY)
+        self.assertGreater(nonce_counter(crypto.encrypt(b"frame")), before)
+        self.assertEqual(crypto.get_nonce_store().get_path(), self.path)
+
+    @unittest.skipUnless(hasattr(saber_protocol, "SeededRandomSource"), "compilato senza SABER_ENABLE_SEEDED_RNG")
+    def test_seeded_restart(self):
+        """Con una sorgente deterministica solo l'archivio evita il riuso del nonce"""
+        def crypto(store):
+            instance = MeshCrypto(saber_protocol.SeededRandomSource(7))
+            instance.set_network_key(NETWORK_KEY)
+            if store:
+                instance.set_nonce_store(NonceCounterStore(self.path, 4))
+            return instance
+        self.assertEqual(crypto(False).encrypt(b"frame")[:12], crypto(False).encrypt(b"frame")[:12])
+        self.assertNotEqual(crypto(True).encrypt(b"frame")[:12], crypto(True).encrypt(b"frame")[:12])
+
+class TestNonceConfig(ConfigFileTestCase):
+    """Test per la configurazione letta dal file"""
+
+    HEADER = '[node]\nid = "master-1"\nrole = "master"\n\n'
+
+    def test_values(self):
+        """Senza security.nonce_file i contatori non vengono persistiti"""
+        self.assertIsNone(self.load('').nonce_file)
+        self.assertEqual(self.load('[security]\nnonce_file = "/var/lib/saber/nonces"\n').nonce_file,
+                         "/var/lib/saber/nonces")
+
+if __name__ == "__main__":
+    unittest.main()
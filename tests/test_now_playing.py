@@ -0,0 +1,105 @@
+# Test unitari per i metadati del brano in riproduzione
+# Verifica revisioni, hash delle copertine e il loro recupero da parte dei sink
+
+import hashlib
+import os
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import NodeRole, SaberConfig, SaberProtocol, SimNetwork, StreamMetadata
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+ARTWORK = bytes(range(256)) * 4
+
+def track(title, stream_id=1):
+    metadata = StreamMetadata()
+    metadata.stream_id = stream_id
+    metadata.title = title
+    metadata.artist = "Artista"
+    metadata.album = "Album"
+    return metadata
+
+class TestNowPlaying(unittest.TestCase):
+    """Test per la pubblicazione e la ricezione dei metadati"""
+
+    def start(self, node_id, role, network):
+        config = SaberConfig.default_config()
+        config.node_id = node_id
+        config.role = role
+        protocol = SaberProtocol(config)
+        protocol.set_sim_network(network)
+        if not protocol.initialize():
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        self.addCleanup(protocol.shutdown)
+        return protocol
+
+    def wait_for(self, network, condition, action=None):
+        deadline = time.monotonic() + 5.0
+        while time.monotonic() < deadline:
+            if action:
+                action()
+            network.advance(50)
+            if condition():
+                return True
+            time.sleep(0.05)
+        return condition()
+
+    def test_without_network(self):
+        """Senza rete mesh i metadati non vengono pubblicati"""
+        protocol = SaberProtocol(SaberConfig.default_config())
+        self.assertFalse(protocol.publish_stream_metadata(track("Brano")))
+        self.assertIsNone(protocol.get_stream_metadata(1))
+        self.assertFalse(protocol.request_artwork("00"))
+
+    def test_revisions_and_hash(self):
+        """Ogni pubblicazione incrementa la revisione e la copertina è indicizzata per SHA-256"""
+        master = self.start("np-master", NodeRole.Master, SimNetwork(1))
+        self.assertTrue(master.publish_stream_metadata(track("Primo")))
+        first = master.get_stream_metadata(1)
+        self.assertEqual(first.revision, 1)
+        self.assertEqual(first.artwork_hash, "")
+
+        self.assertTrue(master.publish_stream_metadata(track("Secondo"), list(ARTWORK)))
+        second = master.get_stream_metadata(1)
+        self.assertEqual(second.revision, 2)
+        self.assertEqual(second.title, "Secondo")
+        self.assertEqual(second.artwork_hash, hashlib.sha256(ARTWORK).hexdigest())
+        self.assertEqual(master.get_artwork(second.artwork_hash), ARTWORK)
+        self.assertIsNone(master.get_artwork("ff" * 32))
+        self.assertIsNone(master.get_stream_metadata(2))
+
+        now_playing = master.get_node_info()["now_playing"]
+        self.assertEqual(now_playing[1]["title"], "Secondo")
+        self.assertEqual(now_playing[1]["revision"], 2)
+
+    def test_sink_fetches_artwork(self):
+        """Il sink riceve i metadati e recupera la copertina dall'hash annunciato"""
+        network = SimNetwork(1)
+        master = self.start("np-master", NodeRole.Master, network)
+        sink = self.start("np-sink", NodeRole.Sink, network)
+
+        # La pubblicazione viene ripetuta finché il sink non conosce la chiave del Master
+        self.assertTrue(self.wait_for(network, lambda: sink.get_stream_metadata(1) is not None,
+                                      lambda: master.publish_stream_metadata(track("Brano"), list(ARTWORK))))
+        self.assertTrue(self.wait_for(network, lambda: sink.get_stream_metadata(1).revision
+                                      == master.get_stream_metadata(1).revision))
+        received = sink.get_stream_metadata(1)
+        self.assertEqual((received.title, received.artist, received.album), ("Brano", "Artista", "Album"))
+        self.assertEqual(received.artwork_hash, hashlib.sha256(ARTWORK).hexdigest())
+
+        # La copertina non viaggia con i metadati: va richiesta
+        self.assertIsNone(sink.get_artwork(received.artwork_hash))
+        self.assertTrue(self.wait_for(network, lambda: sink.get_artwork(received.artwork_hash) is not None,
+                                      lambda: sink.request_artwork(received.artwork_hash)))
+        self.assertEqual(sink.get_artwork(received.artwork_hash), ARTWORK)
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,147 @@
+# Test unitari per l'autenticazione dei pacchetti mesh del protocollo SABER
+# Verifica che la firma copra l'intestazione oltre al contenuto e la cifratura dei pacchetti
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import MeshCrypto, MeshPacket
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+NETWORK_KEY = [7] * 32
+
+class TestPacketAuthentication(unittest.TestCase):
+    """Test per la firma dei pacchetti sull'intera intestazione"""
+    
+    def setUp(self):
+        """Crea un mittente e un ricevitore che conosce la sua chiave"""
+        self.sender = MeshCrypto()
+        self.receiver = MeshCrypto()
+        self.receiver.register_node_key("master-1", self.sender.get_public_key())
+        
+        self.packet = MeshPacket.create_command("volume", {"level": "50"})
+        self.packet.set_header("master-1", 42, 8)
+        self.packet.sign(self.sender)
+    
+    def test_valid_signature(self):
+        """Un pacchetto non modificato viene accettato"""
+        self.assertTrue(self.packet.verify_signature(self.receiver))
+    
+    def test_ttl_decrement_keeps_signature(self):
+        """Il decremento del TTL durante l'inoltro non invalida la firma"""
+        self.assertTrue(self.packet.decrement_ttl())
+        self.assertEqual(self.packet.get_ttl(), 7)
+        self.assertEqual(self.packet.get_origin_ttl(), 8)
+        self.assertTrue(self.packet.verify_signature(self.receiver))
+    
+    def test_tampered_sequence_rejected(self):
+        """Un Repeater non può alterare il numero di sequenza"""
+        self.packet.set_header("master-1", 43, 8)
+        self.assertFalse(self.packet.verify_signature(self.receiver))
+    
+    def test_tampered_origin_ttl_rejected(self):
+        """Un Repeater non può aumentare il TTL all'origine"""
+        self.packet.set_header("master-1", 42, 16)
+        self.assertFalse(self.packet.verify_signature(self.receiver))
+    
+    def test_raised_ttl_rejected(self):
+        """Un Repeater non può riportare il TTL sopra quello d'origine"""
+        encoded = bytearray(self.packet.encode())
+        # Versione, tipo, sorgente (lunghezza e byte), sequenza e TTL d'origine precedono il TTL
+        offset = 1 + 1 + 2 + len("master-1") + 4 + 1
+        self.assertEqual(encoded[offset], 8)
+        encoded[offset] = 9
+        with self.assertRaises(ValueError):
+            MeshPacket.decode(bytes(encoded))
+    
+    def test_tampered_source_rejected(self):
+        """Un pacchetto riattribuito ad un altro nodo viene rifiutato"""
+        other = MeshCrypto()
+        self.receiver.register_node_key("master-2", other.get_public_key())
+        self.packet.set_header("master-2", 42, 8)
+        self.assertFalse(self.packet.verify_signature(self.receiver))
+    
+    def test_unsigned_packet_rejected(self):
+        """Un pacchetto senza firma non è considerato autentico"""
+        packet = MeshPacket.create_time_beacon(123456)
+        packet.set_header("master-1", 1, 8)
+        self.assertFalse(packet.is_signed())
+        self.assertFalse(packet.verify_signature(self.receiver))
+    
+    def test_signature_not_transferable(self):
+        """La firma di un pacchetto non vale per un contenuto diverso"""
+        forged = MeshPacket.create_command("volume", {"level": "100"})
+        forged.set_header("master-1", 42, 8)
+        self.assertFalse(forged.is_signed())
+        self.assertNotEqual(forged.signing_bytes(), self.packet.signing_bytes())
+
+class TestPacketEncryption(unittest.TestCase):
+    """Test per la cifratura autenticata dei pacchetti (sign-then-encrypt)"""
+    
+    def setUp(self):
+        """Crea mittente e ricevitore con la stessa chiave di rete"""
+        self.sender = MeshCrypto.with_network_key(NETWORK_KEY)
+        self.receiver = MeshCrypto.with_network_key(NETWORK_KEY)
+        self.receiver.register_node_key("master-1", self.sender.get_public_key())
+        
+        packet = MeshPacket.create_command("volume", {"level": "50"})
+        packet.set_header("master-1", 42, 8)
+        packet.sign(self.sender)
+        packet.seal(self.sender)
+        self.encoded = packet.encode()
+    
+    def test_roundtrip(self):
+        """Il ricevitore decifra il pacchetto e ne verifica la firma"""
+        received = MeshPacket.decode(self.encoded)
+        self.assertTrue(received.is_encrypted())
+        opened = received.open(self.receiver)
+        self.assertEqual(opened.get_source(), "master-1")
+        self.assertEqual(opened.get_sequence(), 42)
+        self.assertTrue(opened.verify_signature(self.receiver))
+        self.assertEqual(opened.encode(), self.encoded)
+    
+    def test_content_not_readable(self):
+        """Contenuto e firma non compaiono in chiaro sul collegamento"""
+        self.assertNotIn(b"volume", self.encoded)
+    
+    def test_ttl_decrement_keeps_blob(self):
+        """Un Repeater decrementa il TTL senza dover cifrare di nuovo"""
+        received = MeshPacket.decode(self.encoded)
+        self.assertTrue(received.decrement_ttl())
+        self.assertTrue(received.open(self.receiver).verify_signature(self.receiver))
+    
+    def test_wrong_network_key(self):
+        """Un nodo con un'altra chiave di rete non apre il pacchetto"""
+        with self.assertRaises(RuntimeError):
+            MeshPacket.decode(self.encoded).open(MeshCrypto.with_network_key([9] * 32))
+    
+    def test_source_bound(self):
+        """Il blob non può essere attribuito ad un'altra sorgente"""
+        received = MeshPacket.decode(self.encoded)
+        received.set_header("master-2", 42, 8)
+        with self.assertRaises(RuntimeError):
+            received.open(self.receiver)
+    
+    def test_replay_rejected(self):
+        """Lo stesso pacchetto cifrato viene aperto una sola volta"""
+        MeshPacket.decode(self.encoded).open(self.receiver)
+        with self.assertRaises(RuntimeError):
+            MeshPacket.decode(self.encoded).open(self.receiver)
+    
+    def test_plain_packet_not_opened(self):
+        """Un pacchetto in chiaro non ha nulla da decifrare"""
+        packet = MeshPacket.create_time_beacon(123456)
+        packet.set_header("master-1", 1, 8)
+        self.assertFalse(packet.is_encrypted())
+        with self.assertRaises(RuntimeError):
+            packet.open(self.receiver)
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,80 @@
+# Test unitari per il TTL e la registrazione del percorso dei pacchetti mesh
+# Verifica il conteggio degli hop, il rilevamento dei cicli e la compatibilità del formato
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import MeshPacket, short_node_id
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+SOURCE = "master-1"
+
+class TestPacketPath(unittest.TestCase):
+    """Test per il percorso registrato nei pacchetti"""
+
+    def audio(self, record=True):
+        packet = MeshPacket.create_audio(3, 77, 1700000000123456, [1, 2, 3])
+        packet.set_header(SOURCE, 9, 4)
+        packet.set_path_recording(record)
+        return packet
+
+    def test_hop_count(self):
+        """Il numero di hop è la differenza tra TTL all'origine e TTL residuo"""
+        packet = self.audio()
+        self.assertEqual(packet.get_hop_count(), 0)
+        self.assertTrue(packet.decrement_ttl())
+        self.assertTrue(packet.decrement_ttl())
+        self.assertEqual(packet.get_hop_count(), 2)
+
+    def test_path_roundtrip(self):
+        """Il percorso registrato sopravvive alla codifica"""
+        packet = self.audio()
+        self.assertTrue(packet.append_hop(short_node_id("rep-1")))
+        self.assertTrue(packet.append_hop(short_node_id("rep-2")))
+        decoded = MeshPacket.decode(packet.encode())
+        self.assertTrue(decoded.is_path_recording())
+        self.assertEqual(decoded.get_path(), [short_node_id("rep-1"), short_node_id("rep-2")])
+        self.assertEqual(decoded.encode(), packet.encode())
+
+    def test_loop_detected(self):
+        """Un nodo già presente nel percorso segnala un ciclo"""
+        packet = self.audio()
+        self.assertTrue(packet.append_hop(short_node_id("rep-1")))
+        self.assertFalse(packet.append_hop(short_node_id("rep-1")))
+        self.assertEqual(packet.get_path(), [short_node_id("rep-1")])
+
+    def test_path_disabled(self):
+        """Senza registrazione il percorso resta vuoto e costa un solo byte"""
+        recorded = self.audio()
+        plain = self.audio(record=False)
+        self.assertEqual(plain.encoded_size() + 1, recorded.encoded_size())
+        self.assertFalse(MeshPacket.decode(plain.encode()).is_path_recording())
+
+    def test_version_one_decodes(self):
+        """I pacchetti del formato precedente, senza flag, sono ancora accettati"""
+        encoded = bytearray(self.audio(record=False).encode())
+        flags = 10 + len(SOURCE)
+        self.assertEqual(encoded[flags], 0)
+        del encoded[flags]
+        encoded[0] = 1
+        decoded = MeshPacket.decode(bytes(encoded))
+        self.assertEqual(decoded.get_source(), SOURCE)
+        self.assertEqual(decoded.get_path(), [])
+
+    def test_unknown_flags_rejected(self):
+        """Un bit di flag sconosciuto rende il pacchetto non valido"""
+        encoded = bytearray(self.audio(record=False).encode())
+        encoded[10 + len(SOURCE)] = 0x80
+        with self.assertRaises(ValueError):
+            MeshPacket.decode(bytes(encoded))
+
+if __name__ == "__main__":
+    unittest.main()
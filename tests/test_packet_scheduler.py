@@ -0,0 +1,97 @@
+# Test unitari per le code dei pacchetti per classe di priorità
+# Verifica la classificazione dei pacchetti, l'ordine di estrazione e lo scarto a coda piena
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (MeshPacket, MeshPacketType, PacketClass, PacketScheduler, SaberConfig, SaberProtocol,
+                                packet_class_from_string, packet_class_of, packet_class_to_string)
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def drain(scheduler):
+    order = []
+    packet = scheduler.pop()
+    while packet is not None:
+        order.append(packet_class_of(packet.get_type()))
+        packet = scheduler.pop()
+    return order
+
+class TestPacketClass(unittest.TestCase):
+    """Test per la classificazione dei pacchetti"""
+
+    def test_classes(self):
+        """Sincronizzazione, audio, stato e comandi finiscono nelle rispettive classi"""
+        self.assertEqual(packet_class_of(MeshPacketType.TimeBeacon), PacketClass.Sync)
+        self.assertEqual(packet_class_of(MeshPacketType.EmergencySync), PacketClass.Sync)
+        self.assertEqual(packet_class_of(MeshPacketType.Audio), PacketClass.Audio)
+        self.assertEqual(packet_class_of(MeshPacketType.Nack), PacketClass.Audio)
+        self.assertEqual(packet_class_of(MeshPacketType.Status), PacketClass.Status)
+        self.assertEqual(packet_class_of(MeshPacketType.Ping), PacketClass.Status)
+        self.assertEqual(packet_class_of(MeshPacketType.Command), PacketClass.Command)
+        self.assertEqual(packet_class_of(MeshPacketType.Artwork), PacketClass.Command)
+
+    def test_names(self):
+        """I nomi delle classi"""
+        for packet_class in (PacketClass.Sync, PacketClass.Audio, PacketClass.Status, PacketClass.Command):
+            self.assertEqual(packet_class_from_string(packet_class_to_string(packet_class)), packet_class)
+        self.assertIsNone(packet_class_from_string("bulk"))
+
+class TestPacketScheduler(unittest.TestCase):
+    """Test per l'ordine di estrazione"""
+
+    def test_sync_first(self):
+        """La sincronizzazione accodata per ultima esce per prima"""
+        scheduler = PacketScheduler()
+        for index in range(10):
+            scheduler.push(MeshPacket.create_command("cmd", {"i": str(index)}))
+            scheduler.push(MeshPacket.create_status("sink-1", 50, 10))
+        scheduler.push(MeshPacket.create_time_beacon(1000))
+        self.assertEqual(scheduler.size(), 21)
+        self.assertEqual(scheduler.pop().get_type(), MeshPacketType.TimeBeacon)
+
+    def test_weights(self):
+        """Stato e comandi si alternano senza che i comandi restino bloccati"""
+        scheduler = PacketScheduler()
+        for _ in range(6):
+            scheduler.push(MeshPacket.create_status("sink-1", 50, 10))
+            scheduler.push(MeshPacket.create_command("cmd", {}))
+        order = drain(scheduler)
+        self.assertEqual(order[:6], [PacketClass.Status, PacketClass.Status, PacketClass.Command] * 2)
+        self.assertEqual(len(order), 12)
+        self.assertTrue(scheduler.empty())
+
+    def test_capacity(self):
+        """A coda piena si scarta il pacchetto più vecchio della stessa classe"""
+        scheduler = PacketScheduler(2)
+        self.assertTrue(scheduler.push(MeshPacket.create_command("first", {})))
+        self.assertTrue(scheduler.push(MeshPacket.create_command("second", {})))
+        self.assertFalse(scheduler.push(MeshPacket.create_command("third", {})))
+        # Le altre classi non ne risentono
+        self.assertTrue(scheduler.push(MeshPacket.create_status("sink-1", 50, 10)))
+
+        stats = scheduler.get_stats()
+        self.assertEqual(stats[PacketClass.Command].queued, 2)
+        self.assertEqual(stats[PacketClass.Command].dropped, 1)
+        self.assertEqual(stats[PacketClass.Status].dropped, 0)
+
+        drain(scheduler)
+        stats = scheduler.get_stats()
+        self.assertEqual(stats[PacketClass.Command].dequeued, 2)
+        self.assertEqual(stats[PacketClass.Command].peak, 2)
+        self.assertEqual(stats[PacketClass.Command].queued, 0)
+
+    def test_protocol(self):
+        """Senza rete il protocollo non ha code"""
+        protocol = SaberProtocol(SaberConfig.default_config())
+        self.assertEqual(protocol.get_packet_queue_stats(), {})
+
+if __name__ == "__main__":
+    unittest.main()
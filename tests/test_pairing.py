@@ -0,0 +1,153 @@
+# Test unitari per l'abbinamento dei nuovi nodi con PIN
+# Verifica lo scambio tra nuovo nodo e Master, il rifiuto dei PIN errati e i codici di abbinamento
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (MeshCrypto, NodeRole, OsRandomSource, PAIRING_MAX_FAILURES, PAIRING_PIN_DIGITS,
+                                PairingJoiner, PairingResponder, PairingState, SaberConfig, SaberProtocol,
+                                generate_pairing_pin, pairing_state_to_string, pairing_uri, parse_pairing_code)
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestPairingHandshake(unittest.TestCase):
+    """Test per lo scambio tra nuovo nodo e Master"""
+
+    def setUp(self):
+        self.master = MeshCrypto()
+        self.node = MeshCrypto()
+        self.responder = PairingResponder("master-1", self.master.get_public_key(), "123456")
+
+    def pair(self, pin):
+        joiner = PairingJoiner("sink-1", NodeRole.Sink, self.node.get_public_key(), pin)
+        challenge = self.responder.challenge("sink-1", joiner.hello())
+        self.assertIsNotNone(challenge)
+        proof = joiner.respond("master-1", challenge)
+        self.assertIsNotNone(proof)
+        return joiner, self.responder.verify("sink-1", proof)
+
+    def test_round_trip(self):
+        """Con il PIN giusto il nuovo nodo riceve chiave di rete, token e identità del Master"""
+        joiner, request = self.pair("123456")
+        self.assertEqual(request.node_id, "sink-1")
+        self.assertEqual(request.role, NodeRole.Sink)
+        self.assertEqual(request.public_key, self.node.get_public_key())
+
+        token = self.master.generate_security_token("sink-1", 60)
+        welcome = self.responder.welcome("sink-1", self.master.get_network_key(), token)
+        credentials = joiner.complete(welcome)
+        self.assertEqual(credentials.master_id, "master-1")
+        self.assertEqual(credentials.master_public_key, self.master.get_public_key())
+        self.assertEqual(credentials.network_key, self.master.get_network_key())
+        self.assertEqual(credentials.token, token)
+
+        # Con la chiave ricevuta il nuovo nodo legge i pacchetti della rete
+        self.node.set_network_key(credentials.network_key)
+        self.assertEqual(self.node.decrypt(self.master.encrypt([1, 2, 3])), [1, 2, 3])
+
+    def test_wrong_pin(self):
+        """Un PIN errato viene rifiutato e conta tra i fallimenti"""
+        _, request = self.pair("654321")
+        self.assertIsNone(request)
+        self.assertEqual(self.responder.get_failures(), 1)
+        self.assertIsNone(self.responder.welcome("sink-1", self.master.get_network_key(), []))
+
+    def test_exhausted(self):
+        """Dopo troppe prove errate il PIN non è più accettato, nemmeno quello giusto"""
+        for _ in range(PAIRING_MAX_FAILURES):
+            self.pair("000000")
+        self.assertTrue(self.responder.is_exhausted())
+        joiner = PairingJoiner("sink-1", NodeRole.Sink, self.node.get_public_key(), "123456")
+        self.assertIsNone(self.responder.challenge("sink-1", joiner.hello()))
+
+    def test_unanswered_challenges(self):
+        """Le sfide senza risposta consumano i tentativi come le prove errate"""
+        for attempt in range(PAIRING_MAX_FAILURES):
+            joiner = PairingJoiner("sink-%d" % attempt, NodeRole.Sink, self.node.get_public_key(), "000000")
+            self.assertIsNotNone(self.responder.challenge("sink-%d" % attempt, joiner.hello()))
+            self.assertTrue(self.responder.is_pending("sink-%d" % attempt))
+        self.assertEqual(self.responder.get_failures(), PAIRING_MAX_FAILURES)
+        self.assertTrue(self.responder.is_exhausted())
+        joiner = PairingJoiner("sink-9", NodeRole.Sink, self.node.get_public_key(), "123456")
+        self.assertIsNone(self.responder.challenge("sink-9", joiner.hello()))
+
+    def test_success_refunds_attempt(self):
+        """La prova giusta non lascia il tentativo tra i fallimenti"""
+        _, request = self.pair("123456")
+        self.assertIsNotNone(request)
+        self.assertFalse(self.responder.is_pending("sink-1"))
+        self.assertEqual(self.responder.get_failures(), 0)
+
+    def test_wrong_pin_reveals_nothing(self):
+        """Uno scambio osservato non permette di verificare un PIN offline"""
+        joiner = PairingJoiner("sink-1", NodeRole.Sink, self.node.get_public_key(), "123456")
+        hello = joiner.hello()
+        challenge = self.responder.challenge("sink-1", hello)
+        proof = joiner.respond("master-1", challenge)
+        self.assertIsNotNone(proof)
+
+        for guess in ["%06d" % value for value in range(0, 1000000, 9973)] + ["123456"]:
+            # Ogni PIN accetta la sfida osservata: nessun PIN viene escluso da messaggi malformati
+            attacker = PairingJoiner("sink-1", NodeRole.Sink, self.node.get_public_key(), guess)
+            attempt = attacker.respond("master-1", challenge)
+            self.assertIsNotNone(attempt, guess)
+            # Senza gli scalari effimeri nemmeno il PIN giusto riproduce la prova osservata
+            self.assertNotEqual(attempt, proof, guess)
+
+            # L'Hello osservato è un punto valido anche per un Master con un PIN qualsiasi
+            master = PairingResponder("master-1", self.master.get_public_key(), guess)
+            self.assertIsNotNone(master.challenge("sink-1", hello), guess)
+
+    def test_malformed(self):
+        """Messaggi troncati vengono scartati senza contare come prove"""
+        self.assertIsNone(self.responder.challenge("sink-1", [0] * 10))
+        self.assertIsNone(self.responder.verify("sink-1", [0] * 32))
+        self.assertEqual(self.responder.get_failures(), 0)
+
+class TestPairingCode(unittest.TestCase):
+    """Test per i PIN e i codici mostrati come QR"""
+
+    def test_pin(self):
+        """Il PIN ha sempre lo stesso numero di cifre"""
+        rng = OsRandomSource()
+        for _ in range(20):
+            pin = generate_pairing_pin(rng)
+            self.assertEqual(len(pin), PAIRING_PIN_DIGITS)
+            self.assertTrue(pin.isdigit())
+
+    def test_uri(self):
+        """Il codice del QR indica il Master ed il PIN"""
+        uri = pairing_uri("master-1", "012345")
+        self.assertEqual(uri, "saber://pair?master=master-1&pin=012345")
+        code = parse_pairing_code(uri)
+        self.assertEqual((code.pin, code.master_id), ("012345", "master-1"))
+        code = parse_pairing_code("012345")
+        self.assertEqual((code.pin, code.master_id), ("012345", ""))
+
+    def test_invalid(self):
+        """Codici senza un PIN valido vengono rifiutati"""
+        for invalid in ("", "12345", "12345a", "1234567", "saber://pair?master=m", "http://pair?pin=123456"):
+            self.assertIsNone(parse_pairing_code(invalid), invalid)
+
+class TestPairingProtocol(unittest.TestCase):
+    """Test per l'abbinamento gestito dal protocollo"""
+
+    def test_without_network(self):
+        """Senza rete l'abbinamento non si apre né si avvia"""
+        protocol = SaberProtocol(SaberConfig.default_config())
+        self.assertIsNone(protocol.open_pairing(60))
+        self.assertFalse(protocol.start_pairing("123456"))
+        self.assertFalse(protocol.close_pairing())
+        status = protocol.get_pairing_status()
+        self.assertEqual(status.state, PairingState.Idle)
+        self.assertEqual(pairing_state_to_string(status.state), "idle")
+
+if __name__ == "__main__":
+    unittest.main()
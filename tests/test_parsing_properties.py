@@ -0,0 +1,166 @@
+# Test di proprietà per la decodifica di token, pacchetti mesh e pacchetti audio
+# Dati arbitrari o alterati vengono interpretati o rifiutati con l'eccezione prevista, mai altro
+
+import os
+import random
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import AUDIO_PACKET_HEADER_BYTES, AudioPacket, MeshCrypto, MeshPacket, TokenScope
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+# Chiave di rete condivisa dai nodi del test
+NETWORK_KEY = list(range(32))
+
+# Lunghezza della firma Ed25519 in coda al token
+SIGNATURE_BYTES = 64
+
+# Lunghezza dell'intestazione del token: versione, ID, ambito, emissione e scadenza
+TOKEN_HEADER_BYTES = 1 + 16 + 1 + 8 + 8
+
+# Casi generati per ogni proprietà
+CASES = 1000
+
+def random_bytes(rng, max_length=96):
+    return bytes(rng.randrange(256) for _ in range(rng.randrange(max_length + 1)))
+
+def mutate(rng, data):
+    """Altera da uno a tre bit, poi a volte tronca o allunga"""
+    data = bytearray(data)
+    for _ in range(rng.randint(1, 3)):
+        if data:
+            data[rng.randrange(len(data))] ^= 1 << rng.randrange(8)
+    choice = rng.randrange(3)
+    if choice == 1:
+        del data[rng.randrange(len(data) + 1):]
+    elif choice == 2:
+        data += random_bytes(rng, 8)
+    return bytes(data)
+
+class TestTokenParsing(unittest.TestCase):
+    """Proprietà della decodifica dei token di sicurezza"""
+
+    def setUp(self):
+        self.crypto = MeshCrypto.with_network_key(NETWORK_KEY)
+        token = bytes(self.crypto.generate_security_token("node-1", 60, TokenScope.ReadOnly))
+        self.token = token
+        self.payload = self.crypto.decrypt(token)[:-SIGNATURE_BYTES]
+
+    def test_payload_round_trip(self):
+        """Il contenuto in chiaro di un token emesso si decodifica senza la firma"""
+        decoded = MeshCrypto.decode_security_token(self.payload)
+        self.assertEqual(decoded.node_id, "node-1")
+        self.assertEqual(decoded.scope, TokenScope.ReadOnly)
+        self.assertEqual(decoded.token_id, self.crypto.verify_security_token(self.token).token_id)
+
+    def test_truncated_payload(self):
+        """Ogni troncamento dell'intestazione viene rifiutato"""
+        for length in range(TOKEN_HEADER_BYTES):
+            with self.assertRaises(RuntimeError):
+                MeshCrypto.decode_security_token(self.payload[:length])
+
+    def test_random_payload(self):
+        """Contenuti arbitrari vengono decodificati o rifiutati con CryptoError"""
+        rng = random.Random(554)
+        for _ in range(CASES):
+            data = mutate(rng, self.payload) if rng.randrange(2) else random_bytes(rng)
+            try:
+                decoded = MeshCrypto.decode_security_token(data)
+            except RuntimeError:
+                continue
+            self.assertGreaterEqual(len(data), TOKEN_HEADER_BYTES)
+            self.assertEqual(len(decoded.token_id), 32)
+            self.assertIn(decoded.scope, (TokenScope.ReadOnly, TokenScope.Admin))
+
+    def test_random_token(self):
+        """Token arbitrari o alterati vengono sempre rifiutati"""
+        rng = random.Random(555)
+        for _ in range(CASES):
+            data = mutate(rng, self.token) if rng.randrange(2) else random_bytes(rng)
+            if data == self.token:
+                continue
+            with self.assertRaises(RuntimeError):
+                self.crypto.verify_security_token(data)
+
+    def test_encrypted_garbage(self):
+        """Dati cifrati con la chiave di rete ma senza firma valida vengono rifiutati"""
+        rng = random.Random(556)
+        for length in range(TOKEN_HEADER_BYTES + SIGNATURE_BYTES + 8):
+            data = bytes(rng.randrange(256) for _ in range(length))
+            with self.assertRaises(RuntimeError):
+                self.crypto.verify_security_token(self.crypto.encrypt(data))
+
+class TestMeshPacketParsing(unittest.TestCase):
+    """Proprietà della decodifica dei pacchetti mesh"""
+
+    def seeds(self):
+        command = MeshPacket.create_command("volume", {"level": "40"})
+        command.set_header("master-1", 7, 5)
+        audio = MeshPacket.create_audio(2, 11, 1700000000000000, [1, 2, 3])
+        audio.set_header("source-1", 8, 4)
+        audio.set_path_recording(True)
+        audio.append_hop(3)
+        return [command.encode(), audio.encode()]
+
+    def check(self, data):
+        try:
+            decoded = MeshPacket.decode(data)
+        except ValueError:
+            return
+        encoded = decoded.encode()
+        self.assertEqual(MeshPacket.decode(encoded).encode(), encoded)
+
+    def test_random_bytes(self):
+        """Dati arbitrari vengono decodificati in un pacchetto stabile o rifiutati"""
+        rng = random.Random(557)
+        for _ in range(CASES):
+            self.check(random_bytes(rng))
+
+    def test_mutated_packets(self):
+        """Pacchetti validi alterati vengono decodificati in un pacchetto stabile o rifiutati"""
+        rng = random.Random(558)
+        seeds = self.seeds()
+        for _ in range(CASES):
+            self.check(mutate(rng, rng.choice(seeds)))
+
+class TestAudioPacketParsing(unittest.TestCase):
+    """Proprietà della decodifica dei pacchetti audio"""
+
+    def setUp(self):
+        self.crypto = MeshCrypto.with_network_key(NETWORK_KEY)
+        self.sealed = AudioPacket(1, 42, 123456, b"lc3-frame").seal(self.crypto)
+
+    def test_header_length(self):
+        """Un'intestazione troncata viene rifiutata, una completa viene letta"""
+        for length in range(AUDIO_PACKET_HEADER_BYTES):
+            with self.assertRaises(IndexError):
+                AudioPacket.peek_header(self.sealed[:length])
+        self.assertEqual(AudioPacket.peek_header(self.sealed[:AUDIO_PACKET_HEADER_BYTES]).sequence, 42)
+
+    def test_random_bytes(self):
+        """Dati arbitrari vengono rifiutati senza uscire dal pacchetto"""
+        rng = random.Random(559)
+        for _ in range(CASES):
+            data = random_bytes(rng)
+            with self.assertRaises((IndexError, ValueError, RuntimeError)):
+                AudioPacket.open(data, self.crypto, "")
+
+    def test_mutated_packets(self):
+        """Un pacchetto alterato non si apre, anche quando l'intestazione resta leggibile"""
+        rng = random.Random(560)
+        for _ in range(CASES):
+            data = mutate(rng, self.sealed)
+            if data == self.sealed:
+                continue
+            with self.assertRaises((IndexError, ValueError, RuntimeError)):
+                AudioPacket.open(data, self.crypto, "")
+
+if __name__ == '__main__':
+    unittest.main()
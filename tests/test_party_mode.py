@@ -0,0 +1,142 @@
+# Test unitari per la modalità festa
+# Verifica l'unione delle zone su un solo stream con buffer comune, l'uscita e il comando "party"
+
+import os
+import socket
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import NodeRole, SaberConfig, SaberProtocol, SimNetwork, StreamMetadata, TokenScope
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+PARTY_STREAM = 5
+
+def free_port():
+    with socket.socket(socket.AF_INET, socket.SOCK_STREAM) as probe:
+        probe.bind(("127.0.0.1", 0))
+        return probe.getsockname()[1]
+
+class TestPartyMode(unittest.TestCase):
+    """Test per l'ingresso e l'uscita dalla modalità festa"""
+
+    def start(self, node_id, role, network, control_port=None):
+        config = SaberConfig.default_config()
+        config.node_id = node_id
+        config.role = role
+        config.start_barrier_lead_ms = 100
+        if control_port is not None:
+            config.control_port = control_port
+            config.control_bind_address = "127.0.0.1"
+        protocol = SaberProtocol(config)
+        protocol.set_sim_network(network)
+        if not protocol.initialize():
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        self.addCleanup(protocol.shutdown)
+        return protocol
+
+    def wait_for(self, network, condition, action=None):
+        deadline = time.monotonic() + 5.0
+        while time.monotonic() < deadline:
+            if action:
+                action()
+            network.advance(50)
+            if condition():
+                return True
+            time.sleep(0.05)
+        return condition()
+
+    def test_without_network(self):
+        """Senza rete mesh la modalità festa non può essere avviata"""
+        protocol = SaberProtocol(SaberConfig.default_config())
+        self.assertFalse(protocol.start_party_mode(["all"], PARTY_STREAM))
+        self.assertFalse(protocol.stop_party_mode())
+        self.assertIsNone(protocol.get_party_mode())
+        with self.assertRaises(ValueError):
+            protocol.start_party_mode([], PARTY_STREAM)
+
+    def test_master_start_and_stop(self):
+        """Il Master entra in modalità festa alla barriera e ne esce su richiesta"""
+        network = SimNetwork(1)
+        master = self.start("party-master", NodeRole.Master, network)
+        self.assertFalse(master.stop_party_mode())
+        self.assertTrue(master.start_party_mode(["sala", "cucina"], PARTY_STREAM, "festa"))
+
+        # Una seconda richiesta, anche prima della barriera, viene rifiutata
+        with self.assertRaises(ValueError):
+            master.start_party_mode(["all"], PARTY_STREAM)
+
+        self.assertTrue(self.wait_for(network, lambda: master.get_party_mode() is not None))
+        party = master.get_party_mode()
+        self.assertEqual(party.zones, ["sala", "cucina"])
+        self.assertEqual(party.stream_id, PARTY_STREAM)
+        self.assertEqual(party.playlist, "festa")
+        self.assertEqual(party.rejected, [])
+        self.assertGreaterEqual(party.buffer_ms, SaberConfig.default_config().spec.default_buffer_ms)
+        self.assertGreater(party.start_at_ms, 0)
+
+        self.assertTrue(master.stop_party_mode())
+        self.assertTrue(self.wait_for(network, lambda: master.get_party_mode() is None))
+        self.assertFalse(master.stop_party_mode())
+
+    def test_sink_follows(self):
+        """I sink di tutte le zone passano allo stream comune con il buffer scelto dal Master"""
+        network = SimNetwork(1)
+        master = self.start("party-master", NodeRole.Master, network)
+        sink = self.start("party-sink", NodeRole.Sink, network)
+
+        # I metadati vengono ripetuti finché il sink non conosce la chiave del Master
+        metadata = StreamMetadata()
+        metadata.stream_id = PARTY_STREAM
+        metadata.title = "Festa"
+        self.assertTrue(self.wait_for(network, lambda: sink.get_stream_metadata(PARTY_STREAM) is not None,
+                                      lambda: master.publish_stream_metadata(metadata)))
+
+        self.assertTrue(master.start_party_mode(["all"], PARTY_STREAM))
+        self.assertTrue(self.wait_for(network, lambda: sink.get_party_mode() is not None))
+        party = sink.get_party_mode()
+        self.assertEqual(party.stream_id, PARTY_STREAM)
+        self.assertEqual(party.buffer_ms, master.get_party_mode().buffer_ms)
+
+        self.assertTrue(master.stop_party_mode())
+        self.assertTrue(self.wait_for(network, lambda: sink.get_party_mode() is None))
+
+    def test_control_command(self):
+        """L'operatore avvia, interroga e termina la modalità festa dal socket di controllo"""
+        port = free_port()
+        network = SimNetwork(1)
+        master = self.start("party-master", NodeRole.Master, network, port)
+        try:
+            client = socket.create_connection(("127.0.0.1", port), timeout=5)
+        except OSError:
+            self.skipTest("socket di controllo non disponibile")
+        self.addCleanup(client.close)
+        lines = client.makefile("r")
+        self.addCleanup(lines.close)
+
+        def command(line):
+            client.sendall((line + "\n").encode())
+            return lines.readline().rstrip("\n")
+
+        token = master.issue_control_token(TokenScope.Admin, 60)
+        self.assertEqual(command("auth " + token), "ok admin")
+        self.assertEqual(command("party status"), "ok non attiva")
+        self.assertTrue(command("party stop").startswith("error"))
+        self.assertEqual(command("party start all %d" % PARTY_STREAM), "ok modalità festa per all tra 100ms")
+        self.assertTrue(command("party start all %d" % PARTY_STREAM).startswith("error"))
+
+        self.assertTrue(self.wait_for(network, lambda: master.get_party_mode() is not None))
+        self.assertTrue(command("party status").startswith("ok zone=all stream=%d buffer=" % PARTY_STREAM))
+        self.assertEqual(command("party stop"), "ok fine della modalità festa tra 100ms")
+        for line in ("party", "party start all", "party start all flusso"):
+            self.assertTrue(command(line).startswith("error"), line)
+
+if __name__ == "__main__":
+    unittest.main()
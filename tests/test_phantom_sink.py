@@ -0,0 +1,61 @@
+# Test unitari per il sink fantasma del protocollo SABER
+# Verifica il conteggio dei frame consumati e lo stato riportato al Master
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import PhantomSink
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+NOW_US = 1700000000 * 1000 * 1000
+
+class TestPhantomSink(unittest.TestCase):
+    """Test per il consumo simulato dei flussi"""
+
+    def test_counts_frames(self):
+        """Frame e byte vengono contati per stream"""
+        phantom = PhantomSink(60)
+        phantom.consume(1, NOW_US + 40000, 120, NOW_US)
+        phantom.consume(1, NOW_US + 50000, 80, NOW_US)
+        phantom.consume(2, NOW_US + 30000, 100, NOW_US)
+        stats = phantom.get_stats()
+        self.assertEqual([stream.stream_id for stream in stats], [1, 2])
+        self.assertEqual(stats[0].frames_consumed, 2)
+        self.assertEqual(stats[0].bytes_consumed, 200)
+        self.assertEqual(stats[0].min_lead_ms, 40)
+
+    def test_late_frames(self):
+        """Un frame arrivato dopo il suo istante di riproduzione è in ritardo"""
+        phantom = PhantomSink(60)
+        phantom.consume(1, NOW_US - 5000, 120, NOW_US)
+        stats = phantom.get_stats()
+        self.assertEqual(stats[0].late_frames, 1)
+        self.assertEqual(stats[0].last_lead_ms, -5)
+        self.assertEqual(phantom.buffer_level(), 0)
+
+    def test_buffer_level(self):
+        """Il buffer riportato è la quota del buffer obiettivo coperta dall'anticipo"""
+        phantom = PhantomSink(60)
+        self.assertEqual(phantom.buffer_level(), 0)
+        phantom.consume(1, NOW_US + 30000, 120, NOW_US)
+        self.assertEqual(phantom.buffer_level(), 50)
+        phantom.consume(1, NOW_US + 90000, 120, NOW_US)
+        self.assertEqual(phantom.buffer_level(), 100)
+
+    def test_status_reports(self):
+        """I pacchetti Status inviati vengono contati"""
+        phantom = PhantomSink(60)
+        phantom.record_status_sent()
+        phantom.record_status_sent()
+        self.assertEqual(phantom.get_status_reports_sent(), 2)
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,64 @@
+# Test unitari per il profilatore della pipeline audio SABER
+# Verifica percentili sulla finestra mobile e tracciamento per flamegraph
+
+import os
+import sys
+import tempfile
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import PipelineProfiler, PipelineStage
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestPipelineProfiler(unittest.TestCase):
+    """Test per i tempi delle fasi della pipeline"""
+
+    def test_percentiles(self):
+        """I percentili sono calcolati sui campioni della finestra"""
+        profiler = PipelineProfiler(100)
+        for duration in range(1, 101):
+            profiler.record(PipelineStage.Decode, duration)
+        timing = profiler.timing(PipelineStage.Decode)
+        self.assertEqual(timing.samples, 100)
+        self.assertEqual(timing.p50_us, 50)
+        self.assertEqual(timing.p95_us, 95)
+        self.assertEqual(timing.p99_us, 99)
+        self.assertEqual(timing.max_us, 100)
+
+    def test_rolling_window(self):
+        """I campioni più vecchi escono dalla finestra"""
+        profiler = PipelineProfiler(10)
+        for _ in range(10):
+            profiler.record(PipelineStage.Render, 5000)
+        for _ in range(10):
+            profiler.record(PipelineStage.Render, 100)
+        timing = profiler.timing(PipelineStage.Render)
+        self.assertEqual(timing.max_us, 100)
+        self.assertEqual(timing.total, 20)
+
+    def test_only_measured_stages(self):
+        """Vengono riportate solo le fasi con almeno un campione"""
+        profiler = PipelineProfiler()
+        profiler.record(PipelineStage.Resample, 10)
+        profiler.record(PipelineStage.Send, 20)
+        stages = [timing.stage for timing in profiler.timings()]
+        self.assertEqual(stages, [PipelineStage.Send, PipelineStage.Resample])
+
+    def test_trace_file(self):
+        """Il tracciamento produce righe a pile compresse"""
+        path = os.path.join(tempfile.mkdtemp(), "trace.folded")
+        profiler = PipelineProfiler(16, "saber;sink-1")
+        self.assertTrue(profiler.set_trace_file(path))
+        profiler.record(PipelineStage.Decode, 412)
+        self.assertTrue(profiler.set_trace_file(""))
+        with open(path) as trace:
+            self.assertEqual(trace.read(), "saber;sink-1;decode 412\n")
+
+if __name__ == "__main__":
+    unittest.main()
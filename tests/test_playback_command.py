@@ -0,0 +1,151 @@
+# Test unitari per i comandi di riproduzione e le loro conferme
+# Verifica la codifica dei comandi, il registro delle conferme per nodo e la pausa ed il volume di AudioSync
+
+import os
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (PLAYBACK_COMMAND_ACK_TIMEOUT_MS, AudioSync, CommandOutcome, CommandTracker,
+                                PlaybackAction, PlaybackCommand, SaberConfig, SaberProtocol, SyncManager,
+                                command_outcome_from_string, command_outcome_to_string, encode_playback_command,
+                                parse_playback_command, playback_action_from_string, playback_action_to_string)
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def command(action, **fields):
+    result = PlaybackCommand()
+    result.action = action
+    result.zone = "cucina"
+    result.at_ms = 5000
+    for name, value in fields.items():
+        setattr(result, name, value)
+    return result
+
+class TestPlaybackCommand(unittest.TestCase):
+    """Test per la codifica dei comandi sulla mesh"""
+
+    def test_round_trip(self):
+        """Ogni azione ritorna uguale dopo codifica e decodifica"""
+        for original in (command(PlaybackAction.Start, playlist="mattino", id=3),
+                         command(PlaybackAction.Seek, position_ms=93000, id=4),
+                         command(PlaybackAction.Volume, volume_percent=35, id=5),
+                         command(PlaybackAction.Pause)):
+            cmd_type = "playback." + playback_action_to_string(original.action)
+            parsed = parse_playback_command(cmd_type, "cucina", encode_playback_command(original))
+            self.assertIsNotNone(parsed, cmd_type)
+            self.assertEqual(parsed.action, original.action)
+            self.assertEqual(parsed.id, original.id)
+            self.assertEqual(parsed.at_ms, 5000)
+            self.assertEqual(parsed.zone, "cucina")
+            self.assertEqual(parsed.playlist, original.playlist)
+            self.assertEqual(parsed.position_ms, original.position_ms)
+            self.assertEqual(parsed.volume_percent, original.volume_percent)
+
+    def test_without_id(self):
+        """Un comando senza identificatore non chiede conferme"""
+        self.assertNotIn("id", encode_playback_command(command(PlaybackAction.Stop)))
+        parsed = parse_playback_command("playback.stop", "all", {"at": "100"})
+        self.assertEqual(parsed.id, 0)
+
+    def test_invalid(self):
+        """Azioni sconosciute e parametri fuori intervallo vengono rifiutati"""
+        self.assertIsNone(parse_playback_command("playback.rewind", "all", {"at": "100"}))
+        self.assertIsNone(parse_playback_command("degrade.apply", "all", {"at": "100"}))
+        self.assertIsNone(parse_playback_command("playback.stop", "all", {}))
+        self.assertIsNone(parse_playback_command("playback.stop", "all", {"at": "-1"}))
+        self.assertIsNone(parse_playback_command("playback.volume", "all", {"at": "100", "volume": "101"}))
+        self.assertIsNone(parse_playback_command("playback.seek", "all", {"at": "100"}))
+        self.assertIsNone(parse_playback_command("playback.stop", "all", {"at": "100", "id": "x"}))
+
+    def test_names(self):
+        """I nomi delle azioni e degli esiti"""
+        for action in (PlaybackAction.Start, PlaybackAction.Stop, PlaybackAction.Pause, PlaybackAction.Resume,
+                       PlaybackAction.Seek, PlaybackAction.Volume):
+            self.assertEqual(playback_action_from_string(playback_action_to_string(action)), action)
+        for outcome in (CommandOutcome.Pending, CommandOutcome.Applied, CommandOutcome.Failed,
+                        CommandOutcome.TimedOut):
+            self.assertEqual(command_outcome_from_string(command_outcome_to_string(outcome)), outcome)
+        self.assertEqual(command_outcome_to_string(CommandOutcome.TimedOut), "timed_out")
+        self.assertIsNone(playback_action_from_string("rewind"))
+
+class TestCommandTracker(unittest.TestCase):
+    """Test per il registro delle conferme"""
+
+    def test_acknowledge(self):
+        """Le conferme aggiornano il nodo e completano il comando"""
+        tracker = CommandTracker()
+        issued = tracker.issue(command(PlaybackAction.Pause), {"sink-1", "sink-2"}, 1000)
+        self.assertEqual(issued.id, 1)
+        self.assertFalse(tracker.get(1).is_complete())
+
+        self.assertTrue(tracker.acknowledge("sink-1", 1, CommandOutcome.Applied, "", 1100))
+        self.assertTrue(tracker.acknowledge("sink-2", 1, CommandOutcome.Failed, "nessun audio", 1200))
+        status = tracker.get(1)
+        self.assertTrue(status.is_complete())
+        self.assertEqual(status.nodes["sink-1"].outcome, CommandOutcome.Applied)
+        self.assertEqual(status.nodes["sink-2"].detail, "nessun audio")
+        self.assertFalse(tracker.acknowledge("sink-1", 99, CommandOutcome.Applied, "", 1300))
+
+    def test_expire(self):
+        """Chi non conferma entro il tempo limite dalla barriera è dato per non raggiunto"""
+        tracker = CommandTracker()
+        tracker.issue(command(PlaybackAction.Stop), {"sink-1", "sink-2"}, 1000)
+        tracker.acknowledge("sink-1", 1, CommandOutcome.Applied, "", 5100)
+        self.assertEqual(tracker.expire(5000 + PLAYBACK_COMMAND_ACK_TIMEOUT_MS - 1), 0)
+        self.assertEqual(tracker.expire(5000 + PLAYBACK_COMMAND_ACK_TIMEOUT_MS), 1)
+        self.assertEqual(tracker.get(1).nodes["sink-2"].outcome, CommandOutcome.TimedOut)
+        self.assertEqual(tracker.get(1).nodes["sink-1"].outcome, CommandOutcome.Applied)
+
+    def test_capacity(self):
+        """Il registro dimentica i comandi più vecchi"""
+        tracker = CommandTracker(2)
+        for _ in range(3):
+            tracker.issue(command(PlaybackAction.Resume), set(), 1000)
+        self.assertEqual([status.command.id for status in tracker.recent()], [2, 3])
+        self.assertIsNone(tracker.get(1))
+
+class TestAudioSyncControl(unittest.TestCase):
+    """Test per la pausa ed il volume di AudioSync"""
+
+    def test_pause(self):
+        """La pausa dura fino alla ripresa o ad un nuovo avvio"""
+        manager = SyncManager()
+        audio = AudioSync(manager, True)
+        audio.pause_playback()
+        self.assertTrue(audio.is_paused())
+        audio.resume_playback()
+        self.assertFalse(audio.is_paused())
+        audio.pause_playback()
+        manager.handle_time_beacon(int(time.time() * 1000))
+        self.assertTrue(audio.start_playback())
+        self.assertFalse(audio.is_paused())
+
+    def test_volume(self):
+        """Il volume parte pieno e non supera il 100%"""
+        audio = AudioSync(SyncManager(), True)
+        self.assertEqual(audio.get_volume(), 100)
+        audio.set_volume(30)
+        self.assertEqual(audio.get_volume(), 30)
+        with self.assertRaises(ValueError):
+            audio.set_volume(101)
+
+class TestProtocolCommands(unittest.TestCase):
+    """Test per i comandi di riproduzione senza avviare la rete"""
+
+    def test_without_network(self):
+        """Senza rete i comandi non partono e non finiscono nel registro"""
+        protocol = SaberProtocol(SaberConfig.default_config())
+        self.assertFalse(protocol.seek_group_playback("cucina", 30000))
+        self.assertFalse(protocol.stop_group_playback("cucina"))
+        self.assertEqual(protocol.get_command_statuses(), [])
+        self.assertIsNone(protocol.get_command_status(1))
+
+if __name__ == "__main__":
+    unittest.main()
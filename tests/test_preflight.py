@@ -0,0 +1,120 @@
+# Test unitari per la verifica dell'ambiente prima dell'avvio
+# Verifica i conflitti sulle porte, i prerequisiti richiesti dalla configurazione e le chiavi del file
+
+import os
+import socket
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (LifecycleError, LifecycleErrorType, NodeRole, PreflightCheck, SaberConfig,
+                                SaberProtocol, SimNetwork)
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestPreflight(unittest.TestCase):
+    """Test per l'esito della verifica dell'ambiente"""
+
+    def create(self):
+        config = SaberConfig.default_config()
+        config.node_id = "preflight-sink"
+        config.role = NodeRole.Sink
+        return config
+
+    def occupied_port(self):
+        # Un socket in ascolto rende la porta non disponibile anche con SO_REUSEADDR
+        listener = socket.socket(socket.AF_INET, socket.SOCK_STREAM)
+        self.addCleanup(listener.close)
+        listener.bind(("127.0.0.1", 0))
+        listener.listen(1)
+        return listener.getsockname()[1]
+
+    def test_default_passes(self):
+        """La configurazione predefinita non richiede Bluetooth né scheda audio"""
+        protocol = SaberProtocol(self.create())
+        protocol.set_sim_network(SimNetwork(1))
+        if not protocol.initialize():
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        self.addCleanup(protocol.shutdown)
+        report = protocol.get_preflight_report()
+        self.assertTrue(report.passed())
+        self.assertGreater(report.clock_resolution_us, 0.0)
+        self.assertFalse(any(issue.check in (PreflightCheck.BluetoothAdapter, PreflightCheck.AudioDevice)
+                             for issue in report.issues))
+
+    def test_port_in_use(self):
+        """Una porta di controllo già occupata impedisce l'avvio e indica la chiave da cambiare"""
+        config = self.create()
+        config.control_bind_address = "127.0.0.1"
+        config.control_port = self.occupied_port()
+        protocol = SaberProtocol(config)
+        protocol.set_sim_network(SimNetwork(1))
+        self.assertFalse(protocol.initialize())
+
+        report = protocol.get_preflight_report()
+        self.assertFalse(report.passed())
+        issues = [issue for issue in report.issues if issue.check == PreflightCheck.PortBindable]
+        self.assertEqual(len(issues), 1)
+        self.assertTrue(issues[0].fatal)
+        self.assertIn("già in uso", issues[0].message)
+        self.assertIn("control.port", issues[0].remedy)
+        self.assertIn("[errore] port:", report.summary())
+
+    def test_restart_reports_issues(self):
+        """Il riavvio non riuscito riporta il riepilogo dei problemi"""
+        config = self.create()
+        config.control_bind_address = "127.0.0.1"
+        config.control_port = self.occupied_port()
+        protocol = SaberProtocol(config)
+        protocol.set_sim_network(SimNetwork(1))
+        with self.assertRaises(LifecycleError) as context:
+            protocol.restart()
+        self.assertEqual(context.exception.type, LifecycleErrorType.InitializationFailed)
+        self.assertIn("control.port", str(context.exception))
+
+    def test_invalid_bind_address(self):
+        """Un indirizzo di ascolto non valido è un problema fatale"""
+        config = self.create()
+        config.control_bind_address = "localhost:80"
+        config.control_port = 0
+        protocol = SaberProtocol(config)
+        protocol.set_sim_network(SimNetwork(1))
+        self.assertFalse(protocol.initialize())
+        issue = protocol.get_preflight_report().issues[0]
+        self.assertEqual(issue.check, PreflightCheck.PortBindable)
+        self.assertIn("Indirizzo non valido", issue.message)
+
+class TestPreflightConfig(ConfigFileTestCase):
+    """Test per le chiavi della verifica nel file"""
+
+    HEADER = '[node]\nid = "sink-1"\nrole = "sink"\n\n[preflight]\n'
+
+    def test_defaults(self):
+        """Senza chiavi non si richiedono periferiche e il limite dell'orologio è 1 ms"""
+        config = SaberConfig.default_config()
+        self.assertFalse(config.require_bluetooth)
+        self.assertFalse(config.require_audio_device)
+        self.assertEqual(config.max_clock_resolution_us, 1000)
+
+    def test_keys(self):
+        """Le chiavi preflight.* sostituiscono i valori predefiniti"""
+        config = self.load("require_bluetooth = true\nrequire_audio_device = true\n"
+                           "max_clock_resolution_us = 200\n")
+        self.assertTrue(config.require_bluetooth)
+        self.assertTrue(config.require_audio_device)
+        self.assertEqual(config.max_clock_resolution_us, 200)
+
+    def test_invalid_resolution(self):
+        """Un limite dell'orologio non positivo rende il file non valido"""
+        for value in (0, -5):
+            with self.assertRaises(RuntimeError, msg=str(value)):
+                self.load("max_clock_resolution_us = %d\n" % value)
+
+if __name__ == "__main__":
+    unittest.main()
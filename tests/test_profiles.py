@@ -0,0 +1,94 @@
+# Test unitari per i profili d'uso del protocollo SABER
+# Verifica i parametri applicati in base al ruolo del nodo e la selezione del profilo dal file
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import NodeRole, SaberConfig, find_profile, list_profiles
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestBuiltinProfiles(unittest.TestCase):
+    """Test per i profili predefiniti"""
+
+    def test_names(self):
+        """I profili predefiniti sono elencati e cercabili per nome"""
+        self.assertEqual(list_profiles(), ["home-music", "conference-voice", "stage-monitor"])
+        self.assertIsNone(find_profile("party"))
+        voice = find_profile("conference-voice")
+        self.assertFalse(voice.is_music_mode)
+        self.assertEqual(voice.codec, "lc3")
+
+    def test_within_spec(self):
+        """Ogni profilo produce parametri coerenti con la specifica per tutti i ruoli"""
+        for name in list_profiles():
+            for role in (NodeRole.Master, NodeRole.Repeater, NodeRole.Sink):
+                config = SaberConfig.for_profile(name, role)
+                config.spec.validate()
+                self.assertEqual(config.profile, name)
+                self.assertEqual(config.role, role)
+
+    def test_unknown(self):
+        """Un profilo sconosciuto viene rifiutato"""
+        with self.assertRaises(ValueError):
+            SaberConfig.for_profile("party", NodeRole.Sink)
+
+class TestRoleAwareProfile(unittest.TestCase):
+    """Test per i parametri che dipendono dal ruolo"""
+
+    def test_stage_monitor_sink(self):
+        """Il sink usa il buffer del profilo e una finestra di riparazione che ci sta dentro"""
+        config = SaberConfig.for_profile("stage-monitor", NodeRole.Sink)
+        self.assertEqual(config.frame_duration_us, 7500)
+        self.assertEqual(config.spec.latency_budget_ms, 20)
+        self.assertEqual(config.spec.default_buffer_ms, 10)
+        self.assertEqual(config.repair.window_ms, 5)
+        self.assertEqual(config.timestamp_anchor_frames, 66)
+        self.assertFalse(config.fec_enabled)
+
+    def test_stage_monitor_master(self):
+        """Master e Repeater dimensionano la cache sui frame ancora riproducibili"""
+        default = SaberConfig.default_config()
+        for role in (NodeRole.Master, NodeRole.Repeater):
+            config = SaberConfig.for_profile("stage-monitor", role)
+            # 20 ms di budget con frame da 7,5 ms: 3 frame in volo, con un minimo di 8 in cache
+            self.assertEqual(config.repair.cache_frames, 8)
+            self.assertEqual(config.repair.window_ms, default.repair.window_ms)
+            self.assertEqual(config.spec.default_buffer_ms, default.spec.default_buffer_ms)
+
+    def test_apply_profile(self):
+        """Un profilo applicato ad una configurazione esistente ne segue il ruolo"""
+        config = SaberConfig.default_config()
+        config.role = NodeRole.Sink
+        config.apply_profile(find_profile("home-music"))
+        self.assertEqual(config.profile, "home-music")
+        self.assertEqual(config.spec.default_buffer_ms, min(30, config.spec.latency_budget_ms))
+        self.assertLessEqual(config.repair.window_ms, config.spec.default_buffer_ms // 2)
+
+class TestProfileConfig(ConfigFileTestCase):
+    """Test per il profilo selezionato nel file"""
+
+    HEADER = '[node]\nid = "sink-1"\nrole = "sink"\n'
+
+    def test_profile_and_override(self):
+        """Le chiavi successive al profilo ne sostituiscono i valori"""
+        config = self.load('profile = "stage-monitor"\n\n[audio]\nrepair_window_ms = 8\n')
+        self.assertEqual(config.profile, "stage-monitor")
+        self.assertEqual(config.spec.default_buffer_ms, 10)
+        self.assertEqual(config.repair.window_ms, 8)
+
+    def test_unknown(self):
+        """Un profilo sconosciuto rende il file non valido"""
+        with self.assertRaises(RuntimeError):
+            self.load('profile = "party"\n')
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,162 @@
+# Test unitari per la finestra di provisioning dei nuovi nodi
+# Verifica l'apertura a tempo, il limite delle richieste a finestra chiusa e il comando "provision"
+
+import os
+import socket
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (JoinDecision, NodeRole, ProvisioningWindow, SaberConfig, SaberProtocol,
+                                SimNetwork, TokenScope)
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def free_port():
+    with socket.socket(socket.AF_INET, socket.SOCK_STREAM) as probe:
+        probe.bind(("127.0.0.1", 0))
+        return probe.getsockname()[1]
+
+class TestProvisioningWindow(unittest.TestCase):
+    """Test per l'ammissione delle richieste di ingresso"""
+
+    def test_open_window(self):
+        """A finestra aperta le richieste sono accettate fino alla scadenza"""
+        window = ProvisioningWindow()
+        window.open(5000, 1000)
+        self.assertTrue(window.is_open(5999))
+        self.assertFalse(window.is_open(6000))
+        self.assertEqual(window.admit(2000), JoinDecision.Accept)
+        window.record_accepted()
+
+        status = window.status(2000)
+        self.assertTrue(status.open)
+        self.assertEqual((status.remaining_ms, status.accepted, status.refused), (4000, 1, 0))
+
+        window.close()
+        self.assertFalse(window.is_open(2000))
+        self.assertEqual(window.status(2000).remaining_ms, 0)
+
+    def test_closed_rate_limit(self):
+        """A finestra chiusa si risponde solo a un numero limitato di richieste al minuto"""
+        window = ProvisioningWindow(2)
+        self.assertEqual(window.admit(1000), JoinDecision.Refuse)
+        self.assertEqual(window.admit(1000), JoinDecision.Refuse)
+        self.assertEqual(window.admit(1000), JoinDecision.Throttle)
+
+        # Dopo mezzo minuto il secchio ha recuperato una risposta
+        self.assertEqual(window.admit(31000), JoinDecision.Refuse)
+        self.assertEqual(window.admit(31000), JoinDecision.Throttle)
+
+        status = window.status(31000)
+        self.assertFalse(status.open)
+        self.assertEqual((status.accepted, status.refused, status.throttled), (0, 3, 2))
+
+    def test_lower_limit(self):
+        """Un limite più basso riduce subito le risposte disponibili"""
+        window = ProvisioningWindow(6)
+        window.set_closed_rate_limit(1)
+        self.assertEqual(window.admit(1000), JoinDecision.Refuse)
+        self.assertEqual(window.admit(1000), JoinDecision.Throttle)
+
+class TestProtocolProvisioning(unittest.TestCase):
+    """Test per la finestra di provisioning di un nodo"""
+
+    def start(self, node_id, role, network, control_port=None):
+        config = SaberConfig.default_config()
+        config.node_id = node_id
+        config.role = role
+        if control_port is not None:
+            config.control_port = control_port
+            config.control_bind_address = "127.0.0.1"
+        protocol = SaberProtocol(config)
+        protocol.set_sim_network(network)
+        if not protocol.initialize():
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        self.addCleanup(protocol.shutdown)
+        return protocol
+
+    def test_without_network(self):
+        """Senza rete mesh la finestra non può essere aperta"""
+        protocol = SaberProtocol(SaberConfig.default_config())
+        self.assertFalse(protocol.open_provisioning(60))
+        self.assertFalse(protocol.close_provisioning())
+        self.assertFalse(protocol.request_join())
+        self.assertFalse(protocol.get_provisioning_status().open)
+
+    def test_open_and_close(self):
+        """La finestra si apre per la durata indicata e si chiude su richiesta"""
+        master = self.start("prov-master", NodeRole.Master, SimNetwork(1))
+        self.assertFalse(master.get_provisioning_status().open)
+        self.assertTrue(master.open_provisioning(60))
+        status = master.get_provisioning_status()
+        self.assertTrue(status.open)
+        self.assertGreater(status.remaining_ms, 55000)
+        self.assertLessEqual(status.remaining_ms, 60000)
+        self.assertTrue(master.close_provisioning())
+        self.assertFalse(master.get_provisioning_status().open)
+
+    def test_closed_window_refuses(self):
+        """A finestra chiusa le richieste di ingresso vengono rifiutate"""
+        network = SimNetwork(1)
+        master = self.start("prov-master", NodeRole.Master, network)
+        sink = self.start("prov-sink", NodeRole.Sink, network)
+        deadline = time.monotonic() + 5.0
+        while time.monotonic() < deadline and master.get_provisioning_status().refused == 0:
+            self.assertTrue(sink.request_join())
+            network.advance(50)
+            time.sleep(0.05)
+        status = master.get_provisioning_status()
+        self.assertGreater(status.refused, 0)
+        self.assertEqual(status.accepted, 0)
+
+    def test_control_command(self):
+        """L'operatore apre, interroga e chiude la finestra dal socket di controllo"""
+        port = free_port()
+        master = self.start("prov-master", NodeRole.Master, SimNetwork(1), port)
+        try:
+            client = socket.create_connection(("127.0.0.1", port), timeout=5)
+        except OSError:
+            self.skipTest("socket di controllo non disponibile")
+        self.addCleanup(client.close)
+        lines = client.makefile("r")
+        self.addCleanup(lines.close)
+
+        def command(line):
+            client.sendall((line + "\n").encode())
+            return lines.readline().rstrip("\n")
+
+        token = master.issue_control_token(TokenScope.Admin, 60)
+        self.assertEqual(command("auth " + token), "ok admin")
+        self.assertEqual(command("provision open 2m"), "ok aperta per 120s")
+        self.assertTrue(command("provision status").startswith("ok aperta remaining_s="))
+        self.assertEqual(command("provision close"), "ok chiusa")
+        self.assertIn("accepted=0", command("provision status"))
+        for line in ("provision open 0", "provision open", "provision"):
+            self.assertTrue(command(line).startswith("error"), line)
+        self.assertFalse(master.get_provisioning_status().open)
+
+class TestProvisioningConfig(ConfigFileTestCase):
+    """Test per le chiavi della finestra di provisioning nel file"""
+
+    HEADER = '[node]\nid = "master-1"\nrole = "master"\n\n[provisioning]\n'
+
+    def test_keys(self):
+        """provisioning.closed_attempts_per_minute sostituisce il limite predefinito"""
+        self.assertEqual(SaberConfig.default_config().join_attempts_per_minute, 6)
+        self.assertEqual(self.load("closed_attempts_per_minute = 2\n").join_attempts_per_minute, 2)
+
+    def test_negative(self):
+        """Un limite negativo rende il file non valido"""
+        with self.assertRaises(RuntimeError):
+            self.load("closed_attempts_per_minute = -1\n")
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,83 @@
+# Test unitari per la stabilità dell'API pubblica del modulo Python saber_protocol
+# Confronta i nomi esposti con l'istantanea in tests/api, così ogni modifica all'API è esplicita
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    import saber_protocol
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+SNAPSHOT = os.path.join(os.path.dirname(__file__), 'api', 'saber_protocol.txt')
+HEADER = [
+    "# Superficie pubblica del modulo Python saber_protocol",
+    "# Le righe con '?' esistono solo con alcune opzioni di compilazione",
+    "# Rigenerare con: SABER_UPDATE_API_SNAPSHOT=1 python tests/test_public_api.py",
+]
+
+def public_members(cls):
+    """Nomi pubblici di una classe, dei valori di un enum o di un'eccezione"""
+    if issubclass(cls, BaseException):
+        return [name for name in dir(cls) if not name.startswith('_') and name not in dir(BaseException)]
+    members = getattr(cls, '__members__', None)
+    if members is not None:
+        return list(members)
+    return [name for name in dir(cls) if not name.startswith('_')]
+
+def public_api(module):
+    """Elenco ordinato dei nomi pubblici del modulo e dei membri delle sue classi"""
+    entries = []
+    for name in sorted(dir(module)):
+        if name.startswith('_'):
+            continue
+        entries.append(name)
+        value = getattr(module, name)
+        if isinstance(value, type):
+            entries.extend(name + '.' + member for member in sorted(public_members(value)))
+    return entries
+
+def load_snapshot():
+    """Voci dell'istantanea: nome -> presente solo con alcune opzioni di compilazione"""
+    entries = {}
+    with open(SNAPSHOT) as snapshot:
+        for line in snapshot:
+            line = line.strip()
+            if not line or line.startswith('#'):
+                continue
+            entries[line.lstrip('?')] = line.startswith('?')
+    return entries
+
+class TestPublicApi(unittest.TestCase):
+    """Test per l'istantanea dell'API pubblica"""
+
+    def test_matches_snapshot(self):
+        """Nessun nome scompare o compare senza aggiornare l'istantanea"""
+        current = public_api(saber_protocol)
+        snapshot = load_snapshot()
+        removed = [name for name, optional in snapshot.items() if not optional and name not in current]
+        added = [name for name in current if name not in snapshot]
+        self.assertEqual(removed, [], "Nomi rimossi dall'API pubblica: è una modifica incompatibile")
+        self.assertEqual(added, [], "Nomi aggiunti all'API pubblica: aggiornare " + SNAPSHOT)
+
+def update_snapshot():
+    """Riscrive l'istantanea conservando le voci opzionali assenti in questa compilazione"""
+    snapshot = load_snapshot() if os.path.exists(SNAPSHOT) else {}
+    current = public_api(saber_protocol)
+    names = set(current) | {name for name, optional in snapshot.items() if optional}
+    with open(SNAPSHOT, 'w') as output:
+        output.write('\n'.join(HEADER) + '\n')
+        for name in sorted(names):
+            output.write(('?' if snapshot.get(name) else '') + name + '\n')
+
+if __name__ == '__main__':
+    if os.environ.get('SABER_UPDATE_API_SNAPSHOT'):
+        update_snapshot()
+    else:
+        unittest.main()
@@ -0,0 +1,89 @@
+# Test unitari per i limiti di traffico in ingresso
+# Verifica il secchio di gettoni per mittente e tipo, i contatori degli scarti e la configurazione da file
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (MeshPacketType, PacketRateLimiter, RateLimit, SaberConfig, default_rate_limits,
+                                mesh_packet_type_from_string, mesh_packet_type_to_string)
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def make_limiter():
+    return PacketRateLimiter({MeshPacketType.Ping: RateLimit(2.0, 3)})
+
+class TestPacketRateLimiter(unittest.TestCase):
+    """Test per il secchio di gettoni"""
+
+    def test_burst(self):
+        """Oltre la raffica i pacchetti vengono scartati"""
+        limiter = make_limiter()
+        allowed = [limiter.allow("rumoroso", MeshPacketType.Ping, 1000) for _ in range(10)]
+        self.assertEqual(allowed.count(True), 3)
+        self.assertEqual(limiter.get_stats().dropped, 7)
+
+    def test_refill(self):
+        """Il secchio si ricarica al ritmo concesso"""
+        limiter = make_limiter()
+        for _ in range(3):
+            limiter.allow("rumoroso", MeshPacketType.Ping, 1000)
+        self.assertFalse(limiter.allow("rumoroso", MeshPacketType.Ping, 1000))
+        self.assertTrue(limiter.allow("rumoroso", MeshPacketType.Ping, 1500))
+        self.assertFalse(limiter.allow("rumoroso", MeshPacketType.Ping, 1500))
+
+    def test_per_source(self):
+        """Un mittente che inonda la rete non tocca gli altri né i tipi senza limite"""
+        limiter = make_limiter()
+        for _ in range(10):
+            limiter.allow("rumoroso", MeshPacketType.Ping, 1000)
+        self.assertTrue(limiter.allow("sink-1", MeshPacketType.Ping, 1000))
+        self.assertTrue(limiter.allow("rumoroso", MeshPacketType.Status, 1000))
+        stats = limiter.get_stats()
+        self.assertEqual(stats.dropped_by_source, {"rumoroso": 7})
+        self.assertEqual(stats.dropped_by_type, {MeshPacketType.Ping: 7})
+
+    def test_defaults(self):
+        """Ping e Status sono limitati di default"""
+        limits = default_rate_limits()
+        self.assertEqual(set(limits), {MeshPacketType.Ping, MeshPacketType.Status})
+        limiter = PacketRateLimiter()
+        limiter.set_limits({})
+        self.assertTrue(all(limiter.allow("rumoroso", MeshPacketType.Ping, 1000) for _ in range(100)))
+
+    def test_names(self):
+        """I nomi dei tipi di pacchetto"""
+        self.assertEqual(mesh_packet_type_to_string(MeshPacketType.TimeBeacon), "time_beacon")
+        self.assertEqual(mesh_packet_type_from_string("status"), MeshPacketType.Status)
+        self.assertIsNone(mesh_packet_type_from_string("flood"))
+
+class TestRateLimitConfig(ConfigFileTestCase):
+    """Test per i limiti letti dal file di configurazione"""
+
+    HEADER = '[node]\nid = "master-1"\nrole = "master"\n\n'
+
+    def test_limits(self):
+        """La sezione [ratelimit.<tipo>] imposta ritmo e raffica"""
+        config = self.load('[ratelimit.command]\nper_second = 10\nburst = 30\n\n[ratelimit.ping]\nper_second = 0\n')
+        self.assertEqual(config.rate_limits[MeshPacketType.Command].packets_per_second, 10)
+        self.assertEqual(config.rate_limits[MeshPacketType.Command].burst, 30)
+        self.assertNotIn(MeshPacketType.Ping, config.rate_limits)
+        self.assertIn(MeshPacketType.Status, config.rate_limits)
+        self.assertTrue(SaberConfig.is_live_reloadable("ratelimit.status.per_second"))
+
+    def test_invalid(self):
+        """Tipi sconosciuti, richieste di ingresso e raffiche nulle vengono rifiutati"""
+        for text in ('[ratelimit.flood]\nper_second = 1\n', '[ratelimit.join]\nper_second = 1\n',
+                     '[ratelimit.status]\nburst = 0\n', '[ratelimit.status]\nwindow = 1\n'):
+            with self.assertRaises(RuntimeError):
+                self.load(text)
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,95 @@
+# Test unitari per i pacchetti Reject della rete mesh del protocollo SABER
+# Verifica che il nodo risponda solo ai pacchetti autenticati, senza riflettere traffico falsificato
+
+import os
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import MeshCrypto, MeshNetwork, MeshPacket, MeshPacketType, Node, NodeRole, RejectReason
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestRejectReplies(unittest.TestCase):
+    """Test per le risposte Reject ai pacchetti scartati"""
+
+    def setUp(self):
+        self.sink = MeshCrypto()
+        crypto = MeshCrypto()
+        crypto.register_node_key("sink-1", self.sink.get_public_key())
+        self.network = MeshNetwork(Node("master-1", NodeRole.Master))
+        self.network.set_crypto(crypto)
+        self.network.set_require_signatures(True)
+        self.network.set_send_rejects(True)
+        self.sent = []
+        self.network.set_link_sender(self.sent.append)
+        self.network.start()
+        self.addCleanup(self.network.stop)
+
+    def rejects(self):
+        return [packet for packet in map(MeshPacket.decode, list(self.sent))
+                if packet.get_type() == MeshPacketType.Reject]
+
+    def wait_for_drop(self, reason):
+        deadline = time.monotonic() + 2.0
+        while time.monotonic() < deadline:
+            if self.network.get_drop_counters().get(reason, 0) > 0:
+                return
+            time.sleep(0.01)
+        self.fail("pacchetto non scartato per " + str(reason))
+
+    def command(self, source, ttl=8):
+        packet = MeshPacket.create_command("volume", {"level": "50"})
+        packet.set_header(source, 1, ttl)
+        return packet
+
+    def test_unsigned_packet_not_answered(self):
+        """Un pacchetto senza firma con sorgente falsificata non genera un Reject verso la vittima"""
+        self.assertTrue(self.network.receive_from_link(self.command("victim").encode()))
+        self.wait_for_drop(RejectReason.BadSignature)
+        self.assertEqual(self.rejects(), [])
+
+    def test_unknown_sender_not_answered(self):
+        """Un pacchetto firmato da una chiave sconosciuta non genera un Reject"""
+        packet = self.command("victim")
+        packet.sign(MeshCrypto())
+        self.assertTrue(self.network.receive_from_link(packet.encode()))
+        self.wait_for_drop(RejectReason.UnknownSender)
+        self.assertEqual(self.rejects(), [])
+
+    def test_authenticated_packet_answered(self):
+        """Un pacchetto autentico scartato riceve il Reject con il motivo"""
+        packet = self.command("sink-1", ttl=0)
+        packet.sign(self.sink)
+        self.assertTrue(self.network.receive_from_link(packet.encode()))
+        self.wait_for_drop(RejectReason.TtlExpired)
+        deadline = time.monotonic() + 2.0
+        while not self.rejects() and time.monotonic() < deadline:
+            time.sleep(0.01)
+        self.assertEqual(len(self.rejects()), 1)
+
+    def test_unsigned_packet_without_required_signatures(self):
+        """Con le firme facoltative un pacchetto senza firma viene elaborato ma non riceve Reject"""
+        self.network.set_require_signatures(False)
+        self.assertTrue(self.network.receive_from_link(self.command("victim", ttl=0).encode()))
+        self.wait_for_drop(RejectReason.TtlExpired)
+        time.sleep(0.1)
+        self.assertEqual(self.rejects(), [])
+
+        # Lo stesso scarto di un pacchetto firmato riceve la risposta
+        packet = self.command("sink-1", ttl=0)
+        packet.sign(self.sink)
+        self.assertTrue(self.network.receive_from_link(packet.encode()))
+        deadline = time.monotonic() + 2.0
+        while not self.rejects() and time.monotonic() < deadline:
+            time.sleep(0.01)
+        self.assertEqual(len(self.rejects()), 1)
+
+if __name__ == "__main__":
+    unittest.main()
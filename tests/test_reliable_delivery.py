@@ -0,0 +1,96 @@
+# Test unitari per la consegna affidabile dei comandi
+# Verifica il pacchetto di conferma, la configurazione delle ritrasmissioni e i contatori
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import MeshPacket, MeshPacketType, NodeRole, SaberConfig, SaberProtocol, SimNetwork
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestAckPacket(unittest.TestCase):
+    """Test per il pacchetto Ack"""
+
+    def test_round_trip(self):
+        """La conferma riporta origine, sequenza e tipo del pacchetto confermato"""
+        command = MeshPacket.create_command("volume.set", {"level": "3"})
+        command.set_header("master-1", 42, 8)
+        ack = MeshPacket.create_ack(command)
+        ack.set_header("sink-1", 7, 8)
+        decoded = MeshPacket.decode(ack.encode())
+        self.assertEqual(decoded.get_type(), MeshPacketType.Ack)
+        data = decoded.get_ack_data()
+        self.assertEqual(data.origin, "master-1")
+        self.assertEqual(data.sequence, 42)
+        self.assertEqual(data.acked_type, MeshPacketType.Command)
+
+    def test_wrong_type(self):
+        """I dati di conferma esistono solo nei pacchetti Ack"""
+        with self.assertRaises(RuntimeError):
+            MeshPacket.create_command("volume.set", {}).get_ack_data()
+
+class TestReliableConfig(ConfigFileTestCase):
+    """Test per la configurazione letta dal file"""
+
+    HEADER = '[node]\nid = "master-1"\nrole = "master"\n\n'
+
+    def test_defaults(self):
+        """Senza chiavi la consegna affidabile è attiva con attesa esponenziale da 200 ms a 3,2 s"""
+        reliable = self.load('').reliable
+        self.assertTrue(reliable.enabled)
+        self.assertEqual(reliable.initial_timeout_ms, 200)
+        self.assertEqual(reliable.max_timeout_ms, 3200)
+        self.assertEqual(reliable.max_attempts, 5)
+
+    def test_values(self):
+        """Le chiavi mesh.reliable_* impostano tempi e tentativi, modificabili senza riavvio"""
+        reliable = self.load('[mesh]\nreliable_enabled = false\nreliable_timeout_ms = 50\n'
+                             'reliable_max_timeout_ms = 400\nreliable_max_attempts = 3\n').reliable
+        self.assertFalse(reliable.enabled)
+        self.assertEqual(reliable.initial_timeout_ms, 50)
+        self.assertEqual(reliable.max_timeout_ms, 400)
+        self.assertEqual(reliable.max_attempts, 3)
+        self.assertTrue(SaberConfig.is_live_reloadable("mesh.reliable_max_attempts"))
+
+    def test_invalid(self):
+        """Tempi o tentativi nulli e un massimo minore dell'attesa iniziale vengono rifiutati"""
+        for text in ('[mesh]\nreliable_timeout_ms = 0\n', '[mesh]\nreliable_max_attempts = 0\n',
+                     '[mesh]\nreliable_timeout_ms = 500\nreliable_max_timeout_ms = 100\n'):
+            with self.assertRaises(RuntimeError):
+                self.load(text)
+
+class TestReliableStats(unittest.TestCase):
+    """Test per i contatori esposti dal protocollo"""
+
+    def test_uninitialized(self):
+        """Senza rete mesh i contatori sono a zero"""
+        config = SaberConfig.default_config()
+        config.node_id = "solo"
+        stats = SaberProtocol(config).get_reliable_stats()
+        self.assertEqual((stats.sent, stats.retransmitted, stats.failed, stats.duplicates), (0, 0, 0, 0))
+
+    def test_metrics(self):
+        """I contatori compaiono tra le metriche Prometheus"""
+        config = SaberConfig.default_config()
+        config.node_id = "reliable-master"
+        config.role = NodeRole.Master
+        protocol = SaberProtocol(config)
+        protocol.set_sim_network(SimNetwork(1))
+        if not protocol.initialize():
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        self.addCleanup(protocol.shutdown)
+        text = protocol.get_prometheus_metrics()
+        for family in ("saber_reliable_retransmitted_total", "saber_reliable_failed_total",
+                       "saber_reliable_duplicates_total"):
+            self.assertIn("# TYPE " + family + " counter", text)
+
+if __name__ == "__main__":
+    unittest.main()
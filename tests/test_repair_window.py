@@ -0,0 +1,99 @@
+# Test unitari per la finestra di riparazione dei frame audio
+# Verifica i NACK generati dai salti di sequenza, la scadenza delle richieste e la configurazione
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import NackTracker, RepairConfig, arrives_before_playout
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestNackTracker(unittest.TestCase):
+    """Test per la rilevazione dei frame mancanti lato sink"""
+
+    def test_gap_and_repair(self):
+        """Un salto richiede i frame mancanti, che contano come riparati se arrivano in tempo"""
+        tracker = NackTracker(20)
+        self.assertEqual(tracker.on_frame(1, 0, 0), [])
+        self.assertEqual(tracker.on_frame(1, 1, 1), [])
+        self.assertEqual(tracker.on_frame(1, 4, 2), [2, 3])
+        self.assertEqual(tracker.get_stats().nacks_sent, 2)
+
+        self.assertEqual(tracker.on_frame(1, 2, 5), [])
+        self.assertEqual(tracker.get_stats().repaired, 1)
+
+        # Il frame 3 non arriva entro la finestra e passa al mascheramento
+        tracker.expire(30)
+        self.assertEqual(tracker.get_stats().expired, 1)
+        tracker.on_frame(1, 3, 31)
+        self.assertEqual(tracker.get_stats().repaired, 1)
+
+    def test_restart(self):
+        """Un salto oltre il massimo indica un riavvio dello stream e non produce NACK"""
+        tracker = NackTracker(20, 4)
+        tracker.on_frame(1, 0, 0)
+        self.assertEqual(tracker.on_frame(1, 10, 1), [])
+        self.assertEqual(tracker.on_frame(1, 12, 2), [11])
+        self.assertEqual(tracker.get_stats().nacks_sent, 1)
+
+    def test_streams_and_wraparound(self):
+        """Ogni stream ha la sua sequenza e il riavvolgimento del contatore non è un salto"""
+        tracker = NackTracker()
+        tracker.on_frame(1, 0xFFFFFFFE, 0)
+        tracker.on_frame(2, 100, 0)
+        self.assertEqual(tracker.on_frame(1, 1, 1), [0xFFFFFFFF, 0])
+        self.assertEqual(tracker.on_frame(2, 101, 1), [])
+
+    def test_window_change(self):
+        """Una finestra più ampia lascia più tempo alla riparazione"""
+        tracker = NackTracker(20)
+        tracker.set_window_ms(100)
+        tracker.on_frame(1, 0, 0)
+        tracker.on_frame(1, 2, 0)
+        tracker.expire(50)
+        tracker.on_frame(1, 1, 60)
+        self.assertEqual((tracker.get_stats().repaired, tracker.get_stats().expired), (1, 0))
+
+class TestPlayoutDeadline(unittest.TestCase):
+    """Test per la decisione di ritrasmettere un frame"""
+
+    def test_deadline(self):
+        """Si ritrasmette solo se il frame arriva entro l'istante di riproduzione"""
+        self.assertTrue(arrives_before_playout(1000, 5000, 3000))
+        self.assertTrue(arrives_before_playout(1000, 5000, 4000))
+        self.assertFalse(arrives_before_playout(1000, 5000, 4001))
+        self.assertFalse(arrives_before_playout(6000, 5000, 0))
+
+class TestRepairConfig(ConfigFileTestCase):
+    """Test per le chiavi della riparazione nel file"""
+
+    HEADER = '[node]\nid = "sink-1"\nrole = "sink"\n\n[audio]\n'
+
+    def test_defaults(self):
+        """La riparazione è attiva con una finestra di 20 ms"""
+        repair = RepairConfig()
+        self.assertTrue(repair.enabled)
+        self.assertEqual((repair.window_ms, repair.cache_frames), (20, 64))
+
+    def test_keys(self):
+        """Le chiavi audio.repair_* sostituiscono i valori predefiniti"""
+        repair = self.load("repair_enabled = false\nrepair_window_ms = 40\nrepair_cache_frames = 128\n").repair
+        self.assertFalse(repair.enabled)
+        self.assertEqual((repair.window_ms, repair.cache_frames), (40, 128))
+
+    def test_negative(self):
+        """Valori negativi rendono il file non valido"""
+        for key in ("repair_window_ms", "repair_cache_frames"):
+            with self.assertRaises(RuntimeError, msg=key):
+                self.load("%s = -1\n" % key)
+
+if __name__ == "__main__":
+    unittest.main()
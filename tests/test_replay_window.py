@@ -0,0 +1,89 @@
+# Test unitari per la finestra anti-replay di MeshCrypto
+# Verifica il rifiuto dei nonce ripetuti, troppo vecchi o di istanze precedenti del mittente
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import MeshCrypto, SecurityEvent, DEFAULT_REPLAY_WINDOW
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+NETWORK_KEY = [7] * 32
+
+class TestReplayWindow(unittest.TestCase):
+    """Test per la finestra anti-replay per mittente"""
+    
+    def setUp(self):
+        """Crea mittente e destinatario con la stessa chiave di rete"""
+        self.sender = MeshCrypto.with_network_key(NETWORK_KEY)
+        self.receiver = MeshCrypto.with_network_key(NETWORK_KEY)
+        self.events = []
+        self.receiver.set_security_event_handler(self.events.append)
+    
+    def test_default_window(self):
+        """La finestra predefinita è quella documentata"""
+        self.assertEqual(self.receiver.get_replay_window(), DEFAULT_REPLAY_WINDOW)
+    
+    def test_repeated_nonce_rejected(self):
+        """Lo stesso pacchetto è accettato una sola volta"""
+        packet = self.sender.encrypt([1, 2, 3])
+        self.assertEqual(self.receiver.decrypt_from("node-1", packet), [1, 2, 3])
+        with self.assertRaises(RuntimeError):
+            self.receiver.decrypt_from("node-1", packet)
+        self.assertEqual(self.receiver.get_replay_rejections(), 1)
+        self.assertEqual(self.events[-1].type, SecurityEvent.Type.ReplayRejected)
+        self.assertEqual(self.events[-1].node_id, "node-1")
+    
+    def test_out_of_order_within_window(self):
+        """I pacchetti fuori ordine dentro la finestra sono accettati"""
+        first = self.sender.encrypt([1])
+        second = self.sender.encrypt([2])
+        self.assertEqual(self.receiver.decrypt_from("node-1", second), [2])
+        self.assertEqual(self.receiver.decrypt_from("node-1", first), [1])
+    
+    def test_too_old_rejected(self):
+        """Un nonce più vecchio della finestra viene rifiutato anche se mai visto"""
+        self.receiver.set_replay_window(4)
+        old = self.sender.encrypt([0])
+        for _ in range(4):
+            self.receiver.decrypt_from("node-1", self.sender.encrypt([1]))
+        with self.assertRaises(RuntimeError):
+            self.receiver.decrypt_from("node-1", old)
+    
+    def test_windows_are_per_sender(self):
+        """Lo stesso nonce da mittenti diversi non è una ripetizione"""
+        packet = self.sender.encrypt([5])
+        self.receiver.decrypt_from("node-1", packet)
+        self.assertEqual(self.receiver.decrypt_from("node-2", packet), [5])
+    
+    def test_previous_instance_rejected(self):
+        """Dopo un riavvio del mittente i pacchetti dell'istanza precedente restano rifiutati"""
+        old = self.sender.encrypt([1])
+        self.receiver.decrypt_from("node-1", old)
+        restarted = MeshCrypto.with_network_key(NETWORK_KEY)
+        self.assertEqual(self.receiver.decrypt_from("node-1", restarted.encrypt([2])), [2])
+        with self.assertRaises(RuntimeError):
+            self.receiver.decrypt_from("node-1", self.sender.encrypt([3]))
+    
+    def test_forged_packet_does_not_advance_window(self):
+        """Un pacchetto non autentico non tocca la finestra"""
+        packet = bytearray(self.sender.encrypt([9]))
+        packet[-1] ^= 0xFF
+        with self.assertRaises(RuntimeError):
+            self.receiver.decrypt_from("node-1", list(packet))
+        self.assertEqual(self.receiver.get_replay_rejections(), 0)
+    
+    def test_zero_window_rejected(self):
+        """Una finestra vuota non è valida"""
+        with self.assertRaises(ValueError):
+            self.receiver.set_replay_window(0)
+
+if __name__ == '__main__':
+    unittest.main()
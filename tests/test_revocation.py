@@ -0,0 +1,129 @@
+# Test unitari per la lista di revoca dei token
+# Verifica la revoca di token e nodi, l'unione delle liste e la codifica scambiata tra i nodi
+
+import os
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (MeshCrypto, Revocation, RevocationKind, RevocationList, SaberConfig, SaberProtocol,
+                                encode_revocations, parse_revocations, revocation_kind_from_string,
+                                revocation_kind_to_string)
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def make_entry(kind, subject, revoked_at_ms):
+    entry = Revocation()
+    entry.kind = kind
+    entry.subject = subject
+    entry.revoked_at_ms = revoked_at_ms
+    return entry
+
+def describe(entries):
+    return [(entry.kind, entry.subject, entry.revoked_at_ms) for entry in entries]
+
+class TestRevocationList(unittest.TestCase):
+    """Test per la lista di revoca"""
+
+    def test_token(self):
+        """Un token revocato resta revocato, qualunque sia l'emissione"""
+        revocations = RevocationList()
+        self.assertTrue(revocations.add(make_entry(RevocationKind.Token, "ab12", 1000)))
+        self.assertFalse(revocations.add(make_entry(RevocationKind.Token, "ab12", 2000)))
+        self.assertTrue(revocations.is_token_revoked("ab12"))
+        self.assertTrue(revocations.is_revoked("ab12", "node-1", 5000))
+        self.assertFalse(revocations.is_revoked("cd34", "node-1", 5000))
+
+    def test_node(self):
+        """Un nodo revocato perde i token emessi fino alla revoca, non quelli successivi"""
+        revocations = RevocationList()
+        revocations.add(make_entry(RevocationKind.Node, "node-1", 1000))
+        self.assertTrue(revocations.is_revoked("ab12", "node-1", 1000))
+        self.assertFalse(revocations.is_revoked("ab12", "node-1", 1001))
+        self.assertFalse(revocations.is_revoked("ab12", "node-2", 500))
+        # Una revoca più recente estende l'effetto, una più vecchia non cambia nulla
+        self.assertTrue(revocations.add(make_entry(RevocationKind.Node, "node-1", 3000)))
+        self.assertFalse(revocations.add(make_entry(RevocationKind.Node, "node-1", 2000)))
+        self.assertTrue(revocations.is_revoked("ab12", "node-1", 2500))
+
+    def test_merge(self):
+        """L'unione restituisce solo le voci nuove e non dipende dall'ordine"""
+        entries = [make_entry(RevocationKind.Token, "ab12", 1000), make_entry(RevocationKind.Node, "node-1", 2000)]
+        first = RevocationList()
+        self.assertEqual(len(first.merge(entries)), 2)
+        self.assertEqual(first.merge(entries), [])
+        second = RevocationList()
+        second.merge(list(reversed(entries)))
+        self.assertEqual(describe(first.entries()), describe(second.entries()))
+        self.assertEqual(first.size(), 2)
+
+    def test_empty_subject(self):
+        """Una voce senza oggetto viene ignorata"""
+        revocations = RevocationList()
+        self.assertFalse(revocations.add(make_entry(RevocationKind.Node, "", 1000)))
+        self.assertEqual(revocations.size(), 0)
+
+class TestEncoding(unittest.TestCase):
+    """Test per la codifica delle voci nei comandi della mesh"""
+
+    def test_round_trip(self):
+        """Le voci codificate si rileggono uguali, anche con ':' nell'ID del nodo"""
+        entries = [make_entry(RevocationKind.Token, "ab12", 1000), make_entry(RevocationKind.Node, "sink:2", 2000)]
+        text = encode_revocations(entries)
+        self.assertEqual(text, "token:ab12:1000,node:sink:2:2000")
+        self.assertEqual(describe(parse_revocations(text)), describe(entries))
+        self.assertEqual(parse_revocations(""), [])
+
+    def test_invalid(self):
+        """Un testo malformato viene rifiutato per intero"""
+        for invalid in ("token", "token:ab12", "token::1000", "key:ab12:1000", "token:ab12:x", "token:ab12:-1",
+                        "token:ab12:1000,,node:n:5"):
+            self.assertIsNone(parse_revocations(invalid), invalid)
+
+    def test_names(self):
+        """I nomi dei tipi di revoca"""
+        for kind in (RevocationKind.Token, RevocationKind.Node):
+            self.assertEqual(revocation_kind_from_string(revocation_kind_to_string(kind)), kind)
+        self.assertIsNone(revocation_kind_from_string("key"))
+
+class TestSecurityToken(unittest.TestCase):
+    """Test per la verifica dei token con la lista di revoca"""
+
+    def test_node_revocation(self):
+        """La revoca del soggetto invalida i token già emessi prima della scadenza"""
+        crypto = MeshCrypto()
+        token = crypto.generate_security_token("node-1", 60)
+        issued_at = crypto.verify_security_token(token).issued_at
+        crypto.get_revocation_list().add(make_entry(RevocationKind.Node, "node-1", issued_at))
+        with self.assertRaises(RuntimeError):
+            crypto.verify_security_token(token)
+        # I token emessi dopo la revoca restano validi
+        time.sleep(0.01)
+        self.assertEqual(crypto.verify_security_token(crypto.generate_security_token("node-1", 60)).node_id, "node-1")
+
+    def test_shared_list(self):
+        """La lista impostata viene consultata ed aggiornata dalla revoca dei token"""
+        crypto = MeshCrypto()
+        revocations = RevocationList()
+        crypto.set_revocation_list(revocations)
+        token = crypto.generate_security_token("node-1", 60)
+        token_id = crypto.verify_security_token(token).token_id
+        crypto.revoke_security_token(token_id)
+        self.assertTrue(revocations.is_token_revoked(token_id))
+        with self.assertRaises(RuntimeError):
+            crypto.verify_security_token(token)
+
+    def test_protocol(self):
+        """Senza rete il protocollo non registra revoche"""
+        protocol = SaberProtocol(SaberConfig.default_config())
+        self.assertFalse(protocol.revoke_node_tokens("node-1"))
+        self.assertEqual(protocol.get_revocations(), [])
+
+if __name__ == "__main__":
+    unittest.main()
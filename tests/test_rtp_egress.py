@@ -0,0 +1,68 @@
+# Test unitari per l'uscita audio di rete RTP/AES67 dei sink
+# Verifica il formato dei pacchetti inviati all'amplificatore e la descrizione SDP del flusso
+
+import os
+import socket
+import struct
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src', 'control'))
+
+try:
+    # Importo i moduli da testare
+    from libpy_audio import AudioController
+except ImportError:
+    print("Errore: impossibile importare i moduli audio. Assicurati di averli compilati.")
+    sys.exit(1)
+
+# Intestazione RTP fissa: versione, tipo di payload, sequenza, timestamp e SSRC
+RTP_HEADER_BYTES = 12
+
+class TestRtpEgress(unittest.TestCase):
+    """Test per l'invio dell'audio sincronizzato ad un ricevitore RTP"""
+
+    def setUp(self):
+        self.receiver = socket.socket(socket.AF_INET, socket.SOCK_DGRAM)
+        self.receiver.bind(("127.0.0.1", 0))
+        self.receiver.settimeout(2)
+        self.addCleanup(self.receiver.close)
+        self.port = self.receiver.getsockname()[1]
+
+        self.audio = AudioController()
+        self.addCleanup(self.audio.stop_stream)
+        self.assertTrue(self.audio.set_rtp_output("127.0.0.1", self.port, payload_type=97, encoding="L16",
+                                                  packet_time_us=1000))
+        self.assertTrue(self.audio.initialize(48000, 2))
+
+    def receive(self):
+        data = self.receiver.recv(2048)
+        version, payload_type, sequence, timestamp, ssrc = struct.unpack("!BBHII", data[:RTP_HEADER_BYTES])
+        return version, payload_type, sequence, timestamp, len(data) - RTP_HEADER_BYTES
+
+    def test_packet_format(self):
+        """I pacchetti hanno intestazione RTP v2, 1 ms di campioni L16 stereo e media clock crescente"""
+        self.assertTrue(self.audio.play_stream("", 20))
+        version, payload_type, sequence, timestamp, payload = self.receive()
+        self.assertEqual(version, 0x80)
+        self.assertEqual(payload_type, 97)
+        self.assertEqual(payload, 48 * 2 * 2)
+
+        _, _, next_sequence, next_timestamp, _ = self.receive()
+        self.assertEqual(next_sequence, (sequence + 1) & 0xFFFF)
+        self.assertGreater((next_timestamp - timestamp) & 0xFFFFFFFF, 0)
+
+    def test_sdp(self):
+        """La descrizione SDP annuncia destinazione, formato e durata del pacchetto"""
+        sdp = self.audio.get_rtp_sdp("SABER", "127.0.0.1")
+        self.assertIn("m=audio " + str(self.port) + " RTP/AVP 97\r\n", sdp)
+        self.assertIn("a=rtpmap:97 L16/48000/2\r\n", sdp)
+        self.assertIn("a=ptime:1\r\n", sdp)
+
+    def test_invalid_encoding(self):
+        """Un formato diverso da L24 o L16 viene rifiutato"""
+        self.assertFalse(AudioController().set_rtp_output("127.0.0.1", self.port, encoding="L32"))
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,89 @@
+# Test unitari per la programmazione oraria del Master SABER
+# Verifica orari, giorni e scostamento da UTC indipendente dal fuso del sistema
+
+import os
+import sys
+import tempfile
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    import saber_protocol
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+# La build minimal-sink non contiene la programmazione oraria del Master
+Scheduler = getattr(saber_protocol, "Scheduler", None)
+
+MINUTE = 60 * 1000
+DAY = 24 * 60 * MINUTE
+
+# Lunedì 2024-01-01 00:00 UTC
+MONDAY = 1704067200 * 1000
+
+@unittest.skipIf(Scheduler is None, "compilato con SABER_MINIMAL_SINK")
+class TestScheduler(unittest.TestCase):
+    """Test per la valutazione delle azioni programmate"""
+
+    def test_parsing(self):
+        """Orari, giorni e scostamenti vengono interpretati"""
+        self.assertEqual(Scheduler.parse_time_of_day("18:00"), 18 * 60)
+        self.assertIsNone(Scheduler.parse_time_of_day("24:00"))
+        self.assertEqual(Scheduler.parse_days("mon-fri"), 0x1F)
+        self.assertEqual(Scheduler.parse_days("fri-mon"), 0x71)
+        self.assertIsNone(Scheduler.parse_days("playback"))
+        self.assertEqual(Scheduler.parse_utc_offset("-05:30"), -330)
+
+    def test_first_check_does_not_fire(self):
+        """All'avvio le azioni già passate non vengono eseguite"""
+        scheduler = Scheduler()
+        scheduler.add(0, 0x7F, "playback stop patio")
+        self.assertEqual(scheduler.due(MONDAY + MINUTE), [])
+
+    def test_fires_once_at_time(self):
+        """Un'azione viene eseguita una sola volta al suo orario"""
+        scheduler = Scheduler()
+        scheduler.add(18 * 60, 0x7F, "playback start patio aperitivo")
+        scheduler.due(MONDAY + 18 * 60 * MINUTE - 1000)
+        due = scheduler.due(MONDAY + 18 * 60 * MINUTE + 100)
+        self.assertEqual([entry.command for entry in due], ["playback start patio aperitivo"])
+        self.assertEqual(scheduler.due(MONDAY + 18 * 60 * MINUTE + 200), [])
+
+    def test_utc_offset(self):
+        """Le 18:00 a UTC+02:00 sono le 16:00 UTC"""
+        scheduler = Scheduler(120)
+        scheduler.add(18 * 60, 0x7F, "playback start patio")
+        scheduler.due(MONDAY + 16 * 60 * MINUTE - 1000)
+        self.assertEqual(len(scheduler.due(MONDAY + 16 * 60 * MINUTE + 100)), 1)
+
+    def test_days_mask(self):
+        """Un'azione dei giorni feriali non scatta di sabato"""
+        scheduler = Scheduler()
+        scheduler.add(9 * 60, 0x1F, "playback start shop")
+        saturday = MONDAY + 5 * DAY + 9 * 60 * MINUTE
+        scheduler.due(saturday - 1000)
+        self.assertEqual(scheduler.due(saturday + 100), [])
+
+    def test_persistence(self):
+        """La programmazione sopravvive ad un salvataggio e ricaricamento"""
+        scheduler = Scheduler()
+        first = scheduler.add(18 * 60, 0x1F, "playback start patio aperitivo")
+        scheduler.add(23 * 60, 0x7F, "playback stop patio")
+        path = os.path.join(tempfile.mkdtemp(), "schedule.txt")
+        self.assertTrue(scheduler.save(path))
+
+        loaded = Scheduler()
+        self.assertTrue(loaded.load(path))
+        entries = loaded.entries()
+        self.assertEqual([entry.command for entry in entries],
+                         ["playback start patio aperitivo", "playback stop patio"])
+        self.assertEqual(entries[0].id, first)
+        self.assertEqual(entries[0].days, 0x1F)
+        self.assertGreater(loaded.add(0, 0x7F, "status"), entries[-1].id)
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,65 @@
+# Test unitari per i token di sicurezza del protocollo SABER
+# Verifica ambito, revoca e legame con la chiave d'identità del nodo
+
+import os
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import MeshCrypto, TokenScope
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestSecurityToken(unittest.TestCase):
+    """Test per l'emissione e la verifica dei token"""
+    
+    def setUp(self):
+        """Crea l'identità del nodo che emette i token"""
+        self.crypto = MeshCrypto()
+    
+    def test_scope_round_trip(self):
+        """Ambito e soggetto sopravvivono alla verifica"""
+        token = self.crypto.generate_security_token("node-1", 60, TokenScope.ReadOnly)
+        verified = self.crypto.verify_security_token(token)
+        self.assertEqual(verified.node_id, "node-1")
+        self.assertEqual(verified.scope, TokenScope.ReadOnly)
+        self.assertGreater(verified.expiry, verified.issued_at)
+    
+    def test_default_scope_is_admin(self):
+        """Senza ambito esplicito il token è admin"""
+        token = self.crypto.generate_security_token("node-1", 60)
+        self.assertEqual(self.crypto.verify_security_token(token).scope, TokenScope.Admin)
+    
+    def test_revoked_token_rejected(self):
+        """Un token revocato non viene più accettato"""
+        token = self.crypto.generate_security_token("node-1", 60)
+        token_id = self.crypto.verify_security_token(token).token_id
+        self.crypto.revoke_security_token(token_id)
+        self.assertTrue(self.crypto.is_token_revoked(token_id))
+        with self.assertRaises(RuntimeError):
+            self.crypto.verify_security_token(token)
+    
+    def test_expired_token_rejected(self):
+        """Un token con validità nulla scade subito"""
+        token = self.crypto.generate_security_token("node-1", 0)
+        time.sleep(0.01)
+        with self.assertRaises(RuntimeError):
+            self.crypto.verify_security_token(token)
+    
+    def test_token_from_other_identity_rejected(self):
+        """Un token firmato da un'altra identità viene rifiutato"""
+        key = list(range(32))
+        issuer = MeshCrypto.with_network_key(key)
+        verifier = MeshCrypto.with_network_key(key)
+        token = issuer.generate_security_token("node-1", 60)
+        with self.assertRaises(RuntimeError):
+            verifier.verify_security_token(token)
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,120 @@
+# Test unitari per le chiavi di sessione tra coppie di nodi
+# Verifica la derivazione X25519, la cifratura dei pacchetti unicast, la configurazione e i contatori
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import MeshCrypto, MeshPacket, NodeRole, SaberConfig, SaberProtocol, SimNetwork
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+NETWORK_KEY = [7] * 32
+
+class TestSessionKey(unittest.TestCase):
+    """Test per la chiave derivata tra due nodi che si conoscono"""
+
+    def setUp(self):
+        self.master = MeshCrypto.with_network_key(NETWORK_KEY)
+        self.sink = MeshCrypto.with_network_key(NETWORK_KEY)
+        self.master.register_node_key("sink-1", self.sink.get_public_key())
+        self.sink.register_node_key("master-1", self.master.get_public_key())
+
+    def test_symmetric(self):
+        """Entrambi i nodi ricavano la stessa chiave dalle chiavi d'identità"""
+        self.assertTrue(self.master.has_session_peer("sink-1"))
+        self.assertEqual(self.master.session_key("sink-1"), self.sink.session_key("master-1"))
+        self.assertFalse(self.master.has_session_peer("sink-2"))
+
+    def test_round_trip(self):
+        """Il destinatario apre il contenuto, un terzo nodo con la chiave di rete no"""
+        data = self.master.encrypt_for("sink-1", b"volume", b"aad")
+        self.assertEqual(self.sink.decrypt_from_peer("master-1", data, b"aad"), b"volume")
+        other = MeshCrypto.with_network_key(NETWORK_KEY)
+        other.register_node_key("master-1", self.master.get_public_key())
+        with self.assertRaises(RuntimeError):
+            other.decrypt_from_peer("master-1", data, b"aad")
+        with self.assertRaises(RuntimeError):
+            other.decrypt(data, b"aad")
+
+    def test_cache_and_epoch(self):
+        """La chiave resta in cache fino al cambio della chiave di rete"""
+        epoch = self.master.get_key_epoch()
+        self.master.session_key("sink-1")
+        self.master.session_key("sink-1")
+        stats = self.master.get_sessions().get_stats()
+        self.assertEqual((stats.derivations, stats.cache_hits, stats.sessions), (1, 1, 1))
+        self.master.set_network_key([9] * 32)
+        self.assertEqual(self.master.get_key_epoch(), epoch + 1)
+        self.master.session_key("sink-1")
+        self.assertEqual(self.master.get_sessions().get_stats().derivations, 2)
+        self.assertEqual(self.master.get_sessions().get_peers(), ["sink-1"])
+
+class TestSessionPacket(unittest.TestCase):
+    """Test per i pacchetti cifrati con la chiave di sessione"""
+
+    def setUp(self):
+        self.master = MeshCrypto.with_network_key(NETWORK_KEY)
+        self.sink = MeshCrypto.with_network_key(NETWORK_KEY)
+        self.master.register_node_key("sink-1", self.sink.get_public_key())
+        self.sink.register_node_key("master-1", self.master.get_public_key())
+        packet = MeshPacket.create_command("volume", {"level": "50", "target": "sink-1"})
+        packet.set_header("master-1", 42, 8)
+        packet.sign(self.master)
+        packet.seal_for(self.master, "sink-1")
+        self.encoded = packet.encode()
+
+    def test_round_trip(self):
+        """Il destinatario indicato nell'intestazione apre e verifica il pacchetto"""
+        received = MeshPacket.decode(self.encoded)
+        self.assertTrue(received.is_encrypted())
+        self.assertEqual(received.get_session_peer(), "sink-1")
+        opened = received.open(self.sink)
+        self.assertEqual(opened.get_sequence(), 42)
+        self.assertTrue(opened.verify_signature(self.sink))
+        self.assertNotIn(b"volume", self.encoded)
+
+    def test_plain_seal(self):
+        """La cifratura con la chiave di rete non indica alcun destinatario"""
+        packet = MeshPacket.create_command("volume", {"level": "50"})
+        packet.set_header("master-1", 43, 8)
+        packet.seal(self.master)
+        self.assertEqual(MeshPacket.decode(packet.encode()).get_session_peer(), "")
+
+class TestSessionConfig(ConfigFileTestCase):
+    """Test per la configurazione letta dal file"""
+
+    HEADER = '[node]\nid = "master-1"\nrole = "master"\n\n'
+
+    def test_values(self):
+        """Le chiavi di sessione sono attive salvo security.session_keys = false"""
+        self.assertTrue(self.load('').session_keys)
+        self.assertFalse(self.load('[security]\nsession_keys = false\n').session_keys)
+
+class TestSessionStats(unittest.TestCase):
+    """Test per i contatori esposti dal protocollo"""
+
+    def test_metrics(self):
+        """Derivazioni e sessioni compaiono tra le metriche Prometheus"""
+        config = SaberConfig.default_config()
+        config.node_id = "session-master"
+        config.role = NodeRole.Master
+        protocol = SaberProtocol(config)
+        protocol.set_sim_network(SimNetwork(1))
+        if not protocol.initialize():
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        self.addCleanup(protocol.shutdown)
+        self.assertEqual(protocol.get_session_stats().sessions, 0)
+        text = protocol.get_prometheus_metrics()
+        self.assertIn("# TYPE saber_session_key_derivations_total counter", text)
+        self.assertIn("# TYPE saber_session_keys gauge", text)
+
+if __name__ == "__main__":
+    unittest.main()
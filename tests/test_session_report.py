@@ -0,0 +1,70 @@
+# Test unitari per i resoconti di sessione del protocollo SABER
+# Verifica il conteggio dei frame riprodotti, ricostruiti e scartati per ogni sink
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import SessionTracker
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+NOW_US = 1700000000 * 1000 * 1000
+
+class TestSessionTracker(unittest.TestCase):
+    """Test per le statistiche di riproduzione di un sink"""
+
+    def setUp(self):
+        self.tracker = SessionTracker("sink-1", 40)
+
+    def test_frames_played(self):
+        """I frame in tempo vengono contati con anticipo medio e latenza massima"""
+        for sequence, lead_ms in enumerate([30, 20, 10]):
+            self.tracker.record_frame(1, sequence, NOW_US + lead_ms * 1000, NOW_US)
+        report = self.tracker.finish(1)
+        self.assertEqual(report.node_id, "sink-1")
+        self.assertEqual(report.stream_id, 1)
+        self.assertEqual(report.frames_played, 3)
+        self.assertEqual(report.frames_dropped, 0)
+        self.assertEqual(report.average_offset_ms, 20)
+        self.assertEqual(report.max_latency_ms, 30)
+
+    def test_gaps_concealed(self):
+        """I frame mancanti nella sequenza vengono contati come ricostruiti"""
+        self.tracker.record_frame(1, 0, NOW_US + 20000, NOW_US)
+        self.tracker.record_frame(1, 3, NOW_US + 20000, NOW_US)
+        report = self.tracker.finish(1)
+        self.assertEqual(report.frames_played, 2)
+        self.assertEqual(report.frames_concealed, 2)
+
+    def test_late_frames_and_rebuffers(self):
+        """Ogni passaggio da frame in tempo a frame in ritardo è un nuovo svuotamento"""
+        for sequence, lead_ms in enumerate([10, -5, -3, 10, -1]):
+            self.tracker.record_frame(1, sequence, NOW_US + lead_ms * 1000, NOW_US)
+        report = self.tracker.finish(1)
+        self.assertEqual(report.frames_played, 2)
+        self.assertEqual(report.frames_dropped, 3)
+        self.assertEqual(report.rebuffer_count, 2)
+        self.assertEqual(report.max_latency_ms, 45)
+
+    def test_idle_sessions_finish(self):
+        """Uno stream senza frame per l'intervallo indicato chiude la sessione"""
+        self.tracker.record_frame(1, 0, NOW_US + 20000, NOW_US)
+        self.tracker.record_frame(2, 0, NOW_US + 20000, NOW_US + 1500 * 1000)
+        self.assertEqual(self.tracker.finish_idle(NOW_US + 1000 * 1000, 2000), [])
+        reports = self.tracker.finish_idle(NOW_US + 2000 * 1000, 2000)
+        self.assertEqual([report.stream_id for report in reports], [1])
+        self.assertEqual([report.stream_id for report in self.tracker.finish_all()], [2])
+
+    def test_unknown_stream(self):
+        """Uno stream senza sessione aperta non produce resoconti"""
+        self.assertIsNone(self.tracker.finish(7))
+
+if __name__ == '__main__':
+    unittest.main()
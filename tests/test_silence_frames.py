@@ -0,0 +1,77 @@
+# Test unitari per la cache dei frame di silenzio e comfort noise
+# Verifica che l'encoder venga eseguito solo alla registrazione e che i frame siano riproducibili
+
+import os
+import struct
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src', 'control'))
+
+try:
+    # Importo i moduli da testare
+    from libpy_audio import CodecType, SilenceFrameCache
+except ImportError:
+    print("Errore: impossibile importare i moduli audio. Assicurati di averli compilati.")
+    sys.exit(1)
+
+# Frame da 10 ms a 48 kHz stereo
+SAMPLE_RATE = 48000
+CHANNELS = 2
+FRAME_US = 10000
+SAMPLES = SAMPLE_RATE * FRAME_US // 1000000 * CHANNELS
+
+class TestSilenceFrameCache(unittest.TestCase):
+    """Test per i frame pre-calcolati usati nel concealment"""
+
+    def test_raw_pcm(self):
+        """Senza encoder i frame sono PCM float: silenzio digitale e rumore entro il livello"""
+        cache = SilenceFrameCache(0.001)
+        cache.register_codec(CodecType.Pcm, SAMPLE_RATE, CHANNELS, FRAME_US)
+        self.assertTrue(cache.has_codec(CodecType.Pcm))
+        self.assertEqual(cache.silence_frame(CodecType.Pcm), bytes(SAMPLES * 4))
+
+        noise = cache.comfort_noise_samples(CodecType.Pcm)
+        self.assertEqual(len(noise), SAMPLES)
+        self.assertTrue(all(abs(sample) <= 0.0011 for sample in noise))
+        self.assertTrue(any(sample != 0.0 for sample in noise))
+        decoded = struct.unpack("<%df" % SAMPLES, cache.comfort_noise_frame(CodecType.Pcm))
+        self.assertEqual(list(decoded), noise)
+
+    def test_encoder_called_once(self):
+        """L'encoder viene invocato solo alla registrazione, non ad ogni lettura"""
+        calls = []
+        def encoder(samples):
+            calls.append(len(samples))
+            return b"lc3" + bytes([len(calls)])
+        cache = SilenceFrameCache()
+        cache.register_codec(CodecType.Lc3, SAMPLE_RATE, CHANNELS, FRAME_US, encoder)
+        self.assertEqual(calls, [SAMPLES, SAMPLES])
+        for _ in range(10):
+            self.assertEqual(cache.silence_frame(CodecType.Lc3), b"lc3\x01")
+            self.assertEqual(cache.comfort_noise_frame(CodecType.Lc3), b"lc3\x02")
+        self.assertEqual(len(calls), 2)
+
+    def test_deterministic_noise(self):
+        """Lo stesso seme produce lo stesso comfort noise, un seme diverso no"""
+        def noise(seed):
+            cache = SilenceFrameCache(0.01, seed)
+            cache.register_codec(CodecType.Pcm, SAMPLE_RATE, CHANNELS, FRAME_US)
+            return cache.comfort_noise_samples(CodecType.Pcm)
+        self.assertEqual(noise(7), noise(7))
+        self.assertNotEqual(noise(7), noise(8))
+
+    def test_invalid_arguments(self):
+        """Codec non registrati, formati vuoti e livelli fuori scala vengono rifiutati"""
+        cache = SilenceFrameCache()
+        self.assertFalse(cache.has_codec(CodecType.Lc3))
+        with self.assertRaises(IndexError):
+            cache.silence_frame(CodecType.Lc3)
+        with self.assertRaises(ValueError):
+            cache.register_codec(CodecType.Pcm, SAMPLE_RATE, 0, FRAME_US)
+        with self.assertRaises(ValueError):
+            SilenceFrameCache(1.5)
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,149 @@
+# Test unitari per la rete simulata
+# Verifica latenza, jitter, perdita e partizioni della rete virtuale e l'avvio di più nodi nello stesso processo
+
+import os
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import NodeRole, SaberConfig, SaberProtocol, SimLinkConfig, SimNetwork
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def link(latency_ms=5, jitter_ms=0, loss_rate=0.0):
+    config = SimLinkConfig()
+    config.latency_ms = latency_ms
+    config.jitter_ms = jitter_ms
+    config.loss_rate = loss_rate
+    return config
+
+class Inbox:
+    """Raccoglie i pacchetti consegnati ad un nodo con l'istante di arrivo"""
+
+    def __init__(self, network):
+        self.network = network
+        self.received = []
+
+    def __call__(self, datagram):
+        self.received.append((self.network.now(), datagram))
+
+class TestSimNetwork(unittest.TestCase):
+    """Test per la rete virtuale"""
+
+    def setUp(self):
+        self.network = SimNetwork(7, link(latency_ms=10))
+        self.inboxes = {}
+        for node_id in ("a", "b", "c"):
+            self.inboxes[node_id] = Inbox(self.network)
+            self.network.attach(node_id, self.inboxes[node_id])
+
+    def test_latency(self):
+        """Un pacchetto raggiunge gli altri nodi dopo la latenza, il mittente no"""
+        self.assertTrue(self.network.send("a", b"ciao"))
+        self.assertEqual(self.network.advance(9), 0)
+        self.assertEqual(self.network.advance(1), 2)
+        self.assertEqual(self.inboxes["b"].received, [(10, b"ciao")])
+        self.assertEqual(self.inboxes["c"].received, [(10, b"ciao")])
+        self.assertEqual(self.inboxes["a"].received, [])
+        self.assertFalse(self.network.send("sconosciuto", b"ciao"))
+
+    def test_per_link(self):
+        """Un collegamento può avere caratteristiche proprie in un solo verso"""
+        self.network.set_link("a", "c", link(latency_ms=50))
+        self.assertEqual(self.network.get_link("c", "a").latency_ms, 10)
+        self.network.send("a", b"x")
+        self.network.advance(100)
+        self.assertEqual(self.inboxes["b"].received[0][0], 10)
+        self.assertEqual(self.inboxes["c"].received[0][0], 50)
+        self.network.clear_link("a", "c")
+        self.assertEqual(self.network.get_link("a", "c").latency_ms, 10)
+
+    def test_loss(self):
+        """Con perdita totale nessuna copia arriva"""
+        self.network.set_default_link(link(loss_rate=1.0))
+        self.network.send("a", b"x")
+        self.assertEqual(self.network.advance(100), 0)
+        self.assertEqual(self.network.get_stats().lost, 2)
+
+    def test_partition(self):
+        """Una partizione separa i nodi finché la rete non viene ricomposta"""
+        self.network.partition({"c"})
+        self.assertFalse(self.network.can_reach("a", "c"))
+        self.network.send("a", b"x")
+        self.network.advance(20)
+        self.assertEqual(len(self.inboxes["b"].received), 1)
+        self.assertEqual(self.inboxes["c"].received, [])
+
+        # Le copie in viaggio al momento della partizione vanno perse
+        self.network.heal()
+        self.network.send("a", b"y")
+        self.network.partition({"b"})
+        self.network.advance(20)
+        self.assertEqual(len(self.inboxes["b"].received), 1)
+        self.assertEqual(self.inboxes["c"].received, [(30, b"y")])
+        self.assertEqual(self.network.get_stats().partitioned, 2)
+
+    def test_detach(self):
+        """Un nodo scollegato non riceve le copie ancora in viaggio"""
+        self.network.send("a", b"x")
+        self.network.detach("b")
+        self.network.advance(20)
+        self.assertEqual(self.inboxes["b"].received, [])
+        self.assertEqual(self.network.get_nodes(), ["a", "c"])
+
+    def test_deterministic(self):
+        """La stessa seed produce le stesse consegne nello stesso ordine"""
+        def run(seed):
+            network = SimNetwork(seed, link(latency_ms=5, jitter_ms=20, loss_rate=0.3))
+            inbox = Inbox(network)
+            network.attach("tx", lambda datagram: None)
+            network.attach("rx", inbox)
+            for i in range(50):
+                network.send("tx", bytes([i]))
+            network.advance(100)
+            return inbox.received
+
+        first = run(42)
+        self.assertEqual(first, run(42))
+        self.assertNotEqual(first, run(43))
+        self.assertLess(len(first), 50)
+        self.assertNotEqual([datagram for _, datagram in first], sorted(datagram for _, datagram in first))
+
+class TestSimulatedNodes(unittest.TestCase):
+    """Test per più protocolli collegati alla stessa rete simulata"""
+
+    def start(self, node_id, role, network):
+        config = SaberConfig.default_config()
+        config.node_id = node_id
+        config.role = role
+        protocol = SaberProtocol(config)
+        protocol.set_sim_network(network)
+        if not protocol.initialize():
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        self.addCleanup(protocol.shutdown)
+        return protocol
+
+    def test_join(self):
+        """Il Master scopre il sink attraverso la rete simulata"""
+        network = SimNetwork(1)
+        master = self.start("sim-master", NodeRole.Master, network)
+        self.start("sim-sink", NodeRole.Sink, network)
+        self.assertEqual(network.get_nodes(), ["sim-master", "sim-sink"])
+
+        deadline = time.monotonic() + 5.0
+        while time.monotonic() < deadline:
+            network.advance(50)
+            if any(node.id == "sim-sink" for node in master.get_topology().nodes):
+                break
+            time.sleep(0.05)
+        self.assertTrue(any(node.id == "sim-sink" for node in master.get_topology().nodes))
+        self.assertGreater(network.get_stats().delivered, 0)
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,160 @@
+# Test unitari per gli stream simulcast
+# Verifica la scelta del livello dei sink, il passaggio tra livelli e la configurazione
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (SIMULCAST_SWITCH_TIMEOUT_MS, SaberConfig, SaberProtocol, SimulcastConfig,
+                                SimulcastGroup, SimulcastLayer, SimulcastReceiver, SimulcastVerdict,
+                                encode_simulcast_layers, parse_simulcast_layers, validate_simulcast_group)
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+FRAME_US = 10000
+
+def make_group(stream_id=1, layers=((1, 128), (2, 64))):
+    group = SimulcastGroup()
+    group.stream_id = stream_id
+    result = []
+    for layer_id, kbps in layers:
+        layer = SimulcastLayer()
+        layer.stream_id = layer_id
+        layer.bitrate_kbps = kbps
+        result.append(layer)
+    group.layers = result
+    return group
+
+class TestSimulcastGroup(unittest.TestCase):
+    """Test per la forma dei gruppi e la loro codifica negli annunci"""
+
+    def test_validation(self):
+        """Servono da 2 a 3 livelli, il primo sullo stream stesso, con bitrate decrescenti"""
+        validate_simulcast_group(make_group())
+        with self.assertRaises(ValueError):
+            validate_simulcast_group(make_group(layers=((1, 128),)))
+        with self.assertRaises(ValueError):
+            validate_simulcast_group(make_group(layers=((2, 128), (1, 64))))
+        with self.assertRaises(ValueError):
+            validate_simulcast_group(make_group(layers=((1, 64), (2, 128))))
+        with self.assertRaises(ValueError):
+            validate_simulcast_group(make_group(layers=((1, 128), (1, 64))))
+        with self.assertRaises(ValueError):
+            validate_simulcast_group(make_group(layers=((1, 128), (2, 96), (3, 64), (4, 32))))
+
+    def test_encoding(self):
+        """I livelli codificati si rileggono uguali; un testo malformato viene rifiutato"""
+        text = encode_simulcast_layers(make_group().layers)
+        self.assertEqual(text, "1:128,2:64")
+        layers = parse_simulcast_layers(text)
+        self.assertEqual([(layer.stream_id, layer.bitrate_kbps) for layer in layers], [(1, 128), (2, 64)])
+        for invalid in ("", "1", "1:x", "70000:64", "1:128,,2:64"):
+            self.assertIsNone(parse_simulcast_layers(invalid), invalid)
+
+class TestSimulcastReceiver(unittest.TestCase):
+    """Test per la scelta del livello e il passaggio al confine dei frame"""
+
+    def test_downgrade_on_budget(self):
+        """Con una banda insufficiente il sink scende subito e passa al nuovo livello senza ripetere frame"""
+        receiver = SimulcastReceiver(make_group())
+        self.assertEqual(receiver.wanted_layers(), [1])
+        self.assertTrue(receiver.update(100, 0))
+        self.assertEqual(receiver.wanted_layers(), [1, 2])
+        self.assertEqual(receiver.get_status().pending_layer, 2)
+
+        self.assertEqual(receiver.accept(1, 0, FRAME_US, 0), SimulcastVerdict.Play)
+        # Il frame già riprodotto dal livello alto prepara solo il decodificatore
+        self.assertEqual(receiver.accept(2, 0, FRAME_US, 0), SimulcastVerdict.Prime)
+        self.assertEqual(receiver.accept(1, 1, 2 * FRAME_US, 10), SimulcastVerdict.Drop)
+        self.assertEqual(receiver.accept(2, 1, 2 * FRAME_US, 10), SimulcastVerdict.Play)
+
+        status = receiver.get_status()
+        self.assertEqual((status.active_layer, status.bitrate_kbps, status.switches), (2, 64, 1))
+        self.assertIsNone(status.pending_layer)
+        self.assertFalse(receiver.update(100, 20))
+        self.assertEqual(receiver.wanted_layers(), [2])
+
+    def test_upgrade_after_clean_period(self):
+        """Il livello superiore viene richiesto solo dopo un periodo senza perdite"""
+        config = SimulcastConfig()
+        config.upgrade_after_ms = 1000
+        receiver = SimulcastReceiver(make_group(), config)
+        receiver.update(70, 0)
+        receiver.accept(2, 0, FRAME_US, 0)
+        self.assertEqual(receiver.get_status().active_layer, 2)
+
+        self.assertFalse(receiver.update(200, 500))
+        self.assertTrue(receiver.update(200, 1000))
+        self.assertEqual(receiver.get_status().pending_layer, 1)
+        # Il frame del livello alto successivo all'ultimo riprodotto viene riprodotto subito
+        self.assertEqual(receiver.accept(1, 1, 2 * FRAME_US, 1010), SimulcastVerdict.Play)
+        self.assertEqual(receiver.get_status().active_layer, 1)
+
+    def test_loss_estimate(self):
+        """Senza banda imposta, una perdita oltre la soglia limita la banda a quanto ricevuto"""
+        receiver = SimulcastReceiver(make_group())
+        for sequence in range(1, 60):
+            if sequence % 5 != 0:
+                receiver.accept(1, sequence, sequence * FRAME_US, sequence * 10)
+        self.assertTrue(receiver.update(None, 600))
+        status = receiver.get_status()
+        self.assertGreater(status.loss_percent, 15.0)
+        self.assertLess(status.budget_kbps, 128)
+        self.assertEqual(status.pending_layer, 2)
+
+    def test_clean_reception(self):
+        """Senza perdite e senza banda imposta il sink resta al livello più alto"""
+        receiver = SimulcastReceiver(make_group())
+        for sequence in range(100):
+            self.assertEqual(receiver.accept(1, sequence, sequence * FRAME_US, sequence * 10), SimulcastVerdict.Play)
+        self.assertFalse(receiver.update(None, 1000))
+        self.assertEqual(receiver.get_status().loss_percent, 0.0)
+        self.assertIsNone(receiver.get_status().budget_kbps)
+
+    def test_switch_timeout(self):
+        """Un livello che non arriva entro il tempo massimo annulla il cambio"""
+        receiver = SimulcastReceiver(make_group())
+        receiver.update(100, 0)
+        self.assertTrue(receiver.update(100, SIMULCAST_SWITCH_TIMEOUT_MS))
+        self.assertEqual(receiver.get_status().switches, 0)
+
+    def test_foreign_stream(self):
+        """I frame di stream estranei al gruppo vengono scartati"""
+        receiver = SimulcastReceiver(make_group())
+        self.assertFalse(receiver.has_layer(7))
+        self.assertEqual(receiver.accept(7, 0, FRAME_US, 0), SimulcastVerdict.Drop)
+
+class TestConfigFile(ConfigFileTestCase):
+    """Test per la scelta del livello letta dal file di configurazione"""
+
+    HEADER = '[node]\nid = "sink-1"\n\n[simulcast]\n'
+
+    def test_simulcast(self):
+        """La sezione [simulcast] imposta margine, attesa per salire e soglia di perdita"""
+        config = self.load('headroom_percent = 30\nupgrade_after_ms = 8000\nloss_threshold_percent = 10\n')
+        self.assertEqual(config.simulcast.headroom_percent, 30)
+        self.assertEqual(config.simulcast.upgrade_after_ms, 8000)
+        self.assertEqual(config.simulcast.loss_threshold_percent, 10)
+        with self.assertRaises(RuntimeError):
+            self.load('loss_threshold_percent = 0\n')
+        with self.assertRaises(RuntimeError):
+            self.load('headroom_percent = -5\n')
+
+    def test_protocol(self):
+        """Solo il Master pubblica gruppi simulcast, e solo se ben formati"""
+        protocol = SaberProtocol(SaberConfig.default_config())
+        with self.assertRaises(ValueError):
+            protocol.publish_simulcast(make_group(layers=((1, 128),)))
+        self.assertFalse(protocol.publish_simulcast(make_group()))
+        self.assertEqual(protocol.get_simulcast_groups(), [])
+        self.assertEqual(protocol.get_simulcast_status(), [])
+
+if __name__ == "__main__":
+    unittest.main()
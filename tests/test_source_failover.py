@@ -0,0 +1,189 @@
+# Test unitari per la scelta della sorgente audio del Master
+# Verifica il passaggio alla sorgente di riserva, la rotazione, il ritorno e il cambio manuale
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (AudioFrame, AudioSourceConfig, NodeRole, SaberConfig, SaberProtocol,
+                                SourceKind, SourceSelectionConfig, SourceSelector, ToneGenerator,
+                                source_kind_from_string, source_kind_to_string)
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def source(name, kind, priority):
+    result = AudioSourceConfig()
+    result.name = name
+    result.kind = kind
+    result.priority = priority
+    return result
+
+def selector(*sources, silence_ms=0, failback_ms=1000, auto_failback=True):
+    config = SourceSelectionConfig()
+    config.sources = list(sources)
+    config.stall_ms = 500
+    config.silence_ms = silence_ms
+    config.failback_ms = failback_ms
+    config.auto_failback = auto_failback
+    return SourceSelector(config)
+
+def frame(level=1000):
+    result = AudioFrame()
+    result.samples = [level] * 960
+    return result
+
+def feed(target, names, start_ms, end_ms, level=1000):
+    """Offre un frame ogni 10ms da ciascuna sorgente ed esegue update() ogni 100ms"""
+    changes = []
+    for now in range(start_ms, end_ms, 10):
+        for name in names:
+            target.offer_frame(name, frame(level), now)
+        if now % 100 == 0:
+            change = target.update(now)
+            if change:
+                changes.append((change.from_source, change.to_source, change.reason))
+    return changes
+
+class TestSourceSelector(unittest.TestCase):
+    """Test per il passaggio automatico tra sorgenti"""
+
+    def test_initial_choice(self):
+        """La prima sorgente attiva è quella in salute con la priorità migliore"""
+        target = selector(source("mixer", SourceKind.Capture, 0), source("tone", SourceKind.TestTone, 9))
+        self.assertIsNone(target.update(0))
+        changes = feed(target, ["mixer", "tone"], 0, 200)
+        self.assertEqual(changes, [("", "mixer", "initial")])
+        self.assertTrue(target.offer_frame("mixer", frame(), 200))
+        self.assertFalse(target.offer_frame("tone", frame(), 200))
+
+    def test_failover_on_stall(self):
+        """Una sorgente senza frame viene sostituita da quella di riserva"""
+        target = selector(source("mixer", SourceKind.Capture, 0), source("tone", SourceKind.TestTone, 9))
+        feed(target, ["mixer", "tone"], 0, 1000)
+        changes = feed(target, ["tone"], 1000, 2000)
+        self.assertEqual(changes, [("mixer", "tone", "stalled")])
+        self.assertEqual(target.get_active(), "tone")
+
+    def test_failover_on_silence(self):
+        """Una sorgente che offre solo silenzio viene sostituita"""
+        target = selector(source("rtp", SourceKind.Rtp, 0), source("queue", SourceKind.FileQueue, 1),
+                          silence_ms=1000)
+        feed(target, ["rtp", "queue"], 0, 500)
+        changes = []
+        for now in range(500, 3000, 10):
+            target.offer_frame("rtp", frame(0), now)
+            target.offer_frame("queue", frame(), now)
+            if now % 100 == 0:
+                change = target.update(now)
+                if change:
+                    changes.append((change.from_source, change.to_source, change.reason))
+        self.assertEqual(changes, [("rtp", "queue", "silent")])
+
+    def test_round_robin(self):
+        """A parità di priorità si passa alla sorgente successiva, a rotazione"""
+        target = selector(source("a", SourceKind.Capture, 0), source("b", SourceKind.Capture, 0),
+                          source("c", SourceKind.Capture, 0), auto_failback=False)
+        feed(target, ["a", "b", "c"], 0, 500)
+        self.assertEqual(target.get_active(), "a")
+        self.assertEqual(feed(target, ["a", "c"], 500, 1100), [])
+        self.assertEqual(feed(target, ["b", "c"], 1100, 2000), [("a", "b", "stalled")])
+        self.assertEqual(feed(target, ["a", "c"], 2000, 3000), [("b", "c", "stalled")])
+
+    def test_failback(self):
+        """La sorgente preferita torna attiva dopo failback_ms in salute"""
+        target = selector(source("mixer", SourceKind.Capture, 0), source("tone", SourceKind.TestTone, 9))
+        feed(target, ["mixer", "tone"], 0, 500)
+        feed(target, ["tone"], 500, 1500)
+        self.assertEqual(target.get_active(), "tone")
+        changes = feed(target, ["mixer", "tone"], 1500, 3000)
+        self.assertEqual(changes, [("tone", "mixer", "failback")])
+
+    def test_manual_switch(self):
+        """Il cambio manuale sospende il ritorno automatico finché la sorgente è in salute"""
+        target = selector(source("mixer", SourceKind.Capture, 0), source("tone", SourceKind.TestTone, 9))
+        feed(target, ["mixer", "tone"], 0, 500)
+        change = target.switch_to("tone")
+        self.assertEqual((change.from_source, change.to_source, change.reason), ("mixer", "tone", "manual"))
+        self.assertTrue(target.is_manual())
+        self.assertEqual(feed(target, ["mixer", "tone"], 500, 3000), [])
+        # La sorgente scelta si ferma: si torna alla scelta automatica
+        self.assertEqual(feed(target, ["mixer"], 3000, 4000), [("tone", "mixer", "stalled")])
+        self.assertFalse(target.is_manual())
+        with self.assertRaises(ValueError):
+            target.switch_to("sconosciuta")
+
+    def test_no_alternative(self):
+        """Senza sorgenti in salute si resta su quella corrente"""
+        target = selector(source("mixer", SourceKind.Capture, 0), source("tone", SourceKind.TestTone, 9))
+        feed(target, ["mixer"], 0, 500)
+        self.assertEqual(feed(target, [], 500, 2000), [])
+        self.assertEqual(target.get_active(), "mixer")
+        status = {entry.name: entry for entry in target.get_status(2000)}
+        self.assertFalse(status["mixer"].healthy)
+        self.assertTrue(status["mixer"].active)
+        self.assertIsNone(status["tone"].idle_ms)
+
+    def test_names(self):
+        """I tipi di sorgente hanno nomi stabili"""
+        self.assertEqual(source_kind_to_string(SourceKind.FileQueue), "file")
+        self.assertEqual(source_kind_from_string("tone"), SourceKind.TestTone)
+        self.assertIsNone(source_kind_from_string("microfono"))
+
+class TestToneGenerator(unittest.TestCase):
+    """Test per il tono di prova"""
+
+    def test_frames(self):
+        """Il tono produce frame di 10ms non silenziosi"""
+        generator = ToneGenerator(1000.0, 48000, 2)
+        first = generator.next(0)
+        self.assertEqual(len(first.samples), 960)
+        self.assertGreater(max(abs(sample) for sample in first.samples), 8000)
+
+class TestConfigFile(ConfigFileTestCase):
+    """Test per le sorgenti lette dal file di configurazione"""
+
+    HEADER = '[node]\nid = "master-1"\nrole = "master"\n'
+
+    def test_sources(self):
+        """Le sezioni [source.<nome>] diventano sorgenti ordinate per priorità"""
+        config = self.load('\n[sources]\nstall_ms = 3000\n'
+                           '\n[source.tone]\nkind = "tone"\npriority = 9\n'
+                           '\n[source.mixer]\nkind = "capture"\nlocation = "hw:1"\npriority = 0\n')
+        self.assertEqual(config.sources.stall_ms, 3000)
+        self.assertEqual([entry.name for entry in config.sources.sources], ["mixer", "tone"])
+        self.assertEqual(config.sources.sources[0].location, "hw:1")
+
+    def test_invalid_sources(self):
+        """Tipi sconosciuti e sorgenti senza tipo vengono rifiutati"""
+        with self.assertRaises(RuntimeError):
+            self.load('\n[source.mixer]\nkind = "microfono"\n')
+        with self.assertRaises(RuntimeError):
+            self.load('\n[source.mixer]\npriority = 1\n')
+
+class TestProtocolSources(unittest.TestCase):
+    """Test per le sorgenti sul protocollo"""
+
+    def test_manual_switch(self):
+        """Il protocollo espone la sorgente attiva e il cambio manuale"""
+        config = SaberConfig.default_config()
+        config.role = NodeRole.Master
+        selection = SourceSelectionConfig()
+        selection.sources = [source("mixer", SourceKind.Capture, 0), source("tone", SourceKind.TestTone, 9)]
+        config.sources = selection
+        protocol = SaberProtocol(config)
+        self.assertIsNone(protocol.get_active_source())
+        protocol.switch_source("tone")
+        self.assertEqual(protocol.get_active_source(), "tone")
+        self.assertEqual([entry.name for entry in protocol.get_source_status()], ["mixer", "tone"])
+        with self.assertRaises(ValueError):
+            protocol.switch_source("sconosciuta")
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,67 @@
+# Test unitari per i parametri di specifica del protocollo SABER
+# Verifica i valori predefiniti, le deviazioni segnalate e le sostituzioni dal file di configurazione
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (SPEC_JITTER_TOLERANCE_MS, SPEC_LATENCY_BUDGET_MS, SpecParameters)
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestSpecParameters(unittest.TestCase):
+    """Test per i parametri e la loro validazione"""
+
+    def test_defaults_follow_spec(self):
+        """I valori predefiniti sono quelli della specifica e non producono deviazioni"""
+        params = SpecParameters()
+        self.assertEqual(params.latency_budget_ms, SPEC_LATENCY_BUDGET_MS)
+        self.assertEqual(params.jitter_tolerance_ms, SPEC_JITTER_TOLERANCE_MS)
+        self.assertEqual(params.deviations(), [])
+        params.validate()
+
+    def test_deviations(self):
+        """Ogni valore diverso dalla specifica viene riportato con il valore atteso"""
+        params = SpecParameters()
+        params.jitter_tolerance_ms = SPEC_JITTER_TOLERANCE_MS + 1
+        self.assertEqual(params.deviations(), ["jitter_tolerance_ms=%d (spec: %d)"
+                                               % (SPEC_JITTER_TOLERANCE_MS + 1, SPEC_JITTER_TOLERANCE_MS)])
+
+    def test_inconsistent_values(self):
+        """Budget nullo, margine oltre il budget o buffer fuori dal budget vengono rifiutati"""
+        for name, value in (("latency_budget_ms", 0),
+                            ("buffer_margin_ms", SPEC_LATENCY_BUDGET_MS),
+                            ("default_buffer_ms", 0),
+                            ("default_buffer_ms", SPEC_LATENCY_BUDGET_MS + 1)):
+            params = SpecParameters()
+            setattr(params, name, value)
+            with self.assertRaises(ValueError, msg=name):
+                params.validate()
+
+class TestSpecOverrides(ConfigFileTestCase):
+    """Test per le sostituzioni nella sezione [spec] del file"""
+
+    HEADER = '[node]\nid = "sink-1"\nrole = "sink"\n\n[spec]\n'
+
+    def test_override(self):
+        """Un valore valido sostituisce quello della specifica ed è segnalato come deviazione"""
+        config = self.load("jitter_tolerance_ms = %d\n" % (SPEC_JITTER_TOLERANCE_MS + 2))
+        self.assertEqual(config.spec.jitter_tolerance_ms, SPEC_JITTER_TOLERANCE_MS + 2)
+        self.assertEqual(len(config.spec.deviations()), 1)
+
+    def test_invalid_override(self):
+        """Valori negativi o incoerenti rendono il file non valido"""
+        for text in ("latency_budget_ms = -1\n", "latency_budget_ms = 0\n",
+                     "buffer_margin_ms = %d\n" % SPEC_LATENCY_BUDGET_MS):
+            with self.assertRaises(RuntimeError, msg=text):
+                self.load(text)
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,81 @@
+# Test unitari per lo standby automatico dei sink del protocollo SABER
+# Verifica l'ingresso in standby, il risveglio con pre-roll e i resoconti diradati
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import PowerState, StandbyConfig, StandbyController, power_state_to_string
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+MINUTE_MS = 60000
+
+class TestStandbyController(unittest.TestCase):
+    """Test per la macchina a stati dello standby"""
+
+    def setUp(self):
+        config = StandbyConfig()
+        config.enabled = True
+        config.idle_minutes = 2
+        config.preroll_ms = 300
+        config.report_interval_ms = 30000
+        self.standby = StandbyController(config)
+
+    def idle_until_standby(self):
+        self.standby.update(False, 0)
+        self.assertEqual(self.standby.update(False, 2 * MINUTE_MS), PowerState.Standby)
+
+    def test_idle_enters_standby(self):
+        """Senza stream per l'intervallo configurato il sink va in standby"""
+        self.assertIsNone(self.standby.update(False, 0))
+        self.assertIsNone(self.standby.update(False, 2 * MINUTE_MS - 1))
+        self.assertEqual(self.standby.update(False, 2 * MINUTE_MS), PowerState.Standby)
+        self.assertEqual(self.standby.get_state(), PowerState.Standby)
+
+    def test_streaming_resets_idle(self):
+        """Uno stream sottoscritto azzera l'inattività"""
+        self.standby.update(False, 0)
+        self.standby.update(True, MINUTE_MS)
+        self.standby.update(False, MINUTE_MS + 1)
+        self.assertIsNone(self.standby.update(False, 2 * MINUTE_MS + 1))
+        self.assertEqual(self.standby.get_state(), PowerState.Active)
+
+    def test_wake_with_preroll(self):
+        """Il comando di risveglio attende il pre-roll prima di tornare attivo"""
+        self.idle_until_standby()
+        now = 3 * MINUTE_MS
+        self.assertEqual(self.standby.wake(now), PowerState.Waking)
+        self.assertIsNone(self.standby.wake(now + 10))
+        self.assertIsNone(self.standby.update(False, now + 299))
+        self.assertEqual(self.standby.update(False, now + 300), PowerState.Active)
+        # La finestra di inattività riparte dal risveglio
+        self.assertIsNone(self.standby.update(False, now + 300 + MINUTE_MS))
+
+    def test_stream_start_wakes(self):
+        """Una nuova sottoscrizione risveglia il sink anche senza comando"""
+        self.idle_until_standby()
+        self.assertEqual(self.standby.update(True, 3 * MINUTE_MS), PowerState.Waking)
+
+    def test_reporting_reduced(self):
+        """In standby annunci e resoconti si diradano"""
+        self.assertEqual(self.standby.report_interval_ms(1000), 1000)
+        self.idle_until_standby()
+        self.assertEqual(self.standby.report_interval_ms(1000), 30000)
+        self.assertEqual(self.standby.report_interval_ms(60000), 60000)
+
+    def test_disabled(self):
+        """Con lo standby disattivato il sink resta attivo"""
+        standby = StandbyController()
+        standby.update(False, 0)
+        self.assertIsNone(standby.update(False, 60 * MINUTE_MS))
+        self.assertEqual(power_state_to_string(standby.get_state()), "active")
+
+if __name__ == "__main__":
+    unittest.main()
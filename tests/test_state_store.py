@@ -0,0 +1,126 @@
+# Test unitari per l'archivio dello stato persistito
+# Verifica registro delle modifiche, snapshot atomici e ripristino dopo scritture interrotte o corrotte
+
+import os
+import shutil
+import sys
+import tempfile
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import SaberConfig, SaberProtocol, StateStore
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestStateStore(unittest.TestCase):
+    """Test per il ripristino dello stato"""
+
+    def setUp(self):
+        self.directory = tempfile.mkdtemp()
+        self.path = os.path.join(self.directory, "state")
+
+    def tearDown(self):
+        shutil.rmtree(self.directory)
+
+    def reopen(self, compact_after=4):
+        store = StateStore(self.path, compact_after)
+        return store, store.recover()
+
+    def fill(self):
+        store, report = self.reopen()
+        self.assertFalse(report.snapshot_loaded)
+        store.put("peer.sink-1", "sink 0a0b")
+        store.put("zone.sink-1", "giardino\tnord\n")
+        store.put("peer.sink-2", "sink")
+        store.erase("peer.sink-2")
+        store.put("zone.sink-2", "salotto")
+        return store
+
+    def test_reopen(self):
+        """Snapshot e registro riportano l'ultimo stato scritto, anche con tabulazioni e a capo"""
+        self.fill()
+        store, report = self.reopen()
+        self.assertTrue(report.snapshot_loaded)
+        self.assertEqual(report.replayed_records, 1)
+        self.assertEqual(report.discarded_bytes, 0)
+        self.assertEqual(store.entries(), {"peer.sink-1": "sink 0a0b", "zone.sink-1": "giardino\tnord\n",
+                                           "zone.sink-2": "salotto"})
+        self.assertIsNone(store.get("peer.sink-2"))
+
+    def test_torn_record(self):
+        """Un record troncato da uno spegnimento viene scartato e il registro resta utilizzabile"""
+        self.fill()
+        with open(self.path + ".wal", "a") as wal:
+            wal.write("6\tP\tzone.sink-3\tcuc")
+        store, report = self.reopen()
+        self.assertEqual(report.discarded_bytes, len("6\tP\tzone.sink-3\tcuc"))
+        self.assertIsNone(store.get("zone.sink-3"))
+        self.assertTrue(store.put("zone.sink-3", "cucina"))
+        store, report = self.reopen()
+        self.assertEqual(report.discarded_bytes, 0)
+        self.assertEqual(store.get("zone.sink-3"), "cucina")
+
+    def test_corrupted_record(self):
+        """Un record con checksum errato interrompe il ripristino"""
+        store = self.fill()
+        store.put("zone.sink-2", "cucina")
+        with open(self.path + ".wal") as wal:
+            content = wal.read()
+        with open(self.path + ".wal", "w") as wal:
+            wal.write(content.replace("cucina", "cucino"))
+        store, report = self.reopen()
+        self.assertGreater(report.discarded_bytes, 0)
+        self.assertEqual(store.get("zone.sink-2"), "salotto")
+
+    def test_corrupted_snapshot(self):
+        """Uno snapshot corrotto lascia il posto a quello precedente, ancora integro"""
+        store = self.fill()
+        for index in range(4):
+            store.put("peer.sink-%d" % (index + 3), "sink")
+        with open(self.path, "r+") as snapshot:
+            snapshot.seek(40)
+            snapshot.write("XX")
+        store, report = self.reopen()
+        self.assertTrue(report.snapshot_loaded)
+        self.assertTrue(report.from_backup)
+        self.assertEqual(store.get("zone.sink-1"), "giardino\tnord\n")
+        # Lo snapshot principale viene riscritto subito
+        store, report = self.reopen()
+        self.assertFalse(report.from_backup)
+
+    def test_interrupted_replacement(self):
+        """Senza snapshot principale, per una rinomina interrotta, vale quello precedente col registro"""
+        store = self.fill()
+        store.compact()
+        store.put("zone.sink-2", "cucina")
+        os.rename(self.path, self.path + ".bak")
+        store, report = self.reopen()
+        self.assertTrue(report.from_backup)
+        self.assertEqual(report.replayed_records, 1)
+        self.assertEqual(store.get("zone.sink-2"), "cucina")
+
+    def test_empty_key(self):
+        """Una chiave vuota viene rifiutata"""
+        store, _ = self.reopen()
+        with self.assertRaises(ValueError):
+            store.put("", "valore")
+
+class TestConfigFile(ConfigFileTestCase):
+    """Test per il file dello stato letto dalla configurazione"""
+
+    HEADER = '[node]\nid = "master-1"\nrole = "master"\n\n'
+
+    def test_state_file(self):
+        """state.file indica dove persistere nodi noti e zone"""
+        self.assertIsNone(SaberConfig.default_config().state_file)
+        self.assertEqual(self.load('[state]\nfile = "/var/lib/saber/state"\n').state_file, "/var/lib/saber/state")
+        self.assertIsNone(SaberProtocol(SaberConfig.default_config()).get_state_recovery())
+
+if __name__ == "__main__":
+    unittest.main()
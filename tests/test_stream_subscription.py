@@ -0,0 +1,87 @@
+# Test unitari per le sottoscrizioni agli stream della rete mesh
+# Verifica i pacchetti Subscribe/Unsubscribe e l'inoltro limitato ai sink sottoscritti
+
+import os
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import MeshNetwork, MeshPacket, MeshPacketType, Node, NodeRole
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestSubscriptionPackets(unittest.TestCase):
+    """Test per il formato dei pacchetti di sottoscrizione"""
+
+    def test_round_trip(self):
+        """Nodo e stream sopravvivono alla codifica"""
+        for create, packet_type in ((MeshPacket.create_subscribe, MeshPacketType.Subscribe),
+                                    (MeshPacket.create_unsubscribe, MeshPacketType.Unsubscribe)):
+            packet = create("sink-1", 3)
+            packet.set_header("sink-1", 1, 8)
+            decoded = MeshPacket.decode(packet.encode())
+            self.assertEqual(decoded.get_type(), packet_type)
+            self.assertEqual(decoded.get_subscription_data(), ("sink-1", 3))
+
+    def test_wrong_type(self):
+        """I dati di sottoscrizione esistono solo nei pacchetti Subscribe/Unsubscribe"""
+        with self.assertRaises(RuntimeError):
+            MeshPacket.create_ping("sink-1", 0).get_subscription_data()
+
+class TestSubscriptionForwarding(unittest.TestCase):
+    """Test per le sottoscrizioni ricevute dal collegamento"""
+
+    def setUp(self):
+        self.network = MeshNetwork(Node("master-1", NodeRole.Master))
+        for node_id in ("sink-1", "sink-2"):
+            self.network.register_node(node_id, NodeRole.Sink)
+            self.network.update_node_status(node_id, 50, 10)
+        self.sequence = 0
+        self.network.start()
+        self.addCleanup(self.network.stop)
+
+    def receive(self, create, node_id, stream_id, source=None):
+        self.sequence += 1
+        packet = create(node_id, stream_id)
+        packet.set_header(source or node_id, self.sequence, 8)
+        self.assertTrue(self.network.receive_from_link(packet.encode()))
+
+    def wait_for_subscribers(self, stream_id, expected):
+        deadline = time.monotonic() + 2.0
+        while self.network.get_subscribers(stream_id) != expected and time.monotonic() < deadline:
+            time.sleep(0.01)
+        self.assertEqual(self.network.get_subscribers(stream_id), expected)
+
+    def test_subscribe_and_unsubscribe(self):
+        """L'audio di uno stream raggiunge solo i sink che lo hanno richiesto"""
+        self.assertEqual(self.network.get_forward_targets(3), [])
+        self.receive(MeshPacket.create_subscribe, "sink-1", 3)
+        self.wait_for_subscribers(3, ["sink-1"])
+        self.assertEqual(self.network.get_forward_targets(3), ["sink-1"])
+
+        self.receive(MeshPacket.create_unsubscribe, "sink-1", 3)
+        self.wait_for_subscribers(3, [])
+        self.assertEqual(self.network.get_forward_targets(3), [])
+
+    def test_spoofed_subscription(self):
+        """Un nodo non può sottoscrivere né disiscrivere un altro nodo"""
+        self.receive(MeshPacket.create_subscribe, "sink-2", 3, source="sink-1")
+        self.receive(MeshPacket.create_subscribe, "sink-1", 4)
+        self.wait_for_subscribers(4, ["sink-1"])
+        self.assertEqual(self.network.get_subscribers(3), [])
+
+        self.receive(MeshPacket.create_subscribe, "sink-2", 4)
+        self.wait_for_subscribers(4, ["sink-1", "sink-2"])
+        self.receive(MeshPacket.create_unsubscribe, "sink-2", 4, source="sink-1")
+        self.receive(MeshPacket.create_subscribe, "sink-1", 5)
+        self.wait_for_subscribers(5, ["sink-1"])
+        self.assertEqual(self.network.get_subscribers(4), ["sink-1", "sink-2"])
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,79 @@
+# Test unitari per lo stato dei nodi attivi e per le annotazioni di tipo del modulo Python
+# Verifica NodeInfo su una rete simulata e che lo stub generato da src/tools/gen_stubs.py copra tutta l'API
+
+import ast
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src', 'tools'))
+
+try:
+    # Importo i moduli da testare
+    import saber_protocol
+    from saber_protocol import NodeRole, SaberConfig, SaberProtocol, SimNetwork
+    import gen_stubs
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestActiveNodeInfo(unittest.TestCase):
+    """Test per lo stato dei nodi attivi"""
+
+    def start(self, node_id, role, network):
+        config = SaberConfig.default_config()
+        config.node_id = node_id
+        config.role = role
+        protocol = SaberProtocol(config)
+        protocol.set_sim_network(network)
+        if not protocol.initialize():
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        self.addCleanup(protocol.shutdown)
+        return protocol
+
+    def test_uninitialized(self):
+        """Senza rete mesh non ci sono nodi attivi"""
+        config = SaberConfig.default_config()
+        config.node_id = "solo"
+        self.assertEqual(SaberProtocol(config).get_active_node_info(), [])
+
+    def test_registered_only(self):
+        """Un nodo registrato ma mai sentito non compare tra i nodi attivi"""
+        master = self.start("info-master", NodeRole.Master, SimNetwork(1))
+        self.assertTrue(master.register_node("info-sink", NodeRole.Sink))
+        self.assertEqual(master.get_active_node_info(), [])
+        self.assertEqual(master.get_active_nodes(), [])
+
+class TestStubs(unittest.TestCase):
+    """Test per lo stub generato dal modulo compilato"""
+
+    def setUp(self):
+        self.text = gen_stubs.generate(saber_protocol)
+        self.tree = ast.parse(self.text)
+
+    def test_covers_module(self):
+        """Ogni nome pubblico del modulo ha una dichiarazione nello stub"""
+        declared = set()
+        for node in self.tree.body:
+            if isinstance(node, (ast.ClassDef, ast.FunctionDef)):
+                declared.add(node.name)
+            elif isinstance(node, ast.AnnAssign):
+                declared.add(node.target.id)
+        public = {name for name in dir(saber_protocol) if not name.startswith('_')}
+        self.assertEqual(public - declared, set())
+
+    def test_node_info(self):
+        """NodeInfo ha proprietà tipate e get_active_node_info ne restituisce una lista"""
+        classes = {node.name: node for node in self.tree.body if isinstance(node, ast.ClassDef)}
+        members = {node.name for node in classes["NodeInfo"].body if isinstance(node, ast.FunctionDef)}
+        self.assertLessEqual({"node_id", "role", "latency_ms", "buffer_state", "last_seen_ms", "to_dict"}, members)
+        self.assertRegex(self.text, r"def get_active_node_info\(self\) -> [Ll]ist\[NodeInfo\]")
+
+    def test_cli(self):
+        """Lo strumento rifiuta una riga di comando incompleta"""
+        self.assertEqual(gen_stubs.main(["gen_stubs.py"]), 2)
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,69 @@
+# Test unitari per i parametri di sincronizzazione di SyncManager
+# Verifica soglia di jitter, limiti del buffer e timeout dei beacon configurabili
+
+import os
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import SyncManager, SpecParameters
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestSyncConfig(unittest.TestCase):
+    """Test per SyncManager creato con parametri espliciti"""
+    
+    def params(self, **values):
+        params = SpecParameters()
+        for name, value in values.items():
+            setattr(params, name, value)
+        return params
+    
+    def test_defaults_match_spec(self):
+        """Senza parametri il gestore usa i valori della specifica"""
+        params = SyncManager().get_parameters()
+        self.assertEqual(params.latency_budget_ms, 40)
+        self.assertEqual(params.min_buffer_ms, 0)
+        self.assertEqual(params.beacon_timeout_ms, 0)
+    
+    def test_buffer_limits(self):
+        """Il buffer calcolato resta tra il minimo e il budget di latenza"""
+        manager = SyncManager(self.params(latency_budget_ms=80, min_buffer_ms=25))
+        self.assertEqual(manager.calculate_buffer_adjustment(2), 25)
+        self.assertEqual(manager.calculate_buffer_adjustment(30), 40)
+        self.assertEqual(manager.calculate_buffer_adjustment(200), 80)
+    
+    def test_default_buffer(self):
+        """Senza latenze misurate si usa il buffer predefinito configurato"""
+        manager = SyncManager(self.params(default_buffer_ms=30))
+        self.assertEqual(manager.get_optimal_buffer_size(), 30)
+    
+    def test_jitter_threshold(self):
+        """La soglia di jitter decide quando un nodo è desincronizzato"""
+        manager = SyncManager(self.params(jitter_tolerance_ms=20))
+        self.assertFalse(manager.is_node_out_of_sync("sink-1", manager.now() - 10))
+        self.assertTrue(manager.is_node_out_of_sync("sink-1", manager.now() - 100))
+    
+    def test_beacon_timeout(self):
+        """Senza beacon oltre il timeout il nodo non è più sincronizzato"""
+        manager = SyncManager(self.params(beacon_timeout_ms=50))
+        manager.handle_time_beacon(int(time.time() * 1000))
+        self.assertTrue(manager.is_synchronized())
+        time.sleep(0.1)
+        self.assertFalse(manager.is_synchronized())
+    
+    def test_invalid_parameters(self):
+        """Parametri incoerenti vengono rifiutati alla creazione"""
+        with self.assertRaises(ValueError):
+            SyncManager(self.params(min_buffer_ms=30))
+        with self.assertRaises(ValueError):
+            SyncManager(self.params(beacon_timeout_ms=5))
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,81 @@
+# Test unitari per il rilevamento dei beacon mancanti del protocollo SABER
+# Verifica la notifica della perdita di sincronizzazione e la sospensione della riproduzione
+
+import os
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import AudioSync, SyncManager, SpecParameters
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def now_ms():
+    return int(time.time() * 1000)
+
+class TestSyncLoss(unittest.TestCase):
+    """Test per la perdita e il recupero della sincronizzazione"""
+
+    def setUp(self):
+        params = SpecParameters()
+        params.beacon_timeout_ms = 50
+        self.manager = SyncManager(params)
+        self.states = []
+        self.manager.set_sync_state_handler(self.states.append)
+
+    def test_loss_is_notified_once(self):
+        """La perdita viene notificata al primo controllo dopo il timeout"""
+        self.manager.handle_time_beacon(now_ms())
+        self.assertFalse(self.manager.check_beacon_timeout())
+        time.sleep(0.1)
+        self.assertTrue(self.manager.check_beacon_timeout())
+        self.assertFalse(self.manager.check_beacon_timeout())
+        self.assertEqual(self.states, [True, False])
+
+    def test_beacon_restores_sync(self):
+        """Il beacon successivo sincronizza di nuovo il nodo"""
+        self.manager.handle_time_beacon(now_ms())
+        time.sleep(0.1)
+        self.manager.check_beacon_timeout()
+        self.manager.handle_time_beacon(now_ms())
+        self.assertTrue(self.manager.is_synchronized())
+        self.assertEqual(self.states, [True, False, True])
+
+    def test_no_timeout_never_loses_sync(self):
+        """Senza timeout configurato il nodo resta sincronizzato"""
+        manager = SyncManager()
+        manager.handle_time_beacon(now_ms())
+        time.sleep(0.1)
+        self.assertFalse(manager.check_beacon_timeout())
+        self.assertTrue(manager.is_synchronized())
+
+    def test_playback_paused_and_resumed(self):
+        """La riproduzione sospesa per la perdita riprende al recupero"""
+        audio = AudioSync(self.manager, True, 2)
+        self.manager.handle_time_beacon(now_ms())
+        self.assertTrue(audio.start_playback())
+        audio.handle_sync_state(False)
+        self.assertTrue(audio.is_paused_for_sync())
+        self.assertFalse(audio.is_playback_synchronized())
+        audio.handle_sync_state(True)
+        self.assertFalse(audio.is_paused_for_sync())
+        self.assertTrue(audio.is_playback_synchronized())
+
+    def test_stopped_playback_stays_stopped(self):
+        """Una riproduzione fermata dall'utente non riparte al recupero"""
+        audio = AudioSync(self.manager, True, 2)
+        self.manager.handle_time_beacon(now_ms())
+        audio.start_playback()
+        audio.handle_sync_state(False)
+        audio.stop_playback()
+        audio.handle_sync_state(True)
+        self.assertFalse(audio.is_playback_synchronized())
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,144 @@
+# Test unitari per la misura di sincronizzazione tra sink del protocollo SABER
+# Verifica la stima dello sfasamento, del tempo di andata e ritorno e il confronto con la tolleranza
+
+import os
+import random
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (AsymmetryMode, SyncProbe, asymmetry_mode_from_string,
+                                asymmetry_mode_to_string)
+    from helpers import load_config
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+NOW_US = 1700000000 * 1000 * 1000
+
+def exchange(skew_us, transit_us, held_us, start_us=NOW_US):
+    """Istanti di uno scambio con un peer sfasato di skew_us e transito simmetrico"""
+    sent = start_us
+    peer_received = sent + transit_us + skew_us
+    peer_replied = peer_received + held_us
+    received = peer_replied - skew_us + transit_us
+    return sent, peer_received, peer_replied, received
+
+def ble_exchanges(seed, count, skew_us, transit_us=3000, interval_us=7500):
+    """Scambi su un collegamento BLE simulato: le ritrasmissioni allungano soprattutto l'andata"""
+    rng = random.Random(seed)
+    exchanges = []
+    extra_out = extra_in = 0
+    for i in range(count):
+        out_delay = transit_us + rng.randint(0, 200)
+        in_delay = transit_us + rng.randint(0, 200)
+        # Ogni ritrasmissione attende il prossimo intervallo di connessione
+        if rng.random() < 0.6:
+            out_delay += interval_us * rng.randint(1, 2)
+        if rng.random() < 0.1:
+            in_delay += interval_us
+        extra_out += out_delay - transit_us
+        extra_in += in_delay - transit_us
+        sent = NOW_US + i * 1000000
+        peer_received = sent + out_delay + skew_us
+        peer_replied = peer_received + 500
+        received = peer_replied - skew_us + in_delay
+        exchanges.append((sent, peer_received, peer_replied, received))
+    return exchanges, (extra_out - extra_in) // count
+
+class TestSyncProbe(unittest.TestCase):
+    """Test per la misura dello sfasamento tra due sink"""
+
+    def test_skew_with_symmetric_transit(self):
+        """Con transito simmetrico lo sfasamento è misurato esattamente"""
+        self.assertEqual(SyncProbe.skew_us(*exchange(3000, 4000, 100000)), 3000)
+        self.assertEqual(SyncProbe.skew_us(*exchange(-1500, 2000, 0)), -1500)
+
+    def test_round_trip_excludes_reply_time(self):
+        """Il tempo di risposta del peer non entra nel tempo di andata e ritorno"""
+        self.assertEqual(SyncProbe.round_trip_us(*exchange(3000, 4000, 100000)), 8000)
+
+    def test_result_within_tolerance(self):
+        """Sfasamenti entro ±5 ms rispettano la tolleranza della specifica"""
+        probe = SyncProbe("sink-2", 3, 5)
+        for skew in [1000, 2000, 3000]:
+            probe.add_sample(*exchange(skew, 2000, 500))
+        self.assertTrue(probe.is_complete())
+        result = probe.result(1234)
+        self.assertEqual(result.peer, "sink-2")
+        self.assertEqual(result.samples, 3)
+        self.assertEqual(result.mean_skew_us, 2000)
+        self.assertEqual(result.min_skew_us, 1000)
+        self.assertEqual(result.max_skew_us, 3000)
+        self.assertEqual(result.mean_round_trip_us, 4000)
+        self.assertTrue(result.within_tolerance)
+        self.assertIsNone(result.acoustic_skew_us)
+
+    def test_result_beyond_tolerance(self):
+        """Un solo scambio oltre la tolleranza basta a segnalarlo"""
+        probe = SyncProbe("sink-2", 2, 5)
+        probe.add_sample(*exchange(1000, 2000, 0))
+        self.assertFalse(probe.is_complete())
+        probe.add_sample(*exchange(-7000, 2000, 0))
+        self.assertFalse(probe.result(0).within_tolerance)
+
+    def test_probe_ids(self):
+        """Gli inneschi sono numerati in ordine di invio"""
+        probe = SyncProbe("sink-2", 2, 5)
+        self.assertEqual(probe.next_probe_id(), 0)
+        self.assertEqual(probe.next_probe_id(), 1)
+        self.assertEqual(probe.get_probes_sent(), 2)
+
+class TestAsymmetry(unittest.TestCase):
+    """Test per la compensazione dell'asimmetria dei collegamenti"""
+
+    def measure(self, mode, exchanges):
+        probe = SyncProbe("sink-2", len(exchanges), 5, mode)
+        for sample in exchanges:
+            probe.add_sample(*sample)
+        return probe.result(0)
+
+    def test_minimum_delay_improves_estimate(self):
+        """Con ritrasmissioni su un solo verso i ritardi minimi riducono l'errore ad una frazione"""
+        for seed in range(5):
+            exchanges, asymmetry = ble_exchanges(seed, 32, 2000)
+            symmetric = self.measure(AsymmetryMode.Symmetric, exchanges)
+            corrected = self.measure(AsymmetryMode.MinimumDelay, exchanges)
+            symmetric_error = abs(symmetric.mean_skew_us - 2000)
+            corrected_error = abs(corrected.mean_skew_us - 2000)
+            self.assertGreater(symmetric_error, 2000)
+            self.assertLess(corrected_error, 200)
+            self.assertLess(corrected_error * 10, symmetric_error)
+            self.assertEqual(corrected.mode, AsymmetryMode.MinimumDelay)
+            # L'asimmetria stimata è quella introdotta, a meno del jitter
+            self.assertLess(abs(corrected.asymmetry_us - asymmetry), 300)
+            self.assertEqual(symmetric.asymmetry_us, corrected.asymmetry_us)
+
+    def test_symmetric_link_unchanged(self):
+        """Su un collegamento simmetrico i due modi coincidono"""
+        exchanges = [exchange(1500, 3000, 500, NOW_US + i * 1000) for i in range(4)]
+        symmetric = self.measure(AsymmetryMode.Symmetric, exchanges)
+        corrected = self.measure(AsymmetryMode.MinimumDelay, exchanges)
+        self.assertEqual(corrected.asymmetry_us, 0)
+        self.assertEqual(symmetric.mean_skew_us, corrected.mean_skew_us)
+        self.assertEqual(corrected.mean_skew_us, 1500)
+
+    def test_names(self):
+        """I modi di stima hanno nomi stabili"""
+        self.assertEqual(asymmetry_mode_to_string(AsymmetryMode.MinimumDelay), "min_delay")
+        self.assertEqual(asymmetry_mode_from_string("symmetric"), AsymmetryMode.Symmetric)
+        self.assertIsNone(asymmetry_mode_from_string("ntp"))
+
+    def test_config_file(self):
+        """Il modo di stima si sceglie con sync.asymmetry_mode"""
+        config = load_config('[node]\nid = "sink-1"\n\n[sync]\nasymmetry_mode = "min_delay"\n')
+        self.assertEqual(config.sync_probe_asymmetry, AsymmetryMode.MinimumDelay)
+        with self.assertRaises(RuntimeError):
+            load_config('[node]\nid = "sink-1"\n\n[sync]\nasymmetry_mode = "ntp"\n')
+
+if __name__ == '__main__':
+    unittest.main()
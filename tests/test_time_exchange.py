@@ -0,0 +1,80 @@
+# Test unitari per lo scambio temporale richiesta/risposta con il Master
+# Verifica il calcolo di offset e tempo di andata e ritorno come in NTP e la compensazione dei beacon
+
+import os
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import SaberConfig, SyncManager
+    from helpers import load_config
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def exchange(manager, offset_us, outbound_us, inbound_us, held_us=500):
+    """Istanti t1..t4 di uno scambio appena concluso con un Master avanti di offset_us"""
+    received = manager.local_us()
+    replied = received + offset_us - inbound_us
+    master_received = replied - held_us
+    sent = master_received - offset_us - outbound_us
+    return sent, master_received, replied, received
+
+class TestTimeExchange(unittest.TestCase):
+    """Test per lo scambio temporale di SyncManager"""
+
+    def test_offset_and_round_trip(self):
+        """Con transito simmetrico l'offset è esatto e il tempo di risposta è escluso"""
+        manager = SyncManager()
+        self.assertIsNone(manager.get_last_exchange())
+        self.assertTrue(manager.handle_time_exchange("master-1", *exchange(manager, 250_000, 3_000, 3_000)))
+        result = manager.get_last_exchange()
+        self.assertEqual(result.offset_us, 250_000)
+        self.assertEqual(result.round_trip_us, 6_000)
+        self.assertTrue(manager.is_synchronized())
+        # Il primo scambio allinea l'orologio di colpo
+        self.assertAlmostEqual(manager.now_us() - manager.local_us(), 250_000, delta=2_000)
+
+    def test_latency_of_master(self):
+        """Metà del tempo di andata e ritorno diventa la latenza del Master"""
+        manager = SyncManager()
+        manager.handle_time_exchange("master-1", *exchange(manager, 0, 8_000, 4_000))
+        self.assertEqual(manager.get_last_exchange().round_trip_us, 12_000)
+        self.assertEqual(manager.get_average_latency(), 6.0)
+
+    def test_inconsistent_exchange(self):
+        """Uno scambio con tempo di andata e ritorno negativo viene scartato"""
+        manager = SyncManager()
+        sent, master_received, replied, received = exchange(manager, 0, 1_000, 1_000, held_us=500)
+        self.assertFalse(manager.handle_time_exchange("master-1", sent, master_received, replied + 10_000, received))
+        self.assertFalse(manager.handle_time_exchange("master-1", received, master_received, replied, sent))
+        self.assertIsNone(manager.get_last_exchange())
+        self.assertFalse(manager.is_synchronized())
+
+    def test_beacon_compensates_transit(self):
+        """Dopo uno scambio il beacon è corretto del transito misurato"""
+        manager = SyncManager()
+        manager.handle_time_exchange("master-1", *exchange(manager, 0, 50_000, 50_000))
+        # Un beacon che ha impiegato 50ms non deve spostare l'orologio indietro di 50ms
+        master = int(time.time() * 1000) - 50
+        manager.handle_time_beacon(master)
+        self.assertLess(abs(manager.get_clock_stats().estimated_offset_us), 20_000)
+
+class TestConfigFile(unittest.TestCase):
+    """Test per l'intervallo degli scambi letto dal file di configurazione"""
+
+    def test_interval(self):
+        """sync.exchange_interval_ms imposta l'intervallo, 0 lascia solo i beacon"""
+        self.assertEqual(SaberConfig.default_config().time_exchange_interval_ms, 1000)
+        config = load_config('[node]\nid = "sink-1"\n\n[sync]\nexchange_interval_ms = 0\n')
+        self.assertEqual(config.time_exchange_interval_ms, 0)
+        with self.assertRaises(RuntimeError):
+            load_config('[node]\nid = "sink-1"\n\n[sync]\nexchange_interval_ms = -5\n')
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,127 @@
+# Test unitari per la compressione dei timestamp nei pacchetti Audio
+# Verifica la ricostruzione attorno al riavvolgimento del contatore
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (MeshPacket, TimestampWidth, TimestampEncoder, TimestampDecoder,
+                                compress_timestamp, expand_timestamp)
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+PERIOD_24 = 1 << 24
+PERIOD_16 = 1 << 16
+
+class TestTimestampCompression(unittest.TestCase):
+    """Test per la ricostruzione dei timestamp compressi"""
+    
+    def roundtrip(self, value, reference, width=TimestampWidth.Bits24):
+        return expand_timestamp(compress_timestamp(value, width), width, reference)
+    
+    def test_forward_across_wrap(self):
+        """Un frame successivo al riavvolgimento dei 24 bit viene ricostruito in avanti"""
+        reference = 5 * PERIOD_24 + PERIOD_24 - 16
+        self.assertEqual(self.roundtrip(reference + 32, reference), reference + 32)
+    
+    def test_backward_across_wrap(self):
+        """Un frame in ritardo, precedente al riavvolgimento, viene ricostruito all'indietro"""
+        reference = 5 * PERIOD_24 + 8
+        self.assertEqual(self.roundtrip(reference - 20, reference), reference - 20)
+    
+    def test_exact_wrap_boundary(self):
+        """Il valore con i bit bassi a zero cade esattamente sul nuovo periodo"""
+        reference = 3 * PERIOD_24 - 1
+        self.assertEqual(self.roundtrip(3 * PERIOD_24, reference), 3 * PERIOD_24)
+    
+    def test_sixteen_bit_wrap(self):
+        """Con 16 bit il riavvolgimento avviene ogni 65 ms"""
+        reference = 7 * PERIOD_16 + PERIOD_16 - 100
+        value = reference + 10000
+        self.assertEqual(self.roundtrip(value, reference, TimestampWidth.Bits16), value)
+    
+    def test_reference_near_zero(self):
+        """All'inizio dell'orologio un ritardatario non produce valori negativi"""
+        self.assertEqual(self.roundtrip(3, 10, TimestampWidth.Bits16), 3)
+    
+    def test_full_width(self):
+        """Con l'ampiezza piena il timestamp non viene mascherato"""
+        self.assertEqual(compress_timestamp(5 * PERIOD_24 + 123, TimestampWidth.Full), 5 * PERIOD_24 + 123)
+        self.assertEqual(compress_timestamp((1 << 32) + 7, TimestampWidth.Full), 7)
+        packet = MeshPacket.create_audio(1, 2, 1700000000123456, [1, 2, 3])
+        self.assertEqual(MeshPacket.decode(packet.encode()).get_audio_data().playout_time_us, 1700000000123456)
+    
+    def test_decoder_requires_anchor(self):
+        """I frame compressi non sono decodificabili prima della prima ancora"""
+        decoder = TimestampDecoder()
+        self.assertIsNone(decoder.decode(TimestampWidth.Bits24, 1234))
+        self.assertFalse(decoder.is_anchored())
+        self.assertEqual(decoder.decode(TimestampWidth.Full, 2 * PERIOD_24), 2 * PERIOD_24)
+        self.assertTrue(decoder.is_anchored())
+    
+    def test_stream_across_several_wraps(self):
+        """Una sequenza di frame da 10 ms attraversa più riavvolgimenti dei 16 bit"""
+        encoder = TimestampEncoder(TimestampWidth.Bits16, 8)
+        decoder = TimestampDecoder()
+        timestamp = PERIOD_16 - 5000
+        anchors = 0
+        for _ in range(40):
+            width = encoder.next(timestamp)
+            if width == TimestampWidth.Full:
+                anchors += 1
+                value = timestamp
+            else:
+                value = compress_timestamp(timestamp, width)
+            self.assertEqual(decoder.decode(width, value), timestamp)
+            timestamp += 10000
+        self.assertEqual(anchors, 5)
+    
+    def test_packet_loss_gap(self):
+        """Una raffica di frame persi più lunga di mezzo periodo a 16 bit non sposta la ricostruzione"""
+        decoder = TimestampDecoder()
+        start = 9 * PERIOD_16 + 1000
+        arrival = 5000000
+        self.assertEqual(decoder.decode(TimestampWidth.Full, start, arrival), start)
+        timestamp = start + 10000
+        self.assertEqual(decoder.decode(TimestampWidth.Bits16, compress_timestamp(timestamp, TimestampWidth.Bits16),
+                                        arrival + 10000), timestamp)
+
+        # Persi i frame dei successivi 50 ms: il prossimo arriva dopo l'intervallo con un po' di jitter
+        timestamp += 50000
+        value = compress_timestamp(timestamp, TimestampWidth.Bits16)
+        self.assertEqual(decoder.decode(TimestampWidth.Bits16, value, arrival + 60000 + 3000), timestamp)
+        timestamp += 10000
+        value = compress_timestamp(timestamp, TimestampWidth.Bits16)
+        self.assertEqual(decoder.decode(TimestampWidth.Bits16, value, arrival + 70000 - 2000), timestamp)
+
+    def test_packet_loss_gap_without_arrival(self):
+        """Senza l'istante di arrivo lo stesso salto cadrebbe sul periodo sbagliato"""
+        decoder = TimestampDecoder()
+        start = 9 * PERIOD_16 + 1000
+        decoder.decode(TimestampWidth.Full, start)
+        timestamp = start + 50000
+        self.assertNotEqual(decoder.decode(TimestampWidth.Bits16, compress_timestamp(timestamp, TimestampWidth.Bits16)),
+                            timestamp)
+
+    def test_gap_forces_anchor(self):
+        """Un salto di mezzo periodo o più viene inviato come ancora"""
+        encoder = TimestampEncoder(TimestampWidth.Bits16, 100)
+        self.assertEqual(encoder.next(1000), TimestampWidth.Full)
+        self.assertEqual(encoder.next(11000), TimestampWidth.Bits16)
+        self.assertEqual(encoder.next(11000 + PERIOD_16 // 2), TimestampWidth.Full)
+    
+    def test_compact_packet_is_smaller(self):
+        """Un frame con timestamp a 24 bit occupa cinque byte in meno di un'ancora"""
+        full = MeshPacket.create_audio(1, 10, 3 * PERIOD_24 + 42, [0] * 40)
+        compact = MeshPacket.create_audio(1, 10, 3 * PERIOD_24 + 42, [0] * 40, TimestampWidth.Bits24)
+        self.assertEqual(full.encoded_size() - compact.encoded_size(), 5)
+        self.assertEqual(compact.get_audio_data().compact_timestamp, 42)
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,106 @@
+# Test unitari per il grafo della topologia
+# Verifica l'esportazione DOT e JSON e i resoconti dei vicini scambiati tra i nodi
+
+import json
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (NodeRole, SaberConfig, SaberProtocol, TopologyGraph, TopologyLink, TopologyNode,
+                                encode_neighbor_report, parse_neighbor_report, topology_to_dot, topology_to_json)
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+def make_node(node_id, role, local=False, hops=None):
+    node = TopologyNode()
+    node.id = node_id
+    node.role = role
+    node.active = True
+    node.local = local
+    node.hops = hops
+    return node
+
+def make_link(from_node, to_node, rssi_dbm=None, age_ms=0):
+    link = TopologyLink()
+    link.from_node = from_node
+    link.to_node = to_node
+    link.rssi_dbm = rssi_dbm
+    link.age_ms = age_ms
+    return link
+
+def make_graph():
+    graph = TopologyGraph()
+    graph.local_node = "sink-1"
+    graph.nodes = [make_node("master-1", NodeRole.Master, hops=1), make_node("repeater-1", NodeRole.Repeater, hops=0),
+                   make_node("sink-1", NodeRole.Sink, local=True)]
+    graph.links = [make_link("master-1", "repeater-1", -55, 300), make_link("repeater-1", "sink-1", -70, 20)]
+    return graph
+
+class TestTopologyExport(unittest.TestCase):
+    """Test per l'esportazione del grafo"""
+
+    def test_json(self):
+        """Il JSON riporta nodi, hop e collegamenti orientati con RSSI ed età"""
+        document = json.loads(topology_to_json(make_graph()))
+        self.assertEqual(document["local"], "sink-1")
+        self.assertEqual([node["id"] for node in document["nodes"]], ["master-1", "repeater-1", "sink-1"])
+        self.assertEqual(document["nodes"][0]["role"], "master")
+        self.assertEqual(document["nodes"][0]["hops"], 1)
+        self.assertIsNone(document["nodes"][2]["hops"])
+        self.assertEqual(document["links"][1], {"from": "repeater-1", "to": "sink-1", "rssi_dbm": -70, "age_ms": 20})
+
+    def test_dot(self):
+        """Il DOT è un grafo orientato con un arco per collegamento"""
+        dot = topology_to_dot(make_graph())
+        self.assertTrue(dot.startswith("digraph saber {"))
+        self.assertIn('"master-1" -> "repeater-1" [label="-55 dBm"];', dot)
+        self.assertIn('"repeater-1" -> "sink-1" [label="-70 dBm"];', dot)
+        self.assertIn('"master-1" [label="master-1\\nmaster" shape=doublecircle];', dot)
+
+    def test_escaping(self):
+        """Gli ID con virgolette restano validi in entrambi i formati"""
+        graph = TopologyGraph()
+        graph.nodes = [make_node('sink "a"', NodeRole.Sink)]
+        self.assertIn('"sink \\"a\\""', topology_to_dot(graph))
+        self.assertEqual(json.loads(topology_to_json(graph))["nodes"][0]["id"], 'sink "a"')
+
+class TestNeighborReport(unittest.TestCase):
+    """Test per i resoconti dei vicini inviati dai nodi"""
+
+    def test_round_trip(self):
+        """I collegamenti codificati si rileggono uguali, con il nodo che li riporta come destinazione"""
+        links = [make_link("master-1", "sink-1", -60, 100), make_link("repeater:2", "sink-1", None, 5)]
+        text = encode_neighbor_report(links)
+        self.assertEqual(text, "master-1:-60:100,repeater:2::5")
+        parsed = parse_neighbor_report("sink-1", text)
+        self.assertEqual([(link.from_node, link.to_node, link.rssi_dbm, link.age_ms) for link in parsed],
+                         [("master-1", "sink-1", -60, 100), ("repeater:2", "sink-1", None, 5)])
+        self.assertEqual(parse_neighbor_report("sink-1", ""), [])
+
+    def test_invalid(self):
+        """Un resoconto malformato viene rifiutato per intero"""
+        for invalid in ("master-1", "master-1:-60", ":-60:100", "master-1:x:100", "master-1:-60:-1",
+                        "master-1:-60:100,,sink-2::5"):
+            self.assertIsNone(parse_neighbor_report("sink-1", invalid), invalid)
+
+class TestProtocol(unittest.TestCase):
+    """Test per il grafo esposto dal protocollo"""
+
+    def test_without_network(self):
+        """Senza rete il grafo è vuoto; i formati non supportati vengono rifiutati"""
+        protocol = SaberProtocol(SaberConfig.default_config())
+        graph = protocol.get_topology()
+        self.assertEqual((graph.nodes, graph.links), ([], []))
+        self.assertTrue(protocol.export_topology_graph("dot").startswith("digraph"))
+        self.assertEqual(json.loads(protocol.export_topology_graph("json"))["links"], [])
+        with self.assertRaises(ValueError):
+            protocol.export_topology_graph("svg")
+
+if __name__ == "__main__":
+    unittest.main()
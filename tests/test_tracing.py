@@ -0,0 +1,114 @@
+# Test unitari per il tracciamento distribuito del protocollo SABER
+# Verifica la propagazione del contesto tra gli span, i pacchetti e l'esportazione OTLP
+
+import json
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import MeshPacket, OtlpExporter, SpanKind, Tracer, encode_otlp_json
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestTracer(unittest.TestCase):
+    """Test per la creazione degli span"""
+
+    def setUp(self):
+        self.now = [1000]
+        self.tracer = Tracer(clock=lambda: self.now[0])
+        self.tracer.set_enabled(True)
+
+    def test_child_span(self):
+        """Uno span figlio eredita la traccia e punta al padre"""
+        parent = self.tracer.start_span("group.start", SpanKind.Producer)
+        child = self.tracer.start_span("command playback.start", SpanKind.Consumer, parent.context)
+        self.assertEqual(child.context.trace_id, parent.context.trace_id)
+        self.assertNotEqual(child.context.span_id, parent.context.span_id)
+        self.assertEqual(child.parent_span_id, parent.context.span_id)
+        self.assertIsNone(parent.parent_span_id)
+
+    def test_end_span(self):
+        """Gli span chiusi vengono accodati una sola volta con la durata"""
+        span = self.tracer.start_span("join.request")
+        self.now[0] = 4000
+        self.tracer.end_span(span)
+        self.tracer.end_span(span)
+        finished = self.tracer.take_finished()
+        self.assertEqual(len(finished), 1)
+        self.assertEqual(finished[0].start_us, 1000)
+        self.assertEqual(finished[0].end_us, 4000)
+        self.assertEqual(self.tracer.take_finished(), [])
+
+    def test_disabled(self):
+        """Con il tracciamento disattivato gli span non vengono registrati"""
+        self.tracer.set_enabled(False)
+        span = self.tracer.start_span("join.request")
+        self.assertFalse(span.recording)
+        self.assertFalse(span.context.is_valid())
+        self.tracer.end_span(span)
+        self.assertEqual(self.tracer.take_finished(), [])
+
+class TestPacketContext(unittest.TestCase):
+    """Test per la propagazione del contesto nei pacchetti"""
+
+    def test_roundtrip(self):
+        """Il contesto sopravvive alla codifica senza toccare la firma"""
+        tracer = Tracer()
+        tracer.set_enabled(True)
+        span = tracer.start_span("group.start", SpanKind.Producer)
+        packet = MeshPacket.create_command("playback.start", {"zone": "sala"})
+        packet.set_header("master-1", 1, 8)
+        signing = packet.signing_bytes()
+        packet.set_trace_context(span.context)
+        self.assertEqual(packet.signing_bytes(), signing)
+        decoded = MeshPacket.decode(packet.encode())
+        self.assertEqual(decoded.get_trace_context(), span.context)
+
+    def test_no_context(self):
+        """Un pacchetto senza contesto non lo inventa"""
+        packet = MeshPacket.create_command("log.set", {"filter": "info"})
+        packet.set_header("master-1", 1, 8)
+        self.assertIsNone(MeshPacket.decode(packet.encode()).get_trace_context())
+
+class TestOtlpExport(unittest.TestCase):
+    """Test per il formato di esportazione OTLP"""
+
+    def test_json(self):
+        """Gli span diventano un ExportTraceServiceRequest JSON"""
+        tracer = Tracer(clock=lambda: 2000)
+        tracer.set_enabled(True)
+        parent = tracer.start_span("group.start", SpanKind.Producer)
+        child = tracer.start_span("playback.barrier", SpanKind.Internal, parent.context)
+        child.attributes = {"saber.zone": "sala"}
+        child.error = "barriera mancata"
+        tracer.end_span(child)
+        tracer.end_span(parent)
+        body = json.loads(encode_otlp_json(tracer.take_finished(), "sink-1"))
+
+        resource = body["resourceSpans"][0]
+        self.assertIn({"key": "service.instance.id", "value": {"stringValue": "sink-1"}},
+                      resource["resource"]["attributes"])
+        spans = resource["scopeSpans"][0]["spans"]
+        self.assertEqual(spans[0]["name"], "playback.barrier")
+        self.assertEqual(spans[0]["parentSpanId"], parent.context.span_id_hex())
+        self.assertEqual(spans[0]["traceId"], parent.context.trace_id_hex())
+        self.assertEqual(spans[0]["startTimeUnixNano"], "2000000")
+        self.assertEqual(spans[0]["status"]["code"], 2)
+        self.assertNotIn("parentSpanId", spans[1])
+        self.assertEqual(spans[1]["kind"], 4)
+
+    def test_invalid_endpoint(self):
+        """Solo gli endpoint http con una porta valida sono accettati"""
+        with self.assertRaises(ValueError):
+            OtlpExporter("https://collector:4318/v1/traces", "sink-1", 1000)
+        with self.assertRaises(ValueError):
+            OtlpExporter("http://collector:99999", "sink-1", 1000)
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,100 @@
+# Test unitari per la scelta del collegamento BLE o UDP verso ogni nodo
+# Verifica la scelta iniziale, l'isteresi e il cambio di percorso per caduta, degrado o misure scadute
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (SaberConfig, SaberProtocol, TransportKind, TransportSelector,
+                                transport_kind_from_string, transport_kind_to_string)
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestTransportSelector(unittest.TestCase):
+    """Test per il percorso attivo verso un nodo"""
+
+    def setUp(self):
+        self.selector = TransportSelector(0.5, 0.15, 2000)
+        self.events = []
+        self.selector.set_failover_handler(self.events.append)
+        # Punteggio: qualità meno latenza/100 ms, quindi UDP (0,9) è preferito a BLE (0,7)
+        self.selector.report_path("sink-1", TransportKind.Ble, 0.9, 20, 0)
+        self.selector.report_path("sink-1", TransportKind.Udp, 0.95, 5, 0)
+
+    def test_initial_choice(self):
+        """Il primo pacchetto usa il percorso con il punteggio migliore, senza eventi"""
+        self.assertIsNone(self.selector.route("sink-2", 0))
+        self.assertEqual(self.selector.route("sink-1", 0), TransportKind.Udp)
+        self.assertEqual(self.selector.active_transport("sink-1"), TransportKind.Udp)
+        self.assertEqual(self.events, [])
+
+    def test_hysteresis(self):
+        """Un percorso di poco migliore non provoca un cambio a metà stream"""
+        self.selector.route("sink-1", 0)
+        self.selector.report_path("sink-1", TransportKind.Ble, 1.0, 0, 100)
+        self.assertEqual(self.selector.route("sink-1", 100), TransportKind.Udp)
+
+        # Con un vantaggio oltre il margine il cambio avviene
+        self.selector.report_path("sink-1", TransportKind.Udp, 0.8, 10, 200)
+        self.assertEqual(self.selector.route("sink-1", 200), TransportKind.Ble)
+        self.assertEqual(self.events[-1].reason, "better")
+
+    def test_degraded_and_down(self):
+        """Un percorso degradato o caduto viene abbandonato subito"""
+        self.selector.route("sink-1", 0)
+        self.selector.report_path("sink-1", TransportKind.Udp, 0.4, 5, 100)
+        self.assertEqual(self.selector.route("sink-1", 100), TransportKind.Ble)
+        event = self.events[-1]
+        self.assertEqual((event.peer, event.from_transport, event.to_transport, event.reason),
+                         ("sink-1", TransportKind.Udp, TransportKind.Ble, "degraded"))
+
+        self.selector.report_path_down("sink-1", TransportKind.Ble)
+        self.assertEqual(self.selector.route("sink-1", 200), TransportKind.Udp)
+        self.assertEqual(self.events[-1].reason, "down")
+
+        stats = self.selector.get_stats()
+        self.assertEqual(stats.failovers["sink-1"], 2)
+        self.assertEqual(stats.active["sink-1"], TransportKind.Udp)
+        self.assertEqual(stats.packets_routed[TransportKind.Udp], 2)
+        self.assertEqual(stats.packets_routed[TransportKind.Ble], 1)
+
+    def test_stale(self):
+        """Un percorso senza rapporti recenti lascia il posto all'altro"""
+        self.selector.route("sink-1", 0)
+        self.selector.report_path("sink-1", TransportKind.Ble, 0.9, 20, 2500)
+        self.assertEqual(self.selector.route("sink-1", 3000), TransportKind.Ble)
+        self.assertEqual(self.events[-1].reason, "stale")
+
+    def test_no_path(self):
+        """Senza percorsi utilizzabili si resta sull'ultimo finché è attivo, poi non si instrada"""
+        self.selector.route("sink-1", 0)
+        self.assertEqual(self.selector.route("sink-1", 10000), TransportKind.Udp)
+        self.selector.report_path_down("sink-1", TransportKind.Udp)
+        self.selector.report_path_down("sink-1", TransportKind.Ble)
+        self.assertIsNone(self.selector.route("sink-1", 10000))
+        self.assertEqual(self.events, [])
+
+class TestTransportNames(unittest.TestCase):
+    """Test per i nomi dei collegamenti"""
+
+    def test_names(self):
+        """I nomi ble e udp sono convertibili in entrambe le direzioni"""
+        for kind in (TransportKind.Ble, TransportKind.Udp):
+            self.assertEqual(transport_kind_from_string(transport_kind_to_string(kind)), kind)
+        self.assertIsNone(transport_kind_from_string("wifi"))
+
+    def test_protocol_without_network(self):
+        """Senza rete mesh le misure non vengono registrate"""
+        protocol = SaberProtocol(SaberConfig.default_config())
+        self.assertFalse(protocol.report_link_quality("sink-1", TransportKind.Udp, 0.9, 5))
+        self.assertFalse(protocol.report_link_down("sink-1", TransportKind.Udp))
+        self.assertEqual(protocol.get_failover_events(), [])
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,111 @@
+# Test unitari per il trasporto UDP multicast della rete mesh
+# Verifica lo scambio di datagrammi nel gruppo, la validazione degli indirizzi e la configurazione
+
+import os
+import random
+import sys
+import threading
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import (MAX_UDP_DATAGRAM_BYTES, SaberConfig, TransportKind, UdpTransport,
+                                UdpTransportConfig, transport_kind_from_string, transport_kind_to_string)
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class Receiver:
+    """Raccoglie i datagrammi ricevuti dal thread del trasporto"""
+
+    def __init__(self):
+        self.datagrams = []
+        self.event = threading.Event()
+
+    def __call__(self, datagram):
+        self.datagrams.append(bytes(datagram))
+        self.event.set()
+
+class TestUdpTransport(unittest.TestCase):
+    """Test per lo scambio di datagrammi nel gruppo multicast"""
+
+    def setUp(self):
+        self.config = UdpTransportConfig()
+        self.config.port = random.randint(20000, 40000)
+
+    def test_exchange(self):
+        """Un datagramma inviato al gruppo arriva a tutti i membri, mittente compreso"""
+        first, second = UdpTransport(self.config), UdpTransport(self.config)
+        first_received, second_received = Receiver(), Receiver()
+        if not first.start(first_received) or not second.start(second_received):
+            self.skipTest("multicast non disponibile")
+        try:
+            self.assertTrue(first.send(b"\x01\x02\x03"))
+            self.assertTrue(second_received.event.wait(2.0))
+            self.assertTrue(first_received.event.wait(2.0))
+            self.assertEqual(second_received.datagrams, [b"\x01\x02\x03"])
+            self.assertEqual(first.get_stats().datagrams_sent, 1)
+            self.assertEqual(first.get_stats().bytes_sent, 3)
+            self.assertEqual(second.get_stats().datagrams_received, 1)
+        finally:
+            first.stop()
+            second.stop()
+        self.assertFalse(first.is_running())
+        self.assertFalse(first.send(b"\x01"))
+
+    def test_oversized(self):
+        """Un pacchetto oltre la dimensione massima di un datagramma non viene inviato"""
+        transport = UdpTransport(self.config)
+        if not transport.start(Receiver()):
+            self.skipTest("multicast non disponibile")
+        try:
+            self.assertFalse(transport.send(bytes(MAX_UDP_DATAGRAM_BYTES + 1)))
+            self.assertEqual(transport.get_stats().send_errors, 1)
+        finally:
+            transport.stop()
+
+    def test_invalid_addresses(self):
+        """Un indirizzo non multicast o un'interfaccia non valida vengono rifiutati"""
+        self.config.multicast_address = "192.168.1.10"
+        with self.assertRaises(ValueError):
+            UdpTransport(self.config)
+        self.config.multicast_address = "239.69.83.1"
+        self.config.interface_address = "eth0"
+        with self.assertRaises(ValueError):
+            UdpTransport(self.config)
+
+    def test_names(self):
+        """I nomi dei collegamenti sono reversibili"""
+        for kind in (TransportKind.Ble, TransportKind.Udp):
+            self.assertEqual(transport_kind_from_string(transport_kind_to_string(kind)), kind)
+        self.assertIsNone(transport_kind_from_string("wifi"))
+
+class TestConfigFile(ConfigFileTestCase):
+    """Test per il collegamento letto dal file di configurazione"""
+
+    HEADER = '[node]\nid = "sink-1"\n\n[transport]\n'
+
+    def test_udp(self):
+        """La sezione [transport] sceglie UDP multicast con gruppo e porta"""
+        self.assertEqual(SaberConfig.default_config().transport, TransportKind.Ble)
+        config = self.load('kind = "udp"\nmulticast_address = "239.1.2.3"\nport = 6000\nmulticast_ttl = 4\n')
+        self.assertEqual(config.transport, TransportKind.Udp)
+        self.assertEqual(config.udp.multicast_address, "239.1.2.3")
+        self.assertEqual(config.udp.port, 6000)
+        self.assertEqual(config.udp.multicast_ttl, 4)
+
+    def test_invalid(self):
+        """Collegamenti sconosciuti, gruppi non multicast e porte fuori intervallo vengono rifiutati"""
+        with self.assertRaises(RuntimeError):
+            self.load('kind = "wifi"\n')
+        with self.assertRaises(RuntimeError):
+            self.load('kind = "udp"\nmulticast_address = "10.0.0.1"\n')
+        with self.assertRaises(RuntimeError):
+            self.load('port = 70000\n')
+
+if __name__ == "__main__":
+    unittest.main()
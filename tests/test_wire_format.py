@@ -0,0 +1,114 @@
+# Test unitari per il formato di trasmissione dei pacchetti mesh
+# Verifica l'andata e ritorno della codifica e il rifiuto dei dati malformati
+
+import os
+import random
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import MeshPacket, MeshPacketType, TimestampWidth
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestWireFormat(unittest.TestCase):
+    """Test per la codifica binaria di MeshPacket"""
+
+    def command(self):
+        packet = MeshPacket.create_command("volume", {"level": "40", "zone": "cucina"})
+        packet.set_header("master-1", 42, 6)
+        return packet
+
+    def test_command_roundtrip(self):
+        """Un comando decodificato conserva intestazione e contenuto"""
+        encoded = self.command().encode()
+        decoded = MeshPacket.decode(encoded)
+        self.assertEqual(decoded.get_type(), MeshPacketType.Command)
+        self.assertEqual(decoded.get_source(), "master-1")
+        self.assertEqual(decoded.get_sequence(), 42)
+        self.assertEqual(decoded.get_ttl(), 6)
+        self.assertEqual(decoded.encode(), encoded)
+
+    def test_audio_roundtrip(self):
+        """Un frame audio decodificato conserva stream, sequenza, timestamp e payload"""
+        packet = MeshPacket.create_audio(3, 77, 1700000000123456, [1, 2, 3, 250])
+        packet.set_header("source-1", 9, 4)
+        decoded = MeshPacket.decode(packet.encode())
+        info = decoded.get_audio_data()
+        self.assertEqual(info.stream_id, 3)
+        self.assertEqual(info.frame_sequence, 77)
+        self.assertEqual(info.playout_time_us, 1700000000123456)
+        self.assertEqual(info.timestamp_width, TimestampWidth.Full)
+        self.assertEqual(info.payload, [1, 2, 3, 250])
+
+    def test_bridges_roundtrip(self):
+        """I ponti attraversati viaggiano nell'intestazione e sono coperti dalla firma"""
+        packet = MeshPacket.create_audio(3, 77, 1700000000123456, [1, 2, 3])
+        self.assertTrue(packet.add_bridge("piano-1"))
+        self.assertFalse(packet.add_bridge("piano-1"))
+        packet.set_header("bridge-1", 9, 4)
+        decoded = MeshPacket.decode(packet.encode())
+        self.assertEqual(decoded.get_bridges(), ["piano-1"])
+        self.assertTrue(decoded.has_crossed_bridge("piano-1"))
+        self.assertEqual(decoded.encode(), packet.encode())
+
+        plain = MeshPacket.create_audio(3, 77, 1700000000123456, [1, 2, 3])
+        plain.set_header("bridge-1", 9, 4)
+        self.assertNotEqual(plain.signing_bytes(), packet.signing_bytes())
+
+    def test_relay_delay_roundtrip(self):
+        """Il ritardo degli inoltri viaggia nell'intestazione fuori dalla firma"""
+        packet = MeshPacket.create_time_beacon(123456)
+        packet.set_header("master-1", 9, 4)
+        signed = packet.signing_bytes()
+        packet.add_relay_delay(1500)
+        packet.add_relay_delay(250)
+        decoded = MeshPacket.decode(packet.encode())
+        self.assertEqual(decoded.get_relay_delay_us(), 1750)
+        self.assertEqual(decoded.signing_bytes(), signed)
+
+    def test_encoded_size(self):
+        """La dimensione dichiarata coincide con i byte prodotti"""
+        packet = self.command()
+        self.assertEqual(packet.encoded_size(), len(packet.encode()))
+
+    def test_truncated_input(self):
+        """Ogni troncamento di un pacchetto valido viene rifiutato"""
+        encoded = self.command().encode()
+        for length in range(len(encoded)):
+            with self.assertRaises(ValueError):
+                MeshPacket.decode(encoded[:length])
+
+    def test_unknown_version(self):
+        """Una versione del formato diversa da quella supportata viene rifiutata"""
+        encoded = bytearray(self.command().encode())
+        encoded[0] += 1
+        with self.assertRaises(ValueError):
+            MeshPacket.decode(bytes(encoded))
+
+    def test_trailing_bytes(self):
+        """Byte in eccesso dopo il pacchetto vengono rifiutati"""
+        with self.assertRaises(ValueError):
+            MeshPacket.decode(self.command().encode() + b"\x00")
+
+    def test_random_input(self):
+        """Dati arbitrari vengono decodificati o rifiutati, mai altro"""
+        rng = random.Random(504)
+        seed = bytearray(self.command().encode())
+        for _ in range(2000):
+            data = bytearray(seed)
+            for _ in range(rng.randint(1, 3)):
+                data[rng.randrange(len(data))] ^= 1 << rng.randrange(8)
+            try:
+                decoded = MeshPacket.decode(bytes(data))
+            except ValueError:
+                continue
+            self.assertEqual(MeshPacket.decode(decoded.encode()).encode(), decoded.encode())
+
+if __name__ == '__main__':
+    unittest.main()
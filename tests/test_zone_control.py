@@ -0,0 +1,82 @@
+# Test unitari per il controllo della riproduzione per zona
+# Verifica i comandi destinati ad una zona, il volume applicato ai frame e i limiti del protocollo
+
+import os
+import sys
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import AudioFrame, MeshPacket, NodeRole, SaberConfig, SaberProtocol, apply_volume
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestZoneCommand(unittest.TestCase):
+    """Test per i comandi destinati ad una zona"""
+
+    def test_zone(self):
+        """La zona viaggia con il comando e sopravvive alla codifica"""
+        packet = MeshPacket.create_zone_command("cucina", "playback.volume", {"volume": "40"})
+        self.assertEqual(packet.get_command_zone(), "cucina")
+        decoded = MeshPacket.decode(packet.encode())
+        self.assertEqual(decoded.get_command_zone(), "cucina")
+
+    def test_without_zone(self):
+        """Un comando ordinario non indica una zona"""
+        self.assertIsNone(MeshPacket.create_command("standby.wake", {}).get_command_zone())
+        with self.assertRaises(RuntimeError):
+            MeshPacket.create_status("sink-1", 50, 10).get_command_zone()
+
+class TestVolume(unittest.TestCase):
+    """Test per il volume applicato ai frame"""
+
+    def frame(self, samples):
+        frame = AudioFrame()
+        frame.samples = samples
+        return frame
+
+    def test_half(self):
+        """Al 50% l'ampiezza si dimezza"""
+        frame = self.frame([10000, -10000, 0])
+        apply_volume(frame, 50)
+        self.assertEqual(frame.samples, [5000, -5000, 0])
+
+    def test_limits(self):
+        """Il 100% lascia il frame invariato, lo 0% lo silenzia"""
+        frame = self.frame([32767, -32768])
+        apply_volume(frame, 100)
+        self.assertEqual(frame.samples, [32767, -32768])
+        apply_volume(frame, 0)
+        self.assertEqual(frame.samples, [0, 0])
+
+class TestProtocolZoneControl(unittest.TestCase):
+    """Test per il controllo delle zone senza avviare la rete"""
+
+    def setUp(self):
+        config = SaberConfig.default_config()
+        config.role = NodeRole.Master
+        self.protocol = SaberProtocol(config)
+
+    def test_defaults(self):
+        """Un nodo parte a pieno volume e non in pausa"""
+        self.assertEqual(self.protocol.get_volume(), 100)
+        self.assertFalse(self.protocol.is_playback_paused())
+
+    def test_without_network(self):
+        """Senza rete i comandi di zona non vengono inviati"""
+        self.assertFalse(self.protocol.pause_group_playback("cucina"))
+        self.assertFalse(self.protocol.resume_group_playback("cucina"))
+        self.assertFalse(self.protocol.set_zone_volume("cucina", 40))
+        self.assertEqual(self.protocol.get_volume(), 100)
+
+    def test_invalid_volume(self):
+        """Un volume oltre il 100% viene rifiutato"""
+        with self.assertRaises(ValueError):
+            self.protocol.set_zone_volume("cucina", 150)
+
+if __name__ == "__main__":
+    unittest.main()
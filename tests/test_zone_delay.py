@@ -0,0 +1,115 @@
+# Test unitari per il ritardo acustico delle zone
+# Verifica la lettura dal file di configurazione, i limiti e l'impostazione centrale dal Master
+
+import asyncio
+import os
+import sys
+import time
+import unittest
+
+# Aggiungo il percorso dei moduli alla path di Python
+sys.path.insert(0, os.path.join(os.path.dirname(__file__), '..', 'src'))
+
+try:
+    # Importo i moduli da testare
+    from saber_protocol import MAX_ZONE_DELAY_MS, MeshPacket, NodeRole, SaberConfig, SaberProtocol, SimNetwork
+    from helpers import ConfigFileTestCase
+except ImportError:
+    print("Errore: impossibile importare saber_protocol. Assicurati di averlo compilato.")
+    sys.exit(1)
+
+class TestConfigFile(ConfigFileTestCase):
+    """Test per i ritardi di zona letti dal file di configurazione"""
+
+    HEADER = '[node]\nid = "master-1"\nrole = "master"\n'
+
+    def test_delays(self):
+        """Le sezioni [zone.<nome>] impostano il ritardo di ogni zona"""
+        config = self.load('\n[zone.giardino]\ndelay_ms = 120\n\n[zone.salotto]\ndelay_ms = 0\n')
+        self.assertEqual(config.zone_delays, {"giardino": 120, "salotto": 0})
+        self.assertEqual(SaberConfig.default_config().zone_delays, {})
+
+    def test_invalid_delays(self):
+        """Ritardi negativi o oltre il massimo e chiavi sconosciute vengono rifiutati"""
+        with self.assertRaises(RuntimeError):
+            self.load('\n[zone.giardino]\ndelay_ms = -1\n')
+        with self.assertRaises(RuntimeError):
+            self.load('\n[zone.giardino]\ndelay_ms = %d\n' % (MAX_ZONE_DELAY_MS + 1))
+        with self.assertRaises(RuntimeError):
+            self.load('\n[zone.giardino]\nvolume = 3\n')
+
+class TestProtocolZoneDelay(unittest.TestCase):
+    """Test per i ritardi di zona sul protocollo"""
+
+    def test_master_sets_delays(self):
+        """Il Master registra i ritardi, 0 li rimuove"""
+        config = SaberConfig.default_config()
+        config.role = NodeRole.Master
+        config.zone_delays = {"giardino": 80}
+        protocol = SaberProtocol(config)
+        self.assertTrue(protocol.set_zone_delay("terrazza", 40))
+        self.assertEqual(protocol.get_zone_delays(), {"giardino": 80, "terrazza": 40})
+        self.assertTrue(protocol.set_zone_delay("giardino", 0))
+        self.assertEqual(protocol.get_zone_delays(), {"terrazza": 40})
+        with self.assertRaises(ValueError):
+            protocol.set_zone_delay("terrazza", MAX_ZONE_DELAY_MS + 1)
+
+    def test_sink_rejects(self):
+        """Un sink non imposta ritardi e finché il Master non li invia non ne applica"""
+        protocol = SaberProtocol(SaberConfig.default_config())
+        self.assertFalse(protocol.set_zone_delay("giardino", 80))
+        self.assertEqual(protocol.get_zone_delays(), {})
+        self.assertEqual(protocol.get_acoustic_delay_ms(), 0)
+
+class TestZoneDelayAuthority(unittest.TestCase):
+    """Test per l'origine dei ritardi di zona ricevuti dalla rete"""
+
+    def start(self, node_id, role):
+        config = SaberConfig.default_config()
+        config.node_id = node_id
+        config.role = role
+        protocol = SaberProtocol(config)
+        protocol.set_sim_network(self.network)
+        if not protocol.initialize():
+            self.skipTest("avvio del protocollo non possibile in questo ambiente")
+        self.addCleanup(protocol.shutdown)
+        return protocol
+
+    def knows(self, protocol, node_ids):
+        return set(node_ids) <= {node.id for node in protocol.get_topology().nodes}
+
+    def send(self, protocol, delay_ms):
+        packet = MeshPacket.create_command("zone.delay", {"target": "zd-sink-2", "zone": "giardino",
+                                                          "delay_ms": str(delay_ms)})
+        async def run():
+            delivery = asyncio.ensure_future(protocol.send_packet_async(packet))
+            while not delivery.done():
+                self.network.advance(20)
+                await asyncio.sleep(0.02)
+            return delivery.result()
+        return asyncio.run(run())
+
+    def test_slave_delay_ignored(self):
+        """Un ritardo inviato da un sink viene ignorato, quello del Master applicato"""
+        self.network = SimNetwork(1)
+        master = self.start("zd-master", NodeRole.Master)
+        slave = self.start("zd-sink-1", NodeRole.Sink)
+        victim = self.start("zd-sink-2", NodeRole.Sink)
+
+        deadline = time.monotonic() + 5.0
+        while time.monotonic() < deadline and not (self.knows(master, ["zd-sink-1", "zd-sink-2"])
+                                                    and self.knows(victim, ["zd-master", "zd-sink-1"])):
+            self.network.advance(50)
+            time.sleep(0.05)
+        self.assertTrue(self.knows(victim, ["zd-master", "zd-sink-1"]))
+
+        self.send(slave, 200)
+        self.network.advance(100)
+        self.assertEqual(victim.get_acoustic_delay_ms(), 0)
+
+        self.send(master, 120)
+        self.network.advance(100)
+        self.assertEqual(victim.get_acoustic_delay_ms(), 120)
+
+if __name__ == "__main__":
+    unittest.main()